@@ -0,0 +1,783 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property tests of the block kernels over random schemas and blocks.
+//!
+//! The blocks favor the degenerate shapes: no row or one row, constant columns, all-null columns,
+//! empty and long strings, NaNs and signed zeros. A failing case is shrunk to a minimal one before
+//! it is reported, with its seed. The runs are deterministic, set DATABLOCKS_FUZZ_SEED and
+//! DATABLOCKS_FUZZ_CASES to reproduce a case or to run more of them.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+
+use common_arrow::arrow;
+use common_arrow::arrow::array::BooleanArray;
+use common_arrow::arrow::bitmap::MutableBitmap;
+use common_arrow::arrow::datatypes::DataType as ArrowType;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use crate::*;
+
+const FUZZ_SEED: u64 = 0x0da7_ab10_c5ee_d001;
+const FUZZ_CASES: u64 = 256;
+const MAX_ROWS: usize = 32;
+const MAX_COLUMNS: usize = 4;
+const MAX_STRING_BYTES: usize = 1024;
+const MAX_SHRINK_STEPS: usize = 1024;
+
+const FUZZ_TYPES: &[DataType] = &[
+    DataType::Boolean,
+    DataType::Int8,
+    DataType::Int16,
+    DataType::Int32,
+    DataType::Int64,
+    DataType::UInt8,
+    DataType::UInt16,
+    DataType::UInt32,
+    DataType::UInt64,
+    DataType::Float32,
+    DataType::Float64,
+    DataType::String,
+];
+
+/// A column of a case, a constant one has the same value in every row.
+#[derive(Debug, Clone)]
+struct FuzzColumn {
+    data_type: DataType,
+    nullable: bool,
+    constant: bool,
+    values: Vec<DataValue>,
+}
+
+/// A block and the arguments of the kernels run on it.
+#[derive(Debug, Clone)]
+struct FuzzCase {
+    rows: usize,
+    columns: Vec<FuzzColumn>,
+    /// The indices of the group by columns.
+    group_by: Vec<usize>,
+    /// The indices of the sort columns, with their asc and nulls_first.
+    sort_by: Vec<(usize, bool, bool)>,
+    limit: Option<usize>,
+    /// The rows kept by the filter.
+    filter: Vec<bool>,
+    split_size: usize,
+}
+
+impl FuzzCase {
+    fn generate(rng: &mut StdRng) -> FuzzCase {
+        let rows = match rng.gen_range(0..4) {
+            0 => 0,
+            1 => 1,
+            _ => rng.gen_range(2..=MAX_ROWS),
+        };
+
+        let columns = (0..rng.gen_range(1..=MAX_COLUMNS))
+            .map(|_| FuzzColumn::generate(rng, rows))
+            .collect::<Vec<_>>();
+
+        let mut group_by = (0..columns.len()).collect::<Vec<_>>();
+        group_by.retain(|_| rng.gen_bool(0.5));
+        if group_by.is_empty() {
+            group_by.push(rng.gen_range(0..columns.len()));
+        }
+
+        let sort_by = (0..rng.gen_range(1..=columns.len()))
+            .map(|_| {
+                let index = rng.gen_range(0..columns.len());
+                (index, rng.gen_bool(0.5), rng.gen_bool(0.5))
+            })
+            .collect();
+
+        FuzzCase {
+            rows,
+            columns,
+            group_by,
+            sort_by,
+            limit: match rng.gen_bool(0.5) {
+                true => Some(rng.gen_range(0..=rows + 1)),
+                false => None,
+            },
+            filter: (0..rows).map(|_| rng.gen_bool(0.5)).collect(),
+            split_size: rng.gen_range(0..=rows + 1),
+        }
+    }
+
+    fn block(&self) -> Result<DataBlock> {
+        let fields = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                DataField::new(
+                    &column_name(index),
+                    column.data_type.clone(),
+                    column.nullable,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| match column.constant {
+                true => {
+                    let value = match column.values.first() {
+                        Some(value) => value.clone(),
+                        None => null_value(&column.data_type),
+                    };
+                    Ok(DataColumn::Constant(value, self.rows))
+                }
+                false => {
+                    let array = DataValue::try_into_data_array(&column.values, &column.data_type)?;
+                    Ok(DataColumn::Array(array))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DataBlock::create(DataSchemaRefExt::create(fields), columns))
+    }
+
+    /// The smaller cases, to find a minimal one which still fails.
+    /// Each of them has fewer rows, fewer columns or fewer values other than the defaults and nulls.
+    fn shrink_candidates(&self) -> Vec<FuzzCase> {
+        let mut candidates = vec![];
+
+        if self.rows > 1 {
+            let half = self.rows / 2;
+            candidates.push(self.retain_rows(|row| row < half));
+            candidates.push(self.retain_rows(|row| row >= half));
+        }
+        for removed in 0..self.rows {
+            candidates.push(self.retain_rows(|row| row != removed));
+        }
+
+        for removed in 0..self.columns.len() {
+            if let Some(candidate) = self.remove_column(removed) {
+                candidates.push(candidate);
+            }
+        }
+
+        for (index, column) in self.columns.iter().enumerate() {
+            let default_value = default_value(&column.data_type);
+            for row in 0..self.rows {
+                let value = &column.values[row];
+                if value.is_null() || *value == default_value {
+                    continue;
+                }
+
+                candidates.push(self.replace_value(index, row, default_value.clone()));
+                if column.nullable {
+                    candidates.push(self.replace_value(index, row, null_value(&column.data_type)));
+                }
+            }
+        }
+
+        candidates
+    }
+
+    fn retain_rows(&self, retained: impl Fn(usize) -> bool) -> FuzzCase {
+        let mut case = self.clone();
+        for column in case.columns.iter_mut() {
+            column.values = retain(&column.values, &retained);
+        }
+        case.filter = retain(&self.filter, &retained);
+        case.rows = case.filter.len();
+        case
+    }
+
+    fn remove_column(&self, removed: usize) -> Option<FuzzCase> {
+        if self.columns.len() == 1 {
+            return None;
+        }
+
+        let index = |i: usize| match i.cmp(&removed) {
+            Ordering::Less => Some(i),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(i - 1),
+        };
+        let mut case = self.clone();
+        case.columns.remove(removed);
+        case.group_by = self.group_by.iter().filter_map(|i| index(*i)).collect();
+        case.sort_by = self
+            .sort_by
+            .iter()
+            .filter_map(|(i, asc, nulls_first)| Some((index(*i)?, *asc, *nulls_first)))
+            .collect();
+
+        match case.group_by.is_empty() || case.sort_by.is_empty() {
+            true => None,
+            false => Some(case),
+        }
+    }
+
+    /// Replaces the value of the row, and of all the rows of a constant column.
+    fn replace_value(&self, column: usize, row: usize, value: DataValue) -> FuzzCase {
+        let mut case = self.clone();
+        let column = &mut case.columns[column];
+        match column.constant {
+            true => column.values = vec![value; self.rows],
+            false => column.values[row] = value,
+        }
+        case
+    }
+}
+
+impl FuzzColumn {
+    fn generate(rng: &mut StdRng, rows: usize) -> FuzzColumn {
+        let data_type = FUZZ_TYPES[rng.gen_range(0..FUZZ_TYPES.len())].clone();
+        let nullable = rng.gen_bool(0.5);
+        let constant = rng.gen_bool(0.2);
+
+        // The nullable columns are all null, partly null or not null.
+        let null_probability = match (nullable, rng.gen_range(0..3)) {
+            (false, _) => 0.0,
+            (true, 0) => 1.0,
+            (true, 1) => 0.5,
+            (true, _) => 0.0,
+        };
+        let mut generate_value = || match rng.gen_bool(null_probability) {
+            true => null_value(&data_type),
+            false => random_value(rng, &data_type),
+        };
+
+        let values = match constant {
+            true => vec![generate_value(); rows],
+            false => (0..rows).map(|_| generate_value()).collect(),
+        };
+
+        FuzzColumn {
+            data_type,
+            nullable,
+            constant,
+            values,
+        }
+    }
+}
+
+/// A value of the type, mostly from a small domain so that the rows have equal keys.
+fn random_value(rng: &mut StdRng, data_type: &DataType) -> DataValue {
+    let small = rng.gen_bool(0.7);
+    match data_type {
+        DataType::Boolean => DataValue::Boolean(Some(rng.gen())),
+        DataType::Int8 if small => DataValue::Int8(Some(rng.gen_range(-1..3))),
+        DataType::Int8 => DataValue::Int8(Some(rng.gen())),
+        DataType::Int16 if small => DataValue::Int16(Some(rng.gen_range(-1..3))),
+        DataType::Int16 => DataValue::Int16(Some(rng.gen())),
+        DataType::Int32 if small => DataValue::Int32(Some(rng.gen_range(-1..3))),
+        DataType::Int32 => DataValue::Int32(Some(rng.gen())),
+        DataType::Int64 if small => DataValue::Int64(Some(rng.gen_range(-1..3))),
+        DataType::Int64 => DataValue::Int64(Some(rng.gen())),
+        DataType::UInt8 if small => DataValue::UInt8(Some(rng.gen_range(0..3))),
+        DataType::UInt8 => DataValue::UInt8(Some(rng.gen())),
+        DataType::UInt16 if small => DataValue::UInt16(Some(rng.gen_range(0..3))),
+        DataType::UInt16 => DataValue::UInt16(Some(rng.gen())),
+        DataType::UInt32 if small => DataValue::UInt32(Some(rng.gen_range(0..3))),
+        DataType::UInt32 => DataValue::UInt32(Some(rng.gen())),
+        DataType::UInt64 if small => DataValue::UInt64(Some(rng.gen_range(0..3))),
+        DataType::UInt64 => DataValue::UInt64(Some(rng.gen())),
+        DataType::Float32 => DataValue::Float32(Some(random_float(rng, small) as f32)),
+        DataType::Float64 => DataValue::Float64(Some(random_float(rng, small))),
+        DataType::String => {
+            let bytes = match (small, rng.gen_range(0..3)) {
+                (true, 0) => vec![],
+                (true, _) => (0..rng.gen_range(1..3))
+                    .map(|_| rng.gen_range(b'a'..b'c'))
+                    .collect(),
+                (false, _) => (0..rng.gen_range(0..=MAX_STRING_BYTES))
+                    .map(|_| rng.gen())
+                    .collect(),
+            };
+            DataValue::String(Some(bytes))
+        }
+        other => unreachable!("Unexpected fuzz type {:?}", other),
+    }
+}
+
+fn random_float(rng: &mut StdRng, small: bool) -> f64 {
+    const SPECIALS: &[f64] = &[0.0, -0.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.5];
+    match small {
+        true => SPECIALS[rng.gen_range(0..SPECIALS.len())],
+        // A NaN with the sign bit set and another payload.
+        false if rng.gen_bool(0.1) => f64::from_bits(0xfff8_0000_0000_0001),
+        false => rng.gen_range(-1e6..1e6),
+    }
+}
+
+fn null_value(data_type: &DataType) -> DataValue {
+    match data_type {
+        DataType::Boolean => DataValue::Boolean(None),
+        DataType::Int8 => DataValue::Int8(None),
+        DataType::Int16 => DataValue::Int16(None),
+        DataType::Int32 => DataValue::Int32(None),
+        DataType::Int64 => DataValue::Int64(None),
+        DataType::UInt8 => DataValue::UInt8(None),
+        DataType::UInt16 => DataValue::UInt16(None),
+        DataType::UInt32 => DataValue::UInt32(None),
+        DataType::UInt64 => DataValue::UInt64(None),
+        DataType::Float32 => DataValue::Float32(None),
+        DataType::Float64 => DataValue::Float64(None),
+        DataType::String => DataValue::String(None),
+        other => unreachable!("Unexpected fuzz type {:?}", other),
+    }
+}
+
+fn default_value(data_type: &DataType) -> DataValue {
+    match data_type {
+        DataType::Boolean => DataValue::Boolean(Some(false)),
+        DataType::Int8 => DataValue::Int8(Some(0)),
+        DataType::Int16 => DataValue::Int16(Some(0)),
+        DataType::Int32 => DataValue::Int32(Some(0)),
+        DataType::Int64 => DataValue::Int64(Some(0)),
+        DataType::UInt8 => DataValue::UInt8(Some(0)),
+        DataType::UInt16 => DataValue::UInt16(Some(0)),
+        DataType::UInt32 => DataValue::UInt32(Some(0)),
+        DataType::UInt64 => DataValue::UInt64(Some(0)),
+        DataType::Float32 => DataValue::Float32(Some(0.0)),
+        DataType::Float64 => DataValue::Float64(Some(0.0)),
+        DataType::String => DataValue::String(Some(vec![])),
+        other => unreachable!("Unexpected fuzz type {:?}", other),
+    }
+}
+
+fn column_name(index: usize) -> String {
+    format!("c{}", index)
+}
+
+fn retain<T: Clone>(values: &[T], retained: impl Fn(usize) -> bool) -> Vec<T> {
+    let values = values.iter().enumerate();
+    values
+        .filter(|(row, _)| retained(*row))
+        .map(|(_, value)| value.clone())
+        .collect()
+}
+
+fn block_rows(block: &DataBlock) -> Result<Vec<Vec<DataValue>>> {
+    (0..block.num_rows())
+        .map(|row| {
+            (0..block.num_columns())
+                .map(|column| block.column(column).try_get(row))
+                .collect()
+        })
+        .collect()
+}
+
+/// The NaNs are one value, and so are the zeros, as the group by keys them.
+fn canonical(value: &DataValue) -> DataValue {
+    match value {
+        DataValue::Float32(Some(v)) if v.is_nan() => DataValue::Float32(Some(f32::NAN)),
+        DataValue::Float32(Some(v)) if *v == 0.0 => DataValue::Float32(Some(0.0)),
+        DataValue::Float64(Some(v)) if v.is_nan() => DataValue::Float64(Some(f64::NAN)),
+        DataValue::Float64(Some(v)) if *v == 0.0 => DataValue::Float64(Some(0.0)),
+        value => value.clone(),
+    }
+}
+
+fn values_key<'a>(values: impl Iterator<Item = &'a DataValue>) -> String {
+    format!("{:?}", values.map(canonical).collect::<Vec<_>>())
+}
+
+fn rows_multiset(rows: &[Vec<DataValue>]) -> HashMap<String, usize> {
+    let mut multiset = HashMap::new();
+    for row in rows {
+        *multiset.entry(values_key(row.iter())).or_default() += 1;
+    }
+    multiset
+}
+
+fn rows_keys(rows: &[Vec<DataValue>]) -> Vec<String> {
+    rows.iter().map(|row| values_key(row.iter())).collect()
+}
+
+fn check(violated: bool, message: impl FnOnce() -> String) -> Result<()> {
+    match violated {
+        true => Err(ErrorCode::LogicalError(message())),
+        false => Ok(()),
+    }
+}
+
+/// The groups partition the rows, with the same key in a group and distinct keys across them.
+fn check_group_by(case: &FuzzCase) -> Result<()> {
+    let block = case.block()?;
+    let names = case
+        .group_by
+        .iter()
+        .map(|i| column_name(*i))
+        .collect::<Vec<_>>();
+    let groups = DataBlock::group_by_blocks(&block, &names)?;
+
+    let mut rows = vec![];
+    let mut keys = HashSet::new();
+    for group in &groups {
+        let group_rows = block_rows(group)?;
+        check(group_rows.is_empty(), || "An empty group".to_string())?;
+
+        let group_key = |row: &Vec<DataValue>| values_key(case.group_by.iter().map(|i| &row[*i]));
+        let key = group_key(&group_rows[0]);
+        for row in &group_rows {
+            check(group_key(row) != key, || {
+                format!("The group {} has a row of the key {}", key, group_key(row))
+            })?;
+        }
+        check(!keys.insert(key.clone()), || {
+            format!("The key {} is in two groups", key)
+        })?;
+        rows.extend(group_rows);
+    }
+
+    let input = block_rows(&block)?;
+    check(rows_multiset(&rows) != rows_multiset(&input), || {
+        format!(
+            "The groups have the rows {:?}, not the ones of the block",
+            rows
+        )
+    })
+}
+
+fn compare_rows(lhs: &[DataValue], rhs: &[DataValue], sort_by: &[(usize, bool, bool)]) -> Ordering {
+    for (index, asc, nulls_first) in sort_by {
+        let ordering = match (lhs[*index].is_null(), rhs[*index].is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) if *nulls_first => Ordering::Less,
+            (true, false) => Ordering::Greater,
+            (false, true) if *nulls_first => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) if *asc => compare_values(&lhs[*index], &rhs[*index]),
+            (false, false) => compare_values(&lhs[*index], &rhs[*index]).reverse(),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn compare_values(lhs: &DataValue, rhs: &DataValue) -> Ordering {
+    match (lhs, rhs) {
+        (DataValue::Boolean(Some(l)), DataValue::Boolean(Some(r))) => l.cmp(r),
+        (DataValue::String(Some(l)), DataValue::String(Some(r))) => l.cmp(r),
+        (DataValue::Float32(Some(l)), DataValue::Float32(Some(r))) => {
+            compare_floats(*l as f64, *r as f64)
+        }
+        (DataValue::Float64(Some(l)), DataValue::Float64(Some(r))) => compare_floats(*l, *r),
+        (l, r) => integer_value(l).cmp(&integer_value(r)),
+    }
+}
+
+/// The IEEE total order, as the arrow sort kernel: -NaN < -inf < -0 < 0 < inf < NaN.
+fn compare_floats(lhs: f64, rhs: f64) -> Ordering {
+    let total_order = |v: f64| {
+        let bits = v.to_bits() as i64;
+        bits ^ ((((bits >> 63) as u64) >> 1) as i64)
+    };
+    total_order(lhs).cmp(&total_order(rhs))
+}
+
+fn integer_value(value: &DataValue) -> i128 {
+    match value {
+        DataValue::Int8(Some(v)) => *v as i128,
+        DataValue::Int16(Some(v)) => *v as i128,
+        DataValue::Int32(Some(v)) => *v as i128,
+        DataValue::Int64(Some(v)) => *v as i128,
+        DataValue::UInt8(Some(v)) => *v as i128,
+        DataValue::UInt16(Some(v)) => *v as i128,
+        DataValue::UInt32(Some(v)) => *v as i128,
+        DataValue::UInt64(Some(v)) => *v as i128,
+        other => unreachable!("Unexpected sort value {:?}", other),
+    }
+}
+
+/// The sorted block is ordered, and is a permutation of the block, or its first rows with a limit.
+fn check_sort(case: &FuzzCase) -> Result<()> {
+    let block = case.block()?;
+    let descriptions = case
+        .sort_by
+        .iter()
+        .map(|(index, asc, nulls_first)| SortColumnDescription {
+            column_name: column_name(*index),
+            asc: *asc,
+            nulls_first: *nulls_first,
+        })
+        .collect::<Vec<_>>();
+
+    let sorted = block_rows(&DataBlock::sort_block(&block, &descriptions, None)?)?;
+    let input = block_rows(&block)?;
+    check(rows_multiset(&sorted) != rows_multiset(&input), || {
+        format!("The sorted rows {:?} are not the ones of the block", sorted)
+    })?;
+    for pair in sorted.windows(2) {
+        check(
+            compare_rows(&pair[0], &pair[1], &case.sort_by) == Ordering::Greater,
+            || format!("The rows {:?} and {:?} are out of order", pair[0], pair[1]),
+        )?;
+    }
+
+    if let Some(limit) = case.limit {
+        let limited = block_rows(&DataBlock::sort_block(&block, &descriptions, Some(limit))?)?;
+        let expected = &sorted[..limit.min(sorted.len())];
+
+        // The ties at the limit may be any of the equal rows.
+        let sort_key = |row: &Vec<DataValue>| values_key(case.sort_by.iter().map(|s| &row[s.0]));
+        let limited_keys = limited.iter().map(sort_key).collect::<Vec<_>>();
+        let expected_keys = expected.iter().map(sort_key).collect::<Vec<_>>();
+        check(limited_keys != expected_keys, || {
+            format!(
+                "The sorted rows of the limit {} are {:?}, expect {:?}",
+                limit, limited, expected
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn filter_block(block: &DataBlock, filter: &[bool]) -> Result<DataBlock> {
+    let mut bitmap = MutableBitmap::from_len_zeroed(filter.len());
+    for (row, kept) in filter.iter().enumerate() {
+        bitmap.set(row, *kept);
+    }
+    let array = BooleanArray::from_data(ArrowType::Boolean, bitmap.into(), None);
+
+    let batch: RecordBatch = block.clone().try_into()?;
+    let batch = arrow::compute::filter::filter_record_batch(&batch, &array)?;
+    batch.try_into()
+}
+
+/// The rows kept and the rows filtered out are in their order, and make the block once concatenated.
+fn check_filter(case: &FuzzCase) -> Result<()> {
+    let block = case.block()?;
+    let rejected_filter = case.filter.iter().map(|kept| !kept).collect::<Vec<_>>();
+    let kept = filter_block(&block, &case.filter)?;
+    let rejected = filter_block(&block, &rejected_filter)?;
+
+    let input = block_rows(&block)?;
+    for (filtered, filter) in [(&kept, &case.filter), (&rejected, &rejected_filter)] {
+        let expected = retain(&input, |row| filter[row]);
+        let filtered = block_rows(filtered)?;
+        check(rows_keys(&filtered) != rows_keys(&expected), || {
+            format!(
+                "The filtered rows are {:?}, expect {:?}",
+                filtered, expected
+            )
+        })?;
+    }
+
+    let concatenated = block_rows(&DataBlock::concat_blocks(&[kept, rejected])?)?;
+    check(
+        rows_multiset(&concatenated) != rows_multiset(&input),
+        || {
+            format!(
+                "The concatenated rows {:?} are not the ones of the block",
+                concatenated
+            )
+        },
+    )
+}
+
+/// The blocks of the split are not empty nor larger than the size, and make the block once concatenated.
+fn check_split(case: &FuzzCase) -> Result<()> {
+    let block = case.block()?;
+    let split = DataBlock::split_block_by_size(&block, case.split_size);
+    if case.split_size == 0 {
+        return check(split.is_ok(), || {
+            "The split into the blocks of 0 rows succeeds".to_string()
+        });
+    }
+
+    let blocks = split?;
+    let expected_blocks = (case.rows + case.split_size - 1) / case.split_size;
+    check(blocks.len() != expected_blocks, || {
+        format!("{} blocks, expect {}", blocks.len(), expected_blocks)
+    })?;
+    for split_block in &blocks {
+        check(
+            split_block.num_rows() == 0 || split_block.num_rows() > case.split_size,
+            || format!("A split block of {} rows", split_block.num_rows()),
+        )?;
+    }
+
+    if !blocks.is_empty() {
+        let input = block_rows(&block)?;
+        let concatenated = block_rows(&DataBlock::concat_blocks(&blocks)?)?;
+        check(rows_keys(&concatenated) != rows_keys(&input), || {
+            format!(
+                "The concatenated rows are {:?}, expect {:?}",
+                concatenated, input
+            )
+        })?;
+    }
+    Ok(())
+}
+
+type Property = fn(&FuzzCase) -> Result<()>;
+
+const PROPERTIES: &[(&str, Property)] = &[
+    ("group_by", check_group_by),
+    ("sort", check_sort),
+    ("filter", check_filter),
+    ("split", check_split),
+];
+
+/// A panic of a kernel is a failure of the property as well.
+fn run_property(property: Property, case: &FuzzCase) -> Result<()> {
+    match catch_unwind(AssertUnwindSafe(|| property(case))) {
+        Ok(result) => result,
+        Err(panic) => Err(ErrorCode::from_panic(panic.as_ref())),
+    }
+}
+
+fn shrink(property: Property, case: FuzzCase) -> FuzzCase {
+    let mut case = case;
+    for _ in 0..MAX_SHRINK_STEPS {
+        let smaller = case
+            .shrink_candidates()
+            .into_iter()
+            .find(|candidate| run_property(property, candidate).is_err());
+        match smaller {
+            None => break,
+            Some(smaller) => case = smaller,
+        }
+    }
+    case
+}
+
+fn assert_property(name: &str, property: Property, case: FuzzCase, seed: u64) {
+    if run_property(property, &case).is_ok() {
+        return;
+    }
+
+    let minimal = shrink(property, case);
+    let cause = run_property(property, &minimal).err();
+    let block = minimal.block().map(|block| format!("{:?}", block));
+    panic!(
+        "The {} property fails for the seed {}, DATABLOCKS_FUZZ_SEED={} DATABLOCKS_FUZZ_CASES=1 to reproduce.\n\
+         Cause: {:?}\nMinimal case: {:#?}\nMinimal block: {:?}",
+        name, seed, seed, cause, minimal, block
+    );
+}
+
+fn fuzz_property(name: &str, property: Property) {
+    let env = |name: &str, default: u64| match std::env::var(name) {
+        Ok(value) => value.parse::<u64>().expect(name),
+        Err(_) => default,
+    };
+    let seed = env("DATABLOCKS_FUZZ_SEED", FUZZ_SEED);
+    let cases = env("DATABLOCKS_FUZZ_CASES", FUZZ_CASES);
+
+    for index in 0..cases {
+        let case_seed = seed.wrapping_add(index);
+        let case = FuzzCase::generate(&mut StdRng::seed_from_u64(case_seed));
+        assert_property(name, property, case, case_seed);
+    }
+}
+
+#[test]
+fn test_fuzz_group_by() {
+    fuzz_property("group_by", check_group_by);
+}
+
+#[test]
+fn test_fuzz_sort() {
+    fuzz_property("sort", check_sort);
+}
+
+#[test]
+fn test_fuzz_filter() {
+    fuzz_property("filter", check_filter);
+}
+
+#[test]
+fn test_fuzz_split() {
+    fuzz_property("split", check_split);
+}
+
+/// The shapes of the bugs fixed in the kernels, checked by every property.
+fn regression_cases() -> Vec<FuzzCase> {
+    let column = |data_type: DataType, values: Vec<DataValue>| FuzzColumn {
+        data_type,
+        nullable: true,
+        constant: false,
+        values,
+    };
+    let case = |columns: Vec<FuzzColumn>, split_size: usize, limit: Option<usize>| {
+        let rows = columns[0].values.len();
+        FuzzCase {
+            rows,
+            group_by: (0..columns.len()).collect(),
+            sort_by: (0..columns.len()).map(|i| (i, true, false)).collect(),
+            columns,
+            limit,
+            filter: (0..rows).map(|row| row % 2 == 0).collect(),
+            split_size,
+        }
+    };
+
+    vec![
+        // The NaNs of different payloads or signs were different groups, and so were the zeros.
+        case(
+            vec![column(DataType::Float64, vec![
+                DataValue::Float64(Some(f64::NAN)),
+                DataValue::Float64(Some(f64::from_bits(0xfff8_0000_0000_0001))),
+                DataValue::Float64(Some(0.0)),
+                DataValue::Float64(Some(-0.0)),
+            ])],
+            3,
+            Some(3),
+        ),
+        // A NULL key was in the group of the default value, e.g. of 0 or ''.
+        case(
+            vec![
+                column(DataType::Int32, vec![
+                    DataValue::Int32(None),
+                    DataValue::Int32(Some(0)),
+                    DataValue::Int32(None),
+                ]),
+                column(DataType::String, vec![
+                    DataValue::String(Some(vec![])),
+                    DataValue::String(None),
+                    DataValue::String(Some(vec![])),
+                ]),
+            ],
+            1,
+            Some(0),
+        ),
+        // The split into the blocks of 0 rows never ended.
+        case(
+            vec![column(DataType::UInt8, vec![DataValue::UInt8(Some(1))])],
+            0,
+            Some(1),
+        ),
+        // No row, the limit is more than the rows.
+        case(vec![column(DataType::Boolean, vec![])], 1, Some(1)),
+    ]
+}
+
+#[test]
+fn test_fuzz_regressions() {
+    for case in regression_cases() {
+        for (name, property) in PROPERTIES {
+            assert_property(name, *property, case.clone(), FUZZ_SEED);
+        }
+    }
+}
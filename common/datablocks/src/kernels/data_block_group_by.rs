@@ -12,8 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
 use common_exception::Result;
 
+use crate::kernels::data_block_group_by_hash::null_masks;
 use crate::kernels::HashMethodKeysU16;
 use crate::kernels::HashMethodKeysU32;
 use crate::kernels::HashMethodKeysU64;
@@ -92,4 +99,133 @@ impl DataBlock {
             }
         })
     }
+
+    /// Hashes the rows into `num_partitions` blocks by their group keys, so that each partition
+    /// can be grouped on its own thread: the groups of all the partitions are the groups of
+    /// `group_by_blocks`. The rows of a key are in the same partition of any block.
+    pub fn group_by_blocks_partitioned(
+        block: &DataBlock,
+        column_names: &[String],
+        num_partitions: usize,
+    ) -> Result<Vec<DataBlock>> {
+        if num_partitions == 0 {
+            return Err(ErrorCode::BadArguments(
+                "The number of the group by partitions must be positive",
+            ));
+        }
+
+        let hashes = Self::group_by_hashes(block, column_names)?;
+        let indices = hashes
+            .into_iter()
+            .map(|hash| hash % num_partitions as u64)
+            .collect::<Vec<_>>();
+        Self::scatter_block(
+            block,
+            &DataColumn::Array(Series::new(indices)),
+            num_partitions,
+        )
+    }
+
+    /// The hash of the group key of each row, the same for the rows of a group.
+    fn group_by_hashes(block: &DataBlock, column_names: &[String]) -> Result<Vec<u64>> {
+        let mut group_columns = Vec::with_capacity(column_names.len());
+        for col in column_names {
+            group_columns.push(block.try_column_by_name(col)?);
+        }
+
+        // A single primitive column is hashed by its values, without building the keys.
+        // A constant column is hashed as an array, so its rows are where those of an array are.
+        if let [column] = group_columns.as_slice() {
+            let column = &DataColumn::Array(column.to_array()?);
+            let hashes = match column.data_type() {
+                DataType::Int8 => primitive_hashes::<i8>(column),
+                DataType::Int16 => primitive_hashes::<i16>(column),
+                DataType::Int32 => primitive_hashes::<i32>(column),
+                DataType::Int64 => primitive_hashes::<i64>(column),
+                DataType::UInt8 => primitive_hashes::<u8>(column),
+                DataType::UInt16 => primitive_hashes::<u16>(column),
+                DataType::UInt32 => primitive_hashes::<u32>(column),
+                DataType::UInt64 => primitive_hashes::<u64>(column),
+                DataType::Float32 => primitive_hashes::<f32>(column),
+                DataType::Float64 => primitive_hashes::<f64>(column),
+                _ => None,
+            };
+            if let Some(hashes) = hashes {
+                return Ok(hashes);
+            }
+        }
+
+        let rows = block.num_rows();
+        let nulls = null_masks(&group_columns, rows)?;
+        let method = Self::choose_hash_method(block, column_names)?;
+        Ok(match method {
+            HashMethodKind::Serializer(s) => key_hashes(s.build_keys(&group_columns, rows)?, nulls),
+            HashMethodKind::KeysU8(s) => key_hashes(s.build_keys(&group_columns, rows)?, nulls),
+            HashMethodKind::KeysU16(s) => key_hashes(s.build_keys(&group_columns, rows)?, nulls),
+            HashMethodKind::KeysU32(s) => key_hashes(s.build_keys(&group_columns, rows)?, nulls),
+            HashMethodKind::KeysU64(s) => key_hashes(s.build_keys(&group_columns, rows)?, nulls),
+        })
+    }
+}
+
+/// The partitions of a key must not depend on the process, nor on the block.
+fn partition_hasher() -> ahash::RandomState {
+    ahash::RandomState::with_seeds(
+        0x243f_6a88_85a3_08d3,
+        0x1319_8a2e_0370_7344,
+        0xa409_3822_299f_31d0,
+        0x082e_fa98_ec4e_6c89,
+    )
+}
+
+fn key_hashes<K: Hash>(keys: Vec<K>, nulls: Vec<Vec<bool>>) -> Vec<u64> {
+    let state = partition_hasher();
+    keys.iter()
+        .zip(nulls.iter())
+        .map(|key| {
+            let mut hasher = state.build_hasher();
+            key.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// None if the column is not an array of `T`.
+fn primitive_hashes<T: DFPrimitiveType>(column: &DataColumn) -> Option<Vec<u64>> {
+    let (values, validity) = column.as_primitive_slice::<T>()?;
+    let state = partition_hasher();
+
+    let hashes = values
+        .iter()
+        .enumerate()
+        .map(|(row, value)| {
+            let is_null = validity.map(|bitmap| !bitmap.get_bit(row)).unwrap_or(false);
+            // The nulls are one group, whatever their values are.
+            let key = match is_null {
+                true => 0,
+                false => primitive_key(*value),
+            };
+
+            let mut hasher = state.build_hasher();
+            (key, is_null).hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect();
+    Some(hashes)
+}
+
+/// The bits of a value, the same for the values of a group: NaNs with different payloads or
+/// signs are one group, and so are 0.0 and -0.0.
+#[inline]
+fn primitive_key<T: DFPrimitiveType>(value: T) -> u64 {
+    match T::FLOATING {
+        true => match value.to_f64() {
+            Some(f) if f.is_nan() => f64::NAN.to_bits(),
+            Some(f) if f == 0.0 => 0,
+            Some(f) => f.to_bits(),
+            None => 0,
+        },
+        false if T::SIGN => value.to_i64().unwrap_or_default() as u64,
+        false => value.to_u64().unwrap_or_default(),
+    }
 }
@@ -23,7 +23,10 @@ use common_exception::Result;
 
 use crate::DataBlock;
 
-type GroupIndices<T> = HashMap<T, (Vec<u32>, Vec<DataValue>), ahash::RandomState>;
+/// The key of a group and which of its columns are null, empty if none is:
+/// the keys don't tell a null from the default value, e.g. a NULL from 0 or ''.
+pub type GroupKey<T> = (T, Vec<bool>);
+type GroupIndices<T> = HashMap<GroupKey<T>, (Vec<u32>, Vec<DataValue>), ahash::RandomState>;
 type GroupBlock<T> = Vec<(T, Vec<DataValue>, DataBlock)>;
 
 pub trait HashMethod {
@@ -72,16 +75,18 @@ pub trait HashMethod {
 
         // 2. Build serialized keys
         let group_keys = self.build_keys(&group_columns, block.num_rows())?;
+        let group_nulls = null_masks(&group_columns, block.num_rows())?;
         // 2. Make group with indices.
         {
-            for (row, group_key) in group_keys.iter().enumerate().take(block.num_rows()) {
-                match group_indices.get_mut(group_key) {
+            let group_keys = group_keys.into_iter().zip(group_nulls.into_iter());
+            for (row, group_key) in group_keys.enumerate().take(block.num_rows()) {
+                match group_indices.get_mut(&group_key) {
                     None => {
                         let mut group_values = Vec::with_capacity(group_columns.len());
                         for col in &group_columns {
                             group_values.push(col.try_get(row)?);
                         }
-                        group_indices.insert(group_key.clone(), (vec![row as u32], group_values));
+                        group_indices.insert(group_key, (vec![row as u32], group_values));
                     }
                     Some((v, _)) => {
                         v.push(row as u32);
@@ -105,7 +110,7 @@ pub trait HashMethod {
         // Table for <(group_key, keys, block)>
         let mut group_blocks = GroupBlock::<Self::HashKey>::with_capacity(group_indices.len());
 
-        for (group_key, (group_indices, group_keys)) in group_indices {
+        for ((group_key, _), (group_indices, group_keys)) in group_indices {
             let take_block = DataBlock::block_take_by_indices(block, column_names, &group_indices)?;
             group_blocks.push((group_key, group_keys, take_block));
         }
@@ -116,6 +121,25 @@ pub trait HashMethod {
     fn build_keys(&self, group_columns: &[&DataColumn], rows: usize) -> Result<Vec<Self::HashKey>>;
 }
 
+/// Which group columns of each row are null, empty for the rows without a null.
+pub(crate) fn null_masks(group_columns: &[&DataColumn], rows: usize) -> Result<Vec<Vec<bool>>> {
+    let mut masks = vec![vec![]; rows];
+    for (index, column) in group_columns.iter().enumerate() {
+        let series = column.to_array()?;
+        if series.null_count() == 0 {
+            continue;
+        }
+
+        for (row, mask) in masks.iter_mut().enumerate() {
+            if series.is_null(row) {
+                mask.resize(group_columns.len(), false);
+                mask[index] = true;
+            }
+        }
+    }
+    Ok(masks)
+}
+
 pub type HashMethodKeysU8 = HashMethodFixedKeys<u8>;
 pub type HashMethodKeysU16 = HashMethodFixedKeys<u16>;
 pub type HashMethodKeysU32 = HashMethodFixedKeys<u32>;
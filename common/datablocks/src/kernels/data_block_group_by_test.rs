@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use common_datavalues::prelude::*;
 use common_exception::Result;
 
@@ -71,3 +73,165 @@ fn test_data_block_group_by() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_data_block_group_by_float() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Float64, false)]);
+
+    // NaNs with different signs or payloads are one group, and so are the zeros.
+    let payload_nan = f64::from_bits(0x7ff8_0000_0000_0001);
+    let block = DataBlock::create_by_array(schema, vec![Series::new(vec![
+        f64::NAN,
+        0.0,
+        -f64::NAN,
+        f64::INFINITY,
+        -0.0,
+        payload_nan,
+        1.5,
+        -payload_nan,
+        -0.0,
+        f64::INFINITY,
+    ])]);
+
+    let columns = &["a".to_string()];
+    let table = DataBlock::group_by_blocks(&block, columns)?;
+    assert_eq!(4, table.len());
+
+    for block in table {
+        let expected = match block.num_rows() {
+            1 => vec!["+-----+", "| a   |", "+-----+", "| 1.5 |", "+-----+"],
+            2 => vec![
+                "+-----+", "| a   |", "+-----+", "| inf |", "| inf |", "+-----+",
+            ],
+            3 => vec![
+                "+---+", "| a |", "+---+", "| 0 |", "| 0 |", "| 0 |", "+---+",
+            ],
+            4 => vec![
+                "+-----+", "| a   |", "+-----+", "| NaN |", "| NaN |", "| NaN |", "| NaN |",
+                "+-----+",
+            ],
+            _ => unreachable!(),
+        };
+        crate::assert_blocks_sorted_eq(expected, &[block]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_data_block_group_by_partitioned() -> Result<()> {
+    // 1M rows of 100k groups, the single primitive key is hashed by its values.
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", DataType::UInt64, false),
+        DataField::new("b", DataType::Int32, false),
+    ]);
+    let rows = 1_000_000u64;
+    let block = DataBlock::create_by_array(schema, vec![
+        Series::new((0..rows).map(|i| (i * 7919) % 100_000).collect::<Vec<_>>()),
+        Series::new((0..rows).map(|i| i as i32).collect::<Vec<_>>()),
+    ]);
+
+    let columns = &["a".to_string()];
+    let expected = group_counts(DataBlock::group_by_blocks(&block, columns)?, columns)?;
+    assert_eq!(100_000, expected.len());
+
+    let partitions = DataBlock::group_by_blocks_partitioned(&block, columns, 8)?;
+    assert_eq!(8, partitions.len());
+    let mut groups = vec![];
+    for partition in &partitions {
+        groups.extend(DataBlock::group_by_blocks(partition, columns)?);
+    }
+    assert_eq!(expected, group_counts(groups, columns)?);
+
+    // The composite keys are hashed by the keys of their hash method.
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", DataType::Int32, false),
+        DataField::new("b", DataType::String, false),
+    ]);
+    let rows = 100_000;
+    let block = DataBlock::create_by_array(schema, vec![
+        Series::new((0..rows).map(|i| i % 100).collect::<Vec<i32>>()),
+        Series::new(
+            (0..rows)
+                .map(|i| format!("x{}", i % 300).into_bytes())
+                .collect::<Vec<_>>(),
+        ),
+    ]);
+
+    let columns = &["a".to_string(), "b".to_string()];
+    let expected = group_counts(DataBlock::group_by_blocks(&block, columns)?, columns)?;
+    assert_eq!(300, expected.len());
+
+    let mut groups = vec![];
+    for partition in DataBlock::group_by_blocks_partitioned(&block, columns, 3)? {
+        groups.extend(DataBlock::group_by_blocks(&partition, columns)?);
+    }
+    assert_eq!(expected, group_counts(groups, columns)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_data_block_group_by_partitioned_same_partition() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Float64, true)]);
+    let nan = f64::from_bits(0x7ff8_0000_0000_0001);
+
+    // The keys of a group in different blocks, as arrays and as a constant.
+    let blocks = vec![
+        DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![
+            Some(1.5),
+            Some(0.0),
+            Some(f64::NAN),
+            None,
+            Some(2.5),
+        ])]),
+        DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![
+            Some(2.5),
+            Some(-nan),
+            None,
+            Some(-0.0),
+            Some(1.5),
+        ])]),
+        DataBlock::create(schema, vec![DataColumn::Constant(
+            DataValue::Float64(Some(1.5)),
+            3,
+        )]),
+    ];
+
+    let columns = &["a".to_string()];
+    let mut partition_of_key = HashMap::new();
+    for block in &blocks {
+        let partitions = DataBlock::group_by_blocks_partitioned(block, columns, 4)?;
+        for (index, partition) in partitions.iter().enumerate() {
+            for group in DataBlock::group_by_blocks(partition, columns)? {
+                let key = group_key(&group, columns)?;
+                let index = *partition_of_key.entry(key.clone()).or_insert(index);
+                assert_eq!(index, partition_of_key[&key], "{}", key);
+            }
+        }
+    }
+    // 1.5, 2.5, the zeros, the NaNs and the nulls.
+    assert_eq!(5, partition_of_key.len());
+
+    assert!(DataBlock::group_by_blocks_partitioned(&blocks[0], columns, 0).is_err());
+    Ok(())
+}
+
+/// The rows of each group by its key.
+fn group_counts(groups: Vec<DataBlock>, columns: &[String]) -> Result<HashMap<String, usize>> {
+    let mut counts = HashMap::new();
+    for group in groups {
+        let previous = counts.insert(group_key(&group, columns)?, group.num_rows());
+        assert!(previous.is_none(), "a key in two groups");
+    }
+    Ok(counts)
+}
+
+/// The key of a group, of the canonical NaN and zero.
+fn group_key(group: &DataBlock, columns: &[String]) -> Result<String> {
+    let key = columns
+        .iter()
+        .map(|name| group.try_column_by_name(name)?.try_get(0))
+        .collect::<Result<Vec<_>>>()?;
+    let key = format!("{:?}", key);
+    Ok(key.replace("-NaN", "NaN").replace("-0.0", "0.0"))
+}
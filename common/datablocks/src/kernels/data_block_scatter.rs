@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
 use common_exception::Result;
 
 use crate::DataBlock;
@@ -23,14 +24,29 @@ impl DataBlock {
         indices: &DataColumn,
         scatter_size: usize,
     ) -> Result<Vec<DataBlock>> {
-        let array = indices.to_array()?;
-        let array = array.u64()?;
+        let indices = match indices {
+            // All the rows go to the same block.
+            DataColumn::Constant(value, _) => {
+                let mut scattered_blocks = vec![block.slice(0, 0); scatter_size];
+                scattered_blocks[value.as_u64()? as usize] = block.clone();
+                return Ok(scattered_blocks);
+            }
+            _ => match indices.as_primitive_slice::<u64>() {
+                Some((values, _)) => values,
+                None => {
+                    return Err(ErrorCode::IllegalDataType(format!(
+                        "cannot unpack Series of type {:?} into u64",
+                        indices.data_type(),
+                    )))
+                }
+            },
+        };
 
         let columns_size = block.num_columns();
         let mut scattered_columns = Vec::with_capacity(scatter_size);
 
         for column_index in 0..columns_size {
-            let mut indices = array.into_no_null_iter().copied();
+            let mut indices = indices.iter().copied();
 
             let columns = unsafe {
                 block
@@ -50,3 +50,42 @@ fn test_data_block_scatter() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_data_block_scatter_constant_indices() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", DataType::Int64, false),
+        DataField::new("b", DataType::String, false),
+    ]);
+
+    let raw = DataBlock::create(schema, vec![
+        Series::new(vec![1i64, 2, 3]).into(),
+        Series::new(vec!["x", "y", "z"]).into(),
+    ]);
+
+    // All the rows go to the block 1.
+    let indices = DataColumn::Constant(DataValue::UInt64(Some(1)), 3);
+    let scattered = DataBlock::scatter_block(&raw, &indices, 3)?;
+    assert_eq!(scattered.len(), 3);
+    assert_eq!(scattered[0].num_rows(), 0);
+    assert_eq!(scattered[1].num_rows(), 3);
+    assert_eq!(scattered[2].num_rows(), 0);
+    assert_eq!(raw.schema(), scattered[0].schema());
+
+    let expected = vec![
+        "+---+---+",
+        "| a | b |",
+        "+---+---+",
+        "| 1 | x |",
+        "| 2 | y |",
+        "| 3 | z |",
+        "+---+---+",
+    ];
+    crate::assert_blocks_eq(expected, &scattered);
+
+    // The indices must be u64.
+    let indices = DataColumn::Array(Series::new([0u32, 1, 0]));
+    assert!(DataBlock::scatter_block(&raw, &indices, 2).is_err());
+
+    Ok(())
+}
@@ -13,12 +13,19 @@
 // limitations under the License.
 
 use common_datavalues::prelude::ceil;
+use common_exception::ErrorCode;
 use common_exception::Result;
 
 use crate::DataBlock;
 
 impl DataBlock {
     pub fn split_block_by_size(block: &DataBlock, max_block_size: usize) -> Result<Vec<DataBlock>> {
+        if max_block_size == 0 {
+            return Err(ErrorCode::BadArguments(
+                "Can't split a block into the blocks of 0 rows, max_block_size must be positive",
+            ));
+        }
+
         let size = block.num_rows();
         let mut blocks = Vec::with_capacity(ceil(size, max_block_size));
 
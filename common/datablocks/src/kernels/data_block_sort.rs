@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Ordering;
 use std::iter::once;
 use std::sync::Arc;
 
@@ -38,9 +39,15 @@ impl DataBlock {
         sort_columns_descriptions: &[SortColumnDescription],
         limit: Option<usize>,
     ) -> Result<DataBlock> {
+        // The partial sort of a limit is only for the limits less than the rows.
+        let limit = limit.filter(|limit| *limit < block.num_rows());
+        if limit == Some(0) {
+            return Ok(DataBlock::empty_with_schema(block.schema().clone()));
+        }
+
         let order_columns = sort_columns_descriptions
             .iter()
-            .map(|f| Ok(block.try_array_by_name(&f.column_name)?.get_array_ref()))
+            .map(|f| Ok(sort_key(block.try_array_by_name(&f.column_name)?)?.get_array_ref()))
             .collect::<Result<Vec<_>>>()?;
 
         let order_arrays = sort_columns_descriptions
@@ -79,10 +86,10 @@ impl DataBlock {
             .iter()
             .map(|f| {
                 let left = lhs.try_column_by_name(&f.column_name)?.clone();
-                let left = left.to_array()?;
+                let left = sort_key(left.to_array()?)?;
 
                 let right = rhs.try_column_by_name(&f.column_name)?.clone();
-                let right = right.to_array()?;
+                let right = sort_key(right.to_array()?)?;
 
                 Ok(vec![left.get_array_ref(), right.get_array_ref()])
             })
@@ -204,4 +211,123 @@ impl DataBlock {
             }
         }
     }
+
+    /// For sorted blocks, returns how many leading rows of each block are not greater than the
+    /// smallest last row among them. Those prefixes can be merged and emitted before any other
+    /// row of the sorted runs the blocks are taken from.
+    pub fn merge_sort_cut_points(
+        blocks: &[DataBlock],
+        sort_columns_descriptions: &[SortColumnDescription],
+    ) -> Result<Vec<usize>> {
+        let sort_arrays = sort_columns_descriptions
+            .iter()
+            .map(|f| {
+                blocks
+                    .iter()
+                    .map(|block| {
+                        let column = block.try_column_by_name(&f.column_name)?;
+                        Ok(sort_key(column.to_array()?)?.get_array_ref())
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let sort_dyn_arrays = sort_arrays
+            .iter()
+            .map(|arrays| arrays.iter().map(|a| a.as_ref()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let sort_options = sort_columns_descriptions
+            .iter()
+            .map(|f| arrow_sort::SortOptions {
+                descending: !f.asc,
+                nulls_first: f.nulls_first,
+            })
+            .collect::<Vec<_>>();
+
+        let sort_options_with_array = sort_dyn_arrays
+            .iter()
+            .zip(sort_options.iter())
+            .map(|(s, opt)| {
+                let paris: (&[&dyn Array], &SortOptions) = (s, opt);
+                paris
+            })
+            .collect::<Vec<_>>();
+
+        let comparator = build_comparator(&sort_options_with_array)?;
+
+        let mut bound: Option<usize> = None;
+        for (index, block) in blocks.iter().enumerate() {
+            if block.num_rows() == 0 {
+                continue;
+            }
+
+            bound = match bound {
+                Some(bound)
+                    if comparator(
+                        bound,
+                        blocks[bound].num_rows() - 1,
+                        index,
+                        block.num_rows() - 1,
+                    ) != Ordering::Greater =>
+                {
+                    Some(bound)
+                }
+                _ => Some(index),
+            };
+        }
+
+        let bound = match bound {
+            None => return Ok(vec![0; blocks.len()]),
+            Some(bound) => bound,
+        };
+
+        let bound_row = blocks[bound].num_rows() - 1;
+        Ok(blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| {
+                if index == bound {
+                    return block.num_rows();
+                }
+
+                // The first row greater than the bound row.
+                let (mut low, mut high) = (0, block.num_rows());
+                while low < high {
+                    let middle = (low + high) / 2;
+                    match comparator(index, middle, bound, bound_row) {
+                        Ordering::Greater => high = middle,
+                        _ => low = middle + 1,
+                    }
+                }
+                low
+            })
+            .collect())
+    }
+}
+
+/// The floats are compared by their total order, in which a NaN with the sign bit set is the least,
+/// and -0.0 is less than 0.0.
+/// Canonicalize the float keys so that every NaN is equal and the greatest, i.e. the last in an ascending order,
+/// and the zeros are equal.
+fn sort_key(series: Series) -> Result<Series> {
+    match series.data_type() {
+        DataType::Float32 => Ok(series
+            .f32()?
+            .apply(|v| match v {
+                v if v.is_nan() => f32::NAN,
+                v if v == 0.0 => 0.0,
+                v => v,
+            })
+            .into_series()),
+        DataType::Float64 => Ok(series
+            .f64()?
+            .apply(|v| match v {
+                v if v.is_nan() => f64::NAN,
+                v if v == 0.0 => 0.0,
+                v => v,
+            })
+            .into_series()),
+        _ => Ok(series),
+    }
 }
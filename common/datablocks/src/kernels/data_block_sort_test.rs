@@ -117,3 +117,112 @@ fn test_data_block_merge_sort() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_data_block_merge_sort_cut_points() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+
+    let block =
+        |values: Vec<i64>| DataBlock::create_by_array(schema.clone(), vec![Series::new(values)]);
+
+    {
+        let options = vec![SortColumnDescription {
+            column_name: "a".to_owned(),
+            asc: true,
+            nulls_first: false,
+        }];
+        let blocks = vec![
+            block(vec![3, 5, 7]),
+            block(vec![2, 4, 6]),
+            block(vec![8, 9]),
+            block(vec![]),
+        ];
+        let cut_points = DataBlock::merge_sort_cut_points(&blocks, &options)?;
+        assert_eq!(vec![2, 3, 0, 0], cut_points);
+    }
+
+    {
+        let options = vec![SortColumnDescription {
+            column_name: "a".to_owned(),
+            asc: false,
+            nulls_first: false,
+        }];
+        let blocks = vec![block(vec![7, 5, 3]), block(vec![6, 4, 2])];
+        let cut_points = DataBlock::merge_sort_cut_points(&blocks, &options)?;
+        assert_eq!(vec![3, 2], cut_points);
+    }
+
+    {
+        let options = vec![SortColumnDescription {
+            column_name: "a".to_owned(),
+            asc: true,
+            nulls_first: false,
+        }];
+        let cut_points = DataBlock::merge_sort_cut_points(&[block(vec![])], &options)?;
+        assert_eq!(vec![0], cut_points);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_data_block_sort_float() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Float64, false)]);
+
+    let block =
+        |values: Vec<f64>| DataBlock::create_by_array(schema.clone(), vec![Series::new(values)]);
+
+    let asc = vec![SortColumnDescription {
+        column_name: "a".to_owned(),
+        asc: true,
+        nulls_first: false,
+    }];
+
+    // A NaN is the greatest whatever its sign is, and -0.0 equals 0.0.
+    {
+        let raw = block(vec![
+            1.5,
+            -f64::NAN,
+            f64::NEG_INFINITY,
+            -0.0,
+            1e300,
+            f64::NAN,
+            f64::INFINITY,
+            5e-324,
+        ]);
+        let results = DataBlock::sort_block(&raw, &asc, None)?;
+
+        let expected = vec![
+            "+--------+",
+            "| a      |",
+            "+--------+",
+            "| -inf   |",
+            "| 0      |",
+            "| 5e-324 |",
+            "| 1.5    |",
+            "| 1e300  |",
+            "| inf    |",
+            "| NaN    |",
+            "| NaN    |",
+            "+--------+",
+        ];
+        crate::assert_blocks_eq(expected, &[results]);
+    }
+
+    {
+        let raw1 = block(vec![f64::NEG_INFINITY, 1.5, f64::NAN]);
+        let raw2 = block(vec![-0.0, f64::INFINITY, -f64::NAN]);
+        let results = DataBlock::merge_sort_block(&raw1, &raw2, &asc, None)?;
+
+        let expected = vec![
+            "+------+", "| a    |", "+------+", "| -inf |", "| 0    |", "| 1.5  |", "| inf  |",
+            "| NaN  |", "| NaN  |", "+------+",
+        ];
+        crate::assert_blocks_eq(expected, &[results]);
+
+        let cut_points = DataBlock::merge_sort_cut_points(&[raw1, raw2], &asc)?;
+        assert_eq!(vec![3, 3], cut_points);
+    }
+
+    Ok(())
+}
@@ -38,8 +38,216 @@ use strength_reduce::StrengthReducedU64;
 use crate::arrays::ops::*;
 use crate::prelude::*;
 
-/// TODO: sundy
-/// check division by zero in rem & div ops
+/// The arithmetic of the integers that tells the overflows, it never overflows for the floats.
+pub trait CheckedArithmetic: Sized {
+    /// The wrapped result and whether it overflows.
+    fn overflowing_arith_add(self, rhs: Self) -> (Self, bool);
+    fn overflowing_arith_sub(self, rhs: Self) -> (Self, bool);
+    fn overflowing_arith_mul(self, rhs: Self) -> (Self, bool);
+    /// The divisor must not be zero.
+    fn checked_arith_div(self, rhs: Self) -> Option<Self>;
+    /// The divisor must not be zero, `MIN % -1` is 0.
+    fn wrapping_arith_rem(self, rhs: Self) -> Self;
+    fn checked_arith_neg(self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_integer {
+    ($ty:ident) => {
+        impl CheckedArithmetic for $ty {
+            fn overflowing_arith_add(self, rhs: Self) -> (Self, bool) {
+                self.overflowing_add(rhs)
+            }
+            fn overflowing_arith_sub(self, rhs: Self) -> (Self, bool) {
+                self.overflowing_sub(rhs)
+            }
+            fn overflowing_arith_mul(self, rhs: Self) -> (Self, bool) {
+                self.overflowing_mul(rhs)
+            }
+            fn checked_arith_div(self, rhs: Self) -> Option<Self> {
+                self.checked_div(rhs)
+            }
+            fn wrapping_arith_rem(self, rhs: Self) -> Self {
+                self.wrapping_rem(rhs)
+            }
+            fn checked_arith_neg(self) -> Option<Self> {
+                self.checked_neg()
+            }
+        }
+    };
+}
+
+macro_rules! impl_checked_floating {
+    ($ty:ident) => {
+        impl CheckedArithmetic for $ty {
+            fn overflowing_arith_add(self, rhs: Self) -> (Self, bool) {
+                (self + rhs, false)
+            }
+            fn overflowing_arith_sub(self, rhs: Self) -> (Self, bool) {
+                (self - rhs, false)
+            }
+            fn overflowing_arith_mul(self, rhs: Self) -> (Self, bool) {
+                (self * rhs, false)
+            }
+            fn checked_arith_div(self, rhs: Self) -> Option<Self> {
+                Some(self / rhs)
+            }
+            fn wrapping_arith_rem(self, rhs: Self) -> Self {
+                self % rhs
+            }
+            fn checked_arith_neg(self) -> Option<Self> {
+                Some(-self)
+            }
+        }
+    };
+}
+
+impl_checked_integer!(u8);
+impl_checked_integer!(u16);
+impl_checked_integer!(u32);
+impl_checked_integer!(u64);
+impl_checked_integer!(i8);
+impl_checked_integer!(i16);
+impl_checked_integer!(i32);
+impl_checked_integer!(i64);
+impl_checked_floating!(f32);
+impl_checked_floating!(f64);
+
+fn overflow_error<T: DFPrimitiveType>(lhs: T, op: &str, rhs: T) -> ErrorCode {
+    ErrorCode::ArithmeticOverflow(format!(
+        "{:?} {} {:?} overflows {:?}",
+        lhs,
+        op,
+        rhs,
+        T::data_type()
+    ))
+}
+
+/// Applies the operation on the valid rows one by one, the values of the null rows are arbitrary.
+/// The operation returns None for a null result, e.g. the integer division by zero.
+fn checked_arithmetic_helper<T, F>(
+    lhs: &DFPrimitiveArray<T>,
+    rhs: &DFPrimitiveArray<T>,
+    operation: F,
+) -> Result<DFPrimitiveArray<T>>
+where
+    T: DFPrimitiveType,
+    F: Fn(T, T) -> Result<Option<T>>,
+{
+    match (lhs.len(), rhs.len()) {
+        (a, b) if a == b => lhs
+            .iter()
+            .zip(rhs.iter())
+            .map(|(l, r)| match (l, r) {
+                (Some(l), Some(r)) => operation(*l, *r),
+                _ => Ok(None),
+            })
+            .collect(),
+        // broadcast right path
+        (_, 1) => match rhs.get(0) {
+            None => Ok(DFPrimitiveArray::<T>::full_null(lhs.len())),
+            Some(r) => lhs
+                .iter()
+                .map(|l| match l {
+                    Some(l) => operation(*l, r),
+                    None => Ok(None),
+                })
+                .collect(),
+        },
+        (1, _) => match lhs.get(0) {
+            None => Ok(DFPrimitiveArray::<T>::full_null(rhs.len())),
+            Some(l) => rhs
+                .iter()
+                .map(|r| match r {
+                    Some(r) => operation(l, *r),
+                    None => Ok(None),
+                })
+                .collect(),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn division_by_zero_error<T: DFPrimitiveType>(lhs: T, op: &str) -> ErrorCode {
+    ErrorCode::DivisionByZero(format!("{:?} {} 0", lhs, op))
+}
+
+/// Applies the operation on all the values at once, the values of the null rows included.
+/// Only when a value overflows, the valid rows are checked one by one to tell which one.
+fn overflowing_arithmetic_helper<T, F>(
+    lhs: &DFPrimitiveArray<T>,
+    rhs: &DFPrimitiveArray<T>,
+    op: &str,
+    operation: F,
+) -> Result<DFPrimitiveArray<T>>
+where
+    T: DFPrimitiveType,
+    F: Fn(T, T) -> (T, bool),
+{
+    let mut overflow = false;
+    let mut apply = |l: T, r: T| {
+        let (v, o) = operation(l, r);
+        overflow |= o;
+        v
+    };
+
+    let (values, validity): (AlignedVec<T>, _) = match (lhs.len(), rhs.len()) {
+        (a, b) if a == b => (
+            lhs.into_no_null_iter()
+                .zip(rhs.into_no_null_iter())
+                .map(|(l, r)| apply(*l, *r))
+                .collect(),
+            combine_validities(lhs.inner().validity(), rhs.inner().validity()),
+        ),
+        // broadcast right path
+        (_, 1) => match rhs.get(0) {
+            None => return Ok(DFPrimitiveArray::<T>::full_null(lhs.len())),
+            Some(r) => (
+                lhs.into_no_null_iter().map(|l| apply(*l, r)).collect(),
+                lhs.inner().validity().clone(),
+            ),
+        },
+        (1, _) => match lhs.get(0) {
+            None => return Ok(DFPrimitiveArray::<T>::full_null(rhs.len())),
+            Some(l) => (
+                rhs.into_no_null_iter().map(|r| apply(l, *r)).collect(),
+                rhs.inner().validity().clone(),
+            ),
+        },
+        _ => unreachable!(),
+    };
+
+    match overflow {
+        // the overflow may be in the null rows only
+        true => checked_arithmetic_helper(lhs, rhs, |l, r| match operation(l, r) {
+            (v, false) => Ok(Some(v)),
+            (_, true) => Err(overflow_error(l, op, r)),
+        }),
+        false => Ok(DFPrimitiveArray::<T>::new_from_owned_with_null_bitmap(
+            values, validity,
+        )),
+    }
+}
+
+fn checked_negate<T>(array: &PrimitiveArray<T>) -> Result<PrimitiveArray<T>>
+where T: DFPrimitiveType + CheckedArithmetic {
+    if let Some(v) = array
+        .iter()
+        .flatten()
+        .find(|v| v.checked_arith_neg().is_none())
+    {
+        return Err(ErrorCode::ArithmeticOverflow(format!(
+            "-({:?}) overflows {:?}",
+            v,
+            T::data_type()
+        )));
+    }
+    Ok(unary(
+        array,
+        |v| v.checked_arith_neg().unwrap_or_default(),
+        array.data_type().clone(),
+    ))
+}
+
 fn arithmetic_helper<T, Kernel, SKernel, F>(
     lhs: &DFPrimitiveArray<T>,
     rhs: &DFPrimitiveArray<T>,
@@ -93,11 +301,17 @@ where T: DFPrimitiveType
         + Mul<Output = T>
         + Div<Output = T>
         + NumCast
+        + CheckedArithmetic
         + num::Zero
 {
     type Output = Result<DFPrimitiveArray<T>>;
 
     fn add(self, rhs: Self) -> Self::Output {
+        if !T::FLOATING {
+            return overflowing_arithmetic_helper(self, rhs, "+", |l, r| {
+                l.overflowing_arith_add(r)
+            });
+        }
         arithmetic_helper(
             self,
             rhs,
@@ -116,11 +330,17 @@ where
         + Mul<Output = T>
         + Div<Output = T>
         + Rem<Output = T>
+        + CheckedArithmetic
         + num::Zero,
 {
     type Output = Result<DFPrimitiveArray<T>>;
 
     fn sub(self, rhs: Self) -> Self::Output {
+        if !T::FLOATING {
+            return overflowing_arithmetic_helper(self, rhs, "-", |l, r| {
+                l.overflowing_arith_sub(r)
+            });
+        }
         arithmetic_helper(
             self,
             rhs,
@@ -140,11 +360,17 @@ where
         + Div<Output = T>
         + Rem<Output = T>
         + NumCast
+        + CheckedArithmetic
         + num::Zero,
 {
     type Output = Result<DFPrimitiveArray<T>>;
 
     fn mul(self, rhs: Self) -> Self::Output {
+        if !T::FLOATING {
+            return overflowing_arithmetic_helper(self, rhs, "*", |l, r| {
+                l.overflowing_arith_mul(r)
+            });
+        }
         arithmetic_helper(
             self,
             rhs,
@@ -164,12 +390,22 @@ where
         + Div<Output = T>
         + Rem<Output = T>
         + NumCast
+        + CheckedArithmetic
         + num::Zero
         + num::One,
 {
     type Output = Result<DFPrimitiveArray<T>>;
 
     fn div(self, rhs: Self) -> Self::Output {
+        if !T::FLOATING {
+            return checked_arithmetic_helper(self, rhs, |l, r| match r.is_zero() {
+                true => Ok(None),
+                false => match l.checked_arith_div(r) {
+                    Some(v) => Ok(Some(v)),
+                    None => Err(overflow_error(l, "/", r)),
+                },
+            });
+        }
         arithmetic_helper(
             self,
             rhs,
@@ -198,10 +434,12 @@ where
         + NumCast
         + ToPrimitive
         + AsPrimitive<u8>
+        + CheckedArithmetic
         + num::Zero
         + num::One,
 {
-    pub fn rem(&self, rhs: &Self, dtype: &DataType) -> Result<Series> {
+    /// The remainder of the integer division by zero is null, or a DivisionByZero error if strict.
+    pub fn rem(&self, rhs: &Self, dtype: &DataType, strict: bool) -> Result<Series> {
         match (rhs.len(), dtype) {
             // TODO(sundy): add more specific cases
             // TODO(sundy): fastmod https://lemire.me/blog/2019/02/08/faster-remainders-when-the-divisor-is-a-constant-beating-compilers-and-libdivide/
@@ -209,6 +447,12 @@ where
                 let opt_rhs = rhs.get(0);
                 match opt_rhs {
                     None => Ok(DFUInt8Array::full_null(self.len()).into_series()),
+                    Some(rhs) if rhs.is_zero() && !T::FLOATING => {
+                        match self.iter().flatten().next() {
+                            Some(lhs) if strict => Err(division_by_zero_error(*lhs, "%")),
+                            _ => Ok(DFUInt8Array::full_null(self.len()).into_series()),
+                        }
+                    }
                     Some(rhs) => match self.data_type() {
                         DataType::UInt64 => {
                             let arr = self.array.as_any().downcast_ref::<UInt64Array>().unwrap();
@@ -243,6 +487,15 @@ where
                 }
             }
 
+            _ if !T::FLOATING => {
+                let array = checked_arithmetic_helper(self, rhs, |l, r| match r.is_zero() {
+                    true if strict => Err(division_by_zero_error(l, "%")),
+                    true => Ok(None),
+                    false => Ok(Some(l.wrapping_arith_rem(r))),
+                })?;
+                Ok(array.into_series())
+            }
+
             _ => {
                 let array = arithmetic_helper(
                     self,
@@ -277,21 +530,25 @@ where
         unsafe {
             match self.data_type() {
                 DataType::Int8 => {
-                    let v = negate(&*(arr as *const dyn Array as *const PrimitiveArray<i8>));
+                    let v =
+                        checked_negate(&*(arr as *const dyn Array as *const PrimitiveArray<i8>))?;
                     Ok(DFInt8Array::new(v).into_series())
                 }
 
                 DataType::Int16 => {
-                    let v = negate(&*(arr as *const dyn Array as *const PrimitiveArray<i16>));
+                    let v =
+                        checked_negate(&*(arr as *const dyn Array as *const PrimitiveArray<i16>))?;
                     Ok(DFInt16Array::new(v).into_series())
                 }
 
                 DataType::Int32 => {
-                    let v = negate(&*(arr as *const dyn Array as *const PrimitiveArray<i32>));
+                    let v =
+                        checked_negate(&*(arr as *const dyn Array as *const PrimitiveArray<i32>))?;
                     Ok(DFInt32Array::new(v).into_series())
                 }
                 DataType::Int64 => {
-                    let v = negate(&*(arr as *const dyn Array as *const PrimitiveArray<i64>));
+                    let v =
+                        checked_negate(&*(arr as *const dyn Array as *const PrimitiveArray<i64>))?;
                     Ok(DFInt64Array::new(v).into_series())
                 }
                 DataType::Float32 => {
@@ -39,3 +39,38 @@ fn test_mul() -> Result<()> {
     assert_eq!(array.collect_values(), vec![Some(14), None, None]);
     Ok(())
 }
+
+#[test]
+fn test_div() -> Result<()> {
+    let array1 = DFUInt16Array::new_from_opt_slice(&[Some(10), Some(5), None]);
+    let array2 = DFUInt16Array::new_from_opt_slice(&[Some(2u16), Some(0), Some(0)]);
+    let array = (&array1 / &array2)?;
+    assert_eq!(array.collect_values(), vec![Some(5), None, None]);
+    Ok(())
+}
+
+#[test]
+fn test_overflow() -> Result<()> {
+    let array1 = DFUInt16Array::new_from_opt_slice(&[Some(1), Some(u16::MAX)]);
+    let array2 = DFUInt16Array::new_from_opt_slice(&[Some(1u16)]);
+    let e = (&array1 + &array2).unwrap_err();
+    assert_eq!(e.code(), 60);
+    assert_eq!(e.message(), "65535 + 1 overflows UInt16");
+
+    // the values of the null rows may overflow
+    let validity = DFInt8Array::new_from_opt_slice(&[Some(1), None, Some(3)])
+        .inner()
+        .validity()
+        .clone();
+    let values = [1i8, i8::MAX, 3]
+        .iter()
+        .copied()
+        .collect::<AlignedVec<i8>>();
+    let array1 = DFInt8Array::new_from_owned_with_null_bitmap(values, validity);
+    let array2 = DFInt8Array::new_from_slice(&[1, 1, 2]);
+    let result = (&array1 + &array2)?;
+    assert_eq!(result.collect_values(), vec![Some(2), None, Some(5)]);
+    let result = (&array1 + &array2.slice(0, 1))?;
+    assert_eq!(result.collect_values(), vec![Some(2), None, Some(4)]);
+    Ok(())
+}
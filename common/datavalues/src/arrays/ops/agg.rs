@@ -103,7 +103,8 @@ where
             });
         }
 
-        if self.is_empty() {
+        // empty or all null: nothing to sum, the result is NULL rather than zero
+        if null_count == self.len() {
             return Ok(DataValue::from(self.data_type()));
         }
 
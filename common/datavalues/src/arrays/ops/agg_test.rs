@@ -43,6 +43,51 @@ fn test_array_agg() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_nullable_array_agg() -> Result<()> {
+    struct Test {
+        name: &'static str,
+        array: DFUInt16Array,
+        expect: [DataValue; 3],
+    }
+
+    let tests = vec![
+        Test {
+            name: "all-null",
+            array: DFUInt16Array::new_from_opt_slice(&[None, None, None]),
+            expect: [
+                DataValue::UInt16(None),
+                DataValue::UInt16(None),
+                DataValue::UInt16(None),
+            ],
+        },
+        Test {
+            name: "mixed",
+            array: DFUInt16Array::new_from_opt_slice(&[Some(3), None, Some(1)]),
+            expect: [
+                DataValue::UInt64(Some(4)),
+                DataValue::UInt16(Some(3)),
+                DataValue::UInt16(Some(1)),
+            ],
+        },
+        Test {
+            name: "no-null",
+            array: DFUInt16Array::new_from_opt_slice(&[Some(3), Some(2), Some(1)]),
+            expect: [
+                DataValue::UInt64(Some(6)),
+                DataValue::UInt16(Some(3)),
+                DataValue::UInt16(Some(1)),
+            ],
+        },
+    ];
+
+    for t in tests {
+        let value = [t.array.sum()?, t.array.max()?, t.array.min()?];
+        assert_eq!(value, t.expect, "{}", t.name);
+    }
+    Ok(())
+}
+
 #[test]
 fn test_boolean_array_agg() -> Result<()> {
     let array = DFBooleanArray::new_from_slice(&[true, false, true]);
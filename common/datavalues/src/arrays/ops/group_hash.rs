@@ -16,6 +16,8 @@ use std::fmt::Debug;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_io::prelude::*;
+use num::NumCast;
+use num::ToPrimitive;
 
 use crate::prelude::*;
 
@@ -69,12 +71,26 @@ where
     fn serialize(&self, vec: &mut Vec<Vec<u8>>) -> Result<()> {
         assert_eq!(vec.len(), self.len());
         for (value, vec) in self.into_no_null_iter().zip(vec.iter_mut()) {
-            BinaryWrite::write_scalar(vec, value)?;
+            if T::FLOATING {
+                BinaryWrite::write_scalar(vec, &canonical_float(*value))?;
+            } else {
+                BinaryWrite::write_scalar(vec, value)?;
+            }
         }
         Ok(())
     }
 }
 
+/// NaNs with different payloads or signs are one group, and so are 0.0 and -0.0.
+#[inline]
+fn canonical_float<T: DFPrimitiveType>(v: T) -> T {
+    match v.to_f64() {
+        Some(f) if f.is_nan() => <T as NumCast>::from(f64::NAN).unwrap_or(v),
+        Some(f) if f == 0.0 => T::default(),
+        _ => v,
+    }
+}
+
 impl GroupHash for DFBooleanArray {
     fn fixed_hash(&self, ptr: *mut u8, step: usize) -> Result<()> {
         let array = self.inner();
@@ -102,6 +102,25 @@ impl DataColumn {
         }
     }
 
+    /// Same as arithmetic, but the integer division or modulo by zero is an error rather than null.
+    pub fn strict_arithmetic(
+        &self,
+        op: DataValueArithmeticOperator,
+        rhs: &DataColumn,
+    ) -> Result<DataColumn> {
+        let result = match op {
+            DataValueArithmeticOperator::Div => self
+                .to_minimal_array()?
+                .strict_div(&rhs.to_minimal_array()?)?,
+            DataValueArithmeticOperator::Modulo => self
+                .to_minimal_array()?
+                .strict_rem(&rhs.to_minimal_array()?)?,
+            op => return self.arithmetic(op, rhs),
+        };
+        let result: DataColumn = result.into();
+        Ok(result.resize_constant(self.len()))
+    }
+
     pub fn unary_arithmetic(&self, op: DataValueArithmeticOperator) -> Result<DataColumn> {
         match op {
             DataValueArithmeticOperator::Plus => Ok(self.clone()),
@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_arrow::arrow::array::Array;
 use common_arrow::arrow::array::ArrayRef;
+use common_arrow::arrow::bitmap::Bitmap;
 use common_exception::Result;
 
 use crate::prelude::*;
@@ -179,6 +181,54 @@ impl DataColumn {
         }
     }
 
+    /// Returns the values and the validity of a primitive array of `T`,
+    /// without converting every value into a DataValue.
+    /// It is None for a constant column, or an array of another type,
+    /// the caller renders the scalar of a constant once for all the rows.
+    #[inline]
+    pub fn as_primitive_slice<T: DFPrimitiveType>(&self) -> Option<(&[T], Option<&Bitmap>)> {
+        match self {
+            DataColumn::Array(array) if array.data_type() == &T::data_type() => {
+                let array = array.static_cast::<DFPrimitiveArray<T>>().inner();
+                Some((array.values().as_slice(), array.validity().as_ref()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Iterates a string array as (value, is_null), without copying the values.
+    /// The strings are bytes, they are not checked to be valid utf8. The value of a null is unspecified.
+    /// It is None for a constant column, or an array of another type.
+    #[inline]
+    pub fn string_iter(&self) -> Option<impl Iterator<Item = (&[u8], bool)> + '_> {
+        match self {
+            DataColumn::Array(array) => {
+                let array = array.string().ok()?.inner();
+                Some((0..array.len()).map(move |i| {
+                    let is_null = array.is_null(i);
+                    // Safety: i is in 0..len.
+                    let value = unsafe { array.value_unchecked(i) };
+                    (value, is_null)
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the values into DataValues by chunks of at most `chunk_size` rows,
+    /// for the types without a bulk accessor.
+    /// It saves the dynamic dispatch and the error handling of `try_get()` on every row.
+    pub fn values_chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Result<Vec<DataValue>>> + '_ {
+        let len = self.len();
+        let chunk_size = chunk_size.max(1);
+        (0..len)
+            .step_by(chunk_size)
+            .map(move |offset| self.slice(offset, chunk_size.min(len - offset)).to_values())
+    }
+
     #[inline]
     pub fn serialize(&self, vec: &mut Vec<Vec<u8>>) -> Result<()> {
         let array = self.to_array()?;
@@ -0,0 +1,200 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::cell::Cell;
+
+use common_exception::Result;
+use pretty_assertions::assert_eq;
+
+use crate::prelude::*;
+
+/// Counts the allocations of the current thread, the tests run in parallel.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(|n| n.get());
+    let res = f();
+    (res, ALLOCATIONS.with(|n| n.get()) - before)
+}
+
+#[test]
+fn test_data_column_as_primitive_slice() -> Result<()> {
+    let column: DataColumn = Series::new(vec![Some(1u32), None, Some(3)]).into();
+    let (values, validity) = column.as_primitive_slice::<u32>().unwrap();
+    assert_eq!(&[1, 3], &[values[0], values[2]]);
+    let validity = validity.unwrap();
+    assert_eq!(vec![true, false, true], validity.iter().collect::<Vec<_>>());
+
+    let column: DataColumn = Series::new(vec![1.5f64, 2.5]).into();
+    let (values, _) = column.as_primitive_slice::<f64>().unwrap();
+    assert_eq!(&[1.5f64, 2.5], values);
+
+    // Not of the type.
+    assert!(column.as_primitive_slice::<f32>().is_none());
+    assert!(column.as_primitive_slice::<u64>().is_none());
+
+    // A constant is rendered by the caller once.
+    let constant = DataColumn::Constant(DataValue::UInt32(Some(7)), 3);
+    assert!(constant.as_primitive_slice::<u32>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_data_column_string_iter() -> Result<()> {
+    let column: DataColumn = Series::new(vec![Some("a"), None, Some("数据"), Some("")]).into();
+    let got = column
+        .string_iter()
+        .unwrap()
+        .map(|(v, is_null)| if is_null { None } else { Some(v) })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            Some(&b"a"[..]),
+            None,
+            Some("数据".as_bytes()),
+            Some(&b""[..])
+        ],
+        got
+    );
+
+    // A slice starts at its offset.
+    let got = column
+        .slice(2, 2)
+        .string_iter()
+        .unwrap()
+        .map(|(v, _)| v.to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["数据".as_bytes().to_vec(), vec![]], got);
+
+    assert!(
+        DataColumn::Constant(DataValue::String(Some(b"a".to_vec())), 2)
+            .string_iter()
+            .is_none()
+    );
+    let column: DataColumn = Series::new(vec![1u8]).into();
+    assert!(column.string_iter().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_data_column_values_chunks() -> Result<()> {
+    let column: DataColumn = Series::new(vec![Some(1i64), None, Some(3), Some(4), Some(5)]).into();
+    for chunk_size in [0, 1, 2, 5, 8] {
+        let values = column
+            .values_chunks(chunk_size)
+            .collect::<Result<Vec<_>>>()?
+            .concat();
+        assert_eq!(column.to_values()?, values, "chunk size: {}", chunk_size);
+    }
+    assert_eq!(3, column.values_chunks(2).count());
+
+    let constant = DataColumn::Constant(DataValue::String(Some(b"x".to_vec())), 3);
+    let values = constant
+        .values_chunks(2)
+        .collect::<Result<Vec<_>>>()?
+        .concat();
+    assert_eq!(vec![DataValue::String(Some(b"x".to_vec())); 3], values);
+
+    Ok(())
+}
+
+#[test]
+fn test_data_column_bulk_access_allocations() -> Result<()> {
+    // A wide block: 32 string columns and 32 integer columns of 1000 rows.
+    let rows = 1000;
+    let strings = (0..rows)
+        .map(|i| {
+            if i % 10 == 0 {
+                None
+            } else {
+                Some(format!("värde-{}", i))
+            }
+        })
+        .collect::<Vec<_>>();
+    let strings = strings.iter().map(|s| s.as_deref()).collect::<Vec<_>>();
+    let numbers = (0..rows as u64).collect::<Vec<_>>();
+
+    let mut columns: Vec<DataColumn> = vec![];
+    for _ in 0..32 {
+        columns.push(Series::new(strings.clone()).into());
+        columns.push(Series::new(numbers.clone()).into());
+    }
+
+    // Per row access: a DataValue per cell, a copy of each string.
+    let (total, per_row) = count_allocations(|| -> Result<usize> {
+        let mut total = 0;
+        for row in 0..rows {
+            for column in columns.iter() {
+                total += match column.try_get(row)? {
+                    DataValue::String(Some(v)) => v.len(),
+                    DataValue::UInt64(Some(v)) => v as usize,
+                    _ => 0,
+                };
+            }
+        }
+        Ok(total)
+    });
+    let want = total?;
+
+    // Bulk access: the values are borrowed from the arrays.
+    let (total, bulk) = count_allocations(|| -> Result<usize> {
+        let mut total = 0;
+        for column in columns.iter() {
+            if let Some(iter) = column.string_iter() {
+                total += iter
+                    .filter(|(_, is_null)| !is_null)
+                    .map(|(v, _)| v.len())
+                    .sum::<usize>();
+            } else if let Some((values, _)) = column.as_primitive_slice::<u64>() {
+                total += values.iter().map(|v| *v as usize).sum::<usize>();
+            }
+        }
+        Ok(total)
+    });
+    assert_eq!(want, total?);
+
+    // 32 * 900 strings are copied per row.
+    assert!(per_row >= 32 * 900, "per row allocations: {}", per_row);
+    assert!(
+        bulk * 1000 < per_row,
+        "bulk: {}, per row: {}",
+        bulk,
+        per_row
+    );
+
+    Ok(())
+}
@@ -357,8 +357,9 @@ impl fmt::Display for DataValue {
         match self {
             DataValue::Null => write!(f, "NULL"),
             DataValue::Boolean(v) => format_data_value_with_option!(f, v),
-            DataValue::Float32(v) => format_data_value_with_option!(f, v),
-            DataValue::Float64(v) => format_data_value_with_option!(f, v),
+            DataValue::Float32(Some(v)) => write!(f, "{}", FloatFormat::text().format_f32(*v)),
+            DataValue::Float64(Some(v)) => write!(f, "{}", FloatFormat::text().format_f64(*v)),
+            DataValue::Float32(None) | DataValue::Float64(None) => write!(f, "NULL"),
             DataValue::Int8(v) => format_data_value_with_option!(f, v),
             DataValue::Int16(v) => format_data_value_with_option!(f, v),
             DataValue::Int32(v) => format_data_value_with_option!(f, v),
@@ -0,0 +1,133 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::borrow::Cow;
+
+/// Renders the floats in the results, shared by every output format so that a value reads the same everywhere.
+///
+/// - By default a float is rendered with the shortest representation that round-trips,
+///   in scientific notation if it is very large or very small, e.g. `1e300`, `5e-324`.
+/// - With a precision, it is rendered with a fixed number of digits after the decimal point.
+/// - NaN and the infinities are rendered as the tokens of the output format.
+/// - A negative zero is rendered as zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatFormat {
+    precision: Option<usize>,
+    nan: Cow<'static, str>,
+    inf: Cow<'static, str>,
+    neg_inf: Cow<'static, str>,
+}
+
+macro_rules! format_float {
+    ($SELF:expr, $V:expr) => {{
+        let v = $V;
+        if v.is_nan() {
+            return $SELF.nan.to_string();
+        }
+        if v.is_infinite() {
+            return if v > 0.0 {
+                $SELF.inf.to_string()
+            } else {
+                $SELF.neg_inf.to_string()
+            };
+        }
+
+        // -0.0 == 0.0
+        let v = if v == 0.0 { 0.0 } else { v };
+
+        match $SELF.precision {
+            Some(precision) => {
+                let s = format!("{:.*}", precision, v);
+                // A tiny negative value rounds to a negative zero, e.g. `-0.00`.
+                match s.strip_prefix('-') {
+                    Some(abs) if abs.bytes().all(|b| b == b'0' || b == b'.') => abs.to_string(),
+                    _ => s,
+                }
+            }
+            None => {
+                let abs = v.abs();
+                if abs != 0.0 && (abs < 1e-7 || abs >= 1e21) {
+                    format!("{:e}", v)
+                } else {
+                    format!("{}", v)
+                }
+            }
+        }
+    }};
+}
+
+impl FloatFormat {
+    /// The format of the MySQL text protocol and the pretty-printed tables.
+    pub fn text() -> Self {
+        FloatFormat {
+            precision: None,
+            nan: Cow::Borrowed("NaN"),
+            inf: Cow::Borrowed("inf"),
+            neg_inf: Cow::Borrowed("-inf"),
+        }
+    }
+
+    /// The format of CSV and TSV.
+    pub fn csv() -> Self {
+        FloatFormat {
+            precision: None,
+            nan: Cow::Borrowed("NaN"),
+            inf: Cow::Borrowed("Infinity"),
+            neg_inf: Cow::Borrowed("-Infinity"),
+        }
+    }
+
+    /// The format of JSON and NDJSON: there is no NaN or infinity in JSON, they are rendered as `null`.
+    pub fn json() -> Self {
+        FloatFormat {
+            precision: None,
+            nan: Cow::Borrowed("null"),
+            inf: Cow::Borrowed("null"),
+            neg_inf: Cow::Borrowed("null"),
+        }
+    }
+
+    /// Render with a fixed number of digits after the decimal point, or the shortest round-trip representation if `None`.
+    pub fn with_precision(mut self, precision: Option<usize>) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Override the tokens of NaN, infinity and negative infinity.
+    pub fn with_special_values(
+        mut self,
+        nan: impl Into<String>,
+        inf: impl Into<String>,
+        neg_inf: impl Into<String>,
+    ) -> Self {
+        self.nan = Cow::Owned(nan.into());
+        self.inf = Cow::Owned(inf.into());
+        self.neg_inf = Cow::Owned(neg_inf.into());
+        self
+    }
+
+    pub fn format_f32(&self, v: f32) -> String {
+        format_float!(self, v)
+    }
+
+    pub fn format_f64(&self, v: f64) -> String {
+        format_float!(self, v)
+    }
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        Self::text()
+    }
+}
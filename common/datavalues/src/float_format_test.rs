@@ -0,0 +1,117 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pretty_assertions::assert_eq;
+
+use crate::prelude::*;
+
+#[test]
+fn test_float_format_shortest() {
+    let f = FloatFormat::default();
+
+    let cases: Vec<(f64, &str)> = vec![
+        (0.1, "0.1"),
+        (1.0, "1"),
+        (1.0 / 3.0, "0.3333333333333333"),
+        (-2.5, "-2.5"),
+        (-0.0, "0"),
+        (0.0, "0"),
+        (1e20, "100000000000000000000"),
+        (1e21, "1e21"),
+        (-1.5e300, "-1.5e300"),
+        (f64::MAX, "1.7976931348623157e308"),
+        (1e-7, "0.0000001"),
+        (1.5e-8, "1.5e-8"),
+        (f64::MIN_POSITIVE, "2.2250738585072014e-308"),
+        // denormals
+        (5e-324, "5e-324"),
+        (-2.5e-320, "-2.5e-320"),
+    ];
+    for (v, want) in cases {
+        assert_eq!(want, f.format_f64(v), "format {:e}", v);
+    }
+
+    // f32 is rendered with the shortest representation of f32, not of the widened f64.
+    assert_eq!("0.1", f.format_f32(0.1));
+    assert_eq!("-0.3", f.format_f32(-0.3));
+    assert_eq!("0", f.format_f32(-0.0));
+    assert_eq!("3.4028235e38", f.format_f32(f32::MAX));
+    assert_eq!("1e-45", f.format_f32(1e-45));
+}
+
+#[test]
+fn test_float_format_precision() {
+    let f = FloatFormat::default().with_precision(Some(3));
+
+    assert_eq!("0.100", f.format_f64(0.1));
+    assert_eq!("0.333", f.format_f64(1.0 / 3.0));
+    assert_eq!("2.000", f.format_f64(2.0));
+    assert_eq!("0.000", f.format_f64(-0.0));
+    assert_eq!(
+        "0.000",
+        f.format_f64(-0.0001),
+        "no negative zero after rounding"
+    );
+    assert_eq!("-0.001", f.format_f64(-0.0006));
+    assert_eq!("0.000", f.format_f64(5e-324));
+    assert_eq!("100000000000000000000.000", f.format_f64(1e20));
+    assert_eq!("0.100", f.format_f32(0.1));
+    assert_eq!("NaN", f.format_f64(f64::NAN));
+
+    let f = FloatFormat::default().with_precision(Some(0));
+    assert_eq!("2", f.format_f64(1.5));
+    assert_eq!("0", f.format_f64(-0.4));
+}
+
+#[test]
+fn test_float_format_special_values() {
+    let neg_nan = -f64::NAN;
+    assert!(neg_nan.is_sign_negative());
+
+    let cases: Vec<(FloatFormat, [&str; 3])> = vec![
+        (FloatFormat::text(), ["NaN", "inf", "-inf"]),
+        (FloatFormat::csv(), ["NaN", "Infinity", "-Infinity"]),
+        (FloatFormat::json(), ["null", "null", "null"]),
+        (
+            FloatFormat::csv().with_special_values("", "+inf", "-inf"),
+            ["", "+inf", "-inf"],
+        ),
+    ];
+
+    for (f, [nan, inf, neg_inf]) in cases {
+        assert_eq!(nan, f.format_f64(f64::NAN));
+        assert_eq!(nan, f.format_f64(neg_nan), "the sign of NaN is ignored");
+        assert_eq!(inf, f.format_f64(f64::INFINITY));
+        assert_eq!(neg_inf, f.format_f64(f64::NEG_INFINITY));
+        assert_eq!(nan, f.format_f32(f32::NAN));
+        assert_eq!(inf, f.format_f32(f32::INFINITY));
+        assert_eq!(neg_inf, f.format_f32(f32::NEG_INFINITY));
+
+        // The other values are rendered the same in every format.
+        assert_eq!("0", f.format_f64(-0.0));
+        assert_eq!("1e300", f.format_f64(1e300));
+    }
+}
+
+#[test]
+fn test_float_format_data_value_display() {
+    assert_eq!("NaN", format!("{}", DataValue::Float64(Some(f64::NAN))));
+    assert_eq!(
+        "-inf",
+        format!("{}", DataValue::Float32(Some(f32::NEG_INFINITY)))
+    );
+    assert_eq!("0", format!("{}", DataValue::Float64(Some(-0.0))));
+    assert_eq!("1e300", format!("{}", DataValue::Float64(Some(1e300))));
+    assert_eq!("NULL", format!("{}", DataValue::Float64(None)));
+}
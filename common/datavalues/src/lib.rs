@@ -21,6 +21,8 @@ mod macros;
 
 #[cfg(test)]
 mod data_array_filter_test;
+#[cfg(test)]
+mod float_format_test;
 
 #[allow(dead_code)]
 mod bit_util;
@@ -32,6 +34,7 @@ mod data_schema;
 mod data_value;
 mod data_value_operator;
 mod data_value_ops;
+mod float_format;
 #[allow(dead_code)]
 mod utils;
 
@@ -57,4 +60,5 @@ pub use data_value::DFTryFrom;
 pub use data_value::DataValue;
 pub use data_value::DataValueRef;
 pub use data_value_operator::*;
+pub use float_format::FloatFormat;
 pub use types::*;
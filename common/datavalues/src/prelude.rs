@@ -48,6 +48,7 @@ pub use crate::DataValueComparisonOperator;
 pub use crate::DataValueComparisonOperator::*;
 pub use crate::DataValueLogicOperator;
 pub use crate::DataValueLogicOperator::*;
+pub use crate::FloatFormat;
 
 pub type AlignedVec<T> = common_arrow::arrow::buffer::MutableBuffer<T>;
 pub type LargeBinaryArray = common_arrow::arrow::array::BinaryArray<i64>;
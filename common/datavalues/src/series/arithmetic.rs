@@ -23,6 +23,7 @@ use std::ops::Sub;
 
 use common_exception::ErrorCode;
 use common_exception::Result;
+use num::Zero;
 
 use crate::prelude::*;
 use crate::DataValueArithmeticOperator;
@@ -58,8 +59,7 @@ impl Div for &Series {
     type Output = Result<Series>;
 
     fn div(self, rhs: Self) -> Self::Output {
-        let (lhs, rhs) = coerce_lhs_rhs(&DataValueArithmeticOperator::Div, self, rhs)?;
-        lhs.divide(&rhs)
+        divide(self, rhs, false)
     }
 }
 
@@ -67,22 +67,19 @@ impl Rem for &Series {
     type Output = Result<Series>;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        // apply rem with the largest types
-        let dtype = numerical_arithmetic_coercion(
-            &DataValueArithmeticOperator::Modulo,
-            self.data_type(),
-            rhs.data_type(),
-        )?;
+        remainder(self, rhs, false)
+    }
+}
 
-        let (lhs, rhs) = coerce_lhs_rhs_no_op(self, rhs)?;
-        let result = lhs.remainder(&rhs, &dtype)?;
+impl Series {
+    /// The division, with the integer division by zero as a DivisionByZero error rather than null.
+    pub fn strict_div(&self, rhs: &Series) -> Result<Series> {
+        divide(self, rhs, true)
+    }
 
-        // then cast back to the lowest types
-        if result.data_type() != &dtype {
-            result.cast_with_type(&dtype)
-        } else {
-            Ok(result)
-        }
+    /// The remainder, with the integer modulo by zero as a DivisionByZero error rather than null.
+    pub fn strict_rem(&self, rhs: &Series) -> Result<Series> {
+        remainder(self, rhs, true)
     }
 }
 
@@ -122,7 +119,7 @@ pub trait NumOpsDispatch: Debug {
         )))
     }
 
-    fn remainder(&self, rhs: &Series, _dtype: &DataType) -> Result<Series> {
+    fn remainder(&self, rhs: &Series, _dtype: &DataType, _strict: bool) -> Result<Series> {
         Err(ErrorCode::BadDataValueType(format!(
             "remainder operation not supported for {:?} and {:?}",
             self, rhs
@@ -173,9 +170,9 @@ where
         let out = (self / rhs)?;
         Ok(out.into_series())
     }
-    fn remainder(&self, rhs: &Series, dtype: &DataType) -> Result<Series> {
+    fn remainder(&self, rhs: &Series, dtype: &DataType, strict: bool) -> Result<Series> {
         let rhs = unsafe { self.unpack(rhs)? };
-        self.rem(rhs, dtype)
+        self.rem(rhs, dtype, strict)
     }
 
     fn negative(&self) -> Result<Series> {
@@ -196,6 +193,64 @@ impl NumOpsDispatch for DFListArray {}
 impl NumOpsDispatch for DFNullArray {}
 impl NumOpsDispatch for DFStructArray {}
 
+fn divide(lhs: &Series, rhs: &Series, strict: bool) -> Result<Series> {
+    let integers = is_integer(lhs.data_type()) && is_integer(rhs.data_type());
+    let (lhs, rhs) = coerce_lhs_rhs(&DataValueArithmeticOperator::Div, lhs, rhs)?;
+    let result = lhs.divide(&rhs)?;
+
+    // the integer division by zero is null rather than inf or NaN
+    match integers {
+        true => null_zero_divisors(&lhs, &result, &rhs, strict),
+        false => Ok(result),
+    }
+}
+
+fn remainder(lhs: &Series, rhs: &Series, strict: bool) -> Result<Series> {
+    // apply rem with the largest types
+    let dtype = numerical_arithmetic_coercion(
+        &DataValueArithmeticOperator::Modulo,
+        lhs.data_type(),
+        rhs.data_type(),
+    )?;
+
+    let (lhs, rhs) = coerce_lhs_rhs_no_op(lhs, rhs)?;
+    let result = lhs.remainder(&rhs, &dtype, strict)?;
+
+    // then cast back to the lowest types
+    if result.data_type() != &dtype {
+        result.cast_with_type(&dtype)
+    } else {
+        Ok(result)
+    }
+}
+
+fn null_zero_divisors(
+    dividend: &Series,
+    quotient: &Series,
+    divisor: &Series,
+    strict: bool,
+) -> Result<Series> {
+    let divisor = divisor.f64()?;
+    if !divisor.iter().any(|d| matches!(d, Some(d) if d.is_zero())) {
+        return Ok(quotient.clone());
+    }
+
+    let dividend = dividend.f64()?;
+    let quotient = quotient.f64()?;
+    // the constant columns have only one row
+    let row = |len: usize, i: usize| if len == 1 { 0 } else { i };
+    let array: DFFloat64Array = (0..quotient.len())
+        .map(|i| match divisor.get(row(divisor.len(), i)) {
+            Some(d) if d.is_zero() => match dividend.get(row(dividend.len(), i)) {
+                Some(l) if strict => Err(ErrorCode::DivisionByZero(format!("{} / 0", l))),
+                _ => Ok(None),
+            },
+            _ => Ok(quotient.get(i)),
+        })
+        .collect::<Result<_>>()?;
+    Ok(array.into_series())
+}
+
 fn coerce_lhs_rhs(
     op: &DataValueArithmeticOperator,
     lhs: &Series,
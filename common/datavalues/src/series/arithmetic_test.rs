@@ -304,3 +304,200 @@ fn test_arithmetic_series() {
         }
     }
 }
+
+#[test]
+fn test_arithmetic_series_division_by_zero_and_overflow() {
+    struct Test {
+        name: &'static str,
+        lhs: Series,
+        rhs: Series,
+        op: DataValueArithmeticOperator,
+        expect: std::result::Result<Vec<DataValue>, &'static str>,
+    }
+
+    let tests = vec![
+        Test {
+            name: "div-integer-zero-column",
+            lhs: Series::new(vec![4i64, 3]),
+            rhs: Series::new(vec![0i64, 1]),
+            op: DataValueArithmeticOperator::Div,
+            expect: Ok(vec![
+                DataValue::Float64(None),
+                DataValue::Float64(Some(3.0)),
+            ]),
+        },
+        Test {
+            name: "div-integer-zero-constant-rhs",
+            lhs: Series::new(vec![4u8, 3]),
+            rhs: Series::new(vec![0u8]),
+            op: DataValueArithmeticOperator::Div,
+            expect: Ok(vec![DataValue::Float64(None), DataValue::Float64(None)]),
+        },
+        Test {
+            name: "div-integer-zero-constant-lhs",
+            lhs: Series::new(vec![4i32]),
+            rhs: Series::new(vec![0i32, 2]),
+            op: DataValueArithmeticOperator::Div,
+            expect: Ok(vec![
+                DataValue::Float64(None),
+                DataValue::Float64(Some(2.0)),
+            ]),
+        },
+        Test {
+            name: "div-float-zero",
+            lhs: Series::new(vec![1.0f64, -1.0]),
+            rhs: Series::new(vec![0.0f64]),
+            op: DataValueArithmeticOperator::Div,
+            expect: Ok(vec![
+                DataValue::Float64(Some(f64::INFINITY)),
+                DataValue::Float64(Some(f64::NEG_INFINITY)),
+            ]),
+        },
+        Test {
+            name: "rem-integer-zero-column",
+            lhs: Series::new(vec![4i32, 3]),
+            rhs: Series::new(vec![0i32, 2]),
+            op: DataValueArithmeticOperator::Modulo,
+            expect: Ok(vec![DataValue::Int64(None), DataValue::Int64(Some(1))]),
+        },
+        Test {
+            name: "rem-integer-zero-constant-lhs",
+            lhs: Series::new(vec![4i32]),
+            rhs: Series::new(vec![3i32, 0]),
+            op: DataValueArithmeticOperator::Modulo,
+            expect: Ok(vec![DataValue::Int64(Some(1)), DataValue::Int64(None)]),
+        },
+        Test {
+            name: "rem-integer-zero-constant-u8",
+            lhs: Series::new(vec![5u64, 6]),
+            rhs: Series::new(vec![0u8]),
+            op: DataValueArithmeticOperator::Modulo,
+            expect: Ok(vec![DataValue::UInt8(None), DataValue::UInt8(None)]),
+        },
+        Test {
+            name: "rem-integer-min-by-minus-one",
+            lhs: Series::new(vec![i64::MIN]),
+            rhs: Series::new(vec![-1i64]),
+            op: DataValueArithmeticOperator::Modulo,
+            expect: Ok(vec![DataValue::Int64(Some(0))]),
+        },
+        Test {
+            name: "plus-widening-no-overflow",
+            lhs: Series::new(vec![u8::MAX]),
+            rhs: Series::new(vec![u8::MAX]),
+            op: DataValueArithmeticOperator::Plus,
+            expect: Ok(vec![DataValue::UInt16(Some(510))]),
+        },
+        Test {
+            name: "plus-overflow",
+            lhs: Series::new(vec![1i64, i64::MAX]),
+            rhs: Series::new(vec![1i64]),
+            op: DataValueArithmeticOperator::Plus,
+            expect: Err("Code: 60, displayText = 9223372036854775807 + 1 overflows Int64."),
+        },
+        Test {
+            name: "minus-overflow",
+            lhs: Series::new(vec![i64::MIN]),
+            rhs: Series::new(vec![0i64, 1]),
+            op: DataValueArithmeticOperator::Minus,
+            expect: Err("Code: 60, displayText = -9223372036854775808 - 1 overflows Int64."),
+        },
+        Test {
+            name: "mul-overflow",
+            lhs: Series::new(vec![u64::MAX, 1]),
+            rhs: Series::new(vec![2u64, 2]),
+            op: DataValueArithmeticOperator::Mul,
+            expect: Err("Code: 60, displayText = 18446744073709551615 * 2 overflows UInt64."),
+        },
+    ];
+
+    for t in tests {
+        let result = match t.op {
+            DataValueArithmeticOperator::Plus => &t.lhs + &t.rhs,
+            DataValueArithmeticOperator::Minus => &t.lhs - &t.rhs,
+            DataValueArithmeticOperator::Mul => &t.lhs * &t.rhs,
+            DataValueArithmeticOperator::Div => &t.lhs / &t.rhs,
+            DataValueArithmeticOperator::Modulo => &t.lhs % &t.rhs,
+        };
+
+        match (result, t.expect) {
+            (Ok(v), Ok(expect)) => {
+                let values = (0..v.len()).map(|i| v.try_get(i).unwrap());
+                assert_eq!(values.collect::<Vec<_>>(), expect, "{}", t.name);
+            }
+            (Err(e), Err(expect)) => assert_eq!(e.to_string(), expect, "{}", t.name),
+            (Ok(v), Err(_)) => panic!("{}: expect an error, but got {:?}", t.name, v),
+            (Err(e), Ok(_)) => panic!("{}: unexpected error {}", t.name, e),
+        }
+    }
+}
+
+#[test]
+fn test_arithmetic_series_neg_overflow() {
+    let s = Series::new(vec![1i64, i64::MIN]);
+    let e = (-&s).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "Code: 60, displayText = -(-9223372036854775808) overflows Int64."
+    );
+}
+
+#[test]
+fn test_arithmetic_series_division_by_zero_integer_widths() -> Result<()> {
+    let args = vec![
+        (Series::new(vec![6u8, 7]), Series::new(vec![0u8, 2])),
+        (Series::new(vec![6u16, 7]), Series::new(vec![0u16, 2])),
+        (Series::new(vec![6u32, 7]), Series::new(vec![0u32, 2])),
+        (Series::new(vec![6u64, 7]), Series::new(vec![0u64, 2])),
+        (Series::new(vec![6i8, 7]), Series::new(vec![0i8, 2])),
+        (Series::new(vec![6i16, 7]), Series::new(vec![0i16, 2])),
+        (Series::new(vec![6i32, 7]), Series::new(vec![0i32, 2])),
+        (Series::new(vec![6i64, 7]), Series::new(vec![0i64, 2])),
+    ];
+
+    for (lhs, rhs) in args {
+        let name = format!("{:?}", lhs.data_type());
+
+        // columnar divisor
+        for result in [(&lhs / &rhs)?, (&lhs % &rhs)?] {
+            assert_eq!(result.len(), 2, "{}", name);
+            assert!(result.is_null(0), "{}", name);
+            assert!(!result.is_null(1), "{}", name);
+        }
+
+        // constant divisor and constant dividend
+        let zero = rhs.slice(0, 1);
+        assert_eq!((&lhs / &zero)?.null_count(), 2, "{}", name);
+        assert_eq!((&lhs % &zero)?.null_count(), 2, "{}", name);
+        assert_eq!((&zero / &rhs)?.null_count(), 1, "{}", name);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_arithmetic_series_strict_division_by_zero() -> Result<()> {
+    let lhs = Series::new(vec![Some(6i32), None, Some(7)]);
+    let rhs = Series::new(vec![2i32, 0, 0]);
+
+    let e = lhs.strict_div(&rhs).unwrap_err();
+    assert_eq!(e.code(), 61);
+    assert_eq!(e.message(), "7 / 0");
+    let e = lhs.strict_rem(&rhs).unwrap_err();
+    assert_eq!(e.code(), 61);
+    assert_eq!(e.message(), "7 % 0");
+
+    // the null dividends are null
+    let rhs = Series::new(vec![2i32, 0, 1]);
+    assert_eq!(lhs.strict_div(&rhs)?.null_count(), 1);
+    assert_eq!(lhs.strict_rem(&rhs)?.null_count(), 1);
+
+    // constant divisor
+    let zero = Series::new(vec![0u8]);
+    assert_eq!(lhs.strict_rem(&zero).unwrap_err().code(), 61);
+    assert_eq!(lhs.strict_div(&zero).unwrap_err().code(), 61);
+
+    // the floats keep the IEEE semantics
+    let lhs = Series::new(vec![6.0f64, 7.0]);
+    assert_eq!(lhs.strict_div(&zero)?.null_count(), 0);
+    Ok(())
+}
@@ -71,7 +71,7 @@ pub trait SeriesTrait: Send + Sync + fmt::Debug {
     fn add_to(&self, rhs: &Series) -> Result<Series>;
     fn multiply(&self, rhs: &Series) -> Result<Series>;
     fn divide(&self, rhs: &Series) -> Result<Series>;
-    fn remainder(&self, rhs: &Series, dtype: &DataType) -> Result<Series>;
+    fn remainder(&self, rhs: &Series, dtype: &DataType, strict: bool) -> Result<Series>;
     fn negative(&self) -> Result<Series>;
 
     fn sum(&self) -> Result<DataValue>;
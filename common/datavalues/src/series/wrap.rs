@@ -141,8 +141,8 @@ macro_rules! impl_dyn_array {
                 NumOpsDispatch::divide(&self.0, rhs)
             }
 
-            fn remainder(&self, rhs: &Series, dtype: &DataType) -> Result<Series> {
-                NumOpsDispatch::remainder(&self.0, rhs, dtype)
+            fn remainder(&self, rhs: &Series, dtype: &DataType, strict: bool) -> Result<Series> {
+                NumOpsDispatch::remainder(&self.0, rhs, dtype, strict)
             }
 
             fn negative(&self) -> Result<Series> {
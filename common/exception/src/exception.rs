@@ -14,10 +14,12 @@
 
 #![allow(non_snake_case)]
 
+use std::any::Any;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::net::AddrParseError;
+use std::ops::RangeInclusive;
 use std::string::FromUtf8Error;
 use std::sync::Arc;
 
@@ -26,8 +28,9 @@ use thiserror::Error;
 use tonic::Code;
 use tonic::Status;
 
-pub static ABORT_SESSION: u16 = 42;
-pub static ABORT_QUERY: u16 = 43;
+pub static ABORT_SESSION: u16 = codes::AbortedSession;
+pub static ABORT_QUERY: u16 = codes::AbortedQuery;
+pub static SESSION_EXPIRED: u16 = codes::SessionExpired;
 
 #[derive(Clone)]
 pub enum ErrorCodeBacktrace {
@@ -96,183 +99,316 @@ macro_rules! as_item {
 }
 
 macro_rules! build_exceptions {
-    ($($body:tt($code:expr)),*$(,)*) => {
+    ($($subsystem:ident { $($body:ident($code:expr, $retryable:expr, $description:expr)),*$(,)* })*) => {
         as_item! {
             impl ErrorCode {
-                $(
+                $($(
                 pub fn $body(display_text: impl Into<String>) -> ErrorCode {
                     ErrorCode {
-                        code:$code,
+                        code: codes::$body,
                         display_text: display_text.into(),
                         cause: None,
                         backtrace: Some(ErrorCodeBacktrace::Origin(Arc::new(Backtrace::new()))),
                     }
-                })*
+                })*)*
             }
         }
+
+        /// Named error codes, to compare with `ErrorCode::code()` instead of numeric literals.
+        #[allow(non_upper_case_globals)]
+        pub mod codes {
+            $($(pub const $body: u16 = $code;)*)*
+        }
+
+        /// Every error code with its subsystem, retryability and description.
+        pub static ERROR_CODES: &[ErrorCodeInfo] = &[
+            $($(ErrorCodeInfo {
+                code: codes::$body,
+                name: stringify!($body),
+                subsystem: ErrorSubsystem::$subsystem,
+                retryable: $retryable,
+                description: $description,
+            },)*)*
+        ];
     }
 }
 
+/// The subsystem an error code belongs to, new codes must be taken from the subsystem range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorSubsystem {
+    Query,
+    Internal,
+    Store,
+    User,
+    Meta,
+    Storage,
+    Kv,
+    Dal,
+    General,
+    Datasource,
+    Session,
+    Network,
+}
+
+impl ErrorSubsystem {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorSubsystem::Query => "query",
+            ErrorSubsystem::Internal => "internal",
+            ErrorSubsystem::Store => "store",
+            ErrorSubsystem::User => "user",
+            ErrorSubsystem::Meta => "meta",
+            ErrorSubsystem::Storage => "storage",
+            ErrorSubsystem::Kv => "kv",
+            ErrorSubsystem::Dal => "dal",
+            ErrorSubsystem::General => "general",
+            ErrorSubsystem::Datasource => "datasource",
+            ErrorSubsystem::Session => "session",
+            ErrorSubsystem::Network => "network",
+        }
+    }
+
+    /// The codes below 1000 predate the ranges, the query range keeps them as they are,
+    /// including the session and network ones.
+    pub fn range(&self) -> RangeInclusive<u16> {
+        match self {
+            ErrorSubsystem::Query => 0..=999,
+            ErrorSubsystem::Internal => 1000..=1999,
+            ErrorSubsystem::Store => 2000..=2999,
+            ErrorSubsystem::User => 3000..=3999,
+            ErrorSubsystem::Meta => 4000..=4999,
+            ErrorSubsystem::Storage => 5000..=5999,
+            ErrorSubsystem::Kv => 6000..=6999,
+            ErrorSubsystem::Dal => 7000..=7099,
+            ErrorSubsystem::General => 7100..=7999,
+            ErrorSubsystem::Datasource => 8000..=8999,
+            ErrorSubsystem::Session => 9000..=9499,
+            ErrorSubsystem::Network => 9500..=9999,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorCodeInfo {
+    pub code: u16,
+    pub name: &'static str,
+    pub subsystem: ErrorSubsystem,
+    /// The same request may succeed if it is sent again later.
+    pub retryable: bool,
+    pub description: &'static str,
+}
+
 build_exceptions! {
-    Ok(0),
-    UnknownTypeOfQuery(1),
-    UnImplement(2),
-    UnknownDatabase(3),
-    UnknownSetting(4),
-    SyntaxException(5),
-    BadArguments(6),
-    IllegalDataType(7),
-    UnknownFunction(8),
-    IllegalFunctionState(9),
-    BadDataValueType(10),
-    UnknownPlan(11),
-    IllegalPipelineState(12),
-    BadTransformType(13),
-    IllegalTransformConnectionState(14),
-    LogicalError(15),
-    EmptyData(16),
-    DataStructMissMatch(17),
-    BadDataArrayLength(18),
-    UnknownContextID(19),
-    UnknownVariable(20),
-    UnknownTableFunction(21),
-    BadOption(22),
-    CannotReadFile(23),
-    ParquetError(24),
-    UnknownTable(25),
-    IllegalAggregateExp(26),
-    UnknownAggregateFunction(27),
-    NumberArgumentsNotMatch(28),
-    NotFoundStream(29),
-    EmptyDataFromServer(30),
-    NotFoundLocalNode(31),
-    PlanScheduleError(32),
-    BadPlanInputs(33),
-    DuplicateClusterNode(34),
-    NotFoundClusterNode(35),
-    BadAddressFormat(36),
-    DnsParseError(37),
-    CannotConnectNode(38),
-    DuplicateGetStream(39),
-    Timeout(40),
-    TooManyUserConnections(41),
-    AbortedSession(ABORT_SESSION),
-    AbortedQuery(ABORT_QUERY),
-    NotFoundSession(44),
-    CannotListenerPort(45),
-    BadBytes(46),
-    InitPrometheusFailure(47),
-    ScalarSubqueryBadRows(48),
-    Overflow(49),
-    InvalidMetaBinaryFormat(50),
-    AuthenticateFailure(51),
-    TLSConfigurationFailure(52),
-    UnknownSession(53),
-
-    // uncategorized
-    UnexpectedResponseType(600),
-
-    UnknownException(1000),
-    TokioError(1001),
-}
-
-// Store errors
-build_exceptions! {
+    Query {
+        Ok(0, false, "Not an error"),
+        UnknownTypeOfQuery(1, false, "The statement type is not supported"),
+        UnImplement(2, false, "The feature is not implemented yet"),
+        UnknownDatabase(3, false, "The database does not exist"),
+        UnknownSetting(4, false, "The setting does not exist"),
+        SyntaxException(5, false, "The SQL can not be parsed"),
+        BadArguments(6, false, "Bad arguments"),
+        IllegalDataType(7, false, "The data type is not supported in this context"),
+        UnknownFunction(8, false, "The function does not exist"),
+        IllegalFunctionState(9, false, "The function is in an illegal state"),
+        BadDataValueType(10, false, "The data value has an unexpected type"),
+        UnknownPlan(11, false, "The plan is not supported"),
+        IllegalPipelineState(12, false, "The pipeline is in an illegal state"),
+        BadTransformType(13, false, "The transform can not handle the plan"),
+        IllegalTransformConnectionState(14, false, "The transforms are connected in an illegal way"),
+        LogicalError(15, false, "Internal logical error"),
+        EmptyData(16, false, "Unexpected empty data"),
+        DataStructMissMatch(17, false, "The data structures do not match"),
+        BadDataArrayLength(18, false, "The arrays have unexpected lengths"),
+        UnknownContextID(19, false, "The query context does not exist"),
+        UnknownVariable(20, false, "The variable does not exist"),
+        UnknownTableFunction(21, false, "The table function does not exist"),
+        BadOption(22, false, "Bad option"),
+        CannotReadFile(23, false, "The file can not be read"),
+        ParquetError(24, false, "Parquet encoding or decoding error"),
+        UnknownTable(25, false, "The table does not exist"),
+        IllegalAggregateExp(26, false, "Illegal aggregate expression"),
+        UnknownAggregateFunction(27, false, "The aggregate function does not exist"),
+        NumberArgumentsNotMatch(28, false, "Wrong number of function arguments"),
+        NotFoundStream(29, false, "The flight stream does not exist"),
+        EmptyDataFromServer(30, false, "The server returned no data"),
+        NotFoundLocalNode(31, false, "The local node is not in the cluster"),
+        PlanScheduleError(32, false, "The plan can not be scheduled on the cluster"),
+        BadPlanInputs(33, false, "The plan has bad inputs"),
+        DuplicateClusterNode(34, false, "The cluster node already exists"),
+        NotFoundClusterNode(35, false, "The cluster node does not exist"),
+        BadAddressFormat(36, false, "The address can not be parsed"),
+        DnsParseError(37, true, "The host name can not be resolved"),
+        CannotConnectNode(38, true, "The node can not be connected"),
+        DuplicateGetStream(39, false, "The flight stream is already taken"),
+        Timeout(40, true, "The operation timed out"),
+        TooManyUserConnections(41, true, "Too many connections of the user"),
+        AbortedSession(42, false, "The session is aborted"),
+        AbortedQuery(43, false, "The query is aborted"),
+        NotFoundSession(44, false, "The session does not exist"),
+        CannotListenerPort(45, false, "The port can not be listened on"),
+        BadBytes(46, false, "The bytes can not be decoded"),
+        InitPrometheusFailure(47, false, "The prometheus exporter can not be initialized"),
+        ScalarSubqueryBadRows(48, false, "The scalar subquery returned more than one row"),
+        Overflow(49, false, "Numeric overflow"),
+        InvalidMetaBinaryFormat(50, false, "The meta binary format is invalid"),
+        AuthenticateFailure(51, false, "The user can not be authenticated"),
+        TLSConfigurationFailure(52, false, "The TLS configuration is invalid"),
+        UnknownSession(53, false, "The session does not exist"),
+        PermissionDenied(54, false, "The operation is not permitted"),
+        TooManyWarnings(55, false, "The statement has more warnings than kept, the others are suppressed"),
+        QueryTimeout(56, false, "The query runs longer than its max_execution_time"),
+        DeadlineExceeded(57, false, "The deadline of the request is exceeded"),
+        BrokenExchangeOrder(58, false, "The blocks of an order-preserving exchange can not be put in order"),
+        UnknownColumn(59, false, "The column does not exist"),
+        ArithmeticOverflow(60, false, "The integer arithmetic overflows its result type"),
+        DivisionByZero(61, false, "Division or modulo by zero with strict_arithmetic"),
+        StageExpired(62, false, "The flight stage expired before its streams were fetched"),
+        SessionExpired(63, false, "The session expired after it was idle for too long"),
+
+        // uncategorized
+        UnexpectedResponseType(600, false, "The response has an unexpected type"),
+    }
+
+    Internal {
+        UnknownException(1000, false, "Unknown error"),
+        TokioError(1001, false, "Tokio runtime error"),
+        // Errors from std and third-party libraries, see `ErrorCode::from_std_error`.
+        ExternalError(1002, false, "Error from a library"),
+        // A panic caught by the servers, see `ErrorCode::from_panic`.
+        PanicError(1003, false, "The query panicked"),
+    }
 
-    FileMetaNotFound(2001),
-    FileDamaged(2002),
+    Store {
+        FileMetaNotFound(2001, false, "The file meta does not exist"),
+        FileDamaged(2002, false, "The file is damaged"),
 
-    // store node errors
+        // store node errors
 
-    UnknownNode(2101),
+        UnknownNode(2101, false, "The store node does not exist"),
 
-    // meta service errors
+        // meta service errors
 
-    // meta service does not work.
-    MetaServiceError(2201),
-    // meta service is shut down.
-    MetaServiceShutdown(2202),
-    // meta service is unavailable for now.
-    MetaServiceUnavailable(2203),
+        // meta service does not work.
+        MetaServiceError(2201, false, "The meta service does not work"),
+        // meta service is shut down.
+        MetaServiceShutdown(2202, false, "The meta service is shut down"),
+        // meta service is unavailable for now.
+        MetaServiceUnavailable(2203, true, "The meta service is unavailable for now"),
 
-    // config errors
+        // config errors
 
-    InvalidConfig(2301),
+        InvalidConfig(2301, false, "The config is invalid"),
 
-    // meta store errors
+        // meta store errors
 
-    MetaStoreDamaged(2401),
-    MetaStoreAlreadyExists(2402),
-    MetaStoreNotFound(2403),
+        MetaStoreDamaged(2401, false, "The meta store is damaged"),
+        MetaStoreAlreadyExists(2402, false, "The meta store already exists"),
+        MetaStoreNotFound(2403, false, "The meta store does not exist"),
 
-    ConcurrentSnapshotInstall(2404),
-    IllegalSnapshot(2405),
+        ConcurrentSnapshotInstall(2404, true, "Another snapshot is being installed"),
+        IllegalSnapshot(2405, false, "The snapshot is illegal"),
+        KVWatchLagged(2406, true, "The kv watcher fell behind the changes and is closed"),
 
-    // MetaSrv server error
+        // MetaSrv server error
 
-    MetaSrvError(2501),
+        MetaSrvError(2501, false, "The metasrv server failed"),
+        ReplyTooLarge(2502, false, "The reply is too large, the request should be paginated"),
 
-    // FS error
+        // FS error
 
-    IllegalFileName(2601),
+        IllegalFileName(2601, false, "The file name is illegal"),
 
-    // Store server error
+        // Store server error
 
-    DatabendStoreError(2701),
+        DatabendStoreError(2701, false, "The store server failed"),
+        StaleRead(2702, true, "The store has not caught up with the data version to read"),
+        IllegalBatch(2703, false, "The batch has an action that is not allowed in a batch"),
+    }
 
     // TODO
     // We may need to separate front-end errors from API errors (and system errors?)
     // That may depend which components are using these error codes, and for what purposes,
     // let's figure it out latter.
 
-    // user-api error codes
-    UnknownUser(3000),
-    UserAlreadyExists(3001),
-    IllegalUserInfoFormat(3002),
-
-    // meta-api error codes
-    DatabaseAlreadyExists(4001),
-    TableAlreadyExists(4003),
-    IllegalMetaOperationArgument(4004),
-    IllegalSchema(4005),
-    IllegalMetaState(4005),
-    MetaNodeInternalError(4006),
-    TruncateTableFailedError(4007),
-
-    // namespace error.
-    NamespaceUnknownNode(4008),
-    NamespaceNodeAlreadyExists(4009),
-    NamespaceIllegalNodeFormat(4010),
+    User {
+        // user-api error codes
+        UnknownUser(3000, false, "The user does not exist"),
+        UserAlreadyExists(3001, false, "The user already exists"),
+        IllegalUserInfoFormat(3002, false, "The user info is illegal"),
+    }
 
-    // storage-api error codes
-    IllegalScanPlan(5000),
-    ReadFileError(5001),
-    BrokenChannel(5002),
+    Meta {
+        // meta-api error codes
+        DatabaseAlreadyExists(4001, false, "The database already exists"),
+        TableAlreadyExists(4003, false, "The table already exists"),
+        IllegalMetaOperationArgument(4004, false, "Illegal argument of a meta operation"),
+        IllegalSchema(4005, false, "The schema is illegal"),
+        MetaNodeInternalError(4006, false, "The meta node failed"),
+        TruncateTableFailedError(4007, false, "The table can not be truncated"),
+
+        // namespace error.
+        NamespaceUnknownNode(4008, false, "The namespace node does not exist"),
+        NamespaceNodeAlreadyExists(4009, false, "The namespace node already exists"),
+        NamespaceIllegalNodeFormat(4010, false, "The namespace node is illegal"),
+
+        // It used to share 4005 with IllegalSchema.
+        IllegalMetaState(4011, false, "The meta state is illegal"),
+        TableVersionMismatched(4012, false, "The table does not match the expected seq"),
+    }
 
-    // kv-api error codes
-    UnknownKey(6000),
+    Storage {
+        // storage-api error codes
+        IllegalScanPlan(5000, false, "The scan plan is illegal"),
+        ReadFileError(5001, false, "The file can not be read"),
+        BrokenChannel(5002, true, "The channel is broken"),
+        QuotaExceeded(5003, false, "The storage quota of the database is exceeded"),
+        CopyTableMismatch(5004, false, "The copied table does not match its source"),
+        ReadOnlyEngine(5005, false, "The table engine does not accept appends"),
+        SchemaMismatch(5006, false, "The appended data does not match the table schema"),
+        ReadOnlyStore(5007, false, "The store is read-only, it does not accept writes"),
+    }
 
+    Kv {
+        // kv-api error codes
+        UnknownKey(6000, false, "The key does not exist"),
+        IllegalJsonPatch(6001, false, "The json patch can not be applied to the value"),
+    }
 
-    // DAL error
-    DALTransportError(7000),
+    Dal {
+        // DAL error
+        DALTransportError(7000, true, "The data access layer transport failed"),
+    }
 
+    General {
+        // A task that already stopped and can not stop twice.
+        AlreadyStarted(7101, false, "The task is already started"),
 
-    // datasource error
-    DuplicatedTableEngineProvider(8000),
-    UnknownDatabaseEngine(8001),
-    UnknownTableEngine(8002),
-    DuplicatedDatabaseEngineProvider(8003),
+        // A task that already started and can not start twice.
+        AlreadyStopped(7102, false, "The task is already stopped"),
+    }
 
+    Datasource {
+        // datasource error
+        DuplicatedTableEngineProvider(8000, false, "The table engine is already registered"),
+        UnknownDatabaseEngine(8001, false, "The database engine does not exist"),
+        UnknownTableEngine(8002, false, "The table engine does not exist"),
+        DuplicatedDatabaseEngineProvider(8003, false, "The database engine is already registered"),
+    }
 }
-// General errors
-build_exceptions! {
 
-    // A task that already stopped and can not stop twice.
-    AlreadyStarted(7101),
+impl ErrorCode {
+    /// The registered info of a code, `None` for unknown codes, e.g. from a newer node.
+    pub fn info_of(code: u16) -> Option<&'static ErrorCodeInfo> {
+        ERROR_CODES.iter().find(|info| info.code == code)
+    }
 
-    // A task that already started and can not start twice.
-    AlreadyStopped(7102),
+    pub fn name(&self) -> &'static str {
+        ErrorCode::info_of(self.code).map_or("Unknown", |info| info.name)
+    }
 
+    pub fn is_retryable(&self) -> bool {
+        ErrorCode::info_of(self.code).map_or(false, |info| info.retryable)
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ErrorCode>;
@@ -334,7 +470,7 @@ impl Debug for OtherErrors {
 impl From<anyhow::Error> for ErrorCode {
     fn from(error: anyhow::Error) -> Self {
         ErrorCode {
-            code: 1002,
+            code: codes::ExternalError,
             display_text: format!("{}, source: {:?}", error, error.source()),
             cause: Some(Box::new(OtherErrors::AnyHow { error })),
             backtrace: Some(ErrorCodeBacktrace::Origin(Arc::new(Backtrace::new()))),
@@ -405,13 +541,25 @@ impl From<prost::EncodeError> for ErrorCode {
 impl ErrorCode {
     pub fn from_std_error<T: std::error::Error>(error: T) -> Self {
         ErrorCode {
-            code: 1002,
+            code: codes::ExternalError,
             display_text: format!("{}", error),
             cause: None,
             backtrace: Some(ErrorCodeBacktrace::Origin(Arc::new(Backtrace::new()))),
         }
     }
 
+    /// The message of the payload of a caught panic, e.g. by `std::panic::catch_unwind`.
+    pub fn from_panic(panic: &(dyn Any + Send)) -> Self {
+        let message = match panic.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match panic.downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => String::from("unknown panic"),
+            },
+        };
+        ErrorCode::PanicError(format!("Panicked: {}", message))
+    }
+
     pub fn create(
         code: u16,
         display_text: String,
@@ -501,6 +649,7 @@ impl From<&Status> for ErrorCode {
                     },
                 }
             }
+            tonic::Code::DeadlineExceeded => ErrorCode::DeadlineExceeded(status.to_string()),
             _ => ErrorCode::UnImplement(status.to_string()),
         }
     }
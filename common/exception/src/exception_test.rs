@@ -108,5 +108,110 @@ fn test_from_and_to_status() -> anyhow::Result<()> {
         assert_eq!("foo", e2.message());
     }
 
+    {
+        // a deadline enforced by tonic itself
+        let status = Status::new(Code::DeadlineExceeded, "Timeout expired");
+        let e2: ErrorCode = status.into();
+        assert_eq!(ErrorCode::DeadlineExceeded("").code(), e2.code());
+    }
+
     Ok(())
 }
+
+#[test]
+fn test_error_code_registry_no_duplicates() {
+    use std::collections::HashMap;
+
+    use crate::exception::ERROR_CODES;
+
+    let mut codes = HashMap::new();
+    let mut names = HashMap::new();
+    for info in ERROR_CODES {
+        if let Some(prev) = codes.insert(info.code, info.name) {
+            panic!("{} and {} share code {}", prev, info.name, info.code);
+        }
+        if let Some(prev) = names.insert(info.name, info.code) {
+            panic!("{} is registered as {} and {}", info.name, prev, info.code);
+        }
+    }
+}
+
+#[test]
+fn test_error_code_registry_ranges() {
+    use crate::exception::ERROR_CODES;
+
+    for info in ERROR_CODES {
+        assert!(
+            info.subsystem.range().contains(&info.code),
+            "{}({}) is out of the {} range {:?}",
+            info.name,
+            info.code,
+            info.subsystem.name(),
+            info.subsystem.range()
+        );
+        assert!(!info.description.is_empty(), "{}", info.name);
+    }
+}
+
+#[test]
+fn test_legacy_error_codes() {
+    use crate::exception::codes;
+    use crate::exception::ErrorCode;
+    use crate::exception::ABORT_QUERY;
+    use crate::exception::ABORT_SESSION;
+
+    // These codes are seen by clients and other nodes, they must never change.
+    let tests: Vec<(u16, ErrorCode)> = vec![
+        (3, ErrorCode::UnknownDatabase("")),
+        (25, ErrorCode::UnknownTable("")),
+        (29, ErrorCode::NotFoundStream("")),
+        (38, ErrorCode::CannotConnectNode("")),
+        (40, ErrorCode::Timeout("")),
+        (42, ErrorCode::AbortedSession("")),
+        (43, ErrorCode::AbortedQuery("")),
+        (1000, ErrorCode::UnknownException("")),
+        (1001, ErrorCode::TokioError("")),
+        (2301, ErrorCode::InvalidConfig("")),
+        (4003, ErrorCode::TableAlreadyExists("")),
+        (4005, ErrorCode::IllegalSchema("")),
+    ];
+
+    for (code, error) in tests {
+        assert_eq!(code, error.code(), "{}", error.name());
+    }
+
+    assert_eq!(42, ABORT_SESSION);
+    assert_eq!(43, ABORT_QUERY);
+    assert_eq!(1002, codes::ExternalError);
+    assert_eq!(
+        codes::ExternalError,
+        ErrorCode::from_std_error(std::fmt::Error {}).code()
+    );
+
+    let panic = std::panic::catch_unwind(|| panic!("crash {}", 1)).unwrap_err();
+    let error = ErrorCode::from_panic(panic.as_ref());
+    assert_eq!(1003, error.code());
+    assert_eq!("Panicked: crash 1", error.message());
+}
+
+#[test]
+fn test_error_code_retryable() {
+    use crate::exception::ErrorCode;
+
+    assert!(ErrorCode::Timeout("").is_retryable());
+    assert!(ErrorCode::CannotConnectNode("").is_retryable());
+    assert!(ErrorCode::MetaServiceUnavailable("").is_retryable());
+    assert!(ErrorCode::BrokenChannel("").is_retryable());
+    assert!(ErrorCode::DALTransportError("").is_retryable());
+
+    assert!(!ErrorCode::UnknownTable("").is_retryable());
+    assert!(!ErrorCode::SyntaxException("").is_retryable());
+    assert!(!ErrorCode::AbortedQuery("").is_retryable());
+
+    assert_eq!("UnknownTable", ErrorCode::UnknownTable("").name());
+    assert_eq!(
+        Some("store"),
+        ErrorCode::info_of(2203).map(|info| info.subsystem.name())
+    );
+    assert!(ErrorCode::info_of(65535).is_none());
+}
@@ -17,9 +17,13 @@ mod exception_test;
 
 pub mod exception;
 
+pub use exception::codes;
 pub use exception::ErrorCode;
+pub use exception::ErrorCodeInfo;
+pub use exception::ErrorSubsystem;
 pub use exception::Result;
 pub use exception::ToErrorCode;
+pub use exception::ERROR_CODES;
 
 pub mod prelude {
 
@@ -78,8 +78,9 @@ where
         Ok(DataType::Float64)
     }
 
+    // avg of an empty or all-null input is NULL
     fn nullable(&self, _input_schema: &DataSchema) -> Result<bool> {
-        Ok(false)
+        Ok(true)
     }
 
     fn init_state(&self, place: StateAddr) {
@@ -111,12 +112,25 @@ where
         _input_rows: usize,
     ) -> Result<()> {
         let array: &DFPrimitiveArray<T> = arrays[0].static_cast();
-
-        array.into_iter().zip(places.iter()).for_each(|(v, place)| {
-            let place = place.next(offset);
-            let state = place.get::<AggregateAvgState<SumT>>();
-            state.add(&v.map(|v| v.as_()), 1);
-        });
+        let values = array.inner().values().as_slice();
+        match array.inner().validity() {
+            Some(validity) if array.null_count() > 0 => values
+                .iter()
+                .zip(validity.into_iter())
+                .zip(places.iter())
+                .for_each(|((v, valid), place)| {
+                    if valid {
+                        let place = place.next(offset);
+                        let state = place.get::<AggregateAvgState<SumT>>();
+                        state.add(&Some(v.as_()), 1);
+                    }
+                }),
+            _ => values.iter().zip(places.iter()).for_each(|(v, place)| {
+                let place = place.next(offset);
+                let state = place.get::<AggregateAvgState<SumT>>();
+                state.add(&Some(v.as_()), 1);
+            }),
+        }
 
         Ok(())
     }
@@ -15,6 +15,7 @@
 use bumpalo::Bump;
 use common_datavalues::prelude::*;
 use common_exception::Result;
+use common_io::prelude::*;
 use pretty_assertions::assert_eq;
 
 use crate::aggregates::*;
@@ -396,3 +397,112 @@ fn test_aggregate_function_on_empty_data() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_aggregate_function_on_nullable_data() -> Result<()> {
+    struct Test {
+        name: &'static str,
+        func_name: &'static str,
+        args: Vec<DataField>,
+        array: Series,
+        expect: DataValue,
+    }
+
+    let all_null = DFInt64Array::new_from_opt_slice(&[None, None, None, None]).into_series();
+    let mixed = DFInt64Array::new_from_opt_slice(&[Some(4), None, Some(1), None]).into_series();
+    let no_null =
+        DFInt64Array::new_from_opt_slice(&[Some(4), Some(3), Some(2), Some(1)]).into_series();
+
+    let arg = DataField::new("a", DataType::Int64, true);
+    let case = |name, func_name, array: &Series, expect| Test {
+        name,
+        func_name,
+        args: vec![arg.clone()],
+        array: array.clone(),
+        expect,
+    };
+
+    let tests = vec![
+        case(
+            "count-all-null",
+            "count",
+            &all_null,
+            DataValue::UInt64(Some(0)),
+        ),
+        case("count-mixed", "count", &mixed, DataValue::UInt64(Some(2))),
+        case(
+            "count-no-null",
+            "count",
+            &no_null,
+            DataValue::UInt64(Some(4)),
+        ),
+        Test {
+            name: "count-star-all-null",
+            func_name: "count",
+            args: vec![],
+            array: all_null.clone(),
+            expect: DataValue::UInt64(Some(4)),
+        },
+        case("sum-all-null", "sum", &all_null, DataValue::Int64(None)),
+        case("sum-mixed", "sum", &mixed, DataValue::Int64(Some(5))),
+        case("sum-no-null", "sum", &no_null, DataValue::Int64(Some(10))),
+        case("avg-all-null", "avg", &all_null, DataValue::Float64(None)),
+        case("avg-mixed", "avg", &mixed, DataValue::Float64(Some(2.5))),
+        case(
+            "avg-no-null",
+            "avg",
+            &no_null,
+            DataValue::Float64(Some(2.5)),
+        ),
+        case("min-all-null", "min", &all_null, DataValue::Int64(None)),
+        case("min-mixed", "min", &mixed, DataValue::Int64(Some(1))),
+        case("min-no-null", "min", &no_null, DataValue::Int64(Some(1))),
+        case("max-all-null", "max", &all_null, DataValue::Int64(None)),
+        case("max-mixed", "max", &mixed, DataValue::Int64(Some(4))),
+        case("max-no-null", "max", &no_null, DataValue::Int64(Some(4))),
+    ];
+
+    for t in tests {
+        let arena = Bump::new();
+        let rows = t.array.len();
+        let arrays = vec![t.array.clone()];
+        let func = AggregateFunctionFactory::get(t.func_name, vec![], t.args.clone())?;
+
+        // single stage: the whole column at once
+        let place: StateAddr = arena.alloc_layout(func.state_layout()).into();
+        func.init_state(place);
+        func.accumulate(place, &arrays, rows)?;
+        let single = func.merge_result(place)?;
+        assert_eq!(&t.expect, &single, "{}", t.name);
+
+        // keyed path: every row lands in the same group
+        let place: StateAddr = arena.alloc_layout(func.state_layout()).into();
+        func.init_state(place);
+        let places: StateAddrs = std::iter::repeat(place).take(rows).collect();
+        func.accumulate_keys(&places, 0, &arrays, rows)?;
+        let keyed = func.merge_result(place)?;
+        assert_eq!(&t.expect, &keyed, "{}", t.name);
+
+        // two stage: partial states are shipped in their serialized form and merged
+        let merged: StateAddr = arena.alloc_layout(func.state_layout()).into();
+        func.init_state(merged);
+        let half = rows / 2;
+        for (offset, length) in [(0, half), (half, rows - half)] {
+            let part = vec![t.array.slice(offset, length)];
+            let partial: StateAddr = arena.alloc_layout(func.state_layout()).into();
+            func.init_state(partial);
+            func.accumulate(partial, &part, length)?;
+
+            let mut buf = BytesMut::new();
+            func.serialize(partial, &mut buf)?;
+
+            let received: StateAddr = arena.alloc_layout(func.state_layout()).into();
+            func.init_state(received);
+            func.deserialize(received, &mut buf.as_ref())?;
+            func.merge(merged, received)?;
+        }
+        let two_stage = func.merge_result(merged)?;
+        assert_eq!(single, two_stage, "{}", t.name);
+    }
+    Ok(())
+}
@@ -92,13 +92,25 @@ where
         is_min: bool,
     ) -> Result<()> {
         let array: &DFPrimitiveArray<T> = series.static_cast();
-        array.into_iter().zip(places.iter()).for_each(|(x, place)| {
-            if let Some(x) = x {
+        let values = array.inner().values().as_slice();
+        match array.inner().validity() {
+            Some(validity) if array.null_count() > 0 => values
+                .iter()
+                .zip(validity.into_iter())
+                .zip(places.iter())
+                .for_each(|((x, valid), place)| {
+                    if valid {
+                        let place = place.next(offset);
+                        let state = place.get::<Self>();
+                        state.merge_value(*x, is_min);
+                    }
+                }),
+            _ => values.iter().zip(places.iter()).for_each(|(x, place)| {
                 let place = place.next(offset);
                 let state = place.get::<Self>();
                 state.merge_value(*x, is_min);
-            }
-        });
+            }),
+        }
         Ok(())
     }
 
@@ -219,8 +231,9 @@ where T: AggregateMinMaxState //  std::cmp::PartialOrd + DFTryFrom<DataValue> +
         Ok(self.arguments[0].data_type().clone())
     }
 
+    // min/max of an empty or all-null input is NULL
     fn nullable(&self, _input_schema: &DataSchema) -> Result<bool> {
-        Ok(false)
+        Ok(true)
     }
 
     fn init_state(&self, place: StateAddr) {
@@ -82,8 +82,9 @@ where
         Ok(value.data_type())
     }
 
+    // sum of an empty or all-null input is NULL
     fn nullable(&self, _input_schema: &DataSchema) -> Result<bool> {
-        Ok(false)
+        Ok(true)
     }
 
     fn init_state(&self, place: StateAddr) {
@@ -114,29 +115,24 @@ where
         _input_rows: usize,
     ) -> Result<()> {
         let darray: &DFPrimitiveArray<T> = arrays[0].static_cast();
-        if darray.null_count() == 0 {
-            darray
-                .inner()
-                .values()
-                .as_slice()
+        let values = darray.inner().values().as_slice();
+        match darray.inner().validity() {
+            Some(validity) if darray.null_count() > 0 => values
                 .iter()
+                .zip(validity.into_iter())
                 .zip(places.iter())
-                .for_each(|(v, place)| {
-                    let place = place.next(offset);
-                    let state = place.get::<AggregateSumState<SumT>>();
-                    state.add(v.as_());
-                });
-        } else {
-            darray
-                .into_iter()
-                .zip(places.iter())
-                .for_each(|(c, place)| {
-                    if let Some(v) = c {
+                .for_each(|((v, valid), place)| {
+                    if valid {
                         let place = place.next(offset);
                         let state = place.get::<AggregateSumState<SumT>>();
                         state.add(v.as_());
                     }
-                });
+                }),
+            _ => values.iter().zip(places.iter()).for_each(|(v, place)| {
+                let place = place.next(offset);
+                let state = place.get::<AggregateSumState<SumT>>();
+                state.add(v.as_());
+            }),
         }
 
         Ok(())
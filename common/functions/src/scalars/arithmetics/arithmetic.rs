@@ -77,18 +77,53 @@ impl Function for ArithmeticFunction {
     }
 
     fn nullable(&self, _input_schema: &DataSchema) -> Result<bool> {
-        Ok(false)
+        // the integer division and modulo by zero are null
+        Ok(matches!(
+            self.op,
+            DataValueArithmeticOperator::Div | DataValueArithmeticOperator::Modulo
+        ))
     }
 
     fn eval(&self, columns: &DataColumnsWithField, _input_rows: usize) -> Result<DataColumn> {
+        self.eval_arithmetic(columns, false)
+    }
+
+    fn eval_strict(
+        &self,
+        columns: &DataColumnsWithField,
+        _input_rows: usize,
+    ) -> Result<DataColumn> {
+        self.eval_arithmetic(columns, true)
+    }
+
+    fn num_arguments(&self) -> usize {
+        0
+    }
+
+    fn variadic_arguments(&self) -> Option<(usize, usize)> {
+        Some((1, 2))
+    }
+}
+
+impl fmt::Display for ArithmeticFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.op)
+    }
+}
+
+impl ArithmeticFunction {
+    fn eval_arithmetic(&self, columns: &DataColumnsWithField, strict: bool) -> Result<DataColumn> {
         let result: DataColumn = {
             // Some logic type need DateType information, try arithmetic on column with field first.
             if let Some(r) = self.try_evaluate_on_column_field(columns) {
                 r?
             } else {
-                match columns.len() {
-                    1 => columns[0].column().unary_arithmetic(self.op.clone()),
-                    _ => columns[0]
+                match (columns.len(), strict) {
+                    (1, _) => columns[0].column().unary_arithmetic(self.op.clone()),
+                    (_, true) => columns[0]
+                        .column()
+                        .strict_arithmetic(self.op.clone(), columns[1].column()),
+                    (_, false) => columns[0]
                         .column()
                         .arithmetic(self.op.clone(), columns[1].column()),
                 }?
@@ -109,22 +144,6 @@ impl Function for ArithmeticFunction {
         }
     }
 
-    fn num_arguments(&self) -> usize {
-        0
-    }
-
-    fn variadic_arguments(&self) -> Option<(usize, usize)> {
-        Some((1, 2))
-    }
-}
-
-impl fmt::Display for ArithmeticFunction {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.op)
-    }
-}
-
-impl ArithmeticFunction {
     // This is an arithmetic support for DataColumnWithField. Maybe we should move it into to "impl DataColumnWithField",
     // thus we can do it like "columns[0].arithmetic(columns[1])".
     // Currently only apply for Plus/Minus operation between Date/DateTime and Interval
@@ -100,7 +100,7 @@ fn test_arithmetic_function() -> Result<()> {
             name: "div-int64-passed",
             display: "divide",
             arg_names: vec!["a", "b"],
-            nullable: false,
+            nullable: true,
             func: ArithmeticDivFunction::try_create_func("")?,
             columns: vec![
                 Series::new(vec![4i64, 3, 2]).into(),
@@ -110,11 +110,25 @@ fn test_arithmetic_function() -> Result<()> {
             expect: Series::new(vec![4.0, 1.5, 0.6666666666666666]).into(),
             error: "",
         },
+        Test {
+            name: "mul-int64-overflow",
+            display: "multiply",
+            arg_names: vec!["a", "b"],
+            nullable: false,
+            func: ArithmeticMulFunction::try_create_func("")?,
+            columns: vec![
+                Series::new(vec![4i64, i64::MAX]).into(),
+                Series::new(vec![1i64, 2]).into(),
+                Series::new(vec![1i16, 2]).into(),
+            ],
+            expect: Series::new(vec![4i64, 0]).into(),
+            error: "Code: 60, displayText = 9223372036854775807 * 2 overflows Int64.",
+        },
         Test {
             name: "mod-int64-passed",
             display: "modulo",
             arg_names: vec!["a", "b"],
-            nullable: false,
+            nullable: true,
             func: ArithmeticModuloFunction::try_create_func("")?,
             columns: vec![
                 Series::new(vec![4i64, 3, 2]).into(),
@@ -146,7 +160,8 @@ fn test_arithmetic_function() -> Result<()> {
 
         let func = t.func;
         if let Err(e) = func.eval(&columns, rows) {
-            assert_eq!(t.error, e.to_string());
+            assert_eq!(t.error, e.to_string(), "{}", t.name);
+            continue;
         }
 
         // Display check.
@@ -451,3 +466,81 @@ fn test_arithmetic_date_interval() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_arithmetic_division_by_zero() -> Result<()> {
+    struct Test {
+        name: &'static str,
+        func: Box<dyn Function>,
+        columns: Vec<DataColumn>,
+        expect: Vec<DataValue>,
+    }
+
+    let tests = vec![
+        Test {
+            name: "div-int64-column-zero",
+            func: ArithmeticDivFunction::try_create_func("")?,
+            columns: vec![
+                Series::new(vec![4i64, 3]).into(),
+                Series::new(vec![0i64, 3]).into(),
+            ],
+            expect: vec![DataValue::Float64(None), DataValue::Float64(Some(1.0))],
+        },
+        Test {
+            name: "div-int64-constant-zero",
+            func: ArithmeticDivFunction::try_create_func("")?,
+            columns: vec![
+                Series::new(vec![4i64, 3]).into(),
+                DataColumn::Constant(DataValue::Int64(Some(0)), 2),
+            ],
+            expect: vec![DataValue::Float64(None), DataValue::Float64(None)],
+        },
+        Test {
+            name: "div-float64-constant-zero",
+            func: ArithmeticDivFunction::try_create_func("")?,
+            columns: vec![
+                DataColumn::Constant(DataValue::Float64(Some(1.0)), 2),
+                Series::new(vec![0.0f64, 2.0]).into(),
+            ],
+            expect: vec![
+                DataValue::Float64(Some(f64::INFINITY)),
+                DataValue::Float64(Some(0.5)),
+            ],
+        },
+        Test {
+            name: "mod-int64-column-zero",
+            func: ArithmeticModuloFunction::try_create_func("")?,
+            columns: vec![
+                Series::new(vec![4i64, 3]).into(),
+                Series::new(vec![2i64, 0]).into(),
+            ],
+            expect: vec![DataValue::Int64(Some(0)), DataValue::Int64(None)],
+        },
+        Test {
+            name: "mod-constant-zero",
+            func: ArithmeticModuloFunction::try_create_func("")?,
+            columns: vec![
+                DataColumn::Constant(DataValue::Int64(Some(4)), 2),
+                DataColumn::Constant(DataValue::Int64(Some(0)), 2),
+            ],
+            expect: vec![DataValue::Int64(None), DataValue::Int64(None)],
+        },
+    ];
+
+    for t in tests {
+        let columns: Vec<DataColumnWithField> = t
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let field = DataField::new(&format!("c{}", i), c.data_type(), false);
+                DataColumnWithField::new(c.clone(), field)
+            })
+            .collect();
+
+        let result = t.func.eval(&columns, 2)?;
+        let values = result.to_values()?;
+        assert_eq!(values, t.expect, "{}", t.name);
+    }
+    Ok(())
+}
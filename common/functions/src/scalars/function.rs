@@ -38,6 +38,11 @@ pub trait Function: fmt::Display + Sync + Send + DynClone {
     fn nullable(&self, _input_schema: &DataSchema) -> Result<bool>;
     fn eval(&self, columns: &DataColumnsWithField, _input_rows: usize) -> Result<DataColumn>;
 
+    // Same as eval, with the integer division or modulo by zero as an error rather than null.
+    fn eval_strict(&self, columns: &DataColumnsWithField, input_rows: usize) -> Result<DataColumn> {
+        self.eval(columns, input_rows)
+    }
+
     // If function returns the same result when same arguments, it is deterministic function.
     fn is_deterministic(&self) -> bool {
         true
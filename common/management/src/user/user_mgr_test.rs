@@ -17,9 +17,12 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use common_exception::ErrorCode;
+use common_metatypes::JsonPatchOp;
 use common_metatypes::KVMeta;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
 use common_store_api::kv_apis::kv_api::MGetKVActionResult;
+use common_store_api::kv_apis::kv_api::PrefixListPage;
 use common_store_api::kv_apis::kv_api::PrefixListReply;
 use common_store_api::GetKVActionResult;
 use common_store_api::KVApi;
@@ -47,6 +50,14 @@ mock! {
             value_meta: Option<KVMeta>
         ) -> common_exception::Result<UpsertKVActionResult>;
 
+        async fn cas_kv(
+            &self,
+            key: &str,
+            expect: Option<Vec<u8>>,
+            value: Option<Vec<u8>>,
+            value_meta: Option<KVMeta>
+        ) -> common_exception::Result<UpsertKVActionResult>;
+
         async fn update_kv_meta(
             &self,
             key: &str,
@@ -54,6 +65,21 @@ mock! {
             value_meta: Option<KVMeta>
         ) -> common_exception::Result<UpsertKVActionResult>;
 
+        async fn upsert_kv_merge(
+            &self,
+            key: &str,
+            seq: MatchSeq,
+            op: MergeOp,
+            value_meta: Option<KVMeta>
+        ) -> common_exception::Result<UpsertKVActionResult>;
+
+        async fn patch_kv_json(
+            &self,
+            key: &str,
+            seq: MatchSeq,
+            patch: Vec<JsonPatchOp>
+        ) -> common_exception::Result<UpsertKVActionResult>;
+
         async fn get_kv(&self, key: &str) -> common_exception::Result<GetKVActionResult>;
 
         async fn mget_kv(
@@ -62,6 +88,22 @@ mock! {
         ) -> common_exception::Result<MGetKVActionResult>;
 
         async fn prefix_list_kv(&self, prefix: &str) -> common_exception::Result<PrefixListReply>;
+
+        async fn delete_prefix_kv(&self, prefix: &str) -> common_exception::Result<usize>;
+
+        async fn prefix_list_kv_page(
+            &self,
+            prefix: &str,
+            limit: Option<usize>,
+            after_key: Option<String>,
+        ) -> common_exception::Result<PrefixListPage>;
+
+        async fn prefix_list_kv_by_seq(
+            &self,
+            prefix: &str,
+            min_seq: u64,
+            limit: usize,
+        ) -> common_exception::Result<PrefixListReply>;
         }
 }
 #[test]
@@ -21,3 +21,22 @@ use crate::MatchSeq;
 pub enum ConflictSeq {
     NotMatch { want: MatchSeq, got: u64 },
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum MergeError {
+    UnknownFunction { name: String },
+    InvalidValue { name: String, reason: String },
+}
+
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::UnknownFunction { name } => {
+                write!(f, "unknown merge function: {}", name)
+            }
+            MergeError::InvalidValue { name, reason } => {
+                write!(f, "merge function {} can not be applied: {}", name, reason)
+            }
+        }
+    }
+}
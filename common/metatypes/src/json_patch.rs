@@ -0,0 +1,223 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+/// Max number of operations in one json patch.
+pub const JSON_PATCH_MAX_OPS: usize = 256;
+
+/// Max size of a json document produced by a patch, in bytes.
+pub const JSON_PATCH_MAX_VALUE_BYTES: usize = 1024 * 1024;
+
+/// An operation of a json patch, as defined by RFC 6902, with the `add`, `remove` and `replace` ops.
+/// `path` is a JSON pointer(RFC 6901), e.g., `/a/b/0`, or an empty string for the whole document.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum JsonPatchOp {
+    /// Add a member to an object, insert an element into an array(`-` appends),
+    /// or replace the whole document. The parent of `path` must exist.
+    Add { path: String, value: Value },
+
+    /// Remove an existent member or element.
+    Remove { path: String },
+
+    /// Replace an existent member or element.
+    Replace { path: String, value: Value },
+}
+
+impl JsonPatchOp {
+    pub fn path(&self) -> &str {
+        match self {
+            JsonPatchOp::Add { path, .. } => path,
+            JsonPatchOp::Remove { path } => path,
+            JsonPatchOp::Replace { path, .. } => path,
+        }
+    }
+
+    fn apply(&self, doc: &mut Value) -> Result<(), String> {
+        let tokens = parse_pointer(self.path())?;
+
+        match self {
+            JsonPatchOp::Add { value, .. } => {
+                let (last, parent) = match tokens.split_last() {
+                    None => {
+                        *doc = value.clone();
+                        return Ok(());
+                    }
+                    Some(x) => x,
+                };
+
+                match lookup_mut(doc, parent)? {
+                    Value::Object(m) => {
+                        m.insert(last.clone(), value.clone());
+                    }
+                    Value::Array(a) => {
+                        let i = if last == "-" {
+                            a.len()
+                        } else {
+                            parse_index(last, a.len() + 1)?
+                        };
+                        a.insert(i, value.clone());
+                    }
+                    _ => return Err(format!("can not add {:?} to a scalar value", last)),
+                }
+            }
+            JsonPatchOp::Remove { .. } => {
+                let (last, parent) = tokens
+                    .split_last()
+                    .ok_or_else(|| "can not remove the whole document".to_string())?;
+
+                match lookup_mut(doc, parent)? {
+                    Value::Object(m) => {
+                        m.remove(last)
+                            .ok_or_else(|| format!("member {:?} does not exist", last))?;
+                    }
+                    Value::Array(a) => {
+                        let i = parse_index(last, a.len())?;
+                        a.remove(i);
+                    }
+                    _ => return Err(format!("can not remove {:?} from a scalar value", last)),
+                }
+            }
+            JsonPatchOp::Replace { value, .. } => {
+                *lookup_mut(doc, &tokens)? = value.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for JsonPatchOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonPatchOp::Add { path, .. } => write!(f, "add {:?}", path),
+            JsonPatchOp::Remove { path } => write!(f, "remove {:?}", path),
+            JsonPatchOp::Replace { path, .. } => write!(f, "replace {:?}", path),
+        }
+    }
+}
+
+/// Check the number of operations and the syntax of the paths, without a document.
+pub fn check_json_patch(ops: &[JsonPatchOp]) -> Result<(), String> {
+    if ops.len() > JSON_PATCH_MAX_OPS {
+        return Err(format!(
+            "too many operations: {}, at most {} are allowed",
+            ops.len(),
+            JSON_PATCH_MAX_OPS
+        ));
+    }
+
+    for op in ops {
+        parse_pointer(op.path()).map_err(|e| format!("{}: {}", op, e))?;
+    }
+    Ok(())
+}
+
+/// Apply the operations in order to the json document `curr`, an absent document is an empty object.
+/// It is all or nothing: if any of the operations fails, an error is returned and no document is produced.
+pub fn apply_json_patch(curr: Option<&[u8]>, ops: &[JsonPatchOp]) -> Result<Vec<u8>, String> {
+    check_json_patch(ops)?;
+
+    let mut doc = match curr {
+        None => Value::Object(Map::new()),
+        Some(v) => serde_json::from_slice(v)
+            .map_err(|e| format!("the current value is not a json document: {}", e))?,
+    };
+
+    for op in ops {
+        op.apply(&mut doc).map_err(|e| format!("{}: {}", op, e))?;
+    }
+
+    let res = serde_json::to_vec(&doc).map_err(|e| e.to_string())?;
+    if res.len() > JSON_PATCH_MAX_VALUE_BYTES {
+        return Err(format!(
+            "the patched document is {} bytes, at most {} bytes are allowed",
+            res.len(),
+            JSON_PATCH_MAX_VALUE_BYTES
+        ));
+    }
+    Ok(res)
+}
+
+/// Split a JSON pointer into unescaped reference tokens. The empty pointer refers to the whole document.
+fn parse_pointer(path: &str) -> Result<Vec<String>, String> {
+    if path.is_empty() {
+        return Ok(vec![]);
+    }
+
+    if !path.starts_with('/') {
+        return Err(format!(
+            "invalid pointer {:?}: it must start with '/'",
+            path
+        ));
+    }
+
+    path[1..].split('/').map(unescape_token).collect()
+}
+
+/// `~1` is `/` and `~0` is `~`, any other `~` is invalid.
+fn unescape_token(token: &str) -> Result<String, String> {
+    let mut res = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => res.push('~'),
+            Some('1') => res.push('/'),
+            _ => return Err(format!("invalid escape in {:?}", token)),
+        }
+    }
+    Ok(res)
+}
+
+fn lookup_mut<'a>(doc: &'a mut Value, tokens: &[String]) -> Result<&'a mut Value, String> {
+    let mut curr = doc;
+    for token in tokens {
+        curr = match curr {
+            Value::Object(m) => m
+                .get_mut(token)
+                .ok_or_else(|| format!("member {:?} does not exist", token))?,
+            Value::Array(a) => {
+                let i = parse_index(token, a.len())?;
+                &mut a[i]
+            }
+            _ => return Err(format!("can not look up {:?} in a scalar value", token)),
+        };
+    }
+    Ok(curr)
+}
+
+/// Parse an array index that must be less than `len`. An index has no leading zero.
+fn parse_index(token: &str, len: usize) -> Result<usize, String> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    if !valid {
+        return Err(format!("invalid array index {:?}", token));
+    }
+
+    match token.parse::<usize>() {
+        Ok(i) if i < len => Ok(i),
+        _ => Err(format!("array index {} is out of bounds", token)),
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::apply_json_patch;
+use crate::check_json_patch;
+use crate::JsonPatchOp;
+use crate::JSON_PATCH_MAX_OPS;
+
+fn add(path: &str, value: Value) -> JsonPatchOp {
+    JsonPatchOp::Add {
+        path: path.to_string(),
+        value,
+    }
+}
+
+fn remove(path: &str) -> JsonPatchOp {
+    JsonPatchOp::Remove {
+        path: path.to_string(),
+    }
+}
+
+fn replace(path: &str, value: Value) -> JsonPatchOp {
+    JsonPatchOp::Replace {
+        path: path.to_string(),
+        value,
+    }
+}
+
+fn patch(doc: Option<Value>, ops: &[JsonPatchOp]) -> Result<Value, String> {
+    let curr = doc.map(|d| serde_json::to_vec(&d).unwrap());
+    let res = apply_json_patch(curr.as_deref(), ops)?;
+    Ok(serde_json::from_slice(&res).unwrap())
+}
+
+#[test]
+fn test_json_patch_op_serde() -> Result<(), String> {
+    // The same form as RFC 6902.
+    let ops: Vec<JsonPatchOp> = serde_json::from_str(
+        r#"[{"op":"add","path":"/a","value":1},{"op":"remove","path":"/b"},{"op":"replace","path":"","value":{}}]"#,
+    )
+    .map_err(|e| e.to_string())?;
+    assert_eq!(
+        vec![add("/a", json!(1)), remove("/b"), replace("", json!({}))],
+        ops
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch_absent_document() -> Result<(), String> {
+    // An absent document is an empty object.
+    assert_eq!(
+        json!({"a": {"b": 1}}),
+        patch(None, &[add("/a", json!({})), add("/a/b", json!(1))])?
+    );
+
+    assert!(patch(None, &[add("/a/b", json!(1))]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch_add() -> Result<(), String> {
+    let doc = json!({"a": 1, "arr": [1, 2]});
+
+    // Add replaces an existent member.
+    assert_eq!(
+        json!({"a": 2, "arr": [1, 2]}),
+        patch(Some(doc.clone()), &[add("/a", json!(2))])?
+    );
+
+    // Insert before an index, at the end, or append with `-`.
+    assert_eq!(
+        json!({"a": 1, "arr": [0, 1, 2]}),
+        patch(Some(doc.clone()), &[add("/arr/0", json!(0))])?
+    );
+    assert_eq!(
+        json!({"a": 1, "arr": [1, 2, 3]}),
+        patch(Some(doc.clone()), &[add("/arr/2", json!(3))])?
+    );
+    assert_eq!(
+        json!({"a": 1, "arr": [1, 2, 3]}),
+        patch(Some(doc.clone()), &[add("/arr/-", json!(3))])?
+    );
+    assert!(patch(Some(doc.clone()), &[add("/arr/3", json!(3))]).is_err());
+    assert!(patch(Some(doc.clone()), &[add("/arr/01", json!(3))]).is_err());
+
+    // The parent must exist and must not be a scalar.
+    assert!(patch(Some(doc.clone()), &[add("/x/y", json!(1))]).is_err());
+    assert!(patch(Some(doc.clone()), &[add("/a/y", json!(1))]).is_err());
+
+    // The empty path is the whole document.
+    assert_eq!(json!([1]), patch(Some(doc), &[add("", json!([1]))])?);
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch_remove_and_replace() -> Result<(), String> {
+    let doc = json!({"a": 1, "arr": [1, 2], "a/b": 3, "m~n": 4});
+
+    assert_eq!(
+        json!({"arr": [2], "a/b": 3, "m~n": 4}),
+        patch(Some(doc.clone()), &[remove("/a"), remove("/arr/0")])?
+    );
+
+    // Escaped tokens.
+    assert_eq!(
+        json!({"a": 1, "arr": [1, 2]}),
+        patch(Some(doc.clone()), &[remove("/a~1b"), remove("/m~0n")])?
+    );
+
+    assert_eq!(
+        json!({"a": {"x": 1}, "arr": [1, 5], "a/b": 3, "m~n": 4}),
+        patch(Some(doc.clone()), &[
+            replace("/a", json!({"x": 1})),
+            replace("/arr/1", json!(5))
+        ])?
+    );
+
+    // Remove and replace require the target to exist.
+    assert!(patch(Some(doc.clone()), &[remove("/x")]).is_err());
+    assert!(patch(Some(doc.clone()), &[remove("/arr/2")]).is_err());
+    assert!(patch(Some(doc.clone()), &[remove("")]).is_err());
+    assert!(patch(Some(doc.clone()), &[replace("/x", json!(1))]).is_err());
+    assert!(patch(Some(doc), &[replace("/arr/2", json!(1))]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch_all_or_nothing() -> Result<(), String> {
+    // The second op fails, thus no document is produced even though the first one applies.
+    let res = patch(Some(json!({"a": 1})), &[add("/b", json!(2)), remove("/c")]);
+    let err = res.unwrap_err();
+    assert!(err.contains("remove \"/c\""), "got: {}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch_invalid() -> Result<(), String> {
+    // The current value must be a json document.
+    let res = apply_json_patch(Some(b"not json"), &[add("/a", json!(1))]);
+    assert!(res.unwrap_err().contains("not a json document"));
+
+    // A pointer must start with '/' and escape '~' properly.
+    assert!(check_json_patch(&[add("a", json!(1))]).is_err());
+    assert!(check_json_patch(&[add("/a~2", json!(1))]).is_err());
+    assert!(check_json_patch(&[add("/a~01", json!(1))]).is_ok());
+
+    // Too many operations.
+    let ops = vec![add("/a", json!(1)); JSON_PATCH_MAX_OPS];
+    assert!(check_json_patch(&ops).is_ok());
+
+    let ops = vec![add("/a", json!(1)); JSON_PATCH_MAX_OPS + 1];
+    assert!(check_json_patch(&ops).is_err());
+    assert!(apply_json_patch(None, &ops).is_err());
+
+    Ok(())
+}
@@ -22,16 +22,31 @@ use std::fmt::Debug;
 use std::fmt::Formatter;
 
 pub use errors::ConflictSeq;
+pub use errors::MergeError;
+pub use json_patch::apply_json_patch;
+pub use json_patch::check_json_patch;
+pub use json_patch::JsonPatchOp;
+pub use json_patch::JSON_PATCH_MAX_OPS;
+pub use json_patch::JSON_PATCH_MAX_VALUE_BYTES;
 pub use match_seq::MatchSeq;
 pub use match_seq::MatchSeqExt;
+pub use merge_op::MergeFn;
+pub use merge_op::MergeOp;
+pub use merge_op::BUILTIN_MERGE_FUNCTIONS;
 use serde::Deserialize;
 use serde::Serialize;
 
 mod errors;
+mod json_patch;
 mod match_seq;
+mod merge_op;
 
+#[cfg(test)]
+mod json_patch_test;
 #[cfg(test)]
 mod match_seq_test;
+#[cfg(test)]
+mod merge_op_test;
 
 /// Value with a corresponding sequence number
 pub type SeqValue<T = Vec<u8>> = (u64, T);
@@ -73,6 +88,55 @@ impl PartialOrd<u64> for KVValue {
     }
 }
 
+/// What happened to a generic-kv record.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum KVChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    Expired,
+}
+
+/// A change of a generic-kv record: the record before and after it.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct KVChange {
+    pub kind: KVChangeKind,
+    pub key: String,
+    /// The record before the change, None if it did not exist or is expired.
+    pub prev: Option<SeqValue<KVValue>>,
+    /// The record after the change, None if it is deleted or expired.
+    pub result: Option<SeqValue<KVValue>>,
+}
+
+impl KVChange {
+    pub fn create(
+        key: &str,
+        prev: Option<SeqValue<KVValue>>,
+        result: Option<SeqValue<KVValue>>,
+    ) -> Self {
+        let kind = match (&prev, &result) {
+            (None, _) => KVChangeKind::Created,
+            (Some(_), Some(_)) => KVChangeKind::Updated,
+            (Some(_), None) => KVChangeKind::Deleted,
+        };
+        KVChange {
+            kind,
+            key: key.to_string(),
+            prev,
+            result,
+        }
+    }
+
+    /// The seq of the record after the change, or the seq of the removed record.
+    pub fn seq(&self) -> u64 {
+        match (&self.result, &self.prev) {
+            (Some((seq, _)), _) => *seq,
+            (None, Some((seq, _))) => *seq,
+            (None, None) => 0,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct Database {
     pub database_id: u64,
@@ -105,6 +169,11 @@ pub struct Table {
 
     /// name of parts that belong to this table.
     pub parts: HashSet<String>,
+
+    /// The version of the parts of this table, bumped whenever parts are appended or removed.
+    /// It is taken from a sequence of the store, a table created later has a greater version.
+    #[serde(default)]
+    pub data_version: u64,
 }
 
 impl fmt::Display for Table {
@@ -113,6 +182,41 @@ impl fmt::Display for Table {
     }
 }
 
+/// A dropped table kept in trash, it can be restored before `expire_at`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct DroppedTable {
+    pub db_name: String,
+    pub table_name: String,
+
+    /// drop time in second since 1970
+    pub dropped_on: u64,
+
+    /// expiration time in second since 1970, after which the table and its parts are purged.
+    pub expire_at: u64,
+
+    pub table: Table,
+}
+
+/// The storage quota of a database and the bytes its data parts take.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Eq, PartialEq)]
+pub struct DatabaseUsage {
+    /// max bytes of the data parts, `None` for unlimited.
+    pub quota: Option<u64>,
+
+    /// bytes on disk of the data parts of the tables, the dropped tables excluded.
+    pub used_bytes: u64,
+}
+
+impl DatabaseUsage {
+    /// Whether `incoming` more bytes would exceed the quota.
+    pub fn exceeded_by(&self, incoming: u64) -> bool {
+        match self.quota {
+            None => false,
+            Some(quota) => self.used_bytes.saturating_add(incoming) > quota,
+        }
+    }
+}
+
 pub type MetaVersion = u64;
 pub type MetaId = u64;
 
@@ -0,0 +1,174 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::MergeError;
+
+/// A built-in merge function: it receives the current value(`None` if the key is absent) and an argument,
+/// and returns the merged value, or `None` if the current value should be left as is.
+pub type MergeFn = fn(Option<&[u8]>, &[u8]) -> Result<Option<Vec<u8>>, String>;
+
+/// The registry of merge functions that can be referred to by `MergeOp::Named`.
+///
+/// Merges are applied by every replica of the state machine, thus only deterministic built-in functions are allowed.
+pub const BUILTIN_MERGE_FUNCTIONS: &[(&str, MergeFn)] = &[
+    ("add_u64", merge_add_u64),
+    ("set_add", merge_set_add),
+    ("set_remove", merge_set_remove),
+];
+
+/// Describes how to transform the value of a key on the server side.
+/// The transformation is done atomically by the state machine, thus concurrent merges never lose an update.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MergeOp {
+    /// Append bytes to the current value. An absent key is treated as an empty value.
+    /// If `max_len` is specified, the oldest bytes are trimmed from the front,
+    /// so that the result contains at most `max_len` bytes.
+    AppendBytes {
+        value: Vec<u8>,
+        max_len: Option<u64>,
+    },
+
+    /// Set the value only if the key is absent. An existent value is left as is.
+    SetIfAbsentBytes(Vec<u8>),
+
+    /// Apply a function from `BUILTIN_MERGE_FUNCTIONS` with an argument.
+    Named { name: String, arg: Vec<u8> },
+}
+
+impl MergeOp {
+    /// Check if this op can be applied, e.g., the function it refers to exists.
+    pub fn check(&self) -> Result<(), MergeError> {
+        match self {
+            MergeOp::Named { name, .. } => find_builtin(name).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the merged value of `curr`, or `None` if `curr` should be left as is.
+    pub fn apply(&self, curr: Option<&[u8]>) -> Result<Option<Vec<u8>>, MergeError> {
+        match self {
+            MergeOp::AppendBytes { value, max_len } => {
+                let mut res = curr.unwrap_or_default().to_vec();
+                res.extend_from_slice(value);
+
+                if let Some(max_len) = max_len {
+                    let max_len = *max_len as usize;
+                    if res.len() > max_len {
+                        res.drain(..res.len() - max_len);
+                    }
+                }
+                Ok(Some(res))
+            }
+            MergeOp::SetIfAbsentBytes(value) => match curr {
+                None => Ok(Some(value.clone())),
+                Some(_) => Ok(None),
+            },
+            MergeOp::Named { name, arg } => {
+                let f = find_builtin(name)?;
+                f(curr, arg).map_err(|reason| MergeError::InvalidValue {
+                    name: name.clone(),
+                    reason,
+                })
+            }
+        }
+    }
+}
+
+impl Display for MergeOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeOp::AppendBytes { value, max_len } => {
+                write!(f, "append {} bytes (max_len: {:?})", value.len(), max_len)
+            }
+            MergeOp::SetIfAbsentBytes(value) => {
+                write!(f, "set {} bytes if absent", value.len())
+            }
+            MergeOp::Named { name, arg } => {
+                write!(f, "{}({} bytes)", name, arg.len())
+            }
+        }
+    }
+}
+
+fn find_builtin(name: &str) -> Result<MergeFn, MergeError> {
+    BUILTIN_MERGE_FUNCTIONS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, f)| *f)
+        .ok_or_else(|| MergeError::UnknownFunction {
+            name: name.to_string(),
+        })
+}
+
+fn parse_u64(v: &[u8]) -> Result<u64, String> {
+    std::str::from_utf8(v)
+        .map_err(|e| e.to_string())?
+        .parse::<u64>()
+        .map_err(|e| format!("{:?} is not a u64: {}", String::from_utf8_lossy(v), e))
+}
+
+/// Treat the value and the argument as decimal u64 and add them up. An absent value is 0.
+fn merge_add_u64(curr: Option<&[u8]>, arg: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let curr = match curr {
+        None => 0,
+        Some(v) => parse_u64(v)?,
+    };
+    let res = curr
+        .checked_add(parse_u64(arg)?)
+        .ok_or_else(|| "u64 overflow".to_string())?;
+    Ok(Some(res.to_string().into_bytes()))
+}
+
+/// Treat the value as a set of `\n` separated items and add the argument to it.
+fn merge_set_add(curr: Option<&[u8]>, arg: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    if arg.is_empty() || arg.contains(&b'\n') {
+        return Err("set item must be non-empty and must not contain '\\n'".to_string());
+    }
+
+    let curr = curr.unwrap_or_default();
+    if curr.split(|b| *b == b'\n').any(|item| item == arg) {
+        return Ok(None);
+    }
+
+    let mut res = curr.to_vec();
+    if !res.is_empty() {
+        res.push(b'\n');
+    }
+    res.extend_from_slice(arg);
+    Ok(Some(res))
+}
+
+/// Treat the value as a set of `\n` separated items and remove the argument from it.
+fn merge_set_remove(curr: Option<&[u8]>, arg: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let curr = match curr {
+        None => return Ok(None),
+        Some(v) => v,
+    };
+
+    if !curr.split(|b| *b == b'\n').any(|item| item == arg) {
+        return Ok(None);
+    }
+
+    let items = curr
+        .split(|b| *b == b'\n')
+        .filter(|item| *item != arg)
+        .collect::<Vec<_>>();
+    Ok(Some(items.join(&b'\n')))
+}
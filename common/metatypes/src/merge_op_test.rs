@@ -0,0 +1,120 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::MergeError;
+use crate::MergeOp;
+
+fn append(value: &str, max_len: Option<u64>) -> MergeOp {
+    MergeOp::AppendBytes {
+        value: value.as_bytes().to_vec(),
+        max_len,
+    }
+}
+
+fn named(name: &str, arg: &str) -> MergeOp {
+    MergeOp::Named {
+        name: name.to_string(),
+        arg: arg.as_bytes().to_vec(),
+    }
+}
+
+#[test]
+fn test_merge_op_append_bytes() -> Result<(), MergeError> {
+    // An absent key is treated as an empty value.
+    assert_eq!(Some(b"ab".to_vec()), append("ab", None).apply(None)?);
+    assert_eq!(
+        Some(b"xyab".to_vec()),
+        append("ab", None).apply(Some(b"xy"))?
+    );
+
+    // Trimming happens only when the result exceeds max_len, and it drops the oldest bytes.
+    assert_eq!(
+        Some(b"xyab".to_vec()),
+        append("ab", Some(4)).apply(Some(b"xy"))?
+    );
+    assert_eq!(
+        Some(b"yab".to_vec()),
+        append("ab", Some(3)).apply(Some(b"xy"))?
+    );
+    assert_eq!(Some(b"b".to_vec()), append("ab", Some(1)).apply(None)?);
+    assert_eq!(
+        Some(b"".to_vec()),
+        append("ab", Some(0)).apply(Some(b"xy"))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_op_set_if_absent() -> Result<(), MergeError> {
+    let op = MergeOp::SetIfAbsentBytes(b"a".to_vec());
+    assert_eq!(Some(b"a".to_vec()), op.apply(None)?);
+    assert_eq!(None, op.apply(Some(b"b"))?);
+    assert_eq!(None, op.apply(Some(b""))?);
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_op_named() -> Result<(), MergeError> {
+    // add_u64
+
+    assert_eq!(Some(b"3".to_vec()), named("add_u64", "3").apply(None)?);
+    assert_eq!(
+        Some(b"5".to_vec()),
+        named("add_u64", "3").apply(Some(b"2"))?
+    );
+    assert!(named("add_u64", "3").apply(Some(b"x")).is_err());
+    assert!(named("add_u64", "1")
+        .apply(Some(u64::MAX.to_string().as_bytes()))
+        .is_err());
+
+    // set_add
+
+    assert_eq!(Some(b"a".to_vec()), named("set_add", "a").apply(None)?);
+    assert_eq!(
+        Some(b"a\nb".to_vec()),
+        named("set_add", "b").apply(Some(b"a"))?
+    );
+    assert_eq!(None, named("set_add", "a").apply(Some(b"a\nb"))?);
+    assert!(named("set_add", "a\nb").apply(None).is_err());
+    assert!(named("set_add", "").apply(None).is_err());
+
+    // set_remove
+
+    assert_eq!(None, named("set_remove", "a").apply(None)?);
+    assert_eq!(None, named("set_remove", "c").apply(Some(b"a\nb"))?);
+    assert_eq!(
+        Some(b"b".to_vec()),
+        named("set_remove", "a").apply(Some(b"a\nb"))?
+    );
+    assert_eq!(
+        Some(b"".to_vec()),
+        named("set_remove", "a").apply(Some(b"a"))?
+    );
+
+    // unknown
+
+    let op = named("foo", "");
+    assert_eq!(
+        Err(MergeError::UnknownFunction {
+            name: "foo".to_string()
+        }),
+        op.check()
+    );
+    assert!(op.apply(None).is_err());
+    assert_eq!(Ok(()), named("set_add", "a").check());
+
+    Ok(())
+}
@@ -41,6 +41,12 @@ mod plan_scan_test;
 #[cfg(test)]
 mod plan_select_test;
 #[cfg(test)]
+mod plan_semantic_hash_test;
+#[cfg(test)]
+mod plan_table_create_test;
+#[cfg(test)]
+mod plan_table_identifier_test;
+#[cfg(test)]
 mod test;
 
 mod plan_aggregator_final;
@@ -81,6 +87,7 @@ mod plan_remote;
 mod plan_rewriter;
 mod plan_scan;
 mod plan_select;
+mod plan_semantic_hash;
 mod plan_setting;
 mod plan_show_table_create;
 mod plan_sort;
@@ -89,12 +96,19 @@ mod plan_statistics;
 mod plan_subqueries_set;
 mod plan_table_create;
 mod plan_table_drop;
+mod plan_table_identifier;
+mod plan_table_modify_column;
+mod plan_table_optimize;
+mod plan_table_rename;
+mod plan_table_undrop;
+mod plan_transaction;
 mod plan_truncate_table;
 mod plan_use_database;
 mod plan_visitor;
 
 pub use plan_aggregator_final::AggregatorFinalPlan;
 pub use plan_aggregator_partial::AggregatorPartialPlan;
+pub use plan_aggregator_partial::AggregatorTopN;
 pub use plan_broadcast::BroadcastPlan;
 pub use plan_builder::PlanBuilder;
 pub use plan_builder_scan::TableScanInfo;
@@ -142,6 +156,9 @@ pub use plan_limit::LimitPlan;
 pub use plan_limit_by::LimitByPlan;
 pub use plan_node::PlanNode;
 pub use plan_partition::Part;
+pub use plan_partition::PartChecksum;
+pub use plan_partition::PartReadInfo;
+pub use plan_partition::PartStorageClass;
 pub use plan_partition::Partitions;
 pub use plan_projection::ProjectionPlan;
 pub use plan_read_datasource::ReadDataSourcePlan;
@@ -150,6 +167,7 @@ pub use plan_rewriter::PlanRewriter;
 pub use plan_rewriter::RewriteHelper;
 pub use plan_scan::ScanPlan;
 pub use plan_select::SelectPlan;
+pub use plan_semantic_hash::PlanCanonicalizer;
 pub use plan_setting::SettingPlan;
 pub use plan_setting::VarValue;
 pub use plan_show_table_create::ShowCreateTablePlan;
@@ -158,9 +176,20 @@ pub use plan_stage::StageKind;
 pub use plan_stage::StagePlan;
 pub use plan_statistics::Statistics;
 pub use plan_subqueries_set::SubQueriesSetPlan;
+pub use plan_table_create::check_table_options;
+pub use plan_table_create::fold_option_name;
+pub use plan_table_create::get_table_option;
+pub use plan_table_create::insert_table_option;
 pub use plan_table_create::CreateTablePlan;
 pub use plan_table_create::TableOptions;
 pub use plan_table_drop::DropTablePlan;
+pub use plan_table_identifier::TableIdentifier;
+pub use plan_table_modify_column::ModifyColumnPlan;
+pub use plan_table_optimize::OptimizeTablePlan;
+pub use plan_table_rename::RenameTablePlan;
+pub use plan_table_undrop::UndropTablePlan;
+pub use plan_transaction::TransactionKind;
+pub use plan_transaction::TransactionPlan;
 pub use plan_truncate_table::TruncateTablePlan;
 pub use plan_use_database::UseDatabasePlan;
 pub use plan_visitor::PlanVisitor;
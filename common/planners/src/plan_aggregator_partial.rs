@@ -25,6 +25,21 @@ pub struct AggregatorPartialPlan {
     pub aggr_expr: Vec<Expression>,
     pub schema: DataSchemaRef,
     pub input: Arc<PlanNode>,
+    /// Set by the optimizer if only the top groups are needed by the final aggregation.
+    pub top_n: Option<AggregatorTopN>,
+}
+
+/// The partial aggregation of an `ORDER BY <aggregate> LIMIT n` in cluster only sends
+/// the top groups of its local aggregate values to the final aggregation.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct AggregatorTopN {
+    /// Index of the aggregate in `aggr_expr` which the groups are ranked by.
+    pub aggr_index: usize,
+    pub asc: bool,
+    /// The limit of the query, the offset included.
+    pub limit: usize,
+    /// How many groups each node keeps, the limit times the safety factor.
+    pub keep: usize,
 }
 
 impl AggregatorPartialPlan {
@@ -35,4 +50,9 @@ impl AggregatorPartialPlan {
     pub fn schema(&self) -> DataSchemaRef {
         self.schema.clone()
     }
+
+    pub fn with_top_n(mut self, top_n: Option<AggregatorTopN>) -> Self {
+        self.top_n = top_n;
+        self
+    }
 }
@@ -150,6 +150,7 @@ impl PlanBuilder {
                     aggr_expr: aggr_expr.to_vec(),
                     group_expr: group_expr.to_vec(),
                     schema: DataSchemaRefExt::create(partial_fields),
+                    top_n: None,
                 }))
             }
             AggregateMode::Final => {
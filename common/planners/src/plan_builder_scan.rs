@@ -30,7 +30,7 @@ pub struct TableScanInfo<'a> {
     pub table_id: u64,
     pub table_version: Option<u64>,
     pub table_schema: &'a DataSchema,
-    pub table_args: Option<Expression>,
+    pub table_args: Option<Vec<Expression>>,
 }
 
 impl PlanBuilder {
@@ -63,6 +63,7 @@ impl PlanBuilder {
                 projection,
                 filters: vec![],
                 limit,
+                ordered_parts: false,
             },
         })))
     }
@@ -66,7 +66,7 @@ impl<'a> fmt::Display for PlanNodeIndentFormatDisplay<'a> {
             PlanNode::Sort(plan) => Self::format_sort(f, plan),
             PlanNode::Limit(plan) => Self::format_limit(f, plan),
             PlanNode::SubQueryExpression(plan) => Self::format_subquery_expr(f, plan),
-            PlanNode::ReadSource(plan) => Self::format_read_source(f, plan),
+            PlanNode::ReadSource(plan) => Self::format_read_source(f, plan, self.indent),
             PlanNode::CreateDatabase(plan) => Self::format_create_database(f, plan),
             PlanNode::DropDatabase(plan) => Self::format_drop_database(f, plan),
             PlanNode::CreateTable(plan) => Self::format_create_table(f, plan),
@@ -154,7 +154,20 @@ impl<'a> PlanNodeIndentFormatDisplay<'a> {
             f,
             "AggregatorPartial: groupBy=[{:?}], aggr=[{:?}]",
             plan.group_expr, plan.aggr_expr
-        )
+        )?;
+
+        if let Some(top_n) = &plan.top_n {
+            write!(
+                f,
+                ", topN=[{:?} {}, limit: {}, keep: {}]",
+                plan.aggr_expr[top_n.aggr_index],
+                if top_n.asc { "ASC" } else { "DESC" },
+                top_n.limit,
+                top_n.keep
+            )?;
+        }
+
+        Ok(())
     }
 
     fn format_aggregator_final(f: &mut Formatter, plan: &AggregatorFinalPlan) -> fmt::Result {
@@ -203,15 +216,90 @@ impl<'a> PlanNodeIndentFormatDisplay<'a> {
         write!(f, "Create sub queries sets: [{}]", names.join(", "))
     }
 
-    fn format_read_source(f: &mut Formatter, plan: &ReadDataSourcePlan) -> fmt::Result {
+    fn format_read_source(
+        f: &mut Formatter,
+        plan: &ReadDataSourcePlan,
+        indent: usize,
+    ) -> fmt::Result {
         write!(
             f,
-            "ReadDataSource: scan partitions: [{}], scan schema: {}, statistics: [read_rows: {:?}, read_bytes: {:?}]",
+            "ReadDataSource: scan table: {}, scan partitions: [{}], scan schema: {}, statistics: [read_rows: {:?}, read_bytes: {:?}]",
+            plan.table_identifier(),
             plan.parts.len(),
             PlanNode::display_schema(plan.schema.as_ref()),
             plan.statistics.read_rows,
             plan.statistics.read_bytes,
-        )
+        )?;
+
+        if plan.remote {
+            Self::format_read_source_attributes(f, plan, indent + 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// The parts and the push downs of a remote source, one `name: value` per line.
+    fn format_read_source_attributes(
+        f: &mut Formatter,
+        plan: &ReadDataSourcePlan,
+        indent: usize,
+    ) -> fmt::Result {
+        let prefix = str::repeat("  ", indent);
+        // The parts and the statistics are all unknown if the source is not asked for them.
+        let known = |value: usize| match plan.partitions_total {
+            None => "unknown".to_string(),
+            Some(_) => value.to_string(),
+        };
+
+        let projection = plan
+            .schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect::<Vec<_>>();
+        let push_downs = &plan.scan_plan.push_downs;
+        let limit = match push_downs.limit {
+            None => "none".to_string(),
+            Some(limit) => limit.to_string(),
+        };
+
+        let total = plan.partitions_total.unwrap_or_default();
+        write!(f, "\n{}partitions_total: {}", prefix, known(total))?;
+        write!(
+            f,
+            "\n{}partitions_scanned: {}",
+            prefix,
+            known(plan.parts.len())
+        )?;
+        let pruned = total.saturating_sub(plan.parts.len());
+        let pruned_by_bloom = plan.partitions_pruned_by_bloom.min(pruned);
+        write!(
+            f,
+            "\n{}partitions_pruned_by_range: {}",
+            prefix,
+            known(pruned - pruned_by_bloom)
+        )?;
+        write!(
+            f,
+            "\n{}partitions_pruned_by_bloom: {}",
+            prefix,
+            known(pruned_by_bloom)
+        )?;
+        write!(
+            f,
+            "\n{}estimated_rows: {}",
+            prefix,
+            known(plan.statistics.read_rows)
+        )?;
+        write!(
+            f,
+            "\n{}estimated_bytes: {}",
+            prefix,
+            known(plan.statistics.read_bytes)
+        )?;
+        write!(f, "\n{}projection: [{}]", prefix, projection.join(", "))?;
+        write!(f, "\n{}filters: {:?}", prefix, push_downs.filters)?;
+        write!(f, "\n{}limit: {}", prefix, limit)
     }
 
     fn format_create_database(f: &mut Formatter, plan: &CreateDatabasePlan) -> fmt::Result {
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use common_datavalues::DataField;
 use common_datavalues::DataSchemaRefExt;
@@ -47,3 +48,85 @@ fn test_plan_display_indent() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_plan_display_indent_remote_read_source() -> Result<()> {
+    use pretty_assertions::assert_eq;
+
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", DataType::Int64, false),
+        DataField::new("b", DataType::Utf8, false),
+    ]);
+
+    let mut scan = ScanPlan::empty();
+    scan.push_downs.filters = vec![lit(1i64)];
+    scan.push_downs.limit = Some(10);
+
+    let mut plan = ReadDataSourcePlan {
+        db: "foo".to_string(),
+        table: "bar".to_string(),
+        table_id: 1,
+        table_version: None,
+        schema,
+        parts: vec![
+            Part {
+                name: "p1".to_string(),
+                version: 0,
+                read_info: None,
+            },
+            Part {
+                name: "p2".to_string(),
+                version: 0,
+                read_info: None,
+            },
+        ],
+        statistics: Statistics::new_estimated(20, 160),
+        description: "".to_string(),
+        scan_plan: Arc::new(scan),
+        remote: true,
+        partitions_total: Some(4),
+        partitions_pruned_by_bloom: 1,
+    };
+
+    assert_eq!(
+        vec![
+            "ReadDataSource: scan table: foo.bar, scan partitions: [2], scan schema: [a:Int64, b:Utf8], statistics: [read_rows: 20, read_bytes: 160]",
+            "  partitions_total: 4",
+            "  partitions_scanned: 2",
+            "  partitions_pruned_by_range: 1",
+            "  partitions_pruned_by_bloom: 1",
+            "  estimated_rows: 20",
+            "  estimated_bytes: 160",
+            "  projection: [a, b]",
+            "  filters: [1]",
+            "  limit: 10",
+        ],
+        format!("{:?}", PlanNode::ReadSource(plan.clone()))
+            .lines()
+            .collect::<Vec<_>>()
+    );
+
+    // The source is not asked for its parts.
+    plan.parts = vec![];
+    plan.statistics = Statistics::default();
+    plan.partitions_total = None;
+    assert_eq!(
+        vec![
+            "ReadDataSource: scan table: foo.bar, scan partitions: [0], scan schema: [a:Int64, b:Utf8], statistics: [read_rows: 0, read_bytes: 0]",
+            "  partitions_total: unknown",
+            "  partitions_scanned: unknown",
+            "  partitions_pruned_by_range: unknown",
+            "  partitions_pruned_by_bloom: unknown",
+            "  estimated_rows: unknown",
+            "  estimated_bytes: unknown",
+            "  projection: [a, b]",
+            "  filters: [1]",
+            "  limit: 10",
+        ],
+        format!("{:?}", PlanNode::ReadSource(plan))
+            .lines()
+            .collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
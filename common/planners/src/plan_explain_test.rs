@@ -38,7 +38,7 @@ fn test_explain_plan() -> Result<()> {
     Having: ((number + 1) = 4)\
     \n  Filter: ((number + 1) = 4)\
     \n    Projection: number as c1:UInt64, number as c2:UInt64\
-    \n      ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]";
+    \n      ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]";
     let actual = format!("{:?}", explain);
     assert_eq!(expect, actual);
     assert_eq!(explain.schema().fields().clone(), vec![DataField::new(
@@ -213,8 +213,40 @@ impl Expression {
         })
     }
 
-    // TODO
-    pub fn nullable(&self, _input_schema: &DataSchemaRef) -> Result<bool> {
+    // TODO: the nullability of the columns and literals
+    pub fn nullable(&self, input_schema: &DataSchemaRef) -> Result<bool> {
+        match self {
+            Expression::Alias(_, expr) => expr.nullable(input_schema),
+            Expression::Sort { expr, .. } => expr.nullable(input_schema),
+            Expression::BinaryExpression { op, left, right } => {
+                Self::function_nullable(op, &[left.as_ref(), right.as_ref()], input_schema)
+            }
+            Expression::UnaryExpression { op, expr } => {
+                Self::function_nullable(op, &[expr.as_ref()], input_schema)
+            }
+            Expression::ScalarFunction { op, args } => {
+                let args = args.iter().collect::<Vec<_>>();
+                Self::function_nullable(op, &args, input_schema)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// A function is nullable if it may return null itself, e.g. the integer division by zero,
+    /// or any of its arguments is nullable.
+    fn function_nullable(
+        op: &str,
+        args: &[&Expression],
+        input_schema: &DataSchemaRef,
+    ) -> Result<bool> {
+        if FunctionFactory::get(op)?.nullable(input_schema)? {
+            return Ok(true);
+        }
+        for arg in args {
+            if arg.nullable(input_schema)? {
+                return Ok(true);
+            }
+        }
         Ok(false)
     }
 
@@ -60,7 +60,7 @@ fn test_expression_plan() -> Result<()> {
         input: Arc::new(plan),
     });
     let expect ="Filter: (((((((number + 1) = 4) and (number != 4)) and (number < 4)) and (number <= 4)) and (number > 4)) and (not (number >= 4)))\
-    \n  ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]";
+    \n  ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]";
     let actual = format!("{:?}", explain);
     assert_eq!(expect, actual);
     Ok(())
@@ -121,3 +121,35 @@ fn test_expression_validate() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_expression_nullable() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", DataType::Int64, false),
+        DataField::new("b", DataType::Int64, false),
+    ]);
+
+    let tests = vec![
+        ("a", col("a"), false),
+        ("a + b", add(col("a"), col("b")), false),
+        ("a % b", modular(col("a"), col("b")), true),
+        (
+            "(a % b) + 1",
+            add(modular(col("a"), col("b")), lit(1)),
+            true,
+        ),
+        ("(a % b) as c", modular(col("a"), col("b")).alias("c"), true),
+        ("not(a % b)", not(modular(col("a"), col("b"))), true),
+    ];
+
+    for (desc, expression, expect) in tests {
+        assert_eq!(expression.nullable(&schema)?, expect, "{}", desc);
+        assert_eq!(
+            expression.to_data_field(&schema)?.is_nullable(),
+            expect,
+            "{}",
+            desc
+        );
+    }
+    Ok(())
+}
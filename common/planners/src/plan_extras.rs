@@ -23,6 +23,9 @@ pub struct Extras {
     pub filters: Vec<Expression>,
     /// Optional limit to skip read
     pub limit: Option<usize>,
+    /// Whether the blocks of the parts must be delivered in the order of the parts,
+    /// otherwise the sources deliver them as soon as they are read
+    pub ordered_parts: bool,
 }
 
 impl Extras {
@@ -31,6 +34,7 @@ impl Extras {
             projection: None,
             filters: vec![],
             limit: None,
+            ordered_parts: false,
         }
     }
 }
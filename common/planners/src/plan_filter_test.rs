@@ -30,7 +30,7 @@ fn test_filter_plan() -> Result<()> {
     let expect ="\
     Projection: number:UInt64\
     \n  Filter: (number = 1)\
-    \n    ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]";
+    \n    ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]";
     let actual = format!("{:?}", plan);
 
     assert_eq!(expect, actual);
@@ -19,6 +19,7 @@ use common_exception::ErrorCode;
 use common_exception::Result;
 
 use crate::plan_broadcast::BroadcastPlan;
+use crate::plan_semantic_hash::PlanCanonicalizer;
 use crate::plan_subqueries_set::SubQueriesSetPlan;
 use crate::AggregatorFinalPlan;
 use crate::AggregatorPartialPlan;
@@ -36,6 +37,8 @@ use crate::InsertIntoPlan;
 use crate::KillPlan;
 use crate::LimitByPlan;
 use crate::LimitPlan;
+use crate::ModifyColumnPlan;
+use crate::OptimizeTablePlan;
 use crate::ProjectionPlan;
 use crate::ReadDataSourcePlan;
 use crate::RemotePlan;
@@ -45,7 +48,9 @@ use crate::SettingPlan;
 use crate::ShowCreateTablePlan;
 use crate::SortPlan;
 use crate::StagePlan;
+use crate::TransactionPlan;
 use crate::TruncateTablePlan;
+use crate::UndropTablePlan;
 use crate::UseDatabasePlan;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq)]
@@ -72,13 +77,17 @@ pub enum PlanNode {
     CreateTable(CreateTablePlan),
     DescribeTable(DescribeTablePlan),
     DropTable(DropTablePlan),
+    UndropTable(UndropTablePlan),
     TruncateTable(TruncateTablePlan),
+    ModifyColumn(ModifyColumnPlan),
+    OptimizeTable(OptimizeTablePlan),
     UseDatabase(UseDatabasePlan),
     SetVariable(SettingPlan),
     InsertInto(InsertIntoPlan),
     ShowCreateTable(ShowCreateTablePlan),
     SubQueryExpression(SubQueriesSetPlan),
     Kill(KillPlan),
+    Transaction(TransactionPlan),
 }
 
 impl PlanNode {
@@ -105,8 +114,11 @@ impl PlanNode {
             PlanNode::DropDatabase(v) => v.schema(),
             PlanNode::CreateTable(v) => v.schema(),
             PlanNode::DropTable(v) => v.schema(),
+            PlanNode::UndropTable(v) => v.schema(),
             PlanNode::DescribeTable(v) => v.schema(),
             PlanNode::TruncateTable(v) => v.schema(),
+            PlanNode::ModifyColumn(v) => v.schema(),
+            PlanNode::OptimizeTable(v) => v.schema(),
             PlanNode::SetVariable(v) => v.schema(),
             PlanNode::Sort(v) => v.schema(),
             PlanNode::UseDatabase(v) => v.schema(),
@@ -114,6 +126,7 @@ impl PlanNode {
             PlanNode::ShowCreateTable(v) => v.schema(),
             PlanNode::SubQueryExpression(v) => v.schema(),
             PlanNode::Kill(v) => v.schema(),
+            PlanNode::Transaction(v) => v.schema(),
         }
     }
 
@@ -140,7 +153,10 @@ impl PlanNode {
             PlanNode::CreateTable(_) => "CreateTablePlan",
             PlanNode::DescribeTable(_) => "DescribeTablePlan",
             PlanNode::DropTable(_) => "DropTablePlan",
+            PlanNode::UndropTable(_) => "UndropTablePlan",
             PlanNode::TruncateTable(_) => "TruncateTablePlan",
+            PlanNode::ModifyColumn(_) => "ModifyColumnPlan",
+            PlanNode::OptimizeTable(_) => "OptimizeTablePlan",
             PlanNode::SetVariable(_) => "SetVariablePlan",
             PlanNode::Sort(_) => "SortPlan",
             PlanNode::UseDatabase(_) => "UseDatabasePlan",
@@ -148,6 +164,7 @@ impl PlanNode {
             PlanNode::ShowCreateTable(_) => "ShowCreateTablePlan",
             PlanNode::SubQueryExpression(_) => "CreateSubQueriesSets",
             PlanNode::Kill(_) => "KillQuery",
+            PlanNode::Transaction(_) => "TransactionPlan",
         }
     }
 
@@ -171,6 +188,13 @@ impl PlanNode {
         }
     }
 
+    /// Hash of the canonical form of the plan, equal for queries that only differ syntactically.
+    /// See `PlanCanonicalizer` for what is normalized.
+    pub fn semantic_hash(&self) -> Result<u64> {
+        let canonical = PlanCanonicalizer::canonicalize(self)?;
+        Ok(PlanCanonicalizer::hash(&canonical))
+    }
+
     pub fn input(&self, n: usize) -> Arc<PlanNode> {
         self.inputs()[n].clone()
     }
@@ -18,4 +18,43 @@ pub type Partitions = Vec<Part>;
 pub struct Part {
     pub name: String,
     pub version: u64,
+    /// How the part is read, recorded by the sources that keep it when the read is planned.
+    /// The read of the part then does not look it up again. `None` for the other sources.
+    #[serde(default)]
+    pub read_info: Option<PartReadInfo>,
+}
+
+/// The descriptor of a part of the store that its read needs.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct PartReadInfo {
+    /// The format the part is written in, `None` for parquet.
+    pub format: Option<String>,
+    pub storage: PartStorageClass,
+    /// The checksum of the file of the part, `None` for the inline parts.
+    pub checksum: Option<PartChecksum>,
+}
+
+/// The size and the digest of the file of a part, taken when the file is written, before it is
+/// moved into place. A read checks the size of the file, and optionally its digest.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct PartChecksum {
+    pub bytes: u64,
+    /// The sha256 of the file, in hex.
+    pub sha256: String,
+}
+
+/// Where the bytes of a part are kept.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PartStorageClass {
+    /// A file of the file system of the store, under the name of the part.
+    File,
+    /// A value of the meta store, under the name of the part. A part too small to be worth a
+    /// file is kept inline, until it is compacted with the other inline parts of its table.
+    Inline,
+}
+
+impl Default for PartStorageClass {
+    fn default() -> Self {
+        PartStorageClass::File
+    }
 }
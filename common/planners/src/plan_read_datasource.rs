@@ -23,6 +23,7 @@ use crate::Extras;
 use crate::Partitions;
 use crate::ScanPlan;
 use crate::Statistics;
+use crate::TableIdentifier;
 
 // TODO: Delete the scan plan field, but it depends on plan_parser:L394
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
@@ -37,6 +38,12 @@ pub struct ReadDataSourcePlan {
     pub description: String,
     pub scan_plan: Arc<ScanPlan>,
     pub remote: bool,
+    /// The parts of the table before pruning, set by the remote sources which are asked for their parts.
+    /// None if it is unknown, e.g. EXPLAIN does not contact the store.
+    pub partitions_total: Option<usize>,
+    /// The parts skipped by their bloom filters, the rest of the pruned parts are skipped by
+    /// the ranges of their columns.
+    pub partitions_pruned_by_bloom: usize,
 }
 
 impl ReadDataSourcePlan {
@@ -52,6 +59,8 @@ impl ReadDataSourcePlan {
             description: "".to_string(),
             scan_plan: Arc::new(ScanPlan::with_table_id(table_id, table_version)),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         }
     }
 
@@ -59,6 +68,11 @@ impl ReadDataSourcePlan {
         self.schema.clone()
     }
 
+    /// The qualified name of the table to read.
+    pub fn table_identifier(&self) -> TableIdentifier {
+        TableIdentifier::create(Some(self.db.clone()), &self.table)
+    }
+
     /// Get the push downs.
     pub fn get_push_downs(&self) -> Extras {
         self.scan_plan.push_downs.clone()
@@ -21,6 +21,9 @@ pub struct RemotePlan {
     pub stage_id: String,
     pub stream_id: String,
     pub fetch_nodes: Vec<String>,
+    /// Merge the blocks of the fetch nodes by their tags instead of as they arrive.
+    #[serde(default)]
+    pub preserve_order: bool,
 }
 
 impl RemotePlan {
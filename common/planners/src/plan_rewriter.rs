@@ -41,6 +41,8 @@ use crate::InsertIntoPlan;
 use crate::KillPlan;
 use crate::LimitByPlan;
 use crate::LimitPlan;
+use crate::ModifyColumnPlan;
+use crate::OptimizeTablePlan;
 use crate::PlanBuilder;
 use crate::PlanNode;
 use crate::ProjectionPlan;
@@ -52,7 +54,9 @@ use crate::SettingPlan;
 use crate::ShowCreateTablePlan;
 use crate::SortPlan;
 use crate::StagePlan;
+use crate::TransactionPlan;
 use crate::TruncateTablePlan;
+use crate::UndropTablePlan;
 use crate::UseDatabasePlan;
 
 /// `PlanRewriter` is a visitor that can help to rewrite `PlanNode`
@@ -100,12 +104,16 @@ pub trait PlanRewriter {
             PlanNode::Expression(plan) => self.rewrite_expression(plan),
             PlanNode::DescribeTable(plan) => self.rewrite_describe_table(plan),
             PlanNode::DropTable(plan) => self.rewrite_drop_table(plan),
+            PlanNode::UndropTable(plan) => self.rewrite_undrop_table(plan),
             PlanNode::DropDatabase(plan) => self.rewrite_drop_database(plan),
             PlanNode::InsertInto(plan) => self.rewrite_insert_into(plan),
             PlanNode::ShowCreateTable(plan) => self.rewrite_show_create_table(plan),
             PlanNode::SubQueryExpression(plan) => self.rewrite_sub_queries_sets(plan),
             PlanNode::TruncateTable(plan) => self.rewrite_truncate_table(plan),
+            PlanNode::ModifyColumn(plan) => self.rewrite_modify_column(plan),
+            PlanNode::OptimizeTable(plan) => self.rewrite_optimize_table(plan),
             PlanNode::Kill(plan) => self.rewrite_kill(plan),
+            PlanNode::Transaction(plan) => self.rewrite_transaction(plan),
         }
     }
 
@@ -344,13 +352,29 @@ pub trait PlanRewriter {
         Ok(PlanNode::ShowCreateTable(plan.clone()))
     }
 
+    fn rewrite_undrop_table(&mut self, plan: &UndropTablePlan) -> Result<PlanNode> {
+        Ok(PlanNode::UndropTable(plan.clone()))
+    }
+
     fn rewrite_truncate_table(&mut self, plan: &TruncateTablePlan) -> Result<PlanNode> {
         Ok(PlanNode::TruncateTable(plan.clone()))
     }
 
+    fn rewrite_modify_column(&mut self, plan: &ModifyColumnPlan) -> Result<PlanNode> {
+        Ok(PlanNode::ModifyColumn(plan.clone()))
+    }
+
+    fn rewrite_optimize_table(&mut self, plan: &OptimizeTablePlan) -> Result<PlanNode> {
+        Ok(PlanNode::OptimizeTable(plan.clone()))
+    }
+
     fn rewrite_kill(&mut self, plan: &KillPlan) -> Result<PlanNode> {
         Ok(PlanNode::Kill(plan.clone()))
     }
+
+    fn rewrite_transaction(&mut self, plan: &TransactionPlan) -> Result<PlanNode> {
+        Ok(PlanNode::Transaction(plan.clone()))
+    }
 }
 
 pub struct RewriteHelper {}
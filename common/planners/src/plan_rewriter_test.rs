@@ -190,6 +190,7 @@ impl PlanRewriter for DefaultRewriter {
             aggr_expr: plan.aggr_expr.clone(),
             group_expr: plan.group_expr.clone(),
             input: Arc::new(self.rewrite_plan_node(plan.input.as_ref())?),
+            top_n: plan.top_n.clone(),
         }))
     }
 
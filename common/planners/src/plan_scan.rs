@@ -30,7 +30,8 @@ pub struct ScanPlan {
     pub table_version: Option<MetaVersion>,
     // The schema of the source data
     pub table_schema: DataSchemaRef,
-    pub table_args: Option<Expression>,
+    // The arguments of a table function, e.g. `numbers(1, 10)`.
+    pub table_args: Option<Vec<Expression>>,
     pub projected_schema: DataSchemaRef,
     // Extras.
     pub push_downs: Extras,
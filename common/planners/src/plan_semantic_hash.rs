@@ -0,0 +1,547 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataValue;
+use common_exception::Result;
+
+use crate::plan_broadcast::BroadcastPlan;
+use crate::plan_subqueries_set::SubQueriesSetPlan;
+use crate::AggregatorFinalPlan;
+use crate::AggregatorPartialPlan;
+use crate::CreateDatabasePlan;
+use crate::CreateTablePlan;
+use crate::DescribeTablePlan;
+use crate::DropDatabasePlan;
+use crate::DropTablePlan;
+use crate::EmptyPlan;
+use crate::ExplainPlan;
+use crate::Expression;
+use crate::ExpressionPlan;
+use crate::Extras;
+use crate::FilterPlan;
+use crate::HavingPlan;
+use crate::InsertIntoPlan;
+use crate::KillPlan;
+use crate::LimitByPlan;
+use crate::LimitPlan;
+use crate::ModifyColumnPlan;
+use crate::OptimizeTablePlan;
+use crate::PlanNode;
+use crate::PlanVisitor;
+use crate::ProjectionPlan;
+use crate::ReadDataSourcePlan;
+use crate::RemotePlan;
+use crate::ScanPlan;
+use crate::SelectPlan;
+use crate::SettingPlan;
+use crate::ShowCreateTablePlan;
+use crate::SortPlan;
+use crate::StagePlan;
+use crate::TransactionPlan;
+use crate::TruncateTablePlan;
+use crate::UndropTablePlan;
+use crate::UseDatabasePlan;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Binary operators whose operands can be swapped without changing the result.
+const COMMUTATIVE_OPS: [&str; 7] = ["and", "or", "+", "*", "=", "!=", "<>"];
+/// Commutative operators whose nested chains are flattened, so `(a AND b) AND c` equals `a AND (b AND c)`.
+const ASSOCIATIVE_OPS: [&str; 2] = ["and", "or"];
+
+/// `PlanCanonicalizer` serializes a plan tree into a canonical text form, which is the input of
+/// `PlanNode::semantic_hash`.
+///
+/// Two plans that only differ syntactically produce the same canonical form:
+/// - identifiers (columns, aliases, databases, tables, function names) are case-folded,
+/// - the operands of commutative operators are sorted, and AND/OR chains are flattened,
+/// - literals are normalized by value rather than by their inferred width (`1i8` equals `1u64`),
+/// - generated names (subquery names, descriptions) and physical details (partitions, statistics) are skipped.
+///
+/// Session dependent values are already resolved by the planner, e.g. an unqualified table name is
+/// bound to the current database in `ReadDataSourcePlan::db`, so they are part of the canonical form.
+///
+/// The canonical form and the hash are stable across versions and processes, don't change them
+/// without bumping every persisted key that depends on them.
+pub struct PlanCanonicalizer {
+    buf: String,
+}
+
+impl PlanCanonicalizer {
+    pub fn create() -> Self {
+        PlanCanonicalizer { buf: String::new() }
+    }
+
+    pub fn canonicalize(plan: &PlanNode) -> Result<String> {
+        let mut canonicalizer = PlanCanonicalizer::create();
+        canonicalizer.visit_plan_node(plan)?;
+        Ok(canonicalizer.buf)
+    }
+
+    /// 64-bit FNV-1a of the canonical form.
+    pub fn hash(canonical: &str) -> u64 {
+        canonical.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    pub fn expr(expr: &Expression) -> Result<String> {
+        Ok(match expr {
+            Expression::Alias(alias, expr) => {
+                format!("alias({},{})", Self::ident(alias), Self::expr(expr)?)
+            }
+            Expression::Column(name) => format!("col({})", Self::ident(name)),
+            Expression::Literal { value, .. } => format!("lit({})", Self::value(value)),
+            Expression::UnaryExpression { op, expr } => {
+                format!("unary({},{})", Self::ident(op), Self::expr(expr)?)
+            }
+            Expression::BinaryExpression { left, op, right } => {
+                let op = Self::ident(op);
+                if COMMUTATIVE_OPS.contains(&op.as_str()) {
+                    let mut operands = vec![];
+                    Self::collect_operands(&op, left, &mut operands)?;
+                    Self::collect_operands(&op, right, &mut operands)?;
+                    operands.sort();
+                    format!("binary({},[{}])", op, operands.join(","))
+                } else {
+                    format!(
+                        "binary({},{},{})",
+                        op,
+                        Self::expr(left)?,
+                        Self::expr(right)?
+                    )
+                }
+            }
+            Expression::ScalarFunction { op, args } => {
+                format!("func({},{})", Self::ident(op), Self::exprs(args)?)
+            }
+            Expression::AggregateFunction {
+                op,
+                distinct,
+                params,
+                args,
+            } => format!(
+                "aggr({},{},[{}],{})",
+                Self::ident(op),
+                distinct,
+                params.iter().map(Self::value).collect::<Vec<_>>().join(","),
+                Self::exprs(args)?
+            ),
+            Expression::Sort {
+                expr,
+                asc,
+                nulls_first,
+            } => format!("sort({},{},{})", Self::expr(expr)?, asc, nulls_first),
+            Expression::Wildcard => "*".to_string(),
+            Expression::Cast { expr, data_type } => {
+                format!("cast({},{:?})", Self::expr(expr)?, data_type)
+            }
+            Expression::ScalarSubquery { query_plan, .. } => {
+                format!("scalar_subquery({})", Self::canonicalize(query_plan)?)
+            }
+            Expression::Subquery { query_plan, .. } => {
+                format!("subquery({})", Self::canonicalize(query_plan)?)
+            }
+        })
+    }
+
+    fn exprs(exprs: &[Expression]) -> Result<String> {
+        let exprs = exprs.iter().map(Self::expr).collect::<Result<Vec<_>>>()?;
+        Ok(format!("[{}]", exprs.join(",")))
+    }
+
+    fn collect_operands(op: &str, expr: &Expression, operands: &mut Vec<String>) -> Result<()> {
+        match expr {
+            Expression::BinaryExpression {
+                left,
+                op: child_op,
+                right,
+            } if ASSOCIATIVE_OPS.contains(&op) && Self::ident(child_op) == op => {
+                Self::collect_operands(op, left, operands)?;
+                Self::collect_operands(op, right, operands)
+            }
+            _ => {
+                operands.push(Self::expr(expr)?);
+                Ok(())
+            }
+        }
+    }
+
+    fn ident(ident: &str) -> String {
+        ident.to_lowercase()
+    }
+
+    fn value(value: &DataValue) -> String {
+        match value {
+            DataValue::Boolean(Some(v)) => format!("bool:{}", v),
+            DataValue::Int8(Some(v)) => format!("int:{}", v),
+            DataValue::Int16(Some(v)) => format!("int:{}", v),
+            DataValue::Int32(Some(v)) => format!("int:{}", v),
+            DataValue::Int64(Some(v)) => format!("int:{}", v),
+            DataValue::UInt8(Some(v)) => format!("int:{}", v),
+            DataValue::UInt16(Some(v)) => format!("int:{}", v),
+            DataValue::UInt32(Some(v)) => format!("int:{}", v),
+            DataValue::UInt64(Some(v)) => format!("int:{}", v),
+            DataValue::Float32(Some(v)) => format!("float:{:?}", *v as f64),
+            DataValue::Float64(Some(v)) => format!("float:{:?}", v),
+            DataValue::String(Some(v)) => {
+                format!("str:{}:{}", v.len(), String::from_utf8_lossy(v))
+            }
+            DataValue::List(Some(values), _) => format!(
+                "list:[{}]",
+                values.iter().map(Self::value).collect::<Vec<_>>().join(",")
+            ),
+            DataValue::Struct(values) => format!(
+                "struct:[{}]",
+                values.iter().map(Self::value).collect::<Vec<_>>().join(",")
+            ),
+            _ => "null".to_string(),
+        }
+    }
+
+    fn schema(schema: &DataSchemaRef) -> String {
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|f| {
+                format!(
+                    "{}:{:?}:{}",
+                    Self::ident(f.name()),
+                    f.data_type(),
+                    f.is_nullable()
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", fields.join(","))
+    }
+
+    fn extras(extras: &Extras) -> Result<String> {
+        let projection = match &extras.projection {
+            None => "none".to_string(),
+            Some(projection) => format!("{:?}", projection),
+        };
+
+        let mut filters = extras
+            .filters
+            .iter()
+            .map(Self::expr)
+            .collect::<Result<Vec<_>>>()?;
+        filters.sort();
+
+        let limit = match extras.limit {
+            None => "none".to_string(),
+            Some(limit) => limit.to_string(),
+        };
+
+        Ok(format!(
+            "extras({},[{}],{})",
+            projection,
+            filters.join(","),
+            limit
+        ))
+    }
+
+    fn table_args(table_args: &Option<Vec<Expression>>) -> Result<String> {
+        match table_args {
+            None => Ok("none".to_string()),
+            Some(args) => {
+                let args = args.iter().map(Self::expr).collect::<Result<Vec<_>>>()?;
+                Ok(args.join(","))
+            }
+        }
+    }
+
+    fn write_node(
+        &mut self,
+        name: &str,
+        fields: &[String],
+        input: Option<&Arc<PlanNode>>,
+    ) -> Result<()> {
+        self.buf.push_str(name);
+        self.buf.push('(');
+        self.buf.push_str(&fields.join(","));
+
+        if let Some(input) = input {
+            if !fields.is_empty() {
+                self.buf.push(',');
+            }
+            self.visit_plan_node(input.as_ref())?;
+        }
+
+        self.buf.push(')');
+        Ok(())
+    }
+}
+
+impl PlanVisitor for PlanCanonicalizer {
+    fn visit_aggregate_partial(&mut self, plan: &AggregatorPartialPlan) -> Result<()> {
+        let fields = [
+            Self::exprs(&plan.group_expr)?,
+            Self::exprs(&plan.aggr_expr)?,
+        ];
+        self.write_node("aggregate_partial", &fields, Some(&plan.input))
+    }
+
+    fn visit_aggregate_final(&mut self, plan: &AggregatorFinalPlan) -> Result<()> {
+        let fields = [
+            Self::exprs(&plan.group_expr)?,
+            Self::exprs(&plan.aggr_expr)?,
+        ];
+        self.write_node("aggregate_final", &fields, Some(&plan.input))
+    }
+
+    fn visit_empty(&mut self, _: &EmptyPlan) -> Result<()> {
+        self.write_node("empty", &[], None)
+    }
+
+    fn visit_stage(&mut self, plan: &StagePlan) -> Result<()> {
+        let fields = [
+            format!("{:?}", plan.kind).to_lowercase(),
+            Self::expr(&plan.scatters_expr)?,
+        ];
+        self.write_node("stage", &fields, Some(&plan.input))
+    }
+
+    fn visit_broadcast(&mut self, plan: &BroadcastPlan) -> Result<()> {
+        self.write_node("broadcast", &[], Some(&plan.input))
+    }
+
+    fn visit_remote(&mut self, plan: &RemotePlan) -> Result<()> {
+        let fields = [
+            plan.query_id.clone(),
+            plan.stage_id.clone(),
+            plan.stream_id.clone(),
+            plan.preserve_order.to_string(),
+        ];
+        self.write_node("remote", &fields, None)
+    }
+
+    fn visit_projection(&mut self, plan: &ProjectionPlan) -> Result<()> {
+        let fields = [Self::exprs(&plan.expr)?];
+        self.write_node("projection", &fields, Some(&plan.input))
+    }
+
+    fn visit_expression(&mut self, plan: &ExpressionPlan) -> Result<()> {
+        let fields = [Self::exprs(&plan.exprs)?];
+        self.write_node("expression", &fields, Some(&plan.input))
+    }
+
+    fn visit_sub_queries_sets(&mut self, plan: &SubQueriesSetPlan) -> Result<()> {
+        let fields = [Self::exprs(&plan.expressions)?];
+        self.write_node("subqueries_set", &fields, Some(&plan.input))
+    }
+
+    fn visit_filter(&mut self, plan: &FilterPlan) -> Result<()> {
+        let fields = [Self::expr(&plan.predicate)?];
+        self.write_node("filter", &fields, Some(&plan.input))
+    }
+
+    fn visit_having(&mut self, plan: &HavingPlan) -> Result<()> {
+        let fields = [Self::expr(&plan.predicate)?];
+        self.write_node("having", &fields, Some(&plan.input))
+    }
+
+    fn visit_sort(&mut self, plan: &SortPlan) -> Result<()> {
+        let fields = [Self::exprs(&plan.order_by)?];
+        self.write_node("sort", &fields, Some(&plan.input))
+    }
+
+    fn visit_limit(&mut self, plan: &LimitPlan) -> Result<()> {
+        let limit = match plan.n {
+            None => "none".to_string(),
+            Some(n) => n.to_string(),
+        };
+        let fields = [limit, plan.offset.to_string()];
+        self.write_node("limit", &fields, Some(&plan.input))
+    }
+
+    fn visit_limit_by(&mut self, plan: &LimitByPlan) -> Result<()> {
+        let fields = [plan.limit.to_string(), Self::exprs(&plan.limit_by)?];
+        self.write_node("limit_by", &fields, Some(&plan.input))
+    }
+
+    fn visit_scan(&mut self, plan: &ScanPlan) -> Result<()> {
+        let fields = [
+            Self::ident(&plan.schema_name),
+            Self::table_args(&plan.table_args)?,
+            Self::extras(&plan.push_downs)?,
+        ];
+        self.write_node("scan", &fields, None)
+    }
+
+    fn visit_read_data_source(&mut self, plan: &ReadDataSourcePlan) -> Result<()> {
+        let fields = [
+            format!("{}.{}", Self::ident(&plan.db), Self::ident(&plan.table)),
+            Self::table_args(&plan.scan_plan.table_args)?,
+            Self::extras(&plan.scan_plan.push_downs)?,
+        ];
+        self.write_node("read_source", &fields, None)
+    }
+
+    fn visit_select(&mut self, plan: &SelectPlan) -> Result<()> {
+        self.write_node("select", &[], Some(&plan.input))
+    }
+
+    fn visit_explain(&mut self, plan: &ExplainPlan) -> Result<()> {
+        let fields = [format!("{:?}", plan.typ).to_lowercase()];
+        self.write_node("explain", &fields, Some(&plan.input))
+    }
+
+    fn visit_create_database(&mut self, plan: &CreateDatabasePlan) -> Result<()> {
+        let mut options = plan
+            .options
+            .iter()
+            .map(|(k, v)| format!("{}={}", Self::ident(k), v))
+            .collect::<Vec<_>>();
+        options.sort();
+
+        let fields = [
+            Self::ident(&plan.db),
+            plan.if_not_exists.to_string(),
+            Self::ident(&plan.engine),
+            format!("[{}]", options.join(",")),
+        ];
+        self.write_node("create_database", &fields, None)
+    }
+
+    fn visit_drop_database(&mut self, plan: &DropDatabasePlan) -> Result<()> {
+        let fields = [Self::ident(&plan.db), plan.if_exists.to_string()];
+        self.write_node("drop_database", &fields, None)
+    }
+
+    fn visit_create_table(&mut self, plan: &CreateTablePlan) -> Result<()> {
+        let mut options = plan
+            .options
+            .iter()
+            .map(|(k, v)| format!("{}={}", Self::ident(k), v))
+            .collect::<Vec<_>>();
+        options.sort();
+
+        let fields = [
+            format!("{}.{}", Self::ident(&plan.db), Self::ident(&plan.table)),
+            plan.if_not_exists.to_string(),
+            Self::schema(&plan.schema),
+            Self::ident(&plan.engine),
+            format!("[{}]", options.join(",")),
+        ];
+        self.write_node("create_table", &fields, None)
+    }
+
+    fn visit_describe_table(&mut self, plan: &DescribeTablePlan) -> Result<()> {
+        let fields = [format!(
+            "{}.{}",
+            Self::ident(&plan.db),
+            Self::ident(&plan.table)
+        )];
+        self.write_node("describe_table", &fields, None)
+    }
+
+    fn visit_drop_table(&mut self, plan: &DropTablePlan) -> Result<()> {
+        let fields = [
+            format!("{}.{}", Self::ident(&plan.db), Self::ident(&plan.table)),
+            plan.if_exists.to_string(),
+        ];
+        self.write_node("drop_table", &fields, None)
+    }
+
+    fn visit_use_database(&mut self, plan: &UseDatabasePlan) -> Result<()> {
+        let fields = [Self::ident(&plan.db)];
+        self.write_node("use_database", &fields, None)
+    }
+
+    fn visit_set_variable(&mut self, plan: &SettingPlan) -> Result<()> {
+        let fields = plan
+            .vars
+            .iter()
+            .map(|var| format!("{}={}", Self::ident(&var.variable), var.value))
+            .collect::<Vec<_>>();
+        self.write_node("set_variable", &fields, None)
+    }
+
+    // The inserted rows arrive on a stream, only the target is known at planning time.
+    fn visit_insert_into(&mut self, plan: &InsertIntoPlan) -> Result<()> {
+        let fields = [
+            format!(
+                "{}.{}",
+                Self::ident(&plan.db_name),
+                Self::ident(&plan.tbl_name)
+            ),
+            Self::schema(&plan.schema),
+        ];
+        self.write_node("insert_into", &fields, None)
+    }
+
+    fn visit_show_create_table(&mut self, plan: &ShowCreateTablePlan) -> Result<()> {
+        let fields = [format!(
+            "{}.{}",
+            Self::ident(&plan.db),
+            Self::ident(&plan.table)
+        )];
+        self.write_node("show_create_table", &fields, None)
+    }
+
+    fn visit_undrop_table(&mut self, plan: &UndropTablePlan) -> Result<()> {
+        let fields = [format!(
+            "{}.{}",
+            Self::ident(&plan.db),
+            Self::ident(&plan.table)
+        )];
+        self.write_node("undrop_table", &fields, None)
+    }
+
+    fn visit_truncate_table(&mut self, plan: &TruncateTablePlan) -> Result<()> {
+        let fields = [format!(
+            "{}.{}",
+            Self::ident(&plan.db),
+            Self::ident(&plan.table)
+        )];
+        self.write_node("truncate_table", &fields, None)
+    }
+
+    fn visit_optimize_table(&mut self, plan: &OptimizeTablePlan) -> Result<()> {
+        let fields = [format!(
+            "{}.{}",
+            Self::ident(&plan.db),
+            Self::ident(&plan.table)
+        )];
+        self.write_node("optimize_table", &fields, None)
+    }
+
+    fn visit_modify_column(&mut self, plan: &ModifyColumnPlan) -> Result<()> {
+        let fields = [
+            format!("{}.{}", Self::ident(&plan.db), Self::ident(&plan.table)),
+            format!(
+                "{}:{:?}:{}",
+                Self::ident(plan.column.name()),
+                plan.column.data_type(),
+                plan.column.is_nullable()
+            ),
+        ];
+        self.write_node("modify_column", &fields, None)
+    }
+
+    fn visit_kill_query(&mut self, plan: &KillPlan) -> Result<()> {
+        let fields = [plan.id.clone(), plan.kill_connection.to_string()];
+        self.write_node("kill", &fields, None)
+    }
+
+    fn visit_transaction(&mut self, plan: &TransactionPlan) -> Result<()> {
+        let fields = [format!("{:?}", plan.kind).to_lowercase()];
+        self.write_node("transaction", &fields, None)
+    }
+}
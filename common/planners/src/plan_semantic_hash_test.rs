@@ -0,0 +1,172 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_exception::Result;
+
+use crate::test::Test;
+use crate::*;
+
+fn source_plan(db: &str) -> Result<PlanNode> {
+    match Test::create().generate_source_plan_for_test(10000)? {
+        PlanNode::ReadSource(plan) => Ok(PlanNode::ReadSource(ReadDataSourcePlan {
+            db: db.to_string(),
+            ..plan
+        })),
+        other => Ok(other),
+    }
+}
+
+fn filter_plan(source: &PlanNode, predicate: Expression) -> Result<PlanNode> {
+    PlanBuilder::from(source)
+        .filter(predicate)?
+        .project(&[col("number")])?
+        .build()
+}
+
+#[test]
+fn test_plan_semantic_hash_golden() -> Result<()> {
+    use pretty_assertions::assert_eq;
+
+    // FNV-1a 64 reference vectors.
+    assert_eq!(0xcbf29ce484222325, PlanCanonicalizer::hash(""));
+    assert_eq!(0xaf63dc4c8601ec8c, PlanCanonicalizer::hash("a"));
+
+    let source = source_plan("system")?;
+    let plan = filter_plan(&source, col("number").eq(lit(1i64)))?;
+    assert_eq!(
+        "projection([col(number)],filter(binary(=,[col(number),lit(int:1)]),read_source(system.numbers_mt,none,extras(none,[],none))))",
+        PlanCanonicalizer::canonicalize(&plan)?
+    );
+    assert_eq!(0x4e7bda0af853edb1, plan.semantic_hash()?);
+
+    let plan = filter_plan(
+        &source,
+        col("number")
+            .gt(lit(1i64))
+            .and(col("number").lt(lit(10i64))),
+    )?;
+    assert_eq!(
+        "projection([col(number)],filter(binary(and,[binary(<,col(number),lit(int:10)),binary(>,col(number),lit(int:1))]),read_source(system.numbers_mt,none,extras(none,[],none))))",
+        PlanCanonicalizer::canonicalize(&plan)?
+    );
+    assert_eq!(0x411e6dfb2b588503, plan.semantic_hash()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_plan_semantic_hash_equal() -> Result<()> {
+    let source = source_plan("system")?;
+
+    struct Case {
+        name: &'static str,
+        left: Expression,
+        right: Expression,
+    }
+
+    let tests = vec![
+        Case {
+            name: "reordered-and-terms",
+            left: col("number")
+                .gt(lit(1i64))
+                .and(col("number").lt(lit(10i64)))
+                .and(col("number").not_eq(lit(5i64))),
+            right: col("number").not_eq(lit(5i64)).and(
+                col("number")
+                    .lt(lit(10i64))
+                    .and(col("number").gt(lit(1i64))),
+            ),
+        },
+        Case {
+            name: "commutative-operands",
+            left: col("number").eq(lit(1i64)),
+            right: lit(1i64).eq(col("number")),
+        },
+        Case {
+            name: "case-folded-identifiers",
+            left: col("number").eq(lit(1i64)),
+            right: Expression::BinaryExpression {
+                left: Box::new(col("NUMBER")),
+                op: "=".to_string(),
+                right: Box::new(lit(1i64)),
+            },
+        },
+        Case {
+            name: "literal-width",
+            left: col("number").eq(lit(1u8)),
+            right: col("number").eq(lit(1i64)),
+        },
+    ];
+
+    for test in tests {
+        let left = filter_plan(&source, test.left)?;
+        let right = filter_plan(&source, test.right)?;
+        assert_eq!(
+            left.semantic_hash()?,
+            right.semantic_hash()?,
+            "{}",
+            test.name
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_plan_semantic_hash_differs() -> Result<()> {
+    let source = source_plan("system")?;
+
+    // Different literal.
+    let left = filter_plan(&source, col("number").eq(lit(1i64)))?;
+    let right = filter_plan(&source, col("number").eq(lit(2i64)))?;
+    assert_ne!(left.semantic_hash()?, right.semantic_hash()?);
+
+    // Integer and float literals are different values.
+    let right = filter_plan(&source, col("number").eq(lit(1f64)))?;
+    assert_ne!(left.semantic_hash()?, right.semantic_hash()?);
+
+    // Non-commutative operands keep their order.
+    let left = filter_plan(&source, col("number").gt(lit(1i64)))?;
+    let right = filter_plan(&source, lit(1i64).gt(col("number")))?;
+    assert_ne!(left.semantic_hash()?, right.semantic_hash()?);
+
+    // The same table name resolved in another database.
+    let other_source = source_plan("default")?;
+    let left = filter_plan(&source, col("number").eq(lit(1i64)))?;
+    let right = filter_plan(&other_source, col("number").eq(lit(1i64)))?;
+    assert_ne!(left.semantic_hash()?, right.semantic_hash()?);
+
+    // Push downs change the result.
+    let read_source = match &source {
+        PlanNode::ReadSource(plan) => plan.clone(),
+        _ => unreachable!(),
+    };
+    let pushed = PlanNode::ReadSource(ReadDataSourcePlan {
+        scan_plan: Arc::new(ScanPlan {
+            push_downs: Extras {
+                projection: None,
+                filters: vec![],
+                limit: Some(1),
+                ordered_parts: false,
+            },
+            ..ScanPlan::empty()
+        }),
+        ..read_source
+    });
+    assert_ne!(source.semantic_hash()?, pushed.semantic_hash()?);
+
+    Ok(())
+}
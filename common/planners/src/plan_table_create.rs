@@ -15,9 +15,67 @@
 use std::collections::HashMap;
 
 use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+use common_exception::Result;
 
+/// The table options, keyed by the option names as they are written.
+/// The names are matched case-insensitively, use `get_table_option()` to look up an option.
 pub type TableOptions = HashMap<String, String>;
 
+/// Folds an option name or a setting name for matching.
+/// The planner and the store fold the names the same way,
+/// so that a plan accepted by the planner is not rejected by the store.
+pub fn fold_option_name(name: &str) -> String {
+    name.to_lowercase()
+}
+
+/// Returns the value of a table option, the name is matched case-insensitively.
+pub fn get_table_option<'a>(options: &'a TableOptions, name: &str) -> Option<&'a String> {
+    let folded = fold_option_name(name);
+    options
+        .iter()
+        .find(|(k, _)| fold_option_name(k) == folded)
+        .map(|(_, v)| v)
+}
+
+/// Adds a table option, keeping the name as it is.
+/// It is an error if there is already an option whose name differs only in case.
+pub fn insert_table_option(
+    options: &mut TableOptions,
+    name: impl Into<String>,
+    value: impl Into<String>,
+) -> Result<()> {
+    let name = name.into();
+    let folded = fold_option_name(&name);
+    if let Some(exist) = options.keys().find(|k| fold_option_name(k) == folded) {
+        return Err(duplicated_table_option(exist, &name));
+    }
+
+    options.insert(name, value.into());
+    Ok(())
+}
+
+/// Checks that no two table options have the same name after folding.
+pub fn check_table_options(options: &TableOptions) -> Result<()> {
+    let mut names = options.keys().collect::<Vec<_>>();
+    names.sort();
+
+    let mut seen: HashMap<String, &String> = HashMap::new();
+    for name in names {
+        if let Some(exist) = seen.insert(fold_option_name(name), name) {
+            return Err(duplicated_table_option(exist, name));
+        }
+    }
+    Ok(())
+}
+
+fn duplicated_table_option(a: &str, b: &str) -> ErrorCode {
+    ErrorCode::BadOption(format!(
+        "Duplicated table option: {:?} and {:?}, option names are case-insensitive",
+        a, b
+    ))
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 pub struct CreateTablePlan {
     pub if_not_exists: bool,
@@ -0,0 +1,60 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::Result;
+use pretty_assertions::assert_eq;
+
+use crate::*;
+
+#[test]
+fn test_table_options() -> Result<()> {
+    let mut options = TableOptions::new();
+    insert_table_option(&mut options, "Location", "/data/a.csv")?;
+    insert_table_option(&mut options, "COMPRESSION", "lz4")?;
+
+    // the names are kept as they are written
+    let mut names = options.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(vec!["COMPRESSION", "Location"], names);
+
+    // and looked up case-insensitively
+    for name in ["location", "LOCATION", "Location"] {
+        assert_eq!(
+            Some(&"/data/a.csv".to_string()),
+            get_table_option(&options, name)
+        );
+    }
+    assert_eq!(None, get_table_option(&options, "has_header"));
+    check_table_options(&options)?;
+
+    // the same name in another case is rejected
+    let dup = insert_table_option(&mut options, "compression", "zstd");
+    assert_eq!(
+        "Code: 22, displayText = Duplicated table option: \"COMPRESSION\" and \"compression\", option names are case-insensitive.",
+        dup.unwrap_err().to_string()
+    );
+    assert_eq!(
+        Some(&"lz4".to_string()),
+        get_table_option(&options, "compression")
+    );
+
+    // e.g. a plan built by another client
+    options.insert("compression".to_string(), "zstd".to_string());
+    assert_eq!(
+        "Code: 22, displayText = Duplicated table option: \"COMPRESSION\" and \"compression\", option names are case-insensitive.",
+        check_table_options(&options).unwrap_err().to_string()
+    );
+
+    Ok(())
+}
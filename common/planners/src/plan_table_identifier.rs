@@ -0,0 +1,82 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+/// A table referenced by a statement, `db.table` or just `table`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TableIdentifier {
+    /// `None` until resolved if the statement does not qualify the table.
+    pub db: Option<String>,
+    pub table: String,
+}
+
+impl TableIdentifier {
+    pub fn create(db: Option<String>, table: impl Into<String>) -> Self {
+        TableIdentifier {
+            db,
+            table: table.into(),
+        }
+    }
+
+    /// Build from the parts of a name in a statement, at most `db.table`.
+    pub fn try_from_parts(parts: &[String]) -> Result<Self> {
+        match parts {
+            [table] => Ok(Self::create(None, table)),
+            [db, table] => Ok(Self::create(Some(db.clone()), table)),
+            _ => Err(ErrorCode::SyntaxException(format!(
+                "Invalid table name '{}', expect [database.]table",
+                parts.join(".")
+            ))),
+        }
+    }
+
+    /// The identifier qualified by `current_db` if the statement does not qualify it.
+    pub fn resolve(&self, current_db: &str) -> Self {
+        match &self.db {
+            Some(_) => self.clone(),
+            None => Self::create(Some(current_db.to_string()), &self.table),
+        }
+    }
+
+    /// The database of a resolved identifier.
+    pub fn db(&self) -> Result<&str> {
+        self.db.as_deref().ok_or_else(|| {
+            ErrorCode::LogicalError(format!("Table '{}' is not resolved", self.table))
+        })
+    }
+
+    /// The database and the table of a resolved identifier.
+    pub fn into_parts(self) -> Result<(String, String)> {
+        let db = self.db()?.to_string();
+        Ok((db, self.table))
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.db.is_some()
+    }
+}
+
+impl fmt::Display for TableIdentifier {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.db {
+            Some(db) => write!(f, "{}.{}", db, self.table),
+            None => write!(f, "{}", self.table),
+        }
+    }
+}
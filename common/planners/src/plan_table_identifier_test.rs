@@ -0,0 +1,45 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::Result;
+use pretty_assertions::assert_eq;
+
+use crate::*;
+
+#[test]
+fn test_table_identifier() -> Result<()> {
+    let parts = |name: &str| name.split('.').map(|x| x.to_string()).collect::<Vec<_>>();
+
+    let unqualified = TableIdentifier::try_from_parts(&parts("t1"))?;
+    assert!(!unqualified.is_resolved());
+    assert!(unqualified.db().is_err());
+    assert_eq!("t1", unqualified.to_string());
+
+    // the current database only fills a missing qualifier
+    let resolved = unqualified.resolve("db3");
+    assert_eq!("db3", resolved.db()?);
+    assert_eq!("db3.t1", resolved.to_string());
+
+    let qualified = TableIdentifier::try_from_parts(&parts("db1.t1"))?;
+    assert_eq!(qualified, qualified.resolve("db3"));
+    assert_eq!("db1.t1", qualified.resolve("db3").to_string());
+
+    let ambiguous = TableIdentifier::try_from_parts(&parts("c.db1.t1"));
+    assert_eq!(
+        "Code: 5, displayText = Invalid table name 'c.db1.t1', expect [database.]table.",
+        ambiguous.unwrap_err().to_string()
+    );
+    assert!(TableIdentifier::try_from_parts(&[]).is_err());
+    Ok(())
+}
@@ -0,0 +1,39 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataType;
+
+/// Compacts the small parts of a table into parts of the target size.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct OptimizeTablePlan {
+    pub db: String,
+    /// The table name
+    pub table: String,
+}
+
+impl OptimizeTablePlan {
+    /// The result is a row of the parts before and after, the bytes rewritten and whether
+    /// the run has compacted every part it could, or is stopped by the max bytes per run.
+    pub fn schema(&self) -> DataSchemaRef {
+        DataSchemaRefExt::create(vec![
+            DataField::new("parts_before", DataType::UInt64, false),
+            DataField::new("parts_after", DataType::UInt64, false),
+            DataField::new("bytes_rewritten", DataType::UInt64, false),
+            DataField::new("finished", DataType::Boolean, false),
+        ])
+    }
+}
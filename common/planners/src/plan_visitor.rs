@@ -33,6 +33,8 @@ use crate::InsertIntoPlan;
 use crate::KillPlan;
 use crate::LimitByPlan;
 use crate::LimitPlan;
+use crate::ModifyColumnPlan;
+use crate::OptimizeTablePlan;
 use crate::PlanNode;
 use crate::ProjectionPlan;
 use crate::ReadDataSourcePlan;
@@ -43,7 +45,9 @@ use crate::SettingPlan;
 use crate::ShowCreateTablePlan;
 use crate::SortPlan;
 use crate::StagePlan;
+use crate::TransactionPlan;
 use crate::TruncateTablePlan;
+use crate::UndropTablePlan;
 use crate::UseDatabasePlan;
 
 /// `PlanVisitor` implements visitor pattern(reference [syn](https://docs.rs/syn/1.0.72/syn/visit/trait.Visit.html)) for `PlanNode`.
@@ -106,8 +110,11 @@ pub trait PlanVisitor {
             PlanNode::DropDatabase(plan) => self.visit_drop_database(plan),
             PlanNode::CreateTable(plan) => self.visit_create_table(plan),
             PlanNode::DropTable(plan) => self.visit_drop_table(plan),
+            PlanNode::UndropTable(plan) => self.visit_undrop_table(plan),
             PlanNode::DescribeTable(plan) => self.visit_describe_table(plan),
             PlanNode::TruncateTable(plan) => self.visit_truncate_table(plan),
+            PlanNode::ModifyColumn(plan) => self.visit_modify_column(plan),
+            PlanNode::OptimizeTable(plan) => self.visit_optimize_table(plan),
             PlanNode::UseDatabase(plan) => self.visit_use_database(plan),
             PlanNode::SetVariable(plan) => self.visit_set_variable(plan),
             PlanNode::Stage(plan) => self.visit_stage(plan),
@@ -119,6 +126,7 @@ pub trait PlanVisitor {
             PlanNode::ShowCreateTable(plan) => self.visit_show_create_table(plan),
             PlanNode::SubQueryExpression(plan) => self.visit_sub_queries_sets(plan),
             PlanNode::Kill(plan) => self.visit_kill_query(plan),
+            PlanNode::Transaction(plan) => self.visit_transaction(plan),
         }
     }
 
@@ -266,11 +274,27 @@ pub trait PlanVisitor {
         Ok(())
     }
 
+    fn visit_undrop_table(&mut self, _: &UndropTablePlan) -> Result<()> {
+        Ok(())
+    }
+
     fn visit_truncate_table(&mut self, _: &TruncateTablePlan) -> Result<()> {
         Ok(())
     }
 
+    fn visit_modify_column(&mut self, _: &ModifyColumnPlan) -> Result<()> {
+        Ok(())
+    }
+
+    fn visit_optimize_table(&mut self, _: &OptimizeTablePlan) -> Result<()> {
+        Ok(())
+    }
+
     fn visit_kill_query(&mut self, _: &KillPlan) -> Result<()> {
         Ok(())
     }
+
+    fn visit_transaction(&mut self, _: &TransactionPlan) -> Result<()> {
+        Ok(())
+    }
 }
@@ -57,6 +57,8 @@ impl Test {
             ),
             scan_plan: Arc::new(ScanPlan::empty()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         }))
     }
 
@@ -69,6 +71,7 @@ impl Test {
             partitions.push(Part {
                 name: format!("{}-{}-{}", total, 0, total,),
                 version: 0,
+                read_info: None,
             })
         } else {
             for part in 0..workers {
@@ -80,6 +83,7 @@ impl Test {
                 partitions.push(Part {
                     name: format!("{}-{}-{}", total, part_begin, part_end,),
                     version: 0,
+                    read_info: None,
                 })
             }
         }
@@ -0,0 +1,178 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use tokio::sync::watch;
+
+pub type ClockSleep = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Clock is the source of time of the time dependent components,
+/// e.g., the expiry of kv records and dropped tables, the idle channels and the periodic tasks.
+///
+/// It is injected through the config or the constructor of a component,
+/// so that a test drives the time with a `VirtualClock` instead of sleeping.
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// The wall clock time.
+    fn now(&self) -> SystemTime;
+
+    /// The monotonic time, for measuring a duration.
+    fn instant(&self) -> Instant;
+
+    /// Completes after `duration` passes on this clock.
+    fn sleep(&self, duration: Duration) -> ClockSleep;
+
+    /// Seconds since the unix epoch.
+    fn now_secs(&self) -> u64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    /// Milliseconds since the unix epoch.
+    fn now_millis(&self) -> u64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+}
+
+/// The production clock, backed by `SystemTime`, `Instant` and the tokio timer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> ClockSleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock for tests that only moves when it is advanced.
+///
+/// It starts at the current real time, so that a wall clock time, e.g. an `expire_at`,
+/// built outside of it still makes sense. The clones share the same time.
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    start: SystemTime,
+    start_instant: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+
+    /// Wakes up the sleepers when the clock is advanced.
+    tx: Arc<watch::Sender<Duration>>,
+    rx: watch::Receiver<Duration>,
+}
+
+impl VirtualClock {
+    pub fn create() -> VirtualClock {
+        let (tx, rx) = watch::channel(Duration::default());
+        VirtualClock {
+            start: SystemTime::now(),
+            start_instant: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::default())),
+            tx: Arc::new(tx),
+            rx,
+        }
+    }
+
+    /// Moves the clock forward by `duration` and wakes up the sleepers whose deadline passes.
+    pub fn advance(&self, duration: Duration) {
+        let mut elapsed = self.elapsed.lock().unwrap();
+        *elapsed += duration;
+        // There is always a receiver: `self.rx`.
+        let _ = self.tx.send(*elapsed);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.start_instant + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> ClockSleep {
+        let deadline = self.elapsed() + duration;
+        let mut rx = self.rx.clone();
+        Box::pin(async move {
+            while *rx.borrow() < deadline {
+                if rx.changed().await.is_err() {
+                    return;
+                }
+            }
+        })
+    }
+}
+
+/// A shared `Clock` that can be a field of a config: it is not part of the config value,
+/// it is skipped by serde and structopt, and does not affect the equality of configs.
+/// It defaults to the `SystemClock`.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn create(clock: impl Clock + 'static) -> SharedClock {
+        SharedClock(Arc::new(clock))
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        SharedClock::create(SystemClock)
+    }
+}
+
+impl Deref for SharedClock {
+    type Target = dyn Clock;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for SharedClock {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use common_exception::Result;
+
+use crate::*;
+
+/// A time dependent check written against the trait, as the components use a clock.
+fn is_expired<C: Clock + ?Sized>(clock: &C, expire_at: u64) -> bool {
+    expire_at < clock.now_secs()
+}
+
+#[test]
+fn test_virtual_clock() -> Result<()> {
+    let clock = VirtualClock::create();
+    let now = clock.now();
+    let instant = clock.instant();
+
+    assert_eq!(now, clock.now(), "a virtual clock does not move by itself");
+    assert_eq!(instant, clock.instant());
+
+    clock.clone().advance(Duration::from_secs(10));
+    assert_eq!(
+        Duration::from_secs(10),
+        clock.elapsed(),
+        "clones share the time"
+    );
+    assert_eq!(now + Duration::from_secs(10), clock.now());
+    assert_eq!(instant + Duration::from_secs(10), clock.instant());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_virtual_clock_sleep() -> Result<()> {
+    let clock = VirtualClock::create();
+    let started = Instant::now();
+
+    let mut sleep = tokio::spawn(clock.sleep(Duration::from_secs(60)));
+
+    clock.advance(Duration::from_secs(30));
+    let pending = tokio::time::timeout(Duration::from_millis(100), &mut sleep).await;
+    assert!(pending.is_err(), "30 of 60 seconds passed");
+
+    clock.advance(Duration::from_secs(30));
+    sleep.await.unwrap();
+
+    // Completed immediately if the duration already passed.
+    clock.sleep(Duration::from_secs(0)).await;
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_clocks_are_interchangeable() -> Result<()> {
+    let virtual_clock = VirtualClock::create();
+    let expire_at = virtual_clock.now_secs() + 10;
+
+    // Generic and trait object injection of either clock.
+    let clocks: Vec<SharedClock> = vec![
+        SharedClock::default(),
+        SharedClock::create(SystemClock),
+        SharedClock::create(virtual_clock.clone()),
+    ];
+    for clock in clocks.iter() {
+        let dyn_clock: &dyn Clock = &**clock;
+        assert!(!is_expired(dyn_clock, expire_at), "{:?}", clock);
+    }
+    assert!(!is_expired(&SystemClock, expire_at));
+    assert!(!is_expired(&virtual_clock, expire_at));
+
+    // Only the virtual one is moved.
+    virtual_clock.advance(Duration::from_secs(20));
+    assert!(is_expired(&*clocks[2], expire_at));
+    assert!(!is_expired(&*clocks[0], expire_at));
+    assert!(SystemTime::now() < virtual_clock.now());
+
+    // Sharing a clock does not affect the equality of the config it is in.
+    assert_eq!(clocks[0], clocks[2]);
+
+    Ok(())
+}
@@ -12,11 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod clock_test;
+#[cfg(test)]
+mod runtime_pools_test;
 #[cfg(test)]
 mod runtime_test;
 
+mod clock;
 mod runtime;
+mod runtime_pools;
 
+pub use clock::Clock;
+pub use clock::ClockSleep;
+pub use clock::SharedClock;
+pub use clock::SystemClock;
+pub use clock::VirtualClock;
 pub use runtime::Dropper;
 pub use runtime::Runtime;
+pub use runtime_pools::RuntimePool;
+pub use runtime_pools::RuntimePoolKind;
+pub use runtime_pools::RuntimePoolMetrics;
+pub use runtime_pools::RuntimePools;
+pub use runtime_pools::RuntimePoolsConfig;
+pub use runtime_pools::METRIC_RUNTIME_POOL_BUSY_THREADS;
+pub use runtime_pools::METRIC_RUNTIME_POOL_QUEUED_TASKS;
+pub use runtime_pools::METRIC_RUNTIME_POOL_TASK_LATENCY;
 pub use tokio;
@@ -23,6 +23,8 @@ use tokio::runtime::Handle;
 use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 
+use crate::RuntimePools;
+
 /// Tokio Runtime wrapper.
 /// If a runtime is in an asynchronous context, shutdown it first.
 pub struct Runtime {
@@ -78,6 +80,42 @@ impl Runtime {
         self.handle.spawn(task)
     }
 
+    /// Spawns a CPU bounded task on the global compute pool.
+    pub fn spawn_compute<T>(task: T) -> JoinHandle<T::Output>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        RuntimePools::global().compute().spawn(task)
+    }
+
+    /// Spawns an asynchronous IO task on the global io pool.
+    pub fn spawn_io<T>(task: T) -> JoinHandle<T::Output>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        RuntimePools::global().io().spawn(task)
+    }
+
+    /// Runs a blocking IO closure on the global io pool.
+    pub fn spawn_blocking_io<F, R>(f: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        RuntimePools::global().io().spawn_blocking(f)
+    }
+
+    /// Spawns a latency sensitive task on the global management pool.
+    pub fn spawn_management<T>(task: T) -> JoinHandle<T::Output>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        RuntimePools::global().management().spawn(task)
+    }
+
     // Poor man's runtime::block_on
     // This mainly used for make async function to sync.
     pub fn block_on<F>(&self, f: F, timeout: Option<Duration>) -> Result<F::Output>
@@ -0,0 +1,430 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use metrics::gauge;
+use metrics::histogram;
+use once_cell::sync::OnceCell;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+pub static METRIC_RUNTIME_POOL_QUEUED_TASKS: &str = "runtime_pool.queued_tasks";
+pub static METRIC_RUNTIME_POOL_BUSY_THREADS: &str = "runtime_pool.busy_threads";
+pub static METRIC_RUNTIME_POOL_TASK_LATENCY: &str = "runtime_pool.task_latency";
+
+static GLOBAL_RUNTIME_POOLS: OnceCell<Arc<RuntimePools>> = OnceCell::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuntimePoolKind {
+    /// Blocking and latency insensitive IO, such as file reads.
+    Io,
+    /// CPU bounded work, such as decoding.
+    Compute,
+    /// Small latency sensitive tasks, such as catalog sync.
+    Management,
+}
+
+impl RuntimePoolKind {
+    /// Pools are drained in this order on shutdown:
+    /// compute produces the IO work, and management must outlive both.
+    pub const DRAIN_ORDER: [RuntimePoolKind; 3] = [
+        RuntimePoolKind::Compute,
+        RuntimePoolKind::Io,
+        RuntimePoolKind::Management,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuntimePoolKind::Io => "io",
+            RuntimePoolKind::Compute => "compute",
+            RuntimePoolKind::Management => "management",
+        }
+    }
+}
+
+/// Thread count of each pool, 0 means the number of CPUs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimePoolsConfig {
+    pub io_threads: usize,
+    pub compute_threads: usize,
+    pub management_threads: usize,
+}
+
+impl Default for RuntimePoolsConfig {
+    fn default() -> Self {
+        RuntimePoolsConfig {
+            io_threads: 0,
+            compute_threads: 0,
+            management_threads: 2,
+        }
+    }
+}
+
+/// A snapshot of the live usage of a pool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimePoolMetrics {
+    pub pool: &'static str,
+    pub threads: usize,
+    /// Tasks spawned but not started yet.
+    pub queued_tasks: usize,
+    /// Tasks started but not finished yet, including the idle ones waiting for an event.
+    pub active_tasks: usize,
+    /// Threads executing a task, a saturated pool has all its threads busy.
+    pub busy_threads: usize,
+    pub completed_tasks: u64,
+}
+
+struct PoolStats {
+    kind: RuntimePoolKind,
+    threads: usize,
+    queued_tasks: AtomicUsize,
+    active_tasks: AtomicUsize,
+    busy_threads: AtomicUsize,
+    completed_tasks: AtomicU64,
+}
+
+impl PoolStats {
+    fn snapshot(&self) -> RuntimePoolMetrics {
+        RuntimePoolMetrics {
+            pool: self.kind.name(),
+            threads: self.threads,
+            queued_tasks: self.queued_tasks.load(Ordering::Relaxed),
+            active_tasks: self.active_tasks.load(Ordering::Relaxed),
+            busy_threads: self.busy_threads.load(Ordering::Relaxed),
+            completed_tasks: self.completed_tasks.load(Ordering::Relaxed),
+        }
+    }
+
+    fn report(&self) {
+        let pool = self.kind.name();
+        let queued_tasks = self.queued_tasks.load(Ordering::Relaxed);
+        let busy_threads = self.busy_threads.load(Ordering::Relaxed);
+        gauge!(METRIC_RUNTIME_POOL_QUEUED_TASKS, queued_tasks as f64, "pool" => pool);
+        gauge!(METRIC_RUNTIME_POOL_BUSY_THREADS, busy_threads as f64, "pool" => pool);
+    }
+}
+
+/// A spawned task waiting for a thread.
+/// Dropping it without starting (e.g. the pool is shut down) takes it out of the queue.
+struct QueuedTask {
+    stats: Arc<PoolStats>,
+    queued_at: Instant,
+}
+
+impl QueuedTask {
+    fn enqueue(stats: Arc<PoolStats>) -> QueuedTask {
+        stats.queued_tasks.fetch_add(1, Ordering::Relaxed);
+        stats.report();
+        QueuedTask {
+            stats,
+            queued_at: Instant::now(),
+        }
+    }
+
+    fn start(self) -> ActiveTask {
+        let pool = self.stats.kind.name();
+        histogram!(METRIC_RUNTIME_POOL_TASK_LATENCY, self.queued_at.elapsed(), "pool" => pool);
+
+        self.stats.active_tasks.fetch_add(1, Ordering::Relaxed);
+        ActiveTask {
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl Drop for QueuedTask {
+    fn drop(&mut self) {
+        self.stats.queued_tasks.fetch_sub(1, Ordering::Relaxed);
+        self.stats.report();
+    }
+}
+
+struct ActiveTask {
+    stats: Arc<PoolStats>,
+}
+
+impl Drop for ActiveTask {
+    fn drop(&mut self) {
+        self.stats.active_tasks.fetch_sub(1, Ordering::Relaxed);
+        self.stats.completed_tasks.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Marks a thread of the pool busy while it is alive.
+struct BusyThread {
+    stats: Arc<PoolStats>,
+}
+
+impl BusyThread {
+    fn enter(stats: Arc<PoolStats>) -> BusyThread {
+        stats.busy_threads.fetch_add(1, Ordering::Relaxed);
+        stats.report();
+        BusyThread { stats }
+    }
+}
+
+impl Drop for BusyThread {
+    fn drop(&mut self) {
+        self.stats.busy_threads.fetch_sub(1, Ordering::Relaxed);
+        self.stats.report();
+    }
+}
+
+struct PoolTask<T> {
+    stats: Arc<PoolStats>,
+    queued: Option<QueuedTask>,
+    active: Option<ActiveTask>,
+    task: Pin<Box<T>>,
+}
+
+impl<T: Future> Future for PoolTask<T> {
+    type Output = T::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T::Output> {
+        if let Some(queued) = self.queued.take() {
+            self.active = Some(queued.start());
+        }
+
+        let _busy = BusyThread::enter(self.stats.clone());
+        self.task.as_mut().poll(cx)
+    }
+}
+
+/// A labeled tokio runtime whose tasks are tracked in the pool metrics.
+pub struct RuntimePool {
+    handle: Handle,
+    stats: Arc<PoolStats>,
+    // Taken on shutdown.
+    runtime: Mutex<Option<tokio::runtime::Runtime>>,
+}
+
+impl RuntimePool {
+    fn try_create(kind: RuntimePoolKind, threads: usize) -> Result<RuntimePool> {
+        let threads = match threads {
+            0 => num_cpus::get(),
+            n => n,
+        };
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name(format!("{}-pool", kind.name()))
+            .worker_threads(threads)
+            .max_blocking_threads(threads)
+            .build()
+            .map_err(|tokio_error| ErrorCode::TokioError(format!("{}", tokio_error)))?;
+
+        Ok(RuntimePool {
+            handle: runtime.handle().clone(),
+            stats: Arc::new(PoolStats {
+                kind,
+                threads,
+                queued_tasks: AtomicUsize::new(0),
+                active_tasks: AtomicUsize::new(0),
+                busy_threads: AtomicUsize::new(0),
+                completed_tasks: AtomicU64::new(0),
+            }),
+            runtime: Mutex::new(Some(runtime)),
+        })
+    }
+
+    pub fn kind(&self) -> RuntimePoolKind {
+        self.stats.kind
+    }
+
+    pub fn spawn<T>(&self, task: T) -> JoinHandle<T::Output>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.handle.spawn(PoolTask {
+            stats: self.stats.clone(),
+            queued: Some(QueuedTask::enqueue(self.stats.clone())),
+            active: None,
+            task: Box::pin(task),
+        })
+    }
+
+    pub fn spawn_blocking<F, R>(&self, f: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let queued = QueuedTask::enqueue(self.stats.clone());
+        self.handle.spawn_blocking(move || {
+            let stats = queued.stats.clone();
+            let _active = queued.start();
+            let _busy = BusyThread::enter(stats);
+            f()
+        })
+    }
+
+    // Same as Runtime::block_on, but the future runs in this pool.
+    pub fn block_on<F>(&self, f: F, timeout: Option<Duration>) -> Result<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (tx, rx) = channel();
+        let _jh = self.spawn(async move {
+            let r = f.await;
+            let _ = tx.send(r);
+        });
+        let reply = match timeout {
+            Some(to) => rx
+                .recv_timeout(to)
+                .map_err(|timeout_err| ErrorCode::Timeout(timeout_err.to_string()))?,
+            None => rx.recv().map_err(ErrorCode::from_std_error)?,
+        };
+        Ok(reply)
+    }
+
+    pub fn metrics(&self) -> RuntimePoolMetrics {
+        self.stats.snapshot()
+    }
+
+    /// Wait until the pool has no queued or running tasks, then shutdown the runtime.
+    /// Tasks still alive at the deadline are cancelled (or detached if they are blocking).
+    /// Returns whether the pool was drained before the deadline.
+    fn shutdown(&self, deadline: Instant) -> bool {
+        let drained = loop {
+            let metrics = self.metrics();
+            if metrics.queued_tasks == 0 && metrics.active_tasks == 0 {
+                break true;
+            }
+
+            if Instant::now() >= deadline {
+                break false;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        if let Some(runtime) = self.runtime.lock().unwrap().take() {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            // The runtime can not be shutdown in an asynchronous context.
+            let _ = thread::spawn(move || runtime.shutdown_timeout(timeout)).join();
+        }
+
+        drained
+    }
+}
+
+impl Drop for RuntimePool {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.lock().unwrap().take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// The registry of the io, compute and management pools.
+pub struct RuntimePools {
+    io: RuntimePool,
+    compute: RuntimePool,
+    management: RuntimePool,
+}
+
+impl RuntimePools {
+    pub fn try_create(config: &RuntimePoolsConfig) -> Result<Arc<RuntimePools>> {
+        Ok(Arc::new(RuntimePools {
+            io: RuntimePool::try_create(RuntimePoolKind::Io, config.io_threads)?,
+            compute: RuntimePool::try_create(RuntimePoolKind::Compute, config.compute_threads)?,
+            management: RuntimePool::try_create(
+                RuntimePoolKind::Management,
+                config.management_threads,
+            )?,
+        }))
+    }
+
+    /// Install the process wide pools, it must be called before the first use of `global`.
+    pub fn init_global(config: &RuntimePoolsConfig) -> Result<Arc<RuntimePools>> {
+        let pools = Self::try_create(config)?;
+        GLOBAL_RUNTIME_POOLS
+            .set(pools.clone())
+            .map_err(|_| ErrorCode::LogicalError("Global runtime pools are already initialized"))?;
+        Ok(pools)
+    }
+
+    /// The process wide pools, created with the default config if they are not installed.
+    pub fn global() -> Arc<RuntimePools> {
+        GLOBAL_RUNTIME_POOLS
+            .get_or_init(|| {
+                Self::try_create(&RuntimePoolsConfig::default())
+                    .expect("runtime pools initialization failure")
+            })
+            .clone()
+    }
+
+    pub fn pool(&self, kind: RuntimePoolKind) -> &RuntimePool {
+        match kind {
+            RuntimePoolKind::Io => &self.io,
+            RuntimePoolKind::Compute => &self.compute,
+            RuntimePoolKind::Management => &self.management,
+        }
+    }
+
+    pub fn io(&self) -> &RuntimePool {
+        &self.io
+    }
+
+    pub fn compute(&self) -> &RuntimePool {
+        &self.compute
+    }
+
+    pub fn management(&self) -> &RuntimePool {
+        &self.management
+    }
+
+    pub fn metrics(&self) -> Vec<RuntimePoolMetrics> {
+        RuntimePoolKind::DRAIN_ORDER
+            .iter()
+            .map(|kind| self.pool(*kind).metrics())
+            .collect()
+    }
+
+    /// Drain and shutdown the pools in `RuntimePoolKind::DRAIN_ORDER`, within `drain_timeout` in total.
+    pub fn shutdown(&self, drain_timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + drain_timeout;
+
+        let mut undrained = vec![];
+        for kind in RuntimePoolKind::DRAIN_ORDER.iter() {
+            if !self.pool(*kind).shutdown(deadline) {
+                undrained.push(kind.name());
+            }
+        }
+
+        match undrained.is_empty() {
+            true => Ok(()),
+            false => Err(ErrorCode::Timeout(format!(
+                "Runtime pools {:?} are not drained in {:?}",
+                undrained, drain_timeout
+            ))),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::Result;
+
+use crate::*;
+
+fn create_pools() -> Result<std::sync::Arc<RuntimePools>> {
+    RuntimePools::try_create(&RuntimePoolsConfig {
+        io_threads: 1,
+        compute_threads: 2,
+        management_threads: 1,
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_pools_isolation() -> Result<()> {
+    let pools = create_pools()?;
+
+    // Saturate the compute pool: two tasks block both threads, two are queued.
+    let mut handles = vec![];
+    for _ in 0..4 {
+        handles.push(pools.compute().spawn(async {
+            std::thread::sleep(Duration::from_millis(500));
+        }));
+    }
+
+    let started = Instant::now();
+    while pools.compute().metrics().busy_threads < 2 {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // The management pool is not delayed by the saturated compute pool.
+    let started = Instant::now();
+    let value = pools.management().spawn(async { 1 }).await.unwrap();
+    assert_eq!(1, value);
+    assert!(started.elapsed() < Duration::from_millis(200));
+
+    let metrics = pools.compute().metrics();
+    assert_eq!("compute", metrics.pool);
+    assert_eq!(2, metrics.threads);
+    assert_eq!(2, metrics.busy_threads);
+    assert_eq!(2, metrics.queued_tasks);
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let metrics = pools.metrics();
+    assert_eq!(
+        vec!["compute", "io", "management"],
+        metrics.iter().map(|m| m.pool).collect::<Vec<_>>()
+    );
+    assert_eq!(0, metrics[0].busy_threads);
+    assert_eq!(0, metrics[0].queued_tasks);
+    assert_eq!(4, metrics[0].completed_tasks);
+    assert_eq!(1, metrics[2].completed_tasks);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_pools_shutdown() -> Result<()> {
+    // Idle pools are drained immediately.
+    {
+        let pools = create_pools()?;
+        pools.io().spawn_blocking(|| 1).await.unwrap();
+        pools.shutdown(Duration::from_secs(5))?;
+    }
+
+    // Pending tasks can not hold the shutdown beyond the drain timeout.
+    {
+        let pools = create_pools()?;
+        pools.compute().spawn(async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        pools.io().spawn_blocking(|| {
+            std::thread::sleep(Duration::from_secs(3));
+        });
+        pools.io().spawn_blocking(|| {
+            std::thread::sleep(Duration::from_secs(3));
+        });
+
+        let started = Instant::now();
+        let result = pools.shutdown(Duration::from_millis(500));
+        assert!(started.elapsed() < Duration::from_millis(1500));
+
+        let expect =
+            "Code: 40, displayText = Runtime pools [\"compute\", \"io\"] are not drained in 500ms.";
+        assert_eq!(expect, format!("{}", result.unwrap_err()));
+
+        // Tasks spawned after shutdown are cancelled.
+        let result = pools.management().spawn(async { 1 }).await;
+        assert!(result.is_err());
+        assert_eq!(0, pools.management().metrics().queued_tasks);
+    }
+
+    Ok(())
+}
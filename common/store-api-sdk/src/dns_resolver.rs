@@ -142,6 +142,17 @@ impl ConnectionFactory {
         addr: impl ToString,
         timeout: Option<Duration>,
         rpc_client_config: Option<RpcClientTlsConfig>,
+    ) -> Result<Channel> {
+        Self::create_flight_channel_with_keepalive(addr, timeout, None, rpc_client_config)
+    }
+
+    /// Creates a channel that sends an HTTP/2 ping every `keepalive`, a ping not acknowledged
+    /// within `keepalive` closes the connection.
+    pub fn create_flight_channel_with_keepalive(
+        addr: impl ToString,
+        timeout: Option<Duration>,
+        keepalive: Option<Duration>,
+        rpc_client_config: Option<RpcClientTlsConfig>,
     ) -> Result<Channel> {
         match format!("http://{}", addr.to_string()).parse::<Uri>() {
             Err(error) => Result::Err(ErrorCode::BadAddressFormat(format!(
@@ -178,6 +189,13 @@ impl ConnectionFactory {
                     endpoint = endpoint.timeout(timeout);
                 }
 
+                if let Some(keepalive) = keepalive {
+                    endpoint = endpoint
+                        .http2_keep_alive_interval(keepalive)
+                        .keep_alive_timeout(keepalive)
+                        .keep_alive_while_idle(true);
+                }
+
                 match endpoint.connect_with_connector_lazy(inner_connector) {
                     Ok(channel) => Result::Ok(channel),
                     Err(error) => Result::Err(ErrorCode::CannotConnectNode(format!(
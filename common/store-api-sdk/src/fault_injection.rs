@@ -0,0 +1,238 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fault injection for the store client and the store flight service, for the tests only.
+//!
+//! Neither side has an injector unless a test installs one, and then every call only costs
+//! a check of the `Option`. An injector runs a scenario: a list of rules, each of which is
+//! applied to the requests or the responses of an action type.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_runtime::tokio;
+use common_tracing::tracing;
+use futures::Stream;
+
+/// Where a fault is applied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FaultPhase {
+    Request,
+    Response,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FaultKind {
+    /// Deliver it late.
+    Delay(Duration),
+    /// Lose it: the caller waits until its timeout.
+    Drop,
+    /// Deliver it twice.
+    Duplicate,
+    /// Fail it with the message, without delivering it.
+    Error(String),
+    /// Cut the connection of a streaming call after the given number of messages.
+    Cut(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct FaultRule {
+    /// The action type, e.g. `CreateTable` or `Append`, None matches all of them.
+    pub action: Option<String>,
+    pub phase: FaultPhase,
+    pub kind: FaultKind,
+    /// How many times the rule is applied, None for always.
+    pub times: Option<usize>,
+}
+
+impl FaultRule {
+    pub fn create(action: Option<&str>, phase: FaultPhase, kind: FaultKind) -> FaultRule {
+        FaultRule {
+            action: action.map(|s| s.to_string()),
+            phase,
+            kind,
+            times: None,
+        }
+    }
+
+    pub fn times(mut self, times: usize) -> FaultRule {
+        self.times = Some(times);
+        self
+    }
+
+    fn matches(&self, phase: FaultPhase, action: &str) -> bool {
+        self.phase == phase
+            && self.times != Some(0)
+            && self.action.as_ref().map(|a| a == action).unwrap_or(true)
+    }
+}
+
+/// What happens to a request or a response once the rules are applied.
+#[derive(Debug, PartialEq)]
+pub enum Injected {
+    Pass,
+    Drop,
+    Duplicate,
+    Fail(String),
+    Cut(usize),
+}
+
+pub struct FaultInjector {
+    rules: Mutex<Vec<FaultRule>>,
+    partitioned_until: Mutex<Option<Instant>>,
+}
+
+impl FaultInjector {
+    pub fn create() -> FaultInjector {
+        FaultInjector::with_scenario(vec![])
+    }
+
+    pub fn with_scenario(rules: Vec<FaultRule>) -> FaultInjector {
+        FaultInjector {
+            rules: Mutex::new(rules),
+            partitioned_until: Mutex::new(None),
+        }
+    }
+
+    pub fn add_rule(&self, rule: FaultRule) {
+        self.rules.lock().push(rule);
+    }
+
+    pub fn clear(&self) {
+        self.rules.lock().clear();
+        *self.partitioned_until.lock() = None;
+    }
+
+    /// Blackhole every request and response for the duration, as if the network is partitioned.
+    pub fn partition(&self, duration: Duration) {
+        *self.partitioned_until.lock() = Some(Instant::now() + duration);
+    }
+
+    pub fn is_partitioned(&self) -> bool {
+        match *self.partitioned_until.lock() {
+            Some(until) => Instant::now() < until,
+            None => false,
+        }
+    }
+
+    /// Applies the first matching rule. A delay is served here, the other faults are
+    /// left to the caller.
+    pub async fn inject(&self, phase: FaultPhase, action: &str) -> Injected {
+        if self.is_partitioned() {
+            return Injected::Drop;
+        }
+
+        let kind = {
+            let mut rules = self.rules.lock();
+            match rules.iter_mut().find(|rule| rule.matches(phase, action)) {
+                None => return Injected::Pass,
+                Some(rule) => {
+                    if let Some(times) = rule.times.as_mut() {
+                        *times -= 1;
+                    }
+                    rule.kind.clone()
+                }
+            }
+        };
+
+        tracing::info!("inject fault {:?} to {:?} of {}", kind, phase, action);
+
+        match kind {
+            FaultKind::Delay(duration) => {
+                tokio::time::sleep(duration).await;
+                Injected::Pass
+            }
+            FaultKind::Drop => Injected::Drop,
+            FaultKind::Duplicate => Injected::Duplicate,
+            FaultKind::Error(message) => Injected::Fail(message),
+            FaultKind::Cut(messages) => Injected::Cut(messages),
+        }
+    }
+
+    /// Runs a unary call through the rules. A lost request or response fails the call
+    /// with a timeout after `timeout`, like a blackholed connection.
+    pub async fn intercept<T, F, Fut>(&self, action: &str, timeout: Duration, call: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        match self.inject(FaultPhase::Request, action).await {
+            Injected::Pass | Injected::Cut(_) => {}
+            Injected::Drop => return Self::timeout(action, timeout).await,
+            Injected::Fail(message) => return Err(ErrorCode::CannotConnectNode(message)),
+            Injected::Duplicate => {
+                let _ = call().await;
+            }
+        }
+
+        let res = call().await;
+
+        match self.inject(FaultPhase::Response, action).await {
+            Injected::Drop => Self::timeout(action, timeout).await,
+            Injected::Fail(message) => Err(ErrorCode::CannotConnectNode(message)),
+            // Only the first one of the duplicated responses is read.
+            Injected::Pass | Injected::Cut(_) | Injected::Duplicate => res,
+        }
+    }
+
+    async fn timeout<T>(action: &str, timeout: Duration) -> Result<T> {
+        tokio::time::sleep(timeout).await;
+        Err(ErrorCode::Timeout(format!(
+            "{} is lost by fault injection, timeout after {:?}",
+            action, timeout
+        )))
+    }
+}
+
+/// A message stream whose connection is cut after a number of messages.
+pub struct CutStream<S> {
+    inner: S,
+    remaining: usize,
+}
+
+impl<S> CutStream<S> {
+    pub fn create(inner: S, messages: usize) -> CutStream<S> {
+        CutStream {
+            inner,
+            remaining: messages,
+        }
+    }
+}
+
+impl<S, T> Stream for CutStream<S>
+where S: Stream<Item = std::result::Result<T, tonic::Status>> + Unpin
+{
+    type Item = std::result::Result<T, tonic::Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.remaining == 0 {
+            return Poll::Ready(Some(Err(tonic::Status::unavailable(
+                "connection is cut by fault injection",
+            ))));
+        }
+
+        let next = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(Some(Ok(_))) = &next {
+            self.remaining -= 1;
+        }
+        next
+    }
+}
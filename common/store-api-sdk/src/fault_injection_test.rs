@@ -0,0 +1,181 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::CutStream;
+use crate::FaultInjector;
+use crate::FaultKind;
+use crate::FaultPhase;
+use crate::FaultRule;
+use crate::Injected;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_fault_injector_disabled() -> Result<()> {
+    // No rules: every call is delivered once, at once, and returns what the call returns.
+    let injector = FaultInjector::create();
+    let calls = &AtomicUsize::new(0);
+
+    let start = Instant::now();
+    for i in 0..100u64 {
+        let call = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(i)
+        };
+        assert_eq!(
+            i,
+            injector
+                .intercept("GetTable", Duration::from_secs(60), call)
+                .await?
+        );
+
+        let call = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<u64, _>(ErrorCode::UnknownTable("t"))
+        };
+        let res = injector
+            .intercept("GetTable", Duration::from_secs(60), call)
+            .await;
+        assert_eq!(ErrorCode::UnknownTable("").code(), res.unwrap_err().code());
+    }
+
+    assert_eq!(200, calls.load(Ordering::SeqCst));
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    for phase in [FaultPhase::Request, FaultPhase::Response] {
+        assert_eq!(Injected::Pass, injector.inject(phase, "Append").await);
+    }
+
+    // A stream passes through untouched until it is cut.
+    let items = futures::stream::iter(vec![Ok::<_, tonic::Status>(1), Ok(2), Ok(3)]);
+    let items: Vec<_> = CutStream::create(items, usize::MAX).collect().await;
+    assert_eq!(3, items.len());
+    assert!(items.iter().all(|item| item.is_ok()));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_fault_injector_rules() -> Result<()> {
+    let injector = FaultInjector::with_scenario(vec![
+        FaultRule::create(
+            Some("CreateTable"),
+            FaultPhase::Request,
+            FaultKind::Duplicate,
+        )
+        .times(1),
+        FaultRule::create(Some("GetTable"), FaultPhase::Response, FaultKind::Drop).times(1),
+        FaultRule::create(
+            Some("GetKV"),
+            FaultPhase::Request,
+            FaultKind::Error("injected".to_string()),
+        )
+        .times(2),
+        FaultRule::create(
+            Some("UpsertKV"),
+            FaultPhase::Request,
+            FaultKind::Delay(Duration::from_millis(200)),
+        )
+        .times(1),
+    ]);
+
+    let calls = &AtomicUsize::new(0);
+    let call = move || async move {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    };
+    let timeout = Duration::from_millis(100);
+
+    // Duplicate: delivered twice, then as usual.
+    injector.intercept("CreateTable", timeout, call).await?;
+    assert_eq!(2, calls.swap(0, Ordering::SeqCst));
+    injector.intercept("CreateTable", timeout, call).await?;
+    assert_eq!(1, calls.swap(0, Ordering::SeqCst));
+
+    // Drop of the response: delivered, but the caller times out.
+    let res = injector.intercept("GetTable", timeout, call).await;
+    assert_eq!(ErrorCode::Timeout("").code(), res.unwrap_err().code());
+    assert_eq!(1, calls.swap(0, Ordering::SeqCst));
+
+    // Error: not delivered, for two times.
+    for _ in 0..2 {
+        let res = injector.intercept("GetKV", timeout, call).await;
+        assert_eq!(
+            ErrorCode::CannotConnectNode("").code(),
+            res.unwrap_err().code()
+        );
+    }
+    assert_eq!(0, calls.swap(0, Ordering::SeqCst));
+    injector.intercept("GetKV", timeout, call).await?;
+    assert_eq!(1, calls.swap(0, Ordering::SeqCst));
+
+    // Delay.
+    let start = Instant::now();
+    injector.intercept("UpsertKV", timeout, call).await?;
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    // Cut: the stream fails after two messages.
+    injector.add_rule(FaultRule::create(
+        Some("Append"),
+        FaultPhase::Request,
+        FaultKind::Cut(2),
+    ));
+    assert_eq!(
+        Injected::Cut(2),
+        injector.inject(FaultPhase::Request, "Append").await
+    );
+    let items = futures::stream::iter(vec![Ok::<_, tonic::Status>(1), Ok(2), Ok(3)]);
+    let items: Vec<_> = CutStream::create(items, 2).collect::<Vec<_>>().await;
+    assert!(items[0].is_ok() && items[1].is_ok() && items[2].is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_fault_injector_partition() -> Result<()> {
+    let injector = FaultInjector::create();
+    let calls = &AtomicUsize::new(0);
+    let call = move || async move {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    };
+    let timeout = Duration::from_millis(100);
+
+    injector.partition(Duration::from_millis(500));
+    assert!(injector.is_partitioned());
+    let res = injector.intercept("GetTable", timeout, call).await;
+    assert_eq!(ErrorCode::Timeout("").code(), res.unwrap_err().code());
+    assert_eq!(0, calls.load(Ordering::SeqCst));
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(!injector.is_partitioned());
+    injector.intercept("GetTable", timeout, call).await?;
+    assert_eq!(1, calls.load(Ordering::SeqCst));
+
+    // Clearing heals the partition.
+    injector.partition(Duration::from_secs(60));
+    injector.clear();
+    assert!(!injector.is_partitioned());
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use common_exception::ErrorCode;
+use futures::channel::oneshot;
+use serde::de::DeserializeOwned;
+
+use crate::action_declare;
+use crate::RequestFor;
+use crate::StoreClient;
+use crate::StoreDoAction;
+
+/// The max number of the actions in a batch.
+pub const MAX_BATCH_ACTIONS: usize = 256;
+
+/// Read-only actions sent in one request, see `StoreDoAction::is_batchable`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BatchAction {
+    pub actions: Vec<StoreDoAction>,
+}
+action_declare!(BatchAction, BatchActionResult, StoreDoAction::Batch);
+
+/// The reply of an action of a batch, the failure of one action does not fail the others.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum BatchItemResult {
+    /// The serialized reply of the action.
+    Ok(String),
+    Err {
+        code: u16,
+        message: String,
+    },
+}
+
+impl BatchItemResult {
+    pub fn failed(e: &ErrorCode) -> Self {
+        BatchItemResult::Err {
+            code: e.code(),
+            message: e.message(),
+        }
+    }
+
+    fn into_reply<R: DeserializeOwned>(self) -> common_exception::Result<R> {
+        match self {
+            BatchItemResult::Ok(body) => Ok(serde_json::from_str(&body)?),
+            BatchItemResult::Err { code, message } => Err(ErrorCode::create(code, message, None)),
+        }
+    }
+}
+
+/// The replies in the order of the actions of the batch.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct BatchActionResult {
+    pub results: Vec<BatchItemResult>,
+}
+
+type ItemSender = oneshot::Sender<common_exception::Result<BatchItemResult>>;
+
+/// Collects the actions of a batch, each `push` returns the future of the reply of the action,
+/// it resolves once the batch is sent by `StoreClient::send_batch`.
+///
+/// ```ignore
+/// let mut batch = BatchRequest::new();
+/// let table = batch.push(GetTableAction { db, table });
+/// let value = batch.push(GetKVAction { key });
+/// client.send_batch(batch).await?;
+/// let (table, value) = (table.await?, value.await?);
+/// ```
+#[derive(Default)]
+pub struct BatchRequest {
+    actions: Vec<StoreDoAction>,
+    senders: Vec<ItemSender>,
+}
+
+impl BatchRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<T, R>(&mut self, action: T) -> BatchReply<R>
+    where
+        T: RequestFor<Reply = R>,
+        T: Into<StoreDoAction>,
+        R: DeserializeOwned,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.actions.push(action.into());
+        self.senders.push(tx);
+        BatchReply {
+            rx,
+            _reply: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// The reply of an action pushed into a `BatchRequest`.
+pub struct BatchReply<R> {
+    rx: oneshot::Receiver<common_exception::Result<BatchItemResult>>,
+    _reply: PhantomData<fn() -> R>,
+}
+
+impl<R: DeserializeOwned> Future for BatchReply<R> {
+    type Output = common_exception::Result<R>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx).poll(cx).map(|res| match res {
+            Ok(item) => item?.into_reply(),
+            Err(_) => Err(ErrorCode::EmptyData(
+                "The batch is dropped before it is sent",
+            )),
+        })
+    }
+}
+
+impl StoreClient {
+    /// Sends the actions of a batch in one request, and resolves the replies of them.
+    ///
+    /// The error of the request, e.g. the batch has a mutation, is returned and is also the reply
+    /// of every action.
+    pub async fn send_batch(&self, batch: BatchRequest) -> common_exception::Result<()> {
+        let BatchRequest { actions, senders } = batch;
+        match self.do_action(BatchAction { actions }).await {
+            Ok(reply) if reply.results.len() == senders.len() => {
+                for (tx, item) in senders.into_iter().zip(reply.results) {
+                    let _ = tx.send(Ok(item));
+                }
+                Ok(())
+            }
+            Ok(reply) => {
+                let e = ErrorCode::UnexpectedResponseType(format!(
+                    "expect {} replies of the batch, but got {}",
+                    senders.len(),
+                    reply.results.len()
+                ));
+                for tx in senders {
+                    let _ = tx.send(Err(ErrorCode::create(e.code(), e.message(), None)));
+                }
+                Err(e)
+            }
+            Err(e) => {
+                for tx in senders {
+                    let _ = tx.send(Err(ErrorCode::create(e.code(), e.message(), None)));
+                }
+                Err(e)
+            }
+        }
+    }
+}
@@ -12,20 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::pin::Pin;
+
+use common_arrow::arrow_flight::Ticket;
+use common_exception::ErrorCode;
 use common_exception::Result;
+use common_metatypes::JsonPatchOp;
+use common_metatypes::KVChange;
 use common_metatypes::KVMeta;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
 pub use common_store_api::kv_apis::kv_api::MGetKVActionResult;
+pub use common_store_api::kv_apis::kv_api::PrefixListPage;
 pub use common_store_api::kv_apis::kv_api::PrefixListReply;
 pub use common_store_api::kv_apis::kv_api::UpsertKVActionResult;
 pub use common_store_api::GetKVActionResult;
 use common_store_api::KVApi;
 use common_tracing::tracing;
+use futures::Stream;
+use futures::StreamExt;
 
 use crate::action_declare;
 use crate::RequestFor;
 use crate::StoreClient;
 use crate::StoreDoAction;
+use crate::StoreDoGet;
+use crate::WatchKVAction;
 
 #[async_trait::async_trait]
 impl KVApi for StoreClient {
@@ -42,6 +54,25 @@ impl KVApi for StoreClient {
             seq,
             value,
             value_meta,
+            return_value_on_conflict: true,
+        })
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, value))]
+    async fn upsert_kv_lean(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> Result<UpsertKVActionResult> {
+        self.do_action(UpsertKVAction {
+            key: key.to_string(),
+            seq,
+            value,
+            value_meta,
+            return_value_on_conflict: false,
         })
         .await
     }
@@ -60,6 +91,55 @@ impl KVApi for StoreClient {
         .await
     }
 
+    #[tracing::instrument(level = "debug", skip(self, op))]
+    async fn upsert_kv_merge(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        op: MergeOp,
+        value_meta: Option<KVMeta>,
+    ) -> Result<UpsertKVActionResult> {
+        self.do_action(MergeKVAction {
+            key: key.to_string(),
+            seq,
+            op,
+            value_meta,
+        })
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, expect, value))]
+    async fn cas_kv(
+        &self,
+        key: &str,
+        expect: Option<Vec<u8>>,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> Result<UpsertKVActionResult> {
+        self.do_action(CasKVAction {
+            key: key.to_string(),
+            expect,
+            value,
+            value_meta,
+        })
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, patch))]
+    async fn patch_kv_json(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        patch: Vec<JsonPatchOp>,
+    ) -> Result<UpsertKVActionResult> {
+        self.do_action(PatchKVJsonAction {
+            key: key.to_string(),
+            seq,
+            patch,
+        })
+        .await
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     async fn get_kv(&self, key: &str) -> Result<GetKVActionResult> {
         self.do_action(GetKVAction {
@@ -77,7 +157,89 @@ impl KVApi for StoreClient {
 
     #[tracing::instrument(level = "debug", skip(self))]
     async fn prefix_list_kv(&self, prefix: &str) -> common_exception::Result<PrefixListReply> {
-        self.do_action(PrefixListReq(prefix.to_string())).await
+        let page = self.prefix_list_kv_page(prefix, None, None).await?;
+        Ok(page.entries)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn delete_prefix_kv(&self, prefix: &str) -> common_exception::Result<usize> {
+        self.do_action(DeletePrefixKVAction {
+            prefix: prefix.to_string(),
+        })
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn prefix_list_kv_page(
+        &self,
+        prefix: &str,
+        limit: Option<usize>,
+        after_key: Option<String>,
+    ) -> common_exception::Result<PrefixListPage> {
+        self.do_action(PrefixListPageReq {
+            prefix: prefix.to_string(),
+            limit,
+            after_key,
+        })
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn prefix_list_kv_by_seq(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
+    ) -> common_exception::Result<PrefixListReply> {
+        self.do_action(PrefixListBySeqReq {
+            prefix: prefix.to_string(),
+            min_seq,
+            limit,
+        })
+        .await
+    }
+}
+
+/// The changes of the generic-kv records under a prefix, in the order they are applied.
+pub type KVChangeStream = Pin<Box<dyn Stream<Item = Result<KVChange>> + Send>>;
+
+impl StoreClient {
+    /// Watches the changes of the keys under `prefix`: the records created, updated, deleted or expired.
+    /// The watch ends when the stream is dropped.
+    pub async fn watch_kv(&self, prefix: &str) -> Result<KVChangeStream> {
+        self.do_watch_kv(WatchKVAction {
+            prefix: prefix.to_string(),
+            after_seq: None,
+        })
+        .await
+    }
+
+    /// Watches the changes of the keys under `prefix`, the records with a seq greater than
+    /// `after_seq` are sent first as created, e.g., to resume a watch that is broken.
+    pub async fn watch_kv_after(&self, prefix: &str, after_seq: u64) -> Result<KVChangeStream> {
+        self.do_watch_kv(WatchKVAction {
+            prefix: prefix.to_string(),
+            after_seq: Some(after_seq),
+        })
+        .await
+    }
+
+    async fn do_watch_kv(&self, action: WatchKVAction) -> Result<KVChangeStream> {
+        // No call timeout: a watch lasts until the client drops it.
+        let mut req = tonic::Request::<Ticket>::from(&StoreDoGet::WatchKV(action));
+        self.attach_query_label(req.metadata_mut());
+
+        let stream = self
+            .client()
+            .do_get(req)
+            .await
+            .map_err(ErrorCode::from)?
+            .into_inner();
+
+        Ok(Box::pin(stream.map(|item| {
+            let data = item.map_err(ErrorCode::from)?;
+            Ok(serde_json::from_slice::<KVChange>(&data.data_body)?)
+        })))
     }
 }
 
@@ -129,6 +291,39 @@ action_declare!(MGetKVAction, MGetKVActionResult, StoreDoAction::MGetKV);
 pub struct PrefixListReq(pub String);
 action_declare!(PrefixListReq, PrefixListReply, StoreDoAction::PrefixListKV);
 
+// - prefix list ordered by seq
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct PrefixListBySeqReq {
+    pub prefix: String,
+    pub min_seq: u64,
+    pub limit: usize,
+}
+action_declare!(
+    PrefixListBySeqReq,
+    PrefixListReply,
+    StoreDoAction::PrefixListKVBySeq
+);
+
+// - prefix list in pages ordered by key
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct PrefixListPageReq {
+    pub prefix: String,
+    pub limit: Option<usize>,
+    pub after_key: Option<String>,
+}
+action_declare!(
+    PrefixListPageReq,
+    PrefixListPage,
+    StoreDoAction::PrefixListKVPage
+);
+
+// - delete by prefix
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct DeletePrefixKVAction {
+    pub prefix: String,
+}
+action_declare!(DeletePrefixKVAction, usize, StoreDoAction::DeletePrefixKV);
+
 // === general-kv: upsert ===
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct UpsertKVAction {
@@ -136,6 +331,15 @@ pub struct UpsertKVAction {
     pub seq: MatchSeq,
     pub value: Option<Vec<u8>>,
     pub value_meta: Option<KVMeta>,
+    /// If false, the reply of an upsert rejected by `seq` carries only the seq and the meta.
+    #[serde(default = "UpsertKVAction::default_return_value_on_conflict")]
+    pub return_value_on_conflict: bool,
+}
+
+impl UpsertKVAction {
+    fn default_return_value_on_conflict() -> bool {
+        true
+    }
 }
 
 action_declare!(
@@ -156,3 +360,39 @@ action_declare!(
     UpsertKVActionResult,
     StoreDoAction::UpdateKVMeta
 );
+
+// === general-kv: compare-and-swap by value ===
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CasKVAction {
+    pub key: String,
+    pub expect: Option<Vec<u8>>,
+    pub value: Option<Vec<u8>>,
+    pub value_meta: Option<KVMeta>,
+}
+
+action_declare!(CasKVAction, UpsertKVActionResult, StoreDoAction::CasKV);
+
+// === general-kv: merge ===
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct MergeKVAction {
+    pub key: String,
+    pub seq: MatchSeq,
+    pub op: MergeOp,
+    pub value_meta: Option<KVMeta>,
+}
+
+action_declare!(MergeKVAction, UpsertKVActionResult, StoreDoAction::MergeKV);
+
+// === general-kv: json patch ===
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct PatchKVJsonAction {
+    pub key: String,
+    pub seq: MatchSeq,
+    pub patch: Vec<JsonPatchOp>,
+}
+
+action_declare!(
+    PatchKVJsonAction,
+    UpsertKVActionResult,
+    StoreDoAction::PatchKVJson
+);
@@ -14,22 +14,36 @@
 //
 
 use common_exception::ErrorCode;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::MatchSeq;
 use common_metatypes::MetaId;
 use common_metatypes::MetaVersion;
 use common_planners::CreateDatabasePlan;
 use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
 use common_planners::DropTablePlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::RenameTablePlan;
+use common_planners::UndropTablePlan;
 use common_store_api::CommitTableReply;
 pub use common_store_api::CreateDatabaseActionResult;
 pub use common_store_api::CreateTableActionResult;
+pub use common_store_api::CreateTableOutcome;
+pub use common_store_api::CreateTablesActionResult;
 pub use common_store_api::DatabaseMetaReply;
 pub use common_store_api::DatabaseMetaSnapshot;
 pub use common_store_api::DropDatabaseActionResult;
 pub use common_store_api::DropTableActionResult;
 pub use common_store_api::GetDatabaseActionResult;
+pub use common_store_api::GetDatabaseUsagesActionResult;
+pub use common_store_api::GetDroppedTablesActionResult;
 pub use common_store_api::GetTableActionResult;
+pub use common_store_api::ListDatabasesReply;
+pub use common_store_api::ListTablesReply;
 use common_store_api::MetaApi;
+pub use common_store_api::ModifyColumnActionResult;
+pub use common_store_api::RenameTableActionResult;
+pub use common_store_api::UndropTableActionResult;
 
 use crate::action_declare;
 use crate::store_do_action::StoreDoAction;
@@ -51,6 +65,10 @@ impl MetaApi for StoreClient {
             .await
     }
 
+    async fn list_databases(&self) -> common_exception::Result<ListDatabasesReply> {
+        self.do_action(ListDatabasesAction {}).await
+    }
+
     /// Drop database call.
     async fn drop_database(
         &self,
@@ -64,7 +82,38 @@ impl MetaApi for StoreClient {
         &self,
         plan: CreateTablePlan,
     ) -> common_exception::Result<CreateTableActionResult> {
-        self.do_action(CreateTableAction { plan }).await
+        let request_id = Some(self.next_request_id());
+        self.do_action(CreateTableAction {
+            plan,
+            request_id,
+            seq: None,
+        })
+        .await
+    }
+
+    /// Create tables call.
+    async fn create_tables(
+        &self,
+        plans: Vec<CreateTablePlan>,
+    ) -> common_exception::Result<CreateTablesActionResult> {
+        let request_id = Some(self.next_request_id());
+        self.do_action(CreateTablesAction { plans, request_id })
+            .await
+    }
+
+    /// Create or replace table call.
+    async fn create_or_replace_table(
+        &self,
+        plan: CreateTablePlan,
+        seq: MatchSeq,
+    ) -> common_exception::Result<CreateTableActionResult> {
+        let request_id = Some(self.next_request_id());
+        self.do_action(CreateTableAction {
+            plan,
+            request_id,
+            seq: Some(seq),
+        })
+        .await
     }
 
     /// Drop table call.
@@ -75,6 +124,35 @@ impl MetaApi for StoreClient {
         self.do_action(DropTableAction { plan }).await
     }
 
+    /// Undrop table call.
+    async fn undrop_table(
+        &self,
+        plan: UndropTablePlan,
+    ) -> common_exception::Result<UndropTableActionResult> {
+        self.do_action(UndropTableAction { plan }).await
+    }
+
+    /// Rename table call.
+    async fn rename_table(
+        &self,
+        plan: RenameTablePlan,
+    ) -> common_exception::Result<RenameTableActionResult> {
+        self.do_action(RenameTableAction { plan }).await
+    }
+
+    /// Get the dropped tables in trash.
+    async fn get_dropped_tables(&self) -> common_exception::Result<GetDroppedTablesActionResult> {
+        self.do_action(GetDroppedTablesAction {}).await
+    }
+
+    /// Modify the type of a column.
+    async fn modify_column(
+        &self,
+        plan: ModifyColumnPlan,
+    ) -> common_exception::Result<ModifyColumnActionResult> {
+        self.do_action(ModifyColumnAction { plan }).await
+    }
+
     /// Get table.
     async fn get_table(
         &self,
@@ -84,6 +162,10 @@ impl MetaApi for StoreClient {
         self.do_action(GetTableAction { db, table }).await
     }
 
+    async fn list_tables(&self, db: String) -> common_exception::Result<ListTablesReply> {
+        self.do_action(ListTablesAction { db }).await
+    }
+
     async fn get_table_ext(
         &self,
         tbl_id: MetaId,
@@ -100,6 +182,25 @@ impl MetaApi for StoreClient {
             .await
     }
 
+    async fn set_database_quota(
+        &self,
+        db: String,
+        quota: Option<u64>,
+    ) -> common_exception::Result<DatabaseUsage> {
+        self.do_action(SetDatabaseQuotaAction { db, quota }).await
+    }
+
+    async fn get_database_usages(&self) -> common_exception::Result<GetDatabaseUsagesActionResult> {
+        self.do_action(GetDatabaseUsagesAction {}).await
+    }
+
+    async fn reconcile_database_usage(
+        &self,
+        db: String,
+    ) -> common_exception::Result<DatabaseUsage> {
+        self.do_action(ReconcileDatabaseUsageAction { db }).await
+    }
+
     async fn commit_table(
         &self,
         _table_id: MetaId,
@@ -133,6 +234,15 @@ action_declare!(
     StoreDoAction::GetDatabase
 );
 
+// - list databases
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ListDatabasesAction {}
+action_declare!(
+    ListDatabasesAction,
+    ListDatabasesReply,
+    StoreDoAction::ListDatabases
+);
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct DropDatabaseAction {
     pub plan: DropDatabasePlan,
@@ -143,11 +253,57 @@ action_declare!(
     StoreDoAction::DropDatabase
 );
 
+// - set database quota
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct SetDatabaseQuotaAction {
+    pub db: String,
+    pub quota: Option<u64>,
+}
+action_declare!(
+    SetDatabaseQuotaAction,
+    DatabaseUsage,
+    StoreDoAction::SetDatabaseQuota
+);
+
+// - get database usages
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct GetDatabaseUsagesAction {}
+action_declare!(
+    GetDatabaseUsagesAction,
+    GetDatabaseUsagesActionResult,
+    StoreDoAction::GetDatabaseUsages
+);
+
+// - reconcile database usage
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ReconcileDatabaseUsageAction {
+    pub db: String,
+}
+action_declare!(
+    ReconcileDatabaseUsageAction,
+    DatabaseUsage,
+    StoreDoAction::ReconcileDatabaseUsage
+);
+
 // == table actions ==
 // - create table
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct CreateTableAction {
     pub plan: CreateTablePlan,
+    /// Identifies the request, so that a retried or duplicated one is applied only once.
+    #[serde(default)]
+    pub request_id: Option<RequestId>,
+    /// Replace the existing table if its id matches, `None` to only create the table.
+    #[serde(default)]
+    pub seq: Option<MatchSeq>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct RequestId {
+    /// The client which has sent the request.
+    pub client: String,
+    /// Increases with every request of the client.
+    pub serial: u64,
 }
 action_declare!(
     CreateTableAction,
@@ -155,6 +311,19 @@ action_declare!(
     StoreDoAction::CreateTable
 );
 
+// - create tables
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CreateTablesAction {
+    pub plans: Vec<CreateTablePlan>,
+    #[serde(default)]
+    pub request_id: Option<RequestId>,
+}
+action_declare!(
+    CreateTablesAction,
+    CreateTablesActionResult,
+    StoreDoAction::CreateTables
+);
+
 // - drop table
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct DropTableAction {
@@ -166,6 +335,48 @@ action_declare!(
     StoreDoAction::DropTable
 );
 
+// - undrop table
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct UndropTableAction {
+    pub plan: UndropTablePlan,
+}
+action_declare!(
+    UndropTableAction,
+    UndropTableActionResult,
+    StoreDoAction::UndropTable
+);
+
+// - rename table
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RenameTableAction {
+    pub plan: RenameTablePlan,
+}
+action_declare!(
+    RenameTableAction,
+    RenameTableActionResult,
+    StoreDoAction::RenameTable
+);
+
+// - get dropped tables
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct GetDroppedTablesAction {}
+action_declare!(
+    GetDroppedTablesAction,
+    GetDroppedTablesActionResult,
+    StoreDoAction::GetDroppedTables
+);
+
+// - modify column
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ModifyColumnAction {
+    pub plan: ModifyColumnPlan,
+}
+action_declare!(
+    ModifyColumnAction,
+    ModifyColumnActionResult,
+    StoreDoAction::ModifyColumn
+);
+
 // - get table
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct GetTableAction {
@@ -178,6 +389,13 @@ action_declare!(
     StoreDoAction::GetTable
 );
 
+// - list tables
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ListTablesAction {
+    pub db: String,
+}
+action_declare!(ListTablesAction, ListTablesReply, StoreDoAction::ListTables);
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct GetTableExtReq {
     pub tbl_id: MetaId,
@@ -13,6 +13,7 @@
 // limitations under the License.
 //
 
+pub mod batch_api_impl;
 pub mod kv_api_impl;
 pub mod meta_api_impl;
 pub mod storage_api_impl;
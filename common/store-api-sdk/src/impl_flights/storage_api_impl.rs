@@ -14,6 +14,8 @@
 //
 
 use std::convert::TryFrom;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 // io::ipc::write::common::{encoded_batch, DictionaryTracker, EncodedData, IpcWriteOptions}
@@ -23,6 +25,7 @@ use common_arrow::arrow::record_batch::RecordBatch;
 use common_arrow::arrow_flight::utils::flight_data_from_arrow_batch;
 use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
 use common_arrow::arrow_flight::utils::flight_data_to_arrow_batch;
+use common_arrow::arrow_flight::FlightData;
 use common_arrow::arrow_flight::Ticket;
 use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
@@ -32,19 +35,37 @@ use common_planners::ScanPlan;
 use common_runtime::tokio;
 pub use common_store_api::AppendResult;
 pub use common_store_api::BlockStream;
+pub use common_store_api::BloomFilter;
+pub use common_store_api::ColumnBound;
+pub use common_store_api::ColumnStatistics;
+pub use common_store_api::CopyTableResult;
+pub use common_store_api::CopyTableSource;
 pub use common_store_api::DataPartInfo;
+pub use common_store_api::OptimizeTableResult;
+pub use common_store_api::PartBloomFilters;
+pub use common_store_api::PartChecksum;
+pub use common_store_api::PartColumnStatistics;
+pub use common_store_api::PartReadInfo;
+pub use common_store_api::PartStorageClass;
+pub use common_store_api::PartsPruning;
 pub use common_store_api::ReadAction;
+pub use common_store_api::ReadPlanReply;
 pub use common_store_api::ReadPlanResult;
 pub use common_store_api::StorageApi;
+pub use common_store_api::TableAccessStats;
 pub use common_store_api::TruncateTableResult;
 use common_streams::SendableDataBlockStream;
+use common_tracing::tracing::Instrument;
 use futures::SinkExt;
 use futures::StreamExt;
 use tonic::Request;
 
 use crate::action_declare;
 use crate::impl_flights::storage_api_impl_utils;
+pub use crate::impl_flights::storage_api_impl_utils::get_deadline;
 pub use crate::impl_flights::storage_api_impl_utils::get_meta;
+pub use crate::impl_flights::storage_api_impl_utils::get_query_label;
+use crate::rpc_tracing::TracedStream;
 use crate::RequestFor;
 use crate::StoreClient;
 use crate::StoreDoAction;
@@ -53,8 +74,14 @@ use crate::StoreDoGet;
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct ReadPlanAction {
     pub scan_plan: ScanPlan,
+    /// The least data version of the table to plan the read on, 0 for any.
+    #[serde(default)]
+    pub min_data_version: u64,
+    /// The names of the columns to read, empty for all the columns.
+    #[serde(default)]
+    pub projection: Vec<String>,
 }
-action_declare!(ReadPlanAction, ReadPlanResult, StoreDoAction::ReadPlan);
+action_declare!(ReadPlanAction, ReadPlanReply, StoreDoAction::ReadPlan);
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct TruncateTableAction {
@@ -67,6 +94,42 @@ action_declare!(
     StoreDoAction::TruncateTable
 );
 
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct OptimizeTableAction {
+    pub db: String,
+    pub table: String,
+}
+action_declare!(
+    OptimizeTableAction,
+    OptimizeTableResult,
+    StoreDoAction::OptimizeTable
+);
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct GetTableAccessStatsAction {
+    pub db: String,
+    pub table: String,
+    pub window_secs: u64,
+}
+action_declare!(
+    GetTableAccessStatsAction,
+    TableAccessStats,
+    StoreDoAction::GetTableAccessStats
+);
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CopyTableAction {
+    pub source: CopyTableSource,
+    pub db: String,
+    pub table: String,
+    pub overwrite_empty: bool,
+}
+action_declare!(CopyTableAction, CopyTableResult, StoreDoAction::CopyTable);
+
+fn flight_data_size(data: &FlightData) -> usize {
+    data.data_header.len() + data.data_body.len()
+}
+
 #[async_trait::async_trait]
 impl StorageApi for StoreClient {
     async fn read_plan(
@@ -75,9 +138,66 @@ impl StorageApi for StoreClient {
         tbl_name: String,
         scan_plan: &ScanPlan,
     ) -> common_exception::Result<ReadPlanResult> {
+        let reply = self
+            .read_plan_with_pruning(db_name, tbl_name, scan_plan)
+            .await?;
+        Ok(reply.parts)
+    }
+
+    async fn read_plan_with_pruning(
+        &self,
+        db_name: String,
+        tbl_name: String,
+        scan_plan: &ScanPlan,
+    ) -> common_exception::Result<ReadPlanReply> {
+        // The appends of this client are always seen.
+        let written = self
+            .written_versions
+            .lock()
+            .get(&(db_name.clone(), tbl_name.clone()))
+            .cloned()
+            .unwrap_or_default();
+        self.read_plan_at_version(db_name, tbl_name, scan_plan, written)
+            .await
+    }
+
+    async fn read_plan_at_version(
+        &self,
+        db_name: String,
+        tbl_name: String,
+        scan_plan: &ScanPlan,
+        min_data_version: u64,
+    ) -> common_exception::Result<ReadPlanReply> {
         let mut plan = scan_plan.clone();
         plan.schema_name = format!("{}/{}", db_name, tbl_name);
-        let plan = ReadPlanAction { scan_plan: plan };
+        let plan = ReadPlanAction {
+            scan_plan: plan,
+            min_data_version,
+            projection: vec![],
+        };
+        self.do_action(plan).await
+    }
+
+    async fn read_plan_with_projection(
+        &self,
+        db_name: String,
+        tbl_name: String,
+        scan_plan: &ScanPlan,
+        projection: Vec<String>,
+    ) -> common_exception::Result<ReadPlanReply> {
+        let written = self
+            .written_versions
+            .lock()
+            .get(&(db_name.clone(), tbl_name.clone()))
+            .cloned()
+            .unwrap_or_default();
+        let mut plan = scan_plan.clone();
+        plan.schema_name = format!("{}/{}", db_name, tbl_name);
+        let plan = ReadPlanAction {
+            scan_plan: plan,
+            min_data_version: written,
+            projection,
+        };
         self.do_action(plan).await
     }
 
@@ -86,10 +206,33 @@ impl StorageApi for StoreClient {
         schema: DataSchemaRef,
         read_action: &ReadAction,
     ) -> common_exception::Result<SendableDataBlockStream> {
+        let rpc = self.rpc_span("Read");
+        rpc.record_resource(self.redact_rpc_keys, || match &read_action.push_down {
+            PlanNode::ReadSource(plan) => format!("{}.{}", plan.db, plan.table),
+            _ => read_action.part.name.clone(),
+        });
+
         let cmd = StoreDoGet::Read(read_action.clone());
         let mut req = tonic::Request::<Ticket>::from(&cmd);
-        req.set_timeout(self.timeout);
-        let res = self.client.clone().do_get(req).await?.into_inner();
+        rpc.record_request_bytes(req.get_ref().ticket.len());
+        if let Err(e) = self.prepare_request(&mut req) {
+            rpc.fail(&e);
+            return Err(e);
+        }
+        let deadline = self.deadline;
+        let res = match self
+            .client()
+            .do_get(req)
+            .instrument(rpc.span().clone())
+            .await
+        {
+            Ok(res) => TracedStream::create(res.into_inner(), rpc),
+            Err(status) => {
+                let e = StoreClient::deadline_error(deadline, ErrorCode::from(status));
+                rpc.fail(&e);
+                return Err(e);
+            }
+        };
         let mut arrow_schema: ArrowSchemaRef = Arc::new(schema.to_arrow());
 
         // replace table schema with projected schema
@@ -99,7 +242,7 @@ impl StorageApi for StoreClient {
         }
 
         let res_stream = res.map(move |item| {
-            item.map_err(|status| ErrorCode::TokioError(status.to_string()))
+            item.map_err(|status| StoreClient::deadline_error(deadline, ErrorCode::from(status)))
                 .and_then(|item| {
                     flight_data_to_arrow_batch(&item, arrow_schema.clone(), true, &[])
                         .map_err(ErrorCode::from)
@@ -116,24 +259,28 @@ impl StorageApi for StoreClient {
         scheme_ref: DataSchemaRef,
         mut block_stream: BlockStream,
     ) -> common_exception::Result<AppendResult> {
+        let rpc = self.rpc_span("Append");
+        rpc.record_resource(self.redact_rpc_keys, || format!("{}.{}", db_name, tbl_name));
+
         let ipc_write_opt = IpcWriteOptions::default();
         let arrow_schema: ArrowSchemaRef = Arc::new(scheme_ref.to_arrow());
 
         let flight_schema = flight_data_from_arrow_schema(arrow_schema.as_ref(), &ipc_write_opt);
+        let sent_bytes = Arc::new(AtomicUsize::new(flight_data_size(&flight_schema)));
         let (mut tx, flight_stream) = futures::channel::mpsc::channel(100);
         tx.send(flight_schema)
             .await
             .map_err(|send_err| ErrorCode::BrokenChannel(send_err.to_string()))?;
 
+        let sent = sent_bytes.clone();
         tokio::spawn(async move {
             while let Some(block) = block_stream.next().await {
                 log::info!("next data block");
                 match RecordBatch::try_from(block) {
                     Ok(batch) => {
-                        if let Err(_e) = tx
-                            .send(flight_data_from_arrow_batch(&batch, &ipc_write_opt).1)
-                            .await
-                        {
+                        let data = flight_data_from_arrow_batch(&batch, &ipc_write_opt).1;
+                        sent.fetch_add(flight_data_size(&data), Ordering::Relaxed);
+                        if let Err(_e) = tx.send(data).await {
                             log::error!("failed to send flight-data to downstream, breaking out");
                             break;
                         }
@@ -152,13 +299,33 @@ impl StorageApi for StoreClient {
         let mut req = Request::new(flight_stream);
         let meta = req.metadata_mut();
         storage_api_impl_utils::put_meta(meta, &db_name, &tbl_name);
+        self.attach_query_label(meta);
+        if let Some(remaining) = self.remaining()? {
+            storage_api_impl_utils::put_deadline(meta, remaining);
+        }
 
-        let res = self.client.clone().do_put(req).await?;
-
-        match res.into_inner().message().await? {
-            Some(res) => Ok(serde_json::from_slice(&res.app_metadata)?),
-            None => Err(ErrorCode::UnknownException("Put result is empty")),
+        let res: common_exception::Result<(AppendResult, usize)> = async {
+            let res = self.client().do_put(req).await?;
+            match res.into_inner().message().await? {
+                Some(res) => {
+                    let v: AppendResult = serde_json::from_slice(&res.app_metadata)?;
+                    Ok((v, res.app_metadata.len()))
+                }
+                None => Err(ErrorCode::UnknownException("Put result is empty")),
+            }
         }
+        .instrument(rpc.span().clone())
+        .await;
+
+        rpc.record_request_bytes(sent_bytes.load(Ordering::Relaxed));
+        let response_bytes = res.as_ref().map(|(_, bytes)| *bytes).unwrap_or_default();
+        rpc.finish(&res, response_bytes);
+        let (v, _) = res?;
+
+        let mut written_versions = self.written_versions.lock();
+        let written = written_versions.entry((db_name, tbl_name)).or_default();
+        *written = (*written).max(v.data_version);
+        Ok(v)
     }
 
     async fn truncate(
@@ -168,4 +335,53 @@ impl StorageApi for StoreClient {
     ) -> common_exception::Result<TruncateTableResult> {
         self.do_action(TruncateTableAction { db, table }).await
     }
+
+    async fn optimize_table(
+        &self,
+        db: String,
+        table: String,
+    ) -> common_exception::Result<OptimizeTableResult> {
+        let res = self
+            .do_action(OptimizeTableAction {
+                db: db.clone(),
+                table: table.clone(),
+            })
+            .await?;
+
+        // The reads after it see the compacted parts.
+        let mut written_versions = self.written_versions.lock();
+        let written = written_versions.entry((db, table)).or_default();
+        *written = (*written).max(res.data_version);
+        Ok(res)
+    }
+
+    async fn get_table_access_stats(
+        &self,
+        db: String,
+        table: String,
+        window_secs: u64,
+    ) -> common_exception::Result<TableAccessStats> {
+        self.do_action(GetTableAccessStatsAction {
+            db,
+            table,
+            window_secs,
+        })
+        .await
+    }
+
+    async fn copy_table(
+        &self,
+        source: CopyTableSource,
+        db: String,
+        table: String,
+        overwrite_empty: bool,
+    ) -> common_exception::Result<CopyTableResult> {
+        self.do_action(CopyTableAction {
+            source,
+            db,
+            table,
+            overwrite_empty,
+        })
+        .await
+    }
 }
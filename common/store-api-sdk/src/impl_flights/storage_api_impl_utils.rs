@@ -13,6 +13,8 @@
 // limitations under the License.
 //
 
+use std::time::Duration;
+
 use common_exception::ErrorCode;
 use common_exception::Result;
 use tonic::metadata::Binary;
@@ -21,6 +23,8 @@ use tonic::metadata::MetadataValue;
 
 pub const META_KEY_DB_NAME: &str = "fq-db-name-bin";
 pub const META_KEY_TBL_NAME: &str = "fq-tbl-name-bin";
+pub const META_KEY_QUERY_LABEL: &str = "fq-query-label-bin";
+pub const META_KEY_DEADLINE: &str = "fq-deadline-ms-bin";
 
 pub fn put_meta(meta: &mut MetadataMap, db_name: &str, tbl_name: &str) {
     meta.insert_bin(
@@ -55,3 +59,35 @@ pub fn get_meta(meta: &MetadataMap) -> Result<(String, String)> {
     let tbl_name = fetch_string(meta, META_KEY_TBL_NAME, "invalid tbl_name meta data")?;
     Ok((db_name, tbl_name))
 }
+
+pub fn put_query_label(meta: &mut MetadataMap, query_label: &str) {
+    meta.insert_bin(
+        META_KEY_QUERY_LABEL,
+        MetadataValue::from_bytes(query_label.as_bytes()),
+    );
+}
+
+/// The label of the query on whose behalf the request is sent, if any.
+pub fn get_query_label(meta: &MetadataMap) -> Option<String> {
+    meta.get_bin(META_KEY_QUERY_LABEL)
+        .and_then(|v| v.to_bytes().ok())
+        .and_then(|b| String::from_utf8(b.to_vec()).ok())
+}
+
+/// Sends the time left for the request, the store gives up the work once it is used up.
+pub fn put_deadline(meta: &mut MetadataMap, remaining: Duration) {
+    let millis = remaining.as_millis().to_string();
+    meta.insert_bin(
+        META_KEY_DEADLINE,
+        MetadataValue::from_bytes(millis.as_bytes()),
+    );
+}
+
+/// The time left for the request when it is sent, if the sender has a deadline.
+pub fn get_deadline(meta: &MetadataMap) -> Option<Duration> {
+    meta.get_bin(META_KEY_DEADLINE)
+        .and_then(|v| v.to_bytes().ok())
+        .and_then(|b| String::from_utf8(b.to_vec()).ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(Duration::from_millis)
+}
@@ -15,10 +15,16 @@
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use tonic::metadata::MetadataMap;
 
+    use crate::impl_flights::storage_api_impl_utils::get_deadline;
     use crate::impl_flights::storage_api_impl_utils::get_meta;
+    use crate::impl_flights::storage_api_impl_utils::get_query_label;
+    use crate::impl_flights::storage_api_impl_utils::put_deadline;
     use crate::impl_flights::storage_api_impl_utils::put_meta;
+    use crate::impl_flights::storage_api_impl_utils::put_query_label;
 
     #[test]
     fn test_get_set_meta() {
@@ -30,4 +36,22 @@ mod test {
         assert_eq!(test_db, db);
         assert_eq!(test_tbl, tbl);
     }
+
+    #[test]
+    fn test_get_set_query_label() {
+        let mut meta = MetadataMap::new();
+        assert_eq!(None, get_query_label(&meta));
+
+        put_query_label(&mut meta, "team=billing");
+        assert_eq!(Some("team=billing".to_string()), get_query_label(&meta));
+    }
+
+    #[test]
+    fn test_get_set_deadline() {
+        let mut meta = MetadataMap::new();
+        assert_eq!(None, get_deadline(&meta));
+
+        put_deadline(&mut meta, Duration::from_millis(1500));
+        assert_eq!(Some(Duration::from_millis(1500)), get_deadline(&meta));
+    }
 }
@@ -18,22 +18,35 @@ pub use common_store_api::MetaApi;
 pub use common_store_api::StorageApi;
 pub use dns_resolver::ConnectionFactory;
 pub use dns_resolver::DNSResolver;
+pub use fault_injection::CutStream;
+pub use fault_injection::FaultInjector;
+pub use fault_injection::FaultKind;
+pub use fault_injection::FaultPhase;
+pub use fault_injection::FaultRule;
+pub use fault_injection::Injected;
 pub use flight_token::FlightClaim;
 pub use flight_token::FlightToken;
+pub use impl_flights::batch_api_impl;
 pub use impl_flights::kv_api_impl;
 pub use impl_flights::meta_api_impl;
 pub use impl_flights::storage_api_impl;
+pub use rpc_tracing::RpcStat;
+pub use rpc_tracing::RpcStats;
 pub use store_client::StoreClient;
+pub use store_client_conf::ChannelConf;
 pub use store_client_conf::ClientConf;
 pub use store_client_conf::StoreClientConf;
 pub use store_do_action::RequestFor;
 pub use store_do_action::StoreDoAction;
 pub use store_do_get::StoreDoGet;
+pub use store_do_get::WatchKVAction;
 
 mod common;
 mod dns_resolver;
+mod fault_injection;
 mod flight_token;
 mod impl_flights;
+mod rpc_tracing;
 mod store_client;
 #[macro_use]
 mod store_do_action;
@@ -49,3 +62,5 @@ pub mod protobuf {
 
 #[cfg(test)]
 mod dns_resolver_test;
+#[cfg(test)]
+mod fault_injection_test;
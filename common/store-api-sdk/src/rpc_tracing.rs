@@ -0,0 +1,238 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spans and per-query counters of the store RPCs.
+//!
+//! Every call of the client is wrapped in a `store_rpc` span, at debug level, that is a child of
+//! the span of the caller, e.g. the one of the interpreter running the query. The counters are
+//! only collected when a [`RpcStats`] is installed on the client.
+
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_arrow::arrow_flight::FlightData;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_tracing::tracing;
+use futures::Stream;
+
+const REDACTED: &str = "<redacted>";
+
+/// Calls of an action type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RpcStat {
+    pub count: u64,
+    pub total: Duration,
+}
+
+/// Per action type counters of the store RPCs, e.g. of a query or of all the clients of a node.
+#[derive(Debug)]
+pub struct RpcStats {
+    stats: Mutex<BTreeMap<String, RpcStat>>,
+    parent: Option<Arc<RpcStats>>,
+}
+
+impl RpcStats {
+    pub fn create() -> RpcStats {
+        RpcStats {
+            stats: Mutex::new(BTreeMap::new()),
+            parent: None,
+        }
+    }
+
+    /// The calls are counted into `parent` as well, e.g. the ones of a query into the node's.
+    pub fn create_with_parent(parent: Arc<RpcStats>) -> RpcStats {
+        RpcStats {
+            stats: Mutex::new(BTreeMap::new()),
+            parent: Some(parent),
+        }
+    }
+
+    pub fn record(&self, action: &str, elapsed: Duration) {
+        {
+            let mut stats = self.stats.lock();
+            let stat = stats.entry(action.to_string()).or_default();
+            stat.count += 1;
+            stat.total += elapsed;
+        }
+        if let Some(parent) = &self.parent {
+            parent.record(action, elapsed);
+        }
+    }
+
+    pub fn get(&self, action: &str) -> Option<RpcStat> {
+        self.stats.lock().get(action).cloned()
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, RpcStat> {
+        self.stats.lock().clone()
+    }
+
+    pub fn total_count(&self) -> u64 {
+        self.stats.lock().values().map(|stat| stat.count).sum()
+    }
+}
+
+/// The span of a single call, closed by `finish` or `fail`.
+pub(crate) struct RpcSpan {
+    span: tracing::Span,
+    action: &'static str,
+    start: Instant,
+    stats: Option<Arc<RpcStats>>,
+}
+
+impl RpcSpan {
+    pub(crate) fn create(action: &'static str, stats: Option<Arc<RpcStats>>) -> RpcSpan {
+        let span = tracing::debug_span!(
+            "store_rpc",
+            action,
+            resource = tracing::field::Empty,
+            request_bytes = tracing::field::Empty,
+            response_bytes = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+            ttfb_ms = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        );
+
+        RpcSpan {
+            span,
+            action,
+            start: Instant::now(),
+            stats,
+        }
+    }
+
+    pub(crate) fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// The resource is only built if the span is recorded by a subscriber.
+    pub(crate) fn record_resource(&self, redact: bool, resource: impl FnOnce() -> String) {
+        if self.span.is_disabled() {
+            return;
+        }
+        if redact {
+            self.span.record("resource", &REDACTED);
+        } else {
+            self.span.record("resource", &resource().as_str());
+        }
+    }
+
+    pub(crate) fn record_request_bytes(&self, bytes: usize) {
+        self.span.record("request_bytes", &(bytes as u64));
+    }
+
+    /// Time to the first message of a streamed response.
+    pub(crate) fn record_first_byte(&self) {
+        self.span
+            .record("ttfb_ms", &(self.start.elapsed().as_millis() as u64));
+    }
+
+    pub(crate) fn finish<T>(self, res: &Result<T>, response_bytes: usize) {
+        match res {
+            Ok(_) => self.finish_with("ok", response_bytes),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    pub(crate) fn fail(self, e: &ErrorCode) {
+        let outcome = format!("error: {}", e.message());
+        self.finish_with(outcome.as_str(), 0);
+    }
+
+    fn finish_with(self, outcome: &str, response_bytes: usize) {
+        let elapsed = self.start.elapsed();
+        self.span.record("response_bytes", &(response_bytes as u64));
+        self.span.record("outcome", &outcome);
+        self.span
+            .record("elapsed_ms", &(elapsed.as_millis() as u64));
+
+        if let Some(stats) = &self.stats {
+            stats.record(self.action, elapsed);
+        }
+    }
+}
+
+/// A streamed response, the call is finished at the end of the stream or when it is dropped.
+pub(crate) struct TracedStream<S> {
+    inner: S,
+    span: Option<RpcSpan>,
+    received: bool,
+    response_bytes: usize,
+}
+
+impl<S> TracedStream<S> {
+    pub(crate) fn create(inner: S, span: RpcSpan) -> TracedStream<S> {
+        TracedStream {
+            inner,
+            span: Some(span),
+            received: false,
+            response_bytes: 0,
+        }
+    }
+
+    fn finish(&mut self, outcome: &str) {
+        if let Some(span) = self.span.take() {
+            span.finish_with(outcome, self.response_bytes);
+        }
+    }
+}
+
+impl<S> Stream for TracedStream<S>
+where S: Stream<Item = std::result::Result<FlightData, tonic::Status>> + Unpin
+{
+    type Item = std::result::Result<FlightData, tonic::Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let next = match &this.span {
+            Some(span) => {
+                let _entered = span.span().enter();
+                Pin::new(&mut this.inner).poll_next(cx)
+            }
+            None => Pin::new(&mut this.inner).poll_next(cx),
+        };
+
+        match &next {
+            Poll::Ready(Some(Ok(data))) => {
+                if !this.received {
+                    this.received = true;
+                    if let Some(span) = &this.span {
+                        span.record_first_byte();
+                    }
+                }
+                this.response_bytes += data.data_header.len() + data.data_body.len();
+            }
+            Poll::Ready(Some(Err(status))) => {
+                let outcome = format!("error: {}", status.message());
+                this.finish(outcome.as_str());
+            }
+            Poll::Ready(None) => this.finish("ok"),
+            Poll::Pending => {}
+        }
+        next
+    }
+}
+
+impl<S> Drop for TracedStream<S> {
+    fn drop(&mut self) {
+        self.finish("cancelled");
+    }
+}
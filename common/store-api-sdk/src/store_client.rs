@@ -12,8 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use common_arrow::arrow_flight::flight_service_client::FlightServiceClient;
 use common_arrow::arrow_flight::Action;
@@ -21,45 +28,93 @@ use common_arrow::arrow_flight::BasicAuth;
 use common_arrow::arrow_flight::HandshakeRequest;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_infallible::Mutex;
 use common_store_api::util::STORE_RUNTIME;
 use common_store_api::util::STORE_SYNC_CALL_TIMEOUT;
 use common_tracing::tracing;
+use common_tracing::tracing::Instrument;
 use futures::stream;
 use futures::StreamExt;
 use log::info;
+use metrics::counter;
 use prost::Message;
 use serde::de::DeserializeOwned;
 use tonic::codegen::InterceptedService;
+use tonic::metadata::MetadataMap;
 use tonic::metadata::MetadataValue;
 use tonic::service::Interceptor;
 use tonic::transport::Channel;
 use tonic::Request;
 
 use crate::common::flight_result_to_str;
+use crate::fault_injection::FaultInjector;
+use crate::impl_flights::storage_api_impl_utils;
+use crate::meta_api_impl::RequestId;
+use crate::rpc_tracing::RpcSpan;
+use crate::rpc_tracing::RpcStats;
+use crate::store_client_conf::ChannelConf;
 use crate::store_client_conf::StoreClientConf;
 use crate::store_do_action::RequestFor;
 use crate::store_do_action::StoreDoAction;
 use crate::ConnectionFactory;
 use crate::RpcClientTlsConfig;
 
+type FlightClient = FlightServiceClient<InterceptedService<Channel, AuthInterceptor>>;
+
+/// The channel in use, shared by the clones of a client.
+struct ChannelSlot {
+    client: FlightClient,
+    created: Instant,
+    last_used: Instant,
+}
+
+impl ChannelSlot {
+    fn create(client: FlightClient, now: Instant) -> ChannelSlot {
+        ChannelSlot {
+            client,
+            created: now,
+            last_used: now,
+        }
+    }
+}
+
+/// Counts the channels replaced, labeled by the reason.
+pub static METRIC_CHANNEL_RECONNECTS: &str = "store_client.channel_reconnects";
+
 #[derive(Clone)]
 pub struct StoreClient {
     token: Vec<u8>,
     pub(crate) timeout: Duration,
-    pub(crate) client: FlightServiceClient<InterceptedService<Channel, AuthInterceptor>>,
+    addr: String,
+    tls_conf: Option<RpcClientTlsConfig>,
+    channel_conf: ChannelConf,
+    channel: Arc<Mutex<ChannelSlot>>,
+    client_id: String,
+    serial: Arc<AtomicU64>,
+    pub(crate) fault_injector: Option<Arc<FaultInjector>>,
+    pub(crate) rpc_stats: Option<Arc<RpcStats>>,
+    pub(crate) redact_rpc_keys: bool,
+    pub(crate) query_label: Option<String>,
+    pub(crate) deadline: Option<Instant>,
+    /// The data version of the last append to each table, by (db, table). A read plan is of at
+    /// least this version, thus the client reads its own writes.
+    pub(crate) written_versions: Arc<Mutex<HashMap<(String, String), u64>>>,
 }
 
 const AUTH_TOKEN_KEY: &str = "auth-token-bin";
 
 impl StoreClient {
     pub async fn try_new(conf: &StoreClientConf) -> Result<StoreClient> {
-        Self::with_tls_conf(
+        let mut client = Self::with_channel_conf(
             &conf.meta_service_config.address,
             &conf.meta_service_config.username,
             &conf.meta_service_config.password,
             conf.meta_service_config.tls_conf.clone(),
+            conf.channel_conf.clone(),
         )
-        .await
+        .await?;
+        client.redact_rpc_keys = conf.redact_rpc_keys;
+        Ok(client)
     }
 
     pub fn sync_try_new(conf: &StoreClientConf) -> Result<StoreClient> {
@@ -81,11 +136,27 @@ impl StoreClient {
         username: &str,
         password: &str,
         conf: Option<RpcClientTlsConfig>,
+    ) -> Result<Self> {
+        Self::with_channel_conf(addr, username, password, conf, ChannelConf::default()).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(password))]
+    pub async fn with_channel_conf(
+        addr: &str,
+        username: &str,
+        password: &str,
+        conf: Option<RpcClientTlsConfig>,
+        channel_conf: ChannelConf,
     ) -> Result<Self> {
         // TODO configuration
         let timeout = Duration::from_secs(60);
 
-        let res = ConnectionFactory::create_flight_channel(addr, Some(timeout), conf);
+        let res = ConnectionFactory::create_flight_channel_with_keepalive(
+            addr,
+            Some(timeout),
+            channel_conf.keepalive,
+            conf.clone(),
+        );
 
         tracing::debug!("connecting to {}, res: {:?}", addr, res);
 
@@ -99,10 +170,29 @@ impl StoreClient {
             FlightServiceClient::with_interceptor(channel, AuthInterceptor { token })
         };
 
+        // Unique enough to tell the clients apart in the meta state machine.
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let client_id = format!("{}-{}-{}", addr, std::process::id(), nanos);
+
+        let created = channel_conf.clock.instant();
         let rx = Self {
             token,
             timeout,
-            client,
+            addr: addr.to_string(),
+            tls_conf: conf,
+            channel_conf,
+            channel: Arc::new(Mutex::new(ChannelSlot::create(client, created))),
+            client_id,
+            serial: Arc::new(AtomicU64::new(0)),
+            fault_injector: None,
+            rpc_stats: None,
+            redact_rpc_keys: false,
+            query_label: None,
+            deadline: None,
+            written_versions: Arc::new(Mutex::new(HashMap::new())),
         };
         Ok(rx)
     }
@@ -111,6 +201,153 @@ impl StoreClient {
         self.timeout = timeout;
     }
 
+    /// Routes the calls through the injector, for the tests only.
+    pub fn set_fault_injector(&mut self, injector: Arc<FaultInjector>) {
+        self.fault_injector = Some(injector);
+    }
+
+    pub fn get_fault_injector(&self) -> Option<Arc<FaultInjector>> {
+        self.fault_injector.clone()
+    }
+
+    /// Counts the calls and their durations into `stats`, e.g. the ones of a query.
+    pub fn set_rpc_stats(&mut self, stats: Arc<RpcStats>) {
+        self.rpc_stats = Some(stats);
+    }
+
+    /// Hides the keys and the table names in the spans of the calls.
+    pub fn set_redact_rpc_keys(&mut self, redact: bool) {
+        self.redact_rpc_keys = redact;
+    }
+
+    /// Sends `label` along with every request, the store records it in its logs of the requests.
+    pub fn set_query_label(&mut self, label: impl Into<String>) {
+        self.query_label = Some(label.into());
+    }
+
+    pub(crate) fn attach_query_label(&self, meta: &mut MetadataMap) {
+        if let Some(label) = &self.query_label {
+            storage_api_impl_utils::put_query_label(meta, label);
+        }
+    }
+
+    /// Bounds every call by `deadline`, e.g. the one of the query on whose behalf it is sent.
+    /// A call is given the time left instead of the configured timeout if that is shorter,
+    /// the store is told the time left too, and gives up the work once it is used up.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    /// The time left before the deadline, if there is one.
+    pub(crate) fn remaining(&self) -> Result<Option<Duration>> {
+        match self.deadline {
+            None => Ok(None),
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(ErrorCode::DeadlineExceeded(
+                        "the deadline is exceeded before the request is sent",
+                    ));
+                }
+                Ok(Some(remaining))
+            }
+        }
+    }
+
+    /// The configured timeout, or the time left before the deadline if that is shorter.
+    pub(crate) fn call_timeout(&self) -> Result<Duration> {
+        Ok(self
+            .remaining()?
+            .map_or(self.timeout, |remaining| remaining.min(self.timeout)))
+    }
+
+    /// Sets the timeout, the query label and the deadline of a request.
+    /// Fails without sending anything if the deadline is already exceeded.
+    pub(crate) fn prepare_request<T>(&self, req: &mut Request<T>) -> Result<()> {
+        if let Some(remaining) = self.remaining()? {
+            storage_api_impl_utils::put_deadline(req.metadata_mut(), remaining);
+        }
+        req.set_timeout(self.call_timeout()?);
+        self.attach_query_label(req.metadata_mut());
+        Ok(())
+    }
+
+    /// Reports an error of a call that runs past the deadline as `DeadlineExceeded`,
+    /// whatever the transport makes of it, e.g. a cancelled call.
+    pub(crate) fn deadline_error(deadline: Option<Instant>, e: ErrorCode) -> ErrorCode {
+        match deadline {
+            Some(deadline)
+                if Instant::now() >= deadline
+                    && e.code() != ErrorCode::DeadlineExceeded("").code() =>
+            {
+                ErrorCode::DeadlineExceeded(format!("the deadline is exceeded, cause: {}", e))
+            }
+            _ => e,
+        }
+    }
+
+    /// Returns the client of the channel, which is replaced with a new one first
+    /// if it is idle or old for longer than the `ChannelConf` allows.
+    pub(crate) fn client(&self) -> FlightClient {
+        let mut slot = self.channel.lock();
+        let now = self.channel_conf.clock.instant();
+        let expired = |limit: Option<Duration>, since: Instant| {
+            limit
+                .map(|limit| now.duration_since(since) >= limit)
+                .unwrap_or(false)
+        };
+
+        let reason = if expired(self.channel_conf.max_idle, slot.last_used) {
+            Some("idle")
+        } else if expired(self.channel_conf.max_lifetime, slot.created) {
+            Some("lifetime")
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            match self.connect() {
+                Ok(client) => {
+                    info!("replace the channel to {}: {}", self.addr, reason);
+                    counter!(METRIC_CHANNEL_RECONNECTS, 1, "reason" => reason);
+                    *slot = ChannelSlot::create(client, now);
+                }
+                // The old channel is kept, the next call tries again.
+                Err(e) => log::warn!("failed to replace the channel to {}: {}", self.addr, e),
+            }
+        }
+
+        slot.last_used = now;
+        slot.client.clone()
+    }
+
+    /// Creates a new channel, the token from the handshake is still valid for it.
+    fn connect(&self) -> Result<FlightClient> {
+        let channel = ConnectionFactory::create_flight_channel_with_keepalive(
+            &self.addr,
+            Some(self.timeout),
+            self.channel_conf.keepalive,
+            self.tls_conf.clone(),
+        )?;
+        Ok(FlightServiceClient::with_interceptor(
+            channel,
+            AuthInterceptor {
+                token: self.token.clone(),
+            },
+        ))
+    }
+
+    pub(crate) fn rpc_span(&self, action: &'static str) -> RpcSpan {
+        RpcSpan::create(action, self.rpc_stats.clone())
+    }
+
+    pub(crate) fn next_request_id(&self) -> RequestId {
+        RequestId {
+            client: self.client_id.clone(),
+            serial: self.serial.fetch_add(1, Ordering::SeqCst) + 1,
+        }
+    }
+
     /// Handshake.
     #[tracing::instrument(level = "debug", skip(client, password))]
     async fn handshake(
@@ -142,7 +379,6 @@ impl StoreClient {
         Ok(token)
     }
 
-    #[tracing::instrument(level = "debug", skip(self, v))]
     pub(crate) async fn do_action<T, R>(&self, v: T) -> Result<R>
     where
         T: RequestFor<Reply = R>,
@@ -150,12 +386,39 @@ impl StoreClient {
         R: DeserializeOwned,
     {
         let act: StoreDoAction = v.into();
-        let req: Request<Action> = (&act).try_into()?;
+        let rpc = self.rpc_span(act.name());
+        rpc.record_resource(self.redact_rpc_keys, || act.resource());
+
+        let res = async {
+            match &self.fault_injector {
+                None => self.do_action_once(&act, &rpc).await,
+                Some(injector) => {
+                    let call = || self.do_action_once(&act, &rpc);
+                    injector
+                        .intercept(act.name(), self.call_timeout()?, call)
+                        .await
+                }
+            }
+        }
+        .instrument(rpc.span().clone())
+        .await;
+
+        let res = res.map_err(|e| Self::deadline_error(self.deadline, e));
+        let response_bytes = res.as_ref().map(|(_, bytes)| *bytes).unwrap_or_default();
+        rpc.finish(&res, response_bytes);
+        res.map(|(v, _)| v)
+    }
+
+    /// Returns the reply and its size in bytes.
+    async fn do_action_once<R>(&self, act: &StoreDoAction, rpc: &RpcSpan) -> Result<(R, usize)>
+    where R: DeserializeOwned {
+        let req: Request<Action> = act.try_into()?;
         let mut req = common_tracing::inject_span_to_tonic_request(req);
+        rpc.record_request_bytes(req.get_ref().body.len());
 
-        req.set_timeout(self.timeout);
+        self.prepare_request(&mut req)?;
 
-        let mut stream = self.client.clone().do_action(req).await?.into_inner();
+        let mut stream = self.client().do_action(req).await?.into_inner();
         match stream.message().await? {
             None => Err(ErrorCode::EmptyData(format!(
                 "Can not receive data from store flight server, action: {:?}",
@@ -164,7 +427,7 @@ impl StoreClient {
             Some(resp) => {
                 info!("do_action: resp: {:}", flight_result_to_str(&resp));
                 let v = serde_json::from_slice::<R>(&resp.body)?;
-                Ok(v)
+                Ok((v, resp.body.len()))
             }
         }
     }
@@ -13,6 +13,10 @@
 //  limitations under the License.
 //
 
+use std::time::Duration;
+
+use common_runtime::SharedClock;
+
 use crate::RpcClientTlsConfig;
 
 #[derive(Clone, Debug, Default)]
@@ -21,6 +25,9 @@ pub struct StoreClientConf {
     pub kv_service_config: ClientConf,
     // deprecated, should be replace by FuseDFS config
     pub block_service_config: ClientConf,
+    /// Hide the keys and the table names in the spans of the RPCs.
+    pub redact_rpc_keys: bool,
+    pub channel_conf: ChannelConf,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -36,3 +43,19 @@ impl ClientConf {
         self.address.is_empty()
     }
 }
+
+/// The lifecycle of the channel of a StoreClient, the default keeps a channel forever.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelConf {
+    /// A channel unused for this long is replaced with a new one on the next call,
+    /// before a NAT or a load balancer drops its connection silently.
+    pub max_idle: Option<Duration>,
+    /// A channel this old is replaced with a new one on the next call,
+    /// to spread the clients over the backends of a load balancer.
+    pub max_lifetime: Option<Duration>,
+    /// Send an HTTP/2 ping at this interval, even if there is no call. A ping not acknowledged
+    /// within the interval closes the connection, and the next call connects again.
+    pub keepalive: Option<Duration>,
+    /// The source of time of `max_idle` and `max_lifetime`.
+    pub clock: SharedClock,
+}
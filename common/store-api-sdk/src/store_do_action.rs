@@ -20,18 +20,38 @@ use common_exception::ErrorCode;
 use prost::Message;
 use tonic::Request;
 
+use crate::impl_flights::batch_api_impl::BatchAction;
+use crate::impl_flights::kv_api_impl::CasKVAction;
+use crate::impl_flights::kv_api_impl::DeletePrefixKVAction;
 use crate::impl_flights::kv_api_impl::GetKVAction;
 use crate::impl_flights::kv_api_impl::KVMetaAction;
 use crate::impl_flights::kv_api_impl::MGetKVAction;
+use crate::impl_flights::kv_api_impl::MergeKVAction;
+use crate::impl_flights::kv_api_impl::PatchKVJsonAction;
+use crate::impl_flights::kv_api_impl::PrefixListBySeqReq;
+use crate::impl_flights::kv_api_impl::PrefixListPageReq;
 use crate::impl_flights::kv_api_impl::PrefixListReq;
 use crate::impl_flights::kv_api_impl::UpsertKVAction;
 use crate::impl_flights::meta_api_impl::CreateDatabaseAction;
 use crate::impl_flights::meta_api_impl::CreateTableAction;
+use crate::impl_flights::meta_api_impl::CreateTablesAction;
 use crate::impl_flights::meta_api_impl::DropDatabaseAction;
 use crate::impl_flights::meta_api_impl::DropTableAction;
 use crate::impl_flights::meta_api_impl::GetDatabaseAction;
 use crate::impl_flights::meta_api_impl::GetDatabaseMetaAction;
+use crate::impl_flights::meta_api_impl::GetDatabaseUsagesAction;
+use crate::impl_flights::meta_api_impl::GetDroppedTablesAction;
 use crate::impl_flights::meta_api_impl::GetTableAction;
+use crate::impl_flights::meta_api_impl::ListDatabasesAction;
+use crate::impl_flights::meta_api_impl::ListTablesAction;
+use crate::impl_flights::meta_api_impl::ModifyColumnAction;
+use crate::impl_flights::meta_api_impl::ReconcileDatabaseUsageAction;
+use crate::impl_flights::meta_api_impl::RenameTableAction;
+use crate::impl_flights::meta_api_impl::SetDatabaseQuotaAction;
+use crate::impl_flights::meta_api_impl::UndropTableAction;
+use crate::impl_flights::storage_api_impl::CopyTableAction;
+use crate::impl_flights::storage_api_impl::GetTableAccessStatsAction;
+use crate::impl_flights::storage_api_impl::OptimizeTableAction;
 use crate::impl_flights::storage_api_impl::ReadPlanAction;
 use crate::impl_flights::storage_api_impl::TruncateTableAction;
 use crate::meta_api_impl::GetTableExtReq;
@@ -62,21 +82,158 @@ pub enum StoreDoAction {
     // database meta
     CreateDatabase(CreateDatabaseAction),
     GetDatabase(GetDatabaseAction),
+    ListDatabases(ListDatabasesAction),
     DropDatabase(DropDatabaseAction),
+    SetDatabaseQuota(SetDatabaseQuotaAction),
+    GetDatabaseUsages(GetDatabaseUsagesAction),
+    ReconcileDatabaseUsage(ReconcileDatabaseUsageAction),
     CreateTable(CreateTableAction),
+    CreateTables(CreateTablesAction),
     DropTable(DropTableAction),
+    UndropTable(UndropTableAction),
+    RenameTable(RenameTableAction),
+    GetDroppedTables(GetDroppedTablesAction),
+    ModifyColumn(ModifyColumnAction),
     GetTable(GetTableAction),
+    ListTables(ListTablesAction),
     GetTableExt(GetTableExtReq),
     GetDatabaseMeta(GetDatabaseMetaAction),
     ReadPlan(ReadPlanAction),
     TruncateTable(TruncateTableAction),
+    OptimizeTable(OptimizeTableAction),
+    GetTableAccessStats(GetTableAccessStatsAction),
+    CopyTable(CopyTableAction),
 
     // general purpose kv
     UpsertKV(UpsertKVAction),
     UpdateKVMeta(KVMetaAction),
+    CasKV(CasKVAction),
+    MergeKV(MergeKVAction),
+    PatchKVJson(PatchKVJsonAction),
     GetKV(GetKVAction),
     MGetKV(MGetKVAction),
     PrefixListKV(PrefixListReq),
+    PrefixListKVBySeq(PrefixListBySeqReq),
+    PrefixListKVPage(PrefixListPageReq),
+    DeletePrefixKV(DeletePrefixKVAction),
+
+    // read-only actions in one request
+    Batch(BatchAction),
+}
+
+impl StoreDoAction {
+    /// The action type, to tell the actions apart without their payload.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoreDoAction::CreateDatabase(_) => "CreateDatabase",
+            StoreDoAction::GetDatabase(_) => "GetDatabase",
+            StoreDoAction::ListDatabases(_) => "ListDatabases",
+            StoreDoAction::DropDatabase(_) => "DropDatabase",
+            StoreDoAction::SetDatabaseQuota(_) => "SetDatabaseQuota",
+            StoreDoAction::GetDatabaseUsages(_) => "GetDatabaseUsages",
+            StoreDoAction::ReconcileDatabaseUsage(_) => "ReconcileDatabaseUsage",
+            StoreDoAction::CreateTable(_) => "CreateTable",
+            StoreDoAction::CreateTables(_) => "CreateTables",
+            StoreDoAction::DropTable(_) => "DropTable",
+            StoreDoAction::UndropTable(_) => "UndropTable",
+            StoreDoAction::RenameTable(_) => "RenameTable",
+            StoreDoAction::GetDroppedTables(_) => "GetDroppedTables",
+            StoreDoAction::ModifyColumn(_) => "ModifyColumn",
+            StoreDoAction::GetTable(_) => "GetTable",
+            StoreDoAction::ListTables(_) => "ListTables",
+            StoreDoAction::GetTableExt(_) => "GetTableExt",
+            StoreDoAction::GetDatabaseMeta(_) => "GetDatabaseMeta",
+            StoreDoAction::ReadPlan(_) => "ReadPlan",
+            StoreDoAction::TruncateTable(_) => "TruncateTable",
+            StoreDoAction::OptimizeTable(_) => "OptimizeTable",
+            StoreDoAction::GetTableAccessStats(_) => "GetTableAccessStats",
+            StoreDoAction::CopyTable(_) => "CopyTable",
+            StoreDoAction::UpsertKV(_) => "UpsertKV",
+            StoreDoAction::UpdateKVMeta(_) => "UpdateKVMeta",
+            StoreDoAction::CasKV(_) => "CasKV",
+            StoreDoAction::MergeKV(_) => "MergeKV",
+            StoreDoAction::PatchKVJson(_) => "PatchKVJson",
+            StoreDoAction::GetKV(_) => "GetKV",
+            StoreDoAction::MGetKV(_) => "MGetKV",
+            StoreDoAction::PrefixListKV(_) => "PrefixListKV",
+            StoreDoAction::PrefixListKVBySeq(_) => "PrefixListKVBySeq",
+            StoreDoAction::PrefixListKVPage(_) => "PrefixListKVPage",
+            StoreDoAction::DeletePrefixKV(_) => "DeletePrefixKV",
+            StoreDoAction::Batch(_) => "Batch",
+        }
+    }
+
+    /// The database, table or key the action is about, for tracing.
+    pub fn resource(&self) -> String {
+        match self {
+            StoreDoAction::CreateDatabase(a) => a.plan.db.clone(),
+            StoreDoAction::GetDatabase(a) => a.db.clone(),
+            StoreDoAction::ListDatabases(_) => "".to_string(),
+            StoreDoAction::DropDatabase(a) => a.plan.db.clone(),
+            StoreDoAction::SetDatabaseQuota(a) => a.db.clone(),
+            StoreDoAction::GetDatabaseUsages(_) => "".to_string(),
+            StoreDoAction::ReconcileDatabaseUsage(a) => a.db.clone(),
+            StoreDoAction::CreateTable(a) => format!("{}.{}", a.plan.db, a.plan.table),
+            StoreDoAction::CreateTables(a) => a
+                .plans
+                .iter()
+                .map(|p| format!("{}.{}", p.db, p.table))
+                .collect::<Vec<_>>()
+                .join(","),
+            StoreDoAction::DropTable(a) => format!("{}.{}", a.plan.db, a.plan.table),
+            StoreDoAction::UndropTable(a) => format!("{}.{}", a.plan.db, a.plan.table),
+            StoreDoAction::RenameTable(a) => format!("{}.{}", a.plan.db, a.plan.table),
+            StoreDoAction::GetDroppedTables(_) => "".to_string(),
+            StoreDoAction::ModifyColumn(a) => format!("{}.{}", a.plan.db, a.plan.table),
+            StoreDoAction::GetTable(a) => format!("{}.{}", a.db, a.table),
+            StoreDoAction::ListTables(a) => a.db.clone(),
+            StoreDoAction::GetTableExt(a) => format!("table_id:{}", a.tbl_id),
+            StoreDoAction::GetDatabaseMeta(_) => "".to_string(),
+            StoreDoAction::ReadPlan(a) => a.scan_plan.schema_name.replace('/', "."),
+            StoreDoAction::TruncateTable(a) => format!("{}.{}", a.db, a.table),
+            StoreDoAction::OptimizeTable(a) => format!("{}.{}", a.db, a.table),
+            StoreDoAction::GetTableAccessStats(a) => format!("{}.{}", a.db, a.table),
+            StoreDoAction::CopyTable(a) => format!("{}.{}", a.db, a.table),
+            StoreDoAction::UpsertKV(a) => a.key.clone(),
+            StoreDoAction::UpdateKVMeta(a) => a.key.clone(),
+            StoreDoAction::CasKV(a) => a.key.clone(),
+            StoreDoAction::MergeKV(a) => a.key.clone(),
+            StoreDoAction::PatchKVJson(a) => a.key.clone(),
+            StoreDoAction::GetKV(a) => a.key.clone(),
+            StoreDoAction::MGetKV(a) => a.keys.join(","),
+            StoreDoAction::PrefixListKV(a) => a.0.clone(),
+            StoreDoAction::PrefixListKVBySeq(a) => a.prefix.clone(),
+            StoreDoAction::PrefixListKVPage(a) => a.prefix.clone(),
+            StoreDoAction::DeletePrefixKV(a) => a.prefix.clone(),
+            StoreDoAction::Batch(a) => a
+                .actions
+                .iter()
+                .map(|a| a.name())
+                .collect::<Vec<_>>()
+                .join(","),
+        }
+    }
+
+    /// Whether the action is allowed in a batch: it reads the meta data only, and the same
+    /// action sent again gets the same reply as long as nothing is changed meanwhile.
+    pub fn is_batchable(&self) -> bool {
+        matches!(
+            self,
+            StoreDoAction::GetDatabase(_)
+                | StoreDoAction::ListDatabases(_)
+                | StoreDoAction::GetDatabaseUsages(_)
+                | StoreDoAction::GetDatabaseMeta(_)
+                | StoreDoAction::GetDroppedTables(_)
+                | StoreDoAction::GetTable(_)
+                | StoreDoAction::ListTables(_)
+                | StoreDoAction::GetTableExt(_)
+                | StoreDoAction::GetKV(_)
+                | StoreDoAction::MGetKV(_)
+                | StoreDoAction::PrefixListKV(_)
+                | StoreDoAction::PrefixListKVBySeq(_)
+                | StoreDoAction::PrefixListKVPage(_)
+        )
+    }
 }
 
 /// Try convert tonic::Request<Action> to DoActionAction.
@@ -30,11 +30,21 @@ pub struct PullAction {
     pub key: String,
 }
 
+/// Watch the changes of the generic-kv records under a prefix, a `KVChange` per message.
+/// With `after_seq`, the records under the prefix with a greater seq are sent first.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct WatchKVAction {
+    pub prefix: String,
+    #[serde(default)]
+    pub after_seq: Option<u64>,
+}
+
 // Action wrapper for do_get.
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub enum StoreDoGet {
     Read(ReadAction),
     Pull(PullAction),
+    WatchKV(WatchKVAction),
 }
 
 /// Try convert tonic::Request<Ticket> to StoreDoGet.
@@ -0,0 +1,105 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+/// The version of the filters built by the appends, bumped whenever the hashing or the layout
+/// changes. The filters of another version are kept along their parts but never consulted.
+pub const BLOOM_FILTER_VERSION: u32 = 1;
+
+/// The most hash functions of a filter, a lower false positive rate only makes it larger.
+const MAX_HASHES: u32 = 16;
+
+/// A bloom filter of the keys of a column in a part.
+///
+/// A key added is always found, a key not added is found with about the false positive rate
+/// the filter is sized for.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct BloomFilter {
+    hashes: u32,
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// A filter of `keys` keys, at most `fpp` of the keys not added are found in it.
+    pub fn with_fpp(keys: usize, fpp: f64) -> BloomFilter {
+        let keys = keys.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-keys * fpp.ln() / (ln2 * ln2)).ceil().max(64.0) as usize;
+        let hashes = (bits as f64 / keys * ln2)
+            .round()
+            .clamp(1.0, MAX_HASHES as f64) as u32;
+
+        BloomFilter {
+            hashes,
+            bits: vec![0; (bits + 63) / 64],
+        }
+    }
+
+    pub fn add(&mut self, key: &[u8]) {
+        for bit in self.bit_positions(key) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    /// False if the key is surely not added, true if it may be.
+    pub fn may_contain(&self, key: &[u8]) -> bool {
+        self.bit_positions(key)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// The positions of the key, by the double hashing of its FNV-1a hash.
+    /// The hashes are kept with the parts, they must not depend on the process building them.
+    fn bit_positions(&self, key: &[u8]) -> impl Iterator<Item = usize> {
+        let hash = key.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        let h1 = mix(hash);
+        let h2 = mix(hash ^ 0x9e37_79b9_7f4a_7c15) | 1;
+        let len = (self.bits.len() * 64) as u64;
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+}
+
+/// The finalizer of splitmix64, the bits of the hash are spread over the whole word.
+fn mix(hash: u64) -> u64 {
+    let hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// The bloom filters of the indexed columns of a part, built when the part is appended.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct PartBloomFilters {
+    pub version: u32,
+    pub columns: BTreeMap<String, BloomFilter>,
+}
+
+impl PartBloomFilters {
+    pub fn create() -> PartBloomFilters {
+        PartBloomFilters {
+            version: BLOOM_FILTER_VERSION,
+            columns: BTreeMap::new(),
+        }
+    }
+
+    /// The filter of a column, `None` if the column is not indexed in the part,
+    /// or if the filters are of another version.
+    pub fn column(&self, name: &str) -> Option<&BloomFilter> {
+        match self.version {
+            BLOOM_FILTER_VERSION => self.columns.get(name),
+            _ => None,
+        }
+    }
+}
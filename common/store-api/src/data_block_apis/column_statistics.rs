@@ -0,0 +1,84 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// The statistics of the columns of a part, by column name. Only the numeric and the string
+/// columns have statistics.
+pub type PartColumnStatistics = BTreeMap<String, ColumnStatistics>;
+
+/// The range and the nulls of a column in a part, taken when the part is appended.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ColumnStatistics {
+    /// The smallest value, `None` if every value is null.
+    pub min: Option<ColumnBound>,
+    /// The largest value, `None` if every value is null.
+    pub max: Option<ColumnBound>,
+    pub null_count: u64,
+}
+
+impl ColumnStatistics {
+    /// Widens the range to include a value.
+    pub fn add(&mut self, value: ColumnBound) {
+        match &self.min {
+            Some(min) if value.compare(min) != Some(Ordering::Less) => {}
+            _ => self.min = Some(value.clone()),
+        }
+        match &self.max {
+            Some(max) if value.compare(max) != Some(Ordering::Greater) => {}
+            _ => self.max = Some(value),
+        }
+    }
+}
+
+/// A min or a max of a column, or a literal compared with them.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub enum ColumnBound {
+    Int(i64),
+    UInt(u64),
+    /// Never NaN, a float column with a NaN has no statistics.
+    Float(f64),
+    /// The bytes of a string, compared bytewise.
+    String(Vec<u8>),
+}
+
+impl Eq for ColumnBound {}
+
+impl ColumnBound {
+    /// Compares two bounds by value, `None` if they are not comparable: a number and a string,
+    /// or an integer and a float the integer can not be converted to exactly.
+    pub fn compare(&self, other: &ColumnBound) -> Option<Ordering> {
+        match (self, other) {
+            (ColumnBound::Int(a), ColumnBound::Int(b)) => Some(a.cmp(b)),
+            (ColumnBound::UInt(a), ColumnBound::UInt(b)) => Some(a.cmp(b)),
+            (ColumnBound::Int(a), ColumnBound::UInt(b)) => Some((*a as i128).cmp(&(*b as i128))),
+            (ColumnBound::UInt(a), ColumnBound::Int(b)) => Some((*a as i128).cmp(&(*b as i128))),
+            (ColumnBound::String(a), ColumnBound::String(b)) => Some(a.cmp(b)),
+            (ColumnBound::String(_), _) | (_, ColumnBound::String(_)) => None,
+            (a, b) => a.as_exact_f64()?.partial_cmp(&b.as_exact_f64()?),
+        }
+    }
+
+    fn as_exact_f64(&self) -> Option<f64> {
+        // The integers of at most 53 bits are exact as f64.
+        const MAX_EXACT: u64 = 1 << 53;
+        match self {
+            ColumnBound::Int(v) if v.unsigned_abs() <= MAX_EXACT => Some(*v as f64),
+            ColumnBound::UInt(v) if *v <= MAX_EXACT => Some(*v as f64),
+            ColumnBound::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
@@ -16,18 +16,85 @@
 use common_datablocks::DataBlock;
 use common_datavalues::DataSchemaRef;
 use common_planners::Part;
+use common_planners::PartChecksum;
+use common_planners::PartReadInfo;
+use common_planners::PartStorageClass;
 use common_planners::PlanNode;
 use common_planners::ScanPlan;
 use common_planners::Statistics;
 use common_streams::SendableDataBlockStream;
 
+use crate::data_block_apis::bloom_filter::PartBloomFilters;
+use crate::data_block_apis::column_statistics::PartColumnStatistics;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct DataPartInfo {
     pub part: Part,
     pub stats: Statistics,
+    /// The format the part is written in, by the engine of its table, `None` for parquet.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// The bloom filters of the indexed columns, `None` for the parts appended before the
+    /// columns are indexed. The store keeps them apart from the descriptors and sets them only
+    /// to prune a read plan, the read plans are sent without them.
+    #[serde(default)]
+    pub bloom_filters: Option<PartBloomFilters>,
+    /// Where the bytes of the part are kept, the parts appended before it is recorded are files.
+    #[serde(default)]
+    pub storage: PartStorageClass,
+    /// The size and the digest of the file of the part, `None` for the inline parts and the
+    /// parts appended before it is recorded.
+    #[serde(default)]
+    pub checksum: Option<PartChecksum>,
+    /// The min, the max and the nulls of the columns, `None` for the parts appended before
+    /// they are recorded. A read plan skips the parts whose ranges rule out its filters.
+    #[serde(default)]
+    pub column_statistics: Option<PartColumnStatistics>,
+}
+
+impl DataPartInfo {
+    /// What a read of the part needs, carried by the part in the read plans.
+    pub fn read_info(&self) -> PartReadInfo {
+        PartReadInfo {
+            format: self.format.clone(),
+            storage: self.storage,
+            checksum: self.checksum.clone(),
+        }
+    }
 }
+
 pub type ReadPlanResult = Option<Vec<DataPartInfo>>;
 
+/// The parts of a table skipped by the store when it plans a read, for EXPLAIN.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PartsPruning {
+    /// The parts of the table before pruning.
+    pub total: usize,
+    /// The parts whose bloom filters are consulted.
+    pub bloom_checked: usize,
+    /// The parts ruled out by their bloom filters.
+    pub pruned_by_bloom: usize,
+    /// The parts whose column ranges are compared with the filters.
+    #[serde(default)]
+    pub range_checked: usize,
+    /// The parts ruled out by their column ranges.
+    #[serde(default)]
+    pub pruned_by_range: usize,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReadPlanReply {
+    pub parts: ReadPlanResult,
+    pub pruning: PartsPruning,
+    /// The data version of the table the parts are of.
+    #[serde(default)]
+    pub data_version: u64,
+    /// The schema of the columns asked for, `None` if all the columns are read. The read
+    /// bytes of the parts are of these columns only.
+    #[serde(default)]
+    pub projected_schema: Option<DataSchemaRef>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct ReadAction {
     pub part: Part,
@@ -55,9 +122,24 @@ pub struct PartitionInfo {
     pub wire_bytes: usize,
     pub disk_bytes: usize,
     pub location: String,
+    /// The format the part is written in, `None` for parquet.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// The bloom filters of the indexed columns of the table, see `DataPartInfo`.
+    #[serde(default)]
+    pub bloom_filters: Option<PartBloomFilters>,
+    #[serde(default)]
+    pub storage: PartStorageClass,
+    /// The checksum of the file of the part, see `DataPartInfo`.
+    #[serde(default)]
+    pub checksum: Option<PartChecksum>,
+    /// The statistics of the columns of the part, see `DataPartInfo`.
+    #[serde(default)]
+    pub column_statistics: Option<PartColumnStatistics>,
 }
 
 impl AppendResult {
+    /// Appends a part written in parquet.
     pub fn append_part(
         &mut self,
         location: &str,
@@ -65,6 +147,18 @@ impl AppendResult {
         cols: usize,
         wire_bytes: usize,
         disk_bytes: usize,
+    ) {
+        self.append_part_with_format(location, None, rows, cols, wire_bytes, disk_bytes)
+    }
+
+    pub fn append_part_with_format(
+        &mut self,
+        location: &str,
+        format: Option<&str>,
+        rows: usize,
+        cols: usize,
+        wire_bytes: usize,
+        disk_bytes: usize,
     ) {
         let part = PartitionInfo {
             rows,
@@ -72,6 +166,11 @@ impl AppendResult {
             wire_bytes,
             disk_bytes,
             location: location.to_string(),
+            format: format.map(|f| f.to_string()),
+            bloom_filters: None,
+            storage: PartStorageClass::File,
+            checksum: None,
+            column_statistics: None,
         };
         self.parts.push(part);
         self.summary.increase(rows, wire_bytes, disk_bytes);
@@ -84,6 +183,10 @@ pub struct AppendResult {
     pub parts: Vec<PartitionInfo>,
     pub session_id: String,
     pub tx_id: String,
+    /// The data version of the table the parts are registered under, a read plan of at least
+    /// this version has them.
+    #[serde(default)]
+    pub data_version: u64,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -91,6 +194,58 @@ pub struct TruncateTableResult {
     pub truncated_table_data_parts_count: usize,
 }
 
+/// The progress of a run of the compaction of the small parts of a table.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OptimizeTableResult {
+    pub parts_before: usize,
+    pub parts_after: usize,
+    /// The bytes of the parts read and written again.
+    pub bytes_rewritten: u64,
+    /// False if the run stops at the max bytes per run with parts left to compact,
+    /// another run goes on from there.
+    pub finished: bool,
+    /// The data version of the table after the last swap of the parts, 0 if none is swapped.
+    pub data_version: u64,
+}
+
+/// Rows and bytes of a table served by the store in a time window.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TableAccessStats {
+    pub rows_read: u64,
+    pub bytes_read: u64,
+    pub rows_written: u64,
+    pub bytes_written: u64,
+}
+
+/// The store a table is copied from.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Eq, PartialEq)]
+pub struct CopyTableSource {
+    /// The flight api address of the source store.
+    pub address: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for CopyTableSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The action is logged when it is received, the password is not.
+        f.debug_struct("CopyTableSource")
+            .field("address", &self.address)
+            .field("username", &self.username)
+            .finish()
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CopyTableResult {
+    /// Parts copied by this run.
+    pub copied_parts: usize,
+    /// Parts copied by an interrupted run before, which are not copied again.
+    pub skipped_parts: usize,
+    /// Rows of the copied table, the same at both stores.
+    pub rows: usize,
+}
+
 // TODO A better name, we already have a SendableDataBlockStream
 pub type BlockStream =
     std::pin::Pin<Box<dyn futures::stream::Stream<Item = DataBlock> + Sync + Send + 'static>>;
@@ -104,6 +259,35 @@ pub trait StorageApi: Send + Sync {
         scan_plan: &ScanPlan,
     ) -> common_exception::Result<ReadPlanResult>;
 
+    /// The same as `read_plan`, with the parts skipped by the store.
+    async fn read_plan_with_pruning(
+        &self,
+        db_name: String,
+        tbl_name: String,
+        scan_plan: &ScanPlan,
+    ) -> common_exception::Result<ReadPlanReply>;
+
+    /// The same as `read_plan_with_pruning`, of a view of the table at least as new as
+    /// `min_data_version`, e.g. the version an append is replied with. The store waits briefly
+    /// for the view to catch up, and fails with StaleRead if it does not.
+    async fn read_plan_at_version(
+        &self,
+        db_name: String,
+        tbl_name: String,
+        scan_plan: &ScanPlan,
+        min_data_version: u64,
+    ) -> common_exception::Result<ReadPlanReply>;
+
+    /// The same as `read_plan_with_pruning`, of the named columns only, all the columns if
+    /// `projection` is empty. Fails with UnknownColumn if a column is not in the table.
+    async fn read_plan_with_projection(
+        &self,
+        db_name: String,
+        tbl_name: String,
+        scan_plan: &ScanPlan,
+        projection: Vec<String>,
+    ) -> common_exception::Result<ReadPlanReply>;
+
     /// Get partition.
     async fn read_partition(
         &self,
@@ -124,4 +308,30 @@ pub trait StorageApi: Send + Sync {
         db: String,
         table: String,
     ) -> common_exception::Result<TruncateTableResult>;
+
+    /// Compacts the adjacent small parts of the table into parts of the target size.
+    async fn optimize_table(
+        &self,
+        db: String,
+        table: String,
+    ) -> common_exception::Result<OptimizeTableResult>;
+
+    /// Get the rows and bytes of the table read and written in the last `window_secs` seconds.
+    async fn get_table_access_stats(
+        &self,
+        db: String,
+        table: String,
+        window_secs: u64,
+    ) -> common_exception::Result<TableAccessStats>;
+
+    /// Copy a table from another store into the store the client is connected to.
+    /// The table must not exist at the destination, unless it is empty and `overwrite_empty` is set.
+    /// An interrupted copy is resumed by calling it again, the parts already copied are skipped.
+    async fn copy_table(
+        &self,
+        source: CopyTableSource,
+        db: String,
+        table: String,
+        overwrite_empty: bool,
+    ) -> common_exception::Result<CopyTableResult>;
 }
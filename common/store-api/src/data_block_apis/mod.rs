@@ -12,4 +12,6 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 //
+pub mod bloom_filter;
+pub mod column_statistics;
 pub mod data_block_api;
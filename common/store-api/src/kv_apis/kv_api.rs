@@ -14,9 +14,12 @@
 //
 
 use async_trait::async_trait;
+use common_exception::ErrorCode;
+use common_metatypes::JsonPatchOp;
 use common_metatypes::KVMeta;
 use common_metatypes::KVValue;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
 use common_metatypes::SeqValue;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -27,6 +30,33 @@ pub struct UpsertKVActionResult {
     pub result: Option<SeqValue<KVValue>>,
 }
 
+impl UpsertKVActionResult {
+    /// Whether the upsert changed nothing, i.e., it is rejected by the `MatchSeq`.
+    /// An applied upsert always gives the value a new seq, or deletes an existent one.
+    pub fn is_unchanged(&self) -> bool {
+        self.prev == self.result
+    }
+
+    /// Drops the value bytes of an unchanged result, the seq and the meta are kept.
+    pub fn without_conflict_value(self) -> Self {
+        if !self.is_unchanged() {
+            return self;
+        }
+        let lean = |sv: Option<SeqValue<KVValue>>| {
+            sv.map(|(seq, v)| {
+                (seq, KVValue {
+                    meta: v.meta,
+                    value: vec![],
+                })
+            })
+        };
+        UpsertKVActionResult {
+            prev: lean(self.prev),
+            result: lean(self.result),
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct GetKVActionResult {
     pub result: Option<SeqValue<KVValue>>,
@@ -39,6 +69,14 @@ pub struct MGetKVActionResult {
 
 pub type PrefixListReply = Vec<(String, SeqValue<KVValue>)>;
 
+/// A page of the records under a prefix, ordered by key.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PrefixListPage {
+    pub entries: PrefixListReply,
+    /// Whether more records follow the last one of `entries`, i.e., the next page is not empty.
+    pub more: bool,
+}
+
 #[async_trait]
 pub trait KVApi: Send + Sync {
     async fn upsert_kv(
@@ -49,6 +87,31 @@ pub trait KVApi: Send + Sync {
         value_meta: Option<KVMeta>,
     ) -> common_exception::Result<UpsertKVActionResult>;
 
+    /// Same as `upsert_kv`, except that the reply of an upsert rejected by `seq` carries only the
+    /// seq and the meta of the current value, e.g., for a CAS loop that retries by the seq.
+    async fn upsert_kv_lean(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        let res = self.upsert_kv(key, seq, value, value_meta).await?;
+        Ok(res.without_conflict_value())
+    }
+
+    /// Update or delete `key` only if its current value is `expect`, atomically, e.g. for the
+    /// participants that do not know the seq. A `None` expects the key to be absent, an expired
+    /// value is absent. The reply is the same as of `upsert_kv`, the current value if it does
+    /// not match.
+    async fn cas_kv(
+        &self,
+        key: &str,
+        expect: Option<Vec<u8>>,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult>;
+
     async fn update_kv_meta(
         &self,
         key: &str,
@@ -56,10 +119,72 @@ pub trait KVApi: Send + Sync {
         value_meta: Option<KVMeta>,
     ) -> common_exception::Result<UpsertKVActionResult>;
 
+    /// Transform the value of `key` on the server side with `op`, atomically.
+    /// `value_meta` replaces the meta of the value if it is not `None`, otherwise the meta is kept.
+    async fn upsert_kv_merge(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        op: MergeOp,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult>;
+
+    /// Apply a json patch to the json document stored at `key` on the server side, atomically.
+    /// An absent key is patched as an empty object. The meta of the value is kept.
+    /// The reply carries the new seq and the patched document, or the current value if `seq` does not match.
+    async fn patch_kv_json(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        patch: Vec<JsonPatchOp>,
+    ) -> common_exception::Result<UpsertKVActionResult>;
+
     async fn get_kv(&self, key: &str) -> common_exception::Result<GetKVActionResult>;
 
     // mockall complains about AsRef... so we use String here
     async fn mget_kv(&self, key: &[String]) -> common_exception::Result<MGetKVActionResult>;
 
+    /// Same as `mget_kv`, except that every key is required: it fails with `UnknownKey` naming
+    /// the first key that is absent or expired. The values are in the order of the keys.
+    async fn mget_kv_strict(
+        &self,
+        keys: &[String],
+    ) -> common_exception::Result<Vec<SeqValue<KVValue>>> {
+        let res = self.mget_kv(keys).await?;
+        if res.result.len() != keys.len() {
+            return Err(ErrorCode::UnexpectedResponseType(format!(
+                "expect {} values of mget_kv, but got {}",
+                keys.len(),
+                res.result.len()
+            )));
+        }
+        keys.iter()
+            .zip(res.result)
+            .map(|(key, v)| v.ok_or_else(|| ErrorCode::UnknownKey(format!("Unknown key {}", key))))
+            .collect()
+    }
+
     async fn prefix_list_kv(&self, prefix: &str) -> common_exception::Result<PrefixListReply>;
+
+    /// Delete every record under `prefix` at once, and return the number of the records deleted.
+    /// The expired records are already gone, they are not counted.
+    async fn delete_prefix_kv(&self, prefix: &str) -> common_exception::Result<usize>;
+
+    /// List up to `limit` of the records under `prefix` with a key greater than `after_key`, ordered by key.
+    /// The last key of a page is the `after_key` of the next one. `None` for either means no bound.
+    async fn prefix_list_kv_page(
+        &self,
+        prefix: &str,
+        limit: Option<usize>,
+        after_key: Option<String>,
+    ) -> common_exception::Result<PrefixListPage>;
+
+    /// List up to `limit` of the records under `prefix` with a seq greater than `min_seq`, ordered by seq,
+    /// e.g., to poll the changes under a prefix since the last seq seen.
+    async fn prefix_list_kv_by_seq(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
+    ) -> common_exception::Result<PrefixListReply>;
 }
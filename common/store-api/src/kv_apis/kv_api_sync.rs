@@ -16,14 +16,19 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use common_metatypes::JsonPatchOp;
 use common_metatypes::KVMeta;
+use common_metatypes::KVValue;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
+use common_metatypes::SeqValue;
 
 use crate::kv_apis::kv_api::MGetKVActionResult;
 use crate::util::STORE_RUNTIME;
 use crate::util::STORE_SYNC_CALL_TIMEOUT;
 use crate::GetKVActionResult;
 use crate::KVApi;
+use crate::PrefixListPage;
 use crate::PrefixListReply;
 use crate::UpsertKVActionResult;
 
@@ -45,6 +50,21 @@ where Self: Clone + 'static
         )?
     }
 
+    fn sync_cas_kv(
+        &self,
+        key: &str,
+        expect: Option<Vec<u8>>,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        let me = self.clone();
+        let key = key.to_owned();
+        STORE_RUNTIME.block_on(
+            async move { me.cas_kv(&key, expect, value, value_meta).await },
+            STORE_SYNC_CALL_TIMEOUT.as_ref().cloned(),
+        )?
+    }
+
     fn sync_update_kv_meta(
         &self,
         key: &str,
@@ -59,6 +79,35 @@ where Self: Clone + 'static
         )?
     }
 
+    fn sync_upsert_kv_merge(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        op: MergeOp,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        let me = self.clone();
+        let key = key.to_owned();
+        STORE_RUNTIME.block_on(
+            async move { me.upsert_kv_merge(&key, seq, op, value_meta).await },
+            STORE_SYNC_CALL_TIMEOUT.as_ref().cloned(),
+        )?
+    }
+
+    fn sync_patch_kv_json(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        patch: Vec<JsonPatchOp>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        let me = self.clone();
+        let key = key.to_owned();
+        STORE_RUNTIME.block_on(
+            async move { me.patch_kv_json(&key, seq, patch).await },
+            STORE_SYNC_CALL_TIMEOUT.as_ref().cloned(),
+        )?
+    }
+
     fn sync_get_kv(&self, key: &str) -> common_exception::Result<GetKVActionResult> {
         let me = self.clone();
         let key = key.to_owned();
@@ -77,6 +126,18 @@ where Self: Clone + 'static
         )?
     }
 
+    fn sync_mget_kv_strict(
+        &self,
+        keys: &[String],
+    ) -> common_exception::Result<Vec<SeqValue<KVValue>>> {
+        let me = self.clone();
+        let keys = keys.to_owned();
+        STORE_RUNTIME.block_on(
+            async move { me.mget_kv_strict(&keys).await },
+            STORE_SYNC_CALL_TIMEOUT.as_ref().cloned(),
+        )?
+    }
+
     fn sync_prefix_list_kv(&self, prefix: &str) -> common_exception::Result<PrefixListReply> {
         let me = self.clone();
         let prefix = prefix.to_owned();
@@ -101,6 +162,28 @@ impl KVApi for Arc<dyn KVApi> {
         self.as_ref().upsert_kv(key, seq, value, value_meta).await
     }
 
+    async fn upsert_kv_lean(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        self.as_ref()
+            .upsert_kv_lean(key, seq, value, value_meta)
+            .await
+    }
+
+    async fn cas_kv(
+        &self,
+        key: &str,
+        expect: Option<Vec<u8>>,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        self.as_ref().cas_kv(key, expect, value, value_meta).await
+    }
+
     async fn update_kv_meta(
         &self,
         key: &str,
@@ -110,6 +193,27 @@ impl KVApi for Arc<dyn KVApi> {
         self.as_ref().update_kv_meta(key, seq, value_meta).await
     }
 
+    async fn upsert_kv_merge(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        op: MergeOp,
+        value_meta: Option<KVMeta>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        self.as_ref()
+            .upsert_kv_merge(key, seq, op, value_meta)
+            .await
+    }
+
+    async fn patch_kv_json(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        patch: Vec<JsonPatchOp>,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        self.as_ref().patch_kv_json(key, seq, patch).await
+    }
+
     async fn get_kv(&self, key: &str) -> common_exception::Result<GetKVActionResult> {
         self.as_ref().get_kv(key).await
     }
@@ -118,7 +222,40 @@ impl KVApi for Arc<dyn KVApi> {
         self.as_ref().mget_kv(key).await
     }
 
+    async fn mget_kv_strict(
+        &self,
+        keys: &[String],
+    ) -> common_exception::Result<Vec<SeqValue<KVValue>>> {
+        self.as_ref().mget_kv_strict(keys).await
+    }
+
     async fn prefix_list_kv(&self, prefix: &str) -> common_exception::Result<PrefixListReply> {
         self.as_ref().prefix_list_kv(prefix).await
     }
+
+    async fn delete_prefix_kv(&self, prefix: &str) -> common_exception::Result<usize> {
+        self.as_ref().delete_prefix_kv(prefix).await
+    }
+
+    async fn prefix_list_kv_page(
+        &self,
+        prefix: &str,
+        limit: Option<usize>,
+        after_key: Option<String>,
+    ) -> common_exception::Result<PrefixListPage> {
+        self.as_ref()
+            .prefix_list_kv_page(prefix, limit, after_key)
+            .await
+    }
+
+    async fn prefix_list_kv_by_seq(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
+    ) -> common_exception::Result<PrefixListReply> {
+        self.as_ref()
+            .prefix_list_kv_by_seq(prefix, min_seq, limit)
+            .await
+    }
 }
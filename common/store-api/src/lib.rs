@@ -13,30 +13,55 @@
 //  limitations under the License.
 //
 
+pub use common_planners::PartChecksum;
+pub use common_planners::PartReadInfo;
+pub use common_planners::PartStorageClass;
+pub use data_block_apis::bloom_filter::BloomFilter;
+pub use data_block_apis::bloom_filter::PartBloomFilters;
+pub use data_block_apis::bloom_filter::BLOOM_FILTER_VERSION;
+pub use data_block_apis::column_statistics::ColumnBound;
+pub use data_block_apis::column_statistics::ColumnStatistics;
+pub use data_block_apis::column_statistics::PartColumnStatistics;
 pub use data_block_apis::data_block_api::AppendResult;
 pub use data_block_apis::data_block_api::BlockStream;
+pub use data_block_apis::data_block_api::CopyTableResult;
+pub use data_block_apis::data_block_api::CopyTableSource;
 pub use data_block_apis::data_block_api::DataPartInfo;
+pub use data_block_apis::data_block_api::OptimizeTableResult;
 pub use data_block_apis::data_block_api::PartitionInfo;
+pub use data_block_apis::data_block_api::PartsPruning;
 pub use data_block_apis::data_block_api::ReadAction;
+pub use data_block_apis::data_block_api::ReadPlanReply;
 pub use data_block_apis::data_block_api::ReadPlanResult;
 pub use data_block_apis::data_block_api::StorageApi;
 pub use data_block_apis::data_block_api::Summary;
+pub use data_block_apis::data_block_api::TableAccessStats;
 pub use data_block_apis::data_block_api::TruncateTableResult;
 pub use kv_apis::kv_api::GetKVActionResult;
 pub use kv_apis::kv_api::KVApi;
+pub use kv_apis::kv_api::PrefixListPage;
 pub use kv_apis::kv_api::PrefixListReply;
 pub use kv_apis::kv_api::UpsertKVActionResult;
 pub use kv_apis::kv_api_sync::SyncKVApi;
 pub use meta_apis::meta_api::CommitTableReply;
 pub use meta_apis::meta_api::CreateDatabaseActionResult;
 pub use meta_apis::meta_api::CreateTableActionResult;
+pub use meta_apis::meta_api::CreateTableOutcome;
+pub use meta_apis::meta_api::CreateTablesActionResult;
 pub use meta_apis::meta_api::DatabaseMetaReply;
 pub use meta_apis::meta_api::DatabaseMetaSnapshot;
 pub use meta_apis::meta_api::DropDatabaseActionResult;
 pub use meta_apis::meta_api::DropTableActionResult;
 pub use meta_apis::meta_api::GetDatabaseActionResult;
+pub use meta_apis::meta_api::GetDatabaseUsagesActionResult;
+pub use meta_apis::meta_api::GetDroppedTablesActionResult;
 pub use meta_apis::meta_api::GetTableActionResult;
+pub use meta_apis::meta_api::ListDatabasesReply;
+pub use meta_apis::meta_api::ListTablesReply;
 pub use meta_apis::meta_api::MetaApi;
+pub use meta_apis::meta_api::ModifyColumnActionResult;
+pub use meta_apis::meta_api::RenameTableActionResult;
+pub use meta_apis::meta_api::UndropTableActionResult;
 
 pub mod data_block_apis;
 pub mod kv_apis;
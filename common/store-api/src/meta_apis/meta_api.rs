@@ -16,7 +16,11 @@
 use std::collections::HashMap;
 
 use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
 use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::DroppedTable;
+use common_metatypes::MatchSeq;
 use common_metatypes::MetaId;
 use common_metatypes::MetaVersion;
 use common_metatypes::Table;
@@ -24,6 +28,9 @@ use common_planners::CreateDatabasePlan;
 use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
 use common_planners::DropTablePlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::RenameTablePlan;
+use common_planners::UndropTablePlan;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct CreateDatabaseActionResult {
@@ -37,17 +44,77 @@ pub struct GetDatabaseActionResult {
     pub engine: String,
 }
 
+/// The databases sorted by their ids.
+pub type ListDatabasesReply = Vec<GetDatabaseActionResult>;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct DropDatabaseActionResult {}
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct CreateTableActionResult {
     pub table_id: u64,
+    /// The table replaced by the create, if any.
+    #[serde(default)]
+    pub replaced: Option<GetTableActionResult>,
+}
+
+/// What became of a table of a batch create, see `MetaApi::create_tables`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum CreateTableOutcome {
+    Created {
+        table_id: u64,
+    },
+    /// The table existed and the plan is `if_not_exists`.
+    AlreadyExists {
+        table_id: u64,
+    },
+    Failed {
+        code: u16,
+        message: String,
+    },
+}
+
+impl CreateTableOutcome {
+    pub fn failed(e: &ErrorCode) -> Self {
+        CreateTableOutcome::Failed {
+            code: e.code(),
+            message: e.message(),
+        }
+    }
+}
+
+/// The outcomes in the order of the plans of the batch.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct CreateTablesActionResult {
+    pub outcomes: Vec<CreateTableOutcome>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct DropTableActionResult {}
 
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct UndropTableActionResult {
+    pub table_id: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct RenameTableActionResult {}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct GetDroppedTablesActionResult {
+    pub tables: Vec<DroppedTable>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct GetDatabaseUsagesActionResult {
+    pub usages: Vec<(String, DatabaseUsage)>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ModifyColumnActionResult {
+    pub table_id: u64,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct GetTableActionResult {
     pub table_id: u64,
@@ -58,6 +125,9 @@ pub struct GetTableActionResult {
     pub options: HashMap<String, String>,
 }
 
+/// The tables of a database sorted by their names.
+pub type ListTablesReply = Vec<GetTableActionResult>;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct DatabaseMetaSnapshot {
     pub meta_ver: u64,
@@ -85,6 +155,9 @@ pub trait MetaApi: Send + Sync {
 
     async fn get_database(&self, db: &str) -> common_exception::Result<GetDatabaseActionResult>;
 
+    /// Get the name, id and engine of every database, sorted by the database id.
+    async fn list_databases(&self) -> common_exception::Result<ListDatabasesReply>;
+
     async fn drop_database(
         &self,
         plan: DropDatabasePlan,
@@ -95,17 +168,52 @@ pub trait MetaApi: Send + Sync {
         plan: CreateTablePlan,
     ) -> common_exception::Result<CreateTableActionResult>;
 
+    /// Create the tables in one meta write, the meta version is bumped once if any of them is created.
+    /// A table that cannot be created is reported in its outcome, it doesn't fail the others.
+    async fn create_tables(
+        &self,
+        plans: Vec<CreateTablePlan>,
+    ) -> common_exception::Result<CreateTablesActionResult>;
+
+    /// Create the table, replacing the existing one if `seq` matches its id, 0 if there is none.
+    /// A replaced table goes to trash as a dropped one.
+    async fn create_or_replace_table(
+        &self,
+        plan: CreateTablePlan,
+        seq: MatchSeq,
+    ) -> common_exception::Result<CreateTableActionResult>;
+
     async fn drop_table(
         &self,
         plan: DropTablePlan,
     ) -> common_exception::Result<DropTableActionResult>;
 
+    async fn undrop_table(
+        &self,
+        plan: UndropTablePlan,
+    ) -> common_exception::Result<UndropTableActionResult>;
+
+    async fn rename_table(
+        &self,
+        plan: RenameTablePlan,
+    ) -> common_exception::Result<RenameTableActionResult>;
+
+    async fn get_dropped_tables(&self) -> common_exception::Result<GetDroppedTablesActionResult>;
+
+    async fn modify_column(
+        &self,
+        plan: ModifyColumnPlan,
+    ) -> common_exception::Result<ModifyColumnActionResult>;
+
     async fn get_table(
         &self,
         db: String,
         table: String,
     ) -> common_exception::Result<GetTableActionResult>;
 
+    /// Get the tables of a database, sorted by the table name.
+    async fn list_tables(&self, db: String) -> common_exception::Result<ListTablesReply>;
+
     async fn get_table_ext(
         &self,
         table_id: MetaId,
@@ -117,6 +225,20 @@ pub trait MetaApi: Send + Sync {
         current_ver: Option<u64>,
     ) -> common_exception::Result<DatabaseMetaReply>;
 
+    /// Set the max bytes of the data parts of a database, `None` for unlimited.
+    async fn set_database_quota(
+        &self,
+        db: String,
+        quota: Option<u64>,
+    ) -> common_exception::Result<DatabaseUsage>;
+
+    /// Get the quota and the used bytes of every database.
+    async fn get_database_usages(&self) -> common_exception::Result<GetDatabaseUsagesActionResult>;
+
+    /// Recompute the used bytes of a database from its data parts.
+    async fn reconcile_database_usage(&self, db: String)
+        -> common_exception::Result<DatabaseUsage>;
+
     async fn commit_table(
         &self,
         table_id: MetaId,
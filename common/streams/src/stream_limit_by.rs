@@ -22,6 +22,7 @@ use common_arrow::arrow::array::BooleanArray;
 use common_arrow::arrow::bitmap::MutableBitmap;
 use common_arrow::arrow::datatypes::DataType as ArrowType;
 use common_datablocks::DataBlock;
+use common_datablocks::GroupKey;
 use common_datablocks::HashMethod;
 use common_datablocks::HashMethodSerializer;
 use common_exception::Result;
@@ -34,7 +35,7 @@ pub struct LimitByStream {
     input: SendableDataBlockStream,
     limit: usize,
     limit_by_columns_name: Vec<String>,
-    keys_count: HashMap<Vec<u8>, usize>,
+    keys_count: HashMap<GroupKey<Vec<u8>>, usize>,
 }
 
 impl LimitByStream {
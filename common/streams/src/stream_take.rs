@@ -41,12 +41,15 @@ impl Stream for TakeStream {
     type Item = Result<DataBlock>;
 
     fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.remaining == 0 {
+            // Done without waiting for the next block of the input.
+            return Poll::Ready(None);
+        }
+
         self.input.poll_next_unpin(ctx).map(|x| match x {
             Some(Ok(ref block)) => {
                 let rows = block.num_rows();
-                if self.remaining == 0 {
-                    None
-                } else if self.remaining >= rows {
+                if self.remaining >= rows {
                     self.remaining -= rows;
                     Some(block.clone())
                 } else {
@@ -12,10 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod log_level_handler_test;
+#[cfg(test)]
+mod log_level_test;
+
+mod log_level;
+pub mod log_level_handler;
 mod logging;
 mod panic_hook;
 mod tracing_to_jaeger;
 
+pub use log_level::global_log_level;
+pub use log_level::LogLevel;
 pub use logging::init_default_tracing;
 pub use logging::init_default_ut_tracing;
 pub use logging::init_global_tracing;
@@ -0,0 +1,168 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use common_runtime::SharedClock;
+use lazy_static::lazy_static;
+use tracing_subscriber::reload;
+use tracing_subscriber::EnvFilter;
+
+lazy_static! {
+    static ref GLOBAL_LOG_LEVEL: Mutex<Option<LogLevel>> = Mutex::new(None);
+}
+
+/// The log level of the global subscriber, if it is installed by `init_tracing_with_file`.
+pub fn global_log_level() -> Option<LogLevel> {
+    GLOBAL_LOG_LEVEL.lock().unwrap().clone()
+}
+
+pub(crate) fn set_global_log_level(log_level: LogLevel) {
+    *GLOBAL_LOG_LEVEL.lock().unwrap() = Some(log_level);
+}
+
+/// The filter of a subscriber, which can be changed while the process is running.
+///
+/// It is a layer in front of the output layers, so all the outputs see the same filter.
+/// The directives are in the `RUST_LOG` syntax, e.g. `databend_store::api=debug,sled=warn`.
+#[derive(Clone)]
+pub struct LogLevel {
+    inner: Arc<LogLevelInner>,
+}
+
+struct LogLevelInner {
+    reload: Box<dyn Fn(EnvFilter) -> Result<()> + Send + Sync>,
+    default: String,
+    clock: SharedClock,
+    state: Mutex<LogLevelState>,
+}
+
+struct LogLevelState {
+    directives: String,
+    /// Bumped by each change, a revert timer only reverts the change it is started for.
+    version: u64,
+}
+
+impl LogLevel {
+    /// Creates the filter layer with the `default` directives, and the `LogLevel` to change it.
+    /// The revert timers run on `clock`.
+    pub fn create<S>(
+        default: &str,
+        clock: SharedClock,
+    ) -> Result<(reload::Layer<EnvFilter, S>, Self)>
+    where
+        S: tracing::Subscriber + 'static,
+    {
+        let (layer, handle) = reload::Layer::new(parse_directives(default)?);
+
+        let log_level = LogLevel {
+            inner: Arc::new(LogLevelInner {
+                reload: Box::new(move |filter| {
+                    handle.reload(filter).map_err(|e| {
+                        ErrorCode::UnknownException(format!("can not reload log level: {}", e))
+                    })
+                }),
+                default: default.to_string(),
+                clock,
+                state: Mutex::new(LogLevelState {
+                    directives: default.to_string(),
+                    version: 0,
+                }),
+            }),
+        };
+        Ok((layer, log_level))
+    }
+
+    /// The directives in effect.
+    pub fn get(&self) -> String {
+        self.inner.state.lock().unwrap().directives.clone()
+    }
+
+    /// The directives the process is started with.
+    pub fn get_default(&self) -> String {
+        self.inner.default.clone()
+    }
+
+    /// Applies `directives` immediately, nothing is changed if they are invalid.
+    /// With `revert_after`, the default directives are applied again after it, unless the log
+    /// level is changed again before that. A timer needs to be started in a tokio runtime.
+    pub fn set(&self, directives: &str, revert_after: Option<Duration>) -> Result<String> {
+        let filter = parse_directives(directives)?;
+        let runtime = match revert_after {
+            None => None,
+            Some(_) => Some(tokio::runtime::Handle::try_current().map_err(|e| {
+                ErrorCode::TokioError(format!("can not start the revert timer: {}", e))
+            })?),
+        };
+
+        let mut state = self.inner.state.lock().unwrap();
+        (self.inner.reload)(filter)?;
+        state.directives = directives.to_string();
+        state.version += 1;
+
+        if let (Some(runtime), Some(revert_after)) = (runtime, revert_after) {
+            let version = state.version;
+            let sleep = self.inner.clock.sleep(revert_after);
+            let log_level = self.clone();
+            runtime.spawn(async move {
+                sleep.await;
+                if let Err(e) = log_level.revert_version(version) {
+                    tracing::warn!("failed to revert the log level: {}", e);
+                }
+            });
+        }
+
+        tracing::info!(
+            "log level is set to {:?}, revert after {:?}",
+            directives,
+            revert_after
+        );
+        Ok(state.directives.clone())
+    }
+
+    /// Applies the default directives again.
+    pub fn revert(&self) -> Result<String> {
+        let default = self.inner.default.clone();
+        self.set(&default, None)
+    }
+
+    fn revert_version(&self, version: u64) -> Result<()> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.version != version {
+            // Changed again after the timer is started.
+            return Ok(());
+        }
+
+        (self.inner.reload)(parse_directives(&self.inner.default)?)?;
+        state.directives = self.inner.default.clone();
+        state.version += 1;
+
+        tracing::info!("log level is reverted to {:?}", state.directives);
+        Ok(())
+    }
+}
+
+fn parse_directives(directives: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(directives).map_err(|e| {
+        ErrorCode::BadArguments(format!(
+            "invalid log level directives {:?}: {}",
+            directives, e
+        ))
+    })
+}
@@ -0,0 +1,97 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use axum::extract::Extension;
+use axum::extract::Json;
+use common_exception::ErrorCode;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::LogLevel;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SetLogLevelRequest {
+    /// In the `RUST_LOG` syntax, e.g. `databend_query::api=debug,sled=warn`.
+    pub directives: String,
+    /// Go back to the configured level after it, so a debug level set for an incident is not left on.
+    #[serde(default)]
+    pub revert_after_secs: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LogLevelResult {
+    /// The directives in effect.
+    pub directives: String,
+    /// The configured directives.
+    pub default: String,
+    /// Why nothing is changed, e.g., the directives are invalid.
+    pub error: Option<String>,
+}
+
+/// The log level of the process, `None` if the logging is not set up to change it, e.g. in tests.
+#[derive(Clone)]
+pub struct LogLevelExtension(pub Option<LogLevel>);
+
+pub async fn get_log_level_handler(
+    log_level: Extension<LogLevelExtension>,
+) -> Json<LogLevelResult> {
+    let log_level = &log_level.0 .0;
+    let res = log_level_or_err(log_level).map(|_| ());
+    Json(log_level_result(log_level, res))
+}
+
+/// Changes the log level immediately, without restarting the process.
+pub async fn set_log_level_handler(
+    log_level: Extension<LogLevelExtension>,
+    req: Json<SetLogLevelRequest>,
+) -> Json<LogLevelResult> {
+    let log_level = &log_level.0 .0;
+    let res = log_level_or_err(log_level).and_then(|log_level| {
+        let revert_after = req.0.revert_after_secs.map(Duration::from_secs);
+        log_level.set(&req.0.directives, revert_after)
+    });
+    Json(log_level_result(log_level, res.map(|_| ())))
+}
+
+/// Goes back to the configured log level.
+pub async fn revert_log_level_handler(
+    log_level: Extension<LogLevelExtension>,
+) -> Json<LogLevelResult> {
+    let log_level = &log_level.0 .0;
+    let res = log_level_or_err(log_level).and_then(|log_level| log_level.revert());
+    Json(log_level_result(log_level, res.map(|_| ())))
+}
+
+fn log_level_or_err(log_level: &Option<LogLevel>) -> common_exception::Result<&LogLevel> {
+    log_level
+        .as_ref()
+        .ok_or_else(|| ErrorCode::UnImplement("the log level can not be changed at runtime"))
+}
+
+fn log_level_result(
+    log_level: &Option<LogLevel>,
+    res: common_exception::Result<()>,
+) -> LogLevelResult {
+    let (directives, default) = match log_level {
+        Some(log_level) => (log_level.get(), log_level.get_default()),
+        None => (String::new(), String::new()),
+    };
+    LogLevelResult {
+        directives,
+        default,
+        error: res.err().map(|e| e.message()),
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::body::Body;
+use axum::handler::get;
+use axum::handler::post;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::http::{self};
+use axum::AddExtensionLayer;
+use axum::Router;
+use common_runtime::tokio;
+use common_runtime::SharedClock;
+use pretty_assertions::assert_eq;
+use tower::ServiceExt;
+use tracing_subscriber::fmt::Layer;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::Registry;
+
+use crate::log_level_handler::get_log_level_handler;
+use crate::log_level_handler::revert_log_level_handler;
+use crate::log_level_handler::set_log_level_handler;
+use crate::log_level_handler::LogLevelExtension;
+use crate::log_level_test::CapturedWriter;
+use crate::LogLevel;
+
+#[tokio::test]
+async fn test_log_level() -> common_exception::Result<()> {
+    let (filter, log_level) = LogLevel::create("info", SharedClock::default())?;
+    let captured = CapturedWriter::default();
+    let writer = captured.clone();
+    let subscriber = Registry::default()
+        .with(filter)
+        .with(Layer::new().with_writer(move || writer.clone()));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let router = Router::new()
+        .route("/v1/get_log_level", get(get_log_level_handler))
+        .route("/v1/set_log_level", post(set_log_level_handler))
+        .route("/v1/revert_log_level", post(revert_log_level_handler))
+        .layer(AddExtensionLayer::new(LogLevelExtension(Some(log_level))));
+
+    let body = call(router.clone(), "/v1/get_log_level", http::Method::GET, "").await;
+    assert_eq!(
+        r#"{"directives":"info","default":"info","error":null}"#,
+        body
+    );
+
+    let body = call(
+        router.clone(),
+        "/v1/set_log_level",
+        http::Method::POST,
+        r#"{"directives":"info,databend_store::api=debug"}"#,
+    )
+    .await;
+    assert_eq!(
+        r#"{"directives":"info,databend_store::api=debug","default":"info","error":null}"#,
+        body
+    );
+    tracing::debug!(target: "databend_store::api", "debug event 1");
+    assert!(captured.contains("debug event 1"));
+
+    // Invalid directives change nothing.
+    let body = call(
+        router.clone(),
+        "/v1/set_log_level",
+        http::Method::POST,
+        r#"{"directives":"databend_store::api=loud"}"#,
+    )
+    .await;
+    assert!(body.contains(r#""directives":"info,databend_store::api=debug""#));
+    assert!(body.contains("invalid log level directives"), "{}", body);
+
+    let body = call(
+        router.clone(),
+        "/v1/revert_log_level",
+        http::Method::POST,
+        "",
+    )
+    .await;
+    assert_eq!(
+        r#"{"directives":"info","default":"info","error":null}"#,
+        body
+    );
+    tracing::debug!(target: "databend_store::api", "debug event 2");
+    assert!(!captured.contains("debug event 2"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_log_level_not_reloadable() -> common_exception::Result<()> {
+    let router = Router::new()
+        .route("/v1/set_log_level", post(set_log_level_handler))
+        .layer(AddExtensionLayer::new(LogLevelExtension(None)));
+
+    let body = call(
+        router.clone(),
+        "/v1/set_log_level",
+        http::Method::POST,
+        r#"{"directives":"debug","revert_after_secs":600}"#,
+    )
+    .await;
+    assert_eq!(
+        r#"{"directives":"","default":"","error":"the log level can not be changed at runtime"}"#,
+        body
+    );
+    Ok(())
+}
+
+async fn call<S, B>(router: S, uri: &str, method: http::Method, body: &str) -> String
+where
+    S: tower::Service<Request<Body>, Response = http::Response<B>>,
+    S::Error: std::fmt::Debug,
+    B: hyper::body::HttpBody,
+    B::Error: std::fmt::Debug,
+{
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .method(method)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
@@ -0,0 +1,142 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use pretty_assertions::assert_eq;
+use tracing_subscriber::fmt::Layer;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::Registry;
+
+use crate::LogLevel;
+
+/// Collects the logs in memory.
+#[derive(Clone, Default)]
+pub(crate) struct CapturedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedWriter {
+    pub(crate) fn contains(&self, s: &str) -> bool {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).contains(s)
+    }
+}
+
+impl io::Write for CapturedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_log_level() -> Result<()> {
+    let (filter, log_level) = LogLevel::create("info", SharedClock::default())?;
+    let captured = CapturedWriter::default();
+    let writer = captured.clone();
+    let subscriber = Registry::default()
+        .with(filter)
+        .with(Layer::new().with_writer(move || writer.clone()));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    tracing::debug!(target: "databend_store::api", "debug event 1");
+    assert!(!captured.contains("debug event 1"));
+    assert_eq!("info", log_level.get());
+
+    let applied = log_level.set("info,databend_store::api=debug", None)?;
+    assert_eq!("info,databend_store::api=debug", applied);
+    assert_eq!(applied, log_level.get());
+
+    tracing::debug!(target: "databend_store::api", "debug event 2");
+    tracing::debug!(target: "databend_query::api", "debug event 3");
+    assert!(captured.contains("debug event 2"));
+    assert!(!captured.contains("debug event 3"));
+
+    // Invalid directives change nothing.
+    let res = log_level.set("databend_store::api=loud", None);
+    assert_eq!(ErrorCode::BadArguments("").code(), res.unwrap_err().code());
+    assert_eq!("info,databend_store::api=debug", log_level.get());
+    tracing::debug!(target: "databend_store::api", "debug event 4");
+    assert!(captured.contains("debug event 4"));
+
+    assert_eq!("info", log_level.revert()?);
+    assert_eq!("info", log_level.get());
+    tracing::debug!(target: "databend_store::api", "debug event 5");
+    assert!(!captured.contains("debug event 5"));
+    tracing::info!(target: "databend_store::api", "info event 6");
+    assert!(captured.contains("info event 6"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_log_level_revert_timer() -> Result<()> {
+    let clock = VirtualClock::create();
+    let (filter, log_level) = LogLevel::create("info", SharedClock::create(clock.clone()))?;
+    let captured = CapturedWriter::default();
+    let writer = captured.clone();
+    let subscriber = Registry::default()
+        .with(filter)
+        .with(Layer::new().with_writer(move || writer.clone()));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    log_level.set("debug", Some(Duration::from_secs(600)))?;
+    tracing::debug!(target: "databend_store::api", "debug event 1");
+    assert!(captured.contains("debug event 1"));
+
+    clock.advance(Duration::from_secs(599));
+    tokio::task::yield_now().await;
+    assert_eq!("debug", log_level.get());
+
+    clock.advance(Duration::from_secs(1));
+    wait_for_log_level(&log_level, "info").await;
+    tracing::debug!(target: "databend_store::api", "debug event 2");
+    assert!(!captured.contains("debug event 2"));
+
+    // A later change cancels the timer of an earlier one.
+    log_level.set("debug", Some(Duration::from_secs(10)))?;
+    log_level.set("warn,databend_store=debug", None)?;
+    clock.advance(Duration::from_secs(10));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!("warn,databend_store=debug", log_level.get());
+
+    Ok(())
+}
+
+#[test]
+fn test_init_tracing_with_invalid_log_level() {
+    // Fails before installing the global subscriber, the process can still report the error.
+    let res = crate::init_tracing_with_file("log_level_test", "_logs", "databend=loud");
+    assert_eq!(ErrorCode::BadArguments("").code(), res.unwrap_err().code());
+    assert!(crate::global_log_level().is_none());
+}
+
+async fn wait_for_log_level(log_level: &LogLevel, directives: &str) {
+    let start = Instant::now();
+    while log_level.get() != directives {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
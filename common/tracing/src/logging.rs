@@ -19,6 +19,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::Once;
 
+use common_runtime::SharedClock;
 use lazy_static::lazy_static;
 use opentelemetry::global;
 use opentelemetry::sdk::propagation::TraceContextPropagator;
@@ -34,7 +35,9 @@ use tracing_subscriber::prelude::*;
 use tracing_subscriber::registry::Registry;
 use tracing_subscriber::EnvFilter;
 
+use crate::log_level::set_global_log_level;
 use crate::tracing::subscriber::DefaultGuard;
+use crate::LogLevel;
 
 /// Write logs to stdout.
 pub fn init_default_tracing() {
@@ -111,7 +114,14 @@ fn jaeger_layer<
 }
 
 /// Write logs to file and rotation by HOUR.
-pub fn init_tracing_with_file(app_name: &str, dir: &str, level: &str) -> Vec<WorkerGuard> {
+pub fn init_tracing_with_file(
+    app_name: &str,
+    dir: &str,
+    level: &str,
+) -> common_exception::Result<Vec<WorkerGuard>> {
+    // Parsed first, invalid directives fail the start before any writer is set up.
+    let (filter, log_level) = LogLevel::create(level, SharedClock::default())?;
+
     let mut guards = vec![];
 
     let (stdout_writer, stdout_guard) = tracing_appender::non_blocking(std::io::stdout());
@@ -123,8 +133,11 @@ pub fn init_tracing_with_file(app_name: &str, dir: &str, level: &str) -> Vec<Wor
     let file_logging_layer = BunyanFormattingLayer::new(app_name.to_string(), file_writer);
     guards.push(file_guard);
 
+    // The level can be changed at runtime by the admin api.
+    set_global_log_level(log_level);
+
     let subscriber = Registry::default()
-        .with(EnvFilter::new(level))
+        .with(filter)
         .with(stdout_logging_layer)
         .with(JsonStorageLayer)
         .with(file_logging_layer)
@@ -133,7 +146,7 @@ pub fn init_tracing_with_file(app_name: &str, dir: &str, level: &str) -> Vec<Wor
     tracing::subscriber::set_global_default(subscriber)
         .expect("error setting global tracing subscriber");
 
-    guards
+    Ok(guards)
 }
 
 /// Creates a tracing/logging subscriber that is valid until the guards are dropped.
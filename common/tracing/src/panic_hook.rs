@@ -19,6 +19,9 @@ pub fn set_panic_hook() {
     // If we are currently in a span when the panic occurred, the logged event
     // will include the current span, allowing the context in which the panic
     // occurred to be recorded.
+    //
+    // It only records the panic, the servers catch the panics of the connections
+    // (e.g. the MySQL handler), so they do not stop the process.
     std::panic::set_hook(Box::new(|panic| {
         if let Some(location) = panic.location() {
             tracing::error!(
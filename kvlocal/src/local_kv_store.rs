@@ -17,15 +17,20 @@ use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use common_exception::ErrorCode;
 use common_exception::Result;
+use common_metatypes::check_json_patch;
+use common_metatypes::JsonPatchOp;
 use common_metatypes::KVMeta;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
 use common_metatypes::Operation;
 use common_runtime::tokio::sync::Mutex;
 use common_store_api::kv_apis::kv_api::MGetKVActionResult;
 use common_store_api::util::STORE_RUNTIME;
 use common_store_api::GetKVActionResult;
 use common_store_api::KVApi;
+use common_store_api::PrefixListPage;
 use common_store_api::PrefixListReply;
 use common_store_api::UpsertKVActionResult;
 use common_tracing::tracing;
@@ -152,6 +157,90 @@ impl KVApi for LocalKVStore {
         }
     }
 
+    async fn cas_kv(
+        &self,
+        key: &str,
+        expect: Option<Vec<u8>>,
+        value: Option<Vec<u8>>,
+        value_meta: Option<KVMeta>,
+    ) -> Result<UpsertKVActionResult> {
+        let cmd = Cmd::CasKV {
+            key: key.to_string(),
+            expect,
+            value: value.into(),
+            value_meta,
+        };
+
+        let mut sm = self.inner.lock().await;
+        let res = sm.apply_cmd(&cmd).await?;
+
+        match res {
+            AppliedState::KV { prev, result } => Ok(UpsertKVActionResult { prev, result }),
+            _ => {
+                panic!("expect AppliedState::KV");
+            }
+        }
+    }
+
+    async fn upsert_kv_merge(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        op: MergeOp,
+        value_meta: Option<KVMeta>,
+    ) -> Result<UpsertKVActionResult> {
+        op.check()
+            .map_err(|e| ErrorCode::IllegalMetaOperationArgument(e.to_string()))?;
+
+        let cmd = Cmd::MergeKV {
+            key: key.to_string(),
+            seq,
+            op,
+            value_meta,
+        };
+
+        let mut sm = self.inner.lock().await;
+        let res = sm.apply_cmd(&cmd).await?;
+
+        match res {
+            AppliedState::KV { prev, result } => Ok(UpsertKVActionResult { prev, result }),
+            AppliedState::KVMergeRejected { reason, .. } => {
+                Err(ErrorCode::IllegalMetaOperationArgument(reason))
+            }
+            _ => {
+                panic!("expect AppliedState::KV");
+            }
+        }
+    }
+
+    async fn patch_kv_json(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        patch: Vec<JsonPatchOp>,
+    ) -> Result<UpsertKVActionResult> {
+        check_json_patch(&patch).map_err(ErrorCode::IllegalJsonPatch)?;
+
+        let cmd = Cmd::PatchKVJson {
+            key: key.to_string(),
+            seq,
+            patch,
+        };
+
+        let mut sm = self.inner.lock().await;
+        let res = sm.apply_cmd(&cmd).await?;
+
+        match res {
+            AppliedState::KV { prev, result } => Ok(UpsertKVActionResult { prev, result }),
+            AppliedState::KVPatchRejected { reason, .. } => {
+                Err(ErrorCode::IllegalJsonPatch(reason))
+            }
+            _ => {
+                panic!("expect AppliedState::KV");
+            }
+        }
+    }
+
     async fn get_kv(&self, key: &str) -> Result<GetKVActionResult> {
         let sm = self.inner.lock().await;
         let res = sm.get_kv(key)?;
@@ -169,4 +258,41 @@ impl KVApi for LocalKVStore {
         let res = sm.prefix_list_kv(prefix)?;
         Ok(res)
     }
+
+    async fn delete_prefix_kv(&self, prefix: &str) -> Result<usize> {
+        let cmd = Cmd::DeletePrefixKV {
+            prefix: prefix.to_string(),
+        };
+
+        let mut sm = self.inner.lock().await;
+        let res = sm.apply_cmd(&cmd).await?;
+
+        match res {
+            AppliedState::KVCount { prev, .. } => Ok(prev.unwrap_or_default()),
+            _ => {
+                panic!("expect AppliedState::KVCount");
+            }
+        }
+    }
+
+    async fn prefix_list_kv_page(
+        &self,
+        prefix: &str,
+        limit: Option<usize>,
+        after_key: Option<String>,
+    ) -> Result<PrefixListPage> {
+        let sm = self.inner.lock().await;
+        sm.prefix_list_kv_page(prefix, limit, after_key.as_deref())
+    }
+
+    async fn prefix_list_kv_by_seq(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
+    ) -> Result<PrefixListReply> {
+        let sm = self.inner.lock().await;
+        let res = sm.prefix_list_kv_by_seq(prefix, min_seq, limit)?;
+        Ok(res)
+    }
 }
@@ -188,6 +188,53 @@ async fn test_local_kv_store() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_local_kv_store_list_in_pages() -> Result<()> {
+    init_testing_sled_db();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let api = LocalKVStore::new_temp().await?;
+
+    for i in 0..1000 {
+        let expire_at = if i % 10 == 0 { Some(now - 1) } else { None };
+        api.upsert_kv(
+            &format!("k/{:04}", i),
+            MatchSeq::Any,
+            Some(b"v".to_vec()),
+            Some(KVMeta { expire_at }),
+        )
+        .await?;
+    }
+    api.upsert_kv("l", MatchSeq::Any, Some(b"v".to_vec()), None)
+        .await?;
+
+    let all = api.prefix_list_kv("k/").await?;
+    assert_eq!(900, all.len());
+
+    let mut paged = vec![];
+    let mut after_key = None;
+    loop {
+        let page = api.prefix_list_kv_page("k/", Some(100), after_key).await?;
+        assert!(page.entries.len() <= 100);
+        after_key = page.entries.last().map(|(k, _)| k.clone());
+        paged.extend(page.entries);
+        if !page.more {
+            break;
+        }
+    }
+    assert_eq!(all, paged);
+
+    let page = api.prefix_list_kv_page("k/", None, None).await?;
+    assert_eq!(all, page.entries);
+    assert!(!page.more);
+
+    Ok(())
+}
+
 #[test]
 fn sync_test_local_kv_store() -> Result<()> {
     init_testing_sled_db();
@@ -27,7 +27,10 @@ fn build_proto() {
         env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR env variable unset");
 
     let proto_dir = Path::new(&manifest_dir).join("proto");
-    let protos = [&Path::new(&proto_dir).join(Path::new("meta.proto"))];
+    let protos = [
+        &Path::new(&proto_dir).join(Path::new("meta.proto")),
+        &Path::new(&proto_dir).join(Path::new("replication.proto")),
+    ];
 
     for proto in protos.iter() {
         println!("cargo:rerun-if-changed={}", proto.to_str().unwrap());
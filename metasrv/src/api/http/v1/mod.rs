@@ -14,8 +14,11 @@
 
 pub mod config;
 pub mod health;
+pub mod sled;
 
 #[cfg(test)]
 mod config_test;
 #[cfg(test)]
 mod health_test;
+#[cfg(test)]
+mod sled_test;
@@ -0,0 +1,43 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Json;
+use common_tracing::tracing;
+
+use crate::sled_store::list_space_stats;
+use crate::sled_store::recount_space_stats;
+
+// GET /v1/sled/spaces
+// list the number of entries and the bytes of every non-empty key space of the opened sled trees
+pub async fn sled_spaces_handler() -> impl IntoResponse {
+    (StatusCode::OK, Json(list_space_stats()))
+}
+
+// POST /v1/sled/spaces/recount
+// recount the key spaces of the opened sled trees to repair the drift of the stats
+// return: the stats after recounting
+pub async fn sled_spaces_recount_handler() -> impl IntoResponse {
+    match recount_space_stats() {
+        Ok(stats) => (StatusCode::OK, Json(serde_json::json!(stats))),
+        Err(e) => {
+            tracing::error!("failed to recount sled key spaces: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.message() })),
+            )
+        }
+    }
+}
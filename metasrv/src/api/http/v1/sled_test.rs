@@ -0,0 +1,81 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::body::Body;
+use axum::handler::get;
+use axum::handler::post;
+use axum::http;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::Router;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+use tower::ServiceExt;
+
+use crate::api::http::v1::sled::sled_spaces_handler;
+use crate::api::http::v1::sled::sled_spaces_recount_handler;
+use crate::sled_store::list_space_stats;
+use crate::sled_store::sled_key_space;
+use crate::sled_store::SledTree;
+use crate::sled_store::SpaceStatsItem;
+use crate::tests::service::new_sled_test_context;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sled_spaces() -> anyhow::Result<()> {
+    let tc = new_sled_test_context();
+    let tree_name = tc.config.meta_config.tree_name("foo");
+    let tree = SledTree::open(&tc.db, &tree_name, true)?;
+    tree.insert::<sled_key_space::Files>(&"a".to_string(), &"1".to_string())
+        .await?;
+
+    let want = SpaceStatsItem {
+        tree: tree_name.clone(),
+        space: "files".to_string(),
+        entries: 1,
+        bytes: tree.space_stats("files").unwrap().bytes,
+    };
+    assert!(list_space_stats().contains(&want));
+
+    let router = Router::new()
+        .route("/v1/sled/spaces", get(sled_spaces_handler))
+        .route("/v1/sled/spaces/recount", post(sled_spaces_recount_handler));
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/v1/sled/spaces")
+                .method(http::Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/v1/sled/spaces/recount")
+                .method(http::Method::POST)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(list_space_stats().contains(&want));
+
+    Ok(())
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use axum::handler::get;
+use axum::handler::post;
 use axum::AddExtensionLayer;
 use axum::Router;
 use common_exception::Result;
@@ -29,6 +30,14 @@ macro_rules! build_router {
         Router::new()
             .route("/v1/health", get(super::http::v1::health::health_handler))
             .route("/v1/config", get(super::http::v1::config::config_handler))
+            .route(
+                "/v1/sled/spaces",
+                get(super::http::v1::sled::sled_spaces_handler),
+            )
+            .route(
+                "/v1/sled/spaces/recount",
+                post(super::http::v1::sled::sled_spaces_recount_handler),
+            )
             .route(
                 "/debug/home",
                 get(super::http::debug::home::debug_home_handler),
@@ -34,7 +34,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "databend-metasrv",
         conf.log_dir.as_str(),
         conf.log_level.as_str(),
-    );
+    )?;
 
     info!("{:?}", conf.clone());
     info!(
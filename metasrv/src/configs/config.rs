@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use common_exception::ErrorCode;
+use common_runtime::SharedClock;
 use lazy_static::lazy_static;
 use serde::Deserialize;
 use serde::Serialize;
@@ -136,6 +139,24 @@ pub struct MetaConfig {
     )]
     pub snapshot_logs_since_last: u64,
 
+    #[structopt(
+    long,
+    env = "METASRV_SNAPSHOT_INTERVAL",
+    default_value = "3600",
+    help = concat!("The interval in seconds to take a snapshot and purge the logs included in it,",
+    " if any log is applied since the last snapshot. 0 to snapshot only by --snapshot-logs-since-last.")
+    )]
+    pub snapshot_interval: u64,
+
+    #[structopt(
+    long,
+    env = "METASRV_LOG_PURGE_APPLIED_ENTRIES",
+    default_value = "1024",
+    help = concat!("Purge the logs included in the last snapshot after every this number of applied logs,",
+    " e.g. the logs left by a crash during a compaction. 0 to purge only when a snapshot is taken.")
+    )]
+    pub log_purge_applied_entries: u64,
+
     #[structopt(
     long,
     env = "METASRV_HEARTBEAT_INTERVAL",
@@ -179,12 +200,106 @@ pub struct MetaConfig {
     )]
     pub id: NodeId,
 
+    #[structopt(
+    long,
+    env = "METASRV_TABLE_TRASH_RETENTION",
+    default_value = "86400",
+    help = concat!("The time in seconds that a dropped table is kept in trash and can be undropped.",
+    " Expired tables and their data parts are purged by a background vacuum.")
+    )]
+    pub table_trash_retention: u64,
+
+    #[structopt(
+    long,
+    env = "METASRV_SEQ_BATCH_SIZE",
+    default_value = "1000",
+    help = concat!("The number of seq allocations per fsync of the seq numbers.",
+    " The others are persisted by the fsync of the write they are allocated for.")
+    )]
+    pub seq_batch_size: u64,
+
+    #[structopt(
+    long,
+    env = "METASRV_KV_SEQ_INDEX_PREFIXES",
+    default_value = "",
+    help = concat!("Comma separated kv key prefixes to index by seq, e.g. for polling the changes under them.",
+    " Listing an unindexed prefix by seq scans and sorts all of the keys under it.")
+    )]
+    pub kv_seq_index_prefixes: String,
+
+    #[structopt(
+    long,
+    env = "METASRV_KV_REPLY_MAX_ENTRIES",
+    default_value = "100000",
+    help = concat!("The max number of entries in a single kv list reply.",
+    " A request for more fails with an error telling the caller to list in pages.")
+    )]
+    pub kv_reply_max_entries: u64,
+
+    #[structopt(
+    long,
+    env = "METASRV_KV_REPLY_MAX_BYTES",
+    default_value = "67108864",
+    help = concat!("The max number of key and value bytes in a single kv list reply.",
+    " A request for more fails with an error telling the caller to list in pages.")
+    )]
+    pub kv_reply_max_bytes: u64,
+
+    #[structopt(
+    long,
+    env = "METASRV_REPLICATION_SINK",
+    default_value = "",
+    help = concat!("The sink to replicate the committed changes to: \"grpc\" or \"file\".",
+    " Replication is disabled if it is empty.")
+    )]
+    pub replication_sink: String,
+
+    #[structopt(
+        long,
+        env = "METASRV_REPLICATION_SINK_TARGET",
+        default_value = "",
+        help = "The address of a grpc sink, e.g. 127.0.0.1:9999, or the NDJSON file path of a file sink."
+    )]
+    pub replication_sink_target: String,
+
+    #[structopt(
+        long,
+        env = "METASRV_REPLICATION_BATCH_SIZE",
+        default_value = "128",
+        help = "The max number of changes shipped to the replication sink in one batch."
+    )]
+    pub replication_batch_size: u64,
+
+    #[structopt(
+    long,
+    env = "METASRV_REPLICATION_BUFFER_SIZE",
+    default_value = "1024",
+    help = concat!("The max number of changes buffered in memory for the replication sink.",
+    " When it is full, the sink falls behind and catches up from the persisted changes.")
+    )]
+    pub replication_buffer_size: u64,
+
+    #[structopt(
+    long,
+    env = "METASRV_REPLICATION_NODE_ID",
+    default_value = "0",
+    help = concat!("The id of the node that replicates the committed changes to the sink.",
+    " The other nodes of the cluster do not feed nor ship the changes.")
+    )]
+    pub replication_node_id: NodeId,
+
     #[structopt(
         long,
         default_value = "",
         help = "For test only: specifies the tree name prefix"
     )]
     pub sled_tree_prefix: String,
+
+    /// The source of time of the kv expiry, the table trash and the periodic tasks.
+    /// For test only: it is replaced with a `VirtualClock` to advance the time instead of sleeping.
+    #[structopt(skip)]
+    #[serde(skip)]
+    pub clock: SharedClock,
 }
 
 impl Config {
@@ -219,9 +334,52 @@ impl MetaConfig {
             ));
         }
 
+        if self.seq_batch_size == 0 {
+            return Err(ErrorCode::InvalidConfig(
+                "--seq-batch-size must be greater than 0",
+            ));
+        }
+
+        if self.kv_reply_max_entries == 0 || self.kv_reply_max_bytes == 0 {
+            return Err(ErrorCode::InvalidConfig(
+                "--kv-reply-max-entries and --kv-reply-max-bytes must be greater than 0",
+            ));
+        }
+
+        match self.replication_sink.as_str() {
+            "" => {}
+            "grpc" | "file" if !self.replication_sink_target.is_empty() => {}
+            "grpc" | "file" => {
+                return Err(ErrorCode::InvalidConfig(
+                    "--replication-sink-target must be set for a replication sink",
+                ));
+            }
+            sink => {
+                return Err(ErrorCode::InvalidConfig(format!(
+                    "Unknown replication sink: {}, expect grpc or file",
+                    sink
+                )));
+            }
+        }
+
         Ok(())
     }
 
+    /// The interval to vacuum the expired dropped tables.
+    /// It is never longer than the retention, thus an expired table is not kept for too long.
+    pub fn trash_vacuum_interval(&self) -> Duration {
+        Duration::from_secs(self.table_trash_retention.clamp(1, 60))
+    }
+
+    pub fn replication_enabled(&self) -> bool {
+        !self.replication_sink.is_empty()
+    }
+
+    /// Returns true if the node `id` is the one that replicates the committed changes to the sink.
+    pub fn replicates(&self, id: NodeId) -> bool {
+        self.replication_enabled() && self.replication_node_id == id
+    }
+
     /// Create a unique sled::Tree name by prepending a unique prefix.
     /// So that multiple instance that depends on a sled::Tree can be used in one process.
     /// sled does not allow to open multiple `sled::Db` in one process.
@@ -31,3 +31,56 @@ fn test_tls_rpc_enabled() -> anyhow::Result<()> {
     assert_eq!(true, conf.tls_rpc_server_enabled());
     Ok(())
 }
+
+#[test]
+fn test_replication_sink_check() -> anyhow::Result<()> {
+    let mut conf = Config::empty();
+    assert!(!conf.meta_config.replication_enabled());
+    assert!(conf.meta_config.check().is_ok());
+
+    conf.meta_config.replication_sink = "file".to_string();
+    assert!(conf.meta_config.replication_enabled());
+    assert_eq!(
+        "Code: 2301, displayText = --replication-sink-target must be set for a replication sink.",
+        conf.meta_config.check().unwrap_err().to_string()
+    );
+
+    conf.meta_config.replication_sink_target = "/tmp/changes.ndjson".to_string();
+    assert!(conf.meta_config.check().is_ok());
+    assert!(conf.meta_config.replicates(0));
+    assert!(!conf.meta_config.replicates(1));
+
+    conf.meta_config.replication_sink = "kafka".to_string();
+    assert_eq!(
+        "Code: 2301, displayText = Unknown replication sink: kafka, expect grpc or file.",
+        conf.meta_config.check().unwrap_err().to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_seq_batch_size_check() -> anyhow::Result<()> {
+    let mut conf = Config::empty();
+    assert_eq!(1000, conf.meta_config.seq_batch_size);
+
+    conf.meta_config.seq_batch_size = 0;
+    assert_eq!(
+        "Code: 2301, displayText = --seq-batch-size must be greater than 0.",
+        conf.meta_config.check().unwrap_err().to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_kv_reply_max_check() -> anyhow::Result<()> {
+    let mut conf = Config::empty();
+    assert_eq!(100000, conf.meta_config.kv_reply_max_entries);
+    assert_eq!(64 * 1024 * 1024, conf.meta_config.kv_reply_max_bytes);
+
+    conf.meta_config.kv_reply_max_bytes = 0;
+    assert_eq!(
+        "Code: 2301, displayText = --kv-reply-max-entries and --kv-reply-max-bytes must be greater than 0.",
+        conf.meta_config.check().unwrap_err().to_string()
+    );
+    Ok(())
+}
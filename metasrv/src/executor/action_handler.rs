@@ -51,9 +51,15 @@ impl ActionHandler {
         match action {
             StoreDoAction::UpsertKV(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::UpdateKVMeta(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::CasKV(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::MergeKV(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::PatchKVJson(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::GetKV(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::MGetKV(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::PrefixListKV(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::PrefixListKVBySeq(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::PrefixListKVPage(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::DeletePrefixKV(a) => s.serialize(self.handle(a).await?),
             _ => {
                 unimplemented!("non-kv API are no longer supported by metasrv. Although they will be maintained for a while in databend-store")
             }
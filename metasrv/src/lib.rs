@@ -17,6 +17,11 @@ pub mod protobuf {
     include!(concat!(env!("OUT_DIR"), concat!("/meta.rs")));
 }
 
+#[allow(clippy::all)]
+pub mod replication_protobuf {
+    include!(concat!(env!("OUT_DIR"), concat!("/replication.rs")));
+}
+
 #[macro_use]
 pub mod tests;
 
@@ -26,4 +31,5 @@ pub mod executor;
 pub mod meta_service;
 pub mod metrics;
 pub mod raft;
+pub mod replication;
 pub mod sled_store;
@@ -16,8 +16,10 @@ use std::fmt;
 
 use async_raft::NodeId;
 use common_metatypes::Database;
+use common_metatypes::JsonPatchOp;
 use common_metatypes::KVMeta;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
 use common_metatypes::Operation;
 use common_metatypes::Table;
 use serde::Deserialize;
@@ -35,6 +37,9 @@ pub enum Cmd {
     /// Override the record with key.
     SetFile { key: String, value: String },
 
+    /// Remove the records of the files, e.g. of the ones no data part refers to.
+    RemoveFiles { keys: Vec<String> },
+
     /// Increment the sequence number generator specified by `key` and returns the new value.
     IncrSeq { key: String },
 
@@ -55,18 +60,29 @@ pub enum Cmd {
         // TODO(ariesdevil): add `seq` for distinguish between the results of the execution of
         // the two commands (failed `add` and successful `delete`)
         name: String,
+        /// The proposal time, see `Cmd::assign_ts()`.
+        #[serde(default)]
+        ts: u64,
     },
 
-    /// Create a table if absent
+    /// Create a table if absent, or replace it if `seq` is given and matches the id of the
+    /// existing table, 0 if there is none.
     CreateTable {
-        // TODO(ariesdevil): add `seq` for distinguish between the results of the execution of
-        // the two commands (failed `add` and successful `delete`)
         db_name: String,
         table_name: String,
         if_not_exists: bool,
         table: Table,
+        #[serde(default)]
+        seq: Option<MatchSeq>,
+        /// The proposal time, see `Cmd::assign_ts()`.
+        #[serde(default)]
+        ts: u64,
     },
 
+    /// Create tables in one log entry, bumping the meta version once if any of them is created.
+    /// An existing table is left as is, a table of a missing database is skipped.
+    CreateTables { tables: Vec<TableToCreate> },
+
     /// Drop a table if absent
     DropTable {
         // TODO(ariesdevil): add `seq` for distinguish between the results of the execution of
@@ -74,6 +90,44 @@ pub enum Cmd {
         db_name: String,
         table_name: String,
         if_exists: bool,
+        /// The proposal time, see `Cmd::assign_ts()`.
+        #[serde(default)]
+        ts: u64,
+    },
+
+    /// Restore the most recently dropped table from trash, if it is not expired
+    /// and the name is not taken by another table.
+    UndropTable {
+        db_name: String,
+        table_name: String,
+        /// The proposal time, see `Cmd::assign_ts()`.
+        #[serde(default)]
+        ts: u64,
+    },
+
+    /// Rename a table, keeping its id, schema, options and data parts.
+    /// It does nothing if the new name is taken by another table.
+    RenameTable {
+        db_name: String,
+        table_name: String,
+        new_table_name: String,
+    },
+
+    /// Purge expired tables and their data parts from trash.
+    VacuumTrash {
+        /// The proposal time, see `Cmd::assign_ts()`.
+        #[serde(default)]
+        ts: u64,
+    },
+
+    /// Replace the schema of a table, e.g., after widening the type of a column.
+    /// It does nothing if the table is dropped or re-created with another id.
+    ModifyTableSchema {
+        db_name: String,
+        table_name: String,
+        table_id: u64,
+        /// serialized schema
+        schema: Vec<u8>,
     },
 
     /// Update or insert a general purpose kv store
@@ -93,8 +147,85 @@ pub enum Cmd {
         value_meta: Option<KVMeta>,
     },
 
+    /// Transform the value of a generic-kv record with a merge operation, atomically.
+    MergeKV {
+        key: String,
+
+        /// The seq the current value must match, the same as in `UpsertKV`.
+        seq: MatchSeq,
+
+        /// How to compute the new value from the current one.
+        op: MergeOp,
+
+        /// Meta data of the merged value. A `None` keeps the meta of the current value.
+        value_meta: Option<KVMeta>,
+    },
+
+    /// Apply a json patch to the json document of a generic-kv record, atomically.
+    /// An absent record is patched as an empty object. The meta of the value is kept.
+    PatchKVJson {
+        key: String,
+
+        /// The seq the current value must match, the same as in `UpsertKV`.
+        seq: MatchSeq,
+
+        /// The operations applied in order, all or nothing.
+        patch: Vec<JsonPatchOp>,
+    },
+
+    /// Update or delete a generic-kv record if its current value is `expect`, atomically.
+    /// An expired record is the same as an absent one.
+    CasKV {
+        key: String,
+
+        /// The value the record must have, `None` if it must be absent.
+        expect: Option<Vec<u8>>,
+
+        /// The value to set, the same as in `UpsertKV`.
+        value: Operation<Vec<u8>>,
+
+        /// Meta data of the value.
+        value_meta: Option<KVMeta>,
+    },
+
+    /// Delete every generic-kv record whose key starts with `prefix`, in one log entry.
+    DeletePrefixKV { prefix: String },
+
     /// Truncate Table
     TruncateTable { db_name: String, table_name: String },
+
+    /// Set the max bytes of the data parts of a database, `None` for unlimited.
+    SetDatabaseQuota { db_name: String, quota: Option<u64> },
+
+    /// Recompute the used bytes of a database from its data parts,
+    /// e.g., if the incrementally tracked one is skewed.
+    ReconcileDatabaseUsage { db_name: String },
+}
+
+/// A table of `Cmd::CreateTables`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TableToCreate {
+    pub db_name: String,
+    pub table_name: String,
+    pub table: Table,
+}
+
+impl Cmd {
+    /// Assigns the time of the leader to a command that depends on the time.
+    ///
+    /// `ts` is the time in seconds when the log is proposed. The leader assigns it here whatever
+    /// the proposer sets. Every replica applies such a command with the time in the log instead
+    /// of its own clock, thus they e.g. purge the same dropped tables from trash.
+    pub fn assign_ts(&mut self, now: u64) {
+        match self {
+            Cmd::DropDatabase { ts, .. }
+            | Cmd::CreateTable { ts, .. }
+            | Cmd::DropTable { ts, .. }
+            | Cmd::UndropTable { ts, .. }
+            | Cmd::VacuumTrash { ts } => *ts = now,
+            _ => {}
+        }
+    }
 }
 
 impl fmt::Display for Cmd {
@@ -106,6 +237,9 @@ impl fmt::Display for Cmd {
             Cmd::SetFile { key, value } => {
                 write!(f, "set_file:{}={}", key, value)
             }
+            Cmd::RemoveFiles { keys } => {
+                write!(f, "remove_files:{}", keys.join(","))
+            }
             Cmd::IncrSeq { key } => {
                 write!(f, "incr_seq:{}", key)
             }
@@ -123,7 +257,7 @@ impl fmt::Display for Cmd {
                     name, db, if_not_exists, db.database_engine
                 )
             }
-            Cmd::DropDatabase { name } => {
+            Cmd::DropDatabase { name, .. } => {
                 write!(f, "drop_db:{}", name)
             }
             Cmd::CreateTable {
@@ -131,17 +265,34 @@ impl fmt::Display for Cmd {
                 table_name,
                 if_not_exists,
                 table,
+                seq,
+                ..
             } => {
                 write!(
                     f,
                     "create_table:{}-{}={}, if_not_exists:{}",
                     db_name, table_name, table, if_not_exists
-                )
+                )?;
+                match seq {
+                    Some(seq) => write!(f, ", replace if seq {}", seq),
+                    None => Ok(()),
+                }
+            }
+            Cmd::CreateTables { tables } => {
+                write!(f, "create_tables:")?;
+                for (i, t) in tables.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, " {}-{}", t.db_name, t.table_name)?;
+                }
+                Ok(())
             }
             Cmd::DropTable {
                 db_name,
                 table_name,
                 if_exists,
+                ..
             } => {
                 write!(
                     f,
@@ -149,6 +300,39 @@ impl fmt::Display for Cmd {
                     db_name, table_name, if_exists
                 )
             }
+            Cmd::UndropTable {
+                db_name,
+                table_name,
+                ..
+            } => {
+                write!(f, "undrop_table:{}-{}", db_name, table_name)
+            }
+            Cmd::RenameTable {
+                db_name,
+                table_name,
+                new_table_name,
+            } => {
+                write!(
+                    f,
+                    "rename_table:{}-{}, new_table_name:{}",
+                    db_name, table_name, new_table_name
+                )
+            }
+            Cmd::VacuumTrash { ts } => {
+                write!(f, "vacuum_trash: ts:{}", ts)
+            }
+            Cmd::ModifyTableSchema {
+                db_name,
+                table_name,
+                table_id,
+                ..
+            } => {
+                write!(
+                    f,
+                    "modify_table_schema:{}-{}, table_id:{}",
+                    db_name, table_name, table_id
+                )
+            }
             Cmd::UpsertKV {
                 key,
                 seq,
@@ -161,12 +345,44 @@ impl fmt::Display for Cmd {
                     key, seq, value, value_meta
                 )
             }
+            Cmd::MergeKV {
+                key,
+                seq,
+                op,
+                value_meta,
+            } => {
+                write!(f, "merge_kv: {}({:?}) {} ({:?})", key, seq, op, value_meta)
+            }
+            Cmd::PatchKVJson { key, seq, patch } => {
+                write!(f, "patch_kv_json: {}({:?}) {} ops", key, seq, patch.len())
+            }
+            Cmd::CasKV {
+                key,
+                expect,
+                value,
+                value_meta,
+            } => {
+                write!(
+                    f,
+                    "cas_kv: {}({:?}) = {:?} ({:?})",
+                    key, expect, value, value_meta
+                )
+            }
+            Cmd::DeletePrefixKV { prefix } => {
+                write!(f, "delete_prefix_kv: {}", prefix)
+            }
             Cmd::TruncateTable {
                 db_name,
                 table_name,
             } => {
                 write!(f, "truncate table:{}-{}", db_name, table_name)
             }
+            Cmd::SetDatabaseQuota { db_name, quota } => {
+                write!(f, "set_database_quota:{}={:?}", db_name, quota)
+            }
+            Cmd::ReconcileDatabaseUsage { db_name } => {
+                write!(f, "reconcile_database_usage:{}", db_name)
+            }
         }
     }
 }
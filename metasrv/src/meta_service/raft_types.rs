@@ -19,8 +19,13 @@ pub use async_raft::NodeId;
 use byteorder::BigEndian;
 use byteorder::ByteOrder;
 use common_exception::ErrorCode;
+use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::DroppedTable;
 use common_metatypes::KVValue;
 use common_metatypes::SeqValue;
+use common_metatypes::Table;
+use common_store_api_sdk::storage_api_impl::PartBloomFilters;
 use sled::IVec;
 
 use crate::sled_store::SledOrderedSerde;
@@ -70,7 +75,29 @@ impl SledSerde for String {
     }
 }
 
+/// The bytes of an inline data part are stored as they are.
+impl SledSerde for Vec<u8> {
+    fn ser(&self) -> Result<IVec, ErrorCode> {
+        Ok(IVec::from(self.as_slice()))
+    }
+
+    fn de<V: AsRef<[u8]>>(v: V) -> Result<Self, ErrorCode>
+    where Self: Sized {
+        Ok(v.as_ref().to_vec())
+    }
+}
+
 impl SledSerde for SeqValue<KVValue> {}
 
+impl SledSerde for DatabaseUsage {}
+
+impl SledSerde for Database {}
+
+impl SledSerde for Table {}
+
+impl SledSerde for DroppedTable {}
+
+impl SledSerde for PartBloomFilters {}
+
 /// For LogId to be able to stored in sled::Tree as a value.
 impl SledSerde for LogId {}
@@ -14,9 +14,15 @@
 
 use std::collections::BTreeSet;
 use std::collections::HashSet;
+use std::future::Future;
 use std::io::Cursor;
 use std::ops::Bound;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use async_raft::async_trait::async_trait;
 use async_raft::config::Config;
@@ -28,6 +34,7 @@ use async_raft::storage::CurrentSnapshotData;
 use async_raft::storage::HardState;
 use async_raft::storage::InitialState;
 use async_raft::ClientWriteError;
+use async_raft::LogId;
 use async_raft::NodeId;
 use async_raft::Raft;
 use async_raft::RaftMetrics;
@@ -37,7 +44,11 @@ use async_raft::SnapshotPolicy;
 use common_exception::prelude::ErrorCode;
 use common_exception::prelude::ToErrorCode;
 use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::DroppedTable;
 use common_metatypes::KVValue;
+use common_metatypes::MatchSeq;
+use common_metatypes::Operation;
 use common_metatypes::SeqValue;
 use common_metatypes::Table;
 use common_runtime::tokio;
@@ -46,6 +57,8 @@ use common_runtime::tokio::sync::Mutex;
 use common_runtime::tokio::sync::RwLock;
 use common_runtime::tokio::sync::RwLockWriteGuard;
 use common_runtime::tokio::task::JoinHandle;
+use common_runtime::SharedClock;
+use common_store_api_sdk::kv_api_impl::PrefixListPage;
 use common_store_api_sdk::storage_api_impl::AppendResult;
 use common_store_api_sdk::storage_api_impl::DataPartInfo;
 use common_tracing::tracing;
@@ -54,21 +67,33 @@ use common_tracing::tracing::Instrument;
 use crate::configs;
 use crate::meta_service::Cmd;
 use crate::meta_service::LogEntry;
+use crate::meta_service::LogIndex;
 use crate::meta_service::MetaServiceClient;
 use crate::meta_service::MetaServiceImpl;
 use crate::meta_service::MetaServiceServer;
 use crate::meta_service::Network;
 use crate::meta_service::RetryableError;
 use crate::meta_service::ShutdownError;
+use crate::raft::change_feed::ChangeEvent;
+use crate::raft::change_feed::ChangeFeed;
 use crate::raft::log::RaftLog;
+use crate::raft::snapshot_store::SnapshotStatus;
+use crate::raft::snapshot_store::SnapshotStore;
 use crate::raft::state::RaftState;
 use crate::raft::state_machine::AppliedState;
+use crate::raft::state_machine::KVWatcher;
 use crate::raft::state_machine::Node;
 use crate::raft::state_machine::SerializableSnapshot;
 use crate::raft::state_machine::Snapshot;
+use crate::raft::state_machine::SnapshotCatalog;
 use crate::raft::state_machine::StateMachine;
+use crate::replication::create_sink;
+use crate::replication::Replicator;
 use crate::sled_store::get_sled_db;
 
+/// The max number of the keys of an `mget_kv` read from the state machine under one lock.
+pub const MGET_KV_CHUNK_KEYS: usize = 1000;
+
 /// An storage system implementing the `async_raft::RaftStorage` trait.
 ///
 /// Trees:
@@ -76,6 +101,8 @@ use crate::sled_store::get_sled_db;
 ///       id
 ///       hard_state
 ///   log
+///   change_feed
+///   snapshot
 ///   state_machine
 /// TODO(xp): MetaNode recovers persisted state when restarted.
 /// TODO(xp): move Metasrv to a standalone file.
@@ -103,6 +130,9 @@ pub struct MetaRaftStore {
 
     pub log: RaftLog,
 
+    /// The committed changes to ship to the replication sink.
+    pub change_feed: ChangeFeed,
+
     /// The Raft state machine.
     ///
     /// sled db has its own concurrency control, e.g., batch or transaction.
@@ -114,6 +144,15 @@ pub struct MetaRaftStore {
 
     /// The current snapshot.
     pub current_snapshot: RwLock<Option<Snapshot>>,
+
+    /// Persists the current snapshot.
+    pub snapshot_store: SnapshotStore,
+
+    /// Serializes the compactions by raft and by the periodic snapshot task.
+    compaction: Mutex<()>,
+
+    /// The number of logs applied since the last purge, see `log_purge_applied_entries`.
+    applied_since_purge: AtomicU64,
 }
 
 // TODO(xp): the following is a draft struct when meta storage is migrated to sled based impl.
@@ -166,6 +205,16 @@ impl MetaRaftStore {
         let log = RaftLog::open(&db, config).await?;
         tracing::info!("RaftLog opened");
 
+        let change_feed = ChangeFeed::open(&db, config).await?;
+        tracing::info!("ChangeFeed opened");
+
+        let snapshot_store = SnapshotStore::open(&db, config).await?;
+        let last_snapshot = snapshot_store.load()?;
+        tracing::info!(
+            "SnapshotStore opened, last snapshot: {:?}",
+            last_snapshot.as_ref().map(|snap| &snap.meta)
+        );
+
         let (sm_id, prev_sm_id) = raft_state.read_state_machine_id()?;
 
         // There is a garbage state machine need to be cleaned.
@@ -175,18 +224,174 @@ impl MetaRaftStore {
         }
 
         let sm = RwLock::new(StateMachine::open(config, sm_id).await?);
-        let current_snapshot = RwLock::new(None);
+        let current_snapshot = RwLock::new(last_snapshot.clone());
 
-        Ok(Self {
+        let sto = Self {
             id: raft_state.id,
             config: config.clone(),
             is_open,
             _db: db,
             raft_state,
             log,
+            change_feed,
             state_machine: sm,
             current_snapshot,
-        })
+            snapshot_store,
+            compaction: Mutex::new(()),
+            applied_since_purge: AtomicU64::new(0),
+        };
+
+        // Some of the state machine, e.g. the slots, is in memory only.
+        // Restore the state machine to the last snapshot, the logs after it are applied again by raft.
+        if let (true, Some(snapshot)) = (is_open, last_snapshot) {
+            tracing::info!("restore state machine from snapshot: {:?}", snapshot.meta);
+            sto.install_snapshot(&snapshot.data).await?;
+        }
+
+        Ok(sto)
+    }
+
+    /// Take a snapshot of the state machine, persist it, then purge the logs included in it.
+    /// Returns the snapshot.
+    ///
+    /// The logs before the last applied one are purged.
+    /// No other local store depends on the raft log:
+    /// the change feed keeps its own copy of the changes until they are shipped,
+    /// and the trash is part of the state machine.
+    #[tracing::instrument(level = "info", skip(self), fields(id=self.id))]
+    pub async fn compact(&self) -> common_exception::Result<Snapshot> {
+        let _guard = self.compaction.lock().await;
+
+        // 1. Take a serialized snapshot.
+
+        let (view, last_applied_log, last_membership, snapshot_id) = {
+            let sm = self.state_machine.write().await;
+            tracing::info!(
+                "take snapshot of state machine, spaces: {:?}",
+                sm.sm_tree.list_space_stats()
+            );
+            sm.snapshot()?
+        };
+
+        let data = StateMachine::serialize_snapshot(view)?;
+
+        let snapshot = Snapshot {
+            meta: SnapshotMeta {
+                last_log_id: last_applied_log,
+                snapshot_id,
+                membership: last_membership,
+            },
+            data,
+            created_at: now_millis(),
+        };
+
+        // 2. Persist it before the logs included in it are purged.
+
+        self.snapshot_store.save(&snapshot).await?;
+
+        // 3. Remove logs that are included in snapshot.
+
+        // When encountered a snapshot pointer, raft replication is switched to snapshot replication.
+        self.log
+            .insert(&Entry::new_snapshot_pointer(&snapshot.meta))
+            .await?;
+
+        self.purge_logs_before(last_applied_log.index).await?;
+
+        tracing::debug!("log purge complete");
+
+        {
+            let mut current_snapshot = self.current_snapshot.write().await;
+            *current_snapshot = Some(snapshot.clone());
+        }
+
+        tracing::info!(
+            snapshot_size = snapshot.data.len(),
+            "log compaction complete: {:?}",
+            snapshot.meta
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Compact if any log is applied since the current snapshot.
+    /// Returns the new snapshot, or `None` if nothing is applied.
+    pub async fn compact_if_applied(&self) -> common_exception::Result<Option<Snapshot>> {
+        let last_applied = self.state_machine.read().await.get_last_applied()?;
+        let snapshot_last = self
+            .current_snapshot
+            .read()
+            .await
+            .as_ref()
+            .map(|snap| snap.meta.last_log_id);
+
+        if last_applied.index == 0 || Some(last_applied) == snapshot_last {
+            return Ok(None);
+        }
+        Ok(Some(self.compact().await?))
+    }
+
+    /// Purge the logs included in the current snapshot, the snapshot pointer is kept.
+    /// The logs after the snapshot are never purged, even if they are applied:
+    /// a follower lagging behind needs them, or the snapshot.
+    ///
+    /// Returns the number of purged logs.
+    pub async fn purge_applied_logs(&self) -> common_exception::Result<u64> {
+        let snapshot_index = self
+            .current_snapshot
+            .read()
+            .await
+            .as_ref()
+            .map(|snap| snap.meta.last_log_id.index);
+
+        match snapshot_index {
+            None => Ok(0),
+            Some(index) => self.purge_logs_before(index).await,
+        }
+    }
+
+    async fn purge_logs_before(&self, index: LogIndex) -> common_exception::Result<u64> {
+        match index {
+            0 => Ok(0),
+            _ => self.log.purge_upto(index - 1).await,
+        }
+    }
+
+    // Purges the logs included in the current snapshot after every `log_purge_applied_entries`
+    // applied logs. `compact()` purges them when it takes a snapshot, this catches the ones it
+    // leaves, e.g. if the node crashes or the purge fails after the snapshot is saved, without
+    // waiting for the next snapshot.
+    //
+    // A log is applied whether the purge succeeds or not: a failure is logged and the purge is
+    // retried after the next `log_purge_applied_entries` logs.
+    async fn purge_after_applied(&self, applied: u64) {
+        let purge_every = self.config.log_purge_applied_entries;
+        if purge_every == 0 {
+            return;
+        }
+
+        let applied = self
+            .applied_since_purge
+            .fetch_add(applied, Ordering::Relaxed)
+            + applied;
+        if applied < purge_every {
+            return;
+        }
+        self.applied_since_purge.store(0, Ordering::Relaxed);
+
+        match self.purge_applied_logs().await {
+            Ok(purged) => tracing::debug!("purged {} logs after {} applied logs", purged, applied),
+            Err(e) => tracing::warn!("failed to purge logs after {} applied logs: {}", applied, e),
+        }
+    }
+
+    /// The last snapshot taken or installed, `None` if there is not any.
+    pub async fn get_snapshot_status(&self) -> Option<SnapshotStatus> {
+        self.current_snapshot
+            .read()
+            .await
+            .as_ref()
+            .map(SnapshotStatus::from)
     }
 
     /// Get a handle to the state machine for testing purposes.
@@ -194,6 +399,33 @@ impl MetaRaftStore {
         self.state_machine.write().await
     }
 
+    /// Apply a log to the state machine, then append the change it makes to the change feed,
+    /// if this node replicates. A log that is rejected or changes nothing is not a change.
+    ///
+    /// Both are done with the state machine locked, thus a snapshot never includes a change that
+    /// is not fed. If the node crashes in between, the logs after the last snapshot are applied
+    /// again after restart and the change is fed again, with the same id.
+    async fn apply_and_feed(
+        &self,
+        sm: &mut StateMachine,
+        entry: &Entry<LogEntry>,
+    ) -> common_exception::Result<AppliedState> {
+        let duplicated = sm.is_duplicated(entry);
+        let resp = sm.apply(entry).await?;
+
+        if !self.config.replicates(self.id) || duplicated {
+            return Ok(resp);
+        }
+
+        if let EntryPayload::Normal(ref norm) = entry.payload {
+            if resp.is_change_of(&norm.data.cmd) {
+                let event = ChangeEvent::new(entry.log_id.index, norm.data.cmd.clone());
+                self.change_feed.append(&event).await?;
+            }
+        }
+        Ok(resp)
+    }
+
     pub async fn read_hard_state(&self) -> common_exception::Result<Option<HardState>> {
         self.raft_state.read_hard_state()
     }
@@ -222,19 +454,23 @@ impl MetaRaftStore {
             .write_state_machine_id(&(sm_id, new_sm_id))
             .await?;
 
-        let new_sm = StateMachine::open(&self.config, new_sm_id).await?;
+        let mut new_sm = StateMachine::open(&self.config, new_sm_id).await?;
         tracing::info!(
             "insert all key-value into new state machine, n={}",
             snap.kvs.len()
         );
 
-        let tree = &new_sm.sm_tree.tree;
         let nkvs = snap.kvs.len();
-        for x in snap.kvs.into_iter() {
-            let k = &x[0];
-            let v = &x[1];
-            tree.insert(k, v.clone())
-                .map_err_to_code(ErrorCode::MetaStoreDamaged, || "fail to insert snapshot")?;
+        let kvs = snap.kvs.into_iter().map(|mut x| {
+            let v = x.pop().unwrap_or_default();
+            let k = x.pop().unwrap_or_default();
+            (k, v)
+        });
+        new_sm.sm_tree.import_snapshot(kvs).await?;
+
+        new_sm.load_catalog()?;
+        if snap.catalog != SnapshotCatalog::default() {
+            new_sm.restore_catalog(snap.catalog).await?;
         }
 
         tracing::info!(
@@ -243,7 +479,10 @@ impl MetaRaftStore {
             new_sm.get_last_applied()?,
         );
 
-        tree.flush_async()
+        new_sm
+            .sm_tree
+            .tree
+            .flush_async()
             .await
             .map_err_to_code(ErrorCode::MetaStoreDamaged, || "fail to flush snapshot")?;
 
@@ -267,6 +506,8 @@ impl MetaRaftStore {
 
         // TODO(xp): use checksum to check consistency?
 
+        // The watchers keep going on the new state machine.
+        new_sm.kv_watch = sm.kv_watch.clone();
         *sm = new_sm;
         Ok(())
     }
@@ -405,69 +646,42 @@ impl RaftStorage<LogEntry, AppliedState> for MetaRaftStore {
         &self,
         entry: &Entry<LogEntry>,
     ) -> anyhow::Result<AppliedState> {
-        let mut sm = self.state_machine.write().await;
-        let resp = sm.apply(entry).await?;
+        let resp = {
+            let mut sm = self.state_machine.write().await;
+            self.apply_and_feed(&mut sm, entry).await?
+        };
+
+        self.purge_after_applied(1).await;
         Ok(resp)
     }
 
     #[tracing::instrument(level = "info", skip(self, entries), fields(id=self.id))]
     async fn replicate_to_state_machine(&self, entries: &[&Entry<LogEntry>]) -> anyhow::Result<()> {
-        let mut sm = self.state_machine.write().await;
-        for entry in entries {
-            sm.apply(*entry).await?;
+        {
+            let mut sm = self.state_machine.write().await;
+            for entry in entries {
+                self.apply_and_feed(&mut sm, entry).await?;
+            }
         }
+
+        self.purge_after_applied(entries.len() as u64).await;
         Ok(())
     }
 
     #[tracing::instrument(level = "info", skip(self), fields(id=self.id))]
     async fn do_log_compaction(&self) -> anyhow::Result<CurrentSnapshotData<Self::Snapshot>> {
         // NOTE: do_log_compaction is guaranteed to be serialized called by RaftCore.
+        //       The periodic snapshot task also compacts, `compact()` serializes them.
 
         // TODO(xp): add test of small chunk snapshot transfer and installation
 
         // TODO(xp): disallow to install a snapshot with smaller last_applied_log
 
-        // 1. Take a serialized snapshot
-
-        let (view, last_applied_log, last_membership, snapshot_id) =
-            self.state_machine.write().await.snapshot()?;
-
-        let data = StateMachine::serialize_snapshot(view)?;
-        let snapshot_size = data.len();
-
-        let snap_meta = SnapshotMeta {
-            last_log_id: last_applied_log,
-            snapshot_id,
-            membership: last_membership.clone(),
-        };
-
-        let snapshot = Snapshot {
-            meta: snap_meta.clone(),
-            data: data.clone(),
-        };
-
-        // 2. Remove logs that are included in snapshot.
-
-        // When encountered a snapshot pointer, raft replication is switched to snapshot replication.
-        self.log
-            .insert(&Entry::new_snapshot_pointer(&snapshot.meta))
-            .await?;
-
-        self.log.range_remove(0..last_applied_log.index).await?;
-
-        tracing::debug!("log range_remove complete");
-
-        // Update the snapshot first.
-        {
-            let mut current_snapshot = self.current_snapshot.write().await;
-            *current_snapshot = Some(snapshot);
-        }
-
-        tracing::debug!(snapshot_size = snapshot_size, "log compaction complete");
+        let snapshot = self.compact().await?;
 
         Ok(CurrentSnapshotData {
-            meta: snap_meta,
-            snapshot: Box::new(Cursor::new(data)),
+            meta: snapshot.meta,
+            snapshot: Box::new(Cursor::new(snapshot.data)),
         })
     }
 
@@ -492,6 +706,7 @@ impl RaftStorage<LogEntry, AppliedState> for MetaRaftStore {
         let new_snapshot = Snapshot {
             meta: meta.clone(),
             data: snapshot.into_inner(),
+            created_at: now_millis(),
         };
 
         tracing::debug!("SNAP META:{:?}", meta);
@@ -505,12 +720,14 @@ impl RaftStorage<LogEntry, AppliedState> for MetaRaftStore {
             }
         };
 
+        self.snapshot_store.save(&new_snapshot).await?;
+
         // When encountered a snapshot pointer, raft replication is switched to snapshot replication.
         self.log
             .insert(&Entry::new_snapshot_pointer(&new_snapshot.meta))
             .await?;
 
-        self.log.range_remove(0..meta.last_log_id.index).await?;
+        self.purge_logs_before(meta.last_log_id.index).await?;
 
         // Update current snapshot.
         {
@@ -685,6 +902,11 @@ impl MetaNode {
         }
     }
 
+    /// The clock of the node, the times in the logs it proposes are read from it.
+    pub fn clock(&self) -> SharedClock {
+        self.sto.config.clock.clone()
+    }
+
     pub fn new_raft_config(config: &configs::MetaConfig) -> Config {
         // TODO(xp): configure cluster name.
 
@@ -870,6 +1092,141 @@ impl MetaNode {
         jh.push(h);
     }
 
+    /// Spawn a task to purge the expired dropped tables periodically.
+    /// Only the leader submits a `VacuumTrash` log. The log carries the time of the leader, and
+    /// every node compares the expiration of the dropped tables with it, thus every node purges
+    /// the same tables.
+    ///
+    /// The meta of the files of the purged parts is removed by the log, the files themselves are
+    /// passed to `remove_files` once it is applied.
+    pub async fn start_trash_vacuum<F, Fut>(mn: Arc<Self>, interval: Duration, remove_files: F)
+    where
+        F: Fn(Vec<DataPartInfo>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let mut running_rx = mn.running_rx.clone();
+        let mut jh = mn.join_handles.lock().await;
+
+        let mn = mn.clone();
+
+        let span = tracing::span!(tracing::Level::INFO, "trash-vacuum");
+
+        let h = tokio::task::spawn(
+            {
+                async move {
+                    loop {
+                        tokio::select! {
+                            _ = running_rx.changed() => {
+                               return Ok::<(), common_exception::ErrorCode>(());
+                            }
+                            _ = mn.sto.config.clock.sleep(interval) => {}
+                        };
+
+                        let is_leader = mn.metrics_rx.borrow().current_leader == Some(mn.sto.id);
+                        if is_leader {
+                            let ts = mn.sto.config.clock.now_secs();
+                            let mut parts = {
+                                let sm = mn.sto.state_machine.read().await;
+                                sm.get_expired_trash_parts(ts)
+                            };
+                            let rst = mn
+                                .write(LogEntry {
+                                    txid: None,
+                                    cmd: Cmd::VacuumTrash { ts },
+                                })
+                                .await;
+
+                            match rst {
+                                Ok(AppliedState::DroppedTables {
+                                    prev: Some(purged), ..
+                                }) => {
+                                    let purged_parts = purged
+                                        .iter()
+                                        .filter_map(|t| parts.remove(&t.table.table_id))
+                                        .flatten()
+                                        .collect::<Vec<_>>();
+                                    if !purged_parts.is_empty() {
+                                        remove_files(purged_parts).await;
+                                    }
+                                }
+                                _ => {
+                                    tracing::info!(
+                                        "fail to vacuum trash: my id={}, rst:{:?}",
+                                        mn.sto.id,
+                                        rst
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            .instrument(span),
+        );
+        jh.push(h);
+    }
+
+    /// Spawn a task to take a snapshot and purge the logs included in it periodically,
+    /// besides the snapshots raft takes every `snapshot_logs_since_last` logs.
+    /// Every node compacts its own logs. It does nothing if `interval` is zero.
+    pub async fn start_snapshot(mn: Arc<Self>, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+
+        let mut running_rx = mn.running_rx.clone();
+        let mut jh = mn.join_handles.lock().await;
+
+        let mn = mn.clone();
+
+        let span = tracing::span!(tracing::Level::INFO, "snapshot");
+
+        let h = tokio::task::spawn(
+            {
+                async move {
+                    loop {
+                        tokio::select! {
+                            _ = running_rx.changed() => {
+                               return Ok::<(), common_exception::ErrorCode>(());
+                            }
+                            _ = mn.sto.config.clock.sleep(interval) => {}
+                        };
+
+                        if let Err(e) = mn.sto.compact_if_applied().await {
+                            tracing::error!("fail to snapshot: my id={}, err:{:?}", mn.sto.id, e);
+                        }
+                    }
+                }
+            }
+            .instrument(span),
+        );
+        jh.push(h);
+    }
+
+    /// Spawn a task to ship the committed changes to the configured replication sink.
+    /// It does nothing if replication is disabled or another node of the cluster replicates,
+    /// thus the sink receives one copy of the changes.
+    pub async fn start_replication(
+        mn: Arc<Self>,
+        config: &configs::MetaConfig,
+    ) -> common_exception::Result<()> {
+        if !config.replicates(mn.sto.id) {
+            return Ok(());
+        }
+
+        let sink = create_sink(mn.sto.id, config)?;
+        let replicator =
+            Replicator::create(mn.sto.clone(), sink, config.replication_batch_size as usize);
+
+        let running_rx = mn.running_rx.clone();
+        let mut jh = mn.join_handles.lock().await;
+
+        let span = tracing::span!(tracing::Level::INFO, "replication");
+        let h = tokio::task::spawn(replicator.run(running_rx).instrument(span));
+        jh.push(h);
+        Ok(())
+    }
+
     /// Boot up the first node to create a cluster.
     /// For every cluster this func should be called exactly once.
     /// When a node is initialized with boot or boot_non_voter, start it with Metasrv::new().
@@ -960,6 +1317,12 @@ impl MetaNode {
         sm.get_file(key)
     }
 
+    /// See `StateMachine::list_unregistered_files`.
+    pub async fn list_unregistered_files(&self) -> common_exception::Result<Vec<(String, String)>> {
+        let sm = self.sto.state_machine.read().await;
+        sm.list_unregistered_files()
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn list_files(&self, prefix: &str) -> common_exception::Result<Vec<String>> {
         // inconsistent get: from local state machine
@@ -1010,6 +1373,22 @@ impl MetaNode {
         sm.get_database(name)
     }
 
+    /// Get all the databases from local meta state machine, sorted by the database id.
+    /// The returned value may not be the latest written.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_databases(&self) -> Vec<(String, Database)> {
+        // inconsistent get: from local state machine
+
+        let sm = self.sto.state_machine.read().await;
+        let mut dbs = sm
+            .get_databases()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        dbs.sort_by_key(|(_, db)| db.database_id);
+        dbs
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_database_meta(
         &self,
@@ -1046,6 +1425,14 @@ impl MetaNode {
         sm.get_table(tid)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_dropped_tables(&self) -> Vec<DroppedTable> {
+        // inconsistent get: from local state machine
+
+        let sm = self.sto.state_machine.read().await;
+        sm.get_dropped_tables()
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_data_parts(
         &self,
@@ -1056,30 +1443,192 @@ impl MetaNode {
         sm.get_data_parts(db_name, table_name)
     }
 
+    /// Sets the bloom filters of the parts, kept apart from their descriptors. The parts without
+    /// filters, and the ones recorded with their filters, are left as they are.
+    #[tracing::instrument(level = "debug", skip(self, parts))]
+    pub async fn load_bloom_filters(
+        &self,
+        parts: &mut [DataPartInfo],
+    ) -> common_exception::Result<()> {
+        let sm = self.sto.state_machine.read().await;
+        for part in parts.iter_mut().filter(|p| p.bloom_filters.is_none()) {
+            part.bloom_filters = sm.get_bloom_filters(&part.part.name)?;
+        }
+        Ok(())
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_data_part(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        part_name: &str,
+    ) -> common_exception::Result<Option<DataPartInfo>> {
+        let sm = self.sto.state_machine.read().await;
+        sm.get_data_part(db_name, table_name, part_name)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, inline_parts))]
     pub async fn append_data_parts(
         &self,
         db_name: &str,
         table_name: &str,
         append_res: &AppendResult,
-    ) {
+        inline_parts: &[(String, Vec<u8>)],
+    ) -> common_exception::Result<Option<u64>> {
+        self.append_data_parts_with_marker(db_name, table_name, append_res, inline_parts, None)
+            .await
+    }
+
+    /// Appends the parts like `append_data_parts`, and sets the generic kv `marker` to an empty
+    /// value if they are appended, under the same lock of the state machine.
+    #[tracing::instrument(level = "debug", skip(self, append_res, inline_parts))]
+    pub async fn append_data_parts_with_marker(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        append_res: &AppendResult,
+        inline_parts: &[(String, Vec<u8>)],
+        marker: Option<&str>,
+    ) -> common_exception::Result<Option<u64>> {
         let mut sm = self.sto.state_machine.write().await;
-        sm.append_data_parts(db_name, table_name, append_res)
+        let data_version = sm
+            .append_data_parts(db_name, table_name, append_res, inline_parts)
+            .await?;
+        if let (Some(_), Some(marker)) = (data_version, marker) {
+            sm.apply_cmd(&Cmd::UpsertKV {
+                key: marker.to_string(),
+                seq: MatchSeq::Any,
+                value: Operation::Update(vec![]),
+                value_meta: None,
+            })
+            .await?;
+        }
+        Ok(data_version)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, append_res, inline_parts))]
+    pub async fn replace_data_parts(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        removed: &[String],
+        append_res: &AppendResult,
+        inline_parts: &[(String, Vec<u8>)],
+    ) -> common_exception::Result<Option<u64>> {
+        self.replace_data_parts_with_markers(
+            db_name,
+            table_name,
+            removed,
+            append_res,
+            inline_parts,
+            &[],
+        )
+        .await
+    }
+
+    /// Replaces the parts as `replace_data_parts`, and sets the kv keys `markers` in the same
+    /// apply if the parts are replaced.
+    #[tracing::instrument(level = "debug", skip(self, append_res, inline_parts))]
+    pub async fn replace_data_parts_with_markers(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        removed: &[String],
+        append_res: &AppendResult,
+        inline_parts: &[(String, Vec<u8>)],
+        markers: &[String],
+    ) -> common_exception::Result<Option<u64>> {
+        let mut sm = self.sto.state_machine.write().await;
+        let data_version = sm
+            .replace_data_parts(db_name, table_name, removed, append_res, inline_parts)
+            .await?;
+        if data_version.is_some() {
+            for marker in markers {
+                sm.apply_cmd(&Cmd::UpsertKV {
+                    key: marker.clone(),
+                    seq: MatchSeq::Any,
+                    value: Operation::Update(vec![]),
+                    value_meta: None,
+                })
+                .await?;
+            }
+        }
+        Ok(data_version)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_inline_part(&self, name: &str) -> common_exception::Result<Option<Vec<u8>>> {
+        // inconsistent get: from local state machine
+
+        let sm = self.sto.state_machine.read().await;
+        sm.get_inline_part(name)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_inline_parts(&self, prefix: &str) -> common_exception::Result<Vec<String>> {
+        let sm = self.sto.state_machine.read().await;
+        sm.list_inline_parts(prefix)
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn remove_table_data_parts(&self, db_name: &str, table_name: &str) {
+    pub async fn get_table_data_version(&self, db_name: &str, table_name: &str) -> Option<u64> {
+        // inconsistent get: from local state machine
+
+        let sm = self.sto.state_machine.read().await;
+        sm.get_table_data_version(db_name, table_name)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_table_data_parts(
+        &self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<()> {
         let mut sm = self.sto.state_machine.write().await;
-        sm.remove_table_data_parts(db_name, table_name)
+        sm.remove_table_data_parts(db_name, table_name).await
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn remove_db_data_parts(&self, db_name: &str) {
+    pub async fn remove_db_data_parts(&self, db_name: &str) -> common_exception::Result<()> {
         let mut sm = self.sto.state_machine.write().await;
-        sm.remove_db_data_parts(db_name)
+        sm.remove_db_data_parts(db_name).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_database_usage(
+        &self,
+        db_name: &str,
+    ) -> common_exception::Result<Option<DatabaseUsage>> {
+        // inconsistent get: from local state machine
+
+        let sm = self.sto.state_machine.read().await;
+        sm.get_database_usage(db_name)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_database_usages(
+        &self,
+    ) -> common_exception::Result<Vec<(String, DatabaseUsage)>> {
+        // inconsistent get: from local state machine
+
+        let sm = self.sto.state_machine.read().await;
+        sm.get_database_usages()
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
+    /// The last snapshot of this node, `None` if there is not any.
+    pub async fn get_snapshot_status(&self) -> Option<SnapshotStatus> {
+        self.sto.get_snapshot_status().await
+    }
+
+    /// The id of the last log applied to the local state machine.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_last_applied(&self) -> common_exception::Result<LogId> {
+        let sm = self.sto.state_machine.read().await;
+        sm.get_last_applied()
+    }
+
     pub async fn get_kv(&self, key: &str) -> common_exception::Result<Option<SeqValue<KVValue>>> {
         // inconsistent get: from local state machine
 
@@ -1087,14 +1636,30 @@ impl MetaNode {
         sm.get_kv(key)
     }
 
+    /// Starts a watcher of the changes of the keys under `prefix` applied to the local state machine.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn watch_kv(
+        &self,
+        prefix: &str,
+        after_seq: Option<u64>,
+    ) -> common_exception::Result<KVWatcher> {
+        let sm = self.sto.state_machine.read().await;
+        sm.watch_kv(prefix, after_seq)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn mget_kv(
         &self,
         keys: &[impl AsRef<str> + std::fmt::Debug],
     ) -> common_exception::Result<Vec<Option<SeqValue<KVValue>>>> {
         // inconsistent get: from local state machine
-        let sm = self.sto.state_machine.read().await;
-        sm.mget_kv(keys)
+        // A large request is read a chunk at a time, the applies are not held off for all of it.
+        let mut res = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(MGET_KV_CHUNK_KEYS) {
+            let sm = self.sto.state_machine.read().await;
+            res.extend(sm.mget_kv(chunk)?);
+        }
+        Ok(res)
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
@@ -1107,6 +1672,37 @@ impl MetaNode {
         sm.prefix_list_kv(prefix)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn prefix_list_kv_page(
+        &self,
+        prefix: &str,
+        limit: Option<usize>,
+        after_key: Option<&str>,
+    ) -> common_exception::Result<PrefixListPage> {
+        // inconsistent get: from local state machine
+        let sm = self.sto.state_machine.read().await;
+        sm.prefix_list_kv_page(prefix, limit, after_key)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn prefix_count_kv(&self, prefix: &str) -> common_exception::Result<usize> {
+        // inconsistent get: from local state machine
+        let sm = self.sto.state_machine.read().await;
+        sm.prefix_count_kv(prefix)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn prefix_list_kv_by_seq(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
+    ) -> common_exception::Result<Vec<(String, SeqValue<KVValue>)>> {
+        // inconsistent get: from local state machine
+        let sm = self.sto.state_machine.read().await;
+        sm.prefix_list_kv_by_seq(prefix, min_seq, limit)
+    }
+
     /// Submit a write request to the known leader. Returns the response after applying the request.
     #[tracing::instrument(level = "info", skip(self))]
     pub async fn write(&self, req: LogEntry) -> common_exception::Result<AppliedState> {
@@ -1175,8 +1771,11 @@ impl MetaNode {
     #[tracing::instrument(level = "info", skip(self))]
     pub async fn write_to_local_leader(
         &self,
-        req: LogEntry,
+        mut req: LogEntry,
     ) -> common_exception::Result<Result<AppliedState, RetryableError>> {
+        // Only the leader accepts a write, its clock is the time of the log on every replica.
+        req.cmd.assign_ts(self.sto.config.clock.now_secs());
+
         let write_rst = self.raft.client_write(ClientWriteRequest::new(req)).await;
 
         tracing::debug!("raft.client_write rst: {:?}", write_rst);
@@ -1199,3 +1798,10 @@ impl MetaNode {
         }
     }
 }
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -15,9 +15,13 @@
 use std::collections::BTreeSet;
 use std::sync::Arc;
 
+use async_raft::raft::Entry;
+use async_raft::raft::EntryPayload;
+use async_raft::LogId;
 use async_raft::RaftMetrics;
 use async_raft::State;
 use common_metatypes::MatchSeq;
+use common_metatypes::Operation;
 use common_runtime::tokio;
 use common_runtime::tokio::time::Duration;
 use common_tracing::tracing;
@@ -31,6 +35,7 @@ use crate::meta_service::MetaNode;
 use crate::meta_service::NodeId;
 use crate::meta_service::RaftTxId;
 use crate::meta_service::RetryableError;
+use crate::raft::snapshot_store::SnapshotStatus;
 use crate::raft::state_machine::AppliedState;
 use crate::tests::assert_meta_connection;
 use crate::tests::service::new_test_context;
@@ -578,7 +583,7 @@ async fn test_meta_node_restart_single_node() -> anyhow::Result<()> {
     //   - raft logs.
     //   - state machine:
     //     - Nodes
-    //   - TODO(xp): snapshot is empty, since snapshot is not persisted in this version see `MetaStore`.
+    //   - snapshot is empty, since no snapshot is taken. See `test_meta_node_snapshot_restart`.
     // - Check cluster:
     //   - Leader is elected.
     //   - TODO(xp): Leader starts replication to follower and non-voter.
@@ -641,8 +646,288 @@ async fn test_meta_node_restart_single_node() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 5)]
+async fn test_meta_node_snapshot_restart() -> anyhow::Result<()> {
+    // - Start a solo leader and write some mutations.
+    // - Take a snapshot: the logs included in it are purged, the state is still readable.
+    // - Write more mutations after the snapshot.
+    // - Restart: the state is restored from the snapshot and the logs after it.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let mut log_cnt: u64 = 0;
+    let (_id, mut tc) = setup_leader().await?;
+    log_cnt += 2;
+
+    let leader = tc.meta_nodes.pop().unwrap();
+
+    let upsert = |key: &str| LogEntry {
+        txid: None,
+        cmd: Cmd::UpsertKV {
+            key: key.to_string(),
+            seq: MatchSeq::Any,
+            value: Operation::Update(key.as_bytes().to_vec()),
+            value_meta: None,
+        },
+    };
+
+    leader
+        .write(LogEntry {
+            txid: None,
+            cmd: Cmd::CreateDatabase {
+                name: "db1".to_string(),
+                if_not_exists: false,
+                db: Default::default(),
+            },
+        })
+        .await?;
+    leader
+        .write(LogEntry {
+            txid: None,
+            cmd: Cmd::CreateTable {
+                db_name: "db1".to_string(),
+                table_name: "tb1".to_string(),
+                if_not_exists: false,
+                table: Default::default(),
+                seq: None,
+                ts: 0,
+            },
+        })
+        .await?;
+    log_cnt += 2;
+
+    for i in 0..10 {
+        leader.write(upsert(&format!("k{}", i))).await?;
+        log_cnt += 1;
+    }
+    wait_for_log(&leader, log_cnt).await?;
+
+    tracing::info!("--- snapshot and purge the logs");
+    let status;
+    {
+        let snapshot = leader.sto.compact().await?;
+        assert_eq!(log_cnt, snapshot.meta.last_log_id.index);
+
+        // Only the snapshot pointer is left.
+        let logs = leader.sto.log.range_values(..)?;
+        assert_eq!(1, logs.len());
+        assert_eq!(snapshot.meta.last_log_id, logs[0].log_id);
+
+        status = leader.get_snapshot_status().await.unwrap();
+        assert_eq!(snapshot.meta.last_log_id, status.last_log_id);
+        assert_eq!(snapshot.data.len() as u64, status.size);
+        assert_eq!(
+            Some(&status),
+            leader
+                .sto
+                .snapshot_store
+                .load()?
+                .as_ref()
+                .map(SnapshotStatus::from)
+                .as_ref()
+        );
+
+        // Nothing is applied since the snapshot.
+        assert!(leader.sto.compact_if_applied().await?.is_none());
+
+        let db = leader.get_database("db1").await.unwrap();
+        assert!(leader.get_table(&db.tables["tb1"]).await.is_some());
+        assert_eq!(b"k0".to_vec(), leader.get_kv("k0").await?.unwrap().1.value);
+    }
+
+    leader.write(upsert("k_after")).await?;
+    log_cnt += 1;
+    wait_for_log(&leader, log_cnt).await?;
+
+    let want_dbs = leader.sto.state_machine.read().await.catalog();
+    let want_kvs = kv_values(&leader).await?;
+    assert_eq!(11, want_kvs.len());
+    leader.stop().await?;
+
+    tracing::info!("--- reopen MetaNode");
+    let leader = MetaNode::open(&tc.config.meta_config).await?;
+    log_cnt += 1;
+
+    wait_for_state(&leader, State::Leader).await?;
+    wait_for_log(&leader, log_cnt).await?;
+
+    tracing::info!("--- check the restored state");
+    {
+        assert_eq!(Some(status), leader.get_snapshot_status().await);
+        assert_eq!(want_dbs, leader.sto.state_machine.read().await.catalog());
+        assert_eq!(want_kvs, kv_values(&leader).await?);
+    }
+
+    Ok(())
+}
+
+/// The generic kvs with their seq.
+async fn kv_values(mn: &MetaNode) -> anyhow::Result<Vec<(String, u64, Vec<u8>)>> {
+    let kvs = mn.prefix_list_kv("k").await?;
+    Ok(kvs
+        .into_iter()
+        .map(|(k, (seq, v))| (k, seq, v.value))
+        .collect())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 5)]
+async fn test_meta_node_restart_replica_same_seq() -> anyhow::Result<()> {
+    // - Start a leader and a non-voter, write some kvs and a database.
+    // - Restart the non-voter, which drops what its state machine has in memory.
+    // - Write more kvs and databases: every node hands out the same seq numbers and ids.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let (_nid0, tc0) = setup_leader().await?;
+    let leader = tc0.meta_nodes[0].clone();
+    let (_nid1, tc1) = setup_non_voter(leader.clone(), 1).await?;
+    let mut replica = tc1.meta_nodes[0].clone();
+
+    let mut log_cnt = leader.raft.metrics().borrow().last_applied;
+
+    let upsert = |key: &str| LogEntry {
+        txid: None,
+        cmd: Cmd::UpsertKV {
+            key: key.to_string(),
+            seq: MatchSeq::Any,
+            value: Operation::Update(key.as_bytes().to_vec()),
+            value_meta: None,
+        },
+    };
+    let create_database = |name: &str| LogEntry {
+        txid: None,
+        cmd: Cmd::CreateDatabase {
+            name: name.to_string(),
+            if_not_exists: false,
+            db: Default::default(),
+        },
+    };
+
+    for i in 0..3 {
+        leader.write(upsert(&format!("k{}", i))).await?;
+    }
+    leader.write(create_database("db1")).await?;
+    log_cnt += 4;
+    wait_for_log(&replica, log_cnt).await?;
+
+    tracing::info!("--- restart the non-voter");
+    {
+        replica.stop().await?;
+        replica = MetaNode::open(&tc1.config.meta_config).await?;
+        wait_for_state(&replica, State::NonVoter).await?;
+    }
+
+    for i in 3..6 {
+        leader.write(upsert(&format!("k{}", i))).await?;
+    }
+    leader.write(create_database("db2")).await?;
+    log_cnt += 4;
+    wait_for_log(&leader, log_cnt).await?;
+    wait_for_log(&replica, log_cnt).await?;
+
+    tracing::info!("--- check the seq numbers and ids");
+    {
+        let want_kvs = kv_values(&leader).await?;
+        let seqs = want_kvs.iter().map(|(_, seq, _)| *seq).collect::<Vec<_>>();
+        assert_eq!((1..=6).collect::<Vec<_>>(), seqs);
+        assert_eq!(want_kvs, kv_values(&replica).await?);
+
+        for name in ["db1", "db2"] {
+            let want = leader.get_database(name).await.unwrap();
+            let got = replica.get_database(name).await.unwrap();
+            assert_eq!(want.database_id, got.database_id, "id of {}", name);
+        }
+        assert_eq!(
+            leader
+                .sto
+                .state_machine
+                .read()
+                .await
+                .get_database_meta_ver()?,
+            replica
+                .sto
+                .state_machine
+                .read()
+                .await
+                .get_database_meta_ver()?
+        );
+    }
+
+    Ok(())
+}
+
 /// Setup a cluster with several voter and several non_voter
 /// The node id 0 must be in `voters` and node 0 is elected as leader.
+#[tokio::test(flavor = "multi_thread", worker_threads = 5)]
+async fn test_meta_node_purge_applied_logs() -> anyhow::Result<()> {
+    // - Start a solo leader, write some mutations and take a snapshot.
+    // - Write more mutations after the snapshot, and put back some logs before the snapshot,
+    //   as if a crash interrupted the purge of a compaction.
+    // - Purge: only the logs before the snapshot are removed.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let mut log_cnt: u64 = 0;
+    let (_id, mut tc) = setup_leader().await?;
+    log_cnt += 2;
+
+    let leader = tc.meta_nodes.pop().unwrap();
+
+    let upsert = |key: &str| LogEntry {
+        txid: None,
+        cmd: Cmd::UpsertKV {
+            key: key.to_string(),
+            seq: MatchSeq::Any,
+            value: Operation::Update(key.as_bytes().to_vec()),
+            value_meta: None,
+        },
+    };
+
+    for i in 0..10 {
+        leader.write(upsert(&format!("k{}", i))).await?;
+        log_cnt += 1;
+    }
+    wait_for_log(&leader, log_cnt).await?;
+
+    let snapshot = leader.sto.compact().await?;
+    assert_eq!(log_cnt, snapshot.meta.last_log_id.index);
+
+    // Nothing to purge right after a compaction.
+    assert_eq!(0, leader.sto.purge_applied_logs().await?);
+
+    for i in 0..3 {
+        leader.write(upsert(&format!("k_after{}", i))).await?;
+        log_cnt += 1;
+    }
+    wait_for_log(&leader, log_cnt).await?;
+
+    let left = (1..=3)
+        .map(|index| Entry {
+            log_id: LogId { term: 1, index },
+            payload: EntryPayload::Blank,
+        })
+        .collect::<Vec<_>>();
+    leader.sto.log.append(&left).await?;
+
+    assert_eq!(3, leader.sto.purge_applied_logs().await?);
+
+    // The snapshot pointer and the logs after it are kept.
+    let logs = leader.sto.log.range_values(..)?;
+    assert_eq!(4, logs.len());
+    assert_eq!(snapshot.meta.last_log_id, logs[0].log_id);
+    assert_eq!(log_cnt, logs[3].log_id.index);
+    assert_eq!(
+        Some(log_cnt),
+        leader.sto.log.last()?.map(|(index, _)| index)
+    );
+
+    leader.stop().await?;
+    Ok(())
+}
+
 async fn setup_cluster(
     voters: BTreeSet<NodeId>,
     non_voters: BTreeSet<NodeId>,
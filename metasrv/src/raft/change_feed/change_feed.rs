@@ -0,0 +1,255 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_infallible::Mutex;
+use common_runtime::tokio::sync::Notify;
+use common_tracing::tracing;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::configs;
+use crate::meta_service::Cmd;
+use crate::meta_service::LogIndex;
+use crate::sled_store::sled_key_space;
+use crate::sled_store::AsKeySpace;
+use crate::sled_store::SeqNum;
+use crate::sled_store::SledSerde;
+use crate::sled_store::SledTree;
+use crate::sled_store::SledValueToKey;
+
+const TREE_CHANGE_FEED: &str = "change_feed";
+
+/// There is only one replication sink, thus only one cursor.
+const CURSOR_KEY: &str = "sink";
+
+/// A committed change to the meta data.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    /// The index of the raft log that carries this change.
+    /// It is increasing but not continuous: logs without a `Cmd` are not changes.
+    pub id: LogIndex,
+
+    /// Unix time in milli seconds when this change is applied.
+    pub applied_at: u64,
+
+    pub cmd: Cmd,
+}
+
+impl ChangeEvent {
+    pub fn new(id: LogIndex, cmd: Cmd) -> ChangeEvent {
+        ChangeEvent {
+            id,
+            applied_at: now_millis(),
+            cmd,
+        }
+    }
+}
+
+impl SledSerde for ChangeEvent {}
+
+impl SledValueToKey<LogIndex> for ChangeEvent {
+    fn to_key(&self) -> LogIndex {
+        self.id
+    }
+}
+
+/// How far the replication sink falls behind the applied changes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplicationLag {
+    /// The number of changes not shipped yet.
+    pub events: u64,
+
+    /// The age of the oldest change not shipped yet.
+    pub duration: Duration,
+}
+
+/// The changes recently appended, so that a consumer that keeps up does not read them back from disk.
+struct ChangeTail {
+    events: VecDeque<ChangeEvent>,
+
+    /// Some changes are not in `events`, the consumer has to read them from the persisted feed.
+    overflowed: bool,
+}
+
+/// ChangeFeed persists the committed changes for the replication sink, until they are shipped.
+/// It is a local store of a node and is not included in a snapshot.
+///
+/// Appending never waits for the consumer: the in-memory tail is bounded,
+/// when it is full, a change is only persisted and the consumer catches up from the persisted feed.
+pub struct ChangeFeed {
+    pub(crate) inner: SledTree,
+
+    tail: Mutex<ChangeTail>,
+    tail_capacity: usize,
+
+    /// The number of persisted changes, i.e., the changes not shipped yet.
+    pending: AtomicU64,
+
+    notify: Notify,
+}
+
+impl ChangeFeed {
+    /// Open ChangeFeed, the shipped changes that are not trimmed before a crash are trimmed.
+    #[tracing::instrument(level = "info", skip(db))]
+    pub async fn open(
+        db: &sled::Db,
+        config: &configs::MetaConfig,
+    ) -> common_exception::Result<ChangeFeed> {
+        let tree_name = config.tree_name(TREE_CHANGE_FEED);
+        let inner = SledTree::open(db, &tree_name, config.is_sync())?;
+
+        let feed = ChangeFeed {
+            inner,
+            tail: Mutex::new(ChangeTail {
+                events: VecDeque::new(),
+                // The changes persisted before opening are not in the tail.
+                overflowed: true,
+            }),
+            tail_capacity: config.replication_buffer_size as usize,
+            pending: AtomicU64::new(0),
+            notify: Notify::new(),
+        };
+
+        let cursor = feed.read_cursor()?;
+        feed.changes().range_remove(..=cursor, true).await?;
+
+        let pending = feed.changes().range_keys(..)?.len();
+        feed.pending.store(pending as u64, Ordering::Relaxed);
+
+        Ok(feed)
+    }
+
+    /// Persist a change and push it to the in-memory tail, then wake up the consumer.
+    /// A change already shipped is ignored, e.g., when the logs after a restored snapshot are applied again.
+    pub async fn append(&self, event: &ChangeEvent) -> common_exception::Result<()> {
+        if event.id <= self.read_cursor()? {
+            return Ok(());
+        }
+
+        let prev = self.changes().insert_value(event).await?;
+        if prev.is_none() {
+            self.pending.fetch_add(1, Ordering::Relaxed);
+        }
+
+        {
+            let mut tail = self.tail.lock();
+            if !tail.overflowed {
+                if tail.events.len() < self.tail_capacity {
+                    tail.events.push_back(event.clone());
+                } else {
+                    tracing::info!("change feed tail is full, the sink falls behind");
+                    tail.events.clear();
+                    tail.overflowed = true;
+                }
+            }
+        }
+
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Returns at most `limit` changes after `cursor`, in order.
+    /// They are taken from the in-memory tail if no change is missing there, otherwise from the persisted feed.
+    pub fn next_changes(
+        &self,
+        cursor: LogIndex,
+        limit: usize,
+    ) -> common_exception::Result<Vec<ChangeEvent>> {
+        let mut tail = self.tail.lock();
+
+        while tail.events.front().map(|e| e.id <= cursor).unwrap_or(false) {
+            tail.events.pop_front();
+        }
+
+        if !tail.overflowed {
+            return Ok(tail.events.iter().take(limit).cloned().collect());
+        }
+
+        let mut changes = Vec::with_capacity(limit);
+        for res in self.changes().range(cursor + 1..)?.take(limit) {
+            let (_id, event) = res?;
+            changes.push(event);
+        }
+
+        // All the persisted changes are read. The tail is locked, thus the changes appended
+        // from now on are pushed to the tail and there is no gap between them and the persisted ones.
+        if changes.len() < limit && changes.len() <= self.tail_capacity {
+            tail.events = changes.iter().cloned().collect();
+            tail.overflowed = false;
+        }
+
+        Ok(changes)
+    }
+
+    /// Wait until a change is appended or the timeout expires.
+    pub async fn wait_changes(&self, timeout: Duration) {
+        let _ = common_runtime::tokio::time::timeout(timeout, self.notify.notified()).await;
+    }
+
+    /// The id of the last shipped change.
+    pub fn read_cursor(&self) -> common_exception::Result<LogIndex> {
+        let cursor = self.cursors().get(&CURSOR_KEY.to_string())?;
+        Ok(cursor.map(u64::from).unwrap_or_default())
+    }
+
+    /// Persist the id of the last shipped change and trim the shipped changes.
+    pub async fn commit_cursor(&self, cursor: LogIndex) -> common_exception::Result<()> {
+        self.cursors()
+            .insert(&CURSOR_KEY.to_string(), &SeqNum(cursor))
+            .await?;
+
+        let shipped = self.changes().range_keys(..=cursor)?.len();
+        self.changes().range_remove(..=cursor, true).await?;
+        self.pending.fetch_sub(shipped as u64, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn lag(&self) -> common_exception::Result<ReplicationLag> {
+        let events = self.pending.load(Ordering::Relaxed);
+
+        let oldest = self.changes().range(..)?.next().transpose()?;
+        let duration = match oldest {
+            None => Duration::default(),
+            Some((_id, event)) => {
+                Duration::from_millis(now_millis().saturating_sub(event.applied_at))
+            }
+        };
+
+        Ok(ReplicationLag { events, duration })
+    }
+
+    /// Returns a borrowed key space in sled::Tree for changes
+    fn changes(&self) -> AsKeySpace<sled_key_space::Changes> {
+        self.inner.key_space()
+    }
+
+    /// Returns a borrowed key space in sled::Tree for replication cursors
+    fn cursors(&self) -> AsKeySpace<sled_key_space::ReplicationCursors> {
+        self.inner.key_space()
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,109 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_runtime::tokio;
+use common_tracing::tracing;
+
+use crate::meta_service::Cmd;
+use crate::raft::change_feed::ChangeEvent;
+use crate::raft::change_feed::ChangeFeed;
+use crate::tests::service::new_sled_test_context;
+
+fn change(id: u64) -> ChangeEvent {
+    ChangeEvent::new(id, Cmd::IncrSeq {
+        key: format!("seq-{}", id),
+    })
+}
+
+fn ids(changes: &[ChangeEvent]) -> Vec<u64> {
+    changes.iter().map(|c| c.id).collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_change_feed_next_changes() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+    let mut tc = new_sled_test_context();
+    tc.config.meta_config.replication_buffer_size = 2;
+    let feed = ChangeFeed::open(&tc.db, &tc.config.meta_config).await?;
+
+    assert_eq!(0, feed.read_cursor()?);
+    assert!(feed.next_changes(0, 10)?.is_empty());
+
+    tracing::info!("--- read from the tail");
+    {
+        for id in [2, 3] {
+            feed.append(&change(id)).await?;
+        }
+
+        assert_eq!(vec![2], ids(&feed.next_changes(0, 1)?));
+        assert_eq!(vec![2, 3], ids(&feed.next_changes(0, 10)?));
+        assert_eq!(vec![3], ids(&feed.next_changes(2, 10)?));
+
+        feed.commit_cursor(3).await?;
+        assert_eq!(3, feed.read_cursor()?);
+        assert_eq!(0, feed.lag()?.events);
+    }
+
+    tracing::info!("--- the tail overflows, catch up from the persisted feed");
+    {
+        for id in [5, 6, 8] {
+            feed.append(&change(id)).await?;
+        }
+        assert_eq!(3, feed.lag()?.events);
+
+        assert_eq!(vec![5, 6], ids(&feed.next_changes(3, 2)?));
+        assert_eq!(vec![6, 8], ids(&feed.next_changes(5, 2)?));
+        assert_eq!(vec![8], ids(&feed.next_changes(6, 2)?));
+
+        // Caught up, changes are read from the tail again.
+        feed.append(&change(9)).await?;
+        assert_eq!(vec![8, 9], ids(&feed.next_changes(6, 10)?));
+
+        feed.commit_cursor(8).await?;
+        assert_eq!(1, feed.lag()?.events);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_change_feed_reopen() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+    let tc = new_sled_test_context();
+
+    {
+        let feed = ChangeFeed::open(&tc.db, &tc.config.meta_config).await?;
+        for id in 1..=4 {
+            feed.append(&change(id)).await?;
+        }
+        feed.commit_cursor(2).await?;
+    }
+
+    let feed = ChangeFeed::open(&tc.db, &tc.config.meta_config).await?;
+    assert_eq!(2, feed.read_cursor()?);
+    assert_eq!(2, feed.lag()?.events);
+
+    // The unshipped changes are resumed from the persisted feed.
+    assert_eq!(vec![3, 4], ids(&feed.next_changes(2, 10)?));
+
+    // The shipped ones are not fed again, e.g., when the logs after a restored snapshot are applied again.
+    feed.append(&change(2)).await?;
+    feed.append(&change(3)).await?;
+    assert_eq!(2, feed.lag()?.events);
+    assert_eq!(vec![3, 4], ids(&feed.next_changes(2, 10)?));
+
+    Ok(())
+}
@@ -28,6 +28,9 @@ use crate::sled_store::SledValueToKey;
 
 const TREE_RAFT_LOG: &str = "raft_log";
 
+/// The max number of logs removed in one sled batch by `purge_upto`.
+const PURGE_BATCH_SIZE: usize = 1024;
+
 /// RaftLog stores the logs of a raft node.
 /// It is part of MetaStore.
 pub struct RaftLog {
@@ -87,6 +90,17 @@ impl RaftLog {
         self.logs().range_remove(range, true).await
     }
 
+    /// Delete the logs with index <= `index`, in batches of `PURGE_BATCH_SIZE` logs.
+    /// It does not check whether these logs are applied, it is the caller's responsibility.
+    ///
+    /// The purge is not atomic, a crash may leave some of the oldest logs, which are purged by the
+    /// next purge. Returns the number of purged logs.
+    pub async fn purge_upto(&self, index: LogIndex) -> common_exception::Result<u64> {
+        self.logs()
+            .range_remove_in_batches(..=index, PURGE_BATCH_SIZE, true)
+            .await
+    }
+
     /// Returns an iterator of logs
     pub fn range<R>(
         &self,
@@ -21,6 +21,7 @@ use common_runtime::tokio;
 use crate::meta_service::Cmd;
 use crate::meta_service::LogEntry;
 use crate::raft::log::RaftLog;
+use crate::sled_store::sled_key_space;
 use crate::tests::service::new_sled_test_context;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -279,3 +280,42 @@ async fn test_raft_log_range_remove() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_raft_log_purge_upto() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let rl = RaftLog::open(db, &tc.config.meta_config).await?;
+
+    let logs = (1..=10_000)
+        .map(|index| Entry {
+            log_id: LogId { term: 1, index },
+            payload: EntryPayload::Blank,
+        })
+        .collect::<Vec<_>>();
+    rl.append(&logs).await?;
+
+    let write_ops = rl.inner.write_ops();
+    assert_eq!(9_000, rl.purge_upto(9_000).await?);
+    // 9000 logs are removed in batches of 1024 logs.
+    assert_eq!(write_ops + 9, rl.inner.write_ops());
+
+    assert_eq!(logs[9_000..], rl.range_values(..)?);
+    assert_eq!(Vec::<u64>::new(), rl.range_keys(..=9_000)?);
+    assert_eq!(None, rl.get(&9_000)?);
+    assert_eq!(Some(logs[9_000].clone()), rl.get(&9_001)?);
+    assert_eq!(Some((10_000, logs[9_999].clone())), rl.last()?);
+    let stats = rl.inner.key_space::<sled_key_space::Logs>().stats();
+    assert_eq!(1_000, stats.entries);
+
+    // Purging again is a no-op, the last log is still served.
+    assert_eq!(0, rl.purge_upto(9_000).await?);
+    assert_eq!(Some((10_000, logs[9_999].clone())), rl.last()?);
+
+    // Purging beyond the last log removes all of them.
+    assert_eq!(1_000, rl.purge_upto(20_000).await?);
+    assert_eq!(None, rl.last()?);
+    Ok(())
+}
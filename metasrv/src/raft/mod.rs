@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod change_feed;
 pub mod log;
+pub mod snapshot_store;
 pub mod state;
 pub mod state_machine;
@@ -0,0 +1,141 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryInto;
+
+use async_raft::LogId;
+use common_exception::ErrorCode;
+use common_tracing::tracing;
+use metrics::gauge;
+use sled::IVec;
+
+use crate::configs;
+use crate::raft::state_machine::Snapshot;
+use crate::sled_store::sled_key_space;
+use crate::sled_store::AsKeySpace;
+use crate::sled_store::SledSerde;
+use crate::sled_store::SledTree;
+
+pub static METRIC_SNAPSHOT_LAST_INDEX: &str = "snapshot.last_index";
+pub static METRIC_SNAPSHOT_SIZE_BYTES: &str = "snapshot.size_bytes";
+pub static METRIC_SNAPSHOT_TIMESTAMP: &str = "snapshot.timestamp";
+
+const TREE_SNAPSHOT: &str = "snapshot";
+
+/// Only the last snapshot is kept.
+const LAST_SNAPSHOT_KEY: &str = "last";
+
+/// The meta is stored as json, the data is appended as is:
+/// `<meta len: u32 BE><meta json><data>`.
+/// Encoding the data as a json array of numbers would bloat it several times.
+impl SledSerde for Snapshot {
+    fn ser(&self) -> Result<IVec, ErrorCode> {
+        let meta = serde_json::to_vec(&(&self.meta, self.created_at))?;
+
+        let mut buf = Vec::with_capacity(4 + meta.len() + self.data.len());
+        buf.extend_from_slice(&(meta.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&meta);
+        buf.extend_from_slice(&self.data);
+        Ok(buf.into())
+    }
+
+    fn de<T: AsRef<[u8]>>(v: T) -> Result<Self, ErrorCode>
+    where Self: Sized {
+        let b = v.as_ref();
+        let damaged = || ErrorCode::MetaStoreDamaged("invalid snapshot record");
+
+        let len = b.get(0..4).ok_or_else(damaged)?;
+        let len = u32::from_be_bytes(len.try_into().map_err(|_| damaged())?) as usize;
+        let meta = b.get(4..4 + len).ok_or_else(damaged)?;
+        let (meta, created_at) = serde_json::from_slice(meta)?;
+
+        Ok(Snapshot {
+            meta,
+            data: b[4 + len..].to_vec(),
+            created_at,
+        })
+    }
+}
+
+/// The last snapshot of a node, for the status call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotStatus {
+    pub last_log_id: LogId,
+
+    /// Unix time in milli seconds when the snapshot is taken or installed.
+    pub created_at: u64,
+
+    /// The size in bytes of the snapshot data.
+    pub size: u64,
+}
+
+impl From<&Snapshot> for SnapshotStatus {
+    fn from(snapshot: &Snapshot) -> Self {
+        SnapshotStatus {
+            last_log_id: snapshot.meta.last_log_id,
+            created_at: snapshot.created_at,
+            size: snapshot.data.len() as u64,
+        }
+    }
+}
+
+/// SnapshotStore persists the last snapshot of the state machine,
+/// so that a restarted node restores its state from it and serves it to a lagging follower,
+/// after the logs included in it are purged.
+/// It is a local store of a node, the same as the raft log.
+pub struct SnapshotStore {
+    pub(crate) inner: SledTree,
+}
+
+impl SnapshotStore {
+    #[tracing::instrument(level = "info", skip(db))]
+    pub async fn open(
+        db: &sled::Db,
+        config: &configs::MetaConfig,
+    ) -> common_exception::Result<SnapshotStore> {
+        let tree_name = config.tree_name(TREE_SNAPSHOT);
+        let inner = SledTree::open(db, &tree_name, config.is_sync())?;
+        let store = SnapshotStore { inner };
+
+        if let Some(snapshot) = store.load()? {
+            Self::report(&snapshot);
+        }
+        Ok(store)
+    }
+
+    /// Replaces the last snapshot. It is fsync-ed before the logs included in it are purged.
+    pub async fn save(&self, snapshot: &Snapshot) -> common_exception::Result<()> {
+        self.snapshots()
+            .insert(&LAST_SNAPSHOT_KEY.to_string(), snapshot)
+            .await?;
+        Self::report(snapshot);
+        Ok(())
+    }
+
+    pub fn load(&self) -> common_exception::Result<Option<Snapshot>> {
+        self.snapshots().get(&LAST_SNAPSHOT_KEY.to_string())
+    }
+
+    fn report(snapshot: &Snapshot) {
+        let status = SnapshotStatus::from(snapshot);
+        gauge!(METRIC_SNAPSHOT_LAST_INDEX, status.last_log_id.index as f64);
+        gauge!(METRIC_SNAPSHOT_SIZE_BYTES, status.size as f64);
+        gauge!(METRIC_SNAPSHOT_TIMESTAMP, (status.created_at / 1000) as f64);
+    }
+
+    /// Returns a borrowed key space in sled::Tree for snapshots
+    fn snapshots(&self) -> AsKeySpace<sled_key_space::Snapshots> {
+        self.inner.key_space()
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_raft::raft::MembershipConfig;
+use async_raft::LogId;
+use async_raft::SnapshotMeta;
+use common_runtime::tokio;
+use maplit::btreeset;
+
+use crate::raft::snapshot_store::SnapshotStatus;
+use crate::raft::snapshot_store::SnapshotStore;
+use crate::raft::state_machine::Snapshot;
+use crate::sled_store::sled_key_space::Snapshots;
+use crate::tests::service::new_sled_test_context;
+
+fn new_snapshot(index: u64, data: Vec<u8>) -> Snapshot {
+    Snapshot {
+        meta: SnapshotMeta {
+            last_log_id: LogId { term: 1, index },
+            snapshot_id: format!("1-{}-{}", index, 1000 + index),
+            membership: MembershipConfig {
+                members: btreeset![1, 2],
+                members_after_consensus: None,
+            },
+        },
+        data,
+        created_at: 1000 + index,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_snapshot_store_save_load() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let ss = SnapshotStore::open(db, &tc.config.meta_config).await?;
+
+    assert!(ss.load()?.is_none());
+
+    // The data is not necessarily valid utf8 or json.
+    let s5 = new_snapshot(5, vec![0, 1, 2, 255, b'{']);
+    ss.save(&s5).await?;
+
+    let got = ss.load()?.unwrap();
+    assert_eq!(s5.meta.snapshot_id, got.meta.snapshot_id);
+    assert_eq!(SnapshotStatus::from(&s5), SnapshotStatus::from(&got));
+    assert_eq!(s5.data, got.data);
+
+    // Only the last one is kept.
+    let s9 = new_snapshot(9, b"foo".to_vec());
+    ss.save(&s9).await?;
+
+    let got = ss.load()?.unwrap();
+    assert_eq!(
+        SnapshotStatus {
+            last_log_id: LogId { term: 1, index: 9 },
+            created_at: 1009,
+            size: 3,
+        },
+        SnapshotStatus::from(&got)
+    );
+    assert_eq!(s9.data, got.data);
+    assert_eq!(1, ss.inner.range_keys::<Snapshots, _>(..)?.len());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_snapshot_store_reopen() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+
+    let s3 = new_snapshot(3, vec![]);
+    {
+        let ss = SnapshotStore::open(db, &tc.config.meta_config).await?;
+        ss.save(&s3).await?;
+    }
+
+    let ss = SnapshotStore::open(db, &tc.config.meta_config).await?;
+    let got = ss.load()?.unwrap();
+    assert_eq!(s3.meta.snapshot_id, got.meta.snapshot_id);
+    assert_eq!(SnapshotStatus::from(&s3), SnapshotStatus::from(&got));
+    assert_eq!(s3.data, got.data);
+
+    Ok(())
+}
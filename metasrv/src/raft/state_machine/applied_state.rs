@@ -14,6 +14,8 @@
 
 use async_raft::AppDataResponse;
 use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::DroppedTable;
 use common_metatypes::KVValue;
 use common_metatypes::SeqValue;
 use common_metatypes::Table;
@@ -21,6 +23,7 @@ use common_store_api_sdk::storage_api_impl::DataPartInfo;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::meta_service::Cmd;
 use crate::meta_service::RaftMes;
 use crate::meta_service::RetryableError;
 use crate::raft::state_machine::Node;
@@ -55,6 +58,19 @@ pub enum AppliedState {
         result: Option<Table>,
     },
 
+    /// The tables of a `Cmd::CreateTables`, in the order of the command.
+    /// A created table has no prev, an existing one is both prev and result,
+    /// a table of a missing database has neither.
+    Tables {
+        prev: Vec<Option<Table>>,
+        result: Vec<Option<Table>>,
+    },
+
+    DroppedTables {
+        prev: Option<Vec<DroppedTable>>,
+        result: Option<Vec<DroppedTable>>,
+    },
+
     DataParts {
         prev: Option<Vec<DataPartInfo>>,
         result: Option<Vec<DataPartInfo>>,
@@ -70,11 +86,75 @@ pub enum AppliedState {
         result: Option<usize>,
     },
 
+    DatabaseUsage {
+        prev: Option<DatabaseUsage>,
+        result: Option<DatabaseUsage>,
+    },
+
+    /// The number of the unexpired generic-kv records, e.g., under a deleted prefix.
+    KVCount {
+        prev: Option<usize>,
+        result: Option<usize>,
+    },
+
+    /// A json patch that can not be applied to the current value of a generic-kv record,
+    /// e.g., the value is not a json document or a pointer refers to a missing member.
+    /// The record is left as is.
+    KVPatchRejected {
+        prev: Option<SeqValue<KVValue>>,
+        reason: String,
+    },
+
+    /// A merge that can not be applied to the current value of a generic-kv record,
+    /// e.g., `add_u64` to a value that is not a number. The record is left as is.
+    KVMergeRejected {
+        prev: Option<SeqValue<KVValue>>,
+        reason: String,
+    },
+
     None,
 }
 
 impl AppDataResponse for AppliedState {}
 
+impl AppliedState {
+    /// Returns true if applying `cmd` with this result changed the state machine,
+    /// false if the command is rejected or it finds nothing to change.
+    pub fn is_change_of(&self, cmd: &Cmd) -> bool {
+        match self {
+            // A failed create returns the existing one as prev and no result, as a drop does.
+            AppliedState::DataBase { prev, result } => match cmd {
+                Cmd::DropDatabase { .. } => prev.is_some(),
+                _ => result.is_some() && prev != result,
+            },
+            AppliedState::Table { prev, result } => match cmd {
+                Cmd::DropTable { .. } => prev.is_some(),
+                // A renamed table is both the prev and the result.
+                Cmd::RenameTable { .. } => result.is_some(),
+                _ => result.is_some() && prev != result,
+            },
+            AppliedState::String { prev, result } => result.is_some() && prev != result,
+            AppliedState::Node { prev, result } => result.is_some() && prev != result,
+            AppliedState::Seq { .. } => true,
+            AppliedState::Tables { prev, result } => prev
+                .iter()
+                .zip(result.iter())
+                .any(|(p, r)| p.is_none() && r.is_some()),
+            AppliedState::DroppedTables { prev, .. } => {
+                prev.as_ref().map(|p| !p.is_empty()).unwrap_or(false)
+            }
+            AppliedState::DataParts { prev, result } => prev != result,
+            AppliedState::KV { prev, result } => prev != result,
+            AppliedState::DataPartsCount { prev, result } => prev != result,
+            AppliedState::DatabaseUsage { prev, result } => prev != result,
+            AppliedState::KVCount { prev, result } => prev != result,
+            AppliedState::KVPatchRejected { .. } => false,
+            AppliedState::KVMergeRejected { .. } => false,
+            AppliedState::None => false,
+        }
+    }
+}
+
 // === raw applied result to AppliedState
 
 impl From<(Option<String>, Option<String>)> for AppliedState {
@@ -137,6 +217,24 @@ impl From<(Option<Vec<DataPartInfo>>, Option<Vec<DataPartInfo>>)> for AppliedSta
     }
 }
 
+impl From<(Option<Vec<DroppedTable>>, Option<Vec<DroppedTable>>)> for AppliedState {
+    fn from(v: (Option<Vec<DroppedTable>>, Option<Vec<DroppedTable>>)) -> Self {
+        AppliedState::DroppedTables {
+            prev: v.0,
+            result: v.1,
+        }
+    }
+}
+
+impl From<(Option<DatabaseUsage>, Option<DatabaseUsage>)> for AppliedState {
+    fn from(v: (Option<DatabaseUsage>, Option<DatabaseUsage>)) -> Self {
+        AppliedState::DatabaseUsage {
+            prev: v.0,
+            result: v.1,
+        }
+    }
+}
+
 impl From<(Option<SeqValue<KVValue>>, Option<SeqValue<KVValue>>)> for AppliedState {
     fn from(v: (Option<SeqValue<KVValue>>, Option<SeqValue<KVValue>>)) -> Self {
         AppliedState::KV {
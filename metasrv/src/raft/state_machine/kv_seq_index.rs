@@ -0,0 +1,117 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use common_infallible::Mutex;
+
+/// The keys under a prefix ordered by the seq of their values.
+type SeqIndex = BTreeMap<u64, String>;
+
+/// KVSeqIndex indexes the keys under the registered prefixes by the seq of their values,
+/// so that the keys changed after a seq are listed without scanning and sorting the prefix.
+///
+/// The index of a prefix is built from a scan of the kvs when it is used for the first time,
+/// and is kept up to date by the kv writes applied since then. It is local to the state machine
+/// and not in a snapshot: a state machine installed from a snapshot builds it again.
+///
+/// An expired kv record stays in the kvs until it is overridden or deleted, its entry is removed
+/// from the index when a listing finds it expired.
+#[derive(Debug, Default)]
+pub struct KVSeqIndex {
+    /// prefix -> its index, None until it is used for the first time.
+    prefixes: Mutex<BTreeMap<String, Option<SeqIndex>>>,
+}
+
+impl KVSeqIndex {
+    pub fn create<'a>(prefixes: impl IntoIterator<Item = &'a str>) -> KVSeqIndex {
+        let index = KVSeqIndex::default();
+        for prefix in prefixes {
+            index.register(prefix);
+        }
+        index
+    }
+
+    pub fn register(&self, prefix: &str) {
+        self.prefixes
+            .lock()
+            .entry(prefix.to_string())
+            .or_insert(None);
+    }
+
+    pub fn is_registered(&self, prefix: &str) -> bool {
+        self.prefixes.lock().contains_key(prefix)
+    }
+
+    /// Updates the index of every prefix of `key` for a kv write:
+    /// `prev_seq` is the seq of the value replaced or removed, `seq` is the seq of the new value.
+    pub fn update(&self, key: &str, prev_seq: Option<u64>, seq: Option<u64>) {
+        let mut prefixes = self.prefixes.lock();
+        for (prefix, index) in prefixes.iter_mut() {
+            let index = match index {
+                Some(index) if key.starts_with(prefix.as_str()) => index,
+                _ => continue,
+            };
+            if let Some(prev_seq) = prev_seq {
+                index.remove(&prev_seq);
+            }
+            if let Some(seq) = seq {
+                index.insert(seq, key.to_string());
+            }
+        }
+    }
+
+    /// Returns up to `limit` of the keys under `prefix` with a seq greater than `min_seq`, ordered by seq.
+    /// The index is built from `scan`, which returns the keys and the seq under the prefix,
+    /// if it is not built yet. It returns None if the prefix is not registered.
+    pub fn range<F>(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
+        scan: F,
+    ) -> common_exception::Result<Option<Vec<(u64, String)>>>
+    where
+        F: FnOnce() -> common_exception::Result<Vec<(String, u64)>>,
+    {
+        let mut prefixes = self.prefixes.lock();
+        let index = match prefixes.get_mut(prefix) {
+            None => return Ok(None),
+            Some(index) => index,
+        };
+
+        if index.is_none() {
+            let built = scan()?
+                .into_iter()
+                .map(|(key, seq)| (seq, key))
+                .collect::<SeqIndex>();
+            *index = Some(built);
+        }
+
+        let index = index.as_ref().unwrap();
+        let keys = index
+            .range(min_seq.saturating_add(1)..)
+            .take(limit)
+            .map(|(seq, key)| (*seq, key.clone()))
+            .collect();
+        Ok(Some(keys))
+    }
+
+    /// Removes an entry that is found stale, e.g. its kv record is expired.
+    pub fn remove(&self, prefix: &str, seq: u64) {
+        if let Some(Some(index)) = self.prefixes.lock().get_mut(prefix) {
+            index.remove(&seq);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_metatypes::KVMeta;
+use common_metatypes::MatchSeq;
+use common_metatypes::Operation;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+
+use crate::meta_service::Cmd;
+use crate::raft::state_machine::AppliedState;
+use crate::raft::state_machine::KVSeqIndex;
+use crate::raft::state_machine::StateMachine;
+use crate::tests::service::new_test_context;
+
+/// Applies an UpsertKV and returns the seq of the result, None if there is no result.
+async fn upsert(
+    sm: &mut StateMachine,
+    key: &str,
+    value: Operation<Vec<u8>>,
+    value_meta: Option<KVMeta>,
+) -> anyhow::Result<Option<u64>> {
+    let resp = sm
+        .apply_cmd(&Cmd::UpsertKV {
+            key: key.to_string(),
+            seq: MatchSeq::Any,
+            value,
+            value_meta,
+        })
+        .await?;
+
+    match resp {
+        AppliedState::KV { result, .. } => Ok(result.map(|x| x.0)),
+        _ => panic!("expect AppliedState::KV, got: {:?}", resp),
+    }
+}
+
+/// Lists the keys and the seq returned by `prefix_list_kv_by_seq`.
+fn list_by_seq(
+    sm: &StateMachine,
+    prefix: &str,
+    min_seq: u64,
+    limit: usize,
+) -> anyhow::Result<Vec<(String, u64)>> {
+    let res = sm.prefix_list_kv_by_seq(prefix, min_seq, limit)?;
+    Ok(res.into_iter().map(|(k, (seq, _))| (k, seq)).collect())
+}
+
+#[test]
+fn test_kv_seq_index() -> anyhow::Result<()> {
+    let index = KVSeqIndex::create(vec!["a/"]);
+    assert!(index.is_registered("a/"));
+    assert!(!index.is_registered("b/"));
+
+    // Not built yet: writes are ignored until it is built from a scan.
+    index.update("a/x", None, Some(1));
+
+    let scan = || Ok(vec![("a/y".to_string(), 3), ("a/x".to_string(), 2)]);
+    let got = index.range("a/", 0, 10, scan)?;
+    assert_eq!(
+        Some(vec![(2, "a/x".to_string()), (3, "a/y".to_string())]),
+        got
+    );
+
+    index.update("a/x", Some(2), Some(4));
+    index.update("b/x", None, Some(5));
+    index.update("a/z", None, Some(6));
+    index.update("a/y", Some(3), None);
+
+    let no_scan = || -> common_exception::Result<Vec<(String, u64)>> {
+        panic!("the index is built");
+    };
+    let got = index.range("a/", 0, 10, no_scan)?;
+    assert_eq!(
+        Some(vec![(4, "a/x".to_string()), (6, "a/z".to_string())]),
+        got
+    );
+
+    let got = index.range("a/", 4, 10, no_scan)?;
+    assert_eq!(Some(vec![(6, "a/z".to_string())]), got);
+
+    let got = index.range("a/", 0, 1, no_scan)?;
+    assert_eq!(Some(vec![(4, "a/x".to_string())]), got);
+
+    index.remove("a/", 4);
+    let got = index.range("a/", 0, 10, no_scan)?;
+    assert_eq!(Some(vec![(6, "a/z".to_string())]), got);
+
+    let got = index.range("b/", 0, 10, no_scan)?;
+    assert_eq!(None, got, "b/ is not registered");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_prefix_list_kv_by_seq() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.kv_seq_index_prefixes = "a/".to_string();
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // Interleave the writes under several prefixes, some before the index is built.
+    let v = || Operation::Update(b"v".to_vec());
+    let a1 = upsert(&mut sm, "a/1", v(), None).await?.unwrap();
+    upsert(&mut sm, "b/1", v(), None).await?;
+    let a2 = upsert(&mut sm, "a/2", v(), None).await?.unwrap();
+
+    let got = list_by_seq(&sm, "a/", 0, 10)?;
+    assert_eq!(vec![("a/1".to_string(), a1), ("a/2".to_string(), a2)], got);
+
+    let a3 = upsert(&mut sm, "a/3", v(), None).await?.unwrap();
+    upsert(&mut sm, "b/2", v(), None).await?;
+    let a1 = upsert(&mut sm, "a/1", v(), None).await?.unwrap();
+    upsert(&mut sm, "ab", v(), None).await?;
+    let a4 = upsert(&mut sm, "a/4", v(), None).await?.unwrap();
+
+    tracing::info!("--- list the changes after a seq");
+
+    let got = list_by_seq(&sm, "a/", a2, 10)?;
+    assert_eq!(
+        vec![
+            ("a/3".to_string(), a3),
+            ("a/1".to_string(), a1),
+            ("a/4".to_string(), a4)
+        ],
+        got
+    );
+
+    let got = list_by_seq(&sm, "a/", a2, 2)?;
+    assert_eq!(vec![("a/3".to_string(), a3), ("a/1".to_string(), a1)], got);
+
+    let got = list_by_seq(&sm, "a/", a4, 10)?;
+    assert!(got.is_empty());
+
+    tracing::info!("--- deleted and expired records are not listed");
+
+    upsert(&mut sm, "a/3", Operation::Delete, None).await?;
+    upsert(
+        &mut sm,
+        "a/1",
+        Operation::AsIs,
+        Some(KVMeta {
+            expire_at: Some(now - 1),
+        }),
+    )
+    .await?;
+
+    let got = list_by_seq(&sm, "a/", 0, 10)?;
+    assert_eq!(vec![("a/2".to_string(), a2), ("a/4".to_string(), a4)], got);
+
+    let got = list_by_seq(&sm, "a/", 0, 1)?;
+    assert_eq!(vec![("a/2".to_string(), a2)], got);
+
+    tracing::info!("--- an unregistered prefix is listed by a scan");
+
+    let got = list_by_seq(&sm, "b/", 0, 10)?;
+    assert_eq!(
+        vec!["b/1".to_string(), "b/2".to_string()],
+        got.into_iter().map(|(k, _)| k).collect::<Vec<_>>()
+    );
+
+    sm.register_kv_seq_index("b/");
+    let indexed = list_by_seq(&sm, "b/", 0, 10)?;
+    let mut scanned = sm.prefix_list_kv("b/")?;
+    scanned.sort_by_key(|(_, (seq, _))| *seq);
+    let scanned = scanned
+        .into_iter()
+        .map(|(k, (seq, _))| (k, seq))
+        .collect::<Vec<_>>();
+    assert_eq!(scanned, indexed);
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use common_exception::ErrorCode;
+use common_metatypes::KVChange;
+use common_metatypes::KVChangeKind;
+use common_metatypes::KVValue;
+use common_metatypes::SeqValue;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::broadcast;
+use common_runtime::tokio::sync::broadcast::error::RecvError;
+use common_runtime::SharedClock;
+
+/// The number of changes a watcher may fall behind before it is closed.
+pub const KV_WATCH_CAPACITY: usize = 1024;
+
+/// KVWatchHub delivers the generic-kv changes applied to the state machine to the watchers.
+///
+/// It is local to a node and not in a snapshot: the hub of a state machine is handed over to the
+/// one installed from a snapshot, so that the watchers keep going. A watcher leaves the hub when
+/// it is dropped.
+#[derive(Debug, Clone)]
+pub struct KVWatchHub {
+    tx: broadcast::Sender<KVChange>,
+}
+
+impl Default for KVWatchHub {
+    fn default() -> Self {
+        KVWatchHub::create(KV_WATCH_CAPACITY)
+    }
+}
+
+impl KVWatchHub {
+    pub fn create(capacity: usize) -> KVWatchHub {
+        let (tx, _) = broadcast::channel(capacity);
+        KVWatchHub { tx }
+    }
+
+    pub fn watcher_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Delivers the change of `key` from `prev` to `result`, the records are not cloned if there is no watcher.
+    /// Removing a record that does not exist is not a change.
+    pub fn notify(
+        &self,
+        key: &str,
+        prev: &Option<SeqValue<KVValue>>,
+        result: &Option<SeqValue<KVValue>>,
+    ) {
+        if self.watcher_count() == 0 || (prev.is_none() && result.is_none()) {
+            return;
+        }
+        let _ = self
+            .tx
+            .send(KVChange::create(key, prev.clone(), result.clone()));
+    }
+
+    /// Starts a watcher of the changes of the keys under `prefix`.
+    ///
+    /// `current` is the records under the prefix when the watch starts, the ones with a seq greater
+    /// than `after_seq` are delivered first, as created records.
+    pub fn watch(
+        &self,
+        prefix: &str,
+        after_seq: Option<u64>,
+        mut current: Vec<(String, SeqValue<KVValue>)>,
+        clock: SharedClock,
+    ) -> KVWatcher {
+        current.sort_by_key(|(_, (seq, _))| *seq);
+
+        let mut watcher = KVWatcher {
+            prefix: prefix.to_string(),
+            rx: self.tx.subscribe(),
+            pending: VecDeque::new(),
+            expiring: BTreeMap::new(),
+            expire_at: HashMap::new(),
+            clock,
+        };
+        for (key, record) in current {
+            let change = KVChange::create(&key, None, Some(record));
+            watcher.track(&change);
+            if after_seq.map(|s| change.seq() > s).unwrap_or(false) {
+                watcher.pending.push_back(change);
+            }
+        }
+        watcher
+    }
+}
+
+/// KVWatcher yields the changes of the keys under a prefix in the order they are applied.
+///
+/// The expiry of a record is not applied to the state machine, the watcher reports it as an
+/// `Expired` change once the expire time of a record it has seen passes.
+pub struct KVWatcher {
+    prefix: String,
+    rx: broadcast::Receiver<KVChange>,
+
+    /// The records found when the watch starts, delivered before the changes.
+    pending: VecDeque<KVChange>,
+
+    /// The records seen that have an expire time, ordered by it: (expire_at, key) -> record.
+    expiring: BTreeMap<(u64, String), SeqValue<KVValue>>,
+
+    /// key -> expire_at of the record in `expiring`.
+    expire_at: HashMap<String, u64>,
+
+    clock: SharedClock,
+}
+
+impl KVWatcher {
+    /// Returns the next change, or None if the hub is gone.
+    ///
+    /// A watcher that falls behind by more than `KV_WATCH_CAPACITY` changes fails with `KVWatchLagged`,
+    /// it should be started again.
+    pub async fn next(&mut self) -> common_exception::Result<Option<KVChange>> {
+        if let Some(change) = self.pending.pop_front() {
+            return Ok(Some(change));
+        }
+
+        loop {
+            if let Some(change) = self.pop_expired() {
+                return Ok(Some(change));
+            }
+
+            let clock = self.clock.clone();
+            let wait = self.next_expiry_wait();
+            let expiry = async move {
+                match wait {
+                    Some(d) => clock.sleep(d).await,
+                    None => futures::future::pending().await,
+                }
+            };
+
+            let res = tokio::select! {
+                res = self.rx.recv() => res,
+                _ = expiry => continue,
+            };
+
+            match res {
+                Ok(change) => {
+                    if change.key.starts_with(&self.prefix) {
+                        self.track(&change);
+                        return Ok(Some(change));
+                    }
+                }
+                Err(RecvError::Closed) => return Ok(None),
+                Err(RecvError::Lagged(n)) => {
+                    return Err(ErrorCode::KVWatchLagged(format!(
+                        "watcher of '{}' missed {} changes",
+                        self.prefix, n
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Keeps the expire time of the record after a change.
+    fn track(&mut self, change: &KVChange) {
+        if let Some(exp) = self.expire_at.remove(&change.key) {
+            self.expiring.remove(&(exp, change.key.clone()));
+        }
+        if let Some(record) = &change.result {
+            if let Some(exp) = record.1.meta.as_ref().and_then(|m| m.expire_at) {
+                self.expire_at.insert(change.key.clone(), exp);
+                self.expiring
+                    .insert((exp, change.key.clone()), record.clone());
+            }
+        }
+    }
+
+    /// Removes the first record that is expired, as the state machine sees it: `expire_at < now`.
+    fn pop_expired(&mut self) -> Option<KVChange> {
+        let now = self.clock.now_secs();
+        let (exp, key) = match self.expiring.keys().next() {
+            Some((exp, _)) if *exp >= now => return None,
+            Some(first) => first.clone(),
+            None => return None,
+        };
+        let record = self.expiring.remove(&(exp, key.clone()))?;
+        self.expire_at.remove(&key);
+        Some(KVChange {
+            kind: KVChangeKind::Expired,
+            key,
+            prev: Some(record),
+            result: None,
+        })
+    }
+
+    fn next_expiry_wait(&self) -> Option<Duration> {
+        let (exp, _) = self.expiring.keys().next()?;
+        let expired_at_ms = (exp + 1) * 1000;
+        Some(Duration::from_millis(
+            expired_at_ms.saturating_sub(self.clock.now_millis()),
+        ))
+    }
+}
@@ -13,24 +13,39 @@
 // limitations under the License.
 
 pub mod applied_state;
+pub mod kv_seq_index;
+pub mod kv_watch;
+pub mod seq_allocator;
 pub mod sm;
 pub mod snapshot;
 pub mod state_machine_meta;
+pub mod table_part;
 
+#[cfg(test)]
+mod kv_seq_index_test;
 pub mod placement;
 #[cfg(test)]
 mod placement_test;
 #[cfg(test)]
+mod seq_allocator_test;
+#[cfg(test)]
 mod state_machine_test;
 
 pub use applied_state::AppliedState;
+pub use kv_seq_index::KVSeqIndex;
+pub use kv_watch::KVWatchHub;
+pub use kv_watch::KVWatcher;
 pub use placement::Placement;
+pub use seq_allocator::SeqAllocator;
 pub use sm::Node;
 pub use sm::Replication;
 pub use sm::SerializableSnapshot;
 pub use sm::Slot;
+pub use sm::SnapshotCatalog;
 pub use sm::SnapshotKeyValue;
 pub use sm::StateMachine;
 pub use snapshot::Snapshot;
 pub use state_machine_meta::StateMachineMetaKey;
 pub use state_machine_meta::StateMachineMetaValue;
+pub use table_part::TablePart;
+pub use table_part::TablePartKey;
@@ -0,0 +1,83 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use common_tracing::tracing;
+
+use crate::sled_store::sled_key_space::Sequences;
+use crate::sled_store::AsKeySpace;
+
+/// SeqAllocator hands out the seq numbers of a state machine, with the fsync of them batched.
+///
+/// Every seq is written to keyspace `Sequences` in the same apply as the write it is allocated
+/// for, thus the seq numbers are a function of the applied log only: the replicas of a cluster
+/// hand out the same seq numbers, before and after a restart or a snapshot install.
+///
+/// Only the fsync is batched: the key is flushed once per `batch_size` allocations. The others
+/// are persisted by the fsync of the write that follows in the same apply, e.g. the kv record,
+/// because sled persists the writes in order. A seq lost in a crash before the fsync belongs to
+/// a log that is not applied on disk either, it is handed out again when the log is re-applied.
+#[derive(Debug)]
+pub struct SeqAllocator {
+    batch_size: u64,
+    unflushed: AtomicU64,
+}
+
+impl SeqAllocator {
+    pub fn create(batch_size: u64) -> SeqAllocator {
+        SeqAllocator {
+            batch_size: batch_size.max(1),
+            unflushed: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the next seq of the key, i.e., the last seq on disk plus one.
+    pub async fn next(
+        &self,
+        sequences: &AsKeySpace<'_, Sequences>,
+        key: &str,
+    ) -> common_exception::Result<u64> {
+        let flush = self.unflushed.fetch_add(1, Ordering::Relaxed) + 1 >= self.batch_size;
+        if flush {
+            self.unflushed.store(0, Ordering::Relaxed);
+        }
+
+        // sled increments the seq atomically, even if others are allocating concurrently.
+        let seq = sequences
+            .update_and_fetch(
+                &key.to_string(),
+                |old| Some(old.unwrap_or_default() + 1),
+                flush,
+            )
+            .await?
+            .unwrap()
+            .0;
+
+        tracing::debug!("allocated seq of {}: {}, flush: {}", key, seq, flush);
+
+        Ok(seq)
+    }
+
+    /// Returns the last seq handed out.
+    pub fn last(
+        &self,
+        sequences: &AsKeySpace<'_, Sequences>,
+        key: &str,
+    ) -> common_exception::Result<Option<u64>> {
+        let res = sequences.get(&key.to_string())?;
+        Ok(res.map(|x| x.0))
+    }
+}
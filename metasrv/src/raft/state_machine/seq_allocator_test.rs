@@ -0,0 +1,128 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_metatypes::MatchSeq;
+use common_metatypes::Operation;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+
+use crate::meta_service::Cmd;
+use crate::raft::state_machine::AppliedState;
+use crate::raft::state_machine::StateMachine;
+use crate::tests::service::new_test_context;
+
+/// Upserts a kv and returns the seq of the result, None if nothing is changed.
+async fn upsert(sm: &mut StateMachine, key: &str, seq: MatchSeq) -> anyhow::Result<Option<u64>> {
+    let resp = sm
+        .apply_cmd(&Cmd::UpsertKV {
+            key: key.to_string(),
+            seq,
+            value: Operation::Update(b"v".to_vec()),
+            value_meta: None,
+        })
+        .await?;
+
+    match resp {
+        AppliedState::KV { prev, result } => match (prev, result) {
+            (Some(prev), Some(result)) if prev.0 == result.0 => Ok(None),
+            (_, result) => Ok(result.map(|x| x.0)),
+        },
+        _ => panic!("expect AppliedState::KV, got: {:?}", resp),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_seq_allocator_flush_ops() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let n = 100;
+
+    // One write for the kv and one for the seq, both flushed.
+    let mut tc = new_test_context();
+    tc.config.meta_config.seq_batch_size = 1;
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let (writes, flushes) = (sm.sm_tree.write_ops(), sm.sm_tree.flush_ops());
+    for i in 0..n {
+        upsert(&mut sm, &format!("k{}", i), MatchSeq::Any).await?;
+    }
+    assert_eq!(2 * n, sm.sm_tree.write_ops() - writes);
+    assert_eq!(2 * n, sm.sm_tree.flush_ops() - flushes);
+
+    // The same writes, the seq is flushed once per 10 allocations.
+    let mut tc = new_test_context();
+    tc.config.meta_config.seq_batch_size = 10;
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let (writes, flushes) = (sm.sm_tree.write_ops(), sm.sm_tree.flush_ops());
+    for i in 0..n {
+        upsert(&mut sm, &format!("k{}", i), MatchSeq::Any).await?;
+    }
+    assert_eq!(2 * n, sm.sm_tree.write_ops() - writes);
+    assert_eq!(n + n / 10, sm.sm_tree.flush_ops() - flushes);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_seq_allocator_restart() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.seq_batch_size = 10;
+
+    let mut seqs = vec![];
+    for _restart in 0..3 {
+        // Reopening the state machine drops what the allocator has in memory, as a crash does.
+        let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+        for i in 0..15 {
+            seqs.extend(upsert(&mut sm, &format!("k{}", i), MatchSeq::Any).await?);
+        }
+    }
+
+    // The seq numbers do not depend on the restarts.
+    let want = (1..=45).collect::<Vec<_>>();
+    assert_eq!(want, seqs);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_seq_allocator_match_seq_across_restart() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.seq_batch_size = 10;
+
+    let seq = {
+        let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+        upsert(&mut sm, "foo", MatchSeq::Any).await?.unwrap()
+    };
+    assert_eq!(1, seq);
+
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    assert_eq!(None, upsert(&mut sm, "foo", MatchSeq::Exact(2)).await?);
+
+    let new_seq = upsert(&mut sm, "foo", MatchSeq::Exact(seq)).await?;
+    assert_eq!(Some(2), new_seq);
+
+    assert_eq!(None, upsert(&mut sm, "foo", MatchSeq::Exact(seq)).await?);
+    assert_eq!(Some(3), upsert(&mut sm, "foo", MatchSeq::Exact(2)).await?);
+
+    Ok(())
+}
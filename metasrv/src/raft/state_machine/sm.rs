@@ -13,12 +13,13 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
-use std::time::SystemTime;
-use std::time::UNIX_EPOCH;
+use std::ops::Bound;
 
 use async_raft::raft::Entry;
 use async_raft::raft::EntryPayload;
@@ -26,7 +27,10 @@ use async_raft::raft::MembershipConfig;
 use async_raft::LogId;
 use common_exception::prelude::ErrorCode;
 use common_exception::ToErrorCode;
+use common_metatypes::apply_json_patch;
 use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::DroppedTable;
 use common_metatypes::KVMeta;
 use common_metatypes::KVValue;
 use common_metatypes::MatchSeqExt;
@@ -35,8 +39,11 @@ use common_metatypes::SeqValue;
 use common_metatypes::Table;
 use common_planners::Part;
 use common_planners::Statistics;
+use common_store_api_sdk::kv_api_impl::PrefixListPage;
 use common_store_api_sdk::storage_api_impl::AppendResult;
 use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::PartBloomFilters;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
 use common_tracing::tracing;
 use serde::Deserialize;
 use serde::Serialize;
@@ -48,14 +55,21 @@ use crate::meta_service::LogEntry;
 use crate::meta_service::NodeId;
 use crate::raft::state_machine::placement::rand_n_from_m;
 use crate::raft::state_machine::AppliedState;
+use crate::raft::state_machine::KVSeqIndex;
+use crate::raft::state_machine::KVWatchHub;
+use crate::raft::state_machine::KVWatcher;
 use crate::raft::state_machine::Placement;
+use crate::raft::state_machine::SeqAllocator;
 use crate::raft::state_machine::StateMachineMetaKey;
 use crate::raft::state_machine::StateMachineMetaKey::Initialized;
 use crate::raft::state_machine::StateMachineMetaKey::LastApplied;
 use crate::raft::state_machine::StateMachineMetaKey::LastMembership;
 use crate::raft::state_machine::StateMachineMetaValue;
+use crate::raft::state_machine::TablePart;
+use crate::raft::state_machine::TablePartKey;
 use crate::sled_store::get_sled_db;
 use crate::sled_store::sled_key_space;
+use crate::sled_store::sled_key_space::SledKeySpace;
 use crate::sled_store::sled_key_space::StateMachineMeta;
 use crate::sled_store::AsKeySpace;
 use crate::sled_store::SledSerde;
@@ -69,12 +83,19 @@ const SEQ_DATABASE_ID: &str = "database_id";
 const SEQ_TABLE_ID: &str = "table_id";
 /// seq number key to database meta version
 const SEQ_DATABASE_META_ID: &str = "database_meta_id";
+/// seq number key to generate the data version of a table
+const SEQ_TABLE_DATA_VERSION: &str = "table_data_version";
+/// seq number key to order the data parts kept in the sled::Tree
+const SEQ_TABLE_PART: &str = "table_part";
 
 /// sled db tree name for nodes
 // const TREE_NODES: &str = "nodes";
 // const TREE_META: &str = "meta";
 const TREE_STATE_MACHINE: &str = "state_machine";
 
+/// The max number of generic-kv records removed in one sled batch by `DeletePrefixKV`.
+pub(crate) const DELETE_PREFIX_KV_CHUNK: usize = 1024;
+
 /// Replication defines the replication strategy.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Replication {
@@ -117,7 +138,9 @@ pub struct StateMachine {
 
     pub replication: Replication,
 
-    /// db name to database mapping
+    /// db name to database mapping.
+    /// The catalog, i.e. this field, `tables`, `table_parts` and `trash`, is served from memory
+    /// and written through to its keyspaces in `sm_tree`, e.g. `Databases`.
     pub databases: BTreeMap<String, Database>,
 
     /// table id to table mapping
@@ -125,6 +148,19 @@ pub struct StateMachine {
 
     /// table parts, table id -> data parts
     pub table_parts: HashMap<u64, Vec<DataPartInfo>>,
+
+    /// dropped tables that can still be undropped, table id -> dropped table.
+    /// The data parts of a dropped table are kept in `table_parts` until it is vacuumed.
+    pub trash: BTreeMap<u64, DroppedTable>,
+
+    /// Hands out the seq numbers kept in keyspace `Sequences`, with their fsync batched.
+    seq_allocator: SeqAllocator,
+
+    /// The keys under the registered prefixes ordered by seq, for `prefix_list_kv_by_seq`.
+    kv_seq_index: KVSeqIndex,
+
+    /// Delivers the generic-kv changes to the watchers.
+    pub kv_watch: KVWatchHub,
 }
 
 /// Initialize state machine for the first time it is brought online.
@@ -169,11 +205,26 @@ impl StateMachineInitializer {
 /// A key-value pair in a snapshot is a vec of two `Vec<u8>`.
 pub type SnapshotKeyValue = Vec<Vec<u8>>;
 
+/// The catalog of a state machine, i.e., the databases, the tables, their data parts and the trash.
+/// It is carried in a snapshot besides the kvs only by the snapshots taken before it is kept in
+/// the sled::Tree.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct SnapshotCatalog {
+    pub databases: BTreeMap<String, Database>,
+    pub tables: BTreeMap<u64, Table>,
+    pub table_parts: BTreeMap<u64, Vec<DataPartInfo>>,
+    pub trash: BTreeMap<u64, DroppedTable>,
+}
+
 /// Snapshot data for serialization and for transport.
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct SerializableSnapshot {
     /// A list of kv pairs.
     pub kvs: Vec<SnapshotKeyValue>,
+
+    /// Empty unless the snapshot is taken before the catalog is kept in the sled::Tree.
+    #[serde(default)]
+    pub catalog: SnapshotCatalog,
 }
 
 impl SerializableSnapshot {
@@ -223,7 +274,7 @@ impl StateMachine {
 
         let sm_tree = SledTree::open(&db, &tree_name, config.is_sync())?;
 
-        let sm = StateMachine {
+        let mut sm = StateMachine {
             config: config.clone(),
             _db: db,
 
@@ -236,6 +287,15 @@ impl StateMachine {
             databases: BTreeMap::new(),
             tables: BTreeMap::new(),
             table_parts: HashMap::new(),
+            trash: BTreeMap::new(),
+            seq_allocator: SeqAllocator::create(config.seq_batch_size),
+            kv_seq_index: KVSeqIndex::create(
+                config
+                    .kv_seq_index_prefixes
+                    .split(',')
+                    .filter(|prefix| !prefix.is_empty()),
+            ),
+            kv_watch: KVWatchHub::default(),
         };
 
         let inited = {
@@ -244,6 +304,7 @@ impl StateMachine {
         };
 
         if inited.is_some() {
+            sm.load_catalog()?;
             Ok(sm)
         } else {
             // Run the default init on a new state machine.
@@ -263,6 +324,9 @@ impl StateMachine {
     /// - the last applied log id
     /// - the last applied membership config
     /// - and a snapshot id
+    ///
+    /// The kvs, the last applied and the membership are read from one copy of the state machine
+    /// taken at a single point in time.
     pub fn snapshot(
         &self,
     ) -> common_exception::Result<(
@@ -271,28 +335,88 @@ impl StateMachine {
         MembershipConfig,
         String,
     )> {
-        let last_applied = self.get_last_applied()?;
-        let mem = self.get_membership()?;
+        let snap = self.sm_tree.export_snapshot()?;
+
+        let last_applied = snap
+            .get::<StateMachineMeta>(&LastApplied)?
+            .map(LogId::from)
+            .unwrap_or_default();
+        let mem = snap
+            .get::<StateMachineMeta>(&LastMembership)?
+            .map(MembershipConfig::from);
 
         // NOTE: An initialize node/cluster always has the first log contains membership config.
         let mem = mem.unwrap_or_default();
 
-        let snapshot_idx = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let snapshot_idx = self.config.clock.now_secs();
 
         let snapshot_id = format!(
             "{}-{}-{}",
             last_applied.term, last_applied.index, snapshot_idx
         );
 
-        Ok((self.sm_tree.tree.iter(), last_applied, mem, snapshot_id))
+        Ok((snap.into_iter().map(Ok), last_applied, mem, snapshot_id))
+    }
+
+    /// The catalog of the state machine.
+    pub fn catalog(&self) -> SnapshotCatalog {
+        SnapshotCatalog {
+            databases: self.databases.clone(),
+            tables: self.tables.clone(),
+            table_parts: self
+                .table_parts
+                .iter()
+                .map(|(tid, parts)| (*tid, parts.clone()))
+                .collect(),
+            trash: self.trash.clone(),
+        }
+    }
+
+    /// Replaces the catalog with the one carried in a snapshot taken before the catalog is kept
+    /// in the sled::Tree.
+    pub async fn restore_catalog(
+        &mut self,
+        catalog: SnapshotCatalog,
+    ) -> common_exception::Result<()> {
+        let prev = self.all_catalog_keys();
+
+        self.databases = catalog.databases;
+        self.tables = catalog.tables;
+        self.table_parts = catalog.table_parts.into_iter().collect();
+        self.trash = catalog.trash;
+
+        let keys = prev.merge(self.all_catalog_keys());
+        for table_id in keys.tables.iter() {
+            self.save_table_parts(*table_id).await?;
+        }
+        self.save_catalog(keys).await
+    }
+
+    /// Loads the catalog kept in the sled::Tree into memory.
+    pub fn load_catalog(&mut self) -> common_exception::Result<()> {
+        self.databases = self
+            .catalog_databases()
+            .range_kvs(..)?
+            .into_iter()
+            .collect();
+        self.tables = self.catalog_tables().range_kvs(..)?.into_iter().collect();
+        let mut table_parts = HashMap::<u64, Vec<TablePart>>::new();
+        for (key, part) in self.catalog_table_parts().range_kvs(..)? {
+            table_parts.entry(key.table_id).or_default().push(part);
+        }
+        self.table_parts = table_parts
+            .into_iter()
+            .map(|(table_id, mut parts)| {
+                parts.sort_by_key(|p| p.seq);
+                (table_id, parts.into_iter().map(|p| p.part).collect())
+            })
+            .collect();
+        self.trash = self.catalog_trash().range_kvs(..)?.into_iter().collect();
+        Ok(())
     }
 
     /// Serialize a snapshot for transport.
-    /// This step does not require a lock, since sled::Tree::iter() creates a consistent view on a tree
-    /// no matter if there are other writes applied to the tree.
+    /// This step does not require a lock, since the view is a copy of the tree taken by `snapshot()`.
     pub fn serialize_snapshot(
         view: impl Iterator<Item = sled::Result<(IVec, IVec)>>,
     ) -> common_exception::Result<Vec<u8>> {
@@ -301,7 +425,10 @@ impl StateMachine {
             let (k, v) = rkv.map_err_to_code(ErrorCode::MetaStoreDamaged, || "taking snapshot")?;
             kvs.push(vec![k.to_vec(), v.to_vec()]);
         }
-        let snap = SerializableSnapshot { kvs };
+        let snap = SerializableSnapshot {
+            kvs,
+            catalog: SnapshotCatalog::default(),
+        };
         let snap = serde_json::to_vec(&snap)?;
         Ok(snap)
     }
@@ -312,17 +439,27 @@ impl StateMachine {
     ///
     /// Note: this can only be called inside apply().
     async fn incr_seq(&self, key: &str) -> common_exception::Result<u64> {
-        let sequences = self.sequences();
-
-        let curr = sequences
-            .update_and_fetch(&key.to_string(), |old| Some(old.unwrap_or_default() + 1))
-            .await?;
-
-        let curr = curr.unwrap();
+        let curr = self.seq_allocator.next(&self.sequences(), key).await?;
 
         tracing::debug!("applied IncrSeq: {}={}", key, curr);
 
-        Ok(curr.0)
+        Ok(curr)
+    }
+
+    /// Returns true if the log is a client request that is already applied,
+    /// `apply()` returns the previous response of it without applying it again.
+    pub fn is_duplicated(&self, entry: &Entry<LogEntry>) -> bool {
+        match entry.payload {
+            EntryPayload::Normal(ref norm) => match norm.data.txid {
+                Some(ref txid) => self
+                    .client_last_resp
+                    .get(&txid.client)
+                    .map(|(serial, _)| *serial == txid.serial)
+                    .unwrap_or(false),
+                None => false,
+            },
+            _ => false,
+        }
     }
 
     /// Apply an log entry to state machine.
@@ -386,6 +523,15 @@ impl StateMachine {
     /// The `cmd` is always committed by raft before applying.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn apply_cmd(&mut self, cmd: &Cmd) -> common_exception::Result<AppliedState> {
+        // The catalog entries the cmd may add, change or remove are saved after it is applied.
+        let prev = self.cmd_catalog_keys(cmd);
+        let resp = self.do_apply_cmd(cmd).await?;
+        let keys = prev.merge(self.cmd_catalog_keys(cmd));
+        self.save_catalog(keys).await?;
+        Ok(resp)
+    }
+
+    async fn do_apply_cmd(&mut self, cmd: &Cmd) -> common_exception::Result<AppliedState> {
         match cmd {
             Cmd::AddFile { ref key, ref value } => {
                 // TODO(xp): put it in a transaction
@@ -410,6 +556,12 @@ impl StateMachine {
                 Ok((prev, Some(value.clone())).into())
             }
 
+            Cmd::RemoveFiles { ref keys } => {
+                self.files().remove_keys(keys, true).await?;
+                tracing::info!("applied RemoveFiles: {:?}", keys);
+                Ok(AppliedState::None)
+            }
+
             Cmd::IncrSeq { ref key } => Ok(self.incr_seq(key).await?.into()),
 
             Cmd::AddNode {
@@ -452,11 +604,17 @@ impl StateMachine {
                 }
             }
 
-            Cmd::DropDatabase { ref name } => {
+            Cmd::DropDatabase { ref name, ts } => {
                 let prev = self.databases.get(name).cloned();
-                if prev.is_some() {
-                    self.remove_db_data_parts(name);
+                if let Some(ref db) = prev {
+                    // Tables of a dropped database go to trash with their data parts.
+                    for (table_name, tbl_id) in db.tables.iter() {
+                        if let Some(table) = self.tables.remove(tbl_id) {
+                            self.move_to_trash(name, table_name, table, ts);
+                        }
+                    }
                     self.databases.remove(name);
+                    self.database_usages().remove(name, true).await?;
                     self.incr_seq(SEQ_DATABASE_META_ID).await?;
                     tracing::debug!("applied DropDatabase: {}", name);
                     Ok((prev, None).into())
@@ -470,36 +628,98 @@ impl StateMachine {
                 ref table_name,
                 if_not_exists: _,
                 ref table,
+                ref seq,
+                ts,
             } => {
                 let db = self.databases.get(db_name);
                 let mut db = db.unwrap().to_owned();
+                let prev = db
+                    .tables
+                    .get(table_name)
+                    .and_then(|tbl_id| self.tables.get(tbl_id))
+                    .cloned();
+
+                // A replace matches the id of the existing table, 0 if there is none,
+                // a plain create never touches an existing one.
+                match seq {
+                    Some(seq) => {
+                        let table_id = prev.as_ref().map(|t| t.table_id).unwrap_or_default();
+                        if seq.match_seq(table_id).is_err() {
+                            return Ok((prev, None).into());
+                        }
+                    }
+                    None => {
+                        if let Some(prev) = prev {
+                            return Ok((Some(prev.clone()), Some(prev)).into());
+                        }
+                    }
+                }
 
-                if db.tables.contains_key(table_name) {
-                    let table_id = db.tables.get(table_name).unwrap();
-                    let prev = self.tables.get(table_id);
-                    Ok((prev.cloned(), prev.cloned()).into())
-                } else {
-                    let table = Table {
-                        table_id: self.incr_seq(SEQ_TABLE_ID).await?,
-                        schema: table.schema.clone(),
-                        table_engine: table.table_engine.clone(),
-                        table_options: table.table_options.clone(),
-                        parts: table.parts.clone(),
+                // The replaced table goes to trash with its data parts, as a dropped one.
+                if let Some(ref prev) = prev {
+                    self.tables.remove(&prev.table_id);
+                    self.move_to_trash(db_name, table_name, prev.clone(), ts);
+                    let removed = self.table_bytes(&prev.table_id);
+                    self.update_database_usage(db_name, 0, removed).await?;
+                }
+
+                let table = self.insert_table(&mut db, table_name, table).await?;
+                self.incr_seq(SEQ_DATABASE_META_ID).await?;
+                self.databases.insert(db_name.clone(), db);
+                tracing::debug!("applied CreateTable: {}={:?}", table_name, table);
+
+                Ok((prev, Some(table)).into())
+            }
+
+            Cmd::CreateTables { ref tables } => {
+                let mut prevs = Vec::with_capacity(tables.len());
+                let mut results = Vec::with_capacity(tables.len());
+                let mut created = 0;
+
+                for t in tables {
+                    let mut db = match self.databases.get(&t.db_name) {
+                        Some(db) => db.clone(),
+                        None => {
+                            prevs.push(None);
+                            results.push(None);
+                            continue;
+                        }
                     };
-                    self.incr_seq(SEQ_DATABASE_META_ID).await?;
-                    db.tables.insert(table_name.clone(), table.table_id);
-                    self.databases.insert(db_name.clone(), db);
-                    self.tables.insert(table.table_id, table.clone());
-                    tracing::debug!("applied CreateTable: {}={:?}", table_name, table);
 
-                    Ok((None, Some(table)).into())
+                    let prev = db
+                        .tables
+                        .get(&t.table_name)
+                        .and_then(|tbl_id| self.tables.get(tbl_id))
+                        .cloned();
+                    if let Some(prev) = prev {
+                        prevs.push(Some(prev.clone()));
+                        results.push(Some(prev));
+                        continue;
+                    }
+
+                    let table = self.insert_table(&mut db, &t.table_name, &t.table).await?;
+                    self.databases.insert(t.db_name.clone(), db);
+                    prevs.push(None);
+                    results.push(Some(table));
+                    created += 1;
                 }
+
+                if created > 0 {
+                    self.incr_seq(SEQ_DATABASE_META_ID).await?;
+                }
+                tracing::debug!("applied CreateTables: {} of {}", created, tables.len());
+
+                Ok(AppliedState::Tables {
+                    prev: prevs,
+                    result: results,
+                })
             }
 
             Cmd::DropTable {
                 ref db_name,
                 ref table_name,
                 if_exists: _,
+                ts,
             } => {
                 let db = self.databases.get_mut(db_name).unwrap();
                 let tbl_id = db.tables.get(table_name);
@@ -508,7 +728,13 @@ impl StateMachine {
                     db.tables.remove(table_name);
                     let prev = self.tables.remove(&tbl_id);
 
-                    self.remove_table_data_parts(db_name, table_name);
+                    // The data parts are kept until the table is vacuumed from trash,
+                    // while they no longer count against the quota of the database.
+                    if let Some(ref table) = prev {
+                        self.move_to_trash(db_name, table_name, table.clone(), ts);
+                        let removed = self.table_bytes(&tbl_id);
+                        self.update_database_usage(db_name, 0, removed).await?;
+                    }
 
                     self.incr_seq(SEQ_DATABASE_META_ID).await?;
 
@@ -518,6 +744,139 @@ impl StateMachine {
                 }
             }
 
+            Cmd::UndropTable {
+                ref db_name,
+                ref table_name,
+                ts,
+            } => {
+                let db = match self.databases.get_mut(db_name) {
+                    None => return Ok((None::<Table>, None::<Table>).into()),
+                    Some(db) => db,
+                };
+
+                // An undrop never overrides a table created after the drop.
+                if let Some(tbl_id) = db.tables.get(table_name) {
+                    let prev = self.tables.get(tbl_id).cloned();
+                    return Ok((prev, None).into());
+                }
+
+                let dropped = self
+                    .trash
+                    .values()
+                    .filter(|t| {
+                        t.db_name == *db_name && t.table_name == *table_name && t.expire_at > ts
+                    })
+                    .max_by_key(|t| (t.dropped_on, t.table.table_id));
+
+                match dropped.map(|t| t.table.table_id) {
+                    None => Ok((None::<Table>, None::<Table>).into()),
+                    Some(tbl_id) => {
+                        let dropped = self.trash.remove(&tbl_id).unwrap();
+                        db.tables.insert(table_name.clone(), tbl_id);
+                        self.tables.insert(tbl_id, dropped.table.clone());
+                        self.incr_seq(SEQ_DATABASE_META_ID).await?;
+                        let added = self.table_bytes(&tbl_id);
+                        self.update_database_usage(db_name, added, 0).await?;
+                        tracing::debug!("applied UndropTable: {}={:?}", table_name, dropped.table);
+
+                        Ok((None, Some(dropped.table)).into())
+                    }
+                }
+            }
+
+            Cmd::RenameTable {
+                ref db_name,
+                ref table_name,
+                ref new_table_name,
+            } => {
+                let db = match self.databases.get_mut(db_name) {
+                    None => return Ok((None::<Table>, None::<Table>).into()),
+                    Some(db) => db,
+                };
+
+                // The new name must not be taken, by another table or by the table itself.
+                if let Some(tbl_id) = db.tables.get(new_table_name) {
+                    let prev = self.tables.get(tbl_id).cloned();
+                    return Ok((prev, None).into());
+                }
+
+                match db.tables.remove(table_name) {
+                    None => Ok((None::<Table>, None::<Table>).into()),
+                    Some(tbl_id) => {
+                        db.tables.insert(new_table_name.clone(), tbl_id);
+                        let table = self.tables.get(&tbl_id).cloned();
+                        self.incr_seq(SEQ_DATABASE_META_ID).await?;
+                        tracing::debug!(
+                            "applied RenameTable: {}->{}={:?}",
+                            table_name,
+                            new_table_name,
+                            table
+                        );
+
+                        Ok((table.clone(), table).into())
+                    }
+                }
+            }
+
+            Cmd::ModifyTableSchema {
+                ref db_name,
+                ref table_name,
+                table_id,
+                ref schema,
+            } => {
+                let current_id = self
+                    .databases
+                    .get(db_name)
+                    .and_then(|db| db.tables.get(table_name))
+                    .cloned();
+
+                if current_id != Some(table_id) {
+                    return Ok((None::<Table>, None::<Table>).into());
+                }
+
+                let prev = self.tables.get(&table_id).cloned();
+                match prev {
+                    None => Ok((None::<Table>, None::<Table>).into()),
+                    Some(prev) => {
+                        let table = Table {
+                            schema: schema.clone(),
+                            ..prev.clone()
+                        };
+                        self.tables.insert(table_id, table.clone());
+                        self.incr_seq(SEQ_DATABASE_META_ID).await?;
+                        tracing::debug!("applied ModifyTableSchema: {}={:?}", table_name, table);
+
+                        Ok((Some(prev), Some(table)).into())
+                    }
+                }
+            }
+
+            Cmd::VacuumTrash { ts } => {
+                let expired = self
+                    .trash
+                    .values()
+                    .filter(|t| t.expire_at <= ts)
+                    .map(|t| t.table.table_id)
+                    .collect::<Vec<_>>();
+
+                let mut purged = vec![];
+                for tbl_id in expired {
+                    if let Some(dropped) = self.trash.remove(&tbl_id) {
+                        if let Some(parts) = self.table_parts.remove(&tbl_id) {
+                            self.remove_table_parts(tbl_id).await?;
+                            self.remove_inline_parts(&parts).await?;
+                            self.remove_bloom_filters(&parts).await?;
+                            // The store deletes the files themselves once the vacuum is applied.
+                            self.remove_part_files(&parts).await?;
+                        }
+                        purged.push(dropped);
+                    }
+                }
+
+                tracing::debug!("applied VacuumTrash: {} tables purged", purged.len());
+                Ok((Some(purged), None).into())
+            }
+
             Cmd::UpsertKV {
                 ref key,
                 ref seq,
@@ -526,10 +885,7 @@ impl StateMachine {
             } => {
                 // TODO(xp): need to be done all in a tx
                 // TODO(xp): now must be a timestamp extracted from raft log.
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
+                let now = self.config.clock.now_secs();
 
                 let kvs = self.kvs();
                 let prev = kvs.get(key)?;
@@ -550,32 +906,147 @@ impl StateMachine {
                     return Ok((prev.clone(), prev).into());
                 }
 
-                // result is the state after applying an operation.
-                let result;
+                let result = self.kv_apply_op(key, value_op, value_meta, &prev).await?;
 
-                match value_op {
-                    Operation::Update(v) => {
-                        result = self.kv_update(key, value_meta, v).await?;
-                    }
-                    Operation::Delete => {
-                        kvs.remove(key, true).await?;
-                        result = None;
+                tracing::debug!("applied UpsertKV: {} {:?}", key, result);
+                Ok((prev, result).into())
+            }
+
+            Cmd::CasKV {
+                ref key,
+                ref expect,
+                value: ref value_op,
+                ref value_meta,
+            } => {
+                let prev = self.unexpired_opt(self.kvs().get(key)?);
+
+                let matched = match (expect, &prev) {
+                    (None, None) => true,
+                    (Some(expect), Some((_, curr))) => curr.value == *expect,
+                    _ => false,
+                };
+                if !matched {
+                    return Ok((prev.clone(), prev).into());
+                }
+
+                let result = self.kv_apply_op(key, value_op, value_meta, &prev).await?;
+
+                tracing::debug!("applied CasKV: {} {:?}", key, result);
+                Ok((prev, result).into())
+            }
+
+            Cmd::MergeKV {
+                ref key,
+                ref seq,
+                ref op,
+                ref value_meta,
+            } => {
+                let prev = self.unexpired_opt(self.kvs().get(key)?);
+
+                if seq.match_seq(&prev).is_err() {
+                    return Ok((prev.clone(), prev).into());
+                }
+
+                let curr = prev.as_ref().map(|(_, v)| v.value.as_slice());
+
+                // A merge that can not be applied is reported to the caller, it must not fail the state machine.
+                let merged = match op.apply(curr) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        tracing::warn!("MergeKV: {} {} not applied: {}", key, op, e);
+                        return Ok(AppliedState::KVMergeRejected {
+                            prev,
+                            reason: e.to_string(),
+                        });
                     }
-                    Operation::AsIs => {
-                        result = match prev {
-                            None => None,
-                            Some((_, ref curr_kv_value)) => {
-                                self.kv_update(key, value_meta, &curr_kv_value.value)
-                                    .await?
-                            }
+                };
+
+                let result = match merged {
+                    None => prev.clone(),
+                    Some(v) => {
+                        let meta = match value_meta {
+                            Some(_) => value_meta.clone(),
+                            None => prev.as_ref().and_then(|(_, p)| p.meta.clone()),
                         };
+                        self.kv_update(key, &meta, &v).await?
                     }
+                };
+
+                tracing::debug!("applied MergeKV: {} {:?}", key, result);
+                Ok((prev, result).into())
+            }
+
+            Cmd::PatchKVJson {
+                ref key,
+                ref seq,
+                ref patch,
+            } => {
+                let prev = self.unexpired_opt(self.kvs().get(key)?);
+
+                if seq.match_seq(&prev).is_err() {
+                    return Ok((prev.clone(), prev).into());
                 }
 
-                tracing::debug!("applied UpsertKV: {} {:?}", key, result);
+                let curr = prev.as_ref().map(|(_, v)| v.value.as_slice());
+
+                // A patch that can not be applied is reported to the caller, it must not fail the state machine.
+                let patched = match apply_json_patch(curr, patch) {
+                    Ok(v) => v,
+                    Err(reason) => {
+                        tracing::warn!("PatchKVJson: {} not applied: {}", key, reason);
+                        return Ok(AppliedState::KVPatchRejected { prev, reason });
+                    }
+                };
+
+                let meta = prev.as_ref().and_then(|(_, p)| p.meta.clone());
+                let result = self.kv_update(key, &meta, &patched).await?;
+
+                tracing::debug!("applied PatchKVJson: {} {:?}", key, result);
                 Ok((prev, result).into())
             }
 
+            Cmd::DeletePrefixKV { ref prefix } => {
+                let kvs = self.kvs();
+
+                // The records are removed in chunks, only one chunk of keys is held at a time.
+                // Applying it again after a crash in the middle removes the rest.
+                let mut count = 0;
+                loop {
+                    let mut chunk = Vec::with_capacity(DELETE_PREFIX_KV_CHUNK);
+                    for item in kvs.scan_prefix_iter(prefix)?.take(DELETE_PREFIX_KV_CHUNK) {
+                        let (key, seq_value) = item?;
+                        // An expired record is already gone: it is removed but not counted.
+                        let seq_value = self.unexpired(seq_value.clone()).ok_or(seq_value.0);
+                        if seq_value.is_ok() {
+                            count += 1;
+                        }
+                        chunk.push((key, seq_value));
+                    }
+
+                    let done = chunk.len() < DELETE_PREFIX_KV_CHUNK;
+                    let keys = chunk.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+                    kvs.remove_keys(&keys, done).await?;
+                    for (key, seq_value) in chunk.into_iter() {
+                        match seq_value {
+                            Ok(removed) => {
+                                self.kv_seq_index.update(&key, Some(removed.0), None);
+                                self.kv_watch.notify(&key, &Some(removed), &None);
+                            }
+                            Err(seq) => self.kv_seq_index.update(&key, Some(seq), None),
+                        }
+                    }
+                    if done {
+                        break;
+                    }
+                }
+
+                tracing::debug!("applied DeletePrefixKV: {} {} records", prefix, count);
+                Ok(AppliedState::KVCount {
+                    prev: Some(count),
+                    result: Some(0),
+                })
+            }
+
             Cmd::TruncateTable {
                 ref db_name,
                 ref table_name,
@@ -585,16 +1056,239 @@ impl StateMachine {
                 if let Some(tbl_id) = tbl_id {
                     let _tbl_id = tbl_id.to_owned();
                     let pre_data_parts_count = self.get_data_parts_count(db_name, table_name);
-                    self.remove_table_data_parts(db_name, table_name);
+                    let parts = self.get_data_parts(db_name, table_name).unwrap_or_default();
+                    self.remove_table_data_parts(db_name, table_name).await?;
+                    // The store deletes the files themselves once the truncate is applied.
+                    self.remove_part_files(&parts).await?;
                     tracing::debug!("applied TruncateTable: {}", table_name);
                     Ok((Some(pre_data_parts_count), Some(0_usize)).into())
                 } else {
                     Ok((None::<usize>, None::<usize>).into())
                 }
             }
+
+            Cmd::SetDatabaseQuota {
+                ref db_name,
+                ref quota,
+            } => {
+                if !self.databases.contains_key(db_name) {
+                    return Ok((None::<DatabaseUsage>, None::<DatabaseUsage>).into());
+                }
+
+                let usages = self.database_usages();
+                let prev = usages.get(db_name)?.unwrap_or_default();
+                let usage = DatabaseUsage {
+                    quota: *quota,
+                    ..prev.clone()
+                };
+                usages.insert(db_name, &usage).await?;
+                tracing::debug!("applied SetDatabaseQuota: {}={:?}", db_name, usage);
+
+                Ok((Some(prev), Some(usage)).into())
+            }
+
+            Cmd::ReconcileDatabaseUsage { ref db_name } => {
+                let tbl_ids = match self.databases.get(db_name) {
+                    None => return Ok((None::<DatabaseUsage>, None::<DatabaseUsage>).into()),
+                    Some(db) => db.tables.values().cloned().collect::<Vec<_>>(),
+                };
+                let used_bytes = tbl_ids.iter().map(|tbl_id| self.table_bytes(tbl_id)).sum();
+
+                let usages = self.database_usages();
+                let prev = usages.get(db_name)?.unwrap_or_default();
+                let usage = DatabaseUsage {
+                    used_bytes,
+                    ..prev.clone()
+                };
+                usages.insert(db_name, &usage).await?;
+                tracing::info!(
+                    "applied ReconcileDatabaseUsage: {} used_bytes: {} -> {}",
+                    db_name,
+                    prev.used_bytes,
+                    usage.used_bytes
+                );
+
+                Ok((Some(prev), Some(usage)).into())
+            }
+        }
+    }
+
+    /// Add a table to `db` with a new id and data version, the caller saves `db`.
+    async fn insert_table(
+        &mut self,
+        db: &mut Database,
+        table_name: &str,
+        table: &Table,
+    ) -> common_exception::Result<Table> {
+        let table = Table {
+            table_id: self.incr_seq(SEQ_TABLE_ID).await?,
+            schema: table.schema.clone(),
+            table_engine: table.table_engine.clone(),
+            table_options: table.table_options.clone(),
+            parts: table.parts.clone(),
+            data_version: self.incr_seq(SEQ_TABLE_DATA_VERSION).await?,
+        };
+        db.tables.insert(table_name.to_string(), table.table_id);
+        self.tables.insert(table.table_id, table.clone());
+        Ok(table)
+    }
+
+    /// The catalog entries a `Cmd` may change, taken both before and after it is applied.
+    fn cmd_catalog_keys(&self, cmd: &Cmd) -> CatalogKeys {
+        match cmd {
+            Cmd::CreateDatabase { ref name, .. } | Cmd::DropDatabase { ref name, .. } => {
+                self.catalog_keys(name, None)
+            }
+            Cmd::CreateTables { ref tables } => {
+                tables.iter().fold(CatalogKeys::default(), |keys, t| {
+                    keys.merge(self.catalog_keys(&t.db_name, Some(&t.table_name)))
+                })
+            }
+            Cmd::CreateTable {
+                ref db_name,
+                ref table_name,
+                ..
+            }
+            | Cmd::DropTable {
+                ref db_name,
+                ref table_name,
+                ..
+            }
+            | Cmd::UndropTable {
+                ref db_name,
+                ref table_name,
+                ..
+            }
+            | Cmd::RenameTable {
+                ref db_name,
+                ref table_name,
+                ..
+            }
+            | Cmd::ModifyTableSchema {
+                ref db_name,
+                ref table_name,
+                ..
+            }
+            | Cmd::TruncateTable {
+                ref db_name,
+                ref table_name,
+            } => self.catalog_keys(db_name, Some(table_name)),
+            Cmd::VacuumTrash { .. } => CatalogKeys {
+                databases: BTreeSet::new(),
+                tables: self.trash.keys().cloned().collect(),
+            },
+            _ => CatalogKeys::default(),
+        }
+    }
+
+    /// The catalog entries of a database and of its table `table_name`, or of all of its tables
+    /// if `table_name` is None.
+    fn catalog_keys(&self, db_name: &str, table_name: Option<&str>) -> CatalogKeys {
+        let tables = match self.databases.get(db_name) {
+            None => BTreeSet::new(),
+            Some(db) => db
+                .tables
+                .iter()
+                .filter(|(name, _)| table_name.map(|t| t == name.as_str()).unwrap_or(true))
+                .map(|(_, table_id)| *table_id)
+                .collect(),
+        };
+        CatalogKeys {
+            databases: std::iter::once(db_name.to_string()).collect(),
+            tables,
         }
     }
 
+    fn all_catalog_keys(&self) -> CatalogKeys {
+        let mut tables = BTreeSet::new();
+        tables.extend(self.tables.keys());
+        tables.extend(self.table_parts.keys());
+        tables.extend(self.trash.keys());
+        CatalogKeys {
+            databases: self.databases.keys().cloned().collect(),
+            tables,
+        }
+    }
+
+    /// Writes the catalog entries of `keys` in memory to the sled::Tree, the absent ones are
+    /// removed from it.
+    async fn save_catalog(&self, keys: CatalogKeys) -> common_exception::Result<()> {
+        for name in keys.databases.iter() {
+            save_entry(self.catalog_databases(), name, self.databases.get(name)).await?;
+        }
+        for id in keys.tables.iter() {
+            save_entry(self.catalog_tables(), id, self.tables.get(id)).await?;
+            save_entry(self.catalog_trash(), id, self.trash.get(id)).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes a part of a table to the sled::Tree, after the parts written before it.
+    async fn insert_table_part(
+        &self,
+        table_id: u64,
+        part: &DataPartInfo,
+    ) -> common_exception::Result<()> {
+        let seq = self.incr_seq(SEQ_TABLE_PART).await?;
+        let part = TablePart {
+            seq,
+            part: part.clone(),
+        };
+        self.catalog_table_parts()
+            .insert(&TablePartKey::new(table_id, &part.part.part.name), &part)
+            .await?;
+        Ok(())
+    }
+
+    /// Removes the parts of a table from the sled::Tree.
+    async fn remove_table_parts(&self, table_id: u64) -> common_exception::Result<()> {
+        self.catalog_table_parts()
+            .range_remove(TablePartKey::table_range(table_id), true)
+            .await
+    }
+
+    /// Rewrites the parts of a table in the sled::Tree from memory, in their order.
+    async fn save_table_parts(&self, table_id: u64) -> common_exception::Result<()> {
+        self.remove_table_parts(table_id).await?;
+        for part in self.table_parts.get(&table_id).into_iter().flatten() {
+            self.insert_table_part(table_id, part).await?;
+        }
+        Ok(())
+    }
+
+    /// Add `added` bytes to and remove `removed` bytes from the used bytes of a database.
+    async fn update_database_usage(
+        &self,
+        db_name: &str,
+        added: u64,
+        removed: u64,
+    ) -> common_exception::Result<()> {
+        if added == removed {
+            return Ok(());
+        }
+
+        self.database_usages()
+            .update_and_fetch(
+                &db_name.to_string(),
+                |prev| {
+                    let mut usage = prev.unwrap_or_default();
+                    usage.used_bytes = (usage.used_bytes + added).saturating_sub(removed);
+                    Some(usage)
+                },
+                true,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Bytes on disk of the data parts of a table.
+    fn table_bytes(&self, tbl_id: &u64) -> u64 {
+        self.table_parts
+            .get(tbl_id)
+            .map(|parts| parts.iter().map(|p| p.stats.read_bytes as u64).sum())
+            .unwrap_or_default()
+    }
+
     /// Update a generic-kv record, without seq checking
     async fn kv_update(
         &self,
@@ -611,9 +1305,43 @@ impl StateMachine {
         let seq_kv_value = (new_seq, kv_value);
 
         let kvs = self.kvs();
-        kvs.insert(&key.to_string(), &seq_kv_value).await?;
+        let prev = kvs.insert(&key.to_string(), &seq_kv_value).await?;
+        self.kv_seq_index
+            .update(key, prev.as_ref().map(|(seq, _)| *seq), Some(new_seq));
+
+        let result = Some(seq_kv_value);
+        self.kv_watch
+            .notify(key, &self.unexpired_opt(prev), &result);
 
-        Ok(Some(seq_kv_value))
+        Ok(result)
+    }
+
+    /// Update, delete or only set the meta of a generic-kv record whose current value is `prev`,
+    /// without seq checking. Returns the state after the operation.
+    async fn kv_apply_op(
+        &self,
+        key: &str,
+        value_op: &Operation<Vec<u8>>,
+        value_meta: &Option<KVMeta>,
+        prev: &Option<SeqValue<KVValue>>,
+    ) -> common_exception::Result<Option<SeqValue<KVValue>>> {
+        match value_op {
+            Operation::Update(v) => self.kv_update(key, value_meta, v).await,
+            Operation::Delete => {
+                let removed = self.kvs().remove(key, true).await?;
+                self.kv_seq_index
+                    .update(key, removed.as_ref().map(|(seq, _)| *seq), None);
+                self.kv_watch
+                    .notify(key, &self.unexpired_opt(removed), &None);
+                Ok(None)
+            }
+            Operation::AsIs => match prev {
+                None => Ok(None),
+                Some((_, curr_kv_value)) => {
+                    self.kv_update(key, value_meta, &curr_kv_value.value).await
+                }
+            },
+        }
     }
 
     pub fn get_membership(&self) -> common_exception::Result<Option<MembershipConfig>> {
@@ -690,6 +1418,22 @@ impl StateMachine {
         Ok(fns.into_iter().map(|(k, _v)| k).collect())
     }
 
+    /// The files no data part refers to, with their records, e.g. the files committed by an
+    /// append whose parts failed to be registered. The tables in trash still refer to theirs.
+    pub fn list_unregistered_files(&self) -> common_exception::Result<Vec<(String, String)>> {
+        let registered = self
+            .catalog_table_parts()
+            .range_values(..)?
+            .into_iter()
+            .map(|table_part| table_part.part.part.name)
+            .collect::<HashSet<_>>();
+        let files = self.files().range_kvs(..)?;
+        Ok(files
+            .into_iter()
+            .filter(|(key, _)| !registered.contains(key))
+            .collect())
+    }
+
     pub fn get_node(&self, node_id: &NodeId) -> common_exception::Result<Option<Node>> {
         let sm_nodes = self.nodes();
         sm_nodes.get(node_id)
@@ -705,9 +1449,8 @@ impl StateMachine {
     }
 
     pub fn get_database_meta_ver(&self) -> common_exception::Result<Option<u64>> {
-        let sequences = self.sequences();
-        let res = sequences.get(&SEQ_DATABASE_META_ID.to_string())?;
-        Ok(res.map(|x| x.0))
+        self.seq_allocator
+            .last(&self.sequences(), SEQ_DATABASE_META_ID)
     }
 
     pub fn get_table(&self, tid: &u64) -> Option<Table> {
@@ -715,6 +1458,61 @@ impl StateMachine {
         x.cloned()
     }
 
+    /// Returns the dropped tables in trash, including the expired ones not yet vacuumed.
+    pub fn get_dropped_tables(&self) -> Vec<DroppedTable> {
+        self.trash.values().cloned().collect()
+    }
+
+    /// The parts of the dropped tables expired at `ts`, by table id, i.e. the parts a
+    /// `VacuumTrash` of `ts` purges.
+    pub fn get_expired_trash_parts(&self, ts: u64) -> HashMap<u64, Vec<DataPartInfo>> {
+        self.trash
+            .values()
+            .filter(|t| t.expire_at <= ts)
+            .filter_map(|t| {
+                let table_id = t.table.table_id;
+                self.table_parts
+                    .get(&table_id)
+                    .map(|parts| (table_id, parts.clone()))
+            })
+            .collect()
+    }
+
+    /// Returns the quota and the used bytes of a database, `None` if it does not exist.
+    pub fn get_database_usage(
+        &self,
+        db_name: &str,
+    ) -> common_exception::Result<Option<DatabaseUsage>> {
+        if !self.databases.contains_key(db_name) {
+            return Ok(None);
+        }
+        let usage = self.database_usages().get(&db_name.to_string())?;
+        Ok(Some(usage.unwrap_or_default()))
+    }
+
+    /// Returns the quota and the used bytes of every database, by name.
+    pub fn get_database_usages(&self) -> common_exception::Result<Vec<(String, DatabaseUsage)>> {
+        let usages = self.database_usages();
+        let mut res = vec![];
+        for name in self.databases.keys() {
+            res.push((name.clone(), usages.get(name)?.unwrap_or_default()));
+        }
+        Ok(res)
+    }
+
+    /// Moves a dropped table to trash, `now` is the time of the log that drops it.
+    fn move_to_trash(&mut self, db_name: &str, table_name: &str, table: Table, now: u64) {
+        let dropped = DroppedTable {
+            db_name: db_name.to_string(),
+            table_name: table_name.to_string(),
+            dropped_on: now,
+            expire_at: now + self.config.table_trash_retention,
+            table,
+        };
+        tracing::debug!("move to trash: {}-{}={:?}", db_name, table_name, dropped);
+        self.trash.insert(dropped.table.table_id, dropped);
+    }
+
     pub fn get_kv(&self, key: &str) -> common_exception::Result<Option<SeqValue<KVValue>>> {
         // TODO(xp) refine get(): a &str is enough for key
         let sv = self.kvs().get(&key.to_string())?;
@@ -724,7 +1522,7 @@ impl StateMachine {
             Some(sv) => sv,
         };
 
-        Ok(Self::unexpired(sv))
+        Ok(self.unexpired(sv))
     }
 
     pub fn get_data_parts(&self, db_name: &str, table_name: &str) -> Option<Vec<DataPartInfo>> {
@@ -738,6 +1536,28 @@ impl StateMachine {
         None
     }
 
+    /// Returns the descriptor of a part of a table, without copying the other parts of it.
+    /// `None` if the part is not of the table, an error if the table is unknown.
+    pub fn get_data_part(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        part_name: &str,
+    ) -> common_exception::Result<Option<DataPartInfo>> {
+        let table_id = self
+            .databases
+            .get(db_name)
+            .and_then(|db| db.tables.get(table_name))
+            .ok_or_else(|| {
+                ErrorCode::UnknownTable(format!("Unknown table: '{}.{}'", db_name, table_name))
+            })?;
+        let part = self
+            .table_parts
+            .get(table_id)
+            .and_then(|parts| parts.iter().find(|p| p.part.name == part_name));
+        Ok(part.cloned())
+    }
+
     pub fn get_data_parts_count(&self, db_name: &str, table_name: &str) -> usize {
         let db = self.databases.get(db_name);
         if let Some(db) = db {
@@ -754,12 +1574,42 @@ impl StateMachine {
         0
     }
 
-    pub fn append_data_parts(
+    /// Registers the parts of `append_res`, and writes the bytes of the inline ones of them in
+    /// `inline_parts`. Nothing is written if the table is gone, no bytes are left unregistered.
+    ///
+    /// The parts are rejected with `QuotaExceeded` if their bytes on disk would exceed the quota
+    /// of the database. The applies are serialized, thus the usage checked is the one updated.
+    pub async fn append_data_parts(
+        &mut self,
+        db_name: &str,
+        table_name: &str,
+        append_res: &AppendResult,
+        inline_parts: &[(String, Vec<u8>)],
+    ) -> common_exception::Result<Option<u64>> {
+        let incoming = append_res.parts.iter().map(|p| p.disk_bytes as u64).sum();
+        let usage = self.get_database_usage(db_name)?.unwrap_or_default();
+        if usage.exceeded_by(incoming) {
+            return Err(ErrorCode::QuotaExceeded(format!(
+                "append to {}.{}: {} bytes used and {} bytes appended exceed the quota of {} bytes",
+                db_name,
+                table_name,
+                usage.used_bytes,
+                incoming,
+                usage.quota.unwrap_or_default()
+            )));
+        }
+
+        self.register_data_parts(db_name, table_name, append_res, inline_parts)
+            .await
+    }
+
+    async fn register_data_parts(
         &mut self,
         db_name: &str,
         table_name: &str,
         append_res: &AppendResult,
-    ) {
+        inline_parts: &[(String, Vec<u8>)],
+    ) -> common_exception::Result<Option<u64>> {
         let part_infos = append_res
             .parts
             .iter()
@@ -769,17 +1619,36 @@ impl StateMachine {
                     part: Part {
                         name: loc.clone(),
                         version: 0,
+                        read_info: None,
                     },
                     stats: Statistics::new_exact(p.rows, p.disk_bytes),
+                    format: p.format.clone(),
+                    // The filters are kept in their own key space, the descriptors of all the
+                    // parts of a table are loaded for every read plan.
+                    bloom_filters: None,
+                    storage: p.storage,
+                    checksum: p.checksum.clone(),
+                    column_statistics: p.column_statistics.clone(),
                 }
             })
             .collect::<Vec<_>>();
 
+        let mut appended = 0;
         let db = self.databases.get(db_name);
         if let Some(db) = db {
             let table_id = db.tables.get(table_name);
             if let Some(table_id) = table_id {
+                for (name, data) in inline_parts {
+                    self.inline_parts().insert(name, data).await?;
+                }
+                for p in &append_res.parts {
+                    if let Some(filters) = &p.bloom_filters {
+                        self.bloom_filters().insert(&p.location, filters).await?;
+                    }
+                }
                 for part in part_infos {
+                    appended += part.stats.read_bytes as u64;
+                    self.insert_table_part(*table_id, &part).await?;
                     let table = self.tables.get_mut(table_id).unwrap();
                     table.parts.insert(part.part.name.clone());
                     // These comments are intentionally left here.
@@ -812,27 +1681,182 @@ impl StateMachine {
                 }
             }
         }
+        let data_version = self.bump_data_versions(db_name, Some(table_name)).await?;
+        self.update_database_usage(db_name, appended, 0).await?;
+        self.save_catalog(self.catalog_keys(db_name, Some(table_name)))
+            .await?;
+        Ok(data_version)
+    }
+
+    /// Replaces the parts `removed` of a table with the ones of `append_res`, e.g., the inline
+    /// parts with the part they are compacted into. Nothing is changed and None is returned if
+    /// the table is gone or if any of the removed parts is, e.g., by a truncate meanwhile.
+    pub async fn replace_data_parts(
+        &mut self,
+        db_name: &str,
+        table_name: &str,
+        removed: &[String],
+        append_res: &AppendResult,
+        inline_parts: &[(String, Vec<u8>)],
+    ) -> common_exception::Result<Option<u64>> {
+        let table_id = match self.databases.get(db_name) {
+            None => return Ok(None),
+            Some(db) => match db.tables.get(table_name) {
+                None => return Ok(None),
+                Some(table_id) => *table_id,
+            },
+        };
+
+        let parts = self.table_parts.get(&table_id).cloned().unwrap_or_default();
+        let (gone, kept): (Vec<_>, Vec<_>) = parts
+            .into_iter()
+            .partition(|p| removed.contains(&p.part.name));
+        if gone.len() != removed.len() {
+            return Ok(None);
+        }
+
+        if let Some(table) = self.tables.get_mut(&table_id) {
+            for p in &gone {
+                table.parts.remove(&p.part.name);
+            }
+        }
+        self.table_parts.insert(table_id, kept);
+        for p in &gone {
+            let key = TablePartKey::new(table_id, &p.part.name);
+            self.catalog_table_parts().remove(&key, true).await?;
+        }
+        self.remove_inline_parts(&gone).await?;
+        self.remove_bloom_filters(&gone).await?;
+        let removed_bytes = gone.iter().map(|p| p.stats.read_bytes as u64).sum();
+        self.update_database_usage(db_name, 0, removed_bytes)
+            .await?;
+
+        // A compaction rewrites the bytes already counted, it is not held to the quota.
+        self.register_data_parts(db_name, table_name, append_res, inline_parts)
+            .await
+    }
+
+    pub fn get_inline_part(&self, name: &str) -> common_exception::Result<Option<Vec<u8>>> {
+        self.inline_parts().get(&name.to_string())
+    }
+
+    /// The names of the inline parts that start with `prefix`.
+    pub fn list_inline_parts(&self, prefix: &str) -> common_exception::Result<Vec<String>> {
+        let parts = self.inline_parts().scan_prefix(&prefix.to_string())?;
+        Ok(parts.into_iter().map(|(name, _)| name).collect())
     }
 
-    pub fn remove_table_data_parts(&mut self, db_name: &str, table_name: &str) {
+    /// Drops the bytes of the inline parts among `parts`, once the parts are no longer readable.
+    async fn remove_inline_parts(&self, parts: &[DataPartInfo]) -> common_exception::Result<()> {
+        for part in parts {
+            if part.storage == PartStorageClass::Inline {
+                self.inline_parts().remove(&part.part.name, true).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The bloom filters of a part, `None` if none is built for it.
+    pub fn get_bloom_filters(
+        &self,
+        part_name: &str,
+    ) -> common_exception::Result<Option<PartBloomFilters>> {
+        self.bloom_filters().get(&part_name.to_string())
+    }
+
+    async fn remove_bloom_filters(&self, parts: &[DataPartInfo]) -> common_exception::Result<()> {
+        let keys = parts
+            .iter()
+            .map(|part| part.part.name.clone())
+            .collect::<Vec<_>>();
+        self.bloom_filters().remove_keys(&keys, true).await
+    }
+
+    async fn remove_part_files(&self, parts: &[DataPartInfo]) -> common_exception::Result<()> {
+        let keys = parts
+            .iter()
+            .filter(|part| part.storage == PartStorageClass::File)
+            .map(|part| part.part.name.clone())
+            .collect::<Vec<_>>();
+        self.files().remove_keys(&keys, true).await
+    }
+
+    pub async fn remove_table_data_parts(
+        &mut self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<()> {
+        let mut removed = 0;
         let db = self.databases.get(db_name);
         if let Some(db) = db {
             let table_id = db.tables.get(table_name);
             if let Some(table_id) = table_id {
+                removed += self.table_bytes(table_id);
                 self.tables.entry(*table_id).and_modify(|t| t.parts.clear());
-                self.table_parts.remove(table_id);
+                if let Some(parts) = self.table_parts.remove(table_id) {
+                    self.remove_table_parts(*table_id).await?;
+                    self.remove_inline_parts(&parts).await?;
+                    self.remove_bloom_filters(&parts).await?;
+                }
             }
         }
+        self.bump_data_versions(db_name, Some(table_name)).await?;
+        self.update_database_usage(db_name, 0, removed).await?;
+        self.save_catalog(self.catalog_keys(db_name, Some(table_name)))
+            .await
     }
 
-    pub fn remove_db_data_parts(&mut self, db_name: &str) {
+    pub async fn remove_db_data_parts(&mut self, db_name: &str) -> common_exception::Result<()> {
+        let mut removed = 0;
         let db = self.databases.get(db_name);
         if let Some(db) = db {
             for table_id in db.tables.values() {
+                removed += self.table_bytes(table_id);
                 self.tables.entry(*table_id).and_modify(|t| t.parts.clear());
-                self.table_parts.remove(table_id);
+                if let Some(parts) = self.table_parts.remove(table_id) {
+                    self.remove_table_parts(*table_id).await?;
+                    self.remove_inline_parts(&parts).await?;
+                    self.remove_bloom_filters(&parts).await?;
+                }
+            }
+        }
+        self.bump_data_versions(db_name, None).await?;
+        self.update_database_usage(db_name, 0, removed).await?;
+        self.save_catalog(self.catalog_keys(db_name, None)).await
+    }
+
+    /// Gives the table, or every table of the database if `table_name` is None, a new data
+    /// version. Returns the version of the last table, None if there is none.
+    async fn bump_data_versions(
+        &mut self,
+        db_name: &str,
+        table_name: Option<&str>,
+    ) -> common_exception::Result<Option<u64>> {
+        let table_ids = match self.databases.get(db_name) {
+            None => vec![],
+            Some(db) => db
+                .tables
+                .iter()
+                .filter(|(name, _)| table_name.map(|t| t == name.as_str()).unwrap_or(true))
+                .map(|(_, table_id)| *table_id)
+                .collect::<Vec<_>>(),
+        };
+
+        let mut data_version = None;
+        for table_id in table_ids {
+            let version = self.incr_seq(SEQ_TABLE_DATA_VERSION).await?;
+            if let Some(table) = self.tables.get_mut(&table_id) {
+                table.data_version = version;
+                data_version = Some(version);
             }
         }
+        Ok(data_version)
+    }
+
+    /// The data version of a table, None if there is no such table.
+    pub fn get_table_data_version(&self, db_name: &str, table_name: &str) -> Option<u64> {
+        let table_id = self.databases.get(db_name)?.tables.get(table_name)?;
+        self.tables.get(table_id).map(|table| table.data_version)
     }
 
     pub fn mget_kv(
@@ -843,39 +1867,191 @@ impl StateMachine {
         let mut res = vec![];
         for x in keys.iter() {
             let v = kvs.get(&x.as_ref().to_string())?;
-            let v = Self::unexpired_opt(v);
+            let v = self.unexpired_opt(v);
             res.push(v)
         }
 
         Ok(res)
     }
 
+    /// Returns the unexpired records under `prefix`, ordered by key.
+    ///
+    /// It fails with `ReplyTooLarge` if they do not fit in a reply, see `kv_reply_max_entries`.
     pub fn prefix_list_kv(
         &self,
         prefix: &str,
+    ) -> common_exception::Result<Vec<(String, SeqValue<KVValue>)>> {
+        let mut cap = self.reply_cap();
+        let mut res = vec![];
+        for item in self.kvs().scan_prefix_iter(&prefix.to_string())? {
+            let (key, seq_value) = item?;
+            if let Some(seq_value) = self.unexpired(seq_value) {
+                cap.add(&key, &seq_value)?;
+                res.push((key, seq_value));
+            }
+        }
+        Ok(res)
+    }
+
+    /// Returns up to `limit` of the records under `prefix` with a key greater than `after_key`, ordered by key.
+    ///
+    /// The records are read in key order and no further than the first one after the page, the
+    /// expired ones are skipped without counting against `limit`.
+    /// A page that does not fit in a reply fails with `ReplyTooLarge`, as `prefix_list_kv()` does.
+    pub fn prefix_list_kv_page(
+        &self,
+        prefix: &str,
+        limit: Option<usize>,
+        after_key: Option<&str>,
+    ) -> common_exception::Result<PrefixListPage> {
+        let start = match after_key {
+            Some(key) if key >= prefix => Bound::Excluded(key.to_string()),
+            _ => Bound::Included(prefix.to_string()),
+        };
+        let limit = limit.unwrap_or(usize::MAX);
+
+        let mut cap = self.reply_cap();
+        let mut page = PrefixListPage::default();
+        for item in self.kvs().range((start, Bound::Unbounded))? {
+            let (key, seq_value) = item?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            let seq_value = match self.unexpired(seq_value) {
+                None => continue,
+                Some(seq_value) => seq_value,
+            };
+            if page.entries.len() == limit {
+                page.more = true;
+                break;
+            }
+            cap.add(&key, &seq_value)?;
+            page.entries.push((key, seq_value));
+        }
+        Ok(page)
+    }
+
+    /// Counts the unexpired records under `prefix`, without keeping them.
+    pub fn prefix_count_kv(&self, prefix: &str) -> common_exception::Result<usize> {
+        let mut count = 0;
+        for item in self.kvs().range(prefix.to_string()..)? {
+            let (key, seq_value) = item?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            if self.unexpired(seq_value).is_some() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Returns up to `limit` of the records under `prefix` with a seq greater than `min_seq`, ordered by seq.
+    ///
+    /// A prefix registered with `register_kv_seq_index` is served from an index. An unregistered
+    /// one falls back to scanning and sorting all of the records under it, which costs
+    /// O(n log n) in the number of records under the prefix, no matter how small `limit` is.
+    pub fn prefix_list_kv_by_seq(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
     ) -> common_exception::Result<Vec<(String, SeqValue<KVValue>)>> {
         let kvs = self.kvs();
-        let kv_pairs = kvs.scan_prefix(&prefix.to_string())?;
+        let scan = || {
+            let kv_pairs = kvs.scan_prefix(&prefix.to_string())?;
+            Ok(kv_pairs.into_iter().map(|(k, (seq, _))| (k, seq)).collect())
+        };
 
-        let x = kv_pairs.into_iter();
+        let mut res = vec![];
+        let mut from = min_seq;
+        while res.len() < limit {
+            let keys = match self
+                .kv_seq_index
+                .range(prefix, from, limit - res.len(), scan)?
+            {
+                None => return self.prefix_list_kv_by_seq_scan(prefix, min_seq, limit),
+                Some(keys) => keys,
+            };
+            if keys.is_empty() {
+                break;
+            }
+
+            for (seq, key) in keys {
+                from = seq;
+                match self.unexpired_opt(kvs.get(&key)?) {
+                    Some(seq_value) if seq_value.0 == seq => res.push((key, seq_value)),
+                    _ => self.kv_seq_index.remove(prefix, seq),
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn prefix_list_kv_by_seq_scan(
+        &self,
+        prefix: &str,
+        min_seq: u64,
+        limit: usize,
+    ) -> common_exception::Result<Vec<(String, SeqValue<KVValue>)>> {
+        let mut res = vec![];
+        for item in self.kvs().scan_prefix_iter(&prefix.to_string())? {
+            let (key, seq_value) = item?;
+            if seq_value.0 <= min_seq {
+                continue;
+            }
+            if let Some(seq_value) = self.unexpired(seq_value) {
+                res.push((key, seq_value));
+            }
+        }
+        res.sort_by_key(|(_, (seq, _))| *seq);
+        res.truncate(limit);
+        Ok(res)
+    }
 
-        // Convert expired to None
-        let x = x.map(|(k, v)| (k, Self::unexpired(v)));
-        // Remove None
-        let x = x.filter(|(_k, v)| v.is_some());
-        // Extract from an Option
-        let x = x.map(|(k, v)| (k, v.unwrap()));
+    /// Serves `prefix_list_kv_by_seq` of the keys under `prefix` from an index.
+    pub fn register_kv_seq_index(&self, prefix: &str) {
+        self.kv_seq_index.register(prefix);
+    }
 
-        Ok(x.collect())
+    /// Starts a watcher of the changes of the keys under `prefix`, see `KVWatchHub::watch`.
+    /// The unexpired records under the prefix are not capped by the reply limits: they are not
+    /// all sent, but the watcher needs their expire time.
+    pub fn watch_kv(
+        &self,
+        prefix: &str,
+        after_seq: Option<u64>,
+    ) -> common_exception::Result<KVWatcher> {
+        let mut current = vec![];
+        for item in self.kvs().scan_prefix_iter(&prefix.to_string())? {
+            let (key, seq_value) = item?;
+            if let Some(seq_value) = self.unexpired(seq_value) {
+                current.push((key, seq_value));
+            }
+        }
+        Ok(self
+            .kv_watch
+            .watch(prefix, after_seq, current, self.config.clock.clone()))
     }
 
-    fn unexpired_opt(seq_value: Option<SeqValue<KVValue>>) -> Option<SeqValue<KVValue>> {
+    fn unexpired_opt(&self, seq_value: Option<SeqValue<KVValue>>) -> Option<SeqValue<KVValue>> {
         match seq_value {
             None => None,
-            Some(sv) => Self::unexpired(sv),
+            Some(sv) => self.unexpired(sv),
         }
     }
-    fn unexpired(seq_value: SeqValue<KVValue>) -> Option<SeqValue<KVValue>> {
+
+    fn reply_cap(&self) -> ReplyCap {
+        ReplyCap {
+            max_entries: self.config.kv_reply_max_entries,
+            max_bytes: self.config.kv_reply_max_bytes,
+            entries: 0,
+            bytes: 0,
+        }
+    }
+
+    fn unexpired(&self, seq_value: SeqValue<KVValue>) -> Option<SeqValue<KVValue>> {
         // TODO(xp): log must be assigned with a ts.
 
         // TODO(xp): background task to clean expired
@@ -893,10 +2069,7 @@ impl StateMachine {
 
         // TODO(xp): maybe it needs a expiration queue for efficient cleaning up.
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = self.config.clock.now_secs();
 
         tracing::debug!("seq_value: {:?} now: {}", seq_value, now);
 
@@ -934,6 +2107,98 @@ impl StateMachine {
     pub fn sequences(&self) -> AsKeySpace<sled_key_space::Sequences> {
         self.sm_tree.key_space()
     }
+
+    /// The quota and the used bytes of the databases, tracked as the data parts are added and removed.
+    pub fn database_usages(&self) -> AsKeySpace<sled_key_space::DatabaseUsages> {
+        self.sm_tree.key_space()
+    }
+
+    /// The bytes of the data parts too small to be kept as files, by part name.
+    pub fn inline_parts(&self) -> AsKeySpace<sled_key_space::InlineParts> {
+        self.sm_tree.key_space()
+    }
+
+    /// The bloom filters of the data parts, by part name.
+    pub fn bloom_filters(&self) -> AsKeySpace<sled_key_space::BloomFilters> {
+        self.sm_tree.key_space()
+    }
+
+    /// The databases by name, the copy of `databases` in the tree.
+    pub fn catalog_databases(&self) -> AsKeySpace<sled_key_space::Databases> {
+        self.sm_tree.key_space()
+    }
+
+    /// The tables by id, the copy of `tables` in the tree.
+    pub fn catalog_tables(&self) -> AsKeySpace<sled_key_space::Tables> {
+        self.sm_tree.key_space()
+    }
+
+    /// The data parts by table id and part name, the copy of `table_parts` in the tree.
+    pub fn catalog_table_parts(&self) -> AsKeySpace<sled_key_space::TableParts> {
+        self.sm_tree.key_space()
+    }
+
+    /// The dropped tables by table id, the copy of `trash` in the tree.
+    pub fn catalog_trash(&self) -> AsKeySpace<sled_key_space::Trash> {
+        self.sm_tree.key_space()
+    }
+}
+
+/// The keys of the catalog entries to save: database names, and table ids of the entries in
+/// `tables` and `trash`. The parts in `table_parts` are written one by one as they change.
+#[derive(Default)]
+struct CatalogKeys {
+    databases: BTreeSet<String>,
+    tables: BTreeSet<u64>,
+}
+
+impl CatalogKeys {
+    fn merge(mut self, other: CatalogKeys) -> Self {
+        self.databases.extend(other.databases);
+        self.tables.extend(other.tables);
+        self
+    }
+}
+
+/// Inserts `value` at `key`, or removes `key` if `value` is None.
+async fn save_entry<KV: SledKeySpace>(
+    key_space: AsKeySpace<'_, KV>,
+    key: &KV::K,
+    value: Option<&KV::V>,
+) -> common_exception::Result<()> {
+    match value {
+        Some(value) => {
+            key_space.insert(key, value).await?;
+        }
+        None => {
+            key_space.remove(key, true).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Counts the entries and the key and value bytes put into a kv reply, and fails once either of
+/// them exceeds the max of the config, before the rest of the reply is read.
+struct ReplyCap {
+    max_entries: u64,
+    max_bytes: u64,
+    entries: u64,
+    bytes: u64,
+}
+
+impl ReplyCap {
+    fn add(&mut self, key: &str, seq_value: &SeqValue<KVValue>) -> common_exception::Result<()> {
+        self.entries += 1;
+        self.bytes += (key.len() + seq_value.1.value.len()) as u64;
+
+        if self.entries > self.max_entries || self.bytes > self.max_bytes {
+            return Err(ErrorCode::ReplyTooLarge(format!(
+                "the reply exceeds {} entries or {} bytes, list it in pages with a limit",
+                self.max_entries, self.max_bytes
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// A slot is a virtual and intermediate allocation unit in a distributed storage.
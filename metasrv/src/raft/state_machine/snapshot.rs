@@ -23,4 +23,8 @@ pub struct Snapshot {
 
     /// The data of the state machine at the time of this snapshot.
     pub data: Vec<u8>,
+
+    /// Unix time in milli seconds when this snapshot is taken or installed.
+    #[serde(default)]
+    pub created_at: u64,
 }
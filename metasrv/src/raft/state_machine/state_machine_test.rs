@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -20,28 +21,43 @@ use async_raft::raft::EntryNormal;
 use async_raft::raft::EntryPayload;
 use async_raft::raft::MembershipConfig;
 use async_raft::LogId;
+use common_exception::ErrorCode;
 use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::JsonPatchOp;
 use common_metatypes::KVMeta;
 use common_metatypes::KVValue;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
 use common_metatypes::Operation;
 use common_metatypes::SeqValue;
+use common_metatypes::Table;
 use common_runtime::tokio;
+use common_runtime::Clock;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use common_store_api_sdk::storage_api_impl::AppendResult;
+use common_store_api_sdk::storage_api_impl::BloomFilter;
+use common_store_api_sdk::storage_api_impl::PartBloomFilters;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
 use common_tracing::tracing;
 use maplit::btreeset;
 use pretty_assertions::assert_eq;
 
+use crate::meta_service::cmd::TableToCreate;
 use crate::meta_service::testing::pretty_snapshot;
 use crate::meta_service::testing::pretty_snapshot_iter;
 use crate::meta_service::testing::snapshot_logs;
 use crate::meta_service::Cmd;
 use crate::meta_service::LogEntry;
+use crate::raft::state_machine::sm::DELETE_PREFIX_KV_CHUNK;
 use crate::raft::state_machine::AppliedState;
 use crate::raft::state_machine::Node;
 use crate::raft::state_machine::Replication;
 use crate::raft::state_machine::SerializableSnapshot;
 use crate::raft::state_machine::Slot;
 use crate::raft::state_machine::StateMachine;
+use crate::raft::state_machine::TablePartKey;
 use crate::tests::service::new_test_context;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -283,6 +299,1037 @@ async fn test_state_machine_apply_add_database() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_catalog_survives_reopen() -> anyhow::Result<()> {
+    // - Create tables, append to one and drop the other: a reopened state machine has them all.
+    // - Drop the database: the reopened one has no database and both tables in trash.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    for table_name in ["t1", "t2"] {
+        m.apply_cmd(&Cmd::CreateTable {
+            db_name: "db1".to_string(),
+            table_name: table_name.to_string(),
+            if_not_exists: false,
+            table: Default::default(),
+            seq: None,
+            ts: 0,
+        })
+        .await?;
+    }
+
+    let mut append_res = AppendResult::default();
+    append_res.append_part("part_1", 3, 1, 10, 10);
+    m.append_data_parts("db1", "t1", &append_res, &[]).await?;
+
+    m.apply_cmd(&Cmd::DropTable {
+        db_name: "db1".to_string(),
+        table_name: "t2".to_string(),
+        if_exists: false,
+        ts: 0,
+    })
+    .await?;
+
+    let want = m.catalog();
+    drop(m);
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+    assert_eq!(want, m.catalog());
+    assert_eq!(1, m.get_data_parts_count("db1", "t1"));
+
+    m.apply_cmd(&Cmd::DropDatabase {
+        name: "db1".to_string(),
+        ts: 0,
+    })
+    .await?;
+
+    drop(m);
+    let m = StateMachine::open(&tc.config.meta_config, 1).await?;
+    assert!(m.get_database("db1").is_none());
+    assert!(m.tables.is_empty());
+    assert_eq!(2, m.get_dropped_tables().len());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_table_parts_kept_one_by_one() -> anyhow::Result<()> {
+    // - Each part is kept under its own key, an append or a replace writes only its parts.
+    // - A reopened state machine lists the parts in the order they are registered.
+    // - Truncating the table removes the keys of its parts.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    m.apply_cmd(&Cmd::CreateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_not_exists: false,
+        table: Default::default(),
+        seq: None,
+        ts: 0,
+    })
+    .await?;
+    let table_id = m.get_database("db1").unwrap().tables["t1"];
+
+    for name in ["part_c", "part_a", "part_b"] {
+        let mut append_res = AppendResult::default();
+        append_res.append_part(name, 1, 1, 10, 10);
+        m.append_data_parts("db1", "t1", &append_res, &[]).await?;
+    }
+
+    let mut merged = AppendResult::default();
+    merged.append_part("part_d", 2, 1, 20, 20);
+    let removed = vec!["part_a".to_string()];
+    m.replace_data_parts("db1", "t1", &removed, &merged, &[])
+        .await?
+        .unwrap();
+
+    let keys = m
+        .catalog_table_parts()
+        .range_keys(TablePartKey::table_range(table_id))?;
+    assert_eq!(
+        vec!["part_b", "part_c", "part_d"],
+        keys.iter()
+            .map(|k| k.part_name.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    drop(m);
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+    let parts = m.get_data_parts("db1", "t1").unwrap();
+    assert_eq!(
+        vec!["part_c", "part_b", "part_d"],
+        parts
+            .iter()
+            .map(|p| p.part.name.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    m.apply_cmd(&Cmd::TruncateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+    })
+    .await?;
+    assert!(m
+        .catalog_table_parts()
+        .range_keys(TablePartKey::table_range(table_id))?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_drop_undrop_vacuum_table() -> anyhow::Result<()> {
+    // - Drop a table with data parts: it is moved to trash and the parts are kept.
+    // - Undrop it: the same table and parts are restored.
+    // - Drop it again and vacuum after the retention: it is purged permanently.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.table_trash_retention = 2;
+    // The clock of the leader that assigns the time of the logs.
+    let clock = VirtualClock::create();
+    // The clock of this replica is far ahead: applying a log does not depend on it.
+    let local_clock = VirtualClock::create();
+    local_clock.advance(Duration::from_secs(3600));
+    tc.config.meta_config.clock = SharedClock::create(local_clock);
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+
+    let create_table = Cmd::CreateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_not_exists: false,
+        table: Default::default(),
+        seq: None,
+        ts: clock.now_secs(),
+    };
+    m.apply_cmd(&create_table).await?;
+
+    m.apply_cmd(&Cmd::AddFile {
+        key: "part_1".to_string(),
+        value: "".to_string(),
+    })
+    .await?;
+    let mut append_res = AppendResult::default();
+    append_res.append_part("part_1", 3, 1, 10, 10);
+    m.append_data_parts("db1", "t1", &append_res, &[]).await?;
+
+    let table_id = m.get_database("db1").unwrap().tables["t1"];
+    let table = m.get_table(&table_id).unwrap();
+    let parts = m.get_data_parts("db1", "t1");
+    assert!(parts.is_some());
+
+    let drop_table = Cmd::DropTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_exists: false,
+        ts: clock.now_secs(),
+    };
+    let undrop_table = Cmd::UndropTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        ts: clock.now_secs(),
+    };
+
+    // drop: hidden from the database, kept in trash with its parts
+
+    let resp = m.apply_cmd(&drop_table).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: Some(table.clone()),
+            result: None
+        },
+        resp
+    );
+    assert!(!m.get_database("db1").unwrap().tables.contains_key("t1"));
+    assert!(m.get_table(&table_id).is_none());
+
+    let dropped = m.get_dropped_tables();
+    assert_eq!(1, dropped.len());
+    assert_eq!("db1", dropped[0].db_name);
+    assert_eq!("t1", dropped[0].table_name);
+    assert_eq!(table, dropped[0].table);
+    assert_eq!(2, dropped[0].expire_at - dropped[0].dropped_on);
+    assert!(m.table_parts.contains_key(&table_id));
+
+    // undrop: identical table and parts
+
+    let resp = m.apply_cmd(&undrop_table).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: None,
+            result: Some(table.clone())
+        },
+        resp
+    );
+    assert_eq!(table_id, m.get_database("db1").unwrap().tables["t1"]);
+    assert_eq!(Some(table.clone()), m.get_table(&table_id));
+    assert_eq!(parts, m.get_data_parts("db1", "t1"));
+    assert!(m.get_dropped_tables().is_empty());
+
+    // undrop again: nothing in trash
+
+    let resp = m.apply_cmd(&undrop_table).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: None,
+            result: None
+        },
+        resp
+    );
+
+    // undrop does not override a table created with the same name
+
+    m.apply_cmd(&drop_table).await?;
+    m.apply_cmd(&create_table).await?;
+    let resp = m.apply_cmd(&undrop_table).await?;
+    match resp {
+        AppliedState::Table {
+            prev: Some(prev),
+            result: None,
+        } => assert_ne!(table_id, prev.table_id),
+        _ => panic!("expect the existent table, got: {:?}", resp),
+    }
+    m.apply_cmd(&drop_table).await?;
+    assert_eq!(2, m.get_dropped_tables().len());
+
+    // vacuum before expiration does nothing
+
+    let resp = m
+        .apply_cmd(&Cmd::VacuumTrash {
+            ts: clock.now_secs(),
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::DroppedTables {
+            prev: Some(vec![]),
+            result: None
+        },
+        resp
+    );
+    assert_eq!(2, m.get_dropped_tables().len());
+
+    // vacuum after expiration purges the tables, their parts and the meta of the part files
+
+    clock.advance(Duration::from_secs(3));
+    let expired = m.get_expired_trash_parts(clock.now_secs());
+    assert_eq!("part_1", expired[&table_id][0].part.name);
+
+    let resp = m
+        .apply_cmd(&Cmd::VacuumTrash {
+            ts: clock.now_secs(),
+        })
+        .await?;
+    match resp {
+        AppliedState::DroppedTables {
+            prev: Some(purged),
+            result: None,
+        } => assert_eq!(2, purged.len()),
+        _ => panic!("expect purged tables, got: {:?}", resp),
+    }
+    assert!(m.get_dropped_tables().is_empty());
+    assert!(!m.table_parts.contains_key(&table_id));
+    assert_eq!(None, m.files().get(&"part_1".to_string())?);
+
+    let resp = m.apply_cmd(&undrop_table).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: None,
+            result: None
+        },
+        resp
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_rename_table() -> anyhow::Result<()> {
+    // - Rename a table with data parts: the id and parts move to the new name.
+    // - Renaming onto a taken name or renaming a missing table does nothing.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+
+    for table_name in ["t1", "t3"] {
+        m.apply_cmd(&Cmd::CreateTable {
+            db_name: "db1".to_string(),
+            table_name: table_name.to_string(),
+            if_not_exists: false,
+            table: Default::default(),
+            seq: None,
+            ts: 0,
+        })
+        .await?;
+    }
+
+    let mut append_res = AppendResult::default();
+    append_res.append_part("part_1", 3, 1, 10, 10);
+    m.append_data_parts("db1", "t1", &append_res, &[]).await?;
+
+    let table_id = m.get_database("db1").unwrap().tables["t1"];
+    let table = m.get_table(&table_id).unwrap();
+    let parts = m.get_data_parts("db1", "t1");
+    assert!(parts.is_some());
+
+    let rename = |table_name: &str, new_table_name: &str| Cmd::RenameTable {
+        db_name: "db1".to_string(),
+        table_name: table_name.to_string(),
+        new_table_name: new_table_name.to_string(),
+    };
+
+    // rename: same table and parts under the new name, meta version bumped
+
+    let ver = m.get_database_meta_ver()?;
+    let resp = m.apply_cmd(&rename("t1", "t2")).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: Some(table.clone()),
+            result: Some(table.clone())
+        },
+        resp
+    );
+    assert_eq!(ver.map(|v| v + 1), m.get_database_meta_ver()?);
+
+    let tables = m.get_database("db1").unwrap().tables;
+    assert!(!tables.contains_key("t1"));
+    assert_eq!(table_id, tables["t2"]);
+    assert_eq!(Some(table.clone()), m.get_table(&table_id));
+    assert_eq!(parts, m.get_data_parts("db1", "t2"));
+    assert!(m.get_data_parts("db1", "t1").is_none());
+
+    // rename onto a taken name: the existent table is returned
+
+    let t3 = m.get_table(&m.get_database("db1").unwrap().tables["t3"]);
+    let ver = m.get_database_meta_ver()?;
+    let resp = m.apply_cmd(&rename("t2", "t3")).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: t3,
+            result: None
+        },
+        resp
+    );
+    assert_eq!(ver, m.get_database_meta_ver()?);
+    assert_eq!(table_id, m.get_database("db1").unwrap().tables["t2"]);
+
+    // rename a missing table
+
+    let resp = m.apply_cmd(&rename("t1", "t4")).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: None,
+            result: None
+        },
+        resp
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_database_usage() -> anyhow::Result<()> {
+    // - Appended parts add to the used bytes of the database.
+    // - Truncate, drop and undrop adjust it, the quota is kept.
+    // - Reconciling fixes a skewed counter.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    for table_name in ["t1", "t2"] {
+        m.apply_cmd(&Cmd::CreateTable {
+            db_name: "db1".to_string(),
+            table_name: table_name.to_string(),
+            if_not_exists: false,
+            table: Default::default(),
+            seq: None,
+            ts: 0,
+        })
+        .await?;
+    }
+
+    let usage = |quota: Option<u64>, used_bytes: u64| DatabaseUsage { quota, used_bytes };
+
+    let resp = m
+        .apply_cmd(&Cmd::SetDatabaseQuota {
+            db_name: "db1".to_string(),
+            quota: Some(100),
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::DatabaseUsage {
+            prev: Some(usage(None, 0)),
+            result: Some(usage(Some(100), 0))
+        },
+        resp
+    );
+
+    let resp = m
+        .apply_cmd(&Cmd::SetDatabaseQuota {
+            db_name: "db2".to_string(),
+            quota: Some(100),
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::DatabaseUsage {
+            prev: None,
+            result: None
+        },
+        resp
+    );
+
+    let mut append_res = AppendResult::default();
+    append_res.append_part("part_1", 3, 1, 10, 10);
+    append_res.append_part("part_2", 3, 1, 20, 20);
+    m.append_data_parts("db1", "t1", &append_res, &[]).await?;
+
+    let mut append_res = AppendResult::default();
+    append_res.append_part("part_3", 3, 1, 40, 40);
+    m.append_data_parts("db1", "t2", &append_res, &[]).await?;
+    assert_eq!(Some(usage(Some(100), 70)), m.get_database_usage("db1")?);
+
+    // an append beyond the quota is rejected, nothing is changed
+
+    let version = m.get_table_data_version("db1", "t2");
+    let mut append_res = AppendResult::default();
+    append_res.append_part("part_4", 3, 1, 40, 40);
+    let res = m.append_data_parts("db1", "t2", &append_res, &[]).await;
+    assert_eq!(ErrorCode::QuotaExceeded("").code(), res.unwrap_err().code());
+    assert_eq!(Some(usage(Some(100), 70)), m.get_database_usage("db1")?);
+    assert_eq!(version, m.get_table_data_version("db1", "t2"));
+    assert_eq!(1, m.get_data_parts("db1", "t2").unwrap().len());
+
+    // truncate
+
+    m.apply_cmd(&Cmd::TruncateTable {
+        db_name: "db1".to_string(),
+        table_name: "t2".to_string(),
+    })
+    .await?;
+    assert_eq!(Some(usage(Some(100), 30)), m.get_database_usage("db1")?);
+
+    // drop and undrop
+
+    m.apply_cmd(&Cmd::DropTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_exists: false,
+        ts: 0,
+    })
+    .await?;
+    assert_eq!(Some(usage(Some(100), 0)), m.get_database_usage("db1")?);
+
+    m.apply_cmd(&Cmd::UndropTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        ts: 0,
+    })
+    .await?;
+    assert_eq!(Some(usage(Some(100), 30)), m.get_database_usage("db1")?);
+
+    // reconcile a skewed counter
+
+    m.database_usages()
+        .insert(&"db1".to_string(), &usage(Some(100), 999))
+        .await?;
+    let resp = m
+        .apply_cmd(&Cmd::ReconcileDatabaseUsage {
+            db_name: "db1".to_string(),
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::DatabaseUsage {
+            prev: Some(usage(Some(100), 999)),
+            result: Some(usage(Some(100), 30))
+        },
+        resp
+    );
+    assert_eq!(
+        vec![("db1".to_string(), usage(Some(100), 30))],
+        m.get_database_usages()?
+    );
+
+    // drop database
+
+    m.apply_cmd(&Cmd::DropDatabase {
+        name: "db1".to_string(),
+        ts: 0,
+    })
+    .await?;
+    assert_eq!(None, m.get_database_usage("db1")?);
+    assert!(m.database_usages().get(&"db1".to_string())?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_modify_table_schema() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    m.apply_cmd(&Cmd::CreateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_not_exists: false,
+        table: Default::default(),
+        seq: None,
+        ts: 0,
+    })
+    .await?;
+
+    let table_id = m.get_database("db1").unwrap().tables["t1"];
+    let table = m.get_table(&table_id).unwrap();
+    let ver = m.get_database_meta_ver()?;
+
+    // modify: the schema is replaced and the meta version is bumped
+
+    let resp = m
+        .apply_cmd(&Cmd::ModifyTableSchema {
+            db_name: "db1".to_string(),
+            table_name: "t1".to_string(),
+            table_id,
+            schema: vec![1, 2, 3],
+        })
+        .await?;
+    let want = Table {
+        schema: vec![1, 2, 3],
+        ..table.clone()
+    };
+    assert_eq!(
+        AppliedState::Table {
+            prev: Some(table.clone()),
+            result: Some(want.clone())
+        },
+        resp
+    );
+    assert_eq!(Some(want.clone()), m.get_table(&table_id));
+    assert_eq!(ver.map(|v| v + 1), m.get_database_meta_ver()?);
+
+    // a stale table id does nothing
+
+    let resp = m
+        .apply_cmd(&Cmd::ModifyTableSchema {
+            db_name: "db1".to_string(),
+            table_name: "t1".to_string(),
+            table_id: table_id + 100,
+            schema: vec![4],
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: None,
+            result: None
+        },
+        resp
+    );
+    assert_eq!(Some(want), m.get_table(&table_id));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_table_data_version() -> anyhow::Result<()> {
+    // - Appending parts or truncating gives the table a greater data version.
+    // - A table created again starts above the versions of the dropped one.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    let create_table = Cmd::CreateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_not_exists: false,
+        table: Default::default(),
+        seq: None,
+        ts: 0,
+    };
+    m.apply_cmd(&create_table).await?;
+    let created = m.get_table_data_version("db1", "t1").unwrap();
+
+    let mut append_res = AppendResult::default();
+    append_res.append_part("part_1", 3, 1, 10, 10);
+    let appended = m
+        .append_data_parts("db1", "t1", &append_res, &[])
+        .await?
+        .unwrap();
+    assert!(appended > created);
+    assert_eq!(Some(appended), m.get_table_data_version("db1", "t1"));
+
+    assert_eq!(
+        None,
+        m.append_data_parts("db1", "t2", &append_res, &[]).await?
+    );
+    assert_eq!(None, m.get_table_data_version("db1", "t2"));
+
+    m.apply_cmd(&Cmd::TruncateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+    })
+    .await?;
+    let truncated = m.get_table_data_version("db1", "t1").unwrap();
+    assert!(truncated > appended);
+
+    m.apply_cmd(&Cmd::DropTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_exists: false,
+        ts: 0,
+    })
+    .await?;
+    m.apply_cmd(&create_table).await?;
+    assert!(m.get_table_data_version("db1", "t1").unwrap() > truncated);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_create_or_replace_table() -> anyhow::Result<()> {
+    // - A seq matching the id of the table replaces it, the replaced one goes to trash.
+    // - A mismatching seq changes nothing, not even the meta version.
+    // - A plain create of an existing table changes nothing.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+
+    let create_table = |seq: Option<MatchSeq>, engine: &str| Cmd::CreateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_not_exists: false,
+        table: Table {
+            table_engine: engine.to_string(),
+            ..Default::default()
+        },
+        seq,
+        ts: 0,
+    };
+
+    // Create if absent: 0 is the id of an absent table.
+    let resp = m
+        .apply_cmd(&create_table(Some(MatchSeq::Exact(0)), "a"))
+        .await?;
+    let created = match resp {
+        AppliedState::Table {
+            prev: None,
+            result: Some(table),
+        } => table,
+        _ => panic!("unexpected {:?}", resp),
+    };
+    let ver = m.get_database_meta_ver()?;
+
+    // A mismatching seq.
+    for seq in [MatchSeq::Exact(0), MatchSeq::Exact(created.table_id + 1)] {
+        let resp = m.apply_cmd(&create_table(Some(seq), "b")).await?;
+        assert_eq!(
+            AppliedState::Table {
+                prev: Some(created.clone()),
+                result: None
+            },
+            resp
+        );
+        assert_eq!(ver, m.get_database_meta_ver()?);
+        assert_eq!(Some(created.clone()), m.get_table(&created.table_id));
+    }
+
+    // A matching seq.
+    let resp = m
+        .apply_cmd(&create_table(Some(MatchSeq::Exact(created.table_id)), "b"))
+        .await?;
+    let replaced = match resp {
+        AppliedState::Table {
+            prev: Some(prev),
+            result: Some(table),
+        } => {
+            assert_eq!(created, prev);
+            table
+        }
+        _ => panic!("unexpected {:?}", resp),
+    };
+    assert!(replaced.table_id > created.table_id);
+    assert_eq!("b", replaced.table_engine);
+    assert_eq!(ver.map(|v| v + 1), m.get_database_meta_ver()?);
+    assert_eq!(
+        replaced.table_id,
+        m.get_database("db1").unwrap().tables["t1"]
+    );
+    assert_eq!(None, m.get_table(&created.table_id));
+    let dropped = m.get_dropped_tables();
+    assert_eq!(1, dropped.len());
+    assert_eq!(created, dropped[0].table);
+
+    // A plain create does not replace.
+    let resp = m.apply_cmd(&create_table(None, "c")).await?;
+    assert_eq!(
+        AppliedState::Table {
+            prev: Some(replaced.clone()),
+            result: Some(replaced.clone())
+        },
+        resp
+    );
+    assert_eq!(ver.map(|v| v + 1), m.get_database_meta_ver()?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_create_tables() -> anyhow::Result<()> {
+    // - The absent tables are created, the meta version is bumped once.
+    // - An existing table and a table of a missing database are left as they are.
+    // - Nothing created, nothing bumped.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    let resp = m
+        .apply_cmd(&Cmd::CreateTable {
+            db_name: "db1".to_string(),
+            table_name: "t2".to_string(),
+            if_not_exists: false,
+            table: Default::default(),
+            seq: None,
+            ts: 0,
+        })
+        .await?;
+    let existing = match resp {
+        AppliedState::Table {
+            result: Some(table),
+            ..
+        } => table,
+        _ => panic!("unexpected {:?}", resp),
+    };
+    let ver = m.get_database_meta_ver()?;
+
+    let create_tables = |names: &[(&str, &str)]| Cmd::CreateTables {
+        tables: names
+            .iter()
+            .map(|(db_name, table_name)| TableToCreate {
+                db_name: db_name.to_string(),
+                table_name: table_name.to_string(),
+                table: Default::default(),
+            })
+            .collect(),
+    };
+
+    let resp = m
+        .apply_cmd(&create_tables(&[
+            ("db1", "t1"),
+            ("db1", "t2"),
+            ("db2", "t1"),
+            ("db1", "t3"),
+        ]))
+        .await?;
+    let (prev, result) = match resp {
+        AppliedState::Tables { prev, result } => (prev, result),
+        _ => panic!("unexpected {:?}", resp),
+    };
+    assert_eq!(vec![None, Some(existing.clone()), None, None], prev);
+    assert_eq!(4, result.len());
+    assert_eq!(Some(existing.clone()), result[1]);
+    assert_eq!(None, result[2]);
+    let db = m.get_database("db1").unwrap();
+    for (i, name) in [(0, "t1"), (3, "t3")] {
+        let created = result[i].clone().unwrap();
+        assert_eq!(created.table_id, db.tables[name]);
+        assert_eq!(Some(created.clone()), m.get_table(&created.table_id));
+    }
+    assert_eq!(ver.map(|v| v + 1), m.get_database_meta_ver()?);
+
+    let resp = m
+        .apply_cmd(&create_tables(&[("db1", "t1"), ("db2", "t1")]))
+        .await?;
+    match resp {
+        AppliedState::Tables { prev, result } => {
+            assert_eq!(prev, result);
+            assert!(result[0].is_some());
+        }
+        _ => panic!("unexpected {:?}", resp),
+    }
+    assert_eq!(ver.map(|v| v + 1), m.get_database_meta_ver()?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_replace_data_parts() -> anyhow::Result<()> {
+    // - The replaced parts are swapped for the appended ones, with their inline bytes dropped.
+    // - A part that is no longer registered rejects the replace, nothing is changed.
+    // - Truncating a table drops the inline bytes of its parts.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    m.apply_cmd(&Cmd::CreateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_not_exists: false,
+        table: Default::default(),
+        seq: None,
+        ts: 0,
+    })
+    .await?;
+
+    let mut inline_res = AppendResult::default();
+    let mut inline_parts = vec![];
+    for name in ["part_1", "part_2"] {
+        inline_res.append_part(name, 1, 1, 10, 10);
+        inline_parts.push((name.to_string(), b"x".to_vec()));
+    }
+    for part in inline_res.parts.iter_mut() {
+        part.storage = PartStorageClass::Inline;
+    }
+    let appended = m
+        .append_data_parts("db1", "t1", &inline_res, &inline_parts)
+        .await?
+        .unwrap();
+    assert_eq!(2, m.list_inline_parts("part_")?.len());
+    assert_eq!(Some(b"x".to_vec()), m.get_inline_part("part_1")?);
+
+    let mut file_res = AppendResult::default();
+    file_res.append_part("part_3", 2, 1, 20, 15);
+    let removed = vec!["part_1".to_string(), "part_2".to_string()];
+    let replaced = m
+        .replace_data_parts("db1", "t1", &removed, &file_res, &[])
+        .await?
+        .unwrap();
+    assert!(replaced > appended);
+
+    let parts = m.get_data_parts("db1", "t1").unwrap();
+    assert_eq!(1, parts.len());
+    assert_eq!("part_3", parts[0].part.name);
+    assert_eq!(PartStorageClass::File, parts[0].storage);
+    assert_eq!(None, m.get_inline_part("part_1")?);
+    assert_eq!(None, m.get_inline_part("part_2")?);
+    assert_eq!(15, m.get_database_usage("db1")?.unwrap().used_bytes);
+
+    // part_1 is gone: rejected.
+    assert_eq!(
+        None,
+        m.replace_data_parts("db1", "t1", &removed, &file_res, &[])
+            .await?
+    );
+    assert_eq!(Some(replaced), m.get_table_data_version("db1", "t1"));
+    assert_eq!(1, m.get_data_parts("db1", "t1").unwrap().len());
+
+    let mut res = AppendResult::default();
+    res.append_part("part_4", 1, 1, 10, 10);
+    res.parts[0].storage = PartStorageClass::Inline;
+    let inline_parts = vec![("part_4".to_string(), b"y".to_vec())];
+
+    // No table, no bytes written.
+    assert_eq!(
+        None,
+        m.append_data_parts("db1", "t2", &res, &inline_parts)
+            .await?
+    );
+    assert_eq!(None, m.get_inline_part("part_4")?);
+
+    m.append_data_parts("db1", "t1", &res, &inline_parts)
+        .await?;
+    m.apply_cmd(&Cmd::TruncateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+    })
+    .await?;
+    assert_eq!(None, m.get_inline_part("part_4")?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_part_bloom_filters() -> anyhow::Result<()> {
+    // - The bloom filters of the appended parts are kept apart from their descriptors.
+    // - The filters of the replaced parts and of a truncated table are dropped.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut m = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    m.apply_cmd(&Cmd::CreateDatabase {
+        name: "db1".to_string(),
+        if_not_exists: false,
+        db: Default::default(),
+    })
+    .await?;
+    m.apply_cmd(&Cmd::CreateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+        if_not_exists: false,
+        table: Default::default(),
+        seq: None,
+        ts: 0,
+    })
+    .await?;
+
+    let mut filters = PartBloomFilters::create();
+    let mut filter = BloomFilter::with_fpp(1, 0.01);
+    filter.add(b"a");
+    filters.columns.insert("c".to_string(), filter);
+
+    let mut res = AppendResult::default();
+    res.append_part("part_1", 1, 1, 10, 10);
+    res.append_part("part_2", 1, 1, 10, 10);
+    res.parts[0].bloom_filters = Some(filters.clone());
+    m.append_data_parts("db1", "t1", &res, &[]).await?;
+
+    let parts = m.get_data_parts("db1", "t1").unwrap();
+    assert!(parts.iter().all(|p| p.bloom_filters.is_none()));
+    assert_eq!(Some(filters.clone()), m.get_bloom_filters("part_1")?);
+    assert_eq!(None, m.get_bloom_filters("part_2")?);
+
+    let mut merged = AppendResult::default();
+    merged.append_part("part_3", 2, 1, 20, 20);
+    merged.parts[0].bloom_filters = Some(filters.clone());
+    let removed = vec!["part_1".to_string(), "part_2".to_string()];
+    m.replace_data_parts("db1", "t1", &removed, &merged, &[])
+        .await?
+        .unwrap();
+    assert_eq!(None, m.get_bloom_filters("part_1")?);
+    assert_eq!(Some(filters), m.get_bloom_filters("part_3")?);
+
+    m.apply_cmd(&Cmd::TruncateTable {
+        db_name: "db1".to_string(),
+        table_name: "t1".to_string(),
+    })
+    .await?;
+    assert_eq!(None, m.get_bloom_filters("part_3")?);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_state_machine_apply_non_dup_generic_kv_upsert_get() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_meta_ut!();
@@ -610,6 +1657,630 @@ async fn test_state_machine_apply_non_dup_generic_kv_delete() -> anyhow::Result<
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_delete_prefix_kv() -> anyhow::Result<()> {
+    // - Deleting a prefix removes all of its records, the expired ones are not counted.
+    // - The records under the other prefix and the seq of the generic-kv are untouched.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    for i in 0..50 {
+        let prefix = if i % 2 == 0 { "a/" } else { "b/" };
+        // 5 of the records under a/ are expired.
+        let expire_at = if i % 10 == 0 { Some(now - 1) } else { None };
+        sm.apply_cmd(&Cmd::UpsertKV {
+            key: format!("{}{}", prefix, i),
+            seq: MatchSeq::Any,
+            value: Some(b"x".to_vec()).into(),
+            value_meta: Some(KVMeta { expire_at }),
+        })
+        .await?;
+    }
+
+    let resp = sm
+        .apply_cmd(&Cmd::DeletePrefixKV {
+            prefix: "a/".to_string(),
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::KVCount {
+            prev: Some(20),
+            result: Some(0)
+        },
+        resp
+    );
+    assert!(sm.prefix_list_kv("a/")?.is_empty());
+    assert!(sm.kvs().scan_prefix(&"a/".to_string())?.is_empty());
+    assert_eq!(25, sm.prefix_list_kv("b/")?.len());
+
+    // Nothing left to delete.
+    let resp = sm
+        .apply_cmd(&Cmd::DeletePrefixKV {
+            prefix: "a/".to_string(),
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::KVCount {
+            prev: Some(0),
+            result: Some(0)
+        },
+        resp
+    );
+
+    // The seq goes on from the last upsert.
+    let resp = sm
+        .apply_cmd(&Cmd::UpsertKV {
+            key: "c".to_string(),
+            seq: MatchSeq::Any,
+            value: Some(b"x".to_vec()).into(),
+            value_meta: None,
+        })
+        .await?;
+    match resp {
+        AppliedState::KV { result, .. } => assert_eq!(Some(51), result.map(|(seq, _)| seq)),
+        _ => panic!("expect AppliedState::KV, got: {:?}", resp),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_prefix_list_kv_page_stops_at_limit() -> anyhow::Result<()> {
+    // A page of a large prefix reads no further than the first record after the page.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    for i in 0..1000 {
+        sm.apply_cmd(&Cmd::UpsertKV {
+            key: format!("a/{:04}", i),
+            seq: MatchSeq::Any,
+            value: Some(b"x".to_vec()).into(),
+            value_meta: None,
+        })
+        .await?;
+    }
+
+    let ops = sm.sm_tree.read_ops();
+    let page = sm.prefix_list_kv_page("a/", Some(10), None)?;
+    assert_eq!(10, page.entries.len());
+    assert!(page.more);
+    assert_eq!(11, sm.sm_tree.read_ops() - ops);
+
+    let ops = sm.sm_tree.read_ops();
+    let page = sm.prefix_list_kv_page("a/", Some(10), Some("a/0989"))?;
+    assert_eq!(10, page.entries.len());
+    assert!(!page.more);
+    assert_eq!(10, sm.sm_tree.read_ops() - ops);
+
+    assert_eq!(1000, sm.prefix_count_kv("a/")?);
+    assert_eq!(100, sm.prefix_count_kv("a/09")?);
+    assert_eq!(0, sm.prefix_count_kv("b/")?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_prefix_list_kv_reply_cap() -> anyhow::Result<()> {
+    // - A list reply with more entries or bytes than the config allows fails.
+    // - The same records can be listed in pages that fit.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.kv_reply_max_entries = 10;
+    tc.config.meta_config.kv_reply_max_bytes = 1024;
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    for i in 0..20 {
+        sm.apply_cmd(&Cmd::UpsertKV {
+            key: format!("a/{:02}", i),
+            seq: MatchSeq::Any,
+            value: Some(b"x".to_vec()).into(),
+            value_meta: None,
+        })
+        .await?;
+    }
+    sm.apply_cmd(&Cmd::UpsertKV {
+        key: "b/big".to_string(),
+        seq: MatchSeq::Any,
+        value: Some(vec![0; 2048]).into(),
+        value_meta: None,
+    })
+    .await?;
+
+    let too_large = ErrorCode::ReplyTooLarge("").code();
+
+    let err = sm.prefix_list_kv("a/").unwrap_err();
+    assert_eq!(too_large, err.code());
+    assert_eq!(
+        "the reply exceeds 10 entries or 1024 bytes, list it in pages with a limit",
+        err.message()
+    );
+    assert_eq!(
+        too_large,
+        sm.prefix_list_kv_page("a/", None, None).unwrap_err().code()
+    );
+    assert_eq!(too_large, sm.prefix_list_kv("b/").unwrap_err().code());
+
+    let first = sm.prefix_list_kv_page("a/", Some(10), None)?;
+    assert_eq!(10, first.entries.len());
+    let second = sm.prefix_list_kv_page("a/", Some(10), Some(&first.entries[9].0))?;
+    assert_eq!(10, second.entries.len());
+    assert!(!second.more);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_delete_prefix_kv_in_chunks() -> anyhow::Result<()> {
+    // Deleting a large prefix reads every record once and removes them a chunk at a time.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let n = 2 * DELETE_PREFIX_KV_CHUNK + 100;
+    for i in 0..n {
+        sm.apply_cmd(&Cmd::UpsertKV {
+            key: format!("a/{}", i),
+            seq: MatchSeq::Any,
+            value: Some(b"x".to_vec()).into(),
+            value_meta: None,
+        })
+        .await?;
+    }
+    sm.apply_cmd(&Cmd::UpsertKV {
+        key: "b/0".to_string(),
+        seq: MatchSeq::Any,
+        value: Some(b"x".to_vec()).into(),
+        value_meta: None,
+    })
+    .await?;
+
+    let (reads, writes) = (sm.sm_tree.read_ops(), sm.sm_tree.write_ops());
+    let resp = sm
+        .apply_cmd(&Cmd::DeletePrefixKV {
+            prefix: "a/".to_string(),
+        })
+        .await?;
+    assert_eq!(
+        AppliedState::KVCount {
+            prev: Some(n),
+            result: Some(0)
+        },
+        resp
+    );
+    assert_eq!(n as u64, sm.sm_tree.read_ops() - reads);
+    assert_eq!(3, sm.sm_tree.write_ops() - writes);
+
+    assert!(sm.kvs().scan_prefix(&"a/".to_string())?.is_empty());
+    assert_eq!(1, sm.prefix_list_kv("b/")?.len());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_non_dup_generic_kv_merge() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let meta = Some(KVMeta {
+        expire_at: Some(now + 10),
+    });
+
+    let append = |v: &str, max_len: Option<u64>| MergeOp::AppendBytes {
+        value: v.as_bytes().to_vec(),
+        max_len,
+    };
+
+    let merge = |key: &str, seq: MatchSeq, op: MergeOp, value_meta: Option<KVMeta>| Cmd::MergeKV {
+        key: key.to_string(),
+        seq,
+        op,
+        value_meta,
+    };
+
+    tracing::info!("--- append to a nonexistent key creates it");
+
+    let resp = sm
+        .apply_cmd(&merge(
+            "list",
+            MatchSeq::Any,
+            append("ab", None),
+            meta.clone(),
+        ))
+        .await?;
+    let (prev, result) = match resp {
+        AppliedState::KV { prev, result } => (prev, result),
+        _ => panic!("expect AppliedState::KV"),
+    };
+    assert_eq!(None, prev);
+    let (seq, got) = result.unwrap();
+    assert_eq!(b"ab".to_vec(), got.value);
+    assert_eq!(meta, got.meta);
+
+    tracing::info!("--- append with seq not matching does nothing");
+
+    let resp = sm
+        .apply_cmd(&merge(
+            "list",
+            MatchSeq::Exact(seq + 1),
+            append("cd", None),
+            None,
+        ))
+        .await?;
+    match resp {
+        AppliedState::KV { prev, result } => assert_eq!(prev, result),
+        _ => panic!("expect AppliedState::KV"),
+    }
+
+    tracing::info!("--- append keeps the meta if no meta is specified");
+
+    sm.apply_cmd(&merge(
+        "list",
+        MatchSeq::Exact(seq),
+        append("cd", None),
+        None,
+    ))
+    .await?;
+    let got = sm.get_kv("list")?.unwrap();
+    assert!(got.0 > seq);
+    assert_eq!(b"abcd".to_vec(), got.1.value);
+    assert_eq!(meta, got.1.meta);
+
+    tracing::info!("--- trim at the boundary");
+
+    // exactly max_len: nothing is trimmed
+    sm.apply_cmd(&merge("list", MatchSeq::Any, append("ef", Some(6)), None))
+        .await?;
+    assert_eq!(b"abcdef".to_vec(), sm.get_kv("list")?.unwrap().1.value);
+
+    // one byte over max_len: the oldest byte is trimmed
+    sm.apply_cmd(&merge("list", MatchSeq::Any, append("g", Some(6)), None))
+        .await?;
+    assert_eq!(b"bcdefg".to_vec(), sm.get_kv("list")?.unwrap().1.value);
+
+    tracing::info!("--- a specified meta replaces the current one");
+
+    let new_meta = Some(KVMeta {
+        expire_at: Some(now + 20),
+    });
+    sm.apply_cmd(&merge(
+        "list",
+        MatchSeq::Any,
+        append("h", Some(6)),
+        new_meta.clone(),
+    ))
+    .await?;
+    let got = sm.get_kv("list")?.unwrap();
+    assert_eq!(b"cdefgh".to_vec(), got.1.value);
+    assert_eq!(new_meta, got.1.meta);
+
+    tracing::info!("--- set-if-absent on a nonexistent key creates it");
+
+    let resp = sm
+        .apply_cmd(&merge(
+            "absent",
+            MatchSeq::Any,
+            MergeOp::SetIfAbsentBytes(b"x".to_vec()),
+            None,
+        ))
+        .await?;
+    let result = match resp {
+        AppliedState::KV { prev, result } => {
+            assert_eq!(None, prev);
+            result
+        }
+        _ => panic!("expect AppliedState::KV"),
+    };
+    assert_eq!(b"x".to_vec(), result.unwrap().1.value);
+
+    tracing::info!("--- set-if-absent on an existent key leaves it as is");
+
+    let before = sm.get_kv("absent")?;
+    let resp = sm
+        .apply_cmd(&merge(
+            "absent",
+            MatchSeq::Any,
+            MergeOp::SetIfAbsentBytes(b"y".to_vec()),
+            None,
+        ))
+        .await?;
+    assert_eq!(
+        AppliedState::KV {
+            prev: before.clone(),
+            result: before.clone(),
+        },
+        resp
+    );
+    assert_eq!(before, sm.get_kv("absent")?);
+
+    tracing::info!("--- a named merge that can not be applied leaves the value as is");
+
+    let before = sm.get_kv("absent")?;
+    let resp = sm
+        .apply_cmd(&merge(
+            "absent",
+            MatchSeq::Any,
+            MergeOp::Named {
+                name: "add_u64".to_string(),
+                arg: b"1".to_vec(),
+            },
+            None,
+        ))
+        .await?;
+    match resp {
+        AppliedState::KVMergeRejected { prev, reason } => {
+            assert_eq!(before, prev);
+            assert!(!reason.is_empty());
+        }
+        _ => panic!("expect AppliedState::KVMergeRejected"),
+    }
+    assert_eq!(before, sm.get_kv("absent")?);
+
+    sm.apply_cmd(&merge(
+        "counter",
+        MatchSeq::Any,
+        MergeOp::Named {
+            name: "add_u64".to_string(),
+            arg: b"2".to_vec(),
+        },
+        None,
+    ))
+    .await?;
+    assert_eq!(b"2".to_vec(), sm.get_kv("counter")?.unwrap().1.value);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_non_dup_generic_kv_patch_json() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let meta = Some(KVMeta {
+        expire_at: Some(now + 10),
+    });
+
+    let add = |path: &str, value: serde_json::Value| JsonPatchOp::Add {
+        path: path.to_string(),
+        value,
+    };
+
+    let patch = |key: &str, seq: MatchSeq, patch: Vec<JsonPatchOp>| Cmd::PatchKVJson {
+        key: key.to_string(),
+        seq,
+        patch,
+    };
+
+    let doc = |v: &SeqValue<KVValue>| -> serde_json::Value {
+        serde_json::from_slice(&v.1.value).unwrap()
+    };
+
+    tracing::info!("--- patch a nonexistent key with seq 0 creates it from an empty object");
+
+    let resp = sm
+        .apply_cmd(&patch("conf", MatchSeq::Exact(0), vec![add(
+            "/a",
+            serde_json::json!(1),
+        )]))
+        .await?;
+    let (prev, result) = match resp {
+        AppliedState::KV { prev, result } => (prev, result),
+        _ => panic!("expect AppliedState::KV"),
+    };
+    assert_eq!(None, prev);
+    let result = result.unwrap();
+    assert_eq!(serde_json::json!({"a": 1}), doc(&result));
+
+    tracing::info!("--- patch with seq not matching does nothing");
+
+    let resp = sm
+        .apply_cmd(&patch("conf", MatchSeq::Exact(0), vec![add(
+            "/a",
+            serde_json::json!(2),
+        )]))
+        .await?;
+    match resp {
+        AppliedState::KV { prev, result } => assert_eq!(prev, result),
+        _ => panic!("expect AppliedState::KV"),
+    }
+
+    tracing::info!("--- patch keeps the meta of the value");
+
+    sm.apply_cmd(&Cmd::UpsertKV {
+        key: "conf".to_string(),
+        seq: MatchSeq::Any,
+        value: Operation::AsIs,
+        value_meta: meta.clone(),
+    })
+    .await?;
+    sm.apply_cmd(&patch("conf", MatchSeq::Any, vec![add(
+        "/b",
+        serde_json::json!({"c": true}),
+    )]))
+    .await?;
+    let got = sm.get_kv("conf")?.unwrap();
+    assert_eq!(serde_json::json!({"a": 1, "b": {"c": true}}), doc(&got));
+    assert_eq!(meta, got.1.meta);
+
+    tracing::info!("--- a pointer to a missing member rejects the whole patch");
+
+    let before = sm.get_kv("conf")?;
+    let resp = sm
+        .apply_cmd(&patch("conf", MatchSeq::Any, vec![
+            add("/d", serde_json::json!(1)),
+            JsonPatchOp::Remove {
+                path: "/x".to_string(),
+            },
+        ]))
+        .await?;
+    match resp {
+        AppliedState::KVPatchRejected { prev, .. } => assert_eq!(before, prev),
+        _ => panic!("expect AppliedState::KVPatchRejected"),
+    }
+    assert_eq!(before, sm.get_kv("conf")?);
+
+    tracing::info!("--- a patch to a non-json value is rejected");
+
+    sm.apply_cmd(&Cmd::UpsertKV {
+        key: "raw".to_string(),
+        seq: MatchSeq::Any,
+        value: Operation::Update(b"not json".to_vec()),
+        value_meta: None,
+    })
+    .await?;
+    let before = sm.get_kv("raw")?;
+    let resp = sm
+        .apply_cmd(&patch("raw", MatchSeq::Any, vec![add(
+            "/a",
+            serde_json::json!(1),
+        )]))
+        .await?;
+    match resp {
+        AppliedState::KVPatchRejected { reason, .. } => {
+            assert!(reason.contains("not a json document"), "got: {}", reason)
+        }
+        _ => panic!("expect AppliedState::KVPatchRejected"),
+    }
+    assert_eq!(before, sm.get_kv("raw")?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_non_dup_generic_kv_cas() -> anyhow::Result<()> {
+    // - Expecting absent creates an absent key, but not an existent one.
+    // - Expecting a value updates or deletes the key only if it has that value.
+    // - An expired value is absent.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_test_context();
+    let mut sm = StateMachine::open(&tc.config.meta_config, 1).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let cas = |key: &str, expect: Option<&[u8]>, value: Operation<Vec<u8>>| Cmd::CasKV {
+        key: key.to_string(),
+        expect: expect.map(|v| v.to_vec()),
+        value,
+        value_meta: None,
+    };
+    let kv = |resp: AppliedState| match resp {
+        AppliedState::KV { prev, result } => (prev, result),
+        _ => panic!("expect AppliedState::KV"),
+    };
+    let value = |sv: &Option<SeqValue<KVValue>>| sv.as_ref().map(|(_, v)| v.value.clone());
+
+    tracing::info!("--- expect absent");
+
+    let (prev, result) = kv(sm
+        .apply_cmd(&cas("leader", None, Operation::Update(b"n1".to_vec())))
+        .await?);
+    assert_eq!(None, prev);
+    assert_eq!(Some(b"n1".to_vec()), value(&result));
+
+    let (prev, result) = kv(sm
+        .apply_cmd(&cas("leader", None, Operation::Update(b"n2".to_vec())))
+        .await?);
+    assert_eq!(prev, result, "an existent key is not absent");
+    assert_eq!(Some(b"n1".to_vec()), value(&sm.get_kv("leader")?));
+
+    tracing::info!("--- expect a value");
+
+    let (prev, result) = kv(sm
+        .apply_cmd(&cas(
+            "leader",
+            Some(b"n2"),
+            Operation::Update(b"n3".to_vec()),
+        ))
+        .await?);
+    assert_eq!(prev, result, "a different value does not match");
+    assert_eq!(Some(b"n1".to_vec()), value(&result));
+
+    let (prev, result) = kv(sm
+        .apply_cmd(&cas(
+            "leader",
+            Some(b"n1"),
+            Operation::Update(b"n2".to_vec()),
+        ))
+        .await?);
+    assert_eq!(Some(b"n1".to_vec()), value(&prev));
+    assert_eq!(Some(b"n2".to_vec()), value(&result));
+    assert!(result.unwrap().0 > prev.unwrap().0);
+
+    let (prev, result) = kv(sm
+        .apply_cmd(&cas("leader", Some(b"n2"), Operation::Delete))
+        .await?);
+    assert_eq!(Some(b"n2".to_vec()), value(&prev));
+    assert_eq!(None, result);
+    assert_eq!(None, sm.get_kv("leader")?);
+
+    tracing::info!("--- an expired value is absent");
+
+    sm.apply_cmd(&Cmd::UpsertKV {
+        key: "lease".to_string(),
+        seq: MatchSeq::Any,
+        value: Operation::Update(b"n1".to_vec()),
+        value_meta: Some(KVMeta {
+            expire_at: Some(now - 1),
+        }),
+    })
+    .await?;
+
+    let (prev, result) = kv(sm
+        .apply_cmd(&cas(
+            "lease",
+            Some(b"n1"),
+            Operation::Update(b"n2".to_vec()),
+        ))
+        .await?);
+    assert_eq!(None, prev);
+    assert_eq!(None, result, "an expired value does not match");
+
+    let (prev, result) = kv(sm
+        .apply_cmd(&cas("lease", None, Operation::Update(b"n2".to_vec())))
+        .await?);
+    assert_eq!(None, prev);
+    assert_eq!(Some(b"n2".to_vec()), value(&result));
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_state_machine_apply_add_file() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_meta_ut!();
@@ -701,8 +2372,6 @@ async fn test_state_machine_snapshot() -> anyhow::Result<()> {
 
     let (logs, want) = snapshot_logs();
     // TODO(xp): following logs are not saving to sled yet:
-    //           database
-    //           table
     //           slots
     //           replication
 
@@ -0,0 +1,86 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::ops::Range;
+
+use byteorder::BigEndian;
+use byteorder::ByteOrder;
+use common_exception::ErrorCode;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use serde::Deserialize;
+use serde::Serialize;
+use sled::IVec;
+
+use crate::sled_store::SledOrderedSerde;
+use crate::sled_store::SledSerde;
+
+/// The key of a data part in sled::Tree: the id of its table then its name, thus the parts of a
+/// table are adjacent and a part is written or removed alone.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TablePartKey {
+    pub table_id: u64,
+    pub part_name: String,
+}
+
+impl TablePartKey {
+    pub fn new(table_id: u64, part_name: &str) -> Self {
+        TablePartKey {
+            table_id,
+            part_name: part_name.to_string(),
+        }
+    }
+
+    /// The keys of the parts of a table.
+    pub fn table_range(table_id: u64) -> Range<TablePartKey> {
+        TablePartKey::new(table_id, "")..TablePartKey::new(table_id + 1, "")
+    }
+}
+
+impl fmt::Display for TablePartKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.table_id, self.part_name)
+    }
+}
+
+impl SledOrderedSerde for TablePartKey {
+    fn ser(&self) -> Result<IVec, ErrorCode> {
+        let mut buf = vec![0; 8];
+        BigEndian::write_u64(&mut buf, self.table_id);
+        buf.extend_from_slice(self.part_name.as_bytes());
+        Ok(buf.into())
+    }
+
+    fn de<V: AsRef<[u8]>>(v: V) -> Result<Self, ErrorCode>
+    where Self: Sized {
+        let slice = v.as_ref();
+        if slice.len() < 8 {
+            return Err(ErrorCode::MetaStoreDamaged("invalid table part key IVec"));
+        }
+        Ok(TablePartKey {
+            table_id: BigEndian::read_u64(&slice[..8]),
+            part_name: String::from_utf8(slice[8..].to_vec())?,
+        })
+    }
+}
+
+/// A data part as kept in sled::Tree, with the seq it is registered at. The parts of a table are
+/// loaded in the order of their seqs, the order they are appended in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TablePart {
+    pub seq: u64,
+    pub part: DataPartInfo,
+}
+
+impl SledSerde for TablePart {}
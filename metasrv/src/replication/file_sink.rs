@@ -0,0 +1,66 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::io::Write;
+
+use crate::meta_service::LogIndex;
+use crate::raft::change_feed::ChangeEvent;
+use crate::replication::ReplicationSink;
+
+/// FileSink appends the changes to a file in NDJSON, one change per line. It is mainly for testing.
+///
+/// The changes already in the file are skipped, thus a change is written exactly once.
+pub struct FileSink {
+    file: File,
+    /// The id of the last change in the file.
+    last_id: LogIndex,
+}
+
+impl FileSink {
+    pub fn try_create(path: &str) -> common_exception::Result<FileSink> {
+        let last_id = match fs::read_to_string(path) {
+            Ok(content) => match content.lines().rev().find(|line| !line.is_empty()) {
+                None => 0,
+                Some(line) => serde_json::from_str::<ChangeEvent>(line)?.id,
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileSink { file, last_id })
+    }
+}
+
+#[async_trait::async_trait]
+impl ReplicationSink for FileSink {
+    async fn ship(&mut self, changes: &[ChangeEvent]) -> common_exception::Result<()> {
+        let mut buf = vec![];
+        for change in changes.iter().filter(|c| c.id > self.last_id) {
+            serde_json::to_writer(&mut buf, change)?;
+            buf.push(b'\n');
+        }
+
+        if let Some(last) = changes.last() {
+            self.file.write_all(&buf)?;
+            self.file.sync_data()?;
+            self.last_id = self.last_id.max(last.id);
+        }
+        Ok(())
+    }
+}
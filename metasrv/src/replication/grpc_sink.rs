@@ -0,0 +1,78 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::prelude::ErrorCode;
+use common_exception::prelude::ToErrorCode;
+use tonic::transport::Channel;
+
+use crate::meta_service::NodeId;
+use crate::raft::change_feed::ChangeEvent;
+use crate::replication::ReplicationSink;
+use crate::replication_protobuf::replication_sink_client::ReplicationSinkClient;
+use crate::replication_protobuf::Change;
+use crate::replication_protobuf::ChangeBatch;
+
+/// GrpcSink pushes the changes in batches to a user provided service that implements `ReplicationSink` in replication.proto.
+pub struct GrpcSink {
+    node_id: NodeId,
+    addr: String,
+    /// Connected on demand and reset on error, to reconnect for the next batch.
+    client: Option<ReplicationSinkClient<Channel>>,
+}
+
+impl GrpcSink {
+    pub fn create(node_id: NodeId, addr: &str) -> GrpcSink {
+        GrpcSink {
+            node_id,
+            addr: addr.to_string(),
+            client: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ReplicationSink for GrpcSink {
+    async fn ship(&mut self, changes: &[ChangeEvent]) -> common_exception::Result<()> {
+        let mut batch = ChangeBatch {
+            node_id: self.node_id,
+            changes: Vec::with_capacity(changes.len()),
+        };
+        for change in changes {
+            batch.changes.push(Change {
+                id: change.id,
+                applied_at: change.applied_at,
+                cmd: serde_json::to_string(&change.cmd)?,
+            });
+        }
+
+        let client = match self.client {
+            Some(ref mut client) => client,
+            None => {
+                let addr = format!("http://{}", self.addr);
+                let client = ReplicationSinkClient::connect(addr)
+                    .await
+                    .map_err_to_code(ErrorCode::CannotConnectNode, || {
+                        format!("fail to connect replication sink {}", self.addr)
+                    })?;
+                self.client.insert(client)
+            }
+        };
+
+        if let Err(status) = client.ship(batch).await {
+            self.client = None;
+            return Err(status.into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! replication ships the committed meta data changes to an external sink, e.g., an audit system.
+
+mod file_sink;
+mod grpc_sink;
+mod replicator;
+mod sink;
+
+#[cfg(test)]
+mod replicator_test;
+
+pub use file_sink::FileSink;
+pub use grpc_sink::GrpcSink;
+pub use replicator::Replicator;
+pub use replicator::METRIC_REPLICATION_LAG_EVENTS;
+pub use replicator::METRIC_REPLICATION_LAG_SECONDS;
+pub use sink::create_sink;
+pub use sink::ReplicationSink;
@@ -0,0 +1,119 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_runtime::tokio;
+use common_runtime::tokio::sync::watch;
+use common_tracing::tracing;
+use metrics::gauge;
+
+use crate::meta_service::MetaRaftStore;
+use crate::raft::change_feed::ChangeFeed;
+use crate::replication::ReplicationSink;
+
+pub static METRIC_REPLICATION_LAG_EVENTS: &str = "replication.lag_events";
+pub static METRIC_REPLICATION_LAG_SECONDS: &str = "replication.lag_seconds";
+
+/// The max time to wait for new changes, before checking the persisted feed again.
+const IDLE_WAIT: Duration = Duration::from_secs(1);
+const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Replicator ships the changes in the change feed to a sink, in order and at least once.
+///
+/// The cursor, i.e., the id of the last shipped change, is persisted only after the sink accepts a batch.
+/// Thus after a restart it resumes from the cursor without a gap.
+pub struct Replicator {
+    sto: Arc<MetaRaftStore>,
+    sink: Box<dyn ReplicationSink>,
+    batch_size: usize,
+}
+
+impl Replicator {
+    pub fn create(
+        sto: Arc<MetaRaftStore>,
+        sink: Box<dyn ReplicationSink>,
+        batch_size: usize,
+    ) -> Replicator {
+        Replicator {
+            sto,
+            sink,
+            batch_size: batch_size.max(1),
+        }
+    }
+
+    /// Ship changes until `running_rx` receives a signal.
+    pub async fn run(
+        mut self,
+        mut running_rx: watch::Receiver<()>,
+    ) -> common_exception::Result<()> {
+        let feed = &self.sto.change_feed;
+        let mut cursor = feed.read_cursor()?;
+        let mut report = tokio::time::interval(REPORT_INTERVAL);
+
+        loop {
+            let changes = feed.next_changes(cursor, self.batch_size)?;
+
+            if changes.is_empty() {
+                tokio::select! {
+                    _ = running_rx.changed() => return Ok(()),
+                    _ = report.tick() => report_lag(feed),
+                    _ = feed.wait_changes(IDLE_WAIT) => {}
+                }
+                continue;
+            }
+
+            // A slow sink must not stop the lag from being reported.
+            let shipped = {
+                let ship = self.sink.ship(&changes);
+                tokio::pin!(ship);
+                loop {
+                    tokio::select! {
+                        _ = running_rx.changed() => return Ok(()),
+                        _ = report.tick() => report_lag(feed),
+                        res = &mut ship => break res,
+                    }
+                }
+            };
+
+            match shipped {
+                Ok(()) => {
+                    cursor = changes[changes.len() - 1].id;
+                    feed.commit_cursor(cursor).await?;
+                }
+                Err(e) => {
+                    tracing::warn!("fail to ship changes after {}: {}, retry later", cursor, e);
+                    tokio::select! {
+                        _ = running_rx.changed() => return Ok(()),
+                        _ = tokio::time::sleep(RETRY_INTERVAL) => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn report_lag(feed: &ChangeFeed) {
+    match feed.lag() {
+        Ok(lag) => {
+            gauge!(METRIC_REPLICATION_LAG_EVENTS, lag.events as f64);
+            gauge!(METRIC_REPLICATION_LAG_SECONDS, lag.duration.as_secs_f64());
+        }
+        Err(e) => {
+            tracing::warn!("fail to get replication lag: {}", e);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_metatypes::Database;
+use common_metatypes::MatchSeq;
+use common_runtime::tokio;
+use common_tracing::tracing;
+use pretty_assertions::assert_eq;
+
+use crate::meta_service::Cmd;
+use crate::meta_service::LogEntry;
+use crate::meta_service::MetaNode;
+use crate::raft::change_feed::ChangeEvent;
+use crate::replication_protobuf::replication_sink_server;
+use crate::replication_protobuf::replication_sink_server::ReplicationSinkServer;
+use crate::replication_protobuf::ChangeBatch;
+use crate::replication_protobuf::ChangeBatchAck;
+use crate::tests::service::new_test_context;
+use crate::tests::service::next_port;
+
+fn create_db(name: &str) -> Cmd {
+    Cmd::CreateDatabase {
+        name: name.to_string(),
+        if_not_exists: false,
+        db: Database::default(),
+    }
+}
+
+fn upsert_kv(key: &str) -> Cmd {
+    Cmd::UpsertKV {
+        key: key.to_string(),
+        seq: MatchSeq::Any,
+        value: Some(b"v".to_vec()).into(),
+        value_meta: None,
+    }
+}
+
+fn update_kv(key: &str, seq: u64) -> Cmd {
+    Cmd::UpsertKV {
+        key: key.to_string(),
+        seq: MatchSeq::Exact(seq),
+        value: Some(b"v2".to_vec()).into(),
+        value_meta: None,
+    }
+}
+
+async fn write(mn: &MetaNode, cmd: Cmd) -> anyhow::Result<()> {
+    mn.write(LogEntry { txid: None, cmd }).await?;
+    Ok(())
+}
+
+fn read_changes(path: &str) -> anyhow::Result<Vec<ChangeEvent>> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let mut changes = vec![];
+    for line in content.lines() {
+        changes.push(serde_json::from_str(line)?);
+    }
+    Ok(changes)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_replication_file_sink_across_restart() -> anyhow::Result<()> {
+    // - Write changes with a file sink, restart the node before all of them are shipped.
+    // - Write more changes after restart.
+    // - Check every change is in the file exactly once, in order.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("changes.ndjson");
+    let path = path.to_str().unwrap().to_string();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.replication_sink = "file".to_string();
+    tc.config.meta_config.replication_sink_target = path.clone();
+    tc.config.meta_config.replication_batch_size = 2;
+    let mc = &tc.config.meta_config;
+
+    let want = vec![
+        create_db("db1"),
+        upsert_kv("k1"),
+        create_db("db2"),
+        upsert_kv("k2"),
+        upsert_kv("k1"),
+    ];
+
+    tracing::info!("--- write before restart");
+    {
+        let mn = MetaNode::boot(0, mc).await?;
+        MetaNode::start_replication(mn.clone(), mc).await?;
+
+        for cmd in want[..3].iter() {
+            write(&mn, cmd.clone()).await?;
+        }
+        mn.stop().await?;
+    }
+
+    tracing::info!("--- write after restart");
+    {
+        let mn = MetaNode::open(mc).await?;
+        MetaNode::start_replication(mn.clone(), mc).await?;
+
+        for cmd in want[3..].iter() {
+            write(&mn, cmd.clone()).await?;
+        }
+
+        wait_shipped(&mn).await?;
+        mn.stop().await?;
+    }
+
+    tracing::info!("--- check the shipped changes");
+    {
+        let changes = read_changes(&path)?;
+
+        let ids = changes.iter().map(|c| c.id).collect::<Vec<_>>();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]), "ids: {:?}", ids);
+
+        let got = changes
+            .into_iter()
+            .map(|c| c.cmd)
+            .filter(|cmd| !matches!(cmd, Cmd::AddNode { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(want, got);
+    }
+
+    Ok(())
+}
+
+async fn wait_shipped(mn: &MetaNode) -> anyhow::Result<()> {
+    let started = Instant::now();
+    while mn.sto.change_feed.lag()?.events > 0 {
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "timeout shipping"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_replication_skip_unchanged() -> anyhow::Result<()> {
+    // A rejected command or one that changes nothing is not shipped.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("changes.ndjson");
+    let path = path.to_str().unwrap().to_string();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.replication_sink = "file".to_string();
+    tc.config.meta_config.replication_sink_target = path.clone();
+    let mc = &tc.config.meta_config;
+
+    let mn = MetaNode::boot(0, mc).await?;
+    MetaNode::start_replication(mn.clone(), mc).await?;
+
+    let cmds = vec![
+        create_db("db1"),
+        create_db("db1"),
+        upsert_kv("k1"),
+        update_kv("k1", 5),
+        update_kv("k1", 1),
+        Cmd::DropDatabase {
+            name: "db2".to_string(),
+            ts: 0,
+        },
+    ];
+    for cmd in cmds.iter() {
+        write(&mn, cmd.clone()).await?;
+    }
+    wait_shipped(&mn).await?;
+    mn.stop().await?;
+
+    let got = read_changes(&path)?
+        .into_iter()
+        .map(|c| c.cmd)
+        .filter(|cmd| !matches!(cmd, Cmd::AddNode { .. }))
+        .collect::<Vec<_>>();
+    let want = vec![create_db("db1"), upsert_kv("k1"), update_kv("k1", 1)];
+    assert_eq!(want, got);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_replication_other_node() -> anyhow::Result<()> {
+    // A node that is not the configured replication node neither feeds nor ships the changes.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("changes.ndjson");
+    let path = path.to_str().unwrap().to_string();
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.replication_sink = "file".to_string();
+    tc.config.meta_config.replication_sink_target = path.clone();
+    tc.config.meta_config.replication_node_id = 1;
+    let mc = &tc.config.meta_config;
+
+    let mn = MetaNode::boot(0, mc).await?;
+    MetaNode::start_replication(mn.clone(), mc).await?;
+
+    write(&mn, create_db("db1")).await?;
+    write(&mn, upsert_kv("k1")).await?;
+
+    assert_eq!(0, mn.sto.change_feed.lag()?.events);
+    mn.stop().await?;
+
+    assert!(read_changes(&path)?.is_empty());
+
+    Ok(())
+}
+
+struct SlowSink {
+    delay: Duration,
+    shipped: Arc<AtomicU64>,
+}
+
+#[tonic::async_trait]
+impl replication_sink_server::ReplicationSink for SlowSink {
+    async fn ship(
+        &self,
+        request: tonic::Request<ChangeBatch>,
+    ) -> Result<tonic::Response<ChangeBatchAck>, tonic::Status> {
+        tokio::time::sleep(self.delay).await;
+        let n = request.into_inner().changes.len();
+        self.shipped.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(tonic::Response::new(ChangeBatchAck {}))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_replication_slow_grpc_sink() -> anyhow::Result<()> {
+    // A slow sink falls behind, but it does not slow down the writes.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let sink_addr = format!("127.0.0.1:{}", next_port());
+    let shipped = Arc::new(AtomicU64::new(0));
+    {
+        let sink = SlowSink {
+            delay: Duration::from_millis(500),
+            shipped: shipped.clone(),
+        };
+        let addr = sink_addr.parse()?;
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(ReplicationSinkServer::new(sink))
+                .serve(addr),
+        );
+    }
+
+    let mut tc = new_test_context();
+    tc.config.meta_config.replication_sink = "grpc".to_string();
+    tc.config.meta_config.replication_sink_target = sink_addr;
+    tc.config.meta_config.replication_batch_size = 1;
+    let mc = &tc.config.meta_config;
+
+    let mn = MetaNode::boot(0, mc).await?;
+    MetaNode::start_replication(mn.clone(), mc).await?;
+
+    let started = Instant::now();
+    for i in 0..20 {
+        write(&mn, upsert_kv(&format!("k{}", i))).await?;
+    }
+    // Shipping the changes one by one takes more than 10 seconds.
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let lag = mn.sto.change_feed.lag()?;
+    assert!(lag.events >= 15, "lag: {:?}", lag);
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let lag2 = mn.sto.change_feed.lag()?;
+    assert!(lag2.duration > lag.duration, "lag: {:?} {:?}", lag, lag2);
+    assert!(lag2.duration >= Duration::from_secs(1));
+    assert!(lag2.events > 0);
+    assert!(shipped.load(Ordering::Relaxed) < 21);
+
+    mn.stop().await?;
+    Ok(())
+}
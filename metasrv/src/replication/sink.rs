@@ -0,0 +1,44 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+
+use crate::configs;
+use crate::meta_service::NodeId;
+use crate::raft::change_feed::ChangeEvent;
+use crate::replication::FileSink;
+use crate::replication::GrpcSink;
+
+/// A sink receives the committed changes in order.
+#[async_trait::async_trait]
+pub trait ReplicationSink: Send {
+    /// Ship a batch of changes.
+    /// A batch may be shipped again if it fails or the node restarts before the cursor is persisted.
+    async fn ship(&mut self, changes: &[ChangeEvent]) -> common_exception::Result<()>;
+}
+
+pub fn create_sink(
+    node_id: NodeId,
+    config: &configs::MetaConfig,
+) -> common_exception::Result<Box<dyn ReplicationSink>> {
+    let target = &config.replication_sink_target;
+    match config.replication_sink.as_str() {
+        "file" => Ok(Box::new(FileSink::try_create(target)?)),
+        "grpc" => Ok(Box::new(GrpcSink::create(node_id, target))),
+        sink => Err(ErrorCode::InvalidConfig(format!(
+            "Unknown replication sink: {}, expect grpc or file",
+            sink
+        ))),
+    }
+}
@@ -23,13 +23,21 @@ pub use sled_serde::SledOrderedSerde;
 pub use sled_serde::SledSerde;
 pub use sled_tree::AsKeySpace;
 pub use sled_tree::SledTree;
+pub use sled_tree::SledTreeSnapshot;
 pub use sled_tree::SledValueToKey;
+pub use space_stats::list_space_stats;
+pub use space_stats::recount_space_stats;
+pub use space_stats::SpaceStats;
+pub use space_stats::SpaceStatsItem;
+pub use space_stats::METRIC_SLED_SPACE_BYTES;
+pub use space_stats::METRIC_SLED_SPACE_ENTRIES;
 
 pub mod db;
 pub mod seq_num;
 pub mod sled_key_space;
 pub mod sled_serde;
 pub mod sled_tree;
+pub mod space_stats;
 
 #[cfg(test)]
 mod sled_tree_test;
@@ -21,21 +21,31 @@ use std::ops::RangeBounds;
 
 use async_raft::raft::Entry;
 use common_exception::ErrorCode;
+use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
+use common_metatypes::DroppedTable;
 use common_metatypes::KVValue;
 use common_metatypes::SeqValue;
+use common_metatypes::Table;
+use common_store_api_sdk::storage_api_impl::PartBloomFilters;
 use sled::IVec;
 
 use crate::meta_service::LogEntry;
 use crate::meta_service::LogIndex;
 use crate::meta_service::NodeId;
+use crate::raft::change_feed::ChangeEvent;
 use crate::raft::state::RaftStateKey;
 use crate::raft::state::RaftStateValue;
 use crate::raft::state_machine::Node;
+use crate::raft::state_machine::Snapshot;
 use crate::raft::state_machine::StateMachineMetaKey;
 use crate::raft::state_machine::StateMachineMetaValue;
+use crate::raft::state_machine::TablePart;
+use crate::raft::state_machine::TablePartKey;
 use crate::sled_store::SeqNum;
 use crate::sled_store::SledOrderedSerde;
 use crate::sled_store::SledSerde;
+use crate::sled_store::SpaceStats;
 
 /// Defines a key space in sled::Tree that has its own key value type.
 /// And a prefix that is used to distinguish keys from different spaces in a SledTree.
@@ -177,3 +187,123 @@ impl SledKeySpace for Sequences {
     type K = String;
     type V = SeqNum;
 }
+
+/// Key-Value Types for the committed changes to replicate in sled::Tree:
+pub struct Changes {}
+impl SledKeySpace for Changes {
+    const PREFIX: u8 = 8;
+    const NAME: &'static str = "changes";
+    type K = LogIndex;
+    type V = ChangeEvent;
+}
+
+/// Key-Value Types for the replication cursor in sled::Tree, i.e., the id of the last shipped change:
+pub struct ReplicationCursors {}
+impl SledKeySpace for ReplicationCursors {
+    const PREFIX: u8 = 9;
+    const NAME: &'static str = "replication-cursors";
+    type K = String;
+    type V = SeqNum;
+}
+
+/// Key-Value Types for the quota and the used bytes of a database in sled::Tree, keyed by database name:
+pub struct DatabaseUsages {}
+impl SledKeySpace for DatabaseUsages {
+    const PREFIX: u8 = 10;
+    const NAME: &'static str = "database-usages";
+    type K = String;
+    type V = DatabaseUsage;
+}
+
+/// Key-Value Types for the last snapshot of the state machine in sled::Tree:
+pub struct Snapshots {}
+impl SledKeySpace for Snapshots {
+    const PREFIX: u8 = 11;
+    const NAME: &'static str = "snapshots";
+    type K = String;
+    type V = Snapshot;
+}
+
+/// Key-Value Types for the bytes of the data parts kept inline in sled::Tree, keyed by part name:
+pub struct InlineParts {}
+impl SledKeySpace for InlineParts {
+    const PREFIX: u8 = 12;
+    const NAME: &'static str = "inline-parts";
+    type K = String;
+    type V = Vec<u8>;
+}
+
+/// Key-Value Types for the databases in sled::Tree, keyed by database name:
+pub struct Databases {}
+impl SledKeySpace for Databases {
+    const PREFIX: u8 = 13;
+    const NAME: &'static str = "databases";
+    type K = String;
+    type V = Database;
+}
+
+/// Key-Value Types for the tables in sled::Tree, keyed by table id:
+pub struct Tables {}
+impl SledKeySpace for Tables {
+    const PREFIX: u8 = 14;
+    const NAME: &'static str = "tables";
+    type K = u64;
+    type V = Table;
+}
+
+/// Key-Value Types for the data parts in sled::Tree, keyed by table id and part name:
+pub struct TableParts {}
+impl SledKeySpace for TableParts {
+    const PREFIX: u8 = 15;
+    const NAME: &'static str = "table-parts";
+    type K = TablePartKey;
+    type V = TablePart;
+}
+
+/// Key-Value Types for the dropped tables in sled::Tree, keyed by table id:
+pub struct Trash {}
+impl SledKeySpace for Trash {
+    const PREFIX: u8 = 16;
+    const NAME: &'static str = "trash";
+    type K = u64;
+    type V = DroppedTable;
+}
+
+/// Key-Value Types for the persisted accounting of the other key spaces in sled::Tree, keyed by key space name:
+pub struct SpaceStatsKV {}
+impl SledKeySpace for SpaceStatsKV {
+    const PREFIX: u8 = 17;
+    const NAME: &'static str = "space-stats";
+    type K = String;
+    type V = SpaceStats;
+}
+
+/// Key-Value Types for the bloom filters of the data parts in sled::Tree, keyed by part name:
+pub struct BloomFilters {}
+impl SledKeySpace for BloomFilters {
+    const PREFIX: u8 = 18;
+    const NAME: &'static str = "bloom-filters";
+    type K = String;
+    type V = PartBloomFilters;
+}
+
+/// The prefix and the name of every key space that is accounted, i.e., all but `SpaceStatsKV`.
+pub const ACCOUNTED_KEY_SPACES: &[(u8, &str)] = &[
+    (Logs::PREFIX, Logs::NAME),
+    (Nodes::PREFIX, Nodes::NAME),
+    (StateMachineMeta::PREFIX, StateMachineMeta::NAME),
+    (RaftStateKV::PREFIX, RaftStateKV::NAME),
+    (Files::PREFIX, Files::NAME),
+    (GenericKV::PREFIX, GenericKV::NAME),
+    (Sequences::PREFIX, Sequences::NAME),
+    (Changes::PREFIX, Changes::NAME),
+    (ReplicationCursors::PREFIX, ReplicationCursors::NAME),
+    (DatabaseUsages::PREFIX, DatabaseUsages::NAME),
+    (Snapshots::PREFIX, Snapshots::NAME),
+    (InlineParts::PREFIX, InlineParts::NAME),
+    (Databases::PREFIX, Databases::NAME),
+    (Tables::PREFIX, Tables::NAME),
+    (TableParts::PREFIX, TableParts::NAME),
+    (Trash::PREFIX, Trash::NAME),
+    (BloomFilters::PREFIX, BloomFilters::NAME),
+];
@@ -16,18 +16,69 @@ use std::fmt::Display;
 use std::marker::PhantomData;
 use std::ops::Bound;
 use std::ops::RangeBounds;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 use common_exception::ErrorCode;
 use common_exception::ToErrorCode;
+use common_infallible::RwLock;
 use common_tracing::tracing;
+use sled::IVec;
 
 use crate::sled_store::sled_key_space::SledKeySpace;
+use crate::sled_store::sled_key_space::SpaceStatsKV;
+use crate::sled_store::space_stats::space_prefix;
+use crate::sled_store::space_stats::AccountedBatch;
+use crate::sled_store::space_stats::SpaceAccounting;
+use crate::sled_store::space_stats::StatsDelta;
+use crate::sled_store::SpaceStats;
 
 /// Extract key from a value of sled tree that includes its key.
 pub trait SledValueToKey<K> {
     fn to_key(&self) -> K;
 }
 
+/// A copy of all the kvs of a SledTree at a single point in time, in key order.
+/// See `SledTree::export_snapshot`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SledTreeSnapshot {
+    kvs: Vec<(IVec, IVec)>,
+}
+
+impl SledTreeSnapshot {
+    pub fn len(&self) -> usize {
+        self.kvs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kvs.is_empty()
+    }
+
+    /// Retrieve the value of a key of key space `KV` as it was when the snapshot was taken.
+    pub fn get<KV: SledKeySpace>(&self, key: &KV::K) -> common_exception::Result<Option<KV::V>> {
+        let k = KV::serialize_key(key)?;
+        match self.kvs.binary_search_by(|(x, _)| x.cmp(&k)) {
+            Ok(i) => Ok(Some(KV::deserialize_value(&self.kvs[i].1)?)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Iterate the raw kvs of all key spaces, e.g. to serialize them into a snapshot blob.
+    pub fn iter(&self) -> impl Iterator<Item = &(IVec, IVec)> {
+        self.kvs.iter()
+    }
+}
+
+impl IntoIterator for SledTreeSnapshot {
+    type Item = (IVec, IVec);
+    type IntoIter = std::vec::IntoIter<(IVec, IVec)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.kvs.into_iter()
+    }
+}
+
 /// SledTree is a wrapper of sled::Tree that provides access of more than one key-value
 /// types.
 /// A `SledKVType` defines a key-value type to be stored.
@@ -44,6 +95,22 @@ pub struct SledTree {
     /// See: https://github.com/drmingdrmer/sledtest/blob/500929ab0b89afe547143a38fde6fe85d88f1f80/src/ben_sync.rs
     sync: bool,
 
+    /// The number of write operations, shared by the clones of this SledTree.
+    write_ops: Arc<AtomicU64>,
+
+    /// The number of write operations that asked for a flush, shared by the clones of this SledTree.
+    flush_ops: Arc<AtomicU64>,
+
+    /// The number of records read by iterating, shared by the clones of this SledTree.
+    read_ops: Arc<AtomicU64>,
+
+    /// The entries and bytes of every key space, shared by the SledTrees opened on the same tree.
+    accounting: Arc<SpaceAccounting>,
+
+    /// The writes hold it shared, an export holds it exclusively to copy the tree at one point in
+    /// time. Shared by the clones of this SledTree.
+    export_gate: Arc<RwLock<()>>,
+
     pub(crate) tree: sled::Tree,
 }
 
@@ -68,14 +135,54 @@ impl SledTree {
 
         tracing::debug!("SledTree opened tree: {}", tree_name);
 
+        let name = format!("{}", tree_name);
+        let accounting = SpaceAccounting::open(&name, &t)?;
+
         let rl = SledTree {
-            name: format!("{}", tree_name),
+            name,
             sync,
+            write_ops: Arc::new(AtomicU64::new(0)),
+            flush_ops: Arc::new(AtomicU64::new(0)),
+            read_ops: Arc::new(AtomicU64::new(0)),
+            accounting,
+            export_gate: Arc::new(RwLock::new(())),
             tree: t,
         };
         Ok(rl)
     }
 
+    /// Returns the number of write operations since this SledTree is opened.
+    pub fn write_ops(&self) -> u64 {
+        self.write_ops.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of write operations that asked for a flush since this SledTree is opened.
+    pub fn flush_ops(&self) -> u64 {
+        self.flush_ops.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of records read by iterating since this SledTree is opened.
+    pub fn read_ops(&self) -> u64 {
+        self.read_ops.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of entries and the approximate bytes of keys and values of a key space,
+    /// `None` if there is no such key space.
+    pub fn space_stats(&self, name: &str) -> Option<SpaceStats> {
+        space_prefix(name).map(|prefix| self.accounting.get(prefix))
+    }
+
+    /// Returns the stats of the non-empty key spaces.
+    pub fn list_space_stats(&self) -> Vec<(&'static str, SpaceStats)> {
+        self.accounting.list()
+    }
+
+    /// Counts the key spaces by scanning the tree, to repair the drift of the stats,
+    /// e.g. after a crash or after the tree is written without SledTree.
+    pub fn recount_space_stats(&self) -> common_exception::Result<()> {
+        self.accounting.recount()
+    }
+
     /// Borrows the SledTree and creates a wrapper with access limited to a specified key space `KV`.
     pub fn key_space<KV: SledKeySpace>(&self) -> AsKeySpace<KV> {
         AsKeySpace::<KV> {
@@ -101,6 +208,7 @@ impl SledTree {
         &self,
         key: &KV::K,
         mut f: F,
+        flush: bool,
     ) -> common_exception::Result<Option<KV::V>>
     where
         F: FnMut(Option<KV::V>) -> Option<KV::V>,
@@ -108,18 +216,32 @@ impl SledTree {
         let mes = || format!("update_and_fetch: {}", key);
 
         let k = KV::serialize_key(key)?;
+        let key_len = k.len();
 
+        // sled retries the update on conflict, the sizes of the last try are the applied ones.
+        let mut sizes = (None, None);
+
+        let gate = self.export_gate.read();
         let res = self
             .tree
-            .update_and_fetch(k, move |old| {
+            .update_and_fetch(k, |old| {
+                let old_len = old.map(|o| o.len());
                 let old = old.map(|o| KV::deserialize_value(o).unwrap());
 
                 let new_val = f(old);
-                new_val.map(|new_val| KV::serialize_value(&new_val).unwrap())
+                let new_val = new_val.map(|new_val| KV::serialize_value(&new_val).unwrap());
+
+                sizes = (old_len, new_val.as_ref().map(|v| v.len()));
+                new_val
             })
             .map_err_to_code(ErrorCode::MetaStoreDamaged, mes)?;
+        drop(gate);
 
-        self.flush_async(true).await?;
+        let mut delta = StatsDelta::default();
+        delta.update(key_len, sizes.0, sizes.1);
+        self.accounting.apply(KV::PREFIX, delta);
+
+        self.flush_async(flush).await?;
 
         let value = match res {
             None => None,
@@ -178,10 +300,19 @@ impl SledTree {
     where
         KV: SledKeySpace,
     {
-        let removed = self
-            .tree
-            .remove(KV::serialize_key(key)?)
-            .map_err_to_code(ErrorCode::MetaStoreDamaged, || format!("removed: {}", key,))?;
+        let k = KV::serialize_key(key)?;
+        let key_len = k.len();
+
+        let removed = {
+            let _gate = self.export_gate.read();
+            self.tree
+                .remove(k)
+                .map_err_to_code(ErrorCode::MetaStoreDamaged, || format!("removed: {}", key,))?
+        };
+
+        let mut delta = StatsDelta::default();
+        delta.update(key_len, removed.as_ref().map(|v| v.len()), None);
+        self.accounting.apply(KV::PREFIX, delta);
 
         self.flush_async(flush).await?;
 
@@ -201,6 +332,7 @@ impl SledTree {
         R: RangeBounds<KV::K>,
     {
         let mut batch = sled::Batch::default();
+        let mut delta = StatsDelta::default();
 
         // Convert K range into sled::IVec range
         let sled_range = KV::serialize_range(&range)?;
@@ -208,17 +340,110 @@ impl SledTree {
         let range_mes = self.range_message::<KV, _>(&range);
 
         for item in self.tree.range(sled_range) {
-            let (k, _) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+            let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
                 format!("range_remove: {}", range_mes,)
             })?;
+            delta.update(k.len(), Some(v.len()), None);
             batch.remove(k);
         }
 
-        self.tree
-            .apply_batch(batch)
+        self.apply_batch(batch)
             .map_err_to_code(ErrorCode::MetaStoreDamaged, || {
                 format!("batch remove: {}", range_mes,)
             })?;
+        self.accounting.apply(KV::PREFIX, delta);
+
+        self.flush_async(flush).await?;
+
+        Ok(())
+    }
+
+    /// Delete kvs that are in `range` in batches of at most `batch_size` kvs, so that removing a
+    /// large range does not build one huge batch.
+    /// Unlike `range_remove` it is not atomic: a crash may leave a part of the range, the beginning
+    /// of the range is removed first.
+    ///
+    /// Returns the number of removed kvs.
+    #[tracing::instrument(level = "debug", skip(self, range))]
+    pub async fn range_remove_in_batches<KV, R>(
+        &self,
+        range: R,
+        batch_size: usize,
+        flush: bool,
+    ) -> common_exception::Result<u64>
+    where
+        KV: SledKeySpace,
+        R: RangeBounds<KV::K>,
+    {
+        let batch_size = batch_size.max(1);
+        let mut removed: u64 = 0;
+
+        // Convert K range into sled::IVec range
+        let (mut start, end) = KV::serialize_range(&range)?;
+
+        let range_mes = self.range_message::<KV, _>(&range);
+
+        loop {
+            let mut batch = sled::Batch::default();
+            let mut delta = StatsDelta::default();
+            let mut last_key = None;
+            let mut batch_len = 0;
+
+            let it = self.tree.range((start.clone(), end.clone()));
+            for item in it.take(batch_size) {
+                let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                    format!("range_remove_in_batches: {}", range_mes,)
+                })?;
+                delta.update(k.len(), Some(v.len()), None);
+                batch.remove(k.clone());
+                last_key = Some(k);
+                batch_len += 1;
+            }
+
+            let last_key = match last_key {
+                None => break,
+                Some(last_key) => last_key,
+            };
+
+            self.apply_batch(batch)
+                .map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                    format!("batch remove: {}", range_mes,)
+                })?;
+            self.accounting.apply(KV::PREFIX, delta);
+
+            self.flush_async(flush).await?;
+
+            removed += batch_len;
+            if batch_len < batch_size as u64 {
+                break;
+            }
+            start = Bound::Excluded(last_key);
+        }
+
+        Ok(removed)
+    }
+
+    /// Delete `keys` in one batch, either all of them are removed or none is.
+    #[tracing::instrument(level = "debug", skip(self, keys))]
+    pub async fn remove_keys<KV>(
+        &self,
+        keys: &[KV::K],
+        flush: bool,
+    ) -> common_exception::Result<()>
+    where
+        KV: SledKeySpace,
+    {
+        let mut batch = AccountedBatch::new(&self.tree);
+        for key in keys {
+            batch.remove(KV::serialize_key(key)?)?;
+        }
+
+        let (batch, delta) = batch.into_parts();
+        self.apply_batch(batch)
+            .map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                format!("batch remove: {} keys", keys.len())
+            })?;
+        self.accounting.apply(KV::PREFIX, delta);
 
         self.flush_async(flush).await?;
 
@@ -241,6 +466,7 @@ impl SledTree {
             let (k, _) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
                 format!("range_get: {}", range_mes,)
             })?;
+            self.read_ops.fetch_add(1, Ordering::Relaxed);
 
             let key = KV::deserialize_key(k)?;
             res.push(key);
@@ -265,6 +491,7 @@ impl SledTree {
             let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
                 format!("range_get: {}", range_mes,)
             })?;
+            self.read_ops.fetch_add(1, Ordering::Relaxed);
 
             let key = KV::deserialize_key(k)?;
             let value = KV::deserialize_value(v)?;
@@ -290,11 +517,13 @@ impl SledTree {
         // Convert K range into sled::IVec range
         let range = KV::serialize_range(&range)?;
 
+        let read_ops = self.read_ops.clone();
         let it = self.tree.range(range);
         let it = it.map(move |item| {
             let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
                 format!("range_get: {}", range_mes,)
             })?;
+            read_ops.fetch_add(1, Ordering::Relaxed);
 
             let key = KV::deserialize_key(k)?;
             let value = KV::deserialize_value(v)?;
@@ -308,20 +537,131 @@ impl SledTree {
     /// Get key-valuess in with the same prefix
     pub fn scan_prefix<KV>(&self, prefix: &KV::K) -> common_exception::Result<Vec<(KV::K, KV::V)>>
     where KV: SledKeySpace {
-        let mut res = vec![];
+        self.scan_prefix_iter::<KV>(prefix)?.collect()
+    }
 
-        let mes = || format!("scan_prefix: {}", prefix);
+    /// Iterate key-values with the same prefix in key order.
+    ///
+    /// A record is read from sled only when the iterator is advanced to it.
+    pub fn scan_prefix_iter<KV>(
+        &self,
+        prefix: &KV::K,
+    ) -> common_exception::Result<impl Iterator<Item = common_exception::Result<(KV::K, KV::V)>>>
+    where
+        KV: SledKeySpace,
+    {
+        let mes = format!("scan_prefix: {}", prefix);
 
         let pref = KV::serialize_key(prefix)?;
-        for item in self.tree.scan_prefix(pref) {
-            let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, mes)?;
+
+        let read_ops = self.read_ops.clone();
+        let it = self.tree.scan_prefix(pref).map(move |item| {
+            let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || mes.clone())?;
+            read_ops.fetch_add(1, Ordering::Relaxed);
 
             let key = KV::deserialize_key(k)?;
             let value = KV::deserialize_value(v)?;
-            res.push((key, value));
+
+            Ok((key, value))
+        });
+
+        Ok(it)
+    }
+
+    /// Iterate key-values with the same prefix in key order, or all of the key space if `prefix` is
+    /// None.
+    ///
+    /// Unlike `scan_prefix_iter()`, an entry that fails decoding is yielded as an error with its raw
+    /// key and the scan goes on, so that the caller can log and skip the corrupt entries.
+    pub fn scan_lenient<KV>(
+        &self,
+        prefix: Option<&KV::K>,
+    ) -> common_exception::Result<impl Iterator<Item = common_exception::Result<(KV::K, KV::V)>>>
+    where
+        KV: SledKeySpace,
+    {
+        let read_ops = self.read_ops.clone();
+        let it = self.raw_scan::<KV>(prefix)?.map(move |item| {
+            let (k, v) = item?;
+            read_ops.fetch_add(1, Ordering::Relaxed);
+
+            Self::decode_entry::<KV>(&k, v)
+        });
+
+        Ok(it)
+    }
+
+    /// Delete the entries with the same prefix, or of all of the key space if `prefix` is None,
+    /// that fail decoding. Returns the number of the deleted entries.
+    #[tracing::instrument(level = "debug", skip(self, prefix))]
+    pub async fn repair<KV>(
+        &self,
+        prefix: Option<&KV::K>,
+        flush: bool,
+    ) -> common_exception::Result<u64>
+    where
+        KV: SledKeySpace,
+    {
+        let mut batch = AccountedBatch::new(&self.tree);
+        let mut n = 0;
+
+        for item in self.raw_scan::<KV>(prefix)? {
+            let (k, v) = item?;
+            self.read_ops.fetch_add(1, Ordering::Relaxed);
+
+            if let Err(e) = Self::decode_entry::<KV>(&k, v) {
+                tracing::warn!("repair: remove {}", e);
+                batch.remove(k)?;
+                n += 1;
+            }
         }
 
-        Ok(res)
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let (batch, delta) = batch.into_parts();
+        self.apply_batch(batch)
+            .map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                format!("batch remove: {} undecodable keys", n)
+            })?;
+        self.accounting.apply(KV::PREFIX, delta);
+
+        self.flush_async(flush).await?;
+
+        Ok(n)
+    }
+
+    /// Iterate the raw key-values with the same prefix, or of all of the key space.
+    fn raw_scan<KV>(
+        &self,
+        prefix: Option<&KV::K>,
+    ) -> common_exception::Result<impl Iterator<Item = common_exception::Result<(IVec, IVec)>>>
+    where
+        KV: SledKeySpace,
+    {
+        let pref = match prefix {
+            Some(prefix) => KV::serialize_key(prefix)?,
+            None => IVec::from(&[KV::PREFIX]),
+        };
+        let mes = format!("scan: {}:{:?}", KV::NAME, prefix);
+
+        let it = self
+            .tree
+            .scan_prefix(pref)
+            .map(move |item| item.map_err_to_code(ErrorCode::MetaStoreDamaged, || mes.clone()));
+
+        Ok(it)
+    }
+
+    fn decode_entry<KV>(k: &IVec, v: IVec) -> common_exception::Result<(KV::K, KV::V)>
+    where KV: SledKeySpace {
+        let decoded = KV::deserialize_key(k).and_then(|key| {
+            let value = KV::deserialize_value(v)?;
+            Ok((key, value))
+        });
+
+        decoded.map_err(|e| e.add_message(format!("undecodable entry {}:{:?}", KV::NAME, k)))
     }
 
     /// Get values of key in `range`
@@ -341,6 +681,7 @@ impl SledTree {
             let (_, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
                 format!("range_get: {}", range_mes,)
             })?;
+            self.read_ops.fetch_add(1, Ordering::Relaxed);
 
             let ent = KV::deserialize_value(v)?;
             res.push(ent);
@@ -352,18 +693,19 @@ impl SledTree {
     /// Append many key-values into SledTree.
     pub async fn append<KV>(&self, kvs: &[(KV::K, KV::V)]) -> common_exception::Result<()>
     where KV: SledKeySpace {
-        let mut batch = sled::Batch::default();
+        let mut batch = AccountedBatch::new(&self.tree);
 
         for (key, value) in kvs.iter() {
             let k = KV::serialize_key(key)?;
             let v = KV::serialize_value(value)?;
 
-            batch.insert(k, v);
+            batch.insert(k, v)?;
         }
 
-        self.tree
-            .apply_batch(batch)
+        let (batch, delta) = batch.into_parts();
+        self.apply_batch(batch)
             .map_err_to_code(ErrorCode::MetaStoreDamaged, || "batch append")?;
+        self.accounting.apply(KV::PREFIX, delta);
 
         self.flush_async(true).await?;
 
@@ -378,7 +720,7 @@ impl SledTree {
         KV: SledKeySpace,
         KV::V: SledValueToKey<KV::K>,
     {
-        let mut batch = sled::Batch::default();
+        let mut batch = AccountedBatch::new(&self.tree);
 
         for value in values.iter() {
             let key: KV::K = value.to_key();
@@ -386,12 +728,13 @@ impl SledTree {
             let k = KV::serialize_key(&key)?;
             let v = KV::serialize_value(value)?;
 
-            batch.insert(k, v);
+            batch.insert(k, v)?;
         }
 
-        self.tree
-            .apply_batch(batch)
+        let (batch, delta) = batch.into_parts();
+        self.apply_batch(batch)
             .map_err_to_code(ErrorCode::MetaStoreDamaged, || "batch append_values")?;
+        self.accounting.apply(KV::PREFIX, delta);
 
         self.flush_async(true).await?;
 
@@ -411,13 +754,20 @@ impl SledTree {
     {
         let k = KV::serialize_key(key)?;
         let v = KV::serialize_value(value)?;
+        let (key_len, value_len) = (k.len(), v.len());
 
-        let prev = self
-            .tree
-            .insert(k, v)
-            .map_err_to_code(ErrorCode::MetaStoreDamaged, || {
-                format!("insert_value {}", key)
-            })?;
+        let prev = {
+            let _gate = self.export_gate.read();
+            self.tree
+                .insert(k, v)
+                .map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                    format!("insert_value {}", key)
+                })?
+        };
+
+        let mut delta = StatsDelta::default();
+        delta.update(key_len, prev.as_ref().map(|x| x.len()), Some(value_len));
+        self.accounting.apply(KV::PREFIX, delta);
 
         let prev = match prev {
             None => None,
@@ -440,6 +790,72 @@ impl SledTree {
         self.insert::<KV>(&key, value).await
     }
 
+    /// Copy all the kvs of the tree at a single point in time: the writes through this SledTree
+    /// and its clones are held off while copying.
+    /// A write that is not through SledTree, e.g. to the `tree` directly, is not isolated.
+    pub fn export_snapshot(&self) -> common_exception::Result<SledTreeSnapshot> {
+        let _gate = self.export_gate.write();
+
+        let mut kvs = Vec::new();
+        for item in self.tree.iter() {
+            let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                format!("export_snapshot: {}", self.name)
+            })?;
+            kvs.push((k, v));
+        }
+        self.read_ops.fetch_add(kvs.len() as u64, Ordering::Relaxed);
+
+        Ok(SledTreeSnapshot { kvs })
+    }
+
+    /// Replace all the kvs of the tree with `kvs`, e.g. the ones of an exported snapshot.
+    /// The removal of the present kvs and the insertion are applied in one batch: a reader sees
+    /// either the old kvs or the new ones.
+    /// The space stats are not imported but recounted.
+    #[tracing::instrument(level = "debug", skip(self, kvs))]
+    pub async fn import_snapshot<I, K, V>(&self, kvs: I) -> common_exception::Result<()>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<IVec>,
+        V: Into<IVec>,
+    {
+        let mut batch = sled::Batch::default();
+        {
+            let _gate = self.export_gate.write();
+
+            for item in self.tree.iter().keys() {
+                let k = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                    format!("import_snapshot: {}", self.name)
+                })?;
+                if k.first() != Some(&SpaceStatsKV::PREFIX) {
+                    batch.remove(k);
+                }
+            }
+            for (k, v) in kvs {
+                let k = k.into();
+                if k.first() != Some(&SpaceStatsKV::PREFIX) {
+                    batch.insert(k, v);
+                }
+            }
+
+            self.tree
+                .apply_batch(batch)
+                .map_err_to_code(ErrorCode::MetaStoreDamaged, || "batch import_snapshot")?;
+        }
+
+        // The stats of every key space are changed.
+        self.recount_space_stats()?;
+
+        self.flush_async(true).await?;
+
+        Ok(())
+    }
+
+    fn apply_batch(&self, batch: sled::Batch) -> sled::Result<()> {
+        let _gate = self.export_gate.read();
+        self.tree.apply_batch(batch)
+    }
+
     /// Build a string describing the range for a range operation.
     fn range_message<KV, R>(&self, range: &R) -> String
     where
@@ -457,6 +873,15 @@ impl SledTree {
 
     #[tracing::instrument(level = "debug", skip(self))]
     async fn flush_async(&self, flush: bool) -> common_exception::Result<()> {
+        // Every write operation ends with a flush_async().
+        self.write_ops.fetch_add(1, Ordering::Relaxed);
+        if flush {
+            self.flush_ops.fetch_add(1, Ordering::Relaxed);
+        }
+
+        // The changed stats are persisted with the fsync of the write.
+        self.accounting.written(flush && self.sync)?;
+
         if flush && self.sync {
             self.tree
                 .flush_async()
@@ -474,6 +899,11 @@ pub struct AsKeySpace<'a, KV: SledKeySpace> {
 }
 
 impl<'a, KV: SledKeySpace> AsKeySpace<'a, KV> {
+    /// Returns the number of entries and the approximate bytes of keys and values of this key space.
+    pub fn stats(&self) -> SpaceStats {
+        self.inner.accounting.get(KV::PREFIX)
+    }
+
     pub fn contains_key(&self, key: &KV::K) -> common_exception::Result<bool> {
         self.inner.contains_key::<KV>(key)
     }
@@ -482,11 +912,12 @@ impl<'a, KV: SledKeySpace> AsKeySpace<'a, KV> {
         &self,
         key: &KV::K,
         f: F,
+        flush: bool,
     ) -> common_exception::Result<Option<KV::V>>
     where
         F: FnMut(Option<KV::V>) -> Option<KV::V>,
     {
-        self.inner.update_and_fetch::<KV, _>(key, f).await
+        self.inner.update_and_fetch::<KV, _>(key, f, flush).await
     }
 
     pub fn get(&self, key: &KV::K) -> common_exception::Result<Option<KV::V>> {
@@ -510,6 +941,24 @@ impl<'a, KV: SledKeySpace> AsKeySpace<'a, KV> {
         self.inner.range_remove::<KV, R>(range, flush).await
     }
 
+    pub async fn range_remove_in_batches<R>(
+        &self,
+        range: R,
+        batch_size: usize,
+        flush: bool,
+    ) -> common_exception::Result<u64>
+    where
+        R: RangeBounds<KV::K>,
+    {
+        self.inner
+            .range_remove_in_batches::<KV, R>(range, batch_size, flush)
+            .await
+    }
+
+    pub async fn remove_keys(&self, keys: &[KV::K], flush: bool) -> common_exception::Result<()> {
+        self.inner.remove_keys::<KV>(keys, flush).await
+    }
+
     pub fn range_keys<R>(&self, range: R) -> common_exception::Result<Vec<KV::K>>
     where R: RangeBounds<KV::K> {
         self.inner.range_keys::<KV, R>(range)
@@ -536,6 +985,30 @@ impl<'a, KV: SledKeySpace> AsKeySpace<'a, KV> {
         self.inner.scan_prefix::<KV>(prefix)
     }
 
+    pub fn scan_prefix_iter(
+        &self,
+        prefix: &KV::K,
+    ) -> common_exception::Result<impl Iterator<Item = common_exception::Result<(KV::K, KV::V)>>>
+    {
+        self.inner.scan_prefix_iter::<KV>(prefix)
+    }
+
+    pub fn scan_lenient(
+        &self,
+        prefix: Option<&KV::K>,
+    ) -> common_exception::Result<impl Iterator<Item = common_exception::Result<(KV::K, KV::V)>>>
+    {
+        self.inner.scan_lenient::<KV>(prefix)
+    }
+
+    pub async fn repair(
+        &self,
+        prefix: Option<&KV::K>,
+        flush: bool,
+    ) -> common_exception::Result<u64> {
+        self.inner.repair::<KV>(prefix, flush).await
+    }
+
     pub fn range_values<R>(&self, range: R) -> common_exception::Result<Vec<KV::V>>
     where R: RangeBounds<KV::K> {
         self.inner.range_values::<KV, R>(range)
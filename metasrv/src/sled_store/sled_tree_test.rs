@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
 use async_raft::raft::Entry;
 use async_raft::raft::EntryNormal;
 use async_raft::raft::EntryPayload;
@@ -28,7 +32,10 @@ use crate::raft::state_machine::StateMachineMetaValue;
 use crate::sled_store::sled_key_space;
 use crate::sled_store::sled_key_space::SledKeySpace;
 use crate::sled_store::sled_key_space::StateMachineMeta;
+use crate::sled_store::SeqNum;
 use crate::sled_store::SledTree;
+use crate::sled_store::SledTreeSnapshot;
+use crate::sled_store::SpaceStats;
 use crate::tests::service::new_sled_test_context;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -499,16 +506,20 @@ async fn test_sledtree_update_and_fetch() -> anyhow::Result<()> {
     let tree = SledTree::open(db, tc.config.meta_config.tree_name("foo"), true)?;
 
     let v = tree
-        .update_and_fetch::<sled_key_space::Files, _>(&"foo".to_string(), |v| {
-            Some(v.unwrap_or_default() + "1")
-        })
+        .update_and_fetch::<sled_key_space::Files, _>(
+            &"foo".to_string(),
+            |v| Some(v.unwrap_or_default() + "1"),
+            true,
+        )
         .await?;
     assert_eq!(Some("1".to_string()), v);
 
     let v = tree
-        .update_and_fetch::<sled_key_space::Files, _>(&"foo".to_string(), |v| {
-            Some(v.unwrap_or_default() + "1")
-        })
+        .update_and_fetch::<sled_key_space::Files, _>(
+            &"foo".to_string(),
+            |v| Some(v.unwrap_or_default() + "1"),
+            true,
+        )
         .await?;
     assert_eq!(Some("11".to_string()), v);
 
@@ -1186,12 +1197,20 @@ async fn test_as_update_and_fetch() -> anyhow::Result<()> {
     let file_tree = tree.key_space::<sled_key_space::Files>();
 
     let v = file_tree
-        .update_and_fetch(&"foo".to_string(), |v| Some(v.unwrap_or_default() + "1"))
+        .update_and_fetch(
+            &"foo".to_string(),
+            |v| Some(v.unwrap_or_default() + "1"),
+            true,
+        )
         .await?;
     assert_eq!(Some("1".to_string()), v);
 
     let v = file_tree
-        .update_and_fetch(&"foo".to_string(), |v| Some(v.unwrap_or_default() + "1"))
+        .update_and_fetch(
+            &"foo".to_string(),
+            |v| Some(v.unwrap_or_default() + "1"),
+            true,
+        )
         .await?;
     assert_eq!(Some("11".to_string()), v);
 
@@ -1372,6 +1391,47 @@ async fn test_as_range_remove() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_as_range_remove_in_batches() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let tree = SledTree::open(db, tc.config.meta_config.tree_name("foo"), true)?;
+    let log_tree = tree.key_space::<sled_key_space::Logs>();
+
+    let logs: Vec<Entry<LogEntry>> = (1..=7)
+        .map(|index| Entry {
+            log_id: LogId { term: 1, index },
+            payload: EntryPayload::Blank,
+        })
+        .collect();
+
+    // 5 logs in the range, removed in 3 batches.
+    log_tree.append_values(&logs).await?;
+    let write_ops = tree.write_ops();
+    assert_eq!(5, log_tree.range_remove_in_batches(..=5, 2, true).await?);
+    assert_eq!(write_ops + 3, tree.write_ops());
+    assert_eq!(logs[5..], log_tree.range_values(..)?);
+    assert_eq!(2, log_tree.stats().entries);
+
+    // A full last batch ends with an empty one, an empty range removes nothing.
+    log_tree.append_values(&logs).await?;
+    assert_eq!(4, log_tree.range_remove_in_batches(2..6, 2, false).await?);
+    assert_eq!(0, log_tree.range_remove_in_batches(2..6, 2, false).await?);
+    assert_eq!(logs[0..1], log_tree.range_values(..2)?);
+    assert_eq!(logs[5..], log_tree.range_values(2..)?);
+
+    // The other key spaces are not touched.
+    tree.insert::<StateMachineMeta>(&Initialized, &StateMachineMetaValue::Bool(true))
+        .await?;
+    assert_eq!(3, log_tree.range_remove_in_batches(.., 100, true).await?);
+    assert!(tree.get::<StateMachineMeta>(&Initialized)?.is_some());
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_as_multi_types() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_meta_ut!();
@@ -1440,3 +1500,325 @@ async fn test_as_multi_types() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sledtree_space_stats() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let tree = SledTree::open(db, tc.config.meta_config.tree_name("foo"), true)?;
+
+    let files = tree.key_space::<sled_key_space::Files>();
+    let seqs = tree.key_space::<sled_key_space::Sequences>();
+
+    assert_eq!(SpaceStats::default(), files.stats());
+    assert_eq!(None, tree.space_stats("no-such-space"));
+
+    for i in 0..5 {
+        files.insert(&format!("f{}", i), &"x".repeat(i)).await?;
+    }
+    files
+        .insert(&"f1".to_string(), &"overridden".to_string())
+        .await?;
+    files.remove(&"f0".to_string(), true).await?;
+    files.remove(&"absent".to_string(), true).await?;
+    files
+        .range_remove("f3".to_string()..="f4".to_string(), true)
+        .await?;
+
+    seqs.insert(&"a".to_string(), &SeqNum(1)).await?;
+    seqs.update_and_fetch(&"a".to_string(), |old| old.map(|v| v + 1000), true)
+        .await?;
+    seqs.update_and_fetch(&"b".to_string(), |_| Some(SeqNum(7)), true)
+        .await?;
+    seqs.update_and_fetch(&"b".to_string(), |_| None, true)
+        .await?;
+
+    let got_files = files.stats();
+    let got_seqs = seqs.stats();
+    assert_eq!(2, got_files.entries);
+    assert_eq!(1, got_seqs.entries);
+    assert_eq!(
+        vec![("files", got_files), ("sequences", got_seqs)],
+        tree.list_space_stats()
+    );
+
+    tree.recount_space_stats()?;
+    assert_eq!(got_files, files.stats());
+    assert_eq!(got_seqs, seqs.stats());
+    assert_eq!(Some(got_files), tree.space_stats("files"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sledtree_space_stats_repair_drift() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let tree_name = tc.config.meta_config.tree_name("foo");
+
+    let want = {
+        let tree = SledTree::open(db, &tree_name, true)?;
+        let files = tree.key_space::<sled_key_space::Files>();
+        files.insert(&"a".to_string(), &"1".to_string()).await?;
+        files.insert(&"b".to_string(), &"22".to_string()).await?;
+        let want = files.stats();
+
+        // Skew the persisted stats, which are loaded when the tree is opened again.
+        tree.insert::<sled_key_space::SpaceStatsKV>(&"files".to_string(), &SpaceStats {
+            entries: 100,
+            bytes: 1,
+        })
+        .await?;
+        assert_eq!(want, files.stats());
+        want
+    };
+
+    let tree = SledTree::open(db, &tree_name, true)?;
+    assert_eq!(
+        Some(SpaceStats {
+            entries: 100,
+            bytes: 1
+        }),
+        tree.space_stats("files")
+    );
+
+    tree.recount_space_stats()?;
+    assert_eq!(Some(want), tree.space_stats("files"));
+
+    // The repaired stats are persisted.
+    drop(tree);
+    let tree = SledTree::open(db, &tree_name, true)?;
+    assert_eq!(Some(want), tree.space_stats("files"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sledtree_space_stats_batch() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let tree = SledTree::open(db, tc.config.meta_config.tree_name("foo"), true)?;
+
+    let files = tree.key_space::<sled_key_space::Files>();
+    files.insert(&"a".to_string(), &"1".to_string()).await?;
+
+    // A key written more than once in a batch is counted by its last write.
+    let kvs = vec![
+        ("a".to_string(), "overridden".to_string()),
+        ("b".to_string(), "2".to_string()),
+        ("b".to_string(), "22".to_string()),
+        ("c".to_string(), "333".to_string()),
+    ];
+    let write_ops = tree.write_ops();
+    files.append(&kvs).await?;
+    assert_eq!(write_ops + 1, tree.write_ops());
+
+    let got = files.stats();
+    assert_eq!(3, got.entries);
+    tree.recount_space_stats()?;
+    assert_eq!(got, files.stats());
+
+    // Removing a key twice or an absent key in a batch.
+    files
+        .remove_keys(
+            &["b".to_string(), "b".to_string(), "absent".to_string()],
+            true,
+        )
+        .await?;
+
+    let got = files.stats();
+    assert_eq!(2, got.entries);
+    tree.recount_space_stats()?;
+    assert_eq!(got, files.stats());
+
+    // A batch of another key space does not change it.
+    tree.append::<sled_key_space::Sequences>(&[("s".to_string(), SeqNum(1))])
+        .await?;
+    assert_eq!(got, files.stats());
+    assert_eq!(1, tree.space_stats("sequences").unwrap().entries);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_sledtree_export_snapshot() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let tree = SledTree::open(db, tc.config.meta_config.tree_name("foo"), false)?;
+
+    let counter = "counter".to_string();
+    let mirror = "mirror".to_string();
+
+    // The writers bump the counter and its mirror in one batch, an export never sees them differ.
+    let finished = Arc::new(AtomicUsize::new(0));
+    let writers = (0..2)
+        .map(|_| {
+            let tree = tree.clone();
+            let finished = finished.clone();
+            let (counter, mirror) = (counter.clone(), mirror.clone());
+            tokio::spawn(async move {
+                let seqs = tree.key_space::<sled_key_space::Sequences>();
+                for _ in 0..500 {
+                    let n = seqs.get(&counter)?.unwrap_or_default().0 + 1;
+                    seqs.append(&[(counter.clone(), SeqNum(n)), (mirror.clone(), SeqNum(n))])
+                        .await?;
+                }
+                finished.fetch_add(1, Ordering::Relaxed);
+                Ok::<(), common_exception::ErrorCode>(())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut exported = 0;
+    while finished.load(Ordering::Relaxed) < writers.len() || exported == 0 {
+        let snap = tree.export_snapshot()?;
+        let got_counter = snap.get::<sled_key_space::Sequences>(&counter)?;
+        let got_mirror = snap.get::<sled_key_space::Sequences>(&mirror)?;
+        assert_eq!(got_counter.map(|x| x.0), got_mirror.map(|x| x.0));
+        exported += 1;
+        tokio::task::yield_now().await;
+    }
+    for w in writers {
+        w.await??;
+    }
+
+    let snap = tree.export_snapshot()?;
+    let got = snap.get::<sled_key_space::Sequences>(&mirror)?.map(|x| x.0);
+    assert!(got.unwrap_or_default() > 0);
+    assert_eq!(
+        tree.get::<sled_key_space::Sequences>(&counter)?
+            .map(|x| x.0),
+        got
+    );
+    assert!(snap.get::<sled_key_space::Files>(&counter)?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sledtree_import_snapshot() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let tree = SledTree::open(db, tc.config.meta_config.tree_name("foo"), true)?;
+    let other = SledTree::open(db, tc.config.meta_config.tree_name("bar"), true)?;
+
+    tree.append::<sled_key_space::Files>(&[
+        ("a".to_string(), "1".to_string()),
+        ("b".to_string(), "2".to_string()),
+    ])
+    .await?;
+    tree.insert::<StateMachineMeta>(&Initialized, &StateMachineMetaValue::Bool(true))
+        .await?;
+    other
+        .insert::<sled_key_space::Files>(&"old".to_string(), &"x".to_string())
+        .await?;
+
+    let snap = tree.export_snapshot()?;
+    other.import_snapshot(snap).await?;
+
+    // The old kvs are cleared, the imported ones are accounted.
+    assert!(other
+        .get::<sled_key_space::Files>(&"old".to_string())?
+        .is_none());
+    assert_eq!(
+        Some("2".to_string()),
+        other.get::<sled_key_space::Files>(&"b".to_string())?
+    );
+    assert!(other.get::<StateMachineMeta>(&Initialized)?.is_some());
+    assert_eq!(
+        tree.space_stats("files").unwrap(),
+        other.space_stats("files").unwrap()
+    );
+
+    // Importing an empty snapshot clears all but the space stats.
+    other.import_snapshot(SledTreeSnapshot::default()).await?;
+    let got = other.export_snapshot()?;
+    assert!(got
+        .iter()
+        .all(|(k, _)| k[0] == sled_key_space::SpaceStatsKV::PREFIX));
+    assert_eq!(0, other.space_stats("files").unwrap().entries);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_as_scan_lenient_and_repair() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_sled_test_context();
+    let db = &tc.db;
+    let tree = SledTree::open(db, tc.config.meta_config.tree_name("foo"), true)?;
+    let files = tree.key_space::<sled_key_space::Files>();
+
+    files
+        .append(&[
+            ("a".to_string(), "1".to_string()),
+            ("c".to_string(), "3".to_string()),
+        ])
+        .await?;
+    tree.insert::<StateMachineMeta>(&Initialized, &StateMachineMetaValue::Bool(true))
+        .await?;
+
+    // A garbage value written around SledTree, as if the entry is corrupted on disk.
+    let bad_key = sled_key_space::Files::serialize_key(&"b".to_string())?;
+    tree.tree.insert(bad_key, &b"\xffgarbage"[..])?;
+    tree.recount_space_stats()?;
+
+    // The strict scan fails.
+    assert!(files.range_kvs(..).is_err());
+    assert!(files.scan_prefix(&"".to_string()).is_err());
+
+    // The lenient scan yields the good entries and an error in place of the bad one.
+    let got = files.scan_lenient(None)?.collect::<Vec<_>>();
+    assert_eq!(3, got.len());
+    assert_eq!(
+        ("a".to_string(), "1".to_string()),
+        *got[0].as_ref().unwrap()
+    );
+    assert!(got[1].is_err());
+    assert_eq!(
+        ("c".to_string(), "3".to_string()),
+        *got[2].as_ref().unwrap()
+    );
+
+    // A prefix limits the scan.
+    let got = files
+        .scan_lenient(Some(&"c".to_string()))?
+        .collect::<Vec<_>>();
+    assert_eq!(1, got.len());
+    assert!(got[0].is_ok());
+
+    // Repair removes exactly the bad entry.
+    assert_eq!(0, files.repair(Some(&"a".to_string()), true).await?);
+    assert_eq!(3, files.stats().entries);
+    assert_eq!(1, files.repair(None, true).await?);
+    assert_eq!(0, files.repair(None, true).await?);
+
+    assert_eq!(
+        vec![
+            ("a".to_string(), "1".to_string()),
+            ("c".to_string(), "3".to_string())
+        ],
+        files.range_kvs(..)?
+    );
+    assert_eq!(2, files.stats().entries);
+    assert!(tree.get::<StateMachineMeta>(&Initialized)?.is_some());
+
+    Ok(())
+}
@@ -0,0 +1,405 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Accounting of the key spaces of a sled::Tree: the number of entries and the approximate bytes
+//! of the keys and values of every key space, maintained as the tree is written.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+
+use common_exception::ErrorCode;
+use common_exception::ToErrorCode;
+use common_tracing::tracing;
+use lazy_static::lazy_static;
+use metrics::gauge;
+use sled::IVec;
+
+use crate::sled_store::sled_key_space::SledKeySpace;
+use crate::sled_store::sled_key_space::SpaceStatsKV;
+use crate::sled_store::sled_key_space::ACCOUNTED_KEY_SPACES;
+use crate::sled_store::SledSerde;
+
+pub static METRIC_SLED_SPACE_ENTRIES: &str = "sled.space_entries";
+pub static METRIC_SLED_SPACE_BYTES: &str = "sled.space_bytes";
+
+/// The changed counters of a tree are persisted at least once every so many writes.
+const PERSIST_INTERVAL: u64 = 64;
+
+/// The number of entries and the total bytes of the keys and values in a key space.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpaceStats {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+impl SledSerde for SpaceStats {}
+
+/// The stats of a key space of a tree, an item of the `/v1/sled/spaces` listing.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SpaceStatsItem {
+    pub tree: String,
+    pub space: String,
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// Returns the prefix of an accounted key space by its name.
+pub fn space_prefix(name: &str) -> Option<u8> {
+    ACCOUNTED_KEY_SPACES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(prefix, _)| *prefix)
+}
+
+/// The change of the stats of a key space by one or more writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct StatsDelta {
+    entries: i64,
+    bytes: i64,
+}
+
+impl StatsDelta {
+    /// Accounts a write that changes the value of a key from `prev` bytes to `next` bytes,
+    /// `None` if there is no value.
+    pub(crate) fn update(&mut self, key_len: usize, prev: Option<usize>, next: Option<usize>) {
+        if let Some(v) = prev {
+            self.entries -= 1;
+            self.bytes -= (key_len + v) as i64;
+        }
+        if let Some(v) = next {
+            self.entries += 1;
+            self.bytes += (key_len + v) as i64;
+        }
+    }
+}
+
+/// A sled::Batch of writes to a tree and the change of the stats by it.
+/// A key written more than once in a batch is accounted by its last write.
+pub(crate) struct AccountedBatch<'a> {
+    tree: &'a sled::Tree,
+    batch: sled::Batch,
+    delta: StatsDelta,
+
+    /// The size of the value of the keys written by the batch, `None` if it is removed.
+    written: BTreeMap<IVec, Option<usize>>,
+}
+
+impl<'a> AccountedBatch<'a> {
+    pub(crate) fn new(tree: &'a sled::Tree) -> Self {
+        AccountedBatch {
+            tree,
+            batch: sled::Batch::default(),
+            delta: StatsDelta::default(),
+            written: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, k: IVec, v: IVec) -> common_exception::Result<()> {
+        let prev = self.prev_len(&k)?;
+        self.delta.update(k.len(), prev, Some(v.len()));
+        self.written.insert(k.clone(), Some(v.len()));
+        self.batch.insert(k, v);
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, k: IVec) -> common_exception::Result<()> {
+        let prev = self.prev_len(&k)?;
+        self.delta.update(k.len(), prev, None);
+        self.written.insert(k.clone(), None);
+        self.batch.remove(k);
+        Ok(())
+    }
+
+    pub(crate) fn into_parts(self) -> (sled::Batch, StatsDelta) {
+        (self.batch, self.delta)
+    }
+
+    fn prev_len(&self, k: &IVec) -> common_exception::Result<Option<usize>> {
+        if let Some(len) = self.written.get(k) {
+            return Ok(*len);
+        }
+        let prev = self
+            .tree
+            .get(k)
+            .map_err_to_code(ErrorCode::MetaStoreDamaged, || "get for accounting")?;
+        Ok(prev.map(|v| v.len()))
+    }
+}
+
+#[derive(Default)]
+struct SpaceCounter {
+    entries: AtomicI64,
+    bytes: AtomicI64,
+
+    /// Whether it is changed since it is persisted.
+    dirty: AtomicBool,
+}
+
+impl SpaceCounter {
+    fn load(&self) -> SpaceStats {
+        SpaceStats {
+            entries: self.entries.load(Ordering::Relaxed).max(0) as u64,
+            bytes: self.bytes.load(Ordering::Relaxed).max(0) as u64,
+        }
+    }
+
+    fn store(&self, stats: SpaceStats) {
+        if self.load() != stats {
+            self.entries.store(stats.entries as i64, Ordering::Relaxed);
+            self.bytes.store(stats.bytes as i64, Ordering::Relaxed);
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+lazy_static! {
+    /// The accounting of the opened trees, by tree name.
+    static ref OPENED: Mutex<BTreeMap<String, Weak<SpaceAccounting>>> = Mutex::new(BTreeMap::new());
+}
+
+/// The counters of the key spaces of a sled::Tree, shared by every SledTree opened on it.
+///
+/// A write adds its change to the counters of its key space after it is applied to sled,
+/// a batch updates the counters once. The writes to a key are expected to be serialized,
+/// as the raft log and the state machine do, otherwise the counters may drift.
+///
+/// The changed counters are persisted in `SpaceStatsKV` before a write is fsync-ed and at least
+/// every `PERSIST_INTERVAL` writes, so that a restart loads them instead of recounting.
+/// The changes not persisted before a crash are lost until `recount()`.
+pub(crate) struct SpaceAccounting {
+    tree_name: String,
+    tree: sled::Tree,
+
+    /// Indexed by key space prefix.
+    spaces: Vec<SpaceCounter>,
+
+    /// The number of writes since the counters are persisted.
+    unsaved: AtomicU64,
+}
+
+impl fmt::Debug for SpaceAccounting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpaceAccounting")
+            .field("tree_name", &self.tree_name)
+            .finish()
+    }
+}
+
+impl SpaceAccounting {
+    /// Returns the accounting of a tree, shared with the other opened SledTree of it.
+    /// The counters are loaded from the persisted ones, or counted if there are none.
+    pub(crate) fn open(
+        tree_name: &str,
+        tree: &sled::Tree,
+    ) -> common_exception::Result<Arc<SpaceAccounting>> {
+        let mut opened = OPENED.lock().unwrap();
+        if let Some(acc) = opened.get(tree_name).and_then(|w| w.upgrade()) {
+            return Ok(acc);
+        }
+
+        let acc = Arc::new(SpaceAccounting {
+            tree_name: tree_name.to_string(),
+            tree: tree.clone(),
+            spaces: (0..=u8::MAX).map(|_| SpaceCounter::default()).collect(),
+            unsaved: AtomicU64::new(0),
+        });
+        if !acc.load()? {
+            tracing::info!("no persisted space stats, recount tree: {}", tree_name);
+            acc.recount()?;
+        }
+
+        opened.retain(|_, w| w.strong_count() > 0);
+        opened.insert(tree_name.to_string(), Arc::downgrade(&acc));
+        Ok(acc)
+    }
+
+    pub(crate) fn get(&self, prefix: u8) -> SpaceStats {
+        self.spaces[prefix as usize].load()
+    }
+
+    /// Returns the stats of the non-empty key spaces.
+    pub(crate) fn list(&self) -> Vec<(&'static str, SpaceStats)> {
+        ACCOUNTED_KEY_SPACES
+            .iter()
+            .map(|(prefix, name)| (*name, self.get(*prefix)))
+            .filter(|(_, stats)| *stats != SpaceStats::default())
+            .collect()
+    }
+
+    pub(crate) fn apply(&self, prefix: u8, delta: StatsDelta) {
+        if prefix == SpaceStatsKV::PREFIX || delta == StatsDelta::default() {
+            return;
+        }
+        let counter = &self.spaces[prefix as usize];
+        counter.entries.fetch_add(delta.entries, Ordering::Relaxed);
+        counter.bytes.fetch_add(delta.bytes, Ordering::Relaxed);
+        counter.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Called at the end of every write, persists the changed counters if the write is about to be
+    /// fsync-ed or enough writes are not persisted.
+    pub(crate) fn written(&self, fsync: bool) -> common_exception::Result<()> {
+        let unsaved = self.unsaved.fetch_add(1, Ordering::Relaxed) + 1;
+        if fsync || unsaved >= PERSIST_INTERVAL {
+            self.persist()?;
+        }
+        Ok(())
+    }
+
+    /// Counts every key space by scanning the tree, and replaces the counters with the result.
+    /// The writes during the scan may or may not be counted.
+    pub(crate) fn recount(&self) -> common_exception::Result<()> {
+        let mut counted = vec![SpaceStats::default(); self.spaces.len()];
+
+        for item in self.tree.iter() {
+            let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                format!("recount: {}", self.tree_name)
+            })?;
+            let prefix = match k.first() {
+                None => continue,
+                Some(prefix) => *prefix,
+            };
+            if prefix == SpaceStatsKV::PREFIX {
+                continue;
+            }
+            let stats = &mut counted[prefix as usize];
+            stats.entries += 1;
+            stats.bytes += (k.len() + v.len()) as u64;
+        }
+
+        for (counter, stats) in self.spaces.iter().zip(counted) {
+            counter.store(stats);
+        }
+
+        self.persist()
+    }
+
+    /// Loads the persisted counters, returns false if there are none.
+    fn load(&self) -> common_exception::Result<bool> {
+        let mut loaded = false;
+
+        for item in self.tree.scan_prefix([SpaceStatsKV::PREFIX]) {
+            let (k, v) = item.map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                format!("load space stats: {}", self.tree_name)
+            })?;
+            let name = SpaceStatsKV::deserialize_key(k)?;
+            let stats = SpaceStatsKV::deserialize_value(v)?;
+            loaded = true;
+
+            if let Some(prefix) = space_prefix(&name) {
+                let counter = &self.spaces[prefix as usize];
+                counter
+                    .entries
+                    .store(stats.entries as i64, Ordering::Relaxed);
+                counter.bytes.store(stats.bytes as i64, Ordering::Relaxed);
+                self.report(&name, stats);
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Writes the changed counters to `SpaceStatsKV` and reports them as metrics.
+    fn persist(&self) -> common_exception::Result<()> {
+        self.unsaved.store(0, Ordering::Relaxed);
+
+        let mut batch = sled::Batch::default();
+        let mut persisted = vec![];
+
+        for (prefix, name) in ACCOUNTED_KEY_SPACES.iter() {
+            let counter = &self.spaces[*prefix as usize];
+            if !counter.dirty.swap(false, Ordering::Relaxed) {
+                continue;
+            }
+            let stats = counter.load();
+            batch.insert(
+                SpaceStatsKV::serialize_key(&name.to_string())?,
+                SpaceStatsKV::serialize_value(&stats)?,
+            );
+            persisted.push((*name, stats));
+        }
+
+        if persisted.is_empty() {
+            return Ok(());
+        }
+
+        self.tree
+            .apply_batch(batch)
+            .map_err_to_code(ErrorCode::MetaStoreDamaged, || {
+                format!("persist space stats: {}", self.tree_name)
+            })?;
+
+        for (name, stats) in persisted {
+            self.report(name, stats);
+        }
+        Ok(())
+    }
+
+    fn report(&self, space: &str, stats: SpaceStats) {
+        gauge!(
+            METRIC_SLED_SPACE_ENTRIES,
+            stats.entries as f64,
+            "tree" => self.tree_name.clone(),
+            "space" => space.to_string()
+        );
+        gauge!(
+            METRIC_SLED_SPACE_BYTES,
+            stats.bytes as f64,
+            "tree" => self.tree_name.clone(),
+            "space" => space.to_string()
+        );
+    }
+
+    fn items(&self) -> Vec<SpaceStatsItem> {
+        self.list()
+            .into_iter()
+            .map(|(space, stats)| SpaceStatsItem {
+                tree: self.tree_name.clone(),
+                space: space.to_string(),
+                entries: stats.entries,
+                bytes: stats.bytes,
+            })
+            .collect()
+    }
+}
+
+/// Returns the stats of the non-empty key spaces of every opened tree.
+pub fn list_space_stats() -> Vec<SpaceStatsItem> {
+    opened_accountings()
+        .iter()
+        .flat_map(|acc| acc.items())
+        .collect()
+}
+
+/// Recounts every opened tree to repair the drift of the counters, returns the stats after it.
+pub fn recount_space_stats() -> common_exception::Result<Vec<SpaceStatsItem>> {
+    let accs = opened_accountings();
+    for acc in accs.iter() {
+        acc.recount()?;
+    }
+    Ok(accs.iter().flat_map(|acc| acc.items()).collect())
+}
+
+fn opened_accountings() -> Vec<Arc<SpaceAccounting>> {
+    let opened = OPENED.lock().unwrap();
+    opened.values().filter_map(|w| w.upgrade()).collect()
+}
@@ -14,6 +14,7 @@
 
 //! Test arrow-flight API of metasrv
 
+use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -21,11 +22,16 @@ use common_metatypes::KVMeta;
 use common_metatypes::KVValue;
 use common_metatypes::MatchSeq;
 use common_runtime::tokio;
+use common_runtime::Clock;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
 use common_store_api_sdk::kv_api_impl::UpsertKVActionResult;
 use common_store_api_sdk::KVApi;
 use common_store_api_sdk::StoreClient;
 use common_tracing::tracing;
 use metasrv::init_meta_ut;
+use metasrv::tests::service::new_test_context;
+use metasrv::tests::start_metasrv_with_context;
 use pretty_assertions::assert_eq;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -94,7 +100,7 @@ async fn test_restart() -> anyhow::Result<()> {
 
         // restart by opening existent meta db
         tc.config.meta_config.boot = false;
-        metasrv::tests::start_metasrv_with_context(&mut tc).await?;
+        start_metasrv_with_context(&mut tc).await?;
     }
 
     tokio::time::sleep(tokio::time::Duration::from_millis(10_000)).await;
@@ -504,14 +510,15 @@ async fn test_generic_kv_timeout() -> anyhow::Result<()> {
         let span = tracing::span!(tracing::Level::INFO, "test_generic_kv_timeout");
         let _ent = span.enter();
 
-        let (_tc, addr) = metasrv::tests::start_metasrv().await?;
+        let clock = VirtualClock::create();
+        let mut tc = new_test_context();
+        tc.config.meta_config.clock = SharedClock::create(clock.clone());
+        start_metasrv_with_context(&mut tc).await?;
+        let addr = tc.config.flight_api_address.clone();
 
         let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = clock.now_secs();
 
         client
             .upsert_kv(
@@ -532,16 +539,13 @@ async fn test_generic_kv_timeout() -> anyhow::Result<()> {
 
         tracing::info!("---get expired");
         {
-            tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
+            clock.advance(Duration::from_secs(2));
             let res = client.get_kv(&"k1".to_string()).await?;
             tracing::debug!("got k1:{:?}", res);
             assert!(res.result.is_none(), "got expired");
         }
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = clock.now_secs();
 
         tracing::info!("--- expired entry act as if it does not exist, an ADD op should apply");
         {
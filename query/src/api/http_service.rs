@@ -29,6 +29,7 @@ use axum_server::tls::TlsLoader;
 use common_exception::Result;
 use common_runtime::tokio;
 use common_runtime::tokio::task::JoinHandle;
+use common_tracing::log_level_handler::LogLevelExtension;
 use tokio_rustls::rustls::internal::pemfile::certs;
 use tokio_rustls::rustls::internal::pemfile::pkcs8_private_keys;
 use tokio_rustls::rustls::AllowAnyAuthenticatedClient;
@@ -46,6 +47,7 @@ use crate::servers::Server;
 pub struct HttpService {
     cfg: Config,
     cluster: ClusterRef,
+    log_level: LogLevelExtension,
     join_handle: Option<JoinHandle<std::result::Result<(), std::io::Error>>>,
     abort_handler: axum_server::Handle,
     tls_config: Option<ServerConfig>,
@@ -53,7 +55,7 @@ pub struct HttpService {
 
 // build axum router
 macro_rules! build_router {
-    ($cfg: expr, $cluster: expr) => {
+    ($cfg: expr, $cluster: expr, $log_level: expr) => {
         Router::new()
             .route("/v1/health", get(super::http::v1::health::health_handler))
             .route("/v1/config", get(super::http::v1::config::config_handler))
@@ -70,6 +72,18 @@ macro_rules! build_router {
                 "/v1/cluster/remove",
                 post(super::http::v1::cluster::cluster_remove_handler),
             )
+            .route(
+                "/v1/get_log_level",
+                get(common_tracing::log_level_handler::get_log_level_handler),
+            )
+            .route(
+                "/v1/set_log_level",
+                post(common_tracing::log_level_handler::set_log_level_handler),
+            )
+            .route(
+                "/v1/revert_log_level",
+                post(common_tracing::log_level_handler::revert_log_level_handler),
+            )
             .route(
                 "/debug/home",
                 get(super::http::debug::home::debug_home_handler),
@@ -80,6 +94,7 @@ macro_rules! build_router {
             )
             .layer(AddExtensionLayer::new($cluster.clone()))
             .layer(AddExtensionLayer::new($cfg.clone()))
+            .layer(AddExtensionLayer::new($log_level.clone()))
     };
 }
 
@@ -90,6 +105,7 @@ impl HttpService {
         Box::new(HttpService {
             cfg,
             cluster,
+            log_level: LogLevelExtension(common_tracing::global_log_level()),
             join_handle: None,
             abort_handler: handler,
             tls_config,
@@ -177,7 +193,11 @@ impl Server for HttpService {
     }
 
     async fn start(&mut self, listening: SocketAddr) -> Result<SocketAddr> {
-        let app = build_router!(self.cfg.clone(), self.cluster.clone());
+        let app = build_router!(
+            self.cfg.clone(),
+            self.cluster.clone(),
+            self.log_level.clone()
+        );
         let handler = self.abort_handler.clone();
         match self.tls_config.clone() {
             None => {
@@ -18,9 +18,19 @@ pub use http_service::HttpService;
 pub use rpc::BroadcastAction;
 pub use rpc::CancelAction;
 pub use rpc::FlightAction;
+pub use rpc::FlightBlockTag;
+pub use rpc::FlightChannelPool;
+pub use rpc::FlightChannelStats;
 pub use rpc::FlightClient;
+pub use rpc::FlightCompression;
 pub use rpc::FlightTicket;
+pub use rpc::GetProgressAction;
+pub use rpc::OrderedBlockStream;
+pub use rpc::QueryProgress;
 pub use rpc::ShuffleAction;
+pub use rpc::StageProgress;
+pub use rpc::TaggedBlockStream;
+pub use rpc::FLIGHT_CHANNELS_PER_PEER;
 pub use rpc_service::RpcService;
 
 mod http;
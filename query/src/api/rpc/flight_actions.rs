@@ -28,6 +28,15 @@ pub struct ShuffleAction {
     pub plan: PlanNode,
     pub sinks: Vec<String>,
     pub scatters_expression: Expression,
+    // The label of the query, the stage runs with it on the remote node.
+    #[serde(default)]
+    pub query_label: Option<String>,
+    // Tag the blocks so that the consumer receives the blocks of each sink in the order they are sent.
+    #[serde(default)]
+    pub preserve_order: bool,
+    // The strict_arithmetic setting of the query, the stage is evaluated with it on the remote node.
+    #[serde(default)]
+    pub strict_arithmetic: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -36,6 +45,12 @@ pub struct BroadcastAction {
     pub stage_id: String,
     pub plan: PlanNode,
     pub sinks: Vec<String>,
+    #[serde(default)]
+    pub query_label: Option<String>,
+    #[serde(default)]
+    pub preserve_order: bool,
+    #[serde(default)]
+    pub strict_arithmetic: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -43,6 +58,26 @@ pub struct CancelAction {
     pub query_id: String,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct GetProgressAction {
+    pub query_id: String,
+}
+
+/// The reply of GetProgressAction, the stages of the query on the node.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryProgress {
+    pub stages: Vec<StageProgress>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StageProgress {
+    pub stage_id: String,
+    /// The rows and the bytes of the blocks the stage has produced so far.
+    pub rows: u64,
+    pub bytes: u64,
+    pub finished: bool,
+}
+
 impl TryInto<ShuffleAction> for Vec<u8> {
     type Error = Status;
 
@@ -115,11 +150,36 @@ impl TryInto<Vec<u8>> for CancelAction {
     }
 }
 
+impl TryInto<GetProgressAction> for Vec<u8> {
+    type Error = Status;
+
+    fn try_into(self) -> Result<GetProgressAction, Self::Error> {
+        match std::str::from_utf8(&self) {
+            Err(cause) => Err(Status::invalid_argument(cause.to_string())),
+            Ok(utf8_body) => match serde_json::from_str::<GetProgressAction>(utf8_body) {
+                Err(cause) => Err(Status::invalid_argument(cause.to_string())),
+                Ok(action) => Ok(action),
+            },
+        }
+    }
+}
+
+impl TryInto<Vec<u8>> for GetProgressAction {
+    type Error = ErrorCode;
+
+    fn try_into(self) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec(&self).map_err_to_code(ErrorCode::LogicalError, || {
+            "Logical error: cannot serialize GetProgressAction."
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum FlightAction {
     PrepareShuffleAction(ShuffleAction),
     BroadcastAction(BroadcastAction),
     CancelAction(CancelAction),
+    GetProgressAction(GetProgressAction),
 }
 
 impl FlightAction {
@@ -155,6 +215,30 @@ impl FlightAction {
         }
     }
 
+    pub fn get_query_label(&self) -> Option<String> {
+        match self {
+            FlightAction::BroadcastAction(action) => action.query_label.clone(),
+            FlightAction::PrepareShuffleAction(action) => action.query_label.clone(),
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn get_preserve_order(&self) -> bool {
+        match self {
+            FlightAction::BroadcastAction(action) => action.preserve_order,
+            FlightAction::PrepareShuffleAction(action) => action.preserve_order,
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn get_strict_arithmetic(&self) -> bool {
+        match self {
+            FlightAction::BroadcastAction(action) => action.strict_arithmetic,
+            FlightAction::PrepareShuffleAction(action) => action.strict_arithmetic,
+            _ => unimplemented!(),
+        }
+    }
+
     pub fn get_scatter_expression(&self) -> Option<Expression> {
         match self {
             FlightAction::BroadcastAction(_) => None,
@@ -172,6 +256,7 @@ impl TryInto<FlightAction> for Action {
             "PrepareShuffleAction" => Ok(FlightAction::PrepareShuffleAction(self.body.try_into()?)),
             "BroadcastAction" => Ok(FlightAction::BroadcastAction(self.body.try_into()?)),
             "CancelAction" => Ok(FlightAction::CancelAction(self.body.try_into()?)),
+            "GetProgressAction" => Ok(FlightAction::GetProgressAction(self.body.try_into()?)),
             un_implemented => Err(Status::unimplemented(format!(
                 "UnImplement action {}",
                 un_implemented
@@ -197,6 +282,10 @@ impl TryInto<Action> for FlightAction {
                 r#type: String::from("CancelAction"),
                 body: cancel_action.try_into()?,
             }),
+            FlightAction::GetProgressAction(get_progress_action) => Ok(Action {
+                r#type: String::from("GetProgressAction"),
+                body: get_progress_action.try_into()?,
+            }),
         }
     }
 }
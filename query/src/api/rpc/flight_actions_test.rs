@@ -32,6 +32,9 @@ async fn test_shuffle_action_try_into() -> Result<()> {
         plan: parse_query("SELECT number FROM numbers(5)")?,
         sinks: vec![String::from("stream_id")],
         scatters_expression: Expression::create_literal(DataValue::UInt64(Some(1))),
+        query_label: Some(String::from("team=billing")),
+        preserve_order: true,
+        strict_arithmetic: true,
     };
 
     let from_action = FlightAction::PrepareShuffleAction(shuffle_action);
@@ -39,6 +42,7 @@ async fn test_shuffle_action_try_into() -> Result<()> {
     let from_action: FlightAction = to_action.try_into()?;
     match from_action {
         FlightAction::CancelAction(_) => assert!(false),
+        FlightAction::GetProgressAction(_) => assert!(false),
         FlightAction::BroadcastAction(_) => assert!(false),
         FlightAction::PrepareShuffleAction(action) => {
             assert_eq!(action.query_id, "query_id");
@@ -49,6 +53,9 @@ async fn test_shuffle_action_try_into() -> Result<()> {
                 action.scatters_expression,
                 Expression::create_literal(DataValue::UInt64(Some(1)))
             );
+            assert_eq!(action.query_label, Some(String::from("team=billing")));
+            assert!(action.preserve_order);
+            assert!(action.strict_arithmetic);
         }
     }
 
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::convert::TryInto;
+use std::sync::Arc;
 
 use common_arrow::arrow_flight::flight_service_client::FlightServiceClient;
 use common_arrow::arrow_flight::Action;
@@ -21,24 +22,39 @@ use common_arrow::arrow_flight::Ticket;
 use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_exception::ToErrorCode;
 use common_runtime::tokio::time::Duration;
 use common_streams::SendableDataBlockStream;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
 use tonic::transport::channel::Channel;
 use tonic::Request;
+use tonic::Status;
 use tonic::Streaming;
 
 use crate::api::rpc::flight_actions::FlightAction;
+use crate::api::rpc::flight_actions::GetProgressAction;
+use crate::api::rpc::flight_actions::QueryProgress;
+use crate::api::rpc::flight_client_pool::FlightChannel;
 use crate::api::rpc::flight_client_stream::FlightDataStream;
+use crate::api::rpc::flight_ordering::TaggedBlockStream;
 use crate::api::rpc::flight_tickets::FlightTicket;
 
 pub struct FlightClient {
     inner: FlightServiceClient<Channel>,
+    channel: Arc<FlightChannel>,
 }
 
-// TODO: Integration testing required
 impl FlightClient {
-    pub fn new(inner: FlightServiceClient<Channel>) -> FlightClient {
-        FlightClient { inner }
+    pub fn new(channel: Arc<FlightChannel>) -> FlightClient {
+        FlightClient {
+            inner: FlightServiceClient::new(channel.inner()),
+            channel,
+        }
+    }
+
+    pub fn get_channel(&self) -> Arc<FlightChannel> {
+        self.channel.clone()
     }
 
     pub async fn fetch_stream(
@@ -48,24 +64,73 @@ impl FlightClient {
         timeout: u64,
     ) -> Result<SendableDataBlockStream> {
         let ticket = ticket.try_into()?;
-        let inner = self.do_get(ticket, timeout).await?;
+        let inner = self.do_get_stream(ticket, timeout).await?;
         Ok(Box::pin(FlightDataStream::from_remote(schema, inner)))
     }
 
+    /// Fetch the stream of an order-preserving stage, the blocks come with their tags.
+    pub async fn fetch_tagged_stream(
+        &mut self,
+        ticket: FlightTicket,
+        schema: DataSchemaRef,
+        timeout: u64,
+    ) -> Result<TaggedBlockStream> {
+        let source = ticket.stream_name();
+        let ticket = ticket.try_into()?;
+        let inner = self.do_get_stream(ticket, timeout).await?;
+        Ok(Box::pin(FlightDataStream::from_remote_tagged(
+            schema, source, inner,
+        )))
+    }
+
     pub async fn execute_action(&mut self, action: FlightAction, timeout: u64) -> Result<()> {
         self.do_action(action, timeout).await?;
         Ok(())
     }
 
+    /// The progress of the stages of the query on the node of the client.
+    pub async fn get_progress(&mut self, query_id: &str, timeout: u64) -> Result<QueryProgress> {
+        let action = FlightAction::GetProgressAction(GetProgressAction {
+            query_id: query_id.to_string(),
+        });
+
+        let body = self.do_action(action, timeout).await?;
+        serde_json::from_slice(&body).map_err_to_code(ErrorCode::BadBytes, || {
+            "Cannot deserialize the reply of GetProgressAction."
+        })
+    }
+
     // Execute do_get.
     async fn do_get(&mut self, ticket: Ticket, timeout: u64) -> Result<Streaming<FlightData>> {
         let mut request = Request::new(ticket);
         request.set_timeout(Duration::from_secs(timeout));
 
-        let response = self.inner.do_get(request).await?;
+        let response = self
+            .inner
+            .do_get(request)
+            .await
+            .map_err(|status| self.status_error(status))?;
         Ok(response.into_inner())
     }
 
+    // Execute do_get for a stream of data, it is counted on the channel until it is dropped.
+    async fn do_get_stream(
+        &mut self,
+        ticket: Ticket,
+        timeout: u64,
+    ) -> Result<impl Stream<Item = Result<FlightData>>> {
+        let lease = self.channel.lease();
+        let channel = self.channel.clone();
+        let inner = self.do_get(ticket, timeout).await?;
+        Ok(inner.map(move |flight_data| {
+            let _lease = &lease;
+            flight_data.map_err(|status| match channel.broken_error(&status) {
+                Some(broken) => broken,
+                None => ErrorCode::UnknownException(status.message()),
+            })
+        }))
+    }
+
     // Execute do_action.
     async fn do_action(&mut self, action: FlightAction, timeout: u64) -> Result<Vec<u8>> {
         let action: Action = action.try_into()?;
@@ -73,9 +138,15 @@ impl FlightClient {
         let mut request = Request::new(action);
         request.set_timeout(Duration::from_secs(timeout));
 
-        let response = self.inner.do_action(request).await?;
+        let _lease = self.channel.lease();
+        let response = self
+            .inner
+            .do_action(request)
+            .await
+            .map_err(|status| self.status_error(status))?;
 
-        match response.into_inner().message().await? {
+        let message = response.into_inner().message().await;
+        match message.map_err(|status| self.status_error(status))? {
             Some(response) => Ok(response.body),
             None => Result::Err(ErrorCode::EmptyDataFromServer(format!(
                 "Can not receive data from flight server, action: {:?}",
@@ -83,4 +154,11 @@ impl FlightClient {
             ))),
         }
     }
+
+    fn status_error(&self, status: Status) -> ErrorCode {
+        match self.channel.broken_error(&status) {
+            Some(broken) => broken,
+            None => ErrorCode::from(status),
+        }
+    }
 }
@@ -0,0 +1,203 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_store_api_sdk::ConnectionFactory;
+use common_store_api_sdk::RpcClientTlsConfig;
+use tonic::transport::channel::Channel;
+use tonic::Code;
+use tonic::Status;
+
+use crate::api::rpc::flight_client::FlightClient;
+
+/// How many connections a query node opens to another one, all the streams between the two
+/// nodes are multiplexed over them.
+pub const FLIGHT_CHANNELS_PER_PEER: usize = 1;
+
+/// A connection whose ping is not acknowledged within this is closed, failing its streams.
+const FLIGHT_CHANNEL_KEEPALIVE: Duration = Duration::from_secs(20);
+
+/// One gRPC connection to a peer.
+pub struct FlightChannel {
+    id: u64,
+    peer: String,
+    channel: Channel,
+    broken: AtomicBool,
+    active_streams: AtomicUsize,
+    total_streams: AtomicU64,
+}
+
+impl FlightChannel {
+    fn create(id: u64, peer: String, channel: Channel) -> FlightChannel {
+        FlightChannel {
+            id,
+            peer,
+            channel,
+            broken: AtomicBool::new(false),
+            active_streams: AtomicUsize::new(0),
+            total_streams: AtomicU64::new(0),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn peer(&self) -> &str {
+        &self.peer
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.broken.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn inner(&self) -> Channel {
+        self.channel.clone()
+    }
+
+    /// Counts a stream on this channel until the lease is dropped.
+    pub(crate) fn lease(self: &Arc<Self>) -> FlightStreamLease {
+        self.active_streams.fetch_add(1, Ordering::Relaxed);
+        self.total_streams.fetch_add(1, Ordering::Relaxed);
+        FlightStreamLease {
+            channel: self.clone(),
+        }
+    }
+
+    /// If a call failed because of the connection rather than the peer, marks this channel
+    /// broken so that the pool replaces it, and returns the error naming the channel.
+    pub(crate) fn broken_error(&self, status: &Status) -> Option<ErrorCode> {
+        if !is_transport_error(status) {
+            return None;
+        }
+
+        self.broken.store(true, Ordering::Relaxed);
+        Some(ErrorCode::BrokenChannel(format!(
+            "flight channel {} to {} is broken: {}",
+            self.id,
+            self.peer,
+            status.message()
+        )))
+    }
+
+    fn stats(&self) -> FlightChannelStats {
+        FlightChannelStats {
+            peer: self.peer.clone(),
+            channel_id: self.id,
+            active_streams: self.active_streams.load(Ordering::Relaxed),
+            total_streams: self.total_streams.load(Ordering::Relaxed),
+            broken: self.is_broken(),
+        }
+    }
+}
+
+/// Errors of the peer are serialized in the details of an `Unknown` status, anything else
+/// without details is reported by the transport.
+fn is_transport_error(status: &Status) -> bool {
+    match status.code() {
+        Code::Unknown => status.details().is_empty(),
+        Code::Unavailable | Code::Internal => true,
+        _ => false,
+    }
+}
+
+pub struct FlightStreamLease {
+    channel: Arc<FlightChannel>,
+}
+
+impl Drop for FlightStreamLease {
+    fn drop(&mut self) {
+        self.channel.active_streams.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlightChannelStats {
+    pub peer: String,
+    pub channel_id: u64,
+    pub active_streams: usize,
+    pub total_streams: u64,
+    pub broken: bool,
+}
+
+/// The flight channels of a query node, keyed by the address of the peer.
+pub struct FlightChannelPool {
+    channels_per_peer: usize,
+    next_channel_id: AtomicU64,
+    peers: Mutex<HashMap<String, Vec<Arc<FlightChannel>>>>,
+}
+
+impl FlightChannelPool {
+    pub fn create(channels_per_peer: usize) -> FlightChannelPool {
+        FlightChannelPool {
+            channels_per_peer: channels_per_peer.max(1),
+            next_channel_id: AtomicU64::new(1),
+            peers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a client on the channel to `peer` with the fewest active streams, opening a new
+    /// one while there are fewer than `channels_per_peer` healthy channels.
+    pub fn get_flight_client(
+        &self,
+        peer: impl ToString,
+        tls_conf: Option<RpcClientTlsConfig>,
+    ) -> Result<FlightClient> {
+        let peer = peer.to_string();
+        let mut peers = self.peers.lock();
+        let channels = peers.entry(peer.clone()).or_insert_with(Vec::new);
+
+        channels.retain(|channel| !channel.is_broken());
+        if channels.len() < self.channels_per_peer {
+            let channel = ConnectionFactory::create_flight_channel_with_keepalive(
+                peer.clone(),
+                None,
+                Some(FLIGHT_CHANNEL_KEEPALIVE),
+                tls_conf,
+            )?;
+            let id = self.next_channel_id.fetch_add(1, Ordering::Relaxed);
+            channels.push(Arc::new(FlightChannel::create(id, peer, channel)));
+        }
+
+        let channel = channels
+            .iter()
+            .min_by_key(|channel| channel.active_streams.load(Ordering::Relaxed))
+            .cloned()
+            .unwrap();
+        Ok(FlightClient::new(channel))
+    }
+
+    /// The channels in the pool, sorted by peer and id.
+    pub fn get_stats(&self) -> Vec<FlightChannelStats> {
+        let mut stats = self
+            .peers
+            .lock()
+            .values()
+            .flatten()
+            .map(|channel| channel.stats())
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| (&a.peer, a.channel_id).cmp(&(&b.peer, b.channel_id)));
+        stats
+    }
+}
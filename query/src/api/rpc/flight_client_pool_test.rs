@@ -0,0 +1,188 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use common_datablocks::assert_blocks_eq;
+use common_datavalues::DataValue;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::Expression;
+use common_planners::PlanNode;
+use common_runtime::tokio;
+use common_runtime::tokio::net::TcpListener;
+use common_runtime::tokio::sync::Notify;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use tokio_stream::wrappers::TcpListenerStream;
+
+use crate::api::rpc::flight_client_pool::FlightChannelPool;
+use crate::api::rpc::flight_client_pool::FlightChannelStats;
+use crate::api::rpc::flight_tickets::StreamTicket;
+use crate::api::rpc::DatabendQueryFlightDispatcher;
+use crate::api::FlightAction;
+use crate::api::FlightTicket;
+use crate::api::RpcService;
+use crate::api::ShuffleAction;
+use crate::tests::parse_query;
+use crate::tests::try_create_session_mgr;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_channel_pool_shares_connection() -> Result<()> {
+    // - Prepare several stages on a peer and fetch all of their streams at once.
+    // - They all complete with their data over the only connection to the peer.
+
+    let (addr, accepted) = start_counted_rpc_service().await?;
+    let pool = FlightChannelPool::create(1);
+    let plan = parse_query("SELECT number FROM numbers(5)")?;
+    let stages = 4;
+
+    for index in 0..stages {
+        let mut client = pool.get_flight_client(&addr, None)?;
+        let action = prepare_action("query_id", &format!("stage_{}", index), plan.clone());
+        client.execute_action(action, 10).await?;
+    }
+
+    let mut streams = vec![];
+    for index in 0..stages {
+        let mut client = pool.get_flight_client(&addr, None)?;
+        let ticket = stream_ticket("query_id", &format!("stage_{}", index));
+        streams.push(client.fetch_stream(ticket, plan.schema(), 10).await?);
+    }
+
+    let stats = pool.get_stats();
+    assert_eq!(1, stats.len());
+    assert_eq!(stages, stats[0].active_streams);
+
+    for stream in streams {
+        let blocks = stream.try_collect::<Vec<_>>().await?;
+        let expect = vec![
+            "+--------+",
+            "| number |",
+            "+--------+",
+            "| 0      |",
+            "| 1      |",
+            "| 2      |",
+            "| 3      |",
+            "| 4      |",
+            "+--------+",
+        ];
+        assert_blocks_eq(expect, &blocks);
+    }
+
+    assert_eq!(
+        vec![FlightChannelStats {
+            peer: addr.clone(),
+            channel_id: 1,
+            active_streams: 0,
+            total_streams: 2 * stages as u64,
+            broken: false,
+        }],
+        pool.get_stats()
+    );
+    assert_eq!(1, accepted.load(Ordering::Relaxed));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_channel_pool_replaces_broken_channel() -> Result<()> {
+    // - A call to a peer that is gone fails with the channel and the peer in the error.
+    // - The broken channel is replaced by the next client of the peer.
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?.to_string();
+    drop(listener);
+
+    let pool = FlightChannelPool::create(1);
+    let plan = parse_query("SELECT number FROM numbers(5)")?;
+
+    let mut client = pool.get_flight_client(&addr, None)?;
+    let action = prepare_action("query_id", "stage_0", plan);
+    let err = client.execute_action(action, 10).await.unwrap_err();
+    assert_eq!(ErrorCode::BrokenChannel("").code(), err.code());
+    assert!(
+        err.message()
+            .starts_with(&format!("flight channel 1 to {} is broken", addr)),
+        "got: {}",
+        err.message()
+    );
+
+    let stats = pool.get_stats();
+    assert_eq!(1, stats.len());
+    assert!(stats[0].broken);
+    assert_eq!(0, stats[0].active_streams);
+
+    let client = pool.get_flight_client(&addr, None)?;
+    assert_eq!(2, client.get_channel().id());
+    assert!(!client.get_channel().is_broken());
+
+    let ids = pool
+        .get_stats()
+        .iter()
+        .map(|stats| stats.channel_id)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![2], ids);
+
+    Ok(())
+}
+
+/// Starts a flight service on a random port, returns its address and the number of the
+/// connections it accepted.
+async fn start_counted_rpc_service() -> Result<(String, Arc<AtomicUsize>)> {
+    let sessions = try_create_session_mgr(None)?;
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?.to_string();
+
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let incoming = {
+        let accepted = accepted.clone();
+        TcpListenerStream::new(listener).inspect(move |_| {
+            accepted.fetch_add(1, Ordering::Relaxed);
+        })
+    };
+
+    let mut srv = RpcService {
+        sessions,
+        abort_notify: Arc::new(Notify::new()),
+        dispatcher: Arc::new(DatabendQueryFlightDispatcher::create()),
+    };
+    srv.start_with_incoming(incoming).await?;
+    Ok((addr, accepted))
+}
+
+fn prepare_action(query_id: &str, stage_id: &str, plan: PlanNode) -> FlightAction {
+    FlightAction::PrepareShuffleAction(ShuffleAction {
+        query_id: query_id.to_string(),
+        stage_id: stage_id.to_string(),
+        plan,
+        sinks: vec![String::from("stream_id")],
+        scatters_expression: Expression::create_literal(DataValue::UInt64(Some(1))),
+        query_label: None,
+        preserve_order: false,
+        strict_arithmetic: false,
+    })
+}
+
+fn stream_ticket(query_id: &str, stage_id: &str) -> FlightTicket {
+    FlightTicket::StreamTicket(StreamTicket {
+        query_id: query_id.to_string(),
+        stage_id: stage_id.to_string(),
+        stream: String::from("stream_id"),
+        compression: None,
+        dictionary_encoding: false,
+    })
+}
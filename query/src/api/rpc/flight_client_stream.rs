@@ -12,19 +12,128 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
+use std::io::Cursor;
 use std::sync::Arc;
 
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::datatypes::DataType as ArrowDataType;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow::io::ipc::gen::Message::root_as_message;
+use common_arrow::arrow::io::ipc::gen::Message::MessageHeader;
+use common_arrow::arrow::io::ipc::read::read_dictionary;
 use common_arrow::arrow::record_batch::RecordBatch;
 use common_arrow::arrow_flight::utils::flight_data_to_arrow_batch;
 use common_arrow::arrow_flight::FlightData;
 use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
 use common_exception::ErrorCode;
+use common_exception::Result;
 use common_runtime::tokio::sync::mpsc::Receiver;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
-use tonic::Streaming;
+
+use crate::api::rpc::flight_compression::FlightCompression;
+use crate::api::rpc::flight_dictionary::decode_dictionary;
+use crate::api::rpc::flight_ordering::FlightBlockTag;
+
+/// Turns the FlightData of a stream back into blocks. The schema and the dictionary messages of
+/// a dictionary-encoded stream are kept for the record batches after them.
+pub struct FlightDataDecoder {
+    schema: DataSchemaRef,
+    arrow_schema: Arc<ArrowSchema>,
+    dictionaries: Vec<Option<Arc<dyn Array>>>,
+    dictionary_encoded: bool,
+}
+
+impl FlightDataDecoder {
+    pub fn create(schema: DataSchemaRef) -> FlightDataDecoder {
+        let arrow_schema = Arc::new(schema.to_arrow());
+        FlightDataDecoder {
+            schema,
+            dictionaries: vec![None; arrow_schema.fields().len()],
+            arrow_schema,
+            dictionary_encoded: false,
+        }
+    }
+
+    /// Returns the block of a record batch, None for the messages of the state of the stream.
+    pub fn decode(&mut self, flight_data: FlightData) -> Result<Option<DataBlock>> {
+        let flight_data = FlightCompression::decompress(flight_data)?;
+        let message = root_as_message(&flight_data.data_header[..]).map_err(|cause| {
+            ErrorCode::BadBytes(format!("Cannot decode the flight data header: {:?}", cause))
+        })?;
+
+        match message.header_type() {
+            MessageHeader::Schema => {
+                let arrow_schema = ArrowSchema::try_from(&flight_data)?;
+                self.dictionaries = vec![None; arrow_schema.fields().len()];
+                self.arrow_schema = Arc::new(arrow_schema);
+                self.dictionary_encoded = true;
+                Ok(None)
+            }
+            MessageHeader::DictionaryBatch => {
+                let batch = message.header_as_dictionary_batch().ok_or_else(|| {
+                    ErrorCode::BadBytes("Cannot decode the dictionary batch of the flight data")
+                })?;
+                let mut reader = Cursor::new(&flight_data.data_body);
+                read_dictionary(
+                    batch,
+                    &self.arrow_schema,
+                    &mut self.dictionaries,
+                    &mut reader,
+                    0,
+                )?;
+                Ok(None)
+            }
+            _ => {
+                self.check_dictionaries()?;
+                let record_batch = flight_data_to_arrow_batch(
+                    &flight_data,
+                    self.arrow_schema.clone(),
+                    true,
+                    &self.dictionaries,
+                )?;
+                Ok(Some(self.create_data_block(record_batch)?))
+            }
+        }
+    }
+
+    // A record batch can't be read without the dictionaries of its columns.
+    fn check_dictionaries(&self) -> Result<()> {
+        let fields = self.arrow_schema.fields().iter();
+        for (field, dictionary) in fields.zip(self.dictionaries.iter()) {
+            if let (ArrowDataType::Dictionary(_, _), None) = (field.data_type(), dictionary) {
+                return Err(ErrorCode::BadBytes(format!(
+                    "The dictionary of the column {} is not received",
+                    field.name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_data_block(&self, record_batch: RecordBatch) -> Result<DataBlock> {
+        if !self.dictionary_encoded {
+            let columns = record_batch
+                .columns()
+                .iter()
+                .map(|column| DataColumn::Array(column.clone().into_series()))
+                .collect::<Vec<_>>();
+
+            let schema = DataSchema::from(record_batch.schema().as_ref());
+            return Ok(DataBlock::create(Arc::new(schema), columns));
+        }
+
+        let columns = record_batch
+            .columns()
+            .iter()
+            .map(|column| Ok(DataColumn::Array(decode_dictionary(column)?.into_series())))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(DataBlock::create(self.schema.clone(), columns))
+    }
+}
 
 #[derive(Debug)]
 pub struct FlightDataStream();
@@ -33,32 +142,44 @@ impl FlightDataStream {
     #[inline]
     pub fn from_remote(
         schema: DataSchemaRef,
-        inner: Streaming<FlightData>,
-    ) -> impl Stream<Item = Result<DataBlock, ErrorCode>> {
-        inner.map(move |flight_data| -> Result<DataBlock, ErrorCode> {
-            match flight_data {
-                Err(status) => Err(ErrorCode::UnknownException(status.message())),
-                Ok(flight_data) => {
-                    fn create_data_block(record_batch: RecordBatch) -> DataBlock {
-                        let columns = record_batch
-                            .columns()
-                            .iter()
-                            .map(|column| DataColumn::Array(column.clone().into_series()))
-                            .collect::<Vec<_>>();
-
-                        DataBlock::create(
-                            Arc::new(DataSchema::from(record_batch.schema().as_ref())),
-                            columns,
-                        )
-                    }
-
-                    let arrow_schema = Arc::new(schema.to_arrow());
-                    Ok(
-                        flight_data_to_arrow_batch(&flight_data, arrow_schema, true, &[])
-                            .map(create_data_block)?,
-                    )
+        inner: impl Stream<Item = Result<FlightData>>,
+    ) -> impl Stream<Item = Result<DataBlock>> {
+        let mut decoder = FlightDataDecoder::create(schema);
+        inner.filter_map(move |flight_data| match flight_data {
+            Err(error_code) => Some(Err(error_code)),
+            Ok(flight_data) => decoder.decode(flight_data).transpose(),
+        })
+    }
+
+    /// Decode the blocks of an order-preserving stage along with their tags,
+    /// a block tagged with another source than `source` is rejected.
+    #[inline]
+    pub fn from_remote_tagged(
+        schema: DataSchemaRef,
+        source: String,
+        inner: impl Stream<Item = Result<FlightData>>,
+    ) -> impl Stream<Item = Result<(FlightBlockTag, DataBlock)>> {
+        let mut decoder = FlightDataDecoder::create(schema);
+        inner.filter_map(move |flight_data| {
+            let decode = |flight_data: Result<FlightData>| {
+                let flight_data = flight_data?;
+                // Only the values of a block are tagged.
+                let app_metadata = flight_data.app_metadata.clone();
+                let block = match decoder.decode(flight_data)? {
+                    None => return Ok(None),
+                    Some(block) => block,
+                };
+
+                let tag = FlightBlockTag::from_app_metadata(&app_metadata)?;
+                if tag.source != source {
+                    return Err(ErrorCode::BrokenExchangeOrder(format!(
+                        "The block of {} is received on the stream {}",
+                        tag.source, source
+                    )));
                 }
-            }
+                Ok(Some((tag, block)))
+            };
+            decode(flight_data).transpose()
         })
     }
 
@@ -67,30 +188,8 @@ impl FlightDataStream {
     #[allow(dead_code)]
     pub fn from_receiver(
         schema_ref: DataSchemaRef,
-        inner: Receiver<Result<FlightData, ErrorCode>>,
-    ) -> impl Stream<Item = Result<DataBlock, ErrorCode>> {
-        ReceiverStream::new(inner).map(move |flight_data| match flight_data {
-            Err(error_code) => Err(error_code),
-            Ok(flight_data) => {
-                fn create_data_block(record_batch: RecordBatch) -> DataBlock {
-                    let columns = record_batch
-                        .columns()
-                        .iter()
-                        .map(|column| DataColumn::Array(column.clone().into_series()))
-                        .collect::<Vec<_>>();
-
-                    let schema = DataSchema::from(record_batch.schema().as_ref());
-                    DataBlock::create(Arc::new(schema), columns)
-                }
-
-                Ok(flight_data_to_arrow_batch(
-                    &flight_data,
-                    Arc::new(schema_ref.to_arrow()),
-                    true,
-                    &[],
-                )
-                .map(create_data_block)?)
-            }
-        })
+        inner: Receiver<Result<FlightData>>,
+    ) -> impl Stream<Item = Result<DataBlock>> {
+        Self::from_remote(schema_ref, ReceiverStream::new(inner))
     }
 }
@@ -0,0 +1,90 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_arrow::arrow_flight::flight_descriptor::DescriptorType;
+use common_arrow::arrow_flight::FlightData;
+use common_arrow::arrow_flight::FlightDescriptor;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+/// The compression of the body of the FlightData of a stream, it is requested by the receiver in
+/// the `StreamTicket`. The sender marks each compressed FlightData in its descriptor, so that the
+/// receiver decodes the blocks of a sender that doesn't compress as well.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlightCompression {
+    Lz4,
+}
+
+impl FlightCompression {
+    /// Parses the flight_compression setting, None means no compression.
+    pub fn from_setting(value: &str) -> Result<Option<FlightCompression>> {
+        match value
+            .trim_matches(|c| c == '\'' || c == '"')
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "none" => Ok(None),
+            "lz4" => Ok(Some(FlightCompression::Lz4)),
+            _ => Err(ErrorCode::BadArguments(format!(
+                "Unknown flight_compression '{}', it must be 'lz4' or 'none'",
+                value
+            ))),
+        }
+    }
+
+    fn marker(&self) -> &'static [u8] {
+        match self {
+            FlightCompression::Lz4 => b"compression:lz4",
+        }
+    }
+
+    fn from_marker(descriptor: &FlightDescriptor) -> Result<Option<FlightCompression>> {
+        match descriptor.cmd.as_slice() {
+            b"compression:lz4" => Ok(Some(FlightCompression::Lz4)),
+            cmd if cmd.starts_with(b"compression:") => Err(ErrorCode::BadBytes(format!(
+                "Unknown compression of the flight data: {}",
+                String::from_utf8_lossy(cmd)
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn compress(&self, mut flight_data: FlightData) -> Result<FlightData> {
+        flight_data.data_body = match self {
+            FlightCompression::Lz4 => lz4::block::compress(&flight_data.data_body, None, true)?,
+        };
+        flight_data.flight_descriptor = Some(FlightDescriptor {
+            r#type: DescriptorType::Cmd as i32,
+            cmd: self.marker().to_vec(),
+            path: vec![],
+        });
+        Ok(flight_data)
+    }
+
+    /// Restores the body of a compressed FlightData, the others are returned as is.
+    pub fn decompress(mut flight_data: FlightData) -> Result<FlightData> {
+        let compression = match &flight_data.flight_descriptor {
+            None => None,
+            Some(descriptor) => Self::from_marker(descriptor)?,
+        };
+
+        if let Some(compression) = compression {
+            flight_data.data_body = match compression {
+                FlightCompression::Lz4 => lz4::block::decompress(&flight_data.data_body, None)?,
+            };
+            flight_data.flight_descriptor = None;
+        }
+        Ok(flight_data)
+    }
+}
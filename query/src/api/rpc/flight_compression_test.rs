@@ -0,0 +1,143 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_arrow::arrow_flight::flight_descriptor::DescriptorType;
+use common_arrow::arrow_flight::FlightData;
+use common_arrow::arrow_flight::FlightDescriptor;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::mpsc::channel;
+use futures::StreamExt;
+
+use crate::api::rpc::flight_client_stream::FlightDataStream as FlightClientStream;
+use crate::api::rpc::flight_service_stream::FlightDataStream as FlightServiceStream;
+use crate::api::FlightCompression;
+
+#[test]
+fn test_flight_compression_from_setting() -> Result<()> {
+    assert_eq!(FlightCompression::from_setting("")?, None);
+    assert_eq!(FlightCompression::from_setting("none")?, None);
+    assert_eq!(
+        FlightCompression::from_setting("LZ4")?,
+        Some(FlightCompression::Lz4)
+    );
+    assert_eq!(
+        FlightCompression::from_setting("'lz4'")?,
+        Some(FlightCompression::Lz4)
+    );
+
+    match FlightCompression::from_setting("snappy") {
+        Ok(_) => assert!(false, "An unknown compression must be rejected"),
+        Err(error) => assert_eq!(error.code(), ErrorCode::BadArguments("").code()),
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_compression_round_trip() -> Result<()> {
+    let block = long_string_block(100000);
+
+    let (plain_bytes, plain_blocks) = round_trip(&block, None).await?;
+    let (lz4_bytes, lz4_blocks) = round_trip(&block, Some(FlightCompression::Lz4)).await?;
+
+    assert_blocks_equal(&plain_blocks, &block)?;
+    assert_blocks_equal(&lz4_blocks, &block)?;
+    assert!(
+        lz4_bytes < plain_bytes,
+        "The compressed payload is {} bytes, the plain one is {} bytes",
+        lz4_bytes,
+        plain_bytes
+    );
+    Ok(())
+}
+
+#[test]
+fn test_flight_compression_unknown_marker() -> Result<()> {
+    let flight_data = FlightData {
+        flight_descriptor: Some(FlightDescriptor {
+            r#type: DescriptorType::Cmd as i32,
+            cmd: b"compression:snappy".to_vec(),
+            path: vec![],
+        }),
+        ..Default::default()
+    };
+
+    match FlightCompression::decompress(flight_data) {
+        Ok(_) => assert!(false, "An unknown compression must be rejected"),
+        Err(error) => assert_eq!(error.code(), ErrorCode::BadBytes("").code()),
+    }
+    Ok(())
+}
+
+fn long_string_block(rows: usize) -> DataBlock {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("number", DataType::UInt64, false),
+        DataField::new("text", DataType::String, false),
+    ]);
+
+    let numbers = (0..rows as u64).collect::<Vec<_>>();
+    let texts = (0..rows)
+        .map(|number| format!("the long string value of the row {:08}", number % 1000))
+        .collect::<Vec<_>>();
+    let texts = texts.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+
+    DataBlock::create_by_array(schema, vec![Series::new(numbers), Series::new(texts)])
+}
+
+// Sends the block through the stream of the sender, and decodes it as the receiver does.
+async fn round_trip(
+    block: &DataBlock,
+    compression: Option<FlightCompression>,
+) -> Result<(usize, Vec<DataBlock>)> {
+    let (tx, rx) = channel(1);
+    tx.send(Ok(block.clone())).await.ok();
+    drop(tx);
+
+    let flight_data = FlightServiceStream::create(rx)
+        .with_compression(compression)
+        .map(|flight_data| flight_data.map_err(ErrorCode::from))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    let payload_bytes = flight_data.iter().map(|x| x.data_body.len()).sum();
+    let blocks = FlightClientStream::from_remote(
+        block.schema().clone(),
+        futures::stream::iter(flight_data.into_iter().map(Ok)),
+    )
+    .collect::<Vec<_>>()
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?;
+
+    Ok((payload_bytes, blocks))
+}
+
+fn assert_blocks_equal(blocks: &[DataBlock], expect: &DataBlock) -> Result<()> {
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].num_rows(), expect.num_rows());
+    assert_eq!(blocks[0].num_columns(), expect.num_columns());
+    for index in 0..expect.num_columns() {
+        let column = blocks[0].column(index).to_array()?;
+        assert!(column.series_equal(&expect.column(index).to_array()?));
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_arrow::arrow::array::Array;
+use common_arrow::arrow::array::BinaryArray;
+use common_arrow::arrow::array::DictionaryArray;
+use common_arrow::arrow::array::MutableBinaryArray;
+use common_arrow::arrow::array::MutableDictionaryArray;
+use common_arrow::arrow::array::TryExtend;
+use common_arrow::arrow::compute::take::take;
+use common_arrow::arrow::datatypes::DataType as ArrowDataType;
+use common_arrow::arrow::datatypes::Field as ArrowField;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+type ArrayRef = Arc<dyn Array>;
+
+/// The schema of the blocks of a stream on the wire, the String columns are dictionary-encoded
+/// with Int32 keys, the dictionary id of a column is its index.
+pub fn dictionary_schema(schema: &ArrowSchema) -> ArrowSchema {
+    let fields = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match field.data_type() {
+            ArrowDataType::LargeBinary => ArrowField::new_dict(
+                field.name(),
+                ArrowDataType::Dictionary(
+                    Box::new(ArrowDataType::Int32),
+                    Box::new(ArrowDataType::LargeBinary),
+                ),
+                field.is_nullable(),
+                index as i64,
+                false,
+            ),
+            _ => field.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    ArrowSchema::new(fields)
+}
+
+/// Dictionary-encodes a String column, the other columns are returned as is.
+pub fn encode_dictionary(column: &ArrayRef) -> Result<ArrayRef> {
+    match column.data_type() {
+        ArrowDataType::LargeBinary => {
+            let values = column
+                .as_any()
+                .downcast_ref::<BinaryArray<i64>>()
+                .ok_or_else(|| ErrorCode::LogicalError("Cannot downcast to BinaryArray<i64>"))?;
+
+            let mut dictionary = MutableDictionaryArray::<i32, MutableBinaryArray<i64>>::new();
+            dictionary.try_extend(values.iter())?;
+            let dictionary: DictionaryArray<i32> = dictionary.into();
+            Ok(Arc::new(dictionary))
+        }
+        _ => Ok(column.clone()),
+    }
+}
+
+/// Restores the values of a dictionary-encoded column, the other columns are returned as is.
+pub fn decode_dictionary(column: &ArrayRef) -> Result<ArrayRef> {
+    match column.data_type() {
+        ArrowDataType::Dictionary(_, _) => {
+            let dictionary = column
+                .as_any()
+                .downcast_ref::<DictionaryArray<i32>>()
+                .ok_or_else(|| {
+                    ErrorCode::BadBytes("Only the dictionaries with Int32 keys are supported")
+                })?;
+
+            let values = take(dictionary.values().as_ref(), dictionary.keys())?;
+            Ok(Arc::from(values))
+        }
+        _ => Ok(column.clone()),
+    }
+}
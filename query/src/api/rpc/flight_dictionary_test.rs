@@ -0,0 +1,125 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_arrow::arrow_flight::FlightData;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::mpsc::channel;
+use futures::StreamExt;
+
+use crate::api::rpc::flight_client_stream::FlightDataStream as FlightClientStream;
+use crate::api::rpc::flight_service_stream::FlightDataStream as FlightServiceStream;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_dictionary_round_trip() -> Result<()> {
+    let first = color_block(1000, &["red", "green", "blue"]);
+    let replaced = color_block(500, &["yellow", "black"]);
+    let blocks = vec![first.clone(), first, replaced];
+
+    let flight_data = encode(&blocks).await?;
+    // The schema, the dictionary and the values of the first block, the second block reuses the
+    // dictionary, the third one replaces it.
+    assert_eq!(flight_data.len(), 6);
+
+    let decoded = decode(&blocks[0], flight_data).await?;
+    assert_eq!(decoded.len(), blocks.len());
+    for (block, expect) in decoded.iter().zip(blocks.iter()) {
+        assert_blocks_equal(block, expect)?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_dictionary_empty_block() -> Result<()> {
+    let blocks = vec![color_block(0, &[]), color_block(10, &["red"])];
+
+    let flight_data = encode(&blocks).await?;
+    let decoded = decode(&blocks[0], flight_data).await?;
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoded[0].num_rows(), 0);
+    assert_blocks_equal(&decoded[1], &blocks[1])?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_dictionary_missing() -> Result<()> {
+    let blocks = vec![color_block(10, &["red", "green"])];
+
+    // The values of the block without its dictionary.
+    let mut flight_data = encode(&blocks).await?;
+    flight_data.remove(1);
+
+    match decode(&blocks[0], flight_data).await {
+        Ok(_) => assert!(false, "A block without its dictionary must be rejected"),
+        Err(error) => assert_eq!(error.code(), ErrorCode::BadBytes("").code()),
+    }
+    Ok(())
+}
+
+fn color_block(rows: usize, colors: &[&str]) -> DataBlock {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("number", DataType::UInt64, false),
+        DataField::new("color", DataType::String, false),
+    ]);
+
+    let numbers = (0..rows as u64).collect::<Vec<_>>();
+    let colors = (0..rows)
+        .map(|number| colors[number % colors.len()])
+        .collect::<Vec<_>>();
+
+    DataBlock::create_by_array(schema, vec![Series::new(numbers), Series::new(colors)])
+}
+
+async fn encode(blocks: &[DataBlock]) -> Result<Vec<FlightData>> {
+    let (tx, rx) = channel(blocks.len());
+    for block in blocks {
+        tx.send(Ok(block.clone())).await.ok();
+    }
+    drop(tx);
+
+    FlightServiceStream::create(rx)
+        .with_dictionary_encoding(true)
+        .map(|flight_data| flight_data.map_err(ErrorCode::from))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+}
+
+async fn decode(block: &DataBlock, flight_data: Vec<FlightData>) -> Result<Vec<DataBlock>> {
+    FlightClientStream::from_remote(
+        block.schema().clone(),
+        futures::stream::iter(flight_data.into_iter().map(Ok)),
+    )
+    .collect::<Vec<_>>()
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>>>()
+}
+
+fn assert_blocks_equal(block: &DataBlock, expect: &DataBlock) -> Result<()> {
+    assert_eq!(block.num_rows(), expect.num_rows());
+    assert_eq!(block.schema(), expect.schema());
+    for index in 0..expect.num_columns() {
+        let column = block.column(index).to_array()?;
+        assert!(column.series_equal(&expect.column(index).to_array()?));
+    }
+    Ok(())
+}
@@ -14,8 +14,11 @@
 
 use std::collections::HashMap;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use common_datablocks::DataBlock;
 use common_datavalues::DataSchemaRef;
@@ -23,8 +26,11 @@ use common_exception::ErrorCode;
 use common_exception::Result;
 use common_exception::ToErrorCode;
 use common_infallible::RwLock;
+use common_runtime::tokio;
 use common_runtime::tokio::sync::mpsc::Sender;
 use common_runtime::tokio::sync::*;
+use common_runtime::SharedClock;
+use common_tracing::tracing;
 use tokio_stream::StreamExt;
 
 use crate::api::rpc::flight_scatter::FlightScatter;
@@ -32,6 +38,8 @@ use crate::api::rpc::flight_scatter_broadcast::BroadcastFlightScatter;
 use crate::api::rpc::flight_scatter_hash::HashFlightScatter;
 use crate::api::rpc::flight_tickets::StreamTicket;
 use crate::api::FlightAction;
+use crate::api::QueryProgress;
+use crate::api::StageProgress;
 use crate::pipelines::processors::PipelineBuilder;
 use crate::sessions::DatabendQueryContext;
 use crate::sessions::SessionRef;
@@ -41,12 +49,72 @@ struct StreamInfo {
     schema: DataSchemaRef,
     tx: mpsc::Sender<Result<DataBlock>>,
     rx: mpsc::Receiver<Result<DataBlock>>,
+    // The blocks are tagged with their position, see `FlightBlockTag`.
+    preserve_order: bool,
+}
+
+// How long the state of a query is kept once all its stages are finished or it is cancelled.
+const QUERY_STATE_TTL: Duration = Duration::from_secs(300);
+
+// How long a prepared stage waits for the first fetch of its streams, see `with_stage_ttl`.
+const DEFAULT_STAGE_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Default)]
+struct StageState {
+    rows: AtomicU64,
+    bytes: AtomicU64,
+    finished: AtomicBool,
+    expired: AtomicBool,
+}
+
+impl StageState {
+    fn add_block(&self, block: &DataBlock) {
+        self.rows
+            .fetch_add(block.num_rows() as u64, Ordering::Relaxed);
+        self.bytes
+            .fetch_add(block.memory_size() as u64, Ordering::Relaxed);
+    }
+
+    fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    fn expire(&self) {
+        self.expired.store(true, Ordering::Relaxed);
+        self.finish();
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expired.load(Ordering::Relaxed)
+    }
+}
+
+// The stages of a query on this node, it outlives the streams so that the progress can be read and
+// the fetches of a cancelled query are rejected.
+#[derive(Default)]
+struct QueryState {
+    cancelled: Arc<AtomicBool>,
+    stages: Vec<(String, Arc<StageState>)>,
+    done_at: Option<Instant>,
+}
+
+impl QueryState {
+    fn is_done(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .stages
+                .iter()
+                .all(|(_, stage)| stage.finished.load(Ordering::Relaxed))
+    }
 }
 
 pub struct DatabendQueryFlightDispatcher {
     streams: Arc<RwLock<HashMap<String, StreamInfo>>>,
     stages_notify: Arc<RwLock<HashMap<String, Arc<Notify>>>>,
+    queries: Arc<RwLock<HashMap<String, QueryState>>>,
     abort: Arc<AtomicBool>,
+    stage_ttl: Duration,
+    clock: SharedClock,
 }
 
 impl DatabendQueryFlightDispatcher {
@@ -54,10 +122,26 @@ impl DatabendQueryFlightDispatcher {
         DatabendQueryFlightDispatcher {
             streams: Arc::new(RwLock::new(HashMap::new())),
             stages_notify: Arc::new(RwLock::new(HashMap::new())),
+            queries: Arc::new(RwLock::new(HashMap::new())),
             abort: Arc::new(AtomicBool::new(false)),
+            stage_ttl: DEFAULT_STAGE_TTL,
+            clock: SharedClock::default(),
         }
     }
 
+    /// A prepared stage whose streams are not fetched within the ttl is dropped with its session,
+    /// e.g. its coordinator is gone. The later fetches fail with StageExpired.
+    pub fn with_stage_ttl(mut self, stage_ttl: Duration) -> DatabendQueryFlightDispatcher {
+        self.stage_ttl = stage_ttl;
+        self
+    }
+
+    /// The source of time of the stage ttl and of how long the done queries are kept.
+    pub fn with_clock(mut self, clock: SharedClock) -> DatabendQueryFlightDispatcher {
+        self.clock = clock;
+        self
+    }
+
     /// Reject new session if is aborted.
     pub fn abort(&self) {
         self.abort.store(true, Ordering::Relaxed)
@@ -67,7 +151,24 @@ impl DatabendQueryFlightDispatcher {
         self.abort.load(Ordering::Relaxed)
     }
 
+    /// The source to tag the blocks of the stream with, if its stage preserves order.
+    /// It must be called before the stream is taken by `get_stream`.
+    pub fn get_order_source(&self, ticket: &StreamTicket) -> Option<String> {
+        let stream_name = ticket.stream_name();
+        match self.streams.read().get(&stream_name) {
+            Some(stream_info) if stream_info.preserve_order => Some(stream_name),
+            _ => None,
+        }
+    }
+
     pub fn get_stream(&self, ticket: &StreamTicket) -> Result<mpsc::Receiver<Result<DataBlock>>> {
+        if self.is_cancelled(&ticket.query_id) {
+            return Err(ErrorCode::AbortedQuery(format!(
+                "The query {} is cancelled",
+                ticket.query_id
+            )));
+        }
+
         let stage_name = format!("{}/{}", ticket.query_id, ticket.stage_id);
         if let Some(notify) = self.stages_notify.write().remove(&stage_name) {
             notify.notify_waiters();
@@ -76,16 +177,104 @@ impl DatabendQueryFlightDispatcher {
         let stream_name = format!("{}/{}", stage_name, ticket.stream);
         match self.streams.write().remove(&stream_name) {
             Some(stream_info) => Ok(stream_info.rx),
+            None if self.is_stage_expired(&ticket.query_id, &ticket.stage_id) => {
+                Err(ErrorCode::StageExpired(format!(
+                    "The stage {} expired before its streams were fetched",
+                    stage_name
+                )))
+            }
             None => Err(ErrorCode::NotFoundStream("Stream is not found")),
         }
     }
 
+    fn is_stage_expired(&self, query_id: &str, stage_id: &str) -> bool {
+        match self.queries.read().get(query_id) {
+            None => false,
+            Some(query_state) => query_state
+                .stages
+                .iter()
+                .any(|(id, stage)| id == stage_id && stage.is_expired()),
+        }
+    }
+
+    /// The flag the streams of the query check, it is set once the query is cancelled.
+    pub fn get_cancel_flag(&self, query_id: &str) -> Arc<AtomicBool> {
+        let mut queries = self.queries.write();
+        let query_state = queries.entry(query_id.to_string()).or_default();
+        query_state.cancelled.clone()
+    }
+
+    pub fn is_cancelled(&self, query_id: &str) -> bool {
+        match self.queries.read().get(query_id) {
+            Some(query_state) => query_state.cancelled.load(Ordering::Relaxed),
+            None => false,
+        }
+    }
+
+    /// Tears down the stages of the query on this node: the streams not fetched yet are dropped,
+    /// the streams being fetched end with an AbortedQuery error and the later fetches are rejected.
+    /// The other queries are not affected.
+    pub fn cancel_query(&self, query_id: &str) {
+        self.get_cancel_flag(query_id)
+            .store(true, Ordering::Relaxed);
+
+        let prefix = format!("{}/", query_id);
+        self.streams
+            .write()
+            .retain(|stream_name, _| !stream_name.starts_with(&prefix));
+
+        let stages_notify = {
+            let mut stages_notify = self.stages_notify.write();
+            let stages_name = stages_notify
+                .keys()
+                .filter(|stage_name| stage_name.starts_with(&prefix))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            stages_name
+                .iter()
+                .filter_map(|stage_name| stages_notify.remove(stage_name))
+                .collect::<Vec<_>>()
+        };
+
+        // The waiting stages start and stop at once, their sinks are gone.
+        for notify in stages_notify {
+            notify.notify_waiters();
+        }
+    }
+
+    /// The rows and the bytes produced by each stage of the query on this node so far.
+    pub fn get_progress(&self, query_id: &str) -> QueryProgress {
+        match self.queries.read().get(query_id) {
+            None => QueryProgress::default(),
+            Some(query_state) => QueryProgress {
+                stages: query_state
+                    .stages
+                    .iter()
+                    .map(|(stage_id, stage)| StageProgress {
+                        stage_id: stage_id.clone(),
+                        rows: stage.rows.load(Ordering::Relaxed),
+                        bytes: stage.bytes.load(Ordering::Relaxed),
+                        finished: stage.finished.load(Ordering::Relaxed),
+                    })
+                    .collect(),
+            },
+        }
+    }
+
     pub fn broadcast_action(&self, session: SessionRef, action: FlightAction) -> Result<()> {
         let query_id = action.get_query_id();
         let stage_id = action.get_stage_id();
         let action_sinks = action.get_sinks();
         let data_schema = action.get_plan().schema();
-        self.create_stage_streams(&query_id, &stage_id, &data_schema, &action_sinks);
+        let preserve_order = action.get_preserve_order();
+        self.create_stage_streams(
+            &query_id,
+            &stage_id,
+            &data_schema,
+            &action_sinks,
+            preserve_order,
+        )?;
 
         match action.get_sinks().len() {
             0 => Err(ErrorCode::LogicalError("")),
@@ -99,7 +288,14 @@ impl DatabendQueryFlightDispatcher {
         let stage_id = action.get_stage_id();
         let action_sinks = action.get_sinks();
         let data_schema = action.get_plan().schema();
-        self.create_stage_streams(&query_id, &stage_id, &data_schema, &action_sinks);
+        let preserve_order = action.get_preserve_order();
+        self.create_stage_streams(
+            &query_id,
+            &stage_id,
+            &data_schema,
+            &action_sinks,
+            preserve_order,
+        )?;
 
         match action.get_sinks().len() {
             0 => Err(ErrorCode::LogicalError("")),
@@ -110,6 +306,11 @@ impl DatabendQueryFlightDispatcher {
 
     fn one_sink_action(&self, session: SessionRef, action: &FlightAction) -> Result<()> {
         let query_context = session.create_context();
+        query_context.set_query_label(action.get_query_label());
+        let strict_arithmetic = action.get_strict_arithmetic() as u64;
+        query_context
+            .get_settings()
+            .set_strict_arithmetic(strict_arithmetic)?;
         let action_context = DatabendQueryContext::new(query_context.clone());
         let pipeline_builder = PipelineBuilder::create(action_context.clone());
 
@@ -129,9 +330,14 @@ impl DatabendQueryFlightDispatcher {
         let tx_ref = self.streams.read().get(&stream_name).map(|x| x.tx.clone());
         let tx = tx_ref.ok_or_else(|| ErrorCode::NotFoundStream("Not found stream"))?;
 
+        let stage_state = self.get_stage_state(&action_query_id, &action_stage_id);
+        let metrics = query_context.get_query_metrics();
         query_context.execute_task(async move {
             let _session = session;
             wait_start(stage_name, stages_notify).await;
+            if stage_state.is_expired() {
+                return;
+            }
 
             match pipeline.execute().await {
                 Err(error) => {
@@ -139,6 +345,11 @@ impl DatabendQueryFlightDispatcher {
                 }
                 Ok(mut abortable_stream) => {
                     while let Some(item) = abortable_stream.next().await {
+                        if let Ok(block) = &item {
+                            stage_state.add_block(block);
+                        }
+
+                        let bytes = item.as_ref().map(DataBlock::memory_size);
                         if let Err(error) = tx.send(item).await {
                             log::error!(
                                 "Cannot push data when run_action_without_scatters. {}",
@@ -146,9 +357,14 @@ impl DatabendQueryFlightDispatcher {
                             );
                             break;
                         }
+                        if let Ok(bytes) = bytes {
+                            metrics.incr_exchange_sent(bytes);
+                        }
                     }
                 }
             };
+
+            stage_state.finish();
         })?;
         Ok(())
     }
@@ -156,6 +372,11 @@ impl DatabendQueryFlightDispatcher {
     fn action_with_scatter<T>(&self, session: SessionRef, action: &FlightAction) -> Result<()>
     where T: FlightScatter + Send + 'static {
         let query_context = session.create_context();
+        query_context.set_query_label(action.get_query_label());
+        let strict_arithmetic = action.get_strict_arithmetic() as u64;
+        query_context
+            .get_settings()
+            .set_strict_arithmetic(strict_arithmetic)?;
         let action_context = DatabendQueryContext::new(query_context.clone());
         let pipeline_builder = PipelineBuilder::create(action_context.clone());
 
@@ -191,21 +412,31 @@ impl DatabendQueryFlightDispatcher {
         let stage_name = format!("{}/{}", action_query_id, action_stage_id);
         let stages_notify = self.stages_notify.clone();
 
+        let strict_arithmetic = query_context.get_settings().get_strict_arithmetic()? != 0;
         let flight_scatter = T::try_create(
             action.get_plan().schema(),
             action.get_scatter_expression(),
             action.get_sinks().len(),
-        )?;
+        )?
+        .with_strict_arithmetic(strict_arithmetic);
 
+        let stage_state = self.get_stage_state(&action_query_id, &action_stage_id);
+        let metrics = query_context.get_query_metrics();
         query_context.execute_task(async move {
             let _session = session;
             wait_start(stage_name, stages_notify).await;
+            if stage_state.is_expired() {
+                return;
+            }
 
             let sinks_tx_ref = &sinks_tx;
+            let stage_state_ref = &stage_state;
             let forward_blocks = async move {
                 let mut abortable_stream = pipeline.execute().await?;
                 while let Some(item) = abortable_stream.next().await {
-                    let forward_blocks = flight_scatter.execute(&item?)?;
+                    let block = item?;
+                    stage_state_ref.add_block(&block);
+                    let forward_blocks = flight_scatter.execute(&block)?;
 
                     assert_eq!(forward_blocks.len(), sinks_tx_ref.len());
 
@@ -216,6 +447,7 @@ impl DatabendQueryFlightDispatcher {
                             .map_err_to_code(ErrorCode::LogicalError, || {
                                 "Cannot push data when run_action"
                             })?;
+                        metrics.incr_exchange_sent(forward_block.memory_size());
                     }
                 }
 
@@ -230,22 +462,108 @@ impl DatabendQueryFlightDispatcher {
                     }
                 }
             }
+
+            stage_state.finish();
         })?;
 
         Ok(())
     }
 
+    fn get_stage_state(&self, query_id: &str, stage_id: &str) -> Arc<StageState> {
+        let mut queries = self.queries.write();
+        let query_state = queries.entry(query_id.to_string()).or_default();
+        match query_state.stages.iter().find(|(id, _)| id == stage_id) {
+            Some((_, stage_state)) => stage_state.clone(),
+            None => {
+                let stage_state = Arc::new(StageState::default());
+                let stage_id = stage_id.to_string();
+                query_state.stages.push((stage_id, stage_state.clone()));
+                stage_state
+            }
+        }
+    }
+
+    // Drops the stage unless its streams are fetched within the ttl. The stage waits for its first
+    // fetch, it is woken up to find itself expired and releases its session.
+    fn expire_stage_after_ttl(
+        &self,
+        stage_name: String,
+        notify: Arc<Notify>,
+        stage_state: Arc<StageState>,
+    ) {
+        let ttl_passed = self.clock.sleep(self.stage_ttl);
+        let streams = self.streams.clone();
+        let stages_notify = self.stages_notify.clone();
+
+        tokio::spawn(async move {
+            ttl_passed.await;
+
+            let expired = {
+                let mut stages_notify = stages_notify.write();
+                match stages_notify.get(&stage_name) {
+                    Some(prepared) if Arc::ptr_eq(prepared, &notify) => {
+                        stages_notify.remove(&stage_name);
+                        true
+                    }
+                    _ => false,
+                }
+            };
+
+            if expired {
+                tracing::warn!(
+                    "The stage {} expired before its streams were fetched",
+                    stage_name
+                );
+
+                let prefix = format!("{}/", stage_name);
+                streams
+                    .write()
+                    .retain(|stream_name, _| !stream_name.starts_with(&prefix));
+
+                stage_state.expire();
+                notify.notify_waiters();
+            }
+        });
+    }
+
+    // Forgets the queries done for a while, it runs whenever a stage is created.
+    fn prune_queries(&self) {
+        let now = self.clock.instant();
+        self.queries.write().retain(|_, query_state| {
+            if !query_state.is_done() {
+                query_state.done_at = None;
+                return true;
+            }
+
+            let done_at = *query_state.done_at.get_or_insert(now);
+            now.duration_since(done_at) < QUERY_STATE_TTL
+        });
+    }
+
     fn create_stage_streams(
         &self,
         query_id: &str,
         stage_id: &str,
         schema: &DataSchemaRef,
         streams_name: &[String],
-    ) {
+        preserve_order: bool,
+    ) -> Result<()> {
+        self.prune_queries();
+        if self.is_cancelled(query_id) {
+            return Err(ErrorCode::AbortedQuery(format!(
+                "The query {} is cancelled",
+                query_id
+            )));
+        }
+
+        let stage_state = self.get_stage_state(query_id, stage_id);
+
         let stage_name = format!("{}/{}", query_id, stage_id);
+        let notify = Arc::new(Notify::new());
         self.stages_notify
             .write()
-            .insert(stage_name.clone(), Arc::new(Notify::new()));
+            .insert(stage_name.clone(), notify.clone());
+        self.expire_stage_after_ttl(stage_name.clone(), notify, stage_state);
 
         let mut streams = self.streams.write();
 
@@ -257,8 +575,11 @@ impl DatabendQueryFlightDispatcher {
                 schema: schema.clone(),
                 tx,
                 rx,
+                preserve_order,
             });
         }
+
+        Ok(())
     }
 }
 
@@ -64,6 +64,9 @@ async fn test_run_shuffle_action_with_no_scatters() -> Result<()> {
                 plan: parse_query("SELECT number FROM numbers(5)")?,
                 sinks: vec![stream_id.clone()],
                 scatters_expression: Expression::create_literal(DataValue::UInt64(Some(1))),
+                query_label: None,
+                preserve_order: false,
+                strict_arithmetic: false,
             }),
         )?;
 
@@ -90,6 +93,86 @@ async fn test_run_shuffle_action_with_no_scatters() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_run_shuffle_action_with_query_label() -> Result<()> {
+    if let (Some(query_id), Some(stage_id), Some(stream_id)) = generate_uuids(3) {
+        let flight_dispatcher = DatabendQueryFlightDispatcher::create();
+
+        let sessions = try_create_session_mgr(None)?;
+        let rpc_session = sessions.create_rpc_session(query_id.clone(), false)?;
+
+        flight_dispatcher.shuffle_action(
+            rpc_session,
+            FlightAction::PrepareShuffleAction(ShuffleAction {
+                query_id: query_id.clone(),
+                stage_id: stage_id.clone(),
+                plan: parse_query("SELECT number FROM numbers(5)")?,
+                sinks: vec![stream_id.clone()],
+                scatters_expression: Expression::create_literal(DataValue::UInt64(Some(1))),
+                query_label: Some("team=billing".to_string()),
+                preserve_order: false,
+                strict_arithmetic: false,
+            }),
+        )?;
+
+        // The stage is waiting for its stream to be fetched.
+        let processes_info = sessions.processes_info();
+        let stage = processes_info.iter().find(|info| info.id == query_id);
+        assert_eq!(
+            stage.and_then(|info| info.query_label.clone()),
+            Some("team=billing".to_string())
+        );
+
+        let stream = stream_ticket(&query_id, &stage_id, &stream_id);
+        let receiver = flight_dispatcher.get_stream(&stream)?;
+        let blocks = ReceiverStream::new(receiver)
+            .collect::<Result<Vec<_>>>()
+            .await?;
+        assert_eq!(blocks.iter().map(|b| b.num_rows()).sum::<usize>(), 5);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_run_shuffle_action_with_strict_arithmetic() -> Result<()> {
+    for strict_arithmetic in [false, true] {
+        if let (Some(query_id), Some(stage_id), Some(stream_id)) = generate_uuids(3) {
+            let flight_dispatcher = DatabendQueryFlightDispatcher::create();
+
+            let sessions = try_create_session_mgr(None)?;
+            let rpc_session = sessions.create_rpc_session(query_id.clone(), false)?;
+
+            // The remote session has the default settings, the action carries the setting.
+            flight_dispatcher.shuffle_action(
+                rpc_session,
+                FlightAction::PrepareShuffleAction(ShuffleAction {
+                    query_id: query_id.clone(),
+                    stage_id: stage_id.clone(),
+                    plan: parse_query("SELECT number % 0 FROM numbers(5)")?,
+                    sinks: vec![stream_id.clone()],
+                    scatters_expression: Expression::create_literal(DataValue::UInt64(Some(1))),
+                    query_label: None,
+                    preserve_order: false,
+                    strict_arithmetic,
+                }),
+            )?;
+
+            let stream = stream_ticket(&query_id, &stage_id, &stream_id);
+            let receiver = flight_dispatcher.get_stream(&stream)?;
+            let blocks = ReceiverStream::new(receiver)
+                .collect::<Result<Vec<_>>>()
+                .await;
+            match strict_arithmetic {
+                true => assert_eq!(61, blocks.unwrap_err().code()),
+                false => assert_eq!(blocks?.iter().map(|b| b.num_rows()).sum::<usize>(), 5),
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_run_shuffle_action_with_scatter() -> Result<()> {
     if let (Some(query_id), Some(stage_id), None) = generate_uuids(2) {
@@ -106,6 +189,9 @@ async fn test_run_shuffle_action_with_scatter() -> Result<()> {
                 plan: parse_query("SELECT number FROM numbers(5)")?,
                 sinks: vec!["stream_1".to_string(), "stream_2".to_string()],
                 scatters_expression: Expression::Column("number".to_string()),
+                query_label: None,
+                preserve_order: false,
+                strict_arithmetic: false,
             }),
         )?;
 
@@ -151,6 +237,8 @@ fn stream_ticket(query_id: &str, stage_id: &str, stream: &str) -> StreamTicket {
         query_id: query_id.to_string(),
         stage_id: stage_id.to_string(),
         stream: stream.to_string(),
+        compression: None,
+        dictionary_encoding: false,
     }
 }
 
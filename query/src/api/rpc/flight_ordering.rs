@@ -0,0 +1,265 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio::time::sleep;
+use common_runtime::tokio::time::Sleep;
+use tokio_stream::Stream;
+
+/// The position of a block in the stream of an order-preserving stage,
+/// it is carried in the app_metadata of the FlightData of the block.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FlightBlockTag {
+    /// The stream the block is sent on, i.e., `query_id/stage_id/stream`.
+    pub source: String,
+    /// The position of the block in the stream, from 0.
+    pub seq: u64,
+}
+
+impl FlightBlockTag {
+    pub fn to_app_metadata(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| ErrorCode::LogicalError(e.to_string()))
+    }
+
+    pub fn from_app_metadata(app_metadata: &[u8]) -> Result<FlightBlockTag> {
+        serde_json::from_slice(app_metadata).map_err(|e| {
+            ErrorCode::BrokenExchangeOrder(format!("Cannot decode the block tag: {}", e))
+        })
+    }
+}
+
+pub type TaggedBlockStream =
+    Pin<Box<dyn Stream<Item = Result<(FlightBlockTag, DataBlock)>> + Send>>;
+
+/// Buffers the blocks of several sources and releases them in order:
+/// the blocks of a source strictly by seq, and the sources round by round,
+/// i.e., the block N of every unfinished source before the block N+1 of any source.
+pub struct FlightReorderBuffer {
+    capacity: usize,
+    next_seq: Vec<u64>,
+    finished: Vec<bool>,
+    pending: BTreeMap<(usize, u64), DataBlock>,
+}
+
+impl FlightReorderBuffer {
+    pub fn create(sources: usize, capacity: usize) -> FlightReorderBuffer {
+        FlightReorderBuffer {
+            capacity: capacity.max(1),
+            next_seq: vec![0; sources],
+            finished: vec![false; sources],
+            pending: BTreeMap::new(),
+        }
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.pending.len() >= self.capacity
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.iter().all(|finished| *finished)
+    }
+
+    /// The source whose next block is released first, `None` if every source is finished.
+    pub fn waiting_for(&self) -> Option<usize> {
+        (0..self.next_seq.len())
+            .filter(|source| !self.finished[*source])
+            .min_by_key(|source| (self.next_seq[*source], *source))
+    }
+
+    /// Buffer a block. A block that is not the awaited one is rejected if the buffer is full.
+    pub fn push(&mut self, source: usize, seq: u64, block: DataBlock) -> Result<()> {
+        if seq < self.next_seq[source] || self.pending.contains_key(&(source, seq)) {
+            return Err(ErrorCode::BrokenExchangeOrder(format!(
+                "The block {} of the source {} is received twice",
+                seq, source
+            )));
+        }
+
+        let awaited = self.waiting_for() == Some(source) && seq == self.next_seq[source];
+        if !awaited && self.is_full() {
+            return Err(ErrorCode::BrokenExchangeOrder(format!(
+                "The reorder buffer is full with {} blocks, the block {} of the source {} is not buffered",
+                self.pending.len(),
+                seq,
+                source
+            )));
+        }
+
+        self.pending.insert((source, seq), block);
+        Ok(())
+    }
+
+    /// A source sends no more blocks, the blocks it skipped are lost.
+    pub fn finish(&mut self, source: usize) -> Result<()> {
+        self.finished[source] = true;
+
+        match self.first_pending(source) {
+            None => Ok(()),
+            Some(_) => Err(ErrorCode::BrokenExchangeOrder(format!(
+                "The block {} of the source {} is lost",
+                self.next_seq[source], source
+            ))),
+        }
+    }
+
+    /// Release the next block in order, if it is buffered.
+    pub fn pop(&mut self) -> Option<DataBlock> {
+        let source = self.waiting_for()?;
+        let seq = self.next_seq[source];
+        let block = self.pending.remove(&(source, seq))?;
+        self.next_seq[source] += 1;
+        Some(block)
+    }
+
+    /// The first missing block of a source whose later blocks are buffered, as `(source, seq)`.
+    pub fn gap(&self) -> Option<(usize, u64)> {
+        (0..self.next_seq.len())
+            .filter(|source| self.first_pending(*source).is_some())
+            .find(|source| {
+                !self
+                    .pending
+                    .contains_key(&(*source, self.next_seq[*source]))
+            })
+            .map(|source| (source, self.next_seq[source]))
+    }
+
+    fn first_pending(&self, source: usize) -> Option<u64> {
+        self.pending
+            .range((source, 0)..=(source, u64::MAX))
+            .next()
+            .map(|((_, seq), _)| *seq)
+    }
+}
+
+/// Merges the tagged blocks of several sources into a stream in the order of `FlightReorderBuffer`.
+/// Only the awaited source is read while the buffer is full, and a missing block whose source
+/// sent later ones fails the stream if it does not arrive within `gap_timeout`.
+pub struct OrderedBlockStream {
+    inputs: Vec<Option<TaggedBlockStream>>,
+    buffer: FlightReorderBuffer,
+    gap_timeout: Duration,
+    gap_deadline: Option<((usize, u64), Pin<Box<Sleep>>)>,
+}
+
+impl OrderedBlockStream {
+    pub fn create(
+        inputs: Vec<TaggedBlockStream>,
+        capacity: usize,
+        gap_timeout: Duration,
+    ) -> OrderedBlockStream {
+        OrderedBlockStream {
+            buffer: FlightReorderBuffer::create(inputs.len(), capacity),
+            inputs: inputs.into_iter().map(Some).collect(),
+            gap_timeout,
+            gap_deadline: None,
+        }
+    }
+
+    // Read one item from the sources, returns false if none of them is ready.
+    fn poll_inputs(&mut self, cx: &mut Context<'_>) -> Result<bool> {
+        let waiting_for = self.buffer.waiting_for();
+
+        for source in 0..self.inputs.len() {
+            if self.buffer.is_full() && Some(source) != waiting_for {
+                continue;
+            }
+
+            let input = match self.inputs[source].as_mut() {
+                None => continue,
+                Some(input) => input,
+            };
+
+            match input.as_mut().poll_next(cx) {
+                Poll::Pending => continue,
+                Poll::Ready(Some(Err(error))) => return Err(error),
+                Poll::Ready(Some(Ok((tag, block)))) => {
+                    self.buffer.push(source, tag.seq, block)?;
+                    return Ok(true);
+                }
+                Poll::Ready(None) => {
+                    self.inputs[source] = None;
+                    self.buffer.finish(source)?;
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn poll_gap(&mut self, cx: &mut Context<'_>) -> Result<()> {
+        let gap = match self.buffer.gap() {
+            None => {
+                self.gap_deadline = None;
+                return Ok(());
+            }
+            Some(gap) => gap,
+        };
+
+        if !matches!(&self.gap_deadline, Some((waiting, _)) if *waiting == gap) {
+            self.gap_deadline = Some((gap, Box::pin(sleep(self.gap_timeout))));
+        }
+
+        if let Some((_, deadline)) = self.gap_deadline.as_mut() {
+            if deadline.as_mut().poll(cx).is_ready() {
+                return Err(ErrorCode::BrokenExchangeOrder(format!(
+                    "The block {} of the source {} is not received in {:?}",
+                    gap.1, gap.0, self.gap_timeout
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Stream for OrderedBlockStream {
+    type Item = Result<DataBlock>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(block) = self.buffer.pop() {
+                return Poll::Ready(Some(Ok(block)));
+            }
+
+            if self.buffer.is_finished() {
+                return Poll::Ready(None);
+            }
+
+            match self.poll_inputs(cx) {
+                Err(error) => return Poll::Ready(Some(Err(error))),
+                Ok(true) => continue,
+                Ok(false) => {}
+            }
+
+            return match self.poll_gap(cx) {
+                Err(error) => Poll::Ready(Some(Err(error))),
+                Ok(_) => Poll::Pending,
+            };
+        }
+    }
+}
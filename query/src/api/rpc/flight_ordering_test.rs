@@ -0,0 +1,235 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use common_runtime::tokio::time::sleep;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultKind;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::FaultRule;
+use common_store_api_sdk::Injected;
+use futures::StreamExt;
+
+use crate::api::rpc::flight_ordering::FlightReorderBuffer;
+use crate::api::FlightBlockTag;
+use crate::api::OrderedBlockStream;
+use crate::api::TaggedBlockStream;
+
+#[test]
+fn test_flight_block_tag_app_metadata() -> Result<()> {
+    let tag = FlightBlockTag {
+        source: String::from("query_id/stage_id/stream_id"),
+        seq: 7,
+    };
+
+    let app_metadata = tag.to_app_metadata()?;
+    assert_eq!(FlightBlockTag::from_app_metadata(&app_metadata)?, tag);
+
+    match FlightBlockTag::from_app_metadata(b"not a tag") {
+        Ok(_) => assert!(false, "A broken tag must be rejected"),
+        Err(error) => assert_eq!(error.code(), broken_order_code()),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_ordered_stream_with_delayed_sources() -> Result<()> {
+    // The source 0 is slow, the source 1 is fast and longer,
+    // the source 2 sends its first two blocks in reverse.
+    let inputs = vec![
+        tagged_stream(0, vec![(0, 30), (1, 30), (2, 30)]),
+        tagged_stream(1, vec![(0, 1), (1, 1), (2, 1), (3, 1)]),
+        tagged_stream(2, vec![(1, 5), (0, 5), (2, 5)]),
+    ];
+
+    let stream = OrderedBlockStream::create(inputs, 8, Duration::from_secs(10));
+    let values = collect_values(stream).await?;
+
+    // The blocks of a source by seq, and the block N of every source before the block N+1.
+    assert_eq!(values, vec![0, 100, 200, 1, 101, 201, 2, 102, 202, 103]);
+    Ok(())
+}
+
+#[test]
+fn test_reorder_buffer_bound() -> Result<()> {
+    let mut buffer = FlightReorderBuffer::create(2, 2);
+
+    // The source 0 is awaited, the source 1 runs ahead until the buffer is full.
+    buffer.push(1, 0, block(100))?;
+    buffer.push(1, 1, block(101))?;
+    assert!(buffer.is_full());
+    assert_eq!(buffer.waiting_for(), Some(0));
+
+    match buffer.push(1, 2, block(102)) {
+        Ok(_) => assert!(
+            false,
+            "A full buffer must reject the block which is not awaited"
+        ),
+        Err(error) => assert_eq!(error.code(), broken_order_code()),
+    }
+    assert_eq!(buffer.buffered(), 2);
+
+    // The awaited block is always accepted, it is released at once.
+    buffer.push(0, 0, block(0))?;
+    assert_eq!(value(&buffer.pop().unwrap())?, 0);
+    assert_eq!(value(&buffer.pop().unwrap())?, 100);
+    assert!(buffer.pop().is_none());
+    assert_eq!(buffer.buffered(), 1);
+
+    // A block received twice.
+    match buffer.push(1, 0, block(100)) {
+        Ok(_) => assert!(false, "A duplicated block must be rejected"),
+        Err(error) => assert_eq!(error.code(), broken_order_code()),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_ordered_stream_respects_buffer_bound() -> Result<()> {
+    // While the slow source is awaited only it is read, so the fast one never overflows the buffer.
+    let slow_blocks = (0..5).map(|seq| (seq, 10)).collect::<Vec<_>>();
+    let fast_blocks = (0..20).map(|seq| (seq, 0)).collect::<Vec<_>>();
+    let inputs = vec![tagged_stream(0, slow_blocks), tagged_stream(1, fast_blocks)];
+
+    let stream = OrderedBlockStream::create(inputs, 1, Duration::from_secs(10));
+    let values = collect_values(stream).await?;
+
+    let mut expect = vec![];
+    for seq in 0..5 {
+        expect.push(seq);
+        expect.push(100 + seq);
+    }
+    expect.extend(105..120);
+    assert_eq!(values, expect);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_ordered_stream_gap_timeout() -> Result<()> {
+    // The block 1 of the source 0 is lost on the way, the connection of the source stays open.
+    let injector = Arc::new(FaultInjector::create());
+    injector.add_rule(
+        FaultRule::create(
+            Some("query_id/stage_id/0/1"),
+            FaultPhase::Response,
+            FaultKind::Drop,
+        )
+        .times(1),
+    );
+    let inputs = vec![
+        injected_stream(0, vec![0, 1, 2], injector),
+        tagged_stream(1, vec![(0, 0), (1, 0), (2, 0)]),
+    ];
+
+    let mut stream = OrderedBlockStream::create(inputs, 8, Duration::from_millis(50));
+    assert_eq!(value(&stream.next().await.unwrap()?)?, 0);
+    assert_eq!(value(&stream.next().await.unwrap()?)?, 100);
+
+    match stream.next().await {
+        Some(Err(error)) => {
+            assert_eq!(error.code(), broken_order_code());
+            assert!(error.message().contains("The block 1 of the source 0"));
+        }
+        _ => assert!(false, "The dropped block must fail the stream"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_ordered_stream_lost_block() -> Result<()> {
+    // The block 1 of the source 0 is dropped, and the source finishes.
+    let inputs = vec![tagged_stream(0, vec![(0, 0), (2, 0)])];
+
+    let stream = OrderedBlockStream::create(inputs, 8, Duration::from_secs(10));
+    match collect_values(stream).await {
+        Ok(_) => assert!(false, "The lost block must fail the stream"),
+        Err(error) => {
+            assert_eq!(error.code(), broken_order_code());
+            assert!(error.message().contains("is lost"));
+        }
+    }
+
+    Ok(())
+}
+
+fn broken_order_code() -> u16 {
+    ErrorCode::BrokenExchangeOrder("").code()
+}
+
+fn block(value: u64) -> DataBlock {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("v", DataType::UInt64, false)]);
+    DataBlock::create_by_array(schema, vec![Series::new(vec![value])])
+}
+
+fn value(block: &DataBlock) -> Result<u64> {
+    match block.column(0).try_get(0)? {
+        DataValue::UInt64(Some(value)) => Ok(value),
+        other => Err(ErrorCode::LogicalError(format!("Unexpected {:?}", other))),
+    }
+}
+
+// The blocks of the source are sent as (seq, delay in milliseconds), the value of a block is source * 100 + seq.
+fn tagged_stream(source: u64, blocks: Vec<(u64, u64)>) -> TaggedBlockStream {
+    Box::pin(
+        futures::stream::iter(blocks).then(move |(seq, delay)| async move {
+            sleep(Duration::from_millis(delay)).await;
+            let tag = FlightBlockTag {
+                source: format!("query_id/stage_id/{}", source),
+                seq,
+            };
+            Ok((tag, block(source * 100 + seq)))
+        }),
+    )
+}
+
+// The blocks of the source are sent through the injector as the responses of `<source>/<seq>`.
+// A dropped block is lost, and the stream is not closed after the last block, as a connection.
+fn injected_stream(source: u64, seqs: Vec<u64>, injector: Arc<FaultInjector>) -> TaggedBlockStream {
+    let blocks = seqs.into_iter().map(|seq| (seq, 0)).collect();
+    let stream = tagged_stream(source, blocks).filter_map(move |res| {
+        let injector = injector.clone();
+        async move {
+            let action = match &res {
+                Ok((tag, _)) => format!("{}/{}", tag.source, tag.seq),
+                Err(_) => return Some(res),
+            };
+            if injector.inject(FaultPhase::Response, &action).await == Injected::Drop {
+                return None;
+            }
+            Some(res)
+        }
+    });
+    Box::pin(stream.chain(futures::stream::pending()))
+}
+
+async fn collect_values(mut stream: OrderedBlockStream) -> Result<Vec<u64>> {
+    let mut values = vec![];
+    while let Some(block) = stream.next().await {
+        values.push(value(&block?)?);
+    }
+    Ok(values)
+}
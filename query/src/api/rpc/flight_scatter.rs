@@ -20,5 +20,11 @@ use common_planners::Expression;
 pub trait FlightScatter: Sized {
     fn try_create(schema: DataSchemaRef, expr: Option<Expression>, num: usize) -> Result<Self>;
 
+    /// Evaluates the scatter expression as the projections and filters of the query do,
+    /// see the strict_arithmetic setting.
+    fn with_strict_arithmetic(self, _strict_arithmetic: bool) -> Self {
+        self
+    }
+
     fn execute(&self, data_block: &DataBlock) -> Result<Vec<DataBlock>>;
 }
@@ -47,6 +47,13 @@ impl FlightScatter for HashFlightScatter {
         }
     }
 
+    fn with_strict_arithmetic(mut self, strict_arithmetic: bool) -> Self {
+        let executor = self.scatter_expression_executor.as_ref().clone();
+        self.scatter_expression_executor =
+            Arc::new(executor.with_strict_arithmetic(strict_arithmetic));
+        self
+    }
+
     fn execute(&self, data_block: &DataBlock) -> common_exception::Result<Vec<DataBlock>> {
         let expression_executor = self.scatter_expression_executor.clone();
         let evaluated_data_block = expression_executor.execute(data_block)?;
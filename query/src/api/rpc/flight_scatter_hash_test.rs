@@ -0,0 +1,42 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::col;
+use common_planners::lit;
+use common_planners::modular;
+
+use crate::api::rpc::flight_scatter::FlightScatter;
+use crate::api::rpc::flight_scatter_hash::HashFlightScatter;
+
+#[test]
+fn test_hash_scatter_strict_arithmetic() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64, 2, 3])]);
+    let expr = modular(col("a"), lit(0i64));
+
+    // The scatter key is evaluated as a projection or a filter, a modulo by zero is an error.
+    let scatter = HashFlightScatter::try_create(schema.clone(), Some(expr.clone()), 2)?
+        .with_strict_arithmetic(true);
+    assert_eq!(61, scatter.execute(&block).unwrap_err().code());
+
+    let scatter = HashFlightScatter::try_create(schema, Some(expr), 2)?;
+    if let Err(e) = scatter.execute(&block) {
+        assert_ne!(61, e.code());
+    }
+
+    Ok(())
+}
@@ -30,6 +30,8 @@ use common_arrow::arrow_flight::PutResult;
 use common_arrow::arrow_flight::Result as FlightResult;
 use common_arrow::arrow_flight::SchemaResult;
 use common_arrow::arrow_flight::Ticket;
+use common_exception::ErrorCode;
+use common_exception::ToErrorCode;
 use tokio_stream::Stream;
 use tonic::Request;
 use tonic::Response as RawResponse;
@@ -48,6 +50,7 @@ pub type FlightStream<T> =
 pub struct DatabendQueryFlightService {
     sessions: SessionManagerRef,
     dispatcher: Arc<DatabendQueryFlightDispatcher>,
+    compression_enabled: bool,
 }
 
 impl DatabendQueryFlightService {
@@ -58,8 +61,15 @@ impl DatabendQueryFlightService {
         DatabendQueryFlightService {
             sessions,
             dispatcher,
+            compression_enabled: true,
         }
     }
+
+    /// Whether the streams are compressed as their tickets ask, otherwise they are never compressed.
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression_enabled = enabled;
+        self
+    }
 }
 
 type Response<T> = Result<RawResponse<T>, Status>;
@@ -105,10 +115,22 @@ impl FlightService for DatabendQueryFlightService {
 
         match ticket {
             FlightTicket::StreamTicket(steam_ticket) => {
+                let order_source = self.dispatcher.get_order_source(&steam_ticket);
                 let receiver = self.dispatcher.get_stream(&steam_ticket)?;
 
+                let stream = match order_source {
+                    None => FlightDataStream::create(receiver),
+                    Some(source) => FlightDataStream::create_ordered(receiver, source),
+                };
+                let stream = match self.compression_enabled {
+                    true => stream.with_compression(steam_ticket.compression),
+                    false => stream,
+                };
+                let stream = stream
+                    .with_dictionary_encoding(steam_ticket.dictionary_encoding)
+                    .with_cancel_flag(self.dispatcher.get_cancel_flag(&steam_ticket.query_id));
                 Ok(RawResponse::new(
-                    Box::pin(FlightDataStream::create(receiver)) as FlightStream<FlightData>,
+                    Box::pin(stream) as FlightStream<FlightData>
                 ))
             }
         }
@@ -139,15 +161,27 @@ impl FlightService for DatabendQueryFlightService {
         let do_flight_action = || -> common_exception::Result<FlightResult> {
             match &flight_action {
                 FlightAction::CancelAction(action) => {
+                    // The streams are removed before the session is killed, so that the streams
+                    // being fetched see the cancellation once their stages stop.
+                    self.dispatcher.cancel_query(&action.query_id);
+
                     // We only destroy when session is exist
                     let session_id = action.query_id.clone();
                     if let Some(session) = self.sessions.get_session(&session_id) {
-                        // TODO: remove streams
                         session.force_kill_session();
                     }
 
                     Ok(FlightResult { body: vec![] })
                 }
+                FlightAction::GetProgressAction(action) => {
+                    let progress = self.dispatcher.get_progress(&action.query_id);
+                    let body = serde_json::to_vec(&progress)
+                        .map_err_to_code(ErrorCode::LogicalError, || {
+                            "Logical error: cannot serialize QueryProgress."
+                        })?;
+
+                    Ok(FlightResult { body })
+                }
                 FlightAction::BroadcastAction(action) => {
                     let session_id = action.query_id.clone();
                     let is_aborted = self.dispatcher.is_aborted();
@@ -12,12 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::VecDeque;
 use std::convert::TryInto;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
 use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
+use common_arrow::arrow::record_batch::RecordBatch;
 use common_arrow::arrow_flight::utils::flight_data_from_arrow_batch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
 use common_arrow::arrow_flight::FlightData;
 use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
 use common_runtime::tokio::macros::support::Pin;
 use common_runtime::tokio::macros::support::Poll;
 use common_runtime::tokio::sync::mpsc::Receiver;
@@ -25,9 +33,28 @@ use futures::task::Context;
 use tokio_stream::Stream;
 use tonic::Status;
 
+use crate::api::rpc::flight_compression::FlightCompression;
+use crate::api::rpc::flight_dictionary::dictionary_schema;
+use crate::api::rpc::flight_dictionary::encode_dictionary;
+use crate::api::rpc::flight_ordering::FlightBlockTag;
+
 pub struct FlightDataStream {
     input: Receiver<common_exception::Result<DataBlock>>,
     options: IpcWriteOptions,
+    // The tag of the next block, if the stream is of an order-preserving stage.
+    next_tag: Option<FlightBlockTag>,
+    compression: Option<FlightCompression>,
+    dictionary_encoding: bool,
+    // The schema on the wire, it is sent before the first block if it is not the one of the blocks.
+    dictionary_schema: Option<Arc<ArrowSchema>>,
+    // The last dictionary sent of each dictionary-encoded column, the receiver keeps using it
+    // until it is replaced.
+    sent_dictionaries: Vec<FlightData>,
+    // The FlightData of a block, the dictionaries before the values.
+    pending: VecDeque<FlightData>,
+    // Set once the query is cancelled, the stream then ends with an AbortedQuery error.
+    cancelled: Option<Arc<AtomicBool>>,
+    cancel_reported: bool,
 }
 
 impl FlightDataStream {
@@ -35,31 +62,152 @@ impl FlightDataStream {
         FlightDataStream {
             input,
             options: IpcWriteOptions::default(),
+            next_tag: None,
+            compression: None,
+            dictionary_encoding: false,
+            dictionary_schema: None,
+            sent_dictionaries: vec![],
+            pending: VecDeque::new(),
+            cancelled: None,
+            cancel_reported: false,
+        }
+    }
+
+    /// Tag the blocks with their position in the stream, see `FlightBlockTag`.
+    pub fn create_ordered(
+        input: Receiver<common_exception::Result<DataBlock>>,
+        source: String,
+    ) -> FlightDataStream {
+        FlightDataStream {
+            next_tag: Some(FlightBlockTag { source, seq: 0 }),
+            ..Self::create(input)
+        }
+    }
+
+    /// Compress the body of the FlightData, see `FlightCompression`.
+    pub fn with_compression(mut self, compression: Option<FlightCompression>) -> FlightDataStream {
+        self.compression = compression;
+        self
+    }
+
+    /// Dictionary-encode the String columns, see `dictionary_schema`.
+    pub fn with_dictionary_encoding(mut self, enabled: bool) -> FlightDataStream {
+        self.dictionary_encoding = enabled;
+        self
+    }
+
+    /// End the stream with an AbortedQuery error once the flag is set.
+    pub fn with_cancel_flag(mut self, cancelled: Arc<AtomicBool>) -> FlightDataStream {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        match &self.cancelled {
+            None => false,
+            Some(cancelled) => cancelled.load(Ordering::Relaxed),
+        }
+    }
+
+    // The error is reported once, the stream ends after it.
+    fn cancelled_error(&mut self) -> Option<Result<FlightData, Status>> {
+        match self.cancel_reported {
+            true => None,
+            false => {
+                self.cancel_reported = true;
+                let error = ErrorCode::AbortedQuery("The query of the stream is cancelled");
+                Some(Err(Status::from(error)))
+            }
         }
     }
+
+    fn encode(&mut self, block: DataBlock) -> common_exception::Result<()> {
+        let record_batch: RecordBatch = block.try_into()?;
+        let record_batch = match self.dictionary_encoding {
+            true => self.encode_dictionaries(record_batch)?,
+            false => record_batch,
+        };
+
+        let (dicts, mut values) = flight_data_from_arrow_batch(&record_batch, &self.options);
+        for (index, dict) in dicts.into_iter().enumerate() {
+            match self.sent_dictionaries.get(index) {
+                Some(sent)
+                    if sent.data_header == dict.data_header && sent.data_body == dict.data_body =>
+                {
+                    continue;
+                }
+                Some(_) => self.sent_dictionaries[index] = dict.clone(),
+                None => self.sent_dictionaries.push(dict.clone()),
+            }
+            self.push(dict)?;
+        }
+
+        if let Some(tag) = self.next_tag.as_mut() {
+            values.app_metadata = tag.to_app_metadata()?;
+            tag.seq += 1;
+        }
+        self.push(values)
+    }
+
+    fn encode_dictionaries(
+        &mut self,
+        record_batch: RecordBatch,
+    ) -> common_exception::Result<RecordBatch> {
+        let schema = match &self.dictionary_schema {
+            Some(schema) => schema.clone(),
+            None => {
+                let schema = Arc::new(dictionary_schema(record_batch.schema()));
+                self.push(flight_data_from_arrow_schema(&schema, &self.options))?;
+                self.dictionary_schema = Some(schema.clone());
+                schema
+            }
+        };
+
+        let columns = record_batch
+            .columns()
+            .iter()
+            .map(encode_dictionary)
+            .collect::<common_exception::Result<Vec<_>>>()?;
+        Ok(RecordBatch::try_new(schema, columns)?)
+    }
+
+    fn push(&mut self, flight_data: FlightData) -> common_exception::Result<()> {
+        let flight_data = match self.compression {
+            None => flight_data,
+            Some(compression) => compression.compress(flight_data)?,
+        };
+        self.pending.push_back(flight_data);
+        Ok(())
+    }
 }
 
 impl Stream for FlightDataStream {
     type Item = Result<FlightData, Status>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.input.poll_recv(cx).map(|x| match x {
-            None => None,
-            Some(Err(error)) => Some(Err(Status::from(error))),
-            Some(Ok(block)) => match block.try_into() {
-                Err(error) => Some(Err(Status::from(error))),
-                Ok(record_batch) => {
-                    let (dicts, values) =
-                        flight_data_from_arrow_batch(&record_batch, &self.options);
-
-                    match dicts.is_empty() {
-                        true => Some(Ok(values)),
-                        false => Some(Err(Status::unimplemented(
-                            "DatabendQuery does not implement dicts.",
-                        ))),
+        loop {
+            if self.is_cancelled() {
+                return Poll::Ready(self.cancelled_error());
+            }
+
+            if let Some(flight_data) = self.pending.pop_front() {
+                return Poll::Ready(Some(Ok(flight_data)));
+            }
+
+            match self.input.poll_recv(cx) {
+                Poll::Pending => return Poll::Pending,
+                // The stages of a cancelled query stop, their streams end as well.
+                Poll::Ready(None) if self.is_cancelled() => continue,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Ready(Some(Err(error))) => {
+                    return Poll::Ready(Some(Err(Status::from(error))))
+                }
+                Poll::Ready(Some(Ok(block))) => {
+                    if let Err(error) = self.encode(block) {
+                        return Poll::Ready(Some(Err(Status::from(error))));
                     }
                 }
-            },
-        })
+            }
+        }
     }
 }
@@ -14,6 +14,7 @@
 
 use std::convert::TryInto;
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_arrow::arrow_flight::flight_service_server::FlightService;
 use common_arrow::arrow_flight::Action;
@@ -24,14 +25,21 @@ use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::Expression;
 use common_runtime::tokio;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use futures::StreamExt;
 use tonic::Request;
 
 use crate::api::rpc::flight_actions::FlightAction;
 use crate::api::rpc::flight_tickets::StreamTicket;
 use crate::api::rpc::DatabendQueryFlightDispatcher;
 use crate::api::rpc::DatabendQueryFlightService;
+use crate::api::CancelAction;
 use crate::api::FlightTicket;
+use crate::api::GetProgressAction;
+use crate::api::QueryProgress;
 use crate::api::ShuffleAction;
+use crate::api::StageProgress;
 use crate::tests::parse_query;
 use crate::tests::try_create_session_mgr;
 
@@ -153,11 +161,178 @@ async fn test_do_flight_action_with_abort_and_new_session() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_do_flight_action_with_cancel_query() -> Result<()> {
+    let sessions = try_create_session_mgr(None)?;
+    let dispatcher = Arc::new(DatabendQueryFlightDispatcher::create());
+    let service = DatabendQueryFlightService::create(dispatcher, sessions);
+
+    for query_id in &["query_id_1", "query_id_2"] {
+        let request = do_action_request(query_id, "stage_id");
+        service.do_action(request?).await?;
+    }
+
+    let cancel_action = FlightAction::CancelAction(CancelAction {
+        query_id: String::from("query_id_1"),
+    });
+    service
+        .do_action(Request::new(cancel_action.try_into()?))
+        .await?;
+
+    // The cancelled query can neither fetch its streams nor prepare new stages.
+    let request = do_get_request("query_id_1", "stage_id");
+    match service.do_get(request?).await {
+        Ok(_) => assert!(false, "The streams of a cancelled query must be rejected"),
+        Err(error) => {
+            let error_code = ErrorCode::from(error);
+            assert_eq!(error_code.code(), ErrorCode::AbortedQuery("").code());
+            assert_eq!(error_code.message(), "The query query_id_1 is cancelled");
+        }
+    }
+
+    let request = do_action_request("query_id_1", "stage_id_2");
+    match service.do_action(request?).await {
+        Ok(_) => assert!(false, "A cancelled query must not prepare new stages"),
+        Err(error) => {
+            let error_code = ErrorCode::from(error);
+            assert_eq!(error_code.code(), ErrorCode::AbortedQuery("").code());
+        }
+    }
+
+    // The other query is not affected.
+    let request = do_get_request("query_id_2", "stage_id");
+    let flight_data = service
+        .do_get(request?)
+        .await?
+        .into_inner()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(!flight_data.is_empty());
+    for item in flight_data {
+        item?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_do_flight_action_with_get_progress() -> Result<()> {
+    let sessions = try_create_session_mgr(None)?;
+    let dispatcher = Arc::new(DatabendQueryFlightDispatcher::create());
+    let service = DatabendQueryFlightService::create(dispatcher, sessions);
+
+    let request = do_action_request("query_id", "stage_id");
+    service.do_action(request?).await?;
+
+    assert_eq!(get_progress(&service, "query_id").await?, QueryProgress {
+        stages: vec![StageProgress {
+            stage_id: String::from("stage_id"),
+            rows: 0,
+            bytes: 0,
+            finished: false,
+        }]
+    });
+
+    // The stage finishes before its stream ends.
+    let request = do_get_request("query_id", "stage_id");
+    let flight_data = service
+        .do_get(request?)
+        .await?
+        .into_inner()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(!flight_data.is_empty());
+
+    let progress = get_progress(&service, "query_id").await?;
+    assert_eq!(progress.stages.len(), 1);
+    assert_eq!(progress.stages[0].rows, 5);
+    assert!(progress.stages[0].bytes > 0);
+    assert!(progress.stages[0].finished);
+
+    // An unknown query has no stages.
+    let progress = get_progress(&service, "unknown_query_id").await?;
+    assert_eq!(progress, QueryProgress::default());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_do_flight_action_with_expired_stage() -> Result<()> {
+    let sessions = try_create_session_mgr(None)?;
+    let clock = VirtualClock::create();
+    let dispatcher = DatabendQueryFlightDispatcher::create()
+        .with_stage_ttl(Duration::from_secs(300))
+        .with_clock(SharedClock::create(clock.clone()));
+    let service = DatabendQueryFlightService::create(Arc::new(dispatcher), sessions.clone());
+
+    let sessions_before = sessions.processes_info().len();
+    let request = do_action_request("query_id", "stage_id");
+    service.do_action(request?).await?;
+    assert_eq!(sessions.processes_info().len(), sessions_before + 1);
+
+    // Not expired before the ttl passes.
+    clock.advance(Duration::from_secs(299));
+    assert_eq!(sessions.processes_info().len(), sessions_before + 1);
+
+    // The stage is dropped once the ttl passes, its session is released by the tasks of the stage.
+    clock.advance(Duration::from_secs(1));
+    for _index in 0..100 {
+        if sessions.processes_info().len() == sessions_before {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(sessions.processes_info().len(), sessions_before);
+
+    let request = do_get_request("query_id", "stage_id");
+    match service.do_get(request?).await {
+        Ok(_) => assert!(false, "The streams of an expired stage must be rejected"),
+        Err(error) => {
+            let error_code = ErrorCode::from(error);
+            assert_eq!(error_code.code(), ErrorCode::StageExpired("").code());
+        }
+    }
+
+    // A stage fetched in time is not expired.
+    let request = do_action_request("query_id", "stage_id_2");
+    service.do_action(request?).await?;
+    let request = do_get_request("query_id", "stage_id_2");
+    let stream = service.do_get(request?).await?.into_inner();
+    clock.advance(Duration::from_secs(300));
+    for item in stream.collect::<Vec<_>>().await {
+        item?;
+    }
+
+    Ok(())
+}
+
+async fn get_progress(
+    service: &DatabendQueryFlightService,
+    query_id: &str,
+) -> Result<QueryProgress> {
+    let get_progress_action = FlightAction::GetProgressAction(GetProgressAction {
+        query_id: String::from(query_id),
+    });
+
+    let request = Request::new(get_progress_action.try_into()?);
+    let mut results = service.do_action(request).await?.into_inner();
+    match results.next().await {
+        None => Err(ErrorCode::EmptyDataFromServer(
+            "GetProgressAction has no result",
+        )),
+        Some(result) => {
+            let body = result?.body;
+            serde_json::from_slice(&body).map_err(|cause| ErrorCode::BadBytes(cause.to_string()))
+        }
+    }
+}
+
 fn do_get_request(query_id: &str, stage_id: &str) -> Result<Request<Ticket>> {
     let stream_ticket = FlightTicket::StreamTicket(StreamTicket {
         query_id: String::from(query_id),
         stage_id: String::from(stage_id),
         stream: String::from("stream_id"),
+        compression: None,
+        dictionary_encoding: false,
     });
 
     Ok(Request::new(stream_ticket.try_into()?))
@@ -170,6 +345,9 @@ fn do_action_request(query_id: &str, stage_id: &str) -> Result<Request<Action>>
         plan: parse_query("SELECT number FROM numbers(5)")?,
         sinks: vec![String::from("stream_id")],
         scatters_expression: Expression::create_literal(DataValue::UInt64(Some(1))),
+        query_label: None,
+        preserve_order: false,
+        strict_arithmetic: false,
     });
 
     Ok(Request::new(flight_action.try_into()?))
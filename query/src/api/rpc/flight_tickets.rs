@@ -19,11 +19,19 @@ use common_exception::ErrorCode;
 use common_exception::ToErrorCode;
 use tonic::Status;
 
+use crate::api::rpc::flight_compression::FlightCompression;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct StreamTicket {
     pub query_id: String,
     pub stage_id: String,
     pub stream: String,
+    /// The compression the receiver asks for, the sender may ignore it.
+    #[serde(default)]
+    pub compression: Option<FlightCompression>,
+    /// Whether the receiver asks for the String columns dictionary-encoded.
+    #[serde(default)]
+    pub dictionary_encoding: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -37,8 +45,41 @@ impl FlightTicket {
             query_id: query_id.to_string(),
             stage_id: stage_id.to_string(),
             stream: stream.to_string(),
+            compression: None,
+            dictionary_encoding: false,
         })
     }
+
+    pub fn with_compression(self, compression: Option<FlightCompression>) -> FlightTicket {
+        match self {
+            FlightTicket::StreamTicket(ticket) => FlightTicket::StreamTicket(StreamTicket {
+                compression,
+                ..ticket
+            }),
+        }
+    }
+
+    pub fn with_dictionary_encoding(self, dictionary_encoding: bool) -> FlightTicket {
+        match self {
+            FlightTicket::StreamTicket(ticket) => FlightTicket::StreamTicket(StreamTicket {
+                dictionary_encoding,
+                ..ticket
+            }),
+        }
+    }
+
+    /// The name of the stream in the dispatcher, i.e., `query_id/stage_id/stream`.
+    pub fn stream_name(&self) -> String {
+        match self {
+            FlightTicket::StreamTicket(ticket) => ticket.stream_name(),
+        }
+    }
+}
+
+impl StreamTicket {
+    pub fn stream_name(&self) -> String {
+        format!("{}/{}/{}", self.query_id, self.stage_id, self.stream)
+    }
 }
 
 impl TryInto<FlightTicket> for Ticket {
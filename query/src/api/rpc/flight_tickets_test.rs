@@ -19,6 +19,7 @@ use common_exception::Result;
 use common_runtime::tokio;
 
 use crate::api::rpc::flight_tickets::StreamTicket;
+use crate::api::FlightCompression;
 use crate::api::FlightTicket;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -27,6 +28,8 @@ async fn test_stream_ticket_try_into() -> Result<()> {
         query_id: String::from("query_id"),
         stage_id: String::from("stage_id"),
         stream: String::from("stream"),
+        compression: None,
+        dictionary_encoding: false,
     });
 
     let to_ticket: Ticket = from_ticket.try_into()?;
@@ -41,3 +44,30 @@ async fn test_stream_ticket_try_into() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_stream_ticket_without_compression() -> Result<()> {
+    // The ticket of a node that doesn't know the compression.
+    let ticket = Ticket {
+        ticket:
+            br#"{"StreamTicket":{"query_id":"query_id","stage_id":"stage_id","stream":"stream"}}"#
+                .to_vec(),
+    };
+
+    let from_ticket: FlightTicket = ticket.try_into()?;
+    match from_ticket {
+        FlightTicket::StreamTicket(ticket) => assert_eq!(ticket.compression, None),
+    };
+
+    let with_compression = FlightTicket::stream("query_id", "stage_id", "stream")
+        .with_compression(Some(FlightCompression::Lz4));
+    let to_ticket: Ticket = with_compression.try_into()?;
+    let from_ticket: FlightTicket = to_ticket.try_into()?;
+    match from_ticket {
+        FlightTicket::StreamTicket(ticket) => {
+            assert_eq!(ticket.compression, Some(FlightCompression::Lz4))
+        }
+    };
+
+    Ok(())
+}
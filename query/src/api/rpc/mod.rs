@@ -12,9 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod flight_client_pool_test;
+
+#[cfg(test)]
+mod flight_compression_test;
+
+#[cfg(test)]
+mod flight_dictionary_test;
+
 #[cfg(test)]
 mod flight_dispatcher_test;
 
+#[cfg(test)]
+mod flight_ordering_test;
+
+#[cfg(test)]
+mod flight_scatter_hash_test;
+
 #[cfg(test)]
 mod flight_service_test;
 
@@ -27,16 +42,30 @@ mod flight_tickets_test;
 pub use flight_actions::BroadcastAction;
 pub use flight_actions::CancelAction;
 pub use flight_actions::FlightAction;
+pub use flight_actions::GetProgressAction;
+pub use flight_actions::QueryProgress;
 pub use flight_actions::ShuffleAction;
+pub use flight_actions::StageProgress;
 pub use flight_client::FlightClient;
+pub use flight_client_pool::FlightChannelPool;
+pub use flight_client_pool::FlightChannelStats;
+pub use flight_client_pool::FLIGHT_CHANNELS_PER_PEER;
+pub use flight_compression::FlightCompression;
 pub use flight_dispatcher::DatabendQueryFlightDispatcher;
+pub use flight_ordering::FlightBlockTag;
+pub use flight_ordering::OrderedBlockStream;
+pub use flight_ordering::TaggedBlockStream;
 pub use flight_service::DatabendQueryFlightService;
 pub use flight_tickets::FlightTicket;
 
 mod flight_actions;
 mod flight_client;
+mod flight_client_pool;
 mod flight_client_stream;
+mod flight_compression;
+mod flight_dictionary;
 mod flight_dispatcher;
+mod flight_ordering;
 mod flight_scatter;
 mod flight_scatter_broadcast;
 mod flight_scatter_hash;
@@ -15,14 +15,17 @@
 use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_arrow::arrow_flight::flight_service_server::FlightServiceServer;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_runtime::tokio;
 use common_runtime::tokio::net::TcpListener;
+use common_runtime::tokio::net::TcpStream;
 use common_runtime::tokio::sync::Notify;
 use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::Stream;
 use tonic::transport::Identity;
 use tonic::transport::Server;
 use tonic::transport::ServerTlsConfig;
@@ -41,10 +44,15 @@ pub struct RpcService {
 
 impl RpcService {
     pub fn create(sessions: SessionManagerRef) -> Box<dyn DatabendQueryServer> {
+        let stage_ttl = Duration::from_secs(sessions.get_conf().query.flight_stage_ttl_secs);
+        let dispatcher = DatabendQueryFlightDispatcher::create()
+            .with_stage_ttl(stage_ttl)
+            .with_clock(sessions.get_clock());
+
         Box::new(Self {
             sessions,
             abort_notify: Arc::new(Notify::new()),
-            dispatcher: Arc::new(DatabendQueryFlightDispatcher::create()),
+            dispatcher: Arc::new(dispatcher),
         })
     }
 
@@ -76,7 +84,8 @@ impl RpcService {
         Ok(tls_conf)
     }
 
-    pub async fn start_with_incoming(&mut self, listener_stream: TcpListenerStream) -> Result<()> {
+    pub async fn start_with_incoming<S>(&mut self, listener_stream: S) -> Result<()>
+    where S: Stream<Item = std::io::Result<TcpStream>> + Send + 'static {
         let sessions = self.sessions.clone();
         let flight_dispatcher = self.dispatcher.clone();
         let flight_api_service = DatabendQueryFlightService::create(flight_dispatcher, sessions);
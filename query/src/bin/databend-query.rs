@@ -13,8 +13,11 @@
 // limitations under the License.
 
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use common_runtime::tokio;
+use common_runtime::RuntimePools;
+use common_runtime::RuntimePoolsConfig;
 use common_tracing::init_tracing_with_file;
 use common_tracing::set_panic_hook;
 use databend_query::api::HttpService;
@@ -53,7 +56,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "databend-query",
         conf.log.log_dir.as_str(),
         conf.log.log_level.as_str(),
-    );
+    )?;
 
     set_panic_hook();
     info!("{:?}", conf);
@@ -62,6 +65,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         *databend_query::configs::config::DATABEND_COMMIT_VERSION,
     );
 
+    let runtime_pools = RuntimePools::init_global(&RuntimePoolsConfig {
+        io_threads: conf.query.runtime_io_threads as usize,
+        compute_threads: conf.query.runtime_compute_threads as usize,
+        management_threads: conf.query.runtime_management_threads as usize,
+    })?;
+
     let cluster = Cluster::create_global(conf.clone())?;
     let session_manager = SessionManager::from_conf(conf.clone(), cluster.clone())?;
     let mut shutdown_handle = ShutdownHandle::create(session_manager.clone());
@@ -111,7 +120,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .query
             .metric_api_address
             .parse::<std::net::SocketAddr>()?;
-        let mut srv = MetricService::create();
+        let mut srv = MetricService::create(conf.clone());
         let listening = srv.start(listening).await?;
         shutdown_handle.add_service(srv);
         info!("Metric API server listening on {}", listening);
@@ -144,5 +153,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     log::info!("Ready for connections.");
     shutdown_handle.wait_for_termination_request().await;
     log::info!("Shutdown server.");
+
+    let drain_timeout = Duration::from_secs(conf.query.runtime_drain_timeout_secs);
+    if let Err(cause) = runtime_pools.shutdown(drain_timeout) {
+        log::warn!("Shutdown runtime pools failure, cause: {}", cause);
+    }
     Ok(())
 }
@@ -18,8 +18,13 @@ use common_exception::Result;
 use common_metatypes::MetaId;
 use common_metatypes::MetaVersion;
 use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::UndropTablePlan;
+use common_store_api::CreateTableOutcome;
 
+use crate::catalogs::meta_backend::DroppedTableInfo;
 use crate::catalogs::Database;
 use crate::catalogs::TableFunctionMeta;
 use crate::catalogs::TableMeta;
@@ -60,6 +65,23 @@ pub trait Catalog {
     fn create_database(&self, plan: CreateDatabasePlan) -> Result<()>;
     fn drop_database(&self, plan: DropDatabasePlan) -> Result<()>;
 
+    // Create the tables, in one meta write for the databases keeping their tables in the meta backend.
+    // A table that cannot be created doesn't fail the others.
+    fn create_tables(&self, plans: Vec<CreateTablePlan>) -> Result<Vec<CreateTableOutcome>>;
+
+    // Restore a dropped table from trash.
+    fn undrop_table(&self, plan: UndropTablePlan) -> Result<()>;
+    // Get the dropped tables which can be restored.
+    fn get_dropped_tables(&self) -> Result<Vec<Arc<DroppedTableInfo>>>;
+    // Change the type of a column of a table.
+    fn modify_column(&self, plan: ModifyColumnPlan) -> Result<()>;
+
     // Get all db engines.
     fn get_db_engines(&self) -> Result<Vec<EngineDescription>>;
+
+    // Why the metas may be stale, e.g. the store is unreachable.
+    fn degraded_warning(&self) -> Option<String>;
+
+    // The version of the metas, it changes with every DDL.
+    fn get_meta_version(&self) -> Result<MetaVersion>;
 }
@@ -22,12 +22,19 @@ use common_infallible::RwLock;
 use common_metatypes::MetaId;
 use common_metatypes::MetaVersion;
 use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::UndropTablePlan;
+use common_store_api::CreateTableOutcome;
+use common_store_api_sdk::RpcStats;
 
 use crate::catalogs::catalog::Catalog;
+use crate::catalogs::impls::meta_backends::CatalogSnapshotCache;
 use crate::catalogs::impls::meta_backends::EmbeddedMetaBackend;
 use crate::catalogs::impls::meta_backends::RemoteMeteStoreClient;
 use crate::catalogs::meta_backend::DatabaseInfo;
+use crate::catalogs::meta_backend::DroppedTableInfo;
 use crate::catalogs::meta_backend::MetaBackend;
 use crate::catalogs::Database;
 use crate::catalogs::TableFunctionMeta;
@@ -61,16 +68,26 @@ pub struct MetaStoreCatalog {
 }
 
 impl MetaStoreCatalog {
-    pub fn try_create_with_config(conf: Config) -> Result<Self> {
+    /// The store clients of the catalog and of its databases count their calls into
+    /// `store_rpc_stats`.
+    pub fn try_create_with_config(conf: Config, store_rpc_stats: Arc<RpcStats>) -> Result<Self> {
         let local_mode = conf.meta.meta_address.is_empty();
+        let store_api_provider = StoreApiProvider::new(&conf).with_rpc_stats(store_rpc_stats);
 
         let meta_backend: Arc<dyn MetaBackend>;
+        let mut remote_backend = None;
 
         meta_backend = if local_mode {
             Arc::new(EmbeddedMetaBackend::new())
         } else {
-            let store_client_provider = Arc::new(StoreApiProvider::new(&conf));
-            Arc::new(RemoteMeteStoreClient::create(store_client_provider))
+            let store_client_provider = Arc::new(store_api_provider.clone());
+            let snapshot_cache = CatalogSnapshotCache::open(&conf.meta.meta_catalog_cache_dir);
+            let remote = Arc::new(
+                RemoteMeteStoreClient::create(store_client_provider)
+                    .with_snapshot_cache(Arc::new(snapshot_cache)),
+            );
+            remote_backend = Some(remote.clone());
+            remote
         };
 
         let plan = CreateDatabasePlan {
@@ -79,7 +96,13 @@ impl MetaStoreCatalog {
             engine: DEFAULT_DB_ENGINE.to_string(),
             options: Default::default(),
         };
-        meta_backend.create_database(plan)?;
+        if let Err(cause) = meta_backend.create_database(plan) {
+            // Come up with the persisted catalog rather than not at all, if the store is unreachable.
+            match &remote_backend {
+                Some(remote) if remote.degrade(&cause) => {}
+                _ => return Err(cause),
+            }
+        }
 
         let db_engine_registry = Arc::new(DatabaseEngineRegistry::new());
         let table_engine_registry = Arc::new(TableEngineRegistry::new());
@@ -89,6 +112,7 @@ impl MetaStoreCatalog {
             &db_engine_registry,
             meta_backend.clone(),
             table_engine_registry,
+            store_api_provider,
         )?;
 
         let cat = MetaStoreCatalog {
@@ -214,8 +238,73 @@ impl Catalog for MetaStoreCatalog {
         Ok(())
     }
 
+    fn create_tables(&self, plans: Vec<CreateTablePlan>) -> Result<Vec<CreateTableOutcome>> {
+        // The tables of the other database engines are created one by one by their databases.
+        let mut outcomes = vec![None; plans.len()];
+        let mut batch = (vec![], vec![]);
+        for (i, plan) in plans.into_iter().enumerate() {
+            match self.get_database(&plan.db) {
+                Ok(db) if !is_default_engine(db.engine()) => {
+                    outcomes[i] = Some(create_table_by_database(db.as_ref(), plan));
+                }
+                // The meta backend reports the unknown databases.
+                _ => {
+                    batch.0.push(i);
+                    batch.1.push(plan);
+                }
+            }
+        }
+
+        if !batch.1.is_empty() {
+            let created = self.meta_backend.create_tables(batch.1)?;
+            for (i, outcome) in batch.0.into_iter().zip(created) {
+                outcomes[i] = Some(outcome);
+            }
+        }
+        Ok(outcomes.into_iter().flatten().collect())
+    }
+
+    fn undrop_table(&self, plan: UndropTablePlan) -> Result<()> {
+        self.meta_backend.undrop_table(plan)
+    }
+
+    fn get_dropped_tables(&self) -> Result<Vec<Arc<DroppedTableInfo>>> {
+        self.meta_backend.get_dropped_tables()
+    }
+
+    fn modify_column(&self, plan: ModifyColumnPlan) -> Result<()> {
+        self.meta_backend.modify_column(plan)
+    }
+
     fn get_db_engines(&self) -> Result<Vec<EngineDescription>> {
         let descriptions = self.db_engine_registry.descriptions();
         Ok(descriptions)
     }
+
+    fn degraded_warning(&self) -> Option<String> {
+        self.meta_backend.degraded_warning()
+    }
+
+    fn get_meta_version(&self) -> Result<MetaVersion> {
+        self.meta_backend.get_meta_version()
+    }
+}
+
+fn is_default_engine(engine: &str) -> bool {
+    engine.is_empty() || engine.eq_ignore_ascii_case(DEFAULT_DB_ENGINE)
+}
+
+/// Create the table of the plan through its database, for the databases keeping their own tables.
+fn create_table_by_database(db: &dyn Database, plan: CreateTablePlan) -> CreateTableOutcome {
+    let name = plan.table.clone();
+    let existing = db.get_table(&name).ok();
+    match (existing, db.create_table(plan)) {
+        (_, Err(e)) => CreateTableOutcome::failed(&e),
+        (Some(table), Ok(_)) => CreateTableOutcome::AlreadyExists {
+            table_id: table.meta_id(),
+        },
+        (None, Ok(_)) => CreateTableOutcome::Created {
+            table_id: db.get_table(&name).map(|t| t.meta_id()).unwrap_or_default(),
+        },
+    }
 }
@@ -15,12 +15,17 @@
 
 use std::sync::Arc;
 
-use common_exception::ErrorCode;
+use common_exception::codes;
 use common_metatypes::MetaId;
 use common_metatypes::MetaVersion;
 use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::UndropTablePlan;
+use common_store_api::CreateTableOutcome;
 
+use crate::catalogs::meta_backend::DroppedTableInfo;
 use crate::catalogs::Catalog;
 use crate::catalogs::Database;
 use crate::catalogs::TableFunctionMeta;
@@ -71,7 +76,7 @@ impl Catalog for OverlaidCatalog {
         let r = self.read_only.get_database(db_name);
         match r {
             Err(e) => {
-                if e.code() == ErrorCode::UnknownDatabase("").code() {
+                if e.code() == codes::UnknownDatabase {
                     self.bottom.get_database(db_name)
                 } else {
                     Err(e)
@@ -134,10 +139,69 @@ impl Catalog for OverlaidCatalog {
         self.bottom.drop_database(plan)
     }
 
+    fn create_tables(
+        &self,
+        plans: Vec<CreateTablePlan>,
+    ) -> common_exception::Result<Vec<CreateTableOutcome>> {
+        let mut read_only = (vec![], vec![]);
+        let mut bottom = (vec![], vec![]);
+        for (i, plan) in plans.into_iter().enumerate() {
+            let layer = match self.read_only.exists_database(&plan.db)? {
+                true => &mut read_only,
+                false => &mut bottom,
+            };
+            layer.0.push(i);
+            layer.1.push(plan);
+        }
+
+        let mut outcomes = vec![None; read_only.0.len() + bottom.0.len()];
+        for (catalog, (indices, plans)) in [(&self.read_only, read_only), (&self.bottom, bottom)] {
+            if plans.is_empty() {
+                continue;
+            }
+            for (i, outcome) in indices.into_iter().zip(catalog.create_tables(plans)?) {
+                outcomes[i] = Some(outcome);
+            }
+        }
+        Ok(outcomes.into_iter().flatten().collect())
+    }
+
+    fn undrop_table(&self, plan: UndropTablePlan) -> common_exception::Result<()> {
+        if self.read_only.exists_database(&plan.db)? {
+            self.read_only.undrop_table(plan)
+        } else {
+            self.bottom.undrop_table(plan)
+        }
+    }
+
+    fn get_dropped_tables(&self) -> common_exception::Result<Vec<Arc<DroppedTableInfo>>> {
+        let mut tables = self.read_only.get_dropped_tables()?;
+        let mut other = self.bottom.get_dropped_tables()?;
+        tables.append(&mut other);
+        Ok(tables)
+    }
+
+    fn modify_column(&self, plan: ModifyColumnPlan) -> common_exception::Result<()> {
+        if self.read_only.exists_database(&plan.db)? {
+            self.read_only.modify_column(plan)
+        } else {
+            self.bottom.modify_column(plan)
+        }
+    }
+
     fn get_db_engines(&self) -> common_exception::Result<Vec<EngineDescription>> {
         let mut dbs = self.read_only.get_db_engines()?;
         let mut other = self.bottom.get_db_engines()?;
         dbs.append(&mut other);
         Ok(dbs)
     }
+
+    fn degraded_warning(&self) -> Option<String> {
+        self.bottom.degraded_warning()
+    }
+
+    fn get_meta_version(&self) -> common_exception::Result<MetaVersion> {
+        // the upper layer is read only
+        self.bottom.get_meta_version()
+    }
 }
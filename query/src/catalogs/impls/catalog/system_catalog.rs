@@ -21,9 +21,14 @@ use common_exception::Result;
 use common_metatypes::MetaId;
 use common_metatypes::MetaVersion;
 use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::UndropTablePlan;
+use common_store_api::CreateTableOutcome;
 
 use crate::catalogs::catalog::Catalog;
+use crate::catalogs::meta_backend::DroppedTableInfo;
 use crate::catalogs::Database;
 use crate::catalogs::TableFunctionMeta;
 use crate::catalogs::TableMeta;
@@ -128,6 +133,26 @@ impl Catalog for SystemCatalog {
         Err(ErrorCode::UnImplement("Cannot drop system database"))
     }
 
+    fn create_tables(&self, plans: Vec<CreateTablePlan>) -> Result<Vec<CreateTableOutcome>> {
+        let e = ErrorCode::UnImplement("Cannot create table for system database");
+        Ok(plans
+            .iter()
+            .map(|_| CreateTableOutcome::failed(&e))
+            .collect())
+    }
+
+    fn undrop_table(&self, _plan: UndropTablePlan) -> Result<()> {
+        Err(ErrorCode::UnImplement("Cannot undrop system table"))
+    }
+
+    fn get_dropped_tables(&self) -> Result<Vec<Arc<DroppedTableInfo>>> {
+        Ok(vec![])
+    }
+
+    fn modify_column(&self, _plan: ModifyColumnPlan) -> Result<()> {
+        Err(ErrorCode::UnImplement("Cannot alter system table"))
+    }
+
     fn get_db_engines(&self) -> Result<Vec<EngineDescription>> {
         // system catalog is special treated, no implicit database engine provided for it.
         let desc = EngineDescription {
@@ -136,4 +161,13 @@ impl Catalog for SystemCatalog {
         };
         Ok(vec![desc])
     }
+
+    fn degraded_warning(&self) -> Option<String> {
+        None
+    }
+
+    fn get_meta_version(&self) -> Result<MetaVersion> {
+        // the system tables never change
+        Ok(0)
+    }
 }
@@ -16,6 +16,7 @@
 use std::sync::Arc;
 
 use common_exception::Result;
+use common_store_api_sdk::RpcStats;
 
 use crate::catalogs::impls::catalog::metastore_catalog::MetaStoreCatalog;
 use crate::catalogs::impls::catalog::overlaid_catalog::OverlaidCatalog;
@@ -26,9 +27,13 @@ use crate::configs::Config;
 pub type DatabaseCatalog = OverlaidCatalog;
 
 impl DatabaseCatalog {
-    pub fn try_create_with_config(conf: Config) -> Result<DatabaseCatalog> {
+    /// The store clients of the catalog count their calls into `store_rpc_stats`.
+    pub fn try_create_with_config(
+        conf: Config,
+        store_rpc_stats: Arc<RpcStats>,
+    ) -> Result<DatabaseCatalog> {
         let system_catalog = SystemCatalog::try_create_with_config(&conf)?;
-        let metastore_catalog = MetaStoreCatalog::try_create_with_config(conf)?;
+        let metastore_catalog = MetaStoreCatalog::try_create_with_config(conf, store_rpc_stats)?;
         let res = DatabaseCatalog::create(Arc::new(system_catalog), Arc::new(metastore_catalog));
         Ok(res)
     }
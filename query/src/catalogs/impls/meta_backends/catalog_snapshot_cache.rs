@@ -0,0 +1,238 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_store_api::DatabaseMetaSnapshot;
+use sha2::Digest;
+
+/// Layout version of the snapshot file, files of other versions are ignored.
+const SNAPSHOT_FILE_VERSION: u32 = 1;
+pub(crate) const SNAPSHOT_FILE_NAME: &str = "catalog_snapshot.json";
+
+/// The content of the snapshot file.
+/// The payload is kept as a string so that the checksum covers the exact bytes written.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    /// hex of the sha256 of `payload`
+    checksum: String,
+    /// json of a `CachedSnapshot`
+    payload: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+struct CachedSnapshot {
+    /// fetch time from the store, in seconds since 1970
+    fetched_at: u64,
+    snapshot: DatabaseMetaSnapshot,
+}
+
+struct CacheState {
+    latest: Option<CachedSnapshot>,
+    /// The meta_ver of the snapshot in the file, the file is rewritten only when it changes.
+    persisted_ver: Option<u64>,
+    /// Why the store could not be reached, cleared by the next successful refresh.
+    degraded: Option<String>,
+}
+
+/// The latest catalog snapshot fetched from the store, persisted under a local directory.
+///
+/// While the store is unreachable the catalog is served from it, marked as stale,
+/// including right after a restart of the query node.
+pub struct CatalogSnapshotCache {
+    file: Option<PathBuf>,
+    state: Mutex<CacheState>,
+}
+
+impl CatalogSnapshotCache {
+    /// Load the snapshot persisted in `dir`, an empty `dir` disables the cache.
+    /// A corrupt or incompatible file is ignored.
+    pub fn open(dir: &str) -> Self {
+        let file = match dir.is_empty() {
+            true => None,
+            false => Some(Path::new(dir).join(SNAPSHOT_FILE_NAME)),
+        };
+
+        let latest = file.as_ref().and_then(|file| match Self::load(file) {
+            Ok(latest) => latest,
+            Err(cause) => {
+                log::warn!(
+                    "Ignore the catalog snapshot file {}: {}",
+                    file.display(),
+                    cause.message()
+                );
+                None
+            }
+        });
+
+        let persisted_ver = latest.as_ref().map(|cached| cached.snapshot.meta_ver);
+        CatalogSnapshotCache {
+            file,
+            state: Mutex::new(CacheState {
+                latest,
+                persisted_ver,
+                degraded: None,
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Keep the snapshot just fetched from the store and leave the degraded mode.
+    /// The file is not rewritten if the meta_ver is the persisted one, thus after a restart the
+    /// age of the snapshot is counted from the last change of the catalog.
+    pub fn refresh(&self, snapshot: &DatabaseMetaSnapshot) {
+        let file = match &self.file {
+            None => return,
+            Some(file) => file,
+        };
+
+        let cached = CachedSnapshot {
+            fetched_at: now_secs(),
+            snapshot: snapshot.clone(),
+        };
+
+        let mut state = self.state.lock();
+        if state.persisted_ver != Some(snapshot.meta_ver) {
+            match Self::persist(file, &cached) {
+                Ok(_) => state.persisted_ver = Some(snapshot.meta_ver),
+                Err(cause) => log::warn!(
+                    "Cannot persist the catalog snapshot to {}: {}",
+                    file.display(),
+                    cause.message()
+                ),
+            }
+        }
+
+        if let Some(cause) = state.degraded.take() {
+            log::info!("The store is reachable again, it was not: {}", cause);
+        }
+        state.latest = Some(cached);
+    }
+
+    /// Enter the degraded mode since the store can not be reached.
+    /// Returns the snapshot to serve instead, an empty one if nothing was persisted,
+    /// or None if the cache is disabled.
+    pub fn degrade(&self, cause: &ErrorCode) -> Option<DatabaseMetaSnapshot> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut state = self.state.lock();
+        if state.degraded.is_none() {
+            log::warn!(
+                "The store is unreachable, serve the catalog from the persisted snapshot: {}",
+                cause.message()
+            );
+        }
+        state.degraded = Some(cause.message());
+
+        let snapshot = match &state.latest {
+            Some(cached) => cached.snapshot.clone(),
+            None => DatabaseMetaSnapshot {
+                meta_ver: 0,
+                db_metas: vec![],
+                tbl_metas: vec![],
+            },
+        };
+        Some(snapshot)
+    }
+
+    /// The warning for the queries served in the degraded mode, with the age of the snapshot.
+    pub fn degraded_warning(&self) -> Option<String> {
+        let state = self.state.lock();
+        let cause = state.degraded.as_ref()?;
+        let warning = match &state.latest {
+            Some(cached) => format!(
+                "The catalog is stale, it is served from a snapshot of {} seconds ago since the store is unreachable: {}",
+                now_secs().saturating_sub(cached.fetched_at),
+                cause
+            ),
+            None => format!(
+                "The catalog is empty, no snapshot is persisted and the store is unreachable: {}",
+                cause
+            ),
+        };
+        Some(warning)
+    }
+
+    fn load(file: &Path) -> Result<Option<CachedSnapshot>> {
+        if !file.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read(file)?;
+        let snapshot_file: SnapshotFile = serde_json::from_slice(&content)?;
+        if snapshot_file.version != SNAPSHOT_FILE_VERSION {
+            return Err(ErrorCode::BadBytes(format!(
+                "snapshot file version {} is not supported, expect {}",
+                snapshot_file.version, SNAPSHOT_FILE_VERSION
+            )));
+        }
+
+        if checksum(&snapshot_file.payload) != snapshot_file.checksum {
+            return Err(ErrorCode::BadBytes("snapshot file checksum mismatch"));
+        }
+
+        let cached = serde_json::from_str(&snapshot_file.payload)?;
+        Ok(Some(cached))
+    }
+
+    /// Write to a temporary file first then rename it, a crash never leaves a partial file.
+    fn persist(file: &Path, cached: &CachedSnapshot) -> Result<()> {
+        let payload = serde_json::to_string(cached)?;
+        let snapshot_file = SnapshotFile {
+            version: SNAPSHOT_FILE_VERSION,
+            checksum: checksum(&payload),
+            payload,
+        };
+        let content = serde_json::to_vec(&snapshot_file)?;
+
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp = file.with_extension("json.tmp");
+        {
+            let mut f = fs::File::create(&tmp)?;
+            f.write_all(&content)?;
+            f.sync_all()?;
+        }
+        fs::rename(&tmp, file)?;
+        Ok(())
+    }
+}
+
+fn checksum(payload: &str) -> String {
+    format!("{:x}", sha2::Sha256::digest(payload.as_bytes()))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
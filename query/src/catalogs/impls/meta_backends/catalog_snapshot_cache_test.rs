@@ -0,0 +1,141 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::collections::HashMap;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_metatypes::Database;
+use common_store_api::DatabaseMetaSnapshot;
+
+use crate::catalogs::impls::meta_backends::catalog_snapshot_cache::SNAPSHOT_FILE_NAME;
+use crate::catalogs::impls::meta_backends::CatalogSnapshotCache;
+
+fn snapshot(meta_ver: u64) -> DatabaseMetaSnapshot {
+    let database = Database {
+        database_id: 1,
+        database_engine: "Default".to_string(),
+        tables: HashMap::new(),
+    };
+    DatabaseMetaSnapshot {
+        meta_ver,
+        db_metas: vec![("db1".to_string(), database)],
+        tbl_metas: vec![],
+    }
+}
+
+#[test]
+fn test_catalog_snapshot_cache_persist_and_reload() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir_str = dir.path().to_str().unwrap();
+    let unreachable = ErrorCode::CannotConnectNode("store is down");
+
+    let cache = CatalogSnapshotCache::open(dir_str);
+    assert!(cache.degraded_warning().is_none());
+    cache.refresh(&snapshot(3));
+    assert!(dir.path().join(SNAPSHOT_FILE_NAME).exists());
+
+    // a restarted node serves the persisted snapshot once the store is found unreachable
+    let cache = CatalogSnapshotCache::open(dir_str);
+    assert!(cache.degraded_warning().is_none());
+    let served = cache.degrade(&unreachable).unwrap();
+    assert_eq!(3, served.meta_ver);
+    assert_eq!("db1", served.db_metas[0].0);
+
+    let warning = cache.degraded_warning().unwrap();
+    assert!(warning.contains("stale"), "{}", warning);
+    assert!(warning.contains("seconds ago"), "{}", warning);
+    assert!(warning.contains("store is down"), "{}", warning);
+
+    // back to live data when the store returns
+    cache.refresh(&snapshot(4));
+    assert!(cache.degraded_warning().is_none());
+    assert_eq!(4, cache.degrade(&unreachable).unwrap().meta_ver);
+    Ok(())
+}
+
+#[test]
+fn test_catalog_snapshot_cache_persist_on_new_meta_ver() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join(SNAPSHOT_FILE_NAME);
+
+    let cache = CatalogSnapshotCache::open(dir.path().to_str().unwrap());
+    cache.refresh(&snapshot(3));
+    assert!(file.exists());
+
+    // the same meta_ver is not written again
+    std::fs::remove_file(&file)?;
+    cache.refresh(&snapshot(3));
+    assert!(!file.exists());
+
+    cache.refresh(&snapshot(4));
+    assert!(file.exists());
+
+    // nor after a restart
+    let cache = CatalogSnapshotCache::open(dir.path().to_str().unwrap());
+    std::fs::remove_file(&file)?;
+    cache.refresh(&snapshot(4));
+    assert!(!file.exists());
+    Ok(())
+}
+
+#[test]
+fn test_catalog_snapshot_cache_ignore_bad_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir_str = dir.path().to_str().unwrap();
+    let file = dir.path().join(SNAPSHOT_FILE_NAME);
+    let unreachable = ErrorCode::CannotConnectNode("store is down");
+
+    CatalogSnapshotCache::open(dir_str).refresh(&snapshot(3));
+    let content = std::fs::read_to_string(&file)?;
+
+    // corrupt
+    std::fs::write(&file, &content[..content.len() / 2])?;
+    let cache = CatalogSnapshotCache::open(dir_str);
+    let served = cache.degrade(&unreachable).unwrap();
+    assert!(served.db_metas.is_empty());
+    let warning = cache.degraded_warning().unwrap();
+    assert!(warning.contains("empty"), "{}", warning);
+
+    // checksum mismatch
+    std::fs::write(&file, content.replace("Default", "Memory"))?;
+    let cache = CatalogSnapshotCache::open(dir_str);
+    assert!(cache.degrade(&unreachable).unwrap().db_metas.is_empty());
+
+    // incompatible version
+    std::fs::write(
+        &file,
+        content.replacen("\"version\":1", "\"version\":999", 1),
+    )?;
+    let cache = CatalogSnapshotCache::open(dir_str);
+    assert!(cache.degrade(&unreachable).unwrap().db_metas.is_empty());
+
+    // a good file is loaded again
+    std::fs::write(&file, &content)?;
+    let cache = CatalogSnapshotCache::open(dir_str);
+    assert_eq!(1, cache.degrade(&unreachable).unwrap().db_metas.len());
+    Ok(())
+}
+
+#[test]
+fn test_catalog_snapshot_cache_disabled() -> Result<()> {
+    let cache = CatalogSnapshotCache::open("");
+    cache.refresh(&snapshot(3));
+    assert!(cache
+        .degrade(&ErrorCode::CannotConnectNode("store is down"))
+        .is_none());
+    assert!(cache.degraded_warning().is_none());
+    Ok(())
+}
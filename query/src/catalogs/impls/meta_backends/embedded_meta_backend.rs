@@ -20,13 +20,18 @@ use common_exception::ErrorCode;
 use common_infallible::RwLock;
 use common_metatypes::MetaId;
 use common_metatypes::MetaVersion;
+use common_planners::check_table_options;
 use common_planners::CreateDatabasePlan;
 use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
 use common_planners::DropTablePlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::UndropTablePlan;
+use common_store_api::CreateTableOutcome;
 
 use crate::catalogs::impls::LOCAL_TBL_ID_BEGIN;
 use crate::catalogs::meta_backend::DatabaseInfo;
+use crate::catalogs::meta_backend::DroppedTableInfo;
 use crate::catalogs::meta_backend::MetaBackend;
 use crate::catalogs::meta_backend::TableInfo;
 
@@ -56,6 +61,7 @@ type Databases = Arc<RwLock<HashMap<String, (Arc<DatabaseInfo>, InMemoryTableInf
 pub struct EmbeddedMetaBackend {
     databases: Databases,
     tbl_id_seq: Arc<RwLock<u64>>,
+    meta_ver: Arc<RwLock<MetaVersion>>,
 }
 
 impl EmbeddedMetaBackend {
@@ -64,6 +70,7 @@ impl EmbeddedMetaBackend {
         Self {
             databases: Default::default(),
             tbl_id_seq,
+            meta_ver: Arc::new(RwLock::new(0)),
         }
     }
 
@@ -72,6 +79,52 @@ impl EmbeddedMetaBackend {
         let r = self.tbl_id_seq.read();
         *r
     }
+
+    fn bump_meta_version(&self) {
+        *self.meta_ver.write() += 1;
+    }
+
+    /// Add the table of the plan, without bumping the meta version.
+    fn insert_table(
+        &self,
+        databases: &mut HashMap<String, (Arc<DatabaseInfo>, InMemoryTableInfo)>,
+        plan: CreateTablePlan,
+    ) -> common_exception::Result<CreateTableOutcome> {
+        check_table_options(&plan.options)?;
+
+        let metas = match databases.get_mut(&plan.db) {
+            None => {
+                return Err(ErrorCode::UnknownDatabase(format!(
+                    "Unknown database: {}",
+                    plan.db
+                )));
+            }
+            Some((_db_info, metas)) => metas,
+        };
+
+        if let Some(existing) = metas.name2meta.get(&plan.table) {
+            return match plan.if_not_exists {
+                true => Ok(CreateTableOutcome::AlreadyExists {
+                    table_id: existing.table_id,
+                }),
+                false => Err(ErrorCode::TableAlreadyExists(format!(
+                    "Table: '{}.{}' already exists.",
+                    plan.db, plan.table,
+                ))),
+            };
+        }
+
+        let table_id = self.next_db_id();
+        metas.insert(TableInfo {
+            db: plan.db,
+            table_id,
+            name: plan.table,
+            schema: plan.schema,
+            table_option: plan.options,
+            engine: plan.engine,
+        });
+        Ok(CreateTableOutcome::Created { table_id })
+    }
 }
 
 impl MetaBackend for EmbeddedMetaBackend {
@@ -176,44 +229,35 @@ impl MetaBackend for EmbeddedMetaBackend {
     }
 
     fn create_table(&self, plan: CreateTablePlan) -> common_exception::Result<()> {
-        let clone = plan.clone();
-        let db_name = clone.db.as_str();
-        let table_name = clone.table.as_str();
+        let outcome = self.insert_table(&mut self.databases.write(), plan)?;
+        if let CreateTableOutcome::Created { .. } = outcome {
+            self.bump_meta_version();
+        }
+        Ok(())
+    }
 
-        let table_info = TableInfo {
-            db: plan.db,
-            table_id: self.next_db_id(),
-            name: plan.table,
-            schema: plan.schema,
-            table_option: plan.options,
-            engine: plan.engine,
+    fn create_tables(
+        &self,
+        plans: Vec<CreateTablePlan>,
+    ) -> common_exception::Result<Vec<CreateTableOutcome>> {
+        let outcomes = {
+            let mut lock = self.databases.write();
+            plans
+                .into_iter()
+                .map(|plan| {
+                    self.insert_table(&mut lock, plan)
+                        .unwrap_or_else(|e| CreateTableOutcome::failed(&e))
+                })
+                .collect::<Vec<_>>()
         };
 
-        let mut lock = self.databases.write();
-        let v = lock.get_mut(db_name);
-        match v {
-            None => {
-                return Err(ErrorCode::UnknownDatabase(format!(
-                    "Unknown database: {}",
-                    db_name
-                )));
-            }
-            Some((_db_info, metas)) => {
-                if metas.name2meta.get(table_name).is_some() {
-                    if plan.if_not_exists {
-                        return Ok(());
-                    } else {
-                        return Err(ErrorCode::TableAlreadyExists(format!(
-                            "Table: '{}.{}' already exists.",
-                            db_name, table_name,
-                        )));
-                    };
-                }
-                metas.insert(table_info);
-            }
+        if outcomes
+            .iter()
+            .any(|o| matches!(o, CreateTableOutcome::Created { .. }))
+        {
+            self.bump_meta_version();
         }
-
-        Ok(())
+        Ok(outcomes)
     }
 
     fn drop_table(&self, plan: DropTablePlan) -> common_exception::Result<()> {
@@ -261,9 +305,28 @@ impl MetaBackend for EmbeddedMetaBackend {
             }
         }
 
+        self.bump_meta_version();
         Ok(())
     }
 
+    fn undrop_table(&self, _plan: UndropTablePlan) -> common_exception::Result<()> {
+        // Dropped tables are not kept in trash by the embedded backend.
+        Err(ErrorCode::UnImplement(
+            "Cannot undrop table with embedded metastore backend",
+        ))
+    }
+
+    fn get_dropped_tables(&self) -> common_exception::Result<Vec<Arc<DroppedTableInfo>>> {
+        Ok(vec![])
+    }
+
+    fn modify_column(&self, _plan: ModifyColumnPlan) -> common_exception::Result<()> {
+        // The data of the local tables is kept in the types they are created with.
+        Err(ErrorCode::UnImplement(
+            "Cannot modify column with embedded metastore backend",
+        ))
+    }
+
     fn create_database(&self, plan: CreateDatabasePlan) -> common_exception::Result<()> {
         let db_name = plan.db.as_str();
 
@@ -289,6 +352,7 @@ impl MetaBackend for EmbeddedMetaBackend {
             plan.db,
             (Arc::new(database_info), InMemoryTableInfo::create()),
         );
+        self.bump_meta_version();
         Ok(())
     }
 
@@ -305,9 +369,18 @@ impl MetaBackend for EmbeddedMetaBackend {
             };
         }
         self.databases.write().remove(db_name);
+        self.bump_meta_version();
         Ok(())
     }
 
+    fn degraded_warning(&self) -> Option<String> {
+        None
+    }
+
+    fn get_meta_version(&self) -> common_exception::Result<MetaVersion> {
+        Ok(*self.meta_ver.read())
+    }
+
     fn name(&self) -> String {
         "embedded metastore backend".to_owned()
     }
@@ -0,0 +1,99 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::collections::HashMap;
+
+use common_exception::Result;
+use common_planners::*;
+use pretty_assertions::assert_eq;
+
+use crate::catalogs::impls::meta_backends::EmbeddedMetaBackend;
+use crate::catalogs::meta_backend::MetaBackend;
+use crate::datasources::table::register_prelude_tbl_engines;
+use crate::datasources::table_engine_registry::TableEngineRegistry;
+use crate::sql::PlanParser;
+
+#[test]
+fn test_embedded_meta_backend_table_options() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let backend = EmbeddedMetaBackend::new();
+    backend.create_database(CreateDatabasePlan {
+        if_not_exists: false,
+        db: "db1".to_string(),
+        engine: "Default".to_string(),
+        options: HashMap::new(),
+    })?;
+
+    let plan = PlanParser::create(ctx.clone()).build_from_sql(
+        "create table db1.t(a int) Engine = Csv Location = '/tmp/a.csv', HAS_HEADER = 'true'",
+    )?;
+    if let PlanNode::CreateTable(plan) = plan {
+        backend.create_table(plan)?;
+    } else {
+        assert!(false)
+    }
+
+    // The engine and the option names are kept as they are written.
+    let info = backend.get_table("db1", "t")?;
+    assert_eq!("Csv", info.engine);
+    let mut names = info.table_option.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(vec!["HAS_HEADER", "Location"], names);
+
+    // And they are matched case-insensitively.
+    let registry = TableEngineRegistry::new();
+    register_prelude_tbl_engines(&registry)?;
+    assert!(registry.engine_provider(&info.engine).is_some());
+    assert!(registry.engine_provider("csv").is_some());
+
+    let options = &info.table_option;
+    assert_eq!(
+        Some(&"/tmp/a.csv".to_string()),
+        get_table_option(options, "location")
+    );
+    assert_eq!(
+        Some(&"true".to_string()),
+        get_table_option(options, "has_header")
+    );
+
+    // An option given twice in different cases.
+    let res = PlanParser::create(ctx).build_from_sql(
+        "create table db1.t2(a int) Engine = Csv location = 'a.csv', LOCATION = 'b.csv'",
+    );
+    assert_eq!(
+        "Code: 22, displayText = Duplicated table option: \"location\" and \"LOCATION\", option names are case-insensitive.",
+        res.unwrap_err().to_string()
+    );
+
+    // A plan not built by the planner is checked the same way.
+    let mut options = TableOptions::new();
+    options.insert("location".to_string(), "a.csv".to_string());
+    options.insert("Location".to_string(), "b.csv".to_string());
+    let res = backend.create_table(CreateTablePlan {
+        if_not_exists: false,
+        db: "db1".to_string(),
+        table: "t2".to_string(),
+        schema: info.schema.clone(),
+        engine: "CSV".to_string(),
+        options,
+    });
+    assert_eq!(
+        "Code: 22, displayText = Duplicated table option: \"Location\" and \"location\", option names are case-insensitive.",
+        res.unwrap_err().to_string()
+    );
+    assert!(!backend.exist_table("db1", "t2")?);
+
+    Ok(())
+}
@@ -15,6 +15,7 @@
 
 // TODO move this mod to catalogs
 
+pub use catalog_snapshot_cache::CatalogSnapshotCache;
 pub use embedded_meta_backend::EmbeddedMetaBackend;
 pub use remote_meta_backend::RemoteMeteStoreClient;
 
@@ -22,5 +23,13 @@ pub use remote_meta_backend::RemoteMeteStoreClient;
 //pub use crate::catalogs::metastore_client::MetaBackend;
 //pub use crate::catalogs::metastore_client::TableInfo;
 
+#[cfg(test)]
+mod catalog_snapshot_cache_test;
+#[cfg(test)]
+mod embedded_meta_backend_test;
+#[cfg(test)]
+mod remote_meta_backend_test;
+
+mod catalog_snapshot_cache;
 mod embedded_meta_backend;
 mod remote_meta_backend;
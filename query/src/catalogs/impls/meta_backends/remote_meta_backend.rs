@@ -23,6 +23,7 @@ use common_arrow::arrow_flight::FlightData;
 use common_cache::Cache;
 use common_cache::LruCache;
 use common_datavalues::DataSchema;
+use common_exception::codes;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_infallible::Mutex;
@@ -32,9 +33,15 @@ use common_planners::CreateDatabasePlan;
 use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
 use common_planners::DropTablePlan;
-use common_runtime::Runtime;
+use common_planners::ModifyColumnPlan;
+use common_planners::UndropTablePlan;
+use common_runtime::RuntimePools;
+use common_store_api::CreateTableOutcome;
+use common_store_api::DatabaseMetaSnapshot;
 
+use crate::catalogs::impls::meta_backends::CatalogSnapshotCache;
 use crate::catalogs::meta_backend::DatabaseInfo;
+use crate::catalogs::meta_backend::DroppedTableInfo;
 use crate::catalogs::meta_backend::MetaBackend;
 use crate::catalogs::meta_backend::TableInfo;
 use crate::common::StoreApiProvider;
@@ -44,10 +51,11 @@ type TableMetaCache = LruCache<(MetaId, MetaVersion), Arc<TableInfo>>;
 
 #[derive(Clone)]
 pub struct RemoteMeteStoreClient {
-    rt: Arc<Runtime>,
+    rt: Arc<RuntimePools>,
     rpc_time_out: Option<Duration>,
     table_meta_cache: Arc<Mutex<TableMetaCache>>,
     store_api_provider: Arc<StoreApiProvider>,
+    snapshot_cache: Arc<CatalogSnapshotCache>,
 }
 
 impl RemoteMeteStoreClient {
@@ -59,13 +67,113 @@ impl RemoteMeteStoreClient {
         apis_provider: Arc<StoreApiProvider>,
         timeout: Option<Duration>,
     ) -> RemoteMeteStoreClient {
-        let rt = Runtime::with_worker_threads(1).expect("remote catalogs initialization failure");
         RemoteMeteStoreClient {
-            rt: Arc::new(rt),
+            // Catalog sync is latency sensitive, keep it away from the heavy io and compute tasks.
+            rt: RuntimePools::global(),
             // TODO configuration
             rpc_time_out: timeout,
             table_meta_cache: Arc::new(Mutex::new(LruCache::new(100))),
             store_api_provider: apis_provider,
+            snapshot_cache: Arc::new(CatalogSnapshotCache::open("")),
+        }
+    }
+
+    /// Serve the catalog from `cache` while the store can not be reached.
+    pub fn with_snapshot_cache(mut self, cache: Arc<CatalogSnapshotCache>) -> Self {
+        self.snapshot_cache = cache;
+        self
+    }
+
+    /// Enter the degraded mode if the catalog can be served without the store.
+    pub fn degrade(&self, cause: &ErrorCode) -> bool {
+        self.snapshot_cache.degrade(cause).is_some()
+    }
+
+    /// The latest snapshot from the store, or the persisted one if the store can not be reached.
+    fn get_database_meta(&self) -> Result<Option<DatabaseMetaSnapshot>> {
+        let cli = self.store_api_provider.clone();
+        let reply = self
+            .rt
+            .management()
+            .block_on(
+                async move {
+                    let client = cli.try_get_meta_client().await?;
+                    // always take the latest snapshot
+                    client.get_database_meta(None).await
+                },
+                self.rpc_time_out,
+            )
+            .and_then(|reply| reply);
+
+        match reply {
+            Ok(reply) => {
+                if let Some(snapshot) = &reply {
+                    self.snapshot_cache.refresh(snapshot);
+                }
+                Ok(reply)
+            }
+            Err(cause) => match self.snapshot_cache.degrade(&cause) {
+                Some(snapshot) => Ok(Some(snapshot)),
+                None => Err(cause),
+            },
+        }
+    }
+
+    /// The store rejecting a request is not a reason to serve the persisted snapshot.
+    fn is_rejection(cause: &ErrorCode) -> bool {
+        cause.code() == codes::UnknownDatabase || cause.code() == codes::UnknownTable
+    }
+
+    fn get_table_from_snapshot(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        cause: ErrorCode,
+    ) -> Result<Arc<TableInfo>> {
+        let snapshot = match self.snapshot_cache.degrade(&cause) {
+            None => return Err(cause),
+            Some(snapshot) => snapshot,
+        };
+
+        let table_id = snapshot
+            .db_metas
+            .iter()
+            .find(|(name, _)| name == db_name)
+            .and_then(|(_, database)| database.tables.get(table_name));
+        let table = table_id.and_then(|id| snapshot.tbl_metas.iter().find(|(t_id, _)| t_id == id));
+
+        match table {
+            Some((_, table)) => Ok(Arc::new(self.to_table_info(db_name, table_name, table)?)),
+            None => Err(ErrorCode::UnknownTable(format!(
+                "table not found: {}.{}",
+                db_name, table_name
+            ))),
+        }
+    }
+
+    fn get_database_from_snapshot(
+        &self,
+        db_name: &str,
+        cause: ErrorCode,
+    ) -> Result<Arc<DatabaseInfo>> {
+        let snapshot = match self.snapshot_cache.degrade(&cause) {
+            None => return Err(cause),
+            Some(snapshot) => snapshot,
+        };
+
+        match snapshot
+            .db_metas
+            .into_iter()
+            .find(|(name, _)| name == db_name)
+        {
+            Some((name, database)) => Ok(Arc::new(DatabaseInfo {
+                name,
+                engine: database.database_engine,
+            })),
+            None => Err(ErrorCode::UnknownDatabase(format!(
+                "database not found: {}",
+                db_name
+            ))),
         }
     }
 
@@ -96,13 +204,22 @@ impl MetaBackend for RemoteMeteStoreClient {
         let reply = {
             let tbl_name = table_name.to_string();
             let db_name = db_name.to_string();
-            self.rt.block_on(
-                async move {
-                    let client = cli_provider.try_get_meta_client().await?;
-                    client.get_table(db_name, tbl_name).await
-                },
-                self.rpc_time_out,
-            )??
+            self.rt
+                .management()
+                .block_on(
+                    async move {
+                        let client = cli_provider.try_get_meta_client().await?;
+                        client.get_table(db_name, tbl_name).await
+                    },
+                    self.rpc_time_out,
+                )
+                .and_then(|reply| reply)
+        };
+
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(cause) if Self::is_rejection(&cause) => return Err(cause),
+            Err(cause) => return self.get_table_from_snapshot(db_name, table_name, cause),
         };
 
         let table_info = TableInfo {
@@ -140,7 +257,7 @@ impl MetaBackend for RemoteMeteStoreClient {
         }
 
         let cli = self.store_api_provider.clone();
-        let reply = self.rt.block_on(
+        let reply = self.rt.management().block_on(
             async move {
                 let client = cli.try_get_meta_client().await?;
                 client.get_table_ext(table_id, table_version).await
@@ -168,13 +285,22 @@ impl MetaBackend for RemoteMeteStoreClient {
         let cli_provider = self.store_api_provider.clone();
         let db = {
             let db_name = db_name.to_owned();
-            self.rt.block_on(
-                async move {
-                    let client = cli_provider.try_get_meta_client().await?;
-                    client.get_database(&db_name).await
-                },
-                self.rpc_time_out,
-            )??
+            self.rt
+                .management()
+                .block_on(
+                    async move {
+                        let client = cli_provider.try_get_meta_client().await?;
+                        client.get_database(&db_name).await
+                    },
+                    self.rpc_time_out,
+                )
+                .and_then(|reply| reply)
+        };
+
+        let db = match db {
+            Ok(db) => db,
+            Err(cause) if Self::is_rejection(&cause) => return Err(cause),
+            Err(cause) => return self.get_database_from_snapshot(db_name, cause),
         };
 
         let database_info = DatabaseInfo {
@@ -186,16 +312,7 @@ impl MetaBackend for RemoteMeteStoreClient {
     }
 
     fn get_databases(&self) -> Result<Vec<Arc<DatabaseInfo>>> {
-        let cli_provider = self.store_api_provider.clone();
-        let db = self.rt.block_on(
-            async move {
-                let client = cli_provider.try_get_meta_client().await?;
-                client.get_database_meta(None).await
-            },
-            self.rpc_time_out,
-        )??;
-
-        match db {
+        match self.get_database_meta()? {
             None => Ok(vec![]),
             Some(snapshot) => {
                 let mut res = vec![];
@@ -222,17 +339,7 @@ impl MetaBackend for RemoteMeteStoreClient {
     }
 
     fn get_tables(&self, db_name: &str) -> Result<Vec<Arc<TableInfo>>> {
-        let cli = self.store_api_provider.clone();
-        let reply = self.rt.block_on(
-            async move {
-                let client = cli.try_get_meta_client().await?;
-                // always take the latest snapshot
-                client.get_database_meta(None).await
-            },
-            self.rpc_time_out,
-        )??;
-
-        match reply {
+        match self.get_database_meta()? {
             None => Ok(vec![]),
             Some(snapshot) => {
                 let id_tbls = snapshot.tbl_metas.into_iter().collect::<HashMap<_, _>>();
@@ -261,7 +368,7 @@ impl MetaBackend for RemoteMeteStoreClient {
     fn create_table(&self, plan: CreateTablePlan) -> Result<()> {
         // TODO validate plan by table engine first
         let cli = self.store_api_provider.clone();
-        let _r = self.rt.block_on(
+        let _r = self.rt.management().block_on(
             async move {
                 let client = cli.try_get_meta_client().await?;
                 client.create_table(plan).await
@@ -271,9 +378,21 @@ impl MetaBackend for RemoteMeteStoreClient {
         Ok(())
     }
 
+    fn create_tables(&self, plans: Vec<CreateTablePlan>) -> Result<Vec<CreateTableOutcome>> {
+        let cli = self.store_api_provider.clone();
+        let res = self.rt.management().block_on(
+            async move {
+                let client = cli.try_get_meta_client().await?;
+                client.create_tables(plans).await
+            },
+            self.rpc_time_out,
+        )??;
+        Ok(res.outcomes)
+    }
+
     fn drop_table(&self, plan: DropTablePlan) -> Result<()> {
         let cli = self.store_api_provider.clone();
-        let _r = self.rt.block_on(
+        let _r = self.rt.management().block_on(
             async move {
                 let client = cli.try_get_meta_client().await?;
                 client.drop_table(plan.clone()).await
@@ -283,9 +402,59 @@ impl MetaBackend for RemoteMeteStoreClient {
         Ok(())
     }
 
+    fn undrop_table(&self, plan: UndropTablePlan) -> Result<()> {
+        let cli = self.store_api_provider.clone();
+        let _r = self.rt.management().block_on(
+            async move {
+                let client = cli.try_get_meta_client().await?;
+                client.undrop_table(plan).await
+            },
+            self.rpc_time_out,
+        )??;
+        Ok(())
+    }
+
+    fn modify_column(&self, plan: ModifyColumnPlan) -> Result<()> {
+        let cli = self.store_api_provider.clone();
+        let _r = self.rt.management().block_on(
+            async move {
+                let client = cli.try_get_meta_client().await?;
+                client.modify_column(plan).await
+            },
+            self.rpc_time_out,
+        )??;
+        Ok(())
+    }
+
+    fn get_dropped_tables(&self) -> Result<Vec<Arc<DroppedTableInfo>>> {
+        let cli = self.store_api_provider.clone();
+        let reply = self.rt.management().block_on(
+            async move {
+                let client = cli.try_get_meta_client().await?;
+                client.get_dropped_tables().await
+            },
+            self.rpc_time_out,
+        )??;
+
+        let res = reply
+            .tables
+            .into_iter()
+            .map(|t| {
+                Arc::new(DroppedTableInfo {
+                    db: t.db_name,
+                    table_id: t.table.table_id,
+                    name: t.table_name,
+                    dropped_on: t.dropped_on,
+                    expire_at: t.expire_at,
+                })
+            })
+            .collect();
+        Ok(res)
+    }
+
     fn create_database(&self, plan: CreateDatabasePlan) -> Result<()> {
         let cli_provider = self.store_api_provider.clone();
-        let _r = self.rt.block_on(
+        let _r = self.rt.management().block_on(
             async move {
                 let cli = cli_provider.try_get_meta_client().await?;
                 cli.create_database(plan).await
@@ -297,7 +466,7 @@ impl MetaBackend for RemoteMeteStoreClient {
 
     fn drop_database(&self, plan: DropDatabasePlan) -> Result<()> {
         let cli_provider = self.store_api_provider.clone();
-        let _r = self.rt.block_on(
+        let _r = self.rt.management().block_on(
             async move {
                 let cli = cli_provider.try_get_meta_client().await?;
                 cli.drop_database(plan).await
@@ -307,6 +476,15 @@ impl MetaBackend for RemoteMeteStoreClient {
         Ok(())
     }
 
+    fn degraded_warning(&self) -> Option<String> {
+        self.snapshot_cache.degraded_warning()
+    }
+
+    fn get_meta_version(&self) -> Result<MetaVersion> {
+        let snapshot = self.get_database_meta()?;
+        Ok(snapshot.map(|snapshot| snapshot.meta_ver).unwrap_or(0))
+    }
+
     fn name(&self) -> String {
         "remote metastore backend".to_owned()
     }
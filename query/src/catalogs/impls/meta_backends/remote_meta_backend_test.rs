@@ -0,0 +1,136 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
+use common_datavalues::DataField;
+use common_datavalues::DataSchema;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_metatypes::Database;
+use common_metatypes::Table;
+use common_store_api::DatabaseMetaSnapshot;
+
+use crate::catalogs::impls::meta_backends::catalog_snapshot_cache::SNAPSHOT_FILE_NAME;
+use crate::catalogs::impls::meta_backends::CatalogSnapshotCache;
+use crate::catalogs::impls::meta_backends::RemoteMeteStoreClient;
+use crate::catalogs::meta_backend::MetaBackend;
+use crate::common::StoreApiProvider;
+use crate::configs::Config;
+
+fn catalog_snapshot() -> DatabaseMetaSnapshot {
+    let schema = DataSchema::new(vec![DataField::new("a", DataType::Int32, false)]);
+    let flight_schema =
+        flight_data_from_arrow_schema(&schema.to_arrow(), &IpcWriteOptions::default());
+    let table = Table {
+        table_id: 10,
+        schema: flight_schema.data_header,
+        table_engine: "remote".to_string(),
+        table_options: HashMap::new(),
+        parts: HashSet::new(),
+        data_version: 0,
+    };
+    let database = Database {
+        database_id: 1,
+        database_engine: "Default".to_string(),
+        tables: vec![("t1".to_string(), 10)].into_iter().collect(),
+    };
+    DatabaseMetaSnapshot {
+        meta_ver: 5,
+        db_metas: vec![("db1".to_string(), database)],
+        tbl_metas: vec![(10, table)],
+    }
+}
+
+/// A (restarted) backend whose store is down.
+fn backend_without_store(cache_dir: &str) -> RemoteMeteStoreClient {
+    let mut conf = Config::default();
+    // nothing listens on it
+    conf.meta.meta_address = "127.0.0.1:1".to_string();
+    conf.meta.meta_catalog_cache_dir = cache_dir.to_string();
+
+    let provider = Arc::new(StoreApiProvider::new(&conf));
+    let cache = CatalogSnapshotCache::open(&conf.meta.meta_catalog_cache_dir);
+    RemoteMeteStoreClient::with_timeout_setting(provider, Some(Duration::from_secs(3)))
+        .with_snapshot_cache(Arc::new(cache))
+}
+
+#[test]
+fn test_remote_meta_backend_serve_persisted_snapshot() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir_str = dir.path().to_str().unwrap();
+
+    // persisted by the refresh of the node before its restart
+    CatalogSnapshotCache::open(dir_str).refresh(&catalog_snapshot());
+
+    let backend = backend_without_store(dir_str);
+    assert!(backend.degraded_warning().is_none());
+
+    // SHOW DATABASES / SHOW TABLES
+    let databases = backend.get_databases()?;
+    assert_eq!(1, databases.len());
+    assert_eq!("db1", databases[0].name);
+    let tables = backend.get_tables("db1")?;
+    assert_eq!(1, tables.len());
+    assert_eq!("t1", tables[0].name);
+
+    // DESCRIBE
+    let table = backend.get_table("db1", "t1")?;
+    assert_eq!(10, table.table_id);
+    assert_eq!("a", table.schema.field(0).name().as_str());
+    assert_eq!(&DataType::Int32, table.schema.field(0).data_type());
+
+    let res = backend.get_table("db1", "t2");
+    assert_eq!(ErrorCode::UnknownTable("").code(), res.unwrap_err().code());
+    assert_eq!("Default", backend.get_database("db1")?.engine);
+
+    let warning = backend.degraded_warning().unwrap();
+    assert!(warning.contains("stale"), "{}", warning);
+    assert!(warning.contains("seconds ago"), "{}", warning);
+    Ok(())
+}
+
+#[test]
+fn test_remote_meta_backend_corrupt_snapshot() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir_str = dir.path().to_str().unwrap();
+    std::fs::write(dir.path().join(SNAPSHOT_FILE_NAME), "{\"version\":1,")?;
+
+    let backend = backend_without_store(dir_str);
+    assert!(backend.get_databases()?.is_empty());
+    assert!(backend.get_tables("db1")?.is_empty());
+
+    let res = backend.get_table("db1", "t1");
+    assert_eq!(ErrorCode::UnknownTable("").code(), res.unwrap_err().code());
+
+    let warning = backend.degraded_warning().unwrap();
+    assert!(warning.contains("empty"), "{}", warning);
+    Ok(())
+}
+
+#[test]
+fn test_remote_meta_backend_without_cache() -> Result<()> {
+    let backend = backend_without_store("");
+    assert!(backend.get_databases().is_err());
+    assert!(backend.get_table("db1", "t1").is_err());
+    assert!(backend.degraded_warning().is_none());
+    Ok(())
+}
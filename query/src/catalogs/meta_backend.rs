@@ -23,7 +23,10 @@ use common_planners::CreateDatabasePlan;
 use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
 use common_planners::DropTablePlan;
+use common_planners::ModifyColumnPlan;
 use common_planners::TableOptions;
+use common_planners::UndropTablePlan;
+use common_store_api::CreateTableOutcome;
 
 #[derive(Debug)]
 pub struct TableInfo {
@@ -35,6 +38,17 @@ pub struct TableInfo {
     pub table_option: TableOptions,
 }
 
+#[derive(Debug)]
+pub struct DroppedTableInfo {
+    pub db: String,
+    pub table_id: u64,
+    pub name: String,
+    /// drop time in seconds since 1970
+    pub dropped_on: u64,
+    /// expiration time in seconds since 1970
+    pub expire_at: u64,
+}
+
 #[derive(Clone)]
 pub struct DatabaseInfo {
     pub name: String,
@@ -61,10 +75,29 @@ pub trait MetaBackend: Send + Sync {
 
     fn create_table(&self, plan: CreateTablePlan) -> Result<()>;
 
+    /// Create the tables with one bump of the meta version, a table that cannot be created
+    /// doesn't fail the others.
+    fn create_tables(&self, plans: Vec<CreateTablePlan>) -> Result<Vec<CreateTableOutcome>>;
+
     fn drop_table(&self, plan: DropTablePlan) -> Result<()>;
 
+    fn undrop_table(&self, plan: UndropTablePlan) -> Result<()>;
+
+    /// Get the dropped tables which are not purged yet.
+    fn get_dropped_tables(&self) -> Result<Vec<Arc<DroppedTableInfo>>>;
+
+    /// Widen the type of a column, the existing data is cast to the new type when it is read.
+    fn modify_column(&self, plan: ModifyColumnPlan) -> Result<()>;
+
     fn create_database(&self, plan: CreateDatabasePlan) -> Result<()>;
 
     fn drop_database(&self, plan: DropDatabasePlan) -> Result<()>;
+
+    /// Why the metas may be stale, e.g. they are served from a local snapshot since the store is unreachable.
+    fn degraded_warning(&self) -> Option<String>;
+
+    /// The version of the metas, it changes with every DDL.
+    fn get_meta_version(&self) -> Result<MetaVersion>;
+
     fn name(&self) -> String;
 }
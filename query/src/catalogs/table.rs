@@ -19,9 +19,11 @@ use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::InsertIntoPlan;
+use common_planners::OptimizeTablePlan;
 use common_planners::ReadDataSourcePlan;
 use common_planners::ScanPlan;
 use common_planners::TruncateTablePlan;
+use common_store_api::OptimizeTableResult;
 use common_streams::SendableDataBlockStream;
 
 use crate::sessions::DatabendQueryContextRef;
@@ -47,6 +49,11 @@ pub trait Table: Sync + Send {
         scan: &ScanPlan,
         partitions: usize,
     ) -> Result<ReadDataSourcePlan>;
+    // Get the read source plan without contacting the underling for the parts,
+    // used by EXPLAIN if the remote tables should not be asked, None if the table can not.
+    fn read_plan_without_parts(&self, _scan: &ScanPlan) -> Result<Option<ReadDataSourcePlan>> {
+        Ok(None)
+    }
     // Read block data from the underling.
     async fn read(
         &self,
@@ -76,6 +83,17 @@ pub trait Table: Sync + Send {
             self.name()
         )))
     }
+
+    async fn optimize(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        _optimize_plan: OptimizeTablePlan,
+    ) -> Result<OptimizeTableResult> {
+        Err(ErrorCode::UnImplement(format!(
+            "optimize for local table {} is not implemented",
+            self.name()
+        )))
+    }
 }
 
 pub type TablePtr = Arc<dyn Table>;
@@ -23,6 +23,10 @@ use common_exception::Result;
 use common_infallible::Mutex;
 use common_store_api_sdk::DNSResolver;
 
+use crate::api::FlightChannelPool;
+use crate::api::FlightChannelStats;
+use crate::api::FlightClient;
+use crate::api::FLIGHT_CHANNELS_PER_PEER;
 use crate::clusters::address::Address;
 use crate::clusters::node::Node;
 use crate::configs::Config;
@@ -32,6 +36,7 @@ pub type ClusterRef = Arc<Cluster>;
 pub struct Cluster {
     local_port: u16,
     nodes: Mutex<HashMap<String, Arc<Node>>>,
+    flight_channels: FlightChannelPool,
 }
 
 impl Cluster {
@@ -39,6 +44,7 @@ impl Cluster {
         Ok(Arc::new(Cluster {
             nodes: Mutex::new(HashMap::new()),
             local_port: Address::create(&cfg.query.flight_api_address)?.port(),
+            flight_channels: FlightChannelPool::create(FLIGHT_CHANNELS_PER_PEER),
         }))
     }
 
@@ -46,6 +52,7 @@ impl Cluster {
         Arc::new(Cluster {
             local_port: 9090,
             nodes: Mutex::new(HashMap::new()),
+            flight_channels: FlightChannelPool::create(FLIGHT_CHANNELS_PER_PEER),
         })
     }
 
@@ -111,6 +118,22 @@ impl Cluster {
         nodes.sort_by(|left, right| left.sequence.cmp(&right.sequence));
         Ok(nodes)
     }
+
+    /// A client of `node` on a pooled channel, shared with the other streams to the same address.
+    pub fn get_flight_client(&self, node: &Node, conf: &Config) -> Result<FlightClient> {
+        let tls_conf = if conf.tls_query_cli_enabled() {
+            Some(conf.tls_query_client_conf())
+        } else {
+            None
+        };
+
+        self.flight_channels
+            .get_flight_client(node.address.clone(), tls_conf)
+    }
+
+    pub fn get_flight_channel_stats(&self) -> Vec<FlightChannelStats> {
+        self.flight_channels.get_stats()
+    }
 }
 
 async fn is_local(address: &Address, expect_port: u16) -> Result<bool> {
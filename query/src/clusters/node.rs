@@ -12,16 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common_arrow::arrow_flight::flight_service_client::FlightServiceClient;
 use common_exception::Result;
-use common_store_api_sdk::ConnectionFactory;
 use serde::de::Error;
 use serde::Deserializer;
 use serde::Serializer;
 
 use super::address::Address;
-use crate::api::FlightClient;
-use crate::configs::Config;
 
 #[derive(Debug)]
 pub struct Node {
@@ -63,18 +59,6 @@ impl Node {
     pub fn is_local(&self) -> bool {
         self.local
     }
-
-    pub async fn get_flight_client(&self, conf: &Config) -> Result<FlightClient> {
-        let tls_conf = if conf.tls_query_cli_enabled() {
-            Some(conf.tls_query_client_conf())
-        } else {
-            None
-        };
-
-        let channel =
-            ConnectionFactory::create_flight_channel(self.address.clone(), None, tls_conf);
-        channel.map(|channel| FlightClient::new(FlightServiceClient::new(channel)))
-    }
 }
 
 impl serde::Serialize for Node {
@@ -13,6 +13,9 @@
 //  limitations under the License.
 //
 
+use std::time::Duration;
+
+use common_store_api_sdk::ChannelConf;
 use common_store_api_sdk::ClientConf;
 use common_store_api_sdk::RpcClientTlsConfig;
 use common_store_api_sdk::StoreClientConf;
@@ -61,6 +64,21 @@ impl From<&Config> for StoreClientConf {
             block_service_config: config,
             // copy meta config from query config
             meta_service_config: meta_config,
+            redact_rpc_keys: conf.store.store_rpc_redact_keys == "1",
+            channel_conf: ChannelConf {
+                max_idle: secs(conf.store.store_rpc_max_idle_secs),
+                max_lifetime: secs(conf.store.store_rpc_max_lifetime_secs),
+                keepalive: secs(conf.store.store_rpc_keepalive_secs),
+                ..Default::default()
+            },
         }
     }
 }
+
+/// Zero seconds disables the limit.
+fn secs(secs: u64) -> Option<Duration> {
+    match secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
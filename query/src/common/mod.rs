@@ -17,6 +17,7 @@ pub use hash_table_entity::HashTableEntity;
 pub use hash_table_entity::KeyValueEntity;
 pub use hash_table_iter::HashTableIter;
 pub use hash_table_key::HashTableKeyable;
+pub use temp_dir_manager::TempDirManager;
 
 #[cfg(test)]
 mod hash_table_grower_test;
@@ -29,6 +30,7 @@ mod hash_table_grower;
 mod hash_table_iter;
 mod hash_table_key;
 mod store_api_provider;
+mod temp_dir_manager;
 
 pub type HashMap<Key, Value> = HashTable<Key, KeyValueEntity<Key, Value>>;
 pub type HashMapIterator<Key, Value> = HashTableIter<Key, KeyValueEntity<Key, Value>>;
@@ -14,11 +14,13 @@
 //
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use common_exception::Result;
 use common_store_api::KVApi;
 use common_store_api::MetaApi;
 use common_store_api::StorageApi;
+use common_store_api_sdk::RpcStats;
 use common_store_api_sdk::StoreClient;
 use common_store_api_sdk::StoreClientConf;
 
@@ -32,20 +34,60 @@ pub struct StoreApiProvider {
     // do not depend on query::configs::Config in case of moving back to sdk
     // also @see config_converter.rs
     conf: StoreClientConf,
+    rpc_stats: Option<Arc<RpcStats>>,
+    query_label: Option<String>,
+    deadline: Option<Instant>,
 }
 
 impl StoreApiProvider {
     pub fn new(conf: impl Into<StoreClientConf>) -> Self {
-        StoreApiProvider { conf: conf.into() }
+        StoreApiProvider {
+            conf: conf.into(),
+            rpc_stats: None,
+            query_label: None,
+            deadline: None,
+        }
+    }
+
+    /// The clients count their calls into `stats`, e.g. the ones of the query using them.
+    pub fn with_rpc_stats(mut self, stats: Arc<RpcStats>) -> Self {
+        self.rpc_stats = Some(stats);
+        self
+    }
+
+    /// The clients send `label` along with their requests, e.g. the label of the query using them.
+    pub fn with_query_label(mut self, label: Option<String>) -> Self {
+        self.query_label = label;
+        self
+    }
+
+    /// The clients bound their calls by `deadline`, e.g. the one of the query using them.
+    /// Without it, the calls keep the configured timeout.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    fn attach_query(&self, mut client: StoreClient) -> StoreClient {
+        if let Some(stats) = &self.rpc_stats {
+            client.set_rpc_stats(stats.clone());
+        }
+        if let Some(label) = &self.query_label {
+            client.set_query_label(label.clone());
+        }
+        if let Some(deadline) = self.deadline {
+            client.set_deadline(deadline);
+        }
+        client
     }
 
     pub async fn try_get_meta_client(&self) -> Result<Arc<dyn MetaApi>> {
-        let client = StoreClient::try_new(&self.conf).await?;
+        let client = self.attach_query(StoreClient::try_new(&self.conf).await?);
         Ok(Arc::new(client))
     }
 
     pub fn sync_try_get_meta_client(&self) -> Result<Arc<dyn MetaApi>> {
-        let client = StoreClient::sync_try_new(&self.conf)?;
+        let client = self.attach_query(StoreClient::sync_try_new(&self.conf)?);
         Ok(Arc::new(client))
     }
 
@@ -55,7 +97,7 @@ impl StoreApiProvider {
             let client = kvlocal::LocalKVStore::new_temp().await?;
             Ok(Arc::new(client))
         } else {
-            let client = StoreClient::try_new(&self.conf).await?;
+            let client = self.attach_query(StoreClient::try_new(&self.conf).await?);
             Ok(Arc::new(client))
         }
     }
@@ -66,18 +108,18 @@ impl StoreApiProvider {
             let client = kvlocal::LocalKVStore::sync_new_temp()?;
             Ok(Arc::new(client))
         } else {
-            let client = StoreClient::sync_try_new(&self.conf)?;
+            let client = self.attach_query(StoreClient::sync_try_new(&self.conf)?);
             Ok(Arc::new(client))
         }
     }
 
     pub async fn try_get_storage_client(&self) -> Result<Arc<dyn StorageApi>> {
-        let client = StoreClient::try_new(&self.conf).await?;
+        let client = self.attach_query(StoreClient::try_new(&self.conf).await?);
         Ok(Arc::new(client))
     }
 
     pub fn sync_try_get_storage_client(&self) -> Result<Arc<dyn StorageApi>> {
-        let client = StoreClient::sync_try_new(&self.conf)?;
+        let client = self.attach_query(StoreClient::sync_try_new(&self.conf)?);
         Ok(Arc::new(client))
     }
 }
@@ -0,0 +1,68 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use common_exception::Result;
+use common_tracing::tracing;
+
+/// Temporary files of a query, e.g. the sorted runs spilled by ORDER BY.
+/// All the files live in a directory named after the query id, which is created on the first
+/// file and removed with everything left in it when the manager is dropped.
+pub struct TempDirManager {
+    root: PathBuf,
+    next_file: AtomicUsize,
+}
+
+impl TempDirManager {
+    pub fn create(root: PathBuf) -> TempDirManager {
+        TempDirManager {
+            root,
+            next_file: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn for_query(query_id: &str) -> TempDirManager {
+        let root = std::env::temp_dir().join("databend-query").join(query_id);
+        TempDirManager::create(root)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn create_temp_file(&self, prefix: &str) -> Result<(PathBuf, File)> {
+        std::fs::create_dir_all(&self.root)?;
+
+        let id = self.next_file.fetch_add(1, Ordering::Relaxed);
+        let path = self.root.join(format!("{}_{}", prefix, id));
+        let file = File::create(&path)?;
+        Ok((path, file))
+    }
+}
+
+impl Drop for TempDirManager {
+    fn drop(&mut self) {
+        if let Err(cause) = std::fs::remove_dir_all(&self.root) {
+            if cause.kind() != ErrorKind::NotFound {
+                tracing::warn!("Cannot remove temp dir {}: {}", self.root.display(), cause);
+            }
+        }
+    }
+}
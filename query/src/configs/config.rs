@@ -63,10 +63,13 @@ const QUERY_NAMESPACE: &str = "QUERY_NAMESPACE";
 const QUERY_NUM_CPUS: &str = "QUERY_NUM_CPUS";
 const QUERY_MYSQL_HANDLER_HOST: &str = "QUERY_MYSQL_HANDLER_HOST";
 const QUERY_MYSQL_HANDLER_PORT: &str = "QUERY_MYSQL_HANDLER_PORT";
+const QUERY_MYSQL_HANDLER_IDLE_TIMEOUT_SECS: &str = "QUERY_MYSQL_HANDLER_IDLE_TIMEOUT_SECS";
 const QUERY_MAX_ACTIVE_SESSIONS: &str = "QUERY_MAX_ACTIVE_SESSIONS";
+const QUERY_SESSION_IDLE_TIMEOUT_SECS: &str = "QUERY_SESSION_IDLE_TIMEOUT_SECS";
 const QUERY_CLICKHOUSE_HANDLER_HOST: &str = "QUERY_CLICKHOUSE_HANDLER_HOST";
 const QUERY_CLICKHOUSE_HANDLER_PORT: &str = "QUERY_CLICKHOUSE_HANDLER_PORT";
 const QUERY_FLIGHT_API_ADDRESS: &str = "QUERY_FLIGHT_API_ADDRESS";
+const QUERY_FLIGHT_STAGE_TTL_SECS: &str = "QUERY_FLIGHT_STAGE_TTL_SECS";
 const QUERY_HTTP_API_ADDRESS: &str = "QUERY_HTTP_API_ADDRESS";
 const QUERY_METRICS_API_ADDRESS: &str = "QUERY_METRIC_API_ADDRESS";
 const QUERY_API_TLS_SERVER_CERT: &str = "QUERY_API_TLS_SERVER_CERT";
@@ -78,6 +81,15 @@ const QUERY_RPC_TLS_SERVER_KEY: &str = "QUERY_RPC_TLS_SERVER_KEY";
 const QUERY_RPC_TLS_SERVER_ROOT_CA_CERT: &str = "QUERY_RPC_TLS_SERVER_ROOT_CA_CERT";
 const QUERY_RPC_TLS_SERVICE_DOMAIN_NAME: &str = "QUERY_RPC_TLS_SERVICE_DOMAIN_NAME";
 const QUERY_DISABLE_LOCAL_DATABASE_ENGINE: &str = "QUERY_DISABLE_LOCAL_DATABASE_ENGINE";
+const QUERY_RESOURCE_GROUPS: &str = "QUERY_RESOURCE_GROUPS";
+const QUERY_RESOURCE_GROUP_USERS: &str = "QUERY_RESOURCE_GROUP_USERS";
+const QUERY_RESOURCE_GROUP_TICK_ROWS: &str = "QUERY_RESOURCE_GROUP_TICK_ROWS";
+const QUERY_RUNTIME_IO_THREADS: &str = "QUERY_RUNTIME_IO_THREADS";
+const QUERY_RUNTIME_COMPUTE_THREADS: &str = "QUERY_RUNTIME_COMPUTE_THREADS";
+const QUERY_RUNTIME_MANAGEMENT_THREADS: &str = "QUERY_RUNTIME_MANAGEMENT_THREADS";
+const QUERY_RUNTIME_DRAIN_TIMEOUT_SECS: &str = "QUERY_RUNTIME_DRAIN_TIMEOUT_SECS";
+const QUERY_ENABLE_KV_TABLE_FUNCTIONS: &str = "QUERY_ENABLE_KV_TABLE_FUNCTIONS";
+const QUERY_METRIC_HISTOGRAM_BUCKETS: &str = "QUERY_METRIC_HISTOGRAM_BUCKETS";
 
 // Meta env.
 const META_ADDRESS: &str = "META_ADDRESS";
@@ -85,6 +97,7 @@ const META_USERNAME: &str = "META_USERNAME";
 const META_PASSWORD: &str = "META_PASSWORD";
 const META_RPC_TLS_SERVER_ROOT_CA_CERT: &str = "META_RPC_TLS_SERVER_ROOT_CA_CERT";
 const META_RPC_TLS_SERVICE_DOMAIN_NAME: &str = "META_RPC_TLS_SERVICE_DOMAIN_NAME";
+const META_CATALOG_CACHE_DIR: &str = "META_CATALOG_CACHE_DIR";
 
 // Store env.
 const STORE_ADDRESS: &str = "STORE_ADDRESS";
@@ -92,6 +105,10 @@ const STORE_USERNAME: &str = "STORE_USERNAME";
 const STORE_PASSWORD: &str = "STORE_PASSWORD";
 const STORE_RPC_TLS_SERVER_ROOT_CA_CERT: &str = "STORE_RPC_TLS_SERVER_ROOT_CA_CERT";
 const STORE_RPC_TLS_SERVICE_DOMAIN_NAME: &str = "STORE_RPC_TLS_SERVICE_DOMAIN_NAME";
+const STORE_RPC_REDACT_KEYS: &str = "STORE_RPC_REDACT_KEYS";
+const STORE_RPC_MAX_IDLE_SECS: &str = "STORE_RPC_MAX_IDLE_SECS";
+const STORE_RPC_MAX_LIFETIME_SECS: &str = "STORE_RPC_MAX_LIFETIME_SECS";
+const STORE_RPC_KEEPALIVE_SECS: &str = "STORE_RPC_KEEPALIVE_SECS";
 
 // Config file.
 const CONFIG_FILE: &str = "CONFIG_FILE";
@@ -152,6 +169,42 @@ pub struct StoreConfig {
     )]
     #[serde(default)]
     pub rpc_tls_store_service_domain_name: String,
+
+    #[structopt(
+        long,
+        env = STORE_RPC_REDACT_KEYS,
+        default_value = "0",
+        help = "Hide the keys and table names in the spans of the store rpcs, 1 to enable"
+    )]
+    #[serde(default)]
+    pub store_rpc_redact_keys: String,
+
+    #[structopt(
+        long,
+        env = STORE_RPC_MAX_IDLE_SECS,
+        default_value = "0",
+        help = "Reconnect the store rpc channel idle for longer than this on the next rpc, 0 to disable"
+    )]
+    #[serde(default)]
+    pub store_rpc_max_idle_secs: u64,
+
+    #[structopt(
+        long,
+        env = STORE_RPC_MAX_LIFETIME_SECS,
+        default_value = "0",
+        help = "Reconnect the store rpc channel older than this on the next rpc, 0 to disable"
+    )]
+    #[serde(default)]
+    pub store_rpc_max_lifetime_secs: u64,
+
+    #[structopt(
+        long,
+        env = STORE_RPC_KEEPALIVE_SECS,
+        default_value = "0",
+        help = "Ping the store rpc connection at this interval and close it if a ping is not acknowledged, 0 to disable"
+    )]
+    #[serde(default)]
+    pub store_rpc_keepalive_secs: u64,
 }
 
 impl StoreConfig {
@@ -162,6 +215,10 @@ impl StoreConfig {
             store_password: "".to_string(),
             rpc_tls_store_server_root_ca_cert: "".to_string(),
             rpc_tls_store_service_domain_name: "localhost".to_string(),
+            store_rpc_redact_keys: "0".to_string(),
+            store_rpc_max_idle_secs: 0,
+            store_rpc_max_lifetime_secs: 0,
+            store_rpc_keepalive_secs: 0,
         }
     }
 }
@@ -208,6 +265,15 @@ pub struct MetaConfig {
     )]
     #[serde(default)]
     pub rpc_tls_meta_service_domain_name: String,
+
+    #[structopt(
+        long,
+        env = META_CATALOG_CACHE_DIR,
+        default_value = "",
+        help = "Dir to persist the catalog snapshot, which is served when the MetaStore is unreachable, empty to disable"
+    )]
+    #[serde(default)]
+    pub meta_catalog_cache_dir: String,
 }
 
 impl MetaConfig {
@@ -218,6 +284,7 @@ impl MetaConfig {
             meta_password: "".to_string(),
             rpc_tls_meta_server_root_ca_cert: "".to_string(),
             rpc_tls_meta_service_domain_name: "localhost".to_string(),
+            meta_catalog_cache_dir: "".to_string(),
         }
     }
 }
@@ -262,6 +329,15 @@ pub struct QueryConfig {
     #[serde(default)]
     pub mysql_handler_port: u16,
 
+    #[structopt(
+        long,
+        env = QUERY_MYSQL_HANDLER_IDLE_TIMEOUT_SECS,
+        default_value = "28800",
+        help = "Seconds a MySQL connection waits for the next command before it is closed, 0 to disable"
+    )]
+    #[serde(default)]
+    pub mysql_handler_idle_timeout_secs: u64,
+
     #[structopt(
     long,
     env = QUERY_MAX_ACTIVE_SESSIONS,
@@ -270,6 +346,15 @@ pub struct QueryConfig {
     #[serde(default)]
     pub max_active_sessions: u64,
 
+    #[structopt(
+        long,
+        env = QUERY_SESSION_IDLE_TIMEOUT_SECS,
+        default_value = "0",
+        help = "Seconds a session without a running query and without the requests of its client is kept, beyond it the session expires and its slot is released, 0 to disable"
+    )]
+    #[serde(default)]
+    pub session_idle_timeout_secs: u64,
+
     #[structopt(
     long,
     env = QUERY_CLICKHOUSE_HANDLER_HOST,
@@ -294,6 +379,15 @@ pub struct QueryConfig {
     #[serde(default)]
     pub flight_api_address: String,
 
+    #[structopt(
+        long,
+        env = QUERY_FLIGHT_STAGE_TTL_SECS,
+        default_value = "300",
+        help = "Seconds a prepared stage waits for its streams to be fetched before it is dropped"
+    )]
+    #[serde(default)]
+    pub flight_stage_ttl_secs: u64,
+
     #[structopt(
     long,
     env = QUERY_HTTP_API_ADDRESS,
@@ -310,6 +404,15 @@ pub struct QueryConfig {
     #[serde(default)]
     pub metric_api_address: String,
 
+    #[structopt(
+        long,
+        env = QUERY_METRIC_HISTOGRAM_BUCKETS,
+        default_value = "",
+        help = "Bucket bounds of the histograms on the metric API, in the form of 0.01,0.1,1,..., empty means summaries"
+    )]
+    #[serde(default)]
+    pub metric_histogram_buckets: String,
+
     #[structopt(long, env = QUERY_API_TLS_SERVER_CERT, default_value = "")]
     #[serde(default)]
     pub api_tls_server_cert: String,
@@ -359,6 +462,78 @@ pub struct QueryConfig {
     #[structopt(long, env = "QUERY_DISABLE_LOCAL_DATABASE_ENGINE", default_value = "0")]
     #[serde(default)]
     pub disable_local_database_engine: String,
+
+    #[structopt(
+        long,
+        env = QUERY_RESOURCE_GROUPS,
+        default_value = "",
+        help = "Resource groups, in the form of name:weight[:max_concurrency],..."
+    )]
+    #[serde(default)]
+    pub resource_groups: String,
+
+    #[structopt(
+        long,
+        env = QUERY_RESOURCE_GROUP_USERS,
+        default_value = "",
+        help = "Resource group of users, in the form of user=group,..."
+    )]
+    #[serde(default)]
+    pub resource_group_users: String,
+
+    #[structopt(
+        long,
+        env = QUERY_RESOURCE_GROUP_TICK_ROWS,
+        default_value = "0",
+        help = "Rows per weight a resource group can process every 10ms, 0 means no throttling"
+    )]
+    #[serde(default)]
+    pub resource_group_tick_rows: u64,
+
+    #[structopt(
+        long,
+        env = QUERY_RUNTIME_IO_THREADS,
+        default_value = "0",
+        help = "Threads of the io runtime pool, 0 means the number of CPUs"
+    )]
+    #[serde(default)]
+    pub runtime_io_threads: u64,
+
+    #[structopt(
+        long,
+        env = QUERY_RUNTIME_COMPUTE_THREADS,
+        default_value = "0",
+        help = "Threads of the compute runtime pool, 0 means the number of CPUs"
+    )]
+    #[serde(default)]
+    pub runtime_compute_threads: u64,
+
+    #[structopt(
+        long,
+        env = QUERY_RUNTIME_MANAGEMENT_THREADS,
+        default_value = "2",
+        help = "Threads of the management runtime pool, 0 means the number of CPUs"
+    )]
+    #[serde(default)]
+    pub runtime_management_threads: u64,
+
+    #[structopt(
+        long,
+        env = QUERY_RUNTIME_DRAIN_TIMEOUT_SECS,
+        default_value = "10",
+        help = "Seconds to wait for the runtime pools to drain on shutdown"
+    )]
+    #[serde(default)]
+    pub runtime_drain_timeout_secs: u64,
+
+    #[structopt(
+        long,
+        env = QUERY_ENABLE_KV_TABLE_FUNCTIONS,
+        default_value = "0",
+        help = "Allow kv_list and kv_get to read the kv store, 1 to enable"
+    )]
+    #[serde(default)]
+    pub enable_kv_table_functions: String,
 }
 
 impl QueryConfig {
@@ -369,12 +544,16 @@ impl QueryConfig {
             num_cpus: 8,
             mysql_handler_host: "127.0.0.1".to_string(),
             mysql_handler_port: 3307,
+            mysql_handler_idle_timeout_secs: 28800,
             max_active_sessions: 256,
+            session_idle_timeout_secs: 0,
             clickhouse_handler_host: "127.0.0.1".to_string(),
             clickhouse_handler_port: 9000,
             flight_api_address: "127.0.0.1:9090".to_string(),
+            flight_stage_ttl_secs: 300,
             http_api_address: "127.0.0.1:8080".to_string(),
             metric_api_address: "127.0.0.1:7070".to_string(),
+            metric_histogram_buckets: "".to_string(),
             api_tls_server_cert: "".to_string(),
             api_tls_server_key: "".to_string(),
             api_tls_server_root_ca_cert: "".to_string(),
@@ -383,6 +562,14 @@ impl QueryConfig {
             rpc_tls_query_server_root_ca_cert: "".to_string(),
             rpc_tls_query_service_domain_name: "localhost".to_string(),
             disable_local_database_engine: "0".to_string(),
+            resource_groups: "".to_string(),
+            resource_group_users: "".to_string(),
+            resource_group_tick_rows: 0,
+            runtime_io_threads: 0,
+            runtime_compute_threads: 0,
+            runtime_management_threads: 2,
+            runtime_drain_timeout_secs: 10,
+            enable_kv_table_functions: "0".to_string(),
         }
     }
 }
@@ -477,6 +664,13 @@ impl Config {
             String,
             META_RPC_TLS_SERVICE_DOMAIN_NAME
         );
+        env_helper!(
+            mut_config,
+            meta,
+            meta_catalog_cache_dir,
+            String,
+            META_CATALOG_CACHE_DIR
+        );
 
         // Store.
         env_helper!(mut_config, store, store_address, String, STORE_ADDRESS);
@@ -498,6 +692,34 @@ impl Config {
             String,
             STORE_RPC_TLS_SERVICE_DOMAIN_NAME
         );
+        env_helper!(
+            mut_config,
+            store,
+            store_rpc_redact_keys,
+            String,
+            STORE_RPC_REDACT_KEYS
+        );
+        env_helper!(
+            mut_config,
+            store,
+            store_rpc_max_idle_secs,
+            u64,
+            STORE_RPC_MAX_IDLE_SECS
+        );
+        env_helper!(
+            mut_config,
+            store,
+            store_rpc_max_lifetime_secs,
+            u64,
+            STORE_RPC_MAX_LIFETIME_SECS
+        );
+        env_helper!(
+            mut_config,
+            store,
+            store_rpc_keepalive_secs,
+            u64,
+            STORE_RPC_KEEPALIVE_SECS
+        );
 
         // Query.
         env_helper!(mut_config, query, tenant, String, QUERY_TENANT);
@@ -517,6 +739,13 @@ impl Config {
             u16,
             QUERY_MYSQL_HANDLER_PORT
         );
+        env_helper!(
+            mut_config,
+            query,
+            mysql_handler_idle_timeout_secs,
+            u64,
+            QUERY_MYSQL_HANDLER_IDLE_TIMEOUT_SECS
+        );
         env_helper!(
             mut_config,
             query,
@@ -524,6 +753,13 @@ impl Config {
             u64,
             QUERY_MAX_ACTIVE_SESSIONS
         );
+        env_helper!(
+            mut_config,
+            query,
+            session_idle_timeout_secs,
+            u64,
+            QUERY_SESSION_IDLE_TIMEOUT_SECS
+        );
         env_helper!(
             mut_config,
             query,
@@ -545,6 +781,13 @@ impl Config {
             String,
             QUERY_FLIGHT_API_ADDRESS
         );
+        env_helper!(
+            mut_config,
+            query,
+            flight_stage_ttl_secs,
+            u64,
+            QUERY_FLIGHT_STAGE_TTL_SECS
+        );
         env_helper!(
             mut_config,
             query,
@@ -559,6 +802,13 @@ impl Config {
             String,
             QUERY_METRICS_API_ADDRESS
         );
+        env_helper!(
+            mut_config,
+            query,
+            metric_histogram_buckets,
+            String,
+            QUERY_METRIC_HISTOGRAM_BUCKETS
+        );
         env_helper!(
             mut_config,
             query,
@@ -566,6 +816,62 @@ impl Config {
             String,
             QUERY_DISABLE_LOCAL_DATABASE_ENGINE
         );
+        env_helper!(
+            mut_config,
+            query,
+            resource_groups,
+            String,
+            QUERY_RESOURCE_GROUPS
+        );
+        env_helper!(
+            mut_config,
+            query,
+            resource_group_users,
+            String,
+            QUERY_RESOURCE_GROUP_USERS
+        );
+        env_helper!(
+            mut_config,
+            query,
+            resource_group_tick_rows,
+            u64,
+            QUERY_RESOURCE_GROUP_TICK_ROWS
+        );
+        env_helper!(
+            mut_config,
+            query,
+            runtime_io_threads,
+            u64,
+            QUERY_RUNTIME_IO_THREADS
+        );
+        env_helper!(
+            mut_config,
+            query,
+            runtime_compute_threads,
+            u64,
+            QUERY_RUNTIME_COMPUTE_THREADS
+        );
+        env_helper!(
+            mut_config,
+            query,
+            runtime_management_threads,
+            u64,
+            QUERY_RUNTIME_MANAGEMENT_THREADS
+        );
+        env_helper!(
+            mut_config,
+            query,
+            runtime_drain_timeout_secs,
+            u64,
+            QUERY_RUNTIME_DRAIN_TIMEOUT_SECS
+        );
+        env_helper!(
+            mut_config,
+            query,
+            enable_kv_table_functions,
+            String,
+            QUERY_ENABLE_KV_TABLE_FUNCTIONS
+        );
 
         // for api http service
         env_helper!(
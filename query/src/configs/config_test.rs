@@ -44,16 +44,33 @@ fn test_env_config() -> Result<()> {
     std::env::set_var("QUERY_NAMESPACE", "cluster-1");
     std::env::set_var("QUERY_MYSQL_HANDLER_HOST", "0.0.0.0");
     std::env::set_var("QUERY_MYSQL_HANDLER_PORT", "3306");
+    std::env::set_var("QUERY_MYSQL_HANDLER_IDLE_TIMEOUT_SECS", "600");
     std::env::set_var("QUERY_MAX_ACTIVE_SESSIONS", "255");
+    std::env::set_var("QUERY_SESSION_IDLE_TIMEOUT_SECS", "300");
     std::env::set_var("QUERY_CLICKHOUSE_HANDLER_HOST", "1.2.3.4");
     std::env::set_var("QUERY_CLICKHOUSE_HANDLER_PORT", "9000");
     std::env::set_var("QUERY_FLIGHT_API_ADDRESS", "1.2.3.4:9091");
+    std::env::set_var("QUERY_FLIGHT_STAGE_TTL_SECS", "60");
     std::env::set_var("QUERY_HTTP_API_ADDRESS", "1.2.3.4:8081");
     std::env::set_var("QUERY_METRIC_API_ADDRESS", "1.2.3.4:7071");
+    std::env::set_var("QUERY_METRIC_HISTOGRAM_BUCKETS", "0.1,1,10");
     std::env::set_var("QUERY_DISABLE_LOCAL_DATABASE_ENGINE", "1");
+    std::env::set_var("QUERY_RESOURCE_GROUPS", "etl:1:2,adhoc:3");
+    std::env::set_var("QUERY_RESOURCE_GROUP_USERS", "bob=etl");
+    std::env::set_var("QUERY_RESOURCE_GROUP_TICK_ROWS", "1000");
+    std::env::set_var("QUERY_RUNTIME_IO_THREADS", "3");
+    std::env::set_var("QUERY_RUNTIME_COMPUTE_THREADS", "4");
+    std::env::set_var("QUERY_RUNTIME_MANAGEMENT_THREADS", "1");
+    std::env::set_var("QUERY_RUNTIME_DRAIN_TIMEOUT_SECS", "30");
+    std::env::set_var("QUERY_ENABLE_KV_TABLE_FUNCTIONS", "1");
+    std::env::set_var("META_CATALOG_CACHE_DIR", "/tmp/catalog");
     std::env::set_var("STORE_ADDRESS", "1.2.3.4:1234");
     std::env::set_var("STORE_USERNAME", "admin");
     std::env::set_var("STORE_PASSWORD", "password!");
+    std::env::set_var("STORE_RPC_REDACT_KEYS", "1");
+    std::env::set_var("STORE_RPC_MAX_IDLE_SECS", "600");
+    std::env::set_var("STORE_RPC_MAX_LIFETIME_SECS", "3600");
+    std::env::set_var("STORE_RPC_KEEPALIVE_SECS", "30");
     std::env::remove_var("CONFIG_FILE");
 
     let default = Config::default();
@@ -64,18 +81,36 @@ fn test_env_config() -> Result<()> {
     assert_eq!("cluster-1", configured.query.namespace);
     assert_eq!("0.0.0.0", configured.query.mysql_handler_host);
     assert_eq!(3306, configured.query.mysql_handler_port);
+    assert_eq!(600, configured.query.mysql_handler_idle_timeout_secs);
     assert_eq!(255, configured.query.max_active_sessions);
+    assert_eq!(300, configured.query.session_idle_timeout_secs);
     assert_eq!("1.2.3.4", configured.query.clickhouse_handler_host);
     assert_eq!(9000, configured.query.clickhouse_handler_port);
 
     assert_eq!("1.2.3.4:9091", configured.query.flight_api_address);
+    assert_eq!(60, configured.query.flight_stage_ttl_secs);
     assert_eq!("1.2.3.4:8081", configured.query.http_api_address);
     assert_eq!("1.2.3.4:7071", configured.query.metric_api_address);
+    assert_eq!("0.1,1,10", configured.query.metric_histogram_buckets);
     assert_eq!("1", configured.query.disable_local_database_engine);
+    assert_eq!("etl:1:2,adhoc:3", configured.query.resource_groups);
+    assert_eq!("bob=etl", configured.query.resource_group_users);
+    assert_eq!(1000, configured.query.resource_group_tick_rows);
+    assert_eq!(3, configured.query.runtime_io_threads);
+    assert_eq!(4, configured.query.runtime_compute_threads);
+    assert_eq!(1, configured.query.runtime_management_threads);
+    assert_eq!(30, configured.query.runtime_drain_timeout_secs);
+    assert_eq!("1", configured.query.enable_kv_table_functions);
+
+    assert_eq!("/tmp/catalog", configured.meta.meta_catalog_cache_dir);
 
     assert_eq!("1.2.3.4:1234", configured.store.store_address);
     assert_eq!("admin", configured.store.store_username);
     assert_eq!("password!", configured.store.store_password);
+    assert_eq!("1", configured.store.store_rpc_redact_keys);
+    assert_eq!(600, configured.store.store_rpc_max_idle_secs);
+    assert_eq!(3600, configured.store.store_rpc_max_lifetime_secs);
+    assert_eq!(30, configured.store.store_rpc_keepalive_secs);
 
     // clean up
     std::env::remove_var("LOG_LEVEL");
@@ -83,17 +118,34 @@ fn test_env_config() -> Result<()> {
     std::env::remove_var("QUERY_NAMESPACE");
     std::env::remove_var("QUERY_MYSQL_HANDLER_HOST");
     std::env::remove_var("QUERY_MYSQL_HANDLER_PORT");
+    std::env::remove_var("QUERY_MYSQL_HANDLER_IDLE_TIMEOUT_SECS");
     std::env::remove_var("QUERY_MAX_ACTIVE_SESSIONS");
+    std::env::remove_var("QUERY_SESSION_IDLE_TIMEOUT_SECS");
     std::env::remove_var("QUERY_CLICKHOUSE_HANDLER_HOST");
     std::env::remove_var("QUERY_CLICKHOUSE_HANDLER_PORT");
     std::env::remove_var("QUERY_CLICKHOUSE_HANDLER_THREAD_NUM");
     std::env::remove_var("QUERY_FLIGHT_API_ADDRESS");
+    std::env::remove_var("QUERY_FLIGHT_STAGE_TTL_SECS");
     std::env::remove_var("QUERY_HTTP_API_ADDRESS");
     std::env::remove_var("QUERY_METRIC_API_ADDRESS");
+    std::env::remove_var("QUERY_METRIC_HISTOGRAM_BUCKETS");
     std::env::remove_var("QUERY_DISABLE_LOCAL_DATABASE_ENGINE");
+    std::env::remove_var("QUERY_RESOURCE_GROUPS");
+    std::env::remove_var("QUERY_RESOURCE_GROUP_USERS");
+    std::env::remove_var("QUERY_RESOURCE_GROUP_TICK_ROWS");
+    std::env::remove_var("QUERY_RUNTIME_IO_THREADS");
+    std::env::remove_var("QUERY_RUNTIME_COMPUTE_THREADS");
+    std::env::remove_var("QUERY_RUNTIME_MANAGEMENT_THREADS");
+    std::env::remove_var("QUERY_RUNTIME_DRAIN_TIMEOUT_SECS");
+    std::env::remove_var("QUERY_ENABLE_KV_TABLE_FUNCTIONS");
+    std::env::remove_var("META_CATALOG_CACHE_DIR");
     std::env::remove_var("STORE_ADDRESS");
     std::env::remove_var("STORE_USERNAME");
     std::env::remove_var("STORE_PASSWORD");
+    std::env::remove_var("STORE_RPC_REDACT_KEYS");
+    std::env::remove_var("STORE_RPC_MAX_IDLE_SECS");
+    std::env::remove_var("STORE_RPC_MAX_LIFETIME_SECS");
+    std::env::remove_var("STORE_RPC_KEEPALIVE_SECS");
     Ok(())
 }
 
@@ -22,4 +22,7 @@ mod line;
 mod part;
 
 pub use line::count_lines;
+pub use part::count_step_range;
 pub use part::generate_parts;
+pub use part::generate_range_parts;
+pub use part::generate_step_range_parts;
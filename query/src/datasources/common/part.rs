@@ -24,6 +24,7 @@ pub fn generate_parts(start: u64, workers: u64, total: u64) -> Partitions {
         partitions.push(Part {
             name: format!("{}-{}-{}", total, start, total,),
             version: 0,
+            read_info: None,
         })
     } else {
         for part in 0..workers {
@@ -38,8 +39,83 @@ pub fn generate_parts(start: u64, workers: u64, total: u64) -> Partitions {
             partitions.push(Part {
                 name: format!("{}-{}-{}", total, part_begin, part_end,),
                 version: 0,
+                read_info: None,
             })
         }
     }
     partitions
 }
+
+/// The parts of the numbers in [begin, end), split as `generate_parts` does from 0.
+pub fn generate_range_parts(begin: u64, end: u64, workers: u64) -> Partitions {
+    let total = end - begin;
+    let part_size = total / workers;
+    let part_remain = total % workers;
+
+    if part_size == 0 {
+        return vec![Part {
+            name: format!("{}-{}-{}", end, begin, end),
+            version: 0,
+            read_info: None,
+        }];
+    }
+
+    (0..workers)
+        .map(|part| {
+            let part_begin = begin + part * part_size;
+            let mut part_end = part_begin + part_size;
+            if part == (workers - 1) {
+                part_end += part_remain;
+            }
+            Part {
+                name: format!("{}-{}-{}", end, part_begin, part_end),
+                version: 0,
+                read_info: None,
+            }
+        })
+        .collect()
+}
+
+/// The parts of the numbers from `begin` below `end` by `step`, split into runs of about the same
+/// number of rows as `generate_range_parts` does. The parts of a step other than 1 are named
+/// `end-begin-end-step`, a part has the numbers of the sequence from its begin below its end.
+pub fn generate_step_range_parts(begin: u64, end: u64, step: u64, workers: u64) -> Partitions {
+    if step == 1 {
+        return generate_range_parts(begin, end, workers);
+    }
+
+    let total = count_step_range(begin, end, step);
+    let part_size = total / workers;
+
+    if part_size == 0 {
+        return vec![Part {
+            name: format!("{}-{}-{}-{}", end, begin, end, step),
+            version: 0,
+            read_info: None,
+        }];
+    }
+
+    // The last part has the remaining numbers as well.
+    (0..workers)
+        .map(|part| {
+            let part_begin = begin + part * part_size * step;
+            let part_end = match part == (workers - 1) {
+                true => end,
+                false => part_begin + part_size * step,
+            };
+            Part {
+                name: format!("{}-{}-{}-{}", end, part_begin, part_end, step),
+                version: 0,
+                read_info: None,
+            }
+        })
+        .collect()
+}
+
+/// The count of the numbers from `begin` below `end` by `step`.
+pub fn count_step_range(begin: u64, end: u64, step: u64) -> u64 {
+    match end > begin {
+        true => (end - begin - 1) / step + 1,
+        false => 0,
+    }
+}
@@ -16,7 +16,10 @@ use common_exception::Result;
 use common_planners::Part;
 use pretty_assertions::assert_eq;
 
+use crate::datasources::common::count_step_range;
 use crate::datasources::common::generate_parts;
+use crate::datasources::common::generate_range_parts;
+use crate::datasources::common::generate_step_range_parts;
 
 #[test]
 fn test_util_generate_parts() -> Result<()> {
@@ -28,21 +31,24 @@ fn test_util_generate_parts() -> Result<()> {
         assert_eq!(
             Part {
                 name: "11-0-3".into(),
-                version: 0
+                version: 0,
+                read_info: None,
             },
             ps[0]
         );
         assert_eq!(
             Part {
                 name: "11-3-6".into(),
-                version: 0
+                version: 0,
+                read_info: None,
             },
             ps[1]
         );
         assert_eq!(
             Part {
                 name: "11-6-11".into(),
-                version: 0
+                version: 0,
+                read_info: None,
             },
             ps[2]
         );
@@ -56,7 +62,8 @@ fn test_util_generate_parts() -> Result<()> {
         assert_eq!(
             Part {
                 name: "0-0-0".into(),
-                version: 0
+                version: 0,
+                read_info: None,
             },
             ps[0]
         );
@@ -69,7 +76,8 @@ fn test_util_generate_parts() -> Result<()> {
         assert_eq!(
             Part {
                 name: "2-0-2".into(),
-                version: 0
+                version: 0,
+                read_info: None,
             },
             ps[0]
         );
@@ -77,3 +85,56 @@ fn test_util_generate_parts() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_util_generate_range_parts() -> Result<()> {
+    let names = |parts: Vec<Part>| parts.into_iter().map(|p| p.name).collect::<Vec<_>>();
+
+    // the same as generate_parts from 0
+    assert_eq!(
+        names(generate_parts(0, 3, 11)),
+        names(generate_range_parts(0, 11, 3))
+    );
+
+    assert_eq!(
+        vec!["11-2-5", "11-5-8", "11-8-11"],
+        names(generate_range_parts(2, 11, 3))
+    );
+    assert_eq!(vec!["11-9-11"], names(generate_range_parts(9, 11, 3)));
+    assert_eq!(vec!["11-11-11"], names(generate_range_parts(11, 11, 3)));
+
+    Ok(())
+}
+
+#[test]
+fn test_util_generate_step_range_parts() -> Result<()> {
+    let names = |parts: Vec<Part>| parts.into_iter().map(|p| p.name).collect::<Vec<_>>();
+
+    // the step 1 is the same as generate_range_parts
+    assert_eq!(
+        names(generate_range_parts(2, 11, 3)),
+        names(generate_step_range_parts(2, 11, 1, 3))
+    );
+
+    // 2, 5, 8, 11, 14, 17, 20
+    assert_eq!(
+        vec!["21-2-8-3", "21-8-14-3", "21-14-21-3"],
+        names(generate_step_range_parts(2, 21, 3, 3))
+    );
+    assert_eq!(
+        vec!["21-2-21-10"],
+        names(generate_step_range_parts(2, 21, 10, 3))
+    );
+    assert_eq!(
+        vec!["5-5-5-2"],
+        names(generate_step_range_parts(5, 5, 2, 3))
+    );
+
+    assert_eq!(7, count_step_range(2, 21, 3));
+    assert_eq!(6, count_step_range(2, 20, 3));
+    assert_eq!(5, count_step_range(2, 17, 3));
+    assert_eq!(0, count_step_range(5, 5, 3));
+    assert_eq!(1, count_step_range(u64::MAX - 1, u64::MAX, u64::MAX));
+
+    Ok(())
+}
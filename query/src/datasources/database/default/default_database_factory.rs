@@ -32,29 +32,33 @@ use crate::datasources::table_engine_registry::TableEngineRegistry;
 pub struct DefaultDatabaseFactory {
     meta_backend: Arc<dyn MetaBackend>,
     table_factory_registry: Arc<TableEngineRegistry>,
+    /// Shared by the databases, the store calls of their tables are counted along with the
+    /// ones of the catalog.
+    store_api_provider: StoreApiProvider,
 }
 
 impl DefaultDatabaseFactory {
     pub fn new(
         meta_backend: Arc<dyn MetaBackend>,
         table_factory_registry: Arc<TableEngineRegistry>,
+        store_api_provider: StoreApiProvider,
     ) -> Self {
         Self {
             meta_backend,
             table_factory_registry,
+            store_api_provider,
         }
     }
 }
 
 impl DatabaseEngine for DefaultDatabaseFactory {
-    fn create(&self, conf: &Config, db_info: &Arc<DatabaseInfo>) -> Result<Arc<dyn Database>> {
-        let client_provider = StoreApiProvider::new(conf);
+    fn create(&self, _conf: &Config, db_info: &Arc<DatabaseInfo>) -> Result<Arc<dyn Database>> {
         let db = DefaultDatabase::new(
             &db_info.name,
             &db_info.engine,
             self.meta_backend.clone(),
             self.table_factory_registry.clone(),
-            client_provider,
+            self.store_api_provider.clone(),
         );
         Ok(Arc::new(db))
     }
@@ -86,6 +86,7 @@ impl Table for ExampleTable {
             parts: vec![Part {
                 name: "".to_string(),
                 version: 0,
+                read_info: None,
             }],
             statistics: Statistics::new_exact(0, 0),
             description: format!(
@@ -94,6 +95,8 @@ impl Table for ExampleTable {
             ),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
@@ -18,6 +18,7 @@ use std::sync::Arc;
 use common_exception::Result;
 
 use crate::catalogs::meta_backend::MetaBackend;
+use crate::common::StoreApiProvider;
 use crate::datasources::database::default::default_database_factory::DefaultDatabaseFactory;
 use crate::datasources::database_engine_registry::DatabaseEngineRegistry;
 use crate::datasources::table_engine_registry::TableEngineRegistry;
@@ -28,8 +29,10 @@ pub fn register_prelude_db_engines(
     registry: &DatabaseEngineRegistry,
     meta_backend: Arc<dyn MetaBackend>,
     table_factory_registry: Arc<TableEngineRegistry>,
+    store_api_provider: StoreApiProvider,
 ) -> Result<()> {
-    let default = DefaultDatabaseFactory::new(meta_backend, table_factory_registry);
+    let default =
+        DefaultDatabaseFactory::new(meta_backend, table_factory_registry, store_api_provider);
     registry.register(DB_ENGINE_DEFAULT, Arc::new(default))?;
     Ok(())
 }
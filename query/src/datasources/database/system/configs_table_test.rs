@@ -45,7 +45,7 @@ async fn test_configs_table() -> Result<()> {
     let result = stream.try_collect::<Vec<_>>().await?;
     let block = &result[0];
     assert_eq!(block.num_columns(), 4);
-    assert_eq!(block.num_rows(), 31);
+    assert_eq!(block.num_rows(), 42);
 
     let expected = vec![
         "+-----------------------------------+----------------+-------+-------------+",
@@ -57,19 +57,27 @@ async fn test_configs_table() -> Result<()> {
         "| clickhouse_handler_host           | 127.0.0.1      | query |             |",
         "| clickhouse_handler_port           | 9000           | query |             |",
         "| disable_local_database_engine     | 0              | query |             |",
+        "| enable_kv_table_functions         | 0              | query |             |",
         "| flight_api_address                | 127.0.0.1:9090 | query |             |",
+        "| flight_stage_ttl_secs             | 300            | query |             |",
         "| http_api_address                  | 127.0.0.1:8080 | query |             |",
         "| log_dir                           | ./_logs        | log   |             |",
         "| log_level                         | INFO           | log   |             |",
         "| max_active_sessions               | 256            | query |             |",
         "| meta_address                      |                | meta  |             |",
+        "| meta_catalog_cache_dir            |                | meta  |             |",
         "| meta_password                     |                | meta  |             |",
         "| meta_username                     | root           | meta  |             |",
         "| metric_api_address                | 127.0.0.1:7070 | query |             |",
+        "| metric_histogram_buckets          |                | query |             |",
         "| mysql_handler_host                | 127.0.0.1      | query |             |",
+        "| mysql_handler_idle_timeout_secs   | 28800          | query |             |",
         "| mysql_handler_port                | 3307           | query |             |",
         "| namespace                         |                | query |             |",
         "| num_cpus                          | 8              | query |             |",
+        "| resource_group_tick_rows          | 0              | query |             |",
+        "| resource_group_users              |                | query |             |",
+        "| resource_groups                   |                | query |             |",
         "| rpc_tls_meta_server_root_ca_cert  |                | meta  |             |",
         "| rpc_tls_meta_service_domain_name  | localhost      | meta  |             |",
         "| rpc_tls_query_server_root_ca_cert |                | query |             |",
@@ -78,8 +86,17 @@ async fn test_configs_table() -> Result<()> {
         "| rpc_tls_server_key                |                | query |             |",
         "| rpc_tls_store_server_root_ca_cert |                | store |             |",
         "| rpc_tls_store_service_domain_name | localhost      | store |             |",
+        "| runtime_compute_threads           | 0              | query |             |",
+        "| runtime_drain_timeout_secs        | 10             | query |             |",
+        "| runtime_io_threads                | 0              | query |             |",
+        "| runtime_management_threads        | 2              | query |             |",
+        "| session_idle_timeout_secs         | 0              | query |             |",
         "| store_address                     |                | store |             |",
         "| store_password                    |                | store |             |",
+        "| store_rpc_keepalive_secs          | 0              | store |             |",
+        "| store_rpc_max_idle_secs           | 0              | store |             |",
+        "| store_rpc_max_lifetime_secs       | 0              | store |             |",
+        "| store_rpc_redact_keys             | 0              | store |             |",
         "| store_username                    | root           | store |             |",
         "| tenant                            |                | query |             |",
         "+-----------------------------------+----------------+-------+-------------+",
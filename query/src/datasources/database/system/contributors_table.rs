@@ -77,11 +77,14 @@ impl Table for ContributorsTable {
             parts: vec![Part {
                 name: "".to_string(),
                 version: 0,
+                read_info: None,
             }],
             statistics: Statistics::default(),
             description: "(Read from system.contributors table)".to_string(),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
@@ -0,0 +1,131 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::common::StoreApiProvider;
+use crate::sessions::DatabendQueryContextRef;
+
+/// The storage quota and the used bytes of the databases in the store.
+/// It is empty in local mode, where there is no store to enforce the quotas.
+pub struct DatabaseUsagesTable {
+    schema: DataSchemaRef,
+}
+
+impl DatabaseUsagesTable {
+    pub fn create() -> Self {
+        DatabaseUsagesTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("name", DataType::String, false),
+                DataField::new("quota", DataType::UInt64, true),
+                DataField::new("used_bytes", DataType::UInt64, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for DatabaseUsagesTable {
+    fn name(&self) -> &str {
+        "database_usages"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemDatabaseUsages"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.database_usages table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let conf = ctx.get_config();
+        let usages = match conf.meta.meta_address.is_empty() {
+            true => vec![],
+            false => {
+                let client = StoreApiProvider::new(&conf)
+                    .with_rpc_stats(ctx.get_query_metrics().get_store_rpc_stats())
+                    .try_get_meta_client()
+                    .await?;
+                client.get_database_usages().await?.usages
+            }
+        };
+
+        let names: Vec<&[u8]> = usages.iter().map(|(db, _)| db.as_bytes()).collect();
+        let quotas: Vec<Option<u64>> = usages.iter().map(|(_, usage)| usage.quota).collect();
+        let used_bytes: Vec<u64> = usages.iter().map(|(_, usage)| usage.used_bytes).collect();
+
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(names),
+            Series::new(quotas),
+            Series::new(used_bytes),
+        ]);
+
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
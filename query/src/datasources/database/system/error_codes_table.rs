@@ -0,0 +1,128 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_exception::ERROR_CODES;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::sessions::DatabendQueryContextRef;
+
+pub struct ErrorCodesTable {
+    schema: DataSchemaRef,
+}
+
+impl ErrorCodesTable {
+    pub fn create() -> Self {
+        ErrorCodesTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("code", DataType::UInt16, false),
+                DataField::new("name", DataType::String, false),
+                DataField::new("subsystem", DataType::String, false),
+                DataField::new("retryable", DataType::Boolean, false),
+                DataField::new("description", DataType::String, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for ErrorCodesTable {
+    fn name(&self) -> &str {
+        "error_codes"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemErrorCodes"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.error_codes table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let codes: Vec<u16> = ERROR_CODES.iter().map(|x| x.code).collect();
+        let names: Vec<&[u8]> = ERROR_CODES.iter().map(|x| x.name.as_bytes()).collect();
+        let subsystems: Vec<&[u8]> = ERROR_CODES
+            .iter()
+            .map(|x| x.subsystem.name().as_bytes())
+            .collect();
+        let retryables: Vec<bool> = ERROR_CODES.iter().map(|x| x.retryable).collect();
+        let descriptions: Vec<&[u8]> = ERROR_CODES
+            .iter()
+            .map(|x| x.description.as_bytes())
+            .collect();
+
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(codes),
+            Series::new(names),
+            Series::new(subsystems),
+            Series::new(retryables),
+            Series::new(descriptions),
+        ]);
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
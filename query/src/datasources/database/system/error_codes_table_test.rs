@@ -0,0 +1,69 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datavalues::DataValue;
+use common_exception::Result;
+use common_exception::ERROR_CODES;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+
+use crate::catalogs::Table;
+use crate::datasources::database::system::ErrorCodesTable;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_error_codes_table() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let table = ErrorCodesTable::create();
+    let source_plan = table.read_plan(
+        ctx.clone(),
+        &ScanPlan::empty(),
+        ctx.get_settings().get_max_threads()? as usize,
+    )?;
+
+    let stream = table.read(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let block = &result[0];
+    assert_eq!(block.num_columns(), 5);
+    assert_eq!(block.num_rows(), ERROR_CODES.len());
+
+    // Spot check some well-known codes.
+    let expects = vec![
+        (3u16, "UnknownDatabase", "query", false),
+        (40, "Timeout", "query", true),
+        (1000, "UnknownException", "internal", false),
+        (2203, "MetaServiceUnavailable", "store", true),
+        (4003, "TableAlreadyExists", "meta", false),
+        (8002, "UnknownTableEngine", "datasource", false),
+    ];
+    for (code, name, subsystem, retryable) in expects {
+        let row = (0..block.num_rows())
+            .find(|row| block.column(0).try_get(*row).ok() == Some(DataValue::UInt16(Some(code))))
+            .unwrap_or_else(|| panic!("code {} not found", code));
+        assert_eq!(
+            block.column(1).try_get(row)?,
+            DataValue::String(Some(name.as_bytes().to_vec()))
+        );
+        assert_eq!(
+            block.column(2).try_get(row)?,
+            DataValue::String(Some(subsystem.as_bytes().to_vec()))
+        );
+        assert_eq!(
+            block.column(3).try_get(row)?,
+            DataValue::Boolean(Some(retryable))
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::sessions::DatabendQueryContextRef;
+
+pub struct FlightChannelsTable {
+    schema: DataSchemaRef,
+}
+
+impl FlightChannelsTable {
+    pub fn create() -> Self {
+        FlightChannelsTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("peer", DataType::String, false),
+                DataField::new("channel_id", DataType::UInt64, false),
+                DataField::new("active_streams", DataType::UInt64, false),
+                DataField::new("total_streams", DataType::UInt64, false),
+                DataField::new("broken", DataType::Boolean, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for FlightChannelsTable {
+    fn name(&self) -> &str {
+        "flight_channels"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemFlightChannels"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.flight_channels table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let stats = ctx.try_get_cluster()?.get_flight_channel_stats();
+
+        let peers: Vec<&[u8]> = stats.iter().map(|x| x.peer.as_bytes()).collect();
+        let channel_ids: Vec<u64> = stats.iter().map(|x| x.channel_id).collect();
+        let active_streams: Vec<u64> = stats.iter().map(|x| x.active_streams as u64).collect();
+        let total_streams: Vec<u64> = stats.iter().map(|x| x.total_streams).collect();
+        let broken: Vec<bool> = stats.iter().map(|x| x.broken).collect();
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(peers),
+            Series::new(channel_ids),
+            Series::new(active_streams),
+            Series::new(total_streams),
+            Series::new(broken),
+        ]);
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_metatypes::KVValue;
+use common_metatypes::SeqValue;
+use common_planners::Expression;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::catalogs::TableFunction;
+use crate::sessions::DatabendQueryContextRef;
+
+/// Reads the generic kv store of the node for administrative queries:
+/// `kv_list(prefix)` lists the keys under a prefix and `kv_get(key)` returns at most one row.
+///
+/// Both are disabled unless `enable_kv_table_functions` is set, there is no privilege to check yet.
+pub struct KvTable {
+    table: &'static str,
+    schema: DataSchemaRef,
+}
+
+impl KvTable {
+    pub fn create(table: &'static str) -> Self {
+        KvTable {
+            table,
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("key", DataType::String, false),
+                DataField::new("seq", DataType::UInt64, false),
+                DataField::new("value", DataType::String, false),
+                DataField::new("expire_at", DataType::DateTime32(None), true),
+            ]),
+        }
+    }
+
+    fn check_enabled(&self, ctx: &DatabendQueryContextRef) -> Result<()> {
+        match ctx.get_config().query.enable_kv_table_functions.as_str() {
+            "1" => Ok(()),
+            _ => Err(ErrorCode::PermissionDenied(format!(
+                "Table function {} is disabled, set enable_kv_table_functions to 1 to enable it",
+                self.table
+            ))),
+        }
+    }
+
+    fn key_argument(&self, scan: &ScanPlan) -> Result<String> {
+        match scan.table_args.as_deref() {
+            Some(
+                [Expression::Literal {
+                    value: DataValue::String(Some(key)),
+                    ..
+                }],
+            ) => Ok(String::from_utf8(key.clone())?),
+            _ => Err(ErrorCode::BadArguments(format!(
+                "Must have one string argument for table function: {}",
+                self.table
+            ))),
+        }
+    }
+
+    // Values which are not valid UTF-8 are rendered as hex.
+    fn to_block(schema: DataSchemaRef, entries: Vec<(String, SeqValue<KVValue>)>) -> DataBlock {
+        let keys: Vec<&[u8]> = entries.iter().map(|(key, _)| key.as_bytes()).collect();
+        let seqs: Vec<u64> = entries.iter().map(|(_, (seq, _))| *seq).collect();
+        let values: Vec<Vec<u8>> = entries
+            .iter()
+            .map(
+                |(_, (_, kv_value))| match std::str::from_utf8(&kv_value.value) {
+                    Ok(_) => kv_value.value.clone(),
+                    Err(_) => kv_value
+                        .value
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect::<String>()
+                        .into_bytes(),
+                },
+            )
+            .collect();
+        let expire_ats: Vec<Option<u32>> = entries
+            .iter()
+            .map(|(_, (_, kv_value))| {
+                kv_value
+                    .meta
+                    .as_ref()
+                    .and_then(|meta| meta.expire_at)
+                    .map(|expire_at| expire_at.min(u32::MAX as u64) as u32)
+            })
+            .collect();
+
+        DataBlock::create_by_array(schema, vec![
+            Series::new(keys),
+            Series::new(seqs),
+            Series::new(values),
+            Series::new(expire_ats),
+        ])
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for KvTable {
+    fn name(&self) -> &str {
+        self.table
+    }
+
+    fn engine(&self) -> &str {
+        match self.table {
+            "kv_list" => "SystemKvList",
+            "kv_get" => "SystemKvGet",
+            _ => unreachable!(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        self.check_enabled(&ctx)?;
+        let key = self.key_argument(scan)?;
+
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: format!("(Read from system.{} table, Key:{})", self.table, key),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        self.check_enabled(&ctx)?;
+        let key = self.key_argument(&source_plan.scan_plan)?;
+        let kv_api = ctx.get_sessions_manager().get_kv_api().await?;
+
+        let schema = self.schema.clone();
+        match self.table {
+            "kv_list" => {
+                // A page of max_block_size records per block, fetched when the downstream pulls
+                // it. The next page starts after the last key of this one.
+                let limit = (ctx.get_settings().get_max_block_size()? as usize).max(1);
+                let pages = futures::stream::try_unfold(Some(None), move |after_key| {
+                    let kv_api = kv_api.clone();
+                    let key = key.clone();
+                    let schema = schema.clone();
+                    async move {
+                        let after_key = match after_key {
+                            None => return Ok(None),
+                            Some(after_key) => after_key,
+                        };
+                        let page = kv_api
+                            .prefix_list_kv_page(&key, Some(limit), after_key)
+                            .await?;
+                        let next = match page.entries.last() {
+                            Some((last_key, _)) if page.more => Some(Some(last_key.clone())),
+                            _ => None,
+                        };
+                        Ok(Some((KvTable::to_block(schema, page.entries), next)))
+                    }
+                });
+                Ok(Box::pin(pages))
+            }
+            "kv_get" => {
+                let entries = kv_api
+                    .get_kv(&key)
+                    .await?
+                    .result
+                    .map(|seq_value| vec![(key, seq_value)])
+                    .unwrap_or_default();
+                let block = KvTable::to_block(schema, entries);
+                Ok(Box::pin(futures::stream::iter(vec![Ok(block)])))
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl TableFunction for KvTable {
+    fn function_name(&self) -> &str {
+        self.table
+    }
+
+    fn db(&self) -> &str {
+        "system"
+    }
+
+    fn as_table<'a>(self: Arc<Self>) -> Arc<dyn Table + 'a>
+    where Self: 'a {
+        self
+    }
+}
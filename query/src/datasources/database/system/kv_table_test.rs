@@ -0,0 +1,119 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datavalues::DataValue;
+use common_exception::codes;
+use common_exception::Result;
+use common_metatypes::KVMeta;
+use common_metatypes::MatchSeq;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+
+use crate::catalogs::Table;
+use crate::configs::Config;
+use crate::datasources::database::system::KvTable;
+
+fn scan_with_key(key: &str) -> ScanPlan {
+    ScanPlan {
+        table_args: Some(vec![Expression::create_literal(DataValue::String(Some(
+            key.as_bytes().to_vec(),
+        )))]),
+        ..ScanPlan::empty()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_kv_table() -> Result<()> {
+    let mut config = Config::default();
+    config.query.enable_kv_table_functions = "1".to_string();
+
+    let ctx = crate::tests::try_create_context_with_conf(config)?;
+    ctx.get_settings().set_max_block_size(2)?;
+
+    let kv_api = ctx.get_sessions_manager().get_kv_api().await?;
+    let expire_at = Some(KVMeta {
+        expire_at: Some(4000000000),
+    });
+    kv_api
+        .upsert_kv("__cluster/a", MatchSeq::Any, Some(b"1".to_vec()), None)
+        .await?;
+    kv_api
+        .upsert_kv("__cluster/b", MatchSeq::Any, Some(b"2".to_vec()), expire_at)
+        .await?;
+    kv_api
+        .upsert_kv("__cluster/c", MatchSeq::Any, Some(vec![0xff, 0x00]), None)
+        .await?;
+    kv_api
+        .upsert_kv("__other/d", MatchSeq::Any, Some(b"4".to_vec()), None)
+        .await?;
+
+    // kv_list, three rows in two blocks.
+    {
+        let table = KvTable::create("kv_list");
+        let source_plan = table.read_plan(ctx.clone(), &scan_with_key("__cluster/"), 1)?;
+        let stream = table.read(ctx.clone(), &source_plan).await?;
+        let result = stream.try_collect::<Vec<_>>().await?;
+        assert_eq!(result.len(), 2);
+
+        let expected = vec![
+            "+-------------+-----+-------+------------+",
+            "| key         | seq | value | expire_at  |",
+            "+-------------+-----+-------+------------+",
+            "| __cluster/a | 1   | 1     | NULL       |",
+            "| __cluster/b | 2   | 2     | 4000000000 |",
+            "| __cluster/c | 3   | ff00  | NULL       |",
+            "+-------------+-----+-------+------------+",
+        ];
+        common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+        // Nothing under the prefix, an empty block.
+        let source_plan = table.read_plan(ctx.clone(), &scan_with_key("__none/"), 1)?;
+        let stream = table.read(ctx.clone(), &source_plan).await?;
+        let result = stream.try_collect::<Vec<_>>().await?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].num_rows(), 0);
+    }
+
+    // kv_get, one row for an existing key and an empty block for a missing one.
+    {
+        let table = KvTable::create("kv_get");
+        let source_plan = table.read_plan(ctx.clone(), &scan_with_key("__other/d"), 1)?;
+        let stream = table.read(ctx.clone(), &source_plan).await?;
+        let result = stream.try_collect::<Vec<_>>().await?;
+        assert_eq!(result[0].num_rows(), 1);
+
+        let source_plan = table.read_plan(ctx.clone(), &scan_with_key("__other/e"), 1)?;
+        let stream = table.read(ctx.clone(), &source_plan).await?;
+        let result = stream.try_collect::<Vec<_>>().await?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].num_columns(), 4);
+        assert_eq!(result[0].num_rows(), 0);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_kv_table_disabled() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    for name in ["kv_list", "kv_get"] {
+        let table = KvTable::create(name);
+        let result = table.read_plan(ctx.clone(), &scan_with_key("__cluster/"), 1);
+        assert_eq!(result.unwrap_err().code(), codes::PermissionDenied);
+    }
+
+    Ok(())
+}
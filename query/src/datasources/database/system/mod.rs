@@ -21,51 +21,89 @@ mod contributors_table_test;
 #[cfg(test)]
 mod credits_table_test;
 #[cfg(test)]
+mod database_usages_table_test;
+#[cfg(test)]
 mod databases_table_test;
 #[cfg(test)]
 mod engines_table_test;
 #[cfg(test)]
+mod error_codes_table_test;
+#[cfg(test)]
+mod flight_channels_table_test;
+#[cfg(test)]
 mod functions_table_test;
 #[cfg(test)]
+mod kv_table_test;
+#[cfg(test)]
 mod numbers_table_test;
 #[cfg(test)]
+mod processes_table_test;
+#[cfg(test)]
+mod query_log_table_test;
+#[cfg(test)]
+mod resource_groups_table_test;
+#[cfg(test)]
 mod settings_table_test;
 #[cfg(test)]
+mod store_rpcs_table_test;
+#[cfg(test)]
+mod tables_history_table_test;
+#[cfg(test)]
 mod tables_table_test;
 #[cfg(test)]
 mod tracing_table_test;
+#[cfg(test)]
+mod warnings_table_test;
 
 mod clusters_table;
 mod configs_table;
 mod contributors_table;
 mod credits_table;
+mod database_usages_table;
 mod databases_table;
 mod engines_table;
+mod error_codes_table;
+mod flight_channels_table;
 mod functions_table;
+mod kv_table;
 mod numbers_stream;
 mod numbers_table;
 mod one_table;
 mod processes_table;
+mod query_log_table;
+mod resource_groups_table;
 mod settings_table;
+mod store_rpcs_table;
 mod system_database;
+mod tables_history_table;
 mod tables_table;
 mod tracing_table;
 mod tracing_table_stream;
+mod warnings_table;
 
 pub use clusters_table::ClustersTable;
 pub use configs_table::ConfigsTable;
 pub use contributors_table::ContributorsTable;
 pub use credits_table::CreditsTable;
+pub use database_usages_table::DatabaseUsagesTable;
 pub use databases_table::DatabasesTable;
 pub use engines_table::EnginesTable;
+pub use error_codes_table::ErrorCodesTable;
+pub use flight_channels_table::FlightChannelsTable;
 pub use functions_table::FunctionsTable;
+pub use kv_table::KvTable;
 pub use numbers_stream::NumbersStream;
 pub use numbers_table::NumbersTable;
 pub use one_table::OneTable;
 pub use processes_table::ProcessesTable;
+pub use query_log_table::QueryLogTable;
+pub use resource_groups_table::ResourceGroupsTable;
 pub use settings_table::SettingsTable;
+pub use store_rpcs_table::StoreRpcsTable;
 pub use system_database::SystemDatabase;
 //pub use system_databases::SystemDatabases;
+pub use tables_history_table::TablesHistoryTable;
 pub use tables_table::TablesTable;
 pub use tracing_table::TracingTable;
 pub use tracing_table_stream::TracingTableStream;
+pub use warnings_table::WarningsTable;
@@ -22,12 +22,15 @@ use common_exception::Result;
 use common_streams::ProgressStream;
 use futures::stream::Stream;
 
+use crate::datasources::common::count_step_range;
 use crate::sessions::DatabendQueryContextRef;
 
+/// The numbers of a block, from begin below end by step.
 #[derive(Debug, Clone)]
 struct BlockRange {
     begin: u64,
     end: u64,
+    step: u64,
 }
 
 pub struct NumbersStream {
@@ -62,31 +65,34 @@ impl NumbersStream {
                 return Ok(None);
             }
 
-            let block_size = self.ctx.get_settings().get_max_block_size()?;
+            // A block has at most max_block_size numbers.
+            let block_size = self.ctx.get_settings().get_max_block_size()?.max(1);
             let mut blocks = Vec::with_capacity(partitions.len());
             for part in partitions {
                 let names: Vec<_> = part.name.split('-').collect();
                 let begin: u64 = names[1].parse()?;
                 let end: u64 = names[2].parse()?;
-
-                let diff = end - begin;
-                let block_nums = diff / block_size;
-                let block_remain = diff % block_size;
-
-                if block_nums == 0 {
-                    blocks.push(BlockRange { begin, end });
-                } else {
-                    for r in 0..block_nums {
-                        let range_begin = begin + block_size * r;
-                        let mut range_end = range_begin + block_size;
-                        if r == (block_nums - 1) && block_remain > 0 {
-                            range_end += block_remain;
-                        }
-                        blocks.push(BlockRange {
-                            begin: range_begin,
-                            end: range_end,
-                        });
-                    }
+                let step: u64 = match names.get(3) {
+                    Some(step) => step.parse()?,
+                    None => 1,
+                };
+
+                let rows = count_step_range(begin, end, step);
+                if rows == 0 {
+                    blocks.push(BlockRange { begin, end, step });
+                    continue;
+                }
+                for first in (0..rows).step_by(block_size as usize) {
+                    let range_begin = begin + first * step;
+                    let range_end = match rows - first > block_size {
+                        true => range_begin + block_size * step,
+                        false => end,
+                    };
+                    blocks.push(BlockRange {
+                        begin: range_begin,
+                        end: range_end,
+                        step,
+                    });
                 }
             }
             self.blocks = blocks;
@@ -99,7 +105,7 @@ impl NumbersStream {
         Ok(if current.begin == current.end {
             None
         } else {
-            let size = (current.end - current.begin) as usize;
+            let size = count_step_range(current.begin, current.end, current.step) as usize;
             let mut av = AlignedVec::with_capacity(size);
 
             unsafe { av.set_len(size) };
@@ -108,7 +114,7 @@ impl NumbersStream {
                 .iter_mut()
                 .enumerate()
                 .for_each(|(idx, num)| {
-                    *num = current.begin + idx as u64;
+                    *num = current.begin + idx as u64 * current.step;
                 });
 
             let series = DFUInt64Array::new_from_aligned_vec(av).into_series();
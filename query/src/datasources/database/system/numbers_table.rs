@@ -20,6 +20,7 @@ use common_datavalues::DataField;
 use common_datavalues::DataSchemaRef;
 use common_datavalues::DataSchemaRefExt;
 use common_datavalues::DataType;
+use common_datavalues::DataValue;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::Expression;
@@ -30,7 +31,8 @@ use common_streams::SendableDataBlockStream;
 
 use crate::catalogs::Table;
 use crate::catalogs::TableFunction;
-use crate::datasources::common::generate_parts;
+use crate::datasources::common::count_step_range;
+use crate::datasources::common::generate_step_range_parts;
 use crate::datasources::database::system::NumbersStream;
 use crate::sessions::DatabendQueryContextRef;
 
@@ -86,21 +88,28 @@ impl Table for NumbersTable {
         scan: &ScanPlan,
         _partitions: usize,
     ) -> Result<ReadDataSourcePlan> {
-        let mut total = None;
-        let ScanPlan { table_args, .. } = scan.clone();
-        if let Some(Expression::Literal { value, .. }) = table_args {
-            total = Some(value.as_u64()?);
-        }
+        let (start, end, step) = self.arguments(scan)?;
 
-        let total = total.ok_or_else(|| {
-            ErrorCode::BadArguments(format!(
-                "Must have one number argument for table: system.{}",
+        // Only the numbers in the range of the pushed down filters are read, the range begins
+        // at a number of the sequence. An inverted range has no numbers.
+        let (begin, end) = scan
+            .push_downs
+            .filters
+            .iter()
+            .fold((start, end.max(start)), |range, filter| {
+                narrow_range(range, filter)
+            });
+        let begin = align_up(start, begin, step).unwrap_or(end).min(end);
+        let rows = count_step_range(begin, end, step);
+
+        let bytes = rows.checked_mul(size_of::<u64>() as u64).ok_or_else(|| {
+            ErrorCode::Overflow(format!(
+                "The {} rows of table function system.{} overflow the read bytes",
+                rows,
                 self.name()
             ))
         })?;
-
-        let statistics =
-            Statistics::new_exact(total as usize, ((total) * size_of::<u64>() as u64) as usize);
+        let statistics = Statistics::new_exact(rows as usize, bytes as usize);
         ctx.try_set_statistics(&statistics)?;
         ctx.add_total_rows_approx(statistics.read_rows);
 
@@ -110,7 +119,7 @@ impl Table for NumbersTable {
             table_id: scan.table_id,
             table_version: scan.table_version,
             schema: self.schema.clone(),
-            parts: generate_parts(0, ctx.get_settings().get_max_threads()?, total),
+            parts: generate_step_range_parts(begin, end, step, ctx.get_max_threads()?),
             statistics: statistics.clone(),
             description: format!(
                 "(Read from system.{} table, Read Rows:{}, Read Bytes:{})",
@@ -118,6 +127,8 @@ impl Table for NumbersTable {
             ),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
@@ -133,6 +144,67 @@ impl Table for NumbersTable {
     }
 }
 
+impl NumbersTable {
+    /// The numbers from begin below end by step, of `numbers(end)`, `numbers(begin, end)` or
+    /// `numbers(begin, end, step)`.
+    fn arguments(&self, scan: &ScanPlan) -> Result<(u64, u64, u64)> {
+        let args = scan
+            .table_args
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|arg| match arg {
+                Expression::Literal { value, .. } => self.argument(value),
+                _ => Err(ErrorCode::BadArguments(format!(
+                    "The arguments of table function system.{} must be number literals",
+                    self.name()
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        match args.as_slice() {
+            [end] => Ok((0, *end, 1)),
+            [begin, end] => Ok((*begin, *end, 1)),
+            [_, _, 0] => Err(ErrorCode::BadArguments(format!(
+                "The step of table function system.{} must not be 0",
+                self.name()
+            ))),
+            [begin, end, step] => Ok((*begin, *end, *step)),
+            _ => Err(ErrorCode::BadArguments(format!(
+                "Must have one to three number arguments for table: system.{}",
+                self.name()
+            ))),
+        }
+    }
+
+    /// An argument is a UInt64, a literal past the Int64 range is planned as a Float64.
+    fn argument(&self, value: &DataValue) -> Result<u64> {
+        let negative = || {
+            ErrorCode::BadArguments(format!(
+                "The arguments of table function system.{} must not be negative, got {}",
+                self.name(),
+                value
+            ))
+        };
+        match value {
+            DataValue::Int8(Some(v)) if *v < 0 => Err(negative()),
+            DataValue::Int16(Some(v)) if *v < 0 => Err(negative()),
+            DataValue::Int32(Some(v)) if *v < 0 => Err(negative()),
+            DataValue::Int64(Some(v)) if *v < 0 => Err(negative()),
+            DataValue::Float64(Some(v)) if *v < 0.0 => Err(negative()),
+            DataValue::Float64(Some(v)) if *v >= u64::MAX as f64 => {
+                Err(ErrorCode::Overflow(format!(
+                    "The argument {} of table function system.{} overflows UInt64",
+                    value,
+                    self.name()
+                )))
+            }
+            DataValue::Float64(Some(v)) if v.fract() == 0.0 => Ok(*v as u64),
+            value => value.as_u64(),
+        }
+    }
+}
+
 impl TableFunction for NumbersTable {
     fn function_name(&self) -> &str {
         self.table
@@ -147,3 +219,48 @@ impl TableFunction for NumbersTable {
         self
     }
 }
+
+/// The first number of the sequence from `start` by `step` not below `begin`, None if it
+/// overflows.
+fn align_up(start: u64, begin: u64, step: u64) -> Option<u64> {
+    if begin <= start {
+        return Some(start);
+    }
+    let offset = begin - start;
+    let steps = offset / step + (offset % step != 0) as u64;
+    start.checked_add(steps.checked_mul(step)?)
+}
+
+/// Narrows the range [begin, end) of the numbers by the comparisons of the number column
+/// with the literals in the filter, e.g. `number > 1 AND number < 5`. The others keep the range.
+fn narrow_range((begin, end): (u64, u64), filter: &Expression) -> (u64, u64) {
+    let (left, op, right) = match filter {
+        Expression::BinaryExpression { left, op, right } => (left, op.to_lowercase(), right),
+        _ => return (begin, end),
+    };
+
+    if op == "and" {
+        let range = narrow_range((begin, end), left);
+        return narrow_range(range, right);
+    }
+
+    let value = match (left.as_ref(), right.as_ref()) {
+        (Expression::Column(name), Expression::Literal { value, .. }) if name == "number" => {
+            match value.as_i64() {
+                Ok(value) if value >= 0 => value as u64,
+                _ => return (begin, end),
+            }
+        }
+        _ => return (begin, end),
+    };
+
+    let (begin, end) = match op.as_str() {
+        ">" => (begin.max(value.saturating_add(1)), end),
+        ">=" => (begin.max(value), end),
+        "<" => (begin, end.min(value)),
+        "<=" => (begin, end.min(value.saturating_add(1))),
+        "=" => (begin.max(value), end.min(value.saturating_add(1))),
+        _ => (begin, end),
+    };
+    (begin.min(end), end)
+}
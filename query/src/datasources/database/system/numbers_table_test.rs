@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::*;
 use common_runtime::tokio;
@@ -20,6 +22,7 @@ use futures::TryStreamExt;
 
 use crate::catalogs::Table;
 use crate::datasources::database::system::NumbersTable;
+use crate::sessions::DatabendQueryContextRef;
 
 #[tokio::test]
 async fn test_number_table() -> Result<()> {
@@ -31,7 +34,7 @@ async fn test_number_table() -> Result<()> {
         table_id: 0,
         table_version: None,
         table_schema: DataSchemaRefExt::create(vec![]),
-        table_args: Some(Expression::create_literal(DataValue::UInt64(Some(8)))),
+        table_args: Some(vec![Expression::create_literal(DataValue::UInt64(Some(8)))]),
         projected_schema: DataSchemaRefExt::create(vec![DataField::new(
             "number",
             DataType::UInt64,
@@ -66,3 +69,193 @@ async fn test_number_table() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_number_table_overloads() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let table = NumbersTable::create("numbers");
+
+    let tests: Vec<(Vec<u64>, Vec<u64>)> = vec![
+        (vec![5], vec![0, 1, 2, 3, 4]),
+        (vec![3, 7], vec![3, 4, 5, 6]),
+        (vec![2, 20, 5], vec![2, 7, 12, 17]),
+        (vec![2, 22, 5], vec![2, 7, 12, 17]),
+        (vec![0, 3, 1], vec![0, 1, 2]),
+        // The empty and the single number ranges.
+        (vec![0], vec![]),
+        (vec![4, 4], vec![]),
+        (vec![10, 3], vec![]),
+        (vec![10, 3, 2], vec![]),
+        (vec![1], vec![0]),
+        (vec![4, 5], vec![4]),
+        (vec![7, 8, 100], vec![7]),
+        (vec![u64::MAX - 1, u64::MAX], vec![u64::MAX - 1]),
+    ];
+    for (args, want) in tests {
+        let (plan, numbers) = read_numbers(&ctx, &table, &args).await?;
+        assert_eq!(want, numbers, "{:?}", args);
+        assert_eq!(want.len(), plan.statistics.read_rows, "{:?}", args);
+        assert!(plan.statistics.is_exact);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_number_table_bad_arguments() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let table = NumbersTable::create("numbers");
+
+    let tests: Vec<(Vec<DataValue>, ErrorCode)> = vec![
+        (vec![], ErrorCode::BadArguments("")),
+        (
+            vec![DataValue::UInt64(Some(1)); 4],
+            ErrorCode::BadArguments(""),
+        ),
+        (
+            vec![
+                DataValue::UInt64(Some(1)),
+                DataValue::UInt64(Some(5)),
+                DataValue::UInt64(Some(0)),
+            ],
+            ErrorCode::BadArguments(""),
+        ),
+        (vec![DataValue::Int8(Some(-1))], ErrorCode::BadArguments("")),
+        // The rows of the range overflow the bytes to read, the arguments overflow UInt64.
+        (
+            vec![DataValue::Int64(Some(i64::MAX))],
+            ErrorCode::Overflow(""),
+        ),
+        (
+            vec![DataValue::UInt64(Some(u64::MAX))],
+            ErrorCode::Overflow(""),
+        ),
+        (
+            vec![DataValue::Float64(Some(1e20))],
+            ErrorCode::Overflow(""),
+        ),
+    ];
+    for (args, want) in tests {
+        let res = table.read_plan(ctx.clone(), &scan_plan(args.clone()), 1);
+        assert_eq!(want.code(), res.unwrap_err().code(), "{:?}", args);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_number_table_block_size() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    ctx.get_settings().set_max_block_size(3)?;
+    let table = NumbersTable::create("numbers");
+
+    for args in [vec![10], vec![0, 20, 2], vec![5, 6], vec![0, 100, 7]] {
+        let plan = table.read_plan(ctx.clone(), &numbers_scan(&args), 1)?;
+        ctx.try_set_partitions(plan.parts.clone())?;
+        let blocks = table
+            .read(ctx.clone(), &plan)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert!(
+            blocks.iter().all(|block| block.num_rows() <= 3),
+            "{:?}",
+            args
+        );
+        let rows: usize = blocks.iter().map(|block| block.num_rows()).sum();
+        assert_eq!(plan.statistics.read_rows, rows, "{:?}", args);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_number_table_mt_coverage() -> Result<()> {
+    // The pipelines reading numbers_mt together have every number exactly once.
+
+    let table = NumbersTable::create("numbers_mt");
+    for threads in [1, 2, 3, 8] {
+        for args in [vec![10000], vec![3, 10003, 3], vec![2], vec![5, 5]] {
+            let ctx = crate::tests::try_create_context()?;
+            ctx.get_settings().set_max_threads(threads)?;
+            ctx.get_settings().set_max_block_size(1000)?;
+
+            let plan = table.read_plan(ctx.clone(), &numbers_scan(&args), threads as usize)?;
+            assert!(plan.parts.len() <= threads as usize);
+            ctx.try_set_partitions(plan.parts.clone())?;
+
+            let mut handles = vec![];
+            for _ in 0..threads {
+                let stream = table.read(ctx.clone(), &plan).await?;
+                handles.push(tokio::spawn(stream.try_collect::<Vec<_>>()));
+            }
+            let mut numbers = vec![];
+            for handle in handles {
+                numbers.extend(to_numbers(&handle.await.unwrap()?)?);
+            }
+            numbers.sort_unstable();
+
+            let want = expected_numbers(&args);
+            assert_eq!(want, numbers, "{} threads: {:?}", threads, args);
+            assert_eq!(want.len(), plan.statistics.read_rows);
+        }
+    }
+
+    Ok(())
+}
+
+fn scan_plan(args: Vec<DataValue>) -> ScanPlan {
+    ScanPlan {
+        table_args: Some(args.into_iter().map(Expression::create_literal).collect()),
+        projected_schema: DataSchemaRefExt::create(vec![DataField::new(
+            "number",
+            DataType::UInt64,
+            false,
+        )]),
+        ..ScanPlan::empty()
+    }
+}
+
+fn numbers_scan(args: &[u64]) -> ScanPlan {
+    scan_plan(
+        args.iter()
+            .map(|arg| DataValue::UInt64(Some(*arg)))
+            .collect(),
+    )
+}
+
+async fn read_numbers(
+    ctx: &DatabendQueryContextRef,
+    table: &NumbersTable,
+    args: &[u64],
+) -> Result<(ReadDataSourcePlan, Vec<u64>)> {
+    let plan = table.read_plan(ctx.clone(), &numbers_scan(args), 1)?;
+    ctx.try_set_partitions(plan.parts.clone())?;
+    let blocks = table
+        .read(ctx.clone(), &plan)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut numbers = to_numbers(&blocks)?;
+    numbers.sort_unstable();
+    Ok((plan, numbers))
+}
+
+fn to_numbers(blocks: &[DataBlock]) -> Result<Vec<u64>> {
+    let mut numbers = vec![];
+    for block in blocks {
+        for value in block.column(0).to_values()? {
+            numbers.push(value.as_u64()?);
+        }
+    }
+    Ok(numbers)
+}
+
+fn expected_numbers(args: &[u64]) -> Vec<u64> {
+    match args {
+        [end] => (0..*end).collect(),
+        [begin, end] => (*begin..*end).collect(),
+        [begin, end, step] => (*begin..*end).step_by(*step as usize).collect(),
+        _ => unreachable!(),
+    }
+}
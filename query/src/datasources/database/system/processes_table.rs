@@ -47,7 +47,15 @@ impl ProcessesTable {
                 DataField::new("host", DataType::String, true),
                 DataField::new("state", DataType::String, false),
                 DataField::new("database", DataType::String, false),
+                DataField::new("resource_group", DataType::String, false),
                 DataField::new("extra_info", DataType::String, true),
+                DataField::new("query_hash", DataType::String, true),
+                DataField::new("query_label", DataType::String, true),
+                DataField::new("result_buffer_bytes", DataType::UInt64, false),
+                DataField::new("query", DataType::String, true),
+                DataField::new("elapsed_ms", DataType::UInt64, true),
+                DataField::new("read_rows", DataType::UInt64, true),
+                DataField::new("read_bytes", DataType::UInt64, true),
             ]),
         }
     }
@@ -63,6 +71,12 @@ impl ProcessesTable {
             .clone()
             .map(|s| s.into_bytes())
     }
+
+    fn process_query_hash(process_info: &ProcessInfo) -> Option<Vec<u8>> {
+        process_info
+            .query_hash
+            .map(|hash| format!("{:016x}", hash).into_bytes())
+    }
 }
 
 #[async_trait::async_trait]
@@ -102,11 +116,14 @@ impl Table for ProcessesTable {
             parts: vec![Part {
                 name: "".to_string(),
                 version: 0,
+                read_info: None,
             }],
             statistics: Statistics::default(),
             description: "(Read from system.processes table)".to_string(),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
@@ -123,15 +140,36 @@ impl Table for ProcessesTable {
         let mut processes_host = Vec::with_capacity(processes_info.len());
         let mut processes_state = Vec::with_capacity(processes_info.len());
         let mut processes_database = Vec::with_capacity(processes_info.len());
+        let mut processes_resource_group = Vec::with_capacity(processes_info.len());
         let mut processes_extra_info = Vec::with_capacity(processes_info.len());
+        let mut processes_query_hash = Vec::with_capacity(processes_info.len());
+        let mut processes_query_label = Vec::with_capacity(processes_info.len());
+        let mut processes_result_buffer_bytes = Vec::with_capacity(processes_info.len());
+        let mut processes_query = Vec::with_capacity(processes_info.len());
+        let mut processes_elapsed_ms = Vec::with_capacity(processes_info.len());
+        let mut processes_read_rows = Vec::with_capacity(processes_info.len());
+        let mut processes_read_bytes = Vec::with_capacity(processes_info.len());
 
         for process_info in &processes_info {
             processes_id.push(process_info.id.clone().into_bytes());
             processes_type.push(process_info.typ.clone().into_bytes());
             processes_state.push(process_info.state.clone().into_bytes());
             processes_database.push(process_info.database.clone().into_bytes());
+            processes_resource_group.push(process_info.resource_group.clone().into_bytes());
             processes_host.push(ProcessesTable::process_host(process_info));
             processes_extra_info.push(ProcessesTable::process_extra_info(process_info));
+            processes_query_hash.push(ProcessesTable::process_query_hash(process_info));
+            processes_query_label.push(process_info.query_label.clone().map(|s| s.into_bytes()));
+            processes_result_buffer_bytes.push(process_info.result_buffer_bytes);
+            processes_query.push(process_info.query.clone().map(|s| s.into_bytes()));
+            processes_elapsed_ms.push(
+                process_info
+                    .query_elapsed
+                    .map(|elapsed| elapsed.as_millis() as u64),
+            );
+            let progress = process_info.query_progress.as_ref();
+            processes_read_rows.push(progress.map(|progress| progress.read_rows as u64));
+            processes_read_bytes.push(progress.map(|progress| progress.read_bytes as u64));
         }
 
         let schema = self.schema.clone();
@@ -141,7 +179,15 @@ impl Table for ProcessesTable {
             Series::new(processes_host),
             Series::new(processes_state),
             Series::new(processes_database),
+            Series::new(processes_resource_group),
             Series::new(processes_extra_info),
+            Series::new(processes_query_hash),
+            Series::new(processes_query_label),
+            Series::new(processes_result_buffer_bytes),
+            Series::new(processes_query),
+            Series::new(processes_elapsed_ms),
+            Series::new(processes_read_rows),
+            Series::new(processes_read_bytes),
         ]);
 
         Ok(Box::pin(DataBlockStream::create(schema, None, vec![block])))
@@ -0,0 +1,93 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datavalues::DataValue;
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+
+use crate::catalogs::Table;
+use crate::datasources::database::system::ProcessesTable;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_processes_table_query_label() -> Result<()> {
+    // Holds the session, so it stays in the processes.
+    let sessions = crate::tests::try_create_session_mgr(None)?;
+    let session = sessions.create_session("TestSession")?;
+    let ctx = session.create_context();
+    ctx.attach_query_str("SELECT /*+ label(team=billing) */ * FROM system.processes");
+
+    let table = ProcessesTable::create();
+    let source_plan = table.read_plan(
+        ctx.clone(),
+        &ScanPlan::empty(),
+        ctx.get_settings().get_max_threads()? as usize,
+    )?;
+
+    let stream = table.read(ctx.clone(), &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let block = &result[0];
+    assert_eq!(block.num_columns(), 14);
+    assert_eq!(block.num_rows(), 1);
+    assert_eq!(
+        block.first("query_label")?,
+        DataValue::String(Some("team=billing".as_bytes().to_vec()))
+    );
+    assert_eq!(
+        block.first("result_buffer_bytes")?,
+        DataValue::UInt64(Some(0))
+    );
+    assert_eq!(
+        block.first("query")?,
+        DataValue::String(Some(
+            "SELECT /*+ label(team=billing) */ * FROM system.processes"
+                .as_bytes()
+                .to_vec()
+        ))
+    );
+    assert!(!block.first("elapsed_ms")?.is_null());
+    assert_eq!(block.first("read_rows")?, DataValue::UInt64(Some(0)));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_processes_table_idle_session() -> Result<()> {
+    let sessions = crate::tests::try_create_session_mgr(None)?;
+    let idle_session = sessions.create_session("TestSession")?;
+    let session = sessions.create_session("TestSession")?;
+    let ctx = session.create_context();
+
+    let table = ProcessesTable::create();
+    let source_plan = table.read_plan(
+        ctx.clone(),
+        &ScanPlan::empty(),
+        ctx.get_settings().get_max_threads()? as usize,
+    )?;
+
+    let stream = table.read(ctx.clone(), &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let block = &result[0];
+    assert_eq!(block.num_rows(), 2);
+
+    // Neither session runs a query.
+    for name in ["query", "elapsed_ms", "read_rows", "read_bytes"] {
+        let column = block.try_column_by_name(name)?.to_array()?;
+        assert_eq!(column.null_count(), 2, "{}", name);
+    }
+
+    drop(idle_session);
+    Ok(())
+}
@@ -0,0 +1,157 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::sessions::DatabendQueryContextRef;
+
+/// The latest queries of the node served by the MySQL handler, from the oldest to the latest.
+pub struct QueryLogTable {
+    schema: DataSchemaRef,
+}
+
+impl QueryLogTable {
+    pub fn create() -> Self {
+        QueryLogTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("query_id", DataType::String, false),
+                DataField::new("session_id", DataType::String, false),
+                DataField::new("query", DataType::String, false),
+                DataField::new("status", DataType::String, false),
+                DataField::new("error_code", DataType::UInt16, true),
+                DataField::new("error", DataType::String, true),
+                DataField::new("start_time", DataType::DateTime32(None), false),
+                DataField::new("duration_ms", DataType::UInt64, false),
+                DataField::new("hints", DataType::String, false),
+                DataField::new("semantic_hash", DataType::UInt64, true),
+                DataField::new("query_label", DataType::String, true),
+                DataField::new("store_rpc_count", DataType::UInt64, false),
+                DataField::new("store_rpc_time_ms", DataType::UInt64, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for QueryLogTable {
+    fn name(&self) -> &str {
+        "query_log"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemQueryLog"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.query_log table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let entries = ctx.get_sessions_manager().get_query_log().entries();
+
+        let query_ids: Vec<&[u8]> = entries.iter().map(|x| x.query_id.as_bytes()).collect();
+        let session_ids: Vec<&[u8]> = entries.iter().map(|x| x.session_id.as_bytes()).collect();
+        let queries: Vec<&[u8]> = entries.iter().map(|x| x.query.as_bytes()).collect();
+        let statuses: Vec<&[u8]> = entries
+            .iter()
+            .map(|x| x.status.as_str().as_bytes())
+            .collect();
+        let error_codes: Vec<Option<u16>> = entries.iter().map(|x| x.error_code).collect();
+        let errors: Vec<Option<Vec<u8>>> = entries
+            .iter()
+            .map(|x| x.error_message.clone().map(|s| s.into_bytes()))
+            .collect();
+        let start_times: Vec<u32> = entries.iter().map(|x| x.start_time).collect();
+        let durations: Vec<u64> = entries.iter().map(|x| x.duration_ms).collect();
+        let hints: Vec<&[u8]> = entries.iter().map(|x| x.hints.as_bytes()).collect();
+        let semantic_hashes: Vec<Option<u64>> = entries.iter().map(|x| x.semantic_hash).collect();
+        let query_labels: Vec<Option<Vec<u8>>> = entries
+            .iter()
+            .map(|x| x.query_label.clone().map(|s| s.into_bytes()))
+            .collect();
+        let store_rpc_counts: Vec<u64> = entries.iter().map(|x| x.store_rpc_count).collect();
+        let store_rpc_times: Vec<u64> = entries.iter().map(|x| x.store_rpc_time_ms).collect();
+
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(query_ids),
+            Series::new(session_ids),
+            Series::new(queries),
+            Series::new(statuses),
+            Series::new(error_codes),
+            Series::new(errors),
+            Series::new(start_times),
+            Series::new(durations),
+            Series::new(hints),
+            Series::new(semantic_hashes),
+            Series::new(query_labels),
+            Series::new(store_rpc_counts),
+            Series::new(store_rpc_times),
+        ]);
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datavalues::DataValue;
+use common_exception::codes;
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+
+use crate::catalogs::Table;
+use crate::datasources::database::system::QueryLogTable;
+use crate::sessions::QueryLogEntry;
+use crate::sessions::QueryLogStatus;
+use crate::tests::try_create_session_mgr;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_query_log_table() -> Result<()> {
+    let sessions = try_create_session_mgr(Some(1))?;
+    let session = sessions.create_session("TestSession")?;
+
+    let query_log = sessions.get_query_log();
+    query_log.append(QueryLogEntry {
+        query_id: "query-1".to_string(),
+        session_id: session.get_id(),
+        query: "SELECT 1".to_string(),
+        status: QueryLogStatus::Finished,
+        error_code: None,
+        error_message: None,
+        start_time: 1,
+        duration_ms: 2,
+        hints: String::new(),
+        semantic_hash: Some(42),
+        query_label: Some("nightly".to_string()),
+        store_rpc_count: 3,
+        store_rpc_time_ms: 5,
+    });
+    query_log.append(QueryLogEntry {
+        query_id: "query-2".to_string(),
+        session_id: session.get_id(),
+        query: "SELECT crashme()".to_string(),
+        status: QueryLogStatus::Panicked,
+        error_code: Some(codes::PanicError),
+        error_message: Some("Panicked: crash me function".to_string()),
+        start_time: 3,
+        duration_ms: 4,
+        hints: "no_distributed, max_threads(4)".to_string(),
+        semantic_hash: None,
+        query_label: None,
+        store_rpc_count: 0,
+        store_rpc_time_ms: 0,
+    });
+
+    let ctx = session.create_context();
+    let table = QueryLogTable::create();
+    let source_plan = table.read_plan(ctx.clone(), &ScanPlan::empty(), 1)?;
+    let stream = table.read(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let block = &result[0];
+    assert_eq!(block.num_columns(), 13);
+    assert_eq!(block.num_rows(), 2);
+
+    let string = |s: &str| DataValue::String(Some(s.as_bytes().to_vec()));
+    let row = |index: usize| -> Result<Vec<DataValue>> {
+        (0..block.num_columns())
+            .map(|column| block.column(column).try_get(index))
+            .collect()
+    };
+    assert_eq!(row(0)?[2..6], [
+        string("SELECT 1"),
+        string("finished"),
+        DataValue::UInt16(None),
+        DataValue::String(None),
+    ]);
+    assert_eq!(row(1)?[2..6], [
+        string("SELECT crashme()"),
+        string("panicked"),
+        DataValue::UInt16(Some(codes::PanicError)),
+        string("Panicked: crash me function"),
+    ]);
+    assert_eq!(row(1)?[7], DataValue::UInt64(Some(4)));
+    assert_eq!(row(0)?[8], string(""));
+    assert_eq!(row(1)?[8], string("no_distributed, max_threads(4)"));
+    assert_eq!(row(0)?[9], DataValue::UInt64(Some(42)));
+    assert_eq!(row(1)?[9], DataValue::UInt64(None));
+    assert_eq!(row(0)?[10..13], [
+        string("nightly"),
+        DataValue::UInt64(Some(3)),
+        DataValue::UInt64(Some(5)),
+    ]);
+    assert_eq!(row(1)?[10..13], [
+        DataValue::String(None),
+        DataValue::UInt64(Some(0)),
+        DataValue::UInt64(Some(0)),
+    ]);
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::sessions::DatabendQueryContextRef;
+
+pub struct ResourceGroupsTable {
+    schema: DataSchemaRef,
+}
+
+impl ResourceGroupsTable {
+    pub fn create() -> Self {
+        ResourceGroupsTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("name", DataType::String, false),
+                DataField::new("weight", DataType::UInt64, false),
+                DataField::new("max_concurrency", DataType::UInt64, false),
+                DataField::new("running_queries", DataType::UInt64, false),
+                DataField::new("queued_queries", DataType::UInt64, false),
+                DataField::new("processed_rows", DataType::UInt64, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for ResourceGroupsTable {
+    fn name(&self) -> &str {
+        "resource_groups"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemResourceGroups"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.resource_groups table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let resource_groups = ctx.get_sessions_manager().get_resource_groups();
+        let usages = resource_groups
+            .get_groups()
+            .iter()
+            .map(|group| group.usage())
+            .collect::<Vec<_>>();
+
+        let names: Vec<&[u8]> = usages.iter().map(|x| x.name.as_bytes()).collect();
+        let weights: Vec<u64> = usages.iter().map(|x| x.weight).collect();
+        let max_concurrencies: Vec<u64> = usages.iter().map(|x| x.max_concurrency).collect();
+        let running: Vec<u64> = usages.iter().map(|x| x.running_queries as u64).collect();
+        let queued: Vec<u64> = usages.iter().map(|x| x.queued_queries as u64).collect();
+        let processed: Vec<u64> = usages.iter().map(|x| x.processed_rows as u64).collect();
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(names),
+            Series::new(weights),
+            Series::new(max_concurrencies),
+            Series::new(running),
+            Series::new(queued),
+            Series::new(processed),
+        ]);
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+
+use crate::catalogs::Table;
+use crate::configs::Config;
+use crate::datasources::database::system::ResourceGroupsTable;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_resource_groups_table() -> Result<()> {
+    let mut config = Config::default();
+    config.query.resource_groups = "etl:3:2,adhoc:1".to_string();
+
+    let ctx = crate::tests::try_create_context_with_conf(config)?;
+    let table = ResourceGroupsTable::create();
+    let source_plan = table.read_plan(
+        ctx.clone(),
+        &ScanPlan::empty(),
+        ctx.get_settings().get_max_threads()? as usize,
+    )?;
+
+    let stream = table.read(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let block = &result[0];
+    assert_eq!(block.num_columns(), 6);
+    assert_eq!(block.num_rows(), 3);
+
+    let expected = vec![
+        "+---------+--------+-----------------+-----------------+----------------+----------------+",
+        "| name    | weight | max_concurrency | running_queries | queued_queries | processed_rows |",
+        "+---------+--------+-----------------+-----------------+----------------+----------------+",
+        "| adhoc   | 1      | 0               | 0               | 0              | 0              |",
+        "| default | 1      | 0               | 0               | 0              | 0              |",
+        "| etl     | 3      | 2               | 0               | 0              | 0              |",
+        "+---------+--------+-----------------+-----------------+----------------+----------------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+
+    Ok(())
+}
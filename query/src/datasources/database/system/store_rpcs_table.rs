@@ -0,0 +1,117 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::sessions::DatabendQueryContextRef;
+
+/// The store calls of all the clients of this node since it started, by action type.
+pub struct StoreRpcsTable {
+    schema: DataSchemaRef,
+}
+
+impl StoreRpcsTable {
+    pub fn create() -> Self {
+        StoreRpcsTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("action", DataType::String, false),
+                DataField::new("count", DataType::UInt64, false),
+                DataField::new("total_ms", DataType::UInt64, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for StoreRpcsTable {
+    fn name(&self) -> &str {
+        "store_rpcs"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemStoreRpcs"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.store_rpcs table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let stats = ctx.get_sessions_manager().get_store_rpc_stats().snapshot();
+
+        let actions: Vec<&[u8]> = stats.keys().map(|x| x.as_bytes()).collect();
+        let counts: Vec<u64> = stats.values().map(|x| x.count).collect();
+        let total_ms: Vec<u64> = stats.values().map(|x| x.total.as_millis() as u64).collect();
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(actions),
+            Series::new(counts),
+            Series::new(total_ms),
+        ]);
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
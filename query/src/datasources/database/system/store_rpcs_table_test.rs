@@ -0,0 +1,54 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_datavalues::DataValue;
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::catalogs::Table;
+use crate::datasources::database::system::StoreRpcsTable;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_store_rpcs_table() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    // The calls of a query are counted into the ones of the node.
+    let stats = ctx.get_query_metrics().get_store_rpc_stats();
+    stats.record("GetTable", Duration::from_millis(3));
+    stats.record("GetTable", Duration::from_millis(4));
+
+    let table = StoreRpcsTable::create();
+    let source_plan = table.read_plan(ctx.clone(), &ScanPlan::empty(), 1)?;
+    let stream = table.read(ctx, &source_plan).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let block = &result[0];
+    assert_eq!(block.num_columns(), 3);
+    assert_eq!(block.num_rows(), 1);
+
+    let row = (0..block.num_columns())
+        .map(|column| block.column(column).try_get(0))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(row, vec![
+        DataValue::String(Some(b"GetTable".to_vec())),
+        DataValue::UInt64(Some(2)),
+        DataValue::UInt64(Some(7)),
+    ]);
+
+    Ok(())
+}
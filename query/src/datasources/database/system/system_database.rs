@@ -63,11 +63,21 @@ impl SystemDatabase {
             Arc::new(system::NumbersTable::create("numbers_mt")),
             Arc::new(system::NumbersTable::create("numbers_local")),
             Arc::new(system::TablesTable::create()),
+            Arc::new(system::TablesHistoryTable::create()),
             Arc::new(system::ClustersTable::create()),
+            Arc::new(system::FlightChannelsTable::create()),
+            Arc::new(system::StoreRpcsTable::create()),
             Arc::new(system::DatabasesTable::create()),
+            Arc::new(system::DatabaseUsagesTable::create()),
             Arc::new(system::TracingTable::create()),
             Arc::new(system::ProcessesTable::create()),
+            Arc::new(system::QueryLogTable::create()),
             Arc::new(system::ConfigsTable::create()),
+            Arc::new(system::ResourceGroupsTable::create()),
+            Arc::new(system::ErrorCodesTable::create()),
+            Arc::new(system::WarningsTable::create()),
+            Arc::new(system::KvTable::create("kv_list")),
+            Arc::new(system::KvTable::create("kv_get")),
         ];
         let tbl_meta_list = table_list
             .iter()
@@ -82,6 +92,8 @@ impl SystemDatabase {
             Arc::new(system::NumbersTable::create("numbers")),
             Arc::new(system::NumbersTable::create("numbers_mt")),
             Arc::new(system::NumbersTable::create("numbers_local")),
+            Arc::new(system::KvTable::create("kv_list")),
+            Arc::new(system::KvTable::create("kv_get")),
         ];
         let mut table_functions = HashMap::default();
         for tbl_func in table_function_list.iter() {
@@ -0,0 +1,136 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Catalog;
+use crate::catalogs::Table;
+use crate::sessions::DatabendQueryContextRef;
+
+/// The dropped tables which are kept in trash and can be undropped.
+pub struct TablesHistoryTable {
+    schema: DataSchemaRef,
+}
+
+impl TablesHistoryTable {
+    pub fn create() -> Self {
+        TablesHistoryTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("database", DataType::String, false),
+                DataField::new("name", DataType::String, false),
+                DataField::new("table_id", DataType::UInt64, false),
+                DataField::new("dropped_on", DataType::UInt64, false),
+                DataField::new("expire_in", DataType::UInt64, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for TablesHistoryTable {
+    fn name(&self) -> &str {
+        "tables_history"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemTablesHistory"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.tables_history table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let dropped_tables = ctx.get_catalog().get_dropped_tables()?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let databases: Vec<&[u8]> = dropped_tables.iter().map(|x| x.db.as_bytes()).collect();
+        let names: Vec<&[u8]> = dropped_tables.iter().map(|x| x.name.as_bytes()).collect();
+        let table_ids: Vec<u64> = dropped_tables.iter().map(|x| x.table_id).collect();
+        let dropped_ons: Vec<u64> = dropped_tables.iter().map(|x| x.dropped_on).collect();
+        let expire_ins: Vec<u64> = dropped_tables
+            .iter()
+            .map(|x| x.expire_at.saturating_sub(now))
+            .collect();
+
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(databases),
+            Series::new(names),
+            Series::new(table_ids),
+            Series::new(dropped_ons),
+            Series::new(expire_ins),
+        ]);
+
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
@@ -39,25 +39,35 @@ async fn test_tables_table() -> Result<()> {
     assert_eq!(block.num_columns(), 3);
 
     let expected = vec![
-        "+----------+---------------+--------------------+",
-        "| database | name          | engine             |",
-        "+----------+---------------+--------------------+",
-        "| system   | clusters      | SystemClusters     |",
-        "| system   | configs       | SystemConfigs      |",
-        "| system   | contributors  | SystemContributors |",
-        "| system   | credits       | SystemCredits      |",
-        "| system   | databases     | SystemDatabases    |",
-        "| system   | engines       | SystemEngines      |",
-        "| system   | functions     | SystemFunctions    |",
-        "| system   | numbers       | SystemNumbers      |",
-        "| system   | numbers_local | SystemNumbersLocal |",
-        "| system   | numbers_mt    | SystemNumbersMt    |",
-        "| system   | one           | SystemOne          |",
-        "| system   | processes     | SystemProcesses    |",
-        "| system   | settings      | SystemSettings     |",
-        "| system   | tables        | SystemTables       |",
-        "| system   | tracing       | SystemTracing      |",
-        "+----------+---------------+--------------------+",
+        "+----------+-----------------+----------------------+",
+        "| database | name            | engine               |",
+        "+----------+-----------------+----------------------+",
+        "| system   | clusters        | SystemClusters       |",
+        "| system   | configs         | SystemConfigs        |",
+        "| system   | contributors    | SystemContributors   |",
+        "| system   | credits         | SystemCredits        |",
+        "| system   | database_usages | SystemDatabaseUsages |",
+        "| system   | databases       | SystemDatabases      |",
+        "| system   | engines         | SystemEngines        |",
+        "| system   | error_codes     | SystemErrorCodes     |",
+        "| system   | flight_channels | SystemFlightChannels |",
+        "| system   | functions       | SystemFunctions      |",
+        "| system   | kv_get          | SystemKvGet          |",
+        "| system   | kv_list         | SystemKvList         |",
+        "| system   | numbers         | SystemNumbers        |",
+        "| system   | numbers_local   | SystemNumbersLocal   |",
+        "| system   | numbers_mt      | SystemNumbersMt      |",
+        "| system   | one             | SystemOne            |",
+        "| system   | processes       | SystemProcesses      |",
+        "| system   | query_log       | SystemQueryLog       |",
+        "| system   | resource_groups | SystemResourceGroups |",
+        "| system   | settings        | SystemSettings       |",
+        "| system   | store_rpcs      | SystemStoreRpcs      |",
+        "| system   | tables          | SystemTables         |",
+        "| system   | tables_history  | SystemTablesHistory  |",
+        "| system   | tracing         | SystemTracing        |",
+        "| system   | warnings        | SystemWarnings       |",
+        "+----------+-----------------+----------------------+",
     ];
     common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
 
@@ -0,0 +1,121 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::catalogs::Table;
+use crate::sessions::DatabendQueryContextRef;
+
+/// The warnings of the previous statement of the session, for `SHOW WARNINGS`.
+pub struct WarningsTable {
+    schema: DataSchemaRef,
+}
+
+impl WarningsTable {
+    pub fn create() -> Self {
+        WarningsTable {
+            schema: DataSchemaRefExt::create(vec![
+                DataField::new("level", DataType::String, false),
+                DataField::new("code", DataType::UInt16, false),
+                DataField::new("message", DataType::String, false),
+                DataField::new("count", DataType::UInt64, false),
+            ]),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Table for WarningsTable {
+    fn name(&self) -> &str {
+        "warnings"
+    }
+
+    fn engine(&self) -> &str {
+        "SystemWarnings"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Result<DataSchemaRef> {
+        Ok(self.schema.clone())
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn read_plan(
+        &self,
+        _ctx: DatabendQueryContextRef,
+        scan: &ScanPlan,
+        _partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        Ok(ReadDataSourcePlan {
+            db: "system".to_string(),
+            table: self.name().to_string(),
+            table_id: scan.table_id,
+            table_version: scan.table_version,
+            schema: self.schema.clone(),
+            parts: vec![Part {
+                name: "".to_string(),
+                version: 0,
+                read_info: None,
+            }],
+            statistics: Statistics::default(),
+            description: "(Read from system.warnings table)".to_string(),
+            scan_plan: Arc::new(scan.clone()),
+            remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
+        })
+    }
+
+    async fn read(
+        &self,
+        ctx: DatabendQueryContextRef,
+        _source_plan: &ReadDataSourcePlan,
+    ) -> Result<SendableDataBlockStream> {
+        let warnings = ctx.get_last_warnings();
+
+        let levels: Vec<&[u8]> = warnings.iter().map(|_| "Warning".as_bytes()).collect();
+        let codes: Vec<u16> = warnings.iter().map(|x| x.code).collect();
+        let messages: Vec<&[u8]> = warnings.iter().map(|x| x.message.as_bytes()).collect();
+        let counts: Vec<u64> = warnings.iter().map(|x| x.count).collect();
+
+        let block = DataBlock::create_by_array(self.schema.clone(), vec![
+            Series::new(levels),
+            Series::new(codes),
+            Series::new(messages),
+            Series::new(counts),
+        ]);
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            vec![block],
+        )))
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datablocks::DataBlock;
+use common_exception::codes;
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+
+use crate::catalogs::Table;
+use crate::datasources::database::system::WarningsTable;
+use crate::sessions::DatabendQueryContextRef;
+use crate::tests::try_create_session_mgr;
+
+async fn read_warnings(ctx: DatabendQueryContextRef) -> Result<Vec<DataBlock>> {
+    let table = WarningsTable::create();
+    let source_plan = table.read_plan(ctx.clone(), &ScanPlan::empty(), 1)?;
+    let stream = table.read(ctx, &source_plan).await?;
+    stream.try_collect::<Vec<_>>().await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_warnings_table() -> Result<()> {
+    let sessions = try_create_session_mgr(Some(1))?;
+    let session = sessions.create_session("TestSession")?;
+
+    {
+        let ctx = session.create_context();
+        ctx.push_warning(codes::UnImplement, "first");
+        ctx.push_warning(codes::BadArguments, "second");
+        ctx.push_warning(codes::UnImplement, "first");
+    }
+
+    let expected = vec![
+        "+---------+------+---------+-------+",
+        "| level   | code | message | count |",
+        "+---------+------+---------+-------+",
+        "| Warning | 2    | first   | 2     |",
+        "| Warning | 6    | second  | 1     |",
+        "+---------+------+---------+-------+",
+    ];
+
+    // Reading them keeps them, while the statement has its own warnings.
+    {
+        let ctx = session.create_context();
+        assert!(ctx.get_warnings().is_empty());
+        let result = read_warnings(ctx).await?;
+        common_datablocks::assert_blocks_eq(expected.clone(), result.as_slice());
+    }
+    {
+        let ctx = session.create_context();
+        let result = read_warnings(ctx).await?;
+        common_datablocks::assert_blocks_eq(expected, result.as_slice());
+    }
+
+    // Another statement replaces them.
+    drop(session.create_context());
+    {
+        let ctx = session.create_context();
+        let result = read_warnings(ctx).await?;
+        assert_eq!(result[0].num_rows(), 0);
+    }
+
+    Ok(())
+}
@@ -20,6 +20,7 @@ use std::sync::Arc;
 use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_planners::get_table_option;
 use common_planners::ReadDataSourcePlan;
 use common_planners::ScanPlan;
 use common_planners::Statistics;
@@ -27,9 +28,12 @@ use common_planners::TableOptions;
 use common_streams::SendableDataBlockStream;
 
 use crate::catalogs::Table;
+use crate::common::StoreApiProvider;
 use crate::datasources::common::count_lines;
 use crate::datasources::common::generate_parts;
 use crate::datasources::table::csv::csv_table_stream::CsvTableStream;
+use crate::datasources::table::remote::remote_table::RemoteTableFactory;
+use crate::datasources::table_engine::TableEngine;
 use crate::sessions::DatabendQueryContextRef;
 
 pub struct CsvTable {
@@ -47,8 +51,8 @@ impl CsvTable {
         schema: DataSchemaRef,
         options: TableOptions,
     ) -> Result<Box<dyn Table>> {
-        let has_header = options.get("has_header").is_some();
-        let file = match options.get("location") {
+        let has_header = get_table_option(&options, "has_header").is_some();
+        let file = match get_table_option(&options, "location") {
             None => {
                 return Result::Err(ErrorCode::BadOption(
                     "CSV Engine must contains file location options",
@@ -67,6 +71,26 @@ impl CsvTable {
     }
 }
 
+/// The CSV engine reads a file of the query node with the `location` option,
+/// or the files in a directory of the store with the `path` option, the store parses them.
+pub struct CsvTableEngine {}
+
+impl TableEngine for CsvTableEngine {
+    fn try_create(
+        &self,
+        db: String,
+        name: String,
+        schema: DataSchemaRef,
+        options: TableOptions,
+        store_provider: StoreApiProvider,
+    ) -> Result<Box<dyn Table>> {
+        if get_table_option(&options, "path").is_some() {
+            return RemoteTableFactory {}.try_create(db, name, schema, options, store_provider);
+        }
+        CsvTable::try_create(db, name, schema, options)
+    }
+}
+
 #[async_trait::async_trait]
 impl Table for CsvTable {
     fn name(&self) -> &str {
@@ -107,13 +131,15 @@ impl Table for CsvTable {
             schema: self.schema.clone(),
             parts: generate_parts(
                 start_line as u64,
-                ctx.get_settings().get_max_threads()?,
+                ctx.get_max_threads()?,
                 lines_count as u64,
             ),
             statistics: Statistics::default(),
             description: format!("(Read from CSV Engine table  {}.{})", self.db, self.name),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
@@ -30,6 +30,7 @@ use common_exception::Result;
 use common_infallible::Mutex;
 use common_planners::Part;
 use common_runtime::tokio::sync::mpsc::Sender;
+use common_runtime::Runtime;
 use futures::StreamExt;
 
 use crate::datasources::dal::DataAccessor;
@@ -97,14 +98,19 @@ pub(crate) async fn read_part(
         let pages = get_page_stream(col_meta, &mut reader, vec![], Arc::new(|_, _| true))
             .await
             .map_err(|e| ErrorCode::ParquetError(e.to_string()))?;
-        let pages = pages.map(|compressed_page| decompress(compressed_page?, &mut vec![]));
-        // QUOTE(from arrow2): deserialize the pages. This is CPU bounded and SHOULD be done in a dedicated thread pool (e.g. Rayon)
-        let array = page_stream_to_array(
-            pages,
-            &metadata.row_groups[0].columns()[idx],
-            fields[idx].data_type.clone(),
-        )
-        .await?;
+        let pages = pages
+            .map(|compressed_page| decompress(compressed_page?, &mut vec![]))
+            .collect::<Vec<_>>()
+            .await;
+
+        // Deserializing the pages is CPU bounded, do it in the compute pool.
+        let column_meta = metadata.row_groups[0].columns()[idx].clone();
+        let data_type = fields[idx].data_type.clone();
+        let array = Runtime::spawn_compute(async move {
+            page_stream_to_array(futures::stream::iter(pages), &column_meta, data_type).await
+        })
+        .await
+        .map_err(|e| ErrorCode::TokioError(e.to_string()))??;
         arrays.push(array.into());
     }
 
@@ -161,6 +161,8 @@ impl Table for FuseTable {
                 description: "".to_string(),
                 scan_plan: Arc::new(scan.clone()),
                 remote: true,
+                partitions_total: Some(snapshot.summary.block_count as usize),
+                partitions_pruned_by_bloom: 0,
             };
             Ok(plan)
         } else {
@@ -168,6 +170,12 @@ impl Table for FuseTable {
         }
     }
 
+    fn read_plan_without_parts(&self, scan: &ScanPlan) -> Result<Option<ReadDataSourcePlan>> {
+        let mut plan = self.empty_read_source_plan(scan)?;
+        plan.partitions_total = None;
+        Ok(Some(plan))
+    }
+
     async fn read(
         &self,
         ctx: DatabendQueryContextRef,
@@ -299,6 +307,8 @@ impl FuseTable {
             description: "".to_string(),
             scan_plan: Arc::new(scan.clone()),
             remote: true,
+            partitions_total: Some(0),
+            partitions_pruned_by_bloom: 0,
         })
     }
 
@@ -102,13 +102,15 @@ impl Table for MemoryTable {
             schema: self.schema.clone(),
             parts: generate_parts(
                 0,
-                ctx.get_settings().get_max_threads()?,
+                ctx.get_max_threads()?,
                 blocks.len() as u64,
             ),
             statistics: Statistics::new_exact(rows, bytes),
             description: format!("(Read from Memory Engine table  {}.{})", self.db, self.name),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
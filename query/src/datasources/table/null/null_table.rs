@@ -88,11 +88,14 @@ impl Table for NullTable {
             parts: vec![Part {
                 name: "".to_string(),
                 version: 0,
+                read_info: None,
             }],
             statistics: Statistics::new_exact(0, 0),
             description: format!("(Read from Null Engine table  {}.{})", self.db, self.name),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
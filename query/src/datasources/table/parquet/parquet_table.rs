@@ -22,12 +22,13 @@ use common_datablocks::DataBlock;
 use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_planners::get_table_option;
 use common_planners::Part;
 use common_planners::ReadDataSourcePlan;
 use common_planners::ScanPlan;
 use common_planners::Statistics;
 use common_planners::TableOptions;
-use common_runtime::tokio::task;
+use common_runtime::Runtime;
 use common_streams::ParquetStream;
 use common_streams::SendableDataBlockStream;
 use crossbeam::channel::bounded;
@@ -51,7 +52,7 @@ impl ParquetTable {
         schema: DataSchemaRef,
         options: TableOptions,
     ) -> Result<Box<dyn Table>> {
-        let file = options.get("location");
+        let file = get_table_option(&options, "location");
         return match file {
             Some(file) => {
                 let table = ParquetTable {
@@ -136,6 +137,7 @@ impl Table for ParquetTable {
             parts: vec![Part {
                 name: "".to_string(),
                 version: 0,
+                read_info: None,
             }],
             statistics: Statistics::default(),
             description: format!(
@@ -144,6 +146,8 @@ impl Table for ParquetTable {
             ),
             scan_plan: Arc::new(scan.clone()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         })
     }
 
@@ -159,7 +163,7 @@ impl Table for ParquetTable {
 
         let file = self.file.clone();
         let projection: Vec<usize> = (0..self.schema.fields().len()).collect();
-        task::spawn_blocking(move || {
+        Runtime::spawn_blocking_io(move || {
             if let Err(e) = read_file(&file, response_tx, &projection) {
                 println!("Parquet reader thread terminated due to error: {:?}", e);
             }
@@ -15,7 +15,7 @@
 
 use common_exception::Result;
 
-use crate::datasources::table::csv::csv_table::CsvTable;
+use crate::datasources::table::csv::csv_table::CsvTableEngine;
 use crate::datasources::table::fuse::FuseTable;
 use crate::datasources::table::memory::memory_table::MemoryTable;
 use crate::datasources::table::null::null_table::NullTable;
@@ -24,7 +24,8 @@ use crate::datasources::table::remote::remote_table::RemoteTableFactory;
 use crate::datasources::table_engine_registry::TableEngineRegistry;
 
 pub fn register_prelude_tbl_engines(registry: &TableEngineRegistry) -> Result<()> {
-    registry.register("CSV", std::sync::Arc::new(CsvTable::try_create))?;
+    registry.register("CSV", std::sync::Arc::new(CsvTableEngine {}))?;
+    registry.register("NDJSON", std::sync::Arc::new(RemoteTableFactory {}))?;
     registry.register("PARQUET", std::sync::Arc::new(ParquetTable::try_create))?;
     registry.register("NULL", std::sync::Arc::new(NullTable::try_create))?;
     registry.register("MEMORY", std::sync::Arc::new(MemoryTable::try_create))?;
@@ -13,5 +13,10 @@
 //  limitations under the License.
 //
 
+pub mod remote_part_reader;
+#[cfg(test)]
+mod remote_part_reader_test;
 pub mod remote_table;
 mod remote_table_do_read;
+#[cfg(test)]
+mod remote_table_test;
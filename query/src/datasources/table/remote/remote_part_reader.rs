@@ -0,0 +1,158 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
+
+use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::Part;
+use common_runtime::tokio::sync::mpsc;
+use common_runtime::tokio::sync::Semaphore;
+use common_runtime::tokio::task::JoinHandle;
+use common_streams::SendableDataBlockStream;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+
+use crate::sessions::DatabendQueryContextRef;
+use crate::sessions::QueryMetrics;
+
+/// The blocks of a part, read from the store.
+pub type PartFuture = BoxFuture<'static, Result<SendableDataBlockStream>>;
+
+/// Fetches a part from the store.
+pub type PartFetcher = Arc<dyn Fn(Part) -> PartFuture + Send + Sync>;
+
+/// Reads the parts of a scan with several fetches in flight.
+///
+/// A fetch holds one of the `permits` until the part is read, the permits are shared by all the
+/// scans of the query, so they bound the part reads of the query. The parts are read ahead in a
+/// task, up to `read_ahead` of them, so the blocks are ready when the pipeline asks for them.
+/// The task and the fetches in flight are aborted when the stream is dropped, e.g. the query is
+/// killed or its LIMIT is reached.
+pub struct RemotePartReader {
+    permits: Arc<Semaphore>,
+    read_ahead: usize,
+    ordered: bool,
+    metrics: Option<Arc<QueryMetrics>>,
+}
+
+impl RemotePartReader {
+    pub fn create(permits: Arc<Semaphore>, read_ahead: usize) -> Self {
+        RemotePartReader {
+            permits,
+            read_ahead: read_ahead.max(1),
+            ordered: false,
+            metrics: None,
+        }
+    }
+
+    /// Deliver the blocks in the order of the parts, instead of as soon as they are read.
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// Count the part reads and their fetch time into `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<QueryMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn read<I>(
+        self,
+        ctx: &DatabendQueryContextRef,
+        parts: I,
+        fetcher: PartFetcher,
+    ) -> Result<SendableDataBlockStream>
+    where
+        I: Iterator<Item = Part> + Send + 'static,
+    {
+        let permits = self.permits;
+        let metrics = self.metrics;
+        let fetches = futures::stream::iter(parts).map(move |part| {
+            let permits = permits.clone();
+            let metrics = metrics.clone();
+            let fetcher = fetcher.clone();
+            async move {
+                let _permit = permits.acquire_owned().await.map_err(|e| {
+                    ErrorCode::TokioError(format!("part read permits are closed: {}", e))
+                })?;
+
+                let start = Instant::now();
+                let blocks = fetcher(part).await?.try_collect::<Vec<_>>().await?;
+                if let Some(metrics) = metrics {
+                    metrics.incr_part_read(start.elapsed());
+                }
+                Ok::<_, ErrorCode>(blocks)
+            }
+        });
+
+        let mut fetched: BoxStream<'static, Result<Vec<DataBlock>>> = match self.ordered {
+            true => Box::pin(fetches.buffered(self.read_ahead)),
+            false => Box::pin(fetches.buffer_unordered(self.read_ahead)),
+        };
+
+        let (tx, rx) = mpsc::channel(self.read_ahead);
+        let handle = ctx.execute_task(async move {
+            while let Some(res) = fetched.next().await {
+                let res = match res {
+                    Ok(blocks) => tx.send(Ok(blocks)).await,
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        return;
+                    }
+                };
+                if res.is_err() {
+                    // The stream is dropped.
+                    return;
+                }
+            }
+        })?;
+
+        let stream = RemotePartStream { rx, handle };
+        let blocks = stream
+            .map_ok(|blocks| futures::stream::iter(blocks.into_iter().map(Ok::<_, ErrorCode>)))
+            .try_flatten();
+        Ok(Box::pin(blocks))
+    }
+}
+
+/// The parts read by the task, the task is aborted if the stream is dropped before it finishes.
+struct RemotePartStream {
+    rx: mpsc::Receiver<Result<Vec<DataBlock>>>,
+    handle: JoinHandle<()>,
+}
+
+impl Stream for RemotePartStream {
+    type Item = Result<Vec<DataBlock>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for RemotePartStream {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
@@ -0,0 +1,237 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::Part;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::Semaphore;
+use common_streams::SendableDataBlockStream;
+use common_streams::TakeStream;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::datasources::table::remote::remote_part_reader::PartFetcher;
+use crate::datasources::table::remote::remote_part_reader::PartFuture;
+use crate::datasources::table::remote::remote_part_reader::RemotePartReader;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_remote_part_reader_concurrency() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    let mut results = vec![];
+    let mut elapsed = vec![];
+    for concurrency in [1, 8] {
+        let counters = Arc::new(FetchCounters::default());
+        let reader = RemotePartReader::create(Arc::new(Semaphore::new(concurrency)), concurrency)
+            .with_metrics(ctx.get_query_metrics());
+
+        let start = Instant::now();
+        let fetcher = fetcher(counters.clone(), |_| Duration::from_millis(20));
+        let stream = reader.read(&ctx, parts(40), fetcher)?;
+        results.push(sorted(read_values(stream).await?));
+        elapsed.push(start.elapsed());
+
+        assert_eq!(concurrency, counters.max_in_flight.load(Ordering::SeqCst));
+        assert_eq!(40, counters.finished.load(Ordering::SeqCst));
+    }
+
+    assert_eq!((0..40).collect::<Vec<i64>>(), results[0]);
+    assert_eq!(results[0], results[1]);
+    assert!(
+        elapsed[1] * 2 < elapsed[0],
+        "8 concurrent reads take {:?}, 1 takes {:?}",
+        elapsed[1],
+        elapsed[0]
+    );
+
+    let metrics = ctx.get_query_metrics().get_values();
+    assert_eq!(80, metrics.part_reads);
+    assert!(metrics.part_read_max_time >= Duration::from_millis(20));
+    assert!(metrics.part_read_time >= Duration::from_millis(80 * 20));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_remote_part_reader_shared_permits() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    ctx.get_settings().set_max_concurrent_part_reads(2)?;
+
+    // The permits are shared by the scans of the query.
+    let permits = ctx.get_part_read_permits()?;
+    assert!(Arc::ptr_eq(&permits, &ctx.get_part_read_permits()?));
+    assert_eq!(2, permits.available_permits());
+
+    let counters = Arc::new(FetchCounters::default());
+    let mut streams = vec![];
+    for _ in 0..2 {
+        let reader = RemotePartReader::create(ctx.get_part_read_permits()?, 8);
+        let fetcher = fetcher(counters.clone(), |_| Duration::from_millis(10));
+        streams.push(reader.read(&ctx, parts(10), fetcher)?);
+    }
+
+    let mut values = vec![];
+    for stream in streams {
+        values.extend(read_values(stream).await?);
+    }
+    assert_eq!(20, values.len());
+    assert_eq!(2, counters.max_in_flight.load(Ordering::SeqCst));
+    assert_eq!(2, permits.available_permits());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_remote_part_reader_ordered() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    // The later parts are read first.
+    let delay = |i: usize| Duration::from_millis(10 * (10 - i as u64));
+
+    let reader = RemotePartReader::create(Arc::new(Semaphore::new(10)), 10).with_ordered(true);
+    let fetcher = fetcher(Arc::new(FetchCounters::default()), delay);
+    let values = read_values(reader.read(&ctx, parts(10), fetcher)?).await?;
+    assert_eq!((0..10).collect::<Vec<i64>>(), values);
+
+    let reader = RemotePartReader::create(Arc::new(Semaphore::new(10)), 10);
+    let fetcher = fetcher(Arc::new(FetchCounters::default()), delay);
+    let values = read_values(reader.read(&ctx, parts(10), fetcher)?).await?;
+    assert_eq!((0..10).collect::<Vec<i64>>(), sorted(values));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_remote_part_reader_cancel_on_limit() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    // Only the first part is read in time.
+    let counters = Arc::new(FetchCounters::default());
+    let delay = |i: usize| match i {
+        0 => Duration::from_millis(0),
+        _ => Duration::from_secs(60),
+    };
+
+    let reader = RemotePartReader::create(Arc::new(Semaphore::new(4)), 4);
+    let fetcher = fetcher(counters.clone(), delay);
+    let stream = reader.read(&ctx, parts(100), fetcher)?;
+
+    let start = Instant::now();
+    let stream = TakeStream::new(stream, 1);
+    let values = read_values(Box::pin(stream)).await?;
+    assert_eq!(vec![0], values);
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    // The fetches in flight are dropped along with the stream.
+    while counters.in_flight.load(Ordering::SeqCst) > 0 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let started = counters.started.load(Ordering::SeqCst);
+    assert!(started <= 5);
+    assert_eq!(1, counters.finished.load(Ordering::SeqCst));
+    assert_eq!(started - 1, counters.cancelled.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct FetchCounters {
+    started: AtomicUsize,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    finished: AtomicUsize,
+    cancelled: AtomicUsize,
+}
+
+/// Counts a fetch as cancelled if it is dropped before it is finished.
+struct FetchGuard {
+    counters: Arc<FetchCounters>,
+    finished: bool,
+}
+
+impl FetchGuard {
+    fn start(counters: Arc<FetchCounters>) -> Self {
+        counters.started.fetch_add(1, Ordering::SeqCst);
+        let in_flight = counters.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        counters
+            .max_in_flight
+            .fetch_max(in_flight, Ordering::SeqCst);
+        FetchGuard {
+            counters,
+            finished: false,
+        }
+    }
+}
+
+impl Drop for FetchGuard {
+    fn drop(&mut self) {
+        self.counters.in_flight.fetch_sub(1, Ordering::SeqCst);
+        match self.finished {
+            true => self.counters.finished.fetch_add(1, Ordering::SeqCst),
+            false => self.counters.cancelled.fetch_add(1, Ordering::SeqCst),
+        };
+    }
+}
+
+/// Reads the part `i` as a block of the value `i`, after `delay(i)`.
+fn fetcher(counters: Arc<FetchCounters>, delay: fn(usize) -> Duration) -> PartFetcher {
+    Arc::new(move |part: Part| -> PartFuture {
+        let counters = counters.clone();
+        Box::pin(async move {
+            let i = part.name.parse::<usize>().unwrap();
+            let mut guard = FetchGuard::start(counters);
+            tokio::time::sleep(delay(i)).await;
+            guard.finished = true;
+
+            let schema =
+                DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+            let block = DataBlock::create_by_array(schema, vec![Series::new(vec![i as i64])]);
+            let stream: SendableDataBlockStream = Box::pin(futures::stream::iter(vec![Ok(block)]));
+            Ok(stream)
+        })
+    })
+}
+
+fn parts(n: usize) -> impl Iterator<Item = Part> + Send + 'static {
+    (0..n).map(|i| Part {
+        name: i.to_string(),
+        version: 0,
+        read_info: None,
+    })
+}
+
+async fn read_values(stream: SendableDataBlockStream) -> Result<Vec<i64>> {
+    let blocks = stream.try_collect::<Vec<_>>().await?;
+    let mut values = vec![];
+    for block in blocks {
+        for i in 0..block.num_rows() {
+            values.push(block.column(0).try_get(i)?.as_i64()?);
+        }
+    }
+    Ok(values)
+}
+
+fn sorted(mut values: Vec<i64>) -> Vec<i64> {
+    values.sort_unstable();
+    values
+}
@@ -21,14 +21,18 @@ use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::InsertIntoPlan;
+use common_planners::OptimizeTablePlan;
 use common_planners::Part;
 use common_planners::ReadDataSourcePlan;
 use common_planners::ScanPlan;
 use common_planners::Statistics;
 use common_planners::TableOptions;
 use common_planners::TruncateTablePlan;
-use common_store_api::ReadPlanResult;
+use common_store_api::OptimizeTableResult;
+use common_store_api::ReadPlanReply;
 use common_streams::SendableDataBlockStream;
+use common_tracing::tracing;
+use common_tracing::tracing::Instrument;
 
 use crate::catalogs::Table;
 use crate::common::StoreApiProvider;
@@ -73,32 +77,40 @@ impl Table for RemoteTable {
     ) -> Result<ReadDataSourcePlan> {
         // Change this method to async at current stage might be harsh
         let (tx, rx) = channel();
-        let cli_provider = self.store_api_provider.clone();
+        let cli_provider = self.query_store_api_provider(&ctx)?;
         let db_name = self.db.clone();
         let tbl_name = self.name.clone();
         {
             let scan = scan.clone();
-            ctx.execute_task(async move {
+            let task = async move {
                 match cli_provider.try_get_storage_client().await {
                     Ok(client) => {
                         let parts_info = client
-                            .read_plan(db_name, tbl_name, &scan)
-                            .await
-                            .map_err(ErrorCode::from);
+                            .read_plan_with_pruning(db_name, tbl_name, &scan)
+                            .await;
                         let _ = tx.send(parts_info);
                     }
                     Err(e) => {
                         let _ = tx.send(Err(e));
                     }
                 }
-            })?;
+            };
+            // keep the rpc spans under the span of the query
+            ctx.execute_task(task.instrument(tracing::Span::current()))?;
         }
 
         rx.recv()
             .map_err(ErrorCode::from_std_error)?
+            .map_err(|e| ctx.map_query_timeout(e))
             .map(|v| self.partitions_to_plan(v, scan.clone()))
     }
 
+    fn read_plan_without_parts(&self, scan: &ScanPlan) -> Result<Option<ReadDataSourcePlan>> {
+        let mut plan = self.partitions_to_plan(ReadPlanReply::default(), scan.clone());
+        plan.partitions_total = None;
+        Ok(Some(plan))
+    }
+
     async fn read(
         &self,
         ctx: DatabendQueryContextRef,
@@ -107,7 +119,7 @@ impl Table for RemoteTable {
         self.do_read(ctx, source_plan).await
     }
 
-    async fn append_data(&self, _ctx: DatabendQueryContextRef, plan: InsertIntoPlan) -> Result<()> {
+    async fn append_data(&self, ctx: DatabendQueryContextRef, plan: InsertIntoPlan) -> Result<()> {
         let opt_stream = {
             let mut inner = plan.input_stream.lock();
             (*inner).take()
@@ -117,7 +129,10 @@ impl Table for RemoteTable {
             let block_stream =
                 opt_stream.ok_or_else(|| ErrorCode::EmptyData("input stream consumed"))?;
 
-            let client = self.store_api_provider.try_get_storage_client().await?;
+            let client = self
+                .query_store_api_provider(&ctx)?
+                .try_get_storage_client()
+                .await?;
 
             client
                 .append_data(
@@ -126,17 +141,39 @@ impl Table for RemoteTable {
                     (&plan).schema().clone(),
                     block_stream,
                 )
-                .await?;
+                .await
+                .map_err(|e| ctx.map_query_timeout(e))?;
         }
 
         Ok(())
     }
 
-    async fn truncate(&self, _ctx: DatabendQueryContextRef, plan: TruncateTablePlan) -> Result<()> {
-        let client = self.store_api_provider.try_get_storage_client().await?;
-        client.truncate(plan.db.clone(), plan.table.clone()).await?;
+    async fn truncate(&self, ctx: DatabendQueryContextRef, plan: TruncateTablePlan) -> Result<()> {
+        let client = self
+            .query_store_api_provider(&ctx)?
+            .try_get_storage_client()
+            .await?;
+        client
+            .truncate(plan.db.clone(), plan.table.clone())
+            .await
+            .map_err(|e| ctx.map_query_timeout(e))?;
         Ok(())
     }
+
+    async fn optimize(
+        &self,
+        ctx: DatabendQueryContextRef,
+        plan: OptimizeTablePlan,
+    ) -> Result<OptimizeTableResult> {
+        let client = self
+            .query_store_api_provider(&ctx)?
+            .try_get_storage_client()
+            .await?;
+        client
+            .optimize_table(plan.db.clone(), plan.table.clone())
+            .await
+            .map_err(|e| ctx.map_query_timeout(e))
+    }
 }
 
 impl RemoteTable {
@@ -156,7 +193,22 @@ impl RemoteTable {
         Box::new(table)
     }
 
-    fn partitions_to_plan(&self, res: ReadPlanResult, scan_plan: ScanPlan) -> ReadDataSourcePlan {
+    /// The clients count their calls into the metrics of the query, send its label along,
+    /// and give up the calls at its deadline.
+    pub(in crate::datasources) fn query_store_api_provider(
+        &self,
+        ctx: &DatabendQueryContextRef,
+    ) -> Result<StoreApiProvider> {
+        let stats = ctx.get_query_metrics().get_store_rpc_stats();
+        Ok(self
+            .store_api_provider
+            .clone()
+            .with_rpc_stats(stats)
+            .with_query_label(ctx.get_query_label())
+            .with_deadline(ctx.get_query_deadline()?))
+    }
+
+    fn partitions_to_plan(&self, res: ReadPlanReply, scan_plan: ScanPlan) -> ReadDataSourcePlan {
         let mut partitions = vec![];
         let mut statistics = Statistics {
             read_rows: 0,
@@ -164,9 +216,10 @@ impl RemoteTable {
             is_exact: false,
         };
 
-        if let Some(parts) = res {
+        if let Some(parts) = res.parts {
             for part in parts {
                 partitions.push(Part {
+                    read_info: Some(part.read_info()),
                     name: part.part.name,
                     version: 0,
                 });
@@ -176,6 +229,9 @@ impl RemoteTable {
             }
         }
 
+        // The store prunes the parts by their bloom filters only.
+        let partitions_total = Some(res.pruning.total);
+        let partitions_pruned_by_bloom = res.pruning.pruned_by_bloom;
         ReadDataSourcePlan {
             db: self.db.clone(),
             table: self.name.clone(),
@@ -187,6 +243,8 @@ impl RemoteTable {
             description: "".to_string(),
             scan_plan: Arc::new(scan_plan),
             remote: true,
+            partitions_total,
+            partitions_pruned_by_bloom,
         }
     }
 }
@@ -13,6 +13,8 @@
 //  limitations under the License.
 //
 
+use std::sync::Arc;
+
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::PlanNode;
@@ -20,8 +22,11 @@ use common_planners::ReadDataSourcePlan;
 use common_store_api::ReadAction;
 use common_streams::ProgressStream;
 use common_streams::SendableDataBlockStream;
-use futures::StreamExt;
+use futures::TryStreamExt;
 
+use crate::datasources::table::remote::remote_part_reader::PartFetcher;
+use crate::datasources::table::remote::remote_part_reader::PartFuture;
+use crate::datasources::table::remote::remote_part_reader::RemotePartReader;
 use crate::datasources::table::remote::remote_table::RemoteTable;
 use crate::sessions::DatabendQueryContextRef;
 
@@ -32,41 +37,57 @@ impl RemoteTable {
         ctx: DatabendQueryContextRef,
         source_plan: &ReadDataSourcePlan,
     ) -> Result<SendableDataBlockStream> {
-        let client = self.store_api_provider.try_get_storage_client().await?;
+        let client = self
+            .query_store_api_provider(&ctx)?
+            .try_get_storage_client()
+            .await?;
         let progress_callback = ctx.progress_callback();
 
-        let plan = source_plan.clone();
-        let iter = std::iter::from_fn(move || match ctx.try_get_partitions(1) {
+        let parts_ctx = ctx.clone();
+        let parts = std::iter::from_fn(move || match parts_ctx.try_get_partitions(1) {
             Err(_) => None,
             Ok(parts) if parts.is_empty() => None,
-            Ok(parts) => {
-                let plan = plan.clone();
-                Some(ReadAction {
-                    part: parts[0].clone(),
-                    push_down: PlanNode::ReadSource(plan),
-                })
-            }
+            Ok(mut parts) => parts.pop(),
         });
 
         let schema = self.schema.clone();
-        let parts = futures::stream::iter(iter);
-        let streams = parts.then(move |parts| {
+        let plan = source_plan.clone();
+        let fetcher_ctx = ctx.clone();
+        let fetcher: PartFetcher = Arc::new(move |part| -> PartFuture {
             let client = client.clone();
             let schema = schema.clone();
-            async move {
-                let r = client.read_partition(schema, &parts).await;
-                r.unwrap_or_else(|e| {
-                    Box::pin(futures::stream::once(async move {
-                        Err(ErrorCode::CannotReadFile(format!(
+            let ctx = fetcher_ctx.clone();
+            let action = ReadAction {
+                part,
+                push_down: PlanNode::ReadSource(plan.clone()),
+            };
+            Box::pin(async move {
+                let blocks = match client.read_partition(schema, &action).await {
+                    Ok(blocks) => blocks,
+                    Err(e) if e.code() == ErrorCode::DeadlineExceeded("").code() => {
+                        return Err(ctx.map_query_timeout(e));
+                    }
+                    Err(e) => {
+                        return Err(ErrorCode::CannotReadFile(format!(
                             "get partition failure. partition [{:?}], error {}",
-                            &parts, e
-                        )))
-                    }))
-                })
-            }
+                            &action, e
+                        )));
+                    }
+                };
+                // The store gives up the part at the deadline of the query.
+                let blocks = blocks.map_err(move |e| ctx.map_query_timeout(e));
+                Ok(Box::pin(blocks) as SendableDataBlockStream)
+            })
         });
 
-        let stream = ProgressStream::try_create(Box::pin(streams.flatten()), progress_callback?)?;
+        let settings = ctx.get_settings();
+        let read_ahead = settings.get_max_concurrent_part_reads()? as usize;
+        let reader = RemotePartReader::create(ctx.get_part_read_permits()?, read_ahead)
+            .with_ordered(source_plan.scan_plan.push_downs.ordered_parts)
+            .with_metrics(ctx.get_query_metrics());
+        let blocks = reader.read(&ctx, parts, fetcher)?;
+
+        let stream = ProgressStream::try_create(blocks, progress_callback?)?;
         Ok(Box::pin(stream))
     }
 }
@@ -0,0 +1,64 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::HashMap;
+
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataType;
+use common_exception::Result;
+use common_planners::PlanNode;
+use common_planners::ScanPlan;
+use pretty_assertions::assert_eq;
+
+use crate::catalogs::Table;
+use crate::common::StoreApiProvider;
+use crate::configs::Config;
+use crate::datasources::table::remote::remote_table::RemoteTable;
+
+#[test]
+fn test_remote_table_read_plan_without_parts() -> Result<()> {
+    let mut conf = Config::default();
+    // nothing listens on it
+    conf.store.store_address = "127.0.0.1:1".to_string();
+
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let table = RemoteTable::create(
+        "db1",
+        "t1",
+        schema,
+        StoreApiProvider::new(&conf),
+        HashMap::new(),
+    );
+
+    let mut scan = ScanPlan::empty();
+    scan.schema_name = "db1".to_string();
+    let plan = table.read_plan_without_parts(&scan)?.unwrap();
+    assert!(plan.remote);
+    assert!(plan.parts.is_empty());
+    assert_eq!(plan.partitions_total, None);
+
+    let explain = format!("{:?}", PlanNode::ReadSource(plan));
+    let lines = explain.lines().collect::<Vec<_>>();
+    assert_eq!(lines[1..7].to_vec(), vec![
+        "  partitions_total: unknown",
+        "  partitions_scanned: unknown",
+        "  partitions_pruned_by_range: unknown",
+        "  partitions_pruned_by_bloom: unknown",
+        "  estimated_rows: unknown",
+        "  estimated_bytes: unknown",
+    ]);
+
+    Ok(())
+}
@@ -48,4 +48,12 @@ impl ContextFunction {
             _ => vec![],
         })
     }
+
+    // The values of the args built from the context, they are planned as literals.
+    pub fn context_values(ctx: &DatabendQueryContextRef) -> Vec<DataValue> {
+        vec![
+            DataValue::String(Some(ctx.get_current_database().into_bytes())),
+            DataValue::String(Some(ctx.get_fuse_version().into_bytes())),
+        ]
+    }
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_datavalues::DataValue;
 use common_exception::Result;
 use common_planners::*;
 use common_runtime::tokio;
@@ -19,7 +20,11 @@ use futures::TryStreamExt;
 use pretty_assertions::assert_eq;
 
 use crate::interpreters::*;
+use crate::sessions::DatabendQueryContextRef;
 use crate::sql::*;
+use crate::tests::try_create_cluster_context;
+use crate::tests::try_create_session_mgr;
+use crate::tests::ClusterNode;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_explain_interpreter() -> Result<()> {
@@ -44,7 +49,7 @@ async fn test_explain_interpreter() -> Result<()> {
             "| Projection: number:UInt64                                                                                             |",
             "|   Having: ((number + 1) = 4)                                                                                          |",
             "|     Filter: ((number + 1) = 4)                                                                                        |",
-            "|       ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80] |",
+            "|       ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80] |",
             "+-----------------------------------------------------------------------------------------------------------------------+",
         ];
         common_datablocks::assert_blocks_eq(expected, result.as_slice());
@@ -54,3 +59,104 @@ async fn test_explain_interpreter() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_explain_interpreter_without_read_plan() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    ctx.get_settings().set_explain_read_plan(0)?;
+
+    // The local tables have no parts to ask the store for, they are planned as usual.
+    if let PlanNode::Explain(plan) = PlanParser::create(ctx.clone())
+        .build_from_sql("explain select number from numbers_mt(10)")?
+    {
+        let executor = ExplainInterpreter::try_create(ctx, plan)?;
+        let stream = executor.execute().await?;
+        let result = stream.try_collect::<Vec<_>>().await?;
+        let expected = vec![
+            "+-----------------------------------------------------------------------------------------------------------------------+",
+            "| explain                                                                                                               |",
+            "+-----------------------------------------------------------------------------------------------------------------------+",
+            "| Projection: number:UInt64                                                                                             |",
+            "|   ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80] |",
+            "+-----------------------------------------------------------------------------------------------------------------------+",
+        ];
+        common_datablocks::assert_blocks_eq(expected, result.as_slice());
+    } else {
+        assert!(false)
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_explain_interpreter_with_query_hints() -> Result<()> {
+    let nodes = [ClusterNode::create("Github", 1, "www.github.com:9090")];
+    let has_stage = |lines: &[String]| lines.iter().any(|line| line.contains("RedistributeStage"));
+
+    // A large cluster query is distributed, unless it is kept on this node by the hint.
+    let query = "explain select sum(number) from numbers(100000000)";
+    let lines = explain(&try_create_cluster_context(&nodes)?, query).await?;
+    assert!(has_stage(&lines), "{:#?}", lines);
+
+    let query = "/*+ no_distributed */ explain select sum(number) from numbers(100000000)";
+    let lines = explain(&try_create_cluster_context(&nodes)?, query).await?;
+    assert!(!has_stage(&lines), "{:#?}", lines);
+
+    // The numbers out of the range of the predicate pushed down are not read.
+    let read_source = |lines: &[String]| lines.last().unwrap().trim().to_string();
+    let ctx = crate::tests::try_create_context()?;
+    let query = "explain select number from numbers(10) where number > 1";
+    assert_eq!(
+        "ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 8, read_bytes: 64]",
+        read_source(&explain(&ctx, query).await?)
+    );
+
+    let ctx = crate::tests::try_create_context()?;
+    let query = "/*+ no_filter_pushdown */ explain select number from numbers(10) where number > 1";
+    assert_eq!(
+        "ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
+        read_source(&explain(&ctx, query).await?)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_explain_interpreter_with_max_threads_hint() -> Result<()> {
+    let sessions = try_create_session_mgr(None)?;
+    let session = sessions.create_session("TestSession")?;
+    session.get_settings().set_max_threads(8)?;
+
+    let ctx = session.create_context();
+    let query = "/*+ max_threads(2) */ explain select number from numbers(10)";
+    let lines = explain(&ctx, query).await?;
+    assert!(lines[1].contains("scan partitions: [2]"), "{:#?}", lines);
+    assert_eq!(2, ctx.get_max_threads()?);
+
+    // The hint is only for its statement, the setting of the session is kept.
+    assert_eq!(8, session.get_settings().get_max_threads()?);
+    let ctx = session.create_context();
+    let lines = explain(&ctx, "explain select number from numbers(10)").await?;
+    assert!(lines[1].contains("scan partitions: [8]"), "{:#?}", lines);
+    assert_eq!(8, ctx.get_max_threads()?);
+
+    Ok(())
+}
+
+/// Explains the query in the context, with the hints of the query attached, one line per row.
+async fn explain(ctx: &DatabendQueryContextRef, query: &str) -> Result<Vec<String>> {
+    ctx.attach_query_str(query);
+    let plan = PlanParser::create(ctx.clone()).build_from_sql(query)?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    let blocks = executor.execute().await?.try_collect::<Vec<_>>().await?;
+
+    let mut lines = vec![];
+    for block in blocks {
+        for row in 0..block.num_rows() {
+            if let DataValue::String(Some(line)) = block.column(0).try_get(row)? {
+                lines.push(String::from_utf8_lossy(&line).to_string());
+            }
+        }
+    }
+    Ok(lines)
+}
@@ -27,10 +27,14 @@ use crate::interpreters::DropTableInterpreter;
 use crate::interpreters::ExplainInterpreter;
 use crate::interpreters::InsertIntoInterpreter;
 use crate::interpreters::Interpreter;
+use crate::interpreters::ModifyColumnInterpreter;
+use crate::interpreters::OptimizeTableInterpreter;
 use crate::interpreters::SelectInterpreter;
 use crate::interpreters::SettingInterpreter;
 use crate::interpreters::ShowCreateTableInterpreter;
+use crate::interpreters::TransactionInterpreter;
 use crate::interpreters::TruncateTableInterpreter;
+use crate::interpreters::UndropTableInterpreter;
 use crate::interpreters::UseDatabaseInterpreter;
 use crate::sessions::DatabendQueryContextRef;
 
@@ -45,13 +49,17 @@ impl InterpreterFactory {
             PlanNode::DropDatabase(v) => DropDatabaseInterpreter::try_create(ctx, v),
             PlanNode::CreateTable(v) => CreateTableInterpreter::try_create(ctx, v),
             PlanNode::DropTable(v) => DropTableInterpreter::try_create(ctx, v),
+            PlanNode::UndropTable(v) => UndropTableInterpreter::try_create(ctx, v),
             PlanNode::DescribeTable(v) => DescribeTableInterpreter::try_create(ctx, v),
             PlanNode::TruncateTable(v) => TruncateTableInterpreter::try_create(ctx, v),
+            PlanNode::ModifyColumn(v) => ModifyColumnInterpreter::try_create(ctx, v),
+            PlanNode::OptimizeTable(v) => OptimizeTableInterpreter::try_create(ctx, v),
             PlanNode::UseDatabase(v) => UseDatabaseInterpreter::try_create(ctx, v),
             PlanNode::SetVariable(v) => SettingInterpreter::try_create(ctx, v),
             PlanNode::InsertInto(v) => InsertIntoInterpreter::try_create(ctx, v),
             PlanNode::ShowCreateTable(v) => ShowCreateTableInterpreter::try_create(ctx, v),
             PlanNode::Kill(v) => KillInterpreter::try_create(ctx, v),
+            PlanNode::Transaction(v) => TransactionInterpreter::try_create(ctx, v),
             _ => Result::Err(ErrorCode::UnknownTypeOfQuery(format!(
                 "Can't get the interpreter by plan:{}",
                 plan.name()
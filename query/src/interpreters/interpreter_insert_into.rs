@@ -18,6 +18,7 @@ use common_exception::Result;
 use common_planners::InsertIntoPlan;
 use common_streams::DataBlockStream;
 use common_streams::SendableDataBlockStream;
+use futures::StreamExt;
 
 use crate::catalogs::Catalog;
 use crate::interpreters::Interpreter;
@@ -48,6 +49,16 @@ impl Interpreter for InsertIntoInterpreter {
         let datasource = self.ctx.get_catalog();
         let database = datasource.get_database(self.plan.db_name.as_str())?;
         let table = database.get_table_by_id(self.plan.tbl_id, None)?;
+
+        // The rows are counted as the table pulls them, they are the affected rows of the query.
+        let input_stream = self.plan.input_stream.lock().take();
+        if let Some(input_stream) = input_stream {
+            let ctx = self.ctx.clone();
+            self.plan.set_input_stream(Box::pin(
+                input_stream.inspect(move |block| ctx.add_affected_rows(block.num_rows() as u64)),
+            ));
+        }
+
         table
             .raw()
             .append_data(self.ctx.clone(), self.plan.clone())
@@ -0,0 +1,170 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_datavalues::series::Series;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::PlanNode;
+use common_store_api::CreateTableOutcome;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+use futures::StreamExt;
+
+use crate::catalogs::Catalog;
+use crate::interpreters::Interpreter;
+use crate::interpreters::InterpreterFactory;
+use crate::interpreters::InterpreterPtr;
+use crate::sessions::DatabendQueryContextRef;
+use crate::sql::DfStatement;
+use crate::sql::PlanParser;
+
+/// Runs the statements of a multi-statement query in order, each one is planned once the previous
+/// one is done. The result is the one of the last statement, the first failure stops the script.
+///
+/// With the ddl_batching setting, a run of consecutive CREATE TABLE statements is created in one
+/// meta write, a table that cannot be created doesn't stop the run. The result of the run is a row
+/// for each table: its name, whether it is created, already exists or failed, and the error.
+pub struct ScriptInterpreter {
+    ctx: DatabendQueryContextRef,
+    statements: Vec<DfStatement>,
+}
+
+impl ScriptInterpreter {
+    pub fn try_create(
+        ctx: DatabendQueryContextRef,
+        statements: Vec<DfStatement>,
+    ) -> Result<InterpreterPtr> {
+        Ok(Arc::new(ScriptInterpreter { ctx, statements }))
+    }
+
+    pub fn create_tables_schema() -> DataSchemaRef {
+        DataSchemaRefExt::create(vec![
+            DataField::new("table", DataType::String, false),
+            DataField::new("status", DataType::String, false),
+            DataField::new("message", DataType::String, false),
+        ])
+    }
+
+    async fn execute_statement(&self, statement: &DfStatement) -> Result<SendableDataBlockStream> {
+        let plan = PlanParser::create(self.ctx.clone()).statement_to_plan(statement)?;
+        self.ctx.attach_query_plan(&plan);
+        let interpreter = InterpreterFactory::get(self.ctx.clone(), plan)?;
+        interpreter.execute().await
+    }
+
+    fn create_tables(&self, statements: &[DfStatement]) -> Result<SendableDataBlockStream> {
+        let parser = PlanParser::create(self.ctx.clone());
+
+        let mut names = Vec::with_capacity(statements.len());
+        let mut outcomes = Vec::with_capacity(statements.len());
+        let mut plans = vec![];
+        for statement in statements {
+            let create = match statement {
+                DfStatement::CreateTable(create) => create,
+                _ => return Err(ErrorCode::LogicalError("Expect a CREATE TABLE statement")),
+            };
+            names.push(create.name.to_string());
+            match parser.statement_to_plan(statement) {
+                Ok(PlanNode::CreateTable(plan)) => {
+                    outcomes.push(None);
+                    plans.push(plan);
+                }
+                Ok(plan) => {
+                    return Err(ErrorCode::LogicalError(format!(
+                        "Expect a CreateTablePlan, but got {}",
+                        plan.name()
+                    )));
+                }
+                Err(e) => outcomes.push(Some(CreateTableOutcome::failed(&e))),
+            }
+        }
+
+        let mut created = self.ctx.get_catalog().create_tables(plans)?.into_iter();
+        let mut status = Vec::with_capacity(outcomes.len());
+        let mut messages = Vec::with_capacity(outcomes.len());
+        for outcome in outcomes {
+            match outcome.or_else(|| created.next()) {
+                Some(CreateTableOutcome::Created { .. }) => {
+                    status.push("created");
+                    messages.push(String::new());
+                }
+                Some(CreateTableOutcome::AlreadyExists { .. }) => {
+                    status.push("already exists");
+                    messages.push(String::new());
+                }
+                Some(CreateTableOutcome::Failed { message, .. }) => {
+                    status.push("error");
+                    messages.push(message);
+                }
+                None => return Err(ErrorCode::LogicalError("Missing outcome of a table")),
+            }
+        }
+
+        let names: Vec<&[u8]> = names.iter().map(|x| x.as_bytes()).collect();
+        let status: Vec<&[u8]> = status.iter().map(|x| x.as_bytes()).collect();
+        let messages: Vec<&[u8]> = messages.iter().map(|x| x.as_bytes()).collect();
+
+        let schema = Self::create_tables_schema();
+        let block = DataBlock::create_by_array(schema.clone(), vec![
+            Series::new(names),
+            Series::new(status),
+            Series::new(messages),
+        ]);
+        Ok(Box::pin(DataBlockStream::create(schema, None, vec![block])))
+    }
+}
+
+#[async_trait::async_trait]
+impl Interpreter for ScriptInterpreter {
+    fn name(&self) -> &str {
+        "ScriptInterpreter"
+    }
+
+    async fn execute(&self) -> Result<SendableDataBlockStream> {
+        let batching = self.ctx.get_settings().get_ddl_batching()? != 0;
+
+        let mut result: Option<SendableDataBlockStream> = None;
+        let mut next = 0;
+        while next < self.statements.len() {
+            // The result of a statement that is not the last is consumed, so that it is done.
+            if let Some(mut stream) = result.take() {
+                while let Some(block) = stream.next().await {
+                    block?;
+                }
+            }
+
+            let run = match batching {
+                true => self.statements[next..]
+                    .iter()
+                    .take_while(|s| matches!(s, DfStatement::CreateTable(_)))
+                    .count(),
+                false => 0,
+            };
+
+            if run > 0 {
+                result = Some(self.create_tables(&self.statements[next..next + run])?);
+                next += run;
+            } else {
+                result = Some(self.execute_statement(&self.statements[next]).await?);
+                next += 1;
+            }
+        }
+
+        result.ok_or_else(|| ErrorCode::SyntaxException("Only support single query"))
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::catalogs::Catalog;
+use crate::interpreters::*;
+use crate::sessions::DatabendQueryContextRef;
+use crate::sql::*;
+
+const SCRIPT: &str = "CREATE TABLE t1(a int) Engine = Null; \
+    CREATE TABLE t2(a int) Engine = Null; \
+    CREATE TABLE t3(a int) Engine = Null; \
+    CREATE TABLE t4(a int) Engine = Null; \
+    CREATE TABLE t5(a int) Engine = Null;";
+
+async fn run_script(ctx: &DatabendQueryContextRef, query: &str) -> Result<Vec<DataBlock>> {
+    let (statements, _) = PlanParser::create(ctx.clone())
+        .parse_script(query)
+        .expect("a script of more than one statement");
+    let executor = ScriptInterpreter::try_create(ctx.clone(), statements)?;
+    assert_eq!(executor.name(), "ScriptInterpreter");
+    let stream = executor.execute().await?;
+    stream.try_collect::<Vec<_>>().await
+}
+
+async fn create_existing_table(ctx: &DatabendQueryContextRef) -> Result<()> {
+    let plan =
+        PlanParser::create(ctx.clone()).build_from_sql("CREATE TABLE t2(a int) Engine = Null")?;
+    InterpreterFactory::get(ctx.clone(), plan)?
+        .execute()
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_script_interpreter_ddl_batching() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    ctx.get_settings().set_ddl_batching(1)?;
+    create_existing_table(&ctx).await?;
+    let catalog = ctx.get_catalog();
+    let ver = catalog.get_meta_version()?;
+
+    // The tables are created in one go, the existing one is reported.
+    let result = run_script(&ctx, SCRIPT).await?;
+    let expected = vec![
+        "+-------+---------+-------------------------------------+",
+        "| table | status  | message                             |",
+        "+-------+---------+-------------------------------------+",
+        "| t1    | created |                                     |",
+        "| t2    | error   | Table: 'default.t2' already exists. |",
+        "| t3    | created |                                     |",
+        "| t4    | created |                                     |",
+        "| t5    | created |                                     |",
+        "+-------+---------+-------------------------------------+",
+    ];
+    common_datablocks::assert_blocks_eq(expected, result.as_slice());
+    assert_eq!(ver + 1, catalog.get_meta_version()?);
+    for table in ["t1", "t3", "t4", "t5"] {
+        assert!(catalog.get_table("default", table).is_ok());
+    }
+
+    // Nothing created, nothing bumped.
+    let result = run_script(
+        &ctx,
+        "CREATE TABLE IF NOT EXISTS t1(a int) Engine = Null; \
+        CREATE TABLE IF NOT EXISTS t2(a int) Engine = Null;",
+    )
+    .await?;
+    let expected = vec![
+        "+-------+----------------+---------+",
+        "| table | status         | message |",
+        "+-------+----------------+---------+",
+        "| t1    | already exists |         |",
+        "| t2    | already exists |         |",
+        "+-------+----------------+---------+",
+    ];
+    common_datablocks::assert_blocks_eq(expected, result.as_slice());
+    assert_eq!(ver + 1, catalog.get_meta_version()?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_script_interpreter_without_ddl_batching() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    create_existing_table(&ctx).await?;
+    let catalog = ctx.get_catalog();
+    let ver = catalog.get_meta_version()?;
+
+    // The statements run one by one, the script stops at the existing table.
+    let result = run_script(&ctx, SCRIPT).await;
+    assert_eq!(
+        ErrorCode::TableAlreadyExists("").code(),
+        result.unwrap_err().code()
+    );
+    assert_eq!(ver + 1, catalog.get_meta_version()?);
+    assert!(catalog.get_table("default", "t1").is_ok());
+    for table in ["t3", "t4", "t5"] {
+        assert!(catalog.get_table("default", table).is_err());
+    }
+
+    // The result is the one of the last statement.
+    let result = run_script(&ctx, "CREATE TABLE t3(a int) Engine = Null; SELECT 1 AS x;").await?;
+    let expected = vec!["+---+", "| x |", "+---+", "| 1 |", "+---+"];
+    common_datablocks::assert_blocks_eq(expected, result.as_slice());
+    assert!(catalog.get_table("default", "t3").is_ok());
+
+    Ok(())
+}
@@ -58,6 +58,9 @@ impl Interpreter for SelectInterpreter {
 
     #[tracing::instrument(level = "info", skip(self), fields(ctx.id = self.ctx.get_id().as_str()))]
     async fn execute(&self) -> Result<SendableDataBlockStream> {
+        // Queue the query if the resource group has reached its max concurrency.
+        self.ctx.acquire_resource_group_slot().await?;
+
         // TODO: maybe panic?
         let mut scheduled = Scheduled::new();
         let timeout = self.ctx.get_settings().get_flight_client_timeout()?;
@@ -86,8 +89,9 @@ impl SelectInterpreter {
         let remote_stage_actions = scheduled_tasks.get_tasks()?;
 
         let timeout = self.ctx.get_settings().get_flight_client_timeout()?;
+        let cluster = self.ctx.try_get_cluster()?;
         for (node, action) in remote_stage_actions {
-            let mut flight_client = node.get_flight_client(&self.ctx.get_config()).await?;
+            let mut flight_client = cluster.get_flight_client(&node, &self.ctx.get_config())?;
             let executing_action = flight_client.execute_action(action.clone(), timeout);
 
             executing_action.await?;
@@ -101,11 +105,16 @@ impl SelectInterpreter {
 
     async fn error_handler(scheduled: Scheduled, context: &DatabendQueryContextRef, timeout: u64) {
         let query_id = context.get_id();
+        let cluster = match context.try_get_cluster() {
+            Ok(cluster) => cluster,
+            Err(cause) => {
+                log::error!("Cannot cancel actions, cause: {}", cause);
+                return;
+            }
+        };
+
         for (_stream_name, scheduled_node) in scheduled {
-            match scheduled_node
-                .get_flight_client(&context.get_config())
-                .await
-            {
+            match cluster.get_flight_client(&scheduled_node, &context.get_config()) {
                 Err(cause) => {
                     log::error!(
                         "Cannot cancel action for {}, cause: {}",
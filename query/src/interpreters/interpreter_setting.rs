@@ -17,14 +17,20 @@ use std::sync::Arc;
 use common_datavalues::DataField;
 use common_datavalues::DataSchemaRefExt;
 use common_datavalues::DataType;
+use common_exception::codes;
+use common_exception::ErrorCode;
 use common_exception::Result;
+use common_planners::fold_option_name;
 use common_planners::SettingPlan;
 use common_streams::DataBlockStream;
 use common_streams::SendableDataBlockStream;
 
 use crate::interpreters::Interpreter;
 use crate::interpreters::InterpreterPtr;
+use crate::sessions::is_query_label_changed;
+use crate::sessions::sanitize_query_label;
 use crate::sessions::DatabendQueryContextRef;
+use crate::sql::SqlDialect;
 
 pub struct SettingInterpreter {
     ctx: DatabendQueryContextRef,
@@ -46,9 +52,42 @@ impl Interpreter for SettingInterpreter {
     async fn execute(&self) -> Result<SendableDataBlockStream> {
         let plan = self.set.clone();
         for var in plan.vars {
-            match var.variable.to_lowercase().as_str() {
+            match fold_option_name(&var.variable).as_str() {
                 // To be compatible with some drivers
-                "sql_mode" | "autocommit" => {}
+                "sql_mode" => {}
+                "autocommit" => {
+                    let autocommit = match var.value.to_lowercase().as_str() {
+                        "1" | "on" | "true" => 1,
+                        "0" | "off" | "false" => 0,
+                        _ => {
+                            return Err(ErrorCode::BadArguments(format!(
+                                "Variable 'autocommit' can't be set to the value of '{}'",
+                                var.value
+                            )))
+                        }
+                    };
+
+                    // Like MySQL, enabling autocommit commits the current transaction.
+                    if autocommit == 1 {
+                        self.ctx.set_in_transaction(false);
+                    }
+                    self.ctx.get_settings().set_autocommit(autocommit)?;
+                }
+                "query_label" => {
+                    let label = sanitize_query_label(&var.value).unwrap_or_default();
+                    if is_query_label_changed(&var.value) {
+                        let message =
+                            format!("The query label {} is changed to {}", var.value, label);
+                        self.ctx.push_warning(codes::BadArguments, message);
+                    }
+                    self.ctx.get_settings().set_query_label(label)?;
+                }
+                "sql_dialect" => {
+                    let dialect = SqlDialect::from_setting(&var.value)?;
+                    self.ctx
+                        .get_settings()
+                        .set_sql_dialect(dialect.name().to_string())?;
+                }
                 "max_threads" => {
                     let threads: u64 = var.value.parse()?;
                     self.ctx.get_settings().set_max_threads(threads)?;
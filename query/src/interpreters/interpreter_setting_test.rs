@@ -58,3 +58,65 @@ async fn test_setting_interpreter_error() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_setting_interpreter_autocommit() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    let plan = PlanParser::create(ctx.clone()).build_from_sql("set autocommit=0")?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    executor.execute().await?;
+    assert_eq!(ctx.get_settings().get_autocommit()?, 0);
+
+    ctx.set_in_transaction(true);
+    let plan = PlanParser::create(ctx.clone()).build_from_sql("set autocommit=ON")?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    executor.execute().await?;
+    assert_eq!(ctx.get_settings().get_autocommit()?, 1);
+    assert!(!ctx.is_in_transaction());
+
+    let plan = PlanParser::create(ctx.clone()).build_from_sql("set autocommit=maybe")?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    assert!(executor.execute().await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_setting_interpreter_query_label() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    assert_eq!(ctx.get_query_label(), None);
+
+    let plan = PlanParser::create(ctx.clone()).build_from_sql("set query_label='team=billing;'")?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    executor.execute().await?;
+    assert_eq!(ctx.get_settings().get_query_label()?, "team=billing_");
+    assert_eq!(ctx.get_query_label(), Some("team=billing_".to_string()));
+
+    // The hint of the query overrides the setting.
+    ctx.attach_query_str("SELECT /*+ label(nightly-etl) */ 1");
+    assert_eq!(ctx.get_query_label(), Some("nightly-etl".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_setting_interpreter_case_insensitive() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    // SET MAX_THREADS works the same as set max_threads.
+    for (sql, want) in [("set max_threads=3", 3), ("SET MAX_THREADS=5", 5)] {
+        let plan = PlanParser::create(ctx.clone()).build_from_sql(sql)?;
+        let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+        executor.execute().await?;
+        assert_eq!(want, ctx.get_settings().get_max_threads()?, "{}", sql);
+    }
+
+    // The settings without a dedicated handler.
+    let plan = PlanParser::create(ctx.clone()).build_from_sql("set Max_Block_Size=7")?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    executor.execute().await?;
+    assert_eq!(7, ctx.get_settings().get_max_block_size()?);
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datavalues::DataField;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+
+use crate::interpreters::*;
+use crate::sql::*;
+
+#[tokio::test]
+async fn test_modify_column_interpreter() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    if let PlanNode::CreateTable(plan) = PlanParser::create(ctx.clone())
+        .build_from_sql("create table default.a(a int, b int) Engine = Null")?
+    {
+        let executor = CreateTableInterpreter::try_create(ctx.clone(), plan.clone())?;
+        let _ = executor.execute().await?;
+    }
+
+    // Modify column: the embedded metastore keeps the local tables in their created types.
+    if let PlanNode::ModifyColumn(plan) = PlanParser::create(ctx.clone())
+        .build_from_sql("alter table a modify column b bigint null")?
+    {
+        assert_eq!(plan.db, "default");
+        assert_eq!(plan.table, "a");
+        assert_eq!(plan.column, DataField::new("b", DataType::Int64, true));
+
+        let executor = ModifyColumnInterpreter::try_create(ctx.clone(), plan.clone())?;
+        assert_eq!(executor.name(), "ModifyColumnInterpreter");
+        match executor.execute().await {
+            Ok(_) => assert!(false),
+            Err(e) => assert_eq!(e.code(), ErrorCode::UnImplement("").code()),
+        }
+    } else {
+        assert!(false)
+    }
+
+    Ok(())
+}
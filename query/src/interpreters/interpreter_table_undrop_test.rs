@@ -0,0 +1,65 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+
+use crate::interpreters::*;
+use crate::sql::*;
+
+#[tokio::test]
+async fn test_undrop_table_interpreter() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    // Create and drop table.
+    {
+        if let PlanNode::CreateTable(plan) = PlanParser::create(ctx.clone())
+            .build_from_sql("create table default.a(a bigint, b int) Engine = Null")?
+        {
+            let executor = CreateTableInterpreter::try_create(ctx.clone(), plan.clone())?;
+            let _ = executor.execute().await?;
+        }
+
+        if let PlanNode::DropTable(plan) =
+            PlanParser::create(ctx.clone()).build_from_sql("drop table a")?
+        {
+            let executor = DropTableInterpreter::try_create(ctx.clone(), plan.clone())?;
+            let _ = executor.execute().await?;
+        }
+    }
+
+    // Undrop table: the embedded metastore does not keep dropped tables.
+    {
+        if let PlanNode::UndropTable(plan) =
+            PlanParser::create(ctx.clone()).build_from_sql("undrop table a")?
+        {
+            assert_eq!(plan.db, "default");
+            assert_eq!(plan.table, "a");
+
+            let executor = UndropTableInterpreter::try_create(ctx.clone(), plan.clone())?;
+            assert_eq!(executor.name(), "UndropTableInterpreter");
+            match executor.execute().await {
+                Ok(_) => assert!(false),
+                Err(e) => assert_eq!(e.code(), ErrorCode::UnImplement("").code()),
+            }
+        } else {
+            assert!(false)
+        }
+    }
+
+    Ok(())
+}
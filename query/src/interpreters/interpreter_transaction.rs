@@ -0,0 +1,70 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_datavalues::DataSchema;
+use common_exception::codes;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::TransactionKind;
+use common_planners::TransactionPlan;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+
+use crate::interpreters::Interpreter;
+use crate::interpreters::InterpreterPtr;
+use crate::sessions::DatabendQueryContextRef;
+
+/// The statements are committed when they are executed, so a transaction only switches
+/// the state of the session that the clients see, to keep the ORMs and the migration tools working.
+pub struct TransactionInterpreter {
+    ctx: DatabendQueryContextRef,
+    plan: TransactionPlan,
+}
+
+impl TransactionInterpreter {
+    pub fn try_create(
+        ctx: DatabendQueryContextRef,
+        plan: TransactionPlan,
+    ) -> Result<InterpreterPtr> {
+        Ok(Arc::new(TransactionInterpreter { ctx, plan }))
+    }
+}
+
+#[async_trait::async_trait]
+impl Interpreter for TransactionInterpreter {
+    fn name(&self) -> &str {
+        "TransactionInterpreter"
+    }
+
+    async fn execute(&self) -> Result<SendableDataBlockStream> {
+        match self.plan.kind {
+            TransactionKind::Begin => self.ctx.set_in_transaction(true),
+            TransactionKind::Commit => self.ctx.set_in_transaction(false),
+            TransactionKind::Rollback => {
+                self.ctx.set_in_transaction(false);
+
+                let message = "ROLLBACK has nothing to roll back, the statements are committed when they are executed";
+                match self.ctx.get_settings().get_strict_transaction()? {
+                    0 => self.ctx.push_warning(codes::UnImplement, message),
+                    _ => return Err(ErrorCode::UnImplement(message)),
+                }
+            }
+        }
+
+        let schema = Arc::new(DataSchema::empty());
+        Ok(Box::pin(DataBlockStream::create(schema, None, vec![])))
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::Result;
+use common_planners::*;
+use common_runtime::tokio;
+use futures::stream::StreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::interpreters::*;
+use crate::sql::*;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transaction_interpreter() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    for (query, in_transaction) in [
+        ("BEGIN", true),
+        ("COMMIT", false),
+        ("START TRANSACTION", true),
+    ] {
+        if let PlanNode::Transaction(plan) =
+            PlanParser::create(ctx.clone()).build_from_sql(query)?
+        {
+            let executor = TransactionInterpreter::try_create(ctx.clone(), plan)?;
+            assert_eq!(executor.name(), "TransactionInterpreter");
+
+            let mut stream = executor.execute().await?;
+            while let Some(_block) = stream.next().await {}
+            assert_eq!(ctx.is_in_transaction(), in_transaction, "{}", query);
+        } else {
+            assert!(false)
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transaction_interpreter_rollback() -> Result<()> {
+    // Not strict: ROLLBACK leaves a warning.
+    {
+        let ctx = crate::tests::try_create_context()?;
+        ctx.set_in_transaction(true);
+
+        let plan = PlanParser::create(ctx.clone()).build_from_sql("ROLLBACK")?;
+        let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+        let mut stream = executor.execute().await?;
+        while let Some(_block) = stream.next().await {}
+
+        assert!(!ctx.is_in_transaction());
+        assert_eq!(ctx.get_warnings().len(), 1);
+    }
+
+    // Strict: ROLLBACK is an error.
+    {
+        let ctx = crate::tests::try_create_context()?;
+        ctx.get_settings().set_strict_transaction(1)?;
+
+        let plan = PlanParser::create(ctx.clone()).build_from_sql("ROLLBACK")?;
+        let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+        if let Err(e) = executor.execute().await {
+            let expect = "Code: 2, displayText = ROLLBACK has nothing to roll back, the statements are committed when they are executed.";
+            assert_eq!(expect, format!("{}", e));
+        } else {
+            assert!(false);
+        }
+        assert!(ctx.get_warnings().is_empty());
+    }
+
+    Ok(())
+}
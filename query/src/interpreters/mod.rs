@@ -21,6 +21,8 @@ mod interpreter_describe_table_test;
 #[cfg(test)]
 mod interpreter_explain_test;
 #[cfg(test)]
+mod interpreter_script_test;
+#[cfg(test)]
 mod interpreter_select_test;
 #[cfg(test)]
 mod interpreter_setting_test;
@@ -31,6 +33,12 @@ mod interpreter_table_create_test;
 #[cfg(test)]
 mod interpreter_table_drop_test;
 #[cfg(test)]
+mod interpreter_table_modify_column_test;
+#[cfg(test)]
+mod interpreter_table_undrop_test;
+#[cfg(test)]
+mod interpreter_transaction_test;
+#[cfg(test)]
 mod interpreter_truncate_table_test;
 #[cfg(test)]
 mod interpreter_use_database_test;
@@ -45,11 +53,16 @@ mod interpreter_explain;
 mod interpreter_factory;
 mod interpreter_insert_into;
 mod interpreter_kill;
+mod interpreter_script;
 mod interpreter_select;
 mod interpreter_setting;
 mod interpreter_show_create_table;
 mod interpreter_table_create;
 mod interpreter_table_drop;
+mod interpreter_table_modify_column;
+mod interpreter_table_optimize;
+mod interpreter_table_undrop;
+mod interpreter_transaction;
 mod interpreter_truncate_table;
 mod interpreter_use_database;
 #[allow(clippy::needless_range_loop)]
@@ -63,10 +76,15 @@ pub use interpreter_describe_table::DescribeTableInterpreter;
 pub use interpreter_explain::ExplainInterpreter;
 pub use interpreter_factory::InterpreterFactory;
 pub use interpreter_insert_into::InsertIntoInterpreter;
+pub use interpreter_script::ScriptInterpreter;
 pub use interpreter_select::SelectInterpreter;
 pub use interpreter_setting::SettingInterpreter;
 pub use interpreter_show_create_table::ShowCreateTableInterpreter;
 pub use interpreter_table_create::CreateTableInterpreter;
 pub use interpreter_table_drop::DropTableInterpreter;
+pub use interpreter_table_modify_column::ModifyColumnInterpreter;
+pub use interpreter_table_optimize::OptimizeTableInterpreter;
+pub use interpreter_table_undrop::UndropTableInterpreter;
+pub use interpreter_transaction::TransactionInterpreter;
 pub use interpreter_truncate_table::TruncateTableInterpreter;
 pub use interpreter_use_database::UseDatabaseInterpreter;
@@ -69,6 +69,11 @@ pub struct PlanScheduler {
     local_pos: usize,
     nodes_plan: Vec<PlanNode>,
     running_mode: RunningMode,
+    // An ancestor of the visiting node depends on the order of its input,
+    // so the exchanges below it must keep the order of the blocks.
+    order_sensitive: bool,
+    // The strict_arithmetic setting, the remote stages are evaluated with it.
+    strict_arithmetic: bool,
     query_context: DatabendQueryContextRef,
     subqueries_expressions: Vec<Expressions>,
 }
@@ -90,6 +95,7 @@ impl PlanScheduler {
             cluster_nodes_name.push(cluster_nodes[index].name.clone());
         }
 
+        let strict_arithmetic = context.get_settings().get_strict_arithmetic()? != 0;
         Ok(PlanScheduler {
             local_pos,
             nodes_plan,
@@ -98,6 +104,8 @@ impl PlanScheduler {
             subqueries_expressions: vec![],
             cluster_nodes: cluster_nodes_name,
             running_mode: RunningMode::Standalone,
+            order_sensitive: false,
+            strict_arithmetic,
         })
     }
 
@@ -106,9 +114,11 @@ impl PlanScheduler {
     pub fn reschedule(mut self, plan: &PlanNode) -> Result<Tasks> {
         let context = self.query_context.clone();
         let cluster = context.try_get_cluster()?;
+        // A no_distributed hint keeps the whole plan on this node.
+        let standalone = cluster.is_empty()? || context.get_query_hints().no_distributed;
         let mut tasks = Tasks::create(context);
 
-        match cluster.is_empty()? {
+        match standalone {
             true => tasks.finalize(plan),
             false => {
                 self.visit_plan_node(plan, &mut tasks)?;
@@ -172,6 +182,9 @@ impl PlanScheduler {
             plan: input.clone(),
             sinks: self.cluster_nodes.clone(),
             scatters_expression: stage.scatters_expr.clone(),
+            query_label: self.query_context.get_query_label(),
+            preserve_order: self.order_sensitive,
+            strict_arithmetic: self.strict_arithmetic,
         }
     }
 
@@ -182,6 +195,7 @@ impl PlanScheduler {
             stage_id: action.stage_id.clone(),
             stream_id: node_name.to_string(),
             fetch_nodes: self.cluster_nodes.clone(),
+            preserve_order: self.order_sensitive,
         }
     }
 
@@ -212,6 +226,9 @@ impl PlanScheduler {
             plan: input.clone(),
             sinks: self.cluster_nodes.clone(),
             scatters_expression: stage.scatters_expr.clone(),
+            query_label: self.query_context.get_query_label(),
+            preserve_order: self.order_sensitive,
+            strict_arithmetic: self.strict_arithmetic,
         }
     }
 
@@ -222,6 +239,7 @@ impl PlanScheduler {
             stage_id: action.stage_id.clone(),
             stream_id: node_name.to_string(),
             fetch_nodes: vec![self.cluster_nodes[self.local_pos].clone()],
+            preserve_order: self.order_sensitive,
         })
     }
 
@@ -255,6 +273,9 @@ impl PlanScheduler {
             plan: input.clone(),
             sinks: vec![self.cluster_nodes[self.local_pos].clone()],
             scatters_expression: stage.scatters_expr.clone(),
+            query_label: self.query_context.get_query_label(),
+            preserve_order: self.order_sensitive,
+            strict_arithmetic: self.strict_arithmetic,
         }
     }
 
@@ -265,6 +286,7 @@ impl PlanScheduler {
             query_id: self.query_context.get_id(),
             stream_id: node_name.to_string(),
             fetch_nodes: self.cluster_nodes.clone(),
+            preserve_order: self.order_sensitive,
         }
     }
 
@@ -314,8 +336,16 @@ impl PlanScheduler {
         }
     }
 
+    // The node reorders or merges its input anyway, the exchanges below it may reorder the blocks.
+    fn visit_order_insensitive(&mut self, input: &PlanNode, tasks: &mut Tasks) -> Result<()> {
+        let order_sensitive = std::mem::replace(&mut self.order_sensitive, false);
+        let visited = self.visit_plan_node(input, tasks);
+        self.order_sensitive = order_sensitive;
+        visited
+    }
+
     fn visit_aggr_part(&mut self, plan: &AggregatorPartialPlan, tasks: &mut Tasks) -> Result<()> {
-        self.visit_plan_node(plan.input.as_ref(), tasks)?;
+        self.visit_order_insensitive(plan.input.as_ref(), tasks)?;
         match self.running_mode {
             RunningMode::Cluster => self.visit_cluster_aggr_part(plan),
             RunningMode::Standalone => self.visit_local_aggr_part(plan),
@@ -329,6 +359,7 @@ impl PlanScheduler {
             aggr_expr: plan.aggr_expr.clone(),
             group_expr: plan.group_expr.clone(),
             input: Arc::new(self.nodes_plan[self.local_pos].clone()),
+            top_n: plan.top_n.clone(),
         });
     }
 
@@ -339,12 +370,13 @@ impl PlanScheduler {
                 aggr_expr: plan.aggr_expr.clone(),
                 group_expr: plan.group_expr.clone(),
                 input: Arc::new(self.nodes_plan[index].clone()),
+                top_n: plan.top_n.clone(),
             });
         }
     }
 
     fn visit_aggr_final(&mut self, plan: &AggregatorFinalPlan, tasks: &mut Tasks) -> Result<()> {
-        self.visit_plan_node(plan.input.as_ref(), tasks)?;
+        self.visit_order_insensitive(plan.input.as_ref(), tasks)?;
 
         match self.running_mode {
             RunningMode::Cluster => self.visit_cluster_aggr_final(plan),
@@ -435,6 +467,9 @@ impl PlanScheduler {
             query_id: self.query_context.get_id(),
             plan: input.clone(),
             sinks: self.cluster_nodes.clone(),
+            query_label: self.query_context.get_query_label(),
+            preserve_order: self.order_sensitive,
+            strict_arithmetic: self.strict_arithmetic,
         }
     }
 
@@ -445,6 +480,7 @@ impl PlanScheduler {
             stage_id: action.stage_id.clone(),
             stream_id: node_name.to_string(),
             fetch_nodes: self.cluster_nodes.clone(),
+            preserve_order: self.order_sensitive,
         }
     }
 
@@ -462,6 +498,7 @@ impl PlanScheduler {
                 stage_id: action.stage_id.clone(),
                 stream_id: node_name.to_string(),
                 fetch_nodes: vec![self.cluster_nodes[self.local_pos].clone()],
+                preserve_order: self.order_sensitive,
             });
         }
     }
@@ -700,7 +737,7 @@ impl PlanScheduler {
     }
 
     fn visit_sort(&mut self, plan: &SortPlan, tasks: &mut Tasks) -> Result<()> {
-        self.visit_plan_node(plan.input.as_ref(), tasks)?;
+        self.visit_order_insensitive(plan.input.as_ref(), tasks)?;
         match self.running_mode {
             RunningMode::Cluster => self.visit_cluster_sort(plan),
             RunningMode::Standalone => self.visit_local_sort(plan),
@@ -727,7 +764,11 @@ impl PlanScheduler {
     }
 
     fn visit_limit(&mut self, plan: &LimitPlan, tasks: &mut Tasks) -> Result<()> {
-        self.visit_plan_node(plan.input.as_ref(), tasks)?;
+        // The first N rows are the first N in the order the input yields them.
+        let order_sensitive = std::mem::replace(&mut self.order_sensitive, true);
+        let visited = self.visit_plan_node(plan.input.as_ref(), tasks);
+        self.order_sensitive = order_sensitive;
+        visited?;
         match self.running_mode {
             RunningMode::Cluster => self.visit_cluster_limit(plan),
             RunningMode::Standalone => self.visit_local_limit(plan),
@@ -840,8 +881,7 @@ impl PlanScheduler {
     fn cluster_source(&mut self, node: &ScanPlan, table: TablePtr) -> Result<ReadDataSourcePlan> {
         let nodes = self.cluster_nodes.clone();
         let context = self.query_context.clone();
-        let settings = context.get_settings();
-        let max_threads = settings.get_max_threads()? as usize;
+        let max_threads = context.get_max_threads()? as usize;
         table.read_plan(context, node, max_threads * nodes.len())
     }
 
@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use common_datavalues::DataValue;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::*;
 use common_runtime::tokio;
@@ -70,6 +71,7 @@ async fn test_scheduler_plan_with_one_convergent_stage() -> Result<()> {
     for (node, remote_action) in scheduled_tasks.get_tasks()? {
         match remote_action {
             FlightAction::CancelAction(_) => assert!(false),
+            FlightAction::GetProgressAction(_) => assert!(false),
             FlightAction::BroadcastAction(_) => assert!(false),
             FlightAction::PrepareShuffleAction(action) => remote_actions.push((node, action)),
         }
@@ -151,6 +153,7 @@ async fn test_scheduler_plan_with_convergent_and_expansive_stage() -> Result<()>
     for (node, remote_action) in scheduled_tasks.get_tasks()? {
         match remote_action {
             FlightAction::CancelAction(_) => assert!(false),
+            FlightAction::GetProgressAction(_) => assert!(false),
             FlightAction::BroadcastAction(_) => assert!(false),
             FlightAction::PrepareShuffleAction(action) => remote_actions.push((node, action)),
         }
@@ -252,6 +255,7 @@ async fn test_scheduler_plan_with_convergent_and_normal_stage() -> Result<()> {
     for (node, remote_action) in scheduled_tasks.get_tasks()? {
         match remote_action {
             FlightAction::CancelAction(_) => assert!(false),
+            FlightAction::GetProgressAction(_) => assert!(false),
             FlightAction::BroadcastAction(_) => assert!(false),
             FlightAction::PrepareShuffleAction(action) => remote_actions.push((node, action)),
         }
@@ -329,6 +333,81 @@ async fn test_scheduler_plan_with_convergent_and_normal_stage() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_scheduler_plan_with_order_sensitive_stage() -> Result<()> {
+    let convergent_stage = |input: PlanNode| {
+        PlanNode::Stage(StagePlan {
+            kind: StageKind::Convergent,
+            scatters_expr: Expression::create_literal(DataValue::UInt64(Some(0))),
+            input: Arc::new(input),
+        })
+    };
+
+    let limit = |input: PlanNode| {
+        PlanNode::Limit(LimitPlan {
+            n: Some(10),
+            offset: 0,
+            input: Arc::new(input),
+        })
+    };
+
+    let sort = |input: PlanNode| {
+        PlanNode::Sort(SortPlan {
+            schema: input.schema(),
+            order_by: vec![],
+            input: Arc::new(input),
+        })
+    };
+
+    let preserve_order_of = |plan: PlanNode, context: DatabendQueryContextRef| -> Result<bool> {
+        let scheduler = PlanScheduler::try_create(context)?;
+        let scheduled_tasks = scheduler.reschedule(&plan)?;
+
+        let mut actions_preserve_order = vec![];
+        for (_, remote_action) in scheduled_tasks.get_tasks()? {
+            match remote_action {
+                FlightAction::PrepareShuffleAction(action) => {
+                    actions_preserve_order.push(action.preserve_order)
+                }
+                _ => assert!(false),
+            }
+        }
+
+        match find_remote(&scheduled_tasks.get_local_task()) {
+            Some(PlanNode::Remote(remote)) => {
+                assert_eq!(actions_preserve_order, vec![remote.preserve_order; 2]);
+                Ok(remote.preserve_order)
+            }
+            _ => Err(ErrorCode::LogicalError(
+                "The local task must have Remote plan",
+            )),
+        }
+    };
+
+    let empty = PlanNode::Empty(EmptyPlan::cluster());
+
+    // Only a LIMIT above the exchange asks for the order.
+    let plan = convergent_stage(empty.clone());
+    assert!(!preserve_order_of(plan, create_env().await?)?);
+
+    let plan = limit(convergent_stage(empty.clone()));
+    assert!(preserve_order_of(plan, create_env().await?)?);
+
+    // A sort between them reorders the rows anyway.
+    let plan = limit(sort(convergent_stage(empty)));
+    assert!(!preserve_order_of(plan, create_env().await?)?);
+
+    Ok(())
+}
+
+fn find_remote(plan: &PlanNode) -> Option<PlanNode> {
+    match plan {
+        PlanNode::Remote(_) => Some(plan.clone()),
+        PlanNode::Empty(_) => None,
+        _ => find_remote(plan.input(0).as_ref()),
+    }
+}
+
 async fn create_env() -> Result<DatabendQueryContextRef> {
     try_create_cluster_context(&[
         ClusterNode::create("dummy_local", 1, "localhost:9090"),
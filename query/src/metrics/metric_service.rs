@@ -27,6 +27,7 @@ use axum::AddExtensionLayer;
 use axum::Router;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_infallible::Mutex;
 use common_runtime::tokio;
 use common_runtime::tokio::task::JoinHandle;
 use futures::future::AbortHandle;
@@ -34,14 +35,22 @@ use futures::future::AbortRegistration;
 use futures::future::Abortable;
 use futures::StreamExt;
 use hyper::server::conn::Http;
+use lazy_static::lazy_static;
 use metrics_exporter_prometheus::PrometheusBuilder;
 use metrics_exporter_prometheus::PrometheusHandle;
 use tokio_stream::wrappers::TcpListenerStream;
 
+use crate::configs::Config;
 use crate::servers::server::ListeningStream;
 use crate::servers::Server;
 
+lazy_static! {
+    // The recorder can only be installed once in a process.
+    static ref PROMETHEUS_HANDLE: Mutex<Option<PrometheusHandle>> = Mutex::new(None);
+}
+
 pub struct MetricService {
+    conf: Config,
     join_handle: Option<JoinHandle<()>>,
     abort_handle: AbortHandle,
     abort_registration: Option<AbortRegistration>,
@@ -75,21 +84,52 @@ macro_rules! build_router {
 }
 
 impl MetricService {
-    pub fn create() -> Box<dyn Server> {
+    pub fn create(conf: Config) -> Box<dyn Server> {
         let (abort_handle, registration) = AbortHandle::new_pair();
         Box::new(MetricService {
+            conf,
             abort_handle,
             abort_registration: Some(registration),
             join_handle: None,
         })
     }
 
-    fn create_prometheus_handle() -> Result<PrometheusHandle> {
-        let builder = PrometheusBuilder::new();
+    /// Parse the bucket bounds of the histograms from the form `0.01,0.1,1,...`.
+    /// Empty means the histograms are exported as summaries.
+    pub fn parse_histogram_buckets(spec: &str) -> Result<Vec<f64>> {
+        let mut buckets = vec![];
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let bound = item.parse::<f64>().map_err(|e| {
+                ErrorCode::BadArguments(format!("Bad histogram bucket: '{}', cause: {}", item, e))
+            })?;
+            if !bound.is_finite() || buckets.last().map_or(false, |last| *last >= bound) {
+                return Err(ErrorCode::BadArguments(format!(
+                    "Histogram buckets must be finite and ascending, got: '{}'",
+                    spec
+                )));
+            }
+            buckets.push(bound);
+        }
+        Ok(buckets)
+    }
+
+    fn create_prometheus_handle(buckets: &[f64]) -> Result<PrometheusHandle> {
+        let mut installed = PROMETHEUS_HANDLE.lock();
+        if let Some(prometheus_handle) = installed.as_ref() {
+            return Ok(prometheus_handle.clone());
+        }
+
+        let mut builder = PrometheusBuilder::new();
+        if !buckets.is_empty() {
+            builder = builder.set_buckets(buckets);
+        }
         let prometheus_recorder = builder.build();
         let prometheus_handle = prometheus_recorder.handle();
         match metrics::set_boxed_recorder(Box::new(prometheus_recorder)) {
-            Ok(_) => Ok(prometheus_handle),
+            Ok(_) => {
+                *installed = Some(prometheus_handle.clone());
+                Ok(prometheus_handle)
+            }
             Err(error) => Err(ErrorCode::InitPrometheusFailure(format!(
                 "Cannot init prometheus recorder. cause: {}",
                 error
@@ -145,7 +185,9 @@ impl Server for MetricService {
         match self.abort_registration.take() {
             None => Err(ErrorCode::LogicalError("Http Service already running.")),
             Some(registration) => {
-                let handle = MetricService::create_prometheus_handle()?;
+                let buckets =
+                    Self::parse_histogram_buckets(&self.conf.query.metric_histogram_buckets)?;
+                let handle = MetricService::create_prometheus_handle(&buckets)?;
                 let (stream, listener) = Self::listener_tcp(listening).await?;
                 let stream = Abortable::new(stream, registration);
                 self.join_handle = Some(tokio::spawn(self.listen_loop(stream, handle)));
@@ -13,17 +13,28 @@
 // limitations under the License.
 
 use std::net::SocketAddr;
+use std::time::Duration;
+use std::time::Instant;
 
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_exception::ToErrorCode;
 use common_runtime::tokio;
 use metrics::counter;
+use mysql::prelude::Queryable;
 
+use crate::configs::Config;
 use crate::metrics::MetricService;
+use crate::servers::MySQLHandler;
+use crate::servers::Server;
+use crate::tests::try_create_context;
+use crate::tests::try_create_session_mgr;
 
 pub static METRIC_TEST: &str = "metrics.test";
 
 #[tokio::test]
 async fn test_metric_server() -> common_exception::Result<()> {
-    let mut service = MetricService::create();
+    let mut service = MetricService::create(Config::default());
     let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
     let listening = service.start(listening).await?;
     let client = reqwest::Client::builder().build().unwrap();
@@ -43,3 +54,150 @@ async fn test_metric_server() -> common_exception::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_parse_histogram_buckets() -> Result<()> {
+    assert!(MetricService::parse_histogram_buckets("")?.is_empty());
+    assert_eq!(
+        MetricService::parse_histogram_buckets("0.005, 0.1,1,10")?,
+        vec![0.005, 0.1, 1.0, 10.0]
+    );
+
+    for spec in ["0.1,x", "1,0.1", "0.1,0.1", "1,inf"] {
+        let error = MetricService::parse_histogram_buckets(spec).unwrap_err();
+        assert_eq!(error.code(), ErrorCode::BadArguments("").code(), "{}", spec);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_metric_server_statements() -> Result<()> {
+    let (_service, url) = start_metric_service().await?;
+
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+    let listening = handler.start("0.0.0.0:0".parse::<SocketAddr>()?).await?;
+    let mut connection = create_connection(listening.port())?;
+
+    for query in [
+        "CREATE TABLE t_metrics(a UInt64) Engine = Memory",
+        "INSERT INTO t_metrics VALUES(1),(2),(3)",
+        "SELECT a FROM t_metrics",
+        "SET max_threads = 1",
+    ] {
+        connection
+            .query_drop(query)
+            .map_err_to_code(ErrorCode::UnknownException, || "Query error")?;
+    }
+    assert!(connection
+        .query_drop("SELECT * FROM t_metrics_unknown")
+        .is_err());
+
+    for labels in [
+        [r#"kind="ddl""#, r#"outcome="ok""#],
+        [r#"kind="insert""#, r#"outcome="ok""#],
+        [r#"kind="select""#, r#"outcome="ok""#],
+        [r#"kind="other""#, r#"outcome="ok""#],
+        [r#"kind="other""#, r#"outcome="error""#],
+    ] {
+        // The statement is counted once its context is dropped, after its result is written.
+        let count = scrape_until(&url, "query_statement_seconds_count", &labels).await?;
+        assert!(count > 0.0, "{:?}", labels);
+    }
+
+    let metrics = scrape(&url).await?;
+    let mysql = [r#"handler="mysql""#];
+    assert!(sample_value(&metrics, "session_created", &mysql) > 0.0);
+    assert!(metrics.contains("session_active{"));
+    assert!(sample_value(&metrics, "query_result_rows", &[]) >= 3.0);
+    assert!(sample_value(&metrics, "query_result_bytes", &[]) > 0.0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_metric_server_rejected_session() -> Result<()> {
+    let (_service, url) = start_metric_service().await?;
+    let mysql = [r#"handler="mysql""#];
+    let rejected = sample_value(&scrape(&url).await?, "session_rejected", &mysql);
+
+    let sessions = try_create_session_mgr(Some(1))?;
+    let _session = sessions.create_session("MySQL")?;
+    match sessions.create_session("MySQL") {
+        Ok(_) => panic!("Expected rejected session"),
+        Err(error) => assert_eq!(error.code(), ErrorCode::TooManyUserConnections("").code()),
+    }
+
+    let metrics = scrape(&url).await?;
+    assert!(sample_value(&metrics, "session_rejected", &mysql) >= rejected + 1.0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_metric_server_exchange() -> Result<()> {
+    let (_service, url) = start_metric_service().await?;
+
+    let ctx = try_create_context()?;
+    let query_metrics = ctx.get_query_metrics();
+    query_metrics.incr_exchange_sent(100);
+    query_metrics.incr_exchange_sent(200);
+    query_metrics.incr_exchange_received(50);
+
+    let values = query_metrics.get_values();
+    assert_eq!(values.exchange_sent_blocks, 2);
+    assert_eq!(values.exchange_sent_bytes, 300);
+    assert_eq!(values.exchange_received_blocks, 1);
+    assert_eq!(values.exchange_received_bytes, 50);
+
+    let metrics = scrape(&url).await?;
+    assert!(sample_value(&metrics, "exchange_sent_blocks", &[]) >= 2.0);
+    assert!(sample_value(&metrics, "exchange_sent_bytes", &[]) >= 300.0);
+    assert!(sample_value(&metrics, "exchange_received_blocks", &[]) >= 1.0);
+    assert!(sample_value(&metrics, "exchange_received_bytes", &[]) >= 50.0);
+
+    Ok(())
+}
+
+async fn start_metric_service() -> Result<(Box<dyn Server>, String)> {
+    let mut service = MetricService::create(Config::default());
+    let listening = service.start("127.0.0.1:0".parse::<SocketAddr>()?).await?;
+    Ok((service, format!("http://{}", listening)))
+}
+
+async fn scrape(url: &str) -> Result<String> {
+    let resp = reqwest::get(url)
+        .await
+        .map_err_to_code(ErrorCode::UnknownException, || "Scrape error")?;
+    resp.text()
+        .await
+        .map_err_to_code(ErrorCode::UnknownException, || "Scrape error")
+}
+
+async fn scrape_until(url: &str, name: &str, labels: &[&str]) -> Result<f64> {
+    let start = Instant::now();
+    loop {
+        let value = sample_value(&scrape(url).await?, name, labels);
+        if value > 0.0 || start.elapsed() > Duration::from_secs(5) {
+            return Ok(value);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// The sum of the samples of `name` having all the `labels`, 0 if there is none.
+fn sample_value(metrics: &str, name: &str, labels: &[&str]) -> f64 {
+    metrics
+        .lines()
+        .filter_map(|line| line.rsplit_once(' '))
+        .filter(|(series, _)| series.split('{').next() == Some(name))
+        .filter(|(series, _)| labels.iter().all(|label| series.contains(label)))
+        .filter_map(|(_, value)| value.parse::<f64>().ok())
+        .sum()
+}
+
+fn create_connection(port: u16) -> Result<mysql::Conn> {
+    let uri = &format!("mysql://127.0.0.1:{}?user=default", port);
+    let opts = mysql::Opts::from_url(uri).unwrap();
+    mysql::Conn::new(opts).map_err_to_code(ErrorCode::UnknownException, || "Connect error")
+}
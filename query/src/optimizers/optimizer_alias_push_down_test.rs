@@ -1,11 +1,11 @@
 // Copyright 2020 Datafuse Labs.
-// 
+//
 // Licensed under the Apache License, Version 2.0 (the "License");
 // you may not use this file except in compliance with the License.
 // You may obtain a copy of the License at
-// 
+//
 //     http://www.apache.org/licenses/LICENSE-2.0
-// 
+//
 // Unless required by applicable law or agreed to in writing, software
 // distributed under the License is distributed on an "AS IS" BASIS,
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
@@ -35,7 +35,7 @@ fn test_filter_alias_push_down_optimizer() -> Result<()> {
             Projection: (number + 1) as c1:UInt64, number as c2:UInt64\
             \n  Expression: (number + 1) as c1:UInt64, number as c2:UInt64 (Before Projection)\
             \n    Filter: ((((number + 1) + number) + 1) = 1)\
-            \n      ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]",
+            \n      ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]",
         },
         Test {
             name:"group-by-alias-push-down",
@@ -43,7 +43,7 @@ fn test_filter_alias_push_down_optimizer() -> Result<()> {
             expect: "\
             AggregatorFinal: groupBy=[[((number % 3) + 1) as c2]], aggr=[[max([(number + 1)]) as c1, ((number % 3) + 1) as c2]]\
             \n  AggregatorPartial: groupBy=[[((number % 3) + 1) as c2]], aggr=[[max([(number + 1)]) as c1, ((number % 3) + 1) as c2]]\
-            \n    ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]",
+            \n    ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]",
         },
         Test {
             name:"having-alias-push-down",
@@ -52,7 +52,7 @@ fn test_filter_alias_push_down_optimizer() -> Result<()> {
             Having: (c1 > 10)\
             \n  Projection: (number + 1) as c1:UInt64, ((number % 3) + 1) as c2:UInt16\
             \n    Expression: (number + 1) as c1:UInt64, ((number % 3) + 1) as c2:UInt16 (Before Projection)\
-            \n      ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]",
+            \n      ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10000, read_bytes: 80000]",
         },
         Test {
             name:"order-by-alias-push-down-now-work",
@@ -62,7 +62,7 @@ fn test_filter_alias_push_down_optimizer() -> Result<()> {
             \n  Sort: c2:UInt64\
             \n    Expression: c2:UInt64 (Before OrderBy)\
             \n      Expression: (number + 1) as c1:UInt64, ((number % 3) + 1) as c2:UInt16 (Before Projection)\
-            \n        ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
+            \n        ReadDataSource: scan table: system.numbers_mt, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
         },
     ];
 
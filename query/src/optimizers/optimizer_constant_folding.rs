@@ -31,10 +31,14 @@ use crate::optimizers::Optimizer;
 use crate::pipelines::transforms::ExpressionExecutor;
 use crate::sessions::DatabendQueryContextRef;
 
-pub struct ConstantFoldingOptimizer {}
+pub struct ConstantFoldingOptimizer {
+    ctx: DatabendQueryContextRef,
+}
 
 struct ConstantFoldingImpl {
     before_group_by_schema: Option<DataSchemaRef>,
+    // fold the expressions the way they are evaluated, see the strict_arithmetic setting
+    strict_arithmetic: bool,
 }
 
 impl ConstantFoldingImpl {
@@ -48,16 +52,21 @@ impl ConstantFoldingImpl {
             .any(|expr| !matches!(expr, Expression::Literal { .. }))
     }
 
-    fn rewrite_function<F>(op: &str, args: Expressions, name: String, f: F) -> Result<Expression>
-    where F: Fn(&str, Expressions) -> Expression {
+    fn rewrite_function<F>(
+        &self,
+        op: &str,
+        args: Expressions,
+        name: String,
+        f: F,
+    ) -> Result<Expression>
+    where
+        F: Fn(&str, Expressions) -> Expression,
+    {
         let function = FunctionFactory::get(op)?;
 
         if function.is_deterministic() && ConstantFoldingImpl::constants_arguments(&args) {
             let op = op.to_string();
-            return ConstantFoldingImpl::execute_expression(
-                Expression::ScalarFunction { op, args },
-                name,
-            );
+            return self.execute_expression(Expression::ScalarFunction { op, args }, name);
         }
 
         Ok(f(op, args))
@@ -81,24 +90,33 @@ impl ConstantFoldingImpl {
         Expression::BinaryExpression { op, left, right }
     }
 
-    fn expr_executor(schema: &DataSchemaRef, expr: Expression) -> Result<ExpressionExecutor> {
+    fn expr_executor(
+        &self,
+        schema: &DataSchemaRef,
+        expr: Expression,
+    ) -> Result<ExpressionExecutor> {
         let output_fields = vec![expr.to_data_field(schema)?];
         let output_schema = DataSchemaRefExt::create(output_fields);
-        ExpressionExecutor::try_create(
+        let executor = ExpressionExecutor::try_create(
             "Constant folding optimizer.",
             schema.clone(),
             output_schema,
             vec![expr],
             false,
-        )
+        )?;
+        Ok(executor.with_strict_arithmetic(self.strict_arithmetic))
     }
 
-    fn execute_expression(expression: Expression, origin_name: String) -> Result<Expression> {
+    fn execute_expression(
+        &self,
+        expression: Expression,
+        origin_name: String,
+    ) -> Result<Expression> {
         let input_fields = vec![DataField::new("_dummy", DataType::UInt8, false)];
         let input_schema = Arc::new(DataSchema::new(input_fields));
 
         let data_type = expression.to_data_type(&input_schema)?;
-        let expression_executor = Self::expr_executor(&input_schema, expression)?;
+        let expression_executor = self.expr_executor(&input_schema, expression)?;
         let dummy_columns = vec![DataColumn::Constant(DataValue::UInt8(Some(1)), 1)];
         let data_block = DataBlock::create(input_schema, dummy_columns);
         let executed_data_block = expression_executor.execute(&data_block)?;
@@ -142,12 +160,12 @@ impl PlanRewriter for ConstantFoldingImpl {
                     .collect::<Result<Vec<_>>>()?;
 
                 let origin_name = origin.column_name();
-                Self::rewrite_function(op, new_args, origin_name, Self::create_scalar_function)
+                self.rewrite_function(op, new_args, origin_name, Self::create_scalar_function)
             }
             Expression::UnaryExpression { op, expr } => {
                 let origin_name = origin.column_name();
                 let new_expr = vec![self.rewrite_expr(schema, expr)?];
-                Self::rewrite_function(op, new_expr, origin_name, Self::create_unary_expression)
+                self.rewrite_function(op, new_expr, origin_name, Self::create_unary_expression)
             }
             Expression::BinaryExpression { op, left, right } => {
                 let new_left = self.rewrite_expr(schema, left)?;
@@ -155,7 +173,7 @@ impl PlanRewriter for ConstantFoldingImpl {
 
                 let origin_name = origin.column_name();
                 let new_exprs = vec![new_left, new_right];
-                Self::rewrite_function(op, new_exprs, origin_name, Self::create_binary_expression)
+                self.rewrite_function(op, new_exprs, origin_name, Self::create_binary_expression)
             }
             Expression::Cast { expr, data_type } => {
                 let new_expr = self.rewrite_expr(schema, expr)?;
@@ -166,7 +184,7 @@ impl PlanRewriter for ConstantFoldingImpl {
                         data_type: data_type.clone(),
                     };
 
-                    return Self::execute_expression(optimize_expr, origin.column_name());
+                    return self.execute_expression(optimize_expr, origin.column_name());
                 }
 
                 Ok(Expression::Cast {
@@ -243,9 +261,10 @@ impl PlanRewriter for ConstantFoldingImpl {
 }
 
 impl ConstantFoldingImpl {
-    pub fn new() -> ConstantFoldingImpl {
+    pub fn new(strict_arithmetic: bool) -> ConstantFoldingImpl {
         ConstantFoldingImpl {
             before_group_by_schema: None,
+            strict_arithmetic,
         }
     }
 }
@@ -256,14 +275,15 @@ impl Optimizer for ConstantFoldingOptimizer {
     }
 
     fn optimize(&mut self, plan: &PlanNode) -> Result<PlanNode> {
-        let mut visitor = ConstantFoldingImpl::new();
+        let strict_arithmetic = self.ctx.get_settings().get_strict_arithmetic()? != 0;
+        let mut visitor = ConstantFoldingImpl::new(strict_arithmetic);
         visitor.rewrite_plan_node(plan)
     }
 }
 
 impl ConstantFoldingOptimizer {
-    pub fn create(_ctx: DatabendQueryContextRef) -> Self {
-        ConstantFoldingOptimizer {}
+    pub fn create(ctx: DatabendQueryContextRef) -> Self {
+        ConstantFoldingOptimizer { ctx }
     }
 }
 
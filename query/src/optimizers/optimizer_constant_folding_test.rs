@@ -14,9 +14,17 @@
 
 #[cfg(test)]
 mod tests {
+    use common_datablocks::pretty_format_blocks;
+    use common_datablocks::DataBlock;
     use common_exception::Result;
+    use common_planners::PlanNode;
+    use common_runtime::tokio;
+    use futures::TryStreamExt;
 
     use crate::optimizers::*;
+    use crate::pipelines::processors::PipelineBuilder;
+    use crate::sessions::DatabendQueryContextRef;
+    use crate::sql::PlanParser;
 
     #[test]
     fn test_constant_folding_optimizer() -> Result<()> {
@@ -34,7 +42,7 @@ mod tests {
                 expect: "\
                 Projection: ((1 + 2) + 3):UInt32\
                 \n  Expression: 6:UInt32 (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection left non const recursion",
@@ -42,7 +50,7 @@ mod tests {
                 expect: "\
                 Projection: (((dummy + 1) + 2) + 3):UInt64\
                 \n  Expression: (((dummy + 1) + 2) + 3):UInt64 (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection right non const recursion",
@@ -50,7 +58,7 @@ mod tests {
                 expect: "\
                 Projection: (((1 + 2) + 3) + dummy):UInt64\
                 \n  Expression: (6 + dummy):UInt64 (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection arithmetic const recursion",
@@ -58,7 +66,7 @@ mod tests {
                 expect: "\
                 Projection: ((1 + 2) + (3 / 3)):Float64\
                 \n  Expression: 4:Float64 (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection comparisons const recursion",
@@ -66,7 +74,7 @@ mod tests {
                 expect: "\
                 Projection: (((1 + 2) + 3) > 3):Boolean\
                 \n  Expression: true:Boolean (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection cast const recursion",
@@ -74,7 +82,7 @@ mod tests {
                 expect: "\
                 Projection: cast(1 as Int64):Int64\
                 \n  Expression: 1:Int64 (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection hash const recursion",
@@ -82,7 +90,7 @@ mod tests {
                 expect: "\
                 Projection: sipHash('test_string'):UInt64\
                 \n  Expression: 15735157695654173841:UInt64 (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection logics const recursion",
@@ -90,7 +98,7 @@ mod tests {
                 expect: "\
                 Projection: ((1 = 1) AND (2 > 1)):Boolean\
                 \n  Expression: true:Boolean (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection strings const recursion",
@@ -98,7 +106,7 @@ mod tests {
                 expect: "\
                 Projection: substring('1234567890', 3, 3):String\
                 \n  Expression: 345:String (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
             Test {
                 name: "Projection to type name const recursion",
@@ -106,7 +114,7 @@ mod tests {
                 expect: "\
                 Projection: toTypeName('1234567890'):String\
                 \n  Expression: String:String (Before Projection)\
-                \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+                \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             },
         ];
 
@@ -121,4 +129,75 @@ mod tests {
         }
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_constant_folding_arithmetic() -> Result<()> {
+        struct Test {
+            query: &'static str,
+            // the result with strict_arithmetic = 0 and 1, or the error code
+            expect: [&'static str; 2],
+        }
+
+        let tests = vec![
+            Test {
+                query: "SELECT 7 / 2",
+                expect: ["3.5", "3.5"],
+            },
+            Test {
+                query: "SELECT 7 / 0",
+                expect: ["NULL", "Code: 61"],
+            },
+            Test {
+                query: "SELECT 7 % 0",
+                expect: ["NULL", "Code: 61"],
+            },
+            Test {
+                query: "SELECT 7.0 / 0",
+                expect: ["inf", "inf"],
+            },
+            Test {
+                query: "SELECT 18446744073709551615 + 1",
+                expect: ["Code: 60", "Code: 60"],
+            },
+            Test {
+                query: "SELECT -9223372036854775807 - 2",
+                expect: ["Code: 60", "Code: 60"],
+            },
+        ];
+
+        async fn execute(ctx: DatabendQueryContextRef, plan: &PlanNode) -> Result<Vec<DataBlock>> {
+            let mut pipeline = PipelineBuilder::create(ctx).build(plan)?;
+            let stream = pipeline.execute().await?;
+            stream.try_collect::<Vec<_>>().await
+        }
+
+        fn outcome(result: Result<Vec<DataBlock>>) -> Result<String> {
+            match result {
+                Ok(blocks) => {
+                    let value = blocks[0].column(0).to_values()?.remove(0);
+                    Ok(format!("{}", value))
+                }
+                Err(e) => Ok(format!("Code: {}", e.code())),
+            }
+        }
+
+        for test in tests {
+            for (strict, expect) in test.expect.iter().enumerate() {
+                let ctx = crate::tests::try_create_context()?;
+                ctx.get_settings().set_strict_arithmetic(strict as u64)?;
+                let plan = PlanParser::create(ctx.clone()).build_from_sql(test.query)?;
+
+                // The folded expression is the same as the one evaluated at runtime.
+                let runtime = outcome(execute(ctx.clone(), &plan).await)?;
+                let folded = match ConstantFoldingOptimizer::create(ctx.clone()).optimize(&plan) {
+                    Ok(optimized) => outcome(execute(ctx.clone(), &optimized).await)?,
+                    Err(e) => format!("Code: {}", e.code()),
+                };
+
+                assert_eq!(expect, &runtime, "{}, strict: {}", test.query, strict);
+                assert_eq!(expect, &folded, "{}, strict: {}", test.query, strict);
+            }
+        }
+        Ok(())
+    }
 }
@@ -132,6 +132,8 @@ impl PlanRewriter for ProjectionPushDownImpl {
                     description: plan.description.to_string(),
                     scan_plan: plan.scan_plan.clone(),
                     remote: plan.remote,
+                    partitions_total: plan.partitions_total,
+                    partitions_pruned_by_bloom: plan.partitions_pruned_by_bloom,
                 })
             })
     }
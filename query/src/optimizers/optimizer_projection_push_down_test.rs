@@ -75,7 +75,7 @@ fn test_projection_push_down_optimizer_group_by() -> Result<()> {
         Projection: max(value) as c1:String, name as c2:String\
         \n  AggregatorFinal: groupBy=[[name]], aggr=[[max(value)]]\
         \n    AggregatorPartial: groupBy=[[name]], aggr=[[max(value)]]\
-        \n      ReadDataSource: scan partitions: [1], scan schema: [name:String, value:String], statistics: [read_rows: 0, read_bytes: 0]";
+        \n      ReadDataSource: scan table: system.settings, scan partitions: [1], scan schema: [name:String, value:String], statistics: [read_rows: 0, read_bytes: 0]";
 
     let actual = format!("{:?}", optimized);
     assert_eq!(expect, actual);
@@ -110,6 +110,8 @@ fn test_projection_push_down_optimizer_2() -> Result<()> {
         ),
         scan_plan: Arc::new(ScanPlan::empty()),
         remote: false,
+        partitions_total: None,
+        partitions_pruned_by_bloom: 0,
     });
 
     let filter_plan = PlanBuilder::from(&source_plan)
@@ -128,7 +130,7 @@ fn test_projection_push_down_optimizer_2() -> Result<()> {
     let expect = "\
         Projection: a:String\
         \n  Filter: ((a > 6) and (b <= 10))\
-        \n    ReadDataSource: scan partitions: [8], scan schema: [a:String, b:String], statistics: [read_rows: 10000, read_bytes: 80000]";
+        \n    ReadDataSource: scan table: system.test, scan partitions: [8], scan schema: [a:String, b:String], statistics: [read_rows: 10000, read_bytes: 80000]";
     let actual = format!("{:?}", optimized);
     assert_eq!(expect, actual);
 
@@ -167,6 +169,8 @@ fn test_projection_push_down_optimizer_3() -> Result<()> {
         ),
         scan_plan: Arc::new(ScanPlan::empty()),
         remote: false,
+        partitions_total: None,
+        partitions_pruned_by_bloom: 0,
     });
 
     let group_exprs = &[col("a"), col("c")];
@@ -193,7 +197,7 @@ fn test_projection_push_down_optimizer_3() -> Result<()> {
     \n        AggregatorFinal: groupBy=[[a, c]], aggr=[[]]\
     \n          AggregatorPartial: groupBy=[[a, c]], aggr=[[]]\
     \n            Filter: (b = 10)\
-    \n              ReadDataSource: scan partitions: [8], scan schema: [a:String, b:String, c:String], statistics: [read_rows: 10000, read_bytes: 80000]";
+    \n              ReadDataSource: scan table: system.test, scan partitions: [8], scan schema: [a:String, b:String, c:String], statistics: [read_rows: 10000, read_bytes: 80000]";
 
     let actual = format!("{:?}", optimized);
     assert_eq!(expect, actual);
@@ -213,7 +217,7 @@ fn test_projection_push_down_optimizer_4() -> Result<()> {
 
     let expect = "Projection: substring(value, 1, 3) as c1:String\
                         \n  Expression: substring(value, 1, 3):String (Before Projection)\
-                        \n    ReadDataSource: scan partitions: [1], scan schema: [value:String], statistics: [read_rows: 0, read_bytes: 0]";
+                        \n    ReadDataSource: scan table: system.settings, scan partitions: [1], scan schema: [value:String], statistics: [read_rows: 0, read_bytes: 0]";
 
     let actual = format!("{:?}", optimized);
     assert_eq!(expect, actual);
@@ -14,12 +14,14 @@
 
 use std::sync::Arc;
 
+use common_datavalues::is_integer;
 use common_datavalues::DataSchemaRef;
 use common_datavalues::DataValue;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::AggregatorFinalPlan;
 use common_planners::AggregatorPartialPlan;
+use common_planners::AggregatorTopN;
 use common_planners::BroadcastPlan;
 use common_planners::Expression;
 use common_planners::LimitByPlan;
@@ -50,6 +52,8 @@ struct ScattersOptimizerImpl {
     ctx: DatabendQueryContextRef,
     running_mode: RunningMode,
     before_group_by_schema: Option<DataSchemaRef>,
+    // The top groups enough for the limit above the aggregation.
+    top_n: Option<AggregatorTopN>,
 
     // temporary node
     input: Option<Arc<PlanNode>>,
@@ -61,6 +65,7 @@ impl ScattersOptimizerImpl {
             ctx,
             running_mode: RunningMode::Standalone,
             before_group_by_schema: None,
+            top_n: None,
             input: None,
         }
     }
@@ -79,25 +84,40 @@ impl ScattersOptimizerImpl {
         }
     }
 
-    fn cluster_aggregate_with_key(&mut self, plan: &AggregatorPartialPlan) -> Result<PlanNode> {
+    fn cluster_aggregate_with_key(
+        &mut self,
+        plan: &AggregatorPartialPlan,
+        top_n: Option<AggregatorTopN>,
+    ) -> Result<PlanNode> {
         // Keep running in cluster mode
         self.running_mode = RunningMode::Cluster;
 
         match self.input.take() {
             None => Err(ErrorCode::LogicalError("Cluster aggr input is None")),
-            Some(input) => Self::normal_shuffle_stage(
-                "_group_by_key",
-                PlanBuilder::from(input.as_ref())
+            Some(input) => {
+                let partial = match PlanBuilder::from(input.as_ref())
                     .aggregate_partial(&plan.aggr_expr, &plan.group_expr)?
-                    .build()?,
-            ),
+                    .build()?
+                {
+                    PlanNode::AggregatorPartial(partial) => {
+                        PlanNode::AggregatorPartial(partial.with_top_n(top_n))
+                    }
+                    other => other,
+                };
+
+                Self::normal_shuffle_stage("_group_by_key", partial)
+            }
         }
     }
 
-    fn cluster_aggregate(&mut self, plan: &AggregatorPartialPlan) -> Result<PlanNode> {
+    fn cluster_aggregate(
+        &mut self,
+        plan: &AggregatorPartialPlan,
+        top_n: Option<AggregatorTopN>,
+    ) -> Result<PlanNode> {
         match plan.group_expr.len() {
             0 => self.cluster_aggregate_without_key(plan),
-            _ => self.cluster_aggregate_with_key(plan),
+            _ => self.cluster_aggregate_with_key(plan, top_n),
         }
     }
 
@@ -175,6 +195,91 @@ impl ScattersOptimizerImpl {
         }
     }
 
+    /// The final top N of `Limit -> [Projection] -> Sort -> [Expression] -> AggregatorFinal`
+    /// only needs the top groups of each node, if the groups are sorted by one aggregate:
+    /// max of integers in descending order, or min of integers in ascending order.
+    /// Otherwise all the groups are sent.
+    ///
+    /// The global max of a group is the max of one node, where fewer than N groups are above it,
+    /// so a group of the global top N is in the local top N of that node. It does not hold for
+    /// count or sum: a group second on every node may be the first of all.
+    fn aggregate_top_n(&self, plan: &LimitPlan) -> Result<Option<AggregatorTopN>> {
+        let factor = self.ctx.get_settings().get_aggregate_top_n_factor()? as usize;
+        let limit = match plan.n {
+            Some(n) if factor > 0 && n > 0 => n + plan.offset,
+            _ => return Ok(None),
+        };
+
+        let mut node = plan.input.as_ref();
+        if let PlanNode::Projection(projection) = node {
+            node = projection.input.as_ref();
+        }
+
+        let (column, asc, mut node) = match node {
+            PlanNode::Sort(sort) if sort.order_by.len() == 1 => match &sort.order_by[0] {
+                Expression::Sort { expr, asc, .. } => match expr.as_ref() {
+                    Expression::Column(column) => (column, *asc, sort.input.as_ref()),
+                    _ => return Ok(None),
+                },
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        // The aggregate columns are passed through the expressions.
+        if let PlanNode::Expression(expression) = node {
+            node = expression.input.as_ref();
+        }
+
+        let partial = match node {
+            PlanNode::AggregatorFinal(aggr_final) => match aggr_final.input.as_ref() {
+                PlanNode::AggregatorPartial(partial) if !partial.group_expr.is_empty() => partial,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        let schema_before_group_by = partial.input.schema();
+        for (aggr_index, aggr_expr) in partial.aggr_expr.iter().enumerate() {
+            if &aggr_expr.column_name() == column {
+                return match Self::is_top_n_aggregate(aggr_expr, asc, &schema_before_group_by)? {
+                    false => Ok(None),
+                    true => Ok(Some(AggregatorTopN {
+                        aggr_index,
+                        asc,
+                        limit,
+                        keep: limit.saturating_mul(factor),
+                    })),
+                };
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn is_top_n_aggregate(expr: &Expression, asc: bool, schema: &DataSchemaRef) -> Result<bool> {
+        match expr {
+            Expression::AggregateFunction {
+                op,
+                distinct: false,
+                params,
+                args,
+            } if params.is_empty() => {
+                let arg_type = match args.first() {
+                    None => None,
+                    Some(arg) => Some(arg.to_data_type(schema)?),
+                };
+
+                Ok(match (op.to_lowercase().as_str(), asc, arg_type) {
+                    ("max", false, Some(arg_type)) => is_integer(&arg_type),
+                    ("min", true, Some(arg_type)) => is_integer(&arg_type),
+                    _ => false,
+                })
+            }
+            _ => Ok(false),
+        }
+    }
+
     fn convergent_shuffle_stage_builder(input: Arc<PlanNode>) -> PlanBuilder {
         PlanBuilder::from(&PlanNode::Stage(StagePlan {
             kind: StageKind::Convergent,
@@ -230,13 +335,14 @@ impl PlanRewriter for ScattersOptimizerImpl {
     }
 
     fn rewrite_aggregate_partial(&mut self, plan: &AggregatorPartialPlan) -> Result<PlanNode> {
+        let top_n = self.top_n.take();
         let new_input = Arc::new(self.rewrite_plan_node(&plan.input)?);
 
         self.input = Some(new_input.clone());
         self.before_group_by_schema = Some(new_input.schema());
 
         match self.running_mode {
-            RunningMode::Cluster => self.cluster_aggregate(plan),
+            RunningMode::Cluster => self.cluster_aggregate(plan, top_n),
             RunningMode::Standalone => self.standalone_aggregate(plan),
         }
     }
@@ -262,6 +368,7 @@ impl PlanRewriter for ScattersOptimizerImpl {
     }
 
     fn rewrite_limit(&mut self, plan: &LimitPlan) -> Result<PlanNode> {
+        self.top_n = self.aggregate_top_n(plan)?;
         self.input = Some(Arc::new(self.rewrite_plan_node(plan.input.as_ref())?));
 
         match self.running_mode {
@@ -310,6 +417,11 @@ impl Optimizer for ScattersOptimizer {
             return Ok(plan.clone());
         }
 
+        if self.ctx.get_query_hints().no_distributed {
+            // Kept on this node by the hint of the query.
+            return Ok(plan.clone());
+        }
+
         let mut optimizer_impl = ScattersOptimizerImpl::create(self.ctx.clone());
         let rewrite_plan = optimizer_impl.rewrite_plan_node(plan)?;
 
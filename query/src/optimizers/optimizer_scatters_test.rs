@@ -37,14 +37,14 @@ async fn test_scatter_optimizer() -> Result<()> {
             expect: "\
             Projection: 1:UInt8\
             \n  Expression: 1:UInt8 (Before Projection)\
-            \n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+            \n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
         },
         Test {
             name: "Small local table query",
             query: "SELECT number FROM numbers_local(100)",
             expect: "\
             Projection: number:UInt64\
-            \n  ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
+            \n  ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
         },
         Test {
             name: "Small local table aggregate query with group by key",
@@ -54,7 +54,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n  AggregatorFinal: groupBy=[[(number % 3)]], aggr=[[SUM(number)]]\
             \n    AggregatorPartial: groupBy=[[(number % 3)]], aggr=[[SUM(number)]]\
             \n      Expression: (number % 3):UInt8, number:UInt64 (Before GroupBy)\
-            \n        ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
+            \n        ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
         },
         Test {
             name: "Small local table aggregate query with group by keys",
@@ -64,7 +64,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n  AggregatorFinal: groupBy=[[(number % 3), (number % 2)]], aggr=[[SUM(number)]]\
             \n    AggregatorPartial: groupBy=[[(number % 3), (number % 2)]], aggr=[[SUM(number)]]\
             \n      Expression: (number % 3):UInt8, (number % 2):UInt8, number:UInt64 (Before GroupBy)\
-            \n        ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
+            \n        ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
         },
         Test {
             name: "Small local table aggregate query without group by",
@@ -73,14 +73,14 @@ async fn test_scatter_optimizer() -> Result<()> {
             Projection: SUM(number):UInt64\
             \n  AggregatorFinal: groupBy=[[]], aggr=[[SUM(number)]]\
             \n    AggregatorPartial: groupBy=[[]], aggr=[[SUM(number)]]\
-            \n      ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
+            \n      ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100, read_bytes: 800]",
         },
         Test {
             name: "Large local table query",
             query: "SELECT number FROM numbers_local(100000000)",
             expect: "\
             Projection: number:UInt64\
-            \n  ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n  ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Large local table aggregate query with group by key",
@@ -90,7 +90,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n  AggregatorFinal: groupBy=[[(number % 3)]], aggr=[[SUM(number)]]\
             \n    AggregatorPartial: groupBy=[[(number % 3)]], aggr=[[SUM(number)]]\
             \n      Expression: (number % 3):UInt8, number:UInt64 (Before GroupBy)\
-            \n        ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n        ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Large local table aggregate query with group by keys",
@@ -100,7 +100,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n  AggregatorFinal: groupBy=[[(number % 3), (number % 2)]], aggr=[[SUM(number)]]\
             \n    AggregatorPartial: groupBy=[[(number % 3), (number % 2)]], aggr=[[SUM(number)]]\
             \n      Expression: (number % 3):UInt8, (number % 2):UInt8, number:UInt64 (Before GroupBy)\
-            \n        ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n        ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Large local table aggregate query without group by",
@@ -109,7 +109,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             Projection: SUM(number):UInt64\
             \n  AggregatorFinal: groupBy=[[]], aggr=[[SUM(number)]]\
             \n    AggregatorPartial: groupBy=[[]], aggr=[[SUM(number)]]\
-            \n      ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n      ReadDataSource: scan table: system.numbers_local, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Large cluster table query",
@@ -117,7 +117,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             expect: "\
             RedistributeStage[expr: 0]\
             \n  Projection: number:UInt64\
-            \n    ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n    ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Large cluster table aggregate query with group by key",
@@ -129,7 +129,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n      RedistributeStage[expr: sipHash(_group_by_key)]\
             \n        AggregatorPartial: groupBy=[[(number % 3)]], aggr=[[SUM(number)]]\
             \n          Expression: (number % 3):UInt8, number:UInt64 (Before GroupBy)\
-            \n            ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n            ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Large cluster table aggregate query with group by keys",
@@ -141,7 +141,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n      RedistributeStage[expr: sipHash(_group_by_key)]\
             \n        AggregatorPartial: groupBy=[[(number % 3), (number % 2)]], aggr=[[SUM(number)]]\
             \n          Expression: (number % 3):UInt8, (number % 2):UInt8, number:UInt64 (Before GroupBy)\
-            \n            ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n            ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Large cluster table aggregate query without group by",
@@ -151,7 +151,7 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n  AggregatorFinal: groupBy=[[]], aggr=[[SUM(number)]]\
             \n    RedistributeStage[expr: 0]\
             \n      AggregatorPartial: groupBy=[[]], aggr=[[SUM(number)]]\
-            \n        ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
+            \n        ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 100000000, read_bytes: 800000000]",
         },
         Test {
             name: "Standalone query with standalone subquery",
@@ -161,8 +161,8 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n  Filter: exists(subquery(_subquery_1))\
             \n    Create sub queries sets: [_subquery_1]\
             \n      Projection: number:UInt64\
-            \n        ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
-            \n      ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
+            \n        ReadDataSource: scan table: system.numbers_local, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
+            \n      ReadDataSource: scan table: system.numbers_local, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
         },
         Test {
             name: "Standalone query with cluster subquery",
@@ -172,8 +172,8 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n    Create sub queries sets: [_subquery_1]\
             \n      RedistributeStage[expr: 0]\
             \n        Projection: number:UInt64\
-            \n          ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
-            \n      ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
+            \n          ReadDataSource: scan table: system.numbers, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
+            \n      ReadDataSource: scan table: system.numbers_local, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
         },
         Test {
             name: "Cluster query with standalone subquery",
@@ -185,8 +185,8 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n      Create sub queries sets: [_subquery_1]\
             \n        Broadcast in cluster\
             \n          Projection: number:UInt64\
-            \n            ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
-            \n        ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
+            \n            ReadDataSource: scan table: system.numbers_local, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
+            \n        ReadDataSource: scan table: system.numbers, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
         },
         Test {
             name: "Cluster query with cluster subquery",
@@ -198,8 +198,8 @@ async fn test_scatter_optimizer() -> Result<()> {
             \n      Create sub queries sets: [_subquery_1]\
             \n        Broadcast in cluster\
             \n          Projection: number:UInt64\
-            \n            ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
-            \n        ReadDataSource: scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
+            \n            ReadDataSource: scan table: system.numbers, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]\
+            \n        ReadDataSource: scan table: system.numbers, scan partitions: [1], scan schema: [number:UInt64], statistics: [read_rows: 1, read_bytes: 8]",
         },
     ];
 
@@ -216,3 +216,102 @@ async fn test_scatter_optimizer() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_scatter_optimizer_aggregate_top_n() -> Result<()> {
+    struct Test {
+        name: &'static str,
+        query: &'static str,
+        // None if all the groups are sent.
+        expect_top_n: Option<&'static str>,
+    }
+
+    let tests = vec![
+        Test {
+            name: "Order by max desc",
+            query: "SELECT number % 1000 AS k, MAX(number) AS m FROM numbers(100000000) GROUP BY k ORDER BY m DESC LIMIT 10",
+            expect_top_n: Some("topN=[MAX(number) DESC, limit: 10, keep: 30]"),
+        },
+        Test {
+            name: "Order by max desc with offset",
+            query: "SELECT MAX(number) FROM numbers(100000000) GROUP BY number % 1000 ORDER BY MAX(number) DESC LIMIT 10 OFFSET 5",
+            expect_top_n: Some("topN=[MAX(number) DESC, limit: 15, keep: 45]"),
+        },
+        Test {
+            name: "Order by count desc",
+            query: "SELECT number % 1000 AS k, COUNT(number) AS c FROM numbers(100000000) GROUP BY k ORDER BY c DESC LIMIT 10",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Order by sum desc",
+            query: "SELECT SUM(number) FROM numbers(100000000) GROUP BY number % 1000 ORDER BY SUM(number) DESC LIMIT 10",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Order by min asc",
+            query: "SELECT MIN(number) AS m FROM numbers(100000000) GROUP BY number % 1000 ORDER BY m LIMIT 10",
+            expect_top_n: Some("topN=[MIN(number) ASC, limit: 10, keep: 30]"),
+        },
+        Test {
+            name: "Order by avg",
+            query: "SELECT AVG(number) AS a FROM numbers(100000000) GROUP BY number % 1000 ORDER BY a DESC LIMIT 10",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Order by max asc",
+            query: "SELECT MAX(number) AS m FROM numbers(100000000) GROUP BY number % 1000 ORDER BY m LIMIT 10",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Order by two keys",
+            query: "SELECT MAX(number) AS c FROM numbers(100000000) GROUP BY number % 1000 ORDER BY c DESC, number % 1000 LIMIT 10",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Order by expression of aggregate",
+            query: "SELECT MAX(number) + 1 AS c FROM numbers(100000000) GROUP BY number % 1000 ORDER BY c DESC LIMIT 10",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Having",
+            query: "SELECT MAX(number) AS c FROM numbers(100000000) GROUP BY number % 1000 HAVING c > 1 ORDER BY c DESC LIMIT 10",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Without limit",
+            query: "SELECT MAX(number) AS c FROM numbers(100000000) GROUP BY number % 1000 ORDER BY c DESC",
+            expect_top_n: None,
+        },
+        Test {
+            name: "Local table",
+            query: "SELECT MAX(number) AS c FROM numbers_local(100) GROUP BY number % 10 ORDER BY c DESC LIMIT 3",
+            expect_top_n: None,
+        },
+    ];
+
+    for test in tests {
+        let ctx =
+            try_create_cluster_context(&[ClusterNode::create("Github", 1, "www.github.com:9090")])?;
+
+        let plan = PlanParser::create(ctx.clone()).build_from_sql(test.query)?;
+        let mut optimizer = ScattersOptimizer::create(ctx);
+        let actual = format!("{:?}", optimizer.optimize(&plan)?);
+        match test.expect_top_n {
+            None => assert!(!actual.contains("topN="), "{}: {}", test.name, actual),
+            Some(top_n) => assert!(actual.contains(top_n), "{}: {}", test.name, actual),
+        }
+    }
+
+    // Disabled by the factor 0.
+    let ctx =
+        try_create_cluster_context(&[ClusterNode::create("Github", 1, "www.github.com:9090")])?;
+    ctx.get_settings().set_aggregate_top_n_factor(0)?;
+    let plan = PlanParser::create(ctx.clone()).build_from_sql(
+        "SELECT MAX(number) AS c FROM numbers(100000000) GROUP BY number % 1000 ORDER BY c DESC LIMIT 10",
+    )?;
+    let mut optimizer = ScattersOptimizer::create(ctx);
+    let actual = format!("{:?}", optimizer.optimize(&plan)?);
+    assert!(!actual.contains("topN="), "{}", actual);
+
+    Ok(())
+}
@@ -53,6 +53,8 @@ mod tests {
             ),
             scan_plan: Arc::new(ScanPlan::empty()),
             remote: false,
+            partitions_total: None,
+            partitions_pruned_by_bloom: 0,
         });
 
         let aggr_expr = Expression::AggregateFunction {
@@ -80,7 +82,7 @@ mod tests {
         \n  AggregatorFinal: groupBy=[[]], aggr=[[count(0)]]\
         \n    Projection: 904e as count(0):String\
         \n      Expression: 904e:String (Exact Statistics)\
-        \n        ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]";
+        \n        ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]";
         let actual = format!("{:?}", optimized);
         assert_eq!(expect, actual);
         Ok(())
@@ -44,6 +44,7 @@ use crate::pipelines::transforms::GroupByFinalTransform;
 use crate::pipelines::transforms::GroupByPartialTransform;
 use crate::pipelines::transforms::LimitByTransform;
 use crate::pipelines::transforms::LimitTransform;
+use crate::pipelines::transforms::OrderedRemoteTransform;
 use crate::pipelines::transforms::ProjectionTransform;
 use crate::pipelines::transforms::RemoteTransform;
 use crate::pipelines::transforms::SortMergeTransform;
@@ -113,10 +114,29 @@ impl PipelineBuilder {
 
     fn visit_remote(&self, plan: &RemotePlan) -> Result<Pipeline> {
         let mut pipeline = Pipeline::create(self.ctx.clone());
+        let compression = self.ctx.get_settings().get_flight_compression_codec()?;
+        let dictionary_encoding = self.ctx.get_settings().get_flight_dictionary_encoding()? != 0;
+
+        if plan.preserve_order {
+            let flight_ticket =
+                FlightTicket::stream(&plan.query_id, &plan.stage_id, &plan.stream_id)
+                    .with_compression(compression)
+                    .with_dictionary_encoding(dictionary_encoding);
+
+            pipeline.add_source(Arc::new(OrderedRemoteTransform::try_create(
+                flight_ticket,
+                self.ctx.clone(),
+                /* fetch_nodes_name */ plan.fetch_nodes.clone(),
+                /* fetch_stream_schema */ plan.schema.clone(),
+            )?))?;
+            return Ok(pipeline);
+        }
 
         for fetch_node in &plan.fetch_nodes {
             let flight_ticket =
-                FlightTicket::stream(&plan.query_id, &plan.stage_id, &plan.stream_id);
+                FlightTicket::stream(&plan.query_id, &plan.stage_id, &plan.stream_id)
+                    .with_compression(compression)
+                    .with_dictionary_encoding(dictionary_encoding);
 
             pipeline.add_source(Arc::new(RemoteTransform::try_create(
                 flight_ticket,
@@ -131,24 +151,32 @@ impl PipelineBuilder {
 
     fn visit_expression(&mut self, plan: &ExpressionPlan) -> Result<Pipeline> {
         let mut pipeline = self.visit(&*plan.input)?;
+        let strict_arithmetic = self.ctx.get_settings().get_strict_arithmetic()? != 0;
         pipeline.add_simple_transform(|| {
-            Ok(Box::new(ExpressionTransform::try_create(
-                plan.input.schema(),
-                plan.schema.clone(),
-                plan.exprs.clone(),
-            )?))
+            Ok(Box::new(
+                ExpressionTransform::try_create(
+                    plan.input.schema(),
+                    plan.schema.clone(),
+                    plan.exprs.clone(),
+                )?
+                .with_strict_arithmetic(strict_arithmetic),
+            ))
         })?;
         Ok(pipeline)
     }
 
     fn visit_projection(&mut self, node: &ProjectionPlan) -> Result<Pipeline> {
         let mut pipeline = self.visit(&*node.input)?;
+        let strict_arithmetic = self.ctx.get_settings().get_strict_arithmetic()? != 0;
         pipeline.add_simple_transform(|| {
-            Ok(Box::new(ProjectionTransform::try_create(
-                node.input.schema(),
-                node.schema(),
-                node.expr.clone(),
-            )?))
+            Ok(Box::new(
+                ProjectionTransform::try_create(
+                    node.input.schema(),
+                    node.schema(),
+                    node.expr.clone(),
+                )?
+                .with_strict_arithmetic(strict_arithmetic),
+            ))
         })?;
         Ok(pipeline)
     }
@@ -166,12 +194,16 @@ impl PipelineBuilder {
             })?;
         } else {
             pipeline.add_simple_transform(|| {
-                Ok(Box::new(GroupByPartialTransform::create(
-                    node.schema(),
-                    node.input.schema(),
-                    node.aggr_expr.clone(),
-                    node.group_expr.clone(),
-                )))
+                Ok(Box::new(
+                    GroupByPartialTransform::create(
+                        self.ctx.clone(),
+                        node.schema(),
+                        node.input.schema(),
+                        node.aggr_expr.clone(),
+                        node.group_expr.clone(),
+                    )
+                    .with_top_n(node.top_n.clone()),
+                ))
             })?;
         }
         Ok(pipeline)
@@ -200,31 +232,31 @@ impl PipelineBuilder {
                     node.group_expr.clone(),
                 )))
             })?;
-            pipeline.mixed_processor(self.ctx.get_settings().get_max_threads()? as usize)?;
+            pipeline.mixed_processor(self.ctx.get_max_threads()? as usize)?;
         }
         Ok(pipeline)
     }
 
     fn visit_filter(&mut self, node: &FilterPlan) -> Result<Pipeline> {
         let mut pipeline = self.visit(&*node.input)?;
+        let strict_arithmetic = self.ctx.get_settings().get_strict_arithmetic()? != 0;
         pipeline.add_simple_transform(|| {
-            Ok(Box::new(FilterTransform::try_create(
-                node.schema(),
-                node.predicate.clone(),
-                false,
-            )?))
+            Ok(Box::new(
+                FilterTransform::try_create(node.schema(), node.predicate.clone(), false)?
+                    .with_strict_arithmetic(strict_arithmetic),
+            ))
         })?;
         Ok(pipeline)
     }
 
     fn visit_having(&mut self, node: &HavingPlan) -> Result<Pipeline> {
         let mut pipeline = self.visit(&*node.input)?;
+        let strict_arithmetic = self.ctx.get_settings().get_strict_arithmetic()? != 0;
         pipeline.add_simple_transform(|| {
-            Ok(Box::new(FilterTransform::try_create(
-                node.schema(),
-                node.predicate.clone(),
-                true,
-            )?))
+            Ok(Box::new(
+                FilterTransform::try_create(node.schema(), node.predicate.clone(), true)?
+                    .with_strict_arithmetic(strict_arithmetic),
+            ))
         })?;
         Ok(pipeline)
     }
@@ -248,6 +280,7 @@ impl PipelineBuilder {
         // processor 3: [sorted blocks ...] ---> merge to one sorted block
         pipeline.add_simple_transform(|| {
             Ok(Box::new(SortMergeTransform::try_create(
+                self.ctx.clone(),
                 plan.schema(),
                 plan.order_by.clone(),
                 self.limit,
@@ -263,6 +296,7 @@ impl PipelineBuilder {
             pipeline.merge_processor()?;
             pipeline.add_simple_transform(|| {
                 Ok(Box::new(SortMergeTransform::try_create(
+                    self.ctx.clone(),
                     plan.schema(),
                     plan.order_by.clone(),
                     self.limit,
@@ -300,7 +334,7 @@ impl PipelineBuilder {
         self.ctx.try_set_partitions(plan.parts.clone())?;
 
         let mut pipeline = Pipeline::create(self.ctx.clone());
-        let max_threads = self.ctx.get_settings().get_max_threads()? as usize;
+        let max_threads = self.ctx.get_max_threads()? as usize;
         let max_threads = std::cmp::min(max_threads, plan.parts.len());
         let workers = std::cmp::max(max_threads, 1);
 
@@ -18,6 +18,7 @@ use std::sync::Arc;
 use common_datablocks::DataBlock;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_runtime::tokio;
 use common_runtime::tokio::sync::mpsc;
 use common_streams::SendableDataBlockStream;
 use log::error;
@@ -83,7 +84,17 @@ impl Processor for MergeProcessor {
                             Ok(stream) => stream,
                         };
 
-                        while let Some(item) = stream.next().await {
+                        loop {
+                            // Stop pulling data as soon as the receiver is dropped, e.g. the LIMIT is reached,
+                            // instead of waiting for the next block of the input.
+                            let item = tokio::select! {
+                                _ = sender.closed() => return,
+                                item = stream.next() => match item {
+                                    None => return,
+                                    Some(item) => item,
+                                },
+                            };
+
                             match item {
                                 Ok(item) => {
                                     if let Err(error) = sender.send(Ok(item)).await {
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Reverse;
+
 use common_datablocks::DataBlock;
 use common_datablocks::HashMethod;
 use common_datavalues::arrays::StringArrayBuilder;
@@ -20,10 +22,12 @@ use common_datavalues::prelude::IntoSeries;
 use common_datavalues::prelude::Series;
 use common_datavalues::DataSchemaRef;
 use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataValue;
 use common_exception::Result;
 use common_functions::aggregates::StateAddr;
 use common_functions::aggregates::StateAddrs;
 use common_io::prelude::BytesMut;
+use common_planners::AggregatorTopN;
 use common_streams::DataBlockStream;
 use common_streams::SendableDataBlockStream;
 use futures::StreamExt;
@@ -35,6 +39,9 @@ use crate::pipelines::transforms::group_by::aggregator_state::AggregatorState;
 use crate::pipelines::transforms::group_by::aggregator_state_entity::StateEntity;
 use crate::pipelines::transforms::group_by::PolymorphicKeysHelper;
 
+type GroupEntity<Method> =
+    <<Method as PolymorphicKeysHelper<Method>>::State as AggregatorState<Method>>::Entity;
+
 pub struct Aggregator<Method: HashMethod> {
     method: Method,
     params: AggregatorParamsRef,
@@ -181,13 +188,89 @@ impl<Method: HashMethod + PolymorphicKeysHelper<Method>> Aggregator<Method> {
         schema: DataSchemaRef,
     ) -> Result<SendableDataBlockStream> {
         if groups.len() == 0 {
-            return Ok(Box::pin(DataBlockStream::create(
-                DataSchemaRefExt::create(vec![]),
-                None,
-                vec![],
-            )));
+            return Ok(Self::empty_finalized());
+        }
+
+        self.finalize_groups(groups.iter(), groups.len(), schema)
+    }
+
+    /// Finalize only the top groups ranked by one of the aggregates, the groups tied with the last
+    /// one kept and the groups of NULL values are kept too.
+    /// Returns the stream and how many groups are pruned.
+    #[inline(never)]
+    pub fn aggregate_finalized_top_n(
+        &self,
+        groups: &Method::State,
+        schema: DataSchemaRef,
+        top_n: &AggregatorTopN,
+    ) -> Result<(SendableDataBlockStream, usize)> {
+        if groups.len() <= top_n.keep || top_n.keep == 0 {
+            return Ok((self.aggregate_finalized(groups, schema)?, 0));
+        }
+
+        let aggregator_params = self.params.as_ref();
+        let func = &aggregator_params.aggregate_functions[top_n.aggr_index];
+        let offset = aggregator_params.offsets_aggregate_states[top_n.aggr_index];
+
+        let mut ranked = Vec::with_capacity(groups.len());
+        let mut kept = Vec::with_capacity(top_n.keep);
+        for group_entity in groups.iter() {
+            let place: StateAddr = (*group_entity.get_state_value()).into();
+            match Self::rank_value(&func.merge_result(place.next(offset))?)? {
+                None => kept.push(group_entity),
+                Some(value) => ranked.push((value, group_entity)),
+            }
+        }
+
+        if ranked.len() > top_n.keep {
+            match top_n.asc {
+                true => ranked.select_nth_unstable_by_key(top_n.keep - 1, |(v, _)| *v),
+                false => ranked.select_nth_unstable_by_key(top_n.keep - 1, |(v, _)| Reverse(*v)),
+            };
+
+            let boundary = ranked[top_n.keep - 1].0;
+            let (head, tail) = ranked.split_at(top_n.keep);
+            kept.extend(head.iter().map(|(_, entity)| *entity));
+            kept.extend(
+                tail.iter()
+                    .filter(|(v, _)| *v == boundary)
+                    .map(|(_, entity)| *entity),
+            );
+        } else {
+            kept.extend(ranked.iter().map(|(_, entity)| *entity));
         }
 
+        let pruned = groups.len() - kept.len();
+        let stream = self.finalize_groups(kept.iter().copied(), kept.len(), schema)?;
+        Ok((stream, pruned))
+    }
+
+    /// The aggregates of min/max on integers, NULL is not ranked.
+    fn rank_value(value: &DataValue) -> Result<Option<i128>> {
+        match value {
+            _ if value.is_null() => Ok(None),
+            DataValue::Int8(_)
+            | DataValue::Int16(_)
+            | DataValue::Int32(_)
+            | DataValue::Int64(_) => Ok(Some(value.as_i64()? as i128)),
+            _ => Ok(Some(value.as_u64()? as i128)),
+        }
+    }
+
+    fn empty_finalized() -> SendableDataBlockStream {
+        Box::pin(DataBlockStream::create(
+            DataSchemaRefExt::create(vec![]),
+            None,
+            vec![],
+        ))
+    }
+
+    fn finalize_groups(
+        &self,
+        groups: impl Iterator<Item = *mut GroupEntity<Method>>,
+        groups_len: usize,
+        schema: DataSchemaRef,
+    ) -> Result<SendableDataBlockStream> {
         let aggregator_params = self.params.as_ref();
         let funcs = &aggregator_params.aggregate_functions;
         let aggr_len = funcs.len();
@@ -195,13 +278,13 @@ impl<Method: HashMethod + PolymorphicKeysHelper<Method>> Aggregator<Method> {
 
         // Builders.
         let mut state_builders: Vec<StringArrayBuilder> = (0..aggr_len)
-            .map(|_| StringArrayBuilder::with_capacity(groups.len() * 4))
+            .map(|_| StringArrayBuilder::with_capacity(groups_len * 4))
             .collect();
 
-        let mut group_key_builder = self.method.state_array_builder(groups.len());
+        let mut group_key_builder = self.method.state_array_builder(groups_len);
 
         let mut bytes = BytesMut::new();
-        for group_entity in groups.iter() {
+        for group_entity in groups {
             let place: StateAddr = (*group_entity.get_state_value()).into();
 
             for (idx, func) in funcs.iter().enumerate() {
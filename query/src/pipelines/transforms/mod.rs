@@ -25,6 +25,7 @@ pub use transform_limit::LimitTransform;
 pub use transform_limit_by::LimitByTransform;
 pub use transform_projection::ProjectionTransform;
 pub use transform_remote::RemoteTransform;
+pub use transform_remote_ordered::OrderedRemoteTransform;
 pub use transform_sort_merge::SortMergeTransform;
 pub use transform_sort_partial::SortPartialTransform;
 pub use transform_source::SourceTransform;
@@ -64,8 +65,10 @@ mod transform_limit;
 mod transform_limit_by;
 mod transform_projection;
 mod transform_remote;
+mod transform_remote_ordered;
 mod transform_sort_merge;
 mod transform_sort_partial;
+mod transform_sort_spill;
 mod transform_source;
 
 mod group_by;
@@ -65,6 +65,10 @@ impl ExpressionTransform {
             executor,
         })
     }
+    pub fn with_strict_arithmetic(mut self, strict_arithmetic: bool) -> Self {
+        self.executor = self.executor.with_strict_arithmetic(strict_arithmetic);
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -38,6 +38,8 @@ pub struct ExpressionExecutor {
     chain: Arc<ExpressionChain>,
     // whether to perform alias action in executor
     alias_project: bool,
+    // whether the integer division or modulo by zero is an error rather than null
+    strict_arithmetic: bool,
 }
 
 impl ExpressionExecutor {
@@ -56,9 +58,15 @@ impl ExpressionExecutor {
             output_schema,
             chain: Arc::new(chain),
             alias_project,
+            strict_arithmetic: false,
         })
     }
 
+    pub fn with_strict_arithmetic(mut self, strict_arithmetic: bool) -> Self {
+        self.strict_arithmetic = strict_arithmetic;
+        self
+    }
+
     pub fn validate(&self) -> Result<()> {
         Ok(())
     }
@@ -119,7 +127,10 @@ impl ExpressionExecutor {
                     }
 
                     let func = f.to_function()?;
-                    let column = func.eval(&arg_columns, rows)?;
+                    let column = match self.strict_arithmetic {
+                        true => func.eval_strict(&arg_columns, rows)?,
+                        false => func.eval(&arg_columns, rows)?,
+                    };
 
                     let column = DataColumnWithField::new(
                         column,
@@ -61,6 +61,11 @@ impl FilterTransform {
             having,
         })
     }
+    pub fn with_strict_arithmetic(mut self, strict_arithmetic: bool) -> Self {
+        let executor = self.executor.as_ref().clone();
+        self.executor = Arc::new(executor.with_strict_arithmetic(strict_arithmetic));
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -21,6 +21,7 @@ use common_datablocks::HashMethod;
 use common_datablocks::HashMethodKind;
 use common_datavalues::prelude::*;
 use common_exception::Result;
+use common_planners::AggregatorTopN;
 use common_planners::Expression;
 use common_streams::SendableDataBlockStream;
 use common_tracing::tracing;
@@ -29,11 +30,15 @@ use crate::pipelines::processors::EmptyProcessor;
 use crate::pipelines::processors::Processor;
 use crate::pipelines::transforms::group_by::Aggregator;
 use crate::pipelines::transforms::group_by::AggregatorParams;
+use crate::pipelines::transforms::group_by::AggregatorState;
 use crate::pipelines::transforms::group_by::PolymorphicKeysHelper;
+use crate::sessions::DatabendQueryContextRef;
 
 pub struct GroupByPartialTransform {
+    ctx: DatabendQueryContextRef,
     aggr_exprs: Vec<Expression>,
     group_exprs: Vec<Expression>,
+    top_n: Option<AggregatorTopN>,
 
     schema: DataSchemaRef,
     schema_before_group_by: DataSchemaRef,
@@ -42,20 +47,29 @@ pub struct GroupByPartialTransform {
 
 impl GroupByPartialTransform {
     pub fn create(
+        ctx: DatabendQueryContextRef,
         schema: DataSchemaRef,
         schema_before_group_by: DataSchemaRef,
         aggr_exprs: Vec<Expression>,
         group_exprs: Vec<Expression>,
     ) -> Self {
         Self {
+            ctx,
             aggr_exprs,
             group_exprs,
+            top_n: None,
             schema,
             schema_before_group_by,
             input: Arc::new(EmptyProcessor::create()),
         }
     }
 
+    /// Only send the top groups to the final aggregation.
+    pub fn with_top_n(mut self, top_n: Option<AggregatorTopN>) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
     fn extract_group_columns(&self) -> Vec<String> {
         self.group_exprs
             .iter()
@@ -83,7 +97,16 @@ impl GroupByPartialTransform {
         tracing::debug!("Group by partial cost: {:?}", delta);
 
         let finalized_schema = self.schema.clone();
-        aggregator.aggregate_finalized(&state, finalized_schema)
+        let (stream, pruned) = match &self.top_n {
+            None => (aggregator.aggregate_finalized(&state, finalized_schema)?, 0),
+            Some(top_n) => aggregator.aggregate_finalized_top_n(&state, finalized_schema, top_n)?,
+        };
+
+        let sent = state.len() - pruned;
+        self.ctx
+            .get_query_metrics()
+            .incr_partial_groups(sent, pruned);
+        Ok(stream)
     }
 }
 
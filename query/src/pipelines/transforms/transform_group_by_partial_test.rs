@@ -12,12 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::any::Any;
+use std::cmp::Reverse;
 use std::sync::Arc;
 
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::Series;
+use common_datavalues::prelude::SeriesFrom;
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::*;
 use common_planners::{self};
 use common_runtime::tokio;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
 use futures::TryStreamExt;
 use pretty_assertions::assert_eq;
 
@@ -44,6 +56,7 @@ async fn test_transform_partial_group_by() -> Result<()> {
     pipeline.add_source(Arc::new(source))?;
     pipeline.add_simple_transform(|| {
         Ok(Box::new(GroupByPartialTransform::create(
+            ctx.clone(),
             aggr_partial.schema(),
             source_schema.clone(),
             aggr_exprs.clone(),
@@ -75,3 +88,234 @@ async fn test_transform_partial_group_by() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transform_partial_group_by_top_n_skewed() -> Result<()> {
+    // Zipf-like values of the keys, with a long tail of the keys of small values.
+    let nodes = (0..4u64)
+        .map(|node| {
+            let mut rows = vec![];
+            for k in 0..5000u64 {
+                for r in 0..3u64 {
+                    rows.push((k, 2000 / (k + 1) + (k + node + r) % 3));
+                }
+            }
+            rows
+        })
+        .collect::<Vec<_>>();
+
+    assert_top_n_eq_unpruned(&nodes, aggregate("max", col("v"))).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transform_partial_group_by_top_n_uniform() -> Result<()> {
+    // Every key is seen 3 times by each node, with values a bit different among the nodes.
+    let nodes = (0..4u64)
+        .map(|node| {
+            let mut rows = vec![];
+            for k in 0..1000u64 {
+                for r in 0..3u64 {
+                    rows.push((k, k * 10 + (k * 31 + node * 17 + r * 7) % 10));
+                }
+            }
+            rows
+        })
+        .collect::<Vec<_>>();
+
+    assert_top_n_eq_unpruned(&nodes, aggregate("max", col("v"))).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transform_partial_group_by_top_n_ties() -> Result<()> {
+    // The groups tied with the last one kept are not pruned.
+    let nodes = vec![(0..100u64).map(|k| (k, 1)).collect::<Vec<_>>()];
+
+    let top_n = AggregatorTopN {
+        aggr_index: 0,
+        asc: false,
+        limit: 10,
+        keep: 30,
+    };
+    let (_, sent) = distributed_top_10(&nodes, aggregate("max", col("v")), Some(top_n)).await?;
+    assert_eq!(sent, 100);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transform_partial_group_by_top_n_counterexample() -> Result<()> {
+    // Node A sees k1 10 times and k2 9 times, node B sees k3 10 times and k2 9 times.
+    let rows = |top: u64| {
+        let mut rows = vec![(top, 10); 10];
+        rows.extend(vec![(2, 9); 9]);
+        rows
+    };
+    let nodes = vec![rows(1), rows(3)];
+    let top_1 = AggregatorTopN {
+        aggr_index: 0,
+        asc: false,
+        limit: 1,
+        keep: 1,
+    };
+
+    // k2 is the second of both nodes but the first of all, the local top 1 of the counts
+    // misses it, that is why the counts are not pruned.
+    let count = aggregate("count", col("k"));
+    let (expected, _) = distributed_top_10(&nodes, count.clone(), None).await?;
+    assert_eq!((2, 18), expected[0]);
+    let (pruned, _) = distributed_top_10(&nodes, count, Some(top_1.clone())).await?;
+    assert!(!pruned.contains(&(2, 18)), "{:?}", pruned);
+
+    // The max of a group is the max of one node, the local top 1 keeps the first of all.
+    let (expected, _) = distributed_top_10(&nodes, aggregate("max", col("v")), None).await?;
+    let (actual, _) = distributed_top_10(&nodes, aggregate("max", col("v")), Some(top_1)).await?;
+    assert_eq!(expected[0], actual[0]);
+    assert_eq!(10, actual[0].1);
+
+    Ok(())
+}
+
+fn aggregate(op: &str, arg: Expression) -> Expression {
+    Expression::AggregateFunction {
+        op: op.to_string(),
+        distinct: false,
+        params: vec![],
+        args: vec![arg],
+    }
+}
+
+/// ORDER BY <aggr> DESC LIMIT 10 of the keys aggregated by the nodes, with the top 30 groups of
+/// each node sent to the final aggregation, is the same as with all the groups sent.
+async fn assert_top_n_eq_unpruned(nodes: &[Vec<(u64, u64)>], aggr_expr: Expression) -> Result<()> {
+    let top_n = AggregatorTopN {
+        aggr_index: 0,
+        asc: false,
+        limit: 10,
+        keep: 30,
+    };
+
+    let (expected, unpruned_sent) = distributed_top_10(nodes, aggr_expr.clone(), None).await?;
+    let (actual, pruned_sent) = distributed_top_10(nodes, aggr_expr, Some(top_n)).await?;
+
+    assert_eq!(expected.len(), 10);
+    assert_eq!(expected, actual);
+    assert!(
+        pruned_sent * 10 < unpruned_sent,
+        "sent {} of {}",
+        pruned_sent,
+        unpruned_sent
+    );
+
+    Ok(())
+}
+
+/// The nodes aggregate their rows of (k, v) partially by k, then the partial states are merged.
+/// Returns the top 10 (k, aggr) in descending order, and how many groups the nodes sent.
+async fn distributed_top_10(
+    nodes: &[Vec<(u64, u64)>],
+    aggr_expr: Expression,
+    top_n: Option<AggregatorTopN>,
+) -> Result<(Vec<(u64, u64)>, usize)> {
+    let ctx = crate::tests::try_create_context()?;
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("k", DataType::UInt64, false),
+        DataField::new("v", DataType::UInt64, false),
+    ]);
+
+    let aggr_exprs = vec![aggr_expr];
+    let group_exprs = vec![col("k")];
+    let partial_schema = PlanBuilder::create(schema.clone())
+        .aggregate_partial(&aggr_exprs, &group_exprs)?
+        .build()?
+        .schema();
+    let final_schema = PlanBuilder::create(schema.clone())
+        .aggregate_final(schema.clone(), &aggr_exprs, &group_exprs)?
+        .build()?
+        .schema();
+
+    let mut partial_blocks = vec![];
+    for rows in nodes {
+        let block = DataBlock::create_by_array(schema.clone(), vec![
+            Series::new(rows.iter().map(|(k, _)| *k).collect::<Vec<_>>()),
+            Series::new(rows.iter().map(|(_, v)| *v).collect::<Vec<_>>()),
+        ]);
+
+        let mut partial = GroupByPartialTransform::create(
+            ctx.clone(),
+            partial_schema.clone(),
+            schema.clone(),
+            aggr_exprs.clone(),
+            group_exprs.clone(),
+        )
+        .with_top_n(top_n.clone());
+        partial.connect_to(Arc::new(BlocksSource::create(schema.clone(), vec![block])))?;
+        partial_blocks.extend(partial.execute().await?.try_collect::<Vec<_>>().await?);
+    }
+
+    let mut aggr_final = GroupByFinalTransform::create(
+        final_schema,
+        65536,
+        schema.clone(),
+        aggr_exprs.clone(),
+        group_exprs.clone(),
+    );
+    aggr_final.connect_to(Arc::new(BlocksSource::create(
+        partial_schema,
+        partial_blocks,
+    )))?;
+
+    let mut groups = vec![];
+    for block in aggr_final.execute().await?.try_collect::<Vec<_>>().await? {
+        let values = block.column(0).to_values()?;
+        let keys = block.column(1).to_values()?;
+        for (key, value) in keys.iter().zip(values.iter()) {
+            groups.push((key.as_u64()?, value.as_u64()?));
+        }
+    }
+
+    groups.sort_by_key(|(k, v)| (Reverse(*v), *k));
+    groups.truncate(10);
+
+    let sent = ctx.get_query_metrics().get_values().partial_groups_sent;
+    Ok((groups, sent))
+}
+
+struct BlocksSource {
+    schema: DataSchemaRef,
+    blocks: Vec<DataBlock>,
+}
+
+impl BlocksSource {
+    fn create(schema: DataSchemaRef, blocks: Vec<DataBlock>) -> Self {
+        BlocksSource { schema, blocks }
+    }
+}
+
+#[async_trait::async_trait]
+impl Processor for BlocksSource {
+    fn name(&self) -> &str {
+        "BlocksSource"
+    }
+
+    fn connect_to(&mut self, _: Arc<dyn Processor>) -> Result<()> {
+        Result::Err(ErrorCode::IllegalTransformConnectionState(
+            "Cannot call BlocksSource connect_to",
+        ))
+    }
+
+    fn inputs(&self) -> Vec<Arc<dyn Processor>> {
+        vec![Arc::new(EmptyProcessor::create())]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn execute(&self) -> Result<SendableDataBlockStream> {
+        Ok(Box::pin(DataBlockStream::create(
+            self.schema.clone(),
+            None,
+            self.blocks.clone(),
+        )))
+    }
+}
@@ -52,6 +52,10 @@ impl ProjectionTransform {
             input: Arc::new(EmptyProcessor::create()),
         })
     }
+    pub fn with_strict_arithmetic(mut self, strict_arithmetic: bool) -> Self {
+        self.executor = self.executor.with_strict_arithmetic(strict_arithmetic);
+        self
+    }
 }
 
 #[async_trait::async_trait]
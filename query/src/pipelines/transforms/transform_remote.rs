@@ -20,6 +20,7 @@ use common_exception::ErrorCode;
 use common_exception::Result;
 use common_streams::SendableDataBlockStream;
 use common_tracing::tracing;
+use futures::StreamExt;
 
 use crate::api::FlightClient;
 use crate::api::FlightTicket;
@@ -53,7 +54,7 @@ impl RemoteTransform {
         let context = self.ctx.clone();
         let cluster = context.try_get_cluster()?;
         let fetch_node = cluster.get_node_by_name(self.fetch_node_name.clone())?;
-        fetch_node.get_flight_client(&self.ctx.get_config()).await
+        cluster.get_flight_client(&fetch_node, &self.ctx.get_config())
     }
 }
 
@@ -90,8 +91,14 @@ impl Processor for RemoteTransform {
         let fetch_ticket = self.ticket.clone();
         let mut flight_client = self.flight_client().await?;
         let fetch_stream = flight_client.fetch_stream(fetch_ticket, data_schema, timeout);
+        let metrics = self.ctx.get_query_metrics();
+        let fetch_stream = fetch_stream.await?.inspect(move |block| {
+            if let Ok(block) = block {
+                metrics.incr_exchange_received(block.memory_size());
+            }
+        });
         Ok(Box::pin(
-            self.ctx.try_create_abortable(fetch_stream.await?)?,
+            self.ctx.try_create_abortable(Box::pin(fetch_stream))?,
         ))
     }
 }
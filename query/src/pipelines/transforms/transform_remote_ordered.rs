@@ -0,0 +1,118 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_streams::SendableDataBlockStream;
+use common_tracing::tracing;
+use futures::StreamExt;
+
+use crate::api::FlightClient;
+use crate::api::FlightTicket;
+use crate::api::OrderedBlockStream;
+use crate::pipelines::processors::EmptyProcessor;
+use crate::pipelines::processors::Processor;
+use crate::sessions::DatabendQueryContextRef;
+
+/// Fetch the stream of an order-preserving stage from all the fetch nodes,
+/// and release their blocks in the order of their tags.
+pub struct OrderedRemoteTransform {
+    ticket: FlightTicket,
+    fetch_nodes_name: Vec<String>,
+    schema: DataSchemaRef,
+    pub ctx: DatabendQueryContextRef,
+}
+
+impl OrderedRemoteTransform {
+    pub fn try_create(
+        ticket: FlightTicket,
+        context: DatabendQueryContextRef,
+        fetch_nodes_name: Vec<String>,
+        schema: DataSchemaRef,
+    ) -> Result<OrderedRemoteTransform> {
+        Ok(OrderedRemoteTransform {
+            ticket,
+            fetch_nodes_name,
+            schema,
+            ctx: context,
+        })
+    }
+
+    async fn flight_client(&self, fetch_node_name: &str) -> Result<FlightClient> {
+        let context = self.ctx.clone();
+        let cluster = context.try_get_cluster()?;
+        let fetch_node = cluster.get_node_by_name(fetch_node_name.to_string())?;
+        cluster.get_flight_client(&fetch_node, &self.ctx.get_config())
+    }
+}
+
+#[async_trait::async_trait]
+impl Processor for OrderedRemoteTransform {
+    fn name(&self) -> &str {
+        "OrderedRemoteTransform"
+    }
+
+    fn connect_to(&mut self, _input: Arc<dyn Processor>) -> Result<()> {
+        Result::Err(ErrorCode::LogicalError(
+            "Cannot call OrderedRemoteTransform connect_to",
+        ))
+    }
+
+    fn inputs(&self) -> Vec<Arc<dyn Processor>> {
+        vec![Arc::new(EmptyProcessor::create())]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn execute(&self) -> Result<SendableDataBlockStream> {
+        tracing::debug!(
+            "execute, flight_ticket {:?}, node names:{:?}...",
+            self.ticket,
+            self.fetch_nodes_name
+        );
+
+        let settings = self.ctx.get_settings();
+        let timeout = settings.get_flight_client_timeout()?;
+        let capacity = settings.get_exchange_reorder_buffer_blocks()? as usize;
+        let gap_timeout = Duration::from_millis(settings.get_exchange_reorder_gap_timeout()?);
+
+        let mut inputs = Vec::with_capacity(self.fetch_nodes_name.len());
+        for fetch_node_name in &self.fetch_nodes_name {
+            let data_schema = self.schema.clone();
+            let fetch_ticket = self.ticket.clone();
+            let mut flight_client = self.flight_client(fetch_node_name).await?;
+            let fetch_stream =
+                flight_client.fetch_tagged_stream(fetch_ticket, data_schema, timeout);
+            inputs.push(fetch_stream.await?);
+        }
+
+        let metrics = self.ctx.get_query_metrics();
+        let ordered_stream =
+            OrderedBlockStream::create(inputs, capacity, gap_timeout).inspect(move |block| {
+                if let Ok(block) = block {
+                    metrics.incr_exchange_received(block.memory_size());
+                }
+            });
+        Ok(Box::pin(
+            self.ctx.try_create_abortable(Box::pin(ordered_stream))?,
+        ))
+    }
+}
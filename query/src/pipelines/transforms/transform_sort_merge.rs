@@ -17,6 +17,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use common_datablocks::DataBlock;
+use common_datablocks::SortColumnDescription;
 use common_datavalues::DataSchemaRef;
 use common_exception::Result;
 use common_planners::Expression;
@@ -29,8 +30,12 @@ use futures::StreamExt;
 use crate::pipelines::processors::EmptyProcessor;
 use crate::pipelines::processors::Processor;
 use crate::pipelines::transforms::transform_sort_partial::get_sort_descriptions;
+use crate::pipelines::transforms::transform_sort_spill::SortedRunsMerger;
+use crate::pipelines::transforms::transform_sort_spill::SpilledRun;
+use crate::sessions::DatabendQueryContextRef;
 
 pub struct SortMergeTransform {
+    ctx: DatabendQueryContextRef,
     schema: DataSchemaRef,
     exprs: Vec<Expression>,
     limit: Option<usize>,
@@ -39,11 +44,13 @@ pub struct SortMergeTransform {
 
 impl SortMergeTransform {
     pub fn try_create(
+        ctx: DatabendQueryContextRef,
         schema: DataSchemaRef,
         exprs: Vec<Expression>,
         limit: Option<usize>,
     ) -> Result<Self> {
         Ok(SortMergeTransform {
+            ctx,
             schema,
             exprs,
             limit,
@@ -75,18 +82,69 @@ impl Processor for SortMergeTransform {
         tracing::debug!("execute...");
 
         let sort_columns_descriptions = get_sort_descriptions(&self.schema, &self.exprs)?;
+        let sort_buffer_bytes = self.ctx.get_settings().get_sort_buffer_bytes()? as usize;
         let mut blocks = vec![];
         let mut stream = self.input.execute().await?;
 
+        // With a limit every sorted block is already cut to the top-N rows, keep them in memory.
+        if self.limit.is_some() || sort_buffer_bytes == 0 {
+            while let Some(block) = stream.next().await {
+                blocks.push(block?);
+            }
+
+            return self.merge_in_memory(&blocks, &sort_columns_descriptions);
+        }
+
+        let mut runs = vec![];
+        let mut buffered_bytes = 0;
         while let Some(block) = stream.next().await {
-            blocks.push(block?);
+            let block = block?;
+            buffered_bytes += block.memory_size();
+            blocks.push(block);
+
+            if buffered_bytes > sort_buffer_bytes {
+                runs.push(self.spill(&blocks, &sort_columns_descriptions).await?);
+                blocks.clear();
+                buffered_bytes = 0;
+            }
+        }
+
+        if runs.is_empty() {
+            return self.merge_in_memory(&blocks, &sort_columns_descriptions);
         }
 
+        let in_memory_run = match blocks.len() {
+            0 => None,
+            _ => Some(DataBlock::merge_sort_blocks(
+                &blocks,
+                &sort_columns_descriptions,
+                None,
+            )?),
+        };
+
+        let block_size = self.ctx.get_settings().get_max_block_size()? as usize;
+        let merger =
+            SortedRunsMerger::create(runs, in_memory_run, sort_columns_descriptions, block_size)
+                .await?;
+
+        Ok(Box::pin(CorrectWithSchemaStream::new(
+            Box::pin(merger.into_stream()),
+            self.schema.clone(),
+        )))
+    }
+}
+
+impl SortMergeTransform {
+    fn merge_in_memory(
+        &self,
+        blocks: &[DataBlock],
+        sort_columns_descriptions: &[SortColumnDescription],
+    ) -> Result<SendableDataBlockStream> {
         let results = match blocks.len() {
             0 => vec![],
             _ => vec![DataBlock::merge_sort_blocks(
-                &blocks,
-                &sort_columns_descriptions,
+                blocks,
+                sort_columns_descriptions,
                 self.limit,
             )?],
         };
@@ -96,4 +154,19 @@ impl Processor for SortMergeTransform {
             self.schema.clone(),
         )))
     }
+
+    async fn spill(
+        &self,
+        blocks: &[DataBlock],
+        sort_columns_descriptions: &[SortColumnDescription],
+    ) -> Result<SpilledRun> {
+        let sorted = DataBlock::merge_sort_blocks(blocks, sort_columns_descriptions, None)?;
+        let rows = sorted.num_rows();
+        let block_size = self.ctx.get_settings().get_max_block_size()? as usize;
+        let run = SpilledRun::spill(self.ctx.get_temp_dir_manager(), sorted, block_size).await?;
+
+        self.ctx.get_query_metrics().incr_spill(run.bytes());
+        tracing::debug!("Spilled sorted run of {} rows, {} bytes", rows, run.bytes());
+        Ok(run)
+    }
 }
@@ -0,0 +1,256 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use common_arrow::arrow::io::ipc::read::read_file_metadata;
+use common_arrow::arrow::io::ipc::read::FileReader;
+use common_arrow::arrow::io::ipc::write::FileWriter;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_datablocks::DataBlock;
+use common_datablocks::SortColumnDescription;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::Runtime;
+use common_tracing::tracing;
+use futures::Stream;
+
+use crate::common::TempDirManager;
+
+type BlockIterator = Box<dyn Iterator<Item = Result<DataBlock>> + Send>;
+
+/// A sorted run spilled to a temp file in arrow IPC format, the file is removed on drop.
+pub struct SpilledRun {
+    path: PathBuf,
+    bytes: usize,
+}
+
+impl SpilledRun {
+    /// Writes the sorted run to a temp file in the blocking io pool, see `try_create`.
+    pub async fn spill(
+        temp_dir: Arc<TempDirManager>,
+        sorted: DataBlock,
+        block_size: usize,
+    ) -> Result<SpilledRun> {
+        Runtime::spawn_blocking_io(move || SpilledRun::try_create(&temp_dir, &sorted, block_size))
+            .await
+            .map_err(|e| ErrorCode::TokioError(e.to_string()))?
+    }
+
+    pub fn try_create(
+        temp_dir: &TempDirManager,
+        sorted: &DataBlock,
+        block_size: usize,
+    ) -> Result<SpilledRun> {
+        let (path, file) = temp_dir.create_temp_file("sort_run")?;
+        // Removes the file if the run is not completely written.
+        let mut run = SpilledRun { path, bytes: 0 };
+
+        let mut writer = FileWriter::try_new(file, &sorted.schema().to_arrow())?;
+        for block in DataBlock::split_block_by_size(sorted, block_size)? {
+            writer.write(&RecordBatch::try_from(block)?)?;
+        }
+        writer.finish()?;
+
+        run.bytes = std::fs::metadata(&run.path)?.len() as usize;
+        Ok(run)
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn blocks(&self) -> Result<BlockIterator> {
+        let mut file = File::open(&self.path)?;
+        let metadata = read_file_metadata(&mut file)?;
+        let reader = FileReader::new(file, metadata, None);
+        Ok(Box::new(reader.map(|batch| DataBlock::try_from(batch?))))
+    }
+}
+
+impl Drop for SpilledRun {
+    fn drop(&mut self) {
+        if let Err(cause) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Cannot remove sort run {}: {}", self.path.display(), cause);
+        }
+    }
+}
+
+struct RunCursor {
+    head: Option<DataBlock>,
+    blocks: Option<BlockIterator>,
+    // Keep the spilled file until the run is exhausted.
+    run: Option<SpilledRun>,
+}
+
+impl RunCursor {
+    fn try_create(blocks: BlockIterator, run: Option<SpilledRun>) -> Result<RunCursor> {
+        let mut cursor = RunCursor {
+            head: None,
+            blocks: Some(blocks),
+            run,
+        };
+        cursor.advance()?;
+        Ok(cursor)
+    }
+
+    fn advance(&mut self) -> Result<()> {
+        self.head = None;
+        while let Some(blocks) = self.blocks.as_mut() {
+            match blocks.next() {
+                Some(block) => {
+                    let block = block?;
+                    if block.num_rows() > 0 {
+                        self.head = Some(block);
+                        return Ok(());
+                    }
+                }
+                None => {
+                    self.blocks = None;
+                    self.run = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn consume(&mut self, rows: usize) -> Result<()> {
+        match &self.head {
+            Some(head) if head.num_rows() > rows => {
+                self.head = Some(head.slice(rows, head.num_rows() - rows));
+                Ok(())
+            }
+            _ => self.advance(),
+        }
+    }
+}
+
+/// K-way merge of sorted runs, which only keeps the current block of every run in memory and
+/// outputs blocks of at most `block_size` rows.
+pub struct SortedRunsMerger {
+    cursors: Vec<RunCursor>,
+    sort_columns_descriptions: Vec<SortColumnDescription>,
+    block_size: usize,
+    output: VecDeque<DataBlock>,
+}
+
+impl SortedRunsMerger {
+    /// Opens the runs in the blocking io pool, see `try_create`.
+    pub async fn create(
+        runs: Vec<SpilledRun>,
+        in_memory_run: Option<DataBlock>,
+        sort_columns_descriptions: Vec<SortColumnDescription>,
+        block_size: usize,
+    ) -> Result<SortedRunsMerger> {
+        Runtime::spawn_blocking_io(move || {
+            SortedRunsMerger::try_create(runs, in_memory_run, sort_columns_descriptions, block_size)
+        })
+        .await
+        .map_err(|e| ErrorCode::TokioError(e.to_string()))?
+    }
+
+    /// The merged blocks, every block is merged in the blocking io pool, where the runs are read.
+    pub fn into_stream(self) -> impl Stream<Item = Result<DataBlock>> + Send {
+        futures::stream::unfold(Some(self), |merger| async move {
+            let mut merger = merger?;
+            let next = Runtime::spawn_blocking_io(move || (merger.next(), merger)).await;
+            match next {
+                Ok((Some(block), merger)) => Some((block, Some(merger))),
+                Ok((None, _)) => None,
+                Err(e) => Some((Err(ErrorCode::TokioError(e.to_string())), None)),
+            }
+        })
+    }
+
+    pub fn try_create(
+        runs: Vec<SpilledRun>,
+        in_memory_run: Option<DataBlock>,
+        sort_columns_descriptions: Vec<SortColumnDescription>,
+        block_size: usize,
+    ) -> Result<SortedRunsMerger> {
+        let mut cursors = Vec::with_capacity(runs.len() + 1);
+        for run in runs {
+            cursors.push(RunCursor::try_create(run.blocks()?, Some(run))?);
+        }
+
+        if let Some(sorted) = in_memory_run {
+            let blocks = DataBlock::split_block_by_size(&sorted, block_size)?;
+            cursors.push(RunCursor::try_create(
+                Box::new(blocks.into_iter().map(Ok)),
+                None,
+            )?);
+        }
+
+        Ok(SortedRunsMerger {
+            cursors,
+            sort_columns_descriptions,
+            block_size,
+            output: VecDeque::new(),
+        })
+    }
+
+    // Merge the rows of the current blocks which are not greater than the smallest last row,
+    // every round exhausts at least one current block.
+    fn merge_round(&mut self) -> Result<bool> {
+        let mut indices = vec![];
+        let mut heads = vec![];
+        for (index, cursor) in self.cursors.iter().enumerate() {
+            if let Some(head) = &cursor.head {
+                indices.push(index);
+                heads.push(head.clone());
+            }
+        }
+
+        if heads.is_empty() {
+            return Ok(false);
+        }
+
+        let cut_points = DataBlock::merge_sort_cut_points(&heads, &self.sort_columns_descriptions)?;
+
+        let mut prefixes = Vec::with_capacity(heads.len());
+        for ((index, head), rows) in indices.into_iter().zip(heads.iter()).zip(cut_points) {
+            if rows > 0 {
+                prefixes.push(head.slice(0, rows));
+                self.cursors[index].consume(rows)?;
+            }
+        }
+
+        let merged =
+            DataBlock::merge_sort_blocks(&prefixes, &self.sort_columns_descriptions, None)?;
+        self.output
+            .extend(DataBlock::split_block_by_size(&merged, self.block_size)?);
+        Ok(true)
+    }
+}
+
+impl Iterator for SortedRunsMerger {
+    type Item = Result<DataBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(block) = self.output.pop_front() {
+                return Some(Ok(block));
+            }
+
+            match self.merge_round() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(cause) => return Some(Err(cause)),
+            }
+        }
+    }
+}
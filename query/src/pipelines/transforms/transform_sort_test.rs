@@ -23,6 +23,7 @@ use pretty_assertions::assert_eq;
 
 use crate::pipelines::processors::*;
 use crate::pipelines::transforms::*;
+use crate::sessions::DatabendQueryContextRef;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_transform_sort() -> Result<()> {
@@ -49,6 +50,7 @@ async fn test_transform_sort() -> Result<()> {
 
     pipeline.add_simple_transform(|| {
         Ok(Box::new(SortMergeTransform::try_create(
+            ctx.clone(),
             plan.schema(),
             sort_expression.to_vec(),
             None,
@@ -59,6 +61,7 @@ async fn test_transform_sort() -> Result<()> {
         pipeline.merge_processor()?;
         pipeline.add_simple_transform(|| {
             Ok(Box::new(SortMergeTransform::try_create(
+                ctx.clone(),
                 plan.schema(),
                 sort_expression.to_vec(),
                 None,
@@ -90,3 +93,104 @@ async fn test_transform_sort() -> Result<()> {
 
     Ok(())
 }
+
+async fn sort_numbers_desc(ctx: DatabendQueryContextRef, numbers: i64) -> Result<Vec<u64>> {
+    let test_source = crate::tests::NumberTestData::create(ctx.clone());
+
+    let mut pipeline = Pipeline::create(ctx.clone());
+    let a = test_source.number_source_transform_for_test(numbers)?;
+    pipeline.add_source(Arc::new(a))?;
+
+    let sort_expression = &[sort("number", false, false)];
+    let plan = PlanBuilder::create(test_source.number_schema_for_test()?)
+        .sort(sort_expression)?
+        .build()?;
+
+    pipeline.add_simple_transform(|| {
+        Ok(Box::new(SortPartialTransform::try_create(
+            plan.schema(),
+            sort_expression.to_vec(),
+            None,
+        )?))
+    })?;
+
+    pipeline.add_simple_transform(|| {
+        Ok(Box::new(SortMergeTransform::try_create(
+            ctx.clone(),
+            plan.schema(),
+            sort_expression.to_vec(),
+            None,
+        )?))
+    })?;
+
+    if pipeline.last_pipe()?.nums() > 1 {
+        pipeline.merge_processor()?;
+        pipeline.add_simple_transform(|| {
+            Ok(Box::new(SortMergeTransform::try_create(
+                ctx.clone(),
+                plan.schema(),
+                sort_expression.to_vec(),
+                None,
+            )?))
+        })?;
+    }
+
+    let stream = pipeline.execute().await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+
+    let mut values = vec![];
+    for block in &result {
+        let column = block.column(0).to_array()?;
+        values.extend_from_slice(column.u64()?.inner().values().as_slice());
+    }
+    Ok(values)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transform_sort_spill() -> Result<()> {
+    let numbers = 100000;
+
+    // Reference: everything fits in the default sort buffer.
+    let ctx = crate::tests::try_create_context()?;
+    let expected = sort_numbers_desc(ctx.clone(), numbers).await?;
+    assert_eq!(ctx.get_query_metrics().get_values().spill_count, 0);
+    assert_eq!(ctx.get_query_metrics().get_values().spill_bytes, 0);
+    assert_eq!(expected, (0..numbers as u64).rev().collect::<Vec<_>>());
+
+    // 800KB of numbers with a 64KB sort buffer.
+    let ctx = crate::tests::try_create_context()?;
+    ctx.get_settings().set_sort_buffer_bytes(64 * 1024)?;
+    ctx.get_settings().set_max_block_size(1000)?;
+    let actual = sort_numbers_desc(ctx.clone(), numbers).await?;
+    assert_eq!(expected, actual);
+
+    let metrics = ctx.get_query_metrics().get_values();
+    assert!(metrics.spill_count > 1);
+    assert!(metrics.spill_bytes > 0);
+
+    // The sorted runs are removed once they are merged.
+    let temp_dir = ctx.get_temp_dir_manager();
+    let remaining = match std::fs::read_dir(temp_dir.root()) {
+        Ok(entries) => entries.count(),
+        Err(_) => 0,
+    };
+    assert_eq!(remaining, 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transform_sort_no_spill() -> Result<()> {
+    let numbers = 10000;
+
+    let ctx = crate::tests::try_create_context()?;
+    ctx.get_settings().set_sort_buffer_bytes(1024 * 1024)?;
+    let actual = sort_numbers_desc(ctx.clone(), numbers).await?;
+    assert_eq!(actual, (0..numbers as u64).rev().collect::<Vec<_>>());
+
+    let metrics = ctx.get_query_metrics().get_values();
+    assert_eq!(metrics.spill_count, 0);
+    assert_eq!(metrics.spill_bytes, 0);
+
+    Ok(())
+}
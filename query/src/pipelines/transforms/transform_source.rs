@@ -21,6 +21,7 @@ use common_planners::ReadDataSourcePlan;
 use common_streams::CorrectWithSchemaStream;
 use common_streams::SendableDataBlockStream;
 use common_tracing::tracing;
+use futures::StreamExt;
 
 use crate::pipelines::processors::EmptyProcessor;
 use crate::pipelines::processors::Processor;
@@ -44,8 +45,21 @@ impl SourceTransform {
         let table_ver = self.source_plan.table_version;
         let table = self.ctx.get_table_by_id(db, table_id, table_ver).await?;
         let table_stream = table.raw().read(self.ctx.clone(), &self.source_plan);
+
+        // Each block read consumes the budget of the resource group.
+        let resource_group = self.ctx.try_get_resource_group()?;
+        let scheduled_stream = table_stream.await?.then(move |block| {
+            let resource_group = resource_group.clone();
+            async move {
+                if let Ok(block) = &block {
+                    resource_group.consume(block.num_rows()).await;
+                }
+                block
+            }
+        });
+
         Ok(Box::pin(
-            self.ctx.try_create_abortable(table_stream.await?)?,
+            self.ctx.try_create_abortable(Box::pin(scheduled_stream))?,
         ))
     }
 }
@@ -59,6 +59,7 @@ impl InteractiveWorkerBase {
         log::debug!("{}", query);
 
         let plan = PlanParser::create(ctx.clone()).build_from_sql(query)?;
+        ctx.attach_query_plan(&plan);
 
         match plan {
             PlanNode::InsertInto(insert) => Self::process_insert_query(insert, ch_ctx, ctx).await,
@@ -83,6 +83,7 @@ impl<'a> QueryWriter<'a> {
 
     async fn write_error(&mut self, error: ErrorCode) -> Result<()> {
         log::error!("OnQuery Error: {:?}", error);
+        self.ctx.get_query_metrics().mark_failed();
         let clickhouse_err = to_clickhouse_err(error);
         match self.conn.write_error(&clickhouse_err).await {
             Ok(_) => Ok(()),
@@ -94,6 +95,8 @@ impl<'a> QueryWriter<'a> {
     }
 
     async fn write_block(&mut self, block: DataBlock) -> Result<()> {
+        let (rows, bytes) = (block.num_rows(), block.memory_size());
+        self.ctx.get_query_metrics().incr_result(rows, bytes);
         let block = to_clickhouse_block(block)?;
 
         match self.conn.write_block(&block).await {
@@ -17,10 +17,13 @@ pub use self::mysql_session::MySQLConnection;
 
 #[cfg(test)]
 mod mysql_handler_test;
+#[cfg(test)]
+mod mysql_result_buffer_test;
 
 mod mysql_handler;
 mod mysql_interactive_worker;
 mod mysql_metrics;
+mod mysql_result_buffer;
 mod mysql_session;
 mod reject_connection;
 mod writers;
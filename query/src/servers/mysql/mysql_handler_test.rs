@@ -13,23 +13,35 @@
 // limitations under the License.
 
 use std::net::SocketAddr;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 use std::sync::Barrier;
 use std::thread::JoinHandle;
 use std::time::Duration;
 
+use common_exception::codes;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_exception::ToErrorCode;
+use common_metatypes::MatchSeq;
 use common_runtime::tokio;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use msql_srv::StatusFlags;
 use mysql::prelude::FromRow;
 use mysql::prelude::Queryable;
 use mysql::Conn;
 use mysql::FromRowError;
 use mysql::Row;
+use mysql::TxOpts;
 
+use crate::configs::Config;
+use crate::servers::mysql::mysql_interactive_worker::ok_response;
 use crate::servers::MySQLHandler;
 use crate::tests::try_create_session_mgr;
+use crate::tests::try_create_session_mgr_with_clock;
+use crate::tests::try_create_session_mgr_with_conf;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_use_database_with_on_query() -> Result<()> {
@@ -47,6 +59,732 @@ async fn test_use_database_with_on_query() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_ping_and_init_db() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+    assert!(connection.ping());
+
+    assert!(connection.select_db("system"));
+    let received_data: Vec<String> = query(&mut connection, "SELECT database()")?;
+    assert_eq!(received_data, vec!["system"]);
+    assert!(connection.ping());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_init_unknown_db() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    // The current database stays, the connection keeps serving.
+    assert!(!connection.select_db("not_exists_db"));
+    let received_data: Vec<String> = query(&mut connection, "SELECT database()")?;
+    assert_eq!(received_data, vec!["default"]);
+
+    // The same error as `USE`.
+    match connection.query_drop("USE not_exists_db") {
+        Err(mysql::Error::MySqlError(error)) => assert_eq!(error.code, 1049),
+        other => panic!("expect ER_BAD_DB_ERROR, got: {:?}", other),
+    }
+    assert!(connection.ping());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_idle_timeout() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.mysql_handler_idle_timeout_secs = 2;
+    let mut handler = MySQLHandler::create(try_create_session_mgr_with_conf(conf)?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    // Each command starts the idle time over.
+    for _ in 0..3 {
+        std::thread::sleep(Duration::from_millis(1000));
+        assert!(connection.ping());
+    }
+
+    std::thread::sleep(Duration::from_millis(3000));
+    assert!(!connection.ping());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transaction_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    {
+        let mut transaction = connection
+            .start_transaction(TxOpts::default())
+            .map_err_to_code(ErrorCode::UnknownException, || "Start transaction error")?;
+        let received_data: Vec<u64> = transaction
+            .query("SELECT 1")
+            .map_err_to_code(ErrorCode::UnknownException, || "Query error")?;
+        assert_eq!(received_data, vec![1]);
+        transaction
+            .commit()
+            .map_err_to_code(ErrorCode::UnknownException, || "Commit error")?;
+    }
+
+    let received_data: Vec<u64> = query(&mut connection, "SELECT @@autocommit")?;
+    assert_eq!(received_data, vec![1]);
+    query::<EmptyRow>(&mut connection, "SET autocommit = 0")?;
+    let received_data: Vec<u64> = query(&mut connection, "SELECT @@autocommit")?;
+    assert_eq!(received_data, vec![0]);
+
+    // Nothing to roll back, the ROLLBACK only leaves a warning.
+    query::<EmptyRow>(&mut connection, "BEGIN")?;
+    query::<EmptyRow>(&mut connection, "ROLLBACK")?;
+    assert_eq!(connection.warnings(), 1);
+
+    query::<EmptyRow>(&mut connection, "SET strict_transaction = 1")?;
+    assert!(query::<EmptyRow>(&mut connection, "ROLLBACK").is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_warnings_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    let label_warning = (
+        "Warning".to_string(),
+        codes::BadArguments,
+        "The query label a!b of the hint is changed to a_b".to_string(),
+        1u64,
+    );
+    let rollback_warning = (
+        "Warning".to_string(),
+        codes::UnImplement,
+        "ROLLBACK has nothing to roll back, the statements are committed when they are executed"
+            .to_string(),
+        1u64,
+    );
+
+    // Two warnings of one statement, in the order they are pushed.
+    query::<EmptyRow>(&mut connection, "/*+ label(a!b) */ ROLLBACK")?;
+    assert_eq!(connection.warnings(), 2);
+    let received_data: Vec<(String, u16, String, u64)> = query(&mut connection, "SHOW WARNINGS")?;
+    assert_eq!(received_data, vec![
+        label_warning.clone(),
+        rollback_warning.clone()
+    ]);
+
+    // SHOW WARNINGS does not clear them, the next statement does.
+    let received_data: Vec<(String, u16, String, u64)> = query(&mut connection, "SHOW WARNINGS")?;
+    assert_eq!(received_data.len(), 2);
+    let received_data: Vec<u64> = query(&mut connection, "SELECT 1")?;
+    assert_eq!(received_data, vec![1]);
+    assert_eq!(connection.warnings(), 0);
+    let received_data: Vec<(String, u16, String, u64)> = query(&mut connection, "SHOW WARNINGS")?;
+    assert!(received_data.is_empty());
+
+    // Beyond the cap, the warnings are counted but suppressed.
+    query::<EmptyRow>(&mut connection, "SET max_warnings = 1")?;
+    query::<EmptyRow>(&mut connection, "/*+ label(a!b) */ ROLLBACK")?;
+    assert_eq!(connection.warnings(), 2);
+    let received_data: Vec<(String, u16, String, u64)> = query(&mut connection, "SHOW WARNINGS")?;
+    assert_eq!(received_data, vec![
+        label_warning,
+        (
+            "Warning".to_string(),
+            codes::TooManyWarnings,
+            "1 more warnings are suppressed".to_string(),
+            1u64,
+        )
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_float_format_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    let sql = "SELECT 1 / 0, -1 / 0, 0 / 0, 0 * -1.5, 1 / 3";
+
+    let received_data: Vec<(String, String, String, String, String)> = query(&mut connection, sql)?;
+    assert_eq!(received_data, vec![(
+        "inf".to_string(),
+        "-inf".to_string(),
+        "NaN".to_string(),
+        "0".to_string(),
+        "0.3333333333333333".to_string(),
+    )]);
+
+    query::<EmptyRow>(&mut connection, "SET output_float_precision = 3")?;
+    let received_data: Vec<(String, String, String, String, String)> = query(&mut connection, sql)?;
+    assert_eq!(received_data, vec![(
+        "inf".to_string(),
+        "-inf".to_string(),
+        "NaN".to_string(),
+        "0.000".to_string(),
+        "0.333".to_string(),
+    )]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_prepared_statement_with_on_execute() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    let sql = "SELECT sum(number) FROM numbers(?) WHERE number > ?";
+    let received_data: Vec<u64> = connection
+        .exec(sql, (10u64, 3u64))
+        .map_err_to_code(ErrorCode::UnknownException, || "Execute error")?;
+    assert_eq!(received_data, vec![39]);
+
+    let received_data: Vec<u64> = connection
+        .exec(sql, (20u64, 5u64))
+        .map_err_to_code(ErrorCode::UnknownException, || "Execute error")?;
+    assert_eq!(received_data, vec![175]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_prepared_insert_with_on_execute() -> Result<()> {
+    // The rows inserted by a prepared INSERT are the same as by the text protocol,
+    // a prepared SELECT with a bound integer gets the same result as the text protocol.
+
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    query::<EmptyRow>(
+        &mut connection,
+        "CREATE TABLE t_prepared(a UInt64, b String) Engine = Memory",
+    )?;
+    query::<EmptyRow>(
+        &mut connection,
+        "CREATE TABLE t_text(a UInt64, b String) Engine = Memory",
+    )?;
+
+    let insert = "INSERT INTO t_prepared VALUES(?, ?)";
+    for (a, b) in [(1u64, "x"), (2, "y"), (3, "z")] {
+        connection
+            .exec_drop(insert, (a, b))
+            .map_err_to_code(ErrorCode::UnknownException, || "Execute error")?;
+    }
+    query::<EmptyRow>(
+        &mut connection,
+        "INSERT INTO t_text VALUES(1, 'x'), (2, 'y'), (3, 'z')",
+    )?;
+
+    for table in ["t_prepared", "t_text"] {
+        let select = format!("SELECT a, b FROM {} WHERE a > ? ORDER BY a", table);
+        let received_data: Vec<(u64, String)> = connection
+            .exec(select.as_str(), (1u64,))
+            .map_err_to_code(ErrorCode::UnknownException, || "Execute error")?;
+        let text_data: Vec<(u64, String)> = query(
+            &mut connection,
+            &format!("SELECT a, b FROM {} WHERE a > 1 ORDER BY a", table),
+        )?;
+        assert_eq!(received_data, text_data, "{}", table);
+        assert_eq!(
+            received_data,
+            vec![(2, "y".to_string()), (3, "z".to_string())],
+            "{}",
+            table
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_prepared_statement_after_use_database() -> Result<()> {
+    // A prepared statement resolves its tables in the current database of each execute,
+    // and cannot be executed once it is closed.
+
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    for (db, rows) in [("db1", "(1), (2)"), ("db2", "(10), (20), (30)")] {
+        query::<EmptyRow>(
+            &mut connection,
+            &format!("CREATE DATABASE {} Engine = default", db),
+        )?;
+        query::<EmptyRow>(
+            &mut connection,
+            &format!("CREATE TABLE {}.t(a UInt64) Engine = Memory", db),
+        )?;
+        query::<EmptyRow>(
+            &mut connection,
+            &format!("INSERT INTO {}.t VALUES{}", db, rows),
+        )?;
+    }
+
+    let statement = connection
+        .prep("SELECT sum(a) FROM t WHERE a > ?")
+        .map_err_to_code(ErrorCode::UnknownException, || "Prepare error")?;
+
+    query::<EmptyRow>(&mut connection, "USE db1")?;
+    let received_data: Vec<u64> = connection
+        .exec(&statement, (0u64,))
+        .map_err_to_code(ErrorCode::UnknownException, || "Execute error")?;
+    assert_eq!(received_data, vec![3]);
+
+    query::<EmptyRow>(&mut connection, "USE db2")?;
+    let received_data: Vec<u64> = connection
+        .exec(&statement, (10u64,))
+        .map_err_to_code(ErrorCode::UnknownException, || "Execute error")?;
+    assert_eq!(received_data, vec![50]);
+
+    connection
+        .close(statement.clone())
+        .map_err_to_code(ErrorCode::UnknownException, || "Close error")?;
+    let res: std::result::Result<Vec<u64>, _> = connection.exec(&statement, (0u64,));
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_kv_table_functions_with_on_query() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.enable_kv_table_functions = "1".to_string();
+    let sessions = try_create_session_mgr_with_conf(conf)?;
+
+    let kv_api = sessions.get_kv_api().await?;
+    for (key, value) in [
+        ("__cluster/node-1", b"10.0.0.1".to_vec()),
+        ("__cluster/node-2", b"10.0.0.2".to_vec()),
+        ("__cluster/node-3", vec![0xde, 0xad, 0xbe, 0xef]),
+        ("__registry/udf", b"plus".to_vec()),
+    ] {
+        kv_api
+            .upsert_kv(key, MatchSeq::Any, Some(value), None)
+            .await?;
+    }
+
+    let mut handler = MySQLHandler::create(sessions);
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    let received_data: Vec<(String, u64, String)> = query(
+        &mut connection,
+        "SELECT key, seq, value FROM kv_list('__cluster/') WHERE value LIKE '10.%'",
+    )?;
+    assert_eq!(received_data, vec![
+        ("__cluster/node-1".to_string(), 1, "10.0.0.1".to_string()),
+        ("__cluster/node-2".to_string(), 2, "10.0.0.2".to_string()),
+    ]);
+
+    // There is no JOIN yet, match the keys against a small values list instead.
+    let received_data: Vec<String> = query(
+        &mut connection,
+        "SELECT key FROM kv_list('__') WHERE key = '__cluster/node-2' OR key = '__registry/udf' OR key = '__cluster/node-9'",
+    )?;
+    assert_eq!(received_data, vec!["__cluster/node-2", "__registry/udf"]);
+
+    // Values which are not valid UTF-8 come back as hex.
+    let received_data: Vec<String> = query(
+        &mut connection,
+        "SELECT value FROM kv_get('__cluster/node-3')",
+    )?;
+    assert_eq!(received_data, vec!["deadbeef"]);
+
+    let received_data: Vec<String> = query(
+        &mut connection,
+        "SELECT value FROM kv_get('__cluster/node-9')",
+    )?;
+    assert!(received_data.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_kv_table_functions_disabled_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    let result = query::<EmptyRow>(&mut connection, "SELECT * FROM kv_list('__cluster/')");
+    assert!(result.is_err());
+    let result = query::<EmptyRow>(&mut connection, "SELECT * FROM kv_get('__cluster/')");
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_panicked_query_with_on_query() -> Result<()> {
+    let sessions = try_create_session_mgr(Some(2))?;
+    let mut handler = MySQLHandler::create(sessions.clone());
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+    let mut other_connection = create_connection(runnable_server.port())?;
+
+    // The panic is returned as an error, the connection keeps serving.
+    match query::<EmptyRow>(&mut connection, "SELECT crashme()") {
+        Ok(_) => assert!(false, "Expected the panic as an error"),
+        Err(error) => assert!(error.message().contains("crash me function")),
+    }
+    let received_data: Vec<u64> = query(&mut connection, "SELECT 1")?;
+    assert_eq!(received_data, vec![1]);
+
+    // So do the other sessions, which can read the panic from the query log.
+    let received_data: Vec<(String, u16)> = query(
+        &mut other_connection,
+        "SELECT status, error_code FROM system.query_log WHERE query = 'SELECT crashme()'",
+    )?;
+    assert_eq!(received_data, vec![(
+        "panicked".to_string(),
+        codes::PanicError
+    )]);
+
+    // The slot of the connection is freed once it is closed.
+    drop(connection);
+    let mut accepted = false;
+    for _ in 0..50 {
+        if create_connection(runnable_server.port()).is_ok() {
+            accepted = true;
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(accepted);
+
+    let received_data: Vec<u64> = query(&mut other_connection, "SELECT 1")?;
+    assert_eq!(received_data, vec![1]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_kill_query_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(3))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut killer = create_connection(runnable_server.port())?;
+
+    // KILL QUERY aborts the scan, the connection keeps serving.
+    let connection = create_connection(runnable_server.port())?;
+    let (running, id) = start_long_query(connection, &mut killer)?;
+    query::<EmptyRow>(&mut killer, &format!("KILL QUERY `{}`", id))?;
+    let (result, mut connection) = running
+        .recv_timeout(Duration::from_secs(10))
+        .map_err_to_code(ErrorCode::UnknownException, || "The query is not aborted")?;
+    match result {
+        Ok(_) => assert!(false, "Expected the query to be aborted"),
+        Err(error) => assert!(error.message().contains("Aborted query")),
+    }
+    let received_data: Vec<u64> = query(&mut connection, "SELECT 1")?;
+    assert_eq!(received_data, vec![1]);
+
+    // KILL CONNECTION aborts the scan and closes the connection.
+    let (running, id) = start_long_query(connection, &mut killer)?;
+    query::<EmptyRow>(&mut killer, &format!("KILL CONNECTION `{}`", id))?;
+    let (result, mut connection) = running
+        .recv_timeout(Duration::from_secs(10))
+        .map_err_to_code(ErrorCode::UnknownException, || "The query is not aborted")?;
+    assert!(result.is_err());
+    assert!(query::<u64>(&mut connection, "SELECT 1").is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_show_processlist_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(3))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut observer = create_connection(runnable_server.port())?;
+
+    let connection = create_connection(runnable_server.port())?;
+    let (running, id) = start_long_query(connection, &mut observer)?;
+    std::thread::sleep(Duration::from_millis(100));
+
+    let processes: Vec<Row> = query(&mut observer, "SHOW PROCESSLIST")?;
+    let process = processes
+        .iter()
+        .find(|row| row.get::<String, _>("id").as_deref() == Some(id.as_str()))
+        .expect("the session of the long query");
+    assert_eq!(
+        process.get::<Option<String>, _>("query"),
+        Some(Some(String::from(LONG_QUERY)))
+    );
+    assert!(process.get::<Option<u64>, _>("elapsed_ms").flatten() > Some(0));
+    assert!(process
+        .get::<Option<u64>, _>("read_rows")
+        .flatten()
+        .is_some());
+    assert!(process.get::<Option<String>, _>("host").flatten().is_some());
+
+    query::<EmptyRow>(&mut observer, &format!("KILL QUERY `{}`", id))?;
+    let (result, _connection) = running
+        .recv_timeout(Duration::from_secs(10))
+        .map_err_to_code(ErrorCode::UnknownException, || "The query is not aborted")?;
+    assert!(result.is_err());
+
+    // The session stays, without a running query once the query is released.
+    let mut process = None;
+    for _ in 0..100 {
+        let processes: Vec<Row> = query(&mut observer, "SHOW PROCESSLIST")?;
+        process = processes
+            .into_iter()
+            .find(|row| row.get::<String, _>("id").as_deref() == Some(id.as_str()));
+        match &process {
+            Some(row) if row.get::<Option<String>, _>("query") == Some(None) => break,
+            _ => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+    let process = process.expect("the session of the killed query");
+    for name in ["query", "elapsed_ms", "read_rows", "read_bytes"] {
+        assert_eq!(
+            process.get::<Option<String>, _>(name),
+            Some(None),
+            "{}",
+            name
+        );
+    }
+
+    Ok(())
+}
+
+const LONG_QUERY: &str = "SELECT sum(number) FROM numbers(100000000000)";
+
+type LongQueryResult = (Result<Vec<u64>>, Conn);
+
+/// Runs a scan that lasts for minutes on the connection, returns the session id of the connection
+/// once the query shows up in system.processes.
+fn start_long_query(
+    mut connection: Conn,
+    other_connection: &mut Conn,
+) -> Result<(Receiver<LongQueryResult>, String)> {
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+        let result = query::<u64>(&mut connection, LONG_QUERY);
+        let _ = tx.send((result, connection));
+    });
+
+    for _ in 0..100 {
+        let processes: Vec<(String, Option<String>)> = query(
+            other_connection,
+            "SELECT id, extra_info FROM system.processes",
+        )?;
+        let running = processes
+            .into_iter()
+            .find(|(_, extra_info)| extra_info.as_deref() == Some(LONG_QUERY));
+        if let Some((id, _)) = running {
+            return Ok((rx, id));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Err(ErrorCode::UnknownSession("The long query is not running"))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_query_hints_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    // An unknown hint is a warning, the others are applied.
+    let received_data: Vec<u64> = query(
+        &mut connection,
+        "/*+ no_distributed max_threads(2) use_index(a) */ SELECT sum(number) FROM numbers(4)",
+    )?;
+    assert_eq!(received_data, vec![6]);
+    assert_eq!(connection.warnings(), 1);
+
+    // The known hints are kept in the query log.
+    let received_data: Vec<(String, String)> = query(
+        &mut connection,
+        "SELECT status, hints FROM system.query_log",
+    )?;
+    assert_eq!(received_data, vec![(
+        "finished".to_string(),
+        "no_distributed, max_threads(2)".to_string()
+    )]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_query_log_semantic_hash_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    // The queries only differ syntactically, but the last one.
+    for sql in [
+        "select number from numbers(10) where number > 1 and number < 5",
+        "SELECT number FROM numbers( 10 ) WHERE (number < 5) AND (number > 1)",
+        "select number from numbers(10) where number > 2 and number < 5",
+    ] {
+        query::<u64>(&mut connection, sql)?;
+    }
+
+    let hashes: Vec<Option<u64>> = query(
+        &mut connection,
+        "SELECT semantic_hash FROM system.query_log",
+    )?;
+    assert_eq!(hashes.len(), 3);
+    assert!(hashes[0].is_some());
+    assert_eq!(hashes[0], hashes[1]);
+    assert_ne!(hashes[0], hashes[2]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_multi_statement_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    // The result is the one of the last statement.
+    let received_data: Vec<String> = query(&mut connection, "USE system; SELECT database()")?;
+    assert_eq!(received_data, vec!["system"]);
+
+    // The CREATE TABLE statements are summarized in one result.
+    query::<EmptyRow>(&mut connection, "USE default; SET ddl_batching = 1")?;
+    let received_data: Vec<(String, String, String)> = query(
+        &mut connection,
+        "CREATE TABLE a(x int) Engine = Null; \
+        CREATE TABLE IF NOT EXISTS a(x int) Engine = Null; \
+        CREATE TABLE b(x int) Engine = Null",
+    )?;
+    assert_eq!(received_data, vec![
+        ("a".to_string(), "created".to_string(), "".to_string()),
+        (
+            "a".to_string(),
+            "already exists".to_string(),
+            "".to_string()
+        ),
+        ("b".to_string(), "created".to_string(), "".to_string()),
+    ]);
+
+    // The first failure stops the script.
+    query::<EmptyRow>(&mut connection, "SET ddl_batching = 0")?;
+    let res = query::<EmptyRow>(
+        &mut connection,
+        "CREATE TABLE a(x int) Engine = Null; CREATE TABLE c(x int) Engine = Null",
+    );
+    assert!(res.is_err());
+    let received_data: Vec<String> = query(
+        &mut connection,
+        "SELECT name FROM system.tables WHERE database = 'default' ORDER BY name",
+    )?;
+    assert_eq!(received_data, vec!["a", "b"]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_affected_rows_with_on_query() -> Result<()> {
+    let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let runnable_server = handler.start(listening).await?;
+    let mut connection = create_connection(runnable_server.port())?;
+
+    query::<EmptyRow>(
+        &mut connection,
+        "CREATE TABLE t_affected(a UInt64) Engine = Memory",
+    )?;
+    assert_eq!(connection.affected_rows(), 0);
+
+    query::<EmptyRow>(
+        &mut connection,
+        "INSERT INTO t_affected VALUES(1), (2), (3)",
+    )?;
+    assert_eq!(connection.affected_rows(), 3);
+
+    connection
+        .exec_drop("INSERT INTO t_affected VALUES(?)", (4u64,))
+        .map_err_to_code(ErrorCode::UnknownException, || "Execute error")?;
+    assert_eq!(connection.affected_rows(), 1);
+
+    // A SELECT still returns a result set.
+    let received_data: Vec<u64> = query(&mut connection, "SELECT a FROM t_affected ORDER BY a")?;
+    assert_eq!(received_data, vec![1, 2, 3, 4]);
+    assert_eq!(connection.affected_rows(), 0);
+
+    query::<EmptyRow>(&mut connection, "DROP TABLE t_affected")?;
+    assert_eq!(connection.affected_rows(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_ok_response_status_flags() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    let response = ok_response(&ctx)?;
+    assert_eq!(response.status_flags, StatusFlags::SERVER_STATUS_AUTOCOMMIT);
+    assert_eq!(response.affected_rows, 0);
+
+    ctx.add_affected_rows(2);
+    ctx.add_affected_rows(3);
+    let response = ok_response(&ctx)?;
+    assert_eq!(response.affected_rows, 5);
+
+    ctx.set_in_transaction(true);
+    ctx.push_warning(codes::UnImplement, "warning");
+    let response = ok_response(&ctx)?;
+    assert!(response
+        .status_flags
+        .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
+    assert_eq!(response.warnings, 1);
+
+    ctx.set_in_transaction(false);
+    ctx.get_settings().set_autocommit(0)?;
+    let response = ok_response(&ctx)?;
+    assert!(response.status_flags.is_empty());
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_rejected_session_with_sequence() -> Result<()> {
     let mut handler = MySQLHandler::create(try_create_session_mgr(Some(1))?);
@@ -78,6 +816,53 @@ async fn test_rejected_session_with_sequence() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_expired_session_with_sequence() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.max_active_sessions = 1;
+    conf.query.session_idle_timeout_secs = 2;
+    let clock = VirtualClock::create();
+    let sessions = try_create_session_mgr_with_clock(conf, SharedClock::create(clock.clone()))?;
+    let mut handler = MySQLHandler::create(sessions.clone());
+
+    let listening = "0.0.0.0:0".parse::<SocketAddr>()?;
+    let listening = handler.start(listening).await?;
+
+    let mut idle_conn = create_connection(listening.port())?;
+
+    // Each query starts the idle time over.
+    for _ in 0..3 {
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(sessions.expire_idle_sessions(Duration::from_secs(2)), 0);
+        let received_data: Vec<u64> = query(&mut idle_conn, "SELECT 1")?;
+        assert_eq!(received_data, vec![1]);
+    }
+    assert!(create_connection(listening.port()).is_err());
+
+    // The reaper may expire it as well. The session may still be ending the last query when
+    // the client has the reply, then it is not idle yet.
+    clock.advance(Duration::from_secs(2));
+    for _ in 0..100 {
+        sessions.expire_idle_sessions(Duration::from_secs(2));
+        if sessions.processes_info().is_empty() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let mut conn = create_connection(listening.port())?;
+    let received_data: Vec<u64> = query(&mut conn, "SELECT 1")?;
+    assert_eq!(received_data, vec![1]);
+
+    match idle_conn.query_drop("SELECT 1") {
+        Err(mysql::Error::MySqlError(error)) => {
+            assert!(error.message.contains("Session expired"), "{}", error)
+        }
+        other => panic!("expect the session expired, got: {:?}", other),
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_rejected_session_with_parallel() -> Result<()> {
     enum CreateServerResult {
@@ -12,34 +12,58 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
 use std::time::Instant;
+use std::time::SystemTime;
 
 use common_datablocks::DataBlock;
+use common_datavalues::DataValue;
+use common_datavalues::FloatFormat;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_io::prelude::*;
 use common_runtime::tokio;
+use common_streams::SendableDataBlockStream;
+use metrics::counter;
 use metrics::histogram;
+use msql_srv::Column;
+use msql_srv::ColumnFlags;
+use msql_srv::ColumnType;
 use msql_srv::ErrorKind;
 use msql_srv::InitWriter;
 use msql_srv::MysqlShim;
+use msql_srv::OkResponse;
 use msql_srv::ParamParser;
+use msql_srv::ParamValue;
 use msql_srv::QueryResultWriter;
 use msql_srv::StatementMetaWriter;
+use msql_srv::StatusFlags;
+use msql_srv::ValueInner;
 use rand::RngCore;
-use tokio_stream::StreamExt;
 
 use crate::interpreters::InterpreterFactory;
+use crate::interpreters::ScriptInterpreter;
+use crate::servers::mysql::mysql_result_buffer::ResultBuffer;
 use crate::servers::mysql::writers::DFInitResultWriter;
 use crate::servers::mysql::writers::DFQueryResultWriter;
 use crate::servers::server::mock::get_mock_user;
 use crate::sessions::DatabendQueryContextRef;
+use crate::sessions::QueryLogEntry;
+use crate::sessions::QueryLogStatus;
 use crate::sessions::SessionRef;
-use crate::sql::DfHint;
+use crate::sql::bind_placeholders;
 use crate::sql::PlanParser;
+use crate::sql::SqlShape;
 
-struct InteractiveWorkerBase<W: std::io::Write>(PhantomData<W>);
+struct InteractiveWorkerBase<W: std::io::Write> {
+    phantom: PhantomData<W>,
+    /// The queries of the prepared statements, by the statement ids.
+    prepared: HashMap<u32, String>,
+    next_statement_id: u32,
+}
 
 pub struct InteractiveWorker<W: std::io::Write> {
     base: InteractiveWorkerBase<W>,
@@ -52,15 +76,12 @@ impl<W: std::io::Write> MysqlShim<W> for InteractiveWorker<W> {
     type Error = ErrorCode;
 
     fn on_prepare(&mut self, query: &str, writer: StatementMetaWriter<W>) -> Result<()> {
-        if self.session.is_aborting() {
+        if let Err(cause) = self.check_aborting() {
             writer.error(
                 ErrorKind::ER_ABORTING_CONNECTION,
-                "Aborting this connection. because we are try aborting server.".as_bytes(),
+                cause.message().as_bytes(),
             )?;
-
-            return Err(ErrorCode::AbortedSession(
-                "Aborting this connection. because we are try aborting server.",
-            ));
+            return Err(cause);
         }
 
         self.base
@@ -73,19 +94,24 @@ impl<W: std::io::Write> MysqlShim<W> for InteractiveWorker<W> {
         param: ParamParser,
         writer: QueryResultWriter<W>,
     ) -> Result<()> {
-        if self.session.is_aborting() {
+        if let Err(cause) = self.check_aborting() {
             writer.error(
                 ErrorKind::ER_ABORTING_CONNECTION,
-                "Aborting this connection. because we are try aborting server.".as_bytes(),
+                cause.message().as_bytes(),
             )?;
-
-            return Err(ErrorCode::AbortedSession(
-                "Aborting this connection. because we are try aborting server.",
-            ));
+            return Err(cause);
         }
 
-        self.base
-            .do_execute(id, param, writer, self.session.create_context())
+        let start = Instant::now();
+        let context = self.session.create_context();
+
+        match self.base.do_bind(id, param, &context) {
+            Ok(query) => {
+                context.attach_query_str(&query);
+                self.run_query(&query, &context, writer, start)
+            }
+            Err(cause) => DFQueryResultWriter::create(writer).write_error(cause),
+        }
     }
 
     fn on_close(&mut self, id: u32) {
@@ -93,46 +119,28 @@ impl<W: std::io::Write> MysqlShim<W> for InteractiveWorker<W> {
     }
 
     fn on_query(&mut self, query: &str, writer: QueryResultWriter<W>) -> Result<()> {
-        if self.session.is_aborting() {
+        if let Err(cause) = self.check_aborting() {
             writer.error(
                 ErrorKind::ER_ABORTING_CONNECTION,
-                "Aborting this connection. because we are try aborting server.".as_bytes(),
+                cause.message().as_bytes(),
             )?;
-
-            return Err(ErrorCode::AbortedSession(
-                "Aborting this connection. because we are try aborting server.",
-            ));
+            return Err(cause);
         }
 
         let start = Instant::now();
         let context = self.session.create_context();
 
         context.attach_query_str(query);
-        if let Err(cause) =
-            DFQueryResultWriter::create(writer).write(self.base.do_query(query, context))
-        {
-            let new_error = cause.add_message(query);
-            return Err(new_error);
-        };
-
-        histogram!(
-            super::mysql_metrics::METRIC_MYSQL_PROCESSOR_REQUEST_DURATION,
-            start.elapsed()
-        );
-
-        Ok(())
+        self.run_query(query, &context, writer, start)
     }
 
     fn on_init(&mut self, database_name: &str, writer: InitWriter<W>) -> Result<()> {
-        if self.session.is_aborting() {
+        if let Err(cause) = self.check_aborting() {
             writer.error(
                 ErrorKind::ER_ABORTING_CONNECTION,
-                "Aborting this connection. because we are try aborting server.".as_bytes(),
+                cause.message().as_bytes(),
             )?;
-
-            return Err(ErrorCode::AbortedSession(
-                "Aborting this connection. because we are try aborting server.",
-            ));
+            return Err(cause);
         }
 
         let context = self.session.create_context();
@@ -192,7 +200,11 @@ impl<W: std::io::Write> MysqlShim<W> for InteractiveWorker<W> {
                 }
                 _ => auth_data.to_vec(),
             };
-            return user.authenticate_user(encode_password);
+            let authenticated = user.authenticate_user(encode_password);
+            if authenticated {
+                self.session.set_current_user(user.name.clone());
+            }
+            return authenticated;
         }
 
         false
@@ -202,48 +214,99 @@ impl<W: std::io::Write> MysqlShim<W> for InteractiveWorker<W> {
 impl<W: std::io::Write> InteractiveWorkerBase<W> {
     fn do_prepare(
         &mut self,
-        _: &str,
+        query: &str,
         writer: StatementMetaWriter<'_, W>,
-        _: DatabendQueryContextRef,
+        context: DatabendQueryContextRef,
     ) -> Result<()> {
-        writer.error(
-            ErrorKind::ER_UNKNOWN_ERROR,
-            "Prepare is not support in Databend.".as_bytes(),
-        )?;
+        let dialect = context.get_settings().get_parser_dialect();
+        let shape = match dialect.and_then(|dialect| SqlShape::parse(query, dialect)) {
+            Ok(shape) => shape,
+            Err(cause) => {
+                writer.error(ErrorKind::ER_PARSE_ERROR, cause.message().as_bytes())?;
+                return Ok(());
+            }
+        };
+
+        // The columns of the result are only known once the statement is executed.
+        let params = (0..shape.placeholders)
+            .map(|_| Column {
+                table: String::new(),
+                column: String::from("?"),
+                coltype: ColumnType::MYSQL_TYPE_VAR_STRING,
+                colflags: ColumnFlags::empty(),
+            })
+            .collect::<Vec<_>>();
+
+        let id = self.next_statement_id;
+        self.next_statement_id = self.next_statement_id.wrapping_add(1);
+        writer.reply(id, &params, &[])?;
+        self.prepared.insert(id, query.to_string());
         Ok(())
     }
 
-    fn do_execute(
+    /// The query of the prepared statement with the literals of the params,
+    /// it is planned from the plan template of the statement if there is one.
+    fn do_bind(
         &mut self,
-        _: u32,
-        _: ParamParser<'_>,
-        writer: QueryResultWriter<'_, W>,
-        _: DatabendQueryContextRef,
-    ) -> Result<()> {
-        writer.error(
-            ErrorKind::ER_UNKNOWN_ERROR,
-            "Execute is not support in Databend.".as_bytes(),
-        )?;
-        Ok(())
+        id: u32,
+        params: ParamParser<'_>,
+        context: &DatabendQueryContextRef,
+    ) -> Result<String> {
+        let query = self.prepared.get(&id).ok_or_else(|| {
+            ErrorCode::BadArguments(format!("Unknown prepared statement: {}", id))
+        })?;
+
+        let params = params
+            .into_iter()
+            .map(Self::param_value)
+            .collect::<Result<Vec<_>>>()?;
+        let dialect = context.get_settings().get_parser_dialect()?;
+        bind_placeholders(query, dialect, &params)
     }
 
-    fn do_close(&mut self, _: u32, _: DatabendQueryContextRef) {}
+    fn param_value(param: ParamValue) -> Result<DataValue> {
+        match param.value.into_inner() {
+            ValueInner::NULL => Ok(DataValue::Null),
+            ValueInner::Int(v) => Ok(DataValue::Int64(Some(v))),
+            ValueInner::UInt(v) => Ok(DataValue::UInt64(Some(v))),
+            ValueInner::Double(v) => Ok(DataValue::Float64(Some(v))),
+            ValueInner::Bytes(v) => Ok(DataValue::String(Some(v.to_vec()))),
+            _ => Err(ErrorCode::BadArguments(
+                "Unsupported parameter of the prepared statement, bind the dates and times as strings",
+            )),
+        }
+    }
 
-    fn do_query(
-        &mut self,
-        query: &str,
-        context: DatabendQueryContextRef,
-    ) -> Result<(Vec<DataBlock>, String)> {
+    fn do_close(&mut self, id: u32, _: DatabendQueryContextRef) {
+        self.prepared.remove(&id);
+    }
+
+    fn do_query(&mut self, query: &str, context: DatabendQueryContextRef) -> Result<QueryBlocks> {
         log::debug!("{}", query);
 
         let runtime = Self::build_runtime()?;
-        let (plan, hints) = PlanParser::create(context.clone()).build_with_hint_from_sql(query);
-
-        let start = Instant::now();
+        let parser = PlanParser::create(context.clone());
+        // The statements of a script are planned as it runs them.
+        let (interpreter, hints) = match parser.parse_script(query) {
+            Some((statements, hints)) => (
+                ScriptInterpreter::try_create(context.clone(), statements),
+                hints,
+            ),
+            None => {
+                let (plan, hints) = parser.build_with_template_from_sql(query);
+                if let Ok(plan) = &plan {
+                    context.attach_query_plan(plan);
+                }
+                let interpreter =
+                    plan.and_then(|plan| InterpreterFactory::get(context.clone(), plan));
+                (interpreter, hints)
+            }
+        };
+        let expected_error = hints.iter().find_map(|hint| hint.error_code);
 
-        let fetch_query_blocks = || -> Result<Vec<DataBlock>> {
+        let fetch_data_stream = || -> Result<SendableDataBlockStream> {
             let start = Instant::now();
-            let interpreter = InterpreterFactory::get(context.clone(), plan?)?;
+            let interpreter = interpreter?;
             let name = interpreter.name().to_string();
             let data_stream = runtime.block_on(interpreter.execute())?;
 
@@ -252,10 +315,32 @@ impl<W: std::io::Write> InteractiveWorkerBase<W> {
                 start.elapsed(),
                 "interpreter" => name
             );
-            runtime.block_on(data_stream.collect::<Result<Vec<DataBlock>>>())
+            Ok(data_stream)
+        };
+
+        // The blocks are pulled from the stream as they are written to the client.
+        let buffer = match fetch_data_stream() {
+            Ok(data_stream) => Some(ResultBuffer::create(runtime, data_stream, context)?),
+            Err(cause) => {
+                QueryBlocks::expect_error(cause, expected_error)?;
+                None
+            }
         };
-        let blocks = fetch_query_blocks();
 
+        Ok(QueryBlocks {
+            buffer,
+            expected_error,
+        })
+    }
+
+    /// COM_INIT_DB, switches the current database as `USE` does. The name is not a part of a
+    /// query, so it is taken as is, without quoting.
+    fn do_init(&mut self, database_name: &str, context: DatabendQueryContextRef) -> Result<()> {
+        context.set_current_database(database_name.to_string())
+    }
+
+    /// The info of the OK packet, the progress of the query and its warnings.
+    fn extra_info(context: &DatabendQueryContextRef, start: Instant) -> String {
         let progress = context.get_progress_value();
         let seconds = start.elapsed().as_millis() as f64 / 1000f64;
         let extra_info = format!(
@@ -267,36 +352,20 @@ impl<W: std::io::Write> InteractiveWorkerBase<W> {
             convert_byte_size((progress.read_bytes as f64) / (seconds as f64)),
         );
 
-        match blocks {
-            Ok(v) => Ok((v, extra_info)),
-            Err(e) => {
-                let hint = hints.iter().find(|v| v.error_code.is_some());
-                if let Some(DfHint {
-                    error_code: Some(code),
-                    ..
-                }) = hint
-                {
-                    if *code == e.code() {
-                        Ok((vec![DataBlock::empty()], extra_info))
-                    } else {
-                        let actual_code = e.code();
-                        Err(e.add_message(format!(
-                            "Expected server error code: {} but got: {}.",
-                            code, actual_code
-                        )))
-                    }
-                } else {
-                    Err(e)
-                }
+        let warnings = context.get_warnings();
+        match warnings.is_empty() {
+            true => extra_info,
+            false => {
+                let messages = warnings.iter().map(|w| w.message.as_str());
+                format!(
+                    "{} Warnings: {}",
+                    extra_info,
+                    messages.collect::<Vec<_>>().join("; ")
+                )
             }
         }
     }
 
-    fn do_init(&mut self, database_name: &str, context: DatabendQueryContextRef) -> Result<()> {
-        self.do_query(&format!("USE {};", database_name), context)?;
-        Ok(())
-    }
-
     fn build_runtime() -> Result<tokio::runtime::Runtime> {
         tokio::runtime::Builder::new_multi_thread()
             .enable_all()
@@ -323,9 +392,169 @@ impl<W: std::io::Write> InteractiveWorker<W> {
 
         InteractiveWorker::<W> {
             session,
-            base: InteractiveWorkerBase::<W>(PhantomData::<W>),
+            base: InteractiveWorkerBase::<W> {
+                phantom: PhantomData::<W>,
+                prepared: HashMap::new(),
+                next_statement_id: 1,
+            },
             salt: scramble,
             version: context.get_fuse_version(),
         }
     }
+
+    /// An aborting session serves no more requests, the connection is closed once the client
+    /// gets the error.
+    fn check_aborting(&self) -> Result<()> {
+        match (self.session.is_aborting(), self.session.is_expired()) {
+            (false, _) => Ok(()),
+            (true, true) => Err(ErrorCode::SessionExpired(
+                "Session expired, it was idle for longer than session_idle_timeout_secs",
+            )),
+            (true, false) => Err(ErrorCode::AbortedSession(
+                "Aborting this connection. because we are try aborting server.",
+            )),
+        }
+    }
+
+    /// Runs the query, writes its result and records it into the query log.
+    /// A panic of the query is returned as its error, so the connection keeps serving.
+    fn run_query(
+        &mut self,
+        query: &str,
+        context: &DatabendQueryContextRef,
+        writer: QueryResultWriter<W>,
+        start: Instant,
+    ) -> Result<()> {
+        let start_time = SystemTime::now();
+
+        let base = &mut self.base;
+        let mut writer = DFQueryResultWriter::create(writer);
+        let run = catch_unwind(AssertUnwindSafe(|| -> Result<()> {
+            let float_format = context
+                .get_settings()
+                .get_output_float_format(FloatFormat::text())?;
+            let mut blocks = base.do_query(query, context.clone())?;
+            writer.write(
+                || blocks.next_block(),
+                &float_format,
+                || {
+                    Ok(OkResponse {
+                        info: InteractiveWorkerBase::<W>::extra_info(context, start),
+                        ..ok_response(context)?
+                    })
+                },
+            )
+        }));
+        let (status, query_result) = match run {
+            Ok(Ok(())) => (QueryLogStatus::Finished, Ok(())),
+            Ok(Err(cause)) => (QueryLogStatus::Failed, Err(cause)),
+            Err(panic) => {
+                counter!(super::mysql_metrics::METRIC_MYSQL_PANICS, 1);
+                // The query may still run in the other threads.
+                self.session.force_kill_query();
+                let cause = ErrorCode::from_panic(panic.as_ref());
+                log::error!("Query {} panicked: {}", query, cause);
+                (QueryLogStatus::Panicked, Err(cause))
+            }
+        };
+
+        let (error_code, error_message) = match &query_result {
+            Ok(_) => (None, None),
+            Err(cause) => {
+                context.get_query_metrics().mark_failed();
+                (Some(cause.code()), Some(cause.message()))
+            }
+        };
+        let store_rpcs = context.get_query_metrics().get_values().store_rpcs;
+        let query_log = self.session.get_sessions_manager().get_query_log();
+        query_log.append(QueryLogEntry {
+            query_id: context.get_id(),
+            session_id: self.session.get_id(),
+            query: query.to_string(),
+            status,
+            error_code,
+            error_message,
+            start_time: QueryLogEntry::seconds_since_epoch(start_time),
+            duration_ms: start.elapsed().as_millis() as u64,
+            hints: context.get_query_hints().to_string(),
+            semantic_hash: context.get_query_semantic_hash(),
+            query_label: context.get_query_label(),
+            store_rpc_count: store_rpcs.values().map(|stat| stat.count).sum(),
+            store_rpc_time_ms: store_rpcs
+                .values()
+                .map(|stat| stat.total.as_millis() as u64)
+                .sum(),
+        });
+
+        // A part of the result may be written already, then the connection is closed.
+        if let Err(cause) = query_result.or_else(|cause| writer.write_error(cause)) {
+            let new_error = cause.add_message(query);
+            return Err(new_error);
+        };
+
+        histogram!(
+            super::mysql_metrics::METRIC_MYSQL_PROCESSOR_REQUEST_DURATION,
+            start.elapsed()
+        );
+
+        Ok(())
+    }
+}
+
+/// The blocks of the result of a query, pulled as they are written to the client.
+struct QueryBlocks {
+    buffer: Option<ResultBuffer>,
+    /// The error code expected by a hint of the query, e.g. `-- {ErrorCode 1002}` of the stateless tests.
+    expected_error: Option<u16>,
+}
+
+impl QueryBlocks {
+    fn next_block(&mut self) -> Option<Result<DataBlock>> {
+        match self.buffer.as_mut()?.next_block()? {
+            Ok(block) => Some(Ok(block)),
+            Err(cause) => match Self::expect_error(cause, self.expected_error) {
+                // The query succeeds without the rest of the result.
+                Ok(()) => {
+                    self.buffer = None;
+                    None
+                }
+                Err(cause) => Some(Err(cause)),
+            },
+        }
+    }
+
+    fn expect_error(cause: ErrorCode, expected_error: Option<u16>) -> Result<()> {
+        match expected_error {
+            Some(code) if code == cause.code() => Ok(()),
+            Some(code) => {
+                let actual_code = cause.code();
+                Err(cause.add_message(format!(
+                    "Expected server error code: {} but got: {}.",
+                    code, actual_code
+                )))
+            }
+            None => Err(cause),
+        }
+    }
+}
+
+/// The outcome of a statement that returns no result set, e.g. the rows written by an INSERT,
+/// and the status of the session that the OK packet carries, the clients use it to
+/// know whether a transaction is open.
+pub(crate) fn ok_response(context: &DatabendQueryContextRef) -> Result<OkResponse> {
+    let mut status_flags = StatusFlags::empty();
+    if context.is_in_transaction() {
+        status_flags |= StatusFlags::SERVER_STATUS_IN_TRANS;
+    }
+    if context.get_settings().get_autocommit()? == 1 {
+        status_flags |= StatusFlags::SERVER_STATUS_AUTOCOMMIT;
+    }
+
+    Ok(OkResponse {
+        affected_rows: context.get_affected_rows(),
+        last_insert_id: context.get_last_insert_id(),
+        status_flags,
+        warnings: context.get_warning_count().min(u16::MAX as u64) as u16,
+        ..Default::default()
+    })
 }
@@ -14,3 +14,4 @@
 
 pub static METRIC_MYSQL_PROCESSOR_REQUEST_DURATION: &str = "mysql.process_request_duration";
 pub static METRIC_INTERPRETER_USEDTIME: &str = "interpreter.usedtime";
+pub static METRIC_MYSQL_PANICS: &str = "mysql.panics";
@@ -0,0 +1,225 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::mpsc;
+use common_runtime::tokio::sync::Semaphore;
+use common_streams::SendableDataBlockStream;
+use futures::FutureExt;
+use metrics::counter;
+use tokio_stream::StreamExt;
+
+use crate::sessions::DatabendQueryContextRef;
+use crate::sessions::QueryMetrics;
+
+struct BufferedBlock {
+    block: DataBlock,
+    bytes: u64,
+    permits: u32,
+}
+
+/// The blocks of a query result between its pipeline and the MySQL writer.
+///
+/// A task of the query runtime pulls the pipeline, it stops pulling while the blocks not
+/// written to the client yet exceed the max_result_buffer_bytes setting, so a client reading
+/// slowly slows the query down rather than growing the buffer. The buffered bytes are
+/// accounted to the session, see `result_buffer_bytes` of system.processes.
+/// If the client doesn't read for result_stall_timeout, the query is aborted and the
+/// connection closed.
+pub struct ResultBuffer {
+    runtime: tokio::runtime::Runtime,
+    receiver: mpsc::UnboundedReceiver<Result<BufferedBlock>>,
+    permits: Option<Arc<Semaphore>>,
+    buffered_bytes: Arc<AtomicU64>,
+    metrics: Arc<QueryMetrics>,
+    // The block returned by the last `next_block`, it is buffered until it is written.
+    writing: Option<(u64, u32)>,
+}
+
+impl ResultBuffer {
+    /// Starts to pull the stream on the runtime, which is kept until the result is written.
+    pub fn create(
+        runtime: tokio::runtime::Runtime,
+        stream: SendableDataBlockStream,
+        context: DatabendQueryContextRef,
+    ) -> Result<ResultBuffer> {
+        let settings = context.get_settings();
+        let max_bytes = settings.get_max_result_buffer_bytes()?.min(u32::MAX as u64);
+        let permits = match max_bytes {
+            0 => None,
+            max_bytes => Some(Arc::new(Semaphore::new(max_bytes as usize))),
+        };
+        let stall_timeout = match settings.get_result_stall_timeout()? {
+            0 => None,
+            millis => Some(Duration::from_millis(millis)),
+        };
+
+        let buffered_bytes = context.get_result_buffer_bytes();
+        let metrics = context.get_query_metrics();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let panic_sender = sender.clone();
+        let pull = Self::pull(
+            stream,
+            sender,
+            permits.clone(),
+            max_bytes,
+            buffered_bytes.clone(),
+            stall_timeout,
+            context,
+        );
+        runtime.spawn(async move {
+            // A panic of the pipeline fails the query, rather than truncates its result.
+            if let Err(panic) = AssertUnwindSafe(pull).catch_unwind().await {
+                counter!(super::mysql_metrics::METRIC_MYSQL_PANICS, 1);
+                let cause = ErrorCode::from_panic(panic.as_ref());
+                log::error!("Query result panicked: {}", cause);
+                let _ = panic_sender.send(Err(cause));
+            }
+        });
+
+        Ok(ResultBuffer {
+            runtime,
+            receiver,
+            permits,
+            buffered_bytes,
+            metrics,
+            writing: None,
+        })
+    }
+
+    /// The next block of the result, the previous one is released as it is written to the client.
+    pub fn next_block(&mut self) -> Option<Result<DataBlock>> {
+        self.release_written();
+
+        match self.runtime.block_on(self.receiver.recv())? {
+            Err(cause) => Some(Err(cause)),
+            Ok(buffered) => {
+                let rows = buffered.block.num_rows();
+                self.metrics.incr_result(rows, buffered.bytes as usize);
+                self.writing = Some((buffered.bytes, buffered.permits));
+                Some(Ok(buffered.block))
+            }
+        }
+    }
+
+    fn release_written(&mut self) {
+        if let Some((bytes, permits)) = self.writing.take() {
+            self.release(bytes, permits);
+        }
+    }
+
+    fn release(&self, bytes: u64, permits: u32) {
+        self.buffered_bytes.fetch_sub(bytes, Ordering::Relaxed);
+        if let Some(semaphore) = &self.permits {
+            semaphore.add_permits(permits as usize);
+        }
+    }
+
+    async fn pull(
+        mut stream: SendableDataBlockStream,
+        sender: mpsc::UnboundedSender<Result<BufferedBlock>>,
+        permits: Option<Arc<Semaphore>>,
+        max_bytes: u64,
+        buffered_bytes: Arc<AtomicU64>,
+        stall_timeout: Option<Duration>,
+        context: DatabendQueryContextRef,
+    ) {
+        while let Some(block) = stream.next().await {
+            let block = match block {
+                Ok(block) => block,
+                Err(cause) => {
+                    let _ = sender.send(Err(cause));
+                    return;
+                }
+            };
+
+            let bytes = block.memory_size() as u64;
+            let mut block_permits = 0;
+            if let Some(semaphore) = &permits {
+                // A block larger than the buffer is pulled once the buffer is empty.
+                block_permits = bytes.min(max_bytes) as u32;
+
+                let acquire = semaphore.acquire_many(block_permits);
+                let acquired = match stall_timeout {
+                    None => acquire.await,
+                    Some(stall_timeout) => match tokio::time::timeout(stall_timeout, acquire).await
+                    {
+                        Ok(acquired) => acquired,
+                        Err(_) => {
+                            let _ = sender.send(Err(Self::abort_stalled(&context, stall_timeout)));
+                            return;
+                        }
+                    },
+                };
+
+                match acquired {
+                    Ok(acquired) => acquired.forget(),
+                    // The result is not written anymore, e.g. the connection is closed.
+                    Err(_) => return,
+                }
+            }
+
+            buffered_bytes.fetch_add(bytes, Ordering::Relaxed);
+            let buffered = BufferedBlock {
+                block,
+                bytes,
+                permits: block_permits,
+            };
+            if sender.send(Ok(buffered)).is_err() {
+                buffered_bytes.fetch_sub(bytes, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    fn abort_stalled(context: &DatabendQueryContextRef, stall_timeout: Duration) -> ErrorCode {
+        let reason = format!(
+            "The client didn't read the result for {}ms, over the result_stall_timeout",
+            stall_timeout.as_millis()
+        );
+        log::warn!(
+            "Aborting the query {} and closing its connection: {}",
+            context.get_id(),
+            reason
+        );
+        context.force_kill_session();
+        ErrorCode::AbortedSession(reason)
+    }
+}
+
+impl Drop for ResultBuffer {
+    fn drop(&mut self) {
+        self.release_written();
+
+        // Stops the pulling task, and releases the blocks which are not written.
+        if let Some(semaphore) = &self.permits {
+            semaphore.close();
+        }
+        self.receiver.close();
+        while let Ok(buffered) = self.receiver.try_recv() {
+            if let Ok(buffered) = buffered {
+                self.release(buffered.bytes, buffered.permits);
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_datablocks::DataBlock;
+use common_datavalues::series::Series;
+use common_datavalues::series::SeriesFrom;
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataType;
+use common_exception::exception::ABORT_SESSION;
+use common_exception::Result;
+use common_runtime::tokio;
+use common_streams::DataBlockStream;
+use common_streams::SendableDataBlockStream;
+use tokio_stream::StreamExt;
+
+use crate::servers::mysql::mysql_result_buffer::ResultBuffer;
+
+/// A stream of `blocks` blocks of the same size, counting the blocks pulled from it.
+fn counted_stream(blocks: usize, pulled: Arc<AtomicUsize>) -> (SendableDataBlockStream, u64) {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64; 1000])]);
+    let block_bytes = block.memory_size() as u64;
+
+    let stream = DataBlockStream::create(schema, None, vec![block; blocks]).map(move |block| {
+        pulled.fetch_add(1, Ordering::Relaxed);
+        block
+    });
+    (Box::pin(stream), block_bytes)
+}
+
+fn create_runtime() -> Result<tokio::runtime::Runtime> {
+    Ok(tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?)
+}
+
+#[test]
+fn test_result_buffer_backpressure() -> Result<()> {
+    let sessions = crate::tests::try_create_session_mgr(None)?;
+    let session = sessions.create_session("TestSession")?;
+    let ctx = session.create_context();
+
+    let pulled = Arc::new(AtomicUsize::new(0));
+    let (stream, block_bytes) = counted_stream(10, pulled.clone());
+    ctx.get_settings()
+        .set_max_result_buffer_bytes(2 * block_bytes)?;
+    ctx.get_settings().set_result_stall_timeout(0)?;
+
+    // The client reads the first block, and stalls writing it.
+    let mut buffer = ResultBuffer::create(create_runtime()?, stream, ctx.clone())?;
+    assert!(buffer.next_block().is_some());
+
+    // The block being written and the next one are buffered, the third one waits for the room.
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(3, pulled.load(Ordering::Relaxed));
+    assert_eq!(2 * block_bytes, session.process_info().result_buffer_bytes);
+
+    // The query doesn't progress while the client is stalled.
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(3, pulled.load(Ordering::Relaxed));
+    assert_eq!(2 * block_bytes, session.process_info().result_buffer_bytes);
+
+    // Then the client reads the rest.
+    let mut blocks = 1;
+    while let Some(block) = buffer.next_block() {
+        block?;
+        blocks += 1;
+        assert!(session.process_info().result_buffer_bytes <= 2 * block_bytes);
+    }
+    assert_eq!(10, blocks);
+
+    drop(buffer);
+    assert_eq!(0, session.process_info().result_buffer_bytes);
+
+    Ok(())
+}
+
+#[test]
+fn test_result_buffer_stall_timeout() -> Result<()> {
+    let sessions = crate::tests::try_create_session_mgr(None)?;
+    let session = sessions.create_session("TestSession")?;
+    let ctx = session.create_context();
+
+    let pulled = Arc::new(AtomicUsize::new(0));
+    let (stream, block_bytes) = counted_stream(10, pulled);
+    ctx.get_settings()
+        .set_max_result_buffer_bytes(block_bytes)?;
+    ctx.get_settings().set_result_stall_timeout(100)?;
+
+    let mut buffer = ResultBuffer::create(create_runtime()?, stream, ctx)?;
+    assert!(buffer.next_block().is_some());
+
+    // The client doesn't read for longer than the timeout.
+    std::thread::sleep(Duration::from_millis(500));
+    assert!(session.is_aborting());
+
+    let error = loop {
+        match buffer.next_block() {
+            None => panic!("The result of the stalled query must end with an error"),
+            Some(Ok(_)) => continue,
+            Some(Err(error)) => break error,
+        }
+    };
+    assert_eq!(ABORT_SESSION, error.code());
+    assert_eq!(
+        "The client didn't read the result for 100ms, over the result_stall_timeout",
+        error.message()
+    );
+
+    drop(buffer);
+    assert_eq!(0, session.process_info().result_buffer_bytes);
+
+    Ok(())
+}
@@ -12,13 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::ErrorKind;
+use std::io::Read;
 use std::net::Shutdown;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
 
 use common_exception::exception::ABORT_SESSION;
+use common_exception::exception::SESSION_EXPIRED;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_exception::ToErrorCode;
 use common_runtime::tokio::net::TcpStream;
+use metrics::counter;
 use msql_srv::MysqlIntermediary;
 
 use crate::servers::mysql::mysql_interactive_worker::InteractiveWorker;
@@ -26,10 +34,33 @@ use crate::sessions::SessionRef;
 
 pub struct MySQLConnection;
 
+/// Releases the session of the connection when its thread exits, normally or by a panic.
+struct ConnectionGuard {
+    session: SessionRef,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        // Abort the query which may still run in the other threads, so its resources are released,
+        // the session is removed from the manager once the session refs are dropped.
+        self.session.force_kill_query();
+    }
+}
+
 impl MySQLConnection {
     pub fn run_on_stream(session: SessionRef, stream: TcpStream) -> Result<()> {
         let blocking_stream = Self::convert_stream(stream)?;
         MySQLConnection::attach_session(&session, &blocking_stream)?;
+
+        let idle_timeout = session
+            .get_sessions_manager()
+            .get_conf()
+            .query
+            .mysql_handler_idle_timeout_secs;
+        if idle_timeout > 0 {
+            blocking_stream.set_read_timeout(Some(Duration::from_secs(idle_timeout)))?;
+        }
+
         std::thread::spawn(move || {
             MySQLConnection::session_executor(session, blocking_stream);
         });
@@ -38,15 +69,35 @@ impl MySQLConnection {
     }
 
     fn session_executor(session: SessionRef, blocking_stream: std::net::TcpStream) {
-        let interactive_worker = InteractiveWorker::create(session);
-        if let Err(error) = MysqlIntermediary::run_on_tcp(interactive_worker, blocking_stream) {
-            if error.code() != ABORT_SESSION {
+        let guard = ConnectionGuard { session };
+        let interactive_worker =
+            InteractiveWorker::create(SessionRef::create(Arc::clone(&guard.session)));
+
+        // The panics of the queries are caught by the worker, this one is out of them,
+        // e.g. of the protocol, the connection is closed without a reply.
+        let run = catch_unwind(AssertUnwindSafe(|| -> Result<()> {
+            let reader = IdleTimeoutReader {
+                session: SessionRef::create(Arc::clone(&guard.session)),
+                stream: blocking_stream.try_clone()?,
+            };
+            MysqlIntermediary::run_on(interactive_worker, reader, blocking_stream)
+        }));
+        match run {
+            Ok(Err(error)) if error.code() != ABORT_SESSION && error.code() != SESSION_EXPIRED => {
                 log::error!(
                     "Unexpected error occurred during query execution: {:?}",
                     error
                 );
             }
-        };
+            Ok(_) => {}
+            Err(panic) => {
+                counter!(super::mysql_metrics::METRIC_MYSQL_PANICS, 1);
+                log::error!(
+                    "MySQL connection panicked: {}",
+                    ErrorCode::from_panic(panic.as_ref())
+                );
+            }
+        }
     }
 
     fn attach_session(session: &SessionRef, blocking_stream: &std::net::TcpStream) -> Result<()> {
@@ -77,3 +128,29 @@ impl MySQLConnection {
         Ok(stream)
     }
 }
+
+/// The commands of a connection, the read timeout of the stream is the idle timeout of the
+/// connection: once it is over, the connection is closed as if the client closed it.
+/// Every packet read is an activity of the session, see session_idle_timeout_secs.
+struct IdleTimeoutReader {
+    session: SessionRef,
+    stream: std::net::TcpStream,
+}
+
+impl Read for IdleTimeoutReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.stream.read(buf) {
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                log::info!(
+                    "MySQL connection {:?} is idle for too long, closing it",
+                    self.stream.peer_addr()
+                );
+                Ok(0)
+            }
+            res => {
+                self.session.touch();
+                res
+            }
+        }
+    }
+}
@@ -16,6 +16,8 @@ use common_exception::ErrorCode;
 use common_exception::Result;
 use msql_srv::*;
 
+use crate::servers::mysql::writers::query_result_writer::error_kind;
+
 pub struct DFInitResultWriter<'a, W: std::io::Write> {
     inner: Option<InitWriter<'a, W>>,
 }
@@ -43,7 +45,7 @@ impl<'a, W: std::io::Write> DFInitResultWriter<'a, W> {
 
     fn err(error: &ErrorCode, writer: InitWriter<'a, W>) -> Result<()> {
         log::error!("OnInit Error: {:?}", error);
-        writer.error(ErrorKind::ER_UNKNOWN_ERROR, format!("{}", error).as_bytes())?;
+        writer.error(error_kind(error), format!("{}", error).as_bytes())?;
         Ok(())
     }
 }
@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod query_result_writer_test;
+
 mod init_result_writer;
 mod query_result_writer;
 
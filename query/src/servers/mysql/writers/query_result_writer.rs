@@ -12,13 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
 use chrono_tz::Tz;
 use common_datablocks::DataBlock;
+use common_datavalues::columns::DataColumn;
+use common_datavalues::DFPrimitiveType;
 use common_datavalues::DataField;
 use common_datavalues::DataSchemaRef;
 use common_datavalues::DataType;
 use common_datavalues::DataValue;
 use common_datavalues::DateConverter;
+use common_datavalues::FloatFormat;
 use common_exception::exception::ABORT_QUERY;
 use common_exception::exception::ABORT_SESSION;
 use common_exception::ErrorCode;
@@ -34,32 +39,88 @@ impl<'a, W: std::io::Write> DFQueryResultWriter<'a, W> {
         DFQueryResultWriter::<'a, W> { inner: Some(inner) }
     }
 
-    pub fn write(&mut self, query_result: Result<(Vec<DataBlock>, String)>) -> Result<()> {
-        if let Some(writer) = self.inner.take() {
-            match query_result {
-                Ok((blocks, extra_info)) => Self::ok(blocks, extra_info, writer)?,
-                Err(error) => Self::err(&error, writer)?,
+    /// Writes the blocks as they are pulled, the response is built once they are all written.
+    /// An error before the first row is returned without being written, see `write_error`,
+    /// after it the result can't be completed and the connection is to be closed.
+    pub fn write<F, R>(
+        &mut self,
+        mut next_block: F,
+        float_format: &FloatFormat,
+        response: R,
+    ) -> Result<()>
+    where
+        F: FnMut() -> Option<Result<DataBlock>>,
+        R: FnOnce() -> Result<OkResponse>,
+    {
+        // The columns of the result are the ones of its first block.
+        let block = match next_block().transpose()? {
+            // XXX: num_columns == 0 may is error?
+            Some(block) if block.num_columns() != 0 => block,
+            _ => {
+                let response = response()?;
+                if let Some(dataset_writer) = self.inner.take() {
+                    dataset_writer.completed(response)?;
+                }
+                return Ok(());
+            }
+        };
+
+        let columns = Self::convert_schema(block.schema())?;
+        let dataset_writer = match self.inner.take() {
+            Some(dataset_writer) => dataset_writer,
+            None => return Ok(()),
+        };
+
+        let mut row_writer = dataset_writer.start(&columns)?;
+        match Self::write_rows(&mut row_writer, block, next_block, float_format)
+            .and_then(|_| response())
+        {
+            Ok(response) => row_writer.finish_with_info(&response.info)?,
+            Err(error) => {
+                // The rows can't be followed by an error packet, the result is left unfinished
+                // rather than ended as if it was complete, and the connection is closed.
+                std::mem::forget(row_writer);
+                return Err(error);
             }
         }
+
         Ok(())
     }
 
-    fn ok(
-        blocks: Vec<DataBlock>,
-        extra_info: String,
-        dataset_writer: QueryResultWriter<'a, W>,
-    ) -> Result<()> {
-        // XXX: num_columns == 0 may is error?
-        let default_response = OkResponse {
-            info: extra_info,
-            ..Default::default()
-        };
+    /// Writes the error of the query, or returns it if a part of the result is already written.
+    pub fn write_error(&mut self, error: ErrorCode) -> Result<()> {
+        match self.inner.take() {
+            Some(writer) => Self::err(&error, writer),
+            None => Err(error),
+        }
+    }
 
-        if blocks.is_empty() || (blocks[0].num_columns() == 0) {
-            dataset_writer.completed(default_response)?;
-            return Ok(());
+    fn write_rows<F>(
+        row_writer: &mut RowWriter<W>,
+        first_block: DataBlock,
+        mut next_block: F,
+        float_format: &FloatFormat,
+    ) -> Result<()>
+    where
+        F: FnMut() -> Option<Result<DataBlock>>,
+    {
+        let mut block = Some(first_block);
+        while let Some(written) = block {
+            if written.num_columns() != 0 {
+                let values = block_values(&written, float_format)?;
+                for row_index in 0..written.num_rows() {
+                    for column in values.iter() {
+                        column[row_index].write(row_writer)?;
+                    }
+                    row_writer.end_row()?;
+                }
+            }
+            block = next_block().transpose()?;
         }
+        Ok(())
+    }
 
+    fn convert_schema(schema: &DataSchemaRef) -> Result<Vec<Column>> {
         fn convert_field_type(field: &DataField) -> Result<ColumnType> {
             match field.data_type() {
                 DataType::Int8 => Ok(ColumnType::MYSQL_TYPE_LONG),
@@ -94,99 +155,13 @@ impl<'a, W: std::io::Write> DFQueryResultWriter<'a, W> {
             })
         }
 
-        fn convert_schema(schema: &DataSchemaRef) -> Result<Vec<Column>> {
-            schema.fields().iter().map(make_column_from_field).collect()
-        }
-
-        let block = blocks[0].clone();
-        let utc: Tz = "UTC".parse().unwrap();
-        match convert_schema(block.schema()) {
-            Err(error) => Self::err(&error, dataset_writer),
-            Ok(columns) => {
-                let columns_size = block.num_columns();
-                let mut row_writer = dataset_writer.start(&columns)?;
-
-                for block in &blocks {
-                    let rows_size = block.column(0).len();
-                    for row_index in 0..rows_size {
-                        for col_index in 0..columns_size {
-                            let val = block.column(col_index).try_get(row_index)?;
-                            if val.is_null() {
-                                row_writer.write_col(None::<u8>)?;
-                                continue;
-                            }
-                            let data_type = block.schema().fields()[col_index].data_type();
-                            match (data_type, val) {
-                                (DataType::Boolean, DataValue::Boolean(Some(v))) => {
-                                    row_writer.write_col(v as i8)?
-                                }
-                                (DataType::Int8, DataValue::Int8(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::Int16, DataValue::Int16(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::Int32, DataValue::Int32(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::Int64, DataValue::Int64(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::UInt8, DataValue::UInt8(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::UInt16, DataValue::UInt16(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::UInt32, DataValue::UInt32(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::UInt64, DataValue::UInt64(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::Float32, DataValue::Float32(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::Float64, DataValue::Float64(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (DataType::Date16, DataValue::UInt16(Some(v))) => {
-                                    row_writer.write_col(v.to_date(&utc).naive_local())?
-                                }
-                                (DataType::Date32, DataValue::UInt32(Some(v))) => {
-                                    row_writer.write_col(v.to_date(&utc).naive_local())?
-                                }
-                                (DataType::DateTime32(tz), DataValue::UInt32(Some(v))) => {
-                                    let tz = tz.clone();
-                                    let tz = tz.unwrap_or_else(|| "UTC".to_string());
-                                    let tz: Tz = tz.parse().unwrap();
-                                    row_writer.write_col(v.to_date_time(&tz).naive_local())?
-                                }
-                                (DataType::String, DataValue::String(Some(v))) => {
-                                    row_writer.write_col(v)?
-                                }
-                                (_, v) => {
-                                    return Err(ErrorCode::BadDataValueType(format!(
-                                        "Unsupported column type:{:?}",
-                                        v.data_type()
-                                    )));
-                                }
-                            }
-                        }
-                        row_writer.end_row()?;
-                    }
-                }
-                row_writer.finish_with_info(&default_response.info)?;
-
-                Ok(())
-            }
-        }
+        schema.fields().iter().map(make_column_from_field).collect()
     }
 
     fn err(error: &ErrorCode, writer: QueryResultWriter<'a, W>) -> Result<()> {
         if error.code() != ABORT_QUERY && error.code() != ABORT_SESSION {
             log::error!("OnQuery Error: {:?}", error);
-            writer.error(ErrorKind::ER_UNKNOWN_ERROR, format!("{}", error).as_bytes())?;
+            writer.error(error_kind(error), format!("{}", error).as_bytes())?;
         } else {
             writer.error(
                 ErrorKind::ER_ABORTING_CONNECTION,
@@ -197,3 +172,212 @@ impl<'a, W: std::io::Write> DFQueryResultWriter<'a, W> {
         Ok(())
     }
 }
+
+/// The MySQL error of an error, the clients tell some errors apart by their codes,
+/// e.g. an unknown database is ER_BAD_DB_ERROR for both `USE` and COM_INIT_DB.
+pub fn error_kind(error: &ErrorCode) -> ErrorKind {
+    match error.code() {
+        code if code == ErrorCode::UnknownDatabase("").code() => ErrorKind::ER_BAD_DB_ERROR,
+        _ => ErrorKind::ER_UNKNOWN_ERROR,
+    }
+}
+
+/// A value written to a MySQL row, of the type the column is declared as.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MySQLValue<'a> {
+    Null,
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
+    Float(String),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> MySQLValue<'a> {
+    /// Converts a DataValue of a column, for the constant columns and the types without a bulk accessor.
+    pub fn from_value(
+        value: &'a DataValue,
+        data_type: &DataType,
+        float_format: &FloatFormat,
+    ) -> Result<MySQLValue<'a>> {
+        if value.is_null() {
+            return Ok(MySQLValue::Null);
+        }
+
+        let v = match (data_type, value) {
+            (DataType::Boolean, DataValue::Boolean(Some(v))) => MySQLValue::Int8(*v as i8),
+            (DataType::Int8, DataValue::Int8(Some(v))) => MySQLValue::Int8(*v),
+            (DataType::Int16, DataValue::Int16(Some(v))) => MySQLValue::Int16(*v),
+            (DataType::Int32, DataValue::Int32(Some(v))) => MySQLValue::Int32(*v),
+            (DataType::Int64, DataValue::Int64(Some(v))) => MySQLValue::Int64(*v),
+            (DataType::UInt8, DataValue::UInt8(Some(v))) => MySQLValue::UInt8(*v),
+            (DataType::UInt16, DataValue::UInt16(Some(v))) => MySQLValue::UInt16(*v),
+            (DataType::UInt32, DataValue::UInt32(Some(v))) => MySQLValue::UInt32(*v),
+            (DataType::UInt64, DataValue::UInt64(Some(v))) => MySQLValue::UInt64(*v),
+            (DataType::Float32, DataValue::Float32(Some(v))) => {
+                MySQLValue::Float(float_format.format_f32(*v))
+            }
+            (DataType::Float64, DataValue::Float64(Some(v))) => {
+                MySQLValue::Float(float_format.format_f64(*v))
+            }
+            (DataType::Date16, DataValue::UInt16(Some(v))) => {
+                MySQLValue::Date(v.to_date(&Tz::UTC).naive_local())
+            }
+            (DataType::Date32, DataValue::UInt32(Some(v))) => {
+                MySQLValue::Date(v.to_date(&Tz::UTC).naive_local())
+            }
+            (DataType::DateTime32(tz), DataValue::UInt32(Some(v))) => {
+                MySQLValue::DateTime(v.to_date_time(&parse_tz(tz)?).naive_local())
+            }
+            (DataType::String, DataValue::String(Some(v))) => MySQLValue::Bytes(v),
+            (_, v) => {
+                return Err(ErrorCode::BadDataValueType(format!(
+                    "Unsupported column type:{:?}",
+                    v.data_type()
+                )));
+            }
+        };
+        Ok(v)
+    }
+
+    fn write<W: std::io::Write>(&self, writer: &mut RowWriter<W>) -> std::io::Result<()> {
+        match self {
+            MySQLValue::Null => writer.write_col(None::<u8>),
+            MySQLValue::Int8(v) => writer.write_col(*v),
+            MySQLValue::Int16(v) => writer.write_col(*v),
+            MySQLValue::Int32(v) => writer.write_col(*v),
+            MySQLValue::Int64(v) => writer.write_col(*v),
+            MySQLValue::UInt8(v) => writer.write_col(*v),
+            MySQLValue::UInt16(v) => writer.write_col(*v),
+            MySQLValue::UInt32(v) => writer.write_col(*v),
+            MySQLValue::UInt64(v) => writer.write_col(*v),
+            MySQLValue::Float(v) => writer.write_col(v.as_str()),
+            MySQLValue::Date(v) => writer.write_col(*v),
+            MySQLValue::DateTime(v) => writer.write_col(*v),
+            MySQLValue::Bytes(v) => writer.write_col(*v),
+        }
+    }
+}
+
+/// Converts the columns of a block into the values of the rows, column by column.
+/// The values are read from the arrays in bulk, without a DataValue per row,
+/// the strings are borrowed from the block, and the scalar of a constant column is converted once.
+pub fn block_values<'a>(
+    block: &'a DataBlock,
+    float_format: &FloatFormat,
+) -> Result<Vec<Vec<MySQLValue<'a>>>> {
+    let fields = block.schema().fields();
+    (0..block.num_columns())
+        .map(|i| column_values(block.column(i), fields[i].data_type(), float_format))
+        .collect()
+}
+
+fn column_values<'a>(
+    column: &'a DataColumn,
+    data_type: &DataType,
+    float_format: &FloatFormat,
+) -> Result<Vec<MySQLValue<'a>>> {
+    if let DataColumn::Constant(value, size) = column {
+        let value = MySQLValue::from_value(value, data_type, float_format)?;
+        return Ok(vec![value; *size]);
+    }
+
+    match data_type {
+        DataType::Int8 => primitive_values(column, MySQLValue::Int8),
+        DataType::Int16 => primitive_values(column, MySQLValue::Int16),
+        DataType::Int32 => primitive_values(column, MySQLValue::Int32),
+        DataType::Int64 => primitive_values(column, MySQLValue::Int64),
+        DataType::UInt8 => primitive_values(column, MySQLValue::UInt8),
+        DataType::UInt16 => primitive_values(column, MySQLValue::UInt16),
+        DataType::UInt32 => primitive_values(column, MySQLValue::UInt32),
+        DataType::UInt64 => primitive_values(column, MySQLValue::UInt64),
+        DataType::Float32 => primitive_values(column, |v: f32| {
+            MySQLValue::Float(float_format.format_f32(v))
+        }),
+        DataType::Float64 => primitive_values(column, |v: f64| {
+            MySQLValue::Float(float_format.format_f64(v))
+        }),
+        DataType::Date16 => primitive_values(column, |v: u16| {
+            MySQLValue::Date(v.to_date(&Tz::UTC).naive_local())
+        }),
+        DataType::Date32 => primitive_values(column, |v: u32| {
+            MySQLValue::Date(v.to_date(&Tz::UTC).naive_local())
+        }),
+        DataType::DateTime32(tz) => {
+            let tz = parse_tz(tz)?;
+            primitive_values(column, |v: u32| {
+                MySQLValue::DateTime(v.to_date_time(&tz).naive_local())
+            })
+        }
+        DataType::String => match column.string_iter() {
+            Some(iter) => Ok(iter
+                .map(|(v, is_null)| match is_null {
+                    true => MySQLValue::Null,
+                    false => MySQLValue::Bytes(v),
+                })
+                .collect()),
+            None => Err(unexpected_column(column, data_type)),
+        },
+        DataType::Boolean => match column {
+            DataColumn::Array(array) => Ok(array
+                .bool()?
+                .inner()
+                .iter()
+                .map(|v| match v {
+                    Some(v) => MySQLValue::Int8(v as i8),
+                    None => MySQLValue::Null,
+                })
+                .collect()),
+            _ => Err(unexpected_column(column, data_type)),
+        },
+        // The other types are only written as nulls.
+        _ => (0..column.len())
+            .map(|row| match column.try_get(row)? {
+                value if value.is_null() => Ok(MySQLValue::Null),
+                value => Err(ErrorCode::BadDataValueType(format!(
+                    "Unsupported column type:{:?}",
+                    value.data_type()
+                ))),
+            })
+            .collect(),
+    }
+}
+
+fn primitive_values<'a, T: DFPrimitiveType>(
+    column: &'a DataColumn,
+    f: impl Fn(T) -> MySQLValue<'a>,
+) -> Result<Vec<MySQLValue<'a>>> {
+    match column.as_primitive_slice::<T>() {
+        Some((values, None)) => Ok(values.iter().map(|v| f(*v)).collect()),
+        Some((values, Some(validity))) => Ok(values
+            .iter()
+            .zip(validity.iter())
+            .map(|(v, is_valid)| match is_valid {
+                true => f(*v),
+                false => MySQLValue::Null,
+            })
+            .collect()),
+        None => Err(unexpected_column(column, &T::data_type())),
+    }
+}
+
+fn unexpected_column(column: &DataColumn, data_type: &DataType) -> ErrorCode {
+    ErrorCode::BadDataValueType(format!(
+        "Unsupported column type:{:?}, expect: {:?}",
+        column.data_type(),
+        data_type
+    ))
+}
+
+fn parse_tz(tz: &Option<String>) -> Result<Tz> {
+    let tz = tz.as_deref().unwrap_or("UTC");
+    tz.parse::<Tz>()
+        .map_err(|e| ErrorCode::BadArguments(format!("Invalid timezone {}: {}", tz, e)))
+}
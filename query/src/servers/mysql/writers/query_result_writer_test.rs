@@ -0,0 +1,111 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use pretty_assertions::assert_eq;
+
+use crate::servers::mysql::writers::query_result_writer::block_values;
+use crate::servers::mysql::writers::query_result_writer::MySQLValue;
+
+/// Reads the values row by row as DataValues, as the writer did before the bulk accessors.
+fn row_data_values(block: &DataBlock) -> Result<Vec<Vec<DataValue>>> {
+    let mut columns = vec![];
+    for column in block.columns() {
+        let mut values = vec![];
+        for row in 0..block.num_rows() {
+            values.push(column.try_get(row)?);
+        }
+        columns.push(values);
+    }
+    Ok(columns)
+}
+
+fn assert_block_values(block: &DataBlock, float_format: &FloatFormat) -> Result<()> {
+    let data_values = row_data_values(block)?;
+    let want = data_values
+        .iter()
+        .zip(block.schema().fields())
+        .map(|(values, field)| {
+            values
+                .iter()
+                .map(|v| MySQLValue::from_value(v, field.data_type(), float_format))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    assert_eq!(want, block_values(block, float_format)?);
+    Ok(())
+}
+
+#[test]
+fn test_block_values() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("i8", DataType::Int8, true),
+        DataField::new("u64", DataType::UInt64, true),
+        DataField::new("f64", DataType::Float64, true),
+        DataField::new("bool", DataType::Boolean, true),
+        DataField::new("str", DataType::String, true),
+        DataField::new("date", DataType::Date16, true),
+        DataField::new(
+            "datetime",
+            DataType::DateTime32(Some("Asia/Shanghai".to_string())),
+            true,
+        ),
+        DataField::new("const_str", DataType::String, false),
+        DataField::new("const_f32", DataType::Float32, false),
+        DataField::new("const_null", DataType::UInt32, true),
+        DataField::new("null", DataType::Null, true),
+    ]);
+
+    let block = DataBlock::create(schema, vec![
+        Series::new(vec![Some(-1i8), None, Some(127)]).into(),
+        Series::new(vec![None, Some(u64::MAX), Some(0)]).into(),
+        Series::new(vec![Some(1.0f64 / 3.0), Some(f64::NAN), None]).into(),
+        Series::new(vec![Some(true), None, Some(false)]).into(),
+        Series::new(vec![Some("数据库"), None, Some("Ünïcødé 🦀")]).into(),
+        Series::new(vec![Some(18000u16), Some(0), None]).into(),
+        Series::new(vec![None, Some(1630320462u32), Some(0)]).into(),
+        DataColumn::Constant(DataValue::String(Some("常量".as_bytes().to_vec())), 3),
+        DataColumn::Constant(DataValue::Float32(Some(1.5)), 3),
+        DataColumn::Constant(DataValue::UInt32(None), 3),
+        DataColumn::Constant(DataValue::Null, 3),
+    ]);
+
+    for float_format in [
+        FloatFormat::text(),
+        FloatFormat::text().with_precision(Some(2)),
+    ] {
+        let want = row_values(&block, &float_format)?;
+        assert_eq!(want, block_values(&block, &float_format)?);
+
+        // A slice of the block reads from its offset.
+        let sliced = block.slice(1, 2);
+        assert_eq!(
+            row_values(&sliced, &float_format)?,
+            block_values(&sliced, &float_format)?
+        );
+    }
+
+    let values = block_values(&block, &FloatFormat::text())?;
+    assert_eq!(MySQLValue::Bytes("Ünïcødé 🦀".as_bytes()), values[4][2]);
+    assert_eq!(MySQLValue::Null, values[4][1]);
+    assert_eq!(MySQLValue::Int8(1), values[3][0]);
+    assert_eq!(vec![MySQLValue::Bytes("常量".as_bytes()); 3], values[7]);
+    assert_eq!(vec![MySQLValue::Float("1.5".to_string()); 3], values[8]);
+    assert_eq!(vec![MySQLValue::Null; 3], values[9]);
+
+    Ok(())
+}
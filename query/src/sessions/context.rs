@@ -14,9 +14,11 @@
 
 use std::collections::VecDeque;
 use std::future::Future;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::atomic::Ordering::Acquire;
 use std::sync::Arc;
+use std::time::Instant;
 
 use common_exception::ErrorCode;
 use common_exception::Result;
@@ -29,23 +31,32 @@ use common_planners::PlanNode;
 use common_planners::Statistics;
 use common_progress::ProgressCallback;
 use common_progress::ProgressValues;
+use common_runtime::tokio::sync::Semaphore;
 use common_runtime::tokio::task::JoinHandle;
 use common_streams::AbortStream;
 use common_streams::SendableDataBlockStream;
+use metrics::counter;
+use metrics::histogram;
 
 use crate::catalogs::impls::DatabaseCatalog;
 use crate::catalogs::Catalog;
 use crate::catalogs::TableFunctionMeta;
 use crate::catalogs::TableMeta;
 use crate::clusters::ClusterRef;
+use crate::common::TempDirManager;
 use crate::configs::Config;
 use crate::datasources::dal::DataAccessor;
 use crate::datasources::dal::Local;
 use crate::datasources::dal::StorageScheme;
 use crate::datasources::dal::S3;
 use crate::sessions::context_shared::DatabendQueryContextShared;
+use crate::sessions::QueryHints;
+use crate::sessions::QueryMetrics;
+use crate::sessions::ResourceGroup;
 use crate::sessions::SessionManagerRef;
 use crate::sessions::Settings;
+use crate::sessions::Warning;
+use crate::sql::PlanTemplateCache;
 
 pub struct DatabendQueryContext {
     statistics: Arc<RwLock<Statistics>>,
@@ -97,6 +108,14 @@ impl DatabendQueryContext {
         }))
     }
 
+    pub fn get_query_metrics(&self) -> Arc<QueryMetrics> {
+        self.shared.metrics.clone()
+    }
+
+    pub fn get_temp_dir_manager(&self) -> Arc<TempDirManager> {
+        self.shared.get_temp_dir_manager()
+    }
+
     pub fn get_progress_value(&self) -> ProgressValues {
         self.shared.progress.as_ref().get_values()
     }
@@ -154,6 +173,27 @@ impl DatabendQueryContext {
         self.shared.get_catalog()
     }
 
+    pub fn try_get_resource_group(&self) -> Result<Arc<ResourceGroup>> {
+        self.shared.try_get_resource_group()
+    }
+
+    pub fn get_part_read_permits(&self) -> Result<Arc<Semaphore>> {
+        self.shared.get_part_read_permits()
+    }
+
+    /// The time the query must be finished by, the calls to the store made for it are bounded by it.
+    pub fn get_query_deadline(&self) -> Result<Option<Instant>> {
+        self.shared.get_query_deadline()
+    }
+
+    pub fn map_query_timeout(&self, error: ErrorCode) -> ErrorCode {
+        self.shared.map_query_timeout(error)
+    }
+
+    pub async fn acquire_resource_group_slot(&self) -> Result<()> {
+        self.shared.acquire_resource_group_slot().await
+    }
+
     pub fn get_table(&self, database: &str, table: &str) -> Result<Arc<TableMeta>> {
         self.get_catalog().get_table(database, table)
     }
@@ -208,6 +248,53 @@ impl DatabendQueryContext {
         self.shared.get_settings()
     }
 
+    pub fn is_in_transaction(&self) -> bool {
+        self.shared.is_in_transaction()
+    }
+
+    pub fn set_in_transaction(&self, in_transaction: bool) {
+        self.shared.set_in_transaction(in_transaction);
+    }
+
+    /// Warn the client rather than fail the statement, `code` is one of `common_exception::codes`.
+    /// The warnings are cleared at the start of the next statement.
+    pub fn push_warning(&self, code: u16, message: impl Into<String>) {
+        self.shared.push_warning(code, message);
+    }
+
+    /// The pushed warnings, and the one of the catalog if it is served in degraded mode.
+    pub fn get_warnings(&self) -> Vec<Warning> {
+        self.shared.get_warnings()
+    }
+
+    /// How many warnings are pushed, including the repeated and the suppressed ones, e.g. for the MySQL OK packet.
+    pub fn get_warning_count(&self) -> u64 {
+        self.shared.get_warning_count()
+    }
+
+    /// The warnings of the previous statement of the session, for `SHOW WARNINGS`.
+    pub fn get_last_warnings(&self) -> Vec<Warning> {
+        self.shared.get_last_warnings()
+    }
+
+    /// Adds the rows written by a statement of the query, e.g. for the affected rows of the MySQL OK packet.
+    pub fn add_affected_rows(&self, rows: u64) {
+        self.shared.affected_rows.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    pub fn get_affected_rows(&self) -> u64 {
+        self.shared.affected_rows.load(Ordering::Relaxed)
+    }
+
+    /// The id generated for the last row inserted by the query, 0 if there is none.
+    pub fn set_last_insert_id(&self, id: u64) {
+        self.shared.last_insert_id.store(id, Ordering::Relaxed);
+    }
+
+    pub fn get_last_insert_id(&self) -> u64 {
+        self.shared.last_insert_id.load(Ordering::Relaxed)
+    }
+
     pub fn get_config(&self) -> Config {
         self.shared.conf.clone()
     }
@@ -225,10 +312,50 @@ impl DatabendQueryContext {
         self.shared.attach_query_plan(query_plan);
     }
 
+    /// The semantic hash of the attached query plan, equal for queries that only differ syntactically.
+    pub fn get_query_semantic_hash(&self) -> Option<u64> {
+        self.shared.get_query_semantic_hash()
+    }
+
+    /// The label of the query, for the chargeback and the debugging.
+    pub fn get_query_label(&self) -> Option<String> {
+        self.shared.get_query_label()
+    }
+
+    /// Overrides the label of the query, e.g. with the one of the query the stage belongs to.
+    pub fn set_query_label(&self, label: Option<String>) {
+        self.shared.set_query_label(label);
+    }
+
+    /// The hints of the `/*+ ... */` comments of the query, see `QueryHints`.
+    pub fn get_query_hints(&self) -> QueryHints {
+        self.shared.get_query_hints()
+    }
+
+    /// The max_threads of the query, a max_threads hint overrides the setting for the query only.
+    pub fn get_max_threads(&self) -> Result<u64> {
+        self.shared.get_max_threads()
+    }
+
     pub fn get_sessions_manager(self: &Arc<Self>) -> SessionManagerRef {
         self.shared.session.get_sessions_manager()
     }
 
+    /// The plan templates of the session, see `PlanTemplateCache`.
+    pub fn get_plan_templates(&self) -> Arc<PlanTemplateCache> {
+        self.shared.session.get_plan_templates()
+    }
+
+    /// The bytes of the results buffered for the client of the session, see `Session::get_result_buffer_bytes`.
+    pub fn get_result_buffer_bytes(&self) -> Arc<AtomicU64> {
+        self.shared.session.get_result_buffer_bytes()
+    }
+
+    /// Aborts the query and closes the connection of the session, e.g. when the client is stalled.
+    pub fn force_kill_session(&self) {
+        self.shared.session.force_kill_session();
+    }
+
     pub fn get_data_accessor(
         &self,
         storage_scheme: &StorageScheme,
@@ -257,7 +384,43 @@ impl DatabendQueryContextShared {
     pub(in crate::sessions) fn destroy_context_ref(&self) {
         if self.ref_count.fetch_sub(1, Ordering::Release) == 1 {
             std::sync::atomic::fence(Acquire);
-            log::info!("Destroy DatabendQueryContext");
+            let metrics = self.metrics.get_values();
+            let query_label = self.get_query_label().unwrap_or_default();
+            counter!(super::metrics::METRIC_QUERY_COUNT, 1, "query_label" => query_label.clone());
+
+            // The stages of the query run by the other nodes are not statements.
+            if self.running_query.read().is_some() {
+                let kind = match self.running_plan.read().as_ref() {
+                    Some(plan) => super::query_metrics::statement_kind(plan),
+                    None => "other",
+                };
+                let outcome = if metrics.failed { "error" } else { "ok" };
+                histogram!(
+                    super::metrics::METRIC_QUERY_STATEMENT_SECONDS,
+                    self.created.elapsed(),
+                    "kind" => kind,
+                    "outcome" => outcome
+                );
+            }
+
+            if metrics.store_rpcs.is_empty() {
+                log::info!(
+                    "Destroy DatabendQueryContext, query label: {:?}",
+                    query_label
+                );
+            } else {
+                log::info!(
+                    "Destroy DatabendQueryContext, query label: {:?}, store rpcs: {:?}, part reads: {} in {:?}, max {:?}",
+                    query_label,
+                    metrics.store_rpcs,
+                    metrics.part_reads,
+                    metrics.part_read_time,
+                    metrics.part_read_max_time
+                );
+            }
+            if !self.keep_last_warnings.load(Ordering::Relaxed) {
+                self.session.set_last_warnings(self.get_warnings());
+            }
             self.session.destroy_context_shared();
         }
     }
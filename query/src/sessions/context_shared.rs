@@ -12,22 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
+use common_exception::codes;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_infallible::RwLock;
 use common_planners::PlanNode;
 use common_progress::Progress;
+use common_runtime::tokio::sync::Mutex as AsyncMutex;
+use common_runtime::tokio::sync::Semaphore;
 use common_runtime::Runtime;
 use futures::future::AbortHandle;
 use uuid::Uuid;
 
 use crate::catalogs::impls::DatabaseCatalog;
 use crate::clusters::ClusterRef;
+use crate::common::TempDirManager;
 use crate::configs::Config;
+use crate::sessions::is_query_label_changed;
+use crate::sessions::query_label_hint;
+use crate::sessions::sanitize_query_label;
+use crate::sessions::QueryHints;
+use crate::sessions::QueryMetrics;
+use crate::sessions::QueryWarnings;
+use crate::sessions::ResourceGroup;
+use crate::sessions::ResourceGroupSlot;
 use crate::sessions::Session;
 use crate::sessions::Settings;
+use crate::sessions::Warning;
+use crate::sessions::QUERY_HINTS;
 
 /// Data that needs to be shared in a query context.
 /// This is very useful, for example, for queries:
@@ -41,15 +61,32 @@ use crate::sessions::Settings;
 pub struct DatabendQueryContextShared {
     pub(in crate::sessions) conf: Config,
     pub(in crate::sessions) progress: Arc<Progress>,
+    pub(in crate::sessions) metrics: Arc<QueryMetrics>,
     pub(in crate::sessions) session: Arc<Session>,
     pub(in crate::sessions) runtime: Arc<RwLock<Option<Arc<Runtime>>>>,
+    pub(in crate::sessions) temp_dir: Arc<RwLock<Option<Arc<TempDirManager>>>>,
     pub(in crate::sessions) init_query_id: Arc<RwLock<String>>,
     pub(in crate::sessions) cluster_cache: Arc<RwLock<Option<ClusterRef>>>,
     pub(in crate::sessions) sources_abort_handle: Arc<RwLock<Vec<AbortHandle>>>,
+    pub(in crate::sessions) aborted: Arc<AtomicBool>,
     pub(in crate::sessions) ref_count: Arc<AtomicUsize>,
     pub(in crate::sessions) subquery_index: Arc<AtomicUsize>,
     pub(in crate::sessions) running_query: Arc<RwLock<Option<String>>>,
+    /// When the running query is attached, the context may be created a bit earlier.
+    pub(in crate::sessions) running_query_start: Arc<RwLock<Option<Instant>>>,
     pub(in crate::sessions) running_plan: Arc<RwLock<Option<PlanNode>>>,
+    pub(in crate::sessions) running_plan_hash: Arc<RwLock<Option<u64>>>,
+    pub(in crate::sessions) resource_group_cache: Arc<RwLock<Option<Arc<ResourceGroup>>>>,
+    /// An async lock, it is held while waiting for the slot so that a query takes one slot.
+    pub(in crate::sessions) resource_group_slot: Arc<AsyncMutex<Option<ResourceGroupSlot>>>,
+    pub(in crate::sessions) warnings: Arc<QueryWarnings>,
+    pub(in crate::sessions) keep_last_warnings: Arc<AtomicBool>,
+    pub(in crate::sessions) affected_rows: Arc<AtomicU64>,
+    pub(in crate::sessions) last_insert_id: Arc<AtomicU64>,
+    pub(in crate::sessions) query_label: Arc<RwLock<Option<String>>>,
+    pub(in crate::sessions) query_hints: Arc<RwLock<QueryHints>>,
+    pub(in crate::sessions) part_read_permits: Arc<RwLock<Option<Arc<Semaphore>>>>,
+    pub(in crate::sessions) created: Instant,
 }
 
 impl DatabendQueryContextShared {
@@ -58,20 +95,39 @@ impl DatabendQueryContextShared {
             conf,
             init_query_id: Arc::new(RwLock::new(Uuid::new_v4().to_string())),
             progress: Arc::new(Progress::create()),
+            metrics: Arc::new(QueryMetrics::create(
+                session.get_sessions_manager().get_store_rpc_stats(),
+            )),
             session,
             runtime: Arc::new(RwLock::new(None)),
+            temp_dir: Arc::new(RwLock::new(None)),
             cluster_cache: Arc::new(RwLock::new(None)),
             sources_abort_handle: Arc::new(RwLock::new(Vec::new())),
+            aborted: Arc::new(AtomicBool::new(false)),
             ref_count: Arc::new(AtomicUsize::new(0)),
             subquery_index: Arc::new(AtomicUsize::new(1)),
             running_query: Arc::new(RwLock::new(None)),
+            running_query_start: Arc::new(RwLock::new(None)),
             running_plan: Arc::new(RwLock::new(None)),
+            running_plan_hash: Arc::new(RwLock::new(None)),
+            resource_group_cache: Arc::new(RwLock::new(None)),
+            resource_group_slot: Arc::new(AsyncMutex::new(None)),
+            warnings: Arc::new(QueryWarnings::create()),
+            keep_last_warnings: Arc::new(AtomicBool::new(false)),
+            affected_rows: Arc::new(AtomicU64::new(0)),
+            last_insert_id: Arc::new(AtomicU64::new(0)),
+            query_label: Arc::new(RwLock::new(None)),
+            query_hints: Arc::new(RwLock::new(QueryHints::default())),
+            part_read_permits: Arc::new(RwLock::new(None)),
+            created: Instant::now(),
         })
     }
 
     pub fn kill(&self) {
         let mut sources_abort_handle = self.sources_abort_handle.write();
 
+        // The sources created from now on are aborted once they are added.
+        self.aborted.store(true, Ordering::Relaxed);
         while let Some(source_abort_handle) = sources_abort_handle.pop() {
             source_abort_handle.abort();
         }
@@ -93,6 +149,77 @@ impl DatabendQueryContextShared {
         }
     }
 
+    pub fn try_get_resource_group(&self) -> Result<Arc<ResourceGroup>> {
+        // We only resolve the resource group once during the query.
+        let mut resource_group_cache = self.resource_group_cache.write();
+
+        match &*resource_group_cache {
+            Some(cached) => Ok(cached.clone()),
+            None => {
+                let resource_group = self.session.get_resource_group()?;
+                *resource_group_cache = Some(resource_group.clone());
+                Ok(resource_group)
+            }
+        }
+    }
+
+    /// The permits of the part reads from the store, shared by all the scans of the query.
+    /// The number of them is taken from the settings once during the query.
+    pub fn get_part_read_permits(&self) -> Result<Arc<Semaphore>> {
+        let mut part_read_permits = self.part_read_permits.write();
+
+        match &*part_read_permits {
+            Some(cached) => Ok(cached.clone()),
+            None => {
+                let permits = self.get_settings().get_max_concurrent_part_reads()?;
+                let permits = Arc::new(Semaphore::new(permits.max(1) as usize));
+                *part_read_permits = Some(permits.clone());
+                Ok(permits)
+            }
+        }
+    }
+
+    /// The time the query must be finished by, None if `max_execution_time` is not set.
+    pub fn get_query_deadline(&self) -> Result<Option<Instant>> {
+        let limit = self.get_settings().get_max_execution_time()?;
+        Ok(match limit {
+            0 => None,
+            limit => Some(self.created + Duration::from_millis(limit)),
+        })
+    }
+
+    /// Reports a call to the store that is given up at the deadline of the query as a timeout
+    /// of the query, other errors are returned as they are.
+    pub fn map_query_timeout(&self, error: ErrorCode) -> ErrorCode {
+        if error.code() != codes::DeadlineExceeded {
+            return error;
+        }
+
+        match self.get_settings().get_max_execution_time() {
+            Ok(limit) if limit > 0 => ErrorCode::QueryTimeout(format!(
+                "Query is stopped after {:?}, max_execution_time is {:?}, cause: {}",
+                self.created.elapsed(),
+                Duration::from_millis(limit),
+                error.message()
+            )),
+            _ => error,
+        }
+    }
+
+    /// Wait for a slot of the resource group, the slot is held until the query is finished.
+    /// Subqueries share the slot of the query.
+    pub async fn acquire_resource_group_slot(&self) -> Result<()> {
+        // The check and the insert are under the same lock, a concurrent caller waits for the
+        // slot of the first one instead of taking another.
+        let mut resource_group_slot = self.resource_group_slot.lock().await;
+        if resource_group_slot.is_none() {
+            let resource_group = self.try_get_resource_group()?;
+            *resource_group_slot = Some(resource_group.acquire_slot().await?);
+        }
+
+        Ok(())
+    }
+
     pub fn get_current_database(&self) -> String {
         self.session.get_current_database()
     }
@@ -105,6 +232,14 @@ impl DatabendQueryContextShared {
         self.session.get_settings()
     }
 
+    pub fn is_in_transaction(&self) -> bool {
+        self.session.is_in_transaction()
+    }
+
+    pub fn set_in_transaction(&self, in_transaction: bool) {
+        self.session.set_in_transaction(in_transaction);
+    }
+
     pub fn get_catalog(&self) -> Arc<DatabaseCatalog> {
         self.session.get_catalog()
     }
@@ -116,8 +251,7 @@ impl DatabendQueryContextShared {
         match &*query_runtime {
             Some(query_runtime) => Ok(query_runtime.clone()),
             None => {
-                let settings = self.get_settings();
-                let max_threads = settings.get_max_threads()? as usize;
+                let max_threads = self.get_max_threads()? as usize;
                 let runtime = Arc::new(Runtime::with_worker_threads(max_threads)?);
                 *query_runtime = Some(runtime.clone());
                 Ok(runtime)
@@ -125,19 +259,125 @@ impl DatabendQueryContextShared {
         }
     }
 
+    /// Init temp dir when first get
+    pub fn get_temp_dir_manager(&self) -> Arc<TempDirManager> {
+        let mut temp_dir = self.temp_dir.write();
+
+        match &*temp_dir {
+            Some(temp_dir) => temp_dir.clone(),
+            None => {
+                let query_id = self.init_query_id.read().clone();
+                let manager = Arc::new(TempDirManager::for_query(&query_id));
+                *temp_dir = Some(manager.clone());
+                manager
+            }
+        }
+    }
+
     pub fn attach_query_str(&self, query: &str) {
         let mut running_query = self.running_query.write();
         *running_query = Some(query.to_string());
+        *self.running_query_start.write() = Some(Instant::now());
+
+        if let Some(hint) = query_label_hint(query) {
+            let label = sanitize_query_label(hint);
+            if is_query_label_changed(hint) {
+                let message = format!(
+                    "The query label {} of the hint is changed to {}",
+                    hint,
+                    label.clone().unwrap_or_default()
+                );
+                self.push_warning(codes::BadArguments, message);
+            }
+            *self.query_label.write() = label;
+        }
+
+        let hints = QueryHints::parse(query);
+        for hint in &hints.ignored {
+            let message = format!(
+                "The query hint {} is ignored, expect one of {}",
+                hint,
+                QUERY_HINTS.join(", ")
+            );
+            self.push_warning(codes::BadArguments, message);
+        }
+        *self.query_hints.write() = hints;
+    }
+
+    pub fn push_warning(&self, code: u16, message: impl Into<String>) {
+        let max_warnings = self.get_settings().get_max_warnings().unwrap_or_default();
+        self.warnings.push(code, message, max_warnings as usize);
+    }
+
+    /// The warnings of the statement, and the one of the catalog if it is served in degraded mode.
+    pub fn get_warnings(&self) -> Vec<Warning> {
+        let mut warnings = self.warnings.get();
+        if let Some(message) = self.get_catalog().degraded_warning() {
+            warnings.push(Warning {
+                code: codes::MetaServiceUnavailable,
+                message,
+                count: 1,
+            });
+        }
+        warnings
+    }
+
+    pub fn get_warning_count(&self) -> u64 {
+        let degraded = self.get_catalog().degraded_warning().is_some();
+        self.warnings.total() + degraded as u64
+    }
+
+    /// The warnings of the previous statement of the session.
+    /// The statement reading them does not replace them with its own ones, like `SHOW WARNINGS` in MySQL.
+    pub fn get_last_warnings(&self) -> Vec<Warning> {
+        self.keep_last_warnings.store(true, Ordering::Relaxed);
+        self.session.get_last_warnings()
+    }
+
+    /// The label of the hint of the query, or else the query_label setting of the session.
+    pub fn get_query_label(&self) -> Option<String> {
+        if let Some(label) = &*self.query_label.read() {
+            return Some(label.clone());
+        }
+
+        let label = self.get_settings().get_query_label().unwrap_or_default();
+        sanitize_query_label(&label)
+    }
+
+    pub fn set_query_label(&self, label: Option<String>) {
+        *self.query_label.write() = label.as_deref().and_then(sanitize_query_label);
+    }
+
+    pub fn get_query_hints(&self) -> QueryHints {
+        self.query_hints.read().clone()
+    }
+
+    /// The max_threads hint of the query, or else the max_threads setting of the session.
+    pub fn get_max_threads(&self) -> Result<u64> {
+        match self.query_hints.read().max_threads {
+            Some(max_threads) => Ok(max_threads),
+            None => self.get_settings().get_max_threads(),
+        }
     }
 
     pub fn attach_query_plan(&self, plan: &PlanNode) {
         let mut running_plan = self.running_plan.write();
         *running_plan = Some(plan.clone());
+
+        let mut running_plan_hash = self.running_plan_hash.write();
+        *running_plan_hash = plan.semantic_hash().ok();
+    }
+
+    pub fn get_query_semantic_hash(&self) -> Option<u64> {
+        *self.running_plan_hash.read()
     }
 
     pub fn add_source_abort_handle(&self, handle: AbortHandle) {
         let mut sources_abort_handle = self.sources_abort_handle.write();
-        sources_abort_handle.push(handle);
+        match self.aborted.load(Ordering::Relaxed) {
+            true => handle.abort(),
+            false => sources_abort_handle.push(handle),
+        }
     }
 }
 
@@ -145,5 +385,6 @@ impl Session {
     pub(in crate::sessions) fn destroy_context_shared(&self) {
         let mut mutable_state = self.mutable_state.lock();
         mutable_state.context_shared.take();
+        mutable_state.last_active = self.sessions.clock.instant();
     }
 }
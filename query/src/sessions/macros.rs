@@ -58,7 +58,7 @@ macro_rules! apply_update_settings {
         pub fn update_settings(&self, key: &str, value: String) -> Result<()> {
             paste::paste! {
                 $(
-                    if (key.to_lowercase().as_str() == $NAME) {
+                    if (fold_option_name(key).as_str() == $NAME) {
                         let v = apply_parse_value!{value, $TYPE};
                         return self.inner.[<try_update_ $TYPE:lower>]($NAME, v);
                     }
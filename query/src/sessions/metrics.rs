@@ -14,3 +14,21 @@
 
 pub static METRIC_SESSION_CONNECT_NUMBERS: &str = "session.connect_numbers";
 pub static METRIC_SESSION_CLOSE_NUMBERS: &str = "session.close_numbers";
+pub static METRIC_QUERY_COUNT: &str = "query.count";
+pub static METRIC_QUERY_SPILL_COUNT: &str = "query.spill_count";
+pub static METRIC_QUERY_SPILL_BYTES: &str = "query.spill_bytes";
+pub static METRIC_QUERY_PARTIAL_GROUPS_SENT: &str = "query.partial_groups_sent";
+pub static METRIC_QUERY_PARTIAL_GROUPS_PRUNED: &str = "query.partial_groups_pruned";
+pub static METRIC_QUERY_PART_READS: &str = "query.part_reads";
+pub static METRIC_QUERY_STATEMENT_SECONDS: &str = "query.statement_seconds";
+pub static METRIC_QUERY_RESULT_ROWS: &str = "query.result_rows";
+pub static METRIC_QUERY_RESULT_BYTES: &str = "query.result_bytes";
+pub static METRIC_QUERY_ADMISSION_WAIT_SECONDS: &str = "query.admission_wait_seconds";
+pub static METRIC_SESSION_CREATED: &str = "session.created";
+pub static METRIC_SESSION_REJECTED: &str = "session.rejected";
+pub static METRIC_SESSION_ACTIVE: &str = "session.active";
+pub static METRIC_SESSION_EXPIRED: &str = "session.expired";
+pub static METRIC_EXCHANGE_SENT_BYTES: &str = "exchange.sent_bytes";
+pub static METRIC_EXCHANGE_SENT_BLOCKS: &str = "exchange.sent_blocks";
+pub static METRIC_EXCHANGE_RECEIVED_BYTES: &str = "exchange.received_bytes";
+pub static METRIC_EXCHANGE_RECEIVED_BLOCKS: &str = "exchange.received_blocks";
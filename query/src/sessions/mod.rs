@@ -12,12 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod query_hints_test;
+#[cfg(test)]
+mod query_label_test;
+#[cfg(test)]
+mod query_log_test;
+#[cfg(test)]
+mod query_timeout_test;
+#[cfg(test)]
+mod query_warnings_test;
+#[cfg(test)]
+mod resource_groups_test;
+#[cfg(test)]
+mod settings_test;
+
 #[macro_use]
 mod macros;
 
 mod context;
 mod context_shared;
 mod metrics;
+mod query_hints;
+mod query_label;
+mod query_log;
+mod query_metrics;
+mod query_warnings;
+mod resource_groups;
 mod session;
 mod session_info;
 mod session_ref;
@@ -28,6 +49,27 @@ mod settings;
 
 pub use context::DatabendQueryContext;
 pub use context::DatabendQueryContextRef;
+pub use query_hints::QueryHints;
+pub use query_hints::QUERY_HINTS;
+pub use query_label::is_query_label_changed;
+pub use query_label::query_label_from_hint;
+pub use query_label::query_label_hint;
+pub use query_label::sanitize_query_label;
+pub use query_label::QUERY_LABEL_MAX_LEN;
+pub use query_log::QueryLog;
+pub use query_log::QueryLogEntry;
+pub use query_log::QueryLogStatus;
+pub use query_log::QUERY_LOG_MAX_ENTRIES;
+pub use query_metrics::QueryMetrics;
+pub use query_metrics::QueryMetricsValues;
+pub use query_warnings::QueryWarnings;
+pub use query_warnings::Warning;
+pub use resource_groups::ResourceGroup;
+pub use resource_groups::ResourceGroupConfig;
+pub use resource_groups::ResourceGroupManager;
+pub use resource_groups::ResourceGroupSlot;
+pub use resource_groups::ResourceGroupUsage;
+pub use resource_groups::DEFAULT_RESOURCE_GROUP;
 pub use session::Session;
 pub use session_info::ProcessInfo;
 pub use session_ref::SessionRef;
@@ -0,0 +1,156 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// The hints known to `QueryHints`, besides the `label(...)` of `query_label_hint`.
+pub const QUERY_HINTS: &[&str] = &["no_distributed", "no_filter_pushdown", "max_threads(N)"];
+
+/// The hints of a statement, from the `/*+ ... */` comments at its head, e.g.
+/// `/*+ no_distributed, max_threads(4) */ SELECT count(*) FROM t`, see `leading_hint_comments`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryHints {
+    /// Run the whole plan on this node, even in cluster mode.
+    pub no_distributed: bool,
+    /// Don't push the WHERE predicate down to the scan of the table.
+    pub no_filter_pushdown: bool,
+    /// Overrides the max_threads setting for this statement only.
+    pub max_threads: Option<u64>,
+    /// The hints unknown or with bad arguments, as they are written. They are ignored.
+    pub ignored: Vec<String>,
+}
+
+impl QueryHints {
+    pub fn parse(query: &str) -> QueryHints {
+        let mut hints = QueryHints::default();
+        for comment in leading_hint_comments(query) {
+            for hint in split_hints(comment) {
+                if !hints.apply(hint) {
+                    hints.ignored.push(hint.to_string());
+                }
+            }
+        }
+        hints
+    }
+
+    fn apply(&mut self, hint: &str) -> bool {
+        let (name, arguments) = match hint.split_once('(') {
+            None => (hint, None),
+            Some((name, arguments)) => match arguments.strip_suffix(')') {
+                None => return false,
+                Some(arguments) => (name.trim_end(), Some(arguments.trim())),
+            },
+        };
+
+        match (name.to_lowercase().as_str(), arguments) {
+            ("no_distributed", None) => self.no_distributed = true,
+            ("no_filter_pushdown", None) => self.no_filter_pushdown = true,
+            ("max_threads", Some(arguments)) => match arguments.parse::<u64>() {
+                Ok(max_threads) if max_threads > 0 => self.max_threads = Some(max_threads),
+                _ => return false,
+            },
+            // The label of the query, see `query_label_hint`.
+            ("label", Some(_)) => {}
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// The known hints of the statement, e.g. `no_distributed, max_threads(4)`, empty if none.
+impl fmt::Display for QueryHints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hints = vec![];
+        if self.no_distributed {
+            hints.push("no_distributed".to_string());
+        }
+        if self.no_filter_pushdown {
+            hints.push("no_filter_pushdown".to_string());
+        }
+        if let Some(max_threads) = self.max_threads {
+            hints.push(format!("max_threads({})", max_threads));
+        }
+        write!(f, "{}", hints.join(", "))
+    }
+}
+
+/// The text of the `/*+ ... */` comments at the head of a statement: before it, and right after
+/// its first keyword as in `SELECT /*+ ... */`. The other comments there are skipped. A `/*+`
+/// further on, e.g. in a string literal, is not a hint.
+fn leading_hint_comments(query: &str) -> Vec<&str> {
+    let mut comments = vec![];
+    let mut keyword_seen = false;
+    let mut rest = query;
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(comment) = rest.strip_prefix("/*") {
+            let end = match comment.find("*/") {
+                None => break,
+                Some(end) => end,
+            };
+            if let Some(hint) = comment[..end].strip_prefix('+') {
+                comments.push(hint);
+            }
+            rest = &comment[end + 2..];
+        } else if let Some(comment) = rest.strip_prefix("--") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if !keyword_seen {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            if end == 0 {
+                break;
+            }
+            keyword_seen = true;
+            rest = &rest[end..];
+        } else {
+            break;
+        }
+    }
+    comments
+}
+
+/// Splits the text of a hint comment by the spaces and commas outside the parentheses,
+/// e.g. `a, b (1) c` into `a`, `b (1)` and `c`.
+fn split_hints(comment: &str) -> Vec<&str> {
+    let mut hints = vec![];
+    let mut start = None;
+    let mut depth = 0usize;
+
+    for (index, c) in comment.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 && (c.is_whitespace() || c == ',') => {
+                // The arguments may be apart from the name of the hint.
+                let next = comment[index..].trim_start();
+                if c.is_whitespace() && start.is_some() && next.starts_with('(') {
+                    continue;
+                }
+                if let Some(start) = start.take() {
+                    hints.push(&comment[start..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(index);
+    }
+
+    if let Some(start) = start {
+        hints.push(&comment[start..]);
+    }
+    hints
+}
@@ -0,0 +1,108 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::codes;
+use common_exception::Result;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+
+use crate::sessions::QueryHints;
+
+#[test]
+fn test_query_hints_parse() {
+    let hints = QueryHints::parse("/*+ no_distributed */ SELECT 1");
+    assert!(hints.no_distributed);
+    assert!(!hints.no_filter_pushdown);
+    assert_eq!(None, hints.max_threads);
+
+    let hints = QueryHints::parse(
+        "SELECT /*+ NO_FILTER_PUSHDOWN, max_threads (4) label('a b') */ /*+ no_distributed */ 1",
+    );
+    assert_eq!(
+        QueryHints {
+            no_distributed: true,
+            no_filter_pushdown: true,
+            max_threads: Some(4),
+            ignored: vec![],
+        },
+        hints
+    );
+    assert_eq!(
+        "no_distributed, no_filter_pushdown, max_threads(4)",
+        hints.to_string()
+    );
+
+    // Only the comments starting with `/*+` have hints.
+    assert_eq!(
+        QueryHints::default(),
+        QueryHints::parse("/* no_distributed */ SELECT 1 -- max_threads(4)")
+    );
+    assert_eq!("", QueryHints::default().to_string());
+
+    // Only the comments at the head of the statement have hints, not a string literal.
+    let hints = QueryHints::parse(
+        "-- daily\n/* report */ SELECT /*+ max_threads(2) */ '/*+ no_distributed */', 1",
+    );
+    assert_eq!(
+        QueryHints {
+            max_threads: Some(2),
+            ..QueryHints::default()
+        },
+        hints
+    );
+    assert_eq!(
+        QueryHints::default(),
+        QueryHints::parse("SELECT '/*+ no_distributed */' /*+ max_threads(2) */")
+    );
+    assert_eq!(
+        QueryHints::default(),
+        QueryHints::parse("INSERT INTO t VALUES ('/*+ no_distributed */')")
+    );
+
+    let hints = QueryHints::parse(
+        "/*+ use_index(a) max_threads(0) max_threads(x) no_distributed(1) */ SELECT 1",
+    );
+    assert_eq!(
+        vec![
+            "use_index(a)",
+            "max_threads(0)",
+            "max_threads(x)",
+            "no_distributed(1)"
+        ],
+        hints.ignored
+    );
+    assert_eq!(QueryHints::default(), QueryHints {
+        ignored: vec![],
+        ..hints
+    });
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_query_hints_warnings() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    ctx.attach_query_str("/*+ max_threads(2) use_index(a) */ SELECT 1");
+
+    assert_eq!(Some(2), ctx.get_query_hints().max_threads);
+    assert_eq!(2, ctx.get_max_threads()?);
+
+    let warnings = ctx.get_warnings();
+    assert_eq!(1, warnings.len());
+    assert_eq!(codes::BadArguments, warnings[0].code);
+    assert_eq!(
+        "The query hint use_index(a) is ignored, expect one of no_distributed, no_filter_pushdown, max_threads(N)",
+        warnings[0].message
+    );
+
+    Ok(())
+}
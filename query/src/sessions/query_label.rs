@@ -0,0 +1,84 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The labels longer than this are truncated.
+pub const QUERY_LABEL_MAX_LEN: usize = 128;
+
+/// Makes a label safe to be logged and sent as request metadata: the surrounding quotes are
+/// stripped, the characters other than alphanumerics and ` _-.:=/,@` are replaced with `_`,
+/// and it is truncated to `QUERY_LABEL_MAX_LEN` characters. Returns None if it is empty.
+pub fn sanitize_query_label(label: &str) -> Option<String> {
+    let label = unquote_query_label(label)
+        .chars()
+        .take(QUERY_LABEL_MAX_LEN)
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() => c,
+            ' ' | '_' | '-' | '.' | ':' | '=' | '/' | ',' | '@' => c,
+            _ => '_',
+        })
+        .collect::<String>();
+
+    match label.is_empty() {
+        true => None,
+        false => Some(label),
+    }
+}
+
+/// Whether `sanitize_query_label` changes more than the surrounding quotes and spaces of the label.
+pub fn is_query_label_changed(label: &str) -> bool {
+    sanitize_query_label(label).unwrap_or_default() != unquote_query_label(label)
+}
+
+fn unquote_query_label(label: &str) -> &str {
+    let label = label.trim();
+    ['\'', '"', '`']
+        .iter()
+        .find_map(|quote| {
+            label
+                .strip_prefix(*quote)
+                .and_then(|label| label.strip_suffix(*quote))
+        })
+        .unwrap_or(label)
+        .trim()
+}
+
+/// The label of a `/*+ label(...) */` hint in the query, e.g.
+/// `SELECT /*+ label(team=billing) */ count(*) FROM t`.
+pub fn query_label_from_hint(query: &str) -> Option<String> {
+    query_label_hint(query).and_then(sanitize_query_label)
+}
+
+/// The label of the first `label` hint in the query as it is written, before `sanitize_query_label`.
+/// The hints whose labels are empty once sanitized are skipped.
+pub fn query_label_hint(query: &str) -> Option<&str> {
+    let mut rest = query;
+    while let Some(start) = rest.find("/*+") {
+        let comment = &rest[start + 3..];
+        let end = comment.find("*/")?;
+        let hint = comment[..end].trim();
+
+        let arguments = hint
+            .get(..5)
+            .filter(|name| name.eq_ignore_ascii_case("label"))
+            .map(|_| hint[5..].trim_start())
+            .and_then(|hint| hint.strip_prefix('('))
+            .and_then(|hint| hint.rfind(')').map(|close| &hint[..close]));
+
+        if let Some(label) = arguments.filter(|label| sanitize_query_label(label).is_some()) {
+            return Some(label);
+        }
+        rest = &comment[end + 2..];
+    }
+    None
+}
@@ -0,0 +1,73 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::sessions::query_label::is_query_label_changed;
+use crate::sessions::query_label::query_label_from_hint;
+use crate::sessions::query_label::sanitize_query_label;
+use crate::sessions::query_label::QUERY_LABEL_MAX_LEN;
+
+#[test]
+fn test_sanitize_query_label() {
+    assert_eq!(
+        Some("team=billing".to_string()),
+        sanitize_query_label("team=billing")
+    );
+    assert_eq!(
+        Some("team=billing".to_string()),
+        sanitize_query_label(" 'team=billing' ")
+    );
+    assert_eq!(
+        Some("nightly etl".to_string()),
+        sanitize_query_label("\"nightly etl\"")
+    );
+    assert_eq!(
+        Some("a_b_c_".to_string()),
+        sanitize_query_label("a\nb;c\u{e9}")
+    );
+    assert_eq!(None, sanitize_query_label("''"));
+    assert_eq!(None, sanitize_query_label("  "));
+
+    let long = "x".repeat(QUERY_LABEL_MAX_LEN * 2);
+    let label = sanitize_query_label(&long).unwrap();
+    assert_eq!(QUERY_LABEL_MAX_LEN, label.len());
+}
+
+#[test]
+fn test_query_label_from_hint() {
+    assert_eq!(
+        Some("team=billing".to_string()),
+        query_label_from_hint("SELECT /*+ label(team=billing) */ count(*) FROM t")
+    );
+    assert_eq!(
+        Some("dashboard".to_string()),
+        query_label_from_hint("/*+ LABEL('dashboard') */ SELECT 1")
+    );
+    assert_eq!(
+        Some("second".to_string()),
+        query_label_from_hint("SELECT /*+ other(1) */ /*+ label(second) */ 1")
+    );
+    assert_eq!(None, query_label_from_hint("SELECT 1"));
+    assert_eq!(None, query_label_from_hint("SELECT /* label(plain) */ 1"));
+    assert_eq!(None, query_label_from_hint("SELECT /*+ label() */ 1"));
+    assert_eq!(None, query_label_from_hint("SELECT /*+ label(unclosed"));
+}
+
+#[test]
+fn test_is_query_label_changed() {
+    assert!(!is_query_label_changed("team=billing"));
+    assert!(!is_query_label_changed(" 'team=billing' "));
+    assert!(!is_query_label_changed("''"));
+    assert!(is_query_label_changed("team;billing"));
+    assert!(is_query_label_changed(&"x".repeat(QUERY_LABEL_MAX_LEN + 1)));
+}
@@ -0,0 +1,102 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_infallible::RwLock;
+
+/// How many of the latest queries are kept by the node.
+pub const QUERY_LOG_MAX_ENTRIES: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryLogStatus {
+    Finished,
+    Failed,
+    /// The query panicked, it is caught and returned to the client as an error.
+    Panicked,
+}
+
+impl QueryLogStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryLogStatus::Finished => "finished",
+            QueryLogStatus::Failed => "failed",
+            QueryLogStatus::Panicked => "panicked",
+        }
+    }
+}
+
+/// A query of the node, shown by `system.query_log`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryLogEntry {
+    pub query_id: String,
+    pub session_id: String,
+    pub query: String,
+    pub status: QueryLogStatus,
+    /// The code and the message of the error if the query is not finished.
+    pub error_code: Option<u16>,
+    pub error_message: Option<String>,
+    /// Seconds since the unix epoch.
+    pub start_time: u32,
+    pub duration_ms: u64,
+    /// The known hints of the query, e.g. `no_distributed, max_threads(4)`, see `QueryHints`.
+    pub hints: String,
+    /// The semantic hash of the query plan, see `PlanNode::semantic_hash`. None if the query is not planned.
+    pub semantic_hash: Option<u64>,
+    /// The label of the query, see `DatabendQueryContext::get_query_label`.
+    pub query_label: Option<String>,
+    /// Count and total duration of the store rpcs of the query, of all the action types.
+    pub store_rpc_count: u64,
+    pub store_rpc_time_ms: u64,
+}
+
+impl QueryLogEntry {
+    pub fn seconds_since_epoch(time: SystemTime) -> u32 {
+        let seconds = time
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        seconds.min(u32::MAX as u64) as u32
+    }
+}
+
+/// The latest queries of the node, the oldest ones are dropped beyond the cap.
+pub struct QueryLog {
+    capacity: usize,
+    entries: RwLock<VecDeque<QueryLogEntry>>,
+}
+
+impl QueryLog {
+    pub fn create(capacity: usize) -> Self {
+        QueryLog {
+            capacity,
+            entries: RwLock::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn append(&self, entry: QueryLogEntry) {
+        let mut entries = self.entries.write();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// The entries from the oldest to the latest.
+    pub fn entries(&self) -> Vec<QueryLogEntry> {
+        self.entries.read().iter().cloned().collect()
+    }
+}
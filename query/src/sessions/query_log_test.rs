@@ -0,0 +1,54 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pretty_assertions::assert_eq;
+
+use crate::sessions::QueryLog;
+use crate::sessions::QueryLogEntry;
+use crate::sessions::QueryLogStatus;
+
+fn entry(query: &str) -> QueryLogEntry {
+    QueryLogEntry {
+        query_id: format!("id-{}", query),
+        session_id: "session".to_string(),
+        query: query.to_string(),
+        status: QueryLogStatus::Finished,
+        error_code: None,
+        error_message: None,
+        start_time: 0,
+        duration_ms: 0,
+        hints: String::new(),
+        semantic_hash: None,
+        query_label: None,
+        store_rpc_count: 0,
+        store_rpc_time_ms: 0,
+    }
+}
+
+#[test]
+fn test_query_log_drops_the_oldest() {
+    let query_log = QueryLog::create(2);
+    query_log.append(entry("SELECT 1"));
+    query_log.append(entry("SELECT 2"));
+    assert_eq!(query_log.entries(), vec![
+        entry("SELECT 1"),
+        entry("SELECT 2")
+    ]);
+
+    query_log.append(entry("SELECT 3"));
+    assert_eq!(query_log.entries(), vec![
+        entry("SELECT 2"),
+        entry("SELECT 3")
+    ]);
+}
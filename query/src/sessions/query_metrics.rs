@@ -0,0 +1,205 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_planners::PlanNode;
+use common_store_api_sdk::RpcStat;
+use common_store_api_sdk::RpcStats;
+use metrics::counter;
+
+#[derive(Debug, Clone)]
+pub struct QueryMetricsValues {
+    pub spill_count: usize,
+    pub spill_bytes: usize,
+    /// Groups sent and pruned by the partial aggregations of the query.
+    pub partial_groups_sent: usize,
+    pub partial_groups_pruned: usize,
+    /// Count, total and max fetch duration of the parts read from the store by the scans.
+    pub part_reads: usize,
+    pub part_read_time: Duration,
+    pub part_read_max_time: Duration,
+    /// Count and total duration of the store rpcs, by action type.
+    pub store_rpcs: BTreeMap<String, RpcStat>,
+    /// Blocks and bytes exchanged with the other nodes by the stages of the query.
+    pub exchange_sent_blocks: usize,
+    pub exchange_sent_bytes: usize,
+    pub exchange_received_blocks: usize,
+    pub exchange_received_bytes: usize,
+    /// Rows and bytes of the result served to the client.
+    pub result_rows: usize,
+    pub result_bytes: usize,
+    pub failed: bool,
+}
+
+/// Counters of a query, shared by the query context and the contexts of its subqueries.
+#[derive(Debug)]
+pub struct QueryMetrics {
+    spill_count: AtomicUsize,
+    spill_bytes: AtomicUsize,
+    partial_groups_sent: AtomicUsize,
+    partial_groups_pruned: AtomicUsize,
+    part_reads: AtomicUsize,
+    part_read_micros: AtomicU64,
+    part_read_max_micros: AtomicU64,
+    store_rpcs: Arc<RpcStats>,
+    exchange_sent_blocks: AtomicUsize,
+    exchange_sent_bytes: AtomicUsize,
+    exchange_received_blocks: AtomicUsize,
+    exchange_received_bytes: AtomicUsize,
+    result_rows: AtomicUsize,
+    result_bytes: AtomicUsize,
+    failed: AtomicBool,
+}
+
+impl QueryMetrics {
+    /// The store calls of the query are counted into `node_store_rpcs` as well.
+    pub fn create(node_store_rpcs: Arc<RpcStats>) -> Self {
+        QueryMetrics {
+            spill_count: AtomicUsize::new(0),
+            spill_bytes: AtomicUsize::new(0),
+            partial_groups_sent: AtomicUsize::new(0),
+            partial_groups_pruned: AtomicUsize::new(0),
+            part_reads: AtomicUsize::new(0),
+            part_read_micros: AtomicU64::new(0),
+            part_read_max_micros: AtomicU64::new(0),
+            store_rpcs: Arc::new(RpcStats::create_with_parent(node_store_rpcs)),
+            exchange_sent_blocks: AtomicUsize::new(0),
+            exchange_sent_bytes: AtomicUsize::new(0),
+            exchange_received_blocks: AtomicUsize::new(0),
+            exchange_received_bytes: AtomicUsize::new(0),
+            result_rows: AtomicUsize::new(0),
+            result_bytes: AtomicUsize::new(0),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn incr_spill(&self, bytes: usize) {
+        self.spill_count.fetch_add(1, Ordering::Relaxed);
+        self.spill_bytes.fetch_add(bytes, Ordering::Relaxed);
+
+        counter!(super::metrics::METRIC_QUERY_SPILL_COUNT, 1);
+        counter!(super::metrics::METRIC_QUERY_SPILL_BYTES, bytes as u64);
+    }
+
+    pub fn incr_partial_groups(&self, sent: usize, pruned: usize) {
+        self.partial_groups_sent.fetch_add(sent, Ordering::Relaxed);
+        self.partial_groups_pruned
+            .fetch_add(pruned, Ordering::Relaxed);
+
+        counter!(
+            super::metrics::METRIC_QUERY_PARTIAL_GROUPS_SENT,
+            sent as u64
+        );
+        counter!(
+            super::metrics::METRIC_QUERY_PARTIAL_GROUPS_PRUNED,
+            pruned as u64
+        );
+    }
+
+    /// A part is read from the store, `elapsed` is the time to fetch and decode it.
+    pub fn incr_part_read(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros() as u64;
+        self.part_reads.fetch_add(1, Ordering::Relaxed);
+        self.part_read_micros.fetch_add(micros, Ordering::Relaxed);
+        self.part_read_max_micros
+            .fetch_max(micros, Ordering::Relaxed);
+
+        counter!(super::metrics::METRIC_QUERY_PART_READS, 1);
+    }
+
+    /// A block is sent to another node by a stage of the query.
+    pub fn incr_exchange_sent(&self, bytes: usize) {
+        self.exchange_sent_blocks.fetch_add(1, Ordering::Relaxed);
+        self.exchange_sent_bytes.fetch_add(bytes, Ordering::Relaxed);
+
+        counter!(super::metrics::METRIC_EXCHANGE_SENT_BLOCKS, 1);
+        counter!(super::metrics::METRIC_EXCHANGE_SENT_BYTES, bytes as u64);
+    }
+
+    /// A block is received from a stage of the query on another node.
+    pub fn incr_exchange_received(&self, bytes: usize) {
+        self.exchange_received_blocks
+            .fetch_add(1, Ordering::Relaxed);
+        self.exchange_received_bytes
+            .fetch_add(bytes, Ordering::Relaxed);
+
+        counter!(super::metrics::METRIC_EXCHANGE_RECEIVED_BLOCKS, 1);
+        counter!(super::metrics::METRIC_EXCHANGE_RECEIVED_BYTES, bytes as u64);
+    }
+
+    /// A block of the result is served to the client.
+    pub fn incr_result(&self, rows: usize, bytes: usize) {
+        self.result_rows.fetch_add(rows, Ordering::Relaxed);
+        self.result_bytes.fetch_add(bytes, Ordering::Relaxed);
+
+        counter!(super::metrics::METRIC_QUERY_RESULT_ROWS, rows as u64);
+        counter!(super::metrics::METRIC_QUERY_RESULT_BYTES, bytes as u64);
+    }
+
+    /// The handler of the query reports its failure, it is the outcome of the statement metrics.
+    pub fn mark_failed(&self) {
+        self.failed.store(true, Ordering::Relaxed);
+    }
+
+    /// The store clients of the query count their calls into it.
+    pub fn get_store_rpc_stats(&self) -> Arc<RpcStats> {
+        self.store_rpcs.clone()
+    }
+
+    pub fn get_values(&self) -> QueryMetricsValues {
+        QueryMetricsValues {
+            spill_count: self.spill_count.load(Ordering::Relaxed),
+            spill_bytes: self.spill_bytes.load(Ordering::Relaxed),
+            partial_groups_sent: self.partial_groups_sent.load(Ordering::Relaxed),
+            partial_groups_pruned: self.partial_groups_pruned.load(Ordering::Relaxed),
+            part_reads: self.part_reads.load(Ordering::Relaxed),
+            part_read_time: Duration::from_micros(self.part_read_micros.load(Ordering::Relaxed)),
+            part_read_max_time: Duration::from_micros(
+                self.part_read_max_micros.load(Ordering::Relaxed),
+            ),
+            store_rpcs: self.store_rpcs.snapshot(),
+            exchange_sent_blocks: self.exchange_sent_blocks.load(Ordering::Relaxed),
+            exchange_sent_bytes: self.exchange_sent_bytes.load(Ordering::Relaxed),
+            exchange_received_blocks: self.exchange_received_blocks.load(Ordering::Relaxed),
+            exchange_received_bytes: self.exchange_received_bytes.load(Ordering::Relaxed),
+            result_rows: self.result_rows.load(Ordering::Relaxed),
+            result_bytes: self.result_bytes.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The `kind` label of the statement metrics.
+pub fn statement_kind(plan: &PlanNode) -> &'static str {
+    match plan {
+        PlanNode::Select(_) => "select",
+        PlanNode::InsertInto(_) => "insert",
+        PlanNode::CreateDatabase(_)
+        | PlanNode::DropDatabase(_)
+        | PlanNode::CreateTable(_)
+        | PlanNode::DropTable(_)
+        | PlanNode::UndropTable(_)
+        | PlanNode::TruncateTable(_)
+        | PlanNode::ModifyColumn(_)
+        | PlanNode::OptimizeTable(_) => "ddl",
+        _ => "other",
+    }
+}
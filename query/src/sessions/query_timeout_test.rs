@@ -0,0 +1,57 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_query_deadline() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    assert_eq!(None, ctx.get_query_deadline()?);
+
+    ctx.get_settings().set_max_execution_time(500)?;
+    let deadline = ctx.get_query_deadline()?.unwrap();
+    assert!(deadline <= Instant::now() + Duration::from_millis(500));
+    assert!(deadline > Instant::now());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_map_query_timeout() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    // Without max_execution_time, the deadline is not the one of the query.
+    let e = ctx.map_query_timeout(ErrorCode::DeadlineExceeded("Read is given up"));
+    assert_eq!(ErrorCode::DeadlineExceeded("").code(), e.code());
+
+    ctx.get_settings().set_max_execution_time(500)?;
+    let e = ctx.map_query_timeout(ErrorCode::DeadlineExceeded("Read is given up"));
+    assert_eq!(ErrorCode::QueryTimeout("").code(), e.code());
+    assert!(e.message().starts_with("Query is stopped after "));
+    assert!(e
+        .message()
+        .ends_with("max_execution_time is 500ms, cause: Read is given up"));
+
+    // The other errors are not the timeouts of the query.
+    let e = ctx.map_query_timeout(ErrorCode::CannotConnectNode("connection refused"));
+    assert_eq!(ErrorCode::CannotConnectNode("").code(), e.code());
+
+    Ok(())
+}
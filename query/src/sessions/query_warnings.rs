@@ -0,0 +1,85 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::codes;
+use common_infallible::RwLock;
+
+/// A warning of a statement, e.g. shown by `SHOW WARNINGS`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    /// One of `common_exception::codes`.
+    pub code: u16,
+    pub message: String,
+    /// How many times the same warning is pushed.
+    pub count: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    list: Vec<Warning>,
+    /// The pushes beyond the cap, they are only counted.
+    suppressed: u64,
+}
+
+/// The warnings of a statement, pushed by the interpreters and the processors of any thread.
+/// A warning pushed again only increases its count, and the list is capped.
+#[derive(Default)]
+pub struct QueryWarnings {
+    inner: RwLock<Inner>,
+}
+
+impl QueryWarnings {
+    pub fn create() -> Self {
+        QueryWarnings::default()
+    }
+
+    pub fn push(&self, code: u16, message: impl Into<String>, max_warnings: usize) {
+        let message = message.into();
+        let mut inner = self.inner.write();
+        let same = inner
+            .list
+            .iter_mut()
+            .find(|warning| warning.code == code && warning.message == message);
+
+        match same {
+            Some(warning) => warning.count += 1,
+            None if inner.list.len() < max_warnings => inner.list.push(Warning {
+                code,
+                message,
+                count: 1,
+            }),
+            None => inner.suppressed += 1,
+        }
+    }
+
+    /// The warnings in the order they are pushed first, with a marker of the suppressed ones.
+    pub fn get(&self) -> Vec<Warning> {
+        let inner = self.inner.read();
+        let mut warnings = inner.list.clone();
+        if inner.suppressed > 0 {
+            warnings.push(Warning {
+                code: codes::TooManyWarnings,
+                message: format!("{} more warnings are suppressed", inner.suppressed),
+                count: 1,
+            });
+        }
+        warnings
+    }
+
+    /// How many warnings are pushed, including the repeated and the suppressed ones.
+    pub fn total(&self) -> u64 {
+        let inner = self.inner.read();
+        inner.list.iter().map(|warning| warning.count).sum::<u64>() + inner.suppressed
+    }
+}
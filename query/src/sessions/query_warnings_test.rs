@@ -0,0 +1,80 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_exception::codes;
+use pretty_assertions::assert_eq;
+
+use crate::sessions::QueryWarnings;
+use crate::sessions::Warning;
+
+fn warning(code: u16, message: &str, count: u64) -> Warning {
+    Warning {
+        code,
+        message: message.to_string(),
+        count,
+    }
+}
+
+#[test]
+fn test_query_warnings_cap() {
+    let warnings = QueryWarnings::create();
+    warnings.push(codes::UnImplement, "first", 2);
+    warnings.push(codes::BadArguments, "second", 2);
+    warnings.push(codes::BadArguments, "third", 2);
+    warnings.push(codes::UnImplement, "first", 2);
+    warnings.push(codes::BadArguments, "fourth", 2);
+
+    // The repeated ones are counted even at the cap, the others are suppressed.
+    assert_eq!(
+        vec![
+            warning(codes::UnImplement, "first", 2),
+            warning(codes::BadArguments, "second", 1),
+            warning(codes::TooManyWarnings, "2 more warnings are suppressed", 1),
+        ],
+        warnings.get()
+    );
+    assert_eq!(5, warnings.total());
+}
+
+#[test]
+fn test_query_warnings_concurrent() {
+    let warnings = Arc::new(QueryWarnings::create());
+
+    let handles = (0..8)
+        .map(|i| {
+            let warnings = warnings.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    warnings.push(codes::BadArguments, format!("from {}", i % 2), 64);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for h in handles {
+        h.join().unwrap();
+    }
+
+    let mut got = warnings.get();
+    got.sort_by(|a, b| a.message.cmp(&b.message));
+    assert_eq!(
+        vec![
+            warning(codes::BadArguments, "from 0", 400),
+            warning(codes::BadArguments, "from 1", 400),
+        ],
+        got
+    );
+    assert_eq!(800, warnings.total());
+}
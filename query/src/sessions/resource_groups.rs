@@ -0,0 +1,304 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_runtime::tokio::sync::OwnedSemaphorePermit;
+use common_runtime::tokio::sync::Semaphore;
+use common_runtime::SharedClock;
+use metrics::histogram;
+
+use crate::configs::Config;
+
+pub const DEFAULT_RESOURCE_GROUP: &str = "default";
+
+/// The interval at which the token budget of a group is refilled.
+const RESOURCE_GROUP_TICK: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceGroupConfig {
+    pub name: String,
+    pub weight: u64,
+    /// 0 means no limit.
+    pub max_concurrency: u64,
+}
+
+impl ResourceGroupConfig {
+    /// Parse groups from the form `name:weight[:max_concurrency],...`.
+    /// The `default` group with weight 1 is always present unless it is redefined.
+    pub fn parse_list(spec: &str) -> Result<Vec<ResourceGroupConfig>> {
+        let mut groups = vec![];
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let fields = item.split(':').map(str::trim).collect::<Vec<_>>();
+            if fields.len() < 2 || fields.len() > 3 || fields[0].is_empty() {
+                return Err(ErrorCode::BadArguments(format!(
+                    "Bad resource group: '{}', expect name:weight[:max_concurrency]",
+                    item
+                )));
+            }
+
+            let weight = fields[1].parse::<u64>()?;
+            if weight == 0 {
+                return Err(ErrorCode::BadArguments(format!(
+                    "Resource group '{}' weight must be greater than 0",
+                    fields[0]
+                )));
+            }
+
+            groups.push(ResourceGroupConfig {
+                name: fields[0].to_lowercase(),
+                weight,
+                max_concurrency: match fields.get(2) {
+                    None => 0,
+                    Some(v) => v.parse::<u64>()?,
+                },
+            });
+        }
+
+        if !groups.iter().any(|g| g.name == DEFAULT_RESOURCE_GROUP) {
+            groups.push(ResourceGroupConfig {
+                name: DEFAULT_RESOURCE_GROUP.to_string(),
+                weight: 1,
+                max_concurrency: 0,
+            });
+        }
+
+        Ok(groups)
+    }
+}
+
+/// A snapshot of the live usage of a resource group.
+#[derive(Clone, Debug)]
+pub struct ResourceGroupUsage {
+    pub name: String,
+    pub weight: u64,
+    pub max_concurrency: u64,
+    pub running_queries: usize,
+    pub queued_queries: usize,
+    pub processed_rows: usize,
+}
+
+struct TokenBudget {
+    tokens: i64,
+    last_refill: Instant,
+}
+
+/// A named group of queries sharing a token budget.
+///
+/// The budget is refilled every tick with `weight * tick_rows` tokens, and a processed
+/// block consumes one token per row. When the budget is exhausted, the consumer yields
+/// until the next tick, so concurrent groups progress proportionally to their weights.
+pub struct ResourceGroup {
+    name: String,
+    weight: u64,
+    max_concurrency: u64,
+    tick_tokens: i64,
+    budget: Mutex<TokenBudget>,
+    /// The clock of the ticks, e.g. a `VirtualClock` of a test.
+    clock: SharedClock,
+    concurrency: Option<Arc<Semaphore>>,
+    running_queries: Arc<AtomicUsize>,
+    queued_queries: AtomicUsize,
+    processed_rows: AtomicUsize,
+}
+
+impl ResourceGroup {
+    pub fn create(
+        config: &ResourceGroupConfig,
+        tick_rows: u64,
+        clock: SharedClock,
+    ) -> Arc<ResourceGroup> {
+        let tick_tokens = (config.weight * tick_rows) as i64;
+        let concurrency = match config.max_concurrency {
+            0 => None,
+            n => Some(Arc::new(Semaphore::new(n as usize))),
+        };
+
+        Arc::new(ResourceGroup {
+            name: config.name.clone(),
+            weight: config.weight,
+            max_concurrency: config.max_concurrency,
+            tick_tokens,
+            budget: Mutex::new(TokenBudget {
+                tokens: tick_tokens,
+                last_refill: clock.instant(),
+            }),
+            clock,
+            concurrency,
+            running_queries: Arc::new(AtomicUsize::new(0)),
+            queued_queries: AtomicUsize::new(0),
+            processed_rows: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Wait for a free query slot of this group.
+    /// The slot is released when the returned `ResourceGroupSlot` is dropped.
+    pub async fn acquire_slot(self: &Arc<Self>) -> Result<ResourceGroupSlot> {
+        let permit = match &self.concurrency {
+            None => None,
+            Some(semaphore) => {
+                let queued = Instant::now();
+                self.queued_queries.fetch_add(1, Ordering::Relaxed);
+                let permit = semaphore.clone().acquire_owned().await;
+                self.queued_queries.fetch_sub(1, Ordering::Relaxed);
+                histogram!(
+                    super::metrics::METRIC_QUERY_ADMISSION_WAIT_SECONDS,
+                    queued.elapsed(),
+                    "resource_group" => self.name.clone()
+                );
+
+                Some(permit.map_err(|cause| {
+                    ErrorCode::LogicalError(format!(
+                        "Cannot acquire slot of resource group {}, cause: {}",
+                        self.name, cause
+                    ))
+                })?)
+            }
+        };
+
+        self.running_queries.fetch_add(1, Ordering::Relaxed);
+        Ok(ResourceGroupSlot {
+            running_queries: self.running_queries.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// Consume `rows` tokens, yielding until the next tick if the budget is exhausted.
+    /// A group with a zero tick budget is never throttled.
+    pub async fn consume(&self, rows: usize) {
+        if self.tick_tokens > 0 {
+            while let Some(wait) = self.try_consume(rows) {
+                self.clock.sleep(wait).await;
+            }
+        }
+
+        self.processed_rows.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    fn try_consume(&self, rows: usize) -> Option<Duration> {
+        let mut budget = self.budget.lock();
+
+        let now = self.clock.instant();
+        let elapsed = now.saturating_duration_since(budget.last_refill);
+        let ticks = (elapsed.as_nanos() / RESOURCE_GROUP_TICK.as_nanos()) as u32;
+        if ticks > 0 {
+            // Unused budget is not accumulated beyond one tick, an idle group can not burst.
+            let refill = self.tick_tokens.saturating_mul(ticks as i64);
+            budget.tokens = (budget.tokens + refill).min(self.tick_tokens);
+            budget.last_refill += RESOURCE_GROUP_TICK * ticks;
+        }
+
+        match budget.tokens > 0 {
+            true => {
+                // The budget may become negative for a large block, it is paid off by later ticks.
+                budget.tokens -= rows as i64;
+                None
+            }
+            false => {
+                let since_refill = now.saturating_duration_since(budget.last_refill);
+                Some(RESOURCE_GROUP_TICK - since_refill.min(RESOURCE_GROUP_TICK))
+            }
+        }
+    }
+
+    pub fn usage(&self) -> ResourceGroupUsage {
+        ResourceGroupUsage {
+            name: self.name.clone(),
+            weight: self.weight,
+            max_concurrency: self.max_concurrency,
+            running_queries: self.running_queries.load(Ordering::Relaxed),
+            queued_queries: self.queued_queries.load(Ordering::Relaxed),
+            processed_rows: self.processed_rows.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A running query slot of a resource group.
+pub struct ResourceGroupSlot {
+    running_queries: Arc<AtomicUsize>,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for ResourceGroupSlot {
+    fn drop(&mut self) {
+        self.running_queries.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub struct ResourceGroupManager {
+    groups: HashMap<String, Arc<ResourceGroup>>,
+    user_groups: HashMap<String, String>,
+}
+
+impl ResourceGroupManager {
+    pub fn from_conf(conf: &Config, clock: SharedClock) -> Result<ResourceGroupManager> {
+        let tick_rows = conf.query.resource_group_tick_rows;
+
+        let mut groups = HashMap::new();
+        for group in ResourceGroupConfig::parse_list(&conf.query.resource_groups)? {
+            let resource_group = ResourceGroup::create(&group, tick_rows, clock.clone());
+            groups.insert(group.name.clone(), resource_group);
+        }
+
+        let mut user_groups = HashMap::new();
+        let mappings = conf.query.resource_group_users.split(',');
+        for mapping in mappings.map(str::trim).filter(|s| !s.is_empty()) {
+            match mapping.split_once('=') {
+                Some((user, group)) if groups.contains_key(&group.trim().to_lowercase()) => {
+                    user_groups.insert(user.trim().to_string(), group.trim().to_lowercase());
+                }
+                _ => {
+                    return Err(ErrorCode::BadArguments(format!(
+                        "Bad resource group user mapping: '{}', expect user=group with a defined group",
+                        mapping
+                    )));
+                }
+            }
+        }
+
+        Ok(ResourceGroupManager {
+            groups,
+            user_groups,
+        })
+    }
+
+    pub fn get_group(&self, name: &str) -> Result<Arc<ResourceGroup>> {
+        self.groups
+            .get(&name.to_lowercase())
+            .cloned()
+            .ok_or_else(|| ErrorCode::BadArguments(format!("Unknown resource group: {}", name)))
+    }
+
+    pub fn get_user_group(&self, user: &str) -> Option<String> {
+        self.user_groups.get(user).cloned()
+    }
+
+    pub fn get_groups(&self) -> Vec<Arc<ResourceGroup>> {
+        let mut groups = self.groups.values().cloned().collect::<Vec<_>>();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_exception::Result;
+use common_runtime::tokio;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use futures::FutureExt;
+
+use crate::clusters::Cluster;
+use crate::configs::Config;
+use crate::sessions::ResourceGroupConfig;
+use crate::sessions::SessionManager;
+
+#[test]
+fn test_resource_group_config_parse() -> Result<()> {
+    let groups = ResourceGroupConfig::parse_list("etl:3:2, adhoc:1")?;
+    assert_eq!(groups, vec![
+        ResourceGroupConfig {
+            name: "etl".to_string(),
+            weight: 3,
+            max_concurrency: 2,
+        },
+        ResourceGroupConfig {
+            name: "adhoc".to_string(),
+            weight: 1,
+            max_concurrency: 0,
+        },
+        ResourceGroupConfig {
+            name: "default".to_string(),
+            weight: 1,
+            max_concurrency: 0,
+        },
+    ]);
+
+    let groups = ResourceGroupConfig::parse_list("default:2")?;
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].weight, 2);
+
+    assert!(ResourceGroupConfig::parse_list("etl").is_err());
+    assert!(ResourceGroupConfig::parse_list("etl:0").is_err());
+    assert!(ResourceGroupConfig::parse_list("etl:1:2:3").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_resource_group_assignment() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.resource_groups = "etl:3,adhoc:1".to_string();
+    conf.query.resource_group_users = "bob=etl".to_string();
+    let sessions = SessionManager::from_conf(conf, Cluster::empty())?;
+
+    // Default group.
+    let session = sessions.create_session("TestSession")?;
+    assert_eq!(session.get_resource_group()?.name(), "default");
+
+    // User mapping.
+    session.set_current_user("bob".to_string());
+    assert_eq!(session.get_resource_group()?.name(), "etl");
+    assert_eq!(session.process_info().resource_group, "etl");
+
+    // SET resource_group overrides the user mapping.
+    session
+        .get_settings()
+        .update_settings("resource_group", "adhoc".to_string())?;
+    assert_eq!(session.get_resource_group()?.name(), "adhoc");
+
+    session
+        .get_settings()
+        .update_settings("resource_group", "unknown".to_string())?;
+    assert!(session.get_resource_group().is_err());
+
+    // Unknown group of user mapping.
+    let mut conf = Config::default();
+    conf.query.resource_group_users = "bob=etl".to_string();
+    assert!(SessionManager::from_conf(conf, Cluster::empty()).is_err());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_resource_group_weighted_scheduling() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.resource_groups = "etl:3,adhoc:1".to_string();
+    conf.query.resource_group_tick_rows = 1000;
+    let clock = VirtualClock::create();
+    let sessions = SessionManager::from_conf_with_clock(
+        conf,
+        Cluster::empty(),
+        SharedClock::create(clock.clone()),
+    )?;
+
+    let resource_groups = sessions.get_resource_groups();
+    let etl = resource_groups.get_group("etl")?;
+    let adhoc = resource_groups.get_group("adhoc")?;
+
+    // Each group reads blocks of 100 rows until it is throttled, then the clock moves a tick.
+    for _tick in 0..50 {
+        for resource_group in [&etl, &adhoc] {
+            while resource_group.consume(100).now_or_never().is_some() {}
+        }
+        clock.advance(Duration::from_millis(10));
+    }
+
+    assert_eq!(50 * 3000, etl.usage().processed_rows);
+    assert_eq!(50 * 1000, adhoc.usage().processed_rows);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_resource_group_concurrent_acquire() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.resource_groups = "etl:1:2".to_string();
+    let sessions = SessionManager::from_conf(conf, Cluster::empty())?;
+
+    let session = sessions.create_session("TestSession")?;
+    session
+        .get_settings()
+        .update_settings("resource_group", "etl".to_string())?;
+    let ctx = session.create_context();
+    let etl = sessions.get_resource_groups().get_group("etl")?;
+
+    // The query and its subqueries share one slot, however many of them acquire it at once.
+    let acquires = (0..8)
+        .map(|_| {
+            let ctx = ctx.clone();
+            tokio::spawn(async move { ctx.acquire_resource_group_slot().await })
+        })
+        .collect::<Vec<_>>();
+    for acquire in acquires {
+        acquire.await.unwrap()?;
+    }
+    assert_eq!(1, etl.usage().running_queries);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_resource_group_max_concurrency() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.resource_groups = "etl:1:1,adhoc:1:1".to_string();
+    let sessions = SessionManager::from_conf(conf, Cluster::empty())?;
+
+    let create_context = |group_name: &str| -> Result<_> {
+        let session = sessions.create_session("TestSession")?;
+        session
+            .get_settings()
+            .update_settings("resource_group", group_name.to_string())?;
+        Ok(session.create_context())
+    };
+
+    let etl_ctx_1 = create_context("etl")?;
+    let etl_ctx_2 = create_context("etl")?;
+    let adhoc_ctx = create_context("adhoc")?;
+    let etl = sessions.get_resource_groups().get_group("etl")?;
+
+    etl_ctx_1.acquire_resource_group_slot().await?;
+    assert_eq!(etl.usage().running_queries, 1);
+
+    // The second query of the group is queued.
+    let queued_ctx = etl_ctx_2.clone();
+    let queued = tokio::spawn(async move { queued_ctx.acquire_resource_group_slot().await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(etl.usage().queued_queries, 1);
+
+    // While the query of other group runs.
+    let acquire = adhoc_ctx.acquire_resource_group_slot();
+    tokio::time::timeout(Duration::from_millis(100), acquire)
+        .await
+        .expect("the query of adhoc group should not be queued")?;
+
+    // The queued query runs after the first query is finished.
+    drop(etl_ctx_1);
+    tokio::time::timeout(Duration::from_secs(1), queued)
+        .await
+        .expect("the queued query should run")
+        .unwrap()?;
+    assert_eq!(etl.usage().queued_queries, 0);
+    assert_eq!(etl.usage().running_queries, 1);
+    Ok(())
+}
@@ -13,8 +13,11 @@
 // limitations under the License.
 
 use std::net::SocketAddr;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use common_exception::Result;
 use common_infallible::Mutex;
@@ -27,17 +30,27 @@ use crate::configs::Config;
 use crate::sessions::context_shared::DatabendQueryContextShared;
 use crate::sessions::DatabendQueryContext;
 use crate::sessions::DatabendQueryContextRef;
+use crate::sessions::ResourceGroup;
 use crate::sessions::SessionManagerRef;
 use crate::sessions::Settings;
+use crate::sessions::Warning;
+use crate::sessions::DEFAULT_RESOURCE_GROUP;
+use crate::sql::PlanTemplateCache;
 
 pub(in crate::sessions) struct MutableStatus {
     pub(in crate::sessions) abort: bool,
+    pub(in crate::sessions) expired: bool,
+    /// The last query start or end, or the last request of the client.
+    pub(in crate::sessions) last_active: Instant,
     pub(in crate::sessions) current_database: String,
+    pub(in crate::sessions) current_user: Option<String>,
+    pub(in crate::sessions) in_transaction: bool,
     pub(in crate::sessions) session_settings: Arc<Settings>,
     #[allow(unused)]
     pub(in crate::sessions) client_host: Option<SocketAddr>,
     pub(in crate::sessions) io_shutdown_tx: Option<Sender<Sender<()>>>,
     pub(in crate::sessions) context_shared: Option<Arc<DatabendQueryContextShared>>,
+    pub(in crate::sessions) last_warnings: Vec<Warning>,
 }
 
 #[derive(Clone)]
@@ -48,6 +61,8 @@ pub struct Session {
     pub(in crate::sessions) sessions: SessionManagerRef,
     pub(in crate::sessions) ref_count: Arc<AtomicUsize>,
     pub(in crate::sessions) mutable_state: Arc<Mutex<MutableStatus>>,
+    pub(in crate::sessions) plan_templates: Arc<PlanTemplateCache>,
+    pub(in crate::sessions) result_buffer_bytes: Arc<AtomicU64>,
 }
 
 impl Session {
@@ -65,12 +80,19 @@ impl Session {
             ref_count: Arc::new(AtomicUsize::new(0)),
             mutable_state: Arc::new(Mutex::new(MutableStatus {
                 abort: false,
+                expired: false,
+                last_active: sessions.clock.instant(),
                 current_database: String::from("default"),
+                current_user: None,
+                in_transaction: false,
                 session_settings: Settings::try_create()?,
                 client_host: None,
                 io_shutdown_tx: None,
                 context_shared: None,
+                last_warnings: vec![],
             })),
+            plan_templates: Arc::new(PlanTemplateCache::create()),
+            result_buffer_bytes: Arc::new(AtomicU64::new(0)),
         }))
     }
 
@@ -86,6 +108,32 @@ impl Session {
         self.mutable_state.lock().abort
     }
 
+    /// Aborted because it was idle for longer than session_idle_timeout_secs.
+    pub fn is_expired(self: &Arc<Self>) -> bool {
+        self.mutable_state.lock().expired
+    }
+
+    /// Records an activity of the session, it is idle from now on.
+    pub fn touch(self: &Arc<Self>) {
+        self.mutable_state.lock().last_active = self.sessions.clock.instant();
+    }
+
+    /// Marks the session expired if it runs no query and has no activity for `idle_timeout`.
+    /// A killed session never expires.
+    pub(in crate::sessions) fn expire_if_idle(self: &Arc<Self>, idle_timeout: Duration) -> bool {
+        let now = self.sessions.clock.instant();
+        let mut mutable_state = self.mutable_state.lock();
+        let idle = !mutable_state.abort
+            && mutable_state.context_shared.is_none()
+            && now.saturating_duration_since(mutable_state.last_active) >= idle_timeout;
+
+        if idle {
+            mutable_state.abort = true;
+            mutable_state.expired = true;
+        }
+        idle
+    }
+
     pub fn kill(self: &Arc<Self>) {
         let mut mutable_state = self.mutable_state.lock();
 
@@ -116,6 +164,7 @@ impl Session {
 
     pub fn create_context(self: &Arc<Self>) -> DatabendQueryContextRef {
         let mut state_guard = self.mutable_state.lock();
+        state_guard.last_active = self.sessions.clock.instant();
 
         if state_guard.context_shared.is_none() {
             let config = self.config.clone();
@@ -154,6 +203,64 @@ impl Session {
         inner.current_database.clone()
     }
 
+    pub fn set_current_user(self: &Arc<Self>, user: String) {
+        let mut inner = self.mutable_state.lock();
+        inner.current_user = Some(user);
+    }
+
+    pub fn get_current_user(self: &Arc<Self>) -> Option<String> {
+        let inner = self.mutable_state.lock();
+        inner.current_user.clone()
+    }
+
+    /// Between BEGIN and COMMIT/ROLLBACK, only to report to the clients,
+    /// the statements are still committed when they are executed.
+    pub fn set_in_transaction(self: &Arc<Self>, in_transaction: bool) {
+        let mut inner = self.mutable_state.lock();
+        inner.in_transaction = in_transaction;
+    }
+
+    pub fn is_in_transaction(self: &Arc<Self>) -> bool {
+        let inner = self.mutable_state.lock();
+        inner.in_transaction
+    }
+
+    /// The warnings of the last statement of the session.
+    pub fn get_last_warnings(self: &Arc<Self>) -> Vec<Warning> {
+        let inner = self.mutable_state.lock();
+        inner.last_warnings.clone()
+    }
+
+    pub fn set_last_warnings(self: &Arc<Self>, warnings: Vec<Warning>) {
+        let mut inner = self.mutable_state.lock();
+        inner.last_warnings = warnings;
+    }
+
+    /// The resource group of the session: the `resource_group` setting if it's set,
+    /// otherwise the group mapped to the current user, otherwise the default group.
+    pub fn get_resource_group(self: &Arc<Self>) -> Result<Arc<ResourceGroup>> {
+        let group_name = self.resource_group_name(&self.mutable_state.lock())?;
+        self.sessions.get_resource_groups().get_group(&group_name)
+    }
+
+    pub(in crate::sessions) fn resource_group_name(
+        self: &Arc<Self>,
+        status: &MutableStatus,
+    ) -> Result<String> {
+        let group_name = status.session_settings.get_resource_group()?;
+        if !group_name.is_empty() {
+            return Ok(group_name);
+        }
+
+        let resource_groups = &self.sessions.resource_groups;
+        Ok(match &status.current_user {
+            None => String::from(DEFAULT_RESOURCE_GROUP),
+            Some(user) => resource_groups
+                .get_user_group(user)
+                .unwrap_or_else(|| String::from(DEFAULT_RESOURCE_GROUP)),
+        })
+    }
+
     pub fn get_settings(self: &Arc<Self>) -> Arc<Settings> {
         self.mutable_state.lock().session_settings.clone()
     }
@@ -169,4 +276,13 @@ impl Session {
     pub fn get_catalog(self: &Arc<Self>) -> Arc<DatabaseCatalog> {
         self.sessions.get_catalog()
     }
+
+    pub fn get_plan_templates(self: &Arc<Self>) -> Arc<PlanTemplateCache> {
+        self.plan_templates.clone()
+    }
+
+    /// The bytes of the query results buffered for the client and not written to it yet.
+    pub fn get_result_buffer_bytes(self: &Arc<Self>) -> Arc<AtomicU64> {
+        self.result_buffer_bytes.clone()
+    }
 }
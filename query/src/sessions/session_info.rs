@@ -13,7 +13,11 @@
 // limitations under the License.
 
 use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+
+use common_progress::ProgressValues;
 
 use crate::sessions::session::MutableStatus;
 use crate::sessions::Session;
@@ -24,10 +28,20 @@ pub struct ProcessInfo {
     pub typ: String,
     pub state: String,
     pub database: String,
+    pub resource_group: String,
     #[allow(unused)]
     pub settings: Arc<Settings>,
     pub client_address: Option<SocketAddr>,
     pub session_extra_info: Option<String>,
+    pub query_hash: Option<u64>,
+    pub query_label: Option<String>,
+    /// The bytes of the query result buffered for the client, see `Session::get_result_buffer_bytes`.
+    pub result_buffer_bytes: u64,
+    /// The running query of the session, None if there is none, so are its elapsed time and
+    /// its progress.
+    pub query: Option<String>,
+    pub query_elapsed: Option<Duration>,
+    pub query_progress: Option<ProgressValues>,
 }
 
 impl Session {
@@ -37,14 +51,26 @@ impl Session {
     }
 
     fn to_process_info(self: &Arc<Self>, status: &MutableStatus) -> ProcessInfo {
+        let (query, query_elapsed, query_progress) = match Session::running_query(status) {
+            Some((query, elapsed, progress)) => (Some(query), Some(elapsed), Some(progress)),
+            None => (None, None, None),
+        };
+
         ProcessInfo {
             id: self.id.clone(),
             typ: self.typ.clone(),
             state: self.process_state(status),
             database: status.current_database.clone(),
+            resource_group: self.resource_group_name(status).unwrap_or_default(),
             settings: status.session_settings.clone(),
             client_address: status.client_host,
             session_extra_info: self.process_extra_info(status),
+            query_hash: Session::query_hash(status),
+            query_label: Session::query_label(status),
+            result_buffer_bytes: self.result_buffer_bytes.load(Ordering::Relaxed),
+            query,
+            query_elapsed,
+            query_progress,
         }
     }
 
@@ -68,6 +94,32 @@ impl Session {
         context_shared.map(|_| String::from("Partial cluster query stage"))
     }
 
+    fn query_hash(status: &MutableStatus) -> Option<u64> {
+        status
+            .context_shared
+            .as_ref()
+            .and_then(|context_shared| context_shared.get_query_semantic_hash())
+    }
+
+    fn query_label(status: &MutableStatus) -> Option<String> {
+        status
+            .context_shared
+            .as_ref()
+            .and_then(|context_shared| context_shared.get_query_label())
+    }
+
+    /// The text, the elapsed time and the progress so far of the running query.
+    fn running_query(status: &MutableStatus) -> Option<(String, Duration, ProgressValues)> {
+        let context_shared = status.context_shared.as_ref()?;
+        let query = context_shared.running_query.read().clone()?;
+        let start = context_shared
+            .running_query_start
+            .read()
+            .unwrap_or(context_shared.created);
+
+        Some((query, start.elapsed(), context_shared.progress.get_values()))
+    }
+
     fn query_extra_info(status: &MutableStatus) -> Option<String> {
         status.context_shared.as_ref().and_then(|context_shared| {
             context_shared
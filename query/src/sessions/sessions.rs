@@ -17,6 +17,7 @@ use std::collections::hash_map::Entry::Vacant;
 use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
+use std::sync::Weak;
 use std::time::Duration;
 
 use common_exception::ErrorCode;
@@ -24,21 +25,38 @@ use common_exception::Result;
 use common_infallible::RwLock;
 use common_runtime::tokio;
 use common_runtime::tokio::sync::mpsc::Receiver;
+use common_runtime::SharedClock;
+use common_store_api::KVApi;
+use common_store_api_sdk::RpcStats;
 use futures::future::Either;
 use metrics::counter;
+use metrics::decrement_gauge;
+use metrics::increment_gauge;
 
 use crate::catalogs::impls::DatabaseCatalog;
 use crate::catalogs::Catalog;
 use crate::clusters::ClusterRef;
+use crate::common::StoreApiProvider;
 use crate::configs::Config;
 use crate::datasources::database::example::ExampleDatabaseEngine;
 use crate::sessions::session::Session;
 use crate::sessions::session_ref::SessionRef;
+use crate::sessions::QueryLog;
+use crate::sessions::ResourceGroupManager;
+use crate::sessions::QUERY_LOG_MAX_ENTRIES;
 
 pub struct SessionManager {
     pub(in crate::sessions) conf: Config,
     pub(in crate::sessions) cluster: ClusterRef,
     pub(in crate::sessions) catalog: Arc<DatabaseCatalog>,
+    pub(in crate::sessions) resource_groups: Arc<ResourceGroupManager>,
+    pub(in crate::sessions) query_log: Arc<QueryLog>,
+    /// The store calls of all the clients of this node, the ones of a query are counted into it too.
+    pub(in crate::sessions) store_rpc_stats: Arc<RpcStats>,
+    /// The source of time of the idle expiry of the sessions.
+    pub(in crate::sessions) clock: SharedClock,
+    // Created on first use, so that the node starts without the kv service being reachable.
+    pub(in crate::sessions) kv_api: RwLock<Option<Arc<dyn KVApi>>>,
 
     pub(in crate::sessions) max_sessions: usize,
     pub(in crate::sessions) active_sessions: Arc<RwLock<HashMap<String, Arc<Session>>>>,
@@ -48,18 +66,46 @@ pub type SessionManagerRef = Arc<SessionManager>;
 
 impl SessionManager {
     pub fn from_conf(conf: Config, cluster: ClusterRef) -> Result<SessionManagerRef> {
-        let catalog = Arc::new(DatabaseCatalog::try_create_with_config(conf.clone())?);
+        Self::from_conf_with_clock(conf, cluster, SharedClock::default())
+    }
+
+    /// The same as `from_conf`, with the clock of the idle expiry and of the resource group
+    /// ticks, e.g. a `VirtualClock` of a test.
+    pub fn from_conf_with_clock(
+        conf: Config,
+        cluster: ClusterRef,
+        clock: SharedClock,
+    ) -> Result<SessionManagerRef> {
+        let store_rpc_stats = Arc::new(RpcStats::create());
+        let catalog = Arc::new(DatabaseCatalog::try_create_with_config(
+            conf.clone(),
+            store_rpc_stats.clone(),
+        )?);
 
         catalog.register_db_engine("example", Arc::new(ExampleDatabaseEngine::create()))?;
 
+        let resource_groups = Arc::new(ResourceGroupManager::from_conf(&conf, clock.clone())?);
+
         let max_active_sessions = conf.query.max_active_sessions as usize;
-        Ok(Arc::new(SessionManager {
+        let idle_timeout = conf.query.session_idle_timeout_secs;
+        let sessions = Arc::new(SessionManager {
             catalog,
+            resource_groups,
+            query_log: Arc::new(QueryLog::create(QUERY_LOG_MAX_ENTRIES)),
+            store_rpc_stats,
+            clock,
+            kv_api: RwLock::new(None),
             conf,
             cluster,
             max_sessions: max_active_sessions,
             active_sessions: Arc::new(RwLock::new(HashMap::with_capacity(max_active_sessions))),
-        }))
+        });
+
+        if idle_timeout > 0 {
+            let idle_timeout = Duration::from_secs(idle_timeout);
+            Self::spawn_idle_reaper(Arc::downgrade(&sessions), idle_timeout);
+        }
+        Ok(sessions)
     }
 
     pub fn get_conf(&self) -> &Config {
@@ -74,23 +120,61 @@ impl SessionManager {
         self.catalog.clone()
     }
 
+    pub fn get_resource_groups(self: &Arc<Self>) -> Arc<ResourceGroupManager> {
+        self.resource_groups.clone()
+    }
+
+    pub fn get_query_log(self: &Arc<Self>) -> Arc<QueryLog> {
+        self.query_log.clone()
+    }
+
+    pub fn get_store_rpc_stats(self: &Arc<Self>) -> Arc<RpcStats> {
+        self.store_rpc_stats.clone()
+    }
+
+    pub fn get_clock(self: &Arc<Self>) -> SharedClock {
+        self.clock.clone()
+    }
+
+    /// The kv client shared by all the sessions of this node.
+    /// Without a meta service address it is a local kv store that lives as long as the node.
+    pub async fn get_kv_api(self: &Arc<Self>) -> Result<Arc<dyn KVApi>> {
+        let created = self.kv_api.read().clone();
+        if let Some(kv_api) = created {
+            return Ok(kv_api);
+        }
+
+        let kv_api = StoreApiProvider::new(&self.conf)
+            .with_rpc_stats(self.store_rpc_stats.clone())
+            .try_get_kv_client()
+            .await?;
+        Ok(self.kv_api.write().get_or_insert(kv_api).clone())
+    }
+
     pub fn create_session(self: &Arc<Self>, typ: impl Into<String>) -> Result<SessionRef> {
         counter!(super::metrics::METRIC_SESSION_CONNECT_NUMBERS, 1);
 
+        let typ = typ.into();
+        let handler = handler_label(&typ);
         let mut sessions = self.active_sessions.write();
         match sessions.len() == self.max_sessions {
-            true => Err(ErrorCode::TooManyUserConnections(
-                "The current accept connection has exceeded mysql_handler_thread_num config",
-            )),
+            true => {
+                counter!(super::metrics::METRIC_SESSION_REJECTED, 1, "handler" => handler);
+                Err(ErrorCode::TooManyUserConnections(
+                    "The current accept connection has exceeded mysql_handler_thread_num config",
+                ))
+            }
             false => {
                 let session = Session::try_create(
                     self.conf.clone(),
                     uuid::Uuid::new_v4().to_string(),
-                    typ.into(),
+                    typ,
                     self.clone(),
                 )?;
 
                 sessions.insert(session.get_id(), session.clone());
+                counter!(super::metrics::METRIC_SESSION_CREATED, 1, "handler" => handler);
+                increment_gauge!(super::metrics::METRIC_SESSION_ACTIVE, 1.0, "handler" => handler);
                 Ok(SessionRef::create(session))
             }
         }
@@ -103,7 +187,10 @@ impl SessionManager {
 
         let session = match sessions.entry(id) {
             Occupied(entry) => entry.get().clone(),
-            Vacant(_) if aborted => return Err(ErrorCode::AbortedSession("Aborting server.")),
+            Vacant(_) if aborted => {
+                counter!(super::metrics::METRIC_SESSION_REJECTED, 1, "handler" => "flight");
+                return Err(ErrorCode::AbortedSession("Aborting server."));
+            }
             Vacant(entry) => {
                 let session = Session::try_create(
                     self.conf.clone(),
@@ -112,6 +199,8 @@ impl SessionManager {
                     self.clone(),
                 )?;
 
+                counter!(super::metrics::METRIC_SESSION_CREATED, 1, "handler" => "flight");
+                increment_gauge!(super::metrics::METRIC_SESSION_ACTIVE, 1.0, "handler" => "flight");
                 entry.insert(session).clone()
             }
         };
@@ -129,9 +218,55 @@ impl SessionManager {
 
     #[allow(clippy::ptr_arg)]
     pub fn destroy_session(self: &Arc<Self>, session_id: &String) {
-        counter!(super::metrics::METRIC_SESSION_CLOSE_NUMBERS, 1);
+        // An expired session is removed before its refs are dropped.
+        if let Some(session) = self.active_sessions.write().remove(session_id) {
+            counter!(super::metrics::METRIC_SESSION_CLOSE_NUMBERS, 1);
+            let handler = handler_label(&session.get_type());
+            decrement_gauge!(super::metrics::METRIC_SESSION_ACTIVE, 1.0, "handler" => handler);
+        }
+    }
+
+    /// Expires the sessions idle for `idle_timeout`, their slots are released at once. The client
+    /// of an expired session gets an error on its next request.
+    pub fn expire_idle_sessions(self: &Arc<Self>, idle_timeout: Duration) -> usize {
+        let expired = self
+            .active_sessions
+            .read()
+            .values()
+            .filter(|session| session.expire_if_idle(idle_timeout))
+            .map(|session| (session.get_id(), session.get_type()))
+            .collect::<Vec<_>>();
+
+        for (id, typ) in &expired {
+            log::info!(
+                "Session {} is idle for longer than {:?}, expired",
+                id,
+                idle_timeout
+            );
+            counter!(super::metrics::METRIC_SESSION_EXPIRED, 1, "handler" => handler_label(typ));
+            self.destroy_session(id);
+        }
+        expired.len()
+    }
+
+    /// Expires the idle sessions every second of the clock, as long as the manager lives.
+    fn spawn_idle_reaper(sessions: Weak<SessionManager>, idle_timeout: Duration) {
+        tokio::spawn(async move {
+            loop {
+                let tick = match sessions.upgrade() {
+                    None => break,
+                    Some(sessions) => sessions.clock.sleep(Duration::from_secs(1)),
+                };
+                tick.await;
 
-        self.active_sessions.write().remove(session_id);
+                match sessions.upgrade() {
+                    None => break,
+                    Some(sessions) => {
+                        sessions.expire_idle_sessions(idle_timeout);
+                    }
+                }
+            }
+        });
     }
 
     pub fn shutdown(self: &Arc<Self>, signal: Option<Receiver<()>>) -> impl Future<Output = ()> {
@@ -181,3 +316,14 @@ impl SessionManager {
         }
     }
 }
+
+/// The `handler` label of the session metrics.
+fn handler_label(typ: &str) -> &'static str {
+    match typ {
+        "MySQL" => "mysql",
+        "ClickHouseSession" => "clickhouse",
+        "HTTP" => "http",
+        "RPCSession" => "flight",
+        _ => "other",
+    }
+}
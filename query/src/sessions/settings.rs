@@ -16,9 +16,14 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_datavalues::DataValue;
+use common_datavalues::FloatFormat;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_infallible::RwLock;
+use common_planners::fold_option_name;
+
+use crate::api::FlightCompression;
+use crate::sql::SqlDialect;
 
 #[derive(Debug)]
 pub struct Settings {
@@ -30,8 +35,30 @@ impl Settings {
         ("max_block_size", u64, 10000, "Maximum block size for reading"),
         ("max_threads", u64, 16, "The maximum number of threads to execute the request. By default, it is determined automatically."),
         ("flight_client_timeout", u64, 60, "Max duration the flight client request is allowed to take in seconds. By default, it is 60 seconds"),
+        ("flight_compression", String, String::new(), "The compression of the blocks fetched from the other query nodes, 'lz4' or 'none'. A node that doesn't support it sends the blocks uncompressed."),
+        ("flight_dictionary_encoding", u64, 0, "Dictionary-encode the String columns of the blocks fetched from the other query nodes, 1 to enable. Works best for the low-cardinality columns."),
         ("min_distributed_rows", u64, 100000000, "Minimum distributed read rows. In cluster mode, when read rows exceeds this value, the local table converted to distributed query."),
-        ("min_distributed_bytes", u64, 500 * 1024 * 1024, "Minimum distributed read bytes. In cluster mode, when read bytes exceeds this value, the local table converted to distributed query.")
+        ("min_distributed_bytes", u64, 500 * 1024 * 1024, "Minimum distributed read bytes. In cluster mode, when read bytes exceeds this value, the local table converted to distributed query."),
+        ("exchange_reorder_buffer_blocks", u64, 64, "Maximum blocks an order-preserving exchange buffers to release the blocks of its sources in order, a source that sends more blocks ahead of a missing one fails the query."),
+        ("exchange_reorder_gap_timeout", u64, 10 * 1000, "The maximum time in milliseconds an order-preserving exchange waits for a missing block while the later blocks of its source arrived, beyond it the block is considered lost and the query fails."),
+        ("sort_buffer_bytes", u64, 256 * 1024 * 1024, "Maximum bytes an ORDER BY without LIMIT buffers in memory, beyond it the sorted runs are spilled to temporary files. 0 means no limit."),
+        ("autocommit", u64, 1, "Whether each statement commits when it is executed, for the MySQL clients. The statements are always committed when they are executed."),
+        ("strict_transaction", u64, 0, "Return an error on ROLLBACK instead of a warning, since the statements are committed when they are executed and nothing can be rolled back. 1 to enable."),
+        ("ddl_batching", u64, 0, "Create the tables of consecutive CREATE TABLE statements of a multi-statement query in one meta write, the result is a row for each table. 1 to enable."),
+        ("resource_group", String, String::new(), "The resource group of the queries in this session. By default, it is determined by the user, or the default group."),
+        ("output_float_precision", u64, 0, "The number of digits after the decimal point of the floats in the results. 0 renders the shortest representation that round-trips."),
+        ("output_float_special_values", String, String::new(), "The tokens of NaN, inf and -inf in the results, separated by commas, e.g. 'nan,inf,-inf'. By default, they are determined by the output format."),
+        ("query_label", String, String::new(), "The label of the queries in this session, e.g. 'team=billing'. It is shown in system.processes and sent along with the requests to the store. A /*+ label(...) */ hint overrides it for a query."),
+        ("max_warnings", u64, 64, "The number of distinct warnings kept for a statement, the others are counted as suppressed."),
+        ("plan_template_cache_size", u64, 64, "The number of plan templates cached by the session, a query of the same shape as a cached one only binds its literals into the template instead of being planned again. 0 to disable."),
+        ("aggregate_top_n_factor", u64, 3, "In cluster mode, each node only sends the top (LIMIT * factor) groups to the final aggregation of a GROUP BY ... ORDER BY max(..) DESC or min(..) ASC LIMIT query. 0 to disable."),
+        ("explain_read_plan", u64, 1, "Whether EXPLAIN asks the remote tables for their parts and statistics. 0 to show them as unknown without contacting the store."),
+        ("max_concurrent_part_reads", u64, 8, "The maximum number of parts a query reads from the store at the same time, shared by all the scans of the query."),
+        ("max_execution_time", u64, 0, "The maximum time in milliseconds a query runs, the calls to the store made for it are bounded by the time left. 0 for no limit."),
+        ("max_result_buffer_bytes", u64, 64 * 1024 * 1024, "Maximum bytes of the result of a query buffered for the MySQL client, beyond it the query is paused until the client reads. 0 means no limit."),
+        ("result_stall_timeout", u64, 600 * 1000, "The maximum time in milliseconds the MySQL client doesn't read the result of a query, beyond it the query is aborted and the connection closed. 0 for no limit."),
+        ("sql_dialect", String, String::new(), "The quoting rules of the queries in this session, 'mysql' quotes the identifiers with backticks and the strings with double quotes, 'ansi' quotes the identifiers with double quotes. By default, both the backticks and the double quotes quote identifiers."),
+        ("strict_arithmetic", u64, 0, "Return an error on the integer division or modulo by zero instead of NULL, the integer overflows are always an error. 1 to enable.")
     }
 
     pub fn try_create() -> Result<Arc<Settings>> {
@@ -45,6 +72,37 @@ impl Settings {
         Ok(settings)
     }
 
+    /// Returns the quoting rules of the queries, see the sql_dialect setting.
+    pub fn get_parser_dialect(&self) -> Result<SqlDialect> {
+        SqlDialect::from_setting(&self.get_sql_dialect()?)
+    }
+
+    /// Returns the compression of the blocks fetched from the other nodes, see the flight_compression setting.
+    pub fn get_flight_compression_codec(&self) -> Result<Option<FlightCompression>> {
+        FlightCompression::from_setting(&self.get_flight_compression()?)
+    }
+
+    /// Returns the float format of an output format, adjusted by the output_float_* settings.
+    pub fn get_output_float_format(&self, format: FloatFormat) -> Result<FloatFormat> {
+        let format = match self.get_output_float_precision()? {
+            0 => format,
+            precision => format.with_precision(Some(precision as usize)),
+        };
+
+        let special_values = self.get_output_float_special_values()?;
+        if special_values.is_empty() {
+            return Ok(format);
+        }
+
+        match special_values.split(',').collect::<Vec<_>>().as_slice() {
+            [nan, inf, neg_inf] => Ok(format.with_special_values(*nan, *inf, *neg_inf)),
+            _ => Err(ErrorCode::BadArguments(format!(
+                "output_float_special_values must be 3 tokens separated by commas, but got: {:?}",
+                special_values
+            ))),
+        }
+    }
+
     pub fn iter(&self) -> SettingsIterator {
         SettingsIterator {
             settings: self.inner.get_settings(),
@@ -101,7 +159,7 @@ impl SettingsBase {
     pub fn try_get_u64(&self, key: &str) -> Result<u64> {
         let settings = self.settings.read();
         let setting_val = settings
-            .get(key)
+            .get(fold_option_name(key).as_str())
             .ok_or_else(|| ErrorCode::UnknownVariable(format!("Unknown variable: {:?}", key)))?;
 
         if let DataValue::Struct(values) = setting_val {
@@ -150,7 +208,7 @@ impl SettingsBase {
     pub fn try_get_i64(&self, key: &str) -> Result<i64> {
         let settings = self.settings.read();
         let setting_val = settings
-            .get(key)
+            .get(fold_option_name(key).as_str())
             .ok_or_else(|| ErrorCode::UnknownVariable(format!("Unknown variable: {:?}", key)))?;
 
         if let DataValue::Struct(values) = setting_val {
@@ -199,7 +257,7 @@ impl SettingsBase {
     pub fn try_get_f64(&self, key: &str) -> Result<f64> {
         let settings = self.settings.read();
         let setting_val = settings
-            .get(key)
+            .get(fold_option_name(key).as_str())
             .ok_or_else(|| ErrorCode::UnknownVariable(format!("Unknown variable: {:?}", key)))?;
 
         if let DataValue::Struct(values) = setting_val {
@@ -215,12 +273,12 @@ impl SettingsBase {
     }
 
     #[allow(unused)]
-    pub fn try_set_string(&self, key: &'static str, val: &str, desc: &str) -> Result<()> {
+    pub fn try_set_string(&self, key: &'static str, val: String, desc: &str) -> Result<()> {
         let mut settings = self.settings.write();
-        let default_value = val;
+        let default_value = val.clone();
         let setting_val = DataValue::Struct(vec![
-            DataValue::String(Some(val.as_bytes().to_vec())),
-            DataValue::String(Some(default_value.as_bytes().to_vec())),
+            DataValue::String(Some(val.into_bytes())),
+            DataValue::String(Some(default_value.into_bytes())),
             DataValue::String(Some(desc.as_bytes().to_vec())),
         ]);
         settings.insert(key, setting_val);
@@ -228,7 +286,7 @@ impl SettingsBase {
     }
 
     #[allow(unused)]
-    pub fn try_update_string(&self, key: &'static str, val: &str) -> Result<()> {
+    pub fn try_update_string(&self, key: &'static str, val: String) -> Result<()> {
         let mut settings = self.settings.write();
         let setting_val = settings
             .get(key)
@@ -236,7 +294,7 @@ impl SettingsBase {
 
         if let DataValue::Struct(values) = setting_val {
             let v = DataValue::Struct(vec![
-                DataValue::String(Some(val.as_bytes().to_vec())),
+                DataValue::String(Some(val.into_bytes())),
                 values[1].clone(),
                 values[2].clone(),
             ]);
@@ -246,15 +304,15 @@ impl SettingsBase {
     }
 
     #[allow(unused)]
-    pub fn try_get_string(&self, key: &str) -> Result<Vec<u8>> {
+    pub fn try_get_string(&self, key: &str) -> Result<String> {
         let settings = self.settings.read();
         let setting_val = settings
-            .get(key)
+            .get(fold_option_name(key).as_str())
             .ok_or_else(|| ErrorCode::UnknownVariable(format!("Unknown variable: {:?}", key)))?;
 
         if let DataValue::Struct(values) = setting_val {
             if let DataValue::String(Some(result)) = values[0].clone() {
-                return Ok(result);
+                return Ok(String::from_utf8(result)?);
             }
         }
 
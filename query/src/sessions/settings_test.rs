@@ -0,0 +1,55 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datavalues::FloatFormat;
+use common_exception::Result;
+
+use crate::sessions::Settings;
+
+#[test]
+fn test_settings_output_float_format() -> Result<()> {
+    let settings = Settings::try_create()?;
+
+    // Defaults of the output formats.
+    let text = settings.get_output_float_format(FloatFormat::text())?;
+    assert_eq!(FloatFormat::text(), text);
+    assert_eq!("0.3333333333333333", text.format_f64(1.0 / 3.0));
+
+    let json = settings.get_output_float_format(FloatFormat::json())?;
+    assert_eq!("null", json.format_f64(f64::NAN));
+
+    // Fixed precision.
+    settings.set_output_float_precision(2)?;
+    let csv = settings.get_output_float_format(FloatFormat::csv())?;
+    assert_eq!("0.33", csv.format_f64(1.0 / 3.0));
+    assert_eq!("0.00", csv.format_f64(-0.0));
+    assert_eq!("Infinity", csv.format_f64(f64::INFINITY));
+
+    // Override the special values of every format.
+    settings.set_output_float_special_values("nan,+inf,-inf".to_string())?;
+    for format in [FloatFormat::text(), FloatFormat::csv(), FloatFormat::json()] {
+        let format = settings.get_output_float_format(format)?;
+        assert_eq!("nan", format.format_f64(f64::NAN));
+        assert_eq!("+inf", format.format_f64(f64::INFINITY));
+        assert_eq!("-inf", format.format_f64(f64::NEG_INFINITY));
+        assert_eq!("1.50", format.format_f64(1.5));
+    }
+
+    settings.set_output_float_special_values("nan,inf".to_string())?;
+    assert!(settings
+        .get_output_float_format(FloatFormat::text())
+        .is_err());
+
+    Ok(())
+}
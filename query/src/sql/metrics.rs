@@ -13,3 +13,5 @@
 // limitations under the License.
 
 pub static METRIC_PARSER_USEDTIME: &str = "parser.parse_usedtime";
+pub static METRIC_PLAN_TEMPLATE_HITS: &str = "plan_template.hits";
+pub static METRIC_PLAN_TEMPLATE_MISSES: &str = "plan_template.misses";
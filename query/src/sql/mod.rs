@@ -15,16 +15,27 @@
 #[cfg(test)]
 mod plan_parser_test;
 #[cfg(test)]
+mod plan_template_test;
+#[cfg(test)]
+mod sql_dialect_test;
+#[cfg(test)]
 mod sql_parser_test;
 
 mod metrics;
 mod parser;
 mod plan_parser;
+mod plan_template;
 mod sql_common;
+mod sql_dialect;
 mod sql_parser;
 mod sql_statement;
 
 pub use plan_parser::PlanParser;
+pub use plan_template::bind_placeholders;
+pub use plan_template::PlanTemplateCache;
+pub use plan_template::PlanTemplateGuard;
+pub use plan_template::SqlShape;
 pub use sql_common::SQLCommon;
+pub use sql_dialect::SqlDialect;
 pub use sql_parser::DfParser;
 pub use sql_statement::*;
@@ -27,6 +27,7 @@ use common_planners::expr_as_column_expr;
 use common_planners::extract_aliases;
 use common_planners::find_aggregate_exprs;
 use common_planners::find_columns_not_satisfy_exprs;
+use common_planners::insert_table_option;
 use common_planners::rebase_expr;
 use common_planners::rebase_expr_from_input;
 use common_planners::resolve_aliases_to_exprs;
@@ -41,29 +42,40 @@ use common_planners::ExplainPlan;
 use common_planners::Expression;
 use common_planners::InsertIntoPlan;
 use common_planners::KillPlan;
+use common_planners::ModifyColumnPlan;
+use common_planners::OptimizeTablePlan;
 use common_planners::PlanBuilder;
 use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
 use common_planners::SelectPlan;
 use common_planners::SettingPlan;
 use common_planners::ShowCreateTablePlan;
+use common_planners::TableIdentifier;
+use common_planners::TableOptions;
 use common_planners::TableScanInfo;
+use common_planners::TransactionPlan;
 use common_planners::TruncateTablePlan;
+use common_planners::UndropTablePlan;
 use common_planners::UseDatabasePlan;
 use common_planners::VarValue;
 use common_streams::Source;
 use common_streams::ValueSource;
 use common_tracing::tracing;
 use nom::FindSubstring;
+use sqlparser::ast::ColumnOption;
 use sqlparser::ast::FunctionArg;
 use sqlparser::ast::Ident;
 use sqlparser::ast::ObjectName;
 use sqlparser::ast::OrderByExpr;
 use sqlparser::ast::Query;
 use sqlparser::ast::Statement;
+use sqlparser::ast::TableAlias;
 use sqlparser::ast::TableFactor;
 use sqlparser::ast::UnaryOperator;
 
 use crate::catalogs::Catalog;
+use crate::catalogs::Table;
 use crate::functions::ContextFunction;
 use crate::sessions::DatabendQueryContextRef;
 use crate::sql::sql_statement::DfCreateTable;
@@ -75,26 +87,38 @@ use crate::sql::DfDropTable;
 use crate::sql::DfExplain;
 use crate::sql::DfHint;
 use crate::sql::DfKillStatement;
+use crate::sql::DfModifyColumn;
+use crate::sql::DfOptimizeTable;
 use crate::sql::DfParser;
 use crate::sql::DfShowCreateTable;
 use crate::sql::DfShowDatabases;
 use crate::sql::DfShowTables;
 use crate::sql::DfStatement;
+use crate::sql::DfTransaction;
 use crate::sql::DfTruncateTable;
+use crate::sql::DfUndropTable;
+use crate::sql::PlanTemplateGuard;
 use crate::sql::SQLCommon;
+use crate::sql::SqlShape;
 
 pub struct PlanParser {
     ctx: DatabendQueryContextRef,
+    // Planning the statement of an EXPLAIN.
+    explain: bool,
 }
 
 impl PlanParser {
     pub fn create(ctx: DatabendQueryContextRef) -> Self {
-        Self { ctx }
+        Self {
+            ctx,
+            explain: false,
+        }
     }
 
     pub fn build_from_sql(&self, query: &str) -> Result<PlanNode> {
         tracing::debug!(query);
-        DfParser::parse_sql(query).and_then(|(stmts, _)| {
+        let dialect = self.ctx.get_settings().get_parser_dialect()?;
+        DfParser::parse_sql_in_dialect(query, dialect).and_then(|(stmts, _)| {
             stmts
                 .first()
                 .map(|statement| self.statement_to_plan(statement))
@@ -106,7 +130,11 @@ impl PlanParser {
 
     pub fn build_with_hint_from_sql(&self, query: &str) -> (Result<PlanNode>, Vec<DfHint>) {
         tracing::debug!(query);
-        let stmt_hints = DfParser::parse_sql(query);
+        let stmt_hints = self
+            .ctx
+            .get_settings()
+            .get_parser_dialect()
+            .and_then(|dialect| DfParser::parse_sql_in_dialect(query, dialect));
         match stmt_hints {
             Ok((stmts, hints)) => match stmts.first() {
                 Some(stmt) => (self.statement_to_plan(stmt), hints),
@@ -119,6 +147,56 @@ impl PlanParser {
         }
     }
 
+    /// The statements and hints of a query of more than one statement, see `ScriptInterpreter`.
+    /// None for a single statement, or if the query cannot be parsed, it is planned as a single
+    /// query then.
+    pub fn parse_script(&self, query: &str) -> Option<(Vec<DfStatement>, Vec<DfHint>)> {
+        // Only a `;` before the end of the query may separate two statements.
+        if !query.trim_end().trim_end_matches(';').contains(';') {
+            return None;
+        }
+
+        let dialect = self.ctx.get_settings().get_parser_dialect().ok()?;
+        match DfParser::parse_sql_in_dialect(query, dialect) {
+            Ok((stmts, hints)) if stmts.len() > 1 => Some((stmts, hints)),
+            _ => None,
+        }
+    }
+
+    /// Plan the query from the template of its shape cached by the session, see `PlanTemplateCache`.
+    /// Otherwise the query is planned fully, and kept as a template.
+    pub fn build_with_template_from_sql(&self, query: &str) -> (Result<PlanNode>, Vec<DfHint>) {
+        let templates = self.ctx.get_plan_templates();
+        let dialect = self.ctx.get_settings().get_parser_dialect();
+        let shape = match dialect.and_then(|dialect| SqlShape::parse(query, dialect)) {
+            Ok(shape) => shape,
+            Err(_) => {
+                templates.record_miss();
+                return self.build_with_hint_from_sql(query);
+            }
+        };
+
+        match templates.try_bind(&self.ctx, &shape) {
+            Ok(Some((plan, hints))) => {
+                templates.record_hit();
+                return (Ok(plan), hints);
+            }
+            Ok(None) => {}
+            Err(cause) => log::warn!("Cannot plan {} from its template: {}", query, cause),
+        }
+
+        templates.record_miss();
+        // Taken before the planning, so a DDL in between drops the template rather than it being used stale.
+        let guard = PlanTemplateGuard::create(&self.ctx);
+        let (plan, hints) = self.build_with_hint_from_sql(query);
+        if let (Ok(plan), Ok(guard)) = (&plan, guard) {
+            if let Err(cause) = templates.try_insert(&self.ctx, shape, guard, plan, &hints) {
+                log::warn!("Cannot keep the template of {}: {}", query, cause);
+            }
+        }
+        (plan, hints)
+    }
+
     pub fn statement_to_plan(&self, statement: &DfStatement) -> Result<PlanNode> {
         match statement {
             DfStatement::Statement(v) => self.sql_statement_to_plan(v),
@@ -129,7 +207,10 @@ impl PlanParser {
             DfStatement::CreateTable(v) => self.sql_create_table_to_plan(v),
             DfStatement::DescribeTable(v) => self.sql_describe_table_to_plan(v),
             DfStatement::DropTable(v) => self.sql_drop_table_to_plan(v),
+            DfStatement::UndropTable(v) => self.sql_undrop_table_to_plan(v),
             DfStatement::TruncateTable(v) => self.sql_truncate_table_to_plan(v),
+            DfStatement::ModifyColumn(v) => self.sql_modify_column_to_plan(v),
+            DfStatement::OptimizeTable(v) => self.sql_optimize_table_to_plan(v),
             DfStatement::UseDatabase(v) => self.sql_use_database_to_plan(v),
             DfStatement::ShowCreateTable(v) => self.sql_show_create_table_to_plan(v),
             DfStatement::ShowTables(df) => {
@@ -158,6 +239,12 @@ impl PlanParser {
                             name.0[0].value.clone()
                         )
                     }
+                    DfShowTables::History => {
+                        format!(
+                            "SELECT name, dropped_on, expire_in FROM system.tables_history where database = '{}' ORDER BY database, name",
+                            self.ctx.get_current_database()
+                        )
+                    }
                 };
                 self.build_from_sql(show_sql.as_str())
             }
@@ -165,8 +252,12 @@ impl PlanParser {
             DfStatement::ShowProcessList(_) => {
                 self.build_from_sql("SELECT * FROM system.processes")
             }
+            DfStatement::ShowWarnings(_) => {
+                self.build_from_sql("SELECT level, code, message, count FROM system.warnings")
+            }
             DfStatement::KillQuery(v) => self.sql_kill_query_to_plan(v),
             DfStatement::KillConn(v) => self.sql_kill_connection_to_plan(v),
+            DfStatement::Transaction(v) => self.sql_transaction_to_plan(v),
         }
     }
 
@@ -199,7 +290,11 @@ impl PlanParser {
     /// Generate a logic plan from an EXPLAIN
     #[tracing::instrument(level = "info", skip(self, explain))]
     pub fn sql_explain_to_plan(&self, explain: &DfExplain) -> Result<PlanNode> {
-        let plan = self.sql_statement_to_plan(&explain.statement)?;
+        let parser = PlanParser {
+            ctx: self.ctx.clone(),
+            explain: true,
+        };
+        let plan = parser.sql_statement_to_plan(&explain.statement)?;
         Ok(PlanNode::Explain(ExplainPlan {
             typ: explain.typ,
             input: Arc::new(plan),
@@ -282,17 +377,16 @@ impl PlanParser {
         }))
     }
 
+    #[tracing::instrument(level = "info", skip(self, transaction), fields(ctx.id = self.ctx.get_id().as_str()))]
+    pub fn sql_transaction_to_plan(&self, transaction: &DfTransaction) -> Result<PlanNode> {
+        Ok(PlanNode::Transaction(TransactionPlan {
+            kind: transaction.kind.clone(),
+        }))
+    }
+
     #[tracing::instrument(level = "info", skip(self, create), fields(ctx.id = self.ctx.get_id().as_str()))]
     pub fn sql_create_table_to_plan(&self, create: &DfCreateTable) -> Result<PlanNode> {
-        let mut db = self.ctx.get_current_database();
-        if create.name.0.is_empty() {
-            return Result::Err(ErrorCode::SyntaxException("Create table name is empty"));
-        }
-        let mut table = create.name.0[0].value.clone();
-        if create.name.0.len() > 1 {
-            db = table;
-            table = create.name.0[1].value.clone();
-        }
+        let (db, table) = self.resolve_table_name(&create.name)?.into_parts()?;
 
         let fields = create
             .columns
@@ -303,15 +397,14 @@ impl PlanParser {
             })
             .collect::<Result<Vec<DataField>>>()?;
 
-        let mut options = HashMap::new();
+        // The option names are kept as they are written and matched case-insensitively.
+        let mut options = TableOptions::new();
         for p in create.options.iter() {
-            options.insert(
-                p.name.value.to_lowercase(),
-                p.value
-                    .to_string()
-                    .trim_matches(|s| s == '\'' || s == '"')
-                    .to_string(),
-            );
+            insert_table_option(
+                &mut options,
+                &p.name.value,
+                p.value.to_string().trim_matches(|s| s == '\'' || s == '"'),
+            )?;
         }
 
         let schema = DataSchemaRefExt::create(fields);
@@ -330,17 +423,7 @@ impl PlanParser {
         &self,
         show_create: &DfShowCreateTable,
     ) -> Result<PlanNode> {
-        let mut db = self.ctx.get_current_database();
-        if show_create.name.0.is_empty() {
-            return Result::Err(ErrorCode::SyntaxException(
-                "Show create table name is empty",
-            ));
-        }
-        let mut table = show_create.name.0[0].value.clone();
-        if show_create.name.0.len() > 1 {
-            db = table;
-            table = show_create.name.0[1].value.clone();
-        }
+        let (db, table) = self.resolve_table_name(&show_create.name)?.into_parts()?;
 
         let fields = vec![
             DataField::new("Table", DataType::String, false),
@@ -358,15 +441,7 @@ impl PlanParser {
     /// DfDescribeTable to plan.
     #[tracing::instrument(level = "info", skip(self, describe), fields(ctx.id = self.ctx.get_id().as_str()))]
     pub fn sql_describe_table_to_plan(&self, describe: &DfDescribeTable) -> Result<PlanNode> {
-        let mut db = self.ctx.get_current_database();
-        if describe.name.0.is_empty() {
-            return Result::Err(ErrorCode::SyntaxException("Describe table name is empty"));
-        }
-        let mut table = describe.name.0[0].value.clone();
-        if describe.name.0.len() > 1 {
-            db = table;
-            table = describe.name.0[1].value.clone();
-        }
+        let (db, table) = self.resolve_table_name(&describe.name)?.into_parts()?;
 
         let schema = DataSchemaRefExt::create(vec![
             DataField::new("Field", DataType::String, false),
@@ -384,15 +459,7 @@ impl PlanParser {
     /// DfDropTable to plan.
     #[tracing::instrument(level = "info", skip(self, drop), fields(ctx.id = self.ctx.get_id().as_str()))]
     pub fn sql_drop_table_to_plan(&self, drop: &DfDropTable) -> Result<PlanNode> {
-        let mut db = self.ctx.get_current_database();
-        if drop.name.0.is_empty() {
-            return Result::Err(ErrorCode::SyntaxException("Drop table name is empty"));
-        }
-        let mut table = drop.name.0[0].value.clone();
-        if drop.name.0.len() > 1 {
-            db = table;
-            table = drop.name.0[1].value.clone();
-        }
+        let (db, table) = self.resolve_table_name(&drop.name)?.into_parts()?;
         Ok(PlanNode::DropTable(DropTablePlan {
             if_exists: drop.if_exists,
             db,
@@ -400,24 +467,50 @@ impl PlanParser {
         }))
     }
 
+    // DfUndropTable to plan.
+    #[tracing::instrument(level = "info", skip(self, undrop), fields(ctx.id = self.ctx.get_id().as_str()))]
+    pub fn sql_undrop_table_to_plan(&self, undrop: &DfUndropTable) -> Result<PlanNode> {
+        let (db, table) = self.resolve_table_name(&undrop.name)?.into_parts()?;
+
+        Ok(PlanNode::UndropTable(UndropTablePlan { db, table }))
+    }
+
+    // DfModifyColumn to plan.
+    #[tracing::instrument(level = "info", skip(self, modify), fields(ctx.id = self.ctx.get_id().as_str()))]
+    pub fn sql_modify_column_to_plan(&self, modify: &DfModifyColumn) -> Result<PlanNode> {
+        let (db, table) = self.resolve_table_name(&modify.name)?.into_parts()?;
+
+        // A column is NOT NULL unless NULL is given, the same as in CREATE TABLE.
+        let column = &modify.column;
+        let data_type = SQLCommon::make_data_type(&column.data_type)?;
+        let nullable = column
+            .options
+            .iter()
+            .any(|o| matches!(o.option, ColumnOption::Null));
+
+        Ok(PlanNode::ModifyColumn(ModifyColumnPlan {
+            db,
+            table,
+            column: DataField::new(&column.name.value, data_type, nullable),
+        }))
+    }
+
     // DfTruncateTable to plan.
     #[tracing::instrument(level = "info", skip(self, truncate), fields(ctx.id = self.ctx.get_id().as_str()))]
     pub fn sql_truncate_table_to_plan(&self, truncate: &DfTruncateTable) -> Result<PlanNode> {
-        let mut db = self.ctx.get_current_database();
-        if truncate.name.0.is_empty() {
-            return Result::Err(ErrorCode::SyntaxException(
-                "TruncateTable table name is empty",
-            ));
-        }
-        let mut table = truncate.name.0[0].value.clone();
-        if truncate.name.0.len() > 1 {
-            db = table;
-            table = truncate.name.0[1].value.clone();
-        }
+        let (db, table) = self.resolve_table_name(&truncate.name)?.into_parts()?;
 
         Ok(PlanNode::TruncateTable(TruncateTablePlan { db, table }))
     }
 
+    // DfOptimizeTable to plan.
+    #[tracing::instrument(level = "info", skip(self, optimize), fields(ctx.id = self.ctx.get_id().as_str()))]
+    pub fn sql_optimize_table_to_plan(&self, optimize: &DfOptimizeTable) -> Result<PlanNode> {
+        let (db, table) = self.resolve_table_name(&optimize.name)?.into_parts()?;
+
+        Ok(PlanNode::OptimizeTable(OptimizeTablePlan { db, table }))
+    }
+
     #[tracing::instrument(level = "info", skip(self, table_name, columns, source), fields(ctx.id = self.ctx.get_id().as_str()))]
     fn insert_to_plan(
         &self,
@@ -426,13 +519,7 @@ impl PlanParser {
         source: &Option<Box<Query>>,
         format_sql: &str,
     ) -> Result<PlanNode> {
-        let mut db_name = self.ctx.get_current_database();
-        let mut tbl_name = table_name.0[0].value.clone();
-
-        if table_name.0.len() > 1 {
-            db_name = tbl_name;
-            tbl_name = table_name.0[1].value.clone();
-        }
+        let (db_name, tbl_name) = self.resolve_table_name(table_name)?.into_parts()?;
         let table = self.ctx.get_catalog().get_table(&db_name, &tbl_name)?;
 
         let mut schema = table.raw().schema()?;
@@ -510,7 +597,7 @@ impl PlanParser {
         // Filter expression
         // In example: Filter=(number > 1)
         let plan = self
-            .plan_tables_with_joins(&select.from)
+            .plan_tables_with_joins(&select.from, Some(select))
             .and_then(|input| self.filter(&input, &select.selection, Some(select)))?;
 
         // Projection expression
@@ -688,10 +775,15 @@ impl PlanParser {
         }
     }
 
-    fn plan_tables_with_joins(&self, from: &[sqlparser::ast::TableWithJoins]) -> Result<PlanNode> {
+    /// The WHERE predicate of `push_down` is pushed down to the scan of the table, see `push_down_filter`.
+    fn plan_tables_with_joins(
+        &self,
+        from: &[sqlparser::ast::TableWithJoins],
+        push_down: Option<&sqlparser::ast::Select>,
+    ) -> Result<PlanNode> {
         match from.len() {
             0 => self.plan_with_dummy_source(),
-            1 => self.plan_table_with_joins(&from[0]),
+            1 => self.plan_table_with_joins(&from[0], push_down),
             // Such as SELECT * FROM t1, t2;
             // It's not `JOIN` clause.
             _ => Result::Err(ErrorCode::SyntaxException("Cannot SELECT multiple tables")),
@@ -726,7 +818,7 @@ impl PlanParser {
                             .read_plan(
                                 self.ctx.clone(),
                                 dummy_scan_plan,
-                                self.ctx.get_settings().get_max_threads()? as usize,
+                                self.ctx.get_max_threads()? as usize,
                             )
                             .map(PlanNode::ReadSource),
                         _unreachable_plan => panic!("Logical error: cannot downcast to scan plan"),
@@ -734,19 +826,29 @@ impl PlanParser {
             })
     }
 
-    fn plan_table_with_joins(&self, t: &sqlparser::ast::TableWithJoins) -> Result<PlanNode> {
-        self.create_relation(&t.relation)
+    fn plan_table_with_joins(
+        &self,
+        t: &sqlparser::ast::TableWithJoins,
+        push_down: Option<&sqlparser::ast::Select>,
+    ) -> Result<PlanNode> {
+        // The joined tables must not be dropped silently.
+        if !t.joins.is_empty() {
+            return Result::Err(ErrorCode::UnImplement(format!(
+                "Unsupported JOIN of {} and {}",
+                t.relation, t.joins[0].relation
+            )));
+        }
+        self.create_relation(&t.relation, push_down)
     }
 
-    fn create_relation(&self, relation: &sqlparser::ast::TableFactor) -> Result<PlanNode> {
+    fn create_relation(
+        &self,
+        relation: &sqlparser::ast::TableFactor,
+        push_down: Option<&sqlparser::ast::Select>,
+    ) -> Result<PlanNode> {
         match relation {
             TableFactor::Table { name, args, .. } => {
-                let mut db_name = self.ctx.get_current_database();
-                let mut table_name = name.to_string();
-                if name.0.len() == 2 {
-                    db_name = name.0[0].to_string();
-                    table_name = name.0[1].to_string();
-                }
+                let (mut db_name, mut table_name) = self.resolve_table_name(name)?.into_parts()?;
                 let mut table_args = None;
                 let meta_id;
                 let meta_version;
@@ -761,19 +863,22 @@ impl PlanParser {
                     }
 
                     let empty_schema = Arc::new(DataSchema::empty());
-                    match &args[0] {
-                        FunctionArg::Named { arg, .. } => {
-                            table_args = Some(self.sql_to_rex(arg, empty_schema.as_ref(), None)?);
-                        }
-                        FunctionArg::Unnamed(arg) => {
-                            table_args = Some(self.sql_to_rex(arg, empty_schema.as_ref(), None)?);
-                        }
-                    }
+                    let exprs = args
+                        .iter()
+                        .map(|arg| match arg {
+                            FunctionArg::Named { arg, .. } | FunctionArg::Unnamed(arg) => {
+                                self.sql_to_rex(arg, empty_schema.as_ref(), None)
+                            }
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    table_args = Some(exprs);
 
                     let func_meta = self.ctx.get_table_function(&table_name)?;
                     meta_id = func_meta.meta_id();
                     meta_version = func_meta.meta_ver();
                     let table_function = func_meta.raw().clone();
+                    // A table function belongs to its own database, not the current one.
+                    db_name = table_function.db().to_string();
                     table_name = table_function.name().to_string();
                     table = table_function.as_table();
                 } else {
@@ -798,23 +903,93 @@ impl PlanParser {
                 };
 
                 // TODO: Move ReadSourcePlan to SelectInterpreter
-                let partitions = self.ctx.get_settings().get_max_threads()? as usize;
+                let partitions = self.ctx.get_max_threads()? as usize;
+                let resolved = TableIdentifier::create(Some(db_name), table_name);
                 scan.and_then(|scan| match scan {
-                    PlanNode::Scan(ref scan) => table
-                        .read_plan(self.ctx.clone(), scan, partitions)
+                    PlanNode::Scan(ref scan) => self
+                        .push_down_filter(scan, push_down)
+                        .and_then(|scan| self.read_source(table.as_ref(), &scan, partitions))
+                        .and_then(|plan| Self::check_read_source(&resolved, plan))
                         .map(PlanNode::ReadSource),
                     _unreachable_plan => panic!("Logical error: Cannot downcast to scan plan"),
                 })
             }
             TableFactor::Derived { subquery, .. } => self.query_to_plan(subquery),
             TableFactor::NestedJoin(table_with_joins) => {
-                self.plan_table_with_joins(table_with_joins)
+                self.plan_table_with_joins(table_with_joins, push_down)
             }
             TableFactor::TableFunction { .. } => {
                 Result::Err(ErrorCode::UnImplement("Unsupported table function"))
             }
         }
     }
+
+    /// Resolve a table name of the statement, the current database only fills a missing qualifier.
+    fn resolve_table_name(&self, name: &ObjectName) -> Result<TableIdentifier> {
+        let parts = name
+            .0
+            .iter()
+            .map(|ident| ident.value.clone())
+            .collect::<Vec<_>>();
+        let identifier = TableIdentifier::try_from_parts(&parts)?;
+        Ok(identifier.resolve(&self.ctx.get_current_database()))
+    }
+
+    /// Pushes the WHERE predicate of the select down to the scan, for the table to skip the parts
+    /// that can't match it, e.g. the numbers out of the range of `number > 1`. The predicate is
+    /// still filtered after the scan. Only a predicate of the columns of the table without
+    /// subqueries is pushed down, and none if the query has a no_filter_pushdown hint.
+    fn push_down_filter(
+        &self,
+        scan: &ScanPlan,
+        select: Option<&sqlparser::ast::Select>,
+    ) -> Result<ScanPlan> {
+        let predicate = match select.and_then(|select| select.selection.as_ref()) {
+            Some(predicate) if is_pushable(predicate, &scan.table_schema) => predicate,
+            _ => return Ok(scan.clone()),
+        };
+        if self.ctx.get_query_hints().no_filter_pushdown {
+            return Ok(scan.clone());
+        }
+
+        let mut scan = scan.clone();
+        let filter = self.sql_to_rex(predicate, &scan.table_schema, select)?;
+        scan.push_downs.filters = vec![filter];
+        Ok(scan)
+    }
+
+    /// The statement of an EXPLAIN only asks the table for the read source plan
+    /// without the parts if the setting explain_read_plan is 0, e.g. not to contact the store.
+    fn read_source(
+        &self,
+        table: &dyn Table,
+        scan: &ScanPlan,
+        partitions: usize,
+    ) -> Result<ReadDataSourcePlan> {
+        if self.explain && self.ctx.get_settings().get_explain_read_plan()? == 0 {
+            if let Some(plan) = table.read_plan_without_parts(scan)? {
+                return Ok(plan);
+            }
+        }
+        table.read_plan(self.ctx.clone(), scan, partitions)
+    }
+
+    /// The table must read from the table it is resolved to,
+    /// the remote nodes and the source transform only look up the table in the plan.
+    fn check_read_source(
+        resolved: &TableIdentifier,
+        plan: ReadDataSourcePlan,
+    ) -> Result<ReadDataSourcePlan> {
+        let read = plan.table_identifier();
+        match read == *resolved {
+            true => Ok(plan),
+            false => Err(ErrorCode::LogicalError(format!(
+                "Table {} is resolved to {}, but reads from {}",
+                resolved.table, resolved, read
+            ))),
+        }
+    }
+
     fn process_compound_ident(
         &self,
         ids: &[Ident],
@@ -824,16 +999,28 @@ impl PlanParser {
         for id in ids {
             var_names.push(id.value.clone());
         }
-        if &var_names[0][0..1] == "@" || var_names.len() != 2 || select == None {
+        if &var_names[0][0..1] == "@" || var_names.len() > 3 || select == None {
             return Err(ErrorCode::UnImplement(format!(
                 "Unsupported compound identifier '{:?}'",
                 var_names,
             )));
         }
 
-        let table_name = &var_names[0];
+        // The column is qualified by `table` or `db.table`.
+        let column = var_names.pop().unwrap();
+        let qualifier = TableIdentifier::try_from_parts(&var_names)?;
+        let table_name = qualifier.to_string();
         let from = &select.unwrap().from;
-        let obj_table_name = ObjectName(vec![Ident::new(table_name)]);
+        let unknown_table = || {
+            Err(ErrorCode::UnknownTable(format!(
+                "Unknown Table '{:?}'",
+                &table_name,
+            )))
+        };
+        let is_alias = |alias: &Option<TableAlias>| match alias {
+            Some(a) => qualifier.db.is_none() && a.name.value == qualifier.table,
+            None => false,
+        };
 
         match from.len() {
             0 => Err(ErrorCode::SyntaxException(
@@ -846,45 +1033,21 @@ impl PlanParser {
                     args: _,
                     with_hints: _,
                 } => {
-                    if *name == obj_table_name {
-                        return Ok(Expression::Column(var_names.pop().unwrap()));
-                    }
-                    match alias {
-                        Some(a) => {
-                            if a.name == ids[0] {
-                                Ok(Expression::Column(var_names.pop().unwrap()))
-                            } else {
-                                Err(ErrorCode::UnknownTable(format!(
-                                    "Unknown Table '{:?}'",
-                                    &table_name,
-                                )))
-                            }
-                        }
-                        None => Err(ErrorCode::UnknownTable(format!(
-                            "Unknown Table '{:?}'",
-                            &table_name,
-                        ))),
+                    // An unqualified table matches the table of any database in the FROM clause.
+                    let from_table = self.resolve_table_name(name)?;
+                    if qualifier.resolve(from_table.db()?) == from_table || is_alias(alias) {
+                        Ok(Expression::Column(column))
+                    } else {
+                        unknown_table()
                     }
                 }
                 TableFactor::Derived {
                     lateral: _,
                     subquery: _,
                     alias,
-                } => match alias {
-                    Some(a) => {
-                        if a.name == ids[0] {
-                            Ok(Expression::Column(var_names.pop().unwrap()))
-                        } else {
-                            Err(ErrorCode::UnknownTable(format!(
-                                "Unknown Table '{:?}'",
-                                &table_name,
-                            )))
-                        }
-                    }
-                    None => Err(ErrorCode::UnknownTable(format!(
-                        "Unknown Table '{:?}'",
-                        &table_name,
-                    ))),
+                } => match is_alias(alias) {
+                    true => Ok(Expression::Column(column)),
+                    false => unknown_table(),
                 },
                 _ => Err(ErrorCode::SyntaxException("Cannot support Nested Join now")),
             },
@@ -929,6 +1092,25 @@ impl PlanParser {
         }
     }
 
+    // The MySQL system variables such as `@@autocommit`, which the clients and ORMs read.
+    fn system_variable_to_rex(&self, variable: &str) -> Result<Expression> {
+        let value = match variable.to_lowercase().as_str() {
+            "@@autocommit" => self.ctx.get_settings().get_autocommit()?,
+            _ => {
+                return Err(ErrorCode::UnknownVariable(format!(
+                    "Unknown system variable {}",
+                    variable
+                )))
+            }
+        };
+
+        Ok(Expression::Literal {
+            value: DataValue::UInt64(Some(value)),
+            column_name: Some(variable.to_string()),
+            data_type: DataType::UInt64,
+        })
+    }
+
     fn value_to_rex(value: &sqlparser::ast::Value) -> Result<Expression> {
         match value {
             sqlparser::ast::Value::Number(ref n, _) => {
@@ -986,6 +1168,9 @@ impl PlanParser {
     ) -> Result<Expression> {
         match expr {
             sqlparser::ast::Expr::Value(value) => Self::value_to_rex(value),
+            sqlparser::ast::Expr::Identifier(ref v) if v.value.starts_with("@@") => {
+                self.system_variable_to_rex(&v.value)
+            }
             sqlparser::ast::Expr::Identifier(ref v) => Ok(Expression::Column(v.clone().value)),
             sqlparser::ast::Expr::BinaryOp { left, op, right } => {
                 Ok(Expression::BinaryExpression {
@@ -1130,6 +1315,30 @@ impl PlanParser {
                         .or(expression.gt(high_expression))),
                 }
             }
+            // An IN list is the equalities of its values, a NOT IN list the inequalities.
+            sqlparser::ast::Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let expression = self.sql_to_rex(expr, schema, select)?;
+                let mut values = list
+                    .iter()
+                    .map(|value| self.sql_to_rex(value, schema, select))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter();
+                let compare = |value: Expression| match *negated {
+                    false => expression.eq(value),
+                    true => expression.not_eq(value),
+                };
+                let first = values.next().map(compare).ok_or_else(|| {
+                    ErrorCode::SyntaxException(format!("Empty IN list: {}", expr))
+                })?;
+                Ok(values.fold(first, |acc, value| match *negated {
+                    false => acc.or(compare(value)),
+                    true => acc.and(compare(value)),
+                }))
+            }
             other => Result::Err(ErrorCode::SyntaxException(format!(
                 "Unsupported expression: {}, type: {:?}",
                 expr, other
@@ -1337,3 +1546,25 @@ impl PlanParser {
             .and_then(|builder| builder.build())
     }
 }
+
+/// Whether the predicate only compares the columns of the table with the literals,
+/// it is then planned the same on the scan as on the filter.
+fn is_pushable(expr: &sqlparser::ast::Expr, schema: &DataSchemaRef) -> bool {
+    match expr {
+        sqlparser::ast::Expr::Value(_) => true,
+        sqlparser::ast::Expr::Identifier(ident) => schema.field_with_name(&ident.value).is_ok(),
+        sqlparser::ast::Expr::BinaryOp { left, right, .. } => {
+            is_pushable(left, schema) && is_pushable(right, schema)
+        }
+        sqlparser::ast::Expr::UnaryOp { expr, .. }
+        | sqlparser::ast::Expr::Nested(expr)
+        | sqlparser::ast::Expr::Cast { expr, .. } => is_pushable(expr, schema),
+        sqlparser::ast::Expr::Between {
+            expr, low, high, ..
+        } => is_pushable(expr, schema) && is_pushable(low, schema) && is_pushable(high, schema),
+        sqlparser::ast::Expr::InList { expr, list, .. } => {
+            is_pushable(expr, schema) && list.iter().all(|value| is_pushable(value, schema))
+        }
+        _ => false,
+    }
+}
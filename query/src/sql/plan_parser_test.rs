@@ -12,9 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_datablocks::assert_blocks_sorted_eq;
+use common_exception::codes;
 use common_exception::Result;
+use common_planners::PlanNode;
+use common_runtime::tokio;
+use futures::TryStreamExt;
 use pretty_assertions::assert_eq;
 
+use crate::interpreters::CreateTableInterpreter;
+use crate::interpreters::Interpreter;
+use crate::interpreters::InterpreterFactory;
 use crate::sql::PlanParser;
 
 #[test]
@@ -93,6 +101,18 @@ fn test_plan_parser() -> Result<()> {
             expect: "",
             error: "",
         },
+        Test {
+            name: "undrop-table-passed",
+            sql: "UNDROP TABLE db1.t1",
+            expect: "",
+            error: "",
+        },
+        Test {
+            name: "modify-column-passed",
+            sql: "ALTER TABLE db1.t1 MODIFY COLUMN c1 BIGINT NULL",
+            expect: "",
+            error: "",
+        },
         Test {
             name: "truncate-table-passed",
             sql: "TRUNCATE TABLE db1.t1",
@@ -102,13 +122,13 @@ fn test_plan_parser() -> Result<()> {
         Test {
             name: "cast-passed",
             sql: "select cast('1' as int)",
-            expect: "Projection: cast('1' as Int32):Int32\n  Expression: cast(1 as Int32):Int32 (Before Projection)\n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+            expect: "Projection: cast('1' as Int32):Int32\n  Expression: cast(1 as Int32):Int32 (Before Projection)\n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             error: "",
         },
         Test {
             name: "database-passed",
             sql: "select database()",
-            expect: "Projection: database():String\n  Expression: database(default):String (Before Projection)\n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+            expect: "Projection: database():String\n  Expression: database(default):String (Before Projection)\n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             error: "",
         },
         Test {
@@ -126,7 +146,7 @@ fn test_plan_parser() -> Result<()> {
         Test {
             name: "interval-passed",
             sql: "SELECT INTERVAL '1' year, INTERVAL '1' month, INTERVAL '1' day, INTERVAL '1' hour, INTERVAL '1' minute, INTERVAL '1' second",
-            expect: "Projection: 12:Interval(YearMonth), 1:Interval(YearMonth), 86400000:Interval(DayTime), 3600000:Interval(DayTime), 60000:Interval(DayTime), 1000:Interval(DayTime)\n  Expression: 12:Interval(YearMonth), 1:Interval(YearMonth), 86400000:Interval(DayTime), 3600000:Interval(DayTime), 60000:Interval(DayTime), 1000:Interval(DayTime) (Before Projection)\n    ReadDataSource: scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
+            expect: "Projection: 12:Interval(YearMonth), 1:Interval(YearMonth), 86400000:Interval(DayTime), 3600000:Interval(DayTime), 60000:Interval(DayTime), 1000:Interval(DayTime)\n  Expression: 12:Interval(YearMonth), 1:Interval(YearMonth), 86400000:Interval(DayTime), 3600000:Interval(DayTime), 60000:Interval(DayTime), 1000:Interval(DayTime) (Before Projection)\n    ReadDataSource: scan table: system.one, scan partitions: [1], scan schema: [dummy:UInt8], statistics: [read_rows: 1, read_bytes: 1]",
             error: "",
         },
         // Test {
@@ -172,7 +192,7 @@ fn test_plan_parser() -> Result<()> {
             \n            AggregatorPartial: groupBy=[[(number % 3)]], aggr=[[sum((number + 1))]]\
             \n              Expression: (number % 3):UInt8, (number + 1):UInt64 (Before GroupBy)\
             \n                Filter: (number > 1)\
-            \n                  ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
+            \n                  ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 8, read_bytes: 64]",
             error: "",
         },
 
@@ -188,7 +208,7 @@ fn test_plan_parser() -> Result<()> {
             expect: "\
             Projection: number:UInt64\
             \n  Filter: NULL\
-            \n    ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
+            \n    ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
             error: "",
         },
         Test {
@@ -197,7 +217,7 @@ fn test_plan_parser() -> Result<()> {
             expect: "\
             Projection: number:UInt64\
             \n  Filter: (NULL AND true)\
-            \n    ReadDataSource: scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
+            \n    ReadDataSource: scan table: system.numbers, scan partitions: [8], scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80]",
             error: "",
         }
     ];
@@ -217,3 +237,156 @@ fn test_plan_parser() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_plan_parser_semantic_hash() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let hash = |sql: &str| -> Result<u64> {
+        PlanParser::create(ctx.clone())
+            .build_from_sql(sql)?
+            .semantic_hash()
+    };
+
+    let equal_pairs = vec![
+        (
+            "whitespace",
+            "select number from numbers(10) where number > 1",
+            "select   number\n  from numbers( 10 )\n where number>1",
+        ),
+        (
+            "keyword-and-alias-case",
+            "select number as n from numbers(10)",
+            "SELECT number AS N FROM numbers(10)",
+        ),
+        (
+            "parentheses",
+            "select number from numbers(10) where number > 1 and number < 5",
+            "select number from numbers(10) where ((number > 1) and (number < 5))",
+        ),
+        (
+            "reordered-and-terms",
+            "select number from numbers(10) where number > 1 and number < 5 and number != 3",
+            "select number from numbers(10) where number != 3 and number < 5 and number > 1",
+        ),
+        (
+            "commutative-operands",
+            "select number + 1 as x from numbers(10) where number = 2",
+            "select 1 + number as x from numbers(10) where 2 = number",
+        ),
+    ];
+
+    for (name, left, right) in equal_pairs {
+        assert_eq!(hash(left)?, hash(right)?, "{}", name);
+    }
+
+    let differing_pairs = vec![
+        (
+            "literal",
+            "select number from numbers(10) where number > 1",
+            "select number from numbers(10) where number > 2",
+        ),
+        (
+            "table-args",
+            "select number from numbers(10)",
+            "select number from numbers(11)",
+        ),
+        (
+            "non-commutative-operands",
+            "select number from numbers(10) where number > 1",
+            "select number from numbers(10) where 1 > number",
+        ),
+        (
+            "projection-order",
+            "select number, number + 1 as x from numbers(10)",
+            "select number + 1 as x, number from numbers(10)",
+        ),
+    ];
+
+    for (name, left, right) in differing_pairs {
+        assert_ne!(hash(left)?, hash(right)?, "{}", name);
+    }
+
+    // The same unqualified table name under different current databases.
+    if let PlanNode::CreateTable(plan) = PlanParser::create(ctx.clone())
+        .build_from_sql("create table default.one(dummy int) Engine = Null")?
+    {
+        let executor = CreateTableInterpreter::try_create(ctx.clone(), plan)?;
+        let _ = executor.execute().await?;
+    }
+
+    ctx.set_current_database("default".to_string())?;
+    let in_default = hash("select * from one")?;
+    ctx.set_current_database("system".to_string())?;
+    let in_system = hash("select * from one")?;
+    assert_ne!(in_default, in_system);
+    assert_eq!(in_system, hash("select * from system.one")?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_plan_parser_qualified_table_names() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let execute = |sql: &str| {
+        let ctx = ctx.clone();
+        let sql = sql.to_string();
+        async move {
+            let plan = PlanParser::create(ctx.clone()).build_from_sql(&sql)?;
+            let executor = InterpreterFactory::get(ctx, plan)?;
+            let stream = executor.execute().await?;
+            stream.try_collect::<Vec<_>>().await
+        }
+    };
+    let explain = |sql: &str| -> Result<String> {
+        let plan = PlanParser::create(ctx.clone()).build_from_sql(sql)?;
+        Ok(format!("{:?}", plan))
+    };
+
+    // Tables of the same name in two databases, queried from a third one.
+    for db in ["db1", "db2", "db3"] {
+        execute(&format!("CREATE DATABASE {} Engine = default", db)).await?;
+    }
+    execute("CREATE TABLE db1.t(a UInt64) Engine = Memory").await?;
+    execute("CREATE TABLE db2.t(a UInt64) Engine = Memory").await?;
+    execute("INSERT INTO db1.t VALUES(1),(2)").await?;
+    execute("INSERT INTO db2.t VALUES(10),(20)").await?;
+    ctx.set_current_database("db3".to_string())?;
+
+    let result = execute("SELECT a FROM db1.t").await?;
+    let expected = vec!["+---+", "| a |", "+---+", "| 1 |", "| 2 |", "+---+"];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+
+    let result = execute("SELECT t.a FROM db2.t WHERE db2.t.a > 10").await?;
+    let expected = vec!["+----+", "| a  |", "+----+", "| 20 |", "+----+"];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+
+    assert!(explain("SELECT a FROM db1.t")?.contains("ReadDataSource: scan table: db1.t,"));
+    assert!(explain("SELECT a FROM db2.t")?.contains("ReadDataSource: scan table: db2.t,"));
+    // a table function is in its own database
+    assert!(explain("SELECT * FROM numbers(1)")?
+        .contains("ReadDataSource: scan table: system.numbers,"));
+
+    // The current database only fills a missing qualifier.
+    let result = execute("SELECT a FROM t").await;
+    assert_eq!(result.unwrap_err().code(), codes::UnknownTable);
+
+    ctx.set_current_database("db1".to_string())?;
+    let result = execute("SELECT a FROM t").await?;
+    let expected = vec!["+---+", "| a |", "+---+", "| 1 |", "| 2 |", "+---+"];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+    assert!(explain("SELECT a FROM t")?.contains("ReadDataSource: scan table: db1.t,"));
+
+    let result = execute("SELECT a FROM db2.t").await?;
+    let expected = vec!["+----+", "| a  |", "+----+", "| 10 |", "| 20 |", "+----+"];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+
+    // Ambiguous or conflicting names.
+    let result = explain("SELECT a FROM c.db2.t");
+    assert_eq!(result.unwrap_err().code(), codes::SyntaxException);
+    let result = explain("SELECT db2.t.a FROM t");
+    assert_eq!(result.unwrap_err().code(), codes::UnknownTable);
+    let result = explain("SELECT * FROM db1.t JOIN db2.t ON db1.t.a = db2.t.a");
+    assert_eq!(result.unwrap_err().code(), codes::UnImplement);
+
+    Ok(())
+}
@@ -0,0 +1,537 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use common_cache::Cache;
+use common_cache::LruCache;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_metatypes::MetaVersion;
+use common_planners::AggregatorFinalPlan;
+use common_planners::AggregatorPartialPlan;
+use common_planners::Expression;
+use common_planners::PlanBuilder;
+use common_planners::PlanCanonicalizer;
+use common_planners::PlanNode;
+use common_planners::PlanRewriter;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use metrics::counter;
+use sqlparser::tokenizer::Token;
+use sqlparser::tokenizer::Whitespace;
+
+use crate::catalogs::Catalog;
+use crate::functions::ContextFunction;
+use crate::sessions::DatabendQueryContextRef;
+use crate::sql::DfHint;
+use crate::sql::SqlDialect;
+
+/// A query with its literals taken out, the queries of the same shape only differ in the literals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlShape {
+    /// The tokens of the query with `?` in place of the literals, the whitespaces are collapsed.
+    pub text: String,
+    /// The number and string literals, in the order of the query.
+    pub params: Vec<DataValue>,
+    /// The number of `?` placeholders, of a prepared statement.
+    pub placeholders: usize,
+}
+
+impl SqlShape {
+    pub fn parse(query: &str, dialect: SqlDialect) -> Result<SqlShape> {
+        let tokens = dialect.tokenize(query)?;
+
+        let mut shape = SqlShape {
+            text: String::with_capacity(query.len()),
+            params: vec![],
+            placeholders: 0,
+        };
+
+        for token in tokens {
+            match token {
+                Token::Number(literal, _) => {
+                    shape.text.push('?');
+                    shape.params.push(DataValue::try_from_literal(&literal)?);
+                }
+                Token::SingleQuotedString(literal) => {
+                    shape.text.push('?');
+                    shape
+                        .params
+                        .push(DataValue::String(Some(literal.into_bytes())));
+                }
+                Token::Whitespace(Whitespace::Space)
+                | Token::Whitespace(Whitespace::Tab)
+                | Token::Whitespace(Whitespace::Newline) => {
+                    if !shape.text.is_empty() && !shape.text.ends_with(' ') {
+                        shape.text.push(' ');
+                    }
+                }
+                token if token.to_string() == "?" => {
+                    shape.text.push('?');
+                    shape.placeholders += 1;
+                }
+                // The comments are kept, the hints are in them.
+                token => shape.text.push_str(&dialect.token_to_string(&token)),
+            }
+        }
+
+        Ok(shape)
+    }
+
+    /// The key of the shape in the `PlanTemplateCache`.
+    pub fn hash(&self) -> u64 {
+        PlanCanonicalizer::hash(&self.text)
+    }
+}
+
+/// Replace the `?` placeholders of a prepared statement with the literals of the params.
+pub fn bind_placeholders(query: &str, dialect: SqlDialect, params: &[DataValue]) -> Result<String> {
+    let tokens = dialect.tokenize(query)?;
+
+    let mut params = params.iter();
+    let mut bound = String::with_capacity(query.len());
+    for token in tokens {
+        match dialect.token_to_string(&token).as_str() {
+            "?" => match params.next() {
+                Some(param) => bound.push_str(&sql_literal(param)?),
+                None => {
+                    return Err(ErrorCode::BadArguments(
+                        "Fewer parameters than the placeholders of the prepared statement",
+                    ))
+                }
+            },
+            token => bound.push_str(token),
+        }
+    }
+
+    match params.next() {
+        None => Ok(bound),
+        Some(_) => Err(ErrorCode::BadArguments(
+            "More parameters than the placeholders of the prepared statement",
+        )),
+    }
+}
+
+fn sql_literal(value: &DataValue) -> Result<String> {
+    match value {
+        v if v.is_null() => Ok("NULL".to_string()),
+        DataValue::String(Some(v)) => Ok(format!(
+            "'{}'",
+            String::from_utf8_lossy(v).replace('\'', "''")
+        )),
+        DataValue::Float32(Some(v)) if v.is_finite() => Ok(v.to_string()),
+        DataValue::Float64(Some(v)) if v.is_finite() => Ok(v.to_string()),
+        DataValue::Int8(_)
+        | DataValue::Int16(_)
+        | DataValue::Int32(_)
+        | DataValue::Int64(_)
+        | DataValue::UInt8(_)
+        | DataValue::UInt16(_)
+        | DataValue::UInt32(_)
+        | DataValue::UInt64(_) => Ok(value.to_string()),
+        _ => Err(ErrorCode::BadArguments(format!(
+            "Unsupported parameter of the prepared statement: {:?}",
+            value
+        ))),
+    }
+}
+
+/// What the plan of a template depends on besides the query, a template is only used
+/// while they stay the same.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanTemplateGuard {
+    meta_version: MetaVersion,
+    current_database: String,
+    /// All the settings, sorted by name, rather than keeping track of those read by the planner.
+    settings: Vec<String>,
+}
+
+impl PlanTemplateGuard {
+    pub fn create(ctx: &DatabendQueryContextRef) -> Result<PlanTemplateGuard> {
+        let mut settings = ctx
+            .get_settings()
+            .iter()
+            .map(|setting| format!("{:?}", setting))
+            .collect::<Vec<_>>();
+        settings.sort();
+
+        Ok(PlanTemplateGuard {
+            meta_version: ctx.get_catalog().get_meta_version()?,
+            current_database: ctx.get_current_database(),
+            settings,
+        })
+    }
+}
+
+/// The plan of a query with the literals of its params, which are replaced to plan another query of the same shape.
+struct PlanTemplate {
+    shape: String,
+    params: Vec<DataValue>,
+    plan: PlanNode,
+    hints: Vec<DfHint>,
+    guard: PlanTemplateGuard,
+}
+
+impl PlanTemplate {
+    /// The params are typed, a query whose literals are of other types is planned again.
+    fn accepts(&self, params: &[DataValue]) -> bool {
+        self.params.len() == params.len()
+            && self
+                .params
+                .iter()
+                .zip(params)
+                .all(|(param, other)| param.data_type() == other.data_type())
+    }
+}
+
+/// The plans of the recent queries of a session by their shapes, see `SqlShape`.
+///
+/// A query of the same shape as a cached one is not planned again, the literals of its params are
+/// bound into a copy of the cached plan, and the sources are read again.
+/// The template is dropped if the catalog, the current database or the settings changed.
+/// The capacity is the `plan_template_cache_size` setting.
+pub struct PlanTemplateCache {
+    templates: Mutex<LruCache<u64, Arc<PlanTemplate>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PlanTemplateCache {
+    pub fn create() -> PlanTemplateCache {
+        PlanTemplateCache {
+            templates: Mutex::new(LruCache::new(1)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The number of queries planned from a template.
+    pub fn get_hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of queries planned fully.
+    pub fn get_misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn record_hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        counter!(super::metrics::METRIC_PLAN_TEMPLATE_HITS, 1);
+    }
+
+    pub fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        counter!(super::metrics::METRIC_PLAN_TEMPLATE_MISSES, 1);
+    }
+
+    /// Plan the query from the template of its shape, None if there is no usable one.
+    pub fn try_bind(
+        &self,
+        ctx: &DatabendQueryContextRef,
+        shape: &SqlShape,
+    ) -> Result<Option<(PlanNode, Vec<DfHint>)>> {
+        if ctx.get_settings().get_plan_template_cache_size()? == 0 {
+            return Ok(None);
+        }
+
+        let key = shape.hash();
+        let template = match self.templates.lock().get(&key) {
+            Some(template) if template.shape == shape.text => template.clone(),
+            _ => return Ok(None),
+        };
+
+        if !template.accepts(&shape.params) {
+            return Ok(None);
+        }
+
+        if template.guard != PlanTemplateGuard::create(ctx)? {
+            self.templates.lock().pop(&key);
+            return Ok(None);
+        }
+
+        let mut binder = LiteralBinder::create(Some(ctx.clone()), &template.params, &shape.params);
+        let plan = binder.rewrite_plan_node(&template.plan)?;
+        Ok(Some((plan, template.hints.clone())))
+    }
+
+    /// Keep the plan of a query as the template of its shape, if the literals of its params can be
+    /// found in the plan. `guard` must be taken before the query is planned.
+    pub fn try_insert(
+        &self,
+        ctx: &DatabendQueryContextRef,
+        shape: SqlShape,
+        guard: PlanTemplateGuard,
+        plan: &PlanNode,
+        hints: &[DfHint],
+    ) -> Result<()> {
+        let capacity = ctx.get_settings().get_plan_template_cache_size()?;
+        if capacity == 0 {
+            self.templates.lock().clear();
+            return Ok(());
+        }
+
+        if !matches!(plan, PlanNode::Select(_)) || shape.placeholders > 0 {
+            return Ok(());
+        }
+
+        if !Self::is_parameterizable(ctx, &shape.params, plan)? {
+            return Ok(());
+        }
+
+        let key = shape.hash();
+        let template = PlanTemplate {
+            shape: shape.text,
+            params: shape.params,
+            plan: plan.clone(),
+            hints: hints.to_vec(),
+            guard,
+        };
+
+        let mut templates = self.templates.lock();
+        templates.set_capacity(capacity);
+        templates.put(key, Arc::new(template));
+        Ok(())
+    }
+
+    /// Whether the plan has no other literal of the same value as a param, i.e. the params can be told apart.
+    fn is_parameterizable(
+        ctx: &DatabendQueryContextRef,
+        params: &[DataValue],
+        plan: &PlanNode,
+    ) -> Result<bool> {
+        for (index, param) in params.iter().enumerate() {
+            if params[index + 1..].contains(param) {
+                return Ok(false);
+            }
+        }
+
+        // e.g. `SELECT database()` is planned with the current database as a literal.
+        let context_values = ContextFunction::context_values(ctx);
+        if params.iter().any(|param| context_values.contains(param)) {
+            return Ok(false);
+        }
+
+        let mut collector = LiteralBinder::create(None, &[], &[]);
+        collector.rewrite_plan_node(plan)?;
+
+        // A param consumed by the planner, e.g. the LIMIT, is not a literal of the plan.
+        // The columns after the aggregation are named after the aggregate expressions,
+        // their literals can't be replaced.
+        Ok(params.iter().all(|param| {
+            collector.literals.contains(param) && !collector.aggregate_literals.contains(param)
+        }))
+    }
+}
+
+/// Replaces the literals of the params in a plan, and reads its sources again.
+/// Without a context, it only collects the literals of the plan.
+struct LiteralBinder<'a> {
+    ctx: Option<DatabendQueryContextRef>,
+    from: &'a [DataValue],
+    to: &'a [DataValue],
+    literals: Vec<DataValue>,
+    aggregate_literals: Vec<DataValue>,
+    in_aggregate: bool,
+    before_group_by_schemas: Vec<DataSchemaRef>,
+}
+
+impl<'a> LiteralBinder<'a> {
+    fn create(
+        ctx: Option<DatabendQueryContextRef>,
+        from: &'a [DataValue],
+        to: &'a [DataValue],
+    ) -> Self {
+        LiteralBinder {
+            ctx,
+            from,
+            to,
+            literals: vec![],
+            aggregate_literals: vec![],
+            in_aggregate: false,
+            before_group_by_schemas: vec![],
+        }
+    }
+
+    fn bind_literal(&mut self, value: &DataValue) -> DataValue {
+        match self.in_aggregate {
+            true => self.aggregate_literals.push(value.clone()),
+            false => self.literals.push(value.clone()),
+        }
+
+        match self.from.iter().position(|param| param == value) {
+            Some(index) => self.to[index].clone(),
+            None => value.clone(),
+        }
+    }
+
+    /// The table function args, e.g. `numbers(10)`, are params as well.
+    fn bind_scan(&mut self, plan: &ScanPlan) -> Result<ScanPlan> {
+        let mut new_scan = plan.clone();
+        if let Some(table_args) = &plan.table_args {
+            let schema = Arc::new(DataSchema::empty());
+            let table_args = table_args
+                .iter()
+                .map(|arg| self.rewrite_expr(&schema, arg))
+                .collect::<Result<Vec<_>>>()?;
+            new_scan.table_args = Some(table_args);
+        }
+        // The WHERE predicate pushed down to the scan has the literals of the filter.
+        new_scan.push_downs.filters = plan
+            .push_downs
+            .filters
+            .iter()
+            .map(|filter| self.rewrite_expr(&plan.table_schema, filter))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(new_scan)
+    }
+
+    fn read_source_again(
+        ctx: &DatabendQueryContextRef,
+        plan: &ReadDataSourcePlan,
+        scan: &ScanPlan,
+    ) -> Result<ReadDataSourcePlan> {
+        let table = match scan.table_args {
+            Some(_) => ctx
+                .get_table_function(&plan.table)?
+                .raw()
+                .clone()
+                .as_table(),
+            None => ctx.get_table(&plan.db, &plan.table)?.raw().clone(),
+        };
+
+        let partitions = ctx.get_max_threads()? as usize;
+        table.read_plan(ctx.clone(), scan, partitions)
+    }
+}
+
+impl<'a> PlanRewriter for LiteralBinder<'a> {
+    fn rewrite_expr(&mut self, schema: &DataSchemaRef, expr: &Expression) -> Result<Expression> {
+        Ok(match expr {
+            Expression::Literal {
+                value,
+                column_name,
+                data_type,
+            } => Expression::Literal {
+                value: self.bind_literal(value),
+                column_name: column_name.clone(),
+                data_type: data_type.clone(),
+            },
+            Expression::Alias(alias, input) => {
+                Expression::Alias(alias.clone(), Box::new(self.rewrite_expr(schema, input)?))
+            }
+            Expression::UnaryExpression { op, expr } => Expression::UnaryExpression {
+                op: op.clone(),
+                expr: Box::new(self.rewrite_expr(schema, expr)?),
+            },
+            Expression::BinaryExpression { op, left, right } => Expression::BinaryExpression {
+                op: op.clone(),
+                left: Box::new(self.rewrite_expr(schema, left)?),
+                right: Box::new(self.rewrite_expr(schema, right)?),
+            },
+            Expression::ScalarFunction { op, args } => Expression::ScalarFunction {
+                op: op.clone(),
+                args: self.rewrite_exprs(schema, args)?,
+            },
+            Expression::AggregateFunction {
+                op,
+                distinct,
+                params,
+                args,
+            } => Expression::AggregateFunction {
+                op: op.clone(),
+                distinct: *distinct,
+                params: params.clone(),
+                args: self.rewrite_exprs(schema, args)?,
+            },
+            Expression::Sort {
+                expr,
+                asc,
+                nulls_first,
+            } => Expression::Sort {
+                expr: Box::new(self.rewrite_expr(schema, expr)?),
+                asc: *asc,
+                nulls_first: *nulls_first,
+            },
+            Expression::Cast { expr, data_type } => Expression::Cast {
+                expr: Box::new(self.rewrite_expr(schema, expr)?),
+                data_type: data_type.clone(),
+            },
+            Expression::Subquery { name, query_plan } => Expression::Subquery {
+                name: name.clone(),
+                query_plan: Arc::new(self.rewrite_subquery_plan(query_plan)?),
+            },
+            Expression::ScalarSubquery { name, query_plan } => Expression::ScalarSubquery {
+                name: name.clone(),
+                query_plan: Arc::new(self.rewrite_subquery_plan(query_plan)?),
+            },
+            Expression::Wildcard | Expression::Column(_) => expr.clone(),
+        })
+    }
+
+    fn rewrite_aggregate_partial(&mut self, plan: &AggregatorPartialPlan) -> Result<PlanNode> {
+        let new_input = self.rewrite_plan_node(&plan.input)?;
+        self.before_group_by_schemas.push(new_input.schema());
+
+        self.in_aggregate = true;
+        let new_aggr_expr = self.rewrite_exprs(&new_input.schema(), &plan.aggr_expr);
+        let new_group_expr = self.rewrite_exprs(&new_input.schema(), &plan.group_expr);
+        self.in_aggregate = false;
+
+        PlanBuilder::from(&new_input)
+            .aggregate_partial(&new_aggr_expr?, &new_group_expr?)?
+            .build()
+    }
+
+    fn rewrite_aggregate_final(&mut self, plan: &AggregatorFinalPlan) -> Result<PlanNode> {
+        let new_input = self.rewrite_plan_node(&plan.input)?;
+        let schema_before_group_by = self.before_group_by_schemas.pop().ok_or_else(|| {
+            ErrorCode::LogicalError("Logical error: before group by schema must be Some")
+        })?;
+
+        self.in_aggregate = true;
+        let new_aggr_expr = self.rewrite_exprs(&new_input.schema(), &plan.aggr_expr);
+        let new_group_expr = self.rewrite_exprs(&new_input.schema(), &plan.group_expr);
+        self.in_aggregate = false;
+
+        PlanBuilder::from(&new_input)
+            .aggregate_final(schema_before_group_by, &new_aggr_expr?, &new_group_expr?)?
+            .build()
+    }
+
+    fn rewrite_scan(&mut self, plan: &ScanPlan) -> Result<PlanNode> {
+        Ok(PlanNode::Scan(self.bind_scan(plan)?))
+    }
+
+    fn rewrite_read_data_source(&mut self, plan: &ReadDataSourcePlan) -> Result<PlanNode> {
+        let new_scan = self.bind_scan(&plan.scan_plan)?;
+
+        // The parts and the statistics of the template are stale, and the context of the query is to be set up.
+        match &self.ctx {
+            None => {
+                let mut new_plan = plan.clone();
+                new_plan.scan_plan = Arc::new(new_scan);
+                Ok(PlanNode::ReadSource(new_plan))
+            }
+            Some(ctx) => Ok(PlanNode::ReadSource(Self::read_source_again(
+                ctx, plan, &new_scan,
+            )?)),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datablocks::assert_blocks_sorted_eq;
+use common_datablocks::DataBlock;
+use common_datavalues::DataValue;
+use common_exception::Result;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::interpreters::InterpreterFactory;
+use crate::sessions::DatabendQueryContextRef;
+use crate::sql::*;
+
+async fn execute(ctx: &DatabendQueryContextRef, query: &str) -> Result<Vec<DataBlock>> {
+    let plan = PlanParser::create(ctx.clone())
+        .build_with_template_from_sql(query)
+        .0?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    let stream = executor.execute().await?;
+    stream.try_collect::<Vec<_>>().await
+}
+
+#[test]
+fn test_sql_shape() -> Result<()> {
+    let shape = SqlShape::parse(
+        "SELECT * FROM t  WHERE a = 1\nAND b = 'x'",
+        SqlDialect::Generic,
+    )?;
+    assert_eq!("SELECT * FROM t WHERE a = ? AND b = ?", shape.text);
+    assert_eq!(
+        vec![
+            DataValue::UInt8(Some(1)),
+            DataValue::String(Some(b"x".to_vec()))
+        ],
+        shape.params
+    );
+    assert_eq!(0, shape.placeholders);
+
+    // the same shape whatever the literals
+    let other = SqlShape::parse(
+        "SELECT * FROM t WHERE a = 300 AND b = 'y'",
+        SqlDialect::Generic,
+    )?;
+    assert_eq!(shape.text, other.text);
+    assert_eq!(shape.hash(), other.hash());
+
+    let prepared = SqlShape::parse("SELECT * FROM t WHERE a = ? AND b = ?", SqlDialect::Generic)?;
+    assert_eq!(shape.text, prepared.text);
+    assert_eq!(2, prepared.placeholders);
+    Ok(())
+}
+
+#[test]
+fn test_bind_placeholders() -> Result<()> {
+    let query = "SELECT * FROM t WHERE a = ? AND b = ? AND c = ?";
+    let params = vec![
+        DataValue::UInt64(Some(3)),
+        DataValue::String(Some(b"it's".to_vec())),
+        DataValue::Null,
+    ];
+    assert_eq!(
+        "SELECT * FROM t WHERE a = 3 AND b = 'it''s' AND c = NULL",
+        bind_placeholders(query, SqlDialect::Generic, &params)?
+    );
+
+    let too_few = bind_placeholders(query, SqlDialect::Generic, &params[..1]);
+    assert!(too_few.is_err());
+    let too_many = bind_placeholders("SELECT ?", SqlDialect::Generic, &params);
+    assert!(too_many.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_plan_template_hit() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let templates = ctx.get_plan_templates();
+
+    let result = execute(&ctx, "SELECT sum(number) FROM numbers(10) WHERE number > 3").await?;
+    let expected = vec![
+        "+-------------+",
+        "| sum(number) |",
+        "+-------------+",
+        "| 39          |",
+        "+-------------+",
+    ];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+    assert_eq!(1, templates.get_misses());
+    assert_eq!(0, templates.get_hits());
+
+    // same shape and types, the new literals are bound into the template
+    let result = execute(&ctx, "SELECT sum(number) FROM numbers(20) WHERE number > 5").await?;
+    let expected = vec![
+        "+-------------+",
+        "| sum(number) |",
+        "+-------------+",
+        "| 175         |",
+        "+-------------+",
+    ];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+    assert_eq!(1, templates.get_misses());
+    assert_eq!(1, templates.get_hits());
+
+    // a literal of a wider type is planned again
+    let result = execute(
+        &ctx,
+        "SELECT sum(number) FROM numbers(300) WHERE number > 298",
+    )
+    .await?;
+    let expected = vec![
+        "+-------------+",
+        "| sum(number) |",
+        "+-------------+",
+        "| 299         |",
+        "+-------------+",
+    ];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+    assert_eq!(2, templates.get_misses());
+    assert_eq!(1, templates.get_hits());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_plan_template_invalidated_by_ddl() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let templates = ctx.get_plan_templates();
+
+    execute(&ctx, "CREATE TABLE default.t(a UInt64) Engine = Memory").await?;
+    execute(&ctx, "INSERT INTO default.t VALUES(1),(2),(3)").await?;
+
+    let result = execute(&ctx, "SELECT a FROM default.t WHERE a > 1").await?;
+    let expected = vec!["+---+", "| a |", "+---+", "| 2 |", "| 3 |", "+---+"];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+
+    let result = execute(&ctx, "SELECT a FROM default.t WHERE a > 2").await?;
+    let expected = vec!["+---+", "| a |", "+---+", "| 3 |", "+---+"];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+    let (hits, misses) = (templates.get_hits(), templates.get_misses());
+    assert_eq!(1, hits);
+
+    // the table is created again, the template of its old schema must not be used
+    execute(&ctx, "DROP TABLE default.t").await?;
+    execute(&ctx, "CREATE TABLE default.t(a UInt64) Engine = Memory").await?;
+    execute(&ctx, "INSERT INTO default.t VALUES(4),(5)").await?;
+
+    let result = execute(&ctx, "SELECT a FROM default.t WHERE a > 4").await?;
+    let expected = vec!["+---+", "| a |", "+---+", "| 5 |", "+---+"];
+    assert_blocks_sorted_eq(expected, result.as_slice());
+    assert_eq!(hits, templates.get_hits());
+    assert!(templates.get_misses() > misses);
+    Ok(())
+}
@@ -0,0 +1,249 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::Token;
+use sqlparser::tokenizer::Tokenizer;
+
+/// The quoting rules of the queries of a session, see the sql_dialect setting.
+///
+/// The string literals are single-quoted in every dialect, a quote is doubled inside a literal or
+/// an identifier of its own quote, e.g. 'it''s' and `a``b`. The identifiers are kept as they are
+/// written once unquoted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SqlDialect {
+    /// Both the backticks and the double quotes quote identifiers.
+    Generic,
+    /// The backticks quote identifiers and the double quotes strings, as MySQL does.
+    MySQL,
+    /// The double quotes quote identifiers, the backticks are not allowed.
+    Ansi,
+}
+
+impl SqlDialect {
+    /// The dialect of the sql_dialect setting, '' for the generic one.
+    pub fn from_setting(value: &str) -> Result<SqlDialect> {
+        match value
+            .trim_matches(|c| c == '\'' || c == '"')
+            .to_lowercase()
+            .as_str()
+        {
+            "" => Ok(SqlDialect::Generic),
+            "mysql" => Ok(SqlDialect::MySQL),
+            "ansi" => Ok(SqlDialect::Ansi),
+            _ => Err(ErrorCode::BadArguments(format!(
+                "Unknown sql_dialect '{}', it must be 'mysql' or 'ansi'",
+                value
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SqlDialect::Generic => "",
+            SqlDialect::MySQL => "mysql",
+            SqlDialect::Ansi => "ansi",
+        }
+    }
+
+    /// Tokenize the query, the quoted strings and identifiers by the rules of the dialect and
+    /// the rest by the tokenizer of sqlparser.
+    pub fn tokenize(&self, sql: &str) -> std::result::Result<Vec<Token>, ParserError> {
+        let mut scanner = Scanner::create(sql);
+        let mut tokens = vec![];
+        let mut unquoted = String::new();
+
+        while let Some(ch) = scanner.peek() {
+            let token = match ch {
+                '-' if scanner.peek_next() == Some('-') => {
+                    scanner.take_line_comment(&mut unquoted);
+                    continue;
+                }
+                '/' if scanner.peek_next() == Some('*') => {
+                    scanner.take_block_comment(&mut unquoted);
+                    continue;
+                }
+                // The national and hex strings, e.g. N'abc' and X'1F', are left to sqlparser.
+                '\'' if Self::is_string_prefix(&unquoted) => {
+                    unquoted.push_str(&scanner.take_raw_quoted("string literal")?);
+                    continue;
+                }
+                '\'' => Token::SingleQuotedString(scanner.take_quoted("string literal")?),
+                '"' if *self == SqlDialect::MySQL => {
+                    Token::SingleQuotedString(scanner.take_quoted("string literal")?)
+                }
+                '"' => Token::make_word(&scanner.take_quoted("quoted identifier")?, Some('"')),
+                '`' if *self == SqlDialect::Ansi => {
+                    let (line, column) = scanner.position();
+                    return Err(ParserError::TokenizerError(format!(
+                        "Backtick-quoted identifiers are not allowed in the ansi sql_dialect, quote them with double quotes, at Line: {}, Column {}",
+                        line, column
+                    )));
+                }
+                '`' => Token::make_word(&scanner.take_quoted("quoted identifier")?, Some('`')),
+                _ => {
+                    unquoted.push(ch);
+                    scanner.next();
+                    continue;
+                }
+            };
+
+            Self::tokenize_unquoted(&mut unquoted, &mut tokens)?;
+            tokens.push(token);
+        }
+
+        Self::tokenize_unquoted(&mut unquoted, &mut tokens)?;
+        Ok(tokens)
+    }
+
+    /// The token as it is written in the dialect, the tokens of the written text are the same ones.
+    pub fn token_to_string(&self, token: &Token) -> String {
+        match token {
+            Token::SingleQuotedString(s) => quote(s, '\''),
+            Token::Word(w) => match w.quote_style {
+                Some(quote_style) => quote(&w.value, quote_style),
+                None => w.value.clone(),
+            },
+            token => token.to_string(),
+        }
+    }
+
+    fn is_string_prefix(unquoted: &str) -> bool {
+        let mut chars = unquoted.chars().rev();
+        match (chars.next(), chars.next()) {
+            (Some('N' | 'n' | 'X' | 'x'), None) => true,
+            (Some('N' | 'n' | 'X' | 'x'), Some(ch)) => !ch.is_ascii_alphanumeric() && ch != '_',
+            _ => false,
+        }
+    }
+
+    fn tokenize_unquoted(
+        unquoted: &mut String,
+        tokens: &mut Vec<Token>,
+    ) -> std::result::Result<(), ParserError> {
+        if !unquoted.is_empty() {
+            let dialect = GenericDialect {};
+            tokens.extend(Tokenizer::new(&dialect, unquoted).tokenize()?);
+            unquoted.clear();
+        }
+        Ok(())
+    }
+}
+
+fn quote(value: &str, quote_style: char) -> String {
+    let doubled = format!("{}{}", quote_style, quote_style);
+    format!(
+        "{}{}{}",
+        quote_style,
+        value.replace(quote_style, &doubled),
+        quote_style
+    )
+}
+
+/// The characters of a query, with the line and the column of the next one.
+struct Scanner<'a> {
+    sql: &'a str,
+    offset: usize,
+    line: u64,
+    column: u64,
+}
+
+impl<'a> Scanner<'a> {
+    fn create(sql: &'a str) -> Scanner<'a> {
+        Scanner {
+            sql,
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.sql[self.offset..].chars().next()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.sql[self.offset..].chars().nth(1)
+    }
+
+    fn position(&self) -> (u64, u64) {
+        (self.line, self.column)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.offset += ch.len_utf8();
+        match ch {
+            '\n' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ => self.column += 1,
+        }
+        Some(ch)
+    }
+
+    /// The text of the quote at the scanner, unquoted.
+    fn take_quoted(&mut self, what: &str) -> std::result::Result<String, ParserError> {
+        let (line, column) = self.position();
+        let quote_style = self.next().unwrap_or_default();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                None => {
+                    return Err(ParserError::TokenizerError(format!(
+                        "Unterminated {} starting at Line: {}, Column {}",
+                        what, line, column
+                    )))
+                }
+                Some(ch) if ch == quote_style && self.peek() == Some(quote_style) => {
+                    value.push(quote_style);
+                    self.next();
+                }
+                Some(ch) if ch == quote_style => return Ok(value),
+                Some(ch) => value.push(ch),
+            }
+        }
+    }
+
+    /// The text of the quote at the scanner, as it is written.
+    fn take_raw_quoted(&mut self, what: &str) -> std::result::Result<String, ParserError> {
+        let start = self.offset;
+        self.take_quoted(what)?;
+        Ok(self.sql[start..self.offset].to_string())
+    }
+
+    fn take_line_comment(&mut self, unquoted: &mut String) {
+        while let Some(ch) = self.next() {
+            unquoted.push(ch);
+            if ch == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn take_block_comment(&mut self, unquoted: &mut String) {
+        let start = self.offset;
+        while self.next().is_some() {
+            // The shortest comment is /**/.
+            if self.offset - start >= 4 && self.sql[start..self.offset].ends_with("*/") {
+                break;
+            }
+        }
+        unquoted.push_str(&self.sql[start..self.offset]);
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_datablocks::assert_blocks_sorted_eq;
+use common_exception::Result;
+use common_runtime::tokio;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+use sqlparser::tokenizer::Token;
+use sqlparser::tokenizer::Whitespace;
+
+use crate::interpreters::InterpreterFactory;
+use crate::sessions::DatabendQueryContextRef;
+use crate::sql::PlanParser;
+use crate::sql::SqlDialect;
+
+fn plan(ctx: &DatabendQueryContextRef, dialect: SqlDialect, sql: &str) -> Result<String> {
+    ctx.get_settings()
+        .set_sql_dialect(dialect.name().to_string())?;
+    let plan = PlanParser::create(ctx.clone()).build_from_sql(sql)?;
+    Ok(format!("{:?}", plan))
+}
+
+#[test]
+fn test_sql_dialect_from_setting() -> Result<()> {
+    assert_eq!(SqlDialect::Generic, SqlDialect::from_setting("")?);
+    assert_eq!(SqlDialect::MySQL, SqlDialect::from_setting("MySQL")?);
+    assert_eq!(SqlDialect::Ansi, SqlDialect::from_setting("'ansi'")?);
+
+    let unknown = SqlDialect::from_setting("postgres");
+    assert_eq!(
+        "Unknown sql_dialect 'postgres', it must be 'mysql' or 'ansi'",
+        unknown.unwrap_err().message()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sql_dialect_tokenize() -> Result<()> {
+    // The quotes are doubled inside the quotes, the comments are left as they are.
+    let tokens = SqlDialect::MySQL.tokenize("SELECT `a``b`, \"it\"\"s\", 'x''y' -- it's\n")?;
+    let tokens = tokens
+        .into_iter()
+        .filter(|token| *token != Token::Whitespace(Whitespace::Space))
+        .collect::<Vec<_>>();
+    assert_eq!(7, tokens.len());
+    assert_eq!(Token::make_word("a`b", Some('`')), tokens[1]);
+    assert_eq!(Token::SingleQuotedString("it\"s".to_string()), tokens[3]);
+    assert_eq!(Token::SingleQuotedString("x'y".to_string()), tokens[5]);
+    assert!(matches!(
+        tokens[6],
+        Token::Whitespace(Whitespace::SingleLineComment { .. })
+    ));
+
+    let tokens = SqlDialect::Ansi.tokenize("\"a\"\"b\"")?;
+    assert_eq!(vec![Token::make_word("a\"b", Some('"'))], tokens);
+
+    // The tokens are written back in the dialect.
+    for dialect in [SqlDialect::Generic, SqlDialect::MySQL, SqlDialect::Ansi] {
+        let sql = match dialect {
+            SqlDialect::Ansi => "SELECT \"a \"\"b\", 'it''s' FROM t",
+            _ => "SELECT `a ``b`, 'it''s' FROM t",
+        };
+        let written = dialect
+            .tokenize(sql)?
+            .iter()
+            .map(|token| dialect.token_to_string(token))
+            .collect::<String>();
+        assert_eq!(sql, written, "{:?}", dialect);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sql_dialect_plans() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+
+    // The same column, quoted in the style of each dialect.
+    let expect = plan(&ctx, SqlDialect::Generic, "SELECT number FROM numbers(3)")?;
+    let queries = [
+        (SqlDialect::Generic, "SELECT `number` FROM numbers(3)"),
+        (SqlDialect::Generic, "SELECT \"number\" FROM numbers(3)"),
+        (SqlDialect::MySQL, "SELECT `number` FROM numbers(3)"),
+        (SqlDialect::Ansi, "SELECT \"number\" FROM numbers(3)"),
+    ];
+    for (dialect, sql) in queries {
+        assert_eq!(expect, plan(&ctx, dialect, sql)?, "{:?} {}", dialect, sql);
+    }
+
+    // The double quotes are strings in mysql.
+    assert_eq!(
+        plan(
+            &ctx,
+            SqlDialect::MySQL,
+            "SELECT 'number', 'it''s' FROM numbers(3)"
+        )?,
+        plan(
+            &ctx,
+            SqlDialect::MySQL,
+            "SELECT \"number\", \"it\"\"s\" FROM numbers(3)"
+        )?,
+    );
+    assert_ne!(
+        plan(&ctx, SqlDialect::Ansi, "SELECT \"number\" FROM numbers(3)")?,
+        plan(&ctx, SqlDialect::MySQL, "SELECT \"number\" FROM numbers(3)")?,
+    );
+
+    // The backticks are not allowed in ansi.
+    let forbidden = plan(&ctx, SqlDialect::Ansi, "SELECT `number` FROM numbers(3)");
+    assert!(format!("{}", forbidden.unwrap_err()).contains(
+        "Backtick-quoted identifiers are not allowed in the ansi sql_dialect, quote them with double quotes, at Line: 1, Column 8"
+    ));
+
+    // The unterminated quotes, with their position.
+    let errors = [
+        (
+            SqlDialect::MySQL,
+            "SELECT 'number FROM numbers(3)",
+            "Unterminated string literal starting at Line: 1, Column 8",
+        ),
+        (
+            SqlDialect::MySQL,
+            "SELECT number\nFROM numbers(3) WHERE \"a",
+            "Unterminated string literal starting at Line: 2, Column 23",
+        ),
+        (
+            SqlDialect::Ansi,
+            "SELECT\n  \"number FROM numbers(3)",
+            "Unterminated quoted identifier starting at Line: 2, Column 3",
+        ),
+        (
+            SqlDialect::Generic,
+            "SELECT `num``ber FROM numbers(3)",
+            "Unterminated quoted identifier starting at Line: 1, Column 8",
+        ),
+    ];
+    for (dialect, sql, error) in errors {
+        let result = plan(&ctx, dialect, sql);
+        let message = format!("{}", result.unwrap_err());
+        assert!(
+            message.contains(error),
+            "{:?} {}: {}",
+            dialect,
+            sql,
+            message
+        );
+    }
+
+    // The statements of the connectors probing the server are the same whatever the dialect.
+    let probes = [
+        "SET NAMES utf8mb4",
+        "SET NAMES 'utf8mb4'",
+        "SET autocommit = 1",
+        "SET sql_mode = 'STRICT_TRANS_TABLES'",
+    ];
+    for sql in probes {
+        let parse = |dialect| plan(&ctx, dialect, sql).map_err(|cause| cause.message());
+        let expect = parse(SqlDialect::Generic);
+        assert_eq!(expect, parse(SqlDialect::MySQL), "{}", sql);
+        assert_eq!(expect, parse(SqlDialect::Ansi), "{}", sql);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sql_dialect_quoted_identifiers() -> Result<()> {
+    let ctx = crate::tests::try_create_context()?;
+    let execute = |sql: String| {
+        let ctx = ctx.clone();
+        async move {
+            let plan = PlanParser::create(ctx.clone()).build_from_sql(&sql)?;
+            let executor = InterpreterFactory::get(ctx, plan)?;
+            let stream = executor.execute().await?;
+            stream.try_collect::<Vec<_>>().await
+        }
+    };
+
+    // A column of a space and a quote in its name, created and selected in each dialect.
+    let tests = [
+        ("mysql", "`a b\"c`", "`it``s`"),
+        ("ansi", "\"a b\"\"c\"", "\"it's\""),
+        ("", "\"a b\"\"c\"", "`it``s`"),
+    ];
+    for (index, (dialect, column, table)) in tests.iter().enumerate() {
+        execute(format!("SET sql_dialect = '{}'", dialect)).await?;
+        assert_eq!(*dialect, ctx.get_settings().get_sql_dialect()?);
+
+        execute(format!("CREATE DATABASE db{} Engine = default", index)).await?;
+        execute(format!(
+            "CREATE TABLE db{}.{}({} Int64) Engine = Memory",
+            index, table, column
+        ))
+        .await?;
+        execute(format!("INSERT INTO db{}.{} VALUES(1),(2)", index, table)).await?;
+
+        let result = execute(format!("SELECT {} FROM db{}.{}", column, index, table)).await?;
+        let expected = vec![
+            "+-------+",
+            "| a b\"c |",
+            "+-------+",
+            "| 1     |",
+            "| 2     |",
+            "+-------+",
+        ];
+        assert_blocks_sorted_eq(expected, result.as_slice());
+    }
+
+    let unknown = execute("SET sql_dialect = 'postgres'".to_string()).await;
+    assert!(unknown.is_err());
+    assert_eq!("", ctx.get_settings().get_sql_dialect()?);
+
+    Ok(())
+}
@@ -19,6 +19,7 @@ use std::time::Instant;
 
 use common_exception::ErrorCode;
 use common_planners::ExplainType;
+use common_planners::TransactionKind;
 use metrics::histogram;
 use sqlparser::ast::BinaryOperator;
 use sqlparser::ast::ColumnDef;
@@ -45,14 +46,20 @@ use crate::sql::DfDropTable;
 use crate::sql::DfExplain;
 use crate::sql::DfHint;
 use crate::sql::DfKillStatement;
+use crate::sql::DfModifyColumn;
+use crate::sql::DfOptimizeTable;
 use crate::sql::DfShowCreateTable;
 use crate::sql::DfShowDatabases;
 use crate::sql::DfShowProcessList;
 use crate::sql::DfShowSettings;
 use crate::sql::DfShowTables;
+use crate::sql::DfShowWarnings;
 use crate::sql::DfStatement;
+use crate::sql::DfTransaction;
 use crate::sql::DfTruncateTable;
+use crate::sql::DfUndropTable;
 use crate::sql::DfUseDatabase;
+use crate::sql::SqlDialect;
 
 // Use `Parser::expected` instead, if possible
 macro_rules! parser_err {
@@ -77,10 +84,13 @@ impl<'a> DfParser<'a> {
     pub fn new_with_dialect(sql: &str, dialect: &'a dyn Dialect) -> Result<Self, ParserError> {
         let mut tokenizer = Tokenizer::new(dialect, sql);
         let tokens = tokenizer.tokenize()?;
+        Ok(DfParser::new_with_tokens(tokens, dialect))
+    }
 
-        Ok(DfParser {
+    fn new_with_tokens(tokens: Vec<Token>, dialect: &'a dyn Dialect) -> Self {
+        DfParser {
             parser: Parser::new(tokens, dialect),
-        })
+        }
     }
 
     /// Parse a SQL statement and produce a set of statements with dialect
@@ -92,12 +102,32 @@ impl<'a> DfParser<'a> {
         Ok(result)
     }
 
+    /// Parse a SQL statement with the quoting rules of the sql_dialect of the session
+    pub fn parse_sql_in_dialect(
+        sql: &str,
+        sql_dialect: SqlDialect,
+    ) -> Result<(Vec<DfStatement>, Vec<DfHint>), ErrorCode> {
+        let start = Instant::now();
+        let tokens = sql_dialect.tokenize(sql)?;
+        let result = DfParser::parse_tokens(tokens, &GenericDialect {})?;
+        histogram!(super::metrics::METRIC_PARSER_USEDTIME, start.elapsed());
+        Ok(result)
+    }
+
     /// Parse a SQL statement and produce a set of statements
     pub fn parse_sql_with_dialect(
         sql: &str,
         dialect: &dyn Dialect,
     ) -> Result<(Vec<DfStatement>, Vec<DfHint>), ParserError> {
-        let mut parser = DfParser::new_with_dialect(sql, dialect)?;
+        let tokens = Tokenizer::new(dialect, sql).tokenize()?;
+        DfParser::parse_tokens(tokens, dialect)
+    }
+
+    fn parse_tokens(
+        tokens: Vec<Token>,
+        dialect: &dyn Dialect,
+    ) -> Result<(Vec<DfStatement>, Vec<DfHint>), ParserError> {
+        let mut parser = DfParser::new_with_tokens(tokens.clone(), dialect);
         let mut stmts = Vec::new();
 
         let mut expecting_statement_delimiter = false;
@@ -120,15 +150,12 @@ impl<'a> DfParser<'a> {
         }
 
         let mut hints = Vec::new();
-
-        let mut parser = DfParser::new_with_dialect(sql, dialect)?;
-        loop {
-            let token = parser.parser.next_token_no_skip();
+        for token in tokens {
             match token {
-                Some(Token::Whitespace(Whitespace::SingleLineComment { comment, prefix })) => {
+                Token::Whitespace(Whitespace::SingleLineComment { comment, prefix }) => {
                     hints.push(DfHint::create_from_comment(comment, prefix));
                 }
-                Some(Token::Whitespace(Whitespace::Newline)) | Some(Token::EOF) | None => break,
+                Token::Whitespace(Whitespace::Newline) | Token::EOF => break,
                 _ => continue,
             }
         }
@@ -161,6 +188,10 @@ impl<'a> DfParser<'a> {
                         self.parser.next_token();
                         self.parse_drop()
                     }
+                    Keyword::ALTER => {
+                        self.parser.next_token();
+                        self.parse_alter()
+                    }
                     Keyword::EXPLAIN => {
                         self.parser.next_token();
                         self.parse_explain()
@@ -183,6 +214,9 @@ impl<'a> DfParser<'a> {
                                     Keyword::FROM | Keyword::IN => Ok(DfStatement::ShowTables(
                                         DfShowTables::FromOrIn(self.parser.parse_object_name()?),
                                     )),
+                                    _ if w.value.to_uppercase() == "HISTORY" => {
+                                        Ok(DfStatement::ShowTables(DfShowTables::History))
+                                    }
                                     _ => self.expected("like or where", tok),
                                 },
                                 _ => self.expected("like or where", tok),
@@ -195,6 +229,8 @@ impl<'a> DfParser<'a> {
                             self.parse_show_create()
                         } else if self.consume_token("PROCESSLIST") {
                             Ok(DfStatement::ShowProcessList(DfShowProcessList))
+                        } else if self.consume_token("WARNINGS") {
+                            Ok(DfStatement::ShowWarnings(DfShowWarnings))
                         } else {
                             self.expected("tables or settings", self.parser.peek_token())
                         }
@@ -203,10 +239,15 @@ impl<'a> DfParser<'a> {
                         self.parser.next_token();
                         self.parse_truncate()
                     }
+                    Keyword::BEGIN | Keyword::START | Keyword::COMMIT | Keyword::ROLLBACK => {
+                        self.parse_transaction()
+                    }
                     Keyword::NoKeyword => match w.value.to_uppercase().as_str() {
                         // Use database
                         "USE" => self.parse_use_database(),
                         "KILL" => self.parse_kill_query(),
+                        "UNDROP" => self.parse_undrop(),
+                        "OPTIMIZE" => self.parse_optimize(),
                         _ => self.expected("Keyword", self.parser.peek_token()),
                     },
                     _ => {
@@ -476,6 +517,31 @@ impl<'a> DfParser<'a> {
         }
     }
 
+    // Parse 'BEGIN', 'START TRANSACTION', 'COMMIT' and 'ROLLBACK'.
+    fn parse_transaction(&mut self) -> Result<DfStatement, ParserError> {
+        let kind = if self.consume_token("BEGIN") {
+            self.consume_token("WORK");
+            TransactionKind::Begin
+        } else if self.consume_token("START") {
+            self.parser.expect_keyword(Keyword::TRANSACTION)?;
+            // The transaction characteristics, e.g. READ ONLY, mean nothing to us.
+            while !matches!(self.parser.peek_token(), Token::EOF | Token::SemiColon) {
+                self.parser.next_token();
+            }
+            TransactionKind::Begin
+        } else if self.consume_token("COMMIT") {
+            self.consume_token("WORK");
+            TransactionKind::Commit
+        } else if self.consume_token("ROLLBACK") {
+            self.consume_token("WORK");
+            TransactionKind::Rollback
+        } else {
+            return self.expected("transaction statement", self.parser.peek_token());
+        };
+
+        Ok(DfStatement::Transaction(DfTransaction { kind }))
+    }
+
     fn parse_database_engine(&mut self) -> Result<String, ParserError> {
         // TODO make ENGINE as a keyword
         if !self.consume_token("ENGINE") {
@@ -497,13 +563,19 @@ impl<'a> DfParser<'a> {
         let mut table_properties = vec![];
 
         // parse table options: https://dev.mysql.com/doc/refman/8.0/en/create-table.html
-        if self.consume_token("LOCATION") {
+        // `name = value [[,] name = value ...]`, the names are kept as they are written.
+        while let Token::Word(w) = self.parser.peek_token() {
+            self.parser.next_token();
             self.parser.expect_token(&Token::Eq)?;
             let value = self.parse_value()?;
             table_properties.push(SqlOption {
-                name: Ident::new("LOCATION"),
+                name: Ident {
+                    value: w.value,
+                    quote_style: w.quote_style,
+                },
                 value,
-            })
+            });
+            self.parser.consume_token(&Token::Comma);
         }
 
         let create = DfCreateTable {
@@ -543,6 +615,48 @@ impl<'a> DfParser<'a> {
         }
     }
 
+    // Parse 'undrop table' table name.
+    fn parse_undrop(&mut self) -> Result<DfStatement, ParserError> {
+        if !self.consume_token("UNDROP") {
+            return self.expected("Must UNDROP", self.parser.peek_token());
+        }
+
+        match self.parser.next_token() {
+            Token::Word(w) => match w.keyword {
+                Keyword::TABLE => {
+                    let table_name = self.parser.parse_object_name()?;
+                    let undrop = DfUndropTable { name: table_name };
+                    Ok(DfStatement::UndropTable(undrop))
+                }
+                _ => self.expected("undrop statement", Token::Word(w)),
+            },
+            unexpected => self.expected("undrop statement", unexpected),
+        }
+    }
+
+    // Parse 'alter table <name> modify [column] <column definition>'.
+    fn parse_alter(&mut self) -> Result<DfStatement, ParserError> {
+        match self.parser.next_token() {
+            Token::Word(w) => match w.keyword {
+                Keyword::TABLE => {
+                    let table_name = self.parser.parse_object_name()?;
+                    if !self.consume_token("MODIFY") {
+                        return self.expected("MODIFY", self.parser.peek_token());
+                    }
+                    self.consume_token("COLUMN");
+                    let column = self.parse_column_def()?;
+                    let modify = DfModifyColumn {
+                        name: table_name,
+                        column,
+                    };
+                    Ok(DfStatement::ModifyColumn(modify))
+                }
+                _ => self.expected("alter statement", Token::Word(w)),
+            },
+            unexpected => self.expected("alter statement", unexpected),
+        }
+    }
+
     fn parse_truncate(&mut self) -> Result<DfStatement, ParserError> {
         match self.parser.next_token() {
             Token::Word(w) => match w.keyword {
@@ -557,6 +671,25 @@ impl<'a> DfParser<'a> {
         }
     }
 
+    // Parse 'optimize table' table name.
+    fn parse_optimize(&mut self) -> Result<DfStatement, ParserError> {
+        if !self.consume_token("OPTIMIZE") {
+            return self.expected("Must OPTIMIZE", self.parser.peek_token());
+        }
+
+        match self.parser.next_token() {
+            Token::Word(w) => match w.keyword {
+                Keyword::TABLE => {
+                    let table_name = self.parser.parse_object_name()?;
+                    let optimize = DfOptimizeTable { name: table_name };
+                    Ok(DfStatement::OptimizeTable(optimize))
+                }
+                _ => self.expected("optimize statement", Token::Word(w)),
+            },
+            unexpected => self.expected("optimize statement", unexpected),
+        }
+    }
+
     fn consume_token(&mut self, expected: &str) -> bool {
         if self.parser.peek_token().to_string().to_uppercase() == *expected.to_uppercase() {
             self.parser.next_token();
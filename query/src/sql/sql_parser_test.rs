@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use common_exception::Result;
+use common_planners::TransactionKind;
 use sqlparser::ast::*;
 
 use crate::sql::sql_statement::DfDropDatabase;
@@ -126,7 +127,7 @@ fn create_table() -> Result<()> {
         columns: vec![make_column_def("c1", DataType::Int)],
         engine: "CSV".to_string(),
         options: vec![SqlOption {
-            name: Ident::new("LOCATION".to_string()),
+            name: Ident::new("location".to_string()),
             value: Value::SingleQuotedString("/data/33.csv".into()),
         }],
     });
@@ -144,12 +145,36 @@ fn create_table() -> Result<()> {
         ],
         engine: "Parquet".to_string(),
         options: vec![SqlOption {
-            name: Ident::new("LOCATION".to_string()),
+            name: Ident::new("location".to_string()),
             value: Value::SingleQuotedString("foo.parquet".into()),
         }],
     });
     expect_parse_ok(sql, expected)?;
 
+    // the option names are kept as they are written
+    let sql = "CREATE TABLE t(c1 int) ENGINE = csv Location = '/data/33.csv', has_header = 'true' COMPRESSION = 'lz4'";
+    let expected = DfStatement::CreateTable(DfCreateTable {
+        if_not_exists: false,
+        name: ObjectName(vec![Ident::new("t")]),
+        columns: vec![make_column_def("c1", DataType::Int)],
+        engine: "csv".to_string(),
+        options: vec![
+            SqlOption {
+                name: Ident::new("Location".to_string()),
+                value: Value::SingleQuotedString("/data/33.csv".into()),
+            },
+            SqlOption {
+                name: Ident::new("has_header".to_string()),
+                value: Value::SingleQuotedString("true".into()),
+            },
+            SqlOption {
+                name: Ident::new("COMPRESSION".to_string()),
+                value: Value::SingleQuotedString("lz4".into()),
+            },
+        ],
+    });
+    expect_parse_ok(sql, expected)?;
+
     Ok(())
 }
 
@@ -205,6 +230,7 @@ fn show_queries() -> Result<()> {
     expect_parse_ok("SHOW TABLES", DfStatement::ShowTables(DfShowTables::All))?;
     expect_parse_ok("SHOW TABLES;", DfStatement::ShowTables(DfShowTables::All))?;
     expect_parse_ok("SHOW SETTINGS", DfStatement::ShowSettings(DfShowSettings))?;
+    expect_parse_ok("SHOW WARNINGS", DfStatement::ShowWarnings(DfShowWarnings))?;
     expect_parse_ok(
         "SHOW TABLES LIKE 'aaa'",
         DfStatement::ShowTables(DfShowTables::Like(Ident::with_quote('\'', "aaa"))),
@@ -264,6 +290,10 @@ fn show_tables_test() -> Result<()> {
         "SHOW TABLES IN `ss`",
         DfStatement::ShowTables(DfShowTables::FromOrIn(name_two)),
     )?;
+    expect_parse_ok(
+        "SHOW TABLES HISTORY",
+        DfStatement::ShowTables(DfShowTables::History),
+    )?;
     Ok(())
 }
 
@@ -285,6 +315,58 @@ fn use_database_test() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn undrop_table() -> Result<()> {
+    {
+        let sql = "UNDROP TABLE t1";
+        let expected = DfStatement::UndropTable(DfUndropTable {
+            name: ObjectName(vec![Ident::new("t1")]),
+        });
+        expect_parse_ok(sql, expected)?;
+    }
+
+    {
+        let sql = "UNDROP TABLE db1.t1";
+        let expected = DfStatement::UndropTable(DfUndropTable {
+            name: ObjectName(vec![Ident::new("db1"), Ident::new("t1")]),
+        });
+        expect_parse_ok(sql, expected)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn modify_column() -> Result<()> {
+    {
+        let sql = "ALTER TABLE t1 MODIFY COLUMN c1 BIGINT";
+        let expected = DfStatement::ModifyColumn(DfModifyColumn {
+            name: ObjectName(vec![Ident::new("t1")]),
+            column: make_column_def("c1", DataType::BigInt),
+        });
+        expect_parse_ok(sql, expected)?;
+    }
+
+    {
+        let sql = "ALTER TABLE db1.t1 MODIFY c1 BIGINT NULL";
+        let mut column = make_column_def("c1", DataType::BigInt);
+        column.options.push(ColumnOptionDef {
+            name: None,
+            option: ColumnOption::Null,
+        });
+        let expected = DfStatement::ModifyColumn(DfModifyColumn {
+            name: ObjectName(vec![Ident::new("db1"), Ident::new("t1")]),
+            column,
+        });
+        expect_parse_ok(sql, expected)?;
+    }
+
+    // Only MODIFY is supported.
+    assert!(DfParser::parse_sql("ALTER TABLE t1 ADD COLUMN c1 BIGINT").is_err());
+
+    Ok(())
+}
+
 #[test]
 fn truncate_table() -> Result<()> {
     {
@@ -298,6 +380,39 @@ fn truncate_table() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn optimize_table() -> Result<()> {
+    {
+        let sql = "OPTIMIZE TABLE db1.t1";
+        let expected = DfStatement::OptimizeTable(DfOptimizeTable {
+            name: ObjectName(vec![Ident::new("db1"), Ident::new("t1")]),
+        });
+        expect_parse_ok(sql, expected)?;
+    }
+
+    assert!(DfParser::parse_sql("OPTIMIZE t1").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn transaction() -> Result<()> {
+    let cases = [
+        ("BEGIN", TransactionKind::Begin),
+        ("BEGIN WORK", TransactionKind::Begin),
+        ("START TRANSACTION", TransactionKind::Begin),
+        ("START TRANSACTION READ ONLY", TransactionKind::Begin),
+        ("COMMIT", TransactionKind::Commit),
+        ("ROLLBACK WORK", TransactionKind::Rollback),
+    ];
+
+    for (sql, kind) in cases {
+        expect_parse_ok(sql, DfStatement::Transaction(DfTransaction { kind }))?;
+    }
+
+    Ok(())
+}
+
 #[test]
 fn hint_test() -> Result<()> {
     {
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use common_planners::ExplainType;
+use common_planners::TransactionKind;
 use nom::bytes::complete::tag;
 use nom::bytes::complete::take_till1;
 use nom::character::complete::digit1;
@@ -32,6 +33,8 @@ pub enum DfShowTables {
     Like(Ident),
     Where(Expr),
     FromOrIn(ObjectName),
+    /// The dropped tables which can be undropped.
+    History,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -45,6 +48,9 @@ pub struct DfShowSettings;
 #[derive(Debug, Clone, PartialEq)]
 pub struct DfShowProcessList;
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DfShowWarnings;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DfExplain {
     pub typ: ExplainType,
@@ -77,11 +83,27 @@ pub struct DfDropTable {
     pub name: ObjectName,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DfUndropTable {
+    pub name: ObjectName,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DfModifyColumn {
+    pub name: ObjectName,
+    pub column: ColumnDef,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DfTruncateTable {
     pub name: ObjectName,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DfOptimizeTable {
+    pub name: ObjectName,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DfCreateDatabase {
     pub if_not_exists: bool,
@@ -106,6 +128,11 @@ pub struct DfKillStatement {
     pub object_id: Ident,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DfTransaction {
+    pub kind: TransactionKind,
+}
+
 /// Tokens parsed by `DFParser` are converted into these values.
 #[derive(Debug, Clone, PartialEq)]
 pub enum DfStatement {
@@ -125,7 +152,10 @@ pub enum DfStatement {
     CreateTable(DfCreateTable),
     DescribeTable(DfDescribeTable),
     DropTable(DfDropTable),
+    UndropTable(DfUndropTable),
     TruncateTable(DfTruncateTable),
+    ModifyColumn(DfModifyColumn),
+    OptimizeTable(DfOptimizeTable),
 
     // Settings.
     ShowSettings(DfShowSettings),
@@ -133,9 +163,15 @@ pub enum DfStatement {
     // ProcessList
     ShowProcessList(DfShowProcessList),
 
+    // Warnings of the last statement
+    ShowWarnings(DfShowWarnings),
+
     // Kill
     KillQuery(DfKillStatement),
     KillConn(DfKillStatement),
+
+    // Transaction
+    Transaction(DfTransaction),
 }
 
 /// Comment hints from SQL.
@@ -12,13 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use common_exception::Result;
+use common_store_api_sdk::RpcStats;
 
 use crate::catalogs::impls::DatabaseCatalog;
 use crate::configs::Config;
 
 pub fn try_create_catalog() -> Result<DatabaseCatalog> {
     let conf = Config::default();
-    let catalog = DatabaseCatalog::try_create_with_config(conf.clone())?;
+    let catalog = DatabaseCatalog::try_create_with_config(conf, Arc::new(RpcStats::create()))?;
     Ok(catalog)
 }
@@ -29,3 +29,5 @@ pub use number::NumberTestData;
 pub use parquet::ParquetTestData;
 pub use parse_query::parse_query;
 pub use sessions::try_create_session_mgr;
+pub use sessions::try_create_session_mgr_with_clock;
+pub use sessions::try_create_session_mgr_with_conf;
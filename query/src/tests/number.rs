@@ -59,7 +59,9 @@ impl NumberTestData {
                 table_id: table_meta.meta_id(),
                 table_version: table_meta.meta_ver(),
                 table_schema: Arc::new(DataSchema::empty()),
-                table_args: Some(Expression::create_literal(DataValue::Int64(Some(numbers)))),
+                table_args: Some(vec![Expression::create_literal(DataValue::Int64(Some(
+                    numbers,
+                )))]),
                 projected_schema: Arc::new(DataSchema::empty()),
                 push_downs: Extras::default(),
             },
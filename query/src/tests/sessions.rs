@@ -15,6 +15,7 @@
 use std::env;
 
 use common_exception::Result;
+use common_runtime::SharedClock;
 
 use crate::clusters::Cluster;
 use crate::configs::Config;
@@ -23,15 +24,27 @@ use crate::sessions::SessionManagerRef;
 
 pub fn try_create_session_mgr(max_active_sessions: Option<u64>) -> Result<SessionManagerRef> {
     let mut conf = Config::default();
+    // Set max active session number if have.
+    if let Some(max) = max_active_sessions {
+        conf.query.max_active_sessions = max;
+    }
+
+    try_create_session_mgr_with_conf(conf)
+}
+
+pub fn try_create_session_mgr_with_conf(conf: Config) -> Result<SessionManagerRef> {
+    try_create_session_mgr_with_clock(conf, SharedClock::default())
+}
+
+pub fn try_create_session_mgr_with_clock(
+    mut conf: Config,
+    clock: SharedClock,
+) -> Result<SessionManagerRef> {
     // Setup log dir to the tests directory.
     conf.log.log_dir = env::current_dir()?
         .join("../tests/data/logs")
         .display()
         .to_string();
-    // Set max active session number if have.
-    if let Some(max) = max_active_sessions {
-        conf.query.max_active_sessions = max;
-    }
 
-    SessionManager::from_conf(conf, Cluster::empty())
+    SessionManager::from_conf_with_clock(conf, Cluster::empty(), clock)
 }
@@ -12,10 +12,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use axum::extract::Extension;
+use axum::response::Json;
+use serde::Serialize;
+
+use crate::configs::ConfigChange;
+use crate::configs::ConfigHandle;
 
-use crate::configs::Config;
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReloadConfigResult {
+    /// The dynamic settings changed by the reload.
+    pub changes: Vec<ConfigChange>,
+    /// Why nothing is applied, e.g., a static setting is changed.
+    pub error: Option<String>,
+}
+
+/// Dumps the effective config, including the reloaded dynamic settings.
+pub async fn config_handler(handle: Extension<Arc<ConfigHandle>>) -> String {
+    format!("{:?}", handle.0.get())
+}
 
-pub async fn config_handler(cfg: Extension<Config>) -> String {
-    format!("{:?}", cfg.0)
+/// Reads the config file again, the same as sending SIGHUP to the store.
+pub async fn reload_config_handler(
+    handle: Extension<Arc<ConfigHandle>>,
+) -> Json<ReloadConfigResult> {
+    let res = match handle.0.reload() {
+        Ok(changes) => ReloadConfigResult {
+            changes,
+            error: None,
+        },
+        Err(e) => ReloadConfigResult {
+            changes: vec![],
+            error: Some(e.message()),
+        },
+    };
+    Json(res)
 }
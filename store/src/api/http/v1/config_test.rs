@@ -29,12 +29,15 @@ async fn test_config() -> common_exception::Result<()> {
     use tower::ServiceExt;
 
     use crate::api::http::v1::config::config_handler;
-    use crate::configs::Config; // for `app.oneshot()`
+    use crate::configs::Config;
+    use crate::configs::ConfigHandle; // for `app.oneshot()`
 
     let conf = Config::empty();
     let cluster_router = Router::new()
         .route("/v1/config", get(config_handler))
-        .layer(AddExtensionLayer::new(conf.clone()));
+        .layer(AddExtensionLayer::new(std::sync::Arc::new(
+            ConfigHandle::create(conf.clone()),
+        )));
 
     let response = cluster_router
         .clone()
@@ -51,3 +54,74 @@ async fn test_config() -> common_exception::Result<()> {
     assert_eq!(response.status(), StatusCode::OK);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_reload_config() -> common_exception::Result<()> {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::handler::get;
+    use axum::handler::post;
+    use axum::http::Request;
+    use axum::http::StatusCode;
+    use axum::http::{self};
+    use axum::AddExtensionLayer;
+    use axum::Router;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    use crate::api::http::v1::config::config_handler;
+    use crate::api::http::v1::config::reload_config_handler;
+    use crate::configs::Config;
+    use crate::configs::ConfigHandle; // for `app.oneshot()`
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    let path = file.path().to_str().unwrap().to_string();
+    writeln!(file, "slow_apply_threshold_ms = 1000")?;
+
+    let conf = Config::load_from_toml_with_args(&path, vec!["databend-store"])?;
+    let handle = Arc::new(ConfigHandle::create(conf));
+    let router = Router::new()
+        .route("/v1/config", get(config_handler))
+        .route("/v1/reload_config", post(reload_config_handler))
+        .layer(AddExtensionLayer::new(handle.clone()));
+
+    std::fs::write(&path, "slow_apply_threshold_ms = 60000\n")?;
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/v1/reload_config")
+                .method(http::Method::POST)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert_eq!(
+        r#"{"changes":[{"name":"slow_apply_threshold_ms","old":"1000","new":"60000"}],"error":null}"#,
+        body
+    );
+
+    // The dump reflects the reloaded value.
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/v1/config")
+                .method(http::Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("slow_apply_threshold_ms: 60000"), "{}", body);
+    Ok(())
+}
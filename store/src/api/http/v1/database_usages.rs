@@ -0,0 +1,71 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::extract::Query;
+use serde::Serialize;
+
+use crate::api::http::v1::listing::list;
+use crate::api::http::v1::listing::ListingItem;
+use crate::api::http::v1::listing::ListingResponse;
+use crate::api::http::v1::listing::ListingSpec;
+use crate::metrics::DatabaseUsageRecorder;
+
+const DATABASE_USAGES_LISTING: ListingSpec = ListingSpec {
+    filterable: &["db", "quota"],
+    aliases: &[],
+};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DatabaseUsageItem {
+    pub db: String,
+    /// `None` for unlimited.
+    pub quota: Option<u64>,
+    pub used_bytes: u64,
+}
+
+impl ListingItem for DatabaseUsageItem {
+    fn key(&self) -> String {
+        self.db.clone()
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "db" => Some(self.db.clone()),
+            "quota" => Some(self.quota.map_or("none".to_string(), |q| q.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// Lists the databases by name, `quota=none` filters the unlimited ones.
+pub async fn database_usages_handler(
+    recorder: Extension<Arc<DatabaseUsageRecorder>>,
+    query: Query<HashMap<String, String>>,
+) -> ListingResponse<DatabaseUsageItem> {
+    let items = recorder
+        .0
+        .get_all()
+        .into_iter()
+        .map(|(db, usage)| DatabaseUsageItem {
+            db,
+            quota: usage.quota,
+            used_bytes: usage.used_bytes,
+        })
+        .collect();
+    list(items, &query.0, &DATABASE_USAGES_LISTING)
+}
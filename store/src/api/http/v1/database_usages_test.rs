@@ -0,0 +1,165 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::handler::get;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::http::{self};
+use axum::AddExtensionLayer;
+use axum::Router;
+use common_metatypes::DatabaseUsage;
+use common_runtime::tokio;
+use pretty_assertions::assert_eq;
+use tower::ServiceExt; // for `app.oneshot()`
+
+use crate::api::http::v1::database_usages::database_usages_handler;
+use crate::metrics::DatabaseUsageRecorder;
+
+#[tokio::test]
+async fn test_database_usages() -> common_exception::Result<()> {
+    let recorder = Arc::new(DatabaseUsageRecorder::create());
+    recorder.reset(vec![
+        ("db1".to_string(), DatabaseUsage {
+            quota: Some(100),
+            used_bytes: 30,
+        }),
+        ("db2".to_string(), DatabaseUsage {
+            quota: None,
+            used_bytes: 7,
+        }),
+    ]);
+    recorder.record("db2", None);
+
+    let router = Router::new()
+        .route("/v1/database_usages", get(database_usages_handler))
+        .layer(AddExtensionLayer::new(recorder));
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/v1/database_usages")
+                .method(http::Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert_eq!(
+        r#"{"items":[{"db":"db1","quota":100,"used_bytes":30}],"next_cursor":null,"total_estimate":1}"#,
+        body
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_database_usages_listing() -> common_exception::Result<()> {
+    let recorder = Arc::new(DatabaseUsageRecorder::create());
+    recorder.reset(
+        (0..250)
+            .map(|i| {
+                (format!("db{:03}", i), DatabaseUsage {
+                    quota: if i % 2 == 0 { Some(100) } else { None },
+                    used_bytes: i,
+                })
+            })
+            .collect(),
+    );
+
+    let router = Router::new()
+        .route("/v1/database_usages", get(database_usages_handler))
+        .layer(AddExtensionLayer::new(recorder));
+
+    // Page through all of them, without a duplicate or a gap.
+    let mut dbs = vec![];
+    let mut uri = "/v1/database_usages?limit=40".to_string();
+    loop {
+        let (status, body) = call(router.clone(), &uri).await;
+        assert_eq!(StatusCode::OK, status);
+        let page: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(250, page["total_estimate"]);
+        let items = page["items"].as_array().unwrap();
+        assert!(items.len() <= 40);
+        dbs.extend(
+            items
+                .iter()
+                .map(|item| item["db"].as_str().unwrap().to_string()),
+        );
+
+        match page["next_cursor"].as_str() {
+            None => break,
+            Some(cursor) => uri = format!("/v1/database_usages?limit=40&cursor={}", cursor),
+        }
+    }
+    let expected = (0..250).map(|i| format!("db{:03}", i)).collect::<Vec<_>>();
+    assert_eq!(expected, dbs);
+
+    // The unlimited ones, the last first.
+    let (status, body) = call(
+        router.clone(),
+        "/v1/database_usages?quota=none&order=desc&limit=2",
+    )
+    .await;
+    assert_eq!(StatusCode::OK, status);
+    let page: serde_json::Value = serde_json::from_str(&body)?;
+    assert_eq!(125, page["total_estimate"]);
+    assert_eq!(
+        serde_json::json!([
+            {"db": "db249", "quota": null, "used_bytes": 249},
+            {"db": "db247", "quota": null, "used_bytes": 247},
+        ]),
+        page["items"]
+    );
+
+    let (status, body) = call(router.clone(), "/v1/database_usages?limit=0").await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert_eq!(
+        r#"{"error":"invalid limit \"0\", expect a positive integer"}"#,
+        body
+    );
+
+    let (status, _) = call(router.clone(), "/v1/database_usages?used_bytes=7").await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+
+    Ok(())
+}
+
+async fn call<S, B>(router: S, uri: &str) -> (StatusCode, String)
+where
+    S: tower::Service<Request<Body>, Response = http::Response<B>>,
+    S::Error: std::fmt::Debug,
+    B: hyper::body::HttpBody,
+    B::Error: std::fmt::Debug,
+{
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .method(http::Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
@@ -0,0 +1,231 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use axum::response::Json;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use serde::Serialize;
+
+/// A page never holds more items than it, whatever `limit` asks for.
+pub const LISTING_MAX_LIMIT: usize = 1000;
+pub const LISTING_DEFAULT_LIMIT: usize = 100;
+
+const CURSOR_VERSION: &str = "v1:";
+
+/// How an endpoint lists its items, besides the common parameters
+/// `limit`, `cursor` and `order`.
+pub struct ListingSpec {
+    /// The fields the items can be filtered by, with `field=value`.
+    pub filterable: &'static [&'static str],
+    /// The legacy parameter names and the ones they stand for, accepted for one release.
+    pub aliases: &'static [(&'static str, &'static str)],
+}
+
+/// An item of a listing, ordered by its natural key, which is unique in the listing.
+pub trait ListingItem {
+    fn key(&self) -> String;
+
+    /// The value of a filterable field, compared with the value of the filter as a string.
+    fn field(&self, name: &str) -> Option<String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingParams {
+    pub limit: usize,
+    /// The key of the last item of the previous page, the page starts after it.
+    pub after: Option<String>,
+    pub order: ListingOrder,
+    pub filters: Vec<(String, String)>,
+}
+
+/// The envelope of a page of a listing.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Listing<T> {
+    pub items: Vec<T>,
+    /// Pass it as `cursor` for the next page, `None` on the last page.
+    pub next_cursor: Option<String>,
+    /// The items matching the filters, it changes if the items do between the pages.
+    pub total_estimate: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ListingError {
+    pub error: String,
+}
+
+pub type ListingResponse<T> =
+    std::result::Result<Json<Listing<T>>, (StatusCode, Json<ListingError>)>;
+
+impl ListingParams {
+    /// Parses the query parameters of a listing, the unknown ones are rejected.
+    pub fn parse(query: &HashMap<String, String>, spec: &ListingSpec) -> Result<ListingParams> {
+        let mut params = ListingParams {
+            limit: LISTING_DEFAULT_LIMIT,
+            after: None,
+            order: ListingOrder::Asc,
+            filters: vec![],
+        };
+
+        // Sorted, so the error of a bad request does not depend on the hash order.
+        let mut query = query.iter().collect::<Vec<_>>();
+        query.sort();
+
+        for (name, value) in query {
+            let name = spec
+                .aliases
+                .iter()
+                .find(|(legacy, _)| *legacy == name.as_str())
+                .map_or(name.as_str(), |(_, name)| *name);
+
+            match name {
+                "limit" => params.limit = parse_limit(value)?,
+                "cursor" => params.after = Some(decode_cursor(value)?),
+                "order" => params.order = parse_order(value)?,
+                _ if spec.filterable.contains(&name) => {
+                    params.filters.push((name.to_string(), value.to_string()))
+                }
+                _ => {
+                    return Err(ErrorCode::BadArguments(format!(
+                        "unknown listing parameter {:?}, expect limit, cursor, order or one of the filterable fields {:?}",
+                        name, spec.filterable
+                    )))
+                }
+            }
+        }
+
+        Ok(params)
+    }
+}
+
+/// Filters, orders and pages `items` by `params`.
+pub fn paginate<T: ListingItem>(items: Vec<T>, params: &ListingParams) -> Listing<T> {
+    let mut items = items
+        .into_iter()
+        .filter(|item| {
+            params
+                .filters
+                .iter()
+                .all(|(name, value)| item.field(name).as_deref() == Some(value.as_str()))
+        })
+        .map(|item| (item.key(), item))
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    if params.order == ListingOrder::Desc {
+        items.reverse();
+    }
+    let total_estimate = items.len();
+
+    let start = match &params.after {
+        None => 0,
+        Some(after) => items
+            .iter()
+            .position(|(key, _)| match params.order {
+                ListingOrder::Asc => key > after,
+                ListingOrder::Desc => key < after,
+            })
+            .unwrap_or(items.len()),
+    };
+
+    let mut page = items
+        .into_iter()
+        .skip(start)
+        .take(params.limit + 1)
+        .collect::<Vec<_>>();
+    let next_cursor = match page.len() > params.limit {
+        true => {
+            page.truncate(params.limit);
+            page.last().map(|(key, _)| encode_cursor(key))
+        }
+        false => None,
+    };
+
+    Listing {
+        items: page.into_iter().map(|(_, item)| item).collect(),
+        next_cursor,
+        total_estimate,
+    }
+}
+
+/// Lists `items` by the query parameters, a bad parameter is answered with 400.
+pub fn list<T: ListingItem>(
+    items: Vec<T>,
+    query: &HashMap<String, String>,
+    spec: &ListingSpec,
+) -> ListingResponse<T> {
+    match ListingParams::parse(query, spec) {
+        Ok(params) => Ok(Json(paginate(items, &params))),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ListingError { error: e.message() }),
+        )),
+    }
+}
+
+pub fn parse_limit(value: &str) -> Result<usize> {
+    match value.parse::<usize>() {
+        Ok(0) | Err(_) => Err(ErrorCode::BadArguments(format!(
+            "invalid limit {:?}, expect a positive integer",
+            value
+        ))),
+        Ok(limit) => Ok(limit.min(LISTING_MAX_LIMIT)),
+    }
+}
+
+fn parse_order(value: &str) -> Result<ListingOrder> {
+    match value.to_lowercase().as_str() {
+        "asc" => Ok(ListingOrder::Asc),
+        "desc" => Ok(ListingOrder::Desc),
+        _ => Err(ErrorCode::BadArguments(format!(
+            "invalid order {:?}, expect asc or desc",
+            value
+        ))),
+    }
+}
+
+/// The cursor is the key of the last item, so it is still valid after a restart.
+pub fn encode_cursor(key: &str) -> String {
+    format!("{}{}", CURSOR_VERSION, key)
+        .as_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<String> {
+    let invalid = || ErrorCode::BadArguments(format!("invalid cursor {:?}", cursor));
+
+    if cursor.len() % 2 != 0 || !cursor.is_ascii() {
+        return Err(invalid());
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+    let decoded = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+    decoded
+        .strip_prefix(CURSOR_VERSION)
+        .map(|key| key.to_string())
+        .ok_or_else(invalid)
+}
@@ -0,0 +1,217 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use pretty_assertions::assert_eq;
+
+use crate::api::http::v1::listing::decode_cursor;
+use crate::api::http::v1::listing::encode_cursor;
+use crate::api::http::v1::listing::paginate;
+use crate::api::http::v1::listing::ListingItem;
+use crate::api::http::v1::listing::ListingOrder;
+use crate::api::http::v1::listing::ListingParams;
+use crate::api::http::v1::listing::ListingSpec;
+use crate::api::http::v1::listing::LISTING_MAX_LIMIT;
+
+const OPERATIONS_LISTING: ListingSpec = ListingSpec {
+    filterable: &["kind"],
+    aliases: &[("max_items", "limit"), ("type", "kind")],
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Operation {
+    id: String,
+    kind: String,
+}
+
+impl ListingItem for Operation {
+    fn key(&self) -> String {
+        self.id.clone()
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "kind" => Some(self.kind.clone()),
+            _ => None,
+        }
+    }
+}
+
+fn operations(n: usize) -> Vec<Operation> {
+    (0..n)
+        .map(|i| Operation {
+            id: format!("op-{:05}", i),
+            kind: if i % 3 == 0 { "compact" } else { "append" }.to_string(),
+        })
+        .collect()
+}
+
+fn query(params: &[(&str, &str)]) -> HashMap<String, String> {
+    params
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Lists all the pages, returns the ids in the order they are listed.
+fn list_all(items: &[Operation], params: &[(&str, &str)]) -> Result<Vec<String>> {
+    let mut ids = vec![];
+    let mut cursor = None;
+    loop {
+        let mut query = query(params);
+        if let Some(cursor) = cursor {
+            query.insert("cursor".to_string(), cursor);
+        }
+        let params = ListingParams::parse(&query, &OPERATIONS_LISTING)?;
+        let page = paginate(items.to_vec(), &params);
+        assert!(page.items.len() <= params.limit);
+        ids.extend(page.items.into_iter().map(|op| op.id));
+
+        match page.next_cursor {
+            None => return Ok(ids),
+            Some(next) => cursor = Some(next),
+        }
+    }
+}
+
+#[test]
+fn test_listing_pages() -> Result<()> {
+    let items = operations(2500);
+    let mut expected = items.iter().map(|op| op.id.clone()).collect::<Vec<_>>();
+
+    // Neither a duplicate nor a gap between the pages.
+    let ids = list_all(&items, &[("limit", "70")])?;
+    assert_eq!(expected, ids);
+
+    expected.reverse();
+    let ids = list_all(&items, &[("limit", "70"), ("order", "desc")])?;
+    assert_eq!(expected, ids);
+
+    // The cursor is the key, so the pages go on if the items change in between.
+    let params = ListingParams::parse(&query(&[("limit", "10")]), &OPERATIONS_LISTING)?;
+    let page = paginate(items.clone(), &params);
+    let mut changed = items[5..].to_vec();
+    changed.push(Operation {
+        id: "op-00009a".to_string(),
+        kind: "append".to_string(),
+    });
+    let query = query(&[("limit", "2"), ("cursor", &page.next_cursor.unwrap())]);
+    let params = ListingParams::parse(&query, &OPERATIONS_LISTING)?;
+    let page = paginate(changed, &params);
+    let ids = page
+        .items
+        .iter()
+        .map(|op| op.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["op-00009a", "op-00010"], ids);
+
+    Ok(())
+}
+
+#[test]
+fn test_listing_limit_cap() -> Result<()> {
+    let items = operations(2500);
+
+    let params = ListingParams::parse(&query(&[("limit", "100000")]), &OPERATIONS_LISTING)?;
+    assert_eq!(LISTING_MAX_LIMIT, params.limit);
+    let page = paginate(items.clone(), &params);
+    assert_eq!(LISTING_MAX_LIMIT, page.items.len());
+    assert_eq!(2500, page.total_estimate);
+    assert!(page.next_cursor.is_some());
+
+    let ids = list_all(&items, &[("limit", "100000")])?;
+    assert_eq!(2500, ids.iter().collect::<HashSet<_>>().len());
+
+    for limit in ["0", "-1", "ten"] {
+        let res = ListingParams::parse(&query(&[("limit", limit)]), &OPERATIONS_LISTING);
+        assert_eq!(ErrorCode::BadArguments("").code(), res.unwrap_err().code());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_listing_filter() -> Result<()> {
+    let items = operations(100);
+
+    let params = ListingParams::parse(&query(&[("kind", "compact")]), &OPERATIONS_LISTING)?;
+    assert_eq!(
+        vec![("kind".to_string(), "compact".to_string())],
+        params.filters
+    );
+    let page = paginate(items.clone(), &params);
+    assert_eq!(34, page.total_estimate);
+    assert!(page.items.iter().all(|op| op.kind == "compact"));
+
+    let ids = list_all(&items, &[("kind", "append"), ("limit", "7")])?;
+    assert_eq!(66, ids.len());
+
+    // Only the declared fields can be filtered by.
+    let res = ListingParams::parse(&query(&[("id", "op-00001")]), &OPERATIONS_LISTING);
+    assert_eq!(ErrorCode::BadArguments("").code(), res.unwrap_err().code());
+
+    Ok(())
+}
+
+#[test]
+fn test_listing_legacy_params() -> Result<()> {
+    let params = ListingParams::parse(
+        &query(&[("max_items", "5"), ("type", "compact"), ("order", "DESC")]),
+        &OPERATIONS_LISTING,
+    )?;
+    assert_eq!(
+        ListingParams {
+            limit: 5,
+            after: None,
+            order: ListingOrder::Desc,
+            filters: vec![("kind".to_string(), "compact".to_string())],
+        },
+        params
+    );
+
+    let page = paginate(operations(100), &params);
+    let ids = page
+        .items
+        .iter()
+        .map(|op| op.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec!["op-00099", "op-00096", "op-00093", "op-00090", "op-00087"],
+        ids
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_listing_cursor() -> Result<()> {
+    let cursor = encode_cursor("db/tb1");
+    assert_eq!("db/tb1", decode_cursor(&cursor)?);
+
+    for cursor in ["", "7", "zz", "db/tb1", &encode_cursor("db")[6..]] {
+        let res = decode_cursor(cursor);
+        assert_eq!(
+            ErrorCode::BadArguments("").code(),
+            res.unwrap_err().code(),
+            "{:?}",
+            cursor
+        );
+    }
+
+    Ok(())
+}
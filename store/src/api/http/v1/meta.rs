@@ -0,0 +1,261 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only views of the meta state of the local meta node, read from its state machine, which
+//! may not have the latest writes.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use async_raft::LogId;
+use axum::extract::Extension;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::Json;
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow_flight::FlightData;
+use common_datavalues::DataSchema;
+use common_exception::ErrorCode;
+use metasrv::meta_service::MetaNode;
+use serde::Serialize;
+
+use crate::api::http::v1::listing::encode_cursor;
+use crate::api::http::v1::listing::list;
+use crate::api::http::v1::listing::Listing;
+use crate::api::http::v1::listing::ListingError;
+use crate::api::http::v1::listing::ListingItem;
+use crate::api::http::v1::listing::ListingOrder;
+use crate::api::http::v1::listing::ListingParams;
+use crate::api::http::v1::listing::ListingResponse;
+use crate::api::http::v1::listing::ListingSpec;
+use crate::api::MetaNodeHandle;
+
+pub type MetaResponse<T> = std::result::Result<Json<T>, (StatusCode, Json<ListingError>)>;
+
+const DATABASES_LISTING: ListingSpec = ListingSpec {
+    filterable: &["engine"],
+    aliases: &[],
+};
+
+const TABLES_LISTING: ListingSpec = ListingSpec {
+    filterable: &["engine"],
+    aliases: &[],
+};
+
+/// `prefix` is not matched as a field, it bounds the scan of the records.
+const KV_LISTING: ListingSpec = ListingSpec {
+    filterable: &["prefix"],
+    aliases: &[],
+};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DatabaseItem {
+    pub name: String,
+    pub id: u64,
+    pub engine: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TableItem {
+    pub name: String,
+    pub id: u64,
+    pub engine: String,
+    pub schema: DataSchema,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct KVItem {
+    pub key: String,
+    pub seq: u64,
+    /// The value as a UTF-8 string, the invalid bytes are replaced.
+    pub value: String,
+    pub expire_at: Option<u64>,
+}
+
+impl ListingItem for DatabaseItem {
+    fn key(&self) -> String {
+        self.name.clone()
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "engine" => Some(self.engine.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl ListingItem for TableItem {
+    fn key(&self) -> String {
+        self.name.clone()
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "engine" => Some(self.engine.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RaftStatus {
+    pub id: u64,
+    /// One of `Leader`, `Follower`, `Candidate`, `NonVoter` and `Shutdown`.
+    pub role: String,
+    pub current_term: u64,
+    pub current_leader: Option<u64>,
+    pub last_log_index: u64,
+    pub last_applied: LogId,
+}
+
+/// Lists the databases by name.
+pub async fn databases_handler(
+    handle: Extension<Arc<MetaNodeHandle>>,
+    query: Query<HashMap<String, String>>,
+) -> ListingResponse<DatabaseItem> {
+    let mn = meta_node(&handle.0)?;
+    let dbs = mn
+        .list_databases()
+        .await
+        .into_iter()
+        .map(|(name, db)| DatabaseItem {
+            name,
+            id: db.database_id,
+            engine: db.database_engine,
+        })
+        .collect();
+    list(dbs, &query.0, &DATABASES_LISTING)
+}
+
+/// Lists the tables of a database by name, 404 if there is no such database.
+pub async fn tables_handler(
+    handle: Extension<Arc<MetaNodeHandle>>,
+    db_name: Path<String>,
+    query: Query<HashMap<String, String>>,
+) -> ListingResponse<TableItem> {
+    let mn = meta_node(&handle.0)?;
+    let db = mn.get_database(&db_name.0).await.ok_or_else(|| {
+        let e = ErrorCode::UnknownDatabase(format!("unknown database {:?}", db_name.0));
+        error_response(StatusCode::NOT_FOUND, e)
+    })?;
+
+    let mut tables = vec![];
+    for (name, table_id) in db.tables {
+        // Dropped in between.
+        let table = match mn.get_table(&table_id).await {
+            None => continue,
+            Some(table) => table,
+        };
+        let schema = ArrowSchema::try_from(&FlightData {
+            data_header: table.schema,
+            ..Default::default()
+        })
+        .map_err(|e| {
+            let e = ErrorCode::IllegalSchema(format!("invalid schema of {}: {}", name, e));
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, e)
+        })?;
+
+        tables.push(TableItem {
+            name,
+            id: table.table_id,
+            engine: table.table_engine,
+            schema: schema.into(),
+        });
+    }
+    list(tables, &query.0, &TABLES_LISTING)
+}
+
+/// Lists the records under `prefix` by key, a page is read from the state machine without
+/// loading the others, thus only the ascending order is served.
+pub async fn kv_list_handler(
+    handle: Extension<Arc<MetaNodeHandle>>,
+    query: Query<HashMap<String, String>>,
+) -> ListingResponse<KVItem> {
+    let params = ListingParams::parse(&query.0, &KV_LISTING)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?;
+    if params.order == ListingOrder::Desc {
+        let e = ErrorCode::BadArguments("the records are listed by key in ascending order only");
+        return Err(error_response(StatusCode::BAD_REQUEST, e));
+    }
+    let prefix = params
+        .filters
+        .iter()
+        .find(|(name, _)| name == "prefix")
+        .map_or("", |(_, prefix)| prefix.as_str());
+
+    let mn = meta_node(&handle.0)?;
+    let internal_error = |e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e);
+    let page = mn
+        .prefix_list_kv_page(prefix, Some(params.limit), params.after.as_deref())
+        .await
+        .map_err(internal_error)?;
+    let total_estimate = mn.prefix_count_kv(prefix).await.map_err(internal_error)?;
+
+    let next_cursor = match page.more {
+        true => page.entries.last().map(|(key, _)| encode_cursor(key)),
+        false => None,
+    };
+    let items = page
+        .entries
+        .into_iter()
+        .map(|(key, (seq, kv_value))| KVItem {
+            key,
+            seq,
+            value: String::from_utf8_lossy(&kv_value.value).to_string(),
+            expire_at: kv_value.meta.and_then(|meta| meta.expire_at),
+        })
+        .collect();
+    Ok(Json(Listing {
+        items,
+        next_cursor,
+        total_estimate,
+    }))
+}
+
+pub async fn raft_status_handler(
+    handle: Extension<Arc<MetaNodeHandle>>,
+) -> MetaResponse<RaftStatus> {
+    let mn = meta_node(&handle.0)?;
+    let metrics = mn.metrics_rx.borrow().clone();
+    let last_applied = mn
+        .get_last_applied()
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    Ok(Json(RaftStatus {
+        id: metrics.id,
+        role: format!("{:?}", metrics.state),
+        current_term: metrics.current_term,
+        current_leader: metrics.current_leader,
+        last_log_index: metrics.last_log_index,
+        last_applied,
+    }))
+}
+
+/// 503 before the meta node is started or after it is stopped.
+fn meta_node(
+    handle: &MetaNodeHandle,
+) -> std::result::Result<Arc<MetaNode>, (StatusCode, Json<ListingError>)> {
+    handle.get().ok_or_else(|| {
+        let e = ErrorCode::MetaServiceUnavailable("the meta node is not running");
+        error_response(StatusCode::SERVICE_UNAVAILABLE, e)
+    })
+}
+
+fn error_response(status: StatusCode, e: ErrorCode) -> (StatusCode, Json<ListingError>) {
+    (status, Json(ListingError { error: e.message() }))
+}
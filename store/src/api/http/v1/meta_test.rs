@@ -0,0 +1,207 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::handler::get;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::http::{self};
+use axum::AddExtensionLayer;
+use axum::Router;
+use common_datavalues::DataField;
+use common_datavalues::DataSchema;
+use common_datavalues::DataType;
+use common_metatypes::MatchSeq;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_runtime::tokio;
+use common_store_api_sdk::KVApi;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StoreClient;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use serde_json::Value;
+use tower::ServiceExt; // for `app.oneshot()`
+
+use crate::api::http::v1::meta::databases_handler;
+use crate::api::http::v1::meta::kv_list_handler;
+use crate::api::http::v1::meta::raft_status_handler;
+use crate::api::http::v1::meta::tables_handler;
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+use crate::tests::stop_store_server;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_http_meta_state() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    let router = Router::new()
+        .route("/v1/meta/databases", get(databases_handler))
+        .route("/v1/meta/databases/:db/tables", get(tables_handler))
+        .route("/v1/kv/list", get(kv_list_handler))
+        .route("/v1/raft/status", get(raft_status_handler))
+        .layer(AddExtensionLayer::new(tc.meta_node_handle.clone()));
+
+    // Not started yet.
+    let (status, _) = call(router.clone(), "/v1/raft/status").await;
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, status);
+
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            table: "tb1".to_string(),
+            schema: Arc::new(DataSchema::new(vec![DataField::new(
+                "number",
+                DataType::UInt64,
+                false,
+            )])),
+            options: Default::default(),
+            engine: "JSON".to_string(),
+        })
+        .await?;
+    for key in ["meta_test/a", "meta_test/b", "other"] {
+        client
+            .upsert_kv(key, MatchSeq::Any, Some(b"v".to_vec()), None)
+            .await?;
+    }
+
+    let (status, body) = call(router.clone(), "/v1/meta/databases").await;
+    assert_eq!(StatusCode::OK, status);
+    let dbs: Value = serde_json::from_str(&body)?;
+    let db1 = dbs["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|db| db["name"] == "db1")
+        .unwrap();
+    assert_eq!("Local", db1["engine"]);
+    let total = dbs["total_estimate"].as_u64().unwrap();
+    assert!(total >= 1);
+
+    // Paged by name.
+    let (status, body) = call(router.clone(), "/v1/meta/databases?limit=1").await;
+    assert_eq!(StatusCode::OK, status);
+    let page: Value = serde_json::from_str(&body)?;
+    assert_eq!(1, page["items"].as_array().unwrap().len());
+    assert_eq!(json!(total), page["total_estimate"]);
+    assert_eq!(total > 1, page["next_cursor"].is_string());
+
+    let (status, body) = call(router.clone(), "/v1/meta/databases?engine=Local").await;
+    assert_eq!(StatusCode::OK, status);
+    let page: Value = serde_json::from_str(&body)?;
+    for db in page["items"].as_array().unwrap() {
+        assert_eq!("Local", db["engine"]);
+    }
+
+    let (status, body) = call(router.clone(), "/v1/meta/databases/db1/tables").await;
+    assert_eq!(StatusCode::OK, status);
+    let tables: Value = serde_json::from_str(&body)?;
+    assert_eq!(1, tables["items"].as_array().unwrap().len());
+    assert_eq!(json!(1), tables["total_estimate"]);
+    assert_eq!(Value::Null, tables["next_cursor"]);
+    assert_eq!("tb1", tables["items"][0]["name"]);
+    assert_eq!("JSON", tables["items"][0]["engine"]);
+    assert!(body.contains(r#""number""#), "{}", body);
+
+    let (status, body) = call(router.clone(), "/v1/meta/databases/db1/tables?engine=Fuse").await;
+    assert_eq!(StatusCode::OK, status);
+    let tables: Value = serde_json::from_str(&body)?;
+    assert_eq!(0, tables["items"].as_array().unwrap().len());
+
+    let (status, body) = call(router.clone(), "/v1/meta/databases/unknown/tables").await;
+    assert_eq!(StatusCode::NOT_FOUND, status);
+    assert!(body.contains("unknown"), "{}", body);
+
+    let (status, body) = call(router.clone(), "/v1/kv/list?prefix=meta_test/&limit=1").await;
+    assert_eq!(StatusCode::OK, status);
+    let page: Value = serde_json::from_str(&body)?;
+    assert_eq!(json!(2), page["total_estimate"]);
+    assert_eq!(1, page["items"].as_array().unwrap().len());
+    assert_eq!("meta_test/a", page["items"][0]["key"]);
+    assert_eq!("v", page["items"][0]["value"]);
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+
+    let uri = format!("/v1/kv/list?prefix=meta_test/&limit=1&cursor={}", cursor);
+    let (status, body) = call(router.clone(), &uri).await;
+    assert_eq!(StatusCode::OK, status);
+    let page: Value = serde_json::from_str(&body)?;
+    assert_eq!(Value::Null, page["next_cursor"]);
+    assert_eq!(1, page["items"].as_array().unwrap().len());
+    assert_eq!("meta_test/b", page["items"][0]["key"]);
+
+    for uri in [
+        "/v1/kv/list?key=other",
+        "/v1/kv/list?after=meta_test/a",
+        "/v1/kv/list?order=desc",
+        "/v1/kv/list?cursor=zz",
+        "/v1/meta/databases?limit=0",
+    ] {
+        let (status, _) = call(router.clone(), uri).await;
+        assert_eq!(StatusCode::BAD_REQUEST, status, "{}", uri);
+    }
+
+    let (status, body) = call(router.clone(), "/v1/raft/status").await;
+    assert_eq!(StatusCode::OK, status);
+    let raft: Value = serde_json::from_str(&body)?;
+    assert_eq!("Leader", raft["role"]);
+    let last_applied = raft["last_applied"]["index"].as_u64().unwrap();
+    assert!(last_applied > 0);
+    assert!(raft["last_log_index"].as_u64().unwrap() >= last_applied);
+
+    stop_store_server(&mut tc).await?;
+    let (status, _) = call(router.clone(), "/v1/meta/databases").await;
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, status);
+
+    Ok(())
+}
+
+async fn call<S, B>(router: S, uri: &str) -> (StatusCode, String)
+where
+    S: tower::Service<Request<Body>, Response = http::Response<B>>,
+    S::Error: std::fmt::Debug,
+    B: hyper::body::HttpBody,
+    B::Error: std::fmt::Debug,
+{
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .method(http::Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
@@ -15,6 +15,15 @@
 pub mod config;
 #[cfg(test)]
 mod config_test;
+pub mod database_usages;
+#[cfg(test)]
+mod database_usages_test;
 pub mod health;
 #[cfg(test)]
 mod health_test;
+pub mod listing;
+#[cfg(test)]
+mod listing_test;
+pub mod meta;
+#[cfg(test)]
+mod meta_test;
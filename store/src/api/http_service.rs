@@ -12,24 +12,68 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use axum::handler::get;
+use axum::handler::post;
 use axum::AddExtensionLayer;
 use axum::Router;
 use common_exception::Result;
+use common_tracing::log_level_handler::LogLevelExtension;
 
 // use crate::api::http::router::Router;
+use crate::api::MetaNodeHandle;
 use crate::configs::Config;
+use crate::configs::ConfigHandle;
+use crate::metrics::DatabaseUsageRecorder;
 
 pub struct HttpService {
     cfg: Config,
+    config_handle: Arc<ConfigHandle>,
+    usage_recorder: Arc<DatabaseUsageRecorder>,
+    log_level: LogLevelExtension,
+    meta_node_handle: Arc<MetaNodeHandle>,
 }
 
 // build axum router
 macro_rules! build_router {
-    ($cfg: expr) => {
+    ($config_handle: expr, $usage_recorder: expr, $log_level: expr, $meta_node_handle: expr) => {
         Router::new()
             .route("/v1/health", get(super::http::v1::health::health_handler))
             .route("/v1/config", get(super::http::v1::config::config_handler))
+            .route(
+                "/v1/reload_config",
+                post(super::http::v1::config::reload_config_handler),
+            )
+            .route(
+                "/v1/database_usages",
+                get(super::http::v1::database_usages::database_usages_handler),
+            )
+            .route(
+                "/v1/meta/databases",
+                get(super::http::v1::meta::databases_handler),
+            )
+            .route(
+                "/v1/meta/databases/:db/tables",
+                get(super::http::v1::meta::tables_handler),
+            )
+            .route("/v1/kv/list", get(super::http::v1::meta::kv_list_handler))
+            .route(
+                "/v1/raft/status",
+                get(super::http::v1::meta::raft_status_handler),
+            )
+            .route(
+                "/v1/get_log_level",
+                get(common_tracing::log_level_handler::get_log_level_handler),
+            )
+            .route(
+                "/v1/set_log_level",
+                post(common_tracing::log_level_handler::set_log_level_handler),
+            )
+            .route(
+                "/v1/revert_log_level",
+                post(common_tracing::log_level_handler::revert_log_level_handler),
+            )
             .route(
                 "/debug/home",
                 get(super::http::debug::home::debug_home_handler),
@@ -38,17 +82,52 @@ macro_rules! build_router {
                 "/debug/pprof/profile",
                 get(super::http::debug::pprof::debug_pprof_handler),
             )
-            .layer(AddExtensionLayer::new($cfg.clone()))
+            .layer(AddExtensionLayer::new($config_handle.clone()))
+            .layer(AddExtensionLayer::new($usage_recorder.clone()))
+            .layer(AddExtensionLayer::new($log_level.clone()))
+            .layer(AddExtensionLayer::new($meta_node_handle.clone()))
     };
 }
 
 impl HttpService {
     pub fn create(cfg: Config) -> Box<Self> {
-        Box::new(HttpService { cfg })
+        Box::new(HttpService {
+            config_handle: Arc::new(ConfigHandle::create(cfg.clone())),
+            cfg,
+            usage_recorder: Arc::new(DatabaseUsageRecorder::create()),
+            log_level: LogLevelExtension(common_tracing::global_log_level()),
+            meta_node_handle: Arc::new(MetaNodeHandle::create()),
+        })
+    }
+
+    /// Serves the config of `handle`, and reloads it on the `reload_config` admin action.
+    pub fn with_config_handle(mut self: Box<Self>, handle: Arc<ConfigHandle>) -> Box<Self> {
+        self.config_handle = handle;
+        self
+    }
+
+    /// Serves the usages of the databases reported by the flight service to `recorder`.
+    pub fn with_usage_recorder(
+        mut self: Box<Self>,
+        recorder: Arc<DatabaseUsageRecorder>,
+    ) -> Box<Self> {
+        self.usage_recorder = recorder;
+        self
+    }
+
+    /// Serves the meta state of the meta node the store server shares through `handle`.
+    pub fn with_meta_node_handle(mut self: Box<Self>, handle: Arc<MetaNodeHandle>) -> Box<Self> {
+        self.meta_node_handle = handle;
+        self
     }
 
     pub async fn start(&mut self) -> Result<()> {
-        let app = build_router!(self.cfg.clone());
+        let app = build_router!(
+            self.config_handle,
+            self.usage_recorder,
+            self.log_level,
+            self.meta_node_handle
+        );
 
         let conf = self.cfg.clone();
         let tls_cert = conf.tls_server_cert;
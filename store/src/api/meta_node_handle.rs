@@ -0,0 +1,39 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_infallible::RwLock;
+use metasrv::meta_service::MetaNode;
+
+/// The meta node the store server runs, shared with the services started before it, e.g., the
+/// http api. It is none before the meta node is started or after it is stopped.
+#[derive(Default)]
+pub struct MetaNodeHandle {
+    meta_node: RwLock<Option<Arc<MetaNode>>>,
+}
+
+impl MetaNodeHandle {
+    pub fn create() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Option<Arc<MetaNode>> {
+        self.meta_node.read().clone()
+    }
+
+    pub(crate) fn set(&self, meta_node: Option<Arc<MetaNode>>) {
+        *self.meta_node.write() = meta_node;
+    }
+}
@@ -18,8 +18,10 @@ mod http;
 mod http_service;
 #[cfg(test)]
 mod http_service_test;
+mod meta_node_handle;
 pub mod rpc;
 mod rpc_service;
 
 pub use http_service::HttpService;
+pub use meta_node_handle::MetaNodeHandle;
 pub use rpc_service::StoreServer;
@@ -0,0 +1,71 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use common_infallible::Mutex;
+
+/// A request served by the flight service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub action: String,
+    /// The label of the query the request is sent for, if the client sets it.
+    pub query_label: Option<String>,
+    pub elapsed: Duration,
+    /// If it takes longer than the `slow_apply_threshold_ms` at the time it is served.
+    pub slow: bool,
+    pub ok: bool,
+}
+
+/// Keeps the latest requests served by the flight service, for the tests only.
+pub struct AuditLog {
+    capacity: usize,
+    records: Mutex<VecDeque<AuditRecord>>,
+    /// The requests given up at the deadlines their clients send.
+    deadline_aborts: AtomicU64,
+}
+
+impl AuditLog {
+    pub fn create(capacity: usize) -> Self {
+        AuditLog {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+            deadline_aborts: AtomicU64::new(0),
+        }
+    }
+
+    pub fn push(&self, record: AuditRecord) {
+        let mut records = self.records.lock();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// The records from the oldest to the latest.
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records.lock().iter().cloned().collect()
+    }
+
+    pub fn incr_deadline_aborts(&self) {
+        self.deadline_aborts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn deadline_aborts(&self) -> u64 {
+        self.deadline_aborts.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::col;
+use common_planners::lit;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::Expression;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::storage_api_impl::ReadPlanReply;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
+const PARTS: usize = 40;
+const ROWS_PER_PART: usize = 100;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_bloom_filter_pruning() -> anyhow::Result<()> {
+    // - Append parts of random ids to a table whose id column is indexed.
+    // - A point lookup on the id skips most of the parts, and the kept parts have the row.
+    // - No part of a looked up id is ever skipped.
+    // - A predicate on a column that is not indexed does not consult the filters.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(table_plan("tb1", &[("bloom_filter_columns", "id")]))
+        .await?;
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut ids = vec![];
+    for part in 0..PARTS {
+        let part_ids = (0..ROWS_PER_PART)
+            .map(|_| format!("user-{:016x}", rng.gen::<u64>()))
+            .collect::<Vec<_>>();
+        let names = (0..ROWS_PER_PART)
+            .map(|row| format!("name-{}-{}", part, row))
+            .collect::<Vec<_>>();
+        let block = DataBlock::create_by_array(schema(), vec![
+            Series::new(part_ids.iter().map(|id| id.as_str()).collect::<Vec<_>>()),
+            Series::new(names.iter().map(|name| name.as_str()).collect::<Vec<_>>()),
+        ]);
+        client
+            .append_data(
+                "db1".into(),
+                "tb1".into(),
+                schema(),
+                Box::pin(futures::stream::iter(vec![block])),
+            )
+            .await?;
+        ids.push(part_ids);
+    }
+
+    // A point lookup keeps the part of the id, and hardly any other.
+    let lookup = &ids[17][42];
+    let reply = read_plan(&client, vec![col("id").eq(lit(lookup.as_bytes()))]).await?;
+    let parts = reply.parts.clone().unwrap_or_default();
+    assert_eq!(PARTS, reply.pruning.total);
+    assert_eq!(PARTS, reply.pruning.bloom_checked);
+    assert_eq!(
+        PARTS - parts.len(),
+        reply.pruning.pruned_by_bloom + reply.pruning.pruned_by_range
+    );
+    assert!(parts.len() <= 3, "{} parts kept", parts.len());
+    assert!(parts.iter().all(|part| part.bloom_filters.is_none()));
+
+    let rows = read_all(&client, &parts)
+        .await?
+        .iter()
+        .map(|block| block.try_column_by_name("id")?.to_values())
+        .collect::<common_exception::Result<Vec<_>>>()?
+        .concat();
+    let found = rows
+        .iter()
+        .filter(|value| **value == DataValue::String(Some(lookup.as_bytes().to_vec())))
+        .count();
+    assert_eq!(1, found);
+
+    // No false negatives: the part of each id is kept, an IN list keeps the parts of all its ids.
+    for (part, part_ids) in ids.iter().enumerate() {
+        for id in part_ids.iter().step_by(10) {
+            let reply = read_plan(&client, vec![col("id").eq(lit(id.as_bytes()))]).await?;
+            let kept = reply.parts.unwrap_or_default().len();
+            assert!(kept >= 1 && kept < PARTS, "id {} of part {}", id, part);
+        }
+    }
+    let in_list = col("id")
+        .eq(lit(ids[3][0].as_bytes()))
+        .or(col("id").eq(lit(ids[29][99].as_bytes())));
+    let reply = read_plan(&client, vec![in_list]).await?;
+    assert!(reply.parts.unwrap_or_default().len() >= 2);
+
+    // Neither a predicate on another column nor a range on the indexed one consult the filters,
+    // they are left to the column ranges.
+    for filters in [
+        vec![col("name").eq(lit("name-17-42".as_bytes()))],
+        vec![col("id").gt(lit(lookup.as_bytes()))],
+        vec![],
+    ] {
+        let reply = read_plan(&client, filters.clone()).await?;
+        assert_eq!(0, reply.pruning.bloom_checked, "{:?}", filters);
+        assert_eq!(0, reply.pruning.pruned_by_bloom, "{:?}", filters);
+        assert_eq!(
+            PARTS - reply.pruning.pruned_by_range,
+            reply.parts.unwrap_or_default().len()
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_bloom_filter_options() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+
+    let invalid = [
+        vec![("bloom_filter_columns", "no_such_column")],
+        vec![("bloom_filter_columns", "id"), ("bloom_filter_fpp", "1.5")],
+        vec![("bloom_filter_columns", "id"), ("bloom_filter_fpp", "abc")],
+    ];
+    for options in invalid {
+        let res = client.create_table(table_plan("tb1", &options)).await;
+        assert_eq!(
+            ErrorCode::BadOption("").code(),
+            res.unwrap_err().code(),
+            "{:?}",
+            options
+        );
+    }
+
+    // The option names are case-insensitive.
+    client
+        .create_table(table_plan("tb1", &[
+            ("Bloom_Filter_Columns", "id, name"),
+            ("BLOOM_FILTER_FPP", "0.001"),
+        ]))
+        .await?;
+
+    Ok(())
+}
+
+async fn read_plan(
+    client: &StoreClient,
+    filters: Vec<Expression>,
+) -> anyhow::Result<ReadPlanReply> {
+    let mut plan = ScanPlan {
+        schema_name: "tb1".to_string(),
+        ..ScanPlan::empty()
+    };
+    plan.push_downs.filters = filters;
+    let reply = client
+        .read_plan_with_pruning("db1".into(), "tb1".into(), &plan)
+        .await?;
+    Ok(reply)
+}
+
+async fn read_all(client: &StoreClient, parts: &[DataPartInfo]) -> anyhow::Result<Vec<DataBlock>> {
+    let mut blocks = vec![];
+    for part in parts {
+        let action = ReadAction {
+            part: part.part.clone(),
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: "db1".to_string(),
+                table: "tb1".to_string(),
+                schema: schema(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let mut part_blocks = client
+            .read_partition(schema(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        blocks.append(&mut part_blocks);
+    }
+    Ok(blocks)
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("id", DataType::String, false),
+        DataField::new("name", DataType::String, false),
+    ])
+}
+
+fn table_plan(table: &str, options: &[(&str, &str)]) -> CreateTablePlan {
+    CreateTablePlan {
+        if_not_exists: false,
+        db: "db1".to_string(),
+        table: table.to_string(),
+        schema: schema(),
+        options: options
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        engine: "PARQUET".to_string(),
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_store_api_sdk::storage_api_impl::CopyTableResult;
+use common_store_api_sdk::storage_api_impl::CopyTableSource;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultKind;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::FaultRule;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_copy_table() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let src_injector = Arc::new(FaultInjector::create());
+    let mut src_tc = new_test_context();
+    src_tc.fault_injector = Some(src_injector.clone());
+    start_store_server_with_context(&mut src_tc).await?;
+    let src_addr = src_tc.config.flight_api_address.clone();
+    let src = StoreClient::try_create(src_addr.as_str(), "root", "xxx").await?;
+
+    let mut dst_tc = new_test_context();
+    start_store_server_with_context(&mut dst_tc).await?;
+    let dst_addr = dst_tc.config.flight_api_address.clone();
+    let mut dst = StoreClient::try_create(dst_addr.as_str(), "root", "xxx").await?;
+    dst.set_timeout(Duration::from_secs(60));
+
+    src.create_database(database_plan("db1")).await?;
+    src.create_table(table_plan("db1", "tb1")).await?;
+    dst.create_database(database_plan("db1")).await?;
+
+    let blocks = (0..3i64)
+        .map(|i| {
+            DataBlock::create_by_array(schema(), vec![
+                Series::new(vec![i * 10, i * 10 + 1, i * 10 + 2]),
+                Series::new(vec!["str1", "str2", "str3"]),
+            ])
+        })
+        .collect::<Vec<_>>();
+    src.append_data(
+        "db1".into(),
+        "tb1".into(),
+        schema(),
+        Box::pin(futures::stream::iter(blocks)),
+    )
+    .await?;
+
+    // The first part is copied, then the source fails to read the second one.
+    src_injector.add_rule(
+        FaultRule::create(
+            Some("Read"),
+            FaultPhase::Request,
+            FaultKind::Delay(Duration::from_millis(0)),
+        )
+        .times(1),
+    );
+    src_injector.add_rule(
+        FaultRule::create(
+            Some("Read"),
+            FaultPhase::Request,
+            FaultKind::Error("source is down".to_string()),
+        )
+        .times(1),
+    );
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), false)
+        .await;
+    assert!(res.is_err());
+    assert_eq!(1, read_plan(&dst, "db1", "tb1").await?.len());
+
+    // Resumed without copying the first part again.
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), false)
+        .await?;
+    assert_eq!(
+        CopyTableResult {
+            copied_parts: 2,
+            skipped_parts: 1,
+            rows: 9,
+        },
+        res
+    );
+
+    let src_table = src.get_table("db1".into(), "tb1".into()).await?;
+    let dst_table = dst.get_table("db1".into(), "tb1".into()).await?;
+    assert_eq!(src_table.schema, dst_table.schema);
+    assert_eq!(src_table.engine, dst_table.engine);
+
+    let src_parts = read_plan(&src, "db1", "tb1").await?;
+    let dst_parts = read_plan(&dst, "db1", "tb1").await?;
+    assert_eq!(src_parts.len(), dst_parts.len());
+    assert_eq!(part_rows(&src_parts), part_rows(&dst_parts));
+
+    assert_eq!(
+        read_col_i(&src, "db1", "tb1").await?,
+        read_col_i(&dst, "db1", "tb1").await?
+    );
+
+    // The copy is done: copying it again is rejected.
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), false)
+        .await;
+    assert_eq!(
+        ErrorCode::TableAlreadyExists("").code(),
+        res.unwrap_err().code()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_copy_table_overwrite_empty() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut src_tc = new_test_context();
+    start_store_server_with_context(&mut src_tc).await?;
+    let src_addr = src_tc.config.flight_api_address.clone();
+    let src = StoreClient::try_create(src_addr.as_str(), "root", "xxx").await?;
+
+    let mut dst_tc = new_test_context();
+    start_store_server_with_context(&mut dst_tc).await?;
+    let dst_addr = dst_tc.config.flight_api_address.clone();
+    let dst = StoreClient::try_create(dst_addr.as_str(), "root", "xxx").await?;
+
+    src.create_database(database_plan("db1")).await?;
+    src.create_table(table_plan("db1", "tb1")).await?;
+    let block = DataBlock::create_by_array(schema(), vec![
+        Series::new(vec![0i64, 1, 2]),
+        Series::new(vec!["str1", "str2", "str3"]),
+    ]);
+    src.append_data(
+        "db1".into(),
+        "tb1".into(),
+        schema(),
+        Box::pin(futures::stream::iter(vec![block])),
+    )
+    .await?;
+
+    dst.create_database(database_plan("db1")).await?;
+    dst.create_table(table_plan("db1", "tb1")).await?;
+
+    // An empty table is only copied into if it is asked for.
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), false)
+        .await;
+    assert_eq!(
+        ErrorCode::TableAlreadyExists("").code(),
+        res.unwrap_err().code()
+    );
+
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), true)
+        .await?;
+    assert_eq!(1, res.copied_parts);
+    assert_eq!(3, res.rows);
+
+    // Not empty any more.
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), true)
+        .await;
+    assert_eq!(
+        ErrorCode::TableAlreadyExists("").code(),
+        res.unwrap_err().code()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_copy_table_append_fails() -> anyhow::Result<()> {
+    // A part is appended along with its progress marker: an append that fails to apply leaves
+    // neither, and the part is copied again on resuming, without duplicated rows.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut src_tc = new_test_context();
+    start_store_server_with_context(&mut src_tc).await?;
+    let src_addr = src_tc.config.flight_api_address.clone();
+    let src = StoreClient::try_create(src_addr.as_str(), "root", "xxx").await?;
+
+    let dst_injector = Arc::new(FaultInjector::create());
+    let mut dst_tc = new_test_context();
+    dst_tc.fault_injector = Some(dst_injector.clone());
+    start_store_server_with_context(&mut dst_tc).await?;
+    let dst_addr = dst_tc.config.flight_api_address.clone();
+    let mut dst = StoreClient::try_create(dst_addr.as_str(), "root", "xxx").await?;
+    dst.set_timeout(Duration::from_secs(60));
+
+    src.create_database(database_plan("db1")).await?;
+    src.create_table(table_plan("db1", "tb1")).await?;
+    dst.create_database(database_plan("db1")).await?;
+
+    let blocks = (0..2i64)
+        .map(|i| {
+            DataBlock::create_by_array(schema(), vec![
+                Series::new(vec![i * 10, i * 10 + 1, i * 10 + 2]),
+                Series::new(vec!["str1", "str2", "str3"]),
+            ])
+        })
+        .collect::<Vec<_>>();
+    src.append_data(
+        "db1".into(),
+        "tb1".into(),
+        schema(),
+        Box::pin(futures::stream::iter(blocks)),
+    )
+    .await?;
+
+    // The first part is appended, the append of the second one fails.
+    dst_injector.add_rule(
+        FaultRule::create(
+            Some("ApplyAppendDataParts"),
+            FaultPhase::Request,
+            FaultKind::Delay(Duration::from_millis(0)),
+        )
+        .times(1),
+    );
+    dst_injector.add_rule(
+        FaultRule::create(
+            Some("ApplyAppendDataParts"),
+            FaultPhase::Request,
+            FaultKind::Error("apply is down".to_string()),
+        )
+        .times(1),
+    );
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), false)
+        .await;
+    assert!(res.is_err());
+    assert_eq!(1, read_plan(&dst, "db1", "tb1").await?.len());
+
+    let res = dst
+        .copy_table(source(&src_addr), "db1".into(), "tb1".into(), false)
+        .await?;
+    assert_eq!(
+        CopyTableResult {
+            copied_parts: 1,
+            skipped_parts: 1,
+            rows: 6,
+        },
+        res
+    );
+    assert_eq!(
+        read_col_i(&src, "db1", "tb1").await?,
+        read_col_i(&dst, "db1", "tb1").await?
+    );
+
+    Ok(())
+}
+
+async fn read_plan(
+    client: &StoreClient,
+    db: &str,
+    table: &str,
+) -> anyhow::Result<Vec<DataPartInfo>> {
+    let plan = ScanPlan {
+        schema_name: table.to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client.read_plan(db.into(), table.into(), &plan).await?;
+    Ok(parts.unwrap_or_default())
+}
+
+fn part_rows(parts: &[DataPartInfo]) -> Vec<usize> {
+    let mut rows = parts
+        .iter()
+        .map(|part| part.stats.read_rows)
+        .collect::<Vec<_>>();
+    rows.sort_unstable();
+    rows
+}
+
+async fn read_col_i(client: &StoreClient, db: &str, table: &str) -> anyhow::Result<Vec<i64>> {
+    let mut values = vec![];
+    for part in read_plan(client, db, table).await? {
+        let action = ReadAction {
+            part: part.part,
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: db.to_string(),
+                table: table.to_string(),
+                schema: schema(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let blocks = client
+            .read_partition(schema(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        for block in blocks {
+            for i in 0..block.num_rows() {
+                values.push(block.column(0).try_get(i)?.as_i64()?);
+            }
+        }
+    }
+    values.sort_unstable();
+    Ok(values)
+}
+
+fn source(address: &str) -> CopyTableSource {
+    CopyTableSource {
+        address: address.to_string(),
+        username: "root".to_string(),
+        password: "xxx".to_string(),
+    }
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ])
+}
+
+fn database_plan(db: &str) -> CreateDatabasePlan {
+    CreateDatabasePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        engine: "Local".to_string(),
+        options: Default::default(),
+    }
+}
+
+fn table_plan(db: &str, table: &str) -> CreateTablePlan {
+    CreateTablePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        table: table.to_string(),
+        schema: schema(),
+        options: Default::default(),
+        engine: "PARQUET".to_string(),
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultKind;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::FaultRule;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::api::rpc::AuditLog;
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_read_partition_given_up_at_deadline() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let injector = Arc::new(FaultInjector::create());
+    let audit_log = Arc::new(AuditLog::create(16));
+    let mut tc = new_test_context();
+    tc.fault_injector = Some(injector.clone());
+    tc.audit_log = Some(audit_log.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    let action = prepare_part(&client).await?;
+
+    // The data of the part is late, the store gives it up at the deadline of the client.
+    injector.add_rule(
+        FaultRule::create(
+            Some("Read"),
+            FaultPhase::Response,
+            FaultKind::Delay(Duration::from_secs(3)),
+        )
+        .times(1),
+    );
+    let mut query_client = client.clone();
+    query_client.set_deadline(Instant::now() + Duration::from_millis(500));
+
+    let started = Instant::now();
+    let res = query_client
+        .read_partition(schema(), &action)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await;
+    let err = res.unwrap_err();
+    assert_eq!(ErrorCode::DeadlineExceeded("").code(), err.code());
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert_eq!(1, audit_log.deadline_aborts());
+
+    // A call not bound by a deadline waits for the late data, within its own timeout.
+    injector.add_rule(
+        FaultRule::create(
+            Some("Read"),
+            FaultPhase::Response,
+            FaultKind::Delay(Duration::from_secs(1)),
+        )
+        .times(1),
+    );
+    let blocks = client
+        .read_partition(schema(), &action)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(3, blocks[0].num_rows());
+    assert_eq!(1, audit_log.deadline_aborts());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_deadline_exceeded_before_sent() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let audit_log = Arc::new(AuditLog::create(16));
+    let mut tc = new_test_context();
+    tc.audit_log = Some(audit_log.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let mut client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client.set_deadline(Instant::now());
+
+    let plan = ScanPlan {
+        schema_name: "tb1".to_string(),
+        ..ScanPlan::empty()
+    };
+    let res = client.read_plan("db1".into(), "tb1".into(), &plan).await;
+    assert_eq!(
+        ErrorCode::DeadlineExceeded("").code(),
+        res.unwrap_err().code()
+    );
+
+    // Nothing is sent to the store.
+    assert!(audit_log.records().is_empty());
+    assert_eq!(0, audit_log.deadline_aborts());
+
+    Ok(())
+}
+
+/// Creates a table with one part, and returns the action to read it.
+async fn prepare_part(client: &StoreClient) -> anyhow::Result<ReadAction> {
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            table: "tb1".to_string(),
+            schema: schema(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    let block = DataBlock::create_by_array(schema(), vec![
+        Series::new(vec![0i64, 1, 2]),
+        Series::new(vec!["str1", "str2", "str3"]),
+    ]);
+    client
+        .append_data(
+            "db1".into(),
+            "tb1".into(),
+            schema(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await?;
+
+    let plan = ScanPlan {
+        schema_name: "tb1".to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client
+        .read_plan("db1".into(), "tb1".into(), &plan)
+        .await?
+        .unwrap_or_default();
+    assert_eq!(1, parts.len());
+
+    Ok(ReadAction {
+        part: parts[0].part.clone(),
+        push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+            db: "db1".to_string(),
+            table: "tb1".to_string(),
+            schema: schema(),
+            ..ReadDataSourcePlan::empty(0, None)
+        }),
+    })
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ])
+}
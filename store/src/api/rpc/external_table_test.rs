@@ -0,0 +1,219 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::TableOptions;
+use common_runtime::tokio;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_external_csv_table() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let dir = tempfile::tempdir()?;
+    let data_dir = dir.path().join("data");
+    fs::create_dir(&data_dir)?;
+    fs::write(data_dir.join("a.csv"), "id;name\n1;a\n2;b\n")?;
+    fs::write(data_dir.join("b.csv"), "id;name\n3;c\n")?;
+    fs::write(data_dir.join("c.txt"), "not a csv file")?;
+
+    let mut tc = new_test_context();
+    tc.config.external_data_dirs = data_dir.display().to_string();
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client.create_database(database_plan("db1")).await?;
+    client
+        .create_table(table_plan(
+            "tb1",
+            options(&[
+                ("path", &format!("{}/*.csv", data_dir.display())),
+                ("header", "true"),
+                ("delimiter", ";"),
+            ]),
+        ))
+        .await?;
+
+    // One part for each matching file.
+    let parts = read_plan(&client, "tb1").await?;
+    assert_eq!(2, parts.len());
+    let sizes = parts
+        .iter()
+        .map(|part| part.stats.read_bytes)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![16, 12], sizes);
+
+    let blocks = read_all(&client, "tb1", &parts).await?;
+    common_datablocks::assert_blocks_sorted_eq(
+        vec![
+            "+----+------+",
+            "| id | name |",
+            "+----+------+",
+            "| 1  | a    |",
+            "| 2  | b    |",
+            "| 3  | c    |",
+            "+----+------+",
+        ],
+        &blocks,
+    );
+
+    // The files are only read, never written.
+    let block = DataBlock::create_by_array(schema(), vec![
+        Series::new(vec![4i64]),
+        Series::new(vec!["d"]),
+    ]);
+    let res = client
+        .append_data(
+            "db1".into(),
+            "tb1".into(),
+            schema(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await;
+    assert!(res.is_err());
+
+    // A file that does not match the schema is rejected when the table is created.
+    fs::write(data_dir.join("bad.csv"), "id;name\nx;a\n")?;
+    let res = client
+        .create_table(table_plan(
+            "tb2",
+            options(&[
+                ("path", &format!("{}/bad.csv", data_dir.display())),
+                ("header", "true"),
+                ("delimiter", ";"),
+            ]),
+        ))
+        .await;
+    assert_eq!(ErrorCode::BadBytes("").code(), res.unwrap_err().code());
+
+    // Unless the bad rows are tolerated.
+    client
+        .create_table(table_plan(
+            "tb2",
+            options(&[
+                ("path", &format!("{}/bad.csv", data_dir.display())),
+                ("header", "true"),
+                ("delimiter", ";"),
+                ("max_bad_rows", "1"),
+            ]),
+        ))
+        .await?;
+    let parts = read_plan(&client, "tb2").await?;
+    assert_eq!(1, parts.len());
+    let blocks = read_all(&client, "tb2", &parts).await?;
+    assert_eq!(0, blocks.iter().map(|b| b.num_rows()).sum::<usize>());
+
+    // Only paths in the configured dirs can be read.
+    let res = client
+        .create_table(table_plan(
+            "tb3",
+            options(&[("path", &format!("{}/*.csv", dir.path().display()))]),
+        ))
+        .await;
+    assert_eq!(ErrorCode::BadOption("").code(), res.unwrap_err().code());
+
+    let res = client.create_table(table_plan("tb3", options(&[]))).await;
+    assert_eq!(ErrorCode::BadOption("").code(), res.unwrap_err().code());
+
+    Ok(())
+}
+
+async fn read_plan(client: &StoreClient, table: &str) -> anyhow::Result<Vec<DataPartInfo>> {
+    let plan = ScanPlan {
+        schema_name: table.to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client.read_plan("db1".into(), table.into(), &plan).await?;
+    Ok(parts.unwrap_or_default())
+}
+
+async fn read_all(
+    client: &StoreClient,
+    table: &str,
+    parts: &[DataPartInfo],
+) -> anyhow::Result<Vec<DataBlock>> {
+    let mut blocks = vec![];
+    for part in parts {
+        let action = ReadAction {
+            part: part.part.clone(),
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: "db1".to_string(),
+                table: table.to_string(),
+                schema: schema(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let mut part_blocks = client
+            .read_partition(schema(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        blocks.append(&mut part_blocks);
+    }
+    Ok(blocks)
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("id", DataType::Int64, false),
+        DataField::new("name", DataType::String, false),
+    ])
+}
+
+fn options(pairs: &[(&str, &str)]) -> TableOptions {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn database_plan(db: &str) -> CreateDatabasePlan {
+    CreateDatabasePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        engine: "Local".to_string(),
+        options: Default::default(),
+    }
+}
+
+fn table_plan(table: &str, options: TableOptions) -> CreateTablePlan {
+    CreateTablePlan {
+        if_not_exists: false,
+        db: "db1".to_string(),
+        table: table.to_string(),
+        schema: schema(),
+        options,
+        engine: "CSV".to_string(),
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultKind;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::FaultRule;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use pretty_assertions::assert_eq;
+
+use crate::fs::STAGING_DIR;
+use crate::tests::partition_client;
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_client_survives_blackhole() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let injector = Arc::new(FaultInjector::create());
+    let mut tc = new_test_context();
+    tc.fault_injector = Some(injector.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let mut client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client.set_timeout(Duration::from_secs(1));
+    client.create_database(database_plan("db1")).await?;
+
+    // The server never answers: the request times out and the connection is still usable.
+    injector.add_rule(
+        FaultRule::create(Some("GetDatabase"), FaultPhase::Response, FaultKind::Drop).times(1),
+    );
+    assert!(client.get_database("db1").await.is_err());
+    assert_eq!(1, client.get_database("db1").await?.database_id);
+
+    // The client is cut off for 5 seconds.
+    partition_client(&mut client, Duration::from_secs(5));
+    let res = client.get_database("db1").await;
+    assert_eq!(ErrorCode::Timeout("").code(), res.unwrap_err().code());
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert_eq!(1, client.get_database("db1").await?.database_id);
+
+    // The server is cut off for 2 seconds: the requests in the meantime are lost.
+    injector.partition(Duration::from_secs(2));
+    assert!(client.get_database("db1").await.is_err());
+
+    // Then it serves the requests again, reads and writes, of the client and of a new one.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(!injector.is_partitioned());
+    assert_eq!(1, client.get_database("db1").await?.database_id);
+    client.create_database(database_plan("db2")).await?;
+    let other = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    other.get_database("db2").await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_create_table_duplicate_delivery() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let server_injector = Arc::new(FaultInjector::create());
+    let mut tc = new_test_context();
+    tc.fault_injector = Some(server_injector.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let mut client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    let client_injector = Arc::new(FaultInjector::create());
+    client.set_fault_injector(client_injector.clone());
+    client.create_database(database_plan("db1")).await?;
+
+    // Resent by the client: without the request id the second one fails with TableAlreadyExists.
+    client_injector.add_rule(
+        FaultRule::create(
+            Some("CreateTable"),
+            FaultPhase::Request,
+            FaultKind::Duplicate,
+        )
+        .times(1),
+    );
+    let res = client
+        .create_table(table_plan("db1", "tb1", schema()))
+        .await?;
+    let table = client.get_table("db1".into(), "tb1".into()).await?;
+    assert_eq!(table.table_id, res.table_id);
+
+    // Delivered twice by the network.
+    server_injector.add_rule(
+        FaultRule::create(
+            Some("CreateTable"),
+            FaultPhase::Request,
+            FaultKind::Duplicate,
+        )
+        .times(1),
+    );
+    let res = client
+        .create_table(table_plan("db1", "tb2", schema()))
+        .await?;
+    let table = client.get_table("db1".into(), "tb2".into()).await?;
+    assert_eq!(table.table_id, res.table_id);
+
+    // A new request for the same table is not a duplicate.
+    let res = client
+        .create_table(table_plan("db1", "tb2", schema()))
+        .await;
+    assert_eq!(
+        ErrorCode::TableAlreadyExists("").code(),
+        res.unwrap_err().code()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_append_interrupted() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let injector = Arc::new(FaultInjector::create());
+    let mut tc = new_test_context();
+    tc.fault_injector = Some(injector.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client.create_database(database_plan("db1")).await?;
+    client
+        .create_table(table_plan("db1", "tb1", schema()))
+        .await?;
+
+    let block = DataBlock::create_by_array(schema(), vec![
+        Series::new(vec![0i64, 1, 2]),
+        Series::new(vec!["str1", "str2", "str3"]),
+    ]);
+    let blocks = vec![block.clone(), block.clone(), block];
+
+    // The schema and the first block arrive, then the connection is cut.
+    injector.add_rule(
+        FaultRule::create(Some("Append"), FaultPhase::Request, FaultKind::Cut(2)).times(1),
+    );
+    let stream = futures::stream::iter(blocks.clone());
+    let res = client
+        .append_data("db1".into(), "tb1".into(), schema(), Box::pin(stream))
+        .await;
+    assert!(res.is_err());
+
+    let plan = ScanPlan {
+        schema_name: "tb1".to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client.read_plan("db1".into(), "tb1".into(), &plan).await?;
+    assert_eq!(0, parts.map(|parts| parts.len()).unwrap_or(0));
+
+    // Once the connection is back, the whole stream is appended.
+    let stream = futures::stream::iter(blocks);
+    let res = client
+        .append_data("db1".into(), "tb1".into(), schema(), Box::pin(stream))
+        .await?;
+    assert_eq!(3, res.parts.len());
+
+    let parts = client.read_plan("db1".into(), "tb1".into(), &plan).await?;
+    assert_eq!(3, parts.map(|parts| parts.len()).unwrap_or(0));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_append_interrupted_leaves_nothing_visible() -> anyhow::Result<()> {
+    // - The connection is cut in the middle of an append whose parts go to files.
+    // - No part is registered and no part file is in place, the written bytes are only staged.
+    // - The staged file is removed by the staging vacuum once it is old enough by the clock.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let clock = VirtualClock::create();
+    let injector = Arc::new(FaultInjector::create());
+    let mut tc = new_test_context();
+    tc.config.meta_config.clock = SharedClock::create(clock.clone());
+    tc.config.inline_part_max_bytes = 0;
+    tc.config.staging_vacuum_interval_secs = 1;
+    tc.config.staged_file_max_age_secs = 2;
+    tc.fault_injector = Some(injector.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client.create_database(database_plan("db1")).await?;
+    client
+        .create_table(table_plan("db1", "tb1", schema()))
+        .await?;
+
+    let block = DataBlock::create_by_array(schema(), vec![
+        Series::new(vec![0i64, 1, 2]),
+        Series::new(vec!["str1", "str2", "str3"]),
+    ]);
+    let blocks = vec![block.clone(), block.clone(), block];
+
+    injector.add_rule(
+        FaultRule::create(Some("Append"), FaultPhase::Request, FaultKind::Cut(2)).times(1),
+    );
+    let stream = futures::stream::iter(blocks);
+    let res = client
+        .append_data("db1".into(), "tb1".into(), schema(), Box::pin(stream))
+        .await;
+    assert!(res.is_err());
+
+    let plan = ScanPlan {
+        schema_name: "tb1".to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client.read_plan("db1".into(), "tb1".into(), &plan).await?;
+    assert_eq!(0, parts.map(|parts| parts.len()).unwrap_or(0));
+
+    // The server may learn of the cut after the client.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let root = Path::new(&tc.config.local_fs_dir);
+    assert!(!root.join("db1/tb1").exists());
+    let staging_dir = root.join(STAGING_DIR);
+    assert_eq!(1, std::fs::read_dir(&staging_dir)?.count());
+
+    clock.advance(Duration::from_secs(1));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(1, std::fs::read_dir(&staging_dir)?.count());
+
+    // The vacuum wakes up as the clock passes its interval.
+    clock.advance(Duration::from_secs(2));
+    let mut staged = 1;
+    for _ in 0..100 {
+        staged = std::fs::read_dir(&staging_dir)?.count();
+        if staged == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(0, staged);
+
+    Ok(())
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ])
+}
+
+fn database_plan(db: &str) -> CreateDatabasePlan {
+    CreateDatabasePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        engine: "Local".to_string(),
+        options: Default::default(),
+    }
+}
+
+fn table_plan(db: &str, table: &str, schema: DataSchemaRef) -> CreateTablePlan {
+    CreateTablePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        table: table.to_string(),
+        schema,
+        options: Default::default(),
+        engine: "PARQUET".to_string(),
+    }
+}
@@ -14,7 +14,11 @@
 
 use std::convert::TryInto;
 use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use common_arrow::arrow_flight;
 use common_arrow::arrow_flight::flight_service_server::FlightService;
@@ -35,8 +39,12 @@ use common_runtime::tokio;
 use common_runtime::tokio::sync::mpsc::Receiver;
 use common_runtime::tokio::sync::mpsc::Sender;
 use common_store_api_sdk::storage_api_impl;
+use common_store_api_sdk::CutStream;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultPhase;
 use common_store_api_sdk::FlightClaim;
 use common_store_api_sdk::FlightToken;
+use common_store_api_sdk::Injected;
 use common_store_api_sdk::StoreDoAction;
 use common_store_api_sdk::StoreDoGet;
 use common_tracing::tracing;
@@ -53,10 +61,20 @@ use tonic::Response;
 use tonic::Status;
 use tonic::Streaming;
 
+use crate::api::rpc::metrics::record_action;
+use crate::api::rpc::request_deadline::abort_at_deadline;
+use crate::api::rpc::AuditLog;
+use crate::api::rpc::AuditRecord;
+use crate::api::rpc::DeadlineStream;
+use crate::api::rpc::RequestDeadline;
 use crate::configs::Config;
+use crate::configs::ConfigHandle;
 use crate::executor::ActionHandler;
+use crate::executor::ApplyQueue;
 use crate::executor::ReplySerializer;
 use crate::fs::FileSystem;
+use crate::metrics::DatabaseUsageRecorder;
+use crate::metrics::TableAccessRecorder;
 
 pub type FlightStream<T> =
     Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send + Sync + 'static>>;
@@ -65,14 +83,118 @@ pub type FlightStream<T> =
 pub struct StoreFlightImpl {
     token: FlightToken,
     action_handler: ActionHandler,
+    fault_injector: Option<Arc<FaultInjector>>,
+    config: Arc<ConfigHandle>,
+    audit_log: Option<Arc<AuditLog>>,
 }
 
 impl StoreFlightImpl {
-    pub fn create(_conf: Config, fs: Arc<dyn FileSystem>, meta_node: Arc<MetaNode>) -> Self {
+    pub fn create(
+        conf: Config,
+        fs: Arc<dyn FileSystem>,
+        meta_node: Arc<MetaNode>,
+        apply_queue: Arc<ApplyQueue>,
+    ) -> Self {
+        let config = Arc::new(ConfigHandle::create(conf.clone()));
         Self {
             token: FlightToken::create(),
             // TODO pass in action handler
-            action_handler: ActionHandler::create(fs, meta_node),
+            action_handler: ActionHandler::create(fs, meta_node, apply_queue)
+                .with_access_recorder(Arc::new(TableAccessRecorder::create(
+                    conf.table_metrics_max_labels,
+                )))
+                .with_external_data_dirs(conf.external_data_dirs())
+                .with_inline_part_max_bytes(conf.inline_part_max_bytes)
+                .with_verify_part_checksum(conf.verify_part_checksum)
+                .with_config_handle(config.clone()),
+            fault_injector: None,
+            config,
+            audit_log: None,
+        }
+    }
+
+    /// Reads the dynamic settings from `handle`, which is shared with the reloader.
+    pub fn with_config_handle(mut self, handle: Arc<ConfigHandle>) -> Self {
+        self.action_handler = self.action_handler.with_config_handle(handle.clone());
+        self.config = handle;
+        self
+    }
+
+    /// Keeps the latest requests in `audit_log`, for the tests only.
+    pub fn with_audit_log(mut self, audit_log: Option<Arc<AuditLog>>) -> Self {
+        self.audit_log = audit_log;
+        self
+    }
+
+    /// Reports the usages of the databases to `recorder`, which is shared with the http api.
+    pub fn with_usage_recorder(mut self, recorder: Arc<DatabaseUsageRecorder>) -> Self {
+        self.action_handler = self.action_handler.with_usage_recorder(recorder);
+        self
+    }
+
+    /// Routes the requests and the responses through the injector, for the tests only.
+    pub fn with_fault_injector(mut self, injector: Option<Arc<FaultInjector>>) -> Self {
+        self.fault_injector = injector;
+        self
+    }
+
+    /// Returns how many times the request or the response is delivered.
+    async fn inject(&self, phase: FaultPhase, action: &str) -> Result<usize, Status> {
+        match &self.fault_injector {
+            None => Ok(1),
+            Some(injector) => match injector.inject(phase, action).await {
+                Injected::Pass | Injected::Cut(_) => Ok(1),
+                Injected::Duplicate => Ok(2),
+                Injected::Fail(message) => Err(Status::unavailable(message)),
+                Injected::Drop => futures::future::pending().await,
+            },
+        }
+    }
+
+    /// Applies the faults of the response of a `Read` before its first message, e.g. delays it.
+    fn inject_read_response(
+        injector: Arc<FaultInjector>,
+        stream: <Self as FlightService>::DoGetStream,
+    ) -> <Self as FlightService>::DoGetStream {
+        let injected = async move {
+            let injected: <Self as FlightService>::DoGetStream =
+                match injector.inject(FaultPhase::Response, "Read").await {
+                    Injected::Pass | Injected::Cut(_) | Injected::Duplicate => stream,
+                    Injected::Fail(message) => Box::pin(futures::stream::once(async move {
+                        Err(Status::unavailable(message))
+                    })),
+                    Injected::Drop => futures::future::pending().await,
+                };
+            injected
+        };
+        Box::pin(futures::stream::once(injected).flatten())
+    }
+
+    /// Logs a served request with the label of the query it is sent for.
+    fn audit(&self, action: &str, query_label: Option<String>, started: Instant, ok: bool) {
+        let elapsed = started.elapsed();
+        info!(
+            "audit: action: {}, query label: {:?}, elapsed: {:?}, ok: {}",
+            action, query_label, elapsed, ok
+        );
+        let slow = elapsed >= Duration::from_millis(self.config.get().slow_apply_threshold_ms);
+        if slow {
+            tracing::warn!(
+                "slow request: action: {}, query label: {:?}, elapsed: {:?}",
+                action,
+                query_label,
+                elapsed
+            );
+        }
+
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.push(AuditRecord {
+                action: action.to_string(),
+                query_label,
+                elapsed,
+                slow,
+                ok,
+            });
         }
     }
 
@@ -162,16 +284,52 @@ impl FlightService for StoreFlightImpl {
     ) -> Result<Response<Self::DoGetStream>, Status> {
         // Check token.
         let _claim = self.check_token(request.metadata())?;
+        let query_label = storage_api_impl::get_query_label(request.metadata());
+        let deadline = RequestDeadline::from_metadata(request.metadata());
+        let started = Instant::now();
 
         // Action.
         let action: StoreDoGet = request.try_into()?;
         match action {
             StoreDoGet::Read(act) => {
+                let cut = match &self.fault_injector {
+                    None => None,
+                    Some(injector) => match injector.inject(FaultPhase::Request, "Read").await {
+                        Injected::Cut(messages) => Some(messages),
+                        Injected::Fail(message) => return Err(Status::unavailable(message)),
+                        Injected::Drop => futures::future::pending().await,
+                        Injected::Pass | Injected::Duplicate => None,
+                    },
+                };
+
+                if let Some(deadline) = &deadline {
+                    deadline.check("Read", &self.audit_log)?;
+                }
+
+                let res = self.action_handler.read_partition(act).await;
+                self.audit("Read", query_label, started, res.is_ok());
+
                 let stream =
-                    self.action_handler.read_partition(act).await.map_err(|e| {
-                        Status::internal(format!("read failure: {}", e.to_string()))
-                    })?;
-                Ok(Response::new(Box::pin(stream)))
+                    res.map_err(|e| Status::internal(format!("read failure: {}", e.to_string())))?;
+                let stream: Self::DoGetStream = match cut {
+                    None => Box::pin(stream),
+                    Some(messages) => Box::pin(CutStream::create(stream, messages)),
+                };
+                let stream = match &self.fault_injector {
+                    None => stream,
+                    Some(injector) => Self::inject_read_response(injector.clone(), stream),
+                };
+
+                // The part is given up between its batches once the client stops waiting.
+                match deadline {
+                    None => Ok(Response::new(stream)),
+                    Some(deadline) => Ok(Response::new(Box::pin(DeadlineStream::create(
+                        stream,
+                        deadline,
+                        "Read",
+                        self.audit_log.clone(),
+                    )))),
+                }
             }
             StoreDoGet::Pull(pull) => {
                 let key = pull.key;
@@ -183,6 +341,20 @@ impl FlightService for StoreFlightImpl {
 
                 self.action_handler.do_pull_file(key, tx).await?;
 
+                Ok(Response::new(
+                    Box::pin(ReceiverStream::new(rx)) as Self::DoGetStream
+                ))
+            }
+            StoreDoGet::WatchKV(act) => {
+                let (tx, rx): (
+                    Sender<Result<FlightData, tonic::Status>>,
+                    Receiver<Result<FlightData, tonic::Status>>,
+                ) = tokio::sync::mpsc::channel(16);
+
+                let res = self.action_handler.do_watch_kv(act, tx).await;
+                self.audit("WatchKV", query_label, started, res.is_ok());
+                res?;
+
                 Ok(Response::new(
                     Box::pin(ReceiverStream::new(rx)) as Self::DoGetStream
                 ))
@@ -197,17 +369,47 @@ impl FlightService for StoreFlightImpl {
     ) -> Result<Response<Self::DoPutStream>, Status> {
         let _claim = self.check_token(request.metadata())?;
         let meta = request.metadata();
+        let query_label = storage_api_impl::get_query_label(meta);
+        let started = Instant::now();
 
         let (db_name, tbl_name) =
             storage_api_impl::get_meta(meta).map_err(|e| Status::internal(e.to_string()))?;
 
-        let append_res = self
-            .action_handler
-            .do_put(db_name, tbl_name, request.into_inner())
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?;
+        let request_bytes = Arc::new(AtomicUsize::new(0));
+        let parts = {
+            let request_bytes = request_bytes.clone();
+            request.into_inner().inspect(move |flight_data| {
+                if let Ok(flight_data) = flight_data {
+                    let n = flight_data.data_header.len() + flight_data.data_body.len();
+                    request_bytes.fetch_add(n, Ordering::Relaxed);
+                }
+            })
+        };
+        let append_res = match &self.fault_injector {
+            None => self.action_handler.do_put(db_name, tbl_name, parts).await,
+            Some(injector) => match injector.inject(FaultPhase::Request, "Append").await {
+                Injected::Cut(messages) => {
+                    let parts = CutStream::create(parts, messages);
+                    self.action_handler.do_put(db_name, tbl_name, parts).await
+                }
+                Injected::Fail(message) => return Err(Status::unavailable(message)),
+                Injected::Drop => futures::future::pending().await,
+                Injected::Pass | Injected::Duplicate => {
+                    self.action_handler.do_put(db_name, tbl_name, parts).await
+                }
+            },
+        };
+        self.audit("Append", query_label, started, append_res.is_ok());
+        let append_res = append_res.and_then(|res| Ok(serde_json::to_vec(&res)?));
+        record_action(
+            "Append",
+            started.elapsed(),
+            request_bytes.load(Ordering::Relaxed),
+            append_res.as_ref().map_or(0, |bytes| bytes.len()),
+            &append_res,
+        );
+        let bytes = append_res.map_err(|e| Status::internal(e.to_string()))?;
 
-        let bytes = serde_json::to_vec(&append_res).map_err(|e| Status::internal(e.to_string()))?;
         let put_res = PutResult {
             app_metadata: bytes,
         };
@@ -234,16 +436,52 @@ impl FlightService for StoreFlightImpl {
     ) -> Result<Response<Self::DoActionStream>, Status> {
         // Check token.
         let _claim = self.check_token(request.metadata())?;
+        let query_label = storage_api_impl::get_query_label(request.metadata());
+        let deadline = RequestDeadline::from_metadata(request.metadata());
+        let started = Instant::now();
 
         common_tracing::extract_remote_span_as_parent(&request);
 
+        let request_bytes = request.get_ref().body.len();
         let action: StoreDoAction = request.try_into()?;
         info!("Receive do_action: {:?}", action);
 
-        let s = JsonSer;
-        let body = self.action_handler.execute(action, s).await?;
+        let name = action.name();
+        if self.inject(FaultPhase::Request, name).await? > 1 {
+            let _ = self.action_handler.execute(action.clone(), JsonSer).await;
+        }
+
+        // A mutation is not started if the client has given up, but is not aborted once started.
+        // A read is given up at the deadline.
+        let res = match deadline {
+            None => self.action_handler.execute(action, JsonSer).await,
+            Some(deadline) => {
+                deadline.check(name, &self.audit_log)?;
+                match action {
+                    StoreDoAction::ReadPlan(_) => {
+                        let work = self.action_handler.execute(action, JsonSer);
+                        match tokio::time::timeout(deadline.remaining(), work).await {
+                            Ok(res) => res,
+                            Err(_) => return Err(abort_at_deadline(name, &self.audit_log)),
+                        }
+                    }
+                    _ => self.action_handler.execute(action, JsonSer).await,
+                }
+            }
+        };
+        self.audit(name, query_label, started, res.is_ok());
+        record_action(
+            name,
+            started.elapsed(),
+            request_bytes,
+            res.as_ref().map_or(0, |body| body.len()),
+            &res,
+        );
+        let body = res?;
         let arrow = arrow_flight::Result { body };
-        let output = futures::stream::once(async { Ok(arrow) });
+
+        let times = self.inject(FaultPhase::Response, name).await?;
+        let output = futures::stream::iter((0..times).map(move |_| Ok(arrow.clone())));
         Ok(Response::new(Box::pin(output)))
     }
 
@@ -13,29 +13,68 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_metatypes::JsonPatchOp;
+use common_metatypes::KVChangeKind;
 use common_metatypes::KVMeta;
 use common_metatypes::KVValue;
 use common_metatypes::MatchSeq;
+use common_metatypes::MergeOp;
+use common_planners::col;
+use common_planners::lit;
 use common_planners::CreateDatabasePlan;
 use common_planners::CreateTablePlan;
 use common_planners::DropDatabasePlan;
 use common_planners::DropTablePlan;
+use common_planners::Expression;
+use common_planners::ModifyColumnPlan;
+use common_planners::Part;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::RenameTablePlan;
 use common_planners::ScanPlan;
 use common_runtime::tokio;
+use common_runtime::Clock;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use common_store_api_sdk::batch_api_impl::BatchRequest;
+use common_store_api_sdk::kv_api_impl::GetKVAction;
+use common_store_api_sdk::kv_api_impl::MGetKVAction;
+use common_store_api_sdk::kv_api_impl::UpsertKVAction;
+use common_store_api_sdk::meta_api_impl::CreateTableOutcome;
 use common_store_api_sdk::meta_api_impl::DropTableActionResult;
+use common_store_api_sdk::meta_api_impl::GetDatabaseActionResult;
+use common_store_api_sdk::meta_api_impl::GetTableAction;
 use common_store_api_sdk::meta_api_impl::GetTableActionResult;
+use common_store_api_sdk::meta_api_impl::ListTablesAction;
+use common_store_api_sdk::meta_api_impl::ListTablesReply;
+use common_store_api_sdk::meta_api_impl::RenameTableActionResult;
+use common_store_api_sdk::storage_api_impl::ColumnBound;
+use common_store_api_sdk::storage_api_impl::ColumnStatistics;
+use common_store_api_sdk::storage_api_impl::PartColumnStatistics;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::storage_api_impl::ReadPlanReply;
 use common_store_api_sdk::KVApi;
 use common_store_api_sdk::MetaApi;
 use common_store_api_sdk::StorageApi;
 use common_store_api_sdk::StoreClient;
 use common_tracing::tracing;
+use futures::TryStreamExt;
 use pretty_assertions::assert_eq;
 
+use crate::api::rpc::AuditLog;
+use crate::configs::Config;
+use crate::configs::ConfigHandle;
+use crate::tests::restart_store_server;
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_flight_restart() -> anyhow::Result<()> {
     // Issue 1134  https://github.com/datafuselabs/databend/issues/1134
@@ -43,6 +82,7 @@ async fn test_flight_restart() -> anyhow::Result<()> {
     // - create db and create table
     // - restart
     // - Test read the db and read the table.
+    // - A table created after restart does not reuse the id of the first one.
 
     let (_log_guards, ut_span) = init_store_ut!();
     let _ent = ut_span.enter();
@@ -112,54 +152,234 @@ async fn test_flight_restart() -> anyhow::Result<()> {
         }
     }
 
-    tracing::info!("--- stop StoreServer");
+    drop(client);
+    let client = restart_store_server(&mut tc, &addr).await?;
+
+    tracing::info!("--- get db");
+    {
+        let res = client.get_database(db_name).await;
+        tracing::debug!("get present database res: {:?}", res);
+        let res = res?;
+        assert_eq!(1, res.database_id, "db1 id is 1");
+        assert_eq!(db_name, res.db, "db1.db is db1");
+        assert_eq!("Local", res.engine, "db1 engine is Local");
+    }
+
+    tracing::info!("--- get table");
+    {
+        let got = client
+            .get_table(db_name.into(), table_name.into())
+            .await
+            .unwrap();
+        let want = GetTableActionResult {
+            table_id: 1,
+            db: db_name.into(),
+            name: table_name.into(),
+            schema: schema.clone(),
+            engine: "JSON".to_owned(),
+            options: maplit::hashmap! {"opt‐1".into() => "val-1".into()},
+        };
+        assert_eq!(want, got, "get created table");
+    }
+
+    tracing::info!("--- create another table after restart");
     {
-        let (stop_tx, fin_rx) = tc.channels.take().unwrap();
-        stop_tx
-            .send(())
-            .map_err(|_| anyhow::anyhow!("fail to send"))?;
+        let plan = CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: "table2".to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "JSON".to_string(),
+        };
+        let res = client.create_table(plan).await?;
+        assert!(res.table_id > 1, "table id {} is not reused", res.table_id);
+
+        let got = client.get_table(db_name.into(), table_name.into()).await?;
+        assert_eq!(1, got.table_id, "table1 is intact");
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_restart_with_data() -> anyhow::Result<()> {
+    // - Create a table and append to it.
+    // - Restart.
+    // - The read plan lists the same parts, and they are readable.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (mut tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let db_name = "db1";
+    let tbl_name = "tb1";
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64, 2, 3])]);
+    let stream = futures::stream::iter(vec![block.clone(), block]);
+    client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(stream),
+        )
+        .await?;
+
+    let plan = ScanPlan {
+        schema_name: tbl_name.to_string(),
+        ..ScanPlan::empty()
+    };
+    let want = client
+        .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+        .await?;
+    assert_eq!(
+        2,
+        want.as_ref().map(|parts| parts.len()).unwrap_or_default()
+    );
+
+    drop(client);
+    let client = restart_store_server(&mut tc, &addr).await?;
+
+    let got = client
+        .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+        .await?;
+    assert_eq!(want, got);
+
+    let mut rows = 0;
+    for part in got.unwrap_or_default() {
+        let action = ReadAction {
+            part: part.part,
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: db_name.to_string(),
+                table: tbl_name.to_string(),
+                schema: schema.clone(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let blocks = client
+            .read_partition(schema.clone(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        rows += blocks.iter().map(|b| b.num_rows()).sum::<usize>();
+    }
+    assert_eq!(6, rows);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_read_partition_with_read_info() -> anyhow::Result<()> {
+    // - A part carries what its read needs, the read does not look it up.
+    // - A part without it is looked up, the lookup fails if the table is unknown.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let db_name = "db1";
+    let tbl_name = "tb1";
 
-        fin_rx.await?;
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
 
-        drop(client);
+    let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64, 2, 3])]);
+    client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await?;
 
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+    let plan = ScanPlan {
+        schema_name: tbl_name.to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client
+        .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+        .await?
+        .unwrap_or_default();
+    assert_eq!(1, parts.len());
+    let part = &parts[0];
+
+    let read_action = |table: &str, read_info| ReadAction {
+        part: Part {
+            read_info,
+            ..part.part.clone()
+        },
+        push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+            db: db_name.to_string(),
+            table: table.to_string(),
+            schema: schema.clone(),
+            ..ReadDataSourcePlan::empty(0, None)
+        }),
+    };
 
-        // restart by opening existent meta db
-        tc.config.meta_config.boot = false;
-        crate::tests::start_store_server_with_context(&mut tc).await?;
+    tracing::info!("--- read a part with its read info");
+    {
+        let action = read_action(tbl_name, Some(part.read_info()));
+        let blocks = client
+            .read_partition(schema.clone(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(3, blocks.iter().map(|b| b.num_rows()).sum::<usize>());
     }
 
-    tokio::time::sleep(tokio::time::Duration::from_millis(10_000)).await;
-
-    // try to reconnect the restarted server.
-    let mut _client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
-
-    // TODO(xp): db and table are still in pure memory store. the following test will no pass.
-
-    // tracing::info!("--- get db");
-    // {
-    //     let res = client.get_database(db_name).await;
-    //     tracing::debug!("get present database res: {:?}", res);
-    //     let res = res?;
-    //     assert_eq!(1, res.database_id, "db1 id is 1");
-    //     assert_eq!(db_name, res.db, "db1.db is db1");
-    // }
-    //
-    // tracing::info!("--- get table");
-    // {
-    //     let got = client
-    //         .get_table(db_name.into(), table_name.into())
-    //         .await
-    //         .unwrap();
-    //     let want = GetTableActionResult {
-    //         table_id: 1,
-    //         db: db_name.into(),
-    //         name: table_name.into(),
-    //         schema: schema.clone(),
-    //     };
-    //     assert_eq!(want, got, "get created table");
-    // }
+    tracing::info!("--- read a part of an unknown table without its read info");
+    {
+        let action = read_action("tb2", None);
+        let res = client.read_partition(schema.clone(), &action).await;
+        let err = match res {
+            Ok(blocks) => blocks.try_collect::<Vec<_>>().await.unwrap_err(),
+            Err(e) => e,
+        };
+        assert_eq!(ErrorCode::UnknownTable("").code(), err.code());
+    }
 
     Ok(())
 }
@@ -231,6 +451,204 @@ async fn test_flight_create_database() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_list_databases() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let create = |db: &str, engine: &str| CreateDatabasePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        engine: engine.to_string(),
+        options: Default::default(),
+    };
+    let listed = |dbs: Vec<GetDatabaseActionResult>| {
+        dbs.into_iter()
+            .map(|db| (db.db, db.database_id, db.engine))
+            .collect::<Vec<_>>()
+    };
+
+    tracing::info!("--- list an empty store");
+    {
+        let res = client.list_databases().await?;
+        assert!(res.is_empty(), "no database in an empty store");
+    }
+
+    tracing::info!("--- list multiple databases, sorted by id");
+    {
+        // Created in the reverse order of their names, to tell the id order from the name order.
+        client.create_database(create("db_c", "Local")).await?;
+        client.create_database(create("db_b", "Remote")).await?;
+        client.create_database(create("db_a", "Local")).await?;
+
+        let res = client.list_databases().await?;
+        assert_eq!(
+            vec![
+                ("db_c".to_string(), 1, "Local".to_string()),
+                ("db_b".to_string(), 2, "Remote".to_string()),
+                ("db_a".to_string(), 3, "Local".to_string()),
+            ],
+            listed(res)
+        );
+    }
+
+    tracing::info!("--- list after a drop and a re-creation");
+    {
+        let plan = DropDatabasePlan {
+            if_exists: false,
+            db: "db_b".to_string(),
+        };
+        client.drop_database(plan).await?;
+
+        let res = client.list_databases().await?;
+        assert_eq!(
+            vec![
+                ("db_c".to_string(), 1, "Local".to_string()),
+                ("db_a".to_string(), 3, "Local".to_string()),
+            ],
+            listed(res)
+        );
+
+        // A database created again after its drop has a new id.
+        client.create_database(create("db_b", "Local")).await?;
+        client.create_database(create("db_d", "Local")).await?;
+
+        let res = client.list_databases().await?;
+        assert_eq!(
+            vec![
+                ("db_c".to_string(), 1, "Local".to_string()),
+                ("db_a".to_string(), 3, "Local".to_string()),
+                ("db_b".to_string(), 4, "Local".to_string()),
+                ("db_d".to_string(), 5, "Local".to_string()),
+            ],
+            listed(res)
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_list_tables() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let schema = Arc::new(DataSchema::new(vec![DataField::new(
+        "number",
+        DataType::UInt64,
+        false,
+    )]));
+    let create = |db: &str, table: &str, engine: &str| CreateTablePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        table: table.to_string(),
+        schema: schema.clone(),
+        options: [("opt".to_string(), table.to_string())]
+            .into_iter()
+            .collect(),
+        engine: engine.to_string(),
+    };
+    let listed = |tables: Vec<GetTableActionResult>| {
+        tables
+            .into_iter()
+            .map(|t| (t.db, t.name, t.engine))
+            .collect::<Vec<_>>()
+    };
+
+    tracing::info!("--- list the tables of an unknown database");
+    {
+        let res = client.list_tables("db1".to_string()).await;
+        let err = res.unwrap_err();
+        assert_eq!(3, err.code());
+        assert_eq!(ErrorCode::UnknownDatabase("").code(), err.code());
+    }
+
+    tracing::info!("--- each database lists only its own tables, sorted by name");
+    {
+        for db in ["db1", "db2"] {
+            client
+                .create_database(CreateDatabasePlan {
+                    if_not_exists: false,
+                    db: db.to_string(),
+                    engine: "Local".to_string(),
+                    options: Default::default(),
+                })
+                .await?;
+        }
+        assert!(client.list_tables("db1".to_string()).await?.is_empty());
+
+        let mut table_ids = vec![];
+        for (db, table, engine) in [
+            ("db1", "tb_b", "JSON"),
+            ("db1", "tb_a", "PARQUET"),
+            ("db2", "tb_c", "PARQUET"),
+            ("db1", "tb_c", "PARQUET"),
+        ] {
+            let res = client.create_table(create(db, table, engine)).await?;
+            table_ids.push(res.table_id);
+        }
+
+        let res = client.list_tables("db1".to_string()).await?;
+        assert_eq!(
+            vec![
+                ("db1".to_string(), "tb_a".to_string(), "PARQUET".to_string()),
+                ("db1".to_string(), "tb_b".to_string(), "JSON".to_string()),
+                ("db1".to_string(), "tb_c".to_string(), "PARQUET".to_string()),
+            ],
+            listed(res.clone())
+        );
+        assert_eq!(
+            vec![table_ids[1], table_ids[0], table_ids[3]],
+            res.iter().map(|t| t.table_id).collect::<Vec<_>>()
+        );
+        for t in &res {
+            assert_eq!(schema, t.schema);
+            assert_eq!(Some(&t.name), t.options.get("opt"));
+        }
+
+        let res = client.list_tables("db2".to_string()).await?;
+        assert_eq!(
+            vec![("db2".to_string(), "tb_c".to_string(), "PARQUET".to_string())],
+            listed(res.clone())
+        );
+        assert_eq!(table_ids[2], res[0].table_id);
+
+        // The same as getting the tables one by one.
+        let table = client
+            .get_table("db2".to_string(), "tb_c".to_string())
+            .await?;
+        assert_eq!(table, res[0]);
+    }
+
+    tracing::info!("--- a dropped table is not listed");
+    {
+        let plan = DropTablePlan {
+            if_exists: false,
+            db: "db1".to_string(),
+            table: "tb_b".to_string(),
+        };
+        client.drop_table(plan).await?;
+
+        let res = client.list_tables("db1".to_string()).await?;
+        assert_eq!(
+            vec![
+                ("db1".to_string(), "tb_a".to_string(), "PARQUET".to_string()),
+                ("db1".to_string(), "tb_c".to_string(), "PARQUET".to_string()),
+            ],
+            listed(res)
+        );
+        assert_eq!(1, client.list_tables("db2".to_string()).await?.len());
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_flight_create_get_table() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_store_ut!();
@@ -466,58 +884,172 @@ async fn test_flight_drop_table() -> anyhow::Result<()> {
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-async fn test_do_append() -> anyhow::Result<()> {
+async fn test_flight_rename_table() -> anyhow::Result<()> {
+    // - Rename a table with data: it keeps its id and parts, the old name is gone.
+    // - Renaming onto a taken name or renaming a missing table fails, unless if_exists.
+
     let (_log_guards, ut_span) = init_store_ut!();
     let _ent = ut_span.enter();
 
-    use std::sync::Arc;
-
-    use common_datavalues::prelude::*;
-    use common_planners::CreateDatabasePlan;
-    use common_planners::CreateTablePlan;
-
     let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
 
-    let schema = Arc::new(DataSchema::new(vec![
-        DataField::new("col_i", DataType::Int64, false),
-        DataField::new("col_s", DataType::String, false),
-    ]));
-    let db_name = "test_db";
-    let tbl_name = "test_tbl";
-
-    let series0 = Series::new(vec![0i64, 1, 2]);
-    let series1 = Series::new(vec!["str1", "str2", "str3"]);
-
-    let expected_rows = series0.len() * 2;
-    let expected_cols = 2;
-
-    let block = DataBlock::create_by_array(schema.clone(), vec![series0, series1]);
-    let batches = vec![block.clone(), block];
-    let num_batch = batches.len();
-    let stream = futures::stream::iter(batches);
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let db_name = "db1";
 
-    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
-    {
-        let plan = CreateDatabasePlan {
+    client
+        .create_database(CreateDatabasePlan {
             if_not_exists: false,
             db: db_name.to_string(),
             engine: "Local".to_string(),
             options: Default::default(),
-        };
-        let res = client.create_database(plan.clone()).await;
-        let res = res.unwrap();
-        assert_eq!(res.database_id, 1, "db created");
-        let plan = CreateTablePlan {
-            if_not_exists: false,
-            db: db_name.to_string(),
-            table: tbl_name.to_string(),
-            schema: schema.clone(),
-            options: maplit::hashmap! {"opt‐1".into() => "val-1".into()},
-            engine: "PARQUET".to_string(),
-        };
-        client.create_table(plan.clone()).await.unwrap();
+        })
+        .await?;
+    for tbl_name in ["tb1", "tb3"] {
+        client
+            .create_table(CreateTablePlan {
+                if_not_exists: false,
+                db: db_name.to_string(),
+                table: tbl_name.to_string(),
+                schema: schema.clone(),
+                options: Default::default(),
+                engine: "PARQUET".to_string(),
+            })
+            .await?;
     }
-    let res = client
+
+    let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64, 2, 3])]);
+    let stream = futures::stream::iter(vec![block.clone(), block]);
+    client
+        .append_data(
+            db_name.to_string(),
+            "tb1".to_string(),
+            schema.clone(),
+            Box::pin(stream),
+        )
+        .await?;
+
+    let table = client.get_table(db_name.into(), "tb1".into()).await?;
+    let parts = client
+        .read_plan(db_name.to_string(), "tb1".to_string(), &ScanPlan::empty())
+        .await?;
+    let meta_ver = client.get_database_meta(None).await?.unwrap().meta_ver;
+
+    let rename = |table: &str, new_table: &str, if_exists: bool| RenameTablePlan {
+        if_exists,
+        db: db_name.to_string(),
+        table: table.to_string(),
+        new_table: new_table.to_string(),
+    };
+
+    {
+        // rename: the same table and parts under the new name, meta version bumped
+        let res = client.rename_table(rename("tb1", "tb2", false)).await?;
+        assert_eq!(RenameTableActionResult {}, res);
+
+        let got = client.get_table(db_name.into(), "tb2".into()).await?;
+        assert_eq!(
+            GetTableActionResult {
+                name: "tb2".to_string(),
+                ..table.clone()
+            },
+            got
+        );
+
+        let err = client
+            .get_table(db_name.into(), "tb1".into())
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorCode::UnknownTable("").code(), err.code());
+
+        let got = client
+            .read_plan(db_name.to_string(), "tb2".to_string(), &ScanPlan::empty())
+            .await?;
+        assert_eq!(2, got.as_ref().map(|p| p.len()).unwrap_or_default());
+        assert_eq!(parts, got);
+
+        let res = client.get_database_meta(Some(meta_ver)).await?;
+        assert!(res.is_some());
+    }
+
+    {
+        // rename onto a taken name
+        let err = client
+            .rename_table(rename("tb2", "tb3", true))
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorCode::TableAlreadyExists("").code(), err.code());
+        assert_eq!(4003, err.code());
+    }
+
+    {
+        // rename a missing table
+        let err = client
+            .rename_table(rename("tb1", "tb4", false))
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorCode::UnknownTable("").code(), err.code());
+
+        let res = client.rename_table(rename("tb1", "tb4", true)).await?;
+        assert_eq!(RenameTableActionResult {}, res);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_do_append() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    use std::sync::Arc;
+
+    use common_datavalues::prelude::*;
+    use common_planners::CreateDatabasePlan;
+    use common_planners::CreateTablePlan;
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+
+    let schema = Arc::new(DataSchema::new(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ]));
+    let db_name = "test_db";
+    let tbl_name = "test_tbl";
+
+    let series0 = Series::new(vec![0i64, 1, 2]);
+    let series1 = Series::new(vec!["str1", "str2", "str3"]);
+
+    let expected_rows = series0.len() * 2;
+    let expected_cols = 2;
+
+    let block = DataBlock::create_by_array(schema.clone(), vec![series0, series1]);
+    let batches = vec![block.clone(), block];
+    let num_batch = batches.len();
+    let stream = futures::stream::iter(batches);
+
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    {
+        let plan = CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        };
+        let res = client.create_database(plan.clone()).await;
+        let res = res.unwrap();
+        assert_eq!(res.database_id, 1, "db created");
+        let plan = CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: maplit::hashmap! {"opt‐1".into() => "val-1".into()},
+            engine: "PARQUET".to_string(),
+        };
+        client.create_table(plan.clone()).await.unwrap();
+    }
+    let res = client
         .append_data(
             db_name.to_string(),
             tbl_name.to_string(),
@@ -537,6 +1069,276 @@ async fn test_do_append() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_do_append_schema_mismatch() -> anyhow::Result<()> {
+    // - Append blocks whose schema does not match the table's.
+    // - Each append is rejected naming the column, and no part is written.
+    // - A subset of the columns in the table's order is accepted.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "test_db";
+    let tbl_name = "test_tbl";
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ]);
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    let append = |fields: Vec<DataField>, columns: Vec<Series>| {
+        let schema = DataSchemaRefExt::create(fields);
+        let block = DataBlock::create_by_array(schema.clone(), columns);
+        client.append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema,
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+    };
+    let parts_count = || async {
+        let parts = client
+            .read_plan(
+                db_name.to_string(),
+                tbl_name.to_string(),
+                &ScanPlan::empty(),
+            )
+            .await?;
+        Ok::<_, ErrorCode>(parts.unwrap_or_default().len())
+    };
+
+    let cases = vec![
+        (
+            "wrong type",
+            vec![
+                DataField::new("col_i", DataType::Int32, false),
+                DataField::new("col_s", DataType::String, false),
+            ],
+            vec![Series::new(vec![0i32, 1]), Series::new(vec!["a", "b"])],
+            "column `col_i` is Int32, expected Int64",
+        ),
+        (
+            "wrong order",
+            vec![
+                DataField::new("col_s", DataType::String, false),
+                DataField::new("col_i", DataType::Int64, false),
+            ],
+            vec![Series::new(vec!["a", "b"]), Series::new(vec![0i64, 1])],
+            "column `col_i` comes after `col_s`",
+        ),
+        (
+            "duplicated column",
+            vec![
+                DataField::new("col_i", DataType::Int64, false),
+                DataField::new("col_i", DataType::Int64, false),
+            ],
+            vec![Series::new(vec![0i64, 1]), Series::new(vec![2i64, 3])],
+            "column `col_i` is given twice",
+        ),
+        (
+            "extra column",
+            vec![
+                DataField::new("col_i", DataType::Int64, false),
+                DataField::new("col_s", DataType::String, false),
+                DataField::new("col_x", DataType::Int64, false),
+            ],
+            vec![
+                Series::new(vec![0i64, 1]),
+                Series::new(vec!["a", "b"]),
+                Series::new(vec![0i64, 1]),
+            ],
+            "column `col_x` is not in the table",
+        ),
+        (
+            "nullable into not nullable",
+            vec![
+                DataField::new("col_i", DataType::Int64, true),
+                DataField::new("col_s", DataType::String, false),
+            ],
+            vec![Series::new(vec![0i64, 1]), Series::new(vec!["a", "b"])],
+            "column `col_i` is nullable Int64, expected not nullable Int64",
+        ),
+    ];
+
+    for (case, fields, columns, want) in cases {
+        let err = append(fields, columns).await.unwrap_err();
+        assert_eq!(ErrorCode::SchemaMismatch("").code(), err.code(), "{}", case);
+        assert!(err.message().contains(want), "{}: {}", case, err.message());
+        assert_eq!(0, parts_count().await?, "{}", case);
+    }
+
+    append(
+        vec![DataField::new("col_s", DataType::String, false)],
+        vec![Series::new(vec!["a", "b"])],
+    )
+    .await?;
+    assert_eq!(1, parts_count().await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_read_plan_pruned_by_column_statistics() -> anyhow::Result<()> {
+    // - Append two blocks of disjoint ranges of `col_i`.
+    // - The append result and the read plan have the min, max and nulls of each part.
+    // - A read plan filtered by `col_i > 100` has only the part of the larger values.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "test_db";
+    let tbl_name = "test_tbl";
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, true),
+    ]);
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    let blocks = vec![
+        DataBlock::create_by_array(schema.clone(), vec![
+            Series::new(vec![1i64, 50, 100]),
+            Series::new(vec![Some("a"), None, Some("c")]),
+        ]),
+        DataBlock::create_by_array(schema.clone(), vec![
+            Series::new(vec![101i64, 150, 200]),
+            Series::new(vec![Some("x"), Some("y"), Some("z")]),
+        ]),
+    ];
+    let res = client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(futures::stream::iter(blocks)),
+        )
+        .await?;
+    assert_eq!(2, res.parts.len());
+
+    let range = |min: ColumnBound, max: ColumnBound, null_count: u64| ColumnStatistics {
+        min: Some(min),
+        max: Some(max),
+        null_count,
+    };
+    let want = vec![
+        [
+            (
+                "col_i".to_string(),
+                range(ColumnBound::Int(1), ColumnBound::Int(100), 0),
+            ),
+            (
+                "col_s".to_string(),
+                range(
+                    ColumnBound::String(b"a".to_vec()),
+                    ColumnBound::String(b"c".to_vec()),
+                    1,
+                ),
+            ),
+        ],
+        [
+            (
+                "col_i".to_string(),
+                range(ColumnBound::Int(101), ColumnBound::Int(200), 0),
+            ),
+            (
+                "col_s".to_string(),
+                range(
+                    ColumnBound::String(b"x".to_vec()),
+                    ColumnBound::String(b"z".to_vec()),
+                    0,
+                ),
+            ),
+        ],
+    ]
+    .into_iter()
+    .map(|columns| columns.into_iter().collect::<PartColumnStatistics>())
+    .collect::<Vec<_>>();
+    let got = res
+        .parts
+        .iter()
+        .map(|p| p.column_statistics.clone().unwrap_or_default())
+        .collect::<Vec<_>>();
+    assert_eq!(want, got);
+
+    let read_plan = |filters: Vec<Expression>| {
+        let mut plan = ScanPlan {
+            schema_name: tbl_name.to_string(),
+            ..ScanPlan::empty()
+        };
+        plan.push_downs.filters = filters;
+        let client = &client;
+        async move {
+            client
+                .read_plan_with_pruning(db_name.to_string(), tbl_name.to_string(), &plan)
+                .await
+        }
+    };
+
+    let reply = read_plan(vec![]).await?;
+    let parts = reply.parts.unwrap_or_default();
+    assert_eq!(2, parts.len());
+    let got = parts
+        .iter()
+        .map(|p| p.column_statistics.clone().unwrap_or_default())
+        .collect::<Vec<_>>();
+    assert_eq!(want, got);
+
+    let reply = read_plan(vec![col("col_i").gt(lit(100i64))]).await?;
+    let parts = reply.parts.unwrap_or_default();
+    assert_eq!(1, parts.len());
+    assert_eq!(res.parts[1].location, parts[0].part.name);
+    assert_eq!(2, reply.pruning.range_checked);
+    assert_eq!(1, reply.pruning.pruned_by_range);
+
+    let reply = read_plan(vec![col("col_s").lt(lit("b".as_bytes()))]).await?;
+    let parts = reply.parts.unwrap_or_default();
+    assert_eq!(1, parts.len());
+    assert_eq!(res.parts[0].location, parts[0].part.name);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_scan_partition() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_store_ut!();
@@ -630,171 +1432,533 @@ async fn test_scan_partition() -> anyhow::Result<()> {
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-async fn test_flight_generic_kv_mget() -> anyhow::Result<()> {
+async fn test_scan_partition_projection() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_store_ut!();
     let _ent = ut_span.enter();
-    {
-        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_list");
-        let _ent = span.enter();
-
-        let (_tc, addr) = crate::tests::start_store_server().await?;
 
-        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    let (_tc, addr) = crate::tests::start_store_server().await?;
 
-        client
-            .upsert_kv("k1", MatchSeq::Any, Some(b"v1".to_vec()), None)
-            .await?;
-        client
-            .upsert_kv("k2", MatchSeq::Any, Some(b"v2".to_vec()), None)
-            .await?;
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ]);
+    let db_name = "test_db";
+    let tbl_name = "test_tbl";
 
-        let res = client
-            .mget_kv(&["k1".to_string(), "k2".to_string()])
-            .await?;
-        assert_eq!(res.result, vec![
-            Some((1, KVValue {
-                meta: None,
-                value: b"v1".to_vec()
-            })),
-            // NOTE, the sequence number is increased globally (inside the namespace of generic kv)
-            Some((2, KVValue {
-                meta: None,
-                value: b"v2".to_vec()
-            })),
-        ]);
+    let block = DataBlock::create(schema.clone(), vec![
+        DataColumn::Array(Series::new(vec![0i64, 1, 2])),
+        DataColumn::Array(Series::new(vec!["str1", "str2", "str3"])),
+    ]);
 
-        let res = client
-            .mget_kv(&["k1".to_string(), "key_no exist".to_string()])
-            .await?;
-        assert_eq!(res.result, vec![
-            Some((1, KVValue {
-                meta: None,
-                value: b"v1".to_vec()
-            })),
-            None
-        ]);
-    }
-    Ok(())
-}
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+    client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(futures::stream::iter(vec![block.clone(), block])),
+        )
+        .await?;
 
-#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-async fn test_flight_generic_kv_list() -> anyhow::Result<()> {
-    let (_log_guards, ut_span) = init_store_ut!();
-    let _ent = ut_span.enter();
-    {
-        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_list");
-        let _ent = span.enter();
+    let plan = ScanPlan {
+        schema_name: tbl_name.to_string(),
+        ..ScanPlan::empty()
+    };
 
-        let (_tc, addr) = crate::tests::start_store_server().await?;
-
-        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
-
-        let mut values = vec![];
-        {
-            client
-                .upsert_kv("t", MatchSeq::Any, Some("".as_bytes().to_vec()), None)
+    // Reads the parts of a plan with the schema of the plan, returns the blocks.
+    let read = |projection: Vec<String>| {
+        let client = &client;
+        let plan = &plan;
+        async move {
+            let reply = client
+                .read_plan_with_projection(
+                    db_name.to_string(),
+                    tbl_name.to_string(),
+                    plan,
+                    projection,
+                )
                 .await?;
-
-            for i in 0..9 {
-                let key = format!("__users/{}", i);
-                let val = format!("val_{}", i);
-                values.push(val.clone());
-                client
-                    .upsert_kv(&key, MatchSeq::Any, Some(val.as_bytes().to_vec()), None)
+            let read_schema = reply
+                .projected_schema
+                .clone()
+                .unwrap_or_else(|| schema.clone());
+
+            let mut blocks = vec![];
+            for part in reply.parts.clone().unwrap_or_default() {
+                let action = ReadAction {
+                    part: part.part,
+                    push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                        db: db_name.to_string(),
+                        table: tbl_name.to_string(),
+                        schema: read_schema.clone(),
+                        ..ReadDataSourcePlan::empty(0, None)
+                    }),
+                };
+                let mut part_blocks = client
+                    .read_partition(read_schema.clone(), &action)
+                    .await?
+                    .try_collect::<Vec<_>>()
                     .await?;
+                blocks.append(&mut part_blocks);
             }
-            client
-                .upsert_kv("v", MatchSeq::Any, Some(b"".to_vec()), None)
-                .await?;
+            Ok::<_, ErrorCode>((reply, blocks))
         }
+    };
+
+    // an empty projection is all the columns
+    let (full, full_blocks) = read(vec![]).await?;
+    assert!(full.projected_schema.is_none());
+    assert!(full_blocks.iter().all(|b| b.num_columns() == 2));
+
+    let (projected, projected_blocks) = read(vec!["col_s".to_string()]).await?;
+    assert_eq!(
+        Some(DataSchemaRefExt::create(vec![DataField::new(
+            "col_s",
+            DataType::String,
+            false
+        )])),
+        projected.projected_schema
+    );
+    assert_eq!(
+        6,
+        projected_blocks.iter().map(|b| b.num_rows()).sum::<usize>()
+    );
+    assert!(projected_blocks.iter().all(|b| b.num_columns() == 1));
+    assert_eq!(
+        DataValue::String(Some(b"str1".to_vec())),
+        projected_blocks[0].column(0).try_get(0)?
+    );
+
+    let bytes = |blocks: &[DataBlock]| blocks.iter().map(|b| b.memory_size()).sum::<usize>();
+    assert!(bytes(&projected_blocks) < bytes(&full_blocks));
+
+    // the bytes are the sizes of the projected columns recorded in the parts
+    let read_bytes = |reply: &ReadPlanReply| {
+        let parts = reply.parts.clone().unwrap_or_default();
+        parts.iter().map(|p| p.stats.read_bytes).sum::<usize>()
+    };
+    assert!(read_bytes(&projected) > 0);
+    assert!(read_bytes(&projected) < read_bytes(&full));
+    let parts = projected.parts.clone().unwrap_or_default();
+    assert!(parts.iter().all(|p| p.stats.is_exact));
+
+    let res = read(vec!["col_x".to_string()]).await;
+    assert_eq!(ErrorCode::UnknownColumn("").code(), res.unwrap_err().code());
 
-        let res = client.prefix_list_kv("__users/").await?;
-        assert_eq!(
-            res.iter()
-                .map(|(_key, (_s, val))| val.clone())
-                .collect::<Vec<_>>(),
-            values
-                .iter()
-                .map(|v| KVValue {
-                    meta: None,
-                    value: v.as_bytes().to_vec()
-                })
-                .collect::<Vec<_>>()
-        );
-    }
     Ok(())
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-async fn test_flight_generic_kv_delete() -> anyhow::Result<()> {
+async fn test_flight_truncate_table() -> anyhow::Result<()> {
+    // - Append two batches as files and truncate the table: no parts and no files are left.
+    // - Append again: only the new rows are read.
+
     let (_log_guards, ut_span) = init_store_ut!();
     let _ent = ut_span.enter();
-    {
-        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_list");
-        let _ent = span.enter();
 
-        let (_tc, addr) = crate::tests::start_store_server().await?;
+    let mut tc = new_test_context();
+    tc.config.inline_part_max_bytes = 0;
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
 
-        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let db_name = "db1";
+    let tbl_name = "tb1";
 
-        let test_key = "test_key";
-        client
-            .upsert_kv(test_key, MatchSeq::Any, Some(b"v1".to_vec()), None)
-            .await?;
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+    let table_id = client
+        .get_table(db_name.into(), tbl_name.into())
+        .await?
+        .table_id;
+
+    let append = |values: Vec<i64>| {
+        let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(values)]);
+        client.append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+    };
+    append(vec![1, 2, 3]).await?;
+    append(vec![4, 5, 6]).await?;
 
-        let current = client.get_kv(test_key).await?;
-        if let Some((seq, _val)) = current.result {
-            // seq mismatch
-            let wrong_seq = Some(seq + 1);
-            let res = client
-                .upsert_kv(test_key, wrong_seq.into(), None, None)
-                .await?;
-            assert_eq!(res.prev, res.result);
+    let plan = ScanPlan::empty();
+    let parts = client
+        .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+        .await?
+        .unwrap_or_default();
+    assert_eq!(2, parts.len());
+    let files = parts
+        .iter()
+        .map(|p| std::path::Path::new(&tc.config.local_fs_dir).join(&p.part.name))
+        .collect::<Vec<_>>();
+    assert!(files.iter().all(|f| f.exists()));
 
-            // seq match
-            let res = client
-                .upsert_kv(test_key, MatchSeq::Exact(seq), None, None)
-                .await?;
-            assert!(res.result.is_none());
+    let res = client
+        .truncate(db_name.to_string(), tbl_name.to_string())
+        .await?;
+    assert_eq!(2, res.truncated_table_data_parts_count);
 
-            // read nothing
-            let r = client.get_kv(test_key).await?;
-            assert!(r.result.is_none());
-        } else {
-            panic!("expecting a value, but got nothing");
-        }
+    let got = client
+        .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+        .await?;
+    assert!(got.unwrap_or_default().is_empty());
+    assert!(files.iter().all(|f| !f.exists()));
 
-        // key not exist
-        let res = client
-            .upsert_kv("not exists", MatchSeq::Any, None, None)
+    // the table definition is kept
+    let table = client.get_table(db_name.into(), tbl_name.into()).await?;
+    assert_eq!(table_id, table.table_id);
+    assert_eq!(schema, table.schema);
+
+    append(vec![7, 8]).await?;
+
+    let parts = client
+        .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+        .await?
+        .unwrap_or_default();
+    assert_eq!(1, parts.len());
+
+    let mut values = vec![];
+    for part in parts {
+        let action = ReadAction {
+            part: part.part,
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: db_name.to_string(),
+                table: tbl_name.to_string(),
+                schema: schema.clone(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let blocks = client
+            .read_partition(schema.clone(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
             .await?;
-        assert_eq!(None, res.prev);
-        assert_eq!(None, res.result);
+        for block in blocks {
+            for i in 0..block.num_rows() {
+                values.push(block.column(0).try_get(i)?.as_i64()?);
+            }
+        }
+    }
+    assert_eq!(vec![7, 8], values);
 
-        // do not care seq
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_vacuum_trash_removes_files() -> anyhow::Result<()> {
+    // - Append two batches as files and drop the table: the files are kept while it is in trash.
+    // - Once the table expires, the vacuum purges it and deletes the files.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let clock = VirtualClock::create();
+    let mut tc = new_test_context();
+    tc.config.inline_part_max_bytes = 0;
+    tc.config.meta_config.table_trash_retention = 1;
+    tc.config.meta_config.clock = SharedClock::create(clock.clone());
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+    let db_name = "db1";
+    let tbl_name = "tb1";
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    for values in [vec![1, 2, 3], vec![4, 5, 6]] {
+        let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(values)]);
         client
-            .upsert_kv(test_key, MatchSeq::Any, Some(b"v2".to_vec()), None)
+            .append_data(
+                db_name.to_string(),
+                tbl_name.to_string(),
+                schema.clone(),
+                Box::pin(futures::stream::iter(vec![block])),
+            )
             .await?;
+    }
 
-        let res = client
-            .upsert_kv(test_key, MatchSeq::Any, None, None)
-            .await?;
-        assert_eq!(
-            (
-                Some((2, KVValue {
-                    meta: None,
-                    value: b"v2".to_vec()
-                })),
-                None
-            ),
-            (res.prev, res.result)
-        );
+    let parts = client
+        .read_plan(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            &ScanPlan::empty(),
+        )
+        .await?
+        .unwrap_or_default();
+    assert_eq!(2, parts.len());
+    let files = parts
+        .iter()
+        .map(|p| std::path::Path::new(&tc.config.local_fs_dir).join(&p.part.name))
+        .collect::<Vec<_>>();
+
+    client
+        .drop_table(DropTablePlan {
+            if_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+        })
+        .await?;
+    assert!(files.iter().all(|f| f.exists()));
+
+    clock.advance(Duration::from_secs(3));
+    for _ in 0..50 {
+        if files.iter().all(|f| !f.exists()) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
     }
+    assert!(files.iter().all(|f| !f.exists()));
+
     Ok(())
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-async fn test_flight_generic_kv_update() -> anyhow::Result<()> {
+async fn test_flight_modify_column() -> anyhow::Result<()> {
+    // - Create a table with an Int32 column and append to it.
+    // - Widen the column to Int64: the existing part is read as Int64.
+    // - Append Int64: the parts of both types are read as Int64.
+    // - Narrowing the column back is rejected, with the part that blocks it.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "db1";
+    let tbl_name = "tb1";
+
+    let schema_i32 = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int32, false)]);
+    let schema_i64 = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema_i32.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    let block = DataBlock::create_by_array(schema_i32.clone(), vec![Series::new(vec![
+        1i32, -2, 2147483647,
+    ])]);
+    client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema_i32.clone(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await?;
+
+    let read_all = |schema: DataSchemaRef| {
+        let client = &client;
+        async move {
+            let plan = ScanPlan {
+                schema_name: tbl_name.to_string(),
+                ..ScanPlan::empty()
+            };
+            let parts = client
+                .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+                .await?
+                .unwrap_or_default();
+
+            let mut blocks = vec![];
+            for part in parts.iter() {
+                let action = ReadAction {
+                    part: part.part.clone(),
+                    push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                        db: db_name.to_string(),
+                        table: tbl_name.to_string(),
+                        schema: schema.clone(),
+                        ..ReadDataSourcePlan::empty(0, None)
+                    }),
+                };
+                let mut got = client
+                    .read_partition(schema.clone(), &action)
+                    .await?
+                    .try_collect::<Vec<_>>()
+                    .await?;
+                blocks.append(&mut got);
+            }
+            Ok::<_, anyhow::Error>(blocks)
+        }
+    };
+
+    // widen to Int64
+
+    let plan = ModifyColumnPlan {
+        db: db_name.to_string(),
+        table: tbl_name.to_string(),
+        column: DataField::new("a", DataType::Int64, false),
+    };
+    client.modify_column(plan).await?;
+
+    let table = client.get_table(db_name.into(), tbl_name.into()).await?;
+    assert_eq!(schema_i64, table.schema);
+
+    let blocks = read_all(table.schema.clone()).await?;
+    assert_eq!(DataType::Int64, blocks[0].column(0).data_type());
+    common_datablocks::assert_blocks_sorted_eq(
+        vec![
+            "+------------+",
+            "| a          |",
+            "+------------+",
+            "| -2         |",
+            "| 1          |",
+            "| 2147483647 |",
+            "+------------+",
+        ],
+        &blocks,
+    );
+
+    // appends must arrive in the new type
+
+    let block = DataBlock::create_by_array(schema_i32.clone(), vec![Series::new(vec![3i32])]);
+    let res = client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema_i32.clone(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await;
+    assert!(res.is_err());
+
+    let block =
+        DataBlock::create_by_array(schema_i64.clone(), vec![Series::new(vec![4294967296i64])]);
+    client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema_i64.clone(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await?;
+
+    let blocks = read_all(table.schema.clone()).await?;
+    common_datablocks::assert_blocks_sorted_eq(
+        vec![
+            "+------------+",
+            "| a          |",
+            "+------------+",
+            "| -2         |",
+            "| 1          |",
+            "| 2147483647 |",
+            "| 4294967296 |",
+            "+------------+",
+        ],
+        &blocks,
+    );
+
+    // narrowing is rejected
+
+    let plan = ModifyColumnPlan {
+        db: db_name.to_string(),
+        table: tbl_name.to_string(),
+        column: DataField::new("a", DataType::Int32, false),
+    };
+    let res = client.modify_column(plan).await;
+    let err = res.unwrap_err();
+    assert_eq!(ErrorCode::IllegalSchema("").code(), err.code());
+    assert!(
+        err.message()
+            .contains("Int64 can not be safely converted to Int32"),
+        "{}",
+        err.message()
+    );
+    assert!(
+        err.message().contains("blocked by parts"),
+        "{}",
+        err.message()
+    );
+    assert!(
+        err.message().contains("(stored as Int64)"),
+        "{}",
+        err.message()
+    );
+    assert!(
+        !err.message().contains("(stored as Int32)"),
+        "{}",
+        err.message()
+    );
+
+    let table = client.get_table(db_name.into(), tbl_name.into()).await?;
+    assert_eq!(schema_i64, table.schema);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_mget() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_store_ut!();
     let _ent = ut_span.enter();
     {
@@ -805,42 +1969,364 @@ async fn test_flight_generic_kv_update() -> anyhow::Result<()> {
 
         let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
 
-        let test_key = "test_key_for_update";
-
-        let r = client
-            .upsert_kv(test_key, MatchSeq::GE(1), Some(b"v1".to_vec()), None)
+        client
+            .upsert_kv("k1", MatchSeq::Any, Some(b"v1".to_vec()), None)
+            .await?;
+        client
+            .upsert_kv("k2", MatchSeq::Any, Some(b"v2".to_vec()), None)
             .await?;
-        assert_eq!((None, None), (r.prev, r.result), "not changed");
 
-        let r = client
-            .upsert_kv(test_key, MatchSeq::Any, Some(b"v1".to_vec()), None)
+        let res = client
+            .mget_kv(&["k1".to_string(), "k2".to_string()])
             .await?;
-        assert_eq!(
+        assert_eq!(res.result, vec![
             Some((1, KVValue {
                 meta: None,
                 value: b"v1".to_vec()
             })),
-            r.result
-        );
-        let seq = r.result.unwrap().0;
+            // NOTE, the sequence number is increased globally (inside the namespace of generic kv)
+            Some((2, KVValue {
+                meta: None,
+                value: b"v2".to_vec()
+            })),
+        ]);
 
-        // unmatched seq
-        let r = client
-            .upsert_kv(
-                test_key,
-                MatchSeq::Exact(seq + 1),
-                Some(b"v2".to_vec()),
-                None,
-            )
+        let res = client
+            .mget_kv(&["k1".to_string(), "key_no exist".to_string()])
             .await?;
-        assert_eq!(
+        assert_eq!(res.result, vec![
             Some((1, KVValue {
                 meta: None,
                 value: b"v1".to_vec()
             })),
-            r.prev
-        );
-        assert_eq!(
+            None
+        ]);
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_mget_strict() -> anyhow::Result<()> {
+    // - Write more keys than a chunk of an mget read, the values are in the order of the keys.
+    // - A strict mget with an absent key fails naming it, a plain one returns None for it.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let keys = (0..2500)
+        .map(|i| format!("mget_strict/{:04}", i))
+        .collect::<Vec<_>>();
+    for batch in keys.chunks(100) {
+        futures::future::try_join_all(
+            batch.iter().map(|key| {
+                client.upsert_kv(key, MatchSeq::Any, Some(key.as_bytes().to_vec()), None)
+            }),
+        )
+        .await?;
+    }
+
+    // Reversed, the order of the values follows the keys, not the seqs.
+    let mut wanted = keys.clone();
+    wanted.reverse();
+    let values = client.mget_kv_strict(&wanted).await?;
+    assert_eq!(wanted.len(), values.len());
+    for (key, (_seq, v)) in wanted.iter().zip(values.iter()) {
+        assert_eq!(key.as_bytes(), &v.value[..]);
+    }
+
+    let mut wanted = keys.clone();
+    wanted.insert(1500, "mget_strict/absent".to_string());
+    let res = client.mget_kv_strict(&wanted).await;
+    let err = res.unwrap_err();
+    assert_eq!(ErrorCode::UnknownKey("").code(), err.code());
+    assert!(err.message().contains("mget_strict/absent"), "{}", err);
+
+    let res = client.mget_kv(&wanted).await?;
+    assert_eq!(wanted.len(), res.result.len());
+    assert_eq!(None, res.result[1500]);
+    assert_eq!(
+        Some(keys[1500].as_bytes().to_vec()),
+        res.result[1501].clone().map(|(_, v)| v.value)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_list() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+    {
+        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_list");
+        let _ent = span.enter();
+
+        let (_tc, addr) = crate::tests::start_store_server().await?;
+
+        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+        let mut values = vec![];
+        {
+            client
+                .upsert_kv("t", MatchSeq::Any, Some("".as_bytes().to_vec()), None)
+                .await?;
+
+            for i in 0..9 {
+                let key = format!("__users/{}", i);
+                let val = format!("val_{}", i);
+                values.push(val.clone());
+                client
+                    .upsert_kv(&key, MatchSeq::Any, Some(val.as_bytes().to_vec()), None)
+                    .await?;
+            }
+            client
+                .upsert_kv("v", MatchSeq::Any, Some(b"".to_vec()), None)
+                .await?;
+        }
+
+        let res = client.prefix_list_kv("__users/").await?;
+        assert_eq!(
+            res.iter()
+                .map(|(_key, (_s, val))| val.clone())
+                .collect::<Vec<_>>(),
+            values
+                .iter()
+                .map(|v| KVValue {
+                    meta: None,
+                    value: v.as_bytes().to_vec()
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_list_in_pages() -> anyhow::Result<()> {
+    // - Listing a prefix page by page, each after the last key of the previous one, gives the
+    //   same records as listing it at once.
+    // - The expired records are skipped.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    client
+        .upsert_kv("__users", MatchSeq::Any, Some(b"".to_vec()), None)
+        .await?;
+    for i in 0..1000 {
+        let expire_at = if i % 10 == 0 { Some(now - 1) } else { None };
+        client
+            .upsert_kv(
+                &format!("__users/{:04}", i),
+                MatchSeq::Any,
+                Some(b"v".to_vec()),
+                Some(KVMeta { expire_at }),
+            )
+            .await?;
+    }
+    client
+        .upsert_kv("__users0", MatchSeq::Any, Some(b"".to_vec()), None)
+        .await?;
+
+    let all = client.prefix_list_kv("__users/").await?;
+    assert_eq!(900, all.len());
+    assert!(all.iter().all(|(k, _)| !k.ends_with('0')));
+
+    let mut paged = vec![];
+    let mut after_key = None;
+    let mut pages = 0;
+    loop {
+        let page = client
+            .prefix_list_kv_page("__users/", Some(100), after_key)
+            .await?;
+        assert!(page.entries.len() <= 100);
+        pages += 1;
+        after_key = page.entries.last().map(|(k, _)| k.clone());
+        paged.extend(page.entries);
+        if !page.more {
+            break;
+        }
+    }
+    assert_eq!(9, pages);
+    assert_eq!(all, paged);
+
+    let page = client
+        .prefix_list_kv_page("__users/", Some(100), Some("__users/0998".to_string()))
+        .await?;
+    assert_eq!(
+        vec!["__users/0999".to_string()],
+        page.entries.into_iter().map(|(k, _)| k).collect::<Vec<_>>()
+    );
+    assert!(!page.more);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_delete_prefix() -> anyhow::Result<()> {
+    // - Deleting a prefix removes all of its records in one request.
+    // - The records under the other prefix and the seq of the generic-kv are untouched.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let mut last_seq = 0;
+    for i in 0..50 {
+        let prefix = if i % 2 == 0 { "__users/" } else { "__roles/" };
+        let r = client
+            .upsert_kv(
+                &format!("{}{}", prefix, i),
+                MatchSeq::Any,
+                Some(b"v".to_vec()),
+                None,
+            )
+            .await?;
+        last_seq = r.result.unwrap().0;
+    }
+    let roles = client.prefix_list_kv("__roles/").await?;
+
+    assert_eq!(25, client.delete_prefix_kv("__users/").await?);
+    assert!(client.prefix_list_kv("__users/").await?.is_empty());
+    assert_eq!(roles, client.prefix_list_kv("__roles/").await?);
+    assert_eq!(0, client.delete_prefix_kv("__users/").await?);
+
+    let r = client
+        .upsert_kv("__users/new", MatchSeq::Any, Some(b"v".to_vec()), None)
+        .await?;
+    assert_eq!(Some(last_seq + 1), r.result.map(|(seq, _)| seq));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_delete() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+    {
+        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_list");
+        let _ent = span.enter();
+
+        let (_tc, addr) = crate::tests::start_store_server().await?;
+
+        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+        let test_key = "test_key";
+        client
+            .upsert_kv(test_key, MatchSeq::Any, Some(b"v1".to_vec()), None)
+            .await?;
+
+        let current = client.get_kv(test_key).await?;
+        if let Some((seq, _val)) = current.result {
+            // seq mismatch
+            let wrong_seq = Some(seq + 1);
+            let res = client
+                .upsert_kv(test_key, wrong_seq.into(), None, None)
+                .await?;
+            assert_eq!(res.prev, res.result);
+
+            // seq match
+            let res = client
+                .upsert_kv(test_key, MatchSeq::Exact(seq), None, None)
+                .await?;
+            assert!(res.result.is_none());
+
+            // read nothing
+            let r = client.get_kv(test_key).await?;
+            assert!(r.result.is_none());
+        } else {
+            panic!("expecting a value, but got nothing");
+        }
+
+        // key not exist
+        let res = client
+            .upsert_kv("not exists", MatchSeq::Any, None, None)
+            .await?;
+        assert_eq!(None, res.prev);
+        assert_eq!(None, res.result);
+
+        // do not care seq
+        client
+            .upsert_kv(test_key, MatchSeq::Any, Some(b"v2".to_vec()), None)
+            .await?;
+
+        let res = client
+            .upsert_kv(test_key, MatchSeq::Any, None, None)
+            .await?;
+        assert_eq!(
+            (
+                Some((2, KVValue {
+                    meta: None,
+                    value: b"v2".to_vec()
+                })),
+                None
+            ),
+            (res.prev, res.result)
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_update() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+    {
+        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_list");
+        let _ent = span.enter();
+
+        let (_tc, addr) = crate::tests::start_store_server().await?;
+
+        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+        let test_key = "test_key_for_update";
+
+        let r = client
+            .upsert_kv(test_key, MatchSeq::GE(1), Some(b"v1".to_vec()), None)
+            .await?;
+        assert_eq!((None, None), (r.prev, r.result), "not changed");
+
+        let r = client
+            .upsert_kv(test_key, MatchSeq::Any, Some(b"v1".to_vec()), None)
+            .await?;
+        assert_eq!(
+            Some((1, KVValue {
+                meta: None,
+                value: b"v1".to_vec()
+            })),
+            r.result
+        );
+        let seq = r.result.unwrap().0;
+
+        // unmatched seq
+        let r = client
+            .upsert_kv(
+                test_key,
+                MatchSeq::Exact(seq + 1),
+                Some(b"v2".to_vec()),
+                None,
+            )
+            .await?;
+        assert_eq!(
+            Some((1, KVValue {
+                meta: None,
+                value: b"v1".to_vec()
+            })),
+            r.prev
+        );
+        assert_eq!(
             Some((1, KVValue {
                 meta: None,
                 value: b"v1".to_vec()
@@ -1001,26 +2487,217 @@ async fn test_flight_generic_kv_update_meta() -> anyhow::Result<()> {
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-async fn test_flight_generic_kv_timeout() -> anyhow::Result<()> {
-    // - Test get  expired and non-expired.
-    // - Test mget expired and non-expired.
-    // - Test list expired and non-expired.
-    // - Test update with a new expire value.
+async fn test_flight_generic_kv_upsert_lean_on_conflict() -> anyhow::Result<()> {
+    // - An upsert rejected by the seq replies with the current value, or only with its seq and
+    //   meta if it is lean.
+    // - The seq in a lean reply is enough to retry.
+    // - A lean upsert that is applied replies as usual.
 
     let (_log_guards, ut_span) = init_store_ut!();
     let _ent = ut_span.enter();
-    {
-        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_timeout");
-        let _ent = span.enter();
 
-        let (_tc, addr) = crate::tests::start_store_server().await?;
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
 
-        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let meta = Some(KVMeta {
+        expire_at: Some(now + 100),
+    });
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+    let key = "test_key_lean";
+    let big = vec![b'x'; 64 * 1024];
+    let r = client
+        .upsert_kv(key, MatchSeq::Any, Some(big.clone()), meta.clone())
+        .await?;
+    let seq = r.result.unwrap().0;
+
+    let full = client
+        .upsert_kv(key, MatchSeq::Exact(seq + 1), Some(b"v2".to_vec()), None)
+        .await?;
+    let current = Some((seq, KVValue {
+        meta: meta.clone(),
+        value: big.clone(),
+    }));
+    assert_eq!(current, full.prev);
+    assert_eq!(current, full.result);
+
+    for match_seq in [MatchSeq::Exact(seq + 1), MatchSeq::Exact(0)] {
+        let lean = client
+            .upsert_kv_lean(key, match_seq, Some(b"v2".to_vec()), None)
+            .await?;
+        let current = Some((seq, KVValue {
+            meta: meta.clone(),
+            value: vec![],
+        }));
+        assert_eq!(current, lean.prev, "{:?}", match_seq);
+        assert_eq!(current, lean.result, "{:?}", match_seq);
+
+        assert!(serde_json::to_vec(&full)?.len() > big.len());
+        assert!(serde_json::to_vec(&lean)?.len() < 1024);
+    }
+
+    tracing::info!("--- retry with the seq of the lean reply");
+
+    let lean = client
+        .upsert_kv_lean(key, MatchSeq::Exact(seq + 1), Some(b"v2".to_vec()), None)
+        .await?;
+    let retry_seq = lean.prev.unwrap().0;
+    let r = client
+        .upsert_kv_lean(key, MatchSeq::Exact(retry_seq), Some(b"v2".to_vec()), None)
+        .await?;
+    assert!(!r.is_unchanged());
+    assert_eq!(Some(big), r.prev.map(|(_, v)| v.value));
+    assert_eq!(Some(b"v2".to_vec()), r.result.map(|(_, v)| v.value));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_cas() -> anyhow::Result<()> {
+    // - Two participants race for a lease expecting it absent, only one gets it.
+    // - The loser sees the holder in the reply, and can not take it by expecting another value.
+    // - The holder hands it over by expecting its own value.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let key = "test_key_cas";
+    let value = |sv: Option<(u64, KVValue)>| sv.map(|(_, v)| v.value);
+
+    let r = client.cas_kv(key, None, Some(b"n1".to_vec()), None).await?;
+    assert!(!r.is_unchanged());
+    assert_eq!(Some(b"n1".to_vec()), value(r.result));
+
+    let r = client.cas_kv(key, None, Some(b"n2".to_vec()), None).await?;
+    assert!(r.is_unchanged());
+    assert_eq!(Some(b"n1".to_vec()), value(r.prev));
+
+    let r = client
+        .cas_kv(key, Some(b"n3".to_vec()), Some(b"n2".to_vec()), None)
+        .await?;
+    assert!(r.is_unchanged());
+
+    let r = client
+        .cas_kv(key, Some(b"n1".to_vec()), Some(b"n2".to_vec()), None)
+        .await?;
+    assert_eq!(Some(b"n1".to_vec()), value(r.prev));
+    assert_eq!(Some(b"n2".to_vec()), value(r.result));
+    assert_eq!(
+        Some(b"n2".to_vec()),
+        value(client.get_kv(key).await?.result)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_watch() -> anyhow::Result<()> {
+    // - Watch a prefix, every change under it is received in order.
+    // - A change out of the prefix is not received.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let mut changes = client.watch_kv("watch/").await?;
+
+    client
+        .upsert_kv("watch/a", MatchSeq::Any, Some(b"a1".to_vec()), None)
+        .await?;
+    client
+        .upsert_kv("other/a", MatchSeq::Any, Some(b"o1".to_vec()), None)
+        .await?;
+    client
+        .upsert_kv("watch/b", MatchSeq::Any, Some(b"b1".to_vec()), None)
+        .await?;
+    client
+        .upsert_kv("watch/a", MatchSeq::Any, Some(b"a2".to_vec()), None)
+        .await?;
+    client
+        .upsert_kv("watch/b", MatchSeq::Any, None, None)
+        .await?;
+
+    let value = |sv: &Option<(u64, KVValue)>| sv.as_ref().map(|(_, v)| v.value.clone());
+
+    let mut got = vec![];
+    for _ in 0..4 {
+        let change = tokio::time::timeout(Duration::from_secs(5), changes.try_next())
+            .await??
+            .ok_or_else(|| anyhow::anyhow!("the watch ended"))?;
+        got.push((
+            change.kind,
+            change.key.clone(),
+            value(&change.prev),
+            value(&change.result),
+        ));
+    }
+
+    assert_eq!(
+        vec![
+            (
+                KVChangeKind::Created,
+                "watch/a".to_string(),
+                None,
+                Some(b"a1".to_vec())
+            ),
+            (
+                KVChangeKind::Created,
+                "watch/b".to_string(),
+                None,
+                Some(b"b1".to_vec())
+            ),
+            (
+                KVChangeKind::Updated,
+                "watch/a".to_string(),
+                Some(b"a1".to_vec()),
+                Some(b"a2".to_vec())
+            ),
+            (
+                KVChangeKind::Deleted,
+                "watch/b".to_string(),
+                Some(b"b1".to_vec()),
+                None
+            ),
+        ],
+        got
+    );
+
+    let more = tokio::time::timeout(Duration::from_millis(500), changes.try_next()).await;
+    assert!(more.is_err(), "no more change is expected, got: {:?}", more);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_generic_kv_timeout() -> anyhow::Result<()> {
+    // - Test get  expired and non-expired.
+    // - Test mget expired and non-expired.
+    // - Test list expired and non-expired.
+    // - Test update with a new expire value.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+    {
+        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_timeout");
+        let _ent = span.enter();
+
+        let clock = VirtualClock::create();
+        let mut tc = new_test_context();
+        tc.config.meta_config.clock = SharedClock::create(clock.clone());
+        start_store_server_with_context(&mut tc).await?;
+        let addr = tc.config.flight_api_address.clone();
+
+        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+        let now = clock.now_secs();
 
         client
             .upsert_kv(
@@ -1041,16 +2718,13 @@ async fn test_flight_generic_kv_timeout() -> anyhow::Result<()> {
 
         tracing::info!("---get expired");
         {
-            tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
+            clock.advance(Duration::from_secs(2));
             let res = client.get_kv(&"k1".to_string()).await?;
             tracing::debug!("got k1:{:?}", res);
             assert!(res.result.is_none(), "got expired");
         }
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = clock.now_secs();
 
         tracing::info!("--- expired entry act as if it does not exist, an ADD op should apply");
         {
@@ -1194,6 +2868,244 @@ async fn test_flight_generic_kv() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_flight_generic_kv_merge() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    {
+        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_merge");
+        let _ent = span.enter();
+
+        let (_tc, addr) = crate::tests::start_store_server().await?;
+
+        let n_clients = 8;
+        let n_appends = 10;
+
+        tracing::info!("--- concurrent appends from several clients");
+
+        let mut handles = vec![];
+        for i in 0..n_clients {
+            let addr = addr.clone();
+            let h = tokio::spawn(async move {
+                let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+                for j in 0..n_appends {
+                    client
+                        .upsert_kv_merge(
+                            "list",
+                            MatchSeq::Any,
+                            MergeOp::AppendBytes {
+                                value: format!("{}-{};", i, j).into_bytes(),
+                                max_len: None,
+                            },
+                            None,
+                        )
+                        .await?;
+                }
+                Ok::<(), anyhow::Error>(())
+            });
+            handles.push(h);
+        }
+        for h in handles {
+            h.await??;
+        }
+
+        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+        let got = client.get_kv("list").await?.result.unwrap();
+        assert_eq!(n_clients * n_appends, got.0, "one seq per merge");
+
+        let value = String::from_utf8(got.1.value)?;
+        let mut fragments = value
+            .split_terminator(';')
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        fragments.sort();
+
+        let mut want = vec![];
+        for i in 0..n_clients {
+            for j in 0..n_appends {
+                want.push(format!("{}-{}", i, j));
+            }
+        }
+        want.sort();
+
+        assert_eq!(want, fragments, "every fragment exactly once");
+
+        tracing::info!("--- the fragments of one client are in order");
+
+        for i in 0..n_clients {
+            let mine = value
+                .split_terminator(';')
+                .filter(|x| x.starts_with(&format!("{}-", i)))
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>();
+            let want = (0..n_appends)
+                .map(|j| format!("{}-{}", i, j))
+                .collect::<Vec<_>>();
+            assert_eq!(want, mine);
+        }
+
+        tracing::info!("--- unknown merge function is rejected");
+
+        let res = client
+            .upsert_kv_merge(
+                "list",
+                MatchSeq::Any,
+                MergeOp::Named {
+                    name: "foo".to_string(),
+                    arg: vec![],
+                },
+                None,
+            )
+            .await;
+        assert!(res.is_err());
+
+        tracing::info!("--- a merge that can not be applied to the value is rejected");
+
+        let res = client
+            .upsert_kv_merge(
+                "list",
+                MatchSeq::Any,
+                MergeOp::Named {
+                    name: "add_u64".to_string(),
+                    arg: b"1".to_vec(),
+                },
+                None,
+            )
+            .await;
+        assert_eq!(
+            ErrorCode::IllegalMetaOperationArgument("").code(),
+            res.unwrap_err().code()
+        );
+
+        let after = client.get_kv("list").await?.result.unwrap();
+        assert_eq!(n_clients * n_appends, after.0, "nothing changed");
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_flight_generic_kv_patch_json() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    {
+        let span = tracing::span!(tracing::Level::INFO, "test_flight_generic_kv_patch_json");
+        let _ent = span.enter();
+
+        let (_tc, addr) = crate::tests::start_store_server().await?;
+
+        let add = |path: &str, value: serde_json::Value| JsonPatchOp::Add {
+            path: path.to_string(),
+            value,
+        };
+
+        let n_clients = 8;
+        let n_patches = 10;
+
+        tracing::info!("--- concurrent patches to different fields of one document");
+
+        let mut handles = vec![];
+        for i in 0..n_clients {
+            let addr = addr.clone();
+            let h = tokio::spawn(async move {
+                let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+                for j in 0..n_patches {
+                    client
+                        .patch_kv_json("conf", MatchSeq::Any, vec![add(
+                            &format!("/c{}", i),
+                            serde_json::json!(j),
+                        )])
+                        .await?;
+                }
+                Ok::<(), anyhow::Error>(())
+            });
+            handles.push(h);
+        }
+        for h in handles {
+            h.await??;
+        }
+
+        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+        let got = client.get_kv("conf").await?.result.unwrap();
+        assert_eq!(n_clients * n_patches, got.0, "one seq per patch");
+
+        let doc: serde_json::Value = serde_json::from_slice(&got.1.value)?;
+        let mut want = serde_json::Map::new();
+        for i in 0..n_clients {
+            want.insert(format!("c{}", i), serde_json::json!(n_patches - 1));
+        }
+        assert_eq!(serde_json::Value::Object(want), doc, "every field lands");
+
+        tracing::info!("--- the reply carries the new seq and the patched document");
+
+        let res = client
+            .patch_kv_json("conf", MatchSeq::Exact(got.0), vec![JsonPatchOp::Remove {
+                path: "/c0".to_string(),
+            }])
+            .await?;
+        let (seq, value) = res.result.unwrap();
+        assert_eq!(got.0 + 1, seq);
+        let doc: serde_json::Value = serde_json::from_slice(&value.value)?;
+        assert_eq!(None, doc.get("c0"));
+        assert_eq!(Some(&serde_json::json!(n_patches - 1)), doc.get("c1"));
+
+        tracing::info!("--- a stale seq does not overwrite a concurrent patch to the same field");
+
+        client
+            .patch_kv_json("conf", MatchSeq::Exact(seq), vec![add(
+                "/c1",
+                serde_json::json!("first"),
+            )])
+            .await?;
+        let res = client
+            .patch_kv_json("conf", MatchSeq::Exact(seq), vec![add(
+                "/c1",
+                serde_json::json!("second"),
+            )])
+            .await?;
+        assert!(res.is_unchanged());
+        let (_, value) = res.result.unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&value.value)?;
+        assert_eq!(Some(&serde_json::json!("first")), doc.get("c1"));
+
+        tracing::info!("--- a pointer to a missing member fails the whole patch");
+
+        let before = client.get_kv("conf").await?.result;
+        let res = client
+            .patch_kv_json("conf", MatchSeq::Any, vec![
+                add("/c2", serde_json::json!("x")),
+                JsonPatchOp::Replace {
+                    path: "/nonexistent".to_string(),
+                    value: serde_json::json!(1),
+                },
+            ])
+            .await;
+        let err = res.unwrap_err();
+        assert_eq!(ErrorCode::IllegalJsonPatch("").code(), err.code());
+        assert_eq!(before, client.get_kv("conf").await?.result);
+
+        tracing::info!("--- a patch to a non-json value fails and leaves it as is");
+
+        client
+            .upsert_kv("raw", MatchSeq::Any, Some(b"not json".to_vec()), None)
+            .await?;
+        let before = client.get_kv("raw").await?.result;
+
+        let res = client
+            .patch_kv_json("raw", MatchSeq::Any, vec![add("/a", serde_json::json!(1))])
+            .await;
+        let err = res.unwrap_err();
+        assert_eq!(ErrorCode::IllegalJsonPatch("").code(), err.code());
+        assert_eq!(before, client.get_kv("raw").await?.result);
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_flight_get_database_meta_empty_db() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_store_ut!();
@@ -1347,3 +3259,909 @@ async fn test_flight_get_database_meta_ddl_table() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_create_or_replace_table() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+
+    let plan = |column: &str| CreateTablePlan {
+        if_not_exists: false,
+        db: "db1".to_string(),
+        table: "tbl1".to_string(),
+        schema: Arc::new(DataSchema::new(vec![DataField::new(
+            column,
+            DataType::UInt64,
+            false,
+        )])),
+        options: Default::default(),
+        engine: "JSON".to_string(),
+    };
+
+    // A plain create behaves as before.
+    let created = client.create_table(plan("a")).await?;
+    assert_eq!(None, created.replaced);
+    let res = client.create_table(plan("a")).await;
+    assert_eq!(
+        ErrorCode::TableAlreadyExists("").code(),
+        res.unwrap_err().code()
+    );
+    let ver = client.get_database_meta(None).await?.unwrap().meta_ver;
+
+    // A mismatching seq fails, the table and the meta version are kept.
+    for seq in [MatchSeq::Exact(0), MatchSeq::Exact(created.table_id + 1)] {
+        let res = client.create_or_replace_table(plan("b"), seq).await;
+        assert_eq!(
+            ErrorCode::TableVersionMismatched("").code(),
+            res.unwrap_err().code()
+        );
+    }
+    assert!(client.get_database_meta(Some(ver)).await?.is_none());
+    let got = client.get_table("db1".into(), "tbl1".into()).await?;
+    assert_eq!(created.table_id, got.table_id);
+
+    // A matching seq replaces the table and returns the replaced one.
+    let replaced = client
+        .create_or_replace_table(plan("b"), MatchSeq::Exact(created.table_id))
+        .await?;
+    assert!(replaced.table_id > created.table_id);
+    assert_eq!(
+        Some(GetTableActionResult {
+            table_id: created.table_id,
+            db: "db1".to_string(),
+            name: "tbl1".to_string(),
+            schema: plan("a").schema,
+            engine: "JSON".to_string(),
+            options: Default::default(),
+        }),
+        replaced.replaced
+    );
+    let snapshot = client.get_database_meta(Some(ver)).await?.unwrap();
+    assert_eq!(ver + 1, snapshot.meta_ver);
+    let got = client.get_table("db1".into(), "tbl1".into()).await?;
+    assert_eq!(replaced.table_id, got.table_id);
+    assert_eq!(plan("b").schema, got.schema);
+
+    // Seq 0 creates an absent table.
+    let created = client
+        .create_or_replace_table(
+            CreateTablePlan {
+                table: "tbl2".to_string(),
+                ..plan("a")
+            },
+            MatchSeq::Exact(0),
+        )
+        .await?;
+    assert_eq!(None, created.replaced);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_create_tables() -> anyhow::Result<()> {
+    // - The tables are created in one meta write, the meta version is bumped once.
+    // - An existing table is reported as such, or as an error unless if_not_exists.
+    // - A table failing the checks or of a missing database doesn't fail the others.
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+
+    let plan = |db: &str, table: &str, if_not_exists: bool| CreateTablePlan {
+        if_not_exists,
+        db: db.to_string(),
+        table: table.to_string(),
+        schema: Arc::new(DataSchema::new(vec![DataField::new(
+            "a",
+            DataType::UInt64,
+            false,
+        )])),
+        options: Default::default(),
+        engine: "JSON".to_string(),
+    };
+
+    let existing = client.create_table(plan("db1", "t2", false)).await?;
+    let ver = client.get_database_meta(None).await?.unwrap().meta_ver;
+
+    let res = client
+        .create_tables(vec![
+            plan("db1", "t1", false),
+            plan("db1", "t2", true),
+            plan("db1", "t2", false),
+            plan("db2", "t3", false),
+            CreateTablePlan {
+                engine: "NoSuchEngine".to_string(),
+                ..plan("db1", "t4", false)
+            },
+            plan("db1", "t5", false),
+        ])
+        .await?;
+
+    let t1 = client.get_table("db1".into(), "t1".into()).await?;
+    let t5 = client.get_table("db1".into(), "t5".into()).await?;
+    assert_eq!(6, res.outcomes.len());
+    assert_eq!(
+        CreateTableOutcome::Created {
+            table_id: t1.table_id
+        },
+        res.outcomes[0]
+    );
+    assert_eq!(
+        CreateTableOutcome::AlreadyExists {
+            table_id: existing.table_id
+        },
+        res.outcomes[1]
+    );
+    let codes = [2, 3, 4].map(|i| match &res.outcomes[i] {
+        CreateTableOutcome::Failed { code, .. } => *code,
+        outcome => panic!("unexpected {:?}", outcome),
+    });
+    assert_eq!(ErrorCode::TableAlreadyExists("").code(), codes[0]);
+    assert_eq!(ErrorCode::UnknownDatabase("").code(), codes[1]);
+    assert_eq!(ErrorCode::UnknownTableEngine("").code(), codes[2]);
+    assert_eq!(
+        CreateTableOutcome::Created {
+            table_id: t5.table_id
+        },
+        res.outcomes[5]
+    );
+    assert!(client.get_table("db1".into(), "t4".into()).await.is_err());
+
+    let snapshot = client.get_database_meta(Some(ver)).await?.unwrap();
+    assert_eq!(ver + 1, snapshot.meta_ver);
+
+    // Nothing created, nothing bumped.
+    let res = client
+        .create_tables(vec![plan("db1", "t1", true), plan("db1", "t5", true)])
+        .await?;
+    assert!(res
+        .outcomes
+        .iter()
+        .all(|o| matches!(o, CreateTableOutcome::AlreadyExists { .. })));
+    assert!(client.get_database_meta(Some(ver + 1)).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_create_tables_with_failed_checks() -> anyhow::Result<()> {
+    // The outcome of each plan is its own, whatever the plans before it failing the checks.
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::UInt64, false)]);
+    let plan = |table: &str, if_not_exists: bool| CreateTablePlan {
+        if_not_exists,
+        db: "db1".to_string(),
+        table: table.to_string(),
+        schema: schema.clone(),
+        options: Default::default(),
+        engine: "JSON".to_string(),
+    };
+    let failing = |table: &str| CreateTablePlan {
+        engine: "NoSuchEngine".to_string(),
+        ..plan(table, false)
+    };
+
+    let existing = client.create_table(plan("t3", false)).await?;
+    let res = client
+        .create_tables(vec![
+            failing("t0"),
+            plan("t1", false),
+            failing("t2"),
+            plan("t3", true),
+            plan("t4", false),
+        ])
+        .await?;
+
+    let t1 = client.get_table("db1".into(), "t1".into()).await?;
+    let t4 = client.get_table("db1".into(), "t4".into()).await?;
+    let engine_code = ErrorCode::UnknownTableEngine("").code();
+    assert!(
+        matches!(&res.outcomes[0], CreateTableOutcome::Failed { code, .. } if *code == engine_code)
+    );
+    assert_eq!(
+        CreateTableOutcome::Created {
+            table_id: t1.table_id
+        },
+        res.outcomes[1]
+    );
+    assert!(
+        matches!(&res.outcomes[2], CreateTableOutcome::Failed { code, .. } if *code == engine_code)
+    );
+    assert_eq!(
+        CreateTableOutcome::AlreadyExists {
+            table_id: existing.table_id
+        },
+        res.outcomes[3]
+    );
+    assert_eq!(
+        CreateTableOutcome::Created {
+            table_id: t4.table_id
+        },
+        res.outcomes[4]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_query_label() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let audit_log = Arc::new(AuditLog::create(16));
+    let mut tc = new_test_context();
+    tc.audit_log = Some(audit_log.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let mut labeled = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    labeled.set_query_label("team=billing");
+    let unlabeled = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    labeled
+        .upsert_kv("label_key", MatchSeq::Any, Some(b"v".to_vec()), None)
+        .await?;
+    unlabeled.get_kv("label_key").await?;
+
+    let records = audit_log.records();
+    let labels = records
+        .iter()
+        .map(|r| (r.action.as_str(), r.query_label.as_deref(), r.ok))
+        .collect::<Vec<_>>();
+    assert_eq!(labels, vec![
+        ("UpsertKV", Some("team=billing"), true),
+        ("GetKV", None, true),
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_reload_config() -> anyhow::Result<()> {
+    // - Start with every request being slow.
+    // - Reload with a larger threshold: the same client goes on, its requests are not slow any more.
+    // - Reload with a changed static setting: it is rejected and the threshold is kept.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.toml").to_str().unwrap().to_string();
+    let write_conf = |conf: &Config| -> anyhow::Result<()> {
+        std::fs::write(&path, toml::Value::try_from(conf)?.to_string())?;
+        Ok(())
+    };
+
+    let audit_log = Arc::new(AuditLog::create(16));
+    let mut tc = new_test_context();
+    tc.config.slow_apply_threshold_ms = 0;
+    write_conf(&tc.config)?;
+    let conf = Config::load_from_toml_with_args(&path, vec!["databend-store"])?;
+    let config_handle = Arc::new(ConfigHandle::create(conf));
+    tc.audit_log = Some(audit_log.clone());
+    tc.config_handle = Some(config_handle.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client.get_kv("reload_key").await?;
+
+    let mut conf = tc.config.clone();
+    conf.slow_apply_threshold_ms = 60_000;
+    write_conf(&conf)?;
+    let changes = config_handle.reload()?;
+    assert_eq!(
+        vec!["slow_apply_threshold_ms"],
+        changes.iter().map(|c| c.name.as_str()).collect::<Vec<_>>()
+    );
+    client.get_kv("reload_key").await?;
+
+    conf.slow_apply_threshold_ms = 0;
+    conf.local_fs_dir = "/tmp/other".to_string();
+    write_conf(&conf)?;
+    let err = config_handle.reload().unwrap_err();
+    assert!(err.message().contains("local_fs_dir"), "{}", err);
+    assert_eq!(60_000, config_handle.get().slow_apply_threshold_ms);
+    client.get_kv("reload_key").await?;
+
+    let slows = audit_log
+        .records()
+        .iter()
+        .map(|r| (r.action.clone(), r.slow))
+        .collect::<Vec<_>>();
+    assert_eq!(slows, vec![
+        ("GetKV".to_string(), true),
+        ("GetKV".to_string(), false),
+        ("GetKV".to_string(), false),
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_reload_read_only() -> anyhow::Result<()> {
+    // - Reload with read_only: the writes are rejected, the reads are still served.
+    // - Reload without it: the same client writes again.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.toml").to_str().unwrap().to_string();
+    let write_conf = |conf: &Config| -> anyhow::Result<()> {
+        std::fs::write(&path, toml::Value::try_from(conf)?.to_string())?;
+        Ok(())
+    };
+
+    let mut tc = new_test_context();
+    write_conf(&tc.config)?;
+    let conf = Config::load_from_toml_with_args(&path, vec!["databend-store"])?;
+    let config_handle = Arc::new(ConfigHandle::create(conf));
+    tc.config_handle = Some(config_handle.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client
+        .upsert_kv("k1", MatchSeq::Any, Some(b"v1".to_vec()), None)
+        .await?;
+
+    let mut conf = tc.config.clone();
+    conf.read_only = true;
+    write_conf(&conf)?;
+    let changes = config_handle.reload()?;
+    assert_eq!(
+        vec!["read_only"],
+        changes.iter().map(|c| c.name.as_str()).collect::<Vec<_>>()
+    );
+
+    let err = client
+        .upsert_kv("k2", MatchSeq::Any, Some(b"v2".to_vec()), None)
+        .await
+        .unwrap_err();
+    assert_eq!(ErrorCode::ReadOnlyStore("").code(), err.code());
+    let got = client.get_kv("k1").await?;
+    assert_eq!(b"v1".to_vec(), got.result.unwrap().1.value);
+    assert!(client.get_kv("k2").await?.result.is_none());
+
+    conf.read_only = false;
+    write_conf(&conf)?;
+    config_handle.reload()?;
+    client
+        .upsert_kv("k2", MatchSeq::Any, Some(b"v2".to_vec()), None)
+        .await?;
+    assert!(client.get_kv("k2").await?.result.is_some());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_table_access_stats() -> anyhow::Result<()> {
+    // - Append to a table and read it back.
+    // - The rows and bytes appended and read are in the access stats of the current window.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "db1";
+    let tbl_name = "tb1";
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64, 2, 3])]);
+    let res = client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await?;
+
+    let plan = ScanPlan {
+        schema_name: tbl_name.to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client
+        .read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+        .await?
+        .unwrap_or_default();
+    assert_eq!(1, parts.len());
+
+    let action = ReadAction {
+        part: parts[0].part.clone(),
+        push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            ..ReadDataSourcePlan::empty(0, None)
+        }),
+    };
+    let blocks = client
+        .read_partition(schema.clone(), &action)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(3, blocks[0].num_rows());
+
+    // the window may start in the previous bucket
+    let stats = client
+        .get_table_access_stats(db_name.to_string(), tbl_name.to_string(), 60)
+        .await?;
+    assert_eq!(3, stats.rows_written);
+    assert_eq!(res.summary.wire_bytes as u64, stats.bytes_written);
+    assert_eq!(3, stats.rows_read);
+    assert!(stats.bytes_read > 0);
+
+    let stats = client
+        .get_table_access_stats(db_name.to_string(), "tb2".to_string(), 60)
+        .await?;
+    assert_eq!(0, stats.rows_read + stats.rows_written);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_database_quota() -> anyhow::Result<()> {
+    // - Append to a table and set the quota slightly above the usage.
+    // - An append beyond the quota is rejected and none of its parts are registered.
+    // - Dropping the table releases its bytes, then an append fits in the quota again.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "db1";
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    for tbl_name in ["tb1", "tb2"] {
+        client
+            .create_table(CreateTablePlan {
+                if_not_exists: false,
+                db: db_name.to_string(),
+                table: tbl_name.to_string(),
+                schema: schema.clone(),
+                options: Default::default(),
+                engine: "PARQUET".to_string(),
+            })
+            .await?;
+    }
+
+    let append = |tbl_name: &str, blocks: Vec<DataBlock>| {
+        client.append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(futures::stream::iter(blocks)),
+        )
+    };
+    let small = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64, 2, 3])]);
+    let large = DataBlock::create_by_array(schema.clone(), vec![Series::new(
+        (0..10000i64).collect::<Vec<_>>(),
+    )]);
+    let count_parts = |tbl_name: &str| {
+        let plan = ScanPlan {
+            schema_name: tbl_name.to_string(),
+            ..ScanPlan::empty()
+        };
+        client.read_plan(db_name.to_string(), tbl_name.to_string(), &plan)
+    };
+
+    append("tb1", vec![small.clone()]).await?;
+    let usages = client.get_database_usages().await?.usages;
+    assert_eq!(1, usages.len());
+    assert_eq!(db_name, usages[0].0);
+    assert_eq!(None, usages[0].1.quota);
+    let used_bytes = usages[0].1.used_bytes;
+    assert!(used_bytes > 0);
+
+    let usage = client
+        .set_database_quota(db_name.to_string(), Some(used_bytes + 1024))
+        .await?;
+    assert_eq!(Some(used_bytes + 1024), usage.quota);
+    assert_eq!(used_bytes, usage.used_bytes);
+
+    let res = client
+        .set_database_quota("unknown".to_string(), Some(1))
+        .await;
+    assert!(res.is_err());
+
+    // rejected when the parts are registered
+    let res = append("tb2", vec![small.clone(), large.clone(), large.clone()]).await;
+    let err = res.unwrap_err();
+    assert!(
+        err.message().contains("quota"),
+        "unexpected error: {}",
+        err.message()
+    );
+    assert_eq!(0, count_parts("tb2").await?.unwrap_or_default().len());
+    assert_eq!(1, count_parts("tb1").await?.unwrap_or_default().len());
+
+    let usage = client.reconcile_database_usage(db_name.to_string()).await?;
+    assert_eq!(used_bytes, usage.used_bytes);
+
+    client
+        .drop_table(DropTablePlan {
+            if_exists: false,
+            db: db_name.to_string(),
+            table: "tb1".to_string(),
+        })
+        .await?;
+    let usages = client.get_database_usages().await?.usages;
+    assert_eq!(0, usages[0].1.used_bytes);
+
+    append("tb2", vec![small]).await?;
+    assert_eq!(1, count_parts("tb2").await?.unwrap_or_default().len());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_read_damaged_part() -> anyhow::Result<()> {
+    // - Append a part to a file, then damage the file behind the store's back.
+    // - Reading the part fails, naming it, whether it is cut short or a byte is flipped.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    tc.config.inline_part_max_bytes = 0;
+    tc.config.verify_part_checksum = true;
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "db1";
+    let tbl_name = "tb1";
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)]);
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+
+    let block = DataBlock::create_by_array(schema.clone(), vec![Series::new(vec![1i64, 2, 3])]);
+    client
+        .append_data(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            schema.clone(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await?;
+
+    let parts = client
+        .read_plan(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            &ScanPlan::empty(),
+        )
+        .await?
+        .unwrap_or_default();
+    assert_eq!(1, parts.len());
+    let part_name = parts[0].part.name.clone();
+    let path = std::path::Path::new(&tc.config.local_fs_dir).join(&part_name);
+    let content = std::fs::read(&path)?;
+    assert_eq!(
+        Some(content.len() as u64),
+        parts[0].checksum.as_ref().map(|c| c.bytes)
+    );
+
+    let action = ReadAction {
+        part: parts[0].part.clone(),
+        push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: schema.clone(),
+            ..ReadDataSourcePlan::empty(0, None)
+        }),
+    };
+    let read = || async {
+        client
+            .read_partition(schema.clone(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await
+    };
+    assert_eq!(3, read().await?[0].num_rows());
+
+    // cut short
+    std::fs::write(&path, &content[..content.len() - 1])?;
+    let err = read().await.unwrap_err();
+    assert!(err.message().contains(&part_name), "{}", err);
+    assert!(err.message().contains("damaged"), "{}", err);
+
+    // same size, a byte flipped
+    let mut flipped = content.clone();
+    flipped[content.len() / 2] ^= 0xff;
+    std::fs::write(&path, &flipped)?;
+    let err = read().await.unwrap_err();
+    assert!(err.message().contains(&part_name), "{}", err);
+    assert!(err.message().contains("sha256"), "{}", err);
+
+    std::fs::write(&path, &content)?;
+    assert_eq!(3, read().await?[0].num_rows());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_batch() -> anyhow::Result<()> {
+    // - A batch of reads gets the same replies as the reads sent one by one, in one request.
+    // - The unknown table fails its own action only.
+    // - A batch with a mutation is rejected as a whole.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let audit_log = Arc::new(AuditLog::create(16));
+    let mut tc = new_test_context();
+    tc.audit_log = Some(audit_log.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "batch_db";
+    let tbl_name = "batch_tbl";
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            table: tbl_name.to_string(),
+            schema: DataSchemaRefExt::create(vec![DataField::new(
+                "number",
+                DataType::UInt64,
+                false,
+            )]),
+            options: Default::default(),
+            engine: "JSON".to_string(),
+        })
+        .await?;
+    client
+        .upsert_kv("batch_key", MatchSeq::Any, Some(b"v".to_vec()), None)
+        .await?;
+
+    let want_table = client
+        .get_table(db_name.to_string(), tbl_name.to_string())
+        .await?;
+    let want_kv = client.get_kv("batch_key").await?;
+    let want_mget = client
+        .mget_kv(&["batch_key".to_string(), "batch_unknown_key".to_string()])
+        .await?;
+    let served = audit_log.records().len();
+
+    let mut batch = BatchRequest::new();
+    let table = batch.push(GetTableAction {
+        db: db_name.to_string(),
+        table: tbl_name.to_string(),
+    });
+    let unknown_table = batch.push(GetTableAction {
+        db: db_name.to_string(),
+        table: "batch_unknown_tbl".to_string(),
+    });
+    let kv = batch.push(GetKVAction {
+        key: "batch_key".to_string(),
+    });
+    let unknown_kv = batch.push(GetKVAction {
+        key: "batch_unknown_key".to_string(),
+    });
+    let mget = batch.push(MGetKVAction {
+        keys: vec!["batch_key".to_string(), "batch_unknown_key".to_string()],
+    });
+    client.send_batch(batch).await?;
+
+    assert_eq!(want_table, table.await?);
+    assert_eq!(
+        ErrorCode::UnknownTable("").code(),
+        unknown_table.await.unwrap_err().code()
+    );
+    assert_eq!(want_kv, kv.await?);
+    assert!(unknown_kv.await?.result.is_none());
+    assert_eq!(want_mget, mget.await?);
+
+    // only one request is served for the batch
+    let records = audit_log.records();
+    let actions = records[served..]
+        .iter()
+        .map(|r| (r.action.as_str(), r.ok))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![("Batch", true)], actions);
+
+    // a mutation is rejected, the other actions are not executed either
+    let mut batch = BatchRequest::new();
+    let kv = batch.push(GetKVAction {
+        key: "batch_key".to_string(),
+    });
+    let upsert = batch.push(UpsertKVAction {
+        key: "batch_key".to_string(),
+        seq: MatchSeq::Any,
+        value: Some(b"w".to_vec()),
+        value_meta: None,
+        return_value_on_conflict: true,
+    });
+    let res = client.send_batch(batch).await;
+    let code = ErrorCode::IllegalBatch("").code();
+    assert_eq!(code, res.unwrap_err().code());
+    assert_eq!(code, kv.await.unwrap_err().code());
+    assert_eq!(code, upsert.await.unwrap_err().code());
+    assert_eq!(want_kv, client.get_kv("batch_key").await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_flight_batch_snapshot() -> anyhow::Result<()> {
+    // - Tables are created while batches read them.
+    // - The tables listed at the beginning and at the end of a batch are the same, and so are
+    //   the tables found by the actions in between.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = crate::tests::start_store_server().await?;
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    let db_name = "batch_db";
+    let table_names = (0..20).map(|i| format!("t{}", i)).collect::<Vec<_>>();
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: db_name.to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+
+    let ddl = {
+        let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+        let table_names = table_names.clone();
+        tokio::spawn(async move {
+            for name in table_names {
+                let plan = CreateTablePlan {
+                    if_not_exists: false,
+                    db: db_name.to_string(),
+                    table: name,
+                    schema: DataSchemaRefExt::create(vec![DataField::new(
+                        "number",
+                        DataType::UInt64,
+                        false,
+                    )]),
+                    options: Default::default(),
+                    engine: "JSON".to_string(),
+                };
+                client.create_table(plan).await?;
+            }
+            Ok::<_, ErrorCode>(())
+        })
+    };
+
+    let listed = |tables: ListTablesReply| {
+        let mut names = tables.into_iter().map(|t| t.name).collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    let mut last = 0;
+    while last < table_names.len() {
+        let mut batch = BatchRequest::new();
+        let first = batch.push(ListTablesAction {
+            db: db_name.to_string(),
+        });
+        let gets = table_names
+            .iter()
+            .map(|name| {
+                batch.push(GetTableAction {
+                    db: db_name.to_string(),
+                    table: name.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let second = batch.push(ListTablesAction {
+            db: db_name.to_string(),
+        });
+        client.send_batch(batch).await?;
+
+        let first = listed(first.await?);
+        let mut found = vec![];
+        for (name, get) in table_names.iter().zip(gets) {
+            if get.await.is_ok() {
+                found.push(name.clone());
+            }
+        }
+        found.sort();
+        assert_eq!(first, listed(second.await?));
+        assert_eq!(first, found);
+        last = first.len();
+    }
+
+    ddl.await??;
+    Ok(())
+}
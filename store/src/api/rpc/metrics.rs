@@ -11,3 +11,50 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use std::time::Duration;
+
+use common_exception::ErrorCode;
+use metrics::counter;
+use metrics::histogram;
+
+/// Counts the requests served by the flight service, labeled by the action and the status.
+pub static METRIC_ACTION_REQUESTS: &str = "store.action_requests";
+/// Counts the failed requests, labeled by the action and the name of the ErrorCode.
+pub static METRIC_ACTION_ERRORS: &str = "store.action_errors";
+/// The time a request is served in, labeled by the action and the status.
+pub static METRIC_ACTION_SECONDS: &str = "store.action_seconds";
+/// The bytes of a request payload, labeled by the action.
+pub static METRIC_ACTION_REQUEST_BYTES: &str = "store.action_request_bytes";
+/// The bytes of a response payload, labeled by the action.
+pub static METRIC_ACTION_RESPONSE_BYTES: &str = "store.action_response_bytes";
+
+pub const STATUS_OK: &str = "ok";
+pub const STATUS_ERROR: &str = "error";
+
+/// Records a served request: `response_bytes` is ignored if it failed.
+pub fn record_action<T>(
+    action: &str,
+    elapsed: Duration,
+    request_bytes: usize,
+    response_bytes: usize,
+    res: &Result<T, ErrorCode>,
+) {
+    let status = match res {
+        Ok(_) => STATUS_OK,
+        Err(_) => STATUS_ERROR,
+    };
+
+    counter!(METRIC_ACTION_REQUESTS, 1, "action" => action.to_string(), "status" => status);
+    histogram!(METRIC_ACTION_SECONDS, elapsed.as_secs_f64(), "action" => action.to_string(), "status" => status);
+    histogram!(METRIC_ACTION_REQUEST_BYTES, request_bytes as f64, "action" => action.to_string());
+
+    match res {
+        Ok(_) => {
+            histogram!(METRIC_ACTION_RESPONSE_BYTES, response_bytes as f64, "action" => action.to_string());
+        }
+        Err(e) => {
+            counter!(METRIC_ACTION_ERRORS, 1, "action" => action.to_string(), "code" => e.name());
+        }
+    }
+}
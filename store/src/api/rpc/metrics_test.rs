@@ -0,0 +1,131 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_datavalues::DataField;
+use common_datavalues::DataSchema;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
+use common_metatypes::MatchSeq;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_runtime::tokio;
+use common_store_api_sdk::KVApi;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StoreClient;
+
+use crate::metrics::MetricService;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_action_metrics() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (tc, addr) = crate::tests::start_store_server().await?;
+
+    // The only test installing the recorder: it can be installed once in a process.
+    MetricService::create(tc.config.clone()).make_server()?;
+    let url = format!("http://{}", tc.config.metric_api_address);
+
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .upsert_kv("k1", MatchSeq::Any, Some(b"v1".to_vec()), None)
+        .await?;
+    let got = client.get_kv("k1").await?;
+    assert!(got.result.is_some());
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    let plan = CreateTablePlan {
+        if_not_exists: false,
+        db: "db1".to_string(),
+        table: "tb1".to_string(),
+        schema: Arc::new(DataSchema::new(vec![DataField::new(
+            "number",
+            DataType::UInt64,
+            false,
+        )])),
+        options: Default::default(),
+        engine: "JSON".to_string(),
+    };
+    client.create_table(plan.clone()).await?;
+
+    let exists = [r#"action="CreateTable""#, r#"code="TableAlreadyExists""#];
+    let errors = sample_value(&scrape(&url).await?, "store_action_errors", &exists);
+
+    let res = client.create_table(plan).await;
+    let code = res.map(|_| 0).unwrap_or_else(|e| e.code());
+    assert_eq!(ErrorCode::TableAlreadyExists("").code(), code);
+
+    let metrics = scrape(&url).await?;
+    for action in ["UpsertKV", "GetKV", "CreateDatabase", "CreateTable"] {
+        let labels = [
+            format!(r#"action="{}""#, action),
+            r#"status="ok""#.to_string(),
+        ];
+        let labels = labels.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+        assert!(
+            sample_value(&metrics, "store_action_requests", &labels) > 0.0,
+            "{}",
+            action
+        );
+        assert!(sample_value(&metrics, "store_action_seconds_count", &labels) > 0.0);
+    }
+
+    let upsert = [r#"action="UpsertKV""#];
+    assert!(sample_value(&metrics, "store_action_request_bytes_sum", &upsert) > 0.0);
+    assert!(sample_value(&metrics, "store_action_response_bytes_sum", &upsert) > 0.0);
+
+    let failed = [r#"action="CreateTable""#, r#"status="error""#];
+    assert!(sample_value(&metrics, "store_action_requests", &failed) >= 1.0);
+    assert_eq!(
+        errors + 1.0,
+        sample_value(&metrics, "store_action_errors", &exists)
+    );
+
+    Ok(())
+}
+
+/// Fetches the metrics, waits for the exporter to listen for at most a few seconds.
+async fn scrape(url: &str) -> anyhow::Result<String> {
+    let start = Instant::now();
+    loop {
+        match reqwest::get(url).await {
+            Ok(resp) => return Ok(resp.text().await?),
+            Err(e) if start.elapsed() > Duration::from_secs(5) => return Err(e.into()),
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+}
+
+/// The sum of the samples of `name` having all the `labels`, 0 if there is none.
+fn sample_value(metrics: &str, name: &str, labels: &[&str]) -> f64 {
+    metrics
+        .lines()
+        .filter_map(|line| line.rsplit_once(' '))
+        .filter(|(series, _)| series.split('{').next() == Some(name))
+        .filter(|(series, _)| labels.iter().all(|label| series.contains(label)))
+        .filter_map(|(_, value)| value.parse::<f64>().ok())
+        .sum()
+}
@@ -12,12 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod bloom_filter_test;
+#[cfg(test)]
+mod copy_table_test;
+#[cfg(test)]
+mod deadline_test;
+#[cfg(test)]
+mod external_table_test;
+#[cfg(test)]
+mod fault_injection_test;
 #[cfg(test)]
 mod flight_service_test;
 #[cfg(test)]
+mod metrics_test;
+#[cfg(test)]
+mod query_node_test;
+#[cfg(test)]
+mod read_your_writes_test;
+#[cfg(test)]
+mod rpc_tracing_test;
+#[cfg(test)]
+mod store_client_channel_test;
+#[cfg(test)]
+mod table_engine_test;
+#[cfg(test)]
 mod tls_flight_service_test;
 
+mod audit_log;
 mod flight_service;
+mod metrics;
+mod request_deadline;
 
+pub use audit_log::AuditLog;
+pub use audit_log::AuditRecord;
 pub use flight_service::FlightStream;
 pub use flight_service::StoreFlightImpl;
+pub use request_deadline::DeadlineStream;
+pub use request_deadline::RequestDeadline;
+pub use request_deadline::METRIC_DEADLINE_ABORTS;
+
+pub use self::metrics::METRIC_ACTION_ERRORS;
+pub use self::metrics::METRIC_ACTION_REQUESTS;
+pub use self::metrics::METRIC_ACTION_REQUEST_BYTES;
+pub use self::metrics::METRIC_ACTION_RESPONSE_BYTES;
+pub use self::metrics::METRIC_ACTION_SECONDS;
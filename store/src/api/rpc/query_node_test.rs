@@ -0,0 +1,172 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use common_exception::ErrorCode;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use common_tracing::tracing;
+use databend_query::catalogs::Catalog;
+use futures::TryStreamExt;
+use mysql::prelude::Queryable;
+use pretty_assertions::assert_eq;
+
+use crate::tests::restart_store_server;
+use crate::tests::stop_store_server;
+use crate::tests::StoreQueryTestContext;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_query_node_on_store() -> anyhow::Result<()> {
+    // - Create a database and a table, insert and select over MySQL.
+    // - The store has the database, the table and the rows in its parts.
+    // - Stop the store, the session reports an error and stays open.
+    // - Restart the store, the session works again.
+    // - Drop the table and the database, the store does not have them any more.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut ctx = StoreQueryTestContext::start().await?;
+    let mut conn = ctx.query.connect()?;
+
+    tracing::info!("--- create, insert and select");
+    {
+        conn.query_drop("CREATE DATABASE db1")?;
+        conn.query_drop("USE db1")?;
+        conn.query_drop("CREATE TABLE t1(a UInt64, b String) Engine = remote")?;
+        conn.query_drop("INSERT INTO t1 (a, b) VALUES (1, 'x'), (2, 'y'), (3, 'z')")?;
+
+        let rows: Vec<(u64, String)> = conn.query("SELECT a, b FROM t1 ORDER BY a")?;
+        assert_eq!(rows, vec![
+            (1, "x".to_string()),
+            (2, "y".to_string()),
+            (3, "z".to_string())
+        ]);
+    }
+
+    tracing::info!("--- the store side");
+    {
+        let client = ctx.store_client().await?;
+        let db = client.get_database("db1").await?;
+        assert_eq!("db1", db.db);
+
+        let table = client.get_table("db1".into(), "t1".into()).await?;
+        let fields = table
+            .schema
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b"], fields);
+        assert_eq!(3, store_rows(&client, "db1", "t1").await?);
+
+        // The query node sees the table the store has.
+        let table_meta = ctx.query.catalog().get_table("db1", "t1")?;
+        assert_eq!(table.table_id, table_meta.meta_id());
+    }
+
+    tracing::info!("--- restart the store");
+    {
+        stop_store_server(&mut ctx.store).await?;
+        let res = conn.query_drop("INSERT INTO t1 (a, b) VALUES (4, 'w')");
+        assert!(res.is_err(), "the store is down, got: {:?}", res);
+
+        let addr = ctx.store.config.flight_api_address.clone();
+        restart_store_server(&mut ctx.store, &addr).await?;
+
+        conn.query_drop("INSERT INTO t1 (a, b) VALUES (4, 'w')")?;
+        let count: Vec<u64> = conn.query("SELECT count(*) FROM t1")?;
+        assert_eq!(vec![4], count);
+
+        let client = ctx.store_client().await?;
+        assert_eq!(4, store_rows(&client, "db1", "t1").await?);
+    }
+
+    tracing::info!("--- drop");
+    {
+        conn.query_drop("DROP TABLE t1")?;
+        conn.query_drop("DROP DATABASE db1")?;
+
+        let client = ctx.store_client().await?;
+        let res = client.get_database("db1").await;
+        let code = res.map(|_| 0).unwrap_or_else(|e| e.code());
+        assert_eq!(ErrorCode::UnknownDatabase("").code(), code);
+        assert!(!ctx.query.catalog().exists_database("db1")?);
+    }
+
+    drop(conn);
+    ctx.shutdown().await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_query_nodes_on_separate_stores() -> anyhow::Result<()> {
+    // Two contexts in one process: a database created through one is not in the store of the other.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let ctx1 = StoreQueryTestContext::start().await?;
+    let ctx2 = StoreQueryTestContext::start().await?;
+
+    ctx1.query.connect()?.query_drop("CREATE DATABASE db1")?;
+    ctx2.query.connect()?.query_drop("CREATE DATABASE db2")?;
+
+    let client1 = ctx1.store_client().await?;
+    let client2 = ctx2.store_client().await?;
+    assert!(client1.get_database("db1").await.is_ok());
+    assert!(client1.get_database("db2").await.is_err());
+    assert!(client2.get_database("db2").await.is_ok());
+    assert!(client2.get_database("db1").await.is_err());
+
+    ctx1.shutdown().await?;
+    ctx2.shutdown().await
+}
+
+/// The rows in the parts of a table, read from the store.
+async fn store_rows(client: &StoreClient, db: &str, table: &str) -> anyhow::Result<usize> {
+    let info = client.get_table(db.into(), table.into()).await?;
+    let plan = ScanPlan {
+        schema_name: table.to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client
+        .read_plan(db.to_string(), table.to_string(), &plan)
+        .await?;
+
+    let mut rows = 0;
+    for part in parts.unwrap_or_default() {
+        let action = ReadAction {
+            part: part.part,
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: db.to_string(),
+                table: table.to_string(),
+                schema: info.schema.clone(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let blocks = client
+            .read_partition(info.schema.clone(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        rows += blocks.iter().map(|b| b.num_rows()).sum::<usize>();
+    }
+    Ok(rows)
+}
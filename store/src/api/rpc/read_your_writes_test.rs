@@ -0,0 +1,251 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use common_store_api_sdk::storage_api_impl::AppendResult;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultKind;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::FaultRule;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use pretty_assertions::assert_eq;
+
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_read_your_writes() -> anyhow::Result<()> {
+    // - Every append is held in the apply queue for a while.
+    // - A read plan right after an append of the same client always has the new part.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let injector = Arc::new(FaultInjector::create());
+    let mut tc = new_test_context();
+    tc.fault_injector = Some(injector.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    create_table(&client).await?;
+
+    injector.add_rule(FaultRule::create(
+        Some("ApplyAppendDataParts"),
+        FaultPhase::Request,
+        FaultKind::Delay(Duration::from_millis(20)),
+    ));
+
+    let mut last_version = 0;
+    for i in 0..20 {
+        let res = append(&client).await?;
+        assert!(res.data_version > last_version, "{}-th append", i);
+        last_version = res.data_version;
+
+        let reply = client
+            .read_plan_with_pruning("db1".into(), "tb1".into(), &scan_plan())
+            .await?;
+        assert!(reply.data_version >= res.data_version, "{}-th append", i);
+        let parts = reply.parts.unwrap_or_default();
+        assert_eq!(i + 1, parts.len(), "{}-th append", i);
+        assert!(parts
+            .iter()
+            .any(|part| part.part.name == res.parts[0].location));
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_read_plan_min_data_version() -> anyhow::Result<()> {
+    // - A read plan of a version not reached yet waits for it, or fails with StaleRead.
+    // - A client without the version of an append may see the table without it.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let injector = Arc::new(FaultInjector::create());
+    let mut tc = new_test_context();
+    tc.fault_injector = Some(injector.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let writer = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    let reader = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    create_table(&writer).await?;
+
+    let first = append(&writer).await?;
+    let reply = reader
+        .read_plan_at_version("db1".into(), "tb1".into(), &scan_plan(), first.data_version)
+        .await?;
+    assert_eq!(first.data_version, reply.data_version);
+    assert_eq!(1, reply.parts.unwrap_or_default().len());
+
+    // A version never reached.
+    let started = Instant::now();
+    let res = reader
+        .read_plan_at_version(
+            "db1".into(),
+            "tb1".into(),
+            &scan_plan(),
+            first.data_version + 1000,
+        )
+        .await;
+    assert_eq!(ErrorCode::StaleRead("").code(), res.unwrap_err().code());
+    assert!(started.elapsed() >= Duration::from_millis(900));
+
+    // The second append is held in the apply queue.
+    injector.add_rule(
+        FaultRule::create(
+            Some("ApplyAppendDataParts"),
+            FaultPhase::Request,
+            FaultKind::Delay(Duration::from_millis(500)),
+        )
+        .times(1),
+    );
+    let second = tokio::spawn({
+        let writer = writer.clone();
+        async move { append(&writer).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Meanwhile the reader sees the old view, without an error.
+    let reply = reader
+        .read_plan_with_pruning("db1".into(), "tb1".into(), &scan_plan())
+        .await?;
+    assert_eq!(first.data_version, reply.data_version);
+    assert_eq!(1, reply.parts.unwrap_or_default().len());
+
+    // A read of a newer version waits for the append to be applied.
+    let reply = reader
+        .read_plan_at_version(
+            "db1".into(),
+            "tb1".into(),
+            &scan_plan(),
+            first.data_version + 1,
+        )
+        .await?;
+    assert_eq!(2, reply.parts.unwrap_or_default().len());
+
+    let second = second.await??;
+    assert_eq!(second.data_version, reply.data_version);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_read_plan_min_data_version_by_clock() -> anyhow::Result<()> {
+    // - A read plan of a version not reached yet waits by the clock of the meta node.
+    // - It fails with StaleRead only once the clock passes the wait.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let clock = VirtualClock::create();
+    let mut tc = new_test_context();
+    tc.config.meta_config.clock = SharedClock::create(clock.clone());
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    create_table(&client).await?;
+    let first = append(&client).await?;
+
+    let mut read = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .read_plan_at_version(
+                    "db1".into(),
+                    "tb1".into(),
+                    &scan_plan(),
+                    first.data_version + 1000,
+                )
+                .await
+        }
+    });
+    let res = tokio::time::timeout(Duration::from_millis(1500), &mut read).await;
+    assert!(res.is_err(), "the wait ended before the clock moved");
+
+    clock.advance(Duration::from_secs(2));
+    let res = tokio::time::timeout(Duration::from_secs(1), read).await??;
+    assert_eq!(ErrorCode::StaleRead("").code(), res.unwrap_err().code());
+
+    Ok(())
+}
+
+async fn create_table(client: &StoreClient) -> anyhow::Result<()> {
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+    client
+        .create_table(CreateTablePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            table: "tb1".to_string(),
+            schema: schema(),
+            options: Default::default(),
+            engine: "PARQUET".to_string(),
+        })
+        .await?;
+    Ok(())
+}
+
+async fn append(client: &StoreClient) -> common_exception::Result<AppendResult> {
+    let block = DataBlock::create_by_array(schema(), vec![
+        Series::new(vec![0i64, 1, 2]),
+        Series::new(vec!["str1", "str2", "str3"]),
+    ]);
+    client
+        .append_data(
+            "db1".into(),
+            "tb1".into(),
+            schema(),
+            Box::pin(futures::stream::iter(vec![block])),
+        )
+        .await
+}
+
+fn scan_plan() -> ScanPlan {
+    ScanPlan {
+        schema_name: "tb1".to_string(),
+        ..ScanPlan::empty()
+    }
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ])
+}
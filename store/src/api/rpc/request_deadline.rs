@@ -0,0 +1,124 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_runtime::tokio::time::Sleep;
+use common_store_api_sdk::storage_api_impl;
+use futures::Future;
+use futures::Stream;
+use metrics::counter;
+use tonic::metadata::MetadataMap;
+use tonic::Status;
+
+use crate::api::rpc::AuditLog;
+
+/// Counts the requests given up at their deadlines, labeled by the action.
+pub static METRIC_DEADLINE_ABORTS: &str = "store.deadline_aborts";
+
+/// The time the client waits for a request until, if it sends the time left along with it.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestDeadline {
+    at: Instant,
+}
+
+impl RequestDeadline {
+    pub fn from_metadata(meta: &MetadataMap) -> Option<Self> {
+        storage_api_impl::get_deadline(meta).map(|remaining| RequestDeadline {
+            at: Instant::now() + remaining,
+        })
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    /// Fails if the client has given up the request, so no more work is done for it.
+    pub fn check(&self, action: &str, audit_log: &Option<Arc<AuditLog>>) -> Result<(), Status> {
+        match self.remaining().is_zero() {
+            false => Ok(()),
+            true => Err(abort_at_deadline(action, audit_log)),
+        }
+    }
+}
+
+/// Records a request given up at its deadline, and returns the error for the client.
+pub fn abort_at_deadline(action: &str, audit_log: &Option<Arc<AuditLog>>) -> Status {
+    counter!(METRIC_DEADLINE_ABORTS, 1, "action" => action.to_string());
+    if let Some(audit_log) = audit_log {
+        audit_log.incr_deadline_aborts();
+    }
+
+    Status::from(ErrorCode::DeadlineExceeded(format!(
+        "{} is given up at the deadline of the request",
+        action
+    )))
+}
+
+/// Ends a response stream with `DeadlineExceeded` at the deadline of the request.
+/// The deadline is checked before each message, and a message that is not ready by then is
+/// not waited for.
+pub struct DeadlineStream<S> {
+    inner: S,
+    sleep: Pin<Box<Sleep>>,
+    action: &'static str,
+    audit_log: Option<Arc<AuditLog>>,
+    done: bool,
+}
+
+impl<S> DeadlineStream<S> {
+    pub fn create(
+        inner: S,
+        deadline: RequestDeadline,
+        action: &'static str,
+        audit_log: Option<Arc<AuditLog>>,
+    ) -> Self {
+        DeadlineStream {
+            inner,
+            sleep: Box::pin(common_runtime::tokio::time::sleep(deadline.remaining())),
+            action,
+            audit_log,
+            done: false,
+        }
+    }
+}
+
+impl<S, T> Stream for DeadlineStream<S>
+where S: Stream<Item = Result<T, Status>> + Unpin
+{
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        if self.sleep.as_mut().poll(cx).is_ready() {
+            self.done = true;
+            return Poll::Ready(Some(Err(abort_at_deadline(self.action, &self.audit_log))));
+        }
+
+        let res = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(None) = res {
+            self.done = true;
+        }
+        res
+    }
+}
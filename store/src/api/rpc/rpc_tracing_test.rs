@@ -0,0 +1,283 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::RpcStats;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use common_tracing::tracing;
+use common_tracing::tracing::field::Field;
+use common_tracing::tracing::field::Visit;
+use common_tracing::tracing::span::Attributes;
+use common_tracing::tracing::span::Id;
+use common_tracing::tracing::span::Record;
+use common_tracing::tracing::Instrument;
+use common_tracing::tracing::Subscriber;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::tests::start_store_server;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_store_rpc_spans() -> anyhow::Result<()> {
+    let (_log_guards, _ut_span) = init_store_ut!();
+
+    // The calls are made from this thread, the spans of the server are not recorded.
+    let recorder = SpanRecorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    let _subscriber_guard = tracing::subscriber::set_default(subscriber);
+
+    let (_tc, addr) = start_store_server().await?;
+    let mut client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    let node_stats = Arc::new(RpcStats::create());
+    let stats = Arc::new(RpcStats::create_with_parent(node_stats.clone()));
+    client.set_rpc_stats(stats.clone());
+
+    let query_span = tracing::info_span!("query");
+    let parts = async {
+        client.create_database(database_plan("db1")).await?;
+        client
+            .create_table(table_plan("db1", "tb1", schema()))
+            .await?;
+        client.get_table("db1".into(), "tb1".into()).await?;
+
+        let block = DataBlock::create_by_array(schema(), vec![
+            Series::new(vec![0i64, 1, 2]),
+            Series::new(vec!["str1", "str2", "str3"]),
+        ]);
+        let stream = futures::stream::iter(vec![block]);
+        client
+            .append_data("db1".into(), "tb1".into(), schema(), Box::pin(stream))
+            .await?;
+
+        let plan = ScanPlan {
+            schema_name: "tb1".to_string(),
+            ..ScanPlan::empty()
+        };
+        let parts = client
+            .read_plan("db1".into(), "tb1".into(), &plan)
+            .await?
+            .unwrap_or_default();
+
+        for part in parts.iter() {
+            let action = ReadAction {
+                part: part.part.clone(),
+                push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                    db: "db1".to_string(),
+                    table: "tb1".to_string(),
+                    schema: schema(),
+                    ..ReadDataSourcePlan::empty(0, None)
+                }),
+            };
+            let blocks = client
+                .read_partition(schema(), &action)
+                .await?
+                .try_collect::<Vec<_>>()
+                .await?;
+            assert_eq!(3, blocks.iter().map(|b| b.num_rows()).sum::<usize>());
+        }
+        Ok::<_, anyhow::Error>(parts.len())
+    }
+    .instrument(query_span)
+    .await?;
+    assert_eq!(1, parts);
+
+    let spans = recorder.closed();
+    let mut issued = BTreeMap::new();
+    for span in spans.iter() {
+        let action = span.field("action");
+        *issued.entry(action.clone()).or_insert(0u64) += 1;
+
+        assert_eq!(Some("query".to_string()), span.parent, "{}", action);
+        assert_eq!("ok", span.field("outcome"), "{}", action);
+        let resource = match action.as_str() {
+            "CreateDatabase" => "db1",
+            _ => "db1.tb1",
+        };
+        assert_eq!(resource, span.field("resource"), "{}", action);
+        assert!(span.fields.contains_key("elapsed_ms"), "{}", action);
+        assert!(span.fields.contains_key("request_bytes"), "{}", action);
+        assert!(span.fields.contains_key("response_bytes"), "{}", action);
+    }
+
+    // The spans and the counters agree with the calls made above.
+    let expected = vec![
+        ("Append", 1),
+        ("CreateDatabase", 1),
+        ("CreateTable", 1),
+        ("GetTable", 1),
+        ("Read", 1),
+        ("ReadPlan", 1),
+    ];
+    for (action, count) in expected.iter() {
+        assert_eq!(Some(count), issued.get(*action), "{}", action);
+        let stat = stats.get(action).unwrap_or_default();
+        assert_eq!(*count, stat.count, "{}", action);
+    }
+    assert_eq!(expected.len(), issued.len());
+    assert_eq!(spans.len() as u64, stats.total_count());
+    // Counted into the parent as well.
+    assert_eq!(stats.snapshot(), node_stats.snapshot());
+
+    // A streamed call records the first message and the whole stream apart.
+    let read = spans.iter().find(|s| s.field("action") == "Read").unwrap();
+    let ttfb: u64 = read.field("ttfb_ms").parse()?;
+    let elapsed: u64 = read.field("elapsed_ms").parse()?;
+    assert!(ttfb <= elapsed);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_store_rpc_spans_redacted() -> anyhow::Result<()> {
+    let (_log_guards, _ut_span) = init_store_ut!();
+
+    let recorder = SpanRecorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    let _subscriber_guard = tracing::subscriber::set_default(subscriber);
+
+    let (_tc, addr) = start_store_server().await?;
+    let mut client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+    client.set_redact_rpc_keys(true);
+
+    client.create_database(database_plan("db1")).await?;
+    let res = client.get_table("db1".into(), "secret".into()).await;
+    assert!(res.is_err());
+
+    let spans = recorder.closed();
+    assert_eq!(2, spans.len());
+    for span in spans.iter() {
+        assert_eq!("<redacted>", span.field("resource"));
+    }
+    assert!(spans[1].field("outcome").starts_with("error"));
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, Default)]
+struct SpanRecord {
+    parent: Option<String>,
+    fields: BTreeMap<String, String>,
+}
+
+impl SpanRecord {
+    fn field(&self, name: &str) -> String {
+        self.fields.get(name).cloned().unwrap_or_default()
+    }
+}
+
+/// Records the fields of the `store_rpc` spans.
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    open: Arc<Mutex<HashMap<u64, SpanRecord>>>,
+    closed: Arc<Mutex<Vec<SpanRecord>>>,
+}
+
+impl SpanRecorder {
+    fn closed(&self) -> Vec<SpanRecord> {
+        self.closed.lock().unwrap().clone()
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S> Layer<S> for SpanRecorder
+where S: Subscriber + for<'a> LookupSpan<'a>
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "store_rpc" {
+            return;
+        }
+
+        let parent = ctx
+            .span(id)
+            .and_then(|span| span.parent())
+            .map(|parent| parent.name().to_string());
+        let mut record = SpanRecord {
+            parent,
+            fields: BTreeMap::new(),
+        };
+        attrs.record(&mut FieldVisitor(&mut record.fields));
+        self.open.lock().unwrap().insert(id.into_u64(), record);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        if let Some(record) = self.open.lock().unwrap().get_mut(&id.into_u64()) {
+            values.record(&mut FieldVisitor(&mut record.fields));
+        }
+    }
+
+    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+        if let Some(record) = self.open.lock().unwrap().remove(&id.into_u64()) {
+            self.closed.lock().unwrap().push(record);
+        }
+    }
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+    ])
+}
+
+fn database_plan(db: &str) -> CreateDatabasePlan {
+    CreateDatabasePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        engine: "Local".to_string(),
+        options: Default::default(),
+    }
+}
+
+fn table_plan(db: &str, table: &str, schema: DataSchemaRef) -> CreateTablePlan {
+    CreateTablePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        table: table.to_string(),
+        schema,
+        options: Default::default(),
+        engine: "PARQUET".to_string(),
+    }
+}
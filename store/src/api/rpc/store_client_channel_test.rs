@@ -0,0 +1,185 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_planners::CreateDatabasePlan;
+use common_runtime::tokio;
+use common_runtime::tokio::io::AsyncReadExt;
+use common_runtime::tokio::io::AsyncWriteExt;
+use common_runtime::tokio::net::tcp::OwnedReadHalf;
+use common_runtime::tokio::net::tcp::OwnedWriteHalf;
+use common_runtime::tokio::net::TcpListener;
+use common_runtime::tokio::net::TcpStream;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use common_store_api_sdk::ChannelConf;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StoreClient;
+use pretty_assertions::assert_eq;
+
+use crate::tests::start_store_server;
+
+/// Forwards the connections to the store and counts them.
+/// Once blackholed, the bytes of either side are not forwarded any more.
+struct TcpProxy {
+    addr: String,
+    accepted: Arc<AtomicUsize>,
+    closed: Arc<AtomicUsize>,
+    blackholed: Arc<AtomicBool>,
+}
+
+impl TcpProxy {
+    async fn start(target: String) -> anyhow::Result<TcpProxy> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let proxy = TcpProxy {
+            addr: listener.local_addr()?.to_string(),
+            accepted: Arc::new(AtomicUsize::new(0)),
+            closed: Arc::new(AtomicUsize::new(0)),
+            blackholed: Arc::new(AtomicBool::new(false)),
+        };
+
+        let accepted = proxy.accepted.clone();
+        let closed = proxy.closed.clone();
+        let blackholed = proxy.blackholed.clone();
+        tokio::spawn(async move {
+            while let Ok((inbound, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let outbound = match TcpStream::connect(&target).await {
+                    Ok(outbound) => outbound,
+                    Err(_) => continue,
+                };
+                let (in_read, in_write) = inbound.into_split();
+                let (out_read, out_write) = outbound.into_split();
+
+                // The client closes the connection when the upstream half ends.
+                let closed = closed.clone();
+                let up = Self::forward(in_read, out_write, blackholed.clone());
+                tokio::spawn(async move {
+                    up.await;
+                    closed.fetch_add(1, Ordering::SeqCst);
+                });
+                tokio::spawn(Self::forward(out_read, in_write, blackholed.clone()));
+            }
+        });
+
+        Ok(proxy)
+    }
+
+    async fn forward(mut from: OwnedReadHalf, mut to: OwnedWriteHalf, blackholed: Arc<AtomicBool>) {
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = match from.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            if blackholed.load(Ordering::SeqCst) {
+                continue;
+            }
+            if to.write_all(&buf[..n]).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    fn accepted(&self) -> usize {
+        self.accepted.load(Ordering::SeqCst)
+    }
+
+    fn closed(&self) -> usize {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn blackhole(&self, blackholed: bool) {
+        self.blackholed.store(blackholed, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_store_client_channel_max_idle() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = start_store_server().await?;
+    let proxy = TcpProxy::start(addr).await?;
+
+    let clock = VirtualClock::create();
+    let client =
+        StoreClient::with_channel_conf(proxy.addr.as_str(), "root", "xxx", None, ChannelConf {
+            max_idle: Some(Duration::from_secs(1)),
+            clock: SharedClock::create(clock.clone()),
+            ..Default::default()
+        })
+        .await?;
+    client.create_database(database_plan("db1")).await?;
+    assert_eq!(1, proxy.accepted());
+
+    // In use, the channel is kept.
+    client.get_database("db1").await?;
+    assert_eq!(1, proxy.accepted());
+
+    // Idle for too long, the next call goes through a new connection.
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(1, client.get_database("db1").await?.database_id);
+    assert_eq!(2, proxy.accepted());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_store_client_channel_keepalive() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, addr) = start_store_server().await?;
+    let proxy = TcpProxy::start(addr).await?;
+
+    let client =
+        StoreClient::with_channel_conf(proxy.addr.as_str(), "root", "xxx", None, ChannelConf {
+            keepalive: Some(Duration::from_millis(500)),
+            ..Default::default()
+        })
+        .await?;
+    client.create_database(database_plan("db1")).await?;
+
+    // Acknowledged pings keep the connection.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(1, proxy.accepted());
+    assert_eq!(0, proxy.closed());
+
+    // The pings are lost: the client closes the connection without waiting for a call.
+    proxy.blackhole(true);
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert_eq!(1, proxy.closed());
+
+    // And connects again on the next call.
+    proxy.blackhole(false);
+    assert_eq!(1, client.get_database("db1").await?.database_id);
+    assert_eq!(2, proxy.accepted());
+
+    Ok(())
+}
+
+fn database_plan(db: &str) -> CreateDatabasePlan {
+    CreateDatabasePlan {
+        if_not_exists: false,
+        db: db.to_string(),
+        engine: "Local".to_string(),
+        options: Default::default(),
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_runtime::tokio;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StorageApi;
+use common_store_api_sdk::StoreClient;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+use crate::tests::service::new_test_context;
+use crate::tests::start_store_server_with_context;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_table_engines() -> anyhow::Result<()> {
+    // - Create a table of each engine, append the same block to both.
+    // - The parts are written in the format of the engine, and recorded with it.
+    // - Both read back the same data.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    // The parts are read from the files, none is kept inline.
+    tc.config.inline_part_max_bytes = 0;
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+
+    let mut contents = vec![];
+    let mut results = vec![];
+    for (table, engine, format) in [
+        ("tb_parquet", "PARQUET", "parquet"),
+        ("tb_json", "JSON", "ndjson"),
+    ] {
+        client.create_table(table_plan(table, engine)).await?;
+        client
+            .append_data(
+                "db1".into(),
+                table.into(),
+                schema(),
+                Box::pin(futures::stream::iter(vec![block()])),
+            )
+            .await?;
+
+        let parts = read_plan(&client, table).await?;
+        assert_eq!(1, parts.len());
+        assert_eq!(
+            Some(format.to_string()),
+            parts[0].format,
+            "format of {}",
+            table
+        );
+        assert!(parts[0].part.name.ends_with(&format!(".{}", format)));
+
+        let path = Path::new(&tc.config.local_fs_dir).join(&parts[0].part.name);
+        contents.push(std::fs::read(path)?);
+        results.push(read_all(&client, table, &parts).await?);
+    }
+
+    // The magic number of parquet, at both ends of the file.
+    let parquet = &contents[0];
+    assert_eq!(b"PAR1", &parquet[..4]);
+    assert_eq!(b"PAR1", &parquet[parquet.len() - 4..]);
+
+    // A JSON object per row, by the column names.
+    let rows = String::from_utf8(contents[1].clone())?
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value>)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        vec![
+            json!({"col_i": 0, "col_s": "str1", "col_f": null}),
+            json!({"col_i": 1, "col_s": "str2", "col_f": 1.5}),
+            json!({"col_i": 2, "col_s": "str3", "col_f": -2.25}),
+        ],
+        rows
+    );
+
+    let expected = vec![
+        "+-------+-------+-------+",
+        "| col_i | col_s | col_f |",
+        "+-------+-------+-------+",
+        "| 0     | str1  | NULL  |",
+        "| 1     | str2  | 1.5   |",
+        "| 2     | str3  | -2.25 |",
+        "+-------+-------+-------+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected.clone(), &results[0]);
+    common_datablocks::assert_blocks_sorted_eq(expected, &results[1]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_unknown_table_engine() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+    start_store_server_with_context(&mut tc).await?;
+    let addr = tc.config.flight_api_address.clone();
+    let client = StoreClient::try_create(addr.as_str(), "root", "xxx").await?;
+
+    client
+        .create_database(CreateDatabasePlan {
+            if_not_exists: false,
+            db: "db1".to_string(),
+            engine: "Local".to_string(),
+            options: Default::default(),
+        })
+        .await?;
+
+    let res = client.create_table(table_plan("tb1", "ORC")).await;
+    assert_eq!(
+        ErrorCode::UnknownTableEngine("").code(),
+        res.unwrap_err().code()
+    );
+
+    // The engines are case-insensitive, and the tables served by the query nodes are kept as is.
+    client.create_table(table_plan("tb1", "json")).await?;
+    client.create_table(table_plan("tb2", "Memory")).await?;
+
+    Ok(())
+}
+
+async fn read_plan(client: &StoreClient, table: &str) -> anyhow::Result<Vec<DataPartInfo>> {
+    let plan = ScanPlan {
+        schema_name: table.to_string(),
+        ..ScanPlan::empty()
+    };
+    let parts = client.read_plan("db1".into(), table.into(), &plan).await?;
+    Ok(parts.unwrap_or_default())
+}
+
+async fn read_all(
+    client: &StoreClient,
+    table: &str,
+    parts: &[DataPartInfo],
+) -> anyhow::Result<Vec<DataBlock>> {
+    let mut blocks = vec![];
+    for part in parts {
+        let action = ReadAction {
+            part: part.part.clone(),
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: "db1".to_string(),
+                table: table.to_string(),
+                schema: schema(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let mut part_blocks = client
+            .read_partition(schema(), &action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        blocks.append(&mut part_blocks);
+    }
+    Ok(blocks)
+}
+
+fn block() -> DataBlock {
+    DataBlock::create_by_array(schema(), vec![
+        Series::new(vec![0i64, 1, 2]),
+        Series::new(vec!["str1", "str2", "str3"]),
+        Series::new(vec![None, Some(1.5f64), Some(-2.25)]),
+    ])
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("col_i", DataType::Int64, false),
+        DataField::new("col_s", DataType::String, false),
+        DataField::new("col_f", DataType::Float64, true),
+    ])
+}
+
+fn table_plan(table: &str, engine: &str) -> CreateTablePlan {
+    CreateTablePlan {
+        if_not_exists: false,
+        db: "db1".to_string(),
+        table: table.to_string(),
+        schema: schema(),
+        options: Default::default(),
+        engine: engine.to_string(),
+    }
+}
@@ -13,12 +13,15 @@
 // limitations under the License.
 
 use common_exception::ErrorCode;
+use common_metatypes::MatchSeq;
 use common_runtime::tokio;
+use common_store_api_sdk::KVApi;
 use common_store_api_sdk::MetaApi;
 use common_store_api_sdk::RpcClientTlsConfig;
 use common_store_api_sdk::StoreClient;
 use pretty_assertions::assert_eq;
 
+use crate::api::StoreServer;
 use crate::tests::service::new_test_context;
 use crate::tests::start_store_server_with_context;
 
@@ -58,6 +61,59 @@ async fn test_flight_tls() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_tls_kv() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+
+    tc.config.rpc_tls_server_key = TEST_SERVER_KEY.to_owned();
+    tc.config.rpc_tls_server_cert = TEST_SERVER_CERT.to_owned();
+
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+
+    let tls_conf = RpcClientTlsConfig {
+        rpc_tls_server_root_ca_cert: TEST_CA_CERT.to_string(),
+        domain_name: TEST_CN_NAME.to_string(),
+    };
+
+    let client = StoreClient::with_tls_conf(addr.as_str(), "root", "xxx", Some(tls_conf)).await?;
+
+    let res = client
+        .upsert_kv("tls_key", MatchSeq::Any, Some(b"tls_value".to_vec()), None)
+        .await?;
+    assert_eq!(None, res.prev);
+
+    let res = client.get_kv("tls_key").await?;
+    let (_seq, kv_value) = res.result.unwrap();
+    assert_eq!(b"tls_value".to_vec(), kv_value.value);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_tls_rejects_plaintext_client() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+
+    tc.config.rpc_tls_server_key = TEST_SERVER_KEY.to_owned();
+    tc.config.rpc_tls_server_cert = TEST_SERVER_CERT.to_owned();
+
+    start_store_server_with_context(&mut tc).await?;
+
+    let addr = tc.config.flight_api_address.clone();
+
+    // The handshake of a client without TLS never reaches the server.
+    let r = StoreClient::try_create(addr.as_str(), "root", "xxx").await;
+    assert!(r.is_err());
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_flight_tls_server_config_failure() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_store_ut!();
@@ -73,6 +129,25 @@ async fn test_flight_tls_server_config_failure() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_flight_tls_server_invalid_identity() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_test_context();
+
+    // The files exist, but a cert is not a private key.
+    tc.config.rpc_tls_server_key = TEST_CA_CERT.to_owned();
+    tc.config.rpc_tls_server_cert = TEST_SERVER_CERT.to_owned();
+
+    let r = StoreServer::create(tc.config.clone()).start().await;
+    match r {
+        Ok(_) => assert!(false, "A broken identity must fail the start"),
+        Err(e) => assert_eq!(e.code(), ErrorCode::TLSConfigurationFailure("").code()),
+    }
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_flight_tls_client_config_failure() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_store_ut!();
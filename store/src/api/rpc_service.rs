@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_arrow::arrow_flight::flight_service_server::FlightServiceServer;
 use common_exception::ErrorCode;
@@ -21,6 +22,7 @@ use common_runtime::tokio;
 use common_runtime::tokio::sync::oneshot;
 use common_runtime::tokio::sync::oneshot::Receiver;
 use common_runtime::tokio::sync::oneshot::Sender;
+use common_store_api_sdk::FaultInjector;
 use common_tracing::tracing;
 use common_tracing::tracing::Instrument;
 use metasrv::meta_service::MetaNode;
@@ -29,18 +31,72 @@ use tonic::transport::Identity;
 use tonic::transport::Server;
 use transport::ServerTlsConfig;
 
+use crate::api::rpc::AuditLog;
 use crate::api::rpc::StoreFlightImpl;
+use crate::api::MetaNodeHandle;
 use crate::configs::Config;
+use crate::configs::ConfigHandle;
 use crate::dfs::Dfs;
+use crate::executor::ActionHandler;
+use crate::executor::Applier;
+use crate::executor::ApplyQueue;
+use crate::executor::FaultyApplier;
+use crate::executor::InlinePartCompactor;
+use crate::executor::StagingVacuum;
+use crate::fs::FileSystem;
 use crate::localfs::LocalFS;
+use crate::metrics::DatabaseUsageRecorder;
 
 pub struct StoreServer {
     conf: Config,
+    config_handle: Arc<ConfigHandle>,
+    fault_injector: Option<Arc<FaultInjector>>,
+    audit_log: Option<Arc<AuditLog>>,
+    usage_recorder: Arc<DatabaseUsageRecorder>,
+    meta_node_handle: Arc<MetaNodeHandle>,
 }
 
 impl StoreServer {
     pub fn create(conf: Config) -> Self {
-        Self { conf }
+        Self {
+            config_handle: Arc::new(ConfigHandle::create(conf.clone())),
+            conf,
+            fault_injector: None,
+            audit_log: None,
+            usage_recorder: Arc::new(DatabaseUsageRecorder::create()),
+            meta_node_handle: Arc::new(MetaNodeHandle::create()),
+        }
+    }
+
+    /// Reads the dynamic settings from `handle`, e.g., the one reloaded on SIGHUP.
+    /// The static settings are still read from the config the server is created with.
+    pub fn with_config_handle(mut self, handle: Arc<ConfigHandle>) -> Self {
+        self.config_handle = handle;
+        self
+    }
+
+    /// Reports the usages of the databases to `recorder`, e.g., the one served by the http api.
+    pub fn with_usage_recorder(mut self, recorder: Arc<DatabaseUsageRecorder>) -> Self {
+        self.usage_recorder = recorder;
+        self
+    }
+
+    /// Shares the meta node once it is started through `handle`, e.g., with the http api.
+    pub fn with_meta_node_handle(mut self, handle: Arc<MetaNodeHandle>) -> Self {
+        self.meta_node_handle = handle;
+        self
+    }
+
+    /// Injects the faults into the flight service, for the tests only.
+    pub fn with_fault_injector(mut self, injector: Option<Arc<FaultInjector>>) -> Self {
+        self.fault_injector = injector;
+        self
+    }
+
+    /// Keeps the latest requests of the flight service in `audit_log`, for the tests only.
+    pub fn with_audit_log(mut self, audit_log: Option<Arc<AuditLog>>) -> Self {
+        self.audit_log = audit_log;
+        self
     }
 
     /// Start store server and returns two channel to send shutdown signal and receive signal when shutdown finished.
@@ -111,9 +167,57 @@ impl StoreServer {
         };
         tracing::info!("Done starting MetaNode: {:?}", self.conf);
 
-        let dfs = Dfs::create(fs, mn.clone());
+        self.usage_recorder.reset(mn.get_database_usages().await?);
+        self.meta_node_handle.set(Some(mn.clone()));
 
-        let flight_impl = StoreFlightImpl::create(self.conf.clone(), Arc::new(dfs), mn.clone());
+        let dfs: Arc<dyn FileSystem> = Arc::new(Dfs::create(fs, mn.clone()));
+
+        MetaNode::start_trash_vacuum(mn.clone(), meta_config.trash_vacuum_interval(), {
+            let dfs = dfs.clone();
+            move |parts| {
+                let dfs = dfs.clone();
+                async move { ActionHandler::remove_files_of_parts(dfs, &parts).await }
+            }
+        })
+        .await;
+        MetaNode::start_snapshot(
+            mn.clone(),
+            Duration::from_secs(meta_config.snapshot_interval),
+        )
+        .await;
+        MetaNode::start_replication(mn.clone(), meta_config).await?;
+
+        let applier: Arc<dyn Applier> = match &self.fault_injector {
+            None => mn.clone(),
+            Some(injector) => Arc::new(FaultyApplier::create(mn.clone(), injector.clone())),
+        };
+        let apply_queue = ApplyQueue::start(
+            applier,
+            self.conf.apply_queue_depth,
+            self.config_handle.clone(),
+        );
+
+        // Paused while their intervals are 0, a reload may resume them.
+        let compactor = {
+            let handler = ActionHandler::create(dfs.clone(), mn.clone(), apply_queue.clone())
+                .with_inline_part_max_bytes(self.conf.inline_part_max_bytes)
+                .with_usage_recorder(self.usage_recorder.clone())
+                .with_config_handle(self.config_handle.clone());
+            InlinePartCompactor::start(Arc::new(handler))
+        };
+
+        let staging_vacuum = {
+            let handler = ActionHandler::create(dfs.clone(), mn.clone(), apply_queue.clone())
+                .with_config_handle(self.config_handle.clone());
+            StagingVacuum::start(Arc::new(handler))
+        };
+
+        let flight_impl =
+            StoreFlightImpl::create(self.conf.clone(), dfs, mn.clone(), apply_queue.clone())
+                .with_config_handle(self.config_handle.clone())
+                .with_fault_injector(self.fault_injector.clone())
+                .with_audit_log(self.audit_log.clone())
+                .with_usage_recorder(self.usage_recorder.clone());
         let flight_srv = FlightServiceServer::new(flight_impl);
 
         let builder = Server::builder();
@@ -140,6 +244,11 @@ impl StoreServer {
             })
             .await;
 
+        compactor.shutdown().await;
+        staging_vacuum.shutdown().await;
+        // The mutations accepted before the stop signal are applied before the meta node stops.
+        apply_queue.shutdown().await;
+        self.meta_node_handle.set(None);
         let _ = mn.stop().await;
         let s = fin_tx.send(());
         tracing::info!(
@@ -160,6 +269,9 @@ impl StoreServer {
             let server_identity = Identity::from_pem(cert, key);
 
             let tls = ServerTlsConfig::new().identity(server_identity);
+
+            // Parse the identity now, a broken cert or key fails the start, not the first RPC.
+            Server::builder().tls_config(tls.clone())?;
             Ok(Some(tls))
         } else {
             Ok(None)
@@ -12,20 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use common_runtime::tokio;
+use common_runtime::tokio::signal::unix::signal;
+use common_runtime::tokio::signal::unix::SignalKind;
 use common_tracing::init_tracing_with_file;
 use common_tracing::set_panic_hook;
 use databend_store::api::HttpService;
+use databend_store::api::MetaNodeHandle;
 use databend_store::api::StoreServer;
 use databend_store::configs::Config;
+use databend_store::configs::ConfigHandle;
+use databend_store::metrics::DatabaseUsageRecorder;
 use databend_store::metrics::MetricService;
+use log::error;
 use log::info;
 use metasrv::sled_store::init_sled_db;
 use structopt::StructOpt;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let conf = Config::from_args();
+    let mut conf = Config::from_args();
+    // The command line args override the config file, on a reload as well.
+    let args = std::env::args().collect::<Vec<_>>();
+    if !conf.config_file.is_empty() {
+        conf = Config::load_from_toml_with_args(&conf.config_file, args.clone())?;
+    }
+
     env_logger::Builder::from_env(
         env_logger::Env::default().default_filter_or(conf.log_level.to_lowercase().as_str()),
     )
@@ -35,7 +49,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "databend-store",
         conf.log_dir.as_str(),
         conf.log_level.as_str(),
-    );
+    )?;
     set_panic_hook();
 
     info!("{:?}", conf.clone());
@@ -55,9 +69,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Metric API server listening on {}", conf.metric_api_address);
     }
 
+    // The running config, the dynamic settings of it are reloaded on SIGHUP.
+    let config_handle = Arc::new(ConfigHandle::create(conf.clone()).with_args(args));
+    {
+        let config_handle = config_handle.clone();
+        let mut hangup = signal(SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                match config_handle.reload() {
+                    Ok(changes) => info!("SIGHUP: reloaded the config, changes: {:?}", changes),
+                    Err(e) => error!("SIGHUP: nothing is reloaded, err: {}", e),
+                }
+            }
+        });
+    }
+
+    // The usages of the databases, reported by the RPC API and served by the HTTP API.
+    let usage_recorder = Arc::new(DatabaseUsageRecorder::create());
+
+    // The meta node of the RPC API service, once started, its state is served by the HTTP API.
+    let meta_node_handle = Arc::new(MetaNodeHandle::create());
+
     // HTTP API service.
     {
-        let mut srv = HttpService::create(conf.clone());
+        let mut srv = HttpService::create(conf.clone())
+            .with_config_handle(config_handle.clone())
+            .with_usage_recorder(usage_recorder.clone())
+            .with_meta_node_handle(meta_node_handle.clone());
         info!("HTTP API server listening on {}", conf.http_api_address);
         tokio::spawn(async move {
             srv.start().await.expect("HTTP: admin api error");
@@ -66,7 +104,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // RPC API service.
     {
-        let srv = StoreServer::create(conf.clone());
+        let srv = StoreServer::create(conf.clone())
+            .with_config_handle(config_handle)
+            .with_usage_recorder(usage_recorder)
+            .with_meta_node_handle(meta_node_handle);
         info!(
             "DatabendStore API server listening on {}",
             conf.flight_api_address
@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::PathBuf;
+
+use common_exception::ErrorCode;
 use lazy_static::lazy_static;
 use structopt::StructOpt;
 use structopt_toml::StructOptToml;
@@ -91,6 +94,117 @@ pub struct Config {
         default_value = "./_local_fs"
     )]
     pub local_fs_dir: String,
+
+    #[structopt(
+        long,
+        env = "STORE_EXTERNAL_DATA_DIRS",
+        help = "Comma separated dirs whose files can be read by the tables of the external engines CSV and NDJSON, none if it is empty",
+        default_value = ""
+    )]
+    pub external_data_dirs: String,
+
+    #[structopt(
+        long,
+        env = "STORE_APPLY_QUEUE_DEPTH",
+        help = "Max number of the mutations waiting to be applied, a write waits when it is full",
+        default_value = "1024"
+    )]
+    pub apply_queue_depth: usize,
+
+    #[structopt(
+        long,
+        env = "STORE_SLOW_APPLY_THRESHOLD_MS",
+        help = "A mutation taking longer than this, queue wait included, or a flight request taking longer than this is logged as a slow op",
+        default_value = "1000"
+    )]
+    pub slow_apply_threshold_ms: u64,
+
+    #[structopt(
+        long,
+        env = "STORE_TABLE_METRICS_MAX_LABELS",
+        help = "Max number of tables exported to prometheus with their own labels, the others are exported as `_other`",
+        default_value = "100"
+    )]
+    pub table_metrics_max_labels: usize,
+
+    #[structopt(
+        long,
+        env = "STORE_INLINE_PART_MAX_BYTES",
+        help = "A part smaller than this is kept in the meta store instead of as a file, 0 to keep every part as a file",
+        default_value = "262144"
+    )]
+    pub inline_part_max_bytes: usize,
+
+    #[structopt(
+        long,
+        env = "STORE_INLINE_PART_COMPACT_INTERVAL_SECS",
+        help = "How often the inline parts of each table are compacted into a file, 0 to never compact them",
+        default_value = "60"
+    )]
+    pub inline_part_compact_interval_secs: u64,
+
+    #[structopt(
+        long,
+        env = "STORE_VERIFY_PART_CHECKSUM",
+        help = "Check the sha256 of a part file every time it is read, its size is always checked"
+    )]
+    pub verify_part_checksum: bool,
+
+    #[structopt(
+        long,
+        env = "STORE_STAGING_VACUUM_INTERVAL_SECS",
+        help = "How often the staged part files left by interrupted appends, and the files of the compacted parts past their grace time, are removed, 0 to never remove them",
+        default_value = "600"
+    )]
+    pub staging_vacuum_interval_secs: u64,
+
+    #[structopt(
+        long,
+        env = "STORE_COMPACT_TARGET_PART_BYTES",
+        help = "The size the small parts of a table are merged to by OPTIMIZE TABLE, overridden by the table option of the same name",
+        default_value = "134217728"
+    )]
+    pub compact_target_part_bytes: u64,
+
+    #[structopt(
+        long,
+        env = "STORE_COMPACT_MAX_BYTES_PER_RUN",
+        help = "Max bytes of the parts an OPTIMIZE TABLE rewrites, 0 for no limit, overridden by the table option of the same name",
+        default_value = "1073741824"
+    )]
+    pub compact_max_bytes_per_run: u64,
+
+    #[structopt(
+        long,
+        env = "STORE_COMPACTED_PART_GRACE_SECS",
+        help = "How long the files of the parts replaced by a compaction are kept for the reads already planned",
+        default_value = "600"
+    )]
+    pub compacted_part_grace_secs: u64,
+
+    #[structopt(
+        long,
+        env = "STORE_STAGED_FILE_MAX_AGE_SECS",
+        help = "A staged part file older than this is taken as left by an interrupted append",
+        default_value = "3600"
+    )]
+    pub staged_file_max_age_secs: u64,
+
+    #[structopt(
+        long,
+        env = "STORE_READ_ONLY",
+        help = "Reject the writes and pause the compactions and the vacuums, the reads are still served"
+    )]
+    pub read_only: bool,
+
+    #[structopt(
+        long,
+        short = "c",
+        env = "STORE_CONFIG_FILE",
+        help = "The toml config file, it is read again on SIGHUP or the reload_config admin action",
+        default_value = ""
+    )]
+    pub config_file: String,
 }
 
 impl Config {
@@ -103,11 +217,71 @@ impl Config {
         <Self as StructOpt>::from_iter(&Vec::<&'static str>::new())
     }
 
+    /// Loads the config from the toml file, the command line `args` override the file.
+    /// The settings missing in the file are taken from the env or the defaults.
+    pub fn load_from_toml_with_args<I>(file: &str, args: I) -> common_exception::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<std::ffi::OsString> + Clone,
+    {
+        let txt = std::fs::read_to_string(file)
+            .map_err(|e| ErrorCode::CannotReadFile(format!("File: {}, err: {:?}", file, e)))?;
+        let invalid = |e: &dyn std::fmt::Debug| {
+            ErrorCode::InvalidConfig(format!("File: {}, err: {:?}", file, e))
+        };
+
+        let args = args.into_iter().map(Into::into).collect::<Vec<_>>();
+        let matches = Self::clap()
+            .get_matches_from_safe(args)
+            .map_err(|e| invalid(&e))?;
+        let from_args = Self::from_clap(&matches);
+
+        // The file overrides the defaults, then the args given explicitly override the file.
+        let mut value = toml::Value::try_from(&from_args).map_err(|e| invalid(&e))?;
+        let from_file = txt.parse::<toml::Value>().map_err(|e| invalid(&e))?;
+        merge_toml(&mut value, from_file);
+        let from_toml = value.try_into::<Self>().map_err(|e| invalid(&e))?;
+
+        let mut conf = Self::merge(from_toml, from_args, &matches);
+        conf.config_file = file.to_string();
+        Ok(conf)
+    }
+
     pub fn check(&self) -> common_exception::Result<()> {
+        if self.apply_queue_depth == 0 {
+            return Err(ErrorCode::InvalidConfig(
+                "--apply-queue-depth must be greater than 0",
+            ));
+        }
         self.meta_config.check()
     }
 
     pub fn tls_rpc_server_enabled(&self) -> bool {
         !self.rpc_tls_server_key.is_empty() && !self.rpc_tls_server_cert.is_empty()
     }
+
+    pub fn external_data_dirs(&self) -> Vec<PathBuf> {
+        self.external_data_dirs
+            .split(',')
+            .map(|dir| dir.trim())
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+}
+
+fn merge_toml(to: &mut toml::Value, from: toml::Value) {
+    match (to, from) {
+        (toml::Value::Table(to), toml::Value::Table(from)) => {
+            for (name, value) in from {
+                match to.get_mut(&name) {
+                    Some(v) => merge_toml(v, value),
+                    None => {
+                        to.insert(name, value);
+                    }
+                }
+            }
+        }
+        (to, from) => *to = from,
+    }
 }
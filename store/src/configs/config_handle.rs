@@ -0,0 +1,166 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::RwLock;
+use common_tracing::tracing;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::configs::Config;
+
+/// The settings applied by a reload, the consumers of them read them through the `ConfigHandle`.
+/// The others are static, changing them needs a restart.
+pub const DYNAMIC_SETTINGS: &[&str] = &[
+    "slow_apply_threshold_ms",
+    "read_only",
+    "inline_part_compact_interval_secs",
+    "staging_vacuum_interval_secs",
+    "staged_file_max_age_secs",
+    "compacted_part_grace_secs",
+    "compact_target_part_bytes",
+    "compact_max_bytes_per_run",
+];
+
+/// A setting changed by a reload.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    /// The name of the setting, e.g. `meta_config.raft_dir` for a nested one.
+    pub name: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// The running config of the store, shared by the services.
+/// A reload swaps it as a whole, the readers always see one consistent config.
+pub struct ConfigHandle {
+    current: RwLock<Arc<Config>>,
+    /// The command line args the store is started with, they still override the file on a reload.
+    args: Vec<String>,
+}
+
+impl ConfigHandle {
+    pub fn create(conf: Config) -> Self {
+        ConfigHandle {
+            current: RwLock::new(Arc::new(conf)),
+            args: vec![],
+        }
+    }
+
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.current.read().clone()
+    }
+
+    /// Reads the config file again and applies it.
+    pub fn reload(&self) -> Result<Vec<ConfigChange>> {
+        let file = self.get().config_file.clone();
+        if file.is_empty() {
+            return Err(ErrorCode::InvalidConfig(
+                "The store is not started with --config-file, there is nothing to reload",
+            ));
+        }
+
+        // The args start with the program name.
+        let args = match self.args.is_empty() {
+            true => vec![String::from("databend-store")],
+            false => self.args.clone(),
+        };
+        let conf = Config::load_from_toml_with_args(&file, args)?;
+        self.apply(conf)
+    }
+
+    /// Applies the changes of the dynamic settings.
+    /// Nothing is applied if the config is invalid, or if any static setting is changed.
+    pub fn apply(&self, conf: Config) -> Result<Vec<ConfigChange>> {
+        conf.check()?;
+
+        let mut current = self.current.write();
+        let changes = Self::diff(&current, &conf)?;
+        let statics = changes
+            .iter()
+            .filter(|change| !DYNAMIC_SETTINGS.contains(&change.name.as_str()))
+            .map(|change| change.name.as_str())
+            .collect::<Vec<_>>();
+        if !statics.is_empty() {
+            return Err(ErrorCode::InvalidConfig(format!(
+                "Static settings can not be reloaded, restart the store to change them: {}",
+                statics.join(", ")
+            )));
+        }
+
+        *current = Arc::new(conf);
+        let summary = changes
+            .iter()
+            .map(|change| format!("{}: {} -> {}", change.name, change.old, change.new))
+            .collect::<Vec<_>>();
+        tracing::info!(changed = changes.len(), changes = ?summary, "reloaded the config");
+        Ok(changes)
+    }
+
+    fn diff(old: &Config, new: &Config) -> Result<Vec<ConfigChange>> {
+        let old = Self::settings(old)?;
+        let new = Self::settings(new)?;
+
+        let mut changes = vec![];
+        for (name, old_value) in old {
+            let new_value = new.get(&name).cloned().unwrap_or_default();
+            // The file is where the config is reloaded from, not a setting.
+            if old_value != new_value && name != "config_file" {
+                changes.push(ConfigChange {
+                    name,
+                    old: old_value,
+                    new: new_value,
+                });
+            }
+        }
+        Ok(changes)
+    }
+
+    /// The settings by their names, the nested ones are named with their paths.
+    fn settings(conf: &Config) -> Result<BTreeMap<String, String>> {
+        fn flatten(path: String, value: Value, settings: &mut BTreeMap<String, String>) {
+            match value {
+                Value::Object(object) => {
+                    for (name, value) in object {
+                        let path = match path.is_empty() {
+                            true => name,
+                            false => format!("{}.{}", path, name),
+                        };
+                        flatten(path, value, settings);
+                    }
+                }
+                Value::String(value) => {
+                    settings.insert(path, value);
+                }
+                value => {
+                    settings.insert(path, value.to_string());
+                }
+            }
+        }
+
+        let value = serde_json::to_value(conf)?;
+        let mut settings = BTreeMap::new();
+        flatten(String::new(), value, &mut settings);
+        Ok(settings)
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use pretty_assertions::assert_eq;
+
+use crate::configs::Config;
+use crate::configs::ConfigChange;
+use crate::configs::ConfigHandle;
+
+fn write_file(path: &str, txt: &str) -> anyhow::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(txt.as_bytes())?;
+    Ok(())
+}
+
+fn load(path: &str, txt: &str) -> anyhow::Result<ConfigHandle> {
+    write_file(path, txt)?;
+    let conf = Config::load_from_toml_with_args(path, vec!["databend-store"])?;
+    Ok(ConfigHandle::create(conf))
+}
+
+#[test]
+fn test_config_handle_reload_dynamic() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.toml").to_str().unwrap().to_string();
+
+    let handle = load(&path, "slow_apply_threshold_ms = 1000\n")?;
+    assert_eq!(1000, handle.get().slow_apply_threshold_ms);
+    assert_eq!(path, handle.get().config_file);
+
+    write_file(&path, "slow_apply_threshold_ms = 50\n")?;
+    let changes = handle.reload()?;
+    assert_eq!(
+        vec![ConfigChange {
+            name: "slow_apply_threshold_ms".to_string(),
+            old: "1000".to_string(),
+            new: "50".to_string(),
+        }],
+        changes
+    );
+    assert_eq!(50, handle.get().slow_apply_threshold_ms);
+
+    // Nothing changed.
+    assert_eq!(Vec::<ConfigChange>::new(), handle.reload()?);
+    Ok(())
+}
+
+#[test]
+fn test_config_handle_reload_background_settings() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.toml").to_str().unwrap().to_string();
+
+    let handle = load(&path, "staging_vacuum_interval_secs = 600\n")?;
+
+    // The vacuum is paused, the compactions are capped and the writes are rejected.
+    write_file(
+        &path,
+        "staging_vacuum_interval_secs = 0\ncompact_max_bytes_per_run = 1024\nread_only = true\n",
+    )?;
+    let changes = handle.reload()?;
+    assert_eq!(
+        vec![
+            "compact_max_bytes_per_run",
+            "read_only",
+            "staging_vacuum_interval_secs"
+        ],
+        changes.iter().map(|c| c.name.as_str()).collect::<Vec<_>>()
+    );
+    let conf = handle.get();
+    assert_eq!(0, conf.staging_vacuum_interval_secs);
+    assert_eq!(1024, conf.compact_max_bytes_per_run);
+    assert!(conf.read_only);
+    Ok(())
+}
+
+#[test]
+fn test_config_handle_reload_static() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.toml").to_str().unwrap().to_string();
+
+    let handle = load(
+        &path,
+        "slow_apply_threshold_ms = 1000\nflight_api_address = \"127.0.0.1:9191\"\n",
+    )?;
+    let before = handle.get();
+
+    // A dynamic setting along with static ones: nothing is applied.
+    write_file(
+        &path,
+        "slow_apply_threshold_ms = 50\nflight_api_address = \"127.0.0.1:9292\"\n[meta_config]\nraft_dir = \"/tmp/other\"\n",
+    )?;
+    let res = handle.reload();
+    let err = res.unwrap_err();
+    assert_eq!("InvalidConfig", err.name());
+    assert!(err.message().contains("flight_api_address"), "{}", err);
+    assert!(err.message().contains("meta_config.raft_dir"), "{}", err);
+    assert!(
+        !err.message().contains("slow_apply_threshold_ms"),
+        "{}",
+        err
+    );
+    assert_eq!(before, handle.get());
+    Ok(())
+}
+
+#[test]
+fn test_config_handle_reload_invalid() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.toml").to_str().unwrap().to_string();
+
+    let handle = load(&path, "slow_apply_threshold_ms = 1000\n")?;
+    let before = handle.get();
+
+    // Malformed.
+    write_file(&path, "slow_apply_threshold_ms = \n")?;
+    assert_eq!("InvalidConfig", handle.reload().unwrap_err().name());
+    assert_eq!(before, handle.get());
+
+    // Fails the check.
+    write_file(
+        &path,
+        "slow_apply_threshold_ms = 50\napply_queue_depth = 0\n",
+    )?;
+    assert_eq!("InvalidConfig", handle.reload().unwrap_err().name());
+    assert_eq!(before, handle.get());
+
+    // Removed.
+    std::fs::remove_file(&path)?;
+    assert_eq!("CannotReadFile", handle.reload().unwrap_err().name());
+    assert_eq!(before, handle.get());
+
+    // Not started with a config file.
+    let handle = ConfigHandle::create(Config::empty());
+    assert_eq!("InvalidConfig", handle.reload().unwrap_err().name());
+    Ok(())
+}
@@ -31,3 +31,19 @@ fn test_tls_rpc_enabled() -> anyhow::Result<()> {
     assert_eq!(true, conf.tls_rpc_server_enabled());
     Ok(())
 }
+
+#[test]
+fn test_apply_queue_depth_check() -> anyhow::Result<()> {
+    let mut conf = Config::empty();
+    assert_eq!(1024, conf.apply_queue_depth);
+    assert!(conf.check().is_ok());
+
+    conf.apply_queue_depth = 0;
+    let res = conf.check();
+    assert!(res.is_err());
+    assert_eq!(
+        "--apply-queue-depth must be greater than 0",
+        res.unwrap_err().message()
+    );
+    Ok(())
+}
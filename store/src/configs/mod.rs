@@ -13,7 +13,13 @@
 // limitations under the License.
 
 pub mod config;
+mod config_handle;
+#[cfg(test)]
+mod config_handle_test;
 #[cfg(test)]
 mod config_test;
 
 pub use config::Config;
+pub use config_handle::ConfigChange;
+pub use config_handle::ConfigHandle;
+pub use config_handle::DYNAMIC_SETTINGS;
@@ -25,51 +25,156 @@ use common_arrow::arrow::record_batch::RecordBatch;
 use common_arrow::arrow_flight::utils::flight_data_to_arrow_batch;
 use common_arrow::arrow_flight::FlightData;
 use common_datablocks::DataBlock;
+use common_runtime::SharedClock;
 use common_store_api_sdk::storage_api_impl::AppendResult;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
 use futures::StreamExt;
 use uuid::Uuid;
 
+use crate::data_part::bloom_index::BloomIndex;
+use crate::data_part::column_stats::column_statistics;
+use crate::data_part::parquet_engine::ParquetEngine;
+use crate::data_part::part_checksum::part_checksum;
+use crate::data_part::table_engine::TableEngine;
 use crate::fs::FileSystem;
 
 pub(crate) struct Appender {
     fs: Arc<dyn FileSystem>,
+    /// Encodes the blocks into parts, the engine of the table appended to.
+    engine: Arc<dyn TableEngine>,
+    /// Builds the bloom filters of the indexed columns of each part, if the table has any.
+    bloom_index: Option<BloomIndex>,
+    /// Keeps the parts smaller than this in the meta store instead of `fs`, none if 0.
+    inline_max_bytes: usize,
+    /// The staged files are named by the time of it, see `FileSystem::stage`.
+    clock: SharedClock,
 }
 
 pub type InputData = std::pin::Pin<Box<dyn futures::Stream<Item = FlightData> + Send>>;
 
+/// The bytes of the inline parts by part name, written to the meta store in the apply that
+/// registers the parts, see `Mutation::AppendDataParts`.
+pub type InlineParts = Vec<(String, Vec<u8>)>;
+
+/// The parts of an append, whose files are staged but not yet in place.
+pub(crate) struct StagedAppend {
+    pub result: AppendResult,
+    /// The staged files and the part names they are moved to.
+    staged: Vec<(String, String)>,
+    inline: InlineParts,
+}
+
 impl Appender {
     pub fn new(fs: Arc<dyn FileSystem>) -> Self {
-        Appender { fs }
+        Appender {
+            fs,
+            engine: Arc::new(ParquetEngine {}),
+            bloom_index: None,
+            inline_max_bytes: 0,
+            clock: SharedClock::default(),
+        }
+    }
+
+    pub fn with_engine(mut self, engine: Arc<dyn TableEngine>) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    pub fn with_bloom_index(mut self, bloom_index: Option<BloomIndex>) -> Self {
+        self.bloom_index = bloom_index;
+        self
     }
 
+    /// Keeps the parts smaller than `max_bytes` in the meta store, none if `max_bytes` is 0.
+    pub fn with_inline_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.inline_max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Encodes the blocks into parts and stages their files, see `FileSystem::stage`.
+    /// Nothing is in place until the append is committed, an append abandoned before that
+    /// leaves its staged files to the staging vacuum. The bytes of the inline parts are only
+    /// kept in memory, nothing of them is left behind.
+    ///
     /// Assumes
     /// - upstream caller has properly batched data
     /// - first element of the incoming stream is a properly serialized schema
-    pub async fn append_data(&self, path: String, mut stream: InputData) -> Result<AppendResult> {
+    pub async fn append_data(&self, path: String, mut stream: InputData) -> Result<StagedAppend> {
         if let Some(flight_data) = stream.next().await {
             let arrow_schema = ArrowSchema::try_from(&flight_data)?;
             let arrow_schema_ref = Arc::new(arrow_schema);
 
             let mut result = AppendResult::default();
+            let mut staged = vec![];
+            let mut inline = vec![];
             while let Some(flight_data) = stream.next().await {
                 let batch =
                     flight_data_to_arrow_batch(&flight_data, arrow_schema_ref.clone(), true, &[])?;
                 let block = DataBlock::try_from(batch)?;
                 let (rows, cols, wire_bytes) =
                     (block.num_rows(), block.num_columns(), block.memory_size());
-                let part_uuid = Uuid::new_v4().to_simple().to_string() + ".parquet";
-                let location = format!("{}/{}", path, part_uuid);
-                let buffer = write_in_memory(block)?;
+                let part_uuid = Uuid::new_v4().to_simple().to_string();
+                let location = format!("{}/{}.{}", path, part_uuid, self.engine.extension());
+                let bloom_filters = match &self.bloom_index {
+                    None => None,
+                    Some(bloom_index) => Some(bloom_index.build(&block)?),
+                };
+                let column_statistics = column_statistics(&block);
+                let buffer = self.engine.encode(block)?;
 
-                result.append_part(&location, rows, cols, wire_bytes, buffer.len());
+                result.append_part_with_format(
+                    &location,
+                    Some(self.engine.format()),
+                    rows,
+                    cols,
+                    wire_bytes,
+                    buffer.len(),
+                );
+                let storage = match buffer.len() < self.inline_max_bytes {
+                    true => PartStorageClass::Inline,
+                    false => PartStorageClass::File,
+                };
+                let checksum = match storage {
+                    PartStorageClass::File => Some(part_checksum(&buffer)),
+                    PartStorageClass::Inline => None,
+                };
+                if let Some(part) = result.parts.last_mut() {
+                    part.bloom_filters = bloom_filters;
+                    part.storage = storage;
+                    part.checksum = checksum;
+                    part.column_statistics = Some(column_statistics);
+                }
 
-                self.fs.add(&location, &buffer).await?;
+                match storage {
+                    PartStorageClass::File => {
+                        staged.push((self.fs.stage(&buffer, self.clock.now()).await?, location))
+                    }
+                    PartStorageClass::Inline => inline.push((location, buffer)),
+                }
             }
-            Ok(result)
+            Ok(StagedAppend {
+                result,
+                staged,
+                inline,
+            })
         } else {
             anyhow::bail!("Schema of input data must be provided")
         }
     }
+
+    /// Moves the staged files of the parts into place, the parts are to be registered next,
+    /// along with the returned bytes of the inline parts.
+    pub async fn commit(&self, append: StagedAppend) -> Result<(AppendResult, InlineParts)> {
+        for (staged, location) in append.staged.iter() {
+            self.fs.commit_staged(staged, location).await?;
+        }
+        Ok((append.result, append.inline))
+    }
 }
 
 pub(crate) fn write_in_memory(block: DataBlock) -> Result<Vec<u8>> {
@@ -29,8 +29,10 @@ mod test {
     use common_datablocks::DataBlock;
     use common_datavalues::prelude::*;
     use common_runtime::tokio;
+    use common_store_api_sdk::storage_api_impl::PartStorageClass;
 
     use crate::data_part::appender::*;
+    use crate::fs::FileSystem;
     use crate::localfs::LocalFS;
 
     #[test]
@@ -71,9 +73,9 @@ mod test {
         let schema = batch.schema();
 
         let p = tempfile::tempdir()?;
-        let fs = LocalFS::try_create(p.path().to_str().unwrap().to_string())?;
+        let fs = Arc::new(LocalFS::try_create(p.path().to_str().unwrap().to_string())?);
 
-        let appender = Appender::new(Arc::new(fs));
+        let appender = Appender::new(fs.clone());
 
         let default_ipc_write_opt = IpcWriteOptions::default();
         let flight_schema = flight_data_from_arrow_schema(schema, &default_ipc_write_opt);
@@ -82,10 +84,54 @@ mod test {
             flight_schema,
             flight_data_from_arrow_batch(&batch, &default_ipc_write_opt).1, // ignore dict
         ]);
-        let r = appender
+        let staged = appender
             .append_data("test_tbl".to_string(), Box::pin(req))
-            .await;
-        assert!(r.is_ok());
+            .await?;
+
+        // The file is staged, not in place.
+        let location = staged.result.parts[0].location.clone();
+        assert_eq!(1, fs.list_staged().await?.len());
+        assert!(fs.read_all(&location).await.is_err());
+
+        let (res, inline) = appender.commit(staged).await?;
+        assert!(inline.is_empty());
+        assert!(fs.list_staged().await?.is_empty());
+        let content = fs.read_all(&location).await?;
+
+        let checksum = res.parts[0].checksum.clone().unwrap();
+        assert_eq!(content.len() as u64, checksum.bytes);
+        assert_eq!(content.len(), res.parts[0].disk_bytes);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_append_inline() -> anyhow::Result<()> {
+        // An inline part writes nothing, its bytes are carried to the apply that registers it.
+        let col0: ArrayRef = Arc::new(Int64Array::from_values(vec![0, 1, 2]));
+        let batch = RecordBatch::try_from_iter(vec![("col0", col0)])?;
+        let schema = batch.schema();
+
+        let p = tempfile::tempdir()?;
+        let fs = Arc::new(LocalFS::try_create(p.path().to_str().unwrap().to_string())?);
+
+        let appender = Appender::new(fs.clone()).with_inline_max_bytes(1 << 20);
+
+        let default_ipc_write_opt = IpcWriteOptions::default();
+        let req = futures::stream::iter(vec![
+            flight_data_from_arrow_schema(schema, &default_ipc_write_opt),
+            flight_data_from_arrow_batch(&batch, &default_ipc_write_opt).1,
+        ]);
+        let staged = appender
+            .append_data("test_tbl".to_string(), Box::pin(req))
+            .await?;
+        assert!(fs.list_staged().await?.is_empty());
+
+        let (res, inline) = appender.commit(staged).await?;
+        assert_eq!(PartStorageClass::Inline, res.parts[0].storage);
+        assert_eq!(1, inline.len());
+        assert_eq!(res.parts[0].location, inline[0].0);
+        assert_eq!(res.parts[0].disk_bytes, inline[0].1.len());
+        assert!(fs.read_all(&res.parts[0].location).await.is_err());
         Ok(())
     }
 }
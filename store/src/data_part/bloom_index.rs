@@ -0,0 +1,232 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::get_table_option;
+use common_planners::Expression;
+use common_planners::TableOptions;
+use common_store_api_sdk::storage_api_impl::BloomFilter;
+use common_store_api_sdk::storage_api_impl::PartBloomFilters;
+use common_store_api_sdk::storage_api_impl::PartsPruning;
+use common_store_api_sdk::storage_api_impl::ReadPlanResult;
+
+/// The string columns a bloom filter is built for in each appended part, separated by commas.
+pub(crate) const BLOOM_FILTER_COLUMNS: &str = "bloom_filter_columns";
+
+/// The false positive rate the filters are sized for, between 0 and 1.
+pub(crate) const BLOOM_FILTER_FPP: &str = "bloom_filter_fpp";
+
+const DEFAULT_BLOOM_FILTER_FPP: f64 = 0.01;
+
+/// The bloom filters of a table, by its options.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BloomIndex {
+    pub columns: Vec<String>,
+    pub fpp: f64,
+}
+
+impl BloomIndex {
+    /// The index of the table, `None` if no column is indexed.
+    pub fn from_options(options: &TableOptions) -> Result<Option<BloomIndex>> {
+        let fpp = match get_table_option(options, BLOOM_FILTER_FPP) {
+            None => DEFAULT_BLOOM_FILTER_FPP,
+            Some(value) => match value.trim().parse::<f64>() {
+                Ok(fpp) if fpp > 0.0 && fpp < 1.0 => fpp,
+                _ => {
+                    return Err(ErrorCode::BadOption(format!(
+                        "option {} must be a number between 0 and 1, got {}",
+                        BLOOM_FILTER_FPP, value
+                    )))
+                }
+            },
+        };
+
+        let columns = get_table_option(options, BLOOM_FILTER_COLUMNS)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|column| column.trim().to_string())
+                    .filter(|column| !column.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        match columns.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(BloomIndex { columns, fpp })),
+        }
+    }
+
+    /// Checks the indexed columns against the schema of the table, only the string columns are
+    /// indexed.
+    pub fn validate(&self, schema: &DataSchema) -> Result<()> {
+        for column in &self.columns {
+            let field = schema.field_with_name(column).map_err(|_| {
+                ErrorCode::BadOption(format!(
+                    "option {}: no column {} in the table",
+                    BLOOM_FILTER_COLUMNS, column
+                ))
+            })?;
+            if field.data_type() != &DataType::String {
+                return Err(ErrorCode::BadOption(format!(
+                    "option {}: column {} is {:?}, only the string columns are indexed",
+                    BLOOM_FILTER_COLUMNS,
+                    column,
+                    field.data_type()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The filters of a block appended as a part, of the indexed columns it has.
+    pub fn build(&self, block: &DataBlock) -> Result<PartBloomFilters> {
+        let mut filters = PartBloomFilters::create();
+        for column in &self.columns {
+            let column_data = match block.try_column_by_name(column) {
+                Ok(column_data) if column_data.data_type() == DataType::String => column_data,
+                _ => continue,
+            };
+
+            let array = column_data.to_array()?;
+            let values = array.string()?;
+            let mut filter = BloomFilter::with_fpp(values.len() - values.null_count(), self.fpp);
+            for value in values.into_iter().flatten() {
+                filter.add(value);
+            }
+            filters.columns.insert(column.clone(), filter);
+        }
+        Ok(filters)
+    }
+
+    /// True if the filters of the parts are consulted for `filters`, i.e. if there is a point
+    /// lookup on an indexed column. The filters are kept apart from the parts, they are loaded
+    /// only then.
+    pub fn has_lookups(&self, filters: &[Expression]) -> bool {
+        !self.lookups(filters).is_empty()
+    }
+
+    /// The point lookups of the filters on the indexed columns.
+    fn lookups(&self, filters: &[Expression]) -> Vec<(String, Vec<Vec<u8>>)> {
+        filters
+            .iter()
+            .flat_map(conjuncts)
+            .filter_map(point_lookup)
+            .filter(|(column, _)| self.columns.contains(column))
+            .collect()
+    }
+
+    /// Skips the parts ruled out by the point lookups of the filters on the indexed columns.
+    ///
+    /// A lookup is an equality of a column and a string literal, or a disjunction of such
+    /// equalities on the same column, as an IN list is planned. The filters are consulted only
+    /// if there is a lookup on an indexed column, the parts without filters of the current
+    /// version are kept.
+    pub fn prune(
+        &self,
+        filters: &[Expression],
+        parts: ReadPlanResult,
+    ) -> (ReadPlanResult, PartsPruning) {
+        let mut pruning = PartsPruning::default();
+        let parts = match parts {
+            None => return (None, pruning),
+            Some(parts) => parts,
+        };
+        pruning.total = parts.len();
+
+        let lookups = self.lookups(filters);
+        if lookups.is_empty() {
+            return (Some(parts), pruning);
+        }
+
+        let mut kept = Vec::with_capacity(parts.len());
+        for part in parts {
+            let blooms = match &part.bloom_filters {
+                None => {
+                    kept.push(part);
+                    continue;
+                }
+                Some(blooms) => blooms,
+            };
+
+            let mut checked = false;
+            let mut ruled_out = false;
+            for (column, keys) in &lookups {
+                if let Some(filter) = blooms.column(column) {
+                    checked = true;
+                    ruled_out |= !keys.iter().any(|key| filter.may_contain(key));
+                }
+            }
+
+            if checked {
+                pruning.bloom_checked += 1;
+            }
+            match ruled_out {
+                true => pruning.pruned_by_bloom += 1,
+                false => kept.push(part),
+            }
+        }
+        (Some(kept), pruning)
+    }
+}
+
+/// The conjuncts of a filter, the filters of a scan are all to hold as well.
+pub(crate) fn conjuncts(expr: &Expression) -> Vec<&Expression> {
+    match expr {
+        Expression::BinaryExpression { op, left, right } if op.eq_ignore_ascii_case("and") => {
+            let mut exprs = conjuncts(left);
+            exprs.extend(conjuncts(right));
+            exprs
+        }
+        _ => vec![expr],
+    }
+}
+
+/// The column and the keys of a point lookup, a row matches if its value is one of the keys.
+fn point_lookup(expr: &Expression) -> Option<(String, Vec<Vec<u8>>)> {
+    match expr {
+        Expression::BinaryExpression { op, left, right } if op == "=" => {
+            match (left.as_ref(), right.as_ref()) {
+                (Expression::Column(column), literal) | (literal, Expression::Column(column)) => {
+                    string_literal(literal).map(|key| (column.clone(), vec![key]))
+                }
+                _ => None,
+            }
+        }
+        Expression::BinaryExpression { op, left, right } if op.eq_ignore_ascii_case("or") => {
+            let (column, mut keys) = point_lookup(left)?;
+            let (other, other_keys) = point_lookup(right)?;
+            if column != other {
+                return None;
+            }
+            keys.extend(other_keys);
+            Some((column, keys))
+        }
+        _ => None,
+    }
+}
+
+fn string_literal(expr: &Expression) -> Option<Vec<u8>> {
+    match expr {
+        Expression::Literal {
+            value: DataValue::String(Some(value)),
+            ..
+        } => Some(value.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::col;
+use common_planners::lit;
+use common_planners::Part;
+use common_planners::Statistics;
+use common_store_api_sdk::storage_api_impl::BloomFilter;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
+use common_store_api_sdk::storage_api_impl::PartsPruning;
+use pretty_assertions::assert_eq;
+
+use crate::data_part::bloom_index::BloomIndex;
+
+#[test]
+fn test_bloom_filter_fpp() -> Result<()> {
+    for fpp in [0.1, 0.01, 0.001] {
+        let mut filter = BloomFilter::with_fpp(10000, fpp);
+        for i in 0..10000 {
+            filter.add(format!("key-{}", i).as_bytes());
+        }
+
+        // Every key added is found, about `fpp` of the other keys are.
+        assert!((0..10000).all(|i| filter.may_contain(format!("key-{}", i).as_bytes())));
+        let found = (10000..110000)
+            .filter(|i| filter.may_contain(format!("key-{}", i).as_bytes()))
+            .count();
+        let rate = found as f64 / 100000.0;
+        assert!(rate < fpp * 1.5, "fpp {}: {}", fpp, rate);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_bloom_index_prune() -> Result<()> {
+    let index = BloomIndex::from_options(
+        &[("bloom_filter_columns".to_string(), "id".to_string())]
+            .into_iter()
+            .collect(),
+    )?
+    .unwrap();
+    assert_eq!(vec!["id".to_string()], index.columns);
+    assert_eq!(0.01, index.fpp);
+
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("id", DataType::String, true),
+        DataField::new("n", DataType::Int64, false),
+    ]);
+    let parts = (0..4)
+        .map(|i| {
+            let id = format!("a{}", i);
+            let block = DataBlock::create_by_array(schema.clone(), vec![
+                Series::new(vec![Some(id.as_str()), None]),
+                Series::new(vec![i as i64, 0]),
+            ]);
+            Ok(DataPartInfo {
+                part: Part {
+                    name: format!("p{}", i),
+                    version: 0,
+                    read_info: None,
+                },
+                stats: Statistics::new_exact(2, 0),
+                format: None,
+                bloom_filters: Some(index.build(&block)?),
+                storage: PartStorageClass::File,
+                checksum: None,
+                column_statistics: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let names = |parts: Option<Vec<DataPartInfo>>| {
+        parts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|part| part.part.name)
+            .collect::<Vec<_>>()
+    };
+
+    // An equality, and an IN list.
+    let (kept, pruning) = index.prune(&[col("id").eq(lit("a2".as_bytes()))], Some(parts.clone()));
+    assert_eq!(vec!["p2"], names(kept));
+    assert_eq!(
+        PartsPruning {
+            total: 4,
+            bloom_checked: 4,
+            pruned_by_bloom: 3,
+            ..Default::default()
+        },
+        pruning
+    );
+    let in_list = lit("a0".as_bytes())
+        .eq(col("id"))
+        .or(col("id").eq(lit("a3".as_bytes())));
+    let (kept, _) = index.prune(&[in_list], Some(parts.clone()));
+    assert_eq!(vec!["p0", "p3"], names(kept));
+
+    // A conjunct of a lookup is enough to skip a part.
+    let filter = col("n")
+        .gt(lit(0i64))
+        .and(col("id").eq(lit("a1".as_bytes())));
+    let (kept, _) = index.prune(&[filter], Some(parts.clone()));
+    assert_eq!(vec!["p1"], names(kept));
+
+    // A disjunction of two columns, or an equality not on a string, is not a lookup.
+    for filter in [
+        col("id")
+            .eq(lit("a1".as_bytes()))
+            .or(col("n").eq(lit(1i64))),
+        col("n").eq(lit(1i64)),
+    ] {
+        let (kept, pruning) = index.prune(&[filter], Some(parts.clone()));
+        assert_eq!(4, names(kept).len());
+        assert_eq!(0, pruning.bloom_checked);
+    }
+
+    // The parts without filters, or with the filters of another version, are kept.
+    let mut old_parts = parts.clone();
+    old_parts[0].bloom_filters = None;
+    if let Some(filters) = old_parts[1].bloom_filters.as_mut() {
+        filters.version += 1;
+    }
+    let (kept, pruning) = index.prune(&[col("id").eq(lit("a3".as_bytes()))], Some(old_parts));
+    assert_eq!(vec!["p0", "p1", "p3"], names(kept));
+    assert_eq!(2, pruning.bloom_checked);
+    assert_eq!(1, pruning.pruned_by_bloom);
+
+    Ok(())
+}
+
+#[test]
+fn test_bloom_index_options() -> Result<()> {
+    let options = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+
+    assert_eq!(None, BloomIndex::from_options(&options(&[]))?);
+    assert_eq!(
+        None,
+        BloomIndex::from_options(&options(&[("bloom_filter_columns", " , ")]))?
+    );
+
+    let index = BloomIndex::from_options(&options(&[
+        ("BLOOM_FILTER_COLUMNS", "a, b"),
+        ("bloom_filter_fpp", "0.05"),
+    ]))?
+    .unwrap();
+    assert_eq!(vec!["a".to_string(), "b".to_string()], index.columns);
+    assert_eq!(0.05, index.fpp);
+
+    for fpp in ["0", "1", "-0.1", "x"] {
+        let res = BloomIndex::from_options(&options(&[
+            ("bloom_filter_columns", "a"),
+            ("bloom_filter_fpp", fpp),
+        ]));
+        assert!(res.is_err(), "{}", fpp);
+    }
+
+    let schema = DataSchema::new(vec![
+        DataField::new("a", DataType::String, false),
+        DataField::new("b", DataType::Int32, false),
+    ]);
+    let res = index.validate(&schema);
+    assert_eq!(
+        "option bloom_filter_columns: column b is Int32, only the string columns are indexed",
+        res.unwrap_err().message()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+use std::cmp::Ordering;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_planners::Expression;
+use common_store_api_sdk::storage_api_impl::ColumnBound;
+use common_store_api_sdk::storage_api_impl::ColumnStatistics;
+use common_store_api_sdk::storage_api_impl::PartColumnStatistics;
+use common_store_api_sdk::storage_api_impl::PartsPruning;
+use common_store_api_sdk::storage_api_impl::ReadPlanResult;
+
+use crate::data_part::bloom_index::conjuncts;
+
+/// The statistics of the numeric and the string columns of a block appended as a part.
+pub(crate) fn column_statistics(block: &DataBlock) -> PartColumnStatistics {
+    let mut stats = PartColumnStatistics::new();
+    for (field, column) in block.schema().fields().iter().zip(block.columns()) {
+        let column_stats = match field.data_type() {
+            DataType::Int8 => primitive_stats::<i8>(column, |v| ColumnBound::Int(v as i64)),
+            DataType::Int16 => primitive_stats::<i16>(column, |v| ColumnBound::Int(v as i64)),
+            DataType::Int32 => primitive_stats::<i32>(column, |v| ColumnBound::Int(v as i64)),
+            DataType::Int64 => primitive_stats::<i64>(column, ColumnBound::Int),
+            DataType::UInt8 => primitive_stats::<u8>(column, |v| ColumnBound::UInt(v as u64)),
+            DataType::UInt16 => primitive_stats::<u16>(column, |v| ColumnBound::UInt(v as u64)),
+            DataType::UInt32 => primitive_stats::<u32>(column, |v| ColumnBound::UInt(v as u64)),
+            DataType::UInt64 => primitive_stats::<u64>(column, ColumnBound::UInt),
+            DataType::Float32 => primitive_stats::<f32>(column, |v| ColumnBound::Float(v as f64)),
+            DataType::Float64 => primitive_stats::<f64>(column, ColumnBound::Float),
+            DataType::String => string_stats(column),
+            _ => None,
+        };
+        if let Some(column_stats) = column_stats {
+            stats.insert(field.name().clone(), column_stats);
+        }
+    }
+    stats
+}
+
+/// `None` for a constant column, or a float column with a NaN, which has no order.
+fn primitive_stats<T: DFPrimitiveType>(
+    column: &DataColumn,
+    bound: impl Fn(T) -> ColumnBound,
+) -> Option<ColumnStatistics> {
+    let (values, validity) = column.as_primitive_slice::<T>()?;
+    let mut stats = ColumnStatistics::default();
+    for (i, value) in values.iter().enumerate() {
+        if let Some(validity) = validity {
+            if !validity.get_bit(i) {
+                stats.null_count += 1;
+                continue;
+            }
+        }
+        let value = bound(*value);
+        if let ColumnBound::Float(v) = value {
+            if v.is_nan() {
+                return None;
+            }
+        }
+        stats.add(value);
+    }
+    Some(stats)
+}
+
+fn string_stats(column: &DataColumn) -> Option<ColumnStatistics> {
+    let mut stats = ColumnStatistics::default();
+    for (value, is_null) in column.string_iter()? {
+        match is_null {
+            true => stats.null_count += 1,
+            false => stats.add(ColumnBound::String(value.to_vec())),
+        }
+    }
+    Some(stats)
+}
+
+/// The comparisons of a column with a literal among the conjuncts of the filters of a scan.
+pub(crate) struct RangePredicates {
+    predicates: Vec<RangePredicate>,
+}
+
+struct RangePredicate {
+    column: String,
+    /// One of `=`, `<`, `<=`, `>` and `>=`, with the column on the left.
+    op: &'static str,
+    literal: ColumnBound,
+}
+
+impl RangePredicates {
+    pub fn from_filters(filters: &[Expression]) -> RangePredicates {
+        let predicates = filters
+            .iter()
+            .flat_map(conjuncts)
+            .filter_map(range_predicate)
+            .collect();
+        RangePredicates { predicates }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+
+    /// False if no row of a part with the statistics can match every predicate.
+    /// A predicate on a column without statistics is taken as matched.
+    pub fn may_match(&self, stats: &PartColumnStatistics) -> bool {
+        self.predicates.iter().all(|predicate| {
+            let column_stats = match stats.get(&predicate.column) {
+                None => return true,
+                Some(column_stats) => column_stats,
+            };
+            // A column of only nulls matches no comparison.
+            let (min, max) = match (&column_stats.min, &column_stats.max) {
+                (Some(min), Some(max)) => (min, max),
+                _ => return false,
+            };
+            let literal = &predicate.literal;
+            let holds = |bound: &ColumnBound, accept: &[Ordering]| match bound.compare(literal) {
+                None => true,
+                Some(ord) => accept.contains(&ord),
+            };
+            match predicate.op {
+                "=" => {
+                    holds(min, &[Ordering::Less, Ordering::Equal])
+                        && holds(max, &[Ordering::Greater, Ordering::Equal])
+                }
+                "<" => holds(min, &[Ordering::Less]),
+                "<=" => holds(min, &[Ordering::Less, Ordering::Equal]),
+                ">" => holds(max, &[Ordering::Greater]),
+                ">=" => holds(max, &[Ordering::Greater, Ordering::Equal]),
+                _ => true,
+            }
+        })
+    }
+
+    /// Skips the parts whose column ranges rule out the predicates, the parts appended before
+    /// the statistics are recorded are kept.
+    pub fn prune(&self, parts: ReadPlanResult, pruning: &mut PartsPruning) -> ReadPlanResult {
+        let parts = parts?;
+        if self.is_empty() {
+            return Some(parts);
+        }
+
+        let mut kept = Vec::with_capacity(parts.len());
+        for part in parts {
+            let stats = match &part.column_statistics {
+                None => {
+                    kept.push(part);
+                    continue;
+                }
+                Some(stats) => stats,
+            };
+
+            pruning.range_checked += 1;
+            match self.may_match(stats) {
+                true => kept.push(part),
+                false => pruning.pruned_by_range += 1,
+            }
+        }
+        Some(kept)
+    }
+}
+
+fn range_predicate(expr: &Expression) -> Option<RangePredicate> {
+    let (op, left, right) = match expr {
+        Expression::BinaryExpression { op, left, right } => (op.as_str(), left, right),
+        _ => return None,
+    };
+    let (column, literal, op) = match (left.as_ref(), right.as_ref()) {
+        (Expression::Column(column), Expression::Literal { value, .. }) => (column, value, op),
+        // `1 < a` is `a > 1`.
+        (Expression::Literal { value, .. }, Expression::Column(column)) => {
+            let flipped = match op {
+                "<" => ">",
+                "<=" => ">=",
+                ">" => "<",
+                ">=" => "<=",
+                op => op,
+            };
+            (column, value, flipped)
+        }
+        _ => return None,
+    };
+    let op = match op {
+        "=" => "=",
+        "<" => "<",
+        "<=" => "<=",
+        ">" => ">",
+        ">=" => ">=",
+        _ => return None,
+    };
+    Some(RangePredicate {
+        column: column.clone(),
+        op,
+        literal: literal_bound(literal)?,
+    })
+}
+
+fn literal_bound(value: &DataValue) -> Option<ColumnBound> {
+    match value {
+        DataValue::Int8(Some(v)) => Some(ColumnBound::Int(*v as i64)),
+        DataValue::Int16(Some(v)) => Some(ColumnBound::Int(*v as i64)),
+        DataValue::Int32(Some(v)) => Some(ColumnBound::Int(*v as i64)),
+        DataValue::Int64(Some(v)) => Some(ColumnBound::Int(*v)),
+        DataValue::UInt8(Some(v)) => Some(ColumnBound::UInt(*v as u64)),
+        DataValue::UInt16(Some(v)) => Some(ColumnBound::UInt(*v as u64)),
+        DataValue::UInt32(Some(v)) => Some(ColumnBound::UInt(*v as u64)),
+        DataValue::UInt64(Some(v)) => Some(ColumnBound::UInt(*v)),
+        DataValue::Float32(Some(v)) if !v.is_nan() => Some(ColumnBound::Float(*v as f64)),
+        DataValue::Float64(Some(v)) if !v.is_nan() => Some(ColumnBound::Float(*v)),
+        DataValue::String(Some(v)) => Some(ColumnBound::String(v.clone())),
+        _ => None,
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_planners::col;
+use common_planners::lit;
+use common_planners::Expression;
+use common_planners::Part;
+use common_planners::Statistics;
+use common_store_api_sdk::storage_api_impl::ColumnBound;
+use common_store_api_sdk::storage_api_impl::ColumnStatistics;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
+use common_store_api_sdk::storage_api_impl::PartsPruning;
+use pretty_assertions::assert_eq;
+
+use crate::data_part::column_stats::column_statistics;
+use crate::data_part::column_stats::RangePredicates;
+
+#[test]
+fn test_column_statistics() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("i", DataType::Int32, true),
+        DataField::new("u", DataType::UInt64, false),
+        DataField::new("f", DataType::Float64, false),
+        DataField::new("s", DataType::String, true),
+        DataField::new("nan", DataType::Float64, false),
+        DataField::new("b", DataType::Boolean, false),
+    ]);
+    let block = DataBlock::create_by_array(schema, vec![
+        Series::new(vec![Some(3i32), None, Some(-7)]),
+        Series::new(vec![5u64, 1, 9]),
+        Series::new(vec![0.5f64, -1.5, 2.0]),
+        Series::new(vec![Some("b"), Some("a"), None]),
+        Series::new(vec![0.0f64, f64::NAN, 1.0]),
+        Series::new(vec![true, false, true]),
+    ]);
+
+    let stats = column_statistics(&block);
+    let range = |min, max, null_count| ColumnStatistics {
+        min: Some(min),
+        max: Some(max),
+        null_count,
+    };
+    assert_eq!(
+        Some(&range(ColumnBound::Int(-7), ColumnBound::Int(3), 1)),
+        stats.get("i")
+    );
+    assert_eq!(
+        Some(&range(ColumnBound::UInt(1), ColumnBound::UInt(9), 0)),
+        stats.get("u")
+    );
+    assert_eq!(
+        Some(&range(ColumnBound::Float(-1.5), ColumnBound::Float(2.0), 0)),
+        stats.get("f")
+    );
+    assert_eq!(
+        Some(&range(
+            ColumnBound::String(b"a".to_vec()),
+            ColumnBound::String(b"b".to_vec()),
+            1
+        )),
+        stats.get("s")
+    );
+    // A NaN has no order, and a boolean has no statistics.
+    assert_eq!(None, stats.get("nan"));
+    assert_eq!(None, stats.get("b"));
+
+    Ok(())
+}
+
+#[test]
+fn test_range_predicates_prune() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("n", DataType::Int64, true),
+        DataField::new("s", DataType::String, false),
+    ]);
+    // p0: n in [0, 9], p1: n in [100, 109], p2: n is all null.
+    let blocks = vec![
+        DataBlock::create_by_array(schema.clone(), vec![
+            Series::new(vec![Some(0i64), Some(9)]),
+            Series::new(vec!["a", "c"]),
+        ]),
+        DataBlock::create_by_array(schema.clone(), vec![
+            Series::new(vec![Some(100i64), Some(109)]),
+            Series::new(vec!["d", "f"]),
+        ]),
+        DataBlock::create_by_array(schema, vec![
+            Series::new(vec![None::<i64>, None]),
+            Series::new(vec!["x", "z"]),
+        ]),
+    ];
+    let parts = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| DataPartInfo {
+            part: Part {
+                name: format!("p{}", i),
+                version: 0,
+                read_info: None,
+            },
+            stats: Statistics::new_exact(2, 0),
+            format: None,
+            bloom_filters: None,
+            storage: PartStorageClass::File,
+            checksum: None,
+            column_statistics: Some(column_statistics(block)),
+        })
+        .collect::<Vec<_>>();
+
+    let kept = |filters: &[Expression]| {
+        let mut pruning = PartsPruning::default();
+        let kept = RangePredicates::from_filters(filters)
+            .prune(Some(parts.clone()), &mut pruning)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|part| part.part.name)
+            .collect::<Vec<_>>();
+        (kept, pruning)
+    };
+
+    let (names, pruning) = kept(&[col("n").gt(lit(100i64))]);
+    assert_eq!(vec!["p1"], names);
+    assert_eq!(3, pruning.range_checked);
+    assert_eq!(2, pruning.pruned_by_range);
+
+    // The literal may be of another integer type, or on the left.
+    assert_eq!(vec!["p0"], kept(&[col("n").lt_eq(lit(9u8))]).0);
+    assert_eq!(vec!["p0"], kept(&[lit(10i32).gt(col("n"))]).0);
+    assert_eq!(vec!["p1"], kept(&[col("n").eq(lit(105i64))]).0);
+    assert_eq!(vec!["p1"], kept(&[col("n").gt(lit(9.5f64))]).0);
+    assert_eq!(vec!["p2"], kept(&[col("s").gt_eq(lit("x".as_bytes()))]).0);
+
+    // Every conjunct must hold.
+    let filter = col("n")
+        .gt_eq(lit(0i64))
+        .and(col("s").eq(lit("e".as_bytes())));
+    assert_eq!(vec!["p1"], kept(&[filter]).0);
+
+    // A disjunction, a comparison of two columns or an unknown column prune nothing.
+    for filter in [
+        col("n").gt(lit(100i64)).or(col("n").lt(lit(0i64))),
+        col("n").gt(col("s")),
+        col("m").gt(lit(100i64)),
+    ] {
+        let (names, pruning) = kept(&[filter]);
+        assert_eq!(3, names.len());
+        assert_eq!(0, pruning.pruned_by_range);
+    }
+
+    // The parts without statistics are kept.
+    let mut old_parts = parts.clone();
+    old_parts[0].column_statistics = None;
+    let mut pruning = PartsPruning::default();
+    let kept = RangePredicates::from_filters(&[col("n").gt(lit(100i64))])
+        .prune(Some(old_parts), &mut pruning)
+        .unwrap_or_default();
+    assert_eq!(2, kept.len());
+    assert_eq!(2, pruning.range_checked);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common_exception::ErrorCode;
+use metasrv::meta_service::MetaNode;
+
+use crate::fs::FileSystem;
+use crate::fs::ListResult;
+
+/// Keeps the parts too small to be worth a file in the meta store, by part name.
+///
+/// The bytes of a part are dropped by the meta store along with the part, i.e., when its table
+/// is truncated or vacuumed from trash, or when the part is compacted into a file.
+pub(crate) struct InlineStore {
+    meta_node: Arc<MetaNode>,
+}
+
+impl InlineStore {
+    pub fn create(meta_node: Arc<MetaNode>) -> InlineStore {
+        InlineStore { meta_node }
+    }
+}
+
+#[async_trait]
+impl FileSystem for InlineStore {
+    /// The bytes are written by the apply that registers the part, see
+    /// `Mutation::AppendDataParts`, a put of its own would be left behind by a failed append.
+    async fn add(&self, path: &str, _data: &[u8]) -> common_exception::Result<()> {
+        Err(ErrorCode::LogicalError(format!(
+            "inline part is written along with its registration: {}",
+            path
+        )))
+    }
+
+    async fn read_all(&self, path: &str) -> common_exception::Result<Vec<u8>> {
+        self.meta_node
+            .get_inline_part(path)
+            .await?
+            .ok_or_else(|| ErrorCode::FileMetaNotFound(format!("inline part not found: {}", path)))
+    }
+
+    async fn list(&self, prefix: &str) -> common_exception::Result<ListResult> {
+        Ok(ListResult {
+            dirs: vec![],
+            files: self.meta_node.list_inline_parts(prefix).await?,
+        })
+    }
+
+    /// The bytes are removed by the meta store along with the part.
+    async fn remove(&self, _path: &str) -> common_exception::Result<()> {
+        Ok(())
+    }
+}
@@ -14,6 +14,22 @@
 //
 
 pub(crate) mod appender;
+pub(crate) mod bloom_index;
+pub(crate) mod column_stats;
+pub(crate) mod inline_store;
+pub(crate) mod ndjson_engine;
+pub(crate) mod parquet_engine;
+pub(crate) mod part_checksum;
+pub(crate) mod schema_evolution;
+pub(crate) mod table_engine;
 
 #[cfg(test)]
 mod appender_test;
+#[cfg(test)]
+mod bloom_index_test;
+#[cfg(test)]
+mod column_stats_test;
+#[cfg(test)]
+mod schema_evolution_test;
+#[cfg(test)]
+mod table_engine_test;
@@ -0,0 +1,136 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use common_datablocks::DataBlock;
+use common_datavalues::is_numeric;
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataType;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::data_part::table_engine::BlockIterator;
+use crate::data_part::table_engine::PartStatistics;
+use crate::data_part::table_engine::TableEngine;
+
+/// Writes a block as rows of JSON objects, one per line, keyed by the column names.
+///
+/// Numbers and booleans are JSON values, the others are their text, a null is a JSON null.
+/// The values are converted to the current types of the table when they are read.
+pub(crate) struct NdJsonEngine {}
+
+impl TableEngine for NdJsonEngine {
+    fn format(&self) -> &'static str {
+        "ndjson"
+    }
+
+    fn extension(&self) -> &'static str {
+        "ndjson"
+    }
+
+    fn statistics(&self) -> PartStatistics {
+        PartStatistics::Rows
+    }
+
+    fn encode(&self, block: DataBlock) -> Result<Vec<u8>> {
+        let fields = block.schema().fields().clone();
+        let mut rows = vec![Map::new(); block.num_rows()];
+
+        for (field, column) in fields.iter().zip(block.columns()) {
+            let texts = field
+                .data_type()
+                .create_serializer(0)?
+                .serialize_strings(column)?;
+            let series = column.to_array()?;
+            let typed = is_numeric(field.data_type()) || field.data_type() == &DataType::Boolean;
+
+            for (i, (row, text)) in rows.iter_mut().zip(texts).enumerate() {
+                let value = if series.is_null(i) {
+                    Value::Null
+                } else if typed {
+                    // NaN and infinity are not JSON numbers, they are kept as text.
+                    match serde_json::from_str::<Value>(&text) {
+                        Ok(v) if v.is_number() || v.is_boolean() => v,
+                        _ => Value::String(text),
+                    }
+                } else {
+                    Value::String(text)
+                };
+                row.insert(field.name().clone(), value);
+            }
+        }
+
+        let mut content = vec![];
+        for row in rows {
+            serde_json::to_writer(&mut content, &Value::Object(row))?;
+            content.push(b'\n');
+        }
+        Ok(content)
+    }
+
+    fn decode(&self, content: Vec<u8>, schema: DataSchemaRef) -> Result<BlockIterator> {
+        let content = String::from_utf8(content)?;
+        let lines = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+
+        let mut desers = schema
+            .fields()
+            .iter()
+            .map(|f| f.data_type().create_serializer(lines.len()))
+            .collect::<Result<Vec<_>>>()?;
+
+        for (n, line) in lines {
+            let bad_row = |reason: String| {
+                ErrorCode::BadBytes(format!(
+                    "bad row of an ndjson part at line {}: {}",
+                    n + 1,
+                    reason
+                ))
+            };
+
+            let value: Value = serde_json::from_str(line).map_err(|e| bad_row(e.to_string()))?;
+            let obj = value
+                .as_object()
+                .ok_or_else(|| bad_row("not a JSON object".to_string()))?;
+
+            // A missing field is a null, the same as the NDJSON files of the external tables.
+            for (deser, field) in desers.iter_mut().zip(schema.fields().iter()) {
+                match obj.get(field.name()) {
+                    None | Some(Value::Null) => deser.de_null(),
+                    Some(Value::String(s)) => deser.de_text(s.as_bytes())?,
+                    Some(v) => deser.de_text(v.to_string().as_bytes())?,
+                }
+            }
+        }
+
+        let series = desers
+            .iter_mut()
+            .map(|deser| deser.finish_to_series())
+            .collect::<Vec<_>>();
+        let block = DataBlock::create_by_array(schema, series);
+        Ok(Box::new(std::iter::once(Ok(block))))
+    }
+
+    fn footer_len(&self) -> Option<usize> {
+        None
+    }
+}
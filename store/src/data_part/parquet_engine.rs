@@ -0,0 +1,134 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
+use common_arrow::arrow::io::parquet::read;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_datablocks::DataBlock;
+use common_datavalues::DataSchema;
+use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+use crate::data_part::appender::write_in_memory;
+use crate::data_part::schema_evolution::cast_to_schema;
+use crate::data_part::table_engine::BlockIterator;
+use crate::data_part::table_engine::PartStatistics;
+use crate::data_part::table_engine::TableEngine;
+
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+const PARQUET_FOOTER_LEN: usize = 8;
+
+/// Writes a block into a parquet file, with the statistics of its columns.
+pub(crate) struct ParquetEngine {}
+
+impl TableEngine for ParquetEngine {
+    fn format(&self) -> &'static str {
+        "parquet"
+    }
+
+    fn extension(&self) -> &'static str {
+        "parquet"
+    }
+
+    fn statistics(&self) -> PartStatistics {
+        PartStatistics::Columns
+    }
+
+    fn encode(&self, block: DataBlock) -> Result<Vec<u8>> {
+        write_in_memory(block).map_err(ErrorCode::from)
+    }
+
+    fn decode(&self, content: Vec<u8>, schema: DataSchemaRef) -> Result<BlockIterator> {
+        // The metadata is parsed once, the columns are found in it by name, the schema may be
+        // a projection of the table.
+        let mut reader = Cursor::new(content);
+        let metadata =
+            read::read_metadata(&mut reader).map_err(|e| ErrorCode::ParquetError(e.to_string()))?;
+        let part_schema = read::get_schema(&metadata)?;
+        let projection = schema
+            .fields()
+            .iter()
+            .map(|f| part_schema.index_of(f.name()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let projected_schema = Arc::new(ArrowSchema::new(
+            projection
+                .iter()
+                .map(|i| part_schema.field(*i).clone())
+                .collect(),
+        ));
+        let arrow_schema = Arc::new(schema.to_arrow());
+
+        // For simplicity, we do the conversion in-memory, to be optimized later
+        // TODO consider using `parquet_table` and `stream_parquet`
+        let mut blocks = Vec::with_capacity(metadata.row_groups.len());
+        for row_group in &metadata.row_groups {
+            let mut columns = Vec::with_capacity(projection.len());
+            for (i, field) in projection.iter().zip(projected_schema.fields()) {
+                let column_meta = &row_group.columns()[*i];
+                let pages = read::get_page_iterator(column_meta, &mut reader, None, vec![])?;
+                let mut pages = read::Decompressor::new(pages, vec![]);
+                let array =
+                    read::page_iter_to_array(&mut pages, column_meta, field.data_type().clone())?;
+                columns.push(Arc::from(array));
+            }
+            let batch = RecordBatch::try_new(projected_schema.clone(), columns)?;
+            // The part may be written before its columns are widened.
+            let batch = cast_to_schema(batch, &arrow_schema)?;
+            blocks.push(DataBlock::try_from(batch));
+        }
+        Ok(Box::new(blocks.into_iter()))
+    }
+
+    /// The length of the metadata, then the magic bytes.
+    fn footer_len(&self) -> Option<usize> {
+        Some(PARQUET_FOOTER_LEN)
+    }
+
+    fn schema_len(&self, footer: &[u8]) -> Result<usize> {
+        if footer.len() != PARQUET_FOOTER_LEN || &footer[4..] != PARQUET_MAGIC {
+            return Err(ErrorCode::ParquetError("invalid parquet footer"));
+        }
+        let metadata_len = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]);
+        Ok(metadata_len as usize + PARQUET_FOOTER_LEN)
+    }
+
+    /// The schema is in the metadata, the row groups before it are not needed.
+    fn part_schema(&self, tail: Vec<u8>) -> Result<DataSchema> {
+        let metadata = read::read_metadata(&mut Cursor::new(tail))
+            .map_err(|e| ErrorCode::ParquetError(e.to_string()))?;
+        let schema = read::get_schema(&metadata)?;
+        Ok(DataSchema::from(&schema))
+    }
+
+    /// The compressed sizes of the chunks of a column in every row group, as in the metadata.
+    fn column_bytes(&self, tail: Vec<u8>) -> Result<Option<HashMap<String, u64>>> {
+        let metadata = read::read_metadata(&mut Cursor::new(tail))
+            .map_err(|e| ErrorCode::ParquetError(e.to_string()))?;
+        let schema = read::get_schema(&metadata)?;
+        let mut bytes = HashMap::new();
+        for row_group in &metadata.row_groups {
+            for (field, column) in schema.fields().iter().zip(row_group.columns()) {
+                *bytes.entry(field.name().clone()).or_default() += column.compressed_size() as u64;
+            }
+        }
+        Ok(Some(bytes))
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_store_api_sdk::storage_api_impl::PartChecksum;
+use sha2::Digest;
+use sha2::Sha256;
+
+pub(crate) fn part_checksum(content: &[u8]) -> PartChecksum {
+    PartChecksum {
+        bytes: content.len() as u64,
+        sha256: format!("{:x}", Sha256::digest(content)),
+    }
+}
+
+/// Checks the bytes read of a part against the checksum recorded when it is written:
+/// always the size, the digest only if `verify_digest`.
+pub(crate) fn verify_part(
+    name: &str,
+    content: &[u8],
+    checksum: Option<&PartChecksum>,
+    verify_digest: bool,
+) -> Result<()> {
+    let checksum = match checksum {
+        None => return Ok(()),
+        Some(checksum) => checksum,
+    };
+
+    if content.len() as u64 != checksum.bytes {
+        return Err(ErrorCode::FileDamaged(format!(
+            "part {} is damaged: {} bytes read, {} bytes written",
+            name,
+            content.len(),
+            checksum.bytes
+        )));
+    }
+
+    if verify_digest {
+        let sha256 = format!("{:x}", Sha256::digest(content));
+        if sha256 != checksum.sha256 {
+            return Err(ErrorCode::FileDamaged(format!(
+                "part {} is damaged: sha256 {} read, {} written",
+                name, sha256, checksum.sha256
+            )));
+        }
+    }
+    Ok(())
+}
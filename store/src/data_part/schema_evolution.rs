@@ -0,0 +1,115 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+
+use common_arrow::arrow::compute::cast;
+use common_arrow::arrow::datatypes::SchemaRef as ArrowSchemaRef;
+use common_arrow::arrow::error::Result as ArrowResult;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_datavalues::DataField;
+use common_datavalues::DataSchema;
+use common_datavalues::DataType;
+
+/// Whether every value of type `from` is kept as is when it is cast to `to`.
+///
+/// Only these conversions are allowed when modifying a column, so that the existing parts
+/// keep their physical type and are cast to the new type when they are read.
+pub(crate) fn is_safe_widening(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+
+    if from == to {
+        return true;
+    }
+
+    matches!(
+        (from, to),
+        (Int8, Int16 | Int32 | Int64 | Float32 | Float64)
+            | (Int16, Int32 | Int64 | Float32 | Float64)
+            | (Int32, Int64 | Float64)
+            | (
+                UInt8,
+                UInt16 | UInt32 | UInt64 | Int16 | Int32 | Int64 | Float32 | Float64
+            )
+            | (UInt16, UInt32 | UInt64 | Int32 | Int64 | Float32 | Float64)
+            | (UInt32, UInt64 | Int64 | Float64)
+            | (Float32, Float64)
+    )
+}
+
+/// Checks the new definition of a column against the current one.
+///
+/// Returns the reason why the column can not be modified, if it can not.
+pub(crate) fn check_modify_column(current: &DataField, column: &DataField) -> Option<String> {
+    if current.is_nullable() && !column.is_nullable() {
+        return Some("a nullable column can not be made NOT NULL".to_string());
+    }
+
+    if !is_safe_widening(current.data_type(), column.data_type()) {
+        return Some(format!(
+            "{} can not be safely converted to {}",
+            current.data_type(),
+            column.data_type()
+        ));
+    }
+    None
+}
+
+/// Checks whether the column of a part can be read as the new type.
+///
+/// The column is found by name, as a part is read. Returns the reason why the part blocks the
+/// modification, if it does.
+pub(crate) fn check_part_column(part_schema: &DataSchema, column: &DataField) -> Option<String> {
+    let physical = match part_schema.column_with_name(column.name()) {
+        None => return Some(format!("column `{}` is missing", column.name())),
+        Some((_, field)) => field.data_type(),
+    };
+    if !is_safe_widening(physical, column.data_type()) {
+        return Some(format!("stored as {}", physical));
+    }
+    None
+}
+
+/// Casts the columns of a batch read from a part to the current types of the table.
+///
+/// A part keeps the types it was written with, the columns of which may have been widened since.
+pub(crate) fn cast_to_schema(
+    batch: RecordBatch,
+    schema: &ArrowSchemaRef,
+) -> ArrowResult<RecordBatch> {
+    let unchanged = batch
+        .columns()
+        .iter()
+        .zip(schema.fields().iter())
+        .all(|(c, f)| c.data_type() == f.data_type());
+    if unchanged {
+        return Ok(batch);
+    }
+
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields().iter())
+        .map(|(c, f)| {
+            if c.data_type() == f.data_type() {
+                Ok(c.clone())
+            } else {
+                cast::cast(c.as_ref(), f.data_type()).map(Arc::from)
+            }
+        })
+        .collect::<ArrowResult<Vec<_>>>()?;
+
+    RecordBatch::try_new(schema.clone(), columns)
+}
@@ -0,0 +1,153 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+
+use common_arrow::arrow::array::ArrayRef;
+use common_arrow::arrow::array::Int32Array;
+use common_arrow::arrow::array::Int64Array;
+use common_arrow::arrow::datatypes::DataType as ArrowDataType;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_datavalues::prelude::*;
+use pretty_assertions::assert_eq;
+
+use crate::data_part::schema_evolution::*;
+
+#[test]
+fn test_is_safe_widening() -> anyhow::Result<()> {
+    let allowed = vec![
+        (DataType::Int32, DataType::Int32),
+        (DataType::Int8, DataType::Int16),
+        (DataType::Int32, DataType::Int64),
+        (DataType::UInt16, DataType::Int32),
+        (DataType::UInt32, DataType::UInt64),
+        (DataType::Int32, DataType::Float64),
+        (DataType::Float32, DataType::Float64),
+    ];
+    for (from, to) in allowed {
+        assert!(is_safe_widening(&from, &to), "{} -> {}", from, to);
+    }
+
+    let rejected = vec![
+        (DataType::Int64, DataType::Int32),
+        (DataType::Int32, DataType::UInt64),
+        (DataType::UInt64, DataType::Int64),
+        (DataType::Int64, DataType::Float64),
+        (DataType::Float64, DataType::Float32),
+        (DataType::Int32, DataType::String),
+        (DataType::String, DataType::Int64),
+    ];
+    for (from, to) in rejected {
+        assert!(!is_safe_widening(&from, &to), "{} -> {}", from, to);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_check_modify_column() -> anyhow::Result<()> {
+    let a = DataField::new("a", DataType::Int32, false);
+    let b = DataField::new("b", DataType::Int64, true);
+
+    // widen
+    assert_eq!(
+        None,
+        check_modify_column(&a, &DataField::new("a", DataType::Int64, false))
+    );
+    // make nullable
+    assert_eq!(
+        None,
+        check_modify_column(&a, &DataField::new("a", DataType::Int32, true))
+    );
+    // narrow
+    assert_eq!(
+        Some("Int64 can not be safely converted to Int32".to_string()),
+        check_modify_column(&b, &DataField::new("b", DataType::Int32, true))
+    );
+    // make not null
+    assert_eq!(
+        Some("a nullable column can not be made NOT NULL".to_string()),
+        check_modify_column(&b, &DataField::new("b", DataType::Int64, false))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_check_part_column() -> anyhow::Result<()> {
+    let part_schema = DataSchema::new(vec![
+        DataField::new("a", DataType::Int32, false),
+        DataField::new("b", DataType::Int64, false),
+    ]);
+
+    let column = DataField::new("a", DataType::Int64, false);
+    assert_eq!(None, check_part_column(&part_schema, &column));
+
+    let column = DataField::new("a", DataType::Int16, false);
+    assert_eq!(
+        Some("stored as Int32".to_string()),
+        check_part_column(&part_schema, &column)
+    );
+
+    // The column is found by name, not by its position in the table.
+    let part_schema = DataSchema::new(vec![
+        DataField::new("b", DataType::Int64, false),
+        DataField::new("a", DataType::Int32, false),
+    ]);
+    let column = DataField::new("a", DataType::Int16, false);
+    assert_eq!(
+        Some("stored as Int32".to_string()),
+        check_part_column(&part_schema, &column)
+    );
+
+    let column = DataField::new("c", DataType::Int64, false);
+    assert_eq!(
+        Some("column `c` is missing".to_string()),
+        check_part_column(&part_schema, &column)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_cast_to_schema() -> anyhow::Result<()> {
+    let part_schema = DataSchema::new(vec![
+        DataField::new("a", DataType::Int32, false),
+        DataField::new("b", DataType::Int64, false),
+    ]);
+    let col_a: ArrayRef = Arc::new(Int32Array::from_values(vec![1, -2, 3]));
+    let col_b: ArrayRef = Arc::new(Int64Array::from_values(vec![4, 5, 6]));
+    let batch = RecordBatch::try_new(Arc::new(part_schema.to_arrow()), vec![col_a, col_b])?;
+
+    // unchanged
+    let schema = Arc::new(part_schema.to_arrow());
+    let got = cast_to_schema(batch.clone(), &schema)?;
+    assert_eq!(batch, got);
+
+    // the widened column is cast, the others are kept as is
+    let table_schema = DataSchema::new(vec![
+        DataField::new("a", DataType::Int64, true),
+        DataField::new("b", DataType::Int64, false),
+    ]);
+    let schema = Arc::new(table_schema.to_arrow());
+    let got = cast_to_schema(batch.clone(), &schema)?;
+    assert_eq!(schema, got.schema().clone());
+    assert_eq!(&ArrowDataType::Int64, got.column(0).data_type());
+    let want: ArrayRef = Arc::new(Int64Array::from_values(vec![1, -2, 3]));
+    assert_eq!(&want, got.column(0));
+    assert_eq!(batch.column(1), got.column(1));
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::DataSchema;
+use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::TableOptions;
+
+use crate::data_part::ndjson_engine::NdJsonEngine;
+use crate::data_part::parquet_engine::ParquetEngine;
+
+/// The engines of the tables served by the query nodes, the store keeps only their meta.
+const QUERY_ENGINES: &[&str] = &["NULL", "MEMORY", "FUSE"];
+
+/// The options of the external engines, a table whose parts are kept by the store has no use of them.
+const EXTERNAL_OPTIONS: &[&str] = &["path", "delimiter", "header", "max_bad_rows"];
+
+pub(crate) type BlockIterator = Box<dyn Iterator<Item = Result<DataBlock>> + Send>;
+
+/// The statistics an engine keeps for the parts it writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PartStatistics {
+    /// The min, max and null count of each column, in the part itself.
+    Columns,
+    /// Only the rows and bytes of the part, in the part descriptor.
+    Rows,
+}
+
+/// TableEngine writes the blocks appended to a table into parts, and reads them back.
+///
+/// The format of a part is recorded in its descriptor, a part is read by the engine of its
+/// format, not by the current engine of its table.
+pub(crate) trait TableEngine: Send + Sync {
+    /// The name of the format, recorded in the part descriptors.
+    fn format(&self) -> &'static str;
+
+    /// The extension of the part files.
+    fn extension(&self) -> &'static str;
+
+    fn statistics(&self) -> PartStatistics;
+
+    fn validate_options(&self, options: &TableOptions) -> Result<()> {
+        reject_external_options(options)
+    }
+
+    fn encode(&self, block: DataBlock) -> Result<Vec<u8>>;
+
+    /// Decodes a part into blocks of `schema`, the current schema of the table or a projection
+    /// of its columns.
+    fn decode(&self, content: Vec<u8>, schema: DataSchemaRef) -> Result<BlockIterator>;
+
+    /// The length of the fixed footer at the end of a part, `None` if the format keeps no types,
+    /// the values of such a part are converted to the current types when they are read.
+    fn footer_len(&self) -> Option<usize>;
+
+    /// The length of the end of a part that holds its schema, found in the footer.
+    fn schema_len(&self, _footer: &[u8]) -> Result<usize> {
+        Err(ErrorCode::LogicalError(format!(
+            "{} parts keep no schema",
+            self.format()
+        )))
+    }
+
+    /// The schema a part is physically written with, read from the last `schema_len` bytes of it.
+    fn part_schema(&self, _tail: Vec<u8>) -> Result<DataSchema> {
+        Err(ErrorCode::LogicalError(format!(
+            "{} parts keep no schema",
+            self.format()
+        )))
+    }
+
+    /// The bytes of each column in a part by name, read from the same end of it as the schema.
+    /// `None` if the format does not keep the columns apart.
+    fn column_bytes(&self, _tail: Vec<u8>) -> Result<Option<HashMap<String, u64>>> {
+        Ok(None)
+    }
+}
+
+/// The table engines known to the store, by the case-insensitive names.
+pub struct TableEngineRegistry {
+    engines: HashMap<String, Arc<dyn TableEngine>>,
+    default_engine: Arc<dyn TableEngine>,
+}
+
+impl TableEngineRegistry {
+    pub fn create() -> Self {
+        let parquet: Arc<dyn TableEngine> = Arc::new(ParquetEngine {});
+        let ndjson: Arc<dyn TableEngine> = Arc::new(NdJsonEngine {});
+
+        let mut engines = HashMap::new();
+        engines.insert("PARQUET".to_string(), parquet.clone());
+        // The tables created by the query nodes through the remote database.
+        engines.insert("REMOTE".to_string(), parquet.clone());
+        engines.insert("JSON".to_string(), ndjson);
+
+        TableEngineRegistry {
+            engines,
+            default_engine: parquet,
+        }
+    }
+
+    pub(crate) fn get(&self, engine: &str) -> Result<Arc<dyn TableEngine>> {
+        self.engines
+            .get(&engine.to_uppercase())
+            .cloned()
+            .ok_or_else(|| {
+                let mut names = self.engines.keys().collect::<Vec<_>>();
+                names.sort();
+                ErrorCode::UnknownTableEngine(format!(
+                    "unknown table engine {}, expect one of {:?}",
+                    engine, names
+                ))
+            })
+    }
+
+    /// Returns the engine a part is read by, a part without a format is written in parquet.
+    pub(crate) fn get_by_format(&self, format: Option<&str>) -> Result<Arc<dyn TableEngine>> {
+        let format = match format {
+            None => return Ok(self.default_engine.clone()),
+            Some(format) => format,
+        };
+        self.engines
+            .values()
+            .find(|e| e.format() == format)
+            .cloned()
+            .ok_or_else(|| {
+                ErrorCode::UnknownTableEngine(format!("no engine writes parts in {}", format))
+            })
+    }
+
+    /// The engine of the appends to a table that is not created through the meta service.
+    pub(crate) fn default_engine(&self) -> Arc<dyn TableEngine> {
+        self.default_engine.clone()
+    }
+
+    /// Checks the engine of a table to create, unless the table is served by the query nodes.
+    pub(crate) fn validate(&self, engine: &str, options: &TableOptions) -> Result<()> {
+        if is_query_engine(engine) {
+            return Ok(());
+        }
+        self.get(engine)?.validate_options(options)
+    }
+}
+
+pub(crate) fn is_query_engine(engine: &str) -> bool {
+    QUERY_ENGINES.iter().any(|e| e.eq_ignore_ascii_case(engine))
+}
+
+fn reject_external_options(options: &TableOptions) -> Result<()> {
+    let mut names = options.keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        if EXTERNAL_OPTIONS
+            .iter()
+            .any(|o| o.eq_ignore_ascii_case(name))
+        {
+            return Err(ErrorCode::BadOption(format!(
+                "option {} is only for the external engines, the parts of this table are kept by the store",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
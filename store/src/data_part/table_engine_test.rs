@@ -0,0 +1,137 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use common_datablocks::assert_blocks_eq;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::TableOptions;
+use pretty_assertions::assert_eq;
+
+use crate::data_part::table_engine::PartStatistics;
+use crate::data_part::table_engine::TableEngineRegistry;
+
+#[test]
+fn test_table_engine_registry() -> Result<()> {
+    let registry = TableEngineRegistry::create();
+
+    assert_eq!("parquet", registry.get("PARQUET")?.format());
+    assert_eq!("parquet", registry.get("Remote")?.format());
+    assert_eq!("ndjson", registry.get("json")?.format());
+    assert_eq!(
+        ErrorCode::UnknownTableEngine("").code(),
+        registry.get("ORC").unwrap_err().code()
+    );
+
+    // The parts written before the formats are recorded are parquet.
+    assert_eq!("parquet", registry.get_by_format(None)?.format());
+    assert_eq!("ndjson", registry.get_by_format(Some("ndjson"))?.format());
+    assert!(registry.get_by_format(Some("orc")).is_err());
+
+    assert_eq!(
+        PartStatistics::Columns,
+        registry.get("PARQUET")?.statistics()
+    );
+    assert_eq!(PartStatistics::Rows, registry.get("JSON")?.statistics());
+
+    let options: TableOptions = [("Path".to_string(), "/tmp/a.csv".to_string())]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        ErrorCode::BadOption("").code(),
+        registry.validate("JSON", &options).unwrap_err().code()
+    );
+    registry.validate("JSON", &Default::default())?;
+    registry.validate("Null", &options)?;
+    assert!(registry.validate("ORC", &Default::default()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_table_engine_round_trip() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", DataType::Int32, false),
+        DataField::new("b", DataType::String, true),
+        DataField::new("c", DataType::Boolean, true),
+        DataField::new("d", DataType::Float64, false),
+    ]);
+    let block = DataBlock::create_by_array(schema.clone(), vec![
+        Series::new(vec![1i32, 2, 3]),
+        Series::new(vec![Some("x"), None, Some("null")]),
+        Series::new(vec![Some(true), Some(false), None]),
+        Series::new(vec![0.5f64, f64::INFINITY, -1.5]),
+    ]);
+
+    let registry = TableEngineRegistry::create();
+    for engine in ["PARQUET", "JSON"] {
+        let engine = registry.get(engine)?;
+        let content = engine.encode(block.clone())?;
+        let blocks = engine
+            .decode(content, schema.clone())?
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_blocks_eq(
+            vec![
+                "+---+------+-------+------+",
+                "| a | b    | c     | d    |",
+                "+---+------+-------+------+",
+                "| 1 | x    | true  | 0.5  |",
+                "| 2 | NULL | false | inf  |",
+                "| 3 | null | NULL  | -1.5 |",
+                "+---+------+-------+------+",
+            ],
+            &blocks,
+        );
+    }
+
+    // The schema of a parquet part is read from the end of it, not from the whole part.
+    let engine = registry.get("PARQUET")?;
+    let content = engine.encode(block.clone())?;
+    let footer_len = engine.footer_len().unwrap();
+    let schema_len = engine.schema_len(&content[content.len() - footer_len..])?;
+    assert!(schema_len < content.len());
+    let tail = content[content.len() - schema_len..].to_vec();
+    assert_eq!(schema.as_ref(), &engine.part_schema(tail.clone())?);
+    assert!(engine.schema_len(&content[..footer_len]).is_err());
+
+    // So are the sizes of its columns, which are all in the part.
+    let column_bytes = engine.column_bytes(tail)?.unwrap();
+    let mut names = column_bytes.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(vec!["a", "b", "c", "d"], names);
+    assert!(column_bytes.values().all(|bytes| *bytes > 0));
+    assert!(column_bytes.values().sum::<u64>() < content.len() as u64);
+
+    // The values of a JSON part are parsed as the current types, e.g. after a column is widened.
+    let engine = registry.get("JSON")?;
+    let content = engine.encode(block)?;
+    assert_eq!(None, engine.footer_len());
+    assert_eq!(None, engine.column_bytes(vec![])?);
+    let widened = DataSchemaRefExt::create(vec![
+        DataField::new("a", DataType::Int64, false),
+        DataField::new("b", DataType::String, true),
+        DataField::new("c", DataType::Boolean, true),
+        DataField::new("d", DataType::Float64, false),
+    ]);
+    let blocks = engine
+        .decode(content, widened.clone())?
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(widened, blocks[0].schema().clone());
+    assert_eq!(3, blocks[0].num_rows());
+
+    Ok(())
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
 use common_exception::exception;
@@ -84,6 +85,15 @@ impl FileSystem for Dfs {
         self.local_fs.read_all(key).await
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn read_tail(&self, key: &str, len: usize) -> exception::Result<Vec<u8>> {
+        let _file_meta = self.meta_node.get_file(key).await?.ok_or_else(|| {
+            ErrorCode::FileMetaNotFound(format!("dfs/meta: key not found: {:?}", key))
+        })?;
+
+        self.local_fs.read_tail(key, len).await
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     async fn list(&self, prefix: &str) -> common_exception::Result<ListResult> {
         let fns = self.meta_node.list_files(prefix).await?;
@@ -93,4 +103,39 @@ impl FileSystem for Dfs {
             files: fns,
         })
     }
+
+    /// Removes the local copy of a file. Its meta is removed along with the data parts that
+    /// refer to it, e.g., when the table is truncated.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn remove(&self, key: &str) -> common_exception::Result<()> {
+        self.local_fs.remove(key).await
+    }
+
+    /// A staged file is local only, it is not replicated until it is committed.
+    #[tracing::instrument(level = "debug", skip(self, data))]
+    async fn stage(&self, data: &[u8], now: SystemTime) -> common_exception::Result<String> {
+        self.local_fs.stage(data, now).await
+    }
+
+    /// The record of the file is the staged file it is committed from, which tells its age, thus
+    /// the vacuum removes it once it is old enough if no data part refers to it.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn commit_staged(&self, staged: &str, path: &str) -> common_exception::Result<()> {
+        self.local_fs.commit_staged(staged, path).await?;
+
+        let req = LogEntry {
+            txid: None,
+            cmd: Cmd::AddFile {
+                key: path.to_string(),
+                value: staged.to_string(),
+            },
+        };
+        let _resp = self.meta_node.write(req).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn list_staged(&self) -> common_exception::Result<Vec<String>> {
+        self.local_fs.list_staged().await
+    }
 }
@@ -12,30 +12,58 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::io::Cursor;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 
+use common_arrow::arrow::datatypes::Schema as ArrowSchema;
 use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
-use common_arrow::arrow::io::parquet::read;
+use common_arrow::arrow::record_batch::RecordBatch;
 use common_arrow::arrow_flight::utils::flight_data_from_arrow_batch;
 use common_arrow::arrow_flight::FlightData;
+use common_datavalues::DataField;
+use common_datavalues::DataSchema;
 use common_exception::ErrorCode;
+use common_infallible::Mutex;
+use common_metatypes::Table;
 use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_runtime::tokio;
 use common_runtime::tokio::sync::mpsc::Sender;
 use common_store_api_sdk::storage_api_impl::AppendResult;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::PartChecksum;
+use common_store_api_sdk::storage_api_impl::PartReadInfo;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
 use common_store_api_sdk::storage_api_impl::ReadAction;
 use common_store_api_sdk::RequestFor;
 use common_store_api_sdk::StoreDoAction;
+use common_store_api_sdk::WatchKVAction;
 use futures::Stream;
 use metasrv::meta_service::MetaNode;
+use metasrv::raft::state_machine::AppliedState;
 use serde::Serialize;
 use tokio_stream::StreamExt;
 use tonic::Status;
-use tonic::Streaming;
 
+use crate::configs::Config;
+use crate::configs::ConfigHandle;
 use crate::data_part::appender::Appender;
+use crate::data_part::bloom_index::BloomIndex;
+use crate::data_part::inline_store::InlineStore;
+use crate::data_part::part_checksum::verify_part;
+use crate::data_part::table_engine::TableEngine;
+use crate::data_part::table_engine::TableEngineRegistry;
+use crate::executor::apply_queue::ApplyQueue;
+use crate::executor::apply_queue::Mutation;
+use crate::external::is_external_engine;
+use crate::external::ExternalTable;
 use crate::fs::FileSystem;
+use crate::metrics::DatabaseUsageRecorder;
+use crate::metrics::TableAccessRecorder;
 
 pub trait ReplySerializer {
     type Output;
@@ -48,9 +76,30 @@ pub struct ActionHandler {
     /// In our design meta serves for both the distributed file system and the catalogs storage such as db,tabel etc.
     /// Thus in case the `fs` is a Dfs impl, `meta_node` is just a reference to the `Dfs.meta_node`.
     pub(crate) meta_node: Arc<MetaNode>,
-    fs: Arc<dyn FileSystem>,
+    /// The mutations are applied through the queue, the reads go to `meta_node` directly.
+    pub(crate) apply_queue: Arc<ApplyQueue>,
+    /// Rows and bytes served per table.
+    pub(crate) access_recorder: Arc<TableAccessRecorder>,
+    /// The quota and the used bytes of the databases, reported whenever they change.
+    pub(crate) usage_recorder: Arc<DatabaseUsageRecorder>,
+    /// The dirs the tables of the external engines can read.
+    pub(crate) external_data_dirs: Vec<PathBuf>,
+    /// The engines the parts of the tables are written and read by.
+    pub(crate) engines: Arc<TableEngineRegistry>,
+    pub(crate) fs: Arc<dyn FileSystem>,
+    /// Keeps the parts smaller than `inline_part_max_bytes` instead of `fs`, none if it is 0.
+    inline_store: Arc<dyn FileSystem>,
+    pub(crate) inline_part_max_bytes: usize,
+    /// Check the digest of a part file whenever it is read, not only its size.
+    pub(crate) verify_part_checksum: bool,
+    /// The dynamic settings, e.g., the sizes of a compaction and the intervals of the vacuums,
+    /// read at every use thus a reload takes effect at once.
+    pub(crate) config: Arc<ConfigHandle>,
 }
 
+/// The max number of rows of a block parsed from a file of an external table.
+const EXTERNAL_BLOCK_SIZE: usize = 8192;
+
 // TODO did this already defined somewhere?
 type DoGetStream =
     Pin<Box<dyn Stream<Item = Result<FlightData, tonic::Status>> + Send + Sync + 'static>>;
@@ -63,8 +112,63 @@ where T: RequestFor
 }
 
 impl ActionHandler {
-    pub fn create(fs: Arc<dyn FileSystem>, meta_node: Arc<MetaNode>) -> Self {
-        ActionHandler { meta_node, fs }
+    pub fn create(
+        fs: Arc<dyn FileSystem>,
+        meta_node: Arc<MetaNode>,
+        apply_queue: Arc<ApplyQueue>,
+    ) -> Self {
+        let inline_store = Arc::new(InlineStore::create(meta_node.clone()));
+        ActionHandler {
+            meta_node,
+            apply_queue,
+            access_recorder: Arc::new(TableAccessRecorder::create(0)),
+            usage_recorder: Arc::new(DatabaseUsageRecorder::create()),
+            external_data_dirs: vec![],
+            engines: Arc::new(TableEngineRegistry::create()),
+            fs,
+            inline_store,
+            inline_part_max_bytes: 0,
+            verify_part_checksum: false,
+            config: Arc::new(ConfigHandle::create(Config::empty())),
+        }
+    }
+
+    pub fn with_access_recorder(mut self, recorder: Arc<TableAccessRecorder>) -> Self {
+        self.access_recorder = recorder;
+        self
+    }
+
+    pub fn with_usage_recorder(mut self, recorder: Arc<DatabaseUsageRecorder>) -> Self {
+        self.usage_recorder = recorder;
+        self
+    }
+
+    pub fn with_external_data_dirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.external_data_dirs = dirs;
+        self
+    }
+
+    pub fn with_inline_part_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.inline_part_max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_verify_part_checksum(mut self, verify: bool) -> Self {
+        self.verify_part_checksum = verify;
+        self
+    }
+
+    pub fn with_config_handle(mut self, handle: Arc<ConfigHandle>) -> Self {
+        self.config = handle;
+        self
+    }
+
+    /// Reports the current usage of a database, e.g., after its parts or its quota are changed.
+    pub(crate) async fn report_usage(&self, db_name: &str) {
+        match self.meta_node.get_database_usage(db_name).await {
+            Ok(usage) => self.usage_recorder.record(db_name, usage),
+            Err(e) => log::warn!("failed to get the usage of database {}: {}", db_name, e),
+        }
     }
 
     /// Handle pull-file request, which is used internally for replicating data copies.
@@ -89,6 +193,45 @@ impl ActionHandler {
         .map_err(|e| Status::internal(format!("{:?}", e)))
     }
 
+    /// Sends the changes of the keys under the prefix to `tx` one per message, until the client is gone.
+    pub async fn do_watch_kv(
+        &self,
+        act: WatchKVAction,
+        tx: Sender<Result<FlightData, tonic::Status>>,
+    ) -> Result<(), Status> {
+        // The watcher is started before the reply, the client sees every change applied after it.
+        let mut watcher = self
+            .meta_node
+            .watch_kv(&act.prefix, act.after_seq)
+            .await
+            .map_err(Status::from)?;
+
+        tokio::spawn(async move {
+            loop {
+                let next = tokio::select! {
+                    next = watcher.next() => next,
+                    // The watcher leaves the hub once it is dropped.
+                    _ = tx.closed() => return,
+                };
+                let item = match next {
+                    Ok(Some(change)) => serde_json::to_vec(&change)
+                        .map(|data_body| FlightData {
+                            data_body,
+                            ..Default::default()
+                        })
+                        .map_err(|e| Status::internal(e.to_string())),
+                    Ok(None) => return,
+                    Err(e) => Err(Status::from(e)),
+                };
+                let failed = item.is_err();
+                if tx.send(item).await.is_err() || failed {
+                    return;
+                }
+            }
+        });
+        Ok(())
+    }
+
     pub async fn execute<S, R>(&self, action: StoreDoAction, s: S) -> common_exception::Result<R>
     where S: ReplySerializer<Output = R> {
         // To keep the code IDE-friendly, we manually expand the enum variants and dispatch them one by one
@@ -97,15 +240,28 @@ impl ActionHandler {
             // database
             StoreDoAction::CreateDatabase(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::GetDatabase(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::ListDatabases(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::DropDatabase(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::SetDatabaseQuota(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::GetDatabaseUsages(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::ReconcileDatabaseUsage(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::GetDatabaseMeta(a) => s.serialize(self.handle(a).await?),
 
             // table
             StoreDoAction::CreateTable(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::CreateTables(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::DropTable(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::UndropTable(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::RenameTable(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::GetDroppedTables(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::ModifyColumn(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::GetTable(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::ListTables(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::GetTableExt(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::TruncateTable(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::OptimizeTable(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::GetTableAccessStats(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::CopyTable(a) => s.serialize(self.handle(a).await?),
 
             // part
             StoreDoAction::ReadPlan(a) => s.serialize(self.handle(a).await?),
@@ -113,39 +269,355 @@ impl ActionHandler {
             // general-purpose kv
             StoreDoAction::UpsertKV(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::UpdateKVMeta(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::CasKV(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::MergeKV(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::PatchKVJson(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::GetKV(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::MGetKV(a) => s.serialize(self.handle(a).await?),
             StoreDoAction::PrefixListKV(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::PrefixListKVBySeq(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::PrefixListKVPage(a) => s.serialize(self.handle(a).await?),
+            StoreDoAction::DeletePrefixKV(a) => s.serialize(self.handle(a).await?),
+
+            // batch
+            StoreDoAction::Batch(a) => s.serialize(self.handle(a).await?),
         }
     }
 
-    pub(crate) async fn do_put(
+    pub(crate) async fn do_put<S>(
         &self,
         db_name: String,
         table_name: String,
-        parts: Streaming<FlightData>,
-    ) -> common_exception::Result<AppendResult> {
-        {
-            // TODO:  Validates the schema of input stream:
-            // The schema of `parts` should be a subset of
-            // table's current schema (or following the evolution rules of table schema)
+        parts: S,
+    ) -> common_exception::Result<AppendResult>
+    where
+        S: Stream<Item = Result<FlightData, Status>> + Send + Unpin + 'static,
+    {
+        self.do_put_with_marker(db_name, table_name, parts, None)
+            .await
+    }
+
+    /// Appends the parts like `do_put`, and sets the generic kv `marker`, if any, in the apply
+    /// that registers them: either both are there or neither is.
+    pub(crate) async fn do_put_with_marker<S>(
+        &self,
+        db_name: String,
+        table_name: String,
+        parts: S,
+        marker: Option<String>,
+    ) -> common_exception::Result<AppendResult>
+    where
+        S: Stream<Item = Result<FlightData, Status>> + Send + Unpin + 'static,
+    {
+        // No file is written if the parts could not be registered.
+        self.apply_queue.check_writable()?;
+
+        // The first message is the schema of the input stream. The data must arrive in the
+        // current types of the table, e.g., in the new type after a column is widened.
+        let mut parts = parts;
+        let first = parts.next().await;
+        if let Some(Ok(flight_data)) = &first {
+            self.check_append_schema(&db_name, &table_name, flight_data)
+                .await?;
         }
+        let engine = self.get_table_engine(&db_name, &table_name).await?;
+        let bloom_index = self.get_bloom_index(&db_name, &table_name).await?;
+        let parts = futures::stream::iter(first).chain(parts);
 
-        let appender = Appender::new(self.fs.clone());
-        let parts = parts
-            .take_while(|item| item.is_ok())
-            .map(|item| item.unwrap());
+        // An interrupted stream must not commit the parts received so far.
+        // The quota of the database is checked by the apply registering the parts.
+        let rejected = Arc::new(Mutex::new(None));
+        let appender = Appender::new(self.fs.clone())
+            .with_engine(engine)
+            .with_bloom_index(bloom_index)
+            .with_inline_max_bytes(self.inline_part_max_bytes)
+            .with_clock(self.meta_node.clock());
+        let parts = {
+            let rejected = rejected.clone();
+            let db_name = db_name.clone();
+            let table_name = table_name.clone();
+            parts
+                .take_while(move |item| {
+                    let status = match item {
+                        Ok(_) => return true,
+                        Err(status) => status,
+                    };
+                    *rejected.lock() = Some(ErrorCode::BrokenChannel(format!(
+                        "append to {}.{} is interrupted: {}",
+                        db_name, table_name, status
+                    )));
+                    false
+                })
+                .map(|item| item.unwrap())
+        };
 
         let res = appender
             .append_data(format!("{}/{}", &db_name, &table_name), Box::pin(parts))
+            .await;
+
+        if let Some(err) = rejected.lock().take() {
+            return Err(err);
+        }
+        let (mut res, inline_parts) = appender.commit(res?).await?;
+
+        let applied = self
+            .apply_queue
+            .apply(Mutation::AppendDataParts {
+                db_name: db_name.clone(),
+                table_name: table_name.clone(),
+                append_res: res.clone(),
+                inline_parts,
+                marker,
+            })
             .await?;
+        if let AppliedState::Seq { seq } = applied {
+            res.data_version = seq;
+        }
 
-        self.meta_node
-            .append_data_parts(&db_name, &table_name, &res)
-            .await;
+        self.access_recorder.record_write(
+            &db_name,
+            &table_name,
+            res.summary.rows as u64,
+            res.summary.wire_bytes as u64,
+        );
+        self.report_usage(&db_name).await;
         Ok(res)
     }
 
+    /// Returns the table and its decoded schema, `None` if the table does not exist.
+    pub(crate) async fn get_table_with_schema(
+        &self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<Option<(Table, DataSchema)>> {
+        let table_id = match self.meta_node.get_database(db_name).await {
+            None => return Ok(None),
+            Some(db) => match db.tables.get(table_name) {
+                None => return Ok(None),
+                Some(table_id) => *table_id,
+            },
+        };
+
+        match self.meta_node.get_table(&table_id).await {
+            None => Ok(None),
+            Some(table) => {
+                let arrow_schema = ArrowSchema::try_from(&FlightData {
+                    data_header: table.schema.clone(),
+                    ..Default::default()
+                })
+                .map_err(|e| {
+                    ErrorCode::IllegalSchema(format!("invalid schema: {:}", e.to_string()))
+                })?;
+                Ok(Some((table, arrow_schema.into())))
+            }
+        }
+    }
+
+    /// Returns the engine the parts appended to a table are written by.
+    async fn get_table_engine(
+        &self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<Arc<dyn TableEngine>> {
+        match self.get_table_with_schema(db_name, table_name).await? {
+            // An append to a table that is not created through the meta service is in parquet.
+            None => Ok(self.engines.default_engine()),
+            Some((table, _)) => self.engines.get(&table.table_engine),
+        }
+    }
+
+    /// Returns the bloom filters of the table, by its options.
+    pub(crate) async fn get_bloom_index(
+        &self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<Option<BloomIndex>> {
+        match self.get_table_with_schema(db_name, table_name).await? {
+            None => Ok(None),
+            Some((table, _)) => BloomIndex::from_options(&table.table_options),
+        }
+    }
+
+    /// Looks up what a read of a part needs, for the read actions of the clients that do not
+    /// carry it. A part not registered is read as a parquet file.
+    async fn get_part_read_info(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        part_name: &str,
+    ) -> common_exception::Result<PartReadInfo> {
+        let part = self
+            .meta_node
+            .get_data_part(db_name, table_name, part_name)
+            .await?;
+        Ok(match part {
+            None => PartReadInfo {
+                format: None,
+                storage: PartStorageClass::File,
+                checksum: None,
+            },
+            Some(part) => part.read_info(),
+        })
+    }
+
+    /// Reads the bytes of a part, from where its descriptor records it is kept, and checks them
+    /// against the checksum recorded along with it.
+    pub(crate) async fn read_part_bytes(
+        &self,
+        name: &str,
+        storage: PartStorageClass,
+        checksum: Option<&PartChecksum>,
+    ) -> common_exception::Result<Vec<u8>> {
+        let content = match storage {
+            PartStorageClass::File => self.fs.read_all(name).await?,
+            PartStorageClass::Inline => self.inline_store.read_all(name).await?,
+        };
+        verify_part(name, &content, checksum, self.verify_part_checksum)?;
+        Ok(content)
+    }
+
+    /// Returns the external table and its schema, None if the table is not of an external engine.
+    pub(crate) async fn get_external_table(
+        &self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<Option<(ExternalTable, DataSchema)>> {
+        let (table, schema) = match self.get_table_with_schema(db_name, table_name).await? {
+            Some((table, schema)) if is_external_engine(&table.table_engine) => (table, schema),
+            _ => return Ok(None),
+        };
+
+        let external = ExternalTable::try_create(
+            &table.table_engine,
+            &table.table_options,
+            &self.external_data_dirs,
+        )?;
+        Ok(Some((external, schema)))
+    }
+
+    async fn check_append_schema(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        flight_data: &FlightData,
+    ) -> common_exception::Result<()> {
+        // An append to a table that is not created through the meta service is not checked.
+        let (table, schema) = match self.get_table_with_schema(db_name, table_name).await? {
+            None => return Ok(()),
+            Some(x) => x,
+        };
+
+        if is_external_engine(&table.table_engine) {
+            return Err(ErrorCode::ReadOnlyEngine(format!(
+                "append to {}.{}: the {} engine reads its files in place and does not accept appends",
+                db_name,
+                table_name,
+                table.table_engine.to_uppercase()
+            )));
+        }
+
+        let incoming = ArrowSchema::try_from(flight_data)
+            .map_err(|e| ErrorCode::IllegalSchema(format!("invalid schema: {:}", e.to_string())))?;
+        let incoming = DataSchema::from(incoming);
+
+        // The columns of the input must be a subset of the table's current columns, each once in
+        // the table's order, and of the same types. A nullable column is not appended to a not
+        // nullable one.
+        let mut prev: Option<(usize, &DataField)> = None;
+        for got in incoming.fields().iter() {
+            let mismatch = |reason: String| {
+                ErrorCode::SchemaMismatch(format!(
+                    "append to {}.{}: column `{}` {}",
+                    db_name,
+                    table_name,
+                    got.name(),
+                    reason
+                ))
+            };
+
+            let index = schema
+                .index_of(got.name())
+                .map_err(|_| mismatch("is not in the table".to_string()))?;
+            let want = schema.field(index);
+
+            if let Some((prev_index, prev_field)) = prev {
+                if index <= prev_index {
+                    let reason = match index == prev_index {
+                        true => "is given twice".to_string(),
+                        false => format!(
+                            "comes after `{}`, expected before it as in the table",
+                            prev_field.name()
+                        ),
+                    };
+                    return Err(mismatch(reason));
+                }
+            }
+            prev = Some((index, got));
+
+            if got.data_type() != want.data_type() {
+                return Err(mismatch(format!(
+                    "is {}, expected {}",
+                    got.data_type(),
+                    want.data_type()
+                )));
+            }
+            if got.is_nullable() && !want.is_nullable() {
+                return Err(mismatch(format!(
+                    "is nullable {}, expected not nullable {}",
+                    got.data_type(),
+                    want.data_type()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the schema a part is written with, `None` if its format keeps no types.
+    ///
+    /// Only the end of the part is read, the footer then the schema before it.
+    pub(crate) async fn read_part_schema(
+        &self,
+        part: &DataPartInfo,
+    ) -> common_exception::Result<Option<DataSchema>> {
+        match self.read_part_tail(part).await? {
+            None => Ok(None),
+            Some((engine, tail)) => engine.part_schema(tail).map(Some),
+        }
+    }
+
+    /// Returns the bytes of each column of a part by name, `None` if its format does not keep
+    /// the columns apart. Only the end of the part is read, like `read_part_schema`.
+    pub(crate) async fn read_part_column_bytes(
+        &self,
+        part: &DataPartInfo,
+    ) -> common_exception::Result<Option<HashMap<String, u64>>> {
+        match self.read_part_tail(part).await? {
+            None => Ok(None),
+            Some((engine, tail)) => engine.column_bytes(tail),
+        }
+    }
+
+    /// Reads the end of a part that holds its schema, with the engine of its format.
+    async fn read_part_tail(
+        &self,
+        part: &DataPartInfo,
+    ) -> common_exception::Result<Option<(Arc<dyn TableEngine>, Vec<u8>)>> {
+        let engine = self.engines.get_by_format(part.format.as_deref())?;
+        let footer_len = match engine.footer_len() {
+            None => return Ok(None),
+            Some(len) => len,
+        };
+
+        let fs = match part.storage {
+            PartStorageClass::File => &self.fs,
+            PartStorageClass::Inline => &self.inline_store,
+        };
+        let footer = fs.read_tail(&part.part.name, footer_len).await?;
+        let schema_len = engine.schema_len(&footer)?;
+        let tail = fs.read_tail(&part.part.name, schema_len).await?;
+        Ok(Some((engine, tail)))
+    }
+
     pub async fn read_partition(
         &self,
         action: ReadAction,
@@ -159,30 +631,80 @@ impl ActionHandler {
             return Err(ErrorCode::IllegalScanPlan("invalid PlanNode passed in"));
         };
 
-        // before push_down is passed in, we returns all the columns
-        let schema = plan.schema;
-        let projection = (0..schema.fields().len()).collect::<Vec<_>>();
+        if let Some((table, _)) = self.get_external_table(&plan.db, &plan.table).await? {
+            return self.read_external_partition(table, &part_file, plan);
+        }
 
-        // TODO expose a reader from fs
-        let content = self.fs.read_all(&part_file).await?;
-        let reader = Cursor::new(content);
+        let info = match action.part.read_info {
+            Some(info) => info,
+            None => {
+                self.get_part_read_info(&plan.db, &plan.table, &part_file)
+                    .await?
+            }
+        };
+        let engine = self.engines.get_by_format(info.format.as_deref())?;
 
-        let reader =
-            read::RecordReader::try_new(reader, Some(projection.to_vec()), None, None, None)?;
+        // TODO expose a reader from fs
+        let content = self
+            .read_part_bytes(&part_file, info.storage, info.checksum.as_ref())
+            .await?;
+        let blocks = engine.decode(content, plan.schema)?;
 
-        // For simplicity, we do the conversion in-memory, to be optimized later
-        // TODO consider using `parquet_table` and `stream_parquet`
         let write_opt = IpcWriteOptions::default();
-        let flights =
-            reader
-                .into_iter()
-                .map(|batch| {
-                    batch.map(
-                    |b| flight_data_from_arrow_batch(&b, &write_opt).1, /*dictionary ignored*/
-                ).map_err(|arrow_err| Status::internal(arrow_err.to_string()))
-                })
-                .collect::<Vec<_>>();
+        let mut rows = 0;
+        let mut bytes = 0;
+        let flights = blocks
+            .map(|block| {
+                block
+                    .and_then(RecordBatch::try_from)
+                    .map(|b| {
+                        rows += b.num_rows();
+                        let (_dictionaries, flight) = flight_data_from_arrow_batch(&b, &write_opt);
+                        bytes += flight.data_header.len() + flight.data_body.len();
+                        flight
+                    })
+                    .map_err(|e| Status::internal(e.to_string()))
+            })
+            .collect::<Vec<_>>();
+        self.access_recorder
+            .record_read(&plan.db, &plan.table, rows as u64, bytes as u64);
+
         let stream = futures::stream::iter(flights);
         Ok(Box::pin(stream))
     }
+
+    /// Parses a file of an external table into blocks, as the blocks are sent.
+    fn read_external_partition(
+        &self,
+        table: ExternalTable,
+        part_file: &str,
+        plan: ReadDataSourcePlan,
+    ) -> common_exception::Result<DoGetStream> {
+        let path = Path::new(part_file);
+        if !table.contains(path) {
+            return Err(ErrorCode::IllegalScanPlan(format!(
+                "{} is not a file of {}.{}",
+                part_file, plan.db, plan.table
+            )));
+        }
+        let reader = table.reader(path, plan.schema.clone(), EXTERNAL_BLOCK_SIZE)?;
+
+        let access_recorder = self.access_recorder.clone();
+        let write_opt = IpcWriteOptions::default();
+        let flights = reader.map(move |block| {
+            let batch = block
+                .and_then(RecordBatch::try_from)
+                .map_err(|e| Status::internal(e.to_string()))?;
+            let (_dictionaries, flight) = flight_data_from_arrow_batch(&batch, &write_opt);
+            access_recorder.record_read(
+                &plan.db,
+                &plan.table,
+                batch.num_rows() as u64,
+                (flight.data_header.len() + flight.data_body.len()) as u64,
+            );
+            Ok(flight)
+        });
+
+        Ok(Box::pin(futures::stream::iter(flights)))
+    }
 }
@@ -47,9 +47,11 @@ use maplit::hashmap;
 use metasrv::meta_service::MetaNode;
 use pretty_assertions::assert_eq;
 
+use crate::configs::ConfigHandle;
 use crate::dfs::Dfs;
 use crate::executor::action_handler::RequestHandler;
 use crate::executor::ActionHandler;
+use crate::executor::ApplyQueue;
 use crate::fs::FileSystem;
 use crate::localfs::LocalFS;
 use crate::tests::service::new_test_context;
@@ -367,6 +369,7 @@ async fn test_action_handler_create_table() -> anyhow::Result<()> {
         let want = match want {
             Ok(want_table_id) => Ok(CreateTableActionResult {
                 table_id: want_table_id,
+                replaced: None,
             }),
             Err(err) => Err(err),
         };
@@ -412,6 +415,8 @@ async fn test_action_handler_create_table() -> anyhow::Result<()> {
             let mes = format!("{}-th: table plan: {:?}, want: {:?}", i, t.plan, t.want);
             let a = CreateTableAction {
                 plan: t.plan.clone(),
+                request_id: None,
+                seq: None,
             };
             let rst = hdlr.handle(a).await;
             match t.want {
@@ -508,7 +513,11 @@ async fn test_action_handler_get_table() -> anyhow::Result<()> {
                 engine: "JSON".to_string(),
                 options: Default::default(),
             };
-            let cta = CreateTableAction { plan };
+            let cta = CreateTableAction {
+                plan,
+                request_id: None,
+                seq: None,
+            };
             hdlr.handle(cta).await?;
         }
 
@@ -617,7 +626,11 @@ async fn test_action_handler_drop_table() -> anyhow::Result<()> {
                 engine: "JSON".to_string(),
                 options: Default::default(),
             };
-            let cta = CreateTableAction { plan };
+            let cta = CreateTableAction {
+                plan,
+                request_id: None,
+                seq: None,
+            };
             hdlr.handle(cta).await?;
         }
 
@@ -721,7 +734,11 @@ async fn test_action_handler_truncate_table() -> anyhow::Result<()> {
                 engine: "JSON".to_string(),
                 options: Default::default(),
             };
-            let cta = CreateTableAction { plan };
+            let cta = CreateTableAction {
+                plan,
+                request_id: None,
+                seq: None,
+            };
             hdlr.handle(cta).await?;
         }
 
@@ -730,8 +747,8 @@ async fn test_action_handler_truncate_table() -> anyhow::Result<()> {
         let location = format!("{}/{}", "path", "part_uuid");
         append_result.append_part(&location, 1, 1, 1, 1);
         hdlr.meta_node
-            .append_data_parts("foo", "foo_t1", &append_result)
-            .await;
+            .append_data_parts("foo", "foo_t1", &append_result, &[])
+            .await?;
         let mut before_parts_len: usize = 0;
         let before_parts = hdlr.meta_node.get_data_parts("foo", "foo_t1").await;
         if let Some(before_parts) = before_parts {
@@ -793,7 +810,12 @@ async fn bring_up_dfs_action_handler(
         tracing::debug!("dfs added file: {} {:?}", *key, *content);
     }
 
-    let ah = ActionHandler::create(Arc::new(dfs), mn);
+    let apply_queue = ApplyQueue::start(
+        mn.clone(),
+        16,
+        Arc::new(ConfigHandle::create(tc.config.clone())),
+    );
+    let ah = ActionHandler::create(Arc::new(dfs), mn, apply_queue);
 
     Ok((tc, ah))
 }
@@ -0,0 +1,367 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_infallible::Mutex;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::mpsc;
+use common_runtime::tokio::sync::oneshot;
+use common_runtime::tokio::sync::OwnedRwLockReadGuard;
+use common_runtime::tokio::sync::RwLock;
+use common_runtime::tokio::task::JoinHandle;
+use common_store_api_sdk::storage_api_impl::AppendResult;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::Injected;
+use common_tracing::tracing;
+use metasrv::meta_service::LogEntry;
+use metasrv::meta_service::MetaNode;
+use metasrv::raft::state_machine::AppliedState;
+use metrics::gauge;
+use metrics::histogram;
+
+use crate::configs::ConfigHandle;
+use crate::data_part::appender::InlineParts;
+
+pub static METRIC_APPLY_QUEUE_DEPTH: &str = "apply_queue.depth";
+pub static METRIC_APPLY_QUEUE_WAIT_SECONDS: &str = "apply_queue.wait_seconds";
+pub static METRIC_APPLY_SECONDS: &str = "apply_queue.apply_seconds";
+
+/// A mutation of the meta data, it is applied by the apply task in the queue order.
+#[derive(Debug)]
+pub enum Mutation {
+    Write(LogEntry),
+    /// Registers the appended parts, and writes the bytes of the inline ones along with them.
+    AppendDataParts {
+        db_name: String,
+        table_name: String,
+        append_res: AppendResult,
+        inline_parts: InlineParts,
+        /// A generic kv key set along with the parts, e.g. the progress of a table copy.
+        marker: Option<String>,
+    },
+    /// Swaps the `removed` parts of a table for the appended ones, in one step.
+    ReplaceDataParts {
+        db_name: String,
+        table_name: String,
+        removed: Vec<String>,
+        append_res: AppendResult,
+        inline_parts: InlineParts,
+        /// The generic kv keys set along with the swap, e.g. the records of the replaced files.
+        markers: Vec<String>,
+    },
+}
+
+impl Mutation {
+    /// The action name the fault injection rules match.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutation::Write(_) => "ApplyWrite",
+            Mutation::AppendDataParts { .. } => "ApplyAppendDataParts",
+            Mutation::ReplaceDataParts { .. } => "ApplyReplaceDataParts",
+        }
+    }
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mutation::Write(entry) => write!(f, "{}", entry.cmd),
+            Mutation::AppendDataParts {
+                db_name,
+                table_name,
+                ..
+            } => write!(f, "append_data_parts:{}.{}", db_name, table_name),
+            Mutation::ReplaceDataParts {
+                db_name,
+                table_name,
+                ..
+            } => write!(f, "replace_data_parts:{}.{}", db_name, table_name),
+        }
+    }
+}
+
+/// Applies the mutations taken from the queue, one at a time.
+#[async_trait::async_trait]
+pub trait Applier: Send + Sync + 'static {
+    async fn apply(&self, mutation: Mutation) -> common_exception::Result<AppliedState>;
+}
+
+#[async_trait::async_trait]
+impl Applier for MetaNode {
+    async fn apply(&self, mutation: Mutation) -> common_exception::Result<AppliedState> {
+        match mutation {
+            Mutation::Write(entry) => self.write(entry).await,
+            Mutation::AppendDataParts {
+                db_name,
+                table_name,
+                append_res,
+                inline_parts,
+                marker,
+            } => {
+                // The parts are registered under the new data version of the table.
+                let data_version = self
+                    .append_data_parts_with_marker(
+                        &db_name,
+                        &table_name,
+                        &append_res,
+                        &inline_parts,
+                        marker.as_deref(),
+                    )
+                    .await?;
+                Ok(data_version
+                    .map(AppliedState::from)
+                    .unwrap_or(AppliedState::None))
+            }
+            Mutation::ReplaceDataParts {
+                db_name,
+                table_name,
+                removed,
+                append_res,
+                inline_parts,
+                markers,
+            } => {
+                // None if the table is gone or any of the removed parts is no longer registered.
+                let data_version = self
+                    .replace_data_parts_with_markers(
+                        &db_name,
+                        &table_name,
+                        &removed,
+                        &append_res,
+                        &inline_parts,
+                        &markers,
+                    )
+                    .await?;
+                Ok(data_version
+                    .map(AppliedState::from)
+                    .unwrap_or(AppliedState::None))
+            }
+        }
+    }
+}
+
+/// Injects the faults of the request phase into the applies, for the tests only.
+///
+/// A delay holds the mutation and the ones queued after it, an error fails it without applying.
+pub struct FaultyApplier {
+    inner: Arc<dyn Applier>,
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultyApplier {
+    pub fn create(inner: Arc<dyn Applier>, injector: Arc<FaultInjector>) -> FaultyApplier {
+        FaultyApplier { inner, injector }
+    }
+}
+
+#[async_trait::async_trait]
+impl Applier for FaultyApplier {
+    async fn apply(&self, mutation: Mutation) -> common_exception::Result<AppliedState> {
+        match self
+            .injector
+            .inject(FaultPhase::Request, mutation.name())
+            .await
+        {
+            Injected::Fail(message) => Err(ErrorCode::MetaServiceError(message)),
+            _ => self.inner.apply(mutation).await,
+        }
+    }
+}
+
+pub type ReplyReceiver = oneshot::Receiver<common_exception::Result<AppliedState>>;
+
+struct Command {
+    mutation: Mutation,
+    enqueued_at: Instant,
+    reply_tx: oneshot::Sender<common_exception::Result<AppliedState>>,
+}
+
+/// ApplyQueue decouples the request handlers from applying the mutations.
+///
+/// The handlers enqueue the mutations and wait for the replies, while a single task applies them in the queue order.
+/// The queue is bounded: when it is full, enqueuing waits until the apply task catches up.
+/// Reads do not go through the queue.
+/// No mutation is enqueued while the store is read-only.
+pub struct ApplyQueue {
+    tx: mpsc::Sender<Command>,
+    config: Arc<ConfigHandle>,
+    depth: Arc<AtomicUsize>,
+    /// Taken by the apply task for every mutation, and by the readers that need one state.
+    hold: Arc<RwLock<()>>,
+    stop_tx: Mutex<Option<oneshot::Sender<()>>>,
+    join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ApplyQueue {
+    /// Spawns the apply task. A mutation taking longer than `slow_apply_threshold_ms` since it is enqueued is logged,
+    /// the threshold is read from `config` for every mutation, thus a reload takes effect at once.
+    pub fn start(
+        applier: Arc<dyn Applier>,
+        capacity: usize,
+        config: Arc<ConfigHandle>,
+    ) -> Arc<ApplyQueue> {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let depth = Arc::new(AtomicUsize::new(0));
+        let hold = Arc::new(RwLock::new(()));
+
+        let join_handle = tokio::spawn(Self::run(
+            applier,
+            rx,
+            stop_rx,
+            depth.clone(),
+            hold.clone(),
+            config.clone(),
+        ));
+
+        Arc::new(ApplyQueue {
+            tx,
+            config,
+            depth,
+            hold,
+            stop_tx: Mutex::new(Some(stop_tx)),
+            join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+
+    /// Enqueues a mutation and waits for it to be applied.
+    pub async fn apply(&self, mutation: Mutation) -> common_exception::Result<AppliedState> {
+        let reply_rx = self.enqueue(mutation).await?;
+        reply_rx
+            .await
+            .map_err(|_| ErrorCode::MetaServiceShutdown("apply queue is closed before replying"))?
+    }
+
+    /// Enqueues a mutation and returns the receiver of the reply.
+    /// It waits while the queue is full, the wait is counted as queue wait of the mutation.
+    pub async fn enqueue(&self, mutation: Mutation) -> common_exception::Result<ReplyReceiver> {
+        self.check_writable()?;
+
+        let enqueued_at = Instant::now();
+        let permit = self
+            .tx
+            .reserve()
+            .await
+            .map_err(|_| ErrorCode::MetaServiceShutdown("apply queue is closed"))?;
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let queued = self.depth.fetch_add(1, Ordering::SeqCst) + 1;
+        gauge!(METRIC_APPLY_QUEUE_DEPTH, queued as f64);
+        permit.send(Command {
+            mutation,
+            enqueued_at,
+            reply_tx,
+        });
+        Ok(reply_rx)
+    }
+
+    /// Fails if the store is read-only, as set by the latest reload.
+    pub fn check_writable(&self) -> common_exception::Result<()> {
+        if self.config.get().read_only {
+            return Err(ErrorCode::ReadOnlyStore(
+                "the store is read-only, the write is rejected",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The number of the mutations in the queue, not taken by the apply task yet.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+
+    /// Holds the mutations back until the guard is dropped, the reads meanwhile see one state of
+    /// the meta data. The mutation being applied is waited for.
+    pub async fn hold_mutations(&self) -> OwnedRwLockReadGuard<()> {
+        self.hold.clone().read_owned().await
+    }
+
+    /// Stops accepting mutations, and returns after the ones already in the queue are applied.
+    pub async fn shutdown(&self) {
+        if let Some(stop_tx) = self.stop_tx.lock().take() {
+            let _ = stop_tx.send(());
+        }
+
+        let join_handle = self.join_handle.lock().take();
+        if let Some(join_handle) = join_handle {
+            if let Err(e) = join_handle.await {
+                tracing::error!("apply queue task failed: {:?}", e);
+            }
+        }
+    }
+
+    async fn run(
+        applier: Arc<dyn Applier>,
+        mut rx: mpsc::Receiver<Command>,
+        mut stop_rx: oneshot::Receiver<()>,
+        depth: Arc<AtomicUsize>,
+        hold: Arc<RwLock<()>>,
+        config: Arc<ConfigHandle>,
+    ) {
+        let mut stopping = false;
+
+        loop {
+            let command = tokio::select! {
+                command = rx.recv() => command,
+                _ = &mut stop_rx, if !stopping => {
+                    // The mutations already in the queue are still received, until it is empty.
+                    stopping = true;
+                    rx.close();
+                    continue;
+                }
+            };
+
+            let command = match command {
+                Some(command) => command,
+                None => break,
+            };
+
+            let queued = depth.fetch_sub(1, Ordering::SeqCst) - 1;
+            gauge!(METRIC_APPLY_QUEUE_DEPTH, queued as f64);
+
+            let wait = command.enqueued_at.elapsed();
+            let desc = command.mutation.to_string();
+            let applied_at = Instant::now();
+            let res = {
+                let _held = hold.write().await;
+                applier.apply(command.mutation).await
+            };
+            let apply = applied_at.elapsed();
+
+            histogram!(METRIC_APPLY_QUEUE_WAIT_SECONDS, wait.as_secs_f64());
+            histogram!(METRIC_APPLY_SECONDS, apply.as_secs_f64());
+            let slow_threshold = Duration::from_millis(config.get().slow_apply_threshold_ms);
+            if wait + apply >= slow_threshold {
+                tracing::warn!(
+                    "slow apply: queue wait: {:?}, apply: {:?}, mutation: {}",
+                    wait,
+                    apply,
+                    desc
+                );
+            }
+
+            // The handler may have given up waiting.
+            let _ = command.reply_tx.send(res);
+        }
+
+        tracing::info!("apply queue is drained and stopped");
+    }
+}
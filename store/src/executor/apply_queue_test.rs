@@ -0,0 +1,211 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_infallible::Mutex;
+use common_metatypes::MatchSeq;
+use common_metatypes::Operation;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::Semaphore;
+use metasrv::meta_service::Cmd;
+use metasrv::meta_service::LogEntry;
+use metasrv::meta_service::MetaNode;
+use metasrv::raft::state_machine::AppliedState;
+
+use crate::configs::Config;
+use crate::configs::ConfigHandle;
+use crate::executor::Applier;
+use crate::executor::ApplyQueue;
+use crate::executor::Mutation;
+use crate::tests::service::new_test_context;
+
+/// An applier that blocks every mutation until the test lets it through.
+fn config_handle(slow_apply_threshold_ms: u64) -> Arc<ConfigHandle> {
+    let mut conf = Config::empty();
+    conf.slow_apply_threshold_ms = slow_apply_threshold_ms;
+    Arc::new(ConfigHandle::create(conf))
+}
+
+struct GatedApplier {
+    gate: Semaphore,
+    started: AtomicUsize,
+    applied: Mutex<Vec<String>>,
+}
+
+impl GatedApplier {
+    fn create() -> Arc<GatedApplier> {
+        Arc::new(GatedApplier {
+            gate: Semaphore::new(0),
+            started: AtomicUsize::new(0),
+            applied: Mutex::new(vec![]),
+        })
+    }
+
+    async fn wait_started(&self, n: usize) {
+        while self.started.load(Ordering::SeqCst) < n {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn applied(&self) -> Vec<String> {
+        self.applied.lock().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Applier for GatedApplier {
+    async fn apply(&self, mutation: Mutation) -> common_exception::Result<AppliedState> {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        self.gate.acquire().await.unwrap().forget();
+        self.applied.lock().push(mutation.to_string());
+        Ok(AppliedState::None)
+    }
+}
+
+fn incr_seq(key: &str) -> Mutation {
+    Mutation::Write(LogEntry {
+        txid: None,
+        cmd: Cmd::IncrSeq {
+            key: key.to_string(),
+        },
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_apply_queue_concurrent_upserts() -> anyhow::Result<()> {
+    // A burst of upserts to the same key are applied one by one:
+    // every upsert sees the result of the previous one as its prev.
+
+    let mut tc = new_test_context();
+    let mn = MetaNode::boot(0, &tc.config.meta_config).await?;
+    tc.meta_nodes.push(mn.clone());
+
+    let queue = ApplyQueue::start(mn.clone(), 4, config_handle(1000));
+
+    let n = 32;
+    let mut handles = vec![];
+    for i in 0..n {
+        let queue = queue.clone();
+        handles.push(tokio::spawn(async move {
+            queue
+                .apply(Mutation::Write(LogEntry {
+                    txid: None,
+                    cmd: Cmd::UpsertKV {
+                        key: "foo".to_string(),
+                        seq: MatchSeq::Any,
+                        value: Operation::Update(vec![i as u8]),
+                        value_meta: None,
+                    },
+                }))
+                .await
+        }));
+    }
+
+    let mut seqs = vec![];
+    for handle in handles {
+        match handle.await?? {
+            AppliedState::KV { prev, result } => {
+                let prev_seq = prev.map(|(seq, _)| seq);
+                let seq = result.map(|(seq, _)| seq).unwrap();
+                seqs.push((seq, prev_seq));
+            }
+            applied => panic!("expect KV result, got: {:?}", applied),
+        }
+    }
+    seqs.sort_unstable();
+
+    let mut last = None;
+    for (seq, prev_seq) in seqs.iter() {
+        assert_eq!(last, *prev_seq, "no update is lost");
+        assert!(last.map(|last| last < *seq).unwrap_or(true));
+        last = Some(*seq);
+    }
+    assert_eq!(n, seqs.len());
+
+    let got = mn.get_kv("foo").await?;
+    assert_eq!(last, got.map(|(seq, _)| seq));
+
+    queue.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_apply_queue_backpressure() -> anyhow::Result<()> {
+    // With a queue of depth 1, the 3rd mutation can not be enqueued
+    // until the 1st is applied.
+
+    let applier = GatedApplier::create();
+    let queue = ApplyQueue::start(applier.clone(), 1, config_handle(10_000));
+
+    let first = queue.enqueue(incr_seq("a")).await?;
+    applier.wait_started(1).await;
+
+    let second = queue.enqueue(incr_seq("b")).await?;
+    assert_eq!(1, queue.depth());
+
+    let third =
+        tokio::time::timeout(Duration::from_millis(200), queue.enqueue(incr_seq("c"))).await;
+    assert!(third.is_err(), "the queue is full");
+    assert_eq!(1, queue.depth());
+
+    applier.gate.add_permits(2);
+    first.await??;
+    second.await??;
+    assert_eq!(0, queue.depth());
+    assert_eq!(vec!["incr_seq:a", "incr_seq:b"], applier.applied());
+
+    queue.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_apply_queue_shutdown_drains() -> anyhow::Result<()> {
+    // Shutdown returns only after the queued mutations are applied,
+    // then no more mutation is accepted.
+
+    let applier = GatedApplier::create();
+    let queue = ApplyQueue::start(applier.clone(), 8, config_handle(10_000));
+
+    let mut replies = vec![];
+    for key in ["a", "b", "c"] {
+        replies.push(queue.enqueue(incr_seq(key)).await?);
+    }
+    applier.wait_started(1).await;
+
+    let mut shutdown = {
+        let queue = queue.clone();
+        tokio::spawn(async move { queue.shutdown().await })
+    };
+
+    let res = tokio::time::timeout(Duration::from_millis(200), &mut shutdown).await;
+    assert!(res.is_err(), "shutdown waits for the queued mutations");
+
+    applier.gate.add_permits(3);
+    shutdown.await?;
+
+    assert_eq!(
+        vec!["incr_seq:a", "incr_seq:b", "incr_seq:c"],
+        applier.applied()
+    );
+    for reply in replies {
+        reply.await??;
+    }
+
+    assert!(queue.apply(incr_seq("d")).await.is_err());
+    Ok(())
+}
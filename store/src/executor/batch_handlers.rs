@@ -0,0 +1,73 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use common_exception::ErrorCode;
+use common_store_api_sdk::batch_api_impl::BatchAction;
+use common_store_api_sdk::batch_api_impl::BatchActionResult;
+use common_store_api_sdk::batch_api_impl::BatchItemResult;
+use common_store_api_sdk::batch_api_impl::MAX_BATCH_ACTIONS;
+use serde::Serialize;
+
+use crate::executor::action_handler::RequestHandler;
+use crate::executor::ActionHandler;
+use crate::executor::ReplySerializer;
+
+/// Serializes the reply of an action of a batch, to be embedded in the reply of the batch.
+struct BatchItemSer;
+
+impl ReplySerializer for BatchItemSer {
+    type Output = String;
+    fn serialize<T>(&self, v: T) -> common_exception::Result<Self::Output>
+    where T: Serialize {
+        Ok(serde_json::to_string(&v)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<BatchAction> for ActionHandler {
+    async fn handle(&self, act: BatchAction) -> common_exception::Result<BatchActionResult> {
+        if act.actions.len() > MAX_BATCH_ACTIONS {
+            return Err(ErrorCode::IllegalBatch(format!(
+                "a batch has at most {} actions, got {}",
+                MAX_BATCH_ACTIONS,
+                act.actions.len()
+            )));
+        }
+        // Nothing of a batch is executed if any of its actions is not allowed.
+        if let Some(a) = act.actions.iter().find(|a| !a.is_batchable()) {
+            return Err(ErrorCode::IllegalBatch(format!(
+                "{} is not allowed in a batch",
+                a.name()
+            )));
+        }
+
+        // The actions see the same meta data, no mutation is applied until they are all done.
+        let _held = self.apply_queue.hold_mutations().await;
+        let results = futures::future::join_all(
+            act.actions
+                .into_iter()
+                .map(|a| self.execute(a, BatchItemSer)),
+        )
+        .await
+        .into_iter()
+        .map(|res| match res {
+            Ok(body) => BatchItemResult::Ok(body),
+            Err(e) => BatchItemResult::failed(&e),
+        })
+        .collect();
+
+        Ok(BatchActionResult { results })
+    }
+}
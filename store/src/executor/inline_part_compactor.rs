@@ -0,0 +1,169 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_infallible::Mutex;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::oneshot;
+use common_runtime::tokio::task::JoinHandle;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
+use common_tracing::tracing;
+
+use crate::executor::action_handler::ActionHandler;
+use crate::executor::part_compactor::get_bytes_option;
+use crate::executor::part_compactor::COMPACT_TARGET_PART_BYTES;
+
+/// A table with fewer inline parts than this is left as it is.
+const MIN_INLINE_PARTS_TO_COMPACT: usize = 2;
+
+/// How often a paused background task checks whether a reload resumes it.
+pub(crate) const PAUSED_RECHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+impl ActionHandler {
+    /// Merges the inline parts of a table into one part kept in a file, and returns the number
+    /// of the inline parts merged.
+    ///
+    /// The merged part is built in memory, thus only the leading inline parts up to the target
+    /// part size are merged, the rest are left to the next compaction.
+    /// The rows of the merged part are in the order of the parts they come from.
+    /// The inline parts are swapped for the merged one in a single apply, a read plan sees either
+    /// of them but never both. If the table is truncated or dropped meanwhile, the swap is
+    /// rejected and nothing is merged.
+    pub(crate) async fn compact_inline_parts(
+        &self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<usize> {
+        let (table, schema) = match self.get_table_with_schema(db_name, table_name).await? {
+            None => return Ok(0),
+            Some(x) => x,
+        };
+        let inline_parts = self
+            .meta_node
+            .get_data_parts(db_name, table_name)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.storage == PartStorageClass::Inline)
+            .collect::<Vec<_>>();
+        if inline_parts.len() < MIN_INLINE_PARTS_TO_COMPACT {
+            return Ok(0);
+        }
+
+        // A compaction always makes progress, even if the first parts exceed the target.
+        let target = get_bytes_option(&table.table_options, COMPACT_TARGET_PART_BYTES)?
+            .unwrap_or(self.config.get().compact_target_part_bytes);
+        let mut bytes = 0;
+        let inline_parts = inline_parts
+            .into_iter()
+            .enumerate()
+            .take_while(|(i, p)| {
+                bytes += p.stats.read_bytes as u64;
+                *i < MIN_INLINE_PARTS_TO_COMPACT || bytes <= target
+            })
+            .map(|(_, p)| p)
+            .collect::<Vec<_>>();
+
+        let merged = self
+            .merge_parts(db_name, table_name, &table, Arc::new(schema), &inline_parts)
+            .await?;
+        if merged.is_none() {
+            tracing::info!(
+                "compact inline parts of {}.{}: the parts are changed meanwhile, skipped",
+                db_name,
+                table_name
+            );
+            return Ok(0);
+        }
+
+        self.report_usage(db_name).await;
+        Ok(inline_parts.len())
+    }
+
+    /// Compacts the inline parts of every table, a failed table does not stop the others.
+    pub(crate) async fn compact_all_inline_parts(&self) {
+        for (db_name, db) in self.meta_node.list_databases().await {
+            for table_name in db.tables.keys() {
+                match self.compact_inline_parts(&db_name, table_name).await {
+                    Ok(0) => {}
+                    Ok(n) => {
+                        tracing::info!("compacted {} inline parts of {}.{}", n, db_name, table_name)
+                    }
+                    Err(e) => tracing::warn!(
+                        "failed to compact inline parts of {}.{}: {}",
+                        db_name,
+                        table_name,
+                        e
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// InlinePartCompactor merges the inline parts of the tables into files, every interval.
+///
+/// The interval is read from the config of the handler before every wait, it is paused while the
+/// interval is 0 or the store is read-only.
+pub struct InlinePartCompactor {
+    stop_tx: Mutex<Option<oneshot::Sender<()>>>,
+    join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl InlinePartCompactor {
+    pub fn start(handler: Arc<ActionHandler>) -> Arc<InlinePartCompactor> {
+        let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+
+        let join_handle = tokio::spawn(async move {
+            loop {
+                let wait = match handler.config.get().inline_part_compact_interval_secs {
+                    0 => PAUSED_RECHECK_INTERVAL,
+                    secs => Duration::from_secs(secs),
+                };
+                tokio::select! {
+                    _ = &mut stop_rx => break,
+                    _ = tokio::time::sleep(wait) => {}
+                }
+
+                let conf = handler.config.get();
+                if conf.inline_part_compact_interval_secs == 0 || conf.read_only {
+                    continue;
+                }
+                handler.compact_all_inline_parts().await;
+            }
+            tracing::info!("inline part compactor is stopped");
+        });
+
+        Arc::new(InlinePartCompactor {
+            stop_tx: Mutex::new(Some(stop_tx)),
+            join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+
+    /// Returns after the compaction in progress, if any, is done.
+    pub async fn shutdown(&self) {
+        if let Some(stop_tx) = self.stop_tx.lock().take() {
+            let _ = stop_tx.send(());
+        }
+
+        let join_handle = self.join_handle.lock().take();
+        if let Some(join_handle) = join_handle {
+            if let Err(e) = join_handle.await {
+                tracing::error!("inline part compactor task failed: {:?}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+use std::path::Path;
+use std::sync::Arc;
+
+use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_batch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
+use common_arrow::arrow_flight::utils::flight_data_to_arrow_batch;
+use common_datablocks::assert_blocks_eq;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_runtime::tokio;
+use common_store_api_sdk::meta_api_impl::CreateDatabaseAction;
+use common_store_api_sdk::meta_api_impl::CreateTableAction;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultKind;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::FaultRule;
+use futures::TryStreamExt;
+use metasrv::meta_service::MetaNode;
+use pretty_assertions::assert_eq;
+use tonic::Status;
+
+use crate::configs::Config;
+use crate::configs::ConfigHandle;
+use crate::dfs::Dfs;
+use crate::executor::action_handler::RequestHandler;
+use crate::executor::ActionHandler;
+use crate::executor::Applier;
+use crate::executor::ApplyQueue;
+use crate::executor::FaultyApplier;
+use crate::localfs::LocalFS;
+use crate::tests::service::new_test_context;
+use crate::tests::service::StoreTestContext;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_inline_parts_compacted_into_file() -> anyhow::Result<()> {
+    // - Append tiny blocks: they are kept inline, no file is written.
+    // - They are read back as the parts of the table.
+    // - Compaction swaps them for one part in a file with the same rows, the inline bytes dropped.
+
+    let (tc, mn, handler) = bring_up(1024 * 1024).await?;
+    let table_dir = Path::new(&tc.config.local_fs_dir).join("db1/tb1");
+
+    for i in 0..3 {
+        append(&handler, vec![i * 2, i * 2 + 1]).await?;
+    }
+
+    let parts = data_parts(&mn).await;
+    assert_eq!(3, parts.len());
+    for part in parts.iter() {
+        assert_eq!(PartStorageClass::Inline, part.storage, "{}", part.part.name);
+        assert!(mn.get_inline_part(&part.part.name).await?.is_some());
+    }
+    assert!(!table_dir.exists());
+
+    let want = vec![
+        "+---+", "| a |", "+---+", "| 0 |", "| 1 |", "| 2 |", "| 3 |", "| 4 |", "| 5 |", "+---+",
+    ];
+    assert_blocks_eq(want.clone(), &read_all(&handler, &parts).await?);
+
+    assert_eq!(3, handler.compact_inline_parts("db1", "tb1").await?);
+
+    let compacted = data_parts(&mn).await;
+    assert_eq!(1, compacted.len());
+    assert_eq!(PartStorageClass::File, compacted[0].storage);
+    assert_eq!(6, compacted[0].stats.read_rows);
+    assert!(Path::new(&tc.config.local_fs_dir)
+        .join(&compacted[0].part.name)
+        .exists());
+    assert_blocks_eq(want, &read_all(&handler, &compacted).await?);
+
+    for part in parts.iter() {
+        assert_eq!(None, mn.get_inline_part(&part.part.name).await?);
+    }
+
+    // Nothing left to compact.
+    assert_eq!(0, handler.compact_inline_parts("db1", "tb1").await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_large_part_written_to_file() -> anyhow::Result<()> {
+    // A part not smaller than the max bytes of the inline parts goes to a file.
+
+    let (tc, mn, handler) = bring_up(16).await?;
+
+    append(&handler, vec![0, 1, 2]).await?;
+
+    let parts = data_parts(&mn).await;
+    assert_eq!(1, parts.len());
+    assert_eq!(PartStorageClass::File, parts[0].storage);
+    assert_eq!(None, mn.get_inline_part(&parts[0].part.name).await?);
+    assert!(Path::new(&tc.config.local_fs_dir)
+        .join(&parts[0].part.name)
+        .exists());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_failed_append_leaves_no_inline_bytes() -> anyhow::Result<()> {
+    // The bytes of an inline part are written by the apply registering it: an append failing to
+    // apply leaves nothing behind, and a later one is written as usual.
+
+    let injector = Arc::new(FaultInjector::create());
+    let (_tc, mn, handler) = bring_up_with_injector(1024 * 1024, Some(injector.clone())).await?;
+    injector.add_rule(
+        FaultRule::create(
+            Some("ApplyAppendDataParts"),
+            FaultPhase::Request,
+            FaultKind::Error("apply is down".to_string()),
+        )
+        .times(1),
+    );
+
+    assert!(append(&handler, vec![0, 1]).await.is_err());
+    assert!(data_parts(&mn).await.is_empty());
+    assert!(mn.list_inline_parts("db1/tb1/").await?.is_empty());
+
+    append(&handler, vec![2, 3]).await?;
+    let parts = data_parts(&mn).await;
+    assert_eq!(1, parts.len());
+    assert_eq!(
+        vec![parts[0].part.name.clone()],
+        mn.list_inline_parts("db1/tb1/").await?
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_compact_inline_parts_bounded() -> anyhow::Result<()> {
+    // A compaction merges the leading inline parts up to the target part size, the rest are
+    // left to the next one.
+
+    let (_tc, mn, handler) = bring_up(1024 * 1024).await?;
+    for i in 0..4 {
+        append(&handler, vec![i]).await?;
+    }
+    let parts = data_parts(&mn).await;
+    let part_bytes = parts.iter().map(|p| p.stats.read_bytes).max().unwrap() as u64;
+    reload(&handler, |conf| {
+        conf.compact_target_part_bytes = part_bytes * 2
+    })?;
+
+    assert_eq!(2, handler.compact_inline_parts("db1", "tb1").await?);
+    let compacted = data_parts(&mn).await;
+    assert_eq!(3, compacted.len());
+    assert_eq!(parts[2..], compacted[..2]);
+    assert_eq!(PartStorageClass::File, compacted[2].storage);
+    assert_eq!(2, compacted[2].stats.read_rows);
+
+    let want = vec![
+        "+---+", "| a |", "+---+", "| 2 |", "| 3 |", "| 0 |", "| 1 |", "+---+",
+    ];
+    assert_blocks_eq(want, &read_all(&handler, &compacted).await?);
+
+    // The rest are merged by the next one.
+    assert_eq!(2, handler.compact_inline_parts("db1", "tb1").await?);
+    assert_eq!(0, handler.compact_inline_parts("db1", "tb1").await?);
+
+    Ok(())
+}
+
+/// Starts a handler keeping the parts smaller than `inline_part_max_bytes` inline, with an
+/// empty table `db1.tb1`.
+async fn bring_up(
+    inline_part_max_bytes: usize,
+) -> anyhow::Result<(StoreTestContext, Arc<MetaNode>, ActionHandler)> {
+    bring_up_with_injector(inline_part_max_bytes, None).await
+}
+
+/// Like `bring_up`, the applies go through the faults of `injector`, if any.
+async fn bring_up_with_injector(
+    inline_part_max_bytes: usize,
+    injector: Option<Arc<FaultInjector>>,
+) -> anyhow::Result<(StoreTestContext, Arc<MetaNode>, ActionHandler)> {
+    let mut tc = new_test_context();
+    let fs = LocalFS::try_create(tc.config.local_fs_dir.clone())?;
+
+    let mn = MetaNode::boot(0, &tc.config.meta_config).await?;
+    tc.meta_nodes.push(mn.clone());
+
+    let dfs = Dfs::create(fs, mn.clone());
+    let applier: Arc<dyn Applier> = match injector {
+        None => mn.clone(),
+        Some(injector) => Arc::new(FaultyApplier::create(mn.clone(), injector)),
+    };
+    let config = Arc::new(ConfigHandle::create(tc.config.clone()));
+    let apply_queue = ApplyQueue::start(applier, 16, config.clone());
+    let handler = ActionHandler::create(Arc::new(dfs), mn.clone(), apply_queue)
+        .with_config_handle(config)
+        .with_inline_part_max_bytes(inline_part_max_bytes);
+
+    handler
+        .handle(CreateDatabaseAction {
+            plan: CreateDatabasePlan {
+                if_not_exists: false,
+                db: "db1".to_string(),
+                engine: "Local".to_string(),
+                options: Default::default(),
+            },
+        })
+        .await?;
+    handler
+        .handle(CreateTableAction {
+            plan: CreateTablePlan {
+                if_not_exists: false,
+                db: "db1".to_string(),
+                table: "tb1".to_string(),
+                schema: schema(),
+                engine: "PARQUET".to_string(),
+                options: Default::default(),
+            },
+            request_id: None,
+            seq: None,
+        })
+        .await?;
+
+    Ok((tc, mn, handler))
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)])
+}
+
+async fn append(handler: &ActionHandler, values: Vec<i64>) -> anyhow::Result<()> {
+    let block = DataBlock::create_by_array(schema(), vec![Series::new(values)]);
+    let options = IpcWriteOptions::default();
+    let flights = vec![
+        flight_data_from_arrow_schema(&schema().to_arrow(), &options),
+        flight_data_from_arrow_batch(&RecordBatch::try_from(block)?, &options).1,
+    ];
+
+    let flights = futures::stream::iter(flights.into_iter().map(Ok::<_, Status>));
+    handler
+        .do_put("db1".to_string(), "tb1".to_string(), flights)
+        .await?;
+    Ok(())
+}
+
+async fn data_parts(mn: &MetaNode) -> Vec<DataPartInfo> {
+    mn.get_data_parts("db1", "tb1").await.unwrap_or_default()
+}
+
+async fn read_all(
+    handler: &ActionHandler,
+    parts: &[DataPartInfo],
+) -> anyhow::Result<Vec<DataBlock>> {
+    let arrow_schema = Arc::new(schema().to_arrow());
+    let mut blocks = vec![];
+    for part in parts {
+        let action = ReadAction {
+            part: part.part.clone(),
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: "db1".to_string(),
+                table: "tb1".to_string(),
+                schema: schema(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let flights = handler
+            .read_partition(action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        for flight in flights {
+            let batch = flight_data_to_arrow_batch(&flight, arrow_schema.clone(), true, &[])?;
+            blocks.push(DataBlock::try_from(batch)?);
+        }
+    }
+    Ok(blocks)
+}
+
+/// Reloads the config of `handler` with the changes made by `set`.
+fn reload(handler: &ActionHandler, set: impl FnOnce(&mut Config)) -> anyhow::Result<()> {
+    let mut conf = (*handler.config.get()).clone();
+    set(&mut conf);
+    handler.config.apply(conf)?;
+    Ok(())
+}
@@ -14,12 +14,20 @@
 //
 
 use common_exception::ErrorCode;
+use common_metatypes::check_json_patch;
 use common_metatypes::Operation;
+use common_store_api_sdk::kv_api_impl::CasKVAction;
+use common_store_api_sdk::kv_api_impl::DeletePrefixKVAction;
 use common_store_api_sdk::kv_api_impl::GetKVAction;
 use common_store_api_sdk::kv_api_impl::GetKVActionResult;
 use common_store_api_sdk::kv_api_impl::KVMetaAction;
 use common_store_api_sdk::kv_api_impl::MGetKVAction;
 use common_store_api_sdk::kv_api_impl::MGetKVActionResult;
+use common_store_api_sdk::kv_api_impl::MergeKVAction;
+use common_store_api_sdk::kv_api_impl::PatchKVJsonAction;
+use common_store_api_sdk::kv_api_impl::PrefixListBySeqReq;
+use common_store_api_sdk::kv_api_impl::PrefixListPage;
+use common_store_api_sdk::kv_api_impl::PrefixListPageReq;
 use common_store_api_sdk::kv_api_impl::PrefixListReply;
 use common_store_api_sdk::kv_api_impl::PrefixListReq;
 use common_store_api_sdk::kv_api_impl::UpsertKVAction;
@@ -29,11 +37,13 @@ use metasrv::meta_service::LogEntry;
 use metasrv::raft::state_machine::AppliedState;
 
 use crate::executor::action_handler::RequestHandler;
+use crate::executor::apply_queue::Mutation;
 use crate::executor::ActionHandler;
 
 #[async_trait::async_trait]
 impl RequestHandler<UpsertKVAction> for ActionHandler {
     async fn handle(&self, act: UpsertKVAction) -> common_exception::Result<UpsertKVActionResult> {
+        let return_value_on_conflict = act.return_value_on_conflict;
         let cr = LogEntry {
             txid: None,
             cmd: Cmd::UpsertKV {
@@ -44,13 +54,20 @@ impl RequestHandler<UpsertKVAction> for ActionHandler {
             },
         };
         let rst = self
-            .meta_node
-            .write(cr)
+            .apply_queue
+            .apply(Mutation::Write(cr))
             .await
             .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
 
         match rst {
-            AppliedState::KV { prev, result } => Ok(UpsertKVActionResult { prev, result }),
+            AppliedState::KV { prev, result } => {
+                let res = UpsertKVActionResult { prev, result };
+                if return_value_on_conflict {
+                    Ok(res)
+                } else {
+                    Ok(res.without_conflict_value())
+                }
+            }
             _ => Err(ErrorCode::MetaNodeInternalError("not a KV result")),
         }
     }
@@ -69,8 +86,33 @@ impl RequestHandler<KVMetaAction> for ActionHandler {
             },
         };
         let rst = self
-            .meta_node
-            .write(cr)
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+
+        match rst {
+            AppliedState::KV { prev, result } => Ok(UpsertKVActionResult { prev, result }),
+            _ => Err(ErrorCode::MetaNodeInternalError("not a KV result")),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<CasKVAction> for ActionHandler {
+    async fn handle(&self, act: CasKVAction) -> common_exception::Result<UpsertKVActionResult> {
+        let cr = LogEntry {
+            txid: None,
+            cmd: Cmd::CasKV {
+                key: act.key,
+                expect: act.expect,
+                value: act.value.into(),
+                value_meta: act.value_meta,
+            },
+        };
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
             .await
             .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
 
@@ -81,6 +123,72 @@ impl RequestHandler<KVMetaAction> for ActionHandler {
     }
 }
 
+#[async_trait::async_trait]
+impl RequestHandler<MergeKVAction> for ActionHandler {
+    async fn handle(&self, act: MergeKVAction) -> common_exception::Result<UpsertKVActionResult> {
+        // Reject an op that can never be applied before it gets into the log.
+        act.op
+            .check()
+            .map_err(|e| ErrorCode::IllegalMetaOperationArgument(e.to_string()))?;
+
+        let cr = LogEntry {
+            txid: None,
+            cmd: Cmd::MergeKV {
+                key: act.key,
+                seq: act.seq,
+                op: act.op,
+                value_meta: act.value_meta,
+            },
+        };
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+
+        match rst {
+            AppliedState::KV { prev, result } => Ok(UpsertKVActionResult { prev, result }),
+            AppliedState::KVMergeRejected { reason, .. } => {
+                Err(ErrorCode::IllegalMetaOperationArgument(reason))
+            }
+            _ => Err(ErrorCode::MetaNodeInternalError("not a KV result")),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<PatchKVJsonAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: PatchKVJsonAction,
+    ) -> common_exception::Result<UpsertKVActionResult> {
+        // Reject a patch that can never be applied before it gets into the log.
+        check_json_patch(&act.patch).map_err(ErrorCode::IllegalJsonPatch)?;
+
+        let cr = LogEntry {
+            txid: None,
+            cmd: Cmd::PatchKVJson {
+                key: act.key,
+                seq: act.seq,
+                patch: act.patch,
+            },
+        };
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+
+        match rst {
+            AppliedState::KV { prev, result } => Ok(UpsertKVActionResult { prev, result }),
+            AppliedState::KVPatchRejected { reason, .. } => {
+                Err(ErrorCode::IllegalJsonPatch(reason))
+            }
+            _ => Err(ErrorCode::MetaNodeInternalError("not a KV result")),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl RequestHandler<GetKVAction> for ActionHandler {
     async fn handle(&self, act: GetKVAction) -> common_exception::Result<GetKVActionResult> {
@@ -104,3 +212,43 @@ impl RequestHandler<PrefixListReq> for ActionHandler {
         Ok(result)
     }
 }
+
+#[async_trait::async_trait]
+impl RequestHandler<PrefixListBySeqReq> for ActionHandler {
+    async fn handle(&self, act: PrefixListBySeqReq) -> common_exception::Result<PrefixListReply> {
+        let result = self
+            .meta_node
+            .prefix_list_kv_by_seq(&act.prefix, act.min_seq, act.limit)
+            .await?;
+        Ok(result)
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<PrefixListPageReq> for ActionHandler {
+    async fn handle(&self, act: PrefixListPageReq) -> common_exception::Result<PrefixListPage> {
+        self.meta_node
+            .prefix_list_kv_page(&act.prefix, act.limit, act.after_key.as_deref())
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<DeletePrefixKVAction> for ActionHandler {
+    async fn handle(&self, act: DeletePrefixKVAction) -> common_exception::Result<usize> {
+        let cr = LogEntry {
+            txid: None,
+            cmd: Cmd::DeletePrefixKV { prefix: act.prefix },
+        };
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+
+        match rst {
+            AppliedState::KVCount { prev, .. } => Ok(prev.unwrap_or_default()),
+            _ => Err(ErrorCode::MetaNodeInternalError("not a KVCount result")),
+        }
+    }
+}
@@ -21,13 +21,20 @@ use common_arrow::arrow::datatypes::Schema as ArrowSchema;
 use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
 use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
 use common_arrow::arrow_flight::FlightData;
+use common_datavalues::DataSchema;
 use common_exception::ErrorCode;
 use common_metatypes::Database;
+use common_metatypes::DatabaseUsage;
 use common_metatypes::Table;
+use common_planners::check_table_options;
+use common_planners::CreateTablePlan;
 use common_store_api_sdk::meta_api_impl::CreateDatabaseAction;
 use common_store_api_sdk::meta_api_impl::CreateDatabaseActionResult;
 use common_store_api_sdk::meta_api_impl::CreateTableAction;
 use common_store_api_sdk::meta_api_impl::CreateTableActionResult;
+use common_store_api_sdk::meta_api_impl::CreateTableOutcome;
+use common_store_api_sdk::meta_api_impl::CreateTablesAction;
+use common_store_api_sdk::meta_api_impl::CreateTablesActionResult;
 use common_store_api_sdk::meta_api_impl::DatabaseMetaReply;
 use common_store_api_sdk::meta_api_impl::DatabaseMetaSnapshot;
 use common_store_api_sdk::meta_api_impl::DropDatabaseAction;
@@ -37,19 +44,49 @@ use common_store_api_sdk::meta_api_impl::DropTableActionResult;
 use common_store_api_sdk::meta_api_impl::GetDatabaseAction;
 use common_store_api_sdk::meta_api_impl::GetDatabaseActionResult;
 use common_store_api_sdk::meta_api_impl::GetDatabaseMetaAction;
+use common_store_api_sdk::meta_api_impl::GetDatabaseUsagesAction;
+use common_store_api_sdk::meta_api_impl::GetDatabaseUsagesActionResult;
+use common_store_api_sdk::meta_api_impl::GetDroppedTablesAction;
+use common_store_api_sdk::meta_api_impl::GetDroppedTablesActionResult;
 use common_store_api_sdk::meta_api_impl::GetTableAction;
 use common_store_api_sdk::meta_api_impl::GetTableActionResult;
 use common_store_api_sdk::meta_api_impl::GetTableExtReq;
+use common_store_api_sdk::meta_api_impl::ListDatabasesAction;
+use common_store_api_sdk::meta_api_impl::ListDatabasesReply;
+use common_store_api_sdk::meta_api_impl::ListTablesAction;
+use common_store_api_sdk::meta_api_impl::ListTablesReply;
+use common_store_api_sdk::meta_api_impl::ModifyColumnAction;
+use common_store_api_sdk::meta_api_impl::ModifyColumnActionResult;
+use common_store_api_sdk::meta_api_impl::ReconcileDatabaseUsageAction;
+use common_store_api_sdk::meta_api_impl::RenameTableAction;
+use common_store_api_sdk::meta_api_impl::RenameTableActionResult;
+use common_store_api_sdk::meta_api_impl::SetDatabaseQuotaAction;
+use common_store_api_sdk::meta_api_impl::UndropTableAction;
+use common_store_api_sdk::meta_api_impl::UndropTableActionResult;
 use log::info;
 use metasrv::meta_service::cmd::Cmd::CreateDatabase;
 use metasrv::meta_service::cmd::Cmd::CreateTable;
+use metasrv::meta_service::cmd::Cmd::CreateTables;
 use metasrv::meta_service::cmd::Cmd::DropDatabase;
 use metasrv::meta_service::cmd::Cmd::DropTable;
+use metasrv::meta_service::cmd::Cmd::ModifyTableSchema;
+use metasrv::meta_service::cmd::Cmd::ReconcileDatabaseUsage;
+use metasrv::meta_service::cmd::Cmd::RenameTable;
+use metasrv::meta_service::cmd::Cmd::SetDatabaseQuota;
+use metasrv::meta_service::cmd::Cmd::UndropTable;
+use metasrv::meta_service::cmd::TableToCreate;
 use metasrv::meta_service::LogEntry;
+use metasrv::meta_service::RaftTxId;
 use metasrv::raft::state_machine::AppliedState;
 
+use crate::data_part::bloom_index::BloomIndex;
+use crate::data_part::schema_evolution::check_modify_column;
+use crate::data_part::schema_evolution::check_part_column;
 use crate::executor::action_handler::RequestHandler;
+use crate::executor::apply_queue::Mutation;
 use crate::executor::ActionHandler;
+use crate::external::is_external_engine;
+use crate::external::ExternalTable;
 
 // Db
 #[async_trait::async_trait]
@@ -76,10 +113,11 @@ impl RequestHandler<CreateDatabaseAction> for ActionHandler {
         };
 
         let rst = self
-            .meta_node
-            .write(cr)
+            .apply_queue
+            .apply(Mutation::Write(cr))
             .await
             .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+        self.report_usage(db_name).await;
 
         match rst {
             AppliedState::DataBase { prev, result } => {
@@ -129,6 +167,24 @@ impl RequestHandler<GetDatabaseAction> for ActionHandler {
     }
 }
 
+#[async_trait::async_trait]
+impl RequestHandler<ListDatabasesAction> for ActionHandler {
+    async fn handle(
+        &self,
+        _act: ListDatabasesAction,
+    ) -> common_exception::Result<ListDatabasesReply> {
+        let dbs = self.meta_node.list_databases().await;
+        Ok(dbs
+            .into_iter()
+            .map(|(db_name, db)| GetDatabaseActionResult {
+                database_id: db.database_id,
+                db: db_name,
+                engine: db.database_engine,
+            })
+            .collect())
+    }
+}
+
 #[async_trait::async_trait]
 impl RequestHandler<DropDatabaseAction> for ActionHandler {
     async fn handle(
@@ -141,14 +197,16 @@ impl RequestHandler<DropDatabaseAction> for ActionHandler {
             txid: None,
             cmd: DropDatabase {
                 name: db_name.clone(),
+                ts: 0,
             },
         };
 
         let rst = self
-            .meta_node
-            .write(cr)
+            .apply_queue
+            .apply(Mutation::Write(cr))
             .await
             .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+        self.report_usage(db_name).await;
 
         match rst {
             AppliedState::DataBase { prev, .. } => {
@@ -167,6 +225,37 @@ impl RequestHandler<DropDatabaseAction> for ActionHandler {
 }
 
 // table
+impl ActionHandler {
+    /// The table of a create plan, once the plan passes the checks.
+    fn table_to_create(&self, plan: &CreateTablePlan) -> common_exception::Result<Table> {
+        // The same check as the planner does, for the plans built by other clients.
+        check_table_options(&plan.options)?;
+
+        // An external table reads the files in place, they are checked before it is created.
+        if is_external_engine(&plan.engine) {
+            ExternalTable::try_create(&plan.engine, &plan.options, &self.external_data_dirs)?
+                .validate(plan.schema.clone())?;
+        } else {
+            self.engines.validate(&plan.engine, &plan.options)?;
+            if let Some(bloom_index) = BloomIndex::from_options(&plan.options)? {
+                bloom_index.validate(&plan.schema)?;
+            }
+        }
+
+        let options = IpcWriteOptions::default();
+        let flight_data = flight_data_from_arrow_schema(&plan.schema.to_arrow(), &options);
+
+        Ok(Table {
+            table_id: 0,
+            schema: flight_data.data_header,
+            table_engine: plan.engine.clone(),
+            table_options: plan.options.clone(),
+            parts: Default::default(),
+            data_version: 0,
+        })
+    }
+}
+
 #[async_trait::async_trait]
 impl RequestHandler<CreateTableAction> for ActionHandler {
     async fn handle(
@@ -180,39 +269,61 @@ impl RequestHandler<CreateTableAction> for ActionHandler {
 
         info!("create table: {:}: {:?}", &db_name, &table_name);
 
-        let options = IpcWriteOptions::default();
-        let flight_data = flight_data_from_arrow_schema(&plan.schema.to_arrow(), &options);
-
-        let table = Table {
-            table_id: 0,
-            schema: flight_data.data_header,
-            table_engine: plan.engine.clone(),
-            table_options: plan.options.clone(),
-            parts: Default::default(),
-        };
+        let table = self.table_to_create(&plan)?;
 
         let cr = LogEntry {
-            txid: None,
+            txid: act
+                .request_id
+                .map(|id| RaftTxId::new(&id.client, id.serial)),
             cmd: CreateTable {
                 db_name: db_name.clone(),
                 table_name: table_name.clone(),
                 if_not_exists,
                 table,
+                seq: act.seq,
+                ts: 0,
             },
         };
 
         let rst = self
-            .meta_node
-            .write(cr)
+            .apply_queue
+            .apply(Mutation::Write(cr))
             .await
             .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
 
         match rst {
-            AppliedState::Table { prev, result } => {
-                if let Some(prev) = prev {
+            AppliedState::Table { prev, result } => match (act.seq, prev, result) {
+                (Some(seq), prev, None) => Err(ErrorCode::TableVersionMismatched(format!(
+                    "table {}: id {} does not match seq {}",
+                    table_name,
+                    prev.map(|t| t.table_id).unwrap_or_default(),
+                    seq
+                ))),
+                (Some(_), Some(prev), Some(result)) => {
+                    let arrow_schema = ArrowSchema::try_from(&FlightData {
+                        data_header: prev.schema,
+                        ..Default::default()
+                    })
+                    .map_err(|e| {
+                        ErrorCode::IllegalSchema(format!("invalid schema: {:}", e.to_string()))
+                    })?;
+                    Ok(CreateTableActionResult {
+                        table_id: result.table_id,
+                        replaced: Some(GetTableActionResult {
+                            table_id: prev.table_id,
+                            db: db_name.clone(),
+                            name: table_name.clone(),
+                            schema: Arc::new(arrow_schema.into()),
+                            engine: prev.table_engine,
+                            options: prev.table_options,
+                        }),
+                    })
+                }
+                (_, Some(prev), _) => {
                     if if_not_exists {
                         Ok(CreateTableActionResult {
                             table_id: prev.table_id,
+                            replaced: None,
                         })
                     } else {
                         Err(ErrorCode::TableAlreadyExists(format!(
@@ -220,17 +331,95 @@ impl RequestHandler<CreateTableAction> for ActionHandler {
                             table_name
                         )))
                     }
-                } else {
-                    Ok(CreateTableActionResult {
-                        table_id: result.unwrap().table_id,
-                    })
                 }
-            }
+                (_, None, result) => Ok(CreateTableActionResult {
+                    table_id: result.unwrap().table_id,
+                    replaced: None,
+                }),
+            },
             _ => Err(ErrorCode::MetaNodeInternalError("not a Table result")),
         }
     }
 }
 
+#[async_trait::async_trait]
+impl RequestHandler<CreateTablesAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: CreateTablesAction,
+    ) -> common_exception::Result<CreateTablesActionResult> {
+        info!("create tables: {:?}", act.plans.len());
+
+        // The plans failing the checks are reported, the others are created in one log entry.
+        let mut outcomes = Vec::with_capacity(act.plans.len());
+        let mut tables = vec![];
+        for plan in act.plans.iter() {
+            match self.table_to_create(plan) {
+                Ok(table) => {
+                    outcomes.push(None);
+                    tables.push(TableToCreate {
+                        db_name: plan.db.clone(),
+                        table_name: plan.table.clone(),
+                        table,
+                    });
+                }
+                Err(e) => outcomes.push(Some(CreateTableOutcome::failed(&e))),
+            }
+        }
+
+        let mut applied = vec![];
+        if !tables.is_empty() {
+            let cr = LogEntry {
+                txid: act
+                    .request_id
+                    .map(|id| RaftTxId::new(&id.client, id.serial)),
+                cmd: CreateTables { tables },
+            };
+
+            let rst = self
+                .apply_queue
+                .apply(Mutation::Write(cr))
+                .await
+                .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+
+            applied = match rst {
+                AppliedState::Tables { prev, result } => prev.into_iter().zip(result).collect(),
+                _ => return Err(ErrorCode::MetaNodeInternalError("not a Tables result")),
+            };
+        }
+
+        // The applied results are of the plans passing the checks only, in the same order.
+        let mut applied = applied.into_iter();
+        let outcomes = act
+            .plans
+            .iter()
+            .zip(outcomes)
+            .map(|(plan, outcome)| match outcome {
+                Some(outcome) => outcome,
+                None => match applied.next() {
+                    Some((None, Some(table))) => CreateTableOutcome::Created {
+                        table_id: table.table_id,
+                    },
+                    Some((Some(prev), _)) if plan.if_not_exists => {
+                        CreateTableOutcome::AlreadyExists {
+                            table_id: prev.table_id,
+                        }
+                    }
+                    Some((Some(_), _)) => CreateTableOutcome::failed(
+                        &ErrorCode::TableAlreadyExists(format!("table exists: {}", plan.table)),
+                    ),
+                    _ => CreateTableOutcome::failed(&ErrorCode::UnknownDatabase(format!(
+                        "create table: database not found {:}",
+                        plan.db
+                    ))),
+                },
+            })
+            .collect();
+
+        Ok(CreateTablesActionResult { outcomes })
+    }
+}
+
 #[async_trait::async_trait]
 impl RequestHandler<DropTableAction> for ActionHandler {
     async fn handle(
@@ -247,14 +436,16 @@ impl RequestHandler<DropTableAction> for ActionHandler {
                 db_name: db_name.clone(),
                 table_name: table_name.clone(),
                 if_exists,
+                ts: 0,
             },
         };
 
         let rst = self
-            .meta_node
-            .write(cr)
+            .apply_queue
+            .apply(Mutation::Write(cr))
             .await
             .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+        self.report_usage(db_name).await;
 
         match rst {
             AppliedState::Table { prev, .. } => {
@@ -272,6 +463,224 @@ impl RequestHandler<DropTableAction> for ActionHandler {
     }
 }
 
+#[async_trait::async_trait]
+impl RequestHandler<UndropTableAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: UndropTableAction,
+    ) -> common_exception::Result<UndropTableActionResult> {
+        let db_name = &act.plan.db;
+        let table_name = &act.plan.table;
+
+        if self.meta_node.get_database(db_name).await.is_none() {
+            return Err(ErrorCode::UnknownDatabase(format!(
+                "undrop table: database not found {:}",
+                db_name
+            )));
+        }
+
+        let cr = LogEntry {
+            txid: None,
+            cmd: UndropTable {
+                db_name: db_name.clone(),
+                table_name: table_name.clone(),
+                ts: 0,
+            },
+        };
+
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+        self.report_usage(db_name).await;
+
+        match rst {
+            AppliedState::Table { prev, result } => match (prev, result) {
+                (_, Some(result)) => Ok(UndropTableActionResult {
+                    table_id: result.table_id,
+                }),
+                (Some(_), None) => Err(ErrorCode::TableAlreadyExists(format!(
+                    "table exists: {}",
+                    table_name
+                ))),
+                (None, None) => Err(ErrorCode::UnknownTable(format!(
+                    "dropped table not found or expired: {:}",
+                    table_name
+                ))),
+            },
+            _ => Err(ErrorCode::MetaNodeInternalError("not a Table result")),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<RenameTableAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: RenameTableAction,
+    ) -> common_exception::Result<RenameTableActionResult> {
+        let db_name = &act.plan.db;
+        let table_name = &act.plan.table;
+        let new_table_name = &act.plan.new_table;
+        let if_exists = act.plan.if_exists;
+
+        if self.meta_node.get_database(db_name).await.is_none() {
+            return Err(ErrorCode::UnknownDatabase(format!(
+                "rename table: database not found {:}",
+                db_name
+            )));
+        }
+
+        let cr = LogEntry {
+            txid: None,
+            cmd: RenameTable {
+                db_name: db_name.clone(),
+                table_name: table_name.clone(),
+                new_table_name: new_table_name.clone(),
+            },
+        };
+
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+
+        match rst {
+            AppliedState::Table { prev, result } => match (prev, result) {
+                (_, Some(_)) => Ok(RenameTableActionResult {}),
+                (Some(_), None) => Err(ErrorCode::TableAlreadyExists(format!(
+                    "table exists: {}",
+                    new_table_name
+                ))),
+                (None, None) => {
+                    if if_exists {
+                        Ok(RenameTableActionResult {})
+                    } else {
+                        Err(ErrorCode::UnknownTable(format!(
+                            "table not found: {:}",
+                            table_name
+                        )))
+                    }
+                }
+            },
+            _ => Err(ErrorCode::MetaNodeInternalError("not a Table result")),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<GetDroppedTablesAction> for ActionHandler {
+    async fn handle(
+        &self,
+        _act: GetDroppedTablesAction,
+    ) -> common_exception::Result<GetDroppedTablesActionResult> {
+        let tables = self.meta_node.get_dropped_tables().await;
+        Ok(GetDroppedTablesActionResult { tables })
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<ModifyColumnAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: ModifyColumnAction,
+    ) -> common_exception::Result<ModifyColumnActionResult> {
+        let db_name = &act.plan.db;
+        let table_name = &act.plan.table;
+        let column = &act.plan.column;
+
+        let (table, schema) = self
+            .get_table_with_schema(db_name, table_name)
+            .await?
+            .ok_or_else(|| {
+                ErrorCode::UnknownTable(format!("table not found: {}.{}", db_name, table_name))
+            })?;
+
+        let illegal = |reason: String| {
+            ErrorCode::IllegalSchema(format!(
+                "can not modify column `{}` of {}.{} to {}: {}",
+                column.name(),
+                db_name,
+                table_name,
+                column.data_type(),
+                reason
+            ))
+        };
+
+        let (index, current) = schema
+            .column_with_name(column.name())
+            .ok_or_else(|| illegal(format!("column `{}` does not exist", column.name())))?;
+
+        let mut reasons = vec![];
+        if let Some(reason) = check_modify_column(current, column) {
+            reasons.push(reason);
+        }
+
+        // The existing parts keep their physical type, all of them must be readable as the new type.
+        let parts = self
+            .meta_node
+            .get_data_parts(db_name, table_name)
+            .await
+            .unwrap_or_default();
+        let mut blocking = vec![];
+        for p in parts.iter() {
+            // A part of a format without types is parsed as the current types when it is read.
+            let part_schema = match self.read_part_schema(p).await? {
+                None => continue,
+                Some(part_schema) => part_schema,
+            };
+            if let Some(reason) = check_part_column(&part_schema, column) {
+                blocking.push(format!("{} ({})", p.part.name, reason));
+            }
+        }
+        if !blocking.is_empty() {
+            reasons.push(format!("blocked by parts: {}", blocking.join(", ")));
+        }
+        if !reasons.is_empty() {
+            return Err(illegal(reasons.join("; ")));
+        }
+
+        let mut fields = schema.fields().clone();
+        fields[index] = column.clone();
+        let new_schema = DataSchema::new_from(fields, schema.meta().clone());
+
+        let options = IpcWriteOptions::default();
+        let flight_data = flight_data_from_arrow_schema(&new_schema.to_arrow(), &options);
+
+        let cr = LogEntry {
+            txid: None,
+            cmd: ModifyTableSchema {
+                db_name: db_name.clone(),
+                table_name: table_name.clone(),
+                table_id: table.table_id,
+                schema: flight_data.data_header,
+            },
+        };
+
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+
+        match rst {
+            AppliedState::Table {
+                result: Some(result),
+                ..
+            } => Ok(ModifyColumnActionResult {
+                table_id: result.table_id,
+            }),
+            AppliedState::Table { result: None, .. } => Err(ErrorCode::UnknownTable(format!(
+                "table is dropped or re-created: {}.{}",
+                db_name, table_name
+            ))),
+            _ => Err(ErrorCode::MetaNodeInternalError("not a Table result")),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl RequestHandler<GetTableAction> for ActionHandler {
     async fn handle(&self, act: GetTableAction) -> common_exception::Result<GetTableActionResult> {
@@ -313,6 +722,44 @@ impl RequestHandler<GetTableAction> for ActionHandler {
     }
 }
 
+#[async_trait::async_trait]
+impl RequestHandler<ListTablesAction> for ActionHandler {
+    async fn handle(&self, act: ListTablesAction) -> common_exception::Result<ListTablesReply> {
+        let db_name = act.db;
+        let db = self
+            .meta_node
+            .get_database(&db_name)
+            .await
+            .ok_or_else(|| ErrorCode::UnknownDatabase(db_name.clone()))?;
+
+        let mut tables = db.tables.into_iter().collect::<Vec<_>>();
+        tables.sort();
+
+        let mut res = Vec::with_capacity(tables.len());
+        for (table_name, table_id) in tables {
+            // A table dropped since the database is read is not listed.
+            let table = match self.meta_node.get_table(&table_id).await {
+                None => continue,
+                Some(table) => table,
+            };
+            let arrow_schema = ArrowSchema::try_from(&FlightData {
+                data_header: table.schema,
+                ..Default::default()
+            })
+            .map_err(|e| ErrorCode::IllegalSchema(format!("invalid schema: {:}", e.to_string())))?;
+            res.push(GetTableActionResult {
+                table_id: table.table_id,
+                db: db_name.clone(),
+                name: table_name,
+                schema: Arc::new(arrow_schema.into()),
+                engine: table.table_engine,
+                options: table.table_options,
+            });
+        }
+        Ok(res)
+    }
+}
+
 #[async_trait::async_trait]
 impl RequestHandler<GetTableExtReq> for ActionHandler {
     async fn handle(&self, act: GetTableExtReq) -> common_exception::Result<GetTableActionResult> {
@@ -365,3 +812,75 @@ impl RequestHandler<GetDatabaseMetaAction> for ActionHandler {
         }))
     }
 }
+
+#[async_trait::async_trait]
+impl RequestHandler<SetDatabaseQuotaAction> for ActionHandler {
+    async fn handle(&self, act: SetDatabaseQuotaAction) -> common_exception::Result<DatabaseUsage> {
+        let cr = LogEntry {
+            txid: None,
+            cmd: SetDatabaseQuota {
+                db_name: act.db.clone(),
+                quota: act.quota,
+            },
+        };
+
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+        self.report_usage(&act.db).await;
+
+        database_usage_of(rst, &act.db)
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<GetDatabaseUsagesAction> for ActionHandler {
+    async fn handle(
+        &self,
+        _act: GetDatabaseUsagesAction,
+    ) -> common_exception::Result<GetDatabaseUsagesActionResult> {
+        let usages = self.meta_node.get_database_usages().await?;
+        Ok(GetDatabaseUsagesActionResult { usages })
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<ReconcileDatabaseUsageAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: ReconcileDatabaseUsageAction,
+    ) -> common_exception::Result<DatabaseUsage> {
+        let cr = LogEntry {
+            txid: None,
+            cmd: ReconcileDatabaseUsage {
+                db_name: act.db.clone(),
+            },
+        };
+
+        let rst = self
+            .apply_queue
+            .apply(Mutation::Write(cr))
+            .await
+            .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+        self.report_usage(&act.db).await;
+
+        database_usage_of(rst, &act.db)
+    }
+}
+
+fn database_usage_of(rst: AppliedState, db_name: &str) -> common_exception::Result<DatabaseUsage> {
+    match rst {
+        AppliedState::DatabaseUsage {
+            result: Some(usage),
+            ..
+        } => Ok(usage),
+        AppliedState::DatabaseUsage { result: None, .. } => Err(ErrorCode::UnknownDatabase(
+            format!("database not found: {:}", db_name),
+        )),
+        _ => Err(ErrorCode::MetaNodeInternalError(
+            "not a DatabaseUsage result",
+        )),
+    }
+}
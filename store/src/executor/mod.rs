@@ -13,12 +13,33 @@
 // limitations under the License.
 
 mod action_handler;
+mod apply_queue;
+mod inline_part_compactor;
+mod part_compactor;
+mod staging_vacuum;
 
 pub use action_handler::ActionHandler;
 pub use action_handler::ReplySerializer;
+pub use apply_queue::Applier;
+pub use apply_queue::ApplyQueue;
+pub use apply_queue::FaultyApplier;
+pub use apply_queue::Mutation;
+pub use apply_queue::ReplyReceiver;
+pub use apply_queue::METRIC_APPLY_QUEUE_DEPTH;
+pub use apply_queue::METRIC_APPLY_QUEUE_WAIT_SECONDS;
+pub use apply_queue::METRIC_APPLY_SECONDS;
+pub use inline_part_compactor::InlinePartCompactor;
+pub use staging_vacuum::StagingVacuum;
 
 #[cfg(test)]
 mod action_handler_test;
+#[cfg(test)]
+mod apply_queue_test;
+mod batch_handlers;
+#[cfg(test)]
+mod inline_parts_test;
 mod kv_handlers;
 mod meta_handlers;
+#[cfg(test)]
+mod part_compactor_test;
 mod storage_handlers;
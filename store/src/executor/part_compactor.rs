@@ -0,0 +1,304 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_batch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
+use common_datablocks::DataBlock;
+use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+use common_metatypes::MatchSeq;
+use common_metatypes::Operation;
+use common_metatypes::Table;
+use common_planners::get_table_option;
+use common_planners::TableOptions;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::OptimizeTableResult;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
+use common_tracing::tracing;
+use metasrv::meta_service::Cmd;
+use metasrv::meta_service::LogEntry;
+use metasrv::raft::state_machine::AppliedState;
+
+use crate::data_part::appender::Appender;
+use crate::data_part::bloom_index::BloomIndex;
+use crate::executor::action_handler::ActionHandler;
+use crate::executor::apply_queue::Mutation;
+
+/// The size the small parts of a table are compacted to, in bytes.
+pub(crate) const COMPACT_TARGET_PART_BYTES: &str = "compact_target_part_bytes";
+
+/// The max bytes of the parts a run of the compaction of a table rewrites, 0 for no limit.
+pub(crate) const COMPACT_MAX_BYTES_PER_RUN: &str = "compact_max_bytes_per_run";
+
+/// The kv keys of the files of the parts replaced by a compaction, each one is
+/// `<prefix><the time in seconds to delete it, 20 digits>/<file>`, set in the apply of the swap.
+pub(crate) const RETIRED_PART_FILES_PREFIX: &str = "__fd_retired_part_files/";
+
+impl ActionHandler {
+    /// Compacts the adjacent parts of a table smaller than the target size into parts of about
+    /// the target size, until the max bytes per run are rewritten. The table options override
+    /// the sizes of the config.
+    ///
+    /// Each group of parts is swapped for the merged part in a single apply, under a new data
+    /// version of the table: a read plan has either of them but never both. A group changed
+    /// meanwhile, e.g. by a truncate, is left as it is. The files of the replaced parts are
+    /// recorded along with the swap and deleted by `vacuum_retired_part_files` after a grace
+    /// time, so that the reads planned before the swap can finish.
+    pub(crate) async fn compact_table(
+        &self,
+        db_name: &str,
+        table_name: &str,
+    ) -> common_exception::Result<OptimizeTableResult> {
+        let (table, schema) = self
+            .get_table_with_schema(db_name, table_name)
+            .await?
+            .ok_or_else(|| {
+                ErrorCode::UnknownTable(format!("table not found: {}.{}", db_name, table_name))
+            })?;
+        let conf = self.config.get();
+        let target = get_bytes_option(&table.table_options, COMPACT_TARGET_PART_BYTES)?
+            .unwrap_or(conf.compact_target_part_bytes);
+        let max_bytes_per_run = get_bytes_option(&table.table_options, COMPACT_MAX_BYTES_PER_RUN)?
+            .unwrap_or(conf.compact_max_bytes_per_run);
+
+        let parts = self
+            .meta_node
+            .get_data_parts(db_name, table_name)
+            .await
+            .unwrap_or_default();
+        let mut res = OptimizeTableResult {
+            parts_before: parts.len(),
+            parts_after: parts.len(),
+            finished: true,
+            ..Default::default()
+        };
+
+        let schema = Arc::new(schema);
+        for group in compaction_groups(&parts, target) {
+            let group_bytes = parts_bytes(&group);
+            // A run always makes progress, even if a group is larger than the limit.
+            if max_bytes_per_run > 0
+                && res.bytes_rewritten > 0
+                && res.bytes_rewritten + group_bytes > max_bytes_per_run
+            {
+                res.finished = false;
+                break;
+            }
+
+            match self
+                .merge_parts(db_name, table_name, &table, schema.clone(), &group)
+                .await?
+            {
+                None => tracing::info!(
+                    "compact {}.{}: the parts are changed meanwhile, skipped {} parts",
+                    db_name,
+                    table_name,
+                    group.len()
+                ),
+                Some((data_version, merged)) => {
+                    res.bytes_rewritten += group_bytes;
+                    res.parts_after = res.parts_after + merged - group.len();
+                    res.data_version = data_version;
+                }
+            }
+        }
+
+        if res.bytes_rewritten > 0 {
+            self.report_usage(db_name).await;
+        }
+        Ok(res)
+    }
+
+    /// Merges the parts of a table into one part kept in a file, the rows in the order of the
+    /// parts, and swaps them for it in a single apply.
+    ///
+    /// Returns the data version of the table after the swap and the number of the parts added,
+    /// or None if the table is gone or any of the parts is no longer registered, then the
+    /// merged files are deleted.
+    pub(crate) async fn merge_parts(
+        &self,
+        db_name: &str,
+        table_name: &str,
+        table: &Table,
+        schema: DataSchemaRef,
+        parts: &[DataPartInfo],
+    ) -> common_exception::Result<Option<(u64, usize)>> {
+        let mut blocks = vec![];
+        for part in parts.iter() {
+            let engine = self.engines.get_by_format(part.format.as_deref())?;
+            let content = self
+                .read_part_bytes(&part.part.name, part.storage, part.checksum.as_ref())
+                .await?;
+            for block in engine.decode(content, schema.clone())? {
+                blocks.push(block?);
+            }
+        }
+        let block = DataBlock::concat_blocks(&blocks)?;
+
+        let options = IpcWriteOptions::default();
+        let batch = RecordBatch::try_from(block)?;
+        let flights = vec![
+            flight_data_from_arrow_schema(&schema.to_arrow(), &options),
+            flight_data_from_arrow_batch(&batch, &options).1,
+        ];
+
+        // The merged part is written by the current engine of the table, always to a file.
+        let appender = Appender::new(self.fs.clone())
+            .with_engine(self.engines.get(&table.table_engine)?)
+            .with_bloom_index(BloomIndex::from_options(&table.table_options)?)
+            .with_clock(self.meta_node.clock());
+        let staged = appender
+            .append_data(
+                format!("{}/{}", db_name, table_name),
+                Box::pin(futures::stream::iter(flights)),
+            )
+            .await?;
+        let (append_res, inline_parts) = appender.commit(staged).await?;
+        let merged_files = append_res
+            .parts
+            .iter()
+            .map(|p| p.location.clone())
+            .collect::<Vec<_>>();
+
+        let delete_at =
+            self.meta_node.clock().now_secs() + self.config.get().compacted_part_grace_secs;
+        let markers = parts
+            .iter()
+            .filter(|p| p.storage == PartStorageClass::File)
+            .map(|p| retired_part_file_key(delete_at, &p.part.name))
+            .collect();
+
+        let applied = self
+            .apply_queue
+            .apply(Mutation::ReplaceDataParts {
+                db_name: db_name.to_string(),
+                table_name: table_name.to_string(),
+                removed: parts.iter().map(|p| p.part.name.clone()).collect(),
+                append_res,
+                inline_parts,
+                markers,
+            })
+            .await?;
+        match applied {
+            AppliedState::Seq { seq } => Ok(Some((seq, merged_files.len()))),
+            _ => {
+                // Never registered, no read plan has the merged files.
+                for file in merged_files {
+                    if let Err(e) = self.fs.remove(&file).await {
+                        tracing::warn!("fail to remove merged part file {}: {}", file, e);
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Deletes the files of the parts replaced by a compaction whose grace time has passed,
+    /// along with their records, and returns the number of them.
+    pub(crate) async fn vacuum_retired_part_files(&self) -> common_exception::Result<usize> {
+        let now = self.meta_node.clock().now_secs();
+        let mut removed = 0;
+        for (key, _) in self
+            .meta_node
+            .prefix_list_kv(RETIRED_PART_FILES_PREFIX)
+            .await?
+        {
+            let (delete_at, file) = match parse_retired_part_file_key(&key) {
+                None => continue,
+                Some(retired) => retired,
+            };
+            // The keys are in the order of the time to delete the files.
+            if delete_at > now {
+                break;
+            }
+
+            self.fs.remove(file).await?;
+            self.apply_queue
+                .apply(Mutation::Write(LogEntry {
+                    txid: None,
+                    cmd: Cmd::UpsertKV {
+                        key: key.clone(),
+                        seq: MatchSeq::Any,
+                        value: Operation::Delete,
+                        value_meta: None,
+                    },
+                }))
+                .await?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+fn retired_part_file_key(delete_at: u64, file: &str) -> String {
+    format!("{}{:020}/{}", RETIRED_PART_FILES_PREFIX, delete_at, file)
+}
+
+fn parse_retired_part_file_key(key: &str) -> Option<(u64, &str)> {
+    let (delete_at, file) = key
+        .strip_prefix(RETIRED_PART_FILES_PREFIX)?
+        .split_once('/')?;
+    Some((delete_at.parse().ok()?, file))
+}
+
+/// Splits the runs of adjacent parts smaller than `target` into groups of at least `target`
+/// bytes, but the last one of a run. A group of one part is left out, there is nothing to merge.
+pub(crate) fn compaction_groups(parts: &[DataPartInfo], target: u64) -> Vec<Vec<DataPartInfo>> {
+    let mut groups = vec![];
+    let mut group = vec![];
+    let mut bytes = 0;
+    for part in parts {
+        let size = part.stats.read_bytes as u64;
+        if size >= target {
+            groups.push(std::mem::take(&mut group));
+            bytes = 0;
+            continue;
+        }
+
+        group.push(part.clone());
+        bytes += size;
+        if bytes >= target {
+            groups.push(std::mem::take(&mut group));
+            bytes = 0;
+        }
+    }
+    groups.push(group);
+
+    groups.into_iter().filter(|g| g.len() > 1).collect()
+}
+
+fn parts_bytes(parts: &[DataPartInfo]) -> u64 {
+    parts.iter().map(|p| p.stats.read_bytes as u64).sum()
+}
+
+pub(crate) fn get_bytes_option(
+    options: &TableOptions,
+    name: &str,
+) -> common_exception::Result<Option<u64>> {
+    match get_table_option(options, name) {
+        None => Ok(None),
+        Some(value) => match value.trim().parse::<u64>() {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(ErrorCode::BadOption(format!(
+                "option {} must be a number of bytes, got {}",
+                name, value
+            ))),
+        },
+    }
+}
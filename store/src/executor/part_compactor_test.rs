@@ -0,0 +1,500 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::convert::TryFrom;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_batch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
+use common_arrow::arrow_flight::utils::flight_data_to_arrow_batch;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::CreateDatabasePlan;
+use common_planners::CreateTablePlan;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_runtime::tokio;
+use common_runtime::SharedClock;
+use common_runtime::VirtualClock;
+use common_store_api_sdk::meta_api_impl::CreateDatabaseAction;
+use common_store_api_sdk::meta_api_impl::CreateTableAction;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::OptimizeTableAction;
+use common_store_api_sdk::storage_api_impl::ReadAction;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::FaultKind;
+use common_store_api_sdk::FaultPhase;
+use common_store_api_sdk::FaultRule;
+use futures::TryStreamExt;
+use metasrv::meta_service::MetaNode;
+use pretty_assertions::assert_eq;
+use tonic::Status;
+
+use crate::configs::Config;
+use crate::configs::ConfigHandle;
+use crate::dfs::Dfs;
+use crate::executor::action_handler::RequestHandler;
+use crate::executor::part_compactor::COMPACT_TARGET_PART_BYTES;
+use crate::executor::part_compactor::RETIRED_PART_FILES_PREFIX;
+use crate::executor::ActionHandler;
+use crate::executor::ApplyQueue;
+use crate::executor::FaultyApplier;
+use crate::fs::FileSystem;
+use crate::localfs::LocalFS;
+use crate::tests::service::new_test_context;
+use crate::tests::service::StoreTestContext;
+
+const ROWS_PER_PART: i64 = 10_000;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_optimize_table_merges_small_parts() -> anyhow::Result<()> {
+    // - Append 8 small parts of about the same size, the target is about 2.5 of them.
+    // - OPTIMIZE merges the adjacent ones into 3 parts: 3 + 3 + 2, with the same rows.
+    // - Another OPTIMIZE finds nothing to merge.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, mn, handler) = bring_up(None, Default::default()).await?;
+    for i in 0..8 {
+        append(&handler, i).await?;
+    }
+    let parts = data_parts(&mn).await;
+    assert_eq!(8, parts.len());
+    let want = values(&read_all(&handler, &parts).await?);
+
+    let max_part_bytes = parts.iter().map(|p| p.stats.read_bytes).max().unwrap() as u64;
+    reload(&handler, |conf| {
+        conf.compact_target_part_bytes = max_part_bytes * 5 / 2
+    })?;
+
+    let res = handler
+        .handle(OptimizeTableAction {
+            db: "db1".to_string(),
+            table: "tb1".to_string(),
+        })
+        .await?;
+    assert_eq!(8, res.parts_before);
+    assert_eq!(3, res.parts_after);
+    assert!(res.finished);
+    assert_eq!(
+        parts.iter().map(|p| p.stats.read_bytes as u64).sum::<u64>(),
+        res.bytes_rewritten
+    );
+
+    let compacted = data_parts(&mn).await;
+    assert_eq!(3, compacted.len());
+    assert_eq!(
+        res.data_version,
+        mn.get_table_data_version("db1", "tb1").await.unwrap()
+    );
+    let mut rows = compacted
+        .iter()
+        .map(|p| p.stats.read_rows)
+        .collect::<Vec<_>>();
+    rows.sort_unstable();
+    assert_eq!(vec![20_000, 30_000, 30_000], rows);
+    assert_eq!(want, values(&read_all(&handler, &compacted).await?));
+
+    let res = handler.compact_table("db1", "tb1").await?;
+    assert_eq!(3, res.parts_before);
+    assert_eq!(3, res.parts_after);
+    assert_eq!(0, res.bytes_rewritten);
+    assert!(res.finished);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_optimize_table_stops_at_max_bytes_per_run() -> anyhow::Result<()> {
+    // - With a cap below the size of a group, a run merges one group and reports unfinished.
+    // - The runs after it go on until every group is merged.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (_tc, mn, handler) = bring_up(None, Default::default()).await?;
+    for i in 0..6 {
+        append(&handler, i).await?;
+    }
+    let parts = data_parts(&mn).await;
+    let want = values(&read_all(&handler, &parts).await?);
+
+    let max_part_bytes = parts.iter().map(|p| p.stats.read_bytes).max().unwrap() as u64;
+    reload(&handler, |conf| {
+        conf.compact_target_part_bytes = max_part_bytes * 3 / 2;
+        conf.compact_max_bytes_per_run = 1;
+    })?;
+
+    let mut parts_left = 6;
+    for i in 0..3 {
+        let res = handler.compact_table("db1", "tb1").await?;
+        assert_eq!(parts_left, res.parts_before, "{}-th run", i);
+        assert_eq!(parts_left - 1, res.parts_after, "{}-th run", i);
+        assert!(res.bytes_rewritten > 0, "{}-th run", i);
+        assert_eq!(i == 2, res.finished, "{}-th run", i);
+        parts_left = res.parts_after;
+    }
+
+    let res = handler.compact_table("db1", "tb1").await?;
+    assert_eq!(0, res.bytes_rewritten);
+    assert!(res.finished);
+
+    let compacted = data_parts(&mn).await;
+    assert_eq!(3, compacted.len());
+    assert_eq!(want, values(&read_all(&handler, &compacted).await?));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_optimize_table_concurrent_reads() -> anyhow::Result<()> {
+    // - Every swap of the parts is held in the apply queue for a while.
+    // - The reads meanwhile, each of the parts listed at that time, always have every row once:
+    //   the merged part and the parts it replaces are never listed together, and the files of
+    //   the replaced parts are kept for the reads planned before the swap.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let injector = Arc::new(FaultInjector::create());
+    let (_tc, mn, handler) = bring_up(Some(injector.clone()), Default::default()).await?;
+    for i in 0..8 {
+        append(&handler, i).await?;
+    }
+    let parts = data_parts(&mn).await;
+    let want = values(&read_all(&handler, &parts).await?);
+
+    let max_part_bytes = parts.iter().map(|p| p.stats.read_bytes).max().unwrap() as u64;
+    reload(&handler, |conf| {
+        conf.compact_target_part_bytes = max_part_bytes * 3 / 2
+    })?;
+    let handler = Arc::new(handler);
+
+    injector.add_rule(FaultRule::create(
+        Some("ApplyReplaceDataParts"),
+        FaultPhase::Request,
+        FaultKind::Delay(Duration::from_millis(200)),
+    ));
+
+    let done = Arc::new(AtomicBool::new(false));
+    let compaction = {
+        let handler = handler.clone();
+        let done = done.clone();
+        tokio::spawn(async move {
+            let res = handler.compact_table("db1", "tb1").await;
+            done.store(true, Ordering::SeqCst);
+            res
+        })
+    };
+
+    let mut reads = 0;
+    while !done.load(Ordering::SeqCst) {
+        let parts = data_parts(&mn).await;
+        let got = values(&read_all(&handler, &parts).await?);
+        assert_eq!(want, got, "{}-th read of {} parts", reads, parts.len());
+        reads += 1;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(reads > 1, "only {} reads during the compaction", reads);
+
+    let res = compaction.await??;
+    assert_eq!(4, res.parts_after);
+    let compacted = data_parts(&mn).await;
+    assert_eq!(want, values(&read_all(&handler, &compacted).await?));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_optimize_table_removes_replaced_files_after_grace() -> anyhow::Result<()> {
+    // - The swap records the files of the replaced parts, they are kept for the grace time.
+    // - Once the clock passes it, the vacuum deletes the files and their records.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let clock = VirtualClock::create();
+    let (tc, mn, handler) =
+        bring_up_with_clock(None, Default::default(), SharedClock::create(clock.clone())).await?;
+    for i in 0..4 {
+        append(&handler, i).await?;
+    }
+    let parts = data_parts(&mn).await;
+    let want = values(&read_all(&handler, &parts).await?);
+
+    let total_bytes = parts.iter().map(|p| p.stats.read_bytes as u64).sum();
+    reload(&handler, |conf| {
+        conf.compact_target_part_bytes = total_bytes;
+        conf.compacted_part_grace_secs = 10;
+    })?;
+    let res = handler.compact_table("db1", "tb1").await?;
+    assert_eq!(1, res.parts_after);
+    assert_eq!(5, local_files(&tc).await?.len());
+    assert_eq!(4, mn.prefix_list_kv(RETIRED_PART_FILES_PREFIX).await?.len());
+
+    assert_eq!(0, handler.vacuum_retired_part_files().await?);
+    assert_eq!(5, local_files(&tc).await?.len());
+
+    clock.advance(Duration::from_secs(11));
+    assert_eq!(4, handler.vacuum_retired_part_files().await?);
+    assert_eq!(1, local_files(&tc).await?.len());
+    assert!(mn
+        .prefix_list_kv(RETIRED_PART_FILES_PREFIX)
+        .await?
+        .is_empty());
+
+    let compacted = data_parts(&mn).await;
+    assert_eq!(want, values(&read_all(&handler, &compacted).await?));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_vacuum_removes_unregistered_files() -> anyhow::Result<()> {
+    // - An append fails to register its part after its file is committed into place.
+    // - Once the file is old enough by the clock, the vacuum removes it and its record, the
+    //   registered files are kept.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let clock = VirtualClock::create();
+    let injector = Arc::new(FaultInjector::create());
+    let (tc, mn, handler) = bring_up_with_clock(
+        Some(injector.clone()),
+        Default::default(),
+        SharedClock::create(clock.clone()),
+    )
+    .await?;
+    injector.add_rule(
+        FaultRule::create(
+            Some("ApplyAppendDataParts"),
+            FaultPhase::Request,
+            FaultKind::Error("apply is down".to_string()),
+        )
+        .times(1),
+    );
+    assert!(append(&handler, 0).await.is_err());
+    append(&handler, 1).await?;
+    assert_eq!(1, data_parts(&mn).await.len());
+    assert_eq!(2, local_files(&tc).await?.len());
+    assert_eq!(2, mn.list_files("db1/tb1/").await?.len());
+
+    let max_age = Duration::from_secs(60);
+    assert_eq!(0, handler.vacuum_unregistered_files(max_age).await?);
+
+    clock.advance(Duration::from_secs(61));
+    assert_eq!(1, handler.vacuum_unregistered_files(max_age).await?);
+    let registered = vec![data_parts(&mn).await[0].part.name.clone()];
+    assert_eq!(registered, mn.list_files("db1/tb1/").await?);
+    assert_eq!(1, local_files(&tc).await?.len());
+    assert_eq!(0, handler.vacuum_unregistered_files(max_age).await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_merge_parts_changed_meanwhile_removes_merged_files() -> anyhow::Result<()> {
+    // - The parts of a group are truncated before they are swapped for the merged part.
+    // - The merged file, never registered, is deleted, the replaced ones are left as they are.
+
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let (tc, mn, handler) = bring_up(None, Default::default()).await?;
+    for i in 0..2 {
+        append(&handler, i).await?;
+    }
+    let parts = data_parts(&mn).await;
+    let (table, schema) = handler.get_table_with_schema("db1", "tb1").await?.unwrap();
+
+    mn.remove_table_data_parts("db1", "tb1").await?;
+    let merged = handler
+        .merge_parts("db1", "tb1", &table, Arc::new(schema), &parts)
+        .await?;
+    assert_eq!(None, merged);
+    assert_eq!(2, local_files(&tc).await?.len());
+    assert!(mn
+        .prefix_list_kv(RETIRED_PART_FILES_PREFIX)
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_optimize_table_bad_option() -> anyhow::Result<()> {
+    let (_log_guards, ut_span) = init_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut options = common_planners::TableOptions::new();
+    options.insert(COMPACT_TARGET_PART_BYTES.to_string(), "128M".to_string());
+    let (_tc, _mn, handler) = bring_up(None, options).await?;
+
+    let res = handler.compact_table("db1", "tb1").await;
+    let err = res.unwrap_err();
+    assert_eq!(ErrorCode::BadOption("").code(), err.code());
+    assert!(err.message().contains(COMPACT_TARGET_PART_BYTES));
+
+    let res = handler.compact_table("db1", "not_a_table").await;
+    assert_eq!(ErrorCode::UnknownTable("").code(), res.unwrap_err().code());
+
+    Ok(())
+}
+
+/// Starts a handler with an empty table `db1.tb1` of the options, every part of it in a file.
+async fn bring_up(
+    injector: Option<Arc<FaultInjector>>,
+    options: common_planners::TableOptions,
+) -> anyhow::Result<(StoreTestContext, Arc<MetaNode>, ActionHandler)> {
+    bring_up_with_clock(injector, options, SharedClock::default()).await
+}
+
+async fn bring_up_with_clock(
+    injector: Option<Arc<FaultInjector>>,
+    options: common_planners::TableOptions,
+    clock: SharedClock,
+) -> anyhow::Result<(StoreTestContext, Arc<MetaNode>, ActionHandler)> {
+    let mut tc = new_test_context();
+    tc.config.meta_config.clock = clock;
+    let fs = LocalFS::try_create(tc.config.local_fs_dir.clone())?;
+
+    let mn = MetaNode::boot(0, &tc.config.meta_config).await?;
+    tc.meta_nodes.push(mn.clone());
+
+    let dfs = Dfs::create(fs, mn.clone());
+    let applier: Arc<dyn crate::executor::Applier> = match injector {
+        None => mn.clone(),
+        Some(injector) => Arc::new(FaultyApplier::create(mn.clone(), injector)),
+    };
+    let config = Arc::new(ConfigHandle::create(tc.config.clone()));
+    let apply_queue = ApplyQueue::start(applier, 16, config.clone());
+    let handler =
+        ActionHandler::create(Arc::new(dfs), mn.clone(), apply_queue).with_config_handle(config);
+
+    handler
+        .handle(CreateDatabaseAction {
+            plan: CreateDatabasePlan {
+                if_not_exists: false,
+                db: "db1".to_string(),
+                engine: "Local".to_string(),
+                options: Default::default(),
+            },
+        })
+        .await?;
+    handler
+        .handle(CreateTableAction {
+            plan: CreateTablePlan {
+                if_not_exists: false,
+                db: "db1".to_string(),
+                table: "tb1".to_string(),
+                schema: schema(),
+                engine: "PARQUET".to_string(),
+                options,
+            },
+            request_id: None,
+            seq: None,
+        })
+        .await?;
+
+    Ok((tc, mn, handler))
+}
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![DataField::new("a", DataType::Int64, false)])
+}
+
+/// Appends a part of the `i`-th `ROWS_PER_PART` values.
+async fn append(handler: &ActionHandler, i: i64) -> anyhow::Result<()> {
+    let values = (i * ROWS_PER_PART..(i + 1) * ROWS_PER_PART).collect::<Vec<_>>();
+    let block = DataBlock::create_by_array(schema(), vec![Series::new(values)]);
+    let options = IpcWriteOptions::default();
+    let flights = vec![
+        flight_data_from_arrow_schema(&schema().to_arrow(), &options),
+        flight_data_from_arrow_batch(&RecordBatch::try_from(block)?, &options).1,
+    ];
+
+    let flights = futures::stream::iter(flights.into_iter().map(Ok::<_, Status>));
+    handler
+        .do_put("db1".to_string(), "tb1".to_string(), flights)
+        .await?;
+    Ok(())
+}
+
+/// The part files of `db1.tb1` on the local disk.
+async fn local_files(tc: &StoreTestContext) -> anyhow::Result<Vec<String>> {
+    let fs = LocalFS::try_create(tc.config.local_fs_dir.clone())?;
+    Ok(fs.list("db1/tb1").await?.files)
+}
+
+async fn data_parts(mn: &MetaNode) -> Vec<DataPartInfo> {
+    mn.get_data_parts("db1", "tb1").await.unwrap_or_default()
+}
+
+async fn read_all(
+    handler: &ActionHandler,
+    parts: &[DataPartInfo],
+) -> anyhow::Result<Vec<DataBlock>> {
+    let arrow_schema = Arc::new(schema().to_arrow());
+    let mut blocks = vec![];
+    for part in parts {
+        let action = ReadAction {
+            part: part.part.clone(),
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: "db1".to_string(),
+                table: "tb1".to_string(),
+                schema: schema(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let flights = handler
+            .read_partition(action)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        for flight in flights {
+            let batch = flight_data_to_arrow_batch(&flight, arrow_schema.clone(), true, &[])?;
+            blocks.push(DataBlock::try_from(batch)?);
+        }
+    }
+    Ok(blocks)
+}
+
+/// The sorted values of column `a` of the blocks.
+fn values(blocks: &[DataBlock]) -> Vec<i64> {
+    let mut values = blocks
+        .iter()
+        .flat_map(|block| block.column(0).to_values().unwrap())
+        .map(|v| match v {
+            DataValue::Int64(Some(v)) => v,
+            v => panic!("unexpected value {:?}", v),
+        })
+        .collect::<Vec<_>>();
+    values.sort_unstable();
+    values
+}
+
+/// Reloads the config of `handler` with the changes made by `set`.
+fn reload(handler: &ActionHandler, set: impl FnOnce(&mut Config)) -> anyhow::Result<()> {
+    let mut conf = (*handler.config.get()).clone();
+    set(&mut conf);
+    handler.config.apply(conf)?;
+    Ok(())
+}
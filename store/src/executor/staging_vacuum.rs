@@ -0,0 +1,166 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use common_infallible::Mutex;
+use common_runtime::tokio;
+use common_runtime::tokio::sync::oneshot;
+use common_runtime::tokio::task::JoinHandle;
+use common_tracing::tracing;
+use metasrv::meta_service::Cmd;
+use metasrv::meta_service::LogEntry;
+
+use crate::executor::action_handler::ActionHandler;
+use crate::executor::apply_queue::Mutation;
+use crate::executor::inline_part_compactor::PAUSED_RECHECK_INTERVAL;
+use crate::fs::staged_at;
+
+impl ActionHandler {
+    /// Removes the staged part files older than `max_age`, and returns the number of them.
+    ///
+    /// An append moves its staged files into place before it returns, a staged file outlives it
+    /// only if the append is interrupted. `max_age` must be longer than any append in progress.
+    pub(crate) async fn vacuum_staged_files(
+        &self,
+        max_age: Duration,
+    ) -> common_exception::Result<usize> {
+        let now = self.meta_node.clock().now();
+        let mut removed = 0;
+        for path in self.fs.list_staged().await? {
+            if is_expired(&path, now, max_age) {
+                self.fs.remove(&path).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Removes the files in place that no data part refers to once they are older than
+    /// `max_age`, along with their records, and returns the number of them.
+    ///
+    /// Such a file is committed by an append whose parts then fail to be registered, e.g. over
+    /// the quota of the database. Its age is of the staged file it is committed from, a file of
+    /// an unknown age is kept.
+    pub(crate) async fn vacuum_unregistered_files(
+        &self,
+        max_age: Duration,
+    ) -> common_exception::Result<usize> {
+        let now = self.meta_node.clock().now();
+        let expired = self
+            .meta_node
+            .list_unregistered_files()
+            .await?
+            .into_iter()
+            .filter(|(_, staged)| is_expired(staged, now, max_age))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        // The files first, a record left by a failure is removed by the next run.
+        for key in expired.iter() {
+            self.fs.remove(key).await?;
+        }
+        let removed = expired.len();
+        self.apply_queue
+            .apply(Mutation::Write(LogEntry {
+                txid: None,
+                cmd: Cmd::RemoveFiles { keys: expired },
+            }))
+            .await?;
+        Ok(removed)
+    }
+}
+
+/// Whether the file staged at the path is older than `max_age`, false if the path does not
+/// tell when.
+fn is_expired(staged: &str, now: SystemTime, max_age: Duration) -> bool {
+    match staged_at(staged) {
+        None => false,
+        Some(t) => now.duration_since(t).map_or(false, |age| age > max_age),
+    }
+}
+
+/// StagingVacuum removes the staged part files left by interrupted appends, the files of the
+/// appends that fail to register their parts, and the files of the parts replaced by a
+/// compaction once their grace time passes, every interval of the clock of the meta node.
+///
+/// Like `InlinePartCompactor`, it is paused while the interval is 0 or the store is read-only.
+pub struct StagingVacuum {
+    stop_tx: Mutex<Option<oneshot::Sender<()>>>,
+    join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl StagingVacuum {
+    pub fn start(handler: Arc<ActionHandler>) -> Arc<StagingVacuum> {
+        let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+
+        let join_handle = tokio::spawn(async move {
+            loop {
+                let wait = match handler.config.get().staging_vacuum_interval_secs {
+                    0 => PAUSED_RECHECK_INTERVAL,
+                    secs => Duration::from_secs(secs),
+                };
+                tokio::select! {
+                    _ = &mut stop_rx => break,
+                    _ = handler.meta_node.clock().sleep(wait) => {}
+                }
+
+                let conf = handler.config.get();
+                if conf.staging_vacuum_interval_secs == 0 || conf.read_only {
+                    continue;
+                }
+                let max_age = Duration::from_secs(conf.staged_file_max_age_secs);
+                match handler.vacuum_staged_files(max_age).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("removed {} staged part files", n),
+                    Err(e) => tracing::warn!("failed to remove staged part files: {}", e),
+                }
+                match handler.vacuum_unregistered_files(max_age).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("removed {} unregistered part files", n),
+                    Err(e) => tracing::warn!("failed to remove unregistered part files: {}", e),
+                }
+                match handler.vacuum_retired_part_files().await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("removed {} compacted part files", n),
+                    Err(e) => tracing::warn!("failed to remove compacted part files: {}", e),
+                }
+            }
+            tracing::info!("staging vacuum is stopped");
+        });
+
+        Arc::new(StagingVacuum {
+            stop_tx: Mutex::new(Some(stop_tx)),
+            join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+
+    pub async fn shutdown(&self) {
+        if let Some(stop_tx) = self.stop_tx.lock().take() {
+            let _ = stop_tx.send(());
+        }
+
+        let join_handle = self.join_handle.lock().take();
+        if let Some(join_handle) = join_handle {
+            if let Err(e) = join_handle.await {
+                tracing::error!("staging vacuum task failed: {:?}", e);
+            }
+        }
+    }
+}
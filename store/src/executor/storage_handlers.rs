@@ -13,22 +13,70 @@
 // limitations under the License.
 //
 
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_arrow::arrow::io::ipc::write::common::IpcWriteOptions;
+use common_arrow::arrow::record_batch::RecordBatch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_batch;
+use common_arrow::arrow_flight::utils::flight_data_from_arrow_schema;
+use common_datablocks::DataBlock;
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataSchemaRefExt;
 use common_exception::ErrorCode;
+use common_metatypes::MatchSeq;
+use common_planners::CreateTablePlan;
+use common_planners::Part;
+use common_planners::PlanNode;
+use common_planners::ReadDataSourcePlan;
+use common_planners::ScanPlan;
+use common_planners::Statistics;
+use common_store_api_sdk::kv_api_impl::UpsertKVAction;
+use common_store_api_sdk::meta_api_impl::CreateTableAction;
+use common_store_api_sdk::storage_api_impl::CopyTableAction;
+use common_store_api_sdk::storage_api_impl::CopyTableResult;
+use common_store_api_sdk::storage_api_impl::DataPartInfo;
+use common_store_api_sdk::storage_api_impl::GetTableAccessStatsAction;
+use common_store_api_sdk::storage_api_impl::OptimizeTableAction;
+use common_store_api_sdk::storage_api_impl::OptimizeTableResult;
+use common_store_api_sdk::storage_api_impl::PartStorageClass;
+use common_store_api_sdk::storage_api_impl::PartsPruning;
+use common_store_api_sdk::storage_api_impl::ReadAction;
 use common_store_api_sdk::storage_api_impl::ReadPlanAction;
-use common_store_api_sdk::storage_api_impl::ReadPlanResult;
+use common_store_api_sdk::storage_api_impl::ReadPlanReply;
+use common_store_api_sdk::storage_api_impl::StorageApi;
+use common_store_api_sdk::storage_api_impl::TableAccessStats;
 use common_store_api_sdk::storage_api_impl::TruncateTableAction;
 use common_store_api_sdk::storage_api_impl::TruncateTableResult;
+use common_store_api_sdk::MetaApi;
+use common_store_api_sdk::StoreClient;
+use futures::TryStreamExt;
 use log::debug;
+use log::info;
+use log::warn;
 use metasrv::meta_service::Cmd;
 use metasrv::meta_service::LogEntry;
 use metasrv::raft::state_machine::AppliedState;
 
+use crate::data_part::column_stats::RangePredicates;
 use crate::executor::action_handler::RequestHandler;
+use crate::executor::apply_queue::Mutation;
 use crate::executor::ActionHandler;
+use crate::fs::FileSystem;
+
+/// The parts of a table copied so far are kept in the meta kv, under
+/// `<prefix>/<db>/<table>/<part of the source>`, until the copy is done.
+const COPY_TABLE_PROGRESS_PREFIX: &str = "__fd_copy_table";
+
+/// How long a read plan waits for the table to reach the data version it asks for, and how
+/// often the version is checked meanwhile.
+const MIN_DATA_VERSION_WAIT: Duration = Duration::from_secs(1);
+const MIN_DATA_VERSION_POLL: Duration = Duration::from_millis(10);
 
 #[async_trait::async_trait]
 impl RequestHandler<ReadPlanAction> for ActionHandler {
-    async fn handle(&self, act: ReadPlanAction) -> common_exception::Result<ReadPlanResult> {
+    async fn handle(&self, act: ReadPlanAction) -> common_exception::Result<ReadPlanReply> {
         let schema = &act.scan_plan.schema_name;
         let splits: Vec<&str> = schema.split('/').collect();
         // TODO error handling
@@ -36,7 +84,174 @@ impl RequestHandler<ReadPlanAction> for ActionHandler {
         let db_name = splits[0];
         let tbl_name = splits[1];
 
-        Ok(self.meta_node.get_data_parts(db_name, tbl_name).await)
+        let projection = self
+            .projected_schema(db_name, tbl_name, &act.projection)
+            .await?;
+
+        // The parts of an external table are its files, their rows are unknown until they are parsed.
+        if let Some((table, _)) = self.get_external_table(db_name, tbl_name).await? {
+            let parts = table
+                .list_files()?
+                .into_iter()
+                .map(|(path, size)| DataPartInfo {
+                    part: Part {
+                        name: path.display().to_string(),
+                        version: 0,
+                        read_info: None,
+                    },
+                    stats: Statistics::new_estimated(0, size as usize),
+                    format: None,
+                    bloom_filters: None,
+                    storage: PartStorageClass::File,
+                    checksum: None,
+                    column_statistics: None,
+                })
+                .collect::<Vec<_>>();
+            return Ok(ReadPlanReply {
+                pruning: PartsPruning {
+                    total: parts.len(),
+                    ..Default::default()
+                },
+                parts: Some(parts),
+                data_version: 0,
+                projected_schema: projection.map(|(schema, _)| schema),
+            });
+        }
+
+        // The version is taken before the parts, the parts are of at least this version.
+        let data_version = self
+            .wait_data_version(db_name, tbl_name, act.min_data_version)
+            .await?;
+        let mut parts = self.meta_node.get_data_parts(db_name, tbl_name).await;
+        let filters = &act.scan_plan.push_downs.filters;
+        let (parts, mut pruning) = match self.get_bloom_index(db_name, tbl_name).await? {
+            Some(bloom_index) => {
+                if bloom_index.has_lookups(filters) {
+                    if let Some(parts) = parts.as_mut() {
+                        self.meta_node.load_bloom_filters(parts).await?;
+                    }
+                }
+                bloom_index.prune(filters, parts)
+            }
+            None => {
+                let total = parts.as_ref().map(|parts| parts.len()).unwrap_or_default();
+                let pruning = PartsPruning {
+                    total,
+                    ..Default::default()
+                };
+                (parts, pruning)
+            }
+        };
+        let parts = RangePredicates::from_filters(filters).prune(parts, &mut pruning);
+
+        // The filters are kept by the store, the query nodes have no use of them.
+        let parts = match parts {
+            None => None,
+            Some(parts) => {
+                let mut projected_parts = Vec::with_capacity(parts.len());
+                for part in parts {
+                    let stats = match &projection {
+                        None => part.stats.clone(),
+                        Some((schema, columns)) => {
+                            self.projected_stats(&part, schema, *columns).await
+                        }
+                    };
+                    projected_parts.push(DataPartInfo {
+                        bloom_filters: None,
+                        stats,
+                        ..part
+                    });
+                }
+                Some(projected_parts)
+            }
+        };
+        Ok(ReadPlanReply {
+            parts,
+            pruning,
+            data_version,
+            projected_schema: projection.map(|(schema, _)| schema),
+        })
+    }
+}
+
+impl ActionHandler {
+    /// Returns the schema of the named columns, in the order of the table, with the number of
+    /// the columns of the table. `None` if `projection` is empty, e.g. all the columns are read.
+    async fn projected_schema(
+        &self,
+        db_name: &str,
+        tbl_name: &str,
+        projection: &[String],
+    ) -> common_exception::Result<Option<(DataSchemaRef, usize)>> {
+        if projection.is_empty() {
+            return Ok(None);
+        }
+
+        let (_, schema) = self
+            .get_table_with_schema(db_name, tbl_name)
+            .await?
+            .ok_or_else(|| {
+                ErrorCode::UnknownTable(format!("table not found: {}.{}", db_name, tbl_name))
+            })?;
+        if let Some(name) = projection
+            .iter()
+            .find(|name| schema.column_with_name(name).is_none())
+        {
+            return Err(ErrorCode::UnknownColumn(format!(
+                "column not found: {} of {}.{}",
+                name, db_name, tbl_name
+            )));
+        }
+
+        let fields = schema
+            .fields()
+            .iter()
+            .filter(|f| projection.contains(f.name()))
+            .cloned()
+            .collect::<Vec<_>>();
+        Ok(Some((
+            DataSchemaRefExt::create(fields),
+            schema.fields().len(),
+        )))
+    }
+
+    /// The statistics of a part when only the columns of `projected` are read, out of the
+    /// `columns` of its table. The bytes read are the sizes of the column chunks recorded in the
+    /// part; if its format keeps none, they are estimated as the share of the columns read.
+    async fn projected_stats(
+        &self,
+        part: &DataPartInfo,
+        projected: &DataSchemaRef,
+        columns: usize,
+    ) -> Statistics {
+        let stats = &part.stats;
+        if columns == 0 || projected.fields().len() >= columns {
+            return stats.clone();
+        }
+
+        match self.read_part_column_bytes(part).await {
+            Ok(Some(column_bytes)) => {
+                let read_bytes = projected
+                    .fields()
+                    .iter()
+                    .filter_map(|f| column_bytes.get(f.name()))
+                    .sum::<u64>();
+                return Statistics {
+                    read_bytes: read_bytes as usize,
+                    ..stats.clone()
+                };
+            }
+            Ok(None) => {}
+            Err(e) => warn!(
+                "failed to read the column sizes of part {}, estimating them: {}",
+                part.part.name, e
+            ),
+        }
+        Statistics {
+            read_bytes: stats.read_bytes * projected.fields().len() / columns,
+            is_exact: false,
+            ..stats.clone()
+        }
     }
 }
 
@@ -57,6 +272,12 @@ impl RequestHandler<TruncateTableAction> for ActionHandler {
             .get(tbl_name)
             .ok_or_else(|| ErrorCode::UnknownTable(format!("table not found: {:}", tbl_name)))?;
 
+        let parts = self
+            .meta_node
+            .get_data_parts(db_name, tbl_name)
+            .await
+            .unwrap_or_default();
+
         let cr = LogEntry {
             txid: None,
             cmd: Cmd::TruncateTable {
@@ -66,10 +287,11 @@ impl RequestHandler<TruncateTableAction> for ActionHandler {
         };
 
         let rst = self
-            .meta_node
-            .write(cr)
+            .apply_queue
+            .apply(Mutation::Write(cr))
             .await
             .map_err(|e| ErrorCode::MetaNodeInternalError(e.to_string()))?;
+        self.report_usage(db_name).await;
 
         match rst {
             AppliedState::DataPartsCount { prev, result } => {
@@ -77,6 +299,7 @@ impl RequestHandler<TruncateTableAction> for ActionHandler {
                     // only success when prev >= 0 and result == 0
                     if let Some(result) = result {
                         if result == 0 {
+                            self.remove_part_files(&parts).await;
                             Ok(TruncateTableResult {
                                 truncated_table_data_parts_count: prev,
                             })
@@ -103,3 +326,263 @@ impl RequestHandler<TruncateTableAction> for ActionHandler {
         }
     }
 }
+
+#[async_trait::async_trait]
+impl RequestHandler<OptimizeTableAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: OptimizeTableAction,
+    ) -> common_exception::Result<OptimizeTableResult> {
+        let res = self.compact_table(&act.db, &act.table).await?;
+        info!("optimize {}.{}: {:?}", act.db, act.table, res);
+        Ok(res)
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<GetTableAccessStatsAction> for ActionHandler {
+    async fn handle(
+        &self,
+        act: GetTableAccessStatsAction,
+    ) -> common_exception::Result<TableAccessStats> {
+        Ok(self
+            .access_recorder
+            .get(&act.db, &act.table, act.window_secs))
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestHandler<CopyTableAction> for ActionHandler {
+    async fn handle(&self, act: CopyTableAction) -> common_exception::Result<CopyTableResult> {
+        let db_name = &act.db;
+        let tbl_name = &act.table;
+        let source = StoreClient::try_create(
+            &act.source.address,
+            &act.source.username,
+            &act.source.password,
+        )
+        .await?;
+        let src_table = source.get_table(db_name.clone(), tbl_name.clone()).await?;
+
+        // The parts copied by an interrupted run, if the table is not dropped since then.
+        let progress_prefix = format!("{}/{}/{}/", COPY_TABLE_PROGRESS_PREFIX, db_name, tbl_name);
+        let exists = self
+            .get_table_with_schema(db_name, tbl_name)
+            .await?
+            .is_some();
+        let copied = if exists {
+            self.meta_node
+                .prefix_list_kv(&progress_prefix)
+                .await?
+                .into_iter()
+                .map(|(key, _)| key[progress_prefix.len()..].to_string())
+                .collect::<HashSet<_>>()
+        } else {
+            HashSet::new()
+        };
+
+        if !exists {
+            let plan = CreateTablePlan {
+                if_not_exists: false,
+                db: db_name.clone(),
+                table: tbl_name.clone(),
+                schema: src_table.schema.clone(),
+                engine: src_table.engine.clone(),
+                options: src_table.options.clone(),
+            };
+            self.handle(CreateTableAction {
+                plan,
+                request_id: None,
+                seq: None,
+            })
+            .await?;
+        } else if !copied.is_empty() {
+            info!(
+                "resume copying {}.{}, {} parts copied",
+                db_name,
+                tbl_name,
+                copied.len()
+            );
+        } else {
+            // An empty table, e.g., created ahead of the copy, is copied into.
+            let local_parts = self
+                .meta_node
+                .get_data_parts(db_name, tbl_name)
+                .await
+                .unwrap_or_default();
+            if !local_parts.is_empty() || !act.overwrite_empty {
+                return Err(ErrorCode::TableAlreadyExists(format!(
+                    "table exists: {}",
+                    tbl_name
+                )));
+            }
+        }
+
+        let scan_plan = ScanPlan {
+            schema_name: tbl_name.clone(),
+            ..ScanPlan::empty()
+        };
+        let parts = source
+            .read_plan(db_name.clone(), tbl_name.clone(), &scan_plan)
+            .await?
+            .unwrap_or_default();
+
+        let mut res = CopyTableResult::default();
+        for (i, part) in parts.iter().enumerate() {
+            if copied.contains(&part.part.name) {
+                res.skipped_parts += 1;
+                continue;
+            }
+
+            // A part is appended along with its progress marker or not at all, it is copied
+            // again on resuming.
+            let marker = format!("{}{}", progress_prefix, part.part.name);
+            self.copy_part(&source, db_name, tbl_name, &src_table.schema, part, marker)
+                .await?;
+            res.copied_parts += 1;
+            info!(
+                "copy {}.{}: {}/{} parts copied",
+                db_name,
+                tbl_name,
+                i + 1,
+                parts.len()
+            );
+        }
+
+        let src_rows = rows_of(&parts);
+        let dst_parts = self
+            .meta_node
+            .get_data_parts(db_name, tbl_name)
+            .await
+            .unwrap_or_default();
+        let dst_rows = rows_of(&dst_parts);
+        if src_rows != dst_rows {
+            return Err(ErrorCode::CopyTableMismatch(format!(
+                "copy {}.{}: {} rows at the source, {} rows copied",
+                db_name, tbl_name, src_rows, dst_rows
+            )));
+        }
+        res.rows = dst_rows;
+
+        // Done, a later copy of the table starts over.
+        for part in parts.iter() {
+            self.handle(UpsertKVAction {
+                key: format!("{}{}", progress_prefix, part.part.name),
+                seq: MatchSeq::Any,
+                value: None,
+                value_meta: None,
+                return_value_on_conflict: true,
+            })
+            .await?;
+        }
+
+        info!(
+            "copy {}.{} from {}: {:?}",
+            db_name, tbl_name, act.source.address, res
+        );
+        Ok(res)
+    }
+}
+
+impl ActionHandler {
+    /// Deletes the files of the parts removed from the meta, a file that fails to be deleted is
+    /// left behind without failing the request.
+    async fn remove_part_files(&self, parts: &[DataPartInfo]) {
+        Self::remove_files_of_parts(self.fs.clone(), parts).await
+    }
+
+    pub(crate) async fn remove_files_of_parts(fs: Arc<dyn FileSystem>, parts: &[DataPartInfo]) {
+        for part in parts {
+            if part.storage != PartStorageClass::File {
+                continue;
+            }
+            if let Err(e) = fs.remove(&part.part.name).await {
+                warn!("fail to remove part file {}: {}", part.part.name, e);
+            }
+        }
+    }
+
+    /// Waits briefly for the data version of the table to reach `min_data_version`, e.g. an
+    /// append still in the apply queue, and returns the version reached.
+    async fn wait_data_version(
+        &self,
+        db_name: &str,
+        tbl_name: &str,
+        min_data_version: u64,
+    ) -> common_exception::Result<u64> {
+        let clock = self.meta_node.clock();
+        let deadline = clock.instant() + MIN_DATA_VERSION_WAIT;
+        loop {
+            // A table not found is not waited for, there are no parts to read.
+            let data_version = match self
+                .meta_node
+                .get_table_data_version(db_name, tbl_name)
+                .await
+            {
+                None => return Ok(0),
+                Some(data_version) => data_version,
+            };
+            if data_version >= min_data_version {
+                return Ok(data_version);
+            }
+            if clock.instant() >= deadline {
+                return Err(ErrorCode::StaleRead(format!(
+                    "read plan of {}.{}: data version {} has not reached {} in {:?}",
+                    db_name, tbl_name, data_version, min_data_version, MIN_DATA_VERSION_WAIT
+                )));
+            }
+            clock.sleep(MIN_DATA_VERSION_POLL).await;
+        }
+    }
+
+    /// Reads a part from the source store and appends it to the local table, along with the
+    /// progress `marker` of the copy.
+    async fn copy_part(
+        &self,
+        source: &StoreClient,
+        db_name: &str,
+        tbl_name: &str,
+        schema: &DataSchemaRef,
+        part: &DataPartInfo,
+        marker: String,
+    ) -> common_exception::Result<()> {
+        let action = ReadAction {
+            part: Part {
+                read_info: Some(part.read_info()),
+                ..part.part.clone()
+            },
+            push_down: PlanNode::ReadSource(ReadDataSourcePlan {
+                db: db_name.to_string(),
+                table: tbl_name.to_string(),
+                schema: schema.clone(),
+                ..ReadDataSourcePlan::empty(0, None)
+            }),
+        };
+        let blocks: Vec<DataBlock> = source
+            .read_partition(schema.clone(), &action)
+            .await?
+            .try_collect()
+            .await?;
+
+        let options = IpcWriteOptions::default();
+        let mut flights = vec![flight_data_from_arrow_schema(&schema.to_arrow(), &options)];
+        for block in blocks {
+            let batch = RecordBatch::try_from(block)?;
+            flights.push(flight_data_from_arrow_batch(&batch, &options).1);
+        }
+
+        let flights = futures::stream::iter(flights.into_iter().map(Ok));
+        self.do_put_with_marker(
+            db_name.to_string(),
+            tbl_name.to_string(),
+            flights,
+            Some(marker),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+fn rows_of(parts: &[DataPartInfo]) -> usize {
+    parts.iter().map(|part| part.stats.read_rows).sum()
+}
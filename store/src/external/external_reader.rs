@@ -0,0 +1,255 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+
+use common_arrow::arrow::io::csv::read::ByteRecord;
+use common_arrow::arrow::io::csv::read::Reader as CsvReader;
+use common_arrow::arrow::io::csv::read::ReaderBuilder;
+use common_datablocks::DataBlock;
+use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+
+use crate::external::external_table::ExternalFormat;
+use crate::external::ExternalTable;
+
+/// The fields of a row as text, None is a null, or the reason why the row can not be parsed.
+type Row = Result<Vec<Option<Vec<u8>>>, String>;
+
+/// The rows of a file in one of the formats.
+enum Rows {
+    Csv(CsvReader<BufReader<File>>),
+    NdJson(BufReader<File>),
+}
+
+/// ExternalReader parses a file of an external table into blocks of the table's schema.
+///
+/// The text of a field is converted to the type of its column. A row that can not be parsed,
+/// e.g. it has a wrong number of fields or a field of a wrong type, is skipped,
+/// until there are more of them than the `max_bad_rows` of the table.
+pub struct ExternalReader {
+    file: String,
+    rows: Rows,
+    schema: DataSchemaRef,
+    block_size: usize,
+    /// The line number of the next row of an NDJSON file, a CSV reader counts the lines itself.
+    line: u64,
+    bad_rows: u64,
+    max_bad_rows: u64,
+    done: bool,
+}
+
+impl ExternalReader {
+    pub fn try_create(
+        table: &ExternalTable,
+        path: &Path,
+        schema: DataSchemaRef,
+        block_size: usize,
+    ) -> common_exception::Result<ExternalReader> {
+        let f = File::open(path).map_err(|e| {
+            ErrorCode::ReadFileError(format!("can not read {}: {}", path.display(), e))
+        })?;
+        let f = BufReader::new(f);
+
+        let rows = match table.format {
+            ExternalFormat::Csv {
+                delimiter,
+                has_header,
+            } => Rows::Csv(
+                ReaderBuilder::new()
+                    .delimiter(delimiter)
+                    .has_headers(has_header)
+                    .flexible(true)
+                    .from_reader(f),
+            ),
+            ExternalFormat::NdJson => Rows::NdJson(f),
+        };
+
+        Ok(ExternalReader {
+            file: path.display().to_string(),
+            rows,
+            schema,
+            block_size,
+            line: 1,
+            bad_rows: 0,
+            max_bad_rows: table.max_bad_rows,
+            done: false,
+        })
+    }
+
+    /// Returns the next row and its line number, None at the end of the file.
+    fn next_row(&mut self) -> common_exception::Result<Option<(u64, Row)>> {
+        let columns = self.schema.fields().len();
+        let read_err = |e: String| ErrorCode::ReadFileError(format!("can not read {}", e));
+
+        loop {
+            let line = self.line;
+            self.line += 1;
+
+            match &mut self.rows {
+                Rows::Csv(reader) => {
+                    let mut record = ByteRecord::new();
+                    let more = reader
+                        .read_byte_record(&mut record)
+                        .map_err(|e| read_err(format!("{} at line {}: {}", self.file, line, e)))?;
+                    if !more {
+                        return Ok(None);
+                    }
+                    // A quoted field may span lines.
+                    let line = record.position().map(|p| p.line()).unwrap_or(line);
+                    if record.len() != columns {
+                        let reason = format!("{} fields, expect {}", record.len(), columns);
+                        return Ok(Some((line, Err(reason))));
+                    }
+                    let fields = record.iter().map(|f| Some(f.to_vec())).collect();
+                    return Ok(Some((line, Ok(fields))));
+                }
+                Rows::NdJson(reader) => {
+                    let mut buf = String::new();
+                    let n = reader
+                        .read_line(&mut buf)
+                        .map_err(|e| read_err(format!("{} at line {}: {}", self.file, line, e)))?;
+                    if n == 0 {
+                        return Ok(None);
+                    }
+                    if buf.trim().is_empty() {
+                        continue;
+                    }
+                    return Ok(Some((line, self.parse_json_row(&buf))));
+                }
+            }
+        }
+    }
+
+    /// Takes the fields of a JSON object by the column names, a missing one is a null.
+    fn parse_json_row(&self, buf: &str) -> Row {
+        let value: serde_json::Value = serde_json::from_str(buf).map_err(|e| e.to_string())?;
+        let obj = value
+            .as_object()
+            .ok_or_else(|| "not a JSON object".to_string())?;
+
+        let fields = self
+            .schema
+            .fields()
+            .iter()
+            .map(|f| match obj.get(f.name()) {
+                None | Some(serde_json::Value::Null) => None,
+                Some(serde_json::Value::String(s)) => Some(s.as_bytes().to_vec()),
+                Some(v) => Some(v.to_string().into_bytes()),
+            })
+            .collect();
+        Ok(fields)
+    }
+
+    /// Marks the rows with a field that can not be converted to the type of its column.
+    fn check_types(&self, rows: &mut [(u64, Row)]) -> common_exception::Result<()> {
+        for (col, field) in self.schema.fields().iter().enumerate() {
+            let mut probe = field.data_type().create_serializer(rows.len())?;
+            for (_, row) in rows.iter_mut() {
+                let bytes = match row {
+                    Ok(fields) => match &fields[col] {
+                        Some(bytes) => bytes,
+                        None => continue,
+                    },
+                    Err(_) => continue,
+                };
+                if let Err(e) = probe.de_text(bytes) {
+                    *row = Err(format!("column {}: {}", field.name(), e.message()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_block(&mut self) -> common_exception::Result<Option<DataBlock>> {
+        let mut rows = vec![];
+        while rows.len() < self.block_size {
+            match self.next_row()? {
+                None => break,
+                Some(row) => rows.push(row),
+            }
+        }
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        self.check_types(&mut rows)?;
+
+        let mut desers = self
+            .schema
+            .fields()
+            .iter()
+            .map(|f| f.data_type().create_serializer(rows.len()))
+            .collect::<common_exception::Result<Vec<_>>>()?;
+
+        for (line, row) in rows {
+            let fields = match row {
+                Ok(fields) => fields,
+                Err(reason) => {
+                    self.bad_rows += 1;
+                    if self.bad_rows > self.max_bad_rows {
+                        return Err(ErrorCode::BadBytes(format!(
+                            "{} at line {}: {}, more than {} bad rows",
+                            self.file, line, reason, self.max_bad_rows
+                        )));
+                    }
+                    log::warn!(
+                        "skip a bad row of {} at line {}: {}",
+                        self.file,
+                        line,
+                        reason
+                    );
+                    continue;
+                }
+            };
+
+            for (deser, field) in desers.iter_mut().zip(fields.iter()) {
+                match field {
+                    Some(bytes) => deser.de_text(bytes)?,
+                    None => deser.de_null(),
+                }
+            }
+        }
+
+        let series = desers
+            .iter_mut()
+            .map(|deser| deser.finish_to_series())
+            .collect::<Vec<_>>();
+        Ok(Some(DataBlock::create_by_array(
+            self.schema.clone(),
+            series,
+        )))
+    }
+}
+
+impl Iterator for ExternalReader {
+    type Item = common_exception::Result<DataBlock>;
+
+    /// Returns the next block, a block may have less than `block_size` rows if some are skipped.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.read_block().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
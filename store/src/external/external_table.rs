@@ -0,0 +1,243 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+use common_planners::get_table_option;
+use common_planners::TableOptions;
+
+use crate::external::external_reader::ExternalReader;
+
+/// The engines of the tables over the files in a directory the store can read, e.g. exported logs.
+/// The files are read in place, thus such a table does not accept appends.
+pub const EXTERNAL_ENGINES: [&str; 2] = ["CSV", "NDJSON"];
+
+/// The rows parsed to validate the files when a table is created.
+const SAMPLE_ROWS: usize = 100;
+
+pub fn is_external_engine(engine: &str) -> bool {
+    EXTERNAL_ENGINES
+        .iter()
+        .any(|e| e.eq_ignore_ascii_case(engine))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExternalFormat {
+    Csv { delimiter: u8, has_header: bool },
+    NdJson,
+}
+
+/// A table over the files in a directory, built from the engine and the options of the table:
+///
+/// - `path`: a file, a directory, or a file name pattern in a directory, e.g. `/data/logs/*.csv`,
+///   `*` and `?` are the wildcards. It must be in one of the `external_data_dirs` of the store.
+/// - `delimiter`: the field delimiter of a CSV file, `,` by default, `\t` for a tab.
+/// - `header`: whether the first line of a CSV file is the header, `false` by default.
+/// - `max_bad_rows`: the max number of the rows that can not be parsed, they are skipped.
+///   The read fails on the next one. 0 by default.
+#[derive(Clone, Debug)]
+pub struct ExternalTable {
+    pub format: ExternalFormat,
+    /// The canonical path of the directory of the files.
+    pub dir: PathBuf,
+    /// The pattern of the names of the files in `dir`.
+    pub pattern: String,
+    pub max_bad_rows: u64,
+}
+
+impl ExternalTable {
+    pub fn try_create(
+        engine: &str,
+        options: &TableOptions,
+        allowed_dirs: &[PathBuf],
+    ) -> common_exception::Result<ExternalTable> {
+        let format = if engine.eq_ignore_ascii_case("NDJSON") {
+            ExternalFormat::NdJson
+        } else if engine.eq_ignore_ascii_case("CSV") {
+            ExternalFormat::Csv {
+                delimiter: parse_delimiter(get_table_option(options, "delimiter"))?,
+                has_header: parse_bool("header", get_table_option(options, "header"))?,
+            }
+        } else {
+            return Err(ErrorCode::UnknownTableEngine(format!(
+                "{} is not an external engine, expect one of {:?}",
+                engine, EXTERNAL_ENGINES
+            )));
+        };
+
+        let max_bad_rows = match get_table_option(options, "max_bad_rows") {
+            None => 0,
+            Some(v) => v.parse::<u64>().map_err(|_| {
+                ErrorCode::BadOption(format!(
+                    "max_bad_rows must be a non-negative integer, got: {}",
+                    v
+                ))
+            })?,
+        };
+
+        let path = get_table_option(options, "path").ok_or_else(|| {
+            ErrorCode::BadOption(format!(
+                "{} engine must contains the path option",
+                engine.to_uppercase()
+            ))
+        })?;
+        let (dir, pattern) = split_path(Path::new(path))?;
+
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| ErrorCode::BadOption(format!("can not read {}: {}", path, e)))?;
+        let allowed = allowed_dirs
+            .iter()
+            .filter_map(|d| d.canonicalize().ok())
+            .any(|d| dir.starts_with(d));
+        if !allowed {
+            return Err(ErrorCode::BadOption(format!(
+                "{} is not in the external data dirs of the store: {:?}",
+                path, allowed_dirs
+            )));
+        }
+
+        Ok(ExternalTable {
+            format,
+            dir,
+            pattern,
+            max_bad_rows,
+        })
+    }
+
+    /// Returns the files of the table and their sizes, ordered by name.
+    pub fn list_files(&self) -> common_exception::Result<Vec<(PathBuf, u64)>> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            ErrorCode::ReadFileError(format!("can not list {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut files = vec![];
+        for entry in entries {
+            let entry = entry.map_err(|e| {
+                ErrorCode::ReadFileError(format!("can not list {}: {}", self.dir.display(), e))
+            })?;
+            let path = entry.path();
+            if !self.contains(&path) {
+                continue;
+            }
+            let meta = entry.metadata().map_err(|e| {
+                ErrorCode::ReadFileError(format!("can not read {}: {}", path.display(), e))
+            })?;
+            if meta.is_file() {
+                files.push((path, meta.len()));
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Whether `path` is one of the files of the table, i.e. a file in `dir` matching the pattern.
+    pub fn contains(&self, path: &Path) -> bool {
+        if path.parent() != Some(self.dir.as_path()) {
+            return false;
+        }
+        match path.file_name().and_then(|name| name.to_str()) {
+            None => false,
+            Some(name) => wildcard_match(self.pattern.as_bytes(), name.as_bytes()),
+        }
+    }
+
+    pub fn reader(
+        &self,
+        path: &Path,
+        schema: DataSchemaRef,
+        block_size: usize,
+    ) -> common_exception::Result<ExternalReader> {
+        ExternalReader::try_create(self, path, schema, block_size)
+    }
+
+    /// Checks that there are files to read, and that the first rows of each file match `schema`.
+    pub fn validate(&self, schema: DataSchemaRef) -> common_exception::Result<()> {
+        let files = self.list_files()?;
+        if files.is_empty() {
+            return Err(ErrorCode::BadOption(format!(
+                "no file matches {} in {}",
+                self.pattern,
+                self.dir.display()
+            )));
+        }
+
+        for (path, _) in files {
+            let mut reader = self.reader(&path, schema.clone(), SAMPLE_ROWS)?;
+            reader.next().transpose()?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a `path` option into the directory and the pattern of the file names.
+fn split_path(path: &Path) -> common_exception::Result<(PathBuf, String)> {
+    if path.is_dir() {
+        return Ok((path.to_path_buf(), "*".to_string()));
+    }
+
+    let name = path.file_name().and_then(|name| name.to_str());
+    match (path.parent(), name) {
+        (Some(dir), Some(name)) if !dir.as_os_str().is_empty() => {
+            Ok((dir.to_path_buf(), name.to_string()))
+        }
+        _ => Err(ErrorCode::BadOption(format!(
+            "path must be an absolute file, directory or file name pattern, got: {}",
+            path.display()
+        ))),
+    }
+}
+
+fn parse_delimiter(v: Option<&String>) -> common_exception::Result<u8> {
+    match v.map(|v| v.as_str()) {
+        None => Ok(b','),
+        Some("\\t") | Some("\t") => Ok(b'\t'),
+        Some(v) if v.len() == 1 && v.is_ascii() => Ok(v.as_bytes()[0]),
+        Some(v) => Err(ErrorCode::BadOption(format!(
+            "delimiter must be a single ascii char, got: {:?}",
+            v
+        ))),
+    }
+}
+
+fn parse_bool(name: &str, v: Option<&String>) -> common_exception::Result<bool> {
+    match v.map(|v| v.to_lowercase()) {
+        None => Ok(false),
+        Some(v) if v == "true" || v == "1" => Ok(true),
+        Some(v) if v == "false" || v == "0" => Ok(false),
+        Some(v) => Err(ErrorCode::BadOption(format!(
+            "{} must be true or false, got: {}",
+            name, v
+        ))),
+    }
+}
+
+/// Matches a file name against a pattern, `*` matches any chars and `?` matches one char.
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            wildcard_match(&pattern[1..], name)
+                || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
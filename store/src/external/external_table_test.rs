@@ -0,0 +1,349 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use common_datablocks::assert_blocks_eq;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_planners::TableOptions;
+use common_tracing::tracing;
+use pretty_assertions::assert_eq;
+
+use crate::external::external_table::ExternalFormat;
+use crate::external::ExternalTable;
+
+fn schema() -> DataSchemaRef {
+    DataSchemaRefExt::create(vec![
+        DataField::new("id", DataType::Int64, false),
+        DataField::new("name", DataType::String, true),
+        DataField::new("score", DataType::Float64, true),
+    ])
+}
+
+fn options(kvs: &[(&str, &str)]) -> TableOptions {
+    kvs.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>()
+}
+
+fn write(dir: &Path, name: &str, content: &str) -> anyhow::Result<PathBuf> {
+    let path = dir.join(name);
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+fn read_all(table: &ExternalTable, path: &Path) -> common_exception::Result<Vec<DataBlock>> {
+    table.reader(path, schema(), 2)?.collect()
+}
+
+#[test]
+fn test_external_table_options() -> anyhow::Result<()> {
+    let allowed = tempfile::tempdir()?;
+    let other = tempfile::tempdir()?;
+    let allowed_dirs = vec![allowed.path().to_path_buf()];
+    let path = |dir: &Path, name: &str| dir.join(name).display().to_string();
+
+    write(allowed.path(), "a.csv", "1,x,1.5\n")?;
+    write(allowed.path(), "b.csv", "2,y,2.5\n3,z,3.5\n")?;
+    write(allowed.path(), "c.json", "{}\n")?;
+    write(other.path(), "a.csv", "1,x,1.5\n")?;
+
+    let create = |engine: &str, kvs: &[(&str, &str)]| {
+        ExternalTable::try_create(engine, &options(kvs), &allowed_dirs)
+    };
+
+    tracing::info!("--- a dir, a pattern or a file");
+    {
+        let dir = allowed.path().display().to_string();
+        let table = create("csv", &[("path", dir.as_str())])?;
+        assert_eq!(3, table.list_files()?.len());
+
+        let table = create("CSV", &[("PATH", path(allowed.path(), "*.csv").as_str())])?;
+        let files = table.list_files()?;
+        assert_eq!(
+            vec![
+                (allowed.path().canonicalize()?.join("a.csv"), 8),
+                (allowed.path().canonicalize()?.join("b.csv"), 16),
+            ],
+            files
+        );
+        assert!(!table.contains(&allowed.path().canonicalize()?.join("c.json")));
+        assert!(!table.contains(&other.path().canonicalize()?.join("a.csv")));
+
+        let table = create("CSV", &[("path", path(allowed.path(), "?.csv").as_str())])?;
+        assert_eq!(2, table.list_files()?.len());
+
+        let table = create("NDJSON", &[(
+            "path",
+            path(allowed.path(), "c.json").as_str(),
+        )])?;
+        assert_eq!(ExternalFormat::NdJson, table.format);
+        assert_eq!(1, table.list_files()?.len());
+    }
+
+    tracing::info!("--- csv options");
+    {
+        let p = path(allowed.path(), "a.csv");
+        let table = create("CSV", &[("path", p.as_str())])?;
+        assert_eq!(
+            ExternalFormat::Csv {
+                delimiter: b',',
+                has_header: false
+            },
+            table.format
+        );
+        assert_eq!(0, table.max_bad_rows);
+
+        let table = create("CSV", &[
+            ("path", p.as_str()),
+            ("delimiter", "\\t"),
+            ("header", "TRUE"),
+            ("max_bad_rows", "3"),
+        ])?;
+        assert_eq!(
+            ExternalFormat::Csv {
+                delimiter: b'\t',
+                has_header: true
+            },
+            table.format
+        );
+        assert_eq!(3, table.max_bad_rows);
+    }
+
+    tracing::info!("--- invalid options");
+    {
+        let p = path(allowed.path(), "a.csv");
+        let cases: Vec<(&str, Vec<(&str, &str)>, &str)> = vec![
+            ("CSV", vec![], "CSV engine must contains the path option"),
+            (
+                "CSV",
+                vec![("path", p.as_str()), ("delimiter", ";;")],
+                "delimiter",
+            ),
+            (
+                "CSV",
+                vec![("path", p.as_str()), ("header", "yes")],
+                "header",
+            ),
+            (
+                "CSV",
+                vec![("path", p.as_str()), ("max_bad_rows", "-1")],
+                "max_bad_rows",
+            ),
+            ("CSV", vec![("path", "a.csv")], "absolute"),
+            ("CSV", vec![("path", "/nonexistent/a.csv")], "can not read"),
+        ];
+        for (engine, kvs, want) in cases {
+            let err = create(engine, &kvs).unwrap_err();
+            assert_eq!(ErrorCode::BadOption("").code(), err.code(), "{:?}", kvs);
+            assert!(err.message().contains(want), "{:?}: {}", kvs, err);
+        }
+
+        let err = create("CSV", &[("path", path(other.path(), "a.csv").as_str())]).unwrap_err();
+        assert!(
+            err.message().contains("not in the external data dirs"),
+            "{}",
+            err
+        );
+
+        let err =
+            ExternalTable::try_create("CSV", &options(&[("path", p.as_str())]), &[]).unwrap_err();
+        assert!(
+            err.message().contains("not in the external data dirs"),
+            "{}",
+            err
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_external_reader_csv() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let allowed_dirs = vec![dir.path().to_path_buf()];
+    let create =
+        |kvs: &[(&str, &str)]| ExternalTable::try_create("CSV", &options(kvs), &allowed_dirs);
+
+    let expected = vec![
+        "+----+------+-------+",
+        "| id | name | score |",
+        "+----+------+-------+",
+        "| 1  | a    | 1.5   |",
+        "| 2  | b b  | NULL  |",
+        "| 3  |      | 3     |",
+        "+----+------+-------+",
+    ];
+
+    tracing::info!("--- no header, comma");
+    {
+        let p = write(dir.path(), "1.csv", "1,a,1.5\n2,b b,null\n3,,3\n")?;
+        let table = create(&[("path", p.display().to_string().as_str())])?;
+        let blocks = read_all(&table, &p)?;
+        assert_eq!(2, blocks.len(), "blocks of 2 rows");
+        assert_eq!(schema(), blocks[0].schema().clone());
+        assert_blocks_eq(expected.clone(), &blocks);
+    }
+
+    tracing::info!("--- header, semicolon");
+    {
+        let p = write(
+            dir.path(),
+            "2.csv",
+            "id;name;score\n1;a;1.5\n2;b b;null\n3;;3\n",
+        )?;
+        let table = create(&[
+            ("path", p.display().to_string().as_str()),
+            ("header", "true"),
+            ("delimiter", ";"),
+        ])?;
+        assert_blocks_eq(expected.clone(), &read_all(&table, &p)?);
+    }
+
+    tracing::info!("--- tab, quoted");
+    {
+        let p = write(dir.path(), "3.tsv", "1\ta\t1.5\n2\t\"b b\"\tnull\n3\t\t3\n")?;
+        let table = create(&[
+            ("path", p.display().to_string().as_str()),
+            ("delimiter", "\t"),
+        ])?;
+        assert_blocks_eq(expected, &read_all(&table, &p)?);
+    }
+
+    tracing::info!("--- validate against the schema");
+    {
+        let p = write(dir.path(), "4.csv", "x,a,1.5\n")?;
+        let table = create(&[("path", p.display().to_string().as_str())])?;
+        let err = table.validate(schema()).unwrap_err();
+        assert_eq!(ErrorCode::BadBytes("").code(), err.code());
+
+        let table = create(&[(
+            "path",
+            dir.path().join("*.csv").display().to_string().as_str(),
+        )])?;
+        let err = table.validate(schema()).unwrap_err();
+        assert!(err.message().contains("4.csv at line 1"), "{}", err);
+
+        let table = create(&[(
+            "path",
+            dir.path().join("1.csv").display().to_string().as_str(),
+        )])?;
+        table.validate(schema())?;
+
+        let table = create(&[(
+            "path",
+            dir.path().join("*.parquet").display().to_string().as_str(),
+        )])?;
+        let err = table.validate(schema()).unwrap_err();
+        assert!(err.message().contains("no file matches"), "{}", err);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_external_reader_bad_rows() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let allowed_dirs = vec![dir.path().to_path_buf()];
+    let p = write(
+        dir.path(),
+        "bad.csv",
+        "1,a,1.5\nx,b,2.5\n3,c\n4,d,4.5\n5,e,5.5\n",
+    )?;
+    let create = |max_bad_rows: &str| {
+        ExternalTable::try_create(
+            "CSV",
+            &options(&[
+                ("path", p.display().to_string().as_str()),
+                ("max_bad_rows", max_bad_rows),
+            ]),
+            &allowed_dirs,
+        )
+    };
+
+    // A wrong type at line 2 and a missing field at line 3 are skipped.
+    let table = create("2")?;
+    assert_blocks_eq(
+        vec![
+            "+----+------+-------+",
+            "| id | name | score |",
+            "+----+------+-------+",
+            "| 1  | a    | 1.5   |",
+            "| 4  | d    | 4.5   |",
+            "| 5  | e    | 5.5   |",
+            "+----+------+-------+",
+        ],
+        &read_all(&table, &p)?,
+    );
+
+    let table = create("1")?;
+    let err = read_all(&table, &p).unwrap_err();
+    assert_eq!(ErrorCode::BadBytes("").code(), err.code());
+    assert!(err.message().contains("bad.csv at line 3"), "{}", err);
+    assert!(err.message().contains("2 fields, expect 3"), "{}", err);
+
+    let table = create("0")?;
+    let err = read_all(&table, &p).unwrap_err();
+    assert!(err.message().contains("bad.csv at line 2"), "{}", err);
+    assert!(err.message().contains("column id"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_external_reader_ndjson() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let allowed_dirs = vec![dir.path().to_path_buf()];
+    let p = write(
+        dir.path(),
+        "1.json",
+        concat!(
+            "{\"id\": 1, \"name\": \"a\", \"score\": 1.5}\n",
+            "\n",
+            "{\"id\": 2, \"score\": null, \"extra\": true}\n",
+            "[1, 2]\n",
+            "{\"id\": \"3\", \"name\": \"c\", \"score\": 3}\n",
+        ),
+    )?;
+    let table = ExternalTable::try_create(
+        "NDJSON",
+        &options(&[
+            ("path", p.display().to_string().as_str()),
+            ("max_bad_rows", "1"),
+        ]),
+        &allowed_dirs,
+    )?;
+
+    // Missing fields are nulls, the array at line 4 is skipped.
+    assert_blocks_eq(
+        vec![
+            "+----+------+-------+",
+            "| id | name | score |",
+            "+----+------+-------+",
+            "| 1  | a    | 1.5   |",
+            "| 2  | NULL | NULL  |",
+            "| 3  | c    | 3     |",
+            "+----+------+-------+",
+        ],
+        &read_all(&table, &p)?,
+    );
+
+    Ok(())
+}
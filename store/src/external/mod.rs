@@ -0,0 +1,24 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+pub(crate) mod external_reader;
+pub(crate) mod external_table;
+
+#[cfg(test)]
+mod external_table_test;
+
+pub(crate) use external_reader::ExternalReader;
+pub(crate) use external_table::is_external_engine;
+pub(crate) use external_table::ExternalTable;
@@ -12,10 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::SystemTime;
+
 use async_trait::async_trait;
 use common_exception::exception;
 
+use crate::fs::staging_path;
 use crate::fs::ListResult;
+use crate::fs::STAGING_DIR;
 
 /// Abstract storage layer API.
 #[async_trait]
@@ -29,9 +33,49 @@ where Self: Sync + Send
     /// read all bytes from a file
     async fn read_all(&self, path: &str) -> exception::Result<Vec<u8>>;
 
+    /// Read the last `len` bytes of a file, all of them if the file is shorter.
+    async fn read_tail(&self, path: &str, len: usize) -> exception::Result<Vec<u8>> {
+        let mut data = self.read_all(path).await?;
+        let start = data.len().saturating_sub(len);
+        Ok(data.split_off(start))
+    }
+
     /// List dir and returns directories and files.
     async fn list(&self, prefix: &str) -> common_exception::Result<ListResult>;
 
+    /// Remove a file, it is not an error if the file does not exist.
+    async fn remove(&self, path: &str) -> common_exception::Result<()>;
+
+    /// Write a file under the staging dir, durably but under no part name, and return its path.
+    /// The path tells the time it is staged at, by the clock of the caller.
+    ///
+    /// A part is staged, then moved into place by `commit_staged`, then registered in the meta.
+    /// A crash in between leaves either a staged file or an unregistered one, never a
+    /// registered part with a partial file.
+    async fn stage(&self, data: &[u8], now: SystemTime) -> common_exception::Result<String> {
+        let path = staging_path(now);
+        self.add(&path, data).await?;
+        Ok(path)
+    }
+
+    /// Move a staged file to `path` atomically, it fails if `path` exists.
+    /// A backend without rename, e.g., an object store, finalizes the upload instead.
+    async fn commit_staged(&self, staged: &str, path: &str) -> common_exception::Result<()> {
+        let data = self.read_all(staged).await?;
+        self.add(path, &data).await?;
+        self.remove(staged).await
+    }
+
+    /// List the files under the staging dir, by their paths.
+    async fn list_staged(&self) -> common_exception::Result<Vec<String>> {
+        let staged = self.list(STAGING_DIR).await?;
+        Ok(staged
+            .files
+            .into_iter()
+            .map(|f| format!("{}/{}", STAGING_DIR, f))
+            .collect())
+    }
+
     // async fn read(
     //     path: &str,
     //     offset: usize,
@@ -14,6 +14,10 @@
 
 pub use ifs::FileSystem;
 pub use list_result::ListResult;
+pub use staging::staged_at;
+pub use staging::staging_path;
+pub use staging::STAGING_DIR;
 
 pub mod ifs;
 mod list_result;
+mod staging;
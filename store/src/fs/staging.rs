@@ -0,0 +1,43 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use uuid::Uuid;
+
+/// The dir the part files are written to before they are moved into place.
+///
+/// No part is registered under it, a file left in it by a crash or an abandoned append is
+/// garbage once it is older than any append in progress.
+pub const STAGING_DIR: &str = "_staging";
+
+/// A new path under the staging dir for a file staged at `now`, named `<unix millis>-<uuid>` so
+/// that its age is known without asking the backend.
+pub fn staging_path(now: SystemTime) -> String {
+    let millis = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    format!("{}/{}-{}", STAGING_DIR, millis, Uuid::new_v4().to_simple())
+}
+
+/// When a staged file is written, by its name. `None` if the name is not of `staging_path`.
+pub fn staged_at(path: &str) -> Option<SystemTime> {
+    let name = path.rsplit('/').next()?;
+    let (millis, _uuid) = name.split_once('-')?;
+    let millis = millis.parse::<u64>().ok()?;
+    Some(UNIX_EPOCH + Duration::from_millis(millis))
+}
@@ -25,3 +25,4 @@ pub mod localfs;
 pub mod metrics;
 
 mod data_part;
+mod external;
@@ -11,7 +11,11 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
@@ -25,6 +29,7 @@ use common_tracing::tracing;
 
 use crate::fs::FileSystem;
 use crate::fs::ListResult;
+use crate::fs::STAGING_DIR;
 
 pub struct LocalFS {
     root: PathBuf,
@@ -80,6 +85,26 @@ impl FileSystem for LocalFS {
         Ok(data)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn read_tail(&self, path: &str, len: usize) -> exception::Result<Vec<u8>> {
+        let p = Path::new(self.root.as_path()).join(path);
+        tracing::info!("read tail: {}, {} bytes", p.as_path().display(), len);
+
+        let read = || -> std::io::Result<Vec<u8>> {
+            let mut f = File::open(p.as_path())?;
+            let size = f.seek(SeekFrom::End(0))?;
+            let start = size.saturating_sub(len as u64);
+            f.seek(SeekFrom::Start(start))?;
+
+            let mut data = Vec::with_capacity((size - start) as usize);
+            f.read_to_end(&mut data)?;
+            Ok(data)
+        };
+        read().map_err_to_code(ErrorCode::FileDamaged, || {
+            format!("LocalFS: fail to read tail: {:?}", path)
+        })
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     async fn list(&self, path: &str) -> common_exception::Result<ListResult> {
         let p = Path::new(self.root.as_path()).join(path);
@@ -112,4 +137,49 @@ impl FileSystem for LocalFS {
 
         Ok(ListResult { dirs, files })
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn remove(&self, path: &str) -> common_exception::Result<()> {
+        let p = Path::new(self.root.as_path()).join(path);
+        match std::fs::remove_file(p.as_path()) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            res => Ok(res.with_context(|| format!("LocalFS: fail to remove {}", path))?),
+        }
+    }
+
+    /// Links the staged file to `path` then unlinks it, the link fails if `path` exists.
+    /// The dir of `path` is synced, the file is in place once it returns.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn commit_staged(&self, staged: &str, path: &str) -> common_exception::Result<()> {
+        let from = Path::new(self.root.as_path()).join(staged);
+        let to = Path::new(self.root.as_path()).join(path);
+        if let Some(dir) = to.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("LocalFS: fail create dir {}", dir.display()))?;
+        }
+
+        std::fs::hard_link(from.as_path(), to.as_path())
+            .with_context(|| format!("LocalFS: fail to move {} to {}", staged, path))?;
+        if let Some(dir) = to.parent() {
+            File::open(dir)
+                .and_then(|d| d.sync_all())
+                .with_context(|| format!("LocalFS: fail to sync dir of {}", path))?;
+        }
+
+        self.remove(staged).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn list_staged(&self) -> common_exception::Result<Vec<String>> {
+        let p = Path::new(self.root.as_path()).join(STAGING_DIR);
+        if !p.exists() {
+            return Ok(vec![]);
+        }
+        let staged = self.list(STAGING_DIR).await?;
+        Ok(staged
+            .files
+            .into_iter()
+            .map(|f| format!("{}/{}", STAGING_DIR, f))
+            .collect())
+    }
 }
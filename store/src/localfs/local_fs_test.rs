@@ -11,10 +11,14 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
 use common_runtime::tokio;
 use pretty_assertions::assert_eq;
 use tempfile::tempdir;
 
+use crate::fs::staged_at;
 use crate::fs::FileSystem;
 use crate::fs::ListResult;
 use crate::localfs::LocalFS;
@@ -80,3 +84,60 @@ async fn test_localfs_read_all() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_localfs_read_tail() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let root = dir.path();
+
+    let f = LocalFS::try_create(root.to_str().unwrap().to_string())?;
+    assert!(f.read_tail("foo.txt", 2).await.is_err());
+
+    f.add("foo.txt", "12345".as_bytes()).await?;
+    assert_eq!(
+        "45",
+        std::str::from_utf8(&f.read_tail("foo.txt", 2).await?)?
+    );
+    assert_eq!("", std::str::from_utf8(&f.read_tail("foo.txt", 0).await?)?);
+
+    // A file shorter than the tail is read as a whole.
+    assert_eq!(
+        "12345",
+        std::str::from_utf8(&f.read_tail("foo.txt", 10).await?)?
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_localfs_stage_and_commit() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let root = dir.path();
+
+    let f = LocalFS::try_create(root.to_str().unwrap().to_string())?;
+    assert!(f.list_staged().await?.is_empty());
+
+    let now = UNIX_EPOCH + Duration::from_millis(1_600_000_000_123);
+    let staged = f.stage("123".as_bytes(), now).await?;
+    assert_eq!(Some(now), staged_at(&staged));
+    assert_eq!(vec![staged.clone()], f.list_staged().await?);
+    assert!(f.read_all("db/tb/p1").await.is_err());
+
+    {
+        // moved into place, nothing left staged
+        f.commit_staged(&staged, "db/tb/p1").await?;
+        assert_eq!("123", std::str::from_utf8(&f.read_all("db/tb/p1").await?)?);
+        assert!(f.list_staged().await?.is_empty());
+    }
+    {
+        // an existing file is not replaced, the staged file is kept
+        let staged = f.stage("456".as_bytes(), now).await?;
+        assert!(f.commit_staged(&staged, "db/tb/p1").await.is_err());
+        assert_eq!("123", std::str::from_utf8(&f.read_all("db/tb/p1").await?)?);
+        assert_eq!(vec![staged], f.list_staged().await?);
+    }
+
+    assert_eq!(None, staged_at("_staging/foo"));
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::BTreeMap;
+
+use common_infallible::RwLock;
+use common_metatypes::DatabaseUsage;
+use metrics::gauge;
+
+pub static METRIC_DATABASE_USED_BYTES: &str = "database.used_bytes";
+/// 0 if the database has no quota.
+pub static METRIC_DATABASE_QUOTA_BYTES: &str = "database.quota_bytes";
+
+/// The quota and the used bytes of the databases, as last reported by the flight service.
+///
+/// They are exported to prometheus with the db label and served by the http api,
+/// which has no access to the meta node.
+#[derive(Default)]
+pub struct DatabaseUsageRecorder {
+    usages: RwLock<BTreeMap<String, DatabaseUsage>>,
+}
+
+impl DatabaseUsageRecorder {
+    pub fn create() -> Self {
+        DatabaseUsageRecorder::default()
+    }
+
+    /// Replace all the usages, e.g., with the ones of the meta node just opened.
+    pub fn reset(&self, usages: Vec<(String, DatabaseUsage)>) {
+        let mut recorded = self.usages.write();
+        for db in recorded.keys() {
+            Self::export(db, &DatabaseUsage::default());
+        }
+        recorded.clear();
+
+        for (db, usage) in usages {
+            Self::export(&db, &usage);
+            recorded.insert(db, usage);
+        }
+    }
+
+    /// Record the usage of a database, `None` if the database is dropped.
+    pub fn record(&self, db: &str, usage: Option<DatabaseUsage>) {
+        let mut recorded = self.usages.write();
+        match usage {
+            None => {
+                Self::export(db, &DatabaseUsage::default());
+                recorded.remove(db);
+            }
+            Some(usage) => {
+                Self::export(db, &usage);
+                recorded.insert(db.to_string(), usage);
+            }
+        }
+    }
+
+    pub fn get(&self, db: &str) -> Option<DatabaseUsage> {
+        self.usages.read().get(db).cloned()
+    }
+
+    /// The usages of all the databases, ordered by name.
+    pub fn get_all(&self) -> Vec<(String, DatabaseUsage)> {
+        let recorded = self.usages.read();
+        recorded
+            .iter()
+            .map(|(db, usage)| (db.clone(), usage.clone()))
+            .collect()
+    }
+
+    fn export(db: &str, usage: &DatabaseUsage) {
+        let quota = usage.quota.unwrap_or_default();
+        gauge!(METRIC_DATABASE_USED_BYTES, usage.used_bytes as f64, "db" => db.to_string());
+        gauge!(METRIC_DATABASE_QUOTA_BYTES, quota as f64, "db" => db.to_string());
+    }
+}
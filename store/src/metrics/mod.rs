@@ -12,6 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod table_access_test;
+
+mod database_usage;
 mod metric_service;
+mod table_access;
 
+pub use database_usage::DatabaseUsageRecorder;
+pub use database_usage::METRIC_DATABASE_QUOTA_BYTES;
+pub use database_usage::METRIC_DATABASE_USED_BYTES;
 pub use metric_service::MetricService;
+pub use table_access::TableAccessRecorder;
+pub use table_access::BUCKET_SECS;
+pub use table_access::METRIC_TABLE_BYTES_READ;
+pub use table_access::METRIC_TABLE_BYTES_WRITTEN;
+pub use table_access::METRIC_TABLE_ROWS_READ;
+pub use table_access::METRIC_TABLE_ROWS_WRITTEN;
+pub use table_access::OTHER_LABEL;
+pub use table_access::RETENTION_BUCKETS;
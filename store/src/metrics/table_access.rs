@@ -0,0 +1,234 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_infallible::Mutex;
+use common_store_api_sdk::storage_api_impl::TableAccessStats;
+use metrics::counter;
+
+pub static METRIC_TABLE_ROWS_READ: &str = "table.rows_read";
+pub static METRIC_TABLE_BYTES_READ: &str = "table.bytes_read";
+pub static METRIC_TABLE_ROWS_WRITTEN: &str = "table.rows_written";
+pub static METRIC_TABLE_BYTES_WRITTEN: &str = "table.bytes_written";
+
+/// The width of a bucket, in seconds.
+pub const BUCKET_SECS: u64 = 60;
+/// How many buckets are kept, the older ones are dropped.
+pub const RETENTION_BUCKETS: u64 = 60;
+/// The db and table label of the tables beyond the label cap.
+pub const OTHER_LABEL: &str = "_other";
+
+type TableKey = (String, String);
+
+#[derive(Debug)]
+struct Bucket {
+    /// in seconds since 1970, a multiple of BUCKET_SECS
+    start: u64,
+    stats: TableAccessStats,
+}
+
+#[derive(Default)]
+struct Buckets(VecDeque<Bucket>);
+
+impl Buckets {
+    fn add(&mut self, now: u64, access: &TableAccessStats) {
+        let start = now - now % BUCKET_SECS;
+        match self.0.back_mut() {
+            Some(bucket) if bucket.start >= start => merge(&mut bucket.stats, access),
+            _ => self.0.push_back(Bucket {
+                start,
+                stats: access.clone(),
+            }),
+        }
+        self.expire(now);
+    }
+
+    /// Drop the buckets older than the retention.
+    fn expire(&mut self, now: u64) {
+        let retention = RETENTION_BUCKETS * BUCKET_SECS;
+        while let Some(bucket) = self.0.front() {
+            if bucket.start + retention > now {
+                break;
+            }
+            self.0.pop_front();
+        }
+    }
+
+    /// Sum of the buckets overlapping the last `window_secs` seconds.
+    fn sum(&self, now: u64, window_secs: u64) -> TableAccessStats {
+        let since = now.saturating_sub(window_secs);
+        let mut sum = TableAccessStats::default();
+        for bucket in self.0.iter().rev() {
+            if bucket.start + BUCKET_SECS <= since {
+                break;
+            }
+            merge(&mut sum, &bucket.stats);
+        }
+        sum
+    }
+}
+
+struct Inner {
+    tables: HashMap<TableKey, Buckets>,
+    /// The tables reported to prometheus with their own labels.
+    labelled: HashSet<TableKey>,
+    /// The tables beyond the label cap, together.
+    other: Buckets,
+    /// The bucket in which the expired tables are removed last time.
+    last_expire: u64,
+}
+
+/// Rows and bytes read and written per table, in fixed time buckets.
+///
+/// They are exported to prometheus with the db and table labels as well,
+/// only for the first `max_labelled_tables` tables, the others are reported as `_other`.
+/// A table without access in the retention, e.g. a dropped table, is removed and frees its label.
+pub struct TableAccessRecorder {
+    max_labelled_tables: usize,
+    inner: Mutex<Inner>,
+}
+
+impl TableAccessRecorder {
+    pub fn create(max_labelled_tables: usize) -> Self {
+        TableAccessRecorder {
+            max_labelled_tables,
+            inner: Mutex::new(Inner {
+                tables: HashMap::new(),
+                labelled: HashSet::new(),
+                other: Buckets::default(),
+                last_expire: 0,
+            }),
+        }
+    }
+
+    pub fn record_read(&self, db: &str, table: &str, rows: u64, bytes: u64) {
+        let access = TableAccessStats {
+            rows_read: rows,
+            bytes_read: bytes,
+            ..Default::default()
+        };
+        self.record_at(db, table, now_secs(), &access);
+    }
+
+    pub fn record_write(&self, db: &str, table: &str, rows: u64, bytes: u64) {
+        let access = TableAccessStats {
+            rows_written: rows,
+            bytes_written: bytes,
+            ..Default::default()
+        };
+        self.record_at(db, table, now_secs(), &access);
+    }
+
+    pub fn get(&self, db: &str, table: &str, window_secs: u64) -> TableAccessStats {
+        self.get_at(db, table, now_secs(), window_secs)
+    }
+
+    /// The access of the tables beyond the label cap, all together.
+    pub fn get_other(&self, window_secs: u64) -> TableAccessStats {
+        self.inner.lock().other.sum(now_secs(), window_secs)
+    }
+
+    pub(crate) fn record_at(&self, db: &str, table: &str, now: u64, access: &TableAccessStats) {
+        let (db_label, table_label) = {
+            let mut inner = self.inner.lock();
+            self.expire_tables(&mut inner, now);
+
+            let key = (db.to_string(), table.to_string());
+            inner
+                .tables
+                .entry(key.clone())
+                .or_default()
+                .add(now, access);
+
+            if inner.labelled.contains(&key) {
+                key
+            } else if inner.labelled.len() < self.max_labelled_tables {
+                inner.labelled.insert(key.clone());
+                key
+            } else {
+                inner.other.add(now, access);
+                (OTHER_LABEL.to_string(), OTHER_LABEL.to_string())
+            }
+        };
+
+        let counters = [
+            (METRIC_TABLE_ROWS_READ, access.rows_read),
+            (METRIC_TABLE_BYTES_READ, access.bytes_read),
+            (METRIC_TABLE_ROWS_WRITTEN, access.rows_written),
+            (METRIC_TABLE_BYTES_WRITTEN, access.bytes_written),
+        ];
+        for (name, value) in counters {
+            if value > 0 {
+                counter!(name, value, "db" => db_label.clone(), "table" => table_label.clone());
+            }
+        }
+    }
+
+    pub(crate) fn get_at(
+        &self,
+        db: &str,
+        table: &str,
+        now: u64,
+        window_secs: u64,
+    ) -> TableAccessStats {
+        let inner = self.inner.lock();
+        match inner.tables.get(&(db.to_string(), table.to_string())) {
+            None => TableAccessStats::default(),
+            Some(buckets) => buckets.sum(now, window_secs),
+        }
+    }
+
+    /// Whether the table is reported with its own labels.
+    pub fn is_labelled(&self, db: &str, table: &str) -> bool {
+        let key = (db.to_string(), table.to_string());
+        self.inner.lock().labelled.contains(&key)
+    }
+
+    /// Remove the tables without access in the retention, at most once a bucket.
+    fn expire_tables(&self, inner: &mut Inner, now: u64) {
+        let start = now - now % BUCKET_SECS;
+        if inner.last_expire >= start {
+            return;
+        }
+        inner.last_expire = start;
+
+        inner.tables.retain(|_, buckets| {
+            buckets.expire(now);
+            !buckets.0.is_empty()
+        });
+        let tables = &inner.tables;
+        inner.labelled.retain(|key| tables.contains_key(key));
+        inner.other.expire(now);
+    }
+}
+
+fn merge(to: &mut TableAccessStats, access: &TableAccessStats) {
+    to.rows_read += access.rows_read;
+    to.bytes_read += access.bytes_read;
+    to.rows_written += access.rows_written;
+    to.bytes_written += access.bytes_written;
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,148 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+
+use common_store_api_sdk::storage_api_impl::TableAccessStats;
+
+use crate::metrics::TableAccessRecorder;
+use crate::metrics::BUCKET_SECS;
+use crate::metrics::RETENTION_BUCKETS;
+
+fn read(rows: u64, bytes: u64) -> TableAccessStats {
+    TableAccessStats {
+        rows_read: rows,
+        bytes_read: bytes,
+        ..Default::default()
+    }
+}
+
+fn write(rows: u64, bytes: u64) -> TableAccessStats {
+    TableAccessStats {
+        rows_written: rows,
+        bytes_written: bytes,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_table_access_buckets() -> anyhow::Result<()> {
+    let recorder = TableAccessRecorder::create(10);
+    let t0 = 1_000 * BUCKET_SECS;
+
+    recorder.record_at("db", "t", t0, &write(3, 30));
+    recorder.record_at("db", "t", t0 + 1, &read(3, 35));
+    recorder.record_at("db", "t", t0 + BUCKET_SECS, &read(2, 20));
+
+    // the current bucket
+    let now = t0 + BUCKET_SECS + 5;
+    assert_eq!(read(2, 20), recorder.get_at("db", "t", now, 0));
+
+    // the buckets overlapping the last 10 minutes
+    let want = TableAccessStats {
+        rows_read: 5,
+        bytes_read: 55,
+        rows_written: 3,
+        bytes_written: 30,
+    };
+    assert_eq!(want, recorder.get_at("db", "t", now, 10 * BUCKET_SECS));
+
+    assert_eq!(
+        TableAccessStats::default(),
+        recorder.get_at("db", "unknown", now, 10 * BUCKET_SECS)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_table_access_expire() -> anyhow::Result<()> {
+    let recorder = TableAccessRecorder::create(1);
+    let t0 = 1_000 * BUCKET_SECS;
+    let retention = RETENTION_BUCKETS * BUCKET_SECS;
+
+    recorder.record_at("db", "dropped", t0, &write(3, 30));
+    assert!(recorder.is_labelled("db", "dropped"));
+
+    // a table without access in the retention ages out and frees its label
+    recorder.record_at("db", "t", t0 + retention, &write(1, 10));
+    assert_eq!(
+        TableAccessStats::default(),
+        recorder.get_at("db", "dropped", t0 + retention, retention)
+    );
+    assert!(!recorder.is_labelled("db", "dropped"));
+    assert!(recorder.is_labelled("db", "t"));
+    Ok(())
+}
+
+#[test]
+fn test_table_access_label_cap() -> anyhow::Result<()> {
+    let recorder = TableAccessRecorder::create(2);
+
+    recorder.record_read("db", "t1", 1, 10);
+    recorder.record_read("db", "t2", 2, 20);
+    recorder.record_read("db", "t3", 3, 30);
+    recorder.record_write("db", "t4", 4, 40);
+    recorder.record_read("db", "t1", 1, 10);
+
+    assert!(recorder.is_labelled("db", "t1"));
+    assert!(recorder.is_labelled("db", "t2"));
+    assert!(!recorder.is_labelled("db", "t3"));
+    assert!(!recorder.is_labelled("db", "t4"));
+
+    // the tables beyond the cap are counted as the other ones, and by themselves
+    let want = TableAccessStats {
+        rows_read: 3,
+        bytes_read: 30,
+        rows_written: 4,
+        bytes_written: 40,
+    };
+    assert_eq!(want, recorder.get_other(BUCKET_SECS));
+    assert_eq!(read(3, 30), recorder.get("db", "t3", BUCKET_SECS));
+    assert_eq!(read(2, 20), recorder.get("db", "t1", BUCKET_SECS));
+    Ok(())
+}
+
+#[test]
+fn test_table_access_concurrent() -> anyhow::Result<()> {
+    let recorder = Arc::new(TableAccessRecorder::create(1));
+
+    let handles = (0..8)
+        .map(|i| {
+            let recorder = recorder.clone();
+            std::thread::spawn(move || {
+                let table = format!("t{}", i % 2);
+                for _ in 0..1000 {
+                    recorder.record_read("db", &table, 1, 8);
+                    recorder.record_write("db", &table, 2, 16);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for h in handles {
+        h.join().unwrap();
+    }
+
+    let want = TableAccessStats {
+        rows_read: 4000,
+        bytes_read: 32000,
+        rows_written: 8000,
+        bytes_written: 64000,
+    };
+    // the threads may cross a bucket boundary
+    let window = 2 * BUCKET_SECS;
+    assert_eq!(want, recorder.get("db", "t0", window));
+    assert_eq!(want, recorder.get("db", "t1", window));
+    Ok(())
+}
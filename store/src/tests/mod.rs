@@ -14,9 +14,15 @@
 
 #[macro_use]
 pub mod service;
+mod query_node;
 pub(crate) mod tls_constants;
 
+pub use query_node::QueryTestNode;
+pub use query_node::StoreQueryTestContext;
 pub use service::assert_meta_connection;
 pub use service::next_port;
+pub use service::partition_client;
+pub use service::restart_store_server;
 pub use service::start_store_server;
 pub use service::start_store_server_with_context;
+pub use service::stop_store_server;
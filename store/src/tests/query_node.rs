@@ -0,0 +1,111 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use common_store_api_sdk::StoreClient;
+use common_tracing::tracing;
+use databend_query::catalogs::impls::DatabaseCatalog;
+use databend_query::clusters::Cluster;
+use databend_query::configs::Config as QueryConfig;
+use databend_query::servers::MySQLHandler;
+use databend_query::servers::Server;
+use databend_query::sessions::SessionManager;
+use databend_query::sessions::SessionManagerRef;
+
+use crate::tests::service::new_test_context;
+use crate::tests::service::StoreTestContext;
+use crate::tests::start_store_server_with_context;
+use crate::tests::stop_store_server;
+
+/// A query node in the process of the test, it keeps its catalog and its tables in the store of a
+/// `StoreTestContext`. Its MySQL handler listens on an ephemeral port.
+pub struct QueryTestNode {
+    pub conf: QueryConfig,
+    pub sessions: SessionManagerRef,
+    mysql_handler: Box<dyn Server>,
+    mysql_address: SocketAddr,
+}
+
+impl QueryTestNode {
+    pub async fn start(tc: &StoreTestContext) -> anyhow::Result<QueryTestNode> {
+        let mut conf = QueryConfig::default();
+        conf.meta.meta_address = tc.config.flight_api_address.clone();
+        conf.store.store_address = tc.config.flight_api_address.clone();
+
+        let sessions = SessionManager::from_conf(conf.clone(), Cluster::empty())?;
+        let mut mysql_handler = MySQLHandler::create(sessions.clone());
+        let mysql_address = mysql_handler.start("127.0.0.1:0".parse()?).await?;
+        tracing::info!("query node listens MySQL on {}", mysql_address);
+
+        Ok(QueryTestNode {
+            conf,
+            sessions,
+            mysql_handler,
+            mysql_address,
+        })
+    }
+
+    /// Opens a MySQL connection to the node, each connection is a session.
+    pub fn connect(&self) -> anyhow::Result<mysql::Conn> {
+        let uri = format!(
+            "mysql://127.0.0.1:{}?user=default",
+            self.mysql_address.port()
+        );
+        Ok(mysql::Conn::new(mysql::Opts::from_url(&uri)?)?)
+    }
+
+    /// The catalog of the node, i.e., what the node sees of the store.
+    pub fn catalog(&self) -> Arc<DatabaseCatalog> {
+        self.sessions.get_catalog()
+    }
+
+    /// Stops accepting connections, a session is released once its connection is closed.
+    pub async fn shutdown(&mut self) {
+        self.mysql_handler.shutdown().await;
+    }
+}
+
+/// A store server and a query node on top of it, every context has its own ports, meta dir and
+/// sled trees, so that the contexts of the tests in one process don't collide.
+pub struct StoreQueryTestContext {
+    pub store: StoreTestContext,
+    pub query: QueryTestNode,
+}
+
+impl StoreQueryTestContext {
+    pub async fn start() -> anyhow::Result<StoreQueryTestContext> {
+        let mut store = new_test_context();
+        start_store_server_with_context(&mut store).await?;
+        let query = QueryTestNode::start(&store).await?;
+
+        Ok(StoreQueryTestContext { store, query })
+    }
+
+    /// A client of the store, to look into the store side.
+    pub async fn store_client(&self) -> anyhow::Result<StoreClient> {
+        let addr = self.store.config.flight_api_address.as_str();
+        Ok(StoreClient::try_create(addr, "root", "xxx").await?)
+    }
+
+    pub async fn shutdown(mut self) -> anyhow::Result<()> {
+        self.query.shutdown().await;
+        if self.store.channels.is_some() {
+            stop_store_server(&mut self.store).await?;
+        }
+        Ok(())
+    }
+}
@@ -13,10 +13,13 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use common_runtime::tokio;
 use common_runtime::tokio::sync::oneshot;
+use common_store_api_sdk::FaultInjector;
+use common_store_api_sdk::StoreClient;
 use common_tracing::tracing;
 use metasrv::meta_service::GetReq;
 use metasrv::meta_service::MetaNode;
@@ -26,8 +29,11 @@ use tempfile::tempdir;
 use tempfile::TempDir;
 
 // use tracing_appender::non_blocking::WorkerGuard;
+use crate::api::rpc::AuditLog;
+use crate::api::MetaNodeHandle;
 use crate::api::StoreServer;
 use crate::configs;
+use crate::configs::ConfigHandle;
 
 // Start one random service and get the session manager.
 #[tracing::instrument(level = "info")]
@@ -42,7 +48,14 @@ pub async fn start_store_server() -> Result<(StoreTestContext, String)> {
 }
 
 pub async fn start_store_server_with_context(tc: &mut StoreTestContext) -> Result<()> {
-    let srv = StoreServer::create(tc.config.clone());
+    let mut srv = StoreServer::create(tc.config.clone());
+    if let Some(config_handle) = &tc.config_handle {
+        srv = srv.with_config_handle(config_handle.clone());
+    }
+    let srv = srv
+        .with_fault_injector(tc.fault_injector.clone())
+        .with_audit_log(tc.audit_log.clone())
+        .with_meta_node_handle(tc.meta_node_handle.clone());
     let (stop_tx, fin_rx) = srv.start().await?;
 
     tc.channels = Some((stop_tx, fin_rx));
@@ -53,6 +66,37 @@ pub async fn start_store_server_with_context(tc: &mut StoreTestContext) -> Resul
     Ok(())
 }
 
+/// Stops the store server of `tc` and waits for the shutdown to finish.
+pub async fn stop_store_server(tc: &mut StoreTestContext) -> anyhow::Result<()> {
+    tracing::info!("--- stop StoreServer");
+    let (stop_tx, fin_rx) = tc.channels.take().unwrap();
+    stop_tx
+        .send(())
+        .map_err(|_| anyhow::anyhow!("fail to send"))?;
+
+    fin_rx.await?;
+    Ok(())
+}
+
+/// Stops the store server of `tc` and starts it again on the same meta db, returns a client
+/// connected to the restarted one.
+pub async fn restart_store_server(
+    tc: &mut StoreTestContext,
+    addr: &str,
+) -> anyhow::Result<StoreClient> {
+    stop_store_server(tc).await?;
+    tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+
+    // restart by opening existent meta db
+    tc.config.meta_config.boot = false;
+    start_store_server_with_context(tc).await?;
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(10_000)).await;
+
+    // try to reconnect the restarted server.
+    Ok(StoreClient::try_create(addr, "root", "xxx").await?)
+}
+
 pub fn next_port() -> u32 {
     19000u32 + (common_uniq_id::uniq_usize() as u32)
 }
@@ -73,6 +117,18 @@ pub struct StoreTestContext {
 
     /// channel to send to stop StoreServer, and channel for waiting for shutdown to finish.
     pub channels: Option<(oneshot::Sender<()>, oneshot::Receiver<()>)>,
+
+    /// Injects faults into the StoreServer if it is set before the server starts.
+    pub fault_injector: Option<Arc<FaultInjector>>,
+
+    /// Keeps the requests the StoreServer serves if it is set before the server starts.
+    pub audit_log: Option<Arc<AuditLog>>,
+
+    /// The StoreServer reads its dynamic settings from it if it is set before the server starts.
+    pub config_handle: Option<Arc<ConfigHandle>>,
+
+    /// The meta node of the running StoreServer.
+    pub meta_node_handle: Arc<MetaNodeHandle>,
 }
 
 /// Create a new Config for test, with unique port assigned
@@ -131,9 +187,26 @@ pub fn new_test_context() -> StoreTestContext {
         meta_nodes: vec![],
 
         channels: None,
+        fault_injector: None,
+        audit_log: None,
+        config_handle: None,
+        meta_node_handle: Arc::new(MetaNodeHandle::create()),
     }
 }
 
+/// Cuts the client off the store for the duration: all of its requests and responses are lost.
+pub fn partition_client(client: &mut StoreClient, duration: Duration) {
+    let injector = match client.get_fault_injector() {
+        Some(injector) => injector,
+        None => {
+            let injector = Arc::new(FaultInjector::create());
+            client.set_fault_injector(injector.clone());
+            injector
+        }
+    };
+    injector.partition(duration);
+}
+
 pub struct SledTestContext {
     pub config: configs::Config,
     pub db: sled::Db,